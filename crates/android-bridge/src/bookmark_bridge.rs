@@ -0,0 +1,405 @@
+// Bookmark and position bridge for Android JNI
+//
+// Lets the app persist bookmarks and listening progress through a library
+// handle even when playback runs in ExoPlayer rather than the Rust engine,
+// e.g. from a MediaSession callback. Every function returns a JSON envelope;
+// the shapes are listed in `crate::ffi`.
+
+use crate::ffi::{
+    jstring_raw_to_option_string, jstring_raw_to_string, string_to_jstring, FfiResult,
+};
+use crate::library_bridge::library_pool;
+use crate::{jni_safe, json, runtime};
+use jni::{
+    objects::JClass,
+    sys::{jlong, jstring},
+    JNIEnv,
+};
+use serde::Serialize;
+use std::panic; // Required for jni_safe! macro
+use storystream_core::types::Validator;
+use storystream_core::{AppError, Book, BookId, Bookmark, BookmarkId, Duration, PlaybackState};
+use storystream_database::queries::{bookmarks, books, playback};
+use storystream_database::DbPool;
+
+/// Bookmark as serialized for the Java layer
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BookmarkJson {
+    id: String,
+    book_id: String,
+    position_ms: u64,
+    title: Option<String>,
+    note: Option<String>,
+    created_at: i64,
+    updated_at: i64,
+}
+
+impl From<&Bookmark> for BookmarkJson {
+    fn from(bookmark: &Bookmark) -> Self {
+        Self {
+            id: bookmark.id.as_string(),
+            book_id: bookmark.book_id.as_string(),
+            position_ms: bookmark.position.as_millis(),
+            title: bookmark.title.clone(),
+            note: bookmark.note.clone(),
+            created_at: bookmark.created_at.as_millis(),
+            updated_at: bookmark.updated_at.as_millis(),
+        }
+    }
+}
+
+/// Saved listening position
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PositionJson {
+    book_id: String,
+    position_ms: u64,
+    duration_ms: u64,
+    /// `None` if nothing was saved yet
+    updated_at: Option<i64>,
+}
+
+/// Result of a delete
+#[derive(Debug, Serialize)]
+struct DeletedJson {
+    id: String,
+    deleted: bool,
+}
+
+fn parse_book_id(id: &str) -> Result<BookId, AppError> {
+    BookId::from_string(id.trim()).map_err(|e| AppError::InvalidArgument {
+        argument: "bookId".to_string(),
+        reason: e.to_string(),
+    })
+}
+
+/// Checks a Java position against the book's length
+fn position_in_book(position_ms: i64, book: &Book) -> Result<Duration, AppError> {
+    if position_ms < 0 {
+        return Err(AppError::InvalidArgument {
+            argument: "positionMs".to_string(),
+            reason: format!("{} is negative", position_ms),
+        });
+    }
+
+    let position = Duration::from_millis(position_ms as u64);
+    if position > book.duration {
+        return Err(AppError::InvalidPosition {
+            position: position.as_millis(),
+            duration: book.duration.as_millis(),
+        });
+    }
+    Ok(position)
+}
+
+/// Treats blank strings from Java text fields as absent
+fn non_blank(text: Option<String>) -> Option<String> {
+    text.map(|t| t.trim().to_string()).filter(|t| !t.is_empty())
+}
+
+async fn save_bookmark(
+    pool: &DbPool,
+    book_id: &str,
+    position_ms: i64,
+    title: Option<String>,
+    note: Option<String>,
+) -> Result<BookmarkJson, AppError> {
+    let book = books::get_book(pool, parse_book_id(book_id)?).await?;
+    let position = position_in_book(position_ms, &book)?;
+
+    let mut bookmark = Bookmark::new(book.id, position);
+    bookmark.title = non_blank(title);
+    bookmark.note = non_blank(note);
+    bookmark
+        .validate()
+        .map_err(|errors| AppError::InvalidArgument {
+            argument: "bookmark".to_string(),
+            reason: errors.join("; "),
+        })?;
+
+    bookmarks::create_bookmark(pool, &bookmark).await?;
+    Ok(BookmarkJson::from(&bookmark))
+}
+
+async fn list_bookmarks(pool: &DbPool, book_id: &str) -> Result<Vec<BookmarkJson>, AppError> {
+    let book = books::get_book(pool, parse_book_id(book_id)?).await?;
+    let found = bookmarks::get_book_bookmarks(pool, book.id).await?;
+    Ok(found.iter().map(BookmarkJson::from).collect())
+}
+
+async fn delete_bookmark(pool: &DbPool, id: &str) -> Result<DeletedJson, AppError> {
+    let id = BookmarkId::from_string(id.trim()).map_err(|e| AppError::InvalidArgument {
+        argument: "id".to_string(),
+        reason: e.to_string(),
+    })?;
+
+    // Fails with RECORD_NOT_FOUND for unknown IDs
+    bookmarks::get_bookmark(pool, id).await?;
+    bookmarks::delete_bookmark(pool, id).await?;
+    Ok(DeletedJson {
+        id: id.as_string(),
+        deleted: true,
+    })
+}
+
+async fn save_position(
+    pool: &DbPool,
+    book_id: &str,
+    position_ms: i64,
+) -> Result<PositionJson, AppError> {
+    let book = books::get_book(pool, parse_book_id(book_id)?).await?;
+    let position = position_in_book(position_ms, &book)?;
+
+    let mut state = match playback::get_playback_state(pool, book.id).await {
+        Ok(state) => state,
+        Err(AppError::RecordNotFound { .. }) => PlaybackState::new(book.id),
+        Err(e) => return Err(e),
+    };
+    state.set_position(position);
+    playback::create_playback_state(pool, &state).await?;
+
+    Ok(PositionJson {
+        book_id: book.id.as_string(),
+        position_ms: state.position.as_millis(),
+        duration_ms: book.duration.as_millis(),
+        updated_at: Some(state.last_updated.as_millis()),
+    })
+}
+
+async fn get_position(pool: &DbPool, book_id: &str) -> Result<PositionJson, AppError> {
+    let book = books::get_book(pool, parse_book_id(book_id)?).await?;
+    let (position_ms, updated_at) = match playback::get_playback_state(pool, book.id).await {
+        Ok(state) => (
+            state.position.as_millis(),
+            Some(state.last_updated.as_millis()),
+        ),
+        Err(AppError::RecordNotFound { .. }) => (0, None),
+        Err(e) => return Err(e),
+    };
+
+    Ok(PositionJson {
+        book_id: book.id.as_string(),
+        position_ms,
+        duration_ms: book.duration.as_millis(),
+        updated_at,
+    })
+}
+
+/// Save a bookmark; `title` and `note` may be null (JSON envelope with a Bookmark)
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamLibrary_nativeSaveBookmark(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    handle: jlong,
+    book_id: jstring,
+    position_ms: jlong,
+    title: jstring,
+    note: jstring,
+) -> jstring {
+    jni_safe!(env, std::ptr::null_mut(), {
+        let book_id = jstring_raw_to_string(&mut env, book_id)?;
+        let title = jstring_raw_to_option_string(&mut env, title)?;
+        let note = jstring_raw_to_option_string(&mut env, note)?;
+
+        let result = library_pool(handle).and_then(|pool| {
+            runtime::block_on(save_bookmark(&pool, &book_id, position_ms, title, note))
+        });
+        string_to_jstring(&mut env, &json::envelope(result))
+    })
+}
+
+/// List a book's bookmarks by position (JSON envelope with a Bookmark array)
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamLibrary_nativeListBookmarks(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    handle: jlong,
+    book_id: jstring,
+) -> jstring {
+    jni_safe!(env, std::ptr::null_mut(), {
+        let book_id = jstring_raw_to_string(&mut env, book_id)?;
+        let result = library_pool(handle)
+            .and_then(|pool| runtime::block_on(list_bookmarks(&pool, &book_id)));
+        string_to_jstring(&mut env, &json::envelope(result))
+    })
+}
+
+/// Delete a bookmark (JSON envelope with a Deleted record)
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamLibrary_nativeDeleteBookmark(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    handle: jlong,
+    bookmark_id: jstring,
+) -> jstring {
+    jni_safe!(env, std::ptr::null_mut(), {
+        let id = jstring_raw_to_string(&mut env, bookmark_id)?;
+        let result =
+            library_pool(handle).and_then(|pool| runtime::block_on(delete_bookmark(&pool, &id)));
+        string_to_jstring(&mut env, &json::envelope(result))
+    })
+}
+
+/// Save the listening position of a book (JSON envelope with a Position)
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamLibrary_nativeSavePosition(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    handle: jlong,
+    book_id: jstring,
+    position_ms: jlong,
+) -> jstring {
+    jni_safe!(env, std::ptr::null_mut(), {
+        let book_id = jstring_raw_to_string(&mut env, book_id)?;
+        let result = library_pool(handle)
+            .and_then(|pool| runtime::block_on(save_position(&pool, &book_id, position_ms)));
+        string_to_jstring(&mut env, &json::envelope(result))
+    })
+}
+
+/// Get the saved listening position of a book (JSON envelope with a Position)
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamLibrary_nativeGetPosition(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    handle: jlong,
+    book_id: jstring,
+) -> jstring {
+    jni_safe!(env, std::ptr::null_mut(), {
+        let book_id = jstring_raw_to_string(&mut env, book_id)?;
+        let result =
+            library_pool(handle).and_then(|pool| runtime::block_on(get_position(&pool, &book_id)));
+        string_to_jstring(&mut env, &json::envelope(result))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn setup() -> (tempfile::TempDir, DbPool, Book) {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = runtime::block_on(async {
+            let pool = storystream_database::connection::connect(
+                storystream_database::connection::DatabaseConfig::new(
+                    dir.path().join("test.db").to_string_lossy(),
+                ),
+            )
+            .await
+            .unwrap();
+            storystream_database::run_migrations(&pool).await.unwrap();
+            pool
+        });
+
+        let book = Book::new(
+            "Middlemarch".to_string(),
+            PathBuf::from("/books/middlemarch.m4b"),
+            1_000,
+            Duration::from_seconds(600),
+        );
+        runtime::block_on(books::create_book(&pool, &book)).unwrap();
+        (dir, pool, book)
+    }
+
+    fn code<T>(result: Result<T, AppError>) -> &'static str {
+        result.err().map(|e| e.code()).unwrap_or("OK")
+    }
+
+    #[test]
+    fn test_bookmark_round_trip() {
+        let (_dir, pool, book) = setup();
+        let id = book.id.as_string();
+
+        let saved = runtime::block_on(save_bookmark(
+            &pool,
+            &id,
+            90_000,
+            Some("Chapter 2".to_string()),
+            Some("  ".to_string()),
+        ))
+        .unwrap();
+        assert_eq!(saved.title.as_deref(), Some("Chapter 2"));
+        assert!(saved.note.is_none());
+
+        let listed = runtime::block_on(list_bookmarks(&pool, &id)).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].position_ms, 90_000);
+
+        let deleted = runtime::block_on(delete_bookmark(&pool, &saved.id)).unwrap();
+        assert!(deleted.deleted);
+        assert!(runtime::block_on(list_bookmarks(&pool, &id))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            code(runtime::block_on(delete_bookmark(&pool, &saved.id))),
+            "RECORD_NOT_FOUND"
+        );
+    }
+
+    #[test]
+    fn test_bookmark_inputs_are_validated() {
+        let (_dir, pool, book) = setup();
+        let id = book.id.as_string();
+        let save = |book_id: &str, position: i64| {
+            code(runtime::block_on(save_bookmark(
+                &pool, book_id, position, None, None,
+            )))
+        };
+
+        assert_eq!(save(&id, -1), "INVALID_ARGUMENT");
+        assert_eq!(save(&id, 0), "INVALID_ARGUMENT");
+        assert_eq!(save(&id, 600_001), "INVALID_POSITION");
+        assert_eq!(save("not-an-id", 1_000), "INVALID_ARGUMENT");
+        assert_eq!(save(&BookId::new().as_string(), 1_000), "RECORD_NOT_FOUND");
+        assert_eq!(
+            code(runtime::block_on(delete_bookmark(&pool, "nope"))),
+            "INVALID_ARGUMENT"
+        );
+    }
+
+    #[test]
+    fn test_position_round_trip() {
+        let (_dir, pool, book) = setup();
+        let id = book.id.as_string();
+
+        let unsaved = runtime::block_on(get_position(&pool, &id)).unwrap();
+        assert_eq!(unsaved.position_ms, 0);
+        assert!(unsaved.updated_at.is_none());
+        assert_eq!(unsaved.duration_ms, 600_000);
+
+        runtime::block_on(save_position(&pool, &id, 125_000)).unwrap();
+        runtime::block_on(save_position(&pool, &id, 130_000)).unwrap();
+        let saved = runtime::block_on(get_position(&pool, &id)).unwrap();
+        assert_eq!(saved.position_ms, 130_000);
+        assert!(saved.updated_at.is_some());
+
+        assert_eq!(
+            code(runtime::block_on(save_position(&pool, &id, -5))),
+            "INVALID_ARGUMENT"
+        );
+        assert_eq!(
+            code(runtime::block_on(save_position(&pool, &id, 700_000))),
+            "INVALID_POSITION"
+        );
+        assert_eq!(
+            code(runtime::block_on(get_position(
+                &pool,
+                &BookId::new().as_string()
+            ))),
+            "RECORD_NOT_FOUND"
+        );
+    }
+
+    #[test]
+    fn test_json_keys_match_documented_shape() {
+        let (_dir, pool, book) = setup();
+        let saved = runtime::block_on(save_position(&pool, &book.id.as_string(), 1_000));
+        let json: serde_json::Value = serde_json::from_str(&json::envelope(saved)).unwrap();
+        let data = &json["data"];
+        for key in ["bookId", "positionMs", "durationMs", "updatedAt"] {
+            assert!(data.get(key).is_some(), "missing {}", key);
+        }
+    }
+}
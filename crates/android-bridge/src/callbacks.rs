@@ -0,0 +1,461 @@
+// Playback event callbacks for Android JNI
+//
+// Delivers player events to a Java listener object from a dedicated dispatcher
+// thread, so the Java side no longer has to poll for position. The dispatch
+// logic is independent of JNI so that the lifecycle can be tested off-device.
+
+use jni::{
+    objects::{GlobalRef, JValue},
+    JNIEnv, JavaVM,
+};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::ThreadId;
+use std::time::Duration;
+
+/// How often position updates are sent while playing
+pub const POSITION_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Player state reported to `onStateChanged(int)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackStatus {
+    /// Nothing loaded
+    Idle,
+    /// A file is loaded and ready to play
+    Ready,
+    /// Audio is playing
+    Playing,
+    /// Playback is paused
+    Paused,
+    /// Playback was stopped and the position reset
+    Stopped,
+}
+
+impl PlaybackStatus {
+    /// Stable integer passed to Java
+    pub fn code(self) -> i32 {
+        match self {
+            PlaybackStatus::Idle => 0,
+            PlaybackStatus::Ready => 1,
+            PlaybackStatus::Playing => 2,
+            PlaybackStatus::Paused => 3,
+            PlaybackStatus::Stopped => 4,
+        }
+    }
+}
+
+/// Audio focus change reported by Android's `OnAudioFocusChangeListener`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioFocus {
+    /// Focus is back (`AUDIOFOCUS_GAIN`)
+    Gain,
+    /// Another app took the output for good (`AUDIOFOCUS_LOSS`)
+    Loss,
+    /// Another app needs the output for a while (`AUDIOFOCUS_LOSS_TRANSIENT`)
+    LossTransient,
+    /// A short sound such as a notification plays over us
+    /// (`AUDIOFOCUS_LOSS_TRANSIENT_CAN_DUCK`)
+    LossTransientCanDuck,
+}
+
+impl AudioFocus {
+    /// Reads one of Android's `AUDIOFOCUS_*` constants
+    pub fn from_code(code: i32) -> Option<Self> {
+        match code {
+            1 => Some(AudioFocus::Gain),
+            -1 => Some(AudioFocus::Loss),
+            -2 => Some(AudioFocus::LossTransient),
+            -3 => Some(AudioFocus::LossTransientCanDuck),
+            _ => None,
+        }
+    }
+}
+
+/// Event emitted by a player
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlaybackEvent {
+    /// Current position in milliseconds
+    PositionChanged(i64),
+    /// Player state changed
+    StateChanged(PlaybackStatus),
+    /// The loaded track played to the end
+    TrackEnded,
+    /// Playback paused because the audio output was taken away;
+    /// `transient` if it resumes once the output is back
+    Interrupted {
+        /// Whether playback resumes by itself
+        transient: bool,
+    },
+    /// Playback failed
+    Error {
+        /// Error code
+        code: i32,
+        /// Human-readable description
+        message: String,
+    },
+}
+
+/// Receiver of playback events
+pub trait PlaybackListener: Send + Sync {
+    /// Handles a single event; called on the dispatcher thread
+    fn on_event(&self, event: &PlaybackEvent);
+}
+
+/// Listener backed by a Java object implementing the callback methods
+///
+/// Expected Java methods:
+/// `onPositionChanged(long)`, `onStateChanged(int)`, `onTrackEnded()`,
+/// `onInterrupted(boolean)` and `onError(int, String)`.
+pub struct JavaPlaybackListener {
+    vm: JavaVM,
+    callback: GlobalRef,
+}
+
+impl JavaPlaybackListener {
+    /// Wraps a global reference to the Java callback object
+    pub fn new(vm: JavaVM, callback: GlobalRef) -> Self {
+        Self { vm, callback }
+    }
+
+    fn call(&self, env: &mut JNIEnv<'_>, event: &PlaybackEvent) -> jni::errors::Result<()> {
+        let target = self.callback.as_obj();
+        match event {
+            PlaybackEvent::PositionChanged(ms) => {
+                env.call_method(target, "onPositionChanged", "(J)V", &[JValue::Long(*ms)])?;
+            }
+            PlaybackEvent::StateChanged(status) => {
+                env.call_method(
+                    target,
+                    "onStateChanged",
+                    "(I)V",
+                    &[JValue::Int(status.code())],
+                )?;
+            }
+            PlaybackEvent::TrackEnded => {
+                env.call_method(target, "onTrackEnded", "()V", &[])?;
+            }
+            PlaybackEvent::Interrupted { transient } => {
+                env.call_method(
+                    target,
+                    "onInterrupted",
+                    "(Z)V",
+                    &[JValue::Bool(*transient as u8)],
+                )?;
+            }
+            PlaybackEvent::Error { code, message } => {
+                let message = env.new_string(message)?;
+                env.call_method(
+                    target,
+                    "onError",
+                    "(ILjava/lang/String;)V",
+                    &[JValue::Int(*code), JValue::Object(&message)],
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PlaybackListener for JavaPlaybackListener {
+    fn on_event(&self, event: &PlaybackEvent) {
+        // The guard detaches the thread again on drop if this call attached it
+        let mut env = match self.vm.attach_current_thread() {
+            Ok(env) => env,
+            Err(e) => {
+                crate::ffi::log_error("StoryStream", &format!("Failed to attach thread: {}", e));
+                return;
+            }
+        };
+
+        if let Err(e) = self.call(&mut env, event) {
+            // A Java exception must not stay pending on a native thread
+            if env.exception_check().unwrap_or(false) {
+                let _ = env.exception_describe();
+                let _ = env.exception_clear();
+            }
+            crate::ffi::log_error("StoryStream", &format!("Playback callback failed: {}", e));
+        }
+    }
+}
+
+/// State shared between a registration and its dispatcher thread
+struct Subscription {
+    active: AtomicBool,
+    /// Held while a callback runs, so unregistering can wait for it
+    delivery: Mutex<()>,
+    dispatcher: OnceLock<ThreadId>,
+}
+
+impl Subscription {
+    fn deliver(&self, listener: &dyn PlaybackListener, event: &PlaybackEvent) -> bool {
+        let _delivering = self.delivery.lock().unwrap_or_else(|e| e.into_inner());
+        if !self.active.load(Ordering::SeqCst) {
+            return false;
+        }
+        listener.on_event(event);
+        true
+    }
+
+    fn cancel(&self) {
+        self.active.store(false, Ordering::SeqCst);
+
+        // Wait for an in-flight callback, unless it is the caller itself
+        if self.dispatcher.get() != Some(&std::thread::current().id()) {
+            drop(self.delivery.lock().unwrap_or_else(|e| e.into_inner()));
+        }
+    }
+}
+
+/// Listener registrations keyed by player handle
+///
+/// Each registration runs a dispatcher thread that forwards events from the
+/// player's channel and, between events, asks `tick` for periodic updates
+/// such as the current position. Once [`ListenerRegistry::unregister`]
+/// returns, the listener is never called again.
+pub struct ListenerRegistry {
+    subscriptions: Mutex<HashMap<i64, Arc<Subscription>>>,
+    interval: Duration,
+}
+
+impl Default for ListenerRegistry {
+    fn default() -> Self {
+        Self::with_interval(POSITION_INTERVAL)
+    }
+}
+
+impl ListenerRegistry {
+    /// Creates a registry that ticks every `interval`
+    pub fn with_interval(interval: Duration) -> Self {
+        Self {
+            subscriptions: Mutex::new(HashMap::new()),
+            interval,
+        }
+    }
+
+    /// Registers `listener` for `handle`, replacing any previous listener
+    pub fn register<F>(
+        &self,
+        handle: i64,
+        listener: Arc<dyn PlaybackListener>,
+        events: Receiver<PlaybackEvent>,
+        mut tick: F,
+    ) where
+        F: FnMut() -> Option<PlaybackEvent> + Send + 'static,
+    {
+        self.unregister(handle);
+
+        let subscription = Arc::new(Subscription {
+            active: AtomicBool::new(true),
+            delivery: Mutex::new(()),
+            dispatcher: OnceLock::new(),
+        });
+        self.subscriptions
+            .lock()
+            .unwrap()
+            .insert(handle, Arc::clone(&subscription));
+
+        let interval = self.interval;
+        std::thread::spawn(move || {
+            let _ = subscription.dispatcher.set(std::thread::current().id());
+
+            loop {
+                let event = match events.recv_timeout(interval) {
+                    Ok(event) => Some(event),
+                    Err(RecvTimeoutError::Timeout) => tick(),
+                    Err(RecvTimeoutError::Disconnected) => break,
+                };
+
+                if !subscription.active.load(Ordering::SeqCst) {
+                    break;
+                }
+                if let Some(event) = event {
+                    if !subscription.deliver(listener.as_ref(), &event) {
+                        break;
+                    }
+                }
+            }
+        });
+    }
+
+    /// Stops callbacks for `handle`; returns false if none were registered
+    pub fn unregister(&self, handle: i64) -> bool {
+        let removed = self.subscriptions.lock().unwrap().remove(&handle);
+        match removed {
+            Some(subscription) => {
+                subscription.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns true if a listener is registered for `handle`
+    pub fn is_registered(&self, handle: i64) -> bool {
+        self.subscriptions.lock().unwrap().contains_key(&handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Instant;
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Mutex<Vec<PlaybackEvent>>,
+    }
+
+    impl PlaybackListener for Recorder {
+        fn on_event(&self, event: &PlaybackEvent) {
+            self.events.lock().unwrap().push(event.clone());
+        }
+    }
+
+    impl Recorder {
+        fn wait_for(&self, count: usize) -> Vec<PlaybackEvent> {
+            let deadline = Instant::now() + Duration::from_secs(2);
+            while Instant::now() < deadline {
+                let events = self.events.lock().unwrap().clone();
+                if events.len() >= count {
+                    return events;
+                }
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            self.events.lock().unwrap().clone()
+        }
+    }
+
+    #[test]
+    fn test_events_delivered_in_order() {
+        let registry = ListenerRegistry::with_interval(Duration::from_secs(60));
+        let recorder = Arc::new(Recorder::default());
+        let (tx, rx) = mpsc::channel();
+
+        registry.register(1, recorder.clone(), rx, || None);
+        tx.send(PlaybackEvent::StateChanged(PlaybackStatus::Playing))
+            .unwrap();
+        tx.send(PlaybackEvent::PositionChanged(1500)).unwrap();
+        tx.send(PlaybackEvent::TrackEnded).unwrap();
+
+        assert_eq!(
+            recorder.wait_for(3),
+            vec![
+                PlaybackEvent::StateChanged(PlaybackStatus::Playing),
+                PlaybackEvent::PositionChanged(1500),
+                PlaybackEvent::TrackEnded,
+            ]
+        );
+        assert!(registry.unregister(1));
+    }
+
+    #[test]
+    fn test_tick_supplies_periodic_events() {
+        let registry = ListenerRegistry::with_interval(Duration::from_millis(5));
+        let recorder = Arc::new(Recorder::default());
+        let (_tx, rx) = mpsc::channel();
+
+        registry.register(2, recorder.clone(), rx, || {
+            Some(PlaybackEvent::PositionChanged(42))
+        });
+
+        let events = recorder.wait_for(3);
+        assert!(events.len() >= 3);
+        assert!(events
+            .iter()
+            .all(|e| *e == PlaybackEvent::PositionChanged(42)));
+        registry.unregister(2);
+    }
+
+    #[test]
+    fn test_no_callbacks_after_unregister() {
+        let registry = ListenerRegistry::with_interval(Duration::from_millis(1));
+        let recorder = Arc::new(Recorder::default());
+        let (tx, rx) = mpsc::channel();
+
+        registry.register(3, recorder.clone(), rx, || {
+            Some(PlaybackEvent::PositionChanged(0))
+        });
+        recorder.wait_for(1);
+
+        assert!(registry.unregister(3));
+        assert!(!registry.is_registered(3));
+        let seen = recorder.events.lock().unwrap().len();
+
+        let _ = tx.send(PlaybackEvent::TrackEnded);
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(recorder.events.lock().unwrap().len(), seen);
+        assert!(!registry.unregister(3));
+    }
+
+    #[test]
+    fn test_register_replaces_previous_listener() {
+        let registry = ListenerRegistry::with_interval(Duration::from_secs(60));
+        let first = Arc::new(Recorder::default());
+        let second = Arc::new(Recorder::default());
+
+        let (tx1, rx1) = mpsc::channel();
+        registry.register(4, first.clone(), rx1, || None);
+        let (tx2, rx2) = mpsc::channel();
+        registry.register(4, second.clone(), rx2, || None);
+
+        let _ = tx1.send(PlaybackEvent::TrackEnded);
+        tx2.send(PlaybackEvent::TrackEnded).unwrap();
+
+        assert_eq!(second.wait_for(1), vec![PlaybackEvent::TrackEnded]);
+        assert!(first.events.lock().unwrap().is_empty());
+        registry.unregister(4);
+    }
+
+    #[test]
+    fn test_unregister_from_callback_does_not_deadlock() {
+        struct Releasing {
+            registry: Arc<ListenerRegistry>,
+            done: mpsc::Sender<bool>,
+        }
+
+        impl PlaybackListener for Releasing {
+            fn on_event(&self, _event: &PlaybackEvent) {
+                let _ = self.done.send(self.registry.unregister(5));
+            }
+        }
+
+        let registry = Arc::new(ListenerRegistry::with_interval(Duration::from_secs(60)));
+        let (done_tx, done_rx) = mpsc::channel();
+        let listener = Arc::new(Releasing {
+            registry: Arc::clone(&registry),
+            done: done_tx,
+        });
+
+        let (tx, rx) = mpsc::channel();
+        registry.register(5, listener, rx, || None);
+        tx.send(PlaybackEvent::TrackEnded).unwrap();
+
+        assert!(done_rx.recv_timeout(Duration::from_secs(2)).unwrap());
+        assert!(!registry.is_registered(5));
+    }
+
+    #[test]
+    fn test_status_codes_are_stable() {
+        assert_eq!(PlaybackStatus::Idle.code(), 0);
+        assert_eq!(PlaybackStatus::Ready.code(), 1);
+        assert_eq!(PlaybackStatus::Playing.code(), 2);
+        assert_eq!(PlaybackStatus::Paused.code(), 3);
+        assert_eq!(PlaybackStatus::Stopped.code(), 4);
+    }
+
+    #[test]
+    fn test_audio_focus_codes() {
+        assert_eq!(AudioFocus::from_code(1), Some(AudioFocus::Gain));
+        assert_eq!(AudioFocus::from_code(-1), Some(AudioFocus::Loss));
+        assert_eq!(AudioFocus::from_code(-2), Some(AudioFocus::LossTransient));
+        assert_eq!(
+            AudioFocus::from_code(-3),
+            Some(AudioFocus::LossTransientCanDuck)
+        );
+        // AUDIOFOCUS_GAIN_TRANSIENT and friends are for requests, not changes
+        assert_eq!(AudioFocus::from_code(2), None);
+    }
+}
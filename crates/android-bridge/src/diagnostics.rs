@@ -0,0 +1,279 @@
+// Handle diagnostics for Android JNI
+//
+// Named handle managers register here so leaks can be inspected from Java
+// (`nativeDumpHandles`) and, optionally, cleaned up by an idle reaper that
+// releases handles the Java side stopped using without closing them.
+
+use crate::ffi::{jstring_raw_to_string, string_to_jstring, FfiResult, HandleStats, HandleTable};
+use crate::json;
+use jni::{
+    objects::JClass,
+    sys::{jint, jlong, jstring},
+    JNIEnv,
+};
+use once_cell::sync::Lazy;
+use std::panic; // Required for jni_safe! macro
+use std::sync::{Arc, Mutex, Once, Weak};
+use std::time::Duration;
+use storystream_core::AppError;
+
+/// How often the reaper looks for idle handles
+pub const REAPER_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Type-erased view of a named handle manager
+pub(crate) trait TrackedHandles: Send + Sync {
+    fn kind(&self) -> &'static str;
+    fn stats(&self) -> HandleStats;
+    fn reap_idle(&self, timeout: Duration) -> Vec<i64>;
+    fn set_capacity(&self, capacity: Option<usize>);
+}
+
+impl<T: Send + Sync + 'static> TrackedHandles for HandleTable<T> {
+    fn kind(&self) -> &'static str {
+        HandleTable::kind(self)
+    }
+
+    fn stats(&self) -> HandleStats {
+        HandleTable::stats(self)
+    }
+
+    fn reap_idle(&self, timeout: Duration) -> Vec<i64> {
+        HandleTable::reap_idle(self, timeout)
+    }
+
+    fn set_capacity(&self, capacity: Option<usize>) {
+        HandleTable::set_capacity(self, capacity)
+    }
+}
+
+static REGISTRY: Lazy<Mutex<Vec<Weak<dyn TrackedHandles>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Idle timeout applied by the reaper, `None` when reaping is off
+static IDLE_TIMEOUT: Lazy<Mutex<Option<Duration>>> = Lazy::new(|| Mutex::new(None));
+
+static REAPER: Once = Once::new();
+
+/// Adds a manager to the diagnostics registry
+pub(crate) fn register<T: Send + Sync + 'static>(table: Arc<HandleTable<T>>) {
+    let table: Arc<dyn TrackedHandles> = table;
+    let mut registry = REGISTRY.lock().unwrap_or_else(|e| e.into_inner());
+    registry.retain(|entry| entry.strong_count() > 0);
+    registry.push(Arc::downgrade(&table));
+}
+
+fn tracked() -> Vec<Arc<dyn TrackedHandles>> {
+    REGISTRY
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .filter_map(Weak::upgrade)
+        .collect()
+}
+
+/// Reports every named manager's live handles
+pub fn dump_handles() -> Vec<HandleStats> {
+    tracked().iter().map(|table| table.stats()).collect()
+}
+
+/// Changes the handle limit of the managers named `kind`
+///
+/// Returns whether such a manager exists.
+pub fn set_capacity(kind: &str, capacity: Option<usize>) -> bool {
+    let mut found = false;
+    for table in tracked().iter().filter(|table| table.kind() == kind) {
+        table.set_capacity(capacity);
+        found = true;
+    }
+    found
+}
+
+/// Releases handles idle for at least `timeout` in every named manager
+///
+/// Returns how many handles were released.
+pub fn reap_idle_handles(timeout: Duration) -> usize {
+    tracked()
+        .iter()
+        .map(|table| table.reap_idle(timeout).len())
+        .sum()
+}
+
+/// Turns the idle reaper on (`Some`) or off (`None`)
+///
+/// The reaper thread starts on first use and then checks every
+/// [`REAPER_INTERVAL`].
+pub fn set_idle_timeout(timeout: Option<Duration>) {
+    *IDLE_TIMEOUT.lock().unwrap_or_else(|e| e.into_inner()) = timeout;
+    if timeout.is_none() {
+        return;
+    }
+
+    REAPER.call_once(|| {
+        let spawned = std::thread::Builder::new()
+            .name("storystream-reaper".to_string())
+            .spawn(|| loop {
+                std::thread::sleep(REAPER_INTERVAL);
+                let timeout = *IDLE_TIMEOUT.lock().unwrap_or_else(|e| e.into_inner());
+                if let Some(timeout) = timeout {
+                    reap_idle_handles(timeout);
+                }
+            });
+        if let Err(e) = spawned {
+            crate::ffi::log_error("StoryStream", &format!("Failed to start reaper: {}", e));
+        }
+    });
+}
+
+/// Returns the reaper's idle timeout, `None` when reaping is off
+pub fn idle_timeout() -> Option<Duration> {
+    *IDLE_TIMEOUT.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Describe every live native handle as JSON
+///
+/// Returns an envelope whose data is a list of `HandleStats`.
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStream_nativeDumpHandles(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+) -> jstring {
+    crate::jni_safe!(env, std::ptr::null_mut(), {
+        let body = json::envelope(Ok(dump_handles()));
+        string_to_jstring(&mut env, &body)
+    })
+}
+
+/// Limit live handles of one type (`capacity <= 0` removes the limit)
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStream_nativeSetHandleCapacity(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    kind: jstring,
+    capacity: jint,
+) {
+    crate::jni_safe!(env, (), {
+        let kind = jstring_raw_to_string(&mut env, kind)?;
+        let capacity = usize::try_from(capacity).ok().filter(|&c| c > 0);
+        if !set_capacity(&kind, capacity) {
+            return Err(AppError::InvalidArgument {
+                argument: "type".to_string(),
+                reason: format!("unknown handle type '{}'", kind),
+            }
+            .into());
+        }
+        Ok(())
+    })
+}
+
+/// Release handles unused for `minutes` (`minutes <= 0` turns reaping off)
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStream_nativeSetIdleTimeout(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    minutes: jlong,
+) {
+    crate::jni_safe!(env, (), {
+        let timeout = u64::try_from(minutes)
+            .ok()
+            .filter(|&m| m > 0)
+            .map(|m| Duration::from_secs(m.saturating_mul(60)));
+        set_idle_timeout(timeout);
+        crate::ffi::log_info(
+            "StoryStream",
+            &match timeout {
+                Some(_) => format!("Idle handle reaper: {} minutes", minutes),
+                None => "Idle handle reaper: off".to_string(),
+            },
+        );
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::{FfiError, HandleManager};
+
+    #[test]
+    fn test_named_managers_are_dumped() {
+        let manager = HandleManager::<String>::named("diagnostics-dump");
+        let first = manager.insert("a".to_string()).unwrap();
+        let second = manager.insert("b".to_string()).unwrap();
+
+        let stats = dump_handles()
+            .into_iter()
+            .find(|s| s.kind == "diagnostics-dump")
+            .unwrap();
+        assert_eq!(stats.count, 2);
+        let handles: Vec<i64> = stats.handles.iter().map(|h| h.handle).collect();
+        assert_eq!(handles, vec![first, second]);
+
+        let body = json::envelope(Ok(vec![stats]));
+        assert!(body.contains(r#""type":"diagnostics-dump""#));
+        assert!(body.contains(r#""ageMs""#));
+
+        drop(manager);
+        assert!(dump_handles().iter().all(|s| s.kind != "diagnostics-dump"));
+    }
+
+    #[test]
+    fn test_capacity_is_configurable_by_type() {
+        let manager = HandleManager::<u8>::named("diagnostics-capacity").with_capacity(1);
+        let handle = manager.insert(1).unwrap();
+        assert!(matches!(manager.insert(2), Err(FfiError::HandleLimit(_))));
+
+        assert!(set_capacity("diagnostics-capacity", Some(2)));
+        manager.insert(2).unwrap();
+        assert!(manager.insert(3).is_err());
+
+        // Releasing frees a slot
+        manager.remove(handle).unwrap();
+        manager.insert(3).unwrap();
+
+        assert!(set_capacity("diagnostics-capacity", None));
+        manager.insert(4).unwrap();
+        assert!(!set_capacity("no-such-type", Some(1)));
+    }
+
+    #[test]
+    fn test_leaked_handles_are_reaped() {
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&closed);
+        let manager = HandleManager::<String>::named("diagnostics-reap")
+            .on_reap(move |handle, value| sink.lock().unwrap().push((handle, value)));
+
+        // Leaked: created and never touched again
+        let leaked = manager.insert("leaked".to_string()).unwrap();
+        std::thread::sleep(Duration::from_millis(60));
+        let active = manager.insert("active".to_string()).unwrap();
+
+        assert_eq!(manager.reap_idle(Duration::from_millis(50)), vec![leaked]);
+        assert_eq!(
+            *closed.lock().unwrap(),
+            vec![(leaked, "leaked".to_string())]
+        );
+        assert!(manager.contains(active));
+
+        // Java still holding the reaped handle gets a clear error
+        let err = manager.get(leaked).unwrap_err();
+        assert!(err.to_string().contains("already released"));
+    }
+
+    #[test]
+    fn test_use_keeps_handles_alive() {
+        let manager = HandleManager::<String>::named("diagnostics-touch");
+        let handle = manager.insert("x".to_string()).unwrap();
+
+        std::thread::sleep(Duration::from_millis(60));
+        manager.get(handle).unwrap();
+        assert!(manager.reap_idle(Duration::from_millis(50)).is_empty());
+        assert!(manager.contains(handle));
+    }
+
+    #[test]
+    fn test_idle_timeout_toggle() {
+        set_idle_timeout(Some(Duration::from_secs(3600)));
+        assert_eq!(idle_timeout(), Some(Duration::from_secs(3600)));
+        set_idle_timeout(None);
+        assert_eq!(idle_timeout(), None);
+    }
+}
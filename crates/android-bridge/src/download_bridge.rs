@@ -0,0 +1,698 @@
+// Download manager bridge for Android JNI
+//
+// Exposes the network crate's AdvancedDownloadManager so the app can fetch
+// LibriVox books. Each manager handle is created with the app-writable
+// directories downloads may be saved to; everything else is rejected.
+
+use crate::ffi::{
+    bool_to_jboolean, jboolean_to_bool, jstring_raw_to_string, string_to_jstring, FfiError,
+    FfiResult, HandleManager,
+};
+use crate::runtime;
+use jni::{
+    objects::{GlobalRef, JClass, JObject, JValue},
+    sys::{jboolean, jint, jlong, jstring},
+    JNIEnv, JavaVM,
+};
+use once_cell::sync::Lazy;
+use std::panic; // Required for jni_safe! macro
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use storystream_core::AppError;
+use storystream_network::{
+    AdvancedDownloadManager, Client, DownloadManagerConfig, DownloadStatus, DownloadTask,
+    NetworkError, Priority, ProgressCallback, StatusCallback,
+};
+
+/// Minimum time between progress callbacks for one download
+pub const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Most download managers alive at once
+pub const MAX_DOWNLOAD_MANAGERS: usize = 4;
+
+/// Global download manager handle manager
+static DOWNLOAD_HANDLES: Lazy<HandleManager<DownloadContext>> = Lazy::new(|| {
+    HandleManager::named("downloads")
+        .with_capacity(MAX_DOWNLOAD_MANAGERS)
+        .on_reap(|handle, context| close_downloads(handle, &context))
+});
+
+/// Event delivered to a download listener
+#[derive(Debug, Clone, PartialEq)]
+pub enum DownloadEvent {
+    /// Bytes received so far
+    Progress {
+        /// Task ID returned by `nativeStartDownload`
+        task_id: String,
+        /// Bytes written to the destination file
+        bytes: u64,
+        /// Total size, if the server reported one
+        total: Option<u64>,
+        /// Average speed since the download (re)started
+        bytes_per_second: f64,
+    },
+    /// The task moved to a new status
+    StatusChanged {
+        /// Task ID returned by `nativeStartDownload`
+        task_id: String,
+        /// New status
+        status: DownloadStatus,
+    },
+}
+
+/// Receiver of download events
+pub trait DownloadListener: Send + Sync {
+    /// Handles one event; called on a runtime worker thread
+    fn on_event(&self, event: &DownloadEvent);
+}
+
+/// Stable integer passed to Java for a status
+pub fn status_code(status: &DownloadStatus) -> i32 {
+    match status {
+        DownloadStatus::Queued => 0,
+        DownloadStatus::InProgress => 1,
+        DownloadStatus::Paused => 2,
+        DownloadStatus::Completed => 3,
+        DownloadStatus::Failed(_) => 4,
+        DownloadStatus::Cancelled => 5,
+    }
+}
+
+/// Maps the Java priority constant to a download priority
+fn priority_from_code(code: i32) -> Result<Priority, AppError> {
+    match code {
+        0 => Ok(Priority::Low),
+        1 => Ok(Priority::Normal),
+        2 => Ok(Priority::High),
+        3 => Ok(Priority::Critical),
+        _ => Err(AppError::InvalidArgument {
+            argument: "priority".to_string(),
+            reason: format!("{} is not between 0 (low) and 3 (critical)", code),
+        }),
+    }
+}
+
+/// Listener backed by a Java object
+///
+/// Expected Java methods: `onProgress(String, long, long, double)` with a
+/// total of -1 when unknown, and `onStatusChanged(String, int, String)` with
+/// the failure reason or null.
+struct JavaDownloadListener {
+    vm: JavaVM,
+    callback: GlobalRef,
+}
+
+impl JavaDownloadListener {
+    fn call(&self, env: &mut JNIEnv<'_>, event: &DownloadEvent) -> jni::errors::Result<()> {
+        let target = self.callback.as_obj();
+        match event {
+            DownloadEvent::Progress {
+                task_id,
+                bytes,
+                total,
+                bytes_per_second,
+            } => {
+                let task_id = env.new_string(task_id)?;
+                env.call_method(
+                    target,
+                    "onProgress",
+                    "(Ljava/lang/String;JJD)V",
+                    &[
+                        JValue::Object(&task_id),
+                        JValue::Long(*bytes as i64),
+                        JValue::Long(total.map_or(-1, |t| t as i64)),
+                        JValue::Double(*bytes_per_second),
+                    ],
+                )?;
+            }
+            DownloadEvent::StatusChanged { task_id, status } => {
+                let task_id = env.new_string(task_id)?;
+                let reason = match status {
+                    DownloadStatus::Failed(reason) => JObject::from(env.new_string(reason)?),
+                    _ => JObject::null(),
+                };
+                env.call_method(
+                    target,
+                    "onStatusChanged",
+                    "(Ljava/lang/String;ILjava/lang/String;)V",
+                    &[
+                        JValue::Object(&task_id),
+                        JValue::Int(status_code(status)),
+                        JValue::Object(&reason),
+                    ],
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl DownloadListener for JavaDownloadListener {
+    fn on_event(&self, event: &DownloadEvent) {
+        // The guard detaches the thread again on drop if this call attached it
+        let mut env = match self.vm.attach_current_thread() {
+            Ok(env) => env,
+            Err(e) => {
+                crate::ffi::log_error("StoryStream", &format!("Failed to attach thread: {}", e));
+                return;
+            }
+        };
+
+        if let Err(e) = self.call(&mut env, event) {
+            if env.exception_check().unwrap_or(false) {
+                let _ = env.exception_describe();
+                let _ = env.exception_clear();
+            }
+            crate::ffi::log_error("StoryStream", &format!("Download callback failed: {}", e));
+        }
+    }
+}
+
+/// Listener slot shared with the task callbacks
+///
+/// Callbacks read the slot while they run, so clearing it waits for any
+/// callback in flight and nothing is delivered afterwards.
+type ListenerSlot = Arc<RwLock<Option<Arc<dyn DownloadListener>>>>;
+
+fn notify(slot: &ListenerSlot, event: DownloadEvent) {
+    let listener = slot.read().unwrap_or_else(|e| e.into_inner());
+    if let Some(listener) = listener.as_ref() {
+        listener.on_event(&event);
+    }
+}
+
+/// State behind a download manager handle
+#[derive(Clone)]
+struct DownloadContext {
+    manager: Arc<AdvancedDownloadManager>,
+    allowed_roots: Arc<Vec<PathBuf>>,
+    listener: ListenerSlot,
+    next_task: Arc<AtomicU64>,
+}
+
+impl DownloadContext {
+    fn new(allowed_roots: Vec<PathBuf>) -> Result<Self, AppError> {
+        if allowed_roots.is_empty() {
+            return Err(AppError::InvalidArgument {
+                argument: "allowedDirs".to_string(),
+                reason: "at least one download directory is required".to_string(),
+            });
+        }
+
+        let client = Client::new().map_err(|e| AppError::InternalError {
+            message: format!("Failed to create HTTP client: {}", e),
+        })?;
+
+        Ok(Self {
+            manager: Arc::new(AdvancedDownloadManager::new(
+                client,
+                DownloadManagerConfig::default(),
+            )),
+            allowed_roots: Arc::new(allowed_roots),
+            listener: Arc::new(RwLock::new(None)),
+            next_task: Arc::new(AtomicU64::new(1)),
+        })
+    }
+
+    /// Queues a download and returns its task ID
+    fn start_download(&self, url: &str, dest: &str, priority: i32) -> Result<String, AppError> {
+        if !(url.starts_with("https://") || url.starts_with("http://")) {
+            return Err(AppError::InvalidUrl {
+                url: url.to_string(),
+            });
+        }
+        let priority = priority_from_code(priority)?;
+        let destination = validate_destination(Path::new(dest), &self.allowed_roots)?;
+
+        let task_id = format!("download-{}", self.next_task.fetch_add(1, Ordering::SeqCst));
+        let task = DownloadTask::new(task_id.clone(), url.to_string(), destination)
+            .with_priority(priority)
+            .with_progress_callback(self.progress_callback(&task_id))
+            .with_status_callback(self.status_callback());
+
+        runtime::block_on(self.manager.enqueue(task)).map_err(|e| AppError::InternalError {
+            message: e.to_string(),
+        })?;
+        Ok(task_id)
+    }
+
+    fn progress_callback(&self, task_id: &str) -> ProgressCallback {
+        let slot = Arc::clone(&self.listener);
+        let task_id = task_id.to_string();
+        // (first bytes seen and when, last emission)
+        let meter: Mutex<Option<(u64, Instant, Instant)>> = Mutex::new(None);
+
+        Arc::new(move |bytes: u64, total: Option<u64>| {
+            let now = Instant::now();
+            let mut meter = meter.lock().unwrap_or_else(|e| e.into_inner());
+
+            // A resumed download restarts the meter at its offset
+            let (start_bytes, started, last) = match *meter {
+                Some((start_bytes, started, last)) if bytes >= start_bytes => {
+                    (start_bytes, started, last)
+                }
+                _ => {
+                    *meter = Some((bytes, now, now));
+                    (bytes, now, now - PROGRESS_INTERVAL)
+                }
+            };
+
+            let finished = total.is_some_and(|t| bytes >= t);
+            if !finished && now.duration_since(last) < PROGRESS_INTERVAL {
+                return;
+            }
+            *meter = Some((start_bytes, started, now));
+            drop(meter);
+
+            let elapsed = now.duration_since(started).as_secs_f64();
+            let bytes_per_second = if elapsed > 0.0 {
+                (bytes - start_bytes) as f64 / elapsed
+            } else {
+                0.0
+            };
+            notify(
+                &slot,
+                DownloadEvent::Progress {
+                    task_id: task_id.clone(),
+                    bytes,
+                    total,
+                    bytes_per_second,
+                },
+            );
+        })
+    }
+
+    fn status_callback(&self) -> StatusCallback {
+        let slot = Arc::clone(&self.listener);
+        Arc::new(move |task_id: &str, status: &DownloadStatus| {
+            notify(
+                &slot,
+                DownloadEvent::StatusChanged {
+                    task_id: task_id.to_string(),
+                    status: status.clone(),
+                },
+            );
+        })
+    }
+
+    fn set_listener(&self, listener: Option<Arc<dyn DownloadListener>>) {
+        *self.listener.write().unwrap_or_else(|e| e.into_inner()) = listener;
+    }
+}
+
+/// Checks that `dest` is a file inside one of the allowed directories
+///
+/// Rejects relative paths and `..` components, then resolves symlinks in the
+/// parent directory so a link cannot point the download elsewhere.
+fn validate_destination(dest: &Path, allowed_roots: &[PathBuf]) -> Result<PathBuf, AppError> {
+    let denied = || AppError::PermissionDenied {
+        operation: "download".to_string(),
+        path: dest.to_path_buf(),
+    };
+
+    if !dest.is_absolute() || dest.file_name().is_none() {
+        return Err(AppError::InvalidArgument {
+            argument: "destPath".to_string(),
+            reason: format!("{} is not an absolute file path", dest.display()),
+        });
+    }
+    if dest
+        .components()
+        .any(|c| matches!(c, Component::ParentDir | Component::CurDir))
+    {
+        return Err(denied());
+    }
+
+    let root = allowed_roots
+        .iter()
+        .find(|root| dest.starts_with(root))
+        .ok_or_else(denied)?;
+
+    let parent = dest.parent().ok_or_else(denied)?;
+    let real_root = root.canonicalize()?;
+
+    // Check the deepest existing directory before creating anything below it
+    let existing = parent
+        .ancestors()
+        .find(|dir| dir.exists())
+        .ok_or_else(denied)?;
+    if !existing.canonicalize()?.starts_with(&real_root) {
+        return Err(denied());
+    }
+
+    std::fs::create_dir_all(parent)?;
+    let real_parent = parent.canonicalize()?;
+    if !real_parent.starts_with(&real_root) {
+        return Err(denied());
+    }
+
+    Ok(real_parent.join(dest.file_name().ok_or_else(denied)?))
+}
+
+fn context(handle: i64) -> FfiResult<DownloadContext> {
+    Ok(DOWNLOAD_HANDLES.get(handle)?.read().unwrap().clone())
+}
+
+/// Silences the listener and stops the manager behind a released handle
+fn close_downloads(handle: i64, context: &DownloadContext) {
+    context.set_listener(None);
+    if let Err(e) = runtime::block_on(context.manager.shutdown()) {
+        crate::ffi::log_error("StoryStream", &format!("Download shutdown failed: {}", e));
+    }
+    crate::ffi::log_info(
+        "StoryStream",
+        &format!("Destroyed download manager handle: {}", handle),
+    );
+}
+
+/// Reports a pause/resume/cancel the manager refused for `taskId`
+fn task_error(error: NetworkError) -> FfiError {
+    AppError::InvalidArgument {
+        argument: "taskId".to_string(),
+        reason: error.to_string(),
+    }
+    .into()
+}
+
+/// Create a download manager that may write to `allowedDirs`
+///
+/// `allowedDirs` uses the platform path-list separator (`:` on Android),
+/// e.g. `getFilesDir()` and `getExternalFilesDir(null)` joined together.
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamDownloads_nativeCreate(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    allowed_dirs: jstring,
+) -> jlong {
+    crate::jni_safe!(env, 0, {
+        let dirs = jstring_raw_to_string(&mut env, allowed_dirs)?;
+        let roots: Vec<PathBuf> = std::env::split_paths(&dirs)
+            .filter(|p| !p.as_os_str().is_empty())
+            .collect();
+
+        let context = DownloadContext::new(roots)?;
+        let manager = Arc::clone(&context.manager);
+        runtime::handle().spawn(async move { manager.start().await });
+
+        let handle = DOWNLOAD_HANDLES.insert(context)?;
+        crate::ffi::log_info(
+            "StoryStream",
+            &format!("Created download manager (handle: {})", handle),
+        );
+        Ok(handle)
+    })
+}
+
+/// Queue a download and return its task ID
+///
+/// `priority` is 0 (low) to 3 (critical).
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamDownloads_nativeStartDownload(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    handle: jlong,
+    url: jstring,
+    dest_path: jstring,
+    priority: jint,
+) -> jstring {
+    crate::jni_safe!(env, std::ptr::null_mut(), {
+        let context = context(handle)?;
+        let url = jstring_raw_to_string(&mut env, url)?;
+        let dest = jstring_raw_to_string(&mut env, dest_path)?;
+
+        let task_id = context.start_download(&url, &dest, priority)?;
+        crate::ffi::log_info(
+            "StoryStream",
+            &format!("Queued download {}: {} -> {}", task_id, url, dest),
+        );
+        string_to_jstring(&mut env, &task_id)
+    })
+}
+
+/// Pause a queued or running download
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamDownloads_nativePauseDownload(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    handle: jlong,
+    task_id: jstring,
+) -> jboolean {
+    crate::jni_safe!(env, bool_to_jboolean(false), {
+        let context = context(handle)?;
+        let task_id = jstring_raw_to_string(&mut env, task_id)?;
+        runtime::block_on(context.manager.pause(&task_id)).map_err(task_error)?;
+        Ok(bool_to_jboolean(true))
+    })
+}
+
+/// Resume a paused download
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamDownloads_nativeResumeDownload(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    handle: jlong,
+    task_id: jstring,
+) -> jboolean {
+    crate::jni_safe!(env, bool_to_jboolean(false), {
+        let context = context(handle)?;
+        let task_id = jstring_raw_to_string(&mut env, task_id)?;
+        runtime::block_on(context.manager.resume(&task_id)).map_err(task_error)?;
+        Ok(bool_to_jboolean(true))
+    })
+}
+
+/// Cancel a download
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamDownloads_nativeCancelDownload(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    handle: jlong,
+    task_id: jstring,
+) -> jboolean {
+    crate::jni_safe!(env, bool_to_jboolean(false), {
+        let context = context(handle)?;
+        let task_id = jstring_raw_to_string(&mut env, task_id)?;
+        runtime::block_on(context.manager.cancel(&task_id)).map_err(task_error)?;
+        Ok(bool_to_jboolean(true))
+    })
+}
+
+/// Get a download's status code, or -1 for an unknown task
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamDownloads_nativeGetStatus(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    handle: jlong,
+    task_id: jstring,
+) -> jint {
+    crate::jni_safe!(env, -1, {
+        let context = context(handle)?;
+        let task_id = jstring_raw_to_string(&mut env, task_id)?;
+        Ok(runtime::block_on(context.manager.get_status(&task_id))
+            .map_or(-1, |status| status_code(&status)))
+    })
+}
+
+/// Tell the manager whether the active connection is metered
+///
+/// Running downloads pause while metered and resume once it is not.
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamDownloads_nativeSetMetered(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    handle: jlong,
+    metered: jboolean,
+) {
+    crate::jni_safe!(env, (), {
+        let context = context(handle)?;
+        runtime::block_on(context.manager.set_metered(jboolean_to_bool(metered)));
+        Ok(())
+    })
+}
+
+/// Register a progress listener, or clear it by passing null
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamDownloads_nativeSetProgressListener(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    handle: jlong,
+    listener: JObject<'_>,
+) -> jboolean {
+    crate::jni_safe!(env, bool_to_jboolean(false), {
+        let context = context(handle)?;
+
+        if listener.is_null() {
+            context.set_listener(None);
+            return Ok(bool_to_jboolean(true));
+        }
+
+        let vm = env.get_java_vm()?;
+        let callback = env.new_global_ref(&listener)?;
+        context.set_listener(Some(Arc::new(JavaDownloadListener { vm, callback })));
+        Ok(bool_to_jboolean(true))
+    })
+}
+
+/// Destroy a download manager, stopping its downloads
+///
+/// No listener callbacks fire after this returns.
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamDownloads_nativeDestroy(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    handle: jlong,
+) {
+    crate::jni_safe!(env, (), {
+        let context = DOWNLOAD_HANDLES.remove(handle)?;
+        close_downloads(handle, &context);
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Mutex<Vec<DownloadEvent>>,
+    }
+
+    impl DownloadListener for Recorder {
+        fn on_event(&self, event: &DownloadEvent) {
+            self.events.lock().unwrap().push(event.clone());
+        }
+    }
+
+    fn test_context(dir: &tempfile::TempDir) -> DownloadContext {
+        DownloadContext::new(vec![dir.path().to_path_buf()]).unwrap()
+    }
+
+    fn dest(dir: &tempfile::TempDir, name: &str) -> String {
+        dir.path().join("books").join(name).display().to_string()
+    }
+
+    #[test]
+    fn test_destination_must_be_inside_allowed_roots() {
+        let dir = tempfile::tempdir().unwrap();
+        let roots = vec![dir.path().to_path_buf()];
+
+        let inside = dir.path().join("librivox").join("book.mp3");
+        let resolved = validate_destination(&inside, &roots).unwrap();
+        assert!(resolved.ends_with("librivox/book.mp3"));
+
+        let escape = dir.path().join("..").join("book.mp3");
+        assert!(matches!(
+            validate_destination(&escape, &roots),
+            Err(AppError::PermissionDenied { .. })
+        ));
+        assert!(validate_destination(Path::new("/etc/book.mp3"), &roots).is_err());
+        assert!(matches!(
+            validate_destination(Path::new("book.mp3"), &roots),
+            Err(AppError::InvalidArgument { .. })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_destination_symlink_cannot_escape() {
+        let dir = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("link")).unwrap();
+
+        let dest = dir.path().join("link").join("sub").join("book.mp3");
+        assert!(matches!(
+            validate_destination(&dest, &[dir.path().to_path_buf()]),
+            Err(AppError::PermissionDenied { .. })
+        ));
+        assert!(!outside.path().join("sub").exists());
+    }
+
+    #[test]
+    fn test_start_download_assigns_task_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let context = test_context(&dir);
+
+        let first = context
+            .start_download("https://example.com/a.mp3", &dest(&dir, "a.mp3"), 1)
+            .unwrap();
+        let second = context
+            .start_download("https://example.com/b.mp3", &dest(&dir, "b.mp3"), 3)
+            .unwrap();
+        assert_ne!(first, second);
+        assert_eq!(
+            runtime::block_on(context.manager.get_status(&first)),
+            Some(DownloadStatus::Queued)
+        );
+
+        assert!(context
+            .start_download("ftp://example.com/c.mp3", &dest(&dir, "c.mp3"), 1)
+            .is_err());
+        assert!(context
+            .start_download("https://example.com/d.mp3", &dest(&dir, "d.mp3"), 9)
+            .is_err());
+    }
+
+    #[test]
+    fn test_status_transitions_reach_listener() {
+        let dir = tempfile::tempdir().unwrap();
+        let context = test_context(&dir);
+        let recorder = Arc::new(Recorder::default());
+        context.set_listener(Some(recorder.clone()));
+
+        let task = context
+            .start_download("https://example.com/a.mp3", &dest(&dir, "a.mp3"), 1)
+            .unwrap();
+        runtime::block_on(context.manager.pause(&task)).unwrap();
+        runtime::block_on(context.manager.resume(&task)).unwrap();
+
+        let codes: Vec<i32> = recorder
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|e| match e {
+                DownloadEvent::StatusChanged { status, .. } => Some(status_code(status)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(codes, [0, 2, 0]);
+    }
+
+    #[test]
+    fn test_progress_is_throttled_and_silenced_after_clear() {
+        let dir = tempfile::tempdir().unwrap();
+        let context = test_context(&dir);
+        let recorder = Arc::new(Recorder::default());
+        context.set_listener(Some(recorder.clone()));
+
+        let progress = context.progress_callback("download-1");
+        progress(100, Some(1000));
+        progress(200, Some(1000));
+        progress(1000, Some(1000));
+        assert_eq!(recorder.events.lock().unwrap().len(), 2);
+
+        context.set_listener(None);
+        progress(1000, Some(1000));
+        assert_eq!(recorder.events.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_handle_lifecycle() {
+        let dir = tempfile::tempdir().unwrap();
+        let handle = DOWNLOAD_HANDLES.insert(test_context(&dir)).unwrap();
+        assert!(context(handle).is_ok());
+
+        let removed = DOWNLOAD_HANDLES.remove(handle).unwrap();
+        removed.set_listener(None);
+        assert!(context(handle).is_err());
+        assert!(DOWNLOAD_HANDLES.remove(handle).is_err());
+    }
+
+    #[test]
+    fn test_empty_roots_rejected() {
+        assert!(DownloadContext::new(Vec::new()).is_err());
+    }
+}
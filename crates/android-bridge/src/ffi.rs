@@ -2,8 +2,44 @@
 //
 // This module provides safe wrappers for JNI operations with panic handling
 // and type conversions between Rust and Java types.
+//
+// JSON responses
+// --------------
+// Query functions return a JSON string wrapped in an envelope (see
+// `crate::json`). Keys are camelCase, times are Unix milliseconds, and
+// durations and positions are milliseconds:
+//
+//   Envelope     {"ok": true, "data": <payload>, "error": null}
+//                {"ok": false, "data": null,
+//                 "error": {"code": "RECORD_NOT_FOUND", "message": "...", "userMessage": "..."}}
+//   Book         {"id", "title", "author"?, "narrator"?, "series"?, "seriesPosition"?,
+//                 "description"?, "durationMs", "filePath", "coverArtPath"?, "addedDate",
+//                 "lastPlayed"?, "playCount", "favorite", "rating"?, "tags": [..]}
+//   BookPage     {"page", "pageSize", "total", "books": [Book]}
+//   InProgress   Book fields plus {"positionMs", "progress"}      (0.0 - 1.0)
+//   Bookmark     {"id", "bookId", "positionMs", "title"?, "note"?, "createdAt", "updatedAt"}
+//   Position     {"bookId", "positionMs", "durationMs", "updatedAt"?}
+//   Deleted      {"id", "deleted": true}
+//   Handles      [{"type", "count", "capacity"?, "handles": [{"handle", "ageMs", "idleMs"}]}]
+//   NowPlaying   {"bookId", "title", "author"?, "narrator"?,
+//                 "chapter"?: {"index", "title", "startMs", "endMs"}, "chapterCount",
+//                 "durationMs", "positionMs", "playing", "speed", "artworkPath"?}
+//   ScanSummary  {"filesFound", "imported", "skipped", "failed", "cancelled", "durationMs",
+//                 "failures": [{"path", "code", "message"}]}
+//
+// Keys marked ? may be null. Error codes are those of `AppError::code`, e.g.
+// INVALID_ARGUMENT for malformed input, INVALID_POSITION for positions past
+// the end of a book, RECORD_NOT_FOUND for unknown IDs and PERMISSION_DENIED
+// for storage the app may not read.
 
-use jni::{objects::JString, sys::jstring, JNIEnv};
+use jni::{
+    objects::{JString, JValue},
+    sys::jstring,
+    JNIEnv,
+};
+use once_cell::sync::Lazy;
+use std::sync::RwLock;
+use storystream_core::AppError;
 // Required for catch_unwind in jni_safe! macro
 
 /// FFI-safe error type that can cross the Rust/Java boundary
@@ -15,8 +51,16 @@ pub enum FfiError {
     Utf8Error(String),
     /// Handle not found or invalid
     InvalidHandle(String),
+    /// Too many live handles of one type
+    HandleLimit(String),
+    /// A one-at-a-time operation is already in progress
+    AlreadyRunning(String),
     /// General error
     General(String),
+    /// Error from the core crates
+    App(AppError),
+    /// Rust code panicked
+    Panic(String),
 }
 
 impl std::fmt::Display for FfiError {
@@ -25,12 +69,219 @@ impl std::fmt::Display for FfiError {
             FfiError::JniError(msg) => write!(f, "JNI Error: {}", msg),
             FfiError::Utf8Error(msg) => write!(f, "UTF-8 Error: {}", msg),
             FfiError::InvalidHandle(msg) => write!(f, "Invalid Handle: {}", msg),
+            FfiError::HandleLimit(msg) => write!(f, "Handle Limit: {}", msg),
+            FfiError::AlreadyRunning(msg) => write!(f, "Already Running: {}", msg),
             FfiError::General(msg) => write!(f, "Error: {}", msg),
+            FfiError::App(err) => write!(f, "{}", err),
+            FfiError::Panic(msg) => write!(f, "Panic: {}", msg),
         }
     }
 }
 
-impl std::error::Error for FfiError {}
+impl std::error::Error for FfiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FfiError::App(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<AppError> for FfiError {
+    fn from(err: AppError) -> Self {
+        FfiError::App(err)
+    }
+}
+
+/// Error category reported to Java as `StoryStreamException.domain`
+///
+/// The numbers are part of the Java API and must never be reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorDomain {
+    /// Failures inside the bridge itself (JNI, handles, panics)
+    Bridge = 1,
+    /// Network errors
+    Network = 2,
+    /// Database errors
+    Database = 3,
+    /// Audio and playback errors
+    Media = 4,
+    /// File system errors
+    FileSystem = 5,
+    /// Metadata parsing errors
+    Metadata = 6,
+    /// Content source errors
+    Content = 7,
+    /// Configuration errors
+    Configuration = 8,
+    /// Sync errors
+    Sync = 9,
+    /// Cache errors
+    Cache = 10,
+    /// System resource errors
+    System = 11,
+    /// Generic errors such as invalid arguments
+    General = 12,
+}
+
+/// Stable identity of an error as seen from Java
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ErrorCode {
+    /// Error category
+    pub domain: ErrorDomain,
+    /// Number unique within the domain
+    pub code: i32,
+    /// Symbolic name, e.g. `RECORD_NOT_FOUND`
+    pub name: &'static str,
+}
+
+impl ErrorCode {
+    const fn new(domain: ErrorDomain, code: i32, name: &'static str) -> Self {
+        Self { domain, code, name }
+    }
+}
+
+/// Maps an `AppError` to its stable code
+///
+/// The match is deliberately exhaustive so a new `AppError` variant fails to
+/// compile until it is given a code here.
+pub fn app_error_code(err: &AppError) -> ErrorCode {
+    use ErrorDomain::*;
+
+    let (domain, code) = match err {
+        AppError::NetworkError { .. } => (Network, 1),
+        AppError::NetworkTimeout { .. } => (Network, 2),
+        AppError::ConnectionLost { .. } => (Network, 3),
+        AppError::InvalidUrl { .. } => (Network, 4),
+        AppError::DatabaseError { .. } => (Database, 1),
+        AppError::DatabaseCorrupted { .. } => (Database, 2),
+        AppError::MigrationFailed { .. } => (Database, 3),
+        AppError::DatabaseLocked { .. } => (Database, 4),
+        AppError::RecordNotFound { .. } => (Database, 5),
+        AppError::DatabaseReadOnly { .. } => (Database, 6),
+        AppError::UnsupportedFormat { .. } => (Media, 1),
+        AppError::AudioDecodeError { .. } => (Media, 2),
+        AppError::CorruptedAudioFile { .. } => (Media, 3),
+        AppError::PlaybackDeviceError { .. } => (Media, 4),
+        AppError::InvalidPosition { .. } => (Media, 5),
+        AppError::FileNotFound { .. } => (FileSystem, 1),
+        AppError::PermissionDenied { .. } => (FileSystem, 2),
+        AppError::DiskFull { .. } => (FileSystem, 3),
+        AppError::IoError { .. } => (FileSystem, 4),
+        AppError::MetadataParseError { .. } => (Metadata, 1),
+        AppError::InvalidMetadata { .. } => (Metadata, 2),
+        AppError::MissingMetadata { .. } => (Metadata, 3),
+        AppError::ContentSourceUnavailable { .. } => (Content, 1),
+        AppError::InvalidContentResponse { .. } => (Content, 2),
+        AppError::ContentNotFound { .. } => (Content, 3),
+        AppError::InvalidConfiguration { .. } => (Configuration, 1),
+        AppError::ConfigurationCorrupted { .. } => (Configuration, 2),
+        AppError::SyncConflict { .. } => (Sync, 1),
+        AppError::SyncAuthFailed { .. } => (Sync, 2),
+        AppError::CacheWriteFailed { .. } => (Cache, 1),
+        AppError::CacheCorrupted { .. } => (Cache, 2),
+        AppError::OutOfMemory { .. } => (System, 1),
+        AppError::TooManyOpenFiles { .. } => (System, 2),
+        AppError::InternalError { .. } => (General, 1),
+        AppError::Cancelled { .. } => (General, 2),
+        AppError::InvalidArgument { .. } => (General, 3),
+    };
+    ErrorCode::new(domain, code, err.code())
+}
+
+impl FfiError {
+    /// Returns the stable code reported to Java
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            FfiError::JniError(_) => ErrorCode::new(ErrorDomain::Bridge, 1, "JNI_ERROR"),
+            FfiError::Utf8Error(_) => ErrorCode::new(ErrorDomain::Bridge, 2, "UTF8_ERROR"),
+            FfiError::InvalidHandle(_) => ErrorCode::new(ErrorDomain::Bridge, 3, "INVALID_HANDLE"),
+            FfiError::General(_) => ErrorCode::new(ErrorDomain::Bridge, 4, "BRIDGE_ERROR"),
+            FfiError::Panic(_) => ErrorCode::new(ErrorDomain::Bridge, 5, "PANIC"),
+            FfiError::HandleLimit(_) => ErrorCode::new(ErrorDomain::Bridge, 6, "HANDLE_LIMIT"),
+            FfiError::AlreadyRunning(_) => {
+                ErrorCode::new(ErrorDomain::Bridge, 7, "ALREADY_RUNNING")
+            }
+            FfiError::App(err) => app_error_code(err),
+        }
+    }
+
+    /// Builds the error for a caught panic payload
+    pub fn from_panic(payload: &(dyn std::any::Any + Send)) -> Self {
+        let msg = if let Some(s) = payload.downcast_ref::<&str>() {
+            s.to_string()
+        } else if let Some(s) = payload.downcast_ref::<String>() {
+            s.clone()
+        } else {
+            "Unknown panic occurred".to_string()
+        };
+        FfiError::Panic(msg)
+    }
+}
+
+/// Java exception class thrown for bridge errors
+pub const DEFAULT_EXCEPTION_CLASS: &str = "com/storystream/StoryStreamException";
+
+/// Exception class name in JNI form (`com/example/Name`)
+static EXCEPTION_CLASS: Lazy<RwLock<String>> =
+    Lazy::new(|| RwLock::new(DEFAULT_EXCEPTION_CLASS.to_string()));
+
+/// Changes the exception class thrown by [`throw_storystream_exception`]
+///
+/// Accepts dotted (`com.example.Name`) or JNI (`com/example/Name`) names.
+/// The class must have a `(int domain, int code, String name, String message)`
+/// constructor.
+pub fn set_exception_class(name: &str) {
+    *EXCEPTION_CLASS.write().unwrap_or_else(|e| e.into_inner()) = name.replace('.', "/");
+}
+
+/// Returns the exception class in JNI form
+pub fn exception_class() -> String {
+    EXCEPTION_CLASS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+/// Throws a `StoryStreamException` carrying `err`'s domain, code and message
+///
+/// Falls back to `java.lang.RuntimeException` if the exception class cannot
+/// be constructed, so an error is never silently dropped.
+pub fn throw_storystream_exception(env: &mut JNIEnv<'_>, err: &FfiError) {
+    let code = err.error_code();
+    let message = err.to_string();
+    log_error("StoryStream", &format!("[{}] {}", code.name, message));
+
+    // A pending exception (e.g. from a failed JNI call) would make every
+    // further JNI call below invalid
+    if env.exception_check().unwrap_or(false) {
+        let _ = env.exception_clear();
+    }
+
+    let thrown = (|| -> jni::errors::Result<()> {
+        let name = env.new_string(code.name)?;
+        let text = env.new_string(&message)?;
+        let exception = env.new_object(
+            exception_class(),
+            "(IILjava/lang/String;Ljava/lang/String;)V",
+            &[
+                JValue::Int(code.domain as i32),
+                JValue::Int(code.code),
+                JValue::Object(&name),
+                JValue::Object(&text),
+            ],
+        )?;
+        env.throw(jni::objects::JThrowable::from(exception))
+    })();
+
+    if thrown.is_err() {
+        let _ = env.exception_clear();
+        let _ = env.throw_new(
+            "java/lang/RuntimeException",
+            format!("[{}] {}", code.name, message),
+        );
+    }
+}
 
 impl From<jni::errors::Error> for FfiError {
     fn from(err: jni::errors::Error) -> Self {
@@ -52,18 +303,167 @@ impl From<std::string::FromUtf8Error> for FfiError {
 
 pub type FfiResult<T> = Result<T, FfiError>;
 
+/// Close hook run on a handle the idle reaper removed
+type ReapHook<T> = Box<dyn Fn(i64, T) + Send + Sync>;
+
+/// Value stored behind a handle plus its bookkeeping
+struct HandleEntry<T> {
+    value: T,
+    created: std::time::Instant,
+    last_used: std::sync::Mutex<std::time::Instant>,
+}
+
+impl<T> HandleEntry<T> {
+    fn new(value: T) -> Self {
+        let now = std::time::Instant::now();
+        Self {
+            value,
+            created: now,
+            last_used: std::sync::Mutex::new(now),
+        }
+    }
+
+    fn touch(&self) {
+        *self.last_used.lock().unwrap_or_else(|e| e.into_inner()) = std::time::Instant::now();
+    }
+
+    fn idle(&self, now: std::time::Instant) -> std::time::Duration {
+        now.saturating_duration_since(*self.last_used.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+/// Age and idle time of one live handle
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HandleInfo {
+    /// Handle value given to Java
+    pub handle: i64,
+    /// Milliseconds since the handle was created
+    pub age_ms: u64,
+    /// Milliseconds since the handle was last used
+    pub idle_ms: u64,
+}
+
+/// Live handles of one type, as reported by `nativeDumpHandles`
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HandleStats {
+    /// Handle type, e.g. `player`
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    /// Number of live handles
+    pub count: usize,
+    /// Maximum live handles, `None` if unlimited
+    pub capacity: Option<usize>,
+    /// Live handles, oldest first
+    pub handles: Vec<HandleInfo>,
+}
+
+/// Shared state behind a [`HandleManager`]
+pub(crate) struct HandleTable<T> {
+    kind: &'static str,
+    handles: std::sync::RwLock<std::collections::HashMap<i64, HandleEntry<T>>>,
+    next_handle: std::sync::atomic::AtomicI64,
+    capacity: std::sync::RwLock<Option<usize>>,
+    on_reap: std::sync::RwLock<Option<ReapHook<T>>>,
+}
+
+impl<T> HandleTable<T> {
+    fn missing(&self, handle: i64) -> FfiError {
+        // Handles are handed out in increasing order and never reused, so a
+        // missing handle below the counter was released earlier
+        let issued =
+            handle > 0 && handle < self.next_handle.load(std::sync::atomic::Ordering::SeqCst);
+        if issued {
+            FfiError::InvalidHandle(format!(
+                "{} handle {} was already released",
+                self.kind, handle
+            ))
+        } else {
+            FfiError::InvalidHandle(format!("{} handle {} not found", self.kind, handle))
+        }
+    }
+
+    /// Reports the live handles, oldest first
+    pub(crate) fn stats(&self) -> HandleStats {
+        let now = std::time::Instant::now();
+        let handles = self.handles.read().unwrap();
+        let mut infos: Vec<HandleInfo> = handles
+            .iter()
+            .map(|(&handle, entry)| HandleInfo {
+                handle,
+                age_ms: now.saturating_duration_since(entry.created).as_millis() as u64,
+                idle_ms: entry.idle(now).as_millis() as u64,
+            })
+            .collect();
+        infos.sort_by_key(|info| info.handle);
+
+        HandleStats {
+            kind: self.kind,
+            count: infos.len(),
+            capacity: *self.capacity.read().unwrap(),
+            handles: infos,
+        }
+    }
+
+    /// Removes handles idle for at least `timeout` and runs the reap hook on each
+    pub(crate) fn reap_idle(&self, timeout: std::time::Duration) -> Vec<i64> {
+        let now = std::time::Instant::now();
+        let reaped: Vec<(i64, HandleEntry<T>)> = {
+            let mut handles = self.handles.write().unwrap();
+            let idle: Vec<i64> = handles
+                .iter()
+                .filter(|(_, entry)| entry.idle(now) >= timeout)
+                .map(|(&handle, _)| handle)
+                .collect();
+            idle.into_iter()
+                .filter_map(|handle| handles.remove(&handle).map(|entry| (handle, entry)))
+                .collect()
+        };
+
+        // The hook may call back into the bridge, so it runs without the lock
+        let hook = self.on_reap.read().unwrap();
+        let mut ids = Vec::with_capacity(reaped.len());
+        for (handle, entry) in reaped {
+            log_error(
+                "StoryStream",
+                &format!(
+                    "LEAK: reaping {} handle {} idle for {}s (created {}s ago) - \
+                     the Java side never released it",
+                    self.kind,
+                    handle,
+                    entry.idle(now).as_secs(),
+                    now.saturating_duration_since(entry.created).as_secs()
+                ),
+            );
+            if let Some(hook) = hook.as_ref() {
+                hook(handle, entry.value);
+            }
+            ids.push(handle);
+        }
+        ids
+    }
+
+    pub(crate) fn kind(&self) -> &'static str {
+        self.kind
+    }
+
+    pub(crate) fn set_capacity(&self, capacity: Option<usize>) {
+        *self.capacity.write().unwrap() = capacity;
+    }
+}
+
 /// Thread-safe handle manager for storing and retrieving typed values
+///
+/// Handles are never reused, so using a handle after it was released is
+/// reported as such instead of reaching another object.
 pub struct HandleManager<T> {
-    handles: std::sync::Arc<std::sync::RwLock<std::collections::HashMap<i64, T>>>,
-    next_handle: std::sync::Arc<std::sync::atomic::AtomicI64>,
+    table: std::sync::Arc<HandleTable<T>>,
 }
 
 impl<T> Default for HandleManager<T> {
     fn default() -> Self {
-        Self {
-            handles: std::sync::Arc::new(std::sync::RwLock::new(std::collections::HashMap::new())),
-            next_handle: std::sync::Arc::new(std::sync::atomic::AtomicI64::new(1)),
-        }
+        Self::with_kind("native")
     }
 }
 
@@ -72,36 +472,107 @@ impl<T> HandleManager<T> {
         Self::default()
     }
 
-    pub fn insert(&self, value: T) -> i64 {
+    fn with_kind(kind: &'static str) -> Self {
+        Self {
+            table: std::sync::Arc::new(HandleTable {
+                kind,
+                handles: std::sync::RwLock::new(std::collections::HashMap::new()),
+                next_handle: std::sync::atomic::AtomicI64::new(1),
+                capacity: std::sync::RwLock::new(None),
+                on_reap: std::sync::RwLock::new(None),
+            }),
+        }
+    }
+
+    /// Creates a manager listed by `nativeDumpHandles` and the idle reaper
+    pub fn named(kind: &'static str) -> Self
+    where
+        T: Send + Sync + 'static,
+    {
+        let manager = Self::with_kind(kind);
+        crate::diagnostics::register(manager.table.clone());
+        manager
+    }
+
+    /// Limits the number of live handles; [`insert`](Self::insert) fails beyond it
+    pub fn with_capacity(self, capacity: usize) -> Self {
+        self.table.set_capacity(Some(capacity));
+        self
+    }
+
+    /// Sets the cleanup run on values removed by the idle reaper
+    pub fn on_reap(self, hook: impl Fn(i64, T) + Send + Sync + 'static) -> Self {
+        *self.table.on_reap.write().unwrap() = Some(Box::new(hook));
+        self
+    }
+
+    /// Stores `value` under a new handle, failing once the capacity is reached
+    pub fn insert(&self, value: T) -> FfiResult<i64> {
+        let mut handles = self.table.handles.write().unwrap();
+        if let Some(capacity) = *self.table.capacity.read().unwrap() {
+            if handles.len() >= capacity {
+                return Err(FfiError::HandleLimit(format!(
+                    "{} handle limit of {} reached; release unused handles first",
+                    self.table.kind, capacity
+                )));
+            }
+        }
+
         let handle = self
+            .table
             .next_handle
             .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
-        self.handles.write().unwrap().insert(handle, value);
-        handle
+        handles.insert(handle, HandleEntry::new(value));
+        Ok(handle)
     }
 
     pub fn get(&self, handle: i64) -> FfiResult<std::sync::Arc<std::sync::RwLock<T>>>
     where
         T: Clone,
     {
-        let handles = self.handles.read().unwrap();
-        handles
+        let handles = self.table.handles.read().unwrap();
+        let entry = handles
             .get(&handle)
-            .cloned()
-            .map(|v| std::sync::Arc::new(std::sync::RwLock::new(v)))
-            .ok_or_else(|| FfiError::InvalidHandle(format!("Handle {} not found", handle)))
+            .ok_or_else(|| self.table.missing(handle))?;
+        entry.touch();
+        Ok(std::sync::Arc::new(std::sync::RwLock::new(
+            entry.value.clone(),
+        )))
     }
 
     pub fn remove(&self, handle: i64) -> FfiResult<T> {
-        self.handles
+        self.table
+            .handles
             .write()
             .unwrap()
             .remove(&handle)
-            .ok_or_else(|| FfiError::InvalidHandle(format!("Handle {} not found", handle)))
+            .map(|entry| entry.value)
+            .ok_or_else(|| self.table.missing(handle))
     }
 
     pub fn contains(&self, handle: i64) -> bool {
-        self.handles.read().unwrap().contains_key(&handle)
+        self.table.handles.read().unwrap().contains_key(&handle)
+    }
+
+    /// Number of live handles
+    pub fn len(&self) -> usize {
+        self.table.handles.read().unwrap().len()
+    }
+
+    /// Whether no handles are live
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reports the live handles, oldest first
+    pub fn stats(&self) -> HandleStats {
+        self.table.stats()
+    }
+
+    /// Removes handles unused for at least `timeout`, returning their values
+    /// to the reap hook, and returns the removed handles
+    pub fn reap_idle(&self, timeout: std::time::Duration) -> Vec<i64> {
+        self.table.reap_idle(timeout)
     }
 }
 
@@ -115,18 +586,12 @@ macro_rules! jni_safe {
         match panic::catch_unwind(panic::AssertUnwindSafe(|| -> FfiResult<_> { $body })) {
             Ok(Ok(result)) => result,
             Ok(Err(e)) => {
-                let _ = $env.throw_new("java/lang/RuntimeException", e.to_string());
+                $crate::ffi::throw_storystream_exception(&mut $env, &e);
                 $default
             }
             Err(panic_err) => {
-                let msg = if let Some(s) = panic_err.downcast_ref::<&str>() {
-                    format!("Panic: {}", s)
-                } else if let Some(s) = panic_err.downcast_ref::<String>() {
-                    format!("Panic: {}", s)
-                } else {
-                    "Unknown panic occurred".to_string()
-                };
-                let _ = $env.throw_new("java/lang/RuntimeException", msg);
+                let e = $crate::ffi::FfiError::from_panic(&*panic_err);
+                $crate::ffi::throw_storystream_exception(&mut $env, &e);
                 $default
             }
         }
@@ -154,6 +619,17 @@ pub fn jstring_raw_to_string(env: &mut JNIEnv, jstr: jstring) -> FfiResult<Strin
     Ok(java_str.to_str()?.to_string())
 }
 
+/// Convert nullable raw jstring to Option<String>
+pub fn jstring_raw_to_option_string(
+    env: &mut JNIEnv<'_>,
+    jstr: jstring,
+) -> FfiResult<Option<String>> {
+    if jstr.is_null() {
+        return Ok(None);
+    }
+    jstring_raw_to_string(env, jstr).map(Some)
+}
+
 /// Convert Rust String to Java string
 pub fn string_to_jstring(env: &mut JNIEnv, s: &str) -> FfiResult<jstring> {
     let jstr = env.new_string(s)?;
@@ -222,7 +698,7 @@ mod tests {
     #[test]
     fn test_handle_manager() {
         let manager = HandleManager::<String>::default();
-        let handle = manager.insert("test".to_string());
+        let handle = manager.insert("test".to_string()).unwrap();
         assert!(handle > 0);
         assert!(manager.get(handle).is_ok());
         assert!(manager.remove(handle).is_ok());
@@ -235,4 +711,213 @@ mod tests {
         assert!(manager.get(999).is_err());
         assert!(manager.remove(999).is_err());
     }
+
+    #[test]
+    fn test_handle_manager_leak_and_double_free() {
+        let manager = HandleManager::<String>::default().with_capacity(2);
+        let first = manager.insert("a".to_string()).unwrap();
+        let _leaked = manager.insert("b".to_string()).unwrap();
+
+        // A leaked handle keeps its slot until released or reaped
+        let err = manager.insert("c".to_string()).unwrap_err();
+        assert_eq!(err.error_code().name, "HANDLE_LIMIT");
+        assert_eq!(manager.stats().count, 2);
+
+        manager.remove(first).unwrap();
+        let err = manager.remove(first).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Invalid Handle: native handle {} was already released",
+                first
+            )
+        );
+        assert!(manager.get(first).is_err());
+        assert!(!manager.contains(first));
+
+        // Releasing frees the slot for a new, never reused, handle
+        let third = manager.insert("c".to_string()).unwrap();
+        assert!(third > first);
+        assert_eq!(manager.len(), 2);
+    }
+
+    /// One value of every `AppError` variant
+    fn every_app_error() -> Vec<AppError> {
+        use std::path::PathBuf;
+
+        let s = || "x".to_string();
+        let p = || PathBuf::from("/x");
+        vec![
+            AppError::NetworkError {
+                message: s(),
+                source: None,
+            },
+            AppError::NetworkTimeout {
+                operation: s(),
+                seconds: 1,
+            },
+            AppError::ConnectionLost { message: s() },
+            AppError::InvalidUrl { url: s() },
+            AppError::DatabaseError {
+                message: s(),
+                source: None,
+            },
+            AppError::DatabaseCorrupted { details: s() },
+            AppError::MigrationFailed {
+                version: s(),
+                reason: s(),
+            },
+            AppError::DatabaseLocked { operation: s() },
+            AppError::DatabaseReadOnly { operation: s() },
+            AppError::RecordNotFound {
+                entity: s(),
+                identifier: s(),
+            },
+            AppError::UnsupportedFormat {
+                format: s(),
+                file: p(),
+            },
+            AppError::AudioDecodeError {
+                message: s(),
+                source: None,
+            },
+            AppError::CorruptedAudioFile {
+                file: p(),
+                reason: s(),
+            },
+            AppError::PlaybackDeviceError { message: s() },
+            AppError::InvalidPosition {
+                position: 2,
+                duration: 1,
+            },
+            AppError::FileNotFound { path: p() },
+            AppError::PermissionDenied {
+                operation: s(),
+                path: p(),
+            },
+            AppError::DiskFull {
+                needed_bytes: 2,
+                available_bytes: 1,
+            },
+            AppError::IoError {
+                message: s(),
+                source: std::io::Error::other("x"),
+            },
+            AppError::MetadataParseError {
+                file: p(),
+                reason: s(),
+            },
+            AppError::InvalidMetadata {
+                field: s(),
+                value: s(),
+            },
+            AppError::MissingMetadata {
+                field: s(),
+                file: p(),
+            },
+            AppError::ContentSourceUnavailable {
+                provider: s(),
+                reason: s(),
+            },
+            AppError::InvalidContentResponse {
+                provider: s(),
+                details: s(),
+            },
+            AppError::ContentNotFound {
+                identifier: s(),
+                provider: s(),
+            },
+            AppError::InvalidConfiguration {
+                setting: s(),
+                value: s(),
+                reason: s(),
+            },
+            AppError::ConfigurationCorrupted { path: p() },
+            AppError::SyncConflict { entity: s() },
+            AppError::SyncAuthFailed { provider: s() },
+            AppError::CacheWriteFailed { reason: s() },
+            AppError::CacheCorrupted {
+                path: p(),
+                reason: s(),
+            },
+            AppError::OutOfMemory { requested_bytes: 1 },
+            AppError::TooManyOpenFiles { limit: 1 },
+            AppError::InternalError { message: s() },
+            AppError::Cancelled { operation: s() },
+            AppError::InvalidArgument {
+                argument: s(),
+                reason: s(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_every_app_error_has_a_unique_code() {
+        let errors = every_app_error();
+        let mut seen = std::collections::HashSet::new();
+        let mut names = std::collections::HashSet::new();
+
+        for err in &errors {
+            let code = app_error_code(err);
+            assert_eq!(code.name, err.code());
+            assert_ne!(code.domain, ErrorDomain::Bridge);
+            assert!(code.code > 0);
+            assert!(
+                seen.insert((code.domain, code.code)),
+                "duplicate code for {}",
+                code.name
+            );
+            names.insert(code.name);
+        }
+
+        // Guards the list above: each variant has its own name
+        assert_eq!(names.len(), errors.len());
+    }
+
+    #[test]
+    fn test_ffi_error_codes() {
+        let errors = [
+            FfiError::JniError("jni".to_string()),
+            FfiError::Utf8Error("utf8".to_string()),
+            FfiError::InvalidHandle("handle".to_string()),
+            FfiError::General("general".to_string()),
+            FfiError::Panic("boom".to_string()),
+            FfiError::HandleLimit("limit".to_string()),
+            FfiError::AlreadyRunning("scan".to_string()),
+        ];
+        let codes: std::collections::HashSet<_> =
+            errors.iter().map(|e| e.error_code().code).collect();
+        assert_eq!(codes.len(), errors.len());
+        assert!(errors
+            .iter()
+            .all(|e| e.error_code().domain == ErrorDomain::Bridge));
+
+        let app = FfiError::from(AppError::InvalidArgument {
+            argument: "limit".to_string(),
+            reason: "negative".to_string(),
+        });
+        let code = app.error_code();
+        assert_eq!((code.domain as i32, code.code), (12, 3));
+        assert_eq!(code.name, "INVALID_ARGUMENT");
+    }
+
+    #[test]
+    fn test_panic_payloads() {
+        let err = FfiError::from_panic(&"static message");
+        assert_eq!(err.to_string(), "Panic: static message");
+
+        let err = FfiError::from_panic(&String::from("owned message"));
+        assert_eq!(err.to_string(), "Panic: owned message");
+
+        let err = FfiError::from_panic(&42);
+        assert_eq!(err.error_code().name, "PANIC");
+    }
+
+    #[test]
+    fn test_exception_class_is_configurable() {
+        assert_eq!(exception_class(), DEFAULT_EXCEPTION_CLASS);
+        set_exception_class("org.example.BridgeException");
+        assert_eq!(exception_class(), "org/example/BridgeException");
+        set_exception_class(DEFAULT_EXCEPTION_CLASS);
+    }
 }
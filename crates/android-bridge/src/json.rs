@@ -0,0 +1,107 @@
+// JSON responses for Android JNI
+//
+// Query functions return a JSON envelope instead of throwing, so the Kotlin
+// layer can deserialize both outcomes with the same adapter:
+//
+//   {"ok": true,  "data": ..., "error": null}
+//   {"ok": false, "data": null,
+//    "error": {"code": "RECORD_NOT_FOUND", "message": "...", "userMessage": "..."}}
+
+use serde::Serialize;
+use storystream_core::AppError;
+
+/// Response wrapper shared by every JSON-returning bridge function
+#[derive(Debug, Serialize)]
+pub struct Envelope<T: Serialize> {
+    /// Whether the call succeeded
+    pub ok: bool,
+    /// Payload on success
+    pub data: Option<T>,
+    /// Failure details
+    pub error: Option<ErrorBody>,
+}
+
+/// Error details carried by a failed [`Envelope`]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorBody {
+    /// Stable machine-readable code, see [`AppError::code`]
+    pub code: &'static str,
+    /// Technical description for logs
+    pub message: String,
+    /// Message suitable for showing to the user
+    pub user_message: String,
+}
+
+impl From<&AppError> for ErrorBody {
+    fn from(error: &AppError) -> Self {
+        Self {
+            code: error.code(),
+            message: error.to_string(),
+            user_message: error.user_message(),
+        }
+    }
+}
+
+/// Serializes `result` into an envelope string
+pub fn envelope<T: Serialize>(result: Result<T, AppError>) -> String {
+    let body = match result {
+        Ok(data) => Envelope {
+            ok: true,
+            data: Some(data),
+            error: None,
+        },
+        Err(error) => {
+            crate::ffi::log_error("StoryStream", &error.to_string());
+            Envelope {
+                ok: false,
+                data: None,
+                error: Some(ErrorBody::from(&error)),
+            }
+        }
+    };
+
+    serde_json::to_string(&body).unwrap_or_else(|e| {
+        // Only reachable if a payload type fails to serialize
+        let internal = AppError::InternalError {
+            message: e.to_string(),
+        };
+        serde_json::json!({
+            "ok": false,
+            "data": null,
+            "error": ErrorBody::from(&internal),
+        })
+        .to_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn test_success_envelope() {
+        let json: Value = serde_json::from_str(&envelope(Ok(vec![1, 2]))).unwrap();
+        assert_eq!(json["ok"], true);
+        assert_eq!(json["data"], serde_json::json!([1, 2]));
+        assert!(json["error"].is_null());
+    }
+
+    #[test]
+    fn test_error_envelope_carries_code() {
+        let result: Result<(), AppError> = Err(AppError::RecordNotFound {
+            entity: "Book".to_string(),
+            identifier: "missing".to_string(),
+        });
+        let json: Value = serde_json::from_str(&envelope(result)).unwrap();
+        assert_eq!(json["ok"], false);
+        assert!(json["data"].is_null());
+        assert_eq!(json["error"]["code"], "RECORD_NOT_FOUND");
+        assert!(json["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("missing"));
+        assert!(json["error"]["userMessage"].is_string());
+    }
+}
@@ -7,12 +7,31 @@
 #![cfg_attr(target_os = "android", allow(dead_code))]
 
 // Module declarations
+/// Bookmarks and listening progress saved through a library handle
+pub mod bookmark_bridge;
+/// Playback events delivered to a Java listener
+pub mod callbacks;
+/// Handle leak diagnostics and the idle reaper
+pub mod diagnostics;
+/// LibriVox downloads with progress callbacks
+pub mod download_bridge;
+/// Error handling, handles and conversions shared by the bridges
 pub mod ffi;
+/// JSON envelopes returned by query functions
+pub mod json;
+/// Library management and queries
 pub mod library_bridge;
+/// Now-playing metadata and artwork for MediaSession
+pub mod media_session;
+/// Playback control
 pub mod player_bridge;
+/// Tokio runtime the bridges block on
+pub mod runtime;
+/// Background library scans with progress
+pub mod scan_bridge;
 
 // Re-export key types for convenience
-pub use ffi::{FfiError, FfiResult, HandleManager};
+pub use ffi::{ErrorCode, ErrorDomain, FfiError, FfiResult, HandleManager};
 
 use jni::{objects::JClass, sys::jstring, JNIEnv};
 use std::panic; // Required for jni_safe! macro
@@ -27,6 +46,8 @@ pub const LIBRARY_NAME: &str = "StoryStream Android Bridge";
 ///
 /// This should be called once when the library is loaded.
 /// On Android, logs will go to logcat. On other platforms, logs go to stderr.
+/// The shared crates log through `tracing`, whose `log` feature is enabled
+/// for Android builds so their events reach logcat as well.
 pub fn init_logging() {
     #[cfg(target_os = "android")]
     {
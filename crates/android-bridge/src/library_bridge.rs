@@ -1,41 +1,281 @@
 // Library management bridge for Android JNI
 //
 // This module provides JNI bindings for audiobook library management including
-// initialization, scanning, and metadata retrieval.
+// initialization, scanning, and metadata retrieval. Query functions return
+// JSON envelopes (see `crate::json`) rather than throwing.
 
 use crate::ffi::{
-    bool_to_jboolean, jstring_raw_to_string, option_string_to_jstring, string_to_jstring, FfiError,
+    bool_to_jboolean, jstring_raw_to_string, option_string_to_jstring, string_to_jstring,
     FfiResult, HandleManager,
 };
-use crate::jni_safe;
+use crate::{jni_safe, json, runtime};
 use jni::{
     objects::JClass,
     sys::{jboolean, jint, jlong, jstring},
     JNIEnv,
 };
+use serde::Serialize;
 use std::panic;
+use std::path::Path;
+use std::sync::Arc;
+use storystream_core::{AppError, Book, BookId, CacheManager};
+use storystream_database::connection::{connect, DatabaseConfig};
+use storystream_database::queries::books::{self, BookSort};
+use storystream_database::{run_migrations, search, DbPool};
 // Required for jni_safe! macro
 
+/// Most libraries open at once
+pub const MAX_LIBRARIES: usize = 8;
+
 /// Global library handle manager
 static LIBRARY_HANDLES: once_cell::sync::Lazy<HandleManager<LibraryContext>> =
-    once_cell::sync::Lazy::new(HandleManager::default);
+    once_cell::sync::Lazy::new(|| HandleManager::named("library").with_capacity(MAX_LIBRARIES));
+
+/// Database file created inside the library root
+const DATABASE_FILE: &str = "storystream.db";
+
+/// Directory created inside the library root for cached files
+const CACHE_DIR: &str = "cache";
+
+/// Disk space the library's caches may use together
+const CACHE_MAX_BYTES: u64 = 500 * 1024 * 1024;
+
+/// Largest page `nativeListBooks` returns
+const MAX_PAGE_SIZE: i32 = 500;
+
+/// Maximum number of search results
+const SEARCH_LIMIT: i64 = 50;
 
 /// Library context holding state for a library instance
 #[derive(Clone)]
 struct LibraryContext {
     root_path: String,
     initialized: bool,
+    pool: DbPool,
+    cache: Arc<CacheManager>,
 }
 
 impl LibraryContext {
-    fn new(root_path: String) -> Self {
+    fn new(root_path: String, pool: DbPool, cache: CacheManager) -> Self {
         Self {
             root_path,
             initialized: true,
+            pool,
+            cache: Arc::new(cache),
+        }
+    }
+}
+
+/// Opens (creating if needed) the library database under `root_path`
+async fn open_database(root_path: &str) -> Result<DbPool, AppError> {
+    std::fs::create_dir_all(root_path)?;
+    let path = Path::new(root_path).join(DATABASE_FILE);
+    let pool = connect(DatabaseConfig::new(path.to_string_lossy()).with_max_connections(4)).await?;
+    run_migrations(&pool).await?;
+    Ok(pool)
+}
+
+/// Opens the library rooted at `path` and returns its handle
+pub(crate) fn open_library(path: &str) -> FfiResult<i64> {
+    // Validate path
+    if path.is_empty() {
+        return Err(AppError::InvalidArgument {
+            argument: "libraryRootPath".to_string(),
+            reason: "Library root path cannot be empty".to_string(),
+        }
+        .into());
+    }
+
+    let pool = runtime::block_on(open_database(path))?;
+    let cache = CacheManager::open(Path::new(path).join(CACHE_DIR), CACHE_MAX_BYTES)?;
+    let context = LibraryContext::new(path.to_string(), pool, cache);
+    let handle = LIBRARY_HANDLES.insert(context)?;
+
+    crate::ffi::log_info(
+        "StoryStream",
+        &format!("Initialized library at: {} (handle: {})", path, handle),
+    );
+
+    Ok(handle)
+}
+
+/// Returns the database pool behind a library handle
+pub(crate) fn library_pool(handle: i64) -> Result<DbPool, AppError> {
+    let context = LIBRARY_HANDLES
+        .get(handle)
+        .map_err(|e| AppError::InvalidArgument {
+            argument: "handle".to_string(),
+            reason: e.to_string(),
+        })?;
+    let pool = context.read().unwrap().pool.clone();
+    Ok(pool)
+}
+
+/// Returns the caches of a library handle
+pub(crate) fn library_cache(handle: i64) -> Result<Arc<CacheManager>, AppError> {
+    let context = LIBRARY_HANDLES
+        .get(handle)
+        .map_err(|e| AppError::InvalidArgument {
+            argument: "handle".to_string(),
+            reason: e.to_string(),
+        })?;
+    let cache = context.read().unwrap().cache.clone();
+    Ok(cache)
+}
+
+/// Book as serialized for the Java layer
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BookJson {
+    id: String,
+    title: String,
+    author: Option<String>,
+    narrator: Option<String>,
+    series: Option<String>,
+    series_position: Option<f32>,
+    genre: Option<String>,
+    description: Option<String>,
+    duration_ms: u64,
+    file_path: String,
+    cover_art_path: Option<String>,
+    added_date: i64,
+    last_played: Option<i64>,
+    play_count: u32,
+    favorite: bool,
+    rating: Option<u8>,
+    tags: Vec<String>,
+}
+
+impl From<&Book> for BookJson {
+    fn from(book: &Book) -> Self {
+        Self {
+            id: book.id.as_string(),
+            title: book.title.clone(),
+            author: book.author.clone(),
+            narrator: book.narrator.clone(),
+            series: book.series.clone(),
+            series_position: book.series_position,
+            genre: book.genre.clone(),
+            description: book.description.clone(),
+            duration_ms: book.duration.as_millis(),
+            file_path: book.file_path.display().to_string(),
+            cover_art_path: book
+                .cover_art_path
+                .as_ref()
+                .map(|p| p.display().to_string()),
+            added_date: book.added_date.as_millis(),
+            last_played: book.last_played.map(|t| t.as_millis()),
+            play_count: book.play_count,
+            favorite: book.is_favorite,
+            rating: book.rating,
+            tags: book.tags.clone(),
         }
     }
 }
 
+/// One page of `nativeListBooks`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BookPage {
+    page: i32,
+    page_size: i32,
+    total: i64,
+    books: Vec<BookJson>,
+}
+
+/// Entry of `nativeContinueListening`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ContinueListeningJson {
+    #[serde(flatten)]
+    book: BookJson,
+    position_ms: u64,
+    /// Fraction of the book listened to, from 0.0 to 1.0
+    progress: f64,
+}
+
+async fn list_books_page(
+    pool: &DbPool,
+    page: i32,
+    page_size: i32,
+    sort: &str,
+) -> Result<BookPage, AppError> {
+    if page < 0 {
+        return Err(AppError::InvalidArgument {
+            argument: "page".to_string(),
+            reason: "must be non-negative".to_string(),
+        });
+    }
+    if !(1..=MAX_PAGE_SIZE).contains(&page_size) {
+        return Err(AppError::InvalidArgument {
+            argument: "pageSize".to_string(),
+            reason: format!("must be between 1 and {}", MAX_PAGE_SIZE),
+        });
+    }
+
+    let sort = BookSort::parse(sort)?;
+    let offset = i64::from(page) * i64::from(page_size);
+    let found = books::list_books_paged(pool, offset, i64::from(page_size), sort).await?;
+
+    Ok(BookPage {
+        page,
+        page_size,
+        total: found.total,
+        books: found.books.iter().map(BookJson::from).collect(),
+    })
+}
+
+async fn search_books(pool: &DbPool, query: &str) -> Result<Vec<BookJson>, AppError> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Err(AppError::InvalidArgument {
+            argument: "query".to_string(),
+            reason: "cannot be empty".to_string(),
+        });
+    }
+
+    let hits = search::search_books(pool, query, SEARCH_LIMIT).await?;
+    Ok(hits.iter().map(|hit| BookJson::from(&hit.item)).collect())
+}
+
+async fn get_book(pool: &DbPool, id: &str) -> Result<BookJson, AppError> {
+    let id = BookId::from_string(id).map_err(|e| AppError::InvalidArgument {
+        argument: "id".to_string(),
+        reason: e.to_string(),
+    })?;
+    Ok(BookJson::from(&books::get_book(pool, id).await?))
+}
+
+async fn continue_listening(
+    pool: &DbPool,
+    limit: i32,
+) -> Result<Vec<ContinueListeningJson>, AppError> {
+    if limit <= 0 {
+        return Err(AppError::InvalidArgument {
+            argument: "limit".to_string(),
+            reason: "must be positive".to_string(),
+        });
+    }
+
+    let in_progress = books::get_in_progress_books(pool, i64::from(limit)).await?;
+    Ok(in_progress
+        .iter()
+        .map(|entry| {
+            let duration = entry.book.duration.as_millis();
+            let position = entry.position.as_millis();
+            ContinueListeningJson {
+                book: BookJson::from(&entry.book),
+                position_ms: position,
+                progress: if duration == 0 {
+                    0.0
+                } else {
+                    (position as f64 / duration as f64).min(1.0)
+                },
+            }
+        })
+        .collect())
+}
+
 /// Initialize a new library instance
 ///
 /// # Safety
@@ -48,23 +288,7 @@ pub extern "C" fn Java_com_storystream_StoryStreamLibrary_nativeInitialize(
 ) -> jlong {
     jni_safe!(env, 0, {
         let path = jstring_raw_to_string(&mut env, library_root_path)?;
-
-        // Validate path
-        if path.is_empty() {
-            return Err(FfiError::General(
-                "Library root path cannot be empty".to_string(),
-            ));
-        }
-
-        let context = LibraryContext::new(path.clone());
-        let handle = LIBRARY_HANDLES.insert(context);
-
-        crate::ffi::log_info(
-            "StoryStream",
-            &format!("Initialized library at: {} (handle: {})", path, handle),
-        );
-
-        Ok(handle)
+        open_library(&path)
     })
 }
 
@@ -300,7 +524,28 @@ pub extern "C" fn Java_com_storystream_StoryStreamLibrary_nativeGetBookCoverArt(
     })
 }
 
-/// Search library with query string
+/// List one page of books (JSON envelope with a `BookPage`)
+///
+/// `page` is zero-based; `sort` is one of `title`, `author`, `added`,
+/// `last_played` or `duration`, reversed by a leading `-`.
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamLibrary_nativeListBooks(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    handle: jlong,
+    page: jint,
+    page_size: jint,
+    sort: jstring,
+) -> jstring {
+    jni_safe!(env, std::ptr::null_mut(), {
+        let sort = jstring_raw_to_string(&mut env, sort)?;
+        let result = library_pool(handle)
+            .and_then(|pool| runtime::block_on(list_books_page(&pool, page, page_size, &sort)));
+        string_to_jstring(&mut env, &json::envelope(result))
+    })
+}
+
+/// Search library with query string (JSON envelope with a book array)
 #[no_mangle]
 pub extern "C" fn Java_com_storystream_StoryStreamLibrary_nativeSearchBooks(
     mut env: JNIEnv,
@@ -309,13 +554,43 @@ pub extern "C" fn Java_com_storystream_StoryStreamLibrary_nativeSearchBooks(
     query: jstring,
 ) -> jstring {
     jni_safe!(env, std::ptr::null_mut(), {
-        LIBRARY_HANDLES.get(handle)?;
         let query_str = jstring_raw_to_string(&mut env, query)?;
 
         crate::ffi::log_info("StoryStream", &format!("Searching library: {}", query_str));
 
-        // Placeholder: Would search database and return JSON array
-        string_to_jstring(&mut env, "[]")
+        let result = library_pool(handle)
+            .and_then(|pool| runtime::block_on(search_books(&pool, &query_str)));
+        string_to_jstring(&mut env, &json::envelope(result))
+    })
+}
+
+/// Get a single book by ID (JSON envelope with a book)
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamLibrary_nativeGetBook(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    handle: jlong,
+    book_id: jstring,
+) -> jstring {
+    jni_safe!(env, std::ptr::null_mut(), {
+        let id = jstring_raw_to_string(&mut env, book_id)?;
+        let result = library_pool(handle).and_then(|pool| runtime::block_on(get_book(&pool, &id)));
+        string_to_jstring(&mut env, &json::envelope(result))
+    })
+}
+
+/// Get started, unfinished books, most recent first (JSON envelope)
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamLibrary_nativeContinueListening(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    handle: jlong,
+    limit: jint,
+) -> jstring {
+    jni_safe!(env, std::ptr::null_mut(), {
+        let result = library_pool(handle)
+            .and_then(|pool| runtime::block_on(continue_listening(&pool, limit)));
+        string_to_jstring(&mut env, &json::envelope(result))
     })
 }
 
@@ -365,7 +640,11 @@ pub extern "C" fn Java_com_storystream_StoryStreamLibrary_nativeGetRecentBooks(
         LIBRARY_HANDLES.get(handle)?;
 
         if limit < 0 {
-            return Err(FfiError::General("Limit must be non-negative".to_string()));
+            return Err(AppError::InvalidArgument {
+                argument: "limit".to_string(),
+                reason: "Limit must be non-negative".to_string(),
+            }
+            .into());
         }
 
         crate::ffi::log_info("StoryStream", &format!("Getting {} recent books", limit));
@@ -400,18 +679,47 @@ pub extern "C" fn Java_com_storystream_StoryStreamLibrary_nativeUpdateBookMetada
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::Value;
+    use std::path::PathBuf;
+    use storystream_core::{Duration, PlaybackState};
+    use storystream_database::queries::playback::create_playback_state;
+
+    fn test_pool(dir: &tempfile::TempDir) -> DbPool {
+        runtime::block_on(open_database(dir.path().to_str().unwrap())).unwrap()
+    }
+
+    fn test_cache(dir: &tempfile::TempDir) -> CacheManager {
+        CacheManager::open(dir.path().join(CACHE_DIR), CACHE_MAX_BYTES).unwrap()
+    }
+
+    fn add_book(pool: &DbPool, title: &str) -> Book {
+        let book = Book::new(
+            title.to_string(),
+            PathBuf::from(format!("/books/{}.m4b", title)),
+            1_000,
+            Duration::from_seconds(1000),
+        );
+        runtime::block_on(books::create_book(pool, &book)).unwrap();
+        book
+    }
+
+    fn to_json<T: Serialize>(result: Result<T, AppError>) -> Value {
+        serde_json::from_str(&json::envelope(result)).unwrap()
+    }
 
     #[test]
     fn test_library_context_creation() {
-        let ctx = LibraryContext::new("/test/path".to_string());
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = LibraryContext::new("/test/path".to_string(), test_pool(&dir), test_cache(&dir));
         assert_eq!(ctx.root_path, "/test/path");
         assert!(ctx.initialized);
     }
 
     #[test]
     fn test_handle_lifecycle() {
-        let ctx = LibraryContext::new("/test".to_string());
-        let handle = LIBRARY_HANDLES.insert(ctx.clone());
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = LibraryContext::new("/test".to_string(), test_pool(&dir), test_cache(&dir));
+        let handle = LIBRARY_HANDLES.insert(ctx.clone()).unwrap();
         assert!(handle > 0);
 
         let retrieved = LIBRARY_HANDLES.get(handle);
@@ -423,4 +731,86 @@ mod tests {
         let not_found = LIBRARY_HANDLES.get(handle);
         assert!(not_found.is_err());
     }
+
+    #[test]
+    fn test_list_books_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = test_pool(&dir);
+        for title in ["Gamma", "Alpha", "Beta"] {
+            add_book(&pool, title);
+        }
+
+        let json = to_json(runtime::block_on(list_books_page(&pool, 1, 2, "title")));
+        assert_eq!(json["ok"], true);
+        assert_eq!(json["data"]["total"], 3);
+        assert_eq!(json["data"]["pageSize"], 2);
+        assert_eq!(json["data"]["books"][0]["title"], "Gamma");
+        assert_eq!(json["data"]["books"][0]["durationMs"], 1_000_000);
+
+        let json = to_json(runtime::block_on(list_books_page(&pool, 0, 0, "title")));
+        assert_eq!(json["error"]["code"], "INVALID_ARGUMENT");
+        let json = to_json(runtime::block_on(list_books_page(&pool, 0, 10, "size")));
+        assert_eq!(json["error"]["code"], "INVALID_ARGUMENT");
+    }
+
+    #[test]
+    fn test_get_book_errors_use_app_error_codes() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = test_pool(&dir);
+        let book = add_book(&pool, "Dune");
+
+        let json = to_json(runtime::block_on(get_book(&pool, &book.id.as_string())));
+        assert_eq!(json["data"]["title"], "Dune");
+
+        let json = to_json(runtime::block_on(get_book(&pool, "not-a-uuid")));
+        assert_eq!(json["error"]["code"], "INVALID_ARGUMENT");
+
+        let missing = BookId::new().as_string();
+        let json = to_json(runtime::block_on(get_book(&pool, &missing)));
+        assert_eq!(json["error"]["code"], "RECORD_NOT_FOUND");
+    }
+
+    #[test]
+    fn test_search_books() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = test_pool(&dir);
+        add_book(&pool, "Foundation");
+        add_book(&pool, "Hyperion");
+
+        let json = to_json(runtime::block_on(search_books(&pool, "Hyperion")));
+        assert_eq!(json["data"].as_array().unwrap().len(), 1);
+        assert_eq!(json["data"][0]["title"], "Hyperion");
+
+        let json = to_json(runtime::block_on(search_books(&pool, "  ")));
+        assert_eq!(json["error"]["code"], "INVALID_ARGUMENT");
+    }
+
+    #[test]
+    fn test_continue_listening_reports_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = test_pool(&dir);
+        let book = add_book(&pool, "Emma");
+        add_book(&pool, "Persuasion");
+
+        let mut state = PlaybackState::new(book.id);
+        state.position = Duration::from_seconds(250);
+        runtime::block_on(create_playback_state(&pool, &state)).unwrap();
+
+        let json = to_json(runtime::block_on(continue_listening(&pool, 5)));
+        let entries = json["data"].as_array().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["title"], "Emma");
+        assert_eq!(entries[0]["positionMs"], 250_000);
+        assert_eq!(entries[0]["progress"], 0.25);
+
+        let json = to_json(runtime::block_on(continue_listening(&pool, 0)));
+        assert_eq!(json["error"]["code"], "INVALID_ARGUMENT");
+    }
+
+    #[test]
+    fn test_unknown_handle_is_an_error_envelope() {
+        let json = to_json(library_pool(-42).map(|_| ()));
+        assert_eq!(json["ok"], false);
+        assert_eq!(json["error"]["code"], "INVALID_ARGUMENT");
+    }
 }
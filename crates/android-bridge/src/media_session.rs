@@ -0,0 +1,529 @@
+// MediaSession metadata bridge for Android JNI
+//
+// The notification and lock screen show what is playing: title, author,
+// current chapter, duration, position and artwork. `nativeLoadBook` ties a
+// player to a library book, and `nativeGetNowPlaying` assembles the metadata
+// from that book and the player's state. Artwork is scaled once and cached,
+// in memory for `nativeGetCoverArt` and on disk for the artwork path, since
+// the notification is refreshed every few seconds.
+
+use crate::ffi::{bool_to_jboolean, jstring_raw_to_string, string_to_jstring, FfiResult};
+use crate::library_bridge::{library_cache, library_pool};
+use crate::player_bridge::player;
+use crate::{jni_safe, json, runtime};
+use jni::{
+    objects::JClass,
+    sys::{jboolean, jbyteArray, jint, jlong, jstring},
+    JNIEnv,
+};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::panic; // Required for jni_safe! macro
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::SystemTime;
+use storystream_core::{AppError, Book, BookId, CacheManager, Chapter, CoverArt};
+use storystream_database::queries::{books, chapters};
+
+/// Size of the artwork written for the notification
+pub const NOTIFICATION_ART_PX: u32 = 512;
+
+/// Largest `maxPx` accepted by `nativeGetCoverArt`
+pub const MAX_COVER_PX: i32 = 2048;
+
+/// Scaled covers kept in memory
+const COVER_CACHE_SIZE: usize = 16;
+
+/// Cache namespace holding scaled artwork
+const COVER_NAMESPACE: &str = "covers";
+
+/// The book a player was loaded with through `nativeLoadBook`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Session {
+    player: i64,
+    library: i64,
+    book: BookId,
+}
+
+static NOW_PLAYING: Lazy<RwLock<Option<Session>>> = Lazy::new(|| RwLock::new(None));
+
+static COVERS: Lazy<CoverCache> = Lazy::new(|| CoverCache::new(COVER_CACHE_SIZE));
+
+/// Forgets the session of a released player
+pub(crate) fn forget_player(handle: i64) {
+    let mut session = NOW_PLAYING.write().unwrap();
+    if session.is_some_and(|s| s.player == handle) {
+        *session = None;
+    }
+}
+
+/// Chapter as shown on the lock screen
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ChapterJson {
+    index: u32,
+    title: String,
+    start_ms: u64,
+    end_ms: u64,
+}
+
+impl From<&Chapter> for ChapterJson {
+    fn from(chapter: &Chapter) -> Self {
+        Self {
+            index: chapter.index,
+            title: chapter.title.clone(),
+            start_ms: chapter.start_time.as_millis(),
+            end_ms: chapter.end_time.as_millis(),
+        }
+    }
+}
+
+/// Metadata for the MediaSession
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NowPlayingJson {
+    book_id: String,
+    title: String,
+    author: Option<String>,
+    narrator: Option<String>,
+    chapter: Option<ChapterJson>,
+    chapter_count: usize,
+    duration_ms: u64,
+    position_ms: u64,
+    playing: bool,
+    speed: f64,
+    /// Scaled cover on disk, `None` if the book has no artwork
+    artwork_path: Option<String>,
+}
+
+/// Scaled covers keyed by source file and size, oldest evicted first
+struct CoverCache {
+    capacity: usize,
+    entries: Mutex<VecDeque<CachedCover>>,
+}
+
+struct CachedCover {
+    source: PathBuf,
+    max_px: u32,
+    modified: Option<SystemTime>,
+    art: Arc<CoverArt>,
+}
+
+impl CoverCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// Returns `source` scaled to `max_px`, or `None` if it has no usable art
+    fn get(&self, source: &Path, max_px: u32) -> Option<Arc<CoverArt>> {
+        // A missing file is the common "no artwork" case, not an error
+        let modified = std::fs::metadata(source).ok()?.modified().ok();
+
+        {
+            let entries = self.entries.lock().unwrap();
+            let hit = entries.iter().find(|entry| {
+                entry.source == source && entry.max_px == max_px && entry.modified == modified
+            });
+            if let Some(entry) = hit {
+                return Some(Arc::clone(&entry.art));
+            }
+        }
+
+        let art = Arc::new(scale(source, max_px)?);
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|entry| !(entry.source == source && entry.max_px == max_px));
+        if entries.len() >= self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(CachedCover {
+            source: source.to_path_buf(),
+            max_px,
+            modified,
+            art: Arc::clone(&art),
+        });
+        Some(art)
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+}
+
+/// Reads and scales a cover, logging why unusable artwork is skipped
+fn scale(source: &Path, max_px: u32) -> Option<CoverArt> {
+    let data = std::fs::read(source).ok()?;
+    let Some(art) = CoverArt::from_bytes(data) else {
+        crate::ffi::log_error(
+            "StoryStream",
+            &format!("Cover art is not an image: {}", source.display()),
+        );
+        return None;
+    };
+
+    match art.thumbnail(max_px) {
+        Ok(scaled) => Some(scaled),
+        Err(e) => {
+            crate::ffi::log_error(
+                "StoryStream",
+                &format!("Cannot scale cover art {}: {}", source.display(), e),
+            );
+            None
+        }
+    }
+}
+
+fn cover(book: &Book, max_px: u32) -> Option<Arc<CoverArt>> {
+    COVERS.get(book.cover_art_path.as_deref()?, max_px)
+}
+
+/// Writes the notification-sized cover to `cache` and returns its path
+///
+/// An existing file is reused unless the source artwork changed since.
+fn artwork_file(cache: &CacheManager, book: &Book) -> Option<PathBuf> {
+    let source = book.cover_art_path.as_deref()?;
+    let source_modified = std::fs::metadata(source).ok()?.modified().ok();
+    let stem = format!("{}-{}", book.id.as_string(), NOTIFICATION_ART_PX);
+
+    for ext in ["jpg", "png"] {
+        let Some(path) = cache.get(COVER_NAMESPACE, &format!("{}.{}", stem, ext)) else {
+            continue;
+        };
+        let cached = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+        if cached.is_some() && cached >= source_modified {
+            return Some(path);
+        }
+    }
+
+    let art = cover(book, NOTIFICATION_ART_PX)?;
+    let ext = if art.mime_type == "image/png" {
+        "png"
+    } else {
+        "jpg"
+    };
+    match cache.put(COVER_NAMESPACE, &format!("{}.{}", stem, ext), &art.data) {
+        Ok(path) => Some(path),
+        Err(e) => {
+            crate::ffi::log_error("StoryStream", &format!("Cannot cache cover art: {}", e));
+            None
+        }
+    }
+}
+
+fn parse_book_id(id: &str) -> Result<BookId, AppError> {
+    BookId::from_string(id.trim()).map_err(|e| AppError::InvalidArgument {
+        argument: "bookId".to_string(),
+        reason: e.to_string(),
+    })
+}
+
+/// Loads a library book into a player and makes it the MediaSession's book
+fn load_book(player_handle: i64, library: i64, book_id: &str) -> FfiResult<()> {
+    let book_id = parse_book_id(book_id)?;
+    let pool = library_pool(library)?;
+    let book = runtime::block_on(books::get_book(&pool, book_id))?;
+    let player = player(player_handle)?;
+
+    player
+        .load(&book.file_path.to_string_lossy())
+        .map_err(|e| AppError::AudioDecodeError {
+            message: format!("Failed to load '{}': {}", book.title, e),
+            source: None,
+        })?;
+
+    *NOW_PLAYING.write().unwrap() = Some(Session {
+        player: player_handle,
+        library,
+        book: book.id,
+    });
+    crate::ffi::log_info("StoryStream", &format!("Now playing: {}", book.title));
+    Ok(())
+}
+
+/// Assembles the MediaSession metadata, `None` when nothing is loaded
+async fn now_playing() -> Result<Option<NowPlayingJson>, AppError> {
+    let Some(session) = *NOW_PLAYING.read().unwrap() else {
+        return Ok(None);
+    };
+    let Ok(player) = player(session.player) else {
+        forget_player(session.player);
+        return Ok(None);
+    };
+
+    let pool = library_pool(session.library)?;
+    let book = books::get_book(&pool, session.book).await?;
+    let chapters = chapters::get_book_chapters(&pool, book.id).await?;
+
+    let position = player.position();
+    let position_ms = position.as_millis() as u64;
+    let chapter = chapters
+        .iter()
+        .find(|c| c.start_time.as_millis() <= position_ms && position_ms < c.end_time.as_millis());
+
+    // The engine knows the real length once the file is open
+    let duration_ms = match player.duration().as_millis() as u64 {
+        0 => book.duration.as_millis(),
+        engine => engine,
+    };
+
+    Ok(Some(NowPlayingJson {
+        book_id: book.id.as_string(),
+        title: book.title.clone(),
+        author: book.author.clone(),
+        narrator: book.narrator.clone(),
+        chapter: chapter.map(ChapterJson::from),
+        chapter_count: chapters.len(),
+        duration_ms,
+        position_ms,
+        playing: player.is_playing(),
+        speed: player.speed(),
+        artwork_path: library_cache(session.library)
+            .ok()
+            .and_then(|cache| artwork_file(&cache, &book))
+            .map(|p| p.display().to_string()),
+    }))
+}
+
+/// Returns a book's cover scaled to fit `max_px`, `None` if it has none
+async fn cover_art(
+    pool: &storystream_database::DbPool,
+    book_id: &str,
+    max_px: i32,
+) -> Result<Option<Arc<CoverArt>>, AppError> {
+    if !(1..=MAX_COVER_PX).contains(&max_px) {
+        return Err(AppError::InvalidArgument {
+            argument: "maxPx".to_string(),
+            reason: format!("must be between 1 and {}", MAX_COVER_PX),
+        });
+    }
+
+    let book = books::get_book(pool, parse_book_id(book_id)?).await?;
+    Ok(cover(&book, max_px as u32))
+}
+
+/// Load a library book into a player for playback and the MediaSession
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamPlayer_nativeLoadBook(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    handle: jlong,
+    library_handle: jlong,
+    book_id: jstring,
+) -> jboolean {
+    jni_safe!(env, bool_to_jboolean(false), {
+        let book_id = jstring_raw_to_string(&mut env, book_id)?;
+        load_book(handle, library_handle, &book_id)?;
+        Ok(bool_to_jboolean(true))
+    })
+}
+
+/// Get MediaSession metadata for the loaded book (JSON envelope)
+///
+/// The data is null when no book is loaded.
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamPlayer_nativeGetNowPlaying(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+) -> jstring {
+    jni_safe!(env, std::ptr::null_mut(), {
+        let result = runtime::block_on(now_playing());
+        string_to_jstring(&mut env, &json::envelope(result))
+    })
+}
+
+/// Get a book's cover as JPEG or PNG bytes scaled to fit `maxPx`
+///
+/// Returns null if the book has no usable artwork.
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamLibrary_nativeGetCoverArt(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    handle: jlong,
+    book_id: jstring,
+    max_px: jint,
+) -> jbyteArray {
+    jni_safe!(env, std::ptr::null_mut(), {
+        let book_id = jstring_raw_to_string(&mut env, book_id)?;
+        let pool = library_pool(handle)?;
+        match runtime::block_on(cover_art(&pool, &book_id, max_px))? {
+            Some(art) => Ok(env.byte_array_from_slice(&art.data)?.into_raw()),
+            None => Ok(std::ptr::null_mut()),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use storystream_core::Duration;
+    use storystream_database::DbPool;
+
+    fn setup() -> (tempfile::TempDir, DbPool) {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = runtime::block_on(async {
+            let pool = storystream_database::connection::connect(
+                storystream_database::connection::DatabaseConfig::new(
+                    dir.path().join("test.db").to_string_lossy(),
+                ),
+            )
+            .await
+            .unwrap();
+            storystream_database::run_migrations(&pool).await.unwrap();
+            pool
+        });
+        (dir, pool)
+    }
+
+    fn write_png(path: &Path, width: u32, height: u32) {
+        image::RgbImage::from_pixel(width, height, image::Rgb([10, 120, 200]))
+            .save(path)
+            .unwrap();
+    }
+
+    fn book_with_cover(pool: &DbPool, cover: Option<PathBuf>) -> Book {
+        let mut book = Book::new(
+            "The Warden".to_string(),
+            PathBuf::from(format!("/books/{}.m4b", BookId::new().as_string())),
+            1_000,
+            Duration::from_seconds(600),
+        );
+        book.cover_art_path = cover;
+        runtime::block_on(books::create_book(pool, &book)).unwrap();
+        book
+    }
+
+    #[test]
+    fn test_cover_art_is_scaled_and_cached() {
+        let (dir, pool) = setup();
+        let source = dir.path().join("cover.png");
+        write_png(&source, 300, 150);
+        let book = book_with_cover(&pool, Some(source.clone()));
+        let id = book.id.as_string();
+
+        let art = runtime::block_on(cover_art(&pool, &id, 100))
+            .unwrap()
+            .unwrap();
+        let decoded = image::load_from_memory(&art.data).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (100, 50));
+
+        // A repeated request is served from the cache
+        let again = runtime::block_on(cover_art(&pool, &id, 100))
+            .unwrap()
+            .unwrap();
+        assert!(Arc::ptr_eq(&art, &again));
+    }
+
+    #[test]
+    fn test_absent_cover_art_is_none() {
+        let (dir, pool) = setup();
+        let none = book_with_cover(&pool, None);
+        let missing = book_with_cover(&pool, Some(dir.path().join("missing.jpg")));
+        let garbage_path = dir.path().join("garbage.jpg");
+        std::fs::write(&garbage_path, b"not an image").unwrap();
+        let garbage = book_with_cover(&pool, Some(garbage_path));
+
+        for book in [none, missing, garbage] {
+            let art = runtime::block_on(cover_art(&pool, &book.id.as_string(), 64)).unwrap();
+            assert!(art.is_none());
+        }
+
+        let err = runtime::block_on(cover_art(&pool, "nope", 64)).unwrap_err();
+        assert_eq!(err.code(), "INVALID_ARGUMENT");
+        let err = runtime::block_on(cover_art(&pool, &BookId::new().as_string(), 0)).unwrap_err();
+        assert_eq!(err.code(), "INVALID_ARGUMENT");
+    }
+
+    #[test]
+    fn test_cover_cache_evicts_oldest() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = CoverCache::new(2);
+        let paths: Vec<PathBuf> = (0..3)
+            .map(|i| {
+                let path = dir.path().join(format!("{}.png", i));
+                write_png(&path, 20, 20);
+                path
+            })
+            .collect();
+
+        let first = cache.get(&paths[0], 10).unwrap();
+        cache.get(&paths[1], 10).unwrap();
+        cache.get(&paths[2], 10).unwrap();
+        assert_eq!(cache.len(), 2);
+
+        // Evicted, so scaled again into a new allocation
+        let reloaded = cache.get(&paths[0], 10).unwrap();
+        assert!(!Arc::ptr_eq(&first, &reloaded));
+    }
+
+    #[test]
+    fn test_now_playing_follows_the_loaded_book() {
+        let dir = tempfile::tempdir().unwrap();
+        let library = crate::library_bridge::open_library(&dir.path().to_string_lossy()).unwrap();
+        let pool = library_pool(library).unwrap();
+
+        let source = dir.path().join("cover.png");
+        write_png(&source, 64, 64);
+        let mut book = book_with_cover(&pool, Some(source));
+        book.author = Some("Anthony Trollope".to_string());
+        runtime::block_on(books::update_book(&pool, &book)).unwrap();
+        let chapter = Chapter::new(
+            book.id,
+            "Hiram's Hospital".to_string(),
+            0,
+            Duration::from_seconds(0),
+            Duration::from_seconds(300),
+        );
+        runtime::block_on(chapters::create_chapter(&pool, &chapter)).unwrap();
+
+        let handle = crate::player_bridge::create_player().unwrap();
+        load_book(handle, library, &book.id.as_string()).unwrap();
+        player(handle)
+            .unwrap()
+            .seek(std::time::Duration::from_secs(90))
+            .unwrap();
+
+        let now = runtime::block_on(now_playing()).unwrap().unwrap();
+        assert_eq!(now.title, "The Warden");
+        assert_eq!(now.author.as_deref(), Some("Anthony Trollope"));
+        assert_eq!(now.position_ms, 90_000);
+        assert_eq!(now.chapter.as_ref().unwrap().title, "Hiram's Hospital");
+        assert_eq!(now.chapter_count, 1);
+        assert!(now.artwork_path.is_some());
+
+        let body = json::envelope(Ok(now));
+        assert!(body.contains(r#""artworkPath""#));
+        assert!(body.contains(r#""chapter":{"index":0"#));
+
+        // Releasing the player ends the session
+        forget_player(handle);
+        assert!(runtime::block_on(now_playing()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_artwork_file_is_written_once() {
+        let (dir, pool) = setup();
+        let source = dir.path().join("cover.png");
+        write_png(&source, 1024, 1024);
+        let book = book_with_cover(&pool, Some(source));
+
+        let cache = CacheManager::open(dir.path().join("cache"), u64::MAX).unwrap();
+        let path = artwork_file(&cache, &book).unwrap();
+        assert!(path.starts_with(cache.root().join(COVER_NAMESPACE)));
+        let decoded = image::open(&path).unwrap();
+        assert_eq!(decoded.width(), NOTIFICATION_ART_PX);
+
+        let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+        assert_eq!(artwork_file(&cache, &book).unwrap(), path);
+        assert_eq!(
+            std::fs::metadata(&path).unwrap().modified().unwrap(),
+            modified
+        );
+
+        let bare = book_with_cover(&pool, None);
+        assert!(artwork_file(&cache, &bare).is_none());
+    }
+}
@@ -3,16 +3,21 @@
 // This module provides JNI bindings for audio playback control including
 // play, pause, seek, and state management.
 
+use crate::callbacks::{
+    AudioFocus, JavaPlaybackListener, ListenerRegistry, PlaybackEvent, PlaybackStatus,
+};
 use crate::ffi::{bool_to_jboolean, jstring_raw_to_string, FfiError, FfiResult, HandleManager};
 use jni::{
-    objects::JClass,
+    objects::{JClass, JObject},
     sys::{jboolean, jdouble, jint, jlong, jstring},
     JNIEnv,
 };
 use once_cell::sync::Lazy;
 use std::panic; // Required for jni_safe! macro
-use std::sync::{Arc, RwLock};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::Duration;
+use storystream_core::AppError;
 
 // Import audio player from media-engine if available
 #[cfg(feature = "media-engine")]
@@ -26,6 +31,13 @@ pub struct AudioPlayer {
     is_playing: Arc<RwLock<bool>>,
     speed: Arc<RwLock<f64>>,
     volume: Arc<RwLock<f64>>,
+    /// Whether a sound playing over us has the volume lowered
+    ducked: RwLock<bool>,
+    /// Whether a transient focus loss paused playback
+    resume_on_gain: RwLock<bool>,
+    /// Whether playback resumes once focus is back
+    auto_resume: RwLock<bool>,
+    subscribers: Mutex<Vec<Sender<PlaybackEvent>>>,
 }
 
 #[cfg(not(feature = "media-engine"))]
@@ -37,27 +49,63 @@ impl AudioPlayer {
             is_playing: Arc::new(RwLock::new(false)),
             speed: Arc::new(RwLock::new(1.0)),
             volume: Arc::new(RwLock::new(1.0)),
+            ducked: RwLock::new(false),
+            resume_on_gain: RwLock::new(false),
+            auto_resume: RwLock::new(true),
+            subscribers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns a channel that receives this player's events
+    pub fn subscribe(&self) -> Receiver<PlaybackEvent> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers.lock().unwrap().push(tx);
+        rx
+    }
+
+    fn emit(&self, event: PlaybackEvent) {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|tx| tx.send(event.clone()).is_ok());
+    }
+
+    /// Reports the end of the track once the position reaches the duration
+    pub fn check_ended(&self) -> bool {
+        let duration = *self.duration.read().unwrap();
+        let ended =
+            self.is_playing() && duration > 0.0 && *self.position.read().unwrap() >= duration;
+        if ended {
+            *self.is_playing.write().unwrap() = false;
+            self.emit(PlaybackEvent::TrackEnded);
         }
+        ended
     }
 
     pub fn play(&self) -> Result<(), String> {
         *self.is_playing.write().unwrap() = true;
+        self.emit(PlaybackEvent::StateChanged(PlaybackStatus::Playing));
         Ok(())
     }
 
     pub fn pause(&self) -> Result<(), String> {
         *self.is_playing.write().unwrap() = false;
+        *self.resume_on_gain.write().unwrap() = false;
+        self.emit(PlaybackEvent::StateChanged(PlaybackStatus::Paused));
         Ok(())
     }
 
     pub fn stop(&self) -> Result<(), String> {
         *self.is_playing.write().unwrap() = false;
         *self.position.write().unwrap() = 0.0;
+        self.emit(PlaybackEvent::StateChanged(PlaybackStatus::Stopped));
+        self.emit(PlaybackEvent::PositionChanged(0));
         Ok(())
     }
 
     pub fn seek(&self, position: Duration) -> Result<(), String> {
         *self.position.write().unwrap() = position.as_secs_f64();
+        self.emit(PlaybackEvent::PositionChanged(position.as_millis() as i64));
         Ok(())
     }
 
@@ -97,14 +145,146 @@ impl AudioPlayer {
         *self.volume.read().unwrap()
     }
 
-    pub fn load(&self, _path: &str) -> Result<(), String> {
+    /// Volume the output plays at, lowered while ducked
+    pub fn output_volume(&self) -> f64 {
+        let duck = if *self.ducked.read().unwrap() {
+            DUCK_VOLUME
+        } else {
+            1.0
+        };
+        self.volume() * duck
+    }
+
+    /// Sets whether playback resumes when focus comes back after a
+    /// transient loss
+    pub fn set_auto_resume(&self, enabled: bool) {
+        *self.auto_resume.write().unwrap() = enabled;
+    }
+
+    /// Follows an audio focus change
+    ///
+    /// A short sound over us lowers the volume; losing focus pauses, and
+    /// after a transient loss playback resumes once focus is back.
+    pub fn audio_focus_changed(&self, focus: AudioFocus) {
+        match focus {
+            AudioFocus::LossTransientCanDuck => *self.ducked.write().unwrap() = true,
+            AudioFocus::Loss | AudioFocus::LossTransient => {
+                if !self.is_playing() {
+                    return;
+                }
+                let transient =
+                    focus == AudioFocus::LossTransient && *self.auto_resume.read().unwrap();
+                *self.is_playing.write().unwrap() = false;
+                *self.resume_on_gain.write().unwrap() = transient;
+                self.emit(PlaybackEvent::StateChanged(PlaybackStatus::Paused));
+                self.emit(PlaybackEvent::Interrupted { transient });
+            }
+            AudioFocus::Gain => {
+                *self.ducked.write().unwrap() = false;
+                let resume = std::mem::take(&mut *self.resume_on_gain.write().unwrap());
+                if resume {
+                    let _ = self.play();
+                }
+            }
+        }
+    }
+
+    /// Loads an audio file, reporting `Ready` or an error to subscribers
+    pub fn load(&self, path: &str) -> Result<(), String> {
+        if path.is_empty() {
+            let message = "Audio file path cannot be empty".to_string();
+            self.emit(PlaybackEvent::Error {
+                code: 1,
+                message: message.clone(),
+            });
+            return Err(message);
+        }
         *self.duration.write().unwrap() = 3600.0; // Mock 1 hour duration
+        self.emit(PlaybackEvent::StateChanged(PlaybackStatus::Ready));
         Ok(())
     }
 }
 
+/// Share of the volume kept while another sound plays over us
+pub const DUCK_VOLUME: f64 = 0.2;
+
+/// Most players alive at once
+pub const MAX_PLAYERS: usize = 16;
+
 /// Global player handle manager
-static PLAYER_HANDLES: Lazy<HandleManager<Arc<AudioPlayer>>> = Lazy::new(HandleManager::new);
+static PLAYER_HANDLES: Lazy<HandleManager<Arc<AudioPlayer>>> = Lazy::new(|| {
+    HandleManager::named("player")
+        .with_capacity(MAX_PLAYERS)
+        .on_reap(|handle, player: Arc<AudioPlayer>| {
+            PLAYER_LISTENERS.unregister(handle);
+            crate::media_session::forget_player(handle);
+            let _ = player.stop();
+        })
+});
+
+/// Playback listeners registered per player handle
+static PLAYER_LISTENERS: Lazy<ListenerRegistry> = Lazy::new(ListenerRegistry::default);
+
+/// Starts delivering `player`'s events to `listener`
+fn attach_listener(
+    handle: i64,
+    player: Arc<AudioPlayer>,
+    listener: Arc<dyn crate::callbacks::PlaybackListener>,
+) {
+    let events = player.subscribe();
+    PLAYER_LISTENERS.register(handle, listener, events, move || {
+        if player.check_ended() || !player.is_playing() {
+            // Track end arrives through the event channel
+            return None;
+        }
+        Some(PlaybackEvent::PositionChanged(
+            player.position().as_millis() as i64,
+        ))
+    });
+}
+
+/// Returns the player behind `handle`
+pub(crate) fn player(handle: i64) -> FfiResult<Arc<AudioPlayer>> {
+    let player = PLAYER_HANDLES.get(handle)?;
+    let player = Arc::clone(&player.read().unwrap());
+    Ok(player)
+}
+
+/// Creates a player and returns its handle
+pub(crate) fn create_player() -> FfiResult<i64> {
+    let handle = PLAYER_HANDLES.insert(Arc::new(AudioPlayer::new()))?;
+    crate::ffi::log_info("StoryStream", &format!("Created player handle: {}", handle));
+    Ok(handle)
+}
+
+/// Stops callbacks and frees the player behind `handle`
+fn release_player(handle: i64) -> FfiResult<()> {
+    PLAYER_LISTENERS.unregister(handle);
+    PLAYER_HANDLES.remove(handle)?;
+    crate::media_session::forget_player(handle);
+    crate::ffi::log_info(
+        "StoryStream",
+        &format!("Released player handle: {}", handle),
+    );
+    Ok(())
+}
+
+/// Rejects a caller-supplied value as `INVALID_ARGUMENT`
+fn invalid_argument(argument: &str, reason: &str) -> FfiError {
+    AppError::InvalidArgument {
+        argument: argument.to_string(),
+        reason: reason.to_string(),
+    }
+    .into()
+}
+
+/// Reports a failed player operation as `PLAYBACK_DEVICE_ERROR`
+fn device_error(action: &str, error: impl std::fmt::Display) -> FfiError {
+    AppError::PlaybackDeviceError {
+        message: format!("Failed to {}: {}", action, error),
+    }
+    .into()
+}
 
 /// Create a new player instance
 #[no_mangle]
@@ -112,14 +292,7 @@ pub extern "C" fn Java_com_storystream_StoryStreamPlayer_nativeCreate(
     mut env: JNIEnv,
     _class: JClass,
 ) -> jlong {
-    crate::jni_safe!(env, 0, {
-        let player = Arc::new(AudioPlayer::new());
-        let handle = PLAYER_HANDLES.insert(player);
-
-        crate::ffi::log_info("StoryStream", &format!("Created player handle: {}", handle));
-
-        Ok(handle)
-    })
+    crate::jni_safe!(env, 0, { create_player() })
 }
 
 /// Load an audio file for playback
@@ -136,11 +309,16 @@ pub extern "C" fn Java_com_storystream_StoryStreamPlayer_nativeLoad(
 
         crate::ffi::log_info("StoryStream", &format!("Loading audio file: {}", path));
 
-        player
-            .read()
-            .unwrap()
-            .load(&path)
-            .map_err(|e| FfiError::General(format!("Failed to load audio: {}", e)))?;
+        if path.is_empty() {
+            return Err(invalid_argument("path", "Audio file path cannot be empty"));
+        }
+
+        player.read().unwrap().load(&path).map_err(|e| {
+            FfiError::from(AppError::AudioDecodeError {
+                message: format!("Failed to load audio: {}", e),
+                source: None,
+            })
+        })?;
 
         Ok(bool_to_jboolean(true))
     })
@@ -162,7 +340,7 @@ pub extern "C" fn Java_com_storystream_StoryStreamPlayer_nativePlay(
             .read()
             .unwrap()
             .play()
-            .map_err(|e| FfiError::General(format!("Failed to play: {}", e)))?;
+            .map_err(|e| device_error("play", e))?;
 
         Ok(bool_to_jboolean(true))
     })
@@ -184,7 +362,7 @@ pub extern "C" fn Java_com_storystream_StoryStreamPlayer_nativePause(
             .read()
             .unwrap()
             .pause()
-            .map_err(|e| FfiError::General(format!("Failed to pause: {}", e)))?;
+            .map_err(|e| device_error("pause", e))?;
 
         Ok(bool_to_jboolean(true))
     })
@@ -206,7 +384,7 @@ pub extern "C" fn Java_com_storystream_StoryStreamPlayer_nativeStop(
             .read()
             .unwrap()
             .stop()
-            .map_err(|e| FfiError::General(format!("Failed to stop: {}", e)))?;
+            .map_err(|e| device_error("stop", e))?;
 
         Ok(bool_to_jboolean(true))
     })
@@ -224,7 +402,7 @@ pub extern "C" fn Java_com_storystream_StoryStreamPlayer_nativeSeek(
         let player = PLAYER_HANDLES.get(handle)?;
 
         if position_seconds < 0.0 {
-            return Err(FfiError::General("Position cannot be negative".to_string()));
+            return Err(invalid_argument("position", "Position cannot be negative"));
         }
 
         crate::ffi::log_info(
@@ -236,7 +414,7 @@ pub extern "C" fn Java_com_storystream_StoryStreamPlayer_nativeSeek(
             .read()
             .unwrap()
             .seek(Duration::from_secs_f64(position_seconds))
-            .map_err(|e| FfiError::General(format!("Failed to seek: {}", e)))?;
+            .map_err(|e| device_error("seek", e))?;
 
         Ok(bool_to_jboolean(true))
     })
@@ -296,8 +474,9 @@ pub extern "C" fn Java_com_storystream_StoryStreamPlayer_nativeSetSpeed(
         let player = PLAYER_HANDLES.get(handle)?;
 
         if !(0.25..=4.0).contains(&speed) {
-            return Err(FfiError::General(
-                "Speed must be between 0.25 and 4.0".to_string(),
+            return Err(invalid_argument(
+                "speed",
+                "Speed must be between 0.25 and 4.0",
             ));
         }
 
@@ -307,7 +486,7 @@ pub extern "C" fn Java_com_storystream_StoryStreamPlayer_nativeSetSpeed(
             .read()
             .unwrap()
             .set_speed(speed)
-            .map_err(|e| FfiError::General(format!("Failed to set speed: {}", e)))?;
+            .map_err(|e| device_error("set speed", e))?;
 
         Ok(bool_to_jboolean(true))
     })
@@ -339,8 +518,9 @@ pub extern "C" fn Java_com_storystream_StoryStreamPlayer_nativeSetVolume(
         let player = PLAYER_HANDLES.get(handle)?;
 
         if !(0.0..=1.0).contains(&volume) {
-            return Err(FfiError::General(
-                "Volume must be between 0.0 and 1.0".to_string(),
+            return Err(invalid_argument(
+                "volume",
+                "Volume must be between 0.0 and 1.0",
             ));
         }
 
@@ -350,7 +530,7 @@ pub extern "C" fn Java_com_storystream_StoryStreamPlayer_nativeSetVolume(
             .read()
             .unwrap()
             .set_volume(volume)
-            .map_err(|e| FfiError::General(format!("Failed to set volume: {}", e)))?;
+            .map_err(|e| device_error("set volume", e))?;
 
         Ok(bool_to_jboolean(true))
     })
@@ -397,8 +577,9 @@ pub extern "C" fn Java_com_storystream_StoryStreamPlayer_nativeSkipToChapter(
         let _player = PLAYER_HANDLES.get(handle)?;
 
         if chapter_index < 0 {
-            return Err(FfiError::General(
-                "Chapter index cannot be negative".to_string(),
+            return Err(invalid_argument(
+                "chapterIndex",
+                "Chapter index cannot be negative",
             ));
         }
 
@@ -412,23 +593,108 @@ pub extern "C" fn Java_com_storystream_StoryStreamPlayer_nativeSkipToChapter(
     })
 }
 
-/// Destroy player instance
+/// Pass on an audio focus change
+///
+/// `focusChange` is the `AudioManager.AUDIOFOCUS_*` value the app's
+/// `OnAudioFocusChangeListener` received. A short sound ducks playback,
+/// a transient loss pauses it until focus returns, and a permanent loss
+/// pauses it for good.
 #[no_mangle]
-pub extern "C" fn Java_com_storystream_StoryStreamPlayer_nativeDestroy(
-    mut env: JNIEnv,
-    _class: JClass,
+pub extern "C" fn Java_com_storystream_StoryStreamPlayer_nativeOnAudioFocusChange(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
     handle: jlong,
-) {
-    crate::jni_safe!(env, (), {
-        PLAYER_HANDLES.remove(handle)?;
+    focus_change: jint,
+) -> jboolean {
+    crate::jni_safe!(env, bool_to_jboolean(false), {
+        let player = PLAYER_HANDLES.get(handle)?;
+        let focus = AudioFocus::from_code(focus_change)
+            .ok_or_else(|| invalid_argument("focusChange", "Unknown audio focus change"))?;
+
+        crate::ffi::log_info("StoryStream", &format!("Audio focus: {:?}", focus));
+
+        player.read().unwrap().audio_focus_changed(focus);
+        Ok(bool_to_jboolean(true))
+    })
+}
+
+/// Set whether playback resumes once audio focus returns after a
+/// transient loss
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamPlayer_nativeSetAutoResume(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    handle: jlong,
+    enabled: jboolean,
+) -> jboolean {
+    crate::jni_safe!(env, bool_to_jboolean(false), {
+        let player = PLAYER_HANDLES.get(handle)?;
+        player.read().unwrap().set_auto_resume(enabled != 0);
+        Ok(bool_to_jboolean(true))
+    })
+}
+
+/// Register a listener for playback events, or clear it by passing null
+///
+/// The listener must implement `onPositionChanged(long)`,
+/// `onStateChanged(int)`, `onTrackEnded()`, `onInterrupted(boolean)` and
+/// `onError(int, String)`.
+/// Callbacks run on a native thread attached to the JVM for each call.
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamPlayer_nativeSetPlaybackListener(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    handle: jlong,
+    listener: JObject<'_>,
+) -> jboolean {
+    crate::jni_safe!(env, bool_to_jboolean(false), {
+        let player = PLAYER_HANDLES.get(handle)?.read().unwrap().clone();
+
+        if listener.is_null() {
+            PLAYER_LISTENERS.unregister(handle);
+            crate::ffi::log_info(
+                "StoryStream",
+                &format!("Cleared playback listener for handle: {}", handle),
+            );
+            return Ok(bool_to_jboolean(true));
+        }
+
+        let vm = env.get_java_vm()?;
+        let callback = env.new_global_ref(&listener)?;
+        attach_listener(
+            handle,
+            player,
+            Arc::new(JavaPlaybackListener::new(vm, callback)),
+        );
+
         crate::ffi::log_info(
             "StoryStream",
-            &format!("Destroyed player handle: {}", handle),
+            &format!("Set playback listener for handle: {}", handle),
         );
-        Ok(())
+        Ok(bool_to_jboolean(true))
     })
 }
 
+/// Release player instance; no listener callbacks fire after this returns
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamPlayer_nativeRelease(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    handle: jlong,
+) {
+    crate::jni_safe!(env, (), { release_player(handle) })
+}
+
+/// Destroy player instance (same as `nativeRelease`)
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamPlayer_nativeDestroy(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    handle: jlong,
+) {
+    crate::jni_safe!(env, (), { release_player(handle) })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -444,7 +710,7 @@ mod tests {
     #[test]
     fn test_player_handle_lifecycle() {
         let player = Arc::new(AudioPlayer::new());
-        let handle = PLAYER_HANDLES.insert(player);
+        let handle = PLAYER_HANDLES.insert(player).unwrap();
         assert!(handle > 0);
 
         assert!(PLAYER_HANDLES.contains(handle));
@@ -462,8 +728,8 @@ mod tests {
         let player1 = Arc::new(AudioPlayer::new());
         let player2 = Arc::new(AudioPlayer::new());
 
-        let handle1 = PLAYER_HANDLES.insert(player1);
-        let handle2 = PLAYER_HANDLES.insert(player2);
+        let handle1 = PLAYER_HANDLES.insert(player1).unwrap();
+        let handle2 = PLAYER_HANDLES.insert(player2).unwrap();
 
         assert_ne!(handle1, handle2);
         assert!(PLAYER_HANDLES.contains(handle1));
@@ -511,4 +777,119 @@ mod tests {
         assert!(player.set_volume(-0.1).is_err()); // Negative
         assert!(player.set_volume(1.1).is_err()); // Too loud
     }
+
+    #[test]
+    fn test_audio_focus_changes() {
+        let player = AudioPlayer::new();
+        let events = player.subscribe();
+        player.play().unwrap();
+
+        // A notification ducks playback without pausing it
+        player.audio_focus_changed(AudioFocus::LossTransientCanDuck);
+        assert!(player.is_playing());
+        assert_eq!(player.output_volume(), DUCK_VOLUME);
+
+        // A call pauses it until focus is back
+        player.audio_focus_changed(AudioFocus::LossTransient);
+        assert!(!player.is_playing());
+        player.audio_focus_changed(AudioFocus::Gain);
+        assert!(player.is_playing());
+        assert_eq!(player.output_volume(), 1.0);
+
+        // Another app taking over stops it for good
+        player.audio_focus_changed(AudioFocus::Loss);
+        player.audio_focus_changed(AudioFocus::Gain);
+        assert!(!player.is_playing());
+
+        // With auto-resume off a transient loss acts like a permanent one
+        player.set_auto_resume(false);
+        player.play().unwrap();
+        player.audio_focus_changed(AudioFocus::LossTransient);
+        player.audio_focus_changed(AudioFocus::Gain);
+        assert!(!player.is_playing());
+
+        let interruptions: Vec<_> = events
+            .try_iter()
+            .filter(|event| matches!(event, PlaybackEvent::Interrupted { .. }))
+            .collect();
+        assert_eq!(
+            interruptions,
+            vec![
+                PlaybackEvent::Interrupted { transient: true },
+                PlaybackEvent::Interrupted { transient: false },
+                PlaybackEvent::Interrupted { transient: false },
+            ]
+        );
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Mutex<Vec<PlaybackEvent>>,
+    }
+
+    impl crate::callbacks::PlaybackListener for Recorder {
+        fn on_event(&self, event: &PlaybackEvent) {
+            self.events.lock().unwrap().push(event.clone());
+        }
+    }
+
+    fn wait_until(recorder: &Recorder, done: impl Fn(&[PlaybackEvent]) -> bool) {
+        for _ in 0..400 {
+            if done(&recorder.events.lock().unwrap()) {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        panic!("timed out waiting for events");
+    }
+
+    #[test]
+    fn test_player_events_reach_listener() {
+        let player = Arc::new(AudioPlayer::new());
+        let handle = PLAYER_HANDLES.insert(Arc::clone(&player)).unwrap();
+        let recorder = Arc::new(Recorder::default());
+
+        attach_listener(handle, Arc::clone(&player), recorder.clone());
+        player.load("/books/test.mp3").unwrap();
+        player.play().unwrap();
+        player.seek(Duration::from_secs(3600)).unwrap();
+
+        wait_until(&recorder, |events| {
+            events.contains(&PlaybackEvent::TrackEnded)
+        });
+        let events = recorder.events.lock().unwrap().clone();
+        assert_eq!(
+            events[0],
+            PlaybackEvent::StateChanged(PlaybackStatus::Ready)
+        );
+        assert_eq!(
+            events[1],
+            PlaybackEvent::StateChanged(PlaybackStatus::Playing)
+        );
+        assert!(!player.is_playing());
+
+        release_player(handle).unwrap();
+    }
+
+    #[test]
+    fn test_no_callbacks_after_release() {
+        let player = Arc::new(AudioPlayer::new());
+        let handle = PLAYER_HANDLES.insert(Arc::clone(&player)).unwrap();
+        let recorder = Arc::new(Recorder::default());
+
+        attach_listener(handle, Arc::clone(&player), recorder.clone());
+        player.play().unwrap();
+        wait_until(&recorder, |events| !events.is_empty());
+
+        release_player(handle).unwrap();
+        let seen = recorder.events.lock().unwrap().len();
+
+        player.pause().unwrap();
+        player.seek(Duration::from_secs(10)).unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+
+        assert_eq!(recorder.events.lock().unwrap().len(), seen);
+        assert!(!PLAYER_LISTENERS.is_registered(handle));
+        assert!(release_player(handle).is_err());
+    }
 }
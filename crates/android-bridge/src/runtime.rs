@@ -0,0 +1,46 @@
+// Async runtime for Android JNI
+//
+// JNI calls arrive on Java threads that know nothing about Tokio, so the
+// bridge owns one multi-threaded runtime and blocks on it for each call.
+
+use once_cell::sync::Lazy;
+use std::future::Future;
+use tokio::runtime::{Builder, Runtime};
+
+/// Runtime shared by every bridge call
+static RUNTIME: Lazy<Runtime> = Lazy::new(|| {
+    Builder::new_multi_thread()
+        .worker_threads(2)
+        .thread_name("storystream-bridge")
+        .enable_all()
+        .build()
+        .expect("Failed to start StoryStream bridge runtime")
+});
+
+/// Runs `future` to completion on the bridge runtime
+///
+/// Must not be called from a runtime thread.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    RUNTIME.block_on(future)
+}
+
+/// Returns the bridge runtime, for spawning background work
+pub fn handle() -> &'static Runtime {
+    &RUNTIME
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_on_runs_future() {
+        assert_eq!(block_on(async { 40 + 2 }), 42);
+    }
+
+    #[test]
+    fn test_spawned_task_completes() {
+        let task = handle().spawn(async { "done" });
+        assert_eq!(block_on(task).unwrap(), "done");
+    }
+}
@@ -0,0 +1,660 @@
+// Library scan bridge for Android JNI
+//
+// WorkManager jobs start scans with `nativeStartScan`. The scan runs on the
+// bridge runtime, reports progress to an optional listener and finishes with
+// a summary envelope; only one scan runs at a time. Finding and importing
+// files is done by the library crate's scanner and importer, compiled in
+// with the `library-scan` feature.
+
+// Without the feature the scan machinery is only reachable from the tests
+#![cfg_attr(not(feature = "library-scan"), allow(dead_code))]
+
+use crate::ffi::{bool_to_jboolean, jstring_raw_to_string, FfiError, FfiResult};
+use crate::library_bridge::library_pool;
+use crate::{json, runtime};
+use jni::{
+    objects::{GlobalRef, JClass, JObject, JValue},
+    sys::{jboolean, jlong, jstring},
+    JNIEnv, JavaVM,
+};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::future::Future;
+use std::panic; // Required for jni_safe! macro
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use storystream_core::AppError;
+use storystream_database::DbPool;
+
+/// Minimum time between progress callbacks
+pub const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Failures listed individually in the summary; the count covers the rest
+const MAX_REPORTED_FAILURES: usize = 50;
+
+/// Running totals of a scan
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanProgress {
+    /// Audio files the scanner found
+    pub files_found: u32,
+    /// Files added to the library
+    pub imported: u32,
+    /// Files already in the library or in an unsupported format
+    pub skipped: u32,
+    /// Files that could not be imported
+    pub failed: u32,
+}
+
+/// A file that could not be imported
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanFailure {
+    /// File path
+    pub path: String,
+    /// Error code, see `AppError::code`
+    pub code: &'static str,
+    /// Technical description
+    pub message: String,
+}
+
+/// Result of a finished scan
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanSummary {
+    /// Final totals
+    #[serde(flatten)]
+    pub progress: ScanProgress,
+    /// Whether `nativeCancelScan` stopped the scan early
+    pub cancelled: bool,
+    /// Wall time of the scan
+    pub duration_ms: u64,
+    /// The first failures, at most 50
+    pub failures: Vec<ScanFailure>,
+}
+
+/// What happened to one scanned file
+#[derive(Debug)]
+pub enum ImportOutcome {
+    /// Added to the library
+    Imported,
+    /// Nothing to do
+    Skipped,
+    /// Import failed
+    Failed(AppError),
+}
+
+/// Receiver of scan events
+pub trait ScanListener: Send + Sync {
+    /// Reports totals so far; called on a runtime worker thread
+    fn on_progress(&self, progress: &ScanProgress);
+    /// Reports the end of the scan as a JSON envelope holding a `ScanSummary`
+    fn on_complete(&self, summary: &str);
+}
+
+/// Listener backed by a Java object
+///
+/// Expected Java methods: `onProgress(int filesFound, int imported,
+/// int skipped, int failed)` and `onComplete(String summaryJson)`.
+struct JavaScanListener {
+    vm: JavaVM,
+    callback: GlobalRef,
+}
+
+impl JavaScanListener {
+    fn with_env(&self, call: impl FnOnce(&mut JNIEnv<'_>) -> jni::errors::Result<()>) {
+        // The guard detaches the thread again on drop if this call attached it
+        let mut env = match self.vm.attach_current_thread() {
+            Ok(env) => env,
+            Err(e) => {
+                crate::ffi::log_error("StoryStream", &format!("Failed to attach thread: {}", e));
+                return;
+            }
+        };
+
+        if let Err(e) = call(&mut env) {
+            // A Java exception must not stay pending on a native thread
+            if env.exception_check().unwrap_or(false) {
+                let _ = env.exception_describe();
+                let _ = env.exception_clear();
+            }
+            crate::ffi::log_error("StoryStream", &format!("Scan callback failed: {}", e));
+        }
+    }
+}
+
+impl ScanListener for JavaScanListener {
+    fn on_progress(&self, progress: &ScanProgress) {
+        self.with_env(|env| {
+            let count = |n: u32| JValue::Int(i32::try_from(n).unwrap_or(i32::MAX));
+            env.call_method(
+                self.callback.as_obj(),
+                "onProgress",
+                "(IIII)V",
+                &[
+                    count(progress.files_found),
+                    count(progress.imported),
+                    count(progress.skipped),
+                    count(progress.failed),
+                ],
+            )?;
+            Ok(())
+        });
+    }
+
+    fn on_complete(&self, summary: &str) {
+        self.with_env(|env| {
+            let summary = env.new_string(summary)?;
+            env.call_method(
+                self.callback.as_obj(),
+                "onComplete",
+                "(Ljava/lang/String;)V",
+                &[JValue::Object(&summary)],
+            )?;
+            Ok(())
+        });
+    }
+}
+
+/// Finds and imports audiobooks for a scan
+trait ScanBackend: Send + Sync + 'static {
+    /// Lists the audio files under `paths`, stopping early once `cancel` is set
+    fn discover(
+        &self,
+        paths: Vec<PathBuf>,
+        cancel: Arc<AtomicBool>,
+    ) -> impl Future<Output = Result<Vec<PathBuf>, AppError>> + Send;
+
+    /// Imports one file
+    fn import(&self, path: &Path) -> impl Future<Output = ImportOutcome> + Send;
+}
+
+#[cfg(feature = "library-scan")]
+struct LibraryBackend {
+    importer: storystream_library::BookImporter,
+}
+
+#[cfg(feature = "library-scan")]
+impl ScanBackend for LibraryBackend {
+    async fn discover(
+        &self,
+        paths: Vec<PathBuf>,
+        cancel: Arc<AtomicBool>,
+    ) -> Result<Vec<PathBuf>, AppError> {
+        let paths = paths.iter().map(|p| p.display().to_string()).collect();
+        let scanner = storystream_library::LibraryScanner::new(paths)
+            .with_cancel(storystream_library::ScanCancel::from(cancel));
+        Ok(scanner.scan().await?)
+    }
+
+    async fn import(&self, path: &Path) -> ImportOutcome {
+        use storystream_library::{ImportOptions, LibraryError};
+
+        match self.importer.is_imported(path).await {
+            Ok(true) => return ImportOutcome::Skipped,
+            Ok(false) => {}
+            Err(e) => return ImportOutcome::Failed(e.into()),
+        }
+        match self.importer.import_file(path, ImportOptions::new()).await {
+            Ok(_) => ImportOutcome::Imported,
+            Err(LibraryError::UnsupportedFormat(_)) => ImportOutcome::Skipped,
+            Err(e) => ImportOutcome::Failed(e.into()),
+        }
+    }
+}
+
+/// Cancel flag of the running scan, if any
+static CURRENT_SCAN: Lazy<Mutex<Option<Arc<AtomicBool>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Claims the single scan slot
+fn begin_scan() -> FfiResult<Arc<AtomicBool>> {
+    let mut current = CURRENT_SCAN.lock().unwrap_or_else(|e| e.into_inner());
+    if current.is_some() {
+        return Err(FfiError::AlreadyRunning(
+            "A library scan is already running".to_string(),
+        ));
+    }
+    let cancel = Arc::new(AtomicBool::new(false));
+    *current = Some(Arc::clone(&cancel));
+    Ok(cancel)
+}
+
+/// Frees the scan slot claimed with `cancel`
+fn finish_scan(cancel: &Arc<AtomicBool>) {
+    let mut current = CURRENT_SCAN.lock().unwrap_or_else(|e| e.into_inner());
+    if current.as_ref().is_some_and(|c| Arc::ptr_eq(c, cancel)) {
+        *current = None;
+    }
+}
+
+/// Asks the running scan to stop; returns whether one was running
+pub fn cancel_scan() -> bool {
+    let current = CURRENT_SCAN.lock().unwrap_or_else(|e| e.into_inner());
+    match current.as_ref() {
+        Some(cancel) => {
+            cancel.store(true, Ordering::SeqCst);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Parses the JSON array of paths given to `nativeStartScan`
+fn parse_paths(paths_json: &str) -> Result<Vec<PathBuf>, AppError> {
+    let invalid = |reason: String| AppError::InvalidArgument {
+        argument: "pathsJson".to_string(),
+        reason,
+    };
+
+    let paths: Vec<String> = serde_json::from_str(paths_json)
+        .map_err(|e| invalid(format!("expected a JSON array of paths: {}", e)))?;
+    if paths.is_empty() {
+        return Err(invalid("no paths to scan".to_string()));
+    }
+    if paths.iter().any(|p| p.trim().is_empty()) {
+        return Err(invalid("paths cannot be empty".to_string()));
+    }
+    Ok(paths.into_iter().map(PathBuf::from).collect())
+}
+
+/// Maps a failure to read a scan root, keeping permission problems explicit
+fn access_error(path: &Path, error: std::io::Error) -> AppError {
+    match error.kind() {
+        std::io::ErrorKind::PermissionDenied => AppError::PermissionDenied {
+            operation: "scan library".to_string(),
+            path: path.to_path_buf(),
+        },
+        std::io::ErrorKind::NotFound => AppError::FileNotFound {
+            path: path.to_path_buf(),
+        },
+        _ => AppError::IoError {
+            message: format!("Cannot read {}: {}", path.display(), error),
+            source: error,
+        },
+    }
+}
+
+/// Checks that a scan root exists and is readable before the scan starts
+fn check_access(path: &Path) -> Result<(), AppError> {
+    let metadata = std::fs::metadata(path).map_err(|e| access_error(path, e))?;
+    if metadata.is_dir() {
+        std::fs::read_dir(path).map_err(|e| access_error(path, e))?;
+    } else {
+        std::fs::File::open(path).map_err(|e| access_error(path, e))?;
+    }
+    Ok(())
+}
+
+/// Discovers and imports files, reporting progress along the way
+async fn run_scan<B: ScanBackend>(
+    backend: &B,
+    paths: Vec<PathBuf>,
+    cancel: Arc<AtomicBool>,
+    listener: Option<&dyn ScanListener>,
+) -> Result<ScanSummary, AppError> {
+    let started = Instant::now();
+    let files = match backend.discover(paths, Arc::clone(&cancel)).await {
+        Ok(files) => files,
+        Err(AppError::Cancelled { .. }) => Vec::new(),
+        Err(e) => return Err(e),
+    };
+
+    let mut progress = ScanProgress {
+        files_found: u32::try_from(files.len()).unwrap_or(u32::MAX),
+        ..ScanProgress::default()
+    };
+    let mut failures = Vec::new();
+    let mut last_report: Option<Instant> = None;
+
+    for file in files {
+        if cancel.load(Ordering::SeqCst) {
+            break;
+        }
+
+        match backend.import(&file).await {
+            ImportOutcome::Imported => progress.imported += 1,
+            ImportOutcome::Skipped => progress.skipped += 1,
+            ImportOutcome::Failed(e) => {
+                progress.failed += 1;
+                crate::ffi::log_error(
+                    "StoryStream",
+                    &format!("Scan failed to import {}: {}", file.display(), e),
+                );
+                if failures.len() < MAX_REPORTED_FAILURES {
+                    failures.push(ScanFailure {
+                        path: file.display().to_string(),
+                        code: e.code(),
+                        message: e.to_string(),
+                    });
+                }
+            }
+        }
+
+        if let Some(listener) = listener {
+            if last_report.is_none_or(|at| at.elapsed() >= PROGRESS_INTERVAL) {
+                listener.on_progress(&progress);
+                last_report = Some(Instant::now());
+            }
+        }
+    }
+
+    // The last update may have been throttled away
+    if let Some(listener) = listener {
+        listener.on_progress(&progress);
+    }
+
+    Ok(ScanSummary {
+        progress,
+        cancelled: cancel.load(Ordering::SeqCst),
+        duration_ms: started.elapsed().as_millis() as u64,
+        failures,
+    })
+}
+
+/// Starts a scan on the bridge runtime
+fn start_scan<B: ScanBackend>(
+    backend: B,
+    paths: Vec<PathBuf>,
+    listener: Option<Arc<dyn ScanListener>>,
+) -> FfiResult<()> {
+    let cancel = begin_scan()?;
+    crate::ffi::log_info(
+        "StoryStream",
+        &format!("Starting library scan of {} paths", paths.len()),
+    );
+
+    runtime::handle().spawn(async move {
+        let result = run_scan(&backend, paths, Arc::clone(&cancel), listener.as_deref()).await;
+        // Freed first, so the listener may start the next scan right away
+        finish_scan(&cancel);
+
+        match &result {
+            Ok(summary) => crate::ffi::log_info(
+                "StoryStream",
+                &format!(
+                    "Library scan finished: {} imported, {} skipped, {} failed{}",
+                    summary.progress.imported,
+                    summary.progress.skipped,
+                    summary.progress.failed,
+                    if summary.cancelled {
+                        " (cancelled)"
+                    } else {
+                        ""
+                    }
+                ),
+            ),
+            Err(e) => crate::ffi::log_error("StoryStream", &format!("Library scan failed: {}", e)),
+        }
+        let body = json::envelope(result);
+        if let Some(listener) = listener {
+            listener.on_complete(&body);
+        }
+    });
+    Ok(())
+}
+
+#[cfg(feature = "library-scan")]
+fn start_library_scan(
+    pool: DbPool,
+    paths: Vec<PathBuf>,
+    listener: Option<Arc<dyn ScanListener>>,
+) -> FfiResult<()> {
+    let backend = LibraryBackend {
+        importer: storystream_library::BookImporter::new(pool),
+    };
+    start_scan(backend, paths, listener)
+}
+
+#[cfg(not(feature = "library-scan"))]
+fn start_library_scan(
+    _pool: DbPool,
+    _paths: Vec<PathBuf>,
+    _listener: Option<Arc<dyn ScanListener>>,
+) -> FfiResult<()> {
+    Err(AppError::InvalidConfiguration {
+        setting: "library-scan".to_string(),
+        value: "disabled".to_string(),
+        reason: "the bridge was built without the library-scan feature".to_string(),
+    }
+    .into())
+}
+
+/// Start a background scan of the directories in `pathsJson`
+///
+/// `pathsJson` is a JSON array of paths. Unreadable paths throw with code
+/// PERMISSION_DENIED before anything runs, and a second scan while one is
+/// running throws ALREADY_RUNNING. `listener` may be null.
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamLibrary_nativeStartScan(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    handle: jlong,
+    paths_json: jstring,
+    listener: JObject<'_>,
+) -> jboolean {
+    crate::jni_safe!(env, bool_to_jboolean(false), {
+        let paths_json = jstring_raw_to_string(&mut env, paths_json)?;
+        let paths = parse_paths(&paths_json)?;
+        for path in &paths {
+            check_access(path)?;
+        }
+        let pool = library_pool(handle)?;
+
+        let listener: Option<Arc<dyn ScanListener>> = if listener.is_null() {
+            None
+        } else {
+            let vm = env.get_java_vm()?;
+            let callback = env.new_global_ref(&listener)?;
+            Some(Arc::new(JavaScanListener { vm, callback }))
+        };
+
+        start_library_scan(pool, paths, listener)?;
+        Ok(bool_to_jboolean(true))
+    })
+}
+
+/// Cancel the running scan; returns false if none is running
+///
+/// The scan stops after the file in progress and still reports its summary.
+#[no_mangle]
+pub extern "C" fn Java_com_storystream_StoryStreamLibrary_nativeCancelScan(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+) -> jboolean {
+    crate::jni_safe!(env, bool_to_jboolean(false), {
+        Ok(bool_to_jboolean(cancel_scan()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    /// Imports by extension: `.dup` is skipped, `.bad` fails
+    struct FakeBackend {
+        files: Vec<PathBuf>,
+        /// Sets the cancel flag while importing this file
+        cancel_on: Option<PathBuf>,
+        cancel: Mutex<Option<Arc<AtomicBool>>>,
+    }
+
+    impl FakeBackend {
+        fn new(names: &[&str]) -> Self {
+            Self {
+                files: names.iter().map(PathBuf::from).collect(),
+                cancel_on: None,
+                cancel: Mutex::new(None),
+            }
+        }
+    }
+
+    impl ScanBackend for FakeBackend {
+        async fn discover(
+            &self,
+            _paths: Vec<PathBuf>,
+            cancel: Arc<AtomicBool>,
+        ) -> Result<Vec<PathBuf>, AppError> {
+            *self.cancel.lock().unwrap() = Some(cancel);
+            Ok(self.files.clone())
+        }
+
+        async fn import(&self, path: &Path) -> ImportOutcome {
+            if self.cancel_on.as_deref() == Some(path) {
+                if let Some(cancel) = self.cancel.lock().unwrap().as_ref() {
+                    cancel.store(true, Ordering::SeqCst);
+                }
+            }
+            match path.extension().and_then(|e| e.to_str()) {
+                Some("dup") => ImportOutcome::Skipped,
+                Some("bad") => ImportOutcome::Failed(AppError::InvalidMetadata {
+                    field: "duration".to_string(),
+                    value: "0".to_string(),
+                }),
+                _ => ImportOutcome::Imported,
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        progress: Mutex<Vec<ScanProgress>>,
+        done: Mutex<Option<mpsc::Sender<String>>>,
+    }
+
+    impl ScanListener for Recorder {
+        fn on_progress(&self, progress: &ScanProgress) {
+            self.progress.lock().unwrap().push(*progress);
+        }
+
+        fn on_complete(&self, summary: &str) {
+            if let Some(tx) = self.done.lock().unwrap().as_ref() {
+                let _ = tx.send(summary.to_string());
+            }
+        }
+    }
+
+    #[test]
+    fn test_scan_counts_outcomes() {
+        let backend =
+            FakeBackend::new(&["/a/one.mp3", "/a/two.dup", "/a/three.bad", "/a/four.m4b"]);
+        let recorder = Recorder::default();
+        let cancel = Arc::new(AtomicBool::new(false));
+
+        let summary = runtime::block_on(run_scan(
+            &backend,
+            vec!["/a".into()],
+            cancel,
+            Some(&recorder),
+        ))
+        .unwrap();
+
+        let expected = ScanProgress {
+            files_found: 4,
+            imported: 2,
+            skipped: 1,
+            failed: 1,
+        };
+        assert_eq!(summary.progress, expected);
+        assert!(!summary.cancelled);
+        assert_eq!(summary.failures.len(), 1);
+        assert_eq!(summary.failures[0].path, "/a/three.bad");
+        assert_eq!(summary.failures[0].code, "INVALID_METADATA");
+        assert_eq!(recorder.progress.lock().unwrap().last(), Some(&expected));
+
+        let body = json::envelope(Ok(summary));
+        assert!(body.contains(r#""filesFound":4"#));
+        assert!(body.contains(r#""cancelled":false"#));
+    }
+
+    #[test]
+    fn test_cancel_stops_between_files() {
+        let mut backend = FakeBackend::new(&["/a/1.mp3", "/a/2.mp3", "/a/3.mp3", "/a/4.mp3"]);
+        backend.cancel_on = Some(PathBuf::from("/a/2.mp3"));
+        let cancel = Arc::new(AtomicBool::new(false));
+
+        let summary =
+            runtime::block_on(run_scan(&backend, vec!["/a".into()], cancel, None)).unwrap();
+
+        // The file in progress finishes, the rest are left alone
+        assert!(summary.cancelled);
+        assert_eq!(summary.progress.files_found, 4);
+        assert_eq!(summary.progress.imported, 2);
+    }
+
+    #[test]
+    fn test_only_one_scan_runs() {
+        let first = begin_scan().unwrap();
+        let err = begin_scan().unwrap_err();
+        assert_eq!(err.error_code().name, "ALREADY_RUNNING");
+
+        assert!(cancel_scan());
+        assert!(first.load(Ordering::SeqCst));
+        finish_scan(&first);
+        assert!(!cancel_scan());
+
+        // A spawned scan frees the slot before reporting its summary
+        let (tx, rx) = mpsc::channel();
+        let recorder = Arc::new(Recorder {
+            done: Mutex::new(Some(tx)),
+            ..Recorder::default()
+        });
+        start_scan(
+            FakeBackend::new(&["/b/book.mp3"]),
+            vec!["/b".into()],
+            Some(recorder.clone()),
+        )
+        .unwrap();
+
+        let body = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(body.contains(r#""ok":true"#));
+        assert!(body.contains(r#""imported":1"#));
+        let next = begin_scan().unwrap();
+        finish_scan(&next);
+    }
+
+    #[test]
+    fn test_paths_are_validated() {
+        for bad in ["not json", "[]", r#"[""]"#, r#"{"path": "/a"}"#] {
+            assert_eq!(
+                parse_paths(bad).unwrap_err().code(),
+                "INVALID_ARGUMENT",
+                "{}",
+                bad
+            );
+        }
+        assert_eq!(
+            parse_paths(r#"["/storage/emulated/0/Audiobooks"]"#).unwrap(),
+            vec![PathBuf::from("/storage/emulated/0/Audiobooks")]
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        assert!(check_access(dir.path()).is_ok());
+        let missing = dir.path().join("missing");
+        assert_eq!(check_access(&missing).unwrap_err().code(), "FILE_NOT_FOUND");
+
+        let denied = access_error(
+            &missing,
+            std::io::Error::from(std::io::ErrorKind::PermissionDenied),
+        );
+        assert_eq!(denied.code(), "PERMISSION_DENIED");
+        assert!(denied.to_string().contains("missing"));
+    }
+
+    #[test]
+    fn test_scan_without_library_feature_is_reported() {
+        if cfg!(feature = "library-scan") {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let pool = runtime::block_on(storystream_database::connection::connect(
+            storystream_database::connection::DatabaseConfig::new(
+                dir.path().join("test.db").to_string_lossy(),
+            ),
+        ))
+        .unwrap();
+        let err = start_library_scan(pool, vec![dir.path().to_path_buf()], None).unwrap_err();
+        assert_eq!(err.error_code().name, "INVALID_CONFIGURATION");
+    }
+}
@@ -39,7 +39,7 @@ mod integration_tests {
         // Create handles from multiple threads
         for i in 0..10 {
             let mgr = Arc::clone(&manager);
-            let handle = thread::spawn(move || mgr.insert(format!("value-{}", i)));
+            let handle = thread::spawn(move || mgr.insert(format!("value-{}", i)).unwrap());
             handles.push(handle);
         }
 
@@ -118,7 +118,7 @@ mod integration_tests {
         ));
 
         // Test double removal
-        let handle = manager.insert("test".to_string());
+        let handle = manager.insert("test".to_string()).unwrap();
         assert!(manager.remove(handle).is_ok());
         assert!(matches!(
             manager.remove(handle),
@@ -126,12 +126,33 @@ mod integration_tests {
         ));
     }
 
+    // Released handles report use-after-release instead of a generic miss
+    #[test]
+    fn test_handle_manager_double_release() {
+        let manager = HandleManager::<String>::default();
+        let handle = manager.insert("test".to_string()).unwrap();
+        manager.remove(handle).unwrap();
+
+        match manager.remove(handle) {
+            Err(FfiError::InvalidHandle(msg)) => assert!(msg.contains("already released")),
+            other => panic!("expected InvalidHandle, got {:?}", other.map(|_| ())),
+        }
+        match manager.get(handle) {
+            Err(FfiError::InvalidHandle(msg)) => assert!(msg.contains("already released")),
+            other => panic!("expected InvalidHandle, got {:?}", other.map(|_| ())),
+        }
+        match manager.get(handle + 1000) {
+            Err(FfiError::InvalidHandle(msg)) => assert!(msg.contains("not found")),
+            other => panic!("expected InvalidHandle, got {:?}", other.map(|_| ())),
+        }
+    }
+
     // Test handle manager with different types
     #[test]
     fn test_handle_manager_generic_types() {
         // Test with integers
         let int_manager = HandleManager::<i32>::default();
-        let h1 = int_manager.insert(42);
+        let h1 = int_manager.insert(42).unwrap();
         assert!(int_manager.get(h1).is_ok());
 
         // Test with structs
@@ -141,15 +162,17 @@ mod integration_tests {
         }
 
         let struct_manager = HandleManager::<TestStruct>::default();
-        let h2 = struct_manager.insert(TestStruct {
-            field: "test".to_string(),
-        });
+        let h2 = struct_manager
+            .insert(TestStruct {
+                field: "test".to_string(),
+            })
+            .unwrap();
         assert!(struct_manager.get(h2).is_ok());
 
         // Test with Options
         let option_manager = HandleManager::<Option<String>>::default();
-        let h3 = option_manager.insert(Some("value".to_string()));
-        let h4 = option_manager.insert(None);
+        let h3 = option_manager.insert(Some("value".to_string())).unwrap();
+        let h4 = option_manager.insert(None).unwrap();
         assert!(option_manager.get(h3).is_ok());
         assert!(option_manager.get(h4).is_ok());
     }
@@ -162,7 +185,7 @@ mod integration_tests {
 
         // Insert many handles
         for i in 0..1000 {
-            handles.push(manager.insert(i));
+            handles.push(manager.insert(i).unwrap());
         }
 
         // Verify all handles are valid and unique
@@ -255,7 +278,7 @@ mod integration_tests {
             // Create 100 handles
             for i in 0..100 {
                 let data = vec![i as u8; 1000]; // 1KB each
-                handles.push(manager.insert(data));
+                handles.push(manager.insert(data).unwrap());
             }
 
             // Remove all handles
@@ -288,14 +311,14 @@ mod ffi_tests {
     #[test]
     fn test_handle_manager_default() {
         let manager = HandleManager::<String>::default();
-        let h = manager.insert("test".to_string());
+        let h = manager.insert("test".to_string()).unwrap();
         assert!(h > 0);
     }
 
     #[test]
     fn test_handle_manager_contains() {
         let manager = HandleManager::<String>::default();
-        let h = manager.insert("test".to_string());
+        let h = manager.insert("test".to_string()).unwrap();
 
         assert!(manager.contains(h));
         assert!(!manager.contains(999));
@@ -315,7 +338,14 @@ mod error_handling_tests {
             FfiError::JniError("jni".to_string()),
             FfiError::Utf8Error("utf8".to_string()),
             FfiError::InvalidHandle("handle".to_string()),
+            FfiError::HandleLimit("limit".to_string()),
+            FfiError::AlreadyRunning("scan".to_string()),
             FfiError::General("general".to_string()),
+            FfiError::App(storystream_core::AppError::InvalidArgument {
+                argument: "arg".to_string(),
+                reason: "reason".to_string(),
+            }),
+            FfiError::Panic("panic".to_string()),
         ];
 
         for error in errors {
@@ -324,6 +354,11 @@ mod error_handling_tests {
 
             // All errors should implement Error trait
             let _e: &dyn std::error::Error = &error;
+
+            // All errors should map to a stable, named code
+            let code = error.error_code();
+            assert!(code.code > 0);
+            assert!(!code.name.is_empty());
         }
     }
 }
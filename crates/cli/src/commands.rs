@@ -1,7 +1,37 @@
 // crates/cli/src/commands.rs
 //! Command-line interface definitions
 
-use clap::{Parser, Subcommand};
+pub mod bookmark;
+pub mod completions;
+pub mod config;
+pub mod doctor;
+pub mod feed;
+pub mod library;
+pub mod output;
+pub mod playlist;
+pub mod scan;
+pub mod source;
+pub mod stats;
+pub mod status;
+pub mod transfer;
+pub mod verify;
+
+pub use output::Output;
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum, ValueHint};
+use clap_complete::Shell;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use storystream_config::{ConfigManager, ConfigOverride, EnvOverride};
+use storystream_core::{AppError, Book, BookId, CacheManager, Duration};
+use storystream_database::{
+    connection::{connect, DatabaseConfig},
+    migrations::run_migrations,
+    queries::books,
+    DbPool,
+};
+use storystream_network::DownloadHistory;
 
 /// StoryStream CLI application
 #[derive(Parser)]
@@ -10,6 +40,33 @@ use clap::{Parser, Subcommand};
 #[command(version = "1.0.0")]
 #[command(about = "Professional Audiobook Player", long_about = None)]
 pub struct Cli {
+    /// Print a JSON result envelope instead of text
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Only print the final result or errors
+    #[arg(short, long, global = true, conflicts_with = "json")]
+    pub quiet: bool,
+
+    /// Append diagnostic logs to this file
+    #[arg(long, global = true, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+
+    /// Log verbosity (error, warn, info, debug, trace) or a RUST_LOG-style filter
+    #[arg(long, global = true, value_name = "FILTER")]
+    pub log_level: Option<String>,
+
+    /// Set a configuration value for this run, such as
+    /// player.default_volume=80; overrides the file and STORYSTREAM_*
+    /// variables (repeatable)
+    #[arg(
+        long = "config-override",
+        global = true,
+        value_name = "KEY=VALUE",
+        value_parser = parse_assignment
+    )]
+    pub config_overrides: Vec<(String, String)>,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -49,37 +106,749 @@ pub enum Commands {
         /// Show only favorites
         #[arg(short, long)]
         favorites: bool,
+
+        /// Sort by title, author, added, last_played or duration; a leading
+        /// '-' reverses the order
+        #[arg(long, allow_hyphen_values = true)]
+        sort: Option<String>,
+
+        /// Maximum number of books to show
+        #[arg(short, long)]
+        limit: Option<i64>,
     },
 
     /// Scan library for new audiobooks
     Scan {
         /// Path to scan (uses config paths if not specified)
+        #[arg(value_hint = ValueHint::DirPath)]
         path: Option<String>,
+
+        /// Show what would be imported without changing the library
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Write the plan to this file instead of importing, to commit later
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        save_plan: Option<PathBuf>,
+
+        /// Read every file again, including those unchanged since they were
+        /// imported
+        #[arg(long)]
+        full: bool,
+
+        /// Import a plan written earlier with --save-plan
+        #[arg(
+            long,
+            value_name = "FILE",
+            value_hint = ValueHint::FilePath,
+            conflicts_with_all = ["path", "dry_run", "save_plan", "full"]
+        )]
+        commit_plan: Option<PathBuf>,
     },
 
-    /// Search for audiobooks
+    /// Search for audiobooks by text, filters, or both
     Search {
         /// Search query
-        query: String,
+        #[arg(required_unless_present_any = SearchFilterArgs::FLAGS)]
+        query: Option<String>,
+
+        #[command(flatten)]
+        filter: SearchFilterArgs,
+
+        /// Sort by title, author, added, last_played or duration instead of
+        /// best match; a leading '-' reverses the order
+        #[arg(long, allow_hyphen_values = true)]
+        sort: Option<String>,
+
+        /// Maximum number of results
+        #[arg(short, long, default_value_t = 20)]
+        limit: i64,
     },
 
-    /// Add a bookmark at current position
+    /// Manage bookmarks
     Bookmark {
-        /// Optional bookmark title
+        #[command(subcommand)]
+        action: BookmarkAction,
+    },
+
+    /// Open a shared bookmark link in your copy of the book
+    Open {
+        /// storystream:// link, or text that contains one
+        link: String,
+    },
+
+    /// Manage playlists
+    Playlist {
+        #[command(subcommand)]
+        action: PlaylistAction,
+    },
+
+    /// Manage podcast and audiobook feed subscriptions
+    Feed {
+        #[command(subcommand)]
+        action: FeedAction,
+    },
+
+    /// Search and download from online audiobook sources
+    Source {
+        #[command(subcommand)]
+        action: SourceAction,
+    },
+
+    /// Download a LibriVox audiobook by its catalogue ID into the library
+    Download {
+        /// LibriVox book ID
+        id: String,
+
+        /// Directory to create the book's folder in
+        #[arg(long, value_name = "DIR", value_hint = ValueHint::DirPath)]
+        dest: Option<PathBuf>,
+    },
+
+    /// Export the library to a JSON file
+    Export {
+        /// File to write
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        out: PathBuf,
+
+        /// Include playback positions
+        #[arg(long)]
+        include_positions: bool,
+
+        /// Include bookmarks
+        #[arg(long)]
+        include_bookmarks: bool,
+    },
+
+    /// Import a library export file
+    Import {
+        /// Export file to read
+        #[arg(value_hint = ValueHint::FilePath)]
+        file: PathBuf,
+
+        /// What to do with books that are already in the library
+        #[arg(long, value_enum, default_value_t = ImportStrategyArg::Skip)]
+        strategy: ImportStrategyArg,
+
+        /// Import without asking for confirmation
         #[arg(short, long)]
-        title: Option<String>,
+        yes: bool,
+    },
+
+    /// Import listening progress and bookmarks from another audiobook app
+    ///
+    /// Entries are matched to books by file path, then by title and author.
+    ImportProgress {
+        /// Export file from the other app
+        #[arg(value_hint = ValueHint::FilePath)]
+        file: PathBuf,
+
+        /// Format of the file
+        #[arg(long, value_enum)]
+        format: ProgressFormat,
+
+        /// Show what would be imported without saving anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Check the database, configuration and library for problems
+    Doctor {
+        /// Apply safe repairs for the problems found
+        #[arg(long)]
+        fix: bool,
+
+        /// Also decode suspect audio files to verify they are readable
+        #[arg(long)]
+        verify_audio: bool,
+
+        /// Re-hash every book file and compare it with the stored hash
+        #[arg(long)]
+        verify_files: bool,
+
+        /// With --verify-files, also decode every file in full
+        #[arg(long, requires = "verify_files")]
+        full: bool,
+
+        /// Remove download history older than this (e.g. 30d)
+        #[arg(long, value_name = "AGE")]
+        prune_downloads: Option<Duration>,
+
+        /// Look for books imported more than once and offer to merge them
+        #[arg(long)]
+        duplicates: bool,
+
+        /// Also run database maintenance: analyze, vacuum and checkpoint
+        #[arg(long)]
+        maintenance: bool,
+
+        /// Remove temporary files that crashed writes and downloads left
+        /// behind
+        #[arg(long)]
+        clean: bool,
+    },
+
+    /// Compare the library with the files in the library paths
+    ///
+    /// Lists books whose file is missing, audio files no book points at, and
+    /// files that changed since they were imported.
+    Verify {
+        /// Mark missing books unavailable, import orphaned files and re-read
+        /// modified ones
+        #[arg(long)]
+        fix: bool,
+    },
+
+    /// Show library and listening statistics
+    Stats {
+        /// Write a per-book CSV export to this file
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        csv: Option<PathBuf>,
+
+        /// Only count listening within this window (e.g. 7d, 12h)
+        #[arg(long)]
+        since: Option<Duration>,
+
+        /// Measure the loudness of books not measured yet and show how
+        /// loud the library is
+        #[arg(long)]
+        loudness: bool,
     },
 
     /// Show current playback status
     Status,
 
     /// Launch the terminal user interface
-    Tui,
+    Tui {
+        /// Announce playback and selection changes for screen readers
+        #[arg(long)]
+        accessible: bool,
+    },
 
     /// Show application configuration
     Config {
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
+
         /// Show full configuration
         #[arg(short, long)]
         full: bool,
     },
+
+    /// Print a shell completion script to stdout
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+
+    /// Print the man page (roff) to stdout
+    Manpage,
+}
+
+/// Config file subcommands
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Check the config file, listing every invalid setting
+    Validate,
+
+    /// Print the JSON Schema of the config file, for editors and tools
+    Schema,
+}
+
+/// Bookmark subcommands
+#[derive(Subcommand)]
+pub enum BookmarkAction {
+    /// List bookmarks, optionally for a single book
+    List {
+        /// Book title or ID
+        #[arg(short, long)]
+        book: Option<String>,
+    },
+
+    /// Add a bookmark to a book
+    Add {
+        /// Book title or ID
+        #[arg(short, long)]
+        book: String,
+
+        /// Position in the book (e.g. 1h23m, 45:10, 90)
+        #[arg(long)]
+        at: Duration,
+
+        /// Optional bookmark title
+        #[arg(short, long)]
+        title: Option<String>,
+
+        /// Optional note
+        #[arg(short, long)]
+        note: Option<String>,
+    },
+
+    /// Remove a bookmark by ID
+    Remove {
+        /// Bookmark ID
+        id: String,
+    },
+
+    /// Print a link that takes someone else to a bookmark
+    Share {
+        /// Bookmark ID
+        id: String,
+    },
+}
+
+/// Playlist subcommands
+#[derive(Subcommand)]
+pub enum PlaylistAction {
+    /// Create an empty playlist, or a smart playlist from criteria
+    Create {
+        /// Playlist name
+        name: String,
+
+        /// Smart playlist criteria as JSON, e.g. '{"authors": ["Jane Austen"]}'
+        #[arg(long, value_name = "CRITERIA")]
+        smart: Option<String>,
+    },
+
+    /// List playlists
+    List,
+
+    /// Show the books in a playlist, in order
+    Show {
+        /// Playlist name or ID
+        name: String,
+    },
+
+    /// Append a book to a playlist
+    Add {
+        /// Playlist name or ID
+        name: String,
+
+        /// Book title or ID
+        #[arg(short, long)]
+        book: String,
+    },
+
+    /// Remove a book from a playlist
+    Remove {
+        /// Playlist name or ID
+        name: String,
+
+        /// Book title or ID
+        #[arg(short, long)]
+        book: String,
+    },
+
+    /// Start a playlist; the TUI picks up where it left off
+    Play {
+        /// Playlist name or ID
+        name: String,
+
+        /// Play the books in random order
+        #[arg(long)]
+        shuffle: bool,
+    },
 }
+
+/// Feed subscription subcommands
+#[derive(Subcommand)]
+pub enum FeedAction {
+    /// Subscribe to a feed, or import subscriptions from an OPML file
+    Add {
+        /// Feed URL
+        #[arg(required_unless_present = "opml")]
+        url: Option<String>,
+
+        /// Import every feed listed in this OPML file
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath, conflicts_with = "url")]
+        opml: Option<PathBuf>,
+    },
+
+    /// List subscribed feeds
+    List {
+        /// Export subscriptions to this OPML file instead of printing them
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        opml: Option<PathBuf>,
+    },
+
+    /// Check feeds for new episodes
+    Refresh {
+        /// Feed title, URL or ID
+        #[arg(required_unless_present = "all")]
+        feed: Option<String>,
+
+        /// Refresh every subscribed feed
+        #[arg(long, conflicts_with = "feed")]
+        all: bool,
+    },
+
+    /// Download episodes and import them into the library
+    Download {
+        /// Feed title, URL or ID
+        feed: String,
+
+        /// Number of most recent episodes to download
+        #[arg(long, default_value_t = 1)]
+        latest: usize,
+
+        /// Directory to save episodes in
+        #[arg(long, value_name = "DIR", value_hint = ValueHint::DirPath)]
+        dir: Option<PathBuf>,
+    },
+
+    /// Show or change a feed's automatic download policy
+    ///
+    /// The policy is applied after every `feed refresh`.
+    Policy {
+        /// Feed title, URL or ID
+        feed: String,
+
+        /// Number of newest unplayed episodes to keep downloaded
+        #[arg(long, value_name = "N")]
+        keep: Option<usize>,
+
+        /// Download new episodes after each refresh
+        #[arg(long, value_name = "BOOL")]
+        auto_download: Option<bool>,
+
+        /// Delete episode files once they have been listened to
+        #[arg(long, value_name = "BOOL")]
+        delete_finished: Option<bool>,
+
+        /// Only download on unmetered connections
+        #[arg(long, value_name = "BOOL")]
+        unmetered_only: Option<bool>,
+    },
+}
+
+/// Online source subcommands
+#[derive(Subcommand)]
+pub enum SourceAction {
+    /// Search online sources and number the results for `source fetch`
+    Search {
+        /// Search text
+        query: String,
+
+        /// Source to search
+        #[arg(long, value_enum, default_value_t = SourceKind::All)]
+        source: SourceKind,
+
+        /// Only show books in this language (e.g. en, French)
+        #[arg(long)]
+        language: Option<String>,
+
+        /// Maximum results per source
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+
+    /// Download a result from the last search and import it into the library
+    Fetch {
+        /// Result number from the last `source search`
+        number: usize,
+
+        /// Directory to save the audiobook in
+        #[arg(long, value_name = "DIR", value_hint = ValueHint::DirPath)]
+        dest: Option<PathBuf>,
+    },
+}
+
+/// Structured filters for `search`
+#[derive(Debug, Default, Args)]
+pub struct SearchFilterArgs {
+    /// Only books whose author contains this text
+    #[arg(long)]
+    pub author: Option<String>,
+
+    /// Only books whose narrator contains this text
+    #[arg(long)]
+    pub narrator: Option<String>,
+
+    /// Only books with this tag
+    #[arg(long)]
+    pub tag: Option<String>,
+
+    /// Only favorites
+    #[arg(short, long)]
+    pub favorites: bool,
+
+    /// Only books listened to the end
+    #[arg(long, conflicts_with = "unfinished")]
+    pub finished: bool,
+
+    /// Only books not yet finished
+    #[arg(long)]
+    pub unfinished: bool,
+
+    /// Only books at least this long (e.g. 20h, 1h30m)
+    #[arg(long, value_name = "DURATION")]
+    pub min_length: Option<Duration>,
+
+    /// Only books at most this long
+    #[arg(long, value_name = "DURATION")]
+    pub max_length: Option<Duration>,
+
+    /// Only files of this format (e.g. m4b, mp3)
+    #[arg(long)]
+    pub format: Option<String>,
+}
+
+impl SearchFilterArgs {
+    /// Flags that make a search without text meaningful
+    pub const FLAGS: [&'static str; 9] = [
+        "author",
+        "narrator",
+        "tag",
+        "favorites",
+        "finished",
+        "unfinished",
+        "min_length",
+        "max_length",
+        "format",
+    ];
+}
+
+/// Online sources that can be searched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SourceKind {
+    Librivox,
+    Archive,
+    All,
+}
+
+/// How `import` treats books that already exist
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ImportStrategyArg {
+    /// Keep the existing book
+    Skip,
+    /// Replace the existing book
+    Overwrite,
+    /// Fill in missing details and keep the furthest progress
+    Merge,
+}
+
+/// Listening data formats read by `import-progress`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ProgressFormat {
+    /// Audiobookshelf API dump (library items, `/api/me` progress and bookmarks)
+    Abs,
+    /// CSV with a header row: path or title, author, position (seconds), finished
+    Csv,
+}
+
+/// Splits a `--config-override` assignment into key and value
+fn parse_assignment(assignment: &str) -> Result<(String, String), String> {
+    match assignment.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("expected KEY=VALUE, got '{}'", assignment)),
+    }
+}
+
+/// Settings from `--config-override`, set once before any command runs
+static CONFIG_OVERRIDES: OnceLock<ConfigOverride> = OnceLock::new();
+
+/// Puts `overrides` over the configuration every command reads, warning
+/// about any that will be ignored
+pub fn set_config_overrides(out: &Output, overrides: ConfigOverride) {
+    if let Ok(manager) = ConfigManager::new() {
+        let mut config = manager.load_or_default();
+        EnvOverride::load().merge_into(&mut config);
+        for error in overrides.merge_into(&mut config) {
+            out.warn(format!("Ignoring --config-override: {}", error));
+        }
+    }
+    let _ = CONFIG_OVERRIDES.set(overrides);
+}
+
+/// The configuration manager, with the `--config-override` settings applied
+/// by [`ConfigManager::load_effective`]
+pub fn config_manager() -> Result<ConfigManager> {
+    let overrides = CONFIG_OVERRIDES.get().cloned().unwrap_or_default();
+    Ok(ConfigManager::new()?.with_overrides(overrides))
+}
+
+/// Shows where configuration and data live, or the whole configuration
+pub fn show_config(out: &Output, full: bool) -> Result<()> {
+    let manager = config_manager()?;
+    let config = manager.load_effective();
+
+    if full {
+        return out.result(&config, || {
+            println!(
+                "StoryStream Configuration ({}):",
+                manager.config_path().display()
+            );
+            match toml::to_string_pretty(&config) {
+                Ok(text) => println!("{}", text),
+                Err(e) => println!("  (could not render configuration: {})", e),
+            }
+        });
+    }
+
+    let paths = serde_json::json!({
+        "config_path": manager.config_path(),
+        "database_path": config.library.database_path,
+    });
+    out.result(&paths, || {
+        println!("StoryStream Configuration:");
+        println!("  Database: {}", config.library.database_path);
+        println!("  Config: {}", manager.config_path().display());
+    })?;
+    out.info("\nUse --full to see complete configuration");
+    Ok(())
+}
+
+/// Opens the library database from the user's configuration
+pub async fn open_database() -> Result<DbPool> {
+    let config = config_manager()?.load_effective();
+    let pool = connect(DatabaseConfig::new(config.library.database_path))
+        .await
+        .context("Failed to connect to database")?;
+    run_migrations(&pool)
+        .await
+        .context("Failed to run database migrations")?;
+    Ok(pool)
+}
+
+/// Opens the library database for commands that only read it
+///
+/// Unlike [`open_database`], a missing database file is an error rather
+/// than a new, empty library.
+pub async fn open_existing_database() -> Result<DbPool> {
+    let config = config_manager()?.load_effective();
+    let path = PathBuf::from(&config.library.database_path);
+    if !path.exists() {
+        return Err(
+            anyhow::Error::new(AppError::FileNotFound { path: path.clone() }).context(format!(
+                "No library database at {}; run 'storystream scan' to create one",
+                path.display()
+            )),
+        );
+    }
+    open_database().await
+}
+
+/// Finds a book by ID, exact title, or unique partial title match
+pub async fn resolve_book(pool: &DbPool, query: &str) -> Result<Book> {
+    if let Ok(id) = BookId::from_string(query) {
+        return books::get_book(pool, id)
+            .await
+            .with_context(|| format!("No book with ID {}", query));
+    }
+
+    let needle = query.to_lowercase();
+    let all_books = books::list_books(pool)
+        .await
+        .context("Failed to load library")?;
+
+    if let Some(book) = all_books.iter().find(|b| b.title.to_lowercase() == needle) {
+        return Ok(book.clone());
+    }
+
+    let mut matches: Vec<Book> = all_books
+        .into_iter()
+        .filter(|b| b.title.to_lowercase().contains(&needle))
+        .collect();
+
+    match matches.len() {
+        0 => Err(anyhow::Error::new(AppError::RecordNotFound {
+            entity: "Book".to_string(),
+            identifier: query.to_string(),
+        })
+        .context(format!("No book matching '{}'", query))),
+        1 => Ok(matches.remove(0)),
+        _ => {
+            let titles: Vec<String> = matches.iter().map(|b| b.title.clone()).collect();
+            bail!("'{}' matches several books: {}", query, titles.join(", "))
+        }
+    }
+}
+
+/// Returns `<first library path>/<subdir>` for downloaded content
+///
+/// Falls back to `<subdir>` under the working directory when no library
+/// path is configured.
+pub fn download_dir(subdir: &str) -> Result<PathBuf> {
+    let config = config_manager()?.load_effective();
+    Ok(config
+        .library
+        .library_paths
+        .first()
+        .map(|p| p.join(subdir))
+        .unwrap_or_else(|| PathBuf::from(subdir)))
+}
+
+/// Download history kept in the config directory
+pub fn download_history() -> Result<DownloadHistory> {
+    let path = ConfigManager::new()?
+        .config_dir()
+        .join(DownloadHistory::FILE_NAME);
+    Ok(DownloadHistory::new(path)?)
+}
+
+/// Caches kept in the config directory, within the configured budget
+pub fn open_cache() -> Result<CacheManager> {
+    let manager = config_manager()?;
+    let budget = manager.load_effective().app.cache_max_bytes();
+    CacheManager::open(manager.cache_dir(), budget).context("Failed to open the cache")
+}
+
+/// Asks a yes/no question on stdin, defaulting to no
+pub fn confirm(prompt: &str) -> Result<bool> {
+    use std::io::Write;
+
+    print!("{} [y/N] ", prompt);
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Replaces characters that are not allowed in file names on common platforms
+pub fn sanitize_filename(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let cleaned = truncate(cleaned.trim(), 100);
+    if cleaned.is_empty() {
+        "untitled".to_string()
+    } else {
+        cleaned
+    }
+}
+
+/// Truncates a string to `max` characters, ending with "..." when shortened
+pub fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        return s.to_string();
+    }
+    let kept: String = s.chars().take(max.saturating_sub(3)).collect();
+    format!("{}...", kept.trim_end())
+}
+
+/// Formats seconds as a compact human-readable duration
+pub fn format_duration(secs: u64) -> String {
+    let hours = secs / 3600;
+    let minutes = (secs % 3600) / 60;
+    let seconds = secs % 60;
+
+    if hours > 0 {
+        format!("{}h {:02}m {:02}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m {:02}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,216 @@
+// crates/cli/src/commands/bookmark.rs
+//! Bookmark management subcommands
+
+use super::{open_database, resolve_book, truncate, BookmarkAction, Output};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{Local, TimeZone};
+use serde::Serialize;
+use std::collections::{hash_map::Entry, HashMap};
+use storystream_core::{Bookmark, BookmarkId, SharedBookmark, Timestamp};
+use storystream_database::{queries::bookmarks, queries::books, DbPool};
+use storystream_library::{resolve_shared, SharedTarget};
+
+/// Title of bookmarks added by `open`
+const SHARED_TITLE: &str = "Shared with me";
+
+/// Bookmark as reported by `bookmark list` and `bookmark add`
+#[derive(Debug, Serialize)]
+struct BookmarkRecord {
+    id: String,
+    book_id: String,
+    book_title: String,
+    position: String,
+    position_ms: u64,
+    title: Option<String>,
+    note: Option<String>,
+    created_at: String,
+}
+
+/// Executes a bookmark subcommand
+pub async fn run(out: &Output, action: BookmarkAction) -> Result<()> {
+    let pool = open_database().await?;
+
+    match action {
+        BookmarkAction::List { book } => list(out, &pool, book.as_deref()).await,
+        BookmarkAction::Add {
+            book,
+            at,
+            title,
+            note,
+        } => {
+            let book = resolve_book(&pool, &book).await?;
+
+            let mut bookmark = Bookmark::new(book.id, at);
+            bookmark.title = title;
+            bookmark.note = note;
+
+            if let Err(errors) = bookmark.validate_for_book(book.duration) {
+                bail!("Invalid bookmark: {}", errors.join("; "));
+            }
+
+            bookmarks::create_bookmark(&pool, &bookmark)
+                .await
+                .context("Failed to save bookmark")?;
+
+            let record = to_record(&bookmark, &book.title);
+            out.result(&record, || {
+                println!(
+                    "Added bookmark {} at {} in '{}'",
+                    bookmark.id, bookmark.position, book.title
+                )
+            })
+        }
+        BookmarkAction::Remove { id } => {
+            let id = BookmarkId::from_string(&id)
+                .with_context(|| format!("Invalid bookmark ID '{}'", id))?;
+
+            bookmarks::get_bookmark(&pool, id)
+                .await
+                .with_context(|| format!("No bookmark with ID {}", id))?;
+            bookmarks::delete_bookmark(&pool, id)
+                .await
+                .context("Failed to remove bookmark")?;
+
+            out.result(&serde_json::json!({ "removed": id.as_string() }), || {
+                println!("Removed bookmark {}", id)
+            })
+        }
+        BookmarkAction::Share { id } => {
+            let id = BookmarkId::from_string(&id)
+                .with_context(|| format!("Invalid bookmark ID '{}'", id))?;
+
+            let bookmark = bookmarks::get_bookmark(&pool, id)
+                .await
+                .with_context(|| format!("No bookmark with ID {}", id))?;
+            let book = books::get_book(&pool, bookmark.book_id)
+                .await
+                .context("Failed to load the bookmarked book")?;
+            let hashes = books::get_file_hashes(&pool)
+                .await
+                .context("Failed to load file hashes")?;
+
+            let text = bookmark.to_share_string(&book, hashes.get(&book.id).map(String::as_str));
+            out.result(&serde_json::json!({ "text": text }), || {
+                println!("{}", text)
+            })
+        }
+    }
+}
+
+/// Opens a shared bookmark link
+///
+/// A book in the library gets a bookmark at the shared position. Otherwise
+/// the error says where the book can be fetched, if the link knows.
+pub async fn open(out: &Output, link: &str) -> Result<()> {
+    let shared = SharedBookmark::parse(link).map_err(|e| anyhow!(e))?;
+    let pool = open_database().await?;
+
+    let book = match resolve_shared(&pool, &shared).await? {
+        SharedTarget::Local(book) => book,
+        SharedTarget::Fetch(source) => bail!(
+            "'{}' is not in your library. Find it with `storystream source search \"{}\" --source {}`",
+            shared.title,
+            shared.title,
+            source.source
+        ),
+        SharedTarget::Missing => bail!("'{}' is not in your library", shared.title),
+    };
+
+    let bookmark = Bookmark::with_title(book.id, shared.position, SHARED_TITLE.to_string());
+    if let Err(errors) = bookmark.validate_for_book(book.duration) {
+        bail!("Invalid shared position: {}", errors.join("; "));
+    }
+    bookmarks::create_bookmark(&pool, &bookmark)
+        .await
+        .context("Failed to save bookmark")?;
+
+    let record = to_record(&bookmark, &book.title);
+    out.result(&record, || {
+        println!(
+            "Bookmarked {} in '{}'; jump there from the bookmarks view",
+            bookmark.position, book.title
+        )
+    })
+}
+
+/// Lists bookmarks for one book, or the whole library
+async fn list(out: &Output, pool: &DbPool, book: Option<&str>) -> Result<()> {
+    let (marks, titles) = match book {
+        Some(query) => {
+            let book = resolve_book(pool, query).await?;
+            let marks = bookmarks::get_book_bookmarks(pool, book.id).await?;
+            (marks, HashMap::from([(book.id, book.title)]))
+        }
+        None => {
+            let marks = bookmarks::list_bookmarks(pool).await?;
+            let mut titles = HashMap::new();
+            for mark in &marks {
+                if let Entry::Vacant(slot) = titles.entry(mark.book_id) {
+                    let title = books::get_book(pool, mark.book_id)
+                        .await
+                        .map(|b| b.title)
+                        .unwrap_or_else(|_| "(unknown book)".to_string());
+                    slot.insert(title);
+                }
+            }
+            (marks, titles)
+        }
+    };
+
+    let records: Vec<BookmarkRecord> = marks
+        .iter()
+        .map(|mark| {
+            let title = titles.get(&mark.book_id).map(String::as_str);
+            to_record(mark, title.unwrap_or_default())
+        })
+        .collect();
+
+    out.result(&records, || {
+        if records.is_empty() {
+            println!("No bookmarks found");
+            return;
+        }
+
+        println!(
+            "{:<36}  {:<24}  {:>9}  {:<16}  NOTE",
+            "ID", "BOOK", "POSITION", "CREATED"
+        );
+        for record in &records {
+            let note = record
+                .note
+                .as_deref()
+                .or(record.title.as_deref())
+                .unwrap_or("");
+            println!(
+                "{:<36}  {:<24}  {:>9}  {:<16}  {}",
+                record.id,
+                truncate(&record.book_title, 24),
+                record.position,
+                record.created_at,
+                note
+            );
+        }
+    })
+}
+
+fn to_record(mark: &Bookmark, book_title: &str) -> BookmarkRecord {
+    BookmarkRecord {
+        id: mark.id.as_string(),
+        book_id: mark.book_id.as_string(),
+        book_title: book_title.to_string(),
+        position: mark.position.as_hms(),
+        position_ms: mark.position.as_millis(),
+        title: mark.title.clone(),
+        note: mark.note.clone(),
+        created_at: format_timestamp(mark.created_at),
+    }
+}
+
+/// Formats a timestamp in local time for display
+fn format_timestamp(ts: Timestamp) -> String {
+    Local
+        .timestamp_millis_opt(ts.as_millis())
+        .single()
+        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| ts.to_string())
+}
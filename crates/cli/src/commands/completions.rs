@@ -0,0 +1,58 @@
+// crates/cli/src/commands/completions.rs
+//! Shell completion and man page generation for packagers
+
+use super::{Cli, Output};
+use anyhow::Result;
+use clap::CommandFactory;
+use clap_complete::Shell;
+use serde_json::json;
+use std::io::Write;
+
+/// Binary name used in generated scripts
+const BIN_NAME: &str = "storystream";
+
+/// Prints the completion script for `shell`, wrapped in an envelope with `--json`
+pub fn completions(out: &Output, shell: Shell) -> Result<()> {
+    let mut script = Vec::new();
+    write_completions(shell, &mut script)?;
+    let script = String::from_utf8(script)?;
+
+    let data = json!({ "shell": shell.to_string(), "script": script });
+    print_raw(out, &data, &script)
+}
+
+/// Prints the man page, wrapped in an envelope with `--json`
+pub fn manpage(out: &Output) -> Result<()> {
+    let mut page = Vec::new();
+    write_manpage(&mut page)?;
+    let page = String::from_utf8(page)?;
+
+    print_raw(out, &json!({ "manpage": page }), &page)
+}
+
+/// Writes `text` verbatim unless JSON output was requested
+fn print_raw(out: &Output, data: &serde_json::Value, text: &str) -> Result<()> {
+    let mut written = Ok(());
+    out.result(data, || {
+        written = std::io::stdout().write_all(text.as_bytes())
+    })?;
+    Ok(written?)
+}
+
+/// Writes the completion script for `shell`
+///
+/// The script is rendered to memory first because the generator panics on
+/// write errors, such as a closed pipe.
+pub fn write_completions(shell: Shell, out: &mut dyn Write) -> Result<()> {
+    let mut command = Cli::command();
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut command, BIN_NAME, &mut script);
+    out.write_all(&script)?;
+    Ok(())
+}
+
+/// Writes the roff man page for the top-level command
+pub fn write_manpage(out: &mut dyn Write) -> Result<()> {
+    clap_mangen::Man::new(Cli::command()).render(out)?;
+    Ok(())
+}
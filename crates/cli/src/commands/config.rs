@@ -0,0 +1,117 @@
+// crates/cli/src/commands/config.rs
+//! Checking the config file and describing its format
+
+use super::{config_manager, ConfigAction, Output};
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use std::path::PathBuf;
+use storystream_config::{schema, ValidationError};
+
+/// Stable JSON schema for `config validate --json`
+#[derive(Debug, Serialize)]
+struct ValidateOutput {
+    path: PathBuf,
+    /// Whether the file exists; without one the defaults are used
+    exists: bool,
+    errors: Vec<InvalidSetting>,
+}
+
+#[derive(Debug, Serialize)]
+struct InvalidSetting {
+    /// Path of the setting, such as `player.default_volume`
+    key: String,
+    value: Option<String>,
+    message: String,
+    /// What the setting accepts, such as `0 to 100`
+    accepted: Option<String>,
+}
+
+pub fn run(out: &Output, action: ConfigAction) -> Result<()> {
+    match action {
+        ConfigAction::Validate => validate(out),
+        ConfigAction::Schema => {
+            let document = schema::json_schema();
+            let text = serde_json::to_string_pretty(&document)?;
+            out.result(&document, || println!("{}", text))
+        }
+    }
+}
+
+/// Checks the config file as written, without environment variables or
+/// `--config-override` settings
+fn validate(out: &Output) -> Result<()> {
+    let manager = config_manager()?;
+    let path = manager.config_path();
+    let config = manager
+        .load()
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let settings = toml::Value::try_from(&config).ok();
+
+    let errors: Vec<InvalidSetting> = config
+        .validate()
+        .err()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|error| invalid_setting(error, settings.as_ref()))
+        .collect();
+    let output = ValidateOutput {
+        exists: path.exists(),
+        path,
+        errors,
+    };
+
+    if output.errors.is_empty() {
+        return out.result(&output, || print_output(&output));
+    }
+    if !out.is_json() {
+        print_output(&output);
+    }
+    Err(out.fail(
+        &output,
+        anyhow!("{} invalid setting(s)", output.errors.len()),
+    ))
+}
+
+fn invalid_setting(error: ValidationError, settings: Option<&toml::Value>) -> InvalidSetting {
+    // Validators do not all report the value they rejected
+    let value = error.value.or_else(|| {
+        error
+            .field
+            .split('.')
+            .try_fold(settings?, |table, name| table.get(name))
+            .map(|value| value.to_string())
+    });
+    InvalidSetting {
+        accepted: schema::accepted_values(&error.field),
+        key: error.field,
+        value,
+        message: error.message,
+    }
+}
+
+fn print_output(output: &ValidateOutput) {
+    if !output.exists {
+        println!(
+            "No config file at {}; the defaults are used",
+            output.path.display()
+        );
+        return;
+    }
+    if output.errors.is_empty() {
+        println!("{} is valid", output.path.display());
+        return;
+    }
+
+    println!("{}", output.path.display());
+    for error in &output.errors {
+        println!("\n  {}", error.key);
+        if let Some(value) = &error.value {
+            println!("    {:<9} {}", "value:", value);
+        }
+        println!("    {:<9} {}", "problem:", error.message);
+        if let Some(accepted) = &error.accepted {
+            println!("    {:<9} {}", "accepted:", accepted);
+        }
+    }
+    println!();
+}
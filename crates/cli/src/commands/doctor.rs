@@ -0,0 +1,783 @@
+// crates/cli/src/commands/doctor.rs
+//! Database, configuration and library health checks
+
+use super::{
+    config_manager, confirm, download_history, format_duration, open_database, truncate, Output,
+};
+use anyhow::{anyhow, Result};
+use console::style;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::io::Write;
+use storystream_config::ConfigManager;
+use storystream_core::{Book, BookId, Duration, Timestamp};
+use storystream_database::{maintenance, optimize, queries::books, verify_integrity, DbPool};
+use storystream_library::{
+    DuplicateGroup, FileProblem, FileVerifier, Janitor, LibraryManager, SuggestedAction,
+    VerifyDepth, VerifyEvent, VerifyReport, VerifyScope,
+};
+use storystream_media_formats::AudioAnalyzer;
+use storystream_network::DownloadRecord;
+use tokio::sync::mpsc;
+
+/// Number of affected books listed under a check before eliding the rest
+const MAX_LISTED: usize = 5;
+
+/// Outcome of a single check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// A single line of the doctor report
+#[derive(Debug, Serialize)]
+struct Check {
+    name: &'static str,
+    status: Status,
+    summary: String,
+    details: Vec<String>,
+    /// What `--fix` changed, if it repaired the problem
+    fixed: Option<String>,
+}
+
+impl Check {
+    fn new(name: &'static str, status: Status, summary: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            summary: summary.into(),
+            details: Vec::new(),
+            fixed: None,
+        }
+    }
+
+    fn with_details(mut self, details: Vec<String>) -> Self {
+        self.details = details;
+        self
+    }
+
+    /// Whether the check still fails after any repair
+    fn is_failure(&self) -> bool {
+        self.status == Status::Fail && self.fixed.is_none()
+    }
+
+    fn print(&self) {
+        let label = match self.status {
+            Status::Pass => style("PASS").green().bold(),
+            Status::Warn => style("WARN").yellow().bold(),
+            Status::Fail => style("FAIL").red().bold(),
+        };
+        println!("[{}] {:<18} {}", label, self.name, self.summary);
+
+        for detail in self.details.iter().take(MAX_LISTED) {
+            println!("       - {}", detail);
+        }
+        if self.details.len() > MAX_LISTED {
+            println!("       ... and {} more", self.details.len() - MAX_LISTED);
+        }
+        if let Some(ref fixed) = self.fixed {
+            println!("       {} {}", style("fixed:").cyan(), fixed);
+        }
+    }
+}
+
+/// Full doctor report as emitted with `--json`
+#[derive(Debug, Serialize)]
+struct Report {
+    checks: Vec<Check>,
+    failures: usize,
+    warnings: usize,
+}
+
+/// What a doctor run checks and repairs beyond the basic checks
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    /// Apply safe repairs for the problems found
+    pub fix: bool,
+    /// Decode suspect audio files to verify they are readable
+    pub verify_audio: bool,
+    /// Re-check every book file against its stored hash, to this depth
+    pub verify_files: Option<VerifyDepth>,
+    /// Remove download history older than this
+    pub prune_downloads: Option<Duration>,
+    /// Look for books imported more than once and offer to merge them
+    pub duplicates: bool,
+    /// Run the database's routine upkeep
+    pub maintenance: bool,
+    /// Remove temporary files left behind by crashes
+    pub clean: bool,
+}
+
+/// Runs every check, applies repairs when `options.fix` is set, and prints
+/// the report
+///
+/// Returns an error when a failure remains, so the process exits non-zero.
+pub async fn run(out: &Output, options: Options) -> Result<()> {
+    let Options {
+        fix,
+        verify_audio,
+        verify_files,
+        prune_downloads,
+        duplicates,
+        maintenance,
+        clean,
+    } = options;
+    let pool = open_database().await?;
+    let library = books::list_books(&pool).await?;
+
+    let mut checks = vec![
+        check_database(&pool).await,
+        check_search_index(&pool, fix).await?,
+        check_config(),
+        check_missing_files(&pool, &library, fix).await?,
+        check_orphans(&pool, fix).await?,
+        check_downloads(prune_downloads).await?,
+        check_leftovers(clean).await,
+    ];
+    if verify_audio {
+        checks.push(check_audio(&library));
+    }
+    if let Some(depth) = verify_files {
+        checks.push(check_file_integrity(out, &pool, depth, fix).await?);
+    }
+    if duplicates {
+        checks.push(check_duplicates(out, &pool).await?);
+    }
+    if maintenance {
+        checks.push(run_maintenance(&pool).await);
+    }
+
+    let failures = checks.iter().filter(|c| c.is_failure()).count();
+    let warnings = checks
+        .iter()
+        .filter(|c| c.status == Status::Warn && c.fixed.is_none())
+        .count();
+    let report = Report {
+        checks,
+        failures,
+        warnings,
+    };
+
+    if failures > 0 {
+        if !out.is_json() {
+            print_report(&report, fix);
+        }
+        return Err(out.fail(
+            &report,
+            anyhow!(
+                "{} check{} failed, {} warning{}",
+                failures,
+                if failures == 1 { "" } else { "s" },
+                warnings,
+                if warnings == 1 { "" } else { "s" }
+            ),
+        ));
+    }
+
+    out.result(&report, || {
+        print_report(&report, fix);
+        println!("{}", style("All checks passed").green());
+    })
+}
+
+fn print_report(report: &Report, fix: bool) {
+    for check in &report.checks {
+        check.print();
+    }
+
+    let fixes: Vec<&str> = report
+        .checks
+        .iter()
+        .filter_map(|c| c.fixed.as_deref())
+        .collect();
+
+    println!();
+    if fix {
+        if fixes.is_empty() {
+            println!("No repairs were needed.");
+        } else {
+            println!("Repairs applied:");
+            for fixed in &fixes {
+                println!("  - {}", fixed);
+            }
+        }
+    } else if report.checks.iter().any(|c| c.status != Status::Pass) {
+        println!("Run `storystream doctor --fix` to apply safe repairs.");
+    }
+}
+
+async fn check_database(pool: &DbPool) -> Check {
+    match verify_integrity(pool).await {
+        Ok(()) => Check::new("Database", Status::Pass, "integrity check ok"),
+        Err(e) => Check::new("Database", Status::Fail, e.to_string()),
+    }
+}
+
+async fn run_maintenance(pool: &DbPool) -> Check {
+    match optimize(pool).await {
+        Ok(report) => Check::new(
+            "Maintenance",
+            Status::Pass,
+            format!(
+                "done in {} ms, {:.1} MB reclaimed",
+                report.elapsed_ms(),
+                report.reclaimed_bytes as f64 / 1_000_000.0
+            ),
+        )
+        .with_details(
+            report
+                .steps
+                .iter()
+                .map(|step| format!("{}: {} ({} ms)", step.name, step.detail, step.elapsed_ms))
+                .collect(),
+        ),
+        Err(e) => Check::new("Maintenance", Status::Fail, e.to_string()),
+    }
+}
+
+async fn check_search_index(pool: &DbPool, fix: bool) -> Result<Check> {
+    let damaged = maintenance::check_search_index(pool).await?;
+    let mut check = if damaged.is_empty() {
+        Check::new("Search index", Status::Pass, "consistent")
+    } else {
+        Check::new(
+            "Search index",
+            Status::Warn,
+            format!("{} index(es) out of date", damaged.len()),
+        )
+        .with_details(damaged.iter().map(|t| t.to_string()).collect())
+    };
+
+    if fix && !damaged.is_empty() {
+        maintenance::rebuild_search_index(pool).await?;
+        check.fixed = Some("rebuilt the search index".to_string());
+    }
+    Ok(check)
+}
+
+fn check_config() -> Check {
+    let manager = match ConfigManager::new() {
+        Ok(manager) => manager,
+        Err(e) => return Check::new("Configuration", Status::Fail, e.to_string()),
+    };
+
+    if !manager.config_path().exists() {
+        return Check::new(
+            "Configuration",
+            Status::Pass,
+            "no config file, using defaults",
+        );
+    }
+
+    match manager.validate() {
+        Ok(errors) if errors.is_empty() => Check::new(
+            "Configuration",
+            Status::Pass,
+            manager.config_path().display().to_string(),
+        ),
+        Ok(errors) => Check::new(
+            "Configuration",
+            Status::Fail,
+            format!("{} invalid setting(s)", errors.len()),
+        )
+        .with_details(errors),
+        Err(e) => Check::new("Configuration", Status::Fail, e.to_string()),
+    }
+}
+
+async fn check_missing_files(pool: &DbPool, library: &[Book], fix: bool) -> Result<Check> {
+    let missing: Vec<&Book> = library.iter().filter(|b| !b.file_path.exists()).collect();
+
+    if missing.is_empty() {
+        return Ok(Check::new(
+            "Library files",
+            Status::Pass,
+            format!("{} book(s), all files present", library.len()),
+        ));
+    }
+
+    let mut check = Check::new(
+        "Library files",
+        Status::Fail,
+        format!(
+            "{} of {} book file(s) missing",
+            missing.len(),
+            library.len()
+        ),
+    )
+    .with_details(
+        missing
+            .iter()
+            .map(|b| format!("{} ({})", truncate(&b.title, 40), b.file_path.display()))
+            .collect(),
+    );
+
+    if fix {
+        for book in &missing {
+            let mut book = (*book).clone();
+            book.deleted_at = Some(Timestamp::now());
+            books::update_book(pool, &book).await?;
+        }
+        check.fixed = Some(format!(
+            "removed {} book(s) with missing files from the library",
+            missing.len()
+        ));
+    }
+    Ok(check)
+}
+
+/// Looks for temporaries that crashes left behind, removing them with `clean`
+///
+/// Partial downloads the history can still resume are left alone.
+async fn check_leftovers(clean: bool) -> Check {
+    let manager = match config_manager() {
+        Ok(manager) => manager,
+        Err(e) => return Check::new("Leftover files", Status::Warn, e.to_string()),
+    };
+    let config = manager.load_effective();
+    let janitor = Janitor::for_config(manager.config_dir(), &config);
+    let protected =
+        async { Ok::<_, anyhow::Error>(janitor.protect_resumable(&download_history()?).await?) };
+    let janitor = match protected.await {
+        Ok(janitor) => janitor,
+        Err(e) => {
+            return Check::new(
+                "Leftover files",
+                Status::Warn,
+                format!("cannot read the download history: {}", e),
+            )
+        }
+    };
+
+    let leftovers = janitor.find();
+    if leftovers.is_empty() {
+        return Check::new(
+            "Leftover files",
+            Status::Pass,
+            "no leftover temporary files",
+        );
+    }
+    let bytes: u64 = leftovers.iter().map(|leftover| leftover.bytes).sum();
+    let mut check = Check::new(
+        "Leftover files",
+        Status::Warn,
+        format!(
+            "{} leftover file(s), {:.1} MB (--clean removes them)",
+            leftovers.len(),
+            bytes as f64 / 1_000_000.0
+        ),
+    )
+    .with_details(
+        leftovers
+            .iter()
+            .map(|leftover| format!("{} ({})", leftover.path.display(), leftover.kind.describe()))
+            .collect(),
+    );
+
+    if clean {
+        let report = janitor.clean();
+        if report.failed.is_empty() {
+            check.fixed = Some(format!(
+                "removed {} leftover file(s), {:.1} MB",
+                report.removed.len(),
+                report.bytes_freed() as f64 / 1_000_000.0
+            ));
+        } else {
+            check.summary = format!(
+                "{} of {} leftover file(s) could not be removed",
+                report.failed.len(),
+                leftovers.len()
+            );
+            check.details = report
+                .failed
+                .iter()
+                .map(|(leftover, e)| format!("{}: {}", leftover.path.display(), e))
+                .collect();
+        }
+    }
+    check
+}
+
+async fn check_orphans(pool: &DbPool, fix: bool) -> Result<Check> {
+    let orphans = maintenance::find_orphans(pool).await?;
+    if orphans.total() == 0 {
+        return Ok(Check::new("Orphaned records", Status::Pass, "none"));
+    }
+
+    let details = [
+        ("chapters", orphans.chapters),
+        ("bookmarks", orphans.bookmarks),
+        ("playback states", orphans.playback_states),
+        ("playlist items", orphans.playlist_items),
+        ("podcast episodes", orphans.podcast_episodes),
+    ]
+    .iter()
+    .filter(|(_, count)| *count > 0)
+    .map(|(name, count)| format!("{} {}", count, name))
+    .collect();
+
+    let mut check = Check::new(
+        "Orphaned records",
+        Status::Warn,
+        format!("{} record(s) reference deleted items", orphans.total()),
+    )
+    .with_details(details);
+
+    if fix {
+        let purged = maintenance::purge_orphans(pool).await?;
+        check.fixed = Some(format!("purged {} orphaned record(s)", purged.total()));
+    }
+    Ok(check)
+}
+
+/// Reports books imported more than once, offering to merge each group
+///
+/// Merging needs someone to pick the copy to keep, so JSON output only
+/// reports the groups.
+async fn check_duplicates(out: &Output, pool: &DbPool) -> Result<Check> {
+    let manager = LibraryManager::with_pool(pool.clone());
+    let groups = manager.find_duplicates().await?;
+    if groups.is_empty() {
+        return Ok(Check::new("Duplicates", Status::Pass, "none"));
+    }
+
+    let mut check = Check::new(
+        "Duplicates",
+        Status::Warn,
+        format!("{} group(s) of duplicate books", groups.len()),
+    )
+    .with_details(
+        groups
+            .iter()
+            .map(|g| {
+                format!(
+                    "{} ({} copies, {})",
+                    truncate(&g.books[0].title, 40),
+                    g.books.len(),
+                    g.reason.describe()
+                )
+            })
+            .collect(),
+    );
+    if out.is_json() {
+        return Ok(check);
+    }
+
+    let mut merged = 0;
+    for (i, group) in groups.iter().enumerate() {
+        println!();
+        println!(
+            "{} {} of {}: {}",
+            style("Duplicates").bold(),
+            i + 1,
+            groups.len(),
+            group.reason.describe()
+        );
+        match choose_copy(group)? {
+            Some(keep) => {
+                let remove = group.others(keep);
+                merged += remove.len();
+                manager.merge_books(keep, remove).await?;
+            }
+            None => println!("Skipped."),
+        }
+    }
+    println!();
+
+    if merged > 0 {
+        check.fixed = Some(format!("merged {} duplicate book(s)", merged));
+    }
+    Ok(check)
+}
+
+/// Lists the copies of a group and asks which to keep; `None` skips the group
+fn choose_copy(group: &DuplicateGroup) -> Result<Option<BookId>> {
+    let suggested = group.suggested_keep();
+    for (i, book) in group.books.iter().enumerate() {
+        println!(
+            "  {}{}. {} by {} ({}, {:.1} MB) {}",
+            if book.id == suggested { "*" } else { " " },
+            i + 1,
+            truncate(&book.title, 40),
+            book.author.as_deref().unwrap_or("Unknown"),
+            format_duration(book.duration.as_seconds()),
+            book.file_size as f64 / (1024.0 * 1024.0),
+            style(book.file_path.display()).dim()
+        );
+    }
+
+    let count = group.books.len();
+    let default = group
+        .books
+        .iter()
+        .position(|b| b.id == suggested)
+        .unwrap_or(0)
+        + 1;
+    let index = loop {
+        print!(
+            "Keep which copy? [1-{}, Enter for {}, s to skip] ",
+            count, default
+        );
+        std::io::stdout().flush()?;
+
+        let mut answer = String::new();
+        if std::io::stdin().read_line(&mut answer)? == 0 {
+            return Ok(None);
+        }
+        match answer.trim() {
+            "" => break default,
+            "s" | "S" => return Ok(None),
+            n => match n.parse::<usize>() {
+                Ok(n) if (1..=count).contains(&n) => break n,
+                _ => println!("Enter a number from 1 to {}.", count),
+            },
+        }
+    };
+
+    let keep = &group.books[index - 1];
+    let prompt = format!(
+        "Merge {} other cop{} into \"{}\"?",
+        count - 1,
+        if count == 2 { "y" } else { "ies" },
+        truncate(&keep.title, 40)
+    );
+    Ok(confirm(&prompt)?.then_some(keep.id))
+}
+
+/// Number of download history records read when looking for failures
+const DOWNLOAD_HISTORY_LIMIT: usize = 1000;
+
+/// Reports downloads whose last attempt failed, pruning old records on request
+async fn check_downloads(prune: Option<Duration>) -> Result<Check> {
+    let history = download_history()?;
+    let pruned = match prune {
+        Some(age) => Some(
+            history
+                .prune(chrono::Duration::milliseconds(age.as_millis() as i64))
+                .await?,
+        ),
+        None => None,
+    };
+
+    let records = history.recent(DOWNLOAD_HISTORY_LIMIT).await?;
+    let failed = latest_failures(&records);
+    let mut check = if failed.is_empty() {
+        Check::new(
+            "Download history",
+            Status::Pass,
+            format!("{} record(s), no failed downloads", records.len()),
+        )
+    } else {
+        Check::new(
+            "Download history",
+            Status::Warn,
+            format!("{} download(s) failed", failed.len()),
+        )
+        .with_details(
+            failed
+                .iter()
+                .map(|r| {
+                    format!(
+                        "{} ({})",
+                        truncate(&r.url, 60),
+                        r.error.as_deref().unwrap_or("unknown error")
+                    )
+                })
+                .collect(),
+        )
+    };
+
+    if let Some(pruned) = pruned.filter(|&n| n > 0) {
+        check.fixed = Some(format!("pruned {} old download record(s)", pruned));
+    }
+    Ok(check)
+}
+
+/// Failed records of tasks that have not succeeded since, newest first
+fn latest_failures(records: &[DownloadRecord]) -> Vec<&DownloadRecord> {
+    let mut seen = HashSet::new();
+    records
+        .iter()
+        .filter(|r| seen.insert(r.task_id.as_str()))
+        .filter(|r| r.is_failed())
+        .collect()
+}
+
+/// Decodes books whose stored metadata no longer matches the file on disk
+fn check_audio(library: &[Book]) -> Check {
+    let analyzer = match AudioAnalyzer::new() {
+        Ok(analyzer) => analyzer,
+        Err(e) => return Check::new("Audio files", Status::Fail, e.to_string()),
+    };
+
+    let suspects: Vec<&Book> = library
+        .iter()
+        .filter(|b| b.file_path.exists() && is_suspect(b))
+        .collect();
+
+    let unreadable: Vec<String> = suspects
+        .iter()
+        .filter_map(|b| {
+            analyzer
+                .analyze(&b.file_path)
+                .err()
+                .map(|e| format!("{}: {}", truncate(&b.title, 40), e))
+        })
+        .collect();
+
+    if unreadable.is_empty() {
+        Check::new(
+            "Audio files",
+            Status::Pass,
+            format!("{} suspect file(s) verified", suspects.len()),
+        )
+    } else {
+        Check::new(
+            "Audio files",
+            Status::Fail,
+            format!(
+                "{} of {} suspect file(s) unreadable",
+                unreadable.len(),
+                suspects.len()
+            ),
+        )
+        .with_details(unreadable)
+    }
+}
+
+/// A book is suspect when its file changed size since import or it has no duration
+fn is_suspect(book: &Book) -> bool {
+    let size_changed = std::fs::metadata(&book.file_path)
+        .map(|m| m.len() != book.file_size)
+        .unwrap_or(true);
+    size_changed || book.duration.as_millis() == 0
+}
+
+/// Re-hashes every book file; Ctrl-C stops early and reports what was found
+async fn check_file_integrity(
+    out: &Output,
+    pool: &DbPool,
+    depth: VerifyDepth,
+    fix: bool,
+) -> Result<Check> {
+    let (tx, mut rx) = mpsc::channel(64);
+    let verifier = FileVerifier::new(pool.clone()).with_events(tx);
+
+    let cancel = verifier.cancel_handle();
+    let interrupt = tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            cancel.cancel();
+        }
+    });
+    let show_progress = out.shows_progress();
+    let progress = tokio::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            match event {
+                VerifyEvent::Checked { done, total, title } if show_progress => {
+                    eprint!("\r\x1b[2K[{}/{}] {}", done, total, truncate(&title, 50));
+                }
+                VerifyEvent::Finished { checked, .. } => {
+                    if show_progress && checked > 0 {
+                        eprintln!();
+                    }
+                    break;
+                }
+                _ => {}
+            }
+        }
+    });
+
+    let report = verifier.verify(VerifyScope::All, depth).await;
+    interrupt.abort();
+    if report.is_ok() {
+        let _ = progress.await;
+    } else {
+        progress.abort();
+    }
+    let report = report?;
+
+    let mut check = file_integrity_check(&report);
+    if fix && !report.cancelled {
+        let (mut updated, mut marked) = (0, 0);
+        for issue in &report.issues {
+            let action = match issue.problem {
+                FileProblem::Changed => SuggestedAction::UpdateHash,
+                FileProblem::Corrupt(_) => SuggestedAction::MarkCorrupt,
+                FileProblem::Missing => continue,
+            };
+            verifier.apply(issue, action).await?;
+            match action {
+                SuggestedAction::UpdateHash => updated += 1,
+                _ => marked += 1,
+            }
+        }
+        if updated + marked > 0 {
+            check.fixed = Some(format!(
+                "stored {} new hash(es), tagged {} book(s) corrupt",
+                updated, marked
+            ));
+        }
+    }
+    Ok(check)
+}
+
+fn file_integrity_check(report: &VerifyReport) -> Check {
+    let changed = report.count(|p| *p == FileProblem::Changed);
+    let corrupt = report.count(|p| matches!(p, FileProblem::Corrupt(_)));
+    let missing = report.count(|p| *p == FileProblem::Missing);
+
+    let mut parts: Vec<String> = [
+        (changed, "changed"),
+        (corrupt, "corrupt"),
+        (missing, "missing"),
+    ]
+    .iter()
+    .filter(|(count, _)| *count > 0)
+    .map(|(count, problem)| format!("{} {}", count, problem))
+    .collect();
+    parts.push(match report.depth {
+        VerifyDepth::Hash => format!("{} file(s) verified", report.checked),
+        VerifyDepth::Full => format!("{} file(s) verified and decoded", report.checked),
+    });
+    if report.baselined > 0 {
+        parts.push(format!("{} hash(es) recorded", report.baselined));
+    }
+    if report.skipped > 0 {
+        parts.push(format!("{} skipped as corrupt", report.skipped));
+    }
+    let mut summary = parts.join(", ");
+    if report.cancelled {
+        summary.push_str(" before cancelling");
+    }
+
+    let status = if corrupt + missing > 0 {
+        Status::Fail
+    } else if changed > 0 || report.cancelled {
+        Status::Warn
+    } else {
+        Status::Pass
+    };
+
+    let details = report
+        .issues
+        .iter()
+        .map(|issue| {
+            let problem = match &issue.problem {
+                FileProblem::Missing => "missing".to_string(),
+                FileProblem::Changed => "changed".to_string(),
+                FileProblem::Corrupt(reason) => format!("corrupt ({})", reason),
+            };
+            let actions: Vec<&str> = issue.actions.iter().map(|a| a.describe()).collect();
+            format!(
+                "{}: {}; suggest: {}",
+                truncate(&issue.title, 40),
+                problem,
+                actions.join(" or ")
+            )
+        })
+        .collect();
+    Check::new("File integrity", status, summary).with_details(details)
+}
@@ -0,0 +1,645 @@
+// crates/cli/src/commands/feed.rs
+//! Feed subscription subcommands
+
+use super::{
+    download_dir, download_history, open_database, sanitize_filename, truncate, FeedAction, Output,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Serialize;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use storystream_core::types::Validator;
+use storystream_core::{DownloadPolicy, Podcast, PodcastEpisode, PodcastId, Timestamp};
+use storystream_database::{queries::podcasts, DbPool};
+use storystream_feed_parser::{parse_opml, write_opml, FeedParser, OpmlOutline};
+use storystream_library::{
+    apply_feed_metadata, refresh_feed, store_episodes, BookImporter, ImportOptions, PolicyAction,
+    RefreshOutcome, RemovalReason, SubscriptionManager, PODCAST_TAG,
+};
+use storystream_network::{
+    AdvancedDownloadManager, Client, ConditionalResponse, DownloadManager, DownloadManagerConfig,
+};
+
+/// Subscription as reported by `feed add` and `feed list`
+#[derive(Debug, Serialize)]
+struct FeedRecord {
+    id: String,
+    title: String,
+    feed_url: String,
+    episodes: usize,
+    /// Milliseconds since the Unix epoch
+    last_fetched: Option<i64>,
+}
+
+impl FeedRecord {
+    fn new(podcast: &Podcast, episodes: usize) -> Self {
+        Self {
+            id: podcast.id.to_string(),
+            title: podcast.title.clone(),
+            feed_url: podcast.feed_url.clone(),
+            episodes,
+            last_fetched: podcast.last_fetched.map(|t| t.as_millis()),
+        }
+    }
+}
+
+/// Per-feed result of `feed refresh`
+#[derive(Debug, Serialize)]
+struct RefreshRecord {
+    id: String,
+    title: String,
+    modified: bool,
+    new_episodes: usize,
+    error: Option<String>,
+    /// What the feed's download policy did afterwards
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    policy: Vec<PolicyAction>,
+}
+
+impl RefreshRecord {
+    fn new(podcast: &Podcast, result: &Result<RefreshOutcome>) -> Self {
+        let (modified, new_episodes, error) = match result {
+            Ok(RefreshOutcome::NotModified) => (false, 0, None),
+            Ok(outcome @ RefreshOutcome::Updated { .. }) => (true, outcome.new_episodes(), None),
+            Err(e) => (false, 0, Some(format!("{:#}", e))),
+        };
+        Self {
+            id: podcast.id.to_string(),
+            title: podcast.title.clone(),
+            modified,
+            new_episodes,
+            error,
+            policy: Vec::new(),
+        }
+    }
+}
+
+/// A feed's download policy as reported by `feed policy`
+#[derive(Debug, Serialize)]
+struct PolicyRecord {
+    id: String,
+    title: String,
+    #[serde(flatten)]
+    policy: DownloadPolicy,
+}
+
+/// Per-episode result of `feed download`
+#[derive(Debug, Serialize)]
+struct DownloadRecord {
+    episode: String,
+    path: Option<String>,
+    book_id: Option<String>,
+    error: Option<String>,
+}
+
+/// Executes a feed subcommand
+pub async fn run(out: &Output, action: FeedAction) -> Result<()> {
+    let pool = open_database().await?;
+    let client = Client::new().context("Failed to create HTTP client")?;
+
+    match action {
+        FeedAction::Add { url, opml } => match (url, opml) {
+            (_, Some(path)) => import_opml(out, &pool, &client, &path).await,
+            (Some(url), None) => {
+                let (podcast, episodes) = subscribe(&pool, &client, &url).await?;
+                out.result(&FeedRecord::new(&podcast, episodes), || {
+                    println!(
+                        "Subscribed to '{}' ({} episode{})",
+                        podcast.title,
+                        episodes,
+                        if episodes == 1 { "" } else { "s" }
+                    )
+                })
+            }
+            (None, None) => bail!("Specify a feed URL or --opml FILE"),
+        },
+        FeedAction::List { opml } => list(out, &pool, opml.as_deref()).await,
+        FeedAction::Refresh { feed, all } => {
+            if all {
+                refresh_all(out, &pool, &client).await
+            } else {
+                let Some(query) = feed else {
+                    bail!("Specify a feed or pass --all");
+                };
+                let mut podcast = resolve_podcast(&pool, &query).await?;
+                let outcome = refresh_feed(&pool, &client, &mut podcast).await?;
+                let description = describe_refresh(&podcast, &outcome);
+                let mut record = RefreshRecord::new(&podcast, &Ok(outcome));
+                let mut actions = apply_policies(out, &pool, &client, &[podcast]).await?;
+                record.policy = actions.pop().unwrap_or_default();
+                out.result(&record, || println!("{}", description))
+            }
+        }
+        FeedAction::Download { feed, latest, dir } => {
+            let podcast = resolve_podcast(&pool, &feed).await?;
+            download(out, &pool, client, &podcast, latest, dir).await
+        }
+        FeedAction::Policy {
+            feed,
+            keep,
+            auto_download,
+            delete_finished,
+            unmetered_only,
+        } => {
+            let mut podcast = resolve_podcast(&pool, &feed).await?;
+            let policy = &mut podcast.policy;
+            let changed = keep.is_some()
+                || auto_download.is_some()
+                || delete_finished.is_some()
+                || unmetered_only.is_some();
+            policy.max_episodes = keep.unwrap_or(policy.max_episodes);
+            policy.auto_download = auto_download.unwrap_or(policy.auto_download);
+            policy.delete_finished = delete_finished.unwrap_or(policy.delete_finished);
+            policy.unmetered_only = unmetered_only.unwrap_or(policy.unmetered_only);
+
+            if changed {
+                if let Err(errors) = podcast.validate() {
+                    bail!("Invalid policy: {}", errors.join("; "));
+                }
+                podcast.updated_at = Timestamp::now();
+                podcasts::update_podcast(&pool, &podcast)
+                    .await
+                    .context("Failed to save policy")?;
+            }
+
+            let policy = podcast.policy;
+            let record = PolicyRecord {
+                id: podcast.id.to_string(),
+                title: podcast.title.clone(),
+                policy,
+            };
+            out.result(&record, || {
+                let yes_no = |flag: bool| if flag { "yes" } else { "no" };
+                println!("Download policy for '{}':", podcast.title);
+                println!("  Keep newest:     {} episodes", policy.max_episodes);
+                println!("  Auto-download:   {}", yes_no(policy.auto_download));
+                println!("  Delete finished: {}", yes_no(policy.delete_finished));
+                println!("  Unmetered only:  {}", yes_no(policy.unmetered_only));
+            })
+        }
+    }
+}
+
+/// Fetches, validates and stores a new subscription
+///
+/// Returns the stored podcast and the number of episodes recorded.
+async fn subscribe(pool: &DbPool, client: &Client, url: &str) -> Result<(Podcast, usize)> {
+    let url = url.trim();
+    if let Some(existing) = podcasts::find_podcast_by_url(pool, url).await? {
+        bail!("Already subscribed to '{}'", existing.title);
+    }
+
+    let (feed, etag, last_modified) = match client
+        .get_conditional(url, None, None)
+        .await
+        .with_context(|| format!("Failed to fetch {}", url))?
+    {
+        ConditionalResponse::Modified {
+            response,
+            etag,
+            last_modified,
+        } => {
+            let body = response.text().await.context("Failed to read feed")?;
+            let feed = FeedParser::parse(&body).context("Failed to parse feed")?;
+            (feed, etag, last_modified)
+        }
+        ConditionalResponse::NotModified => bail!("Server returned 304 for {}", url),
+    };
+
+    let mut podcast = Podcast::new(url.to_string(), feed.title.clone());
+    apply_feed_metadata(&mut podcast, &feed);
+    podcast.etag = etag;
+    podcast.last_modified = last_modified;
+    podcast.last_fetched = Some(Timestamp::now());
+
+    if let Err(errors) = podcast.validate() {
+        bail!("Invalid feed: {}", errors.join("; "));
+    }
+
+    podcasts::create_podcast(pool, &podcast)
+        .await
+        .context("Failed to save subscription")?;
+    let episodes = store_episodes(pool, &podcast, &feed)
+        .await
+        .context("Failed to save episodes")?;
+
+    Ok((podcast, episodes.len()))
+}
+
+/// Subscribes to every feed in an OPML file, reporting failures per feed
+async fn import_opml(out: &Output, pool: &DbPool, client: &Client, path: &Path) -> Result<()> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let outlines =
+        parse_opml(&content).with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    let mut added = 0;
+    let mut failed = 0;
+    for outline in &outlines {
+        if podcasts::find_podcast_by_url(pool, outline.xml_url.trim())
+            .await?
+            .is_some()
+        {
+            out.info(format!("  = {} (already subscribed)", outline.title));
+            continue;
+        }
+
+        match subscribe(pool, client, &outline.xml_url).await {
+            Ok((podcast, episodes)) => {
+                added += 1;
+                out.info(format!("  + {} ({} episodes)", podcast.title, episodes));
+            }
+            Err(e) => {
+                failed += 1;
+                out.warn(format!("  ! {}: {:#}", outline.title, e));
+            }
+        }
+    }
+
+    let summary = json!({ "added": added, "failed": failed, "total": outlines.len() });
+    if failed > 0 {
+        return Err(out.fail(
+            &summary,
+            anyhow!("{} feed(s) could not be imported", failed),
+        ));
+    }
+    out.result(&summary, || {
+        println!(
+            "Imported {} of {} feeds from {}",
+            added,
+            outlines.len(),
+            path.display()
+        )
+    })
+}
+
+/// Prints subscriptions, or exports them as OPML
+async fn list(out: &Output, pool: &DbPool, opml: Option<&Path>) -> Result<()> {
+    let subscriptions = podcasts::list_podcasts(pool).await?;
+
+    if let Some(path) = opml {
+        let outlines: Vec<OpmlOutline> = subscriptions
+            .iter()
+            .map(|p| OpmlOutline::new(p.title.clone(), p.feed_url.clone()))
+            .collect();
+        std::fs::write(path, write_opml("StoryStream subscriptions", &outlines))
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        let exported = json!({ "path": path.display().to_string(), "feeds": subscriptions.len() });
+        return out.result(&exported, || {
+            println!(
+                "Exported {} feeds to {}",
+                subscriptions.len(),
+                path.display()
+            )
+        });
+    }
+
+    let mut records = Vec::with_capacity(subscriptions.len());
+    for podcast in &subscriptions {
+        let episodes = podcasts::get_podcast_episodes(pool, podcast.id).await?;
+        records.push(FeedRecord::new(podcast, episodes.len()));
+    }
+
+    out.result(&records, || {
+        if records.is_empty() {
+            println!("No feed subscriptions");
+            return;
+        }
+
+        println!(
+            "{:<36}  {:<32}  {:>8}  {:<16}  URL",
+            "ID", "TITLE", "EPISODES", "LAST REFRESHED"
+        );
+        for record in &records {
+            let fetched = record
+                .last_fetched
+                .and_then(chrono::DateTime::from_timestamp_millis)
+                .map(|dt| {
+                    dt.with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string()
+                })
+                .unwrap_or_else(|| "never".to_string());
+            println!(
+                "{:<36}  {:<32}  {:>8}  {:<16}  {}",
+                record.id,
+                truncate(&record.title, 32),
+                record.episodes,
+                fetched,
+                record.feed_url
+            );
+        }
+    })
+}
+
+/// Refreshes every subscription, continuing past individual failures
+async fn refresh_all(out: &Output, pool: &DbPool, client: &Client) -> Result<()> {
+    let subscriptions = podcasts::list_podcasts(pool).await?;
+    if subscriptions.is_empty() {
+        return out.result(&Vec::<RefreshRecord>::new(), || {
+            println!("No feed subscriptions")
+        });
+    }
+
+    let total = subscriptions.len();
+    let mut records = Vec::with_capacity(total);
+    let mut refreshed = Vec::with_capacity(total);
+    for mut podcast in subscriptions {
+        let result = refresh_feed(pool, client, &mut podcast)
+            .await
+            .map_err(anyhow::Error::from);
+        match &result {
+            Ok(outcome) => out.info(format!("  {}", describe_refresh(&podcast, outcome))),
+            Err(e) => out.warn(format!("  {}: failed: {:#}", podcast.title, e)),
+        }
+        records.push(RefreshRecord::new(&podcast, &result));
+        if result.is_ok() {
+            refreshed.push(podcast);
+        }
+    }
+
+    let actions = apply_policies(out, pool, client, &refreshed).await?;
+    for (podcast, actions) in refreshed.iter().zip(actions) {
+        let id = podcast.id.to_string();
+        if let Some(record) = records.iter_mut().find(|r| r.id == id) {
+            record.policy = actions;
+        }
+    }
+
+    let failed = records.iter().filter(|r| r.error.is_some()).count();
+    if failed > 0 {
+        return Err(out.fail(
+            &records,
+            anyhow!("{} of {} feeds failed to refresh", failed, total),
+        ));
+    }
+    out.result(&records, || {})
+}
+
+/// Applies the download policies of freshly refreshed feeds
+///
+/// Queued downloads run to completion before the policies are applied once
+/// more to record them. Returns each feed's actions in the order given.
+async fn apply_policies(
+    out: &Output,
+    pool: &DbPool,
+    client: &Client,
+    feeds: &[Podcast],
+) -> Result<Vec<Vec<PolicyAction>>> {
+    if !feeds.iter().any(|p| p.policy.is_active()) {
+        return Ok(vec![Vec::new(); feeds.len()]);
+    }
+
+    let downloads = Arc::new(
+        AdvancedDownloadManager::new(client.clone(), DownloadManagerConfig::default())
+            .with_history(download_history()?),
+    );
+    let root = download_dir("Podcasts")?;
+    let manager = SubscriptionManager::new(pool.clone(), Arc::clone(&downloads), move |p, e| {
+        root.join(sanitize_filename(&p.title))
+            .join(episode_filename(e))
+    });
+
+    let mut reports = Vec::with_capacity(feeds.len());
+    for podcast in feeds {
+        reports.push(if podcast.policy.is_active() {
+            Some(manager.apply_policy(podcast).await?)
+        } else {
+            None
+        });
+    }
+
+    let queued: usize = reports.iter().flatten().map(|r| r.queued()).sum();
+    if queued > 0 {
+        out.info(format!(
+            "Downloading {} episode{}...",
+            queued,
+            if queued == 1 { "" } else { "s" }
+        ));
+        let runner = Arc::clone(&downloads);
+        let worker = tokio::spawn(async move { runner.start().await });
+        manager.wait_for_downloads().await;
+        downloads.shutdown().await?;
+        let _ = worker.await;
+
+        for (podcast, report) in feeds.iter().zip(reports.iter_mut()) {
+            if let Some(report) = report.as_mut().filter(|r| r.queued() > 0) {
+                let finished = manager.apply_policy(podcast).await?;
+                report.actions.extend(finished.actions);
+            }
+        }
+    }
+
+    Ok(reports
+        .into_iter()
+        .map(|report| {
+            let Some(report) = report else {
+                return Vec::new();
+            };
+            for action in &report.actions {
+                let line = format!("  {}: {}", report.title, describe_action(action));
+                match action {
+                    PolicyAction::Failed { .. } => out.warn(line),
+                    _ => out.info(line),
+                }
+            }
+            report.actions
+        })
+        .collect())
+}
+
+fn describe_action(action: &PolicyAction) -> String {
+    match action {
+        PolicyAction::Queued { title, .. } => format!("queued '{}'", title),
+        PolicyAction::Held { title, .. } => {
+            format!("'{}' waits for an unmetered connection", title)
+        }
+        PolicyAction::Downloaded {
+            title,
+            book_id: Some(_),
+            ..
+        } => format!("downloaded '{}'", title),
+        PolicyAction::Downloaded { title, .. } => {
+            format!("downloaded '{}' but could not import it", title)
+        }
+        PolicyAction::Played { title, .. } => format!("'{}' finished", title),
+        PolicyAction::Removed {
+            title,
+            reason: RemovalReason::Finished,
+            ..
+        } => format!("deleted finished '{}'", title),
+        PolicyAction::Removed { title, .. } => {
+            format!("deleted '{}' (over the download limit)", title)
+        }
+        PolicyAction::Failed { title, error, .. } => format!("! '{}': {}", title, error),
+    }
+}
+
+fn describe_refresh(podcast: &Podcast, outcome: &RefreshOutcome) -> String {
+    match outcome {
+        RefreshOutcome::NotModified => format!("{}: not modified", podcast.title),
+        RefreshOutcome::Updated { new_episodes } if new_episodes.is_empty() => {
+            format!("{}: no new episodes", podcast.title)
+        }
+        RefreshOutcome::Updated { new_episodes } => format!(
+            "{}: {} new episode{}",
+            podcast.title,
+            new_episodes.len(),
+            if new_episodes.len() == 1 { "" } else { "s" }
+        ),
+    }
+}
+
+/// Downloads the latest episodes of a feed and imports them as books
+async fn download(
+    out: &Output,
+    pool: &DbPool,
+    client: Client,
+    podcast: &Podcast,
+    latest: usize,
+    dir: Option<PathBuf>,
+) -> Result<()> {
+    let episodes = podcasts::get_podcast_episodes(pool, podcast.id).await?;
+    let pending: Vec<&PodcastEpisode> = episodes
+        .iter()
+        .take(latest)
+        .filter(|e| !e.is_downloaded)
+        .collect();
+
+    if pending.is_empty() {
+        return out.result(&Vec::<DownloadRecord>::new(), || {
+            println!("No new episodes to download for '{}'", podcast.title)
+        });
+    }
+
+    let dir = match dir {
+        Some(dir) => dir,
+        None => download_dir("Podcasts")?.join(sanitize_filename(&podcast.title)),
+    };
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let downloader = DownloadManager::new(client);
+    let importer = BookImporter::new(pool.clone());
+    let author = podcast
+        .author
+        .clone()
+        .unwrap_or_else(|| podcast.title.clone());
+
+    let mut records = Vec::with_capacity(pending.len());
+    for episode in &pending {
+        let path = dir.join(episode_filename(episode));
+        out.info(format!("Downloading '{}'...", episode.title));
+
+        let mut record = DownloadRecord {
+            episode: episode.title.clone(),
+            path: None,
+            book_id: None,
+            error: None,
+        };
+
+        if let Err(e) = downloader
+            .download_file(&episode.audio_url, &path, None)
+            .await
+        {
+            out.warn(format!("  ! download failed: {}", e));
+            let _ = std::fs::remove_file(&path);
+            record.error = Some(e.to_string());
+            records.push(record);
+            continue;
+        }
+
+        podcasts::mark_episode_downloaded(pool, episode.id, &path.to_string_lossy()).await?;
+        record.path = Some(path.display().to_string());
+
+        let options = ImportOptions::new()
+            .with_title(episode.title.clone())
+            .with_author(author.clone())
+            .with_tag(PODCAST_TAG);
+        match importer.import_file(&path, options).await {
+            Ok(book) => {
+                podcasts::link_episode_book(pool, episode.id, book.id).await?;
+                out.info(format!("  imported as {}", book.id));
+                record.book_id = Some(book.id.to_string());
+            }
+            Err(e) => {
+                out.warn(format!(
+                    "  ! saved to {} but import failed: {}",
+                    path.display(),
+                    e
+                ));
+                record.error = Some(e.to_string());
+            }
+        }
+        records.push(record);
+    }
+
+    let failed = records.iter().filter(|r| r.error.is_some()).count();
+    if failed > 0 {
+        return Err(out.fail(
+            &records,
+            anyhow!("{} of {} episodes failed", failed, pending.len()),
+        ));
+    }
+    out.result(&records, || {})
+}
+
+/// Finds a subscription by ID, feed URL, exact title, or unique partial title
+async fn resolve_podcast(pool: &DbPool, query: &str) -> Result<Podcast> {
+    if let Ok(id) = PodcastId::from_string(query) {
+        return podcasts::get_podcast(pool, id)
+            .await
+            .with_context(|| format!("No feed with ID {}", query));
+    }
+
+    if let Some(podcast) = podcasts::find_podcast_by_url(pool, query).await? {
+        return Ok(podcast);
+    }
+
+    let needle = query.to_lowercase();
+    let all = podcasts::list_podcasts(pool).await?;
+
+    if let Some(podcast) = all.iter().find(|p| p.title.to_lowercase() == needle) {
+        return Ok(podcast.clone());
+    }
+
+    let mut matches: Vec<Podcast> = all
+        .into_iter()
+        .filter(|p| p.title.to_lowercase().contains(&needle))
+        .collect();
+
+    match matches.len() {
+        0 => bail!("No feed matching '{}'", query),
+        1 => Ok(matches.remove(0)),
+        _ => {
+            let titles: Vec<String> = matches.iter().map(|p| p.title.clone()).collect();
+            bail!("'{}' matches several feeds: {}", query, titles.join(", "))
+        }
+    }
+}
+
+fn episode_filename(episode: &PodcastEpisode) -> String {
+    let path = episode
+        .audio_url
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default();
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .filter(|e| e.len() <= 4 && e.chars().all(|c| c.is_ascii_alphanumeric()))
+        .unwrap_or("mp3");
+
+    let date = episode
+        .published
+        .and_then(|t| chrono::DateTime::from_timestamp_millis(t.as_millis()))
+        .map(|dt| format!("{} ", dt.format("%Y-%m-%d")))
+        .unwrap_or_default();
+
+    format!(
+        "{}{}.{}",
+        date,
+        sanitize_filename(&episode.title),
+        extension
+    )
+}
@@ -0,0 +1,245 @@
+// crates/cli/src/commands/library.rs
+//! Library listing and search
+
+use super::{format_duration, open_existing_database, truncate, Output, SearchFilterArgs};
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::HashMap;
+use storystream_core::{Book, BookId, Duration};
+use storystream_database::{
+    queries::{
+        books::{self, BookSort},
+        playback,
+    },
+    search::{search_books_filtered, SearchFilter},
+    DbPool,
+};
+
+/// Book as reported by `list` and `search`
+#[derive(Debug, Serialize)]
+pub struct BookRecord {
+    pub id: String,
+    pub title: String,
+    pub author: Option<String>,
+    pub narrator: Option<String>,
+    pub series: Option<String>,
+    pub duration_secs: u64,
+    pub file_path: String,
+    pub favorite: bool,
+    pub play_count: u32,
+    pub position_secs: u64,
+    /// How much of the book has been listened to, from 0 to 100
+    pub progress_percent: f64,
+}
+
+impl BookRecord {
+    /// The record with the listening position saved for the book
+    fn at(mut self, position: Duration) -> Self {
+        let position = position.as_millis().min(self.duration_secs * 1000);
+        self.position_secs = position / 1000;
+        if self.duration_secs > 0 {
+            self.progress_percent = position as f64 / (self.duration_secs * 1000) as f64 * 100.0;
+        }
+        self
+    }
+}
+
+impl From<&Book> for BookRecord {
+    fn from(book: &Book) -> Self {
+        Self {
+            id: book.id.as_string(),
+            title: book.title.clone(),
+            author: book.author.clone(),
+            narrator: book.narrator.clone(),
+            series: book.series.clone(),
+            duration_secs: book.duration.as_seconds(),
+            file_path: book.file_path.display().to_string(),
+            favorite: book.is_favorite,
+            play_count: book.play_count,
+            position_secs: 0,
+            progress_percent: 0.0,
+        }
+    }
+}
+
+/// Search hit with its relevance rank (lower is better)
+#[derive(Debug, Serialize)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub book: BookRecord,
+    pub rank: f64,
+}
+
+/// Lists books, optionally filtered by author or favorites
+///
+/// `sort` is a [`BookSort`] key; `limit` keeps only that many books.
+pub async fn list(
+    out: &Output,
+    author: Option<&str>,
+    favorites: bool,
+    sort: Option<&str>,
+    limit: Option<i64>,
+) -> Result<()> {
+    let sort = sort.map(BookSort::parse).transpose()?.unwrap_or_default();
+    let pool = open_existing_database().await?;
+    let records = list_records(&pool, author, favorites, sort, limit.unwrap_or(-1)).await?;
+
+    out.result(&records, || {
+        if records.is_empty() {
+            println!("No audiobooks found");
+            return;
+        }
+        print_header("");
+        for record in &records {
+            print_row("", record);
+        }
+    })
+}
+
+impl From<&SearchFilterArgs> for SearchFilter {
+    fn from(args: &SearchFilterArgs) -> Self {
+        Self {
+            author: args.author.clone(),
+            narrator: args.narrator.clone(),
+            tag: args.tag.clone(),
+            favorite: args.favorites,
+            finished: match (args.finished, args.unfinished) {
+                (true, _) => Some(true),
+                (_, true) => Some(false),
+                _ => None,
+            },
+            min_duration: args.min_length,
+            max_duration: args.max_length,
+            format: args.format.clone(),
+        }
+    }
+}
+
+/// Searches the library by text, filters, or both
+pub async fn search(
+    out: &Output,
+    query: Option<&str>,
+    filter: &SearchFilterArgs,
+    sort: Option<&str>,
+    limit: i64,
+) -> Result<()> {
+    let sort = sort.map(BookSort::parse).transpose()?;
+    let pool = open_existing_database().await?;
+    let hits = search_records(&pool, query, &filter.into(), sort, limit).await?;
+
+    out.result(&hits, || {
+        if hits.is_empty() {
+            match query {
+                Some(query) => println!("No matches for '{}'", query),
+                None => println!("No audiobooks match the filters"),
+            }
+            return;
+        }
+        print_header("  #");
+        for (index, hit) in hits.iter().enumerate() {
+            print_row(&format!("{:>3}", index + 1), &hit.book);
+        }
+    })
+}
+
+/// Collects the books shown by `list`, in `sort` order
+///
+/// A negative `limit` keeps every book.
+pub async fn list_records(
+    pool: &DbPool,
+    author: Option<&str>,
+    favorites: bool,
+    sort: BookSort,
+    limit: i64,
+) -> Result<Vec<BookRecord>> {
+    let found = books::list_books_page(pool, sort, -1, 0).await?;
+    let positions = playback::get_positions(pool).await?;
+
+    Ok(found
+        .iter()
+        .filter(|b| author.is_none() || b.author.as_deref() == author)
+        .filter(|b| !favorites || b.is_favorite)
+        .take(usize::try_from(limit).unwrap_or(usize::MAX))
+        .map(|b| record(b, &positions))
+        .collect())
+}
+
+/// The book played most recently, with its listening position
+pub async fn last_played_record(pool: &DbPool) -> Result<Option<BookRecord>> {
+    let Some(book) = books::get_recently_played_books(pool, 1).await?.pop() else {
+        return Ok(None);
+    };
+    let positions = playback::get_positions(pool).await?;
+    Ok(Some(record(&book, &positions)))
+}
+
+/// Collects the hits shown by `search`
+pub async fn search_records(
+    pool: &DbPool,
+    query: Option<&str>,
+    filter: &SearchFilter,
+    sort: Option<BookSort>,
+    limit: i64,
+) -> Result<Vec<SearchHit>> {
+    let found = search_books_filtered(pool, query, filter, sort, limit).await?;
+    let positions = playback::get_positions(pool).await?;
+
+    Ok(found
+        .iter()
+        .map(|hit| SearchHit {
+            book: record(&hit.item, &positions),
+            rank: hit.rank,
+        })
+        .collect())
+}
+
+fn record(book: &Book, positions: &HashMap<BookId, Duration>) -> BookRecord {
+    let record = BookRecord::from(book);
+    match positions.get(&book.id) {
+        Some(position) => record.at(*position),
+        None => record,
+    }
+}
+
+/// Progress as the table shows it: `-` before the book is started
+pub fn progress_text(record: &BookRecord) -> String {
+    if record.position_secs == 0 {
+        "-".to_string()
+    } else {
+        format!("{:.0}%", record.progress_percent.floor())
+    }
+}
+
+/// Prints the column headings, after `lead` for a leading column
+fn print_header(lead: &str) {
+    println!(
+        "{}{:<36}  {:<32}  {:<20}  {:>11}  {:>8}",
+        spaced(lead),
+        "ID",
+        "TITLE",
+        "AUTHOR",
+        "LENGTH",
+        "PROGRESS"
+    );
+}
+
+fn print_row(lead: &str, record: &BookRecord) {
+    println!(
+        "{}{:<36}  {:<32}  {:<20}  {:>11}  {:>8}{}",
+        spaced(lead),
+        record.id,
+        truncate(&record.title, 32),
+        truncate(record.author.as_deref().unwrap_or("-"), 20),
+        format_duration(record.duration_secs),
+        progress_text(record),
+        if record.favorite { "  *" } else { "" }
+    );
+}
+
+fn spaced(lead: &str) -> String {
+    if lead.is_empty() {
+        String::new()
+    } else {
+        format!("{}  ", lead)
+    }
+}
@@ -0,0 +1,226 @@
+// crates/cli/src/commands/output.rs
+//! Output modes shared by every command
+//!
+//! Commands report through [`Output`] rather than printing directly, so that
+//! `--json` produces exactly one result envelope on stdout and `--quiet`
+//! leaves only the final result or errors.
+
+use serde::Serialize;
+use std::fmt::{self, Display};
+use storystream_core::AppError;
+
+/// Code reported for failures that did not originate from [`AppError`]
+pub const GENERIC_ERROR_CODE: &str = "COMMAND_FAILED";
+
+/// How results are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputMode {
+    /// Human-readable text with progress messages
+    #[default]
+    Human,
+    /// A single JSON envelope on stdout
+    Json,
+    /// Human-readable final result only
+    Quiet,
+}
+
+/// Error details in a JSON envelope
+#[derive(Debug, Serialize)]
+pub struct ErrorBody {
+    pub code: String,
+    pub message: String,
+}
+
+impl ErrorBody {
+    /// Builds the error body, using the code of the first [`AppError`] in the chain
+    pub fn from_error(error: &anyhow::Error) -> Self {
+        let code = app_error(error)
+            .map(AppError::code)
+            .unwrap_or(GENERIC_ERROR_CODE);
+
+        Self {
+            code: code.to_string(),
+            message: format!("{:#}", error),
+        }
+    }
+}
+
+/// The first [`AppError`] in the chain of `error`
+fn app_error(error: &anyhow::Error) -> Option<&AppError> {
+    error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<AppError>())
+}
+
+/// Exit status for a failed command, following the BSD `sysexits.h` codes
+///
+/// Failures that did not originate from an [`AppError`] exit with 1.
+pub fn exit_code(error: &anyhow::Error) -> i32 {
+    match app_error(error) {
+        Some(AppError::InvalidArgument { .. }) => 64,
+        Some(AppError::DatabaseCorrupted { .. } | AppError::InvalidMetadata { .. }) => 65,
+        Some(AppError::FileNotFound { .. } | AppError::RecordNotFound { .. }) => 66,
+        Some(
+            AppError::DatabaseError { .. }
+            | AppError::DatabaseLocked { .. }
+            | AppError::NetworkError { .. }
+            | AppError::NetworkTimeout { .. }
+            | AppError::ConnectionLost { .. }
+            | AppError::ContentSourceUnavailable { .. },
+        ) => 69,
+        Some(AppError::DiskFull { .. } | AppError::IoError { .. }) => 74,
+        Some(AppError::PermissionDenied { .. } | AppError::DatabaseReadOnly { .. }) => 77,
+        Some(AppError::InvalidConfiguration { .. } | AppError::ConfigurationCorrupted { .. }) => 78,
+        _ => 1,
+    }
+}
+
+/// Result envelope printed in JSON mode
+#[derive(Debug, Serialize)]
+pub struct Envelope<T: Serialize> {
+    pub ok: bool,
+    pub data: Option<T>,
+    pub error: Option<ErrorBody>,
+}
+
+impl<T: Serialize> Envelope<T> {
+    /// A successful result
+    pub fn success(data: T) -> Self {
+        Self {
+            ok: true,
+            data: Some(data),
+            error: None,
+        }
+    }
+
+    /// A failed result, optionally with the partial data gathered so far
+    pub fn failure(data: Option<T>, error: &anyhow::Error) -> Self {
+        Self {
+            ok: false,
+            data,
+            error: Some(ErrorBody::from_error(error)),
+        }
+    }
+}
+
+/// Marks an error whose envelope has already been printed
+#[derive(Debug)]
+pub struct Reported;
+
+impl Display for Reported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "error already reported")
+    }
+}
+
+impl std::error::Error for Reported {}
+
+/// Message-only payload for commands without structured results
+#[derive(Debug, Serialize)]
+struct Message<'a> {
+    message: &'a str,
+}
+
+/// Writes command output according to the selected mode
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Output {
+    mode: OutputMode,
+}
+
+impl Output {
+    /// Creates an output from the global `--json` and `--quiet` flags
+    pub fn new(json: bool, quiet: bool) -> Self {
+        let mode = if json {
+            OutputMode::Json
+        } else if quiet {
+            OutputMode::Quiet
+        } else {
+            OutputMode::Human
+        };
+        Self { mode }
+    }
+
+    /// Whether results are written as JSON
+    pub fn is_json(&self) -> bool {
+        self.mode == OutputMode::Json
+    }
+
+    /// Whether progress output such as bars and per-item lines should be drawn
+    pub fn shows_progress(&self) -> bool {
+        self.mode == OutputMode::Human
+    }
+
+    /// Prints a progress or informational line; human mode only
+    pub fn info(&self, message: impl Display) {
+        if self.shows_progress() {
+            println!("{}", message);
+        }
+    }
+
+    /// Prints a non-fatal problem to stderr; suppressed in quiet mode
+    pub fn warn(&self, message: impl Display) {
+        if self.mode != OutputMode::Quiet {
+            eprintln!("{}", message);
+        }
+    }
+
+    /// Reports the final result: `data` as an envelope in JSON mode, otherwise
+    /// whatever `human` prints
+    pub fn result<T: Serialize>(&self, data: &T, human: impl FnOnce()) -> anyhow::Result<()> {
+        if self.is_json() {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&Envelope::success(data))?
+            );
+        } else {
+            human();
+        }
+        Ok(())
+    }
+
+    /// Reports a one-line result for commands without structured data
+    pub fn done(&self, message: impl Display) -> anyhow::Result<()> {
+        let message = message.to_string();
+        self.result(&Message { message: &message }, || println!("{}", message))
+    }
+
+    /// Reports a failure that still has partial results
+    ///
+    /// In JSON mode the envelope carries both `data` and `error`; the returned
+    /// error is then only used for the exit status.
+    pub fn fail<T: Serialize>(&self, data: &T, error: anyhow::Error) -> anyhow::Error {
+        if !self.is_json() {
+            return error;
+        }
+        match serde_json::to_string_pretty(&Envelope::failure(Some(data), &error)) {
+            Ok(json) => {
+                println!("{}", json);
+                anyhow::Error::new(Reported)
+            }
+            Err(e) => e.into(),
+        }
+    }
+
+    /// Reports an error returned by a command
+    ///
+    /// In human modes an [`AppError`] in the chain leads with its
+    /// [`AppError::user_message`], followed by what failed.
+    pub fn error(&self, error: &anyhow::Error) {
+        if error.is::<Reported>() {
+            return;
+        }
+
+        if self.is_json() {
+            let envelope = Envelope::<()>::failure(None, error);
+            match serde_json::to_string_pretty(&envelope) {
+                Ok(json) => println!("{}", json),
+                Err(_) => eprintln!("Error: {:#}", error),
+            }
+        } else if let Some(cause) = app_error(error) {
+            eprintln!("Error: {}", cause.user_message());
+            eprintln!("  {:#}", error);
+        } else {
+            eprintln!("Error: {:?}", error);
+        }
+    }
+}
@@ -0,0 +1,353 @@
+// crates/cli/src/commands/playlist.rs
+//! Playlist management subcommands
+
+use super::library::BookRecord;
+use super::{format_duration, open_database, resolve_book, truncate, Output, PlaylistAction};
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use storystream_core::types::Validator;
+use storystream_core::{AppError, Book, Playlist, PlaylistId, PlaylistItem, SmartPlaylistCriteria};
+use storystream_database::{queries::playlists, DbPool};
+use storystream_library::LibraryManager;
+
+/// Playlist as reported by `playlist list` and `playlist create`
+#[derive(Debug, Serialize)]
+struct PlaylistRecord {
+    id: String,
+    name: String,
+    smart: bool,
+    criteria: Option<SmartPlaylistCriteria>,
+    books: usize,
+    duration_secs: u64,
+}
+
+impl PlaylistRecord {
+    fn new(playlist: &Playlist, books: &[Book]) -> Self {
+        Self {
+            id: playlist.id.as_string(),
+            name: playlist.name.clone(),
+            smart: playlist.is_smart(),
+            criteria: playlist.smart_criteria.clone(),
+            books: books.len(),
+            duration_secs: books.iter().map(|b| b.duration.as_seconds()).sum(),
+        }
+    }
+}
+
+/// A playlist with its books in playback order
+#[derive(Debug, Serialize)]
+struct PlaylistContents {
+    #[serde(flatten)]
+    playlist: PlaylistRecord,
+    items: Vec<BookRecord>,
+}
+
+impl PlaylistContents {
+    fn new(playlist: &Playlist, books: &[Book]) -> Self {
+        Self {
+            playlist: PlaylistRecord::new(playlist, books),
+            items: books.iter().map(BookRecord::from).collect(),
+        }
+    }
+}
+
+/// Queue built by `playlist play`
+#[derive(Debug, Serialize)]
+struct PlayQueue {
+    #[serde(flatten)]
+    playlist: PlaylistRecord,
+    shuffle: bool,
+    /// Books in playback order
+    queue: Vec<BookRecord>,
+    /// Books left out because their file is missing
+    skipped: Vec<BookRecord>,
+}
+
+/// Executes a playlist subcommand
+pub async fn run(out: &Output, action: PlaylistAction) -> Result<()> {
+    let pool = open_database().await?;
+
+    match action {
+        PlaylistAction::Create { name, smart } => create(out, &pool, &name, smart.as_deref()).await,
+        PlaylistAction::List => list(out, &pool).await,
+        PlaylistAction::Show { name } => {
+            let playlist = resolve_playlist(&pool, &name).await?;
+            let books = playlist_books(&pool, &playlist).await?;
+            let contents = PlaylistContents::new(&playlist, &books);
+            out.result(&contents, || print_contents(&contents))
+        }
+        PlaylistAction::Add { name, book } => {
+            let playlist = resolve_manual_playlist(&pool, &name).await?;
+            let book = resolve_book(&pool, &book).await?;
+
+            let books = playlists::get_playlist_books(&pool, playlist.id).await?;
+            if books.iter().any(|b| b.id == book.id) {
+                return Err(AppError::InvalidArgument {
+                    argument: "book".to_string(),
+                    reason: format!("'{}' is already in '{}'", book.title, playlist.name),
+                }
+                .into());
+            }
+
+            let position = playlists::next_playlist_position(&pool, playlist.id).await?;
+            playlists::add_book_to_playlist(
+                &pool,
+                &PlaylistItem::new(playlist.id, book.id, position),
+            )
+            .await
+            .context("Failed to add book to playlist")?;
+
+            let books = playlists::get_playlist_books(&pool, playlist.id).await?;
+            out.result(&PlaylistContents::new(&playlist, &books), || {
+                println!(
+                    "Added '{}' to '{}' ({} book{})",
+                    book.title,
+                    playlist.name,
+                    books.len(),
+                    plural(books.len())
+                )
+            })
+        }
+        PlaylistAction::Remove { name, book } => {
+            let playlist = resolve_manual_playlist(&pool, &name).await?;
+            let book = resolve_book(&pool, &book).await?;
+
+            let books = playlists::get_playlist_books(&pool, playlist.id).await?;
+            if !books.iter().any(|b| b.id == book.id) {
+                return Err(AppError::InvalidArgument {
+                    argument: "book".to_string(),
+                    reason: format!("'{}' is not in '{}'", book.title, playlist.name),
+                }
+                .into());
+            }
+
+            playlists::remove_book_from_playlist(&pool, playlist.id, book.id)
+                .await
+                .context("Failed to remove book from playlist")?;
+
+            let books = playlists::get_playlist_books(&pool, playlist.id).await?;
+            out.result(&PlaylistContents::new(&playlist, &books), || {
+                println!("Removed '{}' from '{}'", book.title, playlist.name)
+            })
+        }
+        PlaylistAction::Play { name, shuffle } => play(out, &pool, &name, shuffle).await,
+    }
+}
+
+async fn create(out: &Output, pool: &DbPool, name: &str, smart: Option<&str>) -> Result<()> {
+    let name = name.trim();
+    if let Some(existing) = playlists::find_playlist_by_name(pool, name).await? {
+        return Err(AppError::InvalidArgument {
+            argument: "name".to_string(),
+            reason: format!("a playlist named '{}' already exists", existing.name),
+        }
+        .into());
+    }
+
+    let playlist = match smart {
+        Some(json) => Playlist::new_smart(name.to_string(), parse_criteria(json)?),
+        None => Playlist::new_manual(name.to_string()),
+    };
+    if let Err(errors) = playlist.validate() {
+        bail!("Invalid playlist: {}", errors.join("; "));
+    }
+
+    playlists::create_playlist(pool, &playlist)
+        .await
+        .context("Failed to save playlist")?;
+
+    let books = playlist_books(pool, &playlist).await?;
+    let record = PlaylistRecord::new(&playlist, &books);
+    out.result(&record, || {
+        if playlist.is_smart() {
+            println!(
+                "Created smart playlist '{}' matching {} book{}",
+                playlist.name,
+                books.len(),
+                plural(books.len())
+            );
+        } else {
+            println!("Created playlist '{}'", playlist.name);
+        }
+    })
+}
+
+async fn list(out: &Output, pool: &DbPool) -> Result<()> {
+    let all = playlists::list_playlists(pool).await?;
+
+    let mut records = Vec::with_capacity(all.len());
+    for playlist in &all {
+        let books = playlist_books(pool, playlist).await?;
+        records.push(PlaylistRecord::new(playlist, &books));
+    }
+
+    out.result(&records, || {
+        if records.is_empty() {
+            println!("No playlists");
+            return;
+        }
+
+        println!(
+            "{:<32}  {:<6}  {:>5}  {:>10}",
+            "NAME", "TYPE", "BOOKS", "LENGTH"
+        );
+        for record in &records {
+            println!(
+                "{:<32}  {:<6}  {:>5}  {:>10}",
+                truncate(&record.name, 32),
+                if record.smart { "smart" } else { "manual" },
+                record.books,
+                format_duration(record.duration_secs)
+            );
+        }
+    })
+}
+
+/// Starts the playlist's session, leaving out books whose file is missing
+async fn play(out: &Output, pool: &DbPool, name: &str, shuffle: bool) -> Result<()> {
+    let playlist = resolve_playlist(pool, name).await?;
+    let books = playlist_books(pool, &playlist).await?;
+
+    let (queue, missing): (Vec<Book>, Vec<Book>) =
+        books.into_iter().partition(|b| b.file_path.exists());
+    for book in &missing {
+        out.warn(format!(
+            "Skipping '{}': file not found at {}",
+            book.title,
+            book.file_path.display()
+        ));
+    }
+
+    if queue.is_empty() {
+        bail!("Nothing to play in '{}'", playlist.name);
+    }
+
+    let progress = LibraryManager::with_pool(pool.clone())
+        .start_playlist(playlist.id, shuffle)
+        .await
+        .map_err(AppError::from)
+        .context("Failed to start playlist")?;
+    // The session decides the order, which differs when shuffled
+    let queue: Vec<Book> = progress
+        .session
+        .queue
+        .iter()
+        .filter_map(|id| queue.iter().find(|b| b.id == *id).cloned())
+        .collect();
+
+    let result = PlayQueue {
+        playlist: PlaylistRecord::new(&playlist, &queue),
+        shuffle,
+        queue: queue.iter().map(BookRecord::from).collect(),
+        skipped: missing.iter().map(BookRecord::from).collect(),
+    };
+    out.result(&result, || {
+        println!(
+            "Queued {} book{} from '{}' ({}){}",
+            result.queue.len(),
+            plural(result.queue.len()),
+            playlist.name,
+            format_duration(result.playlist.duration_secs),
+            if shuffle { ", shuffled" } else { "" }
+        );
+        print_items(&result.queue);
+    })?;
+    out.info("\nNote: Use 'storystream tui' for full interactive experience");
+    Ok(())
+}
+
+/// Parses `--smart` criteria, rejecting unknown fields and invalid values
+fn parse_criteria(json: &str) -> Result<SmartPlaylistCriteria, AppError> {
+    let invalid = |reason: String| AppError::InvalidArgument {
+        argument: "smart".to_string(),
+        reason,
+    };
+
+    let criteria: SmartPlaylistCriteria =
+        serde_json::from_str(json).map_err(|e| invalid(e.to_string()))?;
+    criteria
+        .validate()
+        .map_err(|errors| invalid(errors.join("; ")))?;
+    Ok(criteria)
+}
+
+/// Finds a playlist by ID or name
+async fn resolve_playlist(pool: &DbPool, query: &str) -> Result<Playlist> {
+    if let Ok(id) = PlaylistId::from_string(query) {
+        return playlists::get_playlist(pool, id)
+            .await
+            .with_context(|| format!("No playlist with ID {}", query));
+    }
+
+    match playlists::find_playlist_by_name(pool, query).await? {
+        Some(playlist) => Ok(playlist),
+        None => Err(anyhow::Error::new(AppError::RecordNotFound {
+            entity: "Playlist".to_string(),
+            identifier: query.to_string(),
+        })
+        .context(format!("No playlist named '{}'", query))),
+    }
+}
+
+/// Finds a playlist whose books can be edited by hand
+async fn resolve_manual_playlist(pool: &DbPool, query: &str) -> Result<Playlist> {
+    let playlist = resolve_playlist(pool, query).await?;
+    if playlist.is_smart() {
+        return Err(AppError::InvalidArgument {
+            argument: "name".to_string(),
+            reason: format!(
+                "'{}' is a smart playlist; its books come from its criteria",
+                playlist.name
+            ),
+        }
+        .into());
+    }
+    Ok(playlist)
+}
+
+/// Returns the books of a playlist in playback order
+async fn playlist_books(pool: &DbPool, playlist: &Playlist) -> Result<Vec<Book>> {
+    let books = match playlist.smart_criteria {
+        Some(ref criteria) => playlists::get_smart_playlist_books(pool, criteria).await?,
+        None => playlists::get_playlist_books(pool, playlist.id).await?,
+    };
+    Ok(books)
+}
+
+fn print_contents(contents: &PlaylistContents) {
+    let playlist = &contents.playlist;
+    println!(
+        "{}{} - {} book{}, {}",
+        playlist.name,
+        if playlist.smart { " (smart)" } else { "" },
+        playlist.books,
+        plural(playlist.books),
+        format_duration(playlist.duration_secs)
+    );
+
+    if contents.items.is_empty() {
+        println!("  (empty)");
+        return;
+    }
+    print_items(&contents.items);
+}
+
+fn print_items(items: &[BookRecord]) {
+    for (index, book) in items.iter().enumerate() {
+        println!(
+            "{:>3}. {:<40}  {:<24}  {:>10}",
+            index + 1,
+            truncate(&book.title, 40),
+            truncate(book.author.as_deref().unwrap_or("-"), 24),
+            format_duration(book.duration_secs)
+        );
+    }
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 {
+        ""
+    } else {
+        "s"
+    }
+}
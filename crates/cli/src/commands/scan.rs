@@ -0,0 +1,147 @@
+// crates/cli/src/commands/scan.rs
+//! Scanning folders into the library, directly or through a saved plan
+
+use super::{config_manager, open_database, Output};
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+use storystream_core::AppError;
+use storystream_library::{BookImporter, ImportPlan, PlannedAction, PlannedImport, ScanMode};
+
+/// Scans `path`, or the configured library paths, and imports what it finds
+///
+/// With `dry_run` the plan is only printed. With `save_plan` it is written
+/// to that file for [`commit`] instead of being applied. An incremental
+/// `mode` passes over books whose file kept its size and modification time.
+pub async fn run(
+    out: &Output,
+    path: Option<&str>,
+    dry_run: bool,
+    save_plan: Option<&Path>,
+    mode: ScanMode,
+) -> Result<()> {
+    let paths = match path {
+        Some(path) => {
+            let path = PathBuf::from(path);
+            if !path.exists() {
+                return Err(missing(&path));
+            }
+            vec![path]
+        }
+        None => {
+            let config = config_manager()?.load_effective();
+            if config.library.library_paths.is_empty() {
+                bail!("No library paths are configured; pass a path to scan");
+            }
+            let (found, gone): (Vec<_>, Vec<_>) = config
+                .library
+                .library_paths
+                .into_iter()
+                .partition(|path| path.exists());
+            for path in &gone {
+                out.warn(format!("Skipping {}: folder not found", path.display()));
+            }
+            match gone.into_iter().next() {
+                Some(path) if found.is_empty() => return Err(missing(&path)),
+                _ => found,
+            }
+        }
+    };
+
+    let importer = BookImporter::new(open_database().await?).with_scan_mode(mode);
+    for path in &paths {
+        out.info(format!("Scanning {}...", path.display()));
+    }
+    let plan = importer
+        .preview(&paths)
+        .await
+        .context("Failed to scan for audiobooks")?;
+    for item in &plan.items {
+        out.info(plan_line(item));
+    }
+
+    if let Some(file) = save_plan {
+        let json = serde_json::to_string_pretty(&plan)?;
+        std::fs::write(file, json)
+            .with_context(|| format!("Failed to write {}", file.display()))?;
+        return out.result(&plan, || {
+            println!("{}", summary(&plan, false));
+            println!(
+                "Plan saved; import it with: storystream scan --commit-plan {}",
+                file.display()
+            );
+        });
+    }
+    if dry_run {
+        return out.result(&plan, || {
+            println!("{}", summary(&plan, false));
+            println!("Dry run: nothing was saved.");
+        });
+    }
+
+    let pending = plan.creates() + plan.updates();
+    if pending > 0 {
+        out.info(format!("Importing {} file(s)...", pending));
+    }
+    importer
+        .commit(&plan)
+        .await
+        .context("Failed to import audiobooks")?;
+    out.result(&plan, || println!("{}", summary(&plan, true)))
+}
+
+/// Imports a plan written by `scan --save-plan`
+///
+/// Nothing is imported if any of its files changed since it was written.
+pub async fn commit(out: &Output, file: &Path) -> Result<()> {
+    let json = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to read {}", file.display()))?;
+    let plan: ImportPlan = serde_json::from_str(&json)
+        .with_context(|| format!("{} is not an import plan", file.display()))?;
+
+    BookImporter::new(open_database().await?)
+        .commit(&plan)
+        .await
+        .with_context(|| format!("Failed to import {}", file.display()))?;
+    out.result(&plan, || println!("{}", summary(&plan, true)))
+}
+
+/// The error for a folder to scan that does not exist
+fn missing(path: &Path) -> anyhow::Error {
+    anyhow::Error::new(AppError::FileNotFound {
+        path: path.to_path_buf(),
+    })
+    .context(format!("Cannot scan {}", path.display()))
+}
+
+/// One line per file: `+` added, `~` updated, `-` skipped or excluded
+pub fn plan_line(item: &PlannedImport) -> String {
+    match &item.action {
+        _ if !item.include && !matches!(item.action, PlannedAction::Skip(_)) => {
+            format!("  - {} (excluded)", item.title())
+        }
+        PlannedAction::Create => format!("  + {} ({})", item.title(), item.path.display()),
+        PlannedAction::Update { changes, .. } if changes.is_empty() => {
+            format!("  ~ {} (file changed)", item.title())
+        }
+        PlannedAction::Update { changes, .. } => {
+            format!("  ~ {} ({} changed)", item.title(), changes.join(", "))
+        }
+        PlannedAction::Skip(reason) => format!("  - {}: {}", item.title(), reason),
+    }
+}
+
+fn summary(plan: &ImportPlan, applied: bool) -> String {
+    let (added, updated) = if applied {
+        ("Added", "updated")
+    } else {
+        ("Would add", "update")
+    };
+    format!(
+        "{} {} book(s) and {} {} ({} file(s) skipped)",
+        added,
+        plan.creates(),
+        updated,
+        plan.updates(),
+        plan.skipped()
+    )
+}
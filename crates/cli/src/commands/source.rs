@@ -0,0 +1,490 @@
+// crates/cli/src/commands/source.rs
+//! Online source search and fetch subcommands
+
+use super::{
+    download_dir, download_history, format_duration, open_cache, open_database, sanitize_filename,
+    truncate,
+};
+use super::{Output, SourceAction, SourceKind};
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use storystream_content_sources::{
+    AggregatedSource, ArchiveSource, ContentSource, ImportPlan, LibriVoxSource, SearchQuery,
+    SearchResult,
+};
+use storystream_core::SourceRef;
+use storystream_library::{
+    BookDownloadProgress, BookImporter, ContentDownloader, ImportOptions, LibraryError,
+};
+use storystream_network::{
+    AdvancedDownloadManager, Client, DownloadManager, DownloadManagerConfig, ProgressTracker,
+};
+
+/// Cache namespace for content source data
+const SOURCES_NAMESPACE: &str = "sources";
+
+/// Cache entry holding the results of the last search
+const LAST_SEARCH_KEY: &str = "last_search.json";
+
+/// Width of the progress bar, in characters
+const PROGRESS_WIDTH: usize = 30;
+
+/// JSON record for a single downloaded file
+#[derive(Serialize)]
+struct FetchedFile {
+    title: String,
+    path: PathBuf,
+    bytes: u64,
+    book_id: Option<String>,
+    error: Option<String>,
+}
+
+/// JSON summary of `source fetch`
+#[derive(Serialize)]
+struct FetchReport {
+    title: String,
+    author: String,
+    source: String,
+    destination: PathBuf,
+    files: Vec<FetchedFile>,
+}
+
+/// JSON summary of `download`
+#[derive(Serialize)]
+struct DownloadReport {
+    title: String,
+    author: String,
+    source: String,
+    destination: PathBuf,
+    files: Vec<PathBuf>,
+}
+
+/// Executes a source subcommand
+pub async fn run(out: &Output, action: SourceAction) -> Result<()> {
+    match action {
+        SourceAction::Search {
+            query,
+            source,
+            language,
+            limit,
+        } => search(out, query, source, language, limit).await,
+        SourceAction::Fetch { number, dest } => fetch(out, number, dest).await,
+    }
+}
+
+/// Returns the sources selected by `--source`
+fn sources(kind: SourceKind) -> Vec<Box<dyn ContentSource>> {
+    match kind {
+        SourceKind::Librivox => vec![Box::new(LibriVoxSource::new())],
+        SourceKind::Archive => vec![Box::new(ArchiveSource::new())],
+        SourceKind::All => vec![
+            Box::new(LibriVoxSource::new()),
+            Box::new(ArchiveSource::new()),
+        ],
+    }
+}
+
+/// Catalogue reference recorded on books fetched for a search result, so
+/// bookmarks shared from them say where to fetch the book
+fn source_ref(result: &SearchResult) -> Option<SourceRef> {
+    let source = match result.source.as_str() {
+        "LibriVox" => "librivox",
+        "Internet Archive" => "archive",
+        _ => return None,
+    };
+    Some(SourceRef::new(source, result.id.clone()))
+}
+
+/// Finds the source that produced a search result
+fn source_named(name: &str) -> Result<Box<dyn ContentSource>> {
+    sources(SourceKind::All)
+        .into_iter()
+        .find(|s| s.metadata().name == name)
+        .ok_or_else(|| anyhow!("Unknown source '{}'", name))
+}
+
+async fn search(
+    out: &Output,
+    text: String,
+    kind: SourceKind,
+    language: Option<String>,
+    limit: usize,
+) -> Result<()> {
+    let mut query = SearchQuery::new(text).with_limit(limit);
+    if let Some(language) = language {
+        query = query.with_language(language);
+    }
+
+    // Sources use blocking HTTP and pace their own requests
+    let search = tokio::task::spawn_blocking(move || {
+        sources(kind)
+            .into_iter()
+            .fold(AggregatedSource::new(), AggregatedSource::with_boxed_source)
+            .search_all(&query)
+    })
+    .await
+    .context("Search task failed")?;
+
+    for failure in &search.failures {
+        out.warn(format!("{}: {}", failure.source, failure.error));
+    }
+
+    if search.is_partial() && search.results.is_empty() {
+        bail!("No source could be searched");
+    }
+    let results = search.results;
+
+    save_results(&results)?;
+
+    out.result(&results, || print_results(&results))
+}
+
+fn print_results(results: &[SearchResult]) {
+    if results.is_empty() {
+        println!("No results");
+        return;
+    }
+
+    println!(
+        "{:>3}  {:<40} {:<24} {:<16} {:>9}",
+        "#", "Title", "Author", "Source", "Duration"
+    );
+    for (index, result) in results.iter().enumerate() {
+        println!(
+            "{:>3}  {:<40} {:<24} {:<16} {:>9}",
+            index + 1,
+            truncate(&result.title, 40),
+            truncate(&result.author, 24),
+            truncate(&result.source, 16),
+            result
+                .duration
+                .map(|d| format_duration(d.as_secs()))
+                .unwrap_or_else(|| "-".to_string())
+        );
+    }
+    println!();
+    println!("Run `storystream source fetch <#>` to download a result.");
+}
+
+async fn fetch(out: &Output, number: usize, dest: Option<PathBuf>) -> Result<()> {
+    let results = load_results()?;
+    let result = number
+        .checked_sub(1)
+        .and_then(|i| results.get(i))
+        .cloned()
+        .ok_or_else(|| {
+            anyhow!(
+                "No result #{} in the last search ({} result{})",
+                number,
+                results.len(),
+                if results.len() == 1 { "" } else { "s" }
+            )
+        })?;
+
+    let source = source_ref(&result);
+    let plan = tokio::task::spawn_blocking(move || {
+        let source = source_named(&result.source)?;
+        source
+            .plan_import(&result)
+            .with_context(|| format!("Failed to plan download of '{}'", result.title))
+    })
+    .await
+    .context("Planning task failed")??;
+
+    let dest = match dest {
+        Some(dest) => dest,
+        None => download_dir("Audiobooks")?.join(sanitize_filename(&format!(
+            "{} - {}",
+            plan.author, plan.title
+        ))),
+    };
+    std::fs::create_dir_all(&dest)
+        .with_context(|| format!("Failed to create {}", dest.display()))?;
+
+    out.info(format!(
+        "Fetching '{}' by {} from {} ({} file{})",
+        plan.title,
+        plan.author,
+        plan.source,
+        plan.files.len(),
+        if plan.files.len() == 1 { "" } else { "s" }
+    ));
+
+    let files = download_plan(out, &plan, &dest, source.as_ref()).await?;
+    let failed = files.iter().filter(|f| f.error.is_some()).count();
+    let total = plan.files.len();
+
+    let report = FetchReport {
+        title: plan.title,
+        author: plan.author,
+        source: plan.source,
+        destination: dest,
+        files,
+    };
+
+    if failed > 0 {
+        return Err(out.fail(&report, anyhow!("{} of {} files failed", failed, total)));
+    }
+    out.result(&report, || {})
+}
+
+/// Downloads a LibriVox book by its catalogue ID and imports it as one book
+///
+/// The book gets a folder of its own under `dest`. Running the command again
+/// after an interruption continues the files where they stopped; Ctrl-C
+/// removes the partial files instead.
+pub async fn download(out: &Output, id: &str, dest: Option<PathBuf>) -> Result<()> {
+    let book_id = id.to_string();
+    let plan = tokio::task::spawn_blocking(move || {
+        LibriVoxSource::new()
+            .get_book(&book_id)
+            .map(|book| book.import_plan())
+    })
+    .await
+    .context("Lookup task failed")?
+    .with_context(|| format!("Failed to look up LibriVox book {}", id))?;
+    if plan.files.is_empty() {
+        bail!("'{}' has no downloadable sections", plan.title);
+    }
+
+    let dest = match dest {
+        Some(dest) => dest,
+        None => download_dir("Audiobooks")?,
+    };
+    out.info(format!(
+        "Downloading '{}' by {} from {} ({} file{})",
+        plan.title,
+        plan.author,
+        plan.source,
+        plan.files.len(),
+        if plan.files.len() == 1 { "" } else { "s" }
+    ));
+
+    let pool = open_database().await?;
+    let client = Client::new().context("Failed to create HTTP client")?;
+    let downloads = Arc::new(
+        AdvancedDownloadManager::new(client, DownloadManagerConfig::default())
+            .with_history(download_history()?),
+    );
+    let runner = Arc::clone(&downloads);
+    let worker = tokio::spawn(async move { runner.start().await });
+
+    let downloader = ContentDownloader::new(pool, Arc::clone(&downloads))
+        .with_tag(SourceRef::new("librivox", id).tag());
+    let cancel = downloader.cancel_handle();
+    let interrupt = tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            cancel.cancel();
+        }
+    });
+
+    let latest = Arc::new(Mutex::new(None));
+    let ticker = out
+        .shows_progress()
+        .then(|| tokio::spawn(show_book_progress(Arc::clone(&latest))));
+    let recorder = Arc::clone(&latest);
+    let downloaded = downloader
+        .download_book(&plan, &dest, move |progress| {
+            *recorder.lock().unwrap_or_else(|e| e.into_inner()) = Some(progress.clone());
+        })
+        .await;
+
+    interrupt.abort();
+    if let Some(ticker) = ticker {
+        ticker.abort();
+        if latest.lock().unwrap_or_else(|e| e.into_inner()).is_some() {
+            eprintln!();
+        }
+    }
+    downloads.shutdown().await?;
+    let _ = worker.await;
+
+    let files = match downloaded {
+        Ok(files) => files,
+        Err(LibraryError::Cancelled) => bail!("Download cancelled; partial files removed"),
+        Err(e) => return Err(anyhow!(e).context(format!("Failed to download '{}'", plan.title))),
+    };
+    let report = DownloadReport {
+        destination: ContentDownloader::book_folder(&plan, &dest),
+        title: plan.title,
+        author: plan.author,
+        source: plan.source,
+        files,
+    };
+    out.result(&report, || {
+        println!(
+            "Downloaded '{}' ({} files) to {}",
+            report.title,
+            report.files.len(),
+            report.destination.display()
+        )
+    })
+}
+
+/// Redraws the download of a book's files until the task is aborted,
+/// naming each file as it starts
+async fn show_book_progress(latest: Arc<Mutex<Option<BookDownloadProgress>>>) {
+    let mut shown = None;
+    loop {
+        let progress = latest.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        if let Some(progress) = progress {
+            if shown != Some(progress.file) {
+                if shown.is_some() {
+                    eprintln!();
+                }
+                eprintln!(
+                    "[{}/{}] {}",
+                    progress.file + 1,
+                    progress.files,
+                    progress.title
+                );
+                shown = Some(progress.file);
+            }
+            let percentage = progress
+                .total
+                .map(|total| (progress.downloaded as f64 / total.max(1) as f64 * 100.0).min(100.0));
+            draw_bar(progress.downloaded, percentage);
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+}
+
+/// Downloads and imports every file of a plan, recording per-file failures
+///
+/// Imported books are tagged with `source` when it is known.
+async fn download_plan(
+    out: &Output,
+    plan: &ImportPlan,
+    dest: &Path,
+    source: Option<&SourceRef>,
+) -> Result<Vec<FetchedFile>> {
+    let pool = open_database().await?;
+    let client = Client::new().context("Failed to create HTTP client")?;
+    let downloader = DownloadManager::new(client.clone());
+    let importer = BookImporter::new(pool);
+
+    let mut fetched = Vec::with_capacity(plan.files.len());
+    for (index, file) in plan.files.iter().enumerate() {
+        let path = dest.join(sanitize_filename(&file.file_name));
+        let mut record = FetchedFile {
+            title: file.title.clone(),
+            path: path.clone(),
+            bytes: 0,
+            book_id: None,
+            error: None,
+        };
+
+        if out.shows_progress() {
+            eprintln!("[{}/{}] {}", index + 1, plan.files.len(), file.title);
+        }
+
+        let total = client.content_length(&file.url).await.ok().flatten();
+        let tracker = ProgressTracker::new(total);
+        let ticker = out
+            .shows_progress()
+            .then(|| tokio::spawn(show_progress(tracker.clone())));
+
+        let downloaded = downloader
+            .download_file(&file.url, &path, Some(tracker.clone()))
+            .await;
+
+        if let Some(ticker) = ticker {
+            ticker.abort();
+            draw_progress(&tracker);
+            eprintln!();
+        }
+
+        match downloaded {
+            Ok(bytes) => {
+                record.bytes = bytes;
+                let mut options = ImportOptions::new()
+                    .with_title(file.title.clone())
+                    .with_author(plan.author.clone());
+                if let Some(source) = source {
+                    options = options.with_tag(source.tag());
+                }
+                match importer.import_file(&path, options).await {
+                    Ok(book) => {
+                        record.book_id = Some(book.id.as_string());
+                        out.info(format!("  imported as {}", book.id));
+                    }
+                    Err(e) => {
+                        out.warn(format!(
+                            "  ! saved to {} but import failed: {}",
+                            path.display(),
+                            e
+                        ));
+                        record.error = Some(e.to_string());
+                    }
+                }
+            }
+            Err(e) => {
+                out.warn(format!("  ! download failed: {}", e));
+                let _ = std::fs::remove_file(&path);
+                record.error = Some(e.to_string());
+            }
+        }
+
+        fetched.push(record);
+    }
+
+    Ok(fetched)
+}
+
+/// Redraws the progress bar until the task is aborted
+async fn show_progress(tracker: ProgressTracker) {
+    loop {
+        draw_progress(&tracker);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+}
+
+fn draw_progress(tracker: &ProgressTracker) {
+    let Some(progress) = tracker.get() else {
+        return;
+    };
+    draw_bar(progress.downloaded_bytes, progress.percentage());
+}
+
+/// Redraws the current line as a bar of `downloaded` bytes
+fn draw_bar(downloaded: u64, percentage: Option<f64>) {
+    let mb = downloaded as f64 / 1_000_000.0;
+    let line = match percentage {
+        Some(percent) => {
+            let filled = ((percent / 100.0) * PROGRESS_WIDTH as f64).round() as usize;
+            let filled = filled.min(PROGRESS_WIDTH);
+            format!(
+                "  [{}{}] {:>5.1}% {:>8.1} MB",
+                "#".repeat(filled),
+                "-".repeat(PROGRESS_WIDTH - filled),
+                percent,
+                mb
+            )
+        }
+        None => format!("  {:>8.1} MB", mb),
+    };
+
+    eprint!("\r{}", line);
+    let _ = std::io::stderr().flush();
+}
+
+fn save_results(results: &[SearchResult]) -> Result<()> {
+    let json = serde_json::to_string(results)?;
+    open_cache()?
+        .put(SOURCES_NAMESPACE, LAST_SEARCH_KEY, json.as_bytes())
+        .context("Failed to save search results")?;
+    Ok(())
+}
+
+/// Results of the last search; unreadable results are dropped by the cache
+fn load_results() -> Result<Vec<SearchResult>> {
+    open_cache()?
+        .load(SOURCES_NAMESPACE, LAST_SEARCH_KEY, |data| {
+            serde_json::from_slice(data).map_err(|e| e.to_string())
+        })
+        .ok_or_else(|| anyhow!("No previous search; run `storystream source search` first"))
+}
@@ -0,0 +1,363 @@
+// crates/cli/src/commands/stats.rs
+//! Library and listening statistics
+
+use super::{format_duration, open_database, truncate, Output};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use storystream_core::{csv_field, Duration, LibraryStats, PlaybackStats, Timestamp};
+use storystream_database::{
+    queries::{books, loudness, playback, stats, LoudnessOutlier, LoudnessSummary},
+    DbPool,
+};
+use storystream_library::{LoudnessAnalyzer, LoudnessEvent, VerifyScope};
+use tokio::sync::mpsc;
+
+/// Number of authors shown in the top authors table
+const TOP_AUTHORS: i64 = 5;
+
+/// Books further than this from the median loudness are listed, in dB
+const LOUDNESS_OUTLIER_DB: f32 = 6.0;
+
+/// Stable JSON schema for `stats --json`
+#[derive(Debug, Serialize)]
+pub struct StatsReport {
+    library: LibrarySection,
+    listening: ListeningSection,
+    top_authors: Vec<AuthorCount>,
+    /// Only with `--loudness`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    loudness: Option<LoudnessSection>,
+}
+
+#[derive(Debug, Serialize)]
+struct LibrarySection {
+    books: usize,
+    chapters: usize,
+    bookmarks: usize,
+    playlists: usize,
+    total_duration_secs: u64,
+    total_size_bytes: u64,
+    favorites: usize,
+    finished: usize,
+    in_progress: usize,
+    authors: usize,
+    narrators: usize,
+    series: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct ListeningSection {
+    since: Option<String>,
+    listening_time_secs: u64,
+    books_started: usize,
+    books_finished: usize,
+    completion_rate: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct LoudnessSection {
+    #[serde(flatten)]
+    summary: LoudnessSummary,
+    /// Books that could not be measured this run
+    failed: usize,
+    outliers: Vec<LoudnessOutlier>,
+}
+
+#[derive(Debug, Serialize)]
+struct AuthorCount {
+    author: String,
+    books: usize,
+}
+
+/// Executes the stats command
+pub async fn run(
+    out: &Output,
+    csv: Option<&Path>,
+    since: Option<Duration>,
+    measure_loudness: bool,
+) -> Result<()> {
+    let pool = open_database().await?;
+
+    if let Some(path) = csv {
+        let rows = write_csv(&pool, path).await?;
+        out.info(format!("Wrote {} books to {}", rows, path.display()));
+    }
+
+    let mut report = collect_report(&pool, since).await?;
+    if measure_loudness {
+        report.loudness = Some(analyze_loudness(out, &pool).await?);
+    }
+    out.result(&report, || print_report(&report))
+}
+
+/// Measures the books not measured yet, then sums up the whole library
+///
+/// Ctrl-C stops the run; what was measured so far is kept and the next run
+/// carries on from there.
+async fn analyze_loudness(out: &Output, pool: &DbPool) -> Result<LoudnessSection> {
+    let (tx, mut rx) = mpsc::channel(64);
+    let analyzer = LoudnessAnalyzer::new(pool.clone()).with_events(tx);
+
+    let cancel = analyzer.cancel_handle();
+    let interrupt = tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            cancel.cancel();
+        }
+    });
+    let show_progress = out.shows_progress();
+    let progress = tokio::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            match event {
+                LoudnessEvent::Analyzed { done, total, title } if show_progress => {
+                    eprint!("\r\x1b[2K[{}/{}] {}", done, total, truncate(&title, 50));
+                }
+                LoudnessEvent::Finished { .. } => break,
+                _ => {}
+            }
+        }
+    });
+
+    let report = analyzer.analyze(VerifyScope::All).await;
+    interrupt.abort();
+    if report.is_ok() {
+        let _ = progress.await;
+        if show_progress {
+            eprint!("\r\x1b[2K");
+        }
+    } else {
+        progress.abort();
+    }
+    let report = report?;
+
+    if report.cancelled {
+        out.warn("Loudness analysis interrupted; run it again to measure the rest");
+    }
+    for failure in &report.failures {
+        out.warn(format!(
+            "Could not measure {}: {}",
+            failure.title, failure.error
+        ));
+    }
+    Ok(LoudnessSection {
+        summary: report.summary,
+        failed: report.failures.len(),
+        outliers: loudness::loudness_outliers(pool, LOUDNESS_OUTLIER_DB).await?,
+    })
+}
+
+/// Gathers library and listening statistics, counting listening within `since`
+pub async fn collect_report(pool: &DbPool, since: Option<Duration>) -> Result<StatsReport> {
+    let cutoff =
+        since.map(|d| Timestamp::from_millis(Timestamp::now().as_millis() - d.as_millis() as i64));
+
+    let library = stats::get_library_stats(pool).await?;
+    let listening = stats::get_playback_stats(pool, cutoff).await?;
+    let top_authors = stats::get_top_authors(pool, TOP_AUTHORS).await?;
+
+    Ok(build_report(&library, &listening, top_authors, since))
+}
+
+fn build_report(
+    library: &LibraryStats,
+    listening: &PlaybackStats,
+    top_authors: Vec<(String, usize)>,
+    since: Option<Duration>,
+) -> StatsReport {
+    StatsReport {
+        library: LibrarySection {
+            books: library.total_books,
+            chapters: library.total_chapters,
+            bookmarks: library.total_bookmarks,
+            playlists: library.total_playlists,
+            total_duration_secs: library.total_duration.as_seconds(),
+            total_size_bytes: library.total_size_bytes,
+            favorites: library.favorite_count,
+            finished: library.finished_count,
+            in_progress: library.unfinished_count,
+            authors: library.authors_count,
+            narrators: library.narrators_count,
+            series: library.series_count,
+        },
+        listening: ListeningSection {
+            since: since.map(format_window),
+            listening_time_secs: listening.total_listening_time.as_seconds(),
+            books_started: listening.books_started,
+            books_finished: listening.books_finished,
+            completion_rate: listening.completion_rate(),
+        },
+        top_authors: top_authors
+            .into_iter()
+            .map(|(author, books)| AuthorCount { author, books })
+            .collect(),
+        loudness: None,
+    }
+}
+
+fn print_report(report: &StatsReport) {
+    let lib = &report.library;
+    let rows = [
+        ("Books", lib.books.to_string()),
+        ("Total length", format_duration(lib.total_duration_secs)),
+        ("Size", format_size(lib.total_size_bytes)),
+        ("Chapters", lib.chapters.to_string()),
+        ("Bookmarks", lib.bookmarks.to_string()),
+        ("Playlists", lib.playlists.to_string()),
+        ("Favorites", lib.favorites.to_string()),
+        ("Finished", lib.finished.to_string()),
+        ("In progress", lib.in_progress.to_string()),
+        ("Authors", lib.authors.to_string()),
+    ];
+    println!("Library");
+    print_rows(&rows);
+
+    let listen = &report.listening;
+    match &listen.since {
+        Some(since) => println!("\nListening (last {})", since),
+        None => println!("\nListening"),
+    }
+    let rows = [
+        (
+            "Listening time",
+            format_duration(listen.listening_time_secs),
+        ),
+        ("Books started", listen.books_started.to_string()),
+        ("Books finished", listen.books_finished.to_string()),
+        ("Completion", format!("{:.0}%", listen.completion_rate)),
+    ];
+    print_rows(&rows);
+
+    if !report.top_authors.is_empty() {
+        println!("\nTop authors");
+        let width = report
+            .top_authors
+            .iter()
+            .map(|a| a.author.chars().count())
+            .max()
+            .unwrap_or(0);
+        for entry in &report.top_authors {
+            println!(
+                "  {:<width$}  {:>3} book{}",
+                entry.author,
+                entry.books,
+                if entry.books == 1 { "" } else { "s" },
+                width = width
+            );
+        }
+    }
+
+    if let Some(loudness) = &report.loudness {
+        print_loudness(loudness);
+    }
+}
+
+fn print_loudness(section: &LoudnessSection) {
+    let summary = &section.summary;
+    let level = |db: Option<f32>| {
+        db.map(|db| format!("{:.1} dB", db))
+            .unwrap_or_else(|| "-".to_string())
+    };
+    println!("\nLoudness");
+    let rows = [
+        ("Measured", summary.analyzed.to_string()),
+        ("Not measured", summary.pending.to_string()),
+        ("Quietest", level(summary.quietest_db)),
+        ("Quietest 10%", level(summary.p10_db)),
+        ("Median", level(summary.median_db)),
+        ("Loudest 10%", level(summary.p90_db)),
+        ("Loudest", level(summary.loudest_db)),
+    ];
+    print_rows(&rows);
+
+    if !section.outliers.is_empty() {
+        println!("\nMore than {:.0} dB from the median", LOUDNESS_OUTLIER_DB);
+        for outlier in &section.outliers {
+            println!(
+                "  {:>+6.1} dB  {}",
+                outlier.deviation_db,
+                truncate(&outlier.title, 60)
+            );
+        }
+    }
+}
+
+fn print_rows(rows: &[(&str, String)]) {
+    let width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+    for (label, value) in rows {
+        println!("  {:<width$}  {:>14}", label, value, width = width);
+    }
+}
+
+/// Writes one row per book and returns the number of rows written
+async fn write_csv(pool: &DbPool, path: &Path) -> Result<usize> {
+    let all_books = books::list_books(pool).await?;
+    let positions: HashMap<_, _> = playback::list_playback_states(pool)
+        .await?
+        .into_iter()
+        .map(|s| (s.book_id, s.position))
+        .collect();
+
+    let file =
+        File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut out = BufWriter::new(file);
+
+    writeln!(
+        out,
+        "id,title,author,narrator,duration_secs,position_secs,progress_percent,play_count,favorite,last_played"
+    )?;
+    for book in &all_books {
+        let position = positions
+            .get(&book.id)
+            .copied()
+            .unwrap_or(Duration::ZERO)
+            .min(book.duration);
+        let progress = if book.duration.is_zero() {
+            0.0
+        } else {
+            position.as_millis() as f64 / book.duration.as_millis() as f64 * 100.0
+        };
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{:.1},{},{},{}",
+            book.id,
+            csv_field(&book.title),
+            csv_field(book.author.as_deref().unwrap_or("")),
+            csv_field(book.narrator.as_deref().unwrap_or("")),
+            book.duration.as_seconds(),
+            position.as_seconds(),
+            progress,
+            book.play_count,
+            book.is_favorite,
+            book.last_played
+                .map(|t| t.as_millis().to_string())
+                .unwrap_or_default(),
+        )?;
+    }
+    out.flush()?;
+
+    Ok(all_books.len())
+}
+
+/// Formats a `--since` window, preferring whole days
+fn format_window(window: Duration) -> String {
+    let secs = window.as_seconds();
+    if secs > 0 && secs.is_multiple_of(86_400) {
+        format!("{}d", secs / 86_400)
+    } else {
+        format_duration(secs)
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const GB: f64 = 1_000_000_000.0;
+    const MB: f64 = 1_000_000.0;
+    let b = bytes as f64;
+    if b >= GB {
+        format!("{:.2} GB", b / GB)
+    } else {
+        format!("{:.1} MB", b / MB)
+    }
+}
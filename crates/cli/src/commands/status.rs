@@ -0,0 +1,48 @@
+// crates/cli/src/commands/status.rs
+//! Playback status between runs of the player
+
+use super::library::{self, BookRecord};
+use super::{format_duration, open_existing_database, Output};
+use anyhow::Result;
+use serde::Serialize;
+
+/// Status as reported by `status`
+#[derive(Debug, Serialize)]
+pub struct StatusReport {
+    /// Whether a book is playing; only `storystream tui` plays, so from the
+    /// command line this is always false
+    pub playing: bool,
+    /// The book played most recently, `null` before any book is played
+    pub last_played: Option<BookRecord>,
+}
+
+/// Shows the book played last and how far into it the listener is
+pub async fn run(out: &Output) -> Result<()> {
+    let pool = open_existing_database().await?;
+    let report = StatusReport {
+        playing: false,
+        last_played: library::last_played_record(&pool).await?,
+    };
+
+    out.result(&report, || {
+        println!("Current Status:");
+        println!("  Playback: Stopped");
+        match &report.last_played {
+            Some(book) => {
+                match &book.author {
+                    Some(author) => println!("  Last played: {} by {}", book.title, author),
+                    None => println!("  Last played: {}", book.title),
+                }
+                println!(
+                    "  Position: {} / {} ({})",
+                    format_duration(book.position_secs),
+                    format_duration(book.duration_secs),
+                    library::progress_text(book)
+                );
+            }
+            None => println!("  Last played: nothing yet"),
+        }
+    })?;
+    out.info("\nNote: Use 'storystream tui' for real-time status display");
+    Ok(())
+}
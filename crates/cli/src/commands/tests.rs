@@ -1,11 +1,11 @@
 use super::*;
+use std::path::PathBuf;
 use storystream_core::{Book, Duration};
 use storystream_database::{
     connection::{connect, DatabaseConfig},
     migrations::run_migrations,
     queries::books,
 };
-use std::path::PathBuf;
 use tempfile::NamedTempFile;
 
 async fn setup_test_db() -> (storystream_database::DbPool, NamedTempFile) {
@@ -64,7 +64,9 @@ async fn test_search_books_finds_results() {
 
     assert_eq!(results.len(), 2);
     assert!(results.iter().any(|r| r.item.title.contains("Gatsby")));
-    assert!(results.iter().any(|r| r.item.title.contains("Expectations")));
+    assert!(results
+        .iter()
+        .any(|r| r.item.title.contains("Expectations")));
 }
 
 #[tokio::test]
@@ -254,4 +256,725 @@ async fn test_mark_played_updates_stats() {
     let retrieved = books::get_book(&pool, book.id).await.unwrap();
     assert_eq!(retrieved.play_count, 1);
     assert!(retrieved.last_played.is_some());
-}
\ No newline at end of file
+}
+#[test]
+fn test_feed_refresh_requires_feed_or_all() {
+    assert!(Cli::try_parse_from(["storystream", "feed", "refresh"]).is_err());
+    assert!(Cli::try_parse_from(["storystream", "feed", "refresh", "--all"]).is_ok());
+    assert!(Cli::try_parse_from(["storystream", "feed", "refresh", "show", "--all"]).is_err());
+}
+
+#[test]
+fn test_search_takes_text_or_filters() {
+    assert!(Cli::try_parse_from(["storystream", "search"]).is_err());
+    assert!(Cli::try_parse_from(["storystream", "search", "--finished", "--unfinished"]).is_err());
+
+    let cli = Cli::try_parse_from([
+        "storystream",
+        "search",
+        "--author",
+        "Sanderson",
+        "--unfinished",
+        "--min-length",
+        "20h",
+    ])
+    .unwrap();
+    match cli.command {
+        Commands::Search { query, filter, .. } => {
+            assert!(query.is_none());
+            let filter = storystream_database::search::SearchFilter::from(&filter);
+            assert_eq!(filter.author.as_deref(), Some("Sanderson"));
+            assert_eq!(filter.finished, Some(false));
+            assert_eq!(filter.min_duration, Some(Duration::from_seconds(20 * 3600)));
+            assert!(filter.max_duration.is_none());
+        }
+        _ => panic!("Expected search"),
+    }
+
+    let cli = Cli::try_parse_from([
+        "storystream",
+        "search",
+        "--author",
+        "Le Guin",
+        "--sort",
+        "-duration",
+    ])
+    .unwrap();
+    match cli.command {
+        Commands::Search { sort, .. } => assert_eq!(sort.as_deref(), Some("-duration")),
+        _ => panic!("Expected search"),
+    }
+}
+
+#[test]
+fn test_feed_download_defaults_to_latest_episode() {
+    let cli = Cli::try_parse_from(["storystream", "feed", "download", "show"]).unwrap();
+    match cli.command {
+        Commands::Feed {
+            action: FeedAction::Download { feed, latest, dir },
+        } => {
+            assert_eq!(feed, "show");
+            assert_eq!(latest, 1);
+            assert!(dir.is_none());
+        }
+        _ => panic!("Expected feed download"),
+    }
+}
+
+#[test]
+fn test_feed_policy_takes_optional_settings() {
+    let cli = Cli::try_parse_from([
+        "storystream",
+        "feed",
+        "policy",
+        "show",
+        "--keep",
+        "3",
+        "--auto-download",
+        "true",
+    ])
+    .unwrap();
+    match cli.command {
+        Commands::Feed {
+            action:
+                FeedAction::Policy {
+                    feed,
+                    keep,
+                    auto_download,
+                    delete_finished,
+                    ..
+                },
+        } => {
+            assert_eq!(feed, "show");
+            assert_eq!(keep, Some(3));
+            assert_eq!(auto_download, Some(true));
+            assert_eq!(delete_finished, None);
+        }
+        _ => panic!("Expected feed policy"),
+    }
+}
+
+#[test]
+fn test_source_search_defaults_to_all_sources() {
+    let cli = Cli::try_parse_from(["storystream", "source", "search", "dracula"]).unwrap();
+    match cli.command {
+        Commands::Source {
+            action:
+                SourceAction::Search {
+                    query,
+                    source,
+                    language,
+                    ..
+                },
+        } => {
+            assert_eq!(query, "dracula");
+            assert_eq!(source, SourceKind::All);
+            assert!(language.is_none());
+        }
+        _ => panic!("Expected source search"),
+    }
+
+    assert!(Cli::try_parse_from([
+        "storystream",
+        "source",
+        "search",
+        "dracula",
+        "--source",
+        "gutenberg"
+    ])
+    .is_err());
+}
+
+#[test]
+fn test_source_fetch_parses_number_and_dest() {
+    let cli = Cli::try_parse_from([
+        "storystream",
+        "source",
+        "fetch",
+        "3",
+        "--dest",
+        "/tmp/books",
+    ])
+    .unwrap();
+    match cli.command {
+        Commands::Source {
+            action: SourceAction::Fetch { number, dest },
+        } => {
+            assert_eq!(number, 3);
+            assert_eq!(dest, Some(PathBuf::from("/tmp/books")));
+        }
+        _ => panic!("Expected source fetch"),
+    }
+}
+
+#[test]
+fn test_download_parses_id_and_dest() {
+    let cli =
+        Cli::try_parse_from(["storystream", "download", "59", "--dest", "/tmp/books"]).unwrap();
+    match cli.command {
+        Commands::Download { id, dest } => {
+            assert_eq!(id, "59");
+            assert_eq!(dest, Some(PathBuf::from("/tmp/books")));
+        }
+        _ => panic!("Expected download"),
+    }
+}
+
+#[test]
+fn test_bookmark_share_and_open_parse() {
+    let cli = Cli::try_parse_from(["storystream", "bookmark", "share", "some-id"]).unwrap();
+    match cli.command {
+        Commands::Bookmark {
+            action: BookmarkAction::Share { id },
+        } => assert_eq!(id, "some-id"),
+        _ => panic!("Expected bookmark share"),
+    }
+
+    let link = "storystream://bookmark?title=Emma&at=0:45:10";
+    let cli = Cli::try_parse_from(["storystream", "open", link]).unwrap();
+    match cli.command {
+        Commands::Open { link: parsed } => assert_eq!(parsed, link),
+        _ => panic!("Expected open"),
+    }
+}
+
+#[test]
+fn test_doctor_flags() {
+    let cli = Cli::try_parse_from(["storystream", "doctor", "--fix", "--verify-audio"]).unwrap();
+    match cli.command {
+        Commands::Doctor {
+            fix,
+            verify_audio,
+            verify_files,
+            full,
+            prune_downloads,
+            duplicates,
+            maintenance,
+            clean,
+        } => {
+            assert!(fix);
+            assert!(verify_audio);
+            assert!(!verify_files);
+            assert!(!full);
+            assert!(prune_downloads.is_none());
+            assert!(!duplicates);
+            assert!(!maintenance);
+            assert!(!clean);
+        }
+        _ => panic!("Expected doctor"),
+    }
+
+    let cli = Cli::try_parse_from(["storystream", "doctor", "--prune-downloads", "30d"]).unwrap();
+    match cli.command {
+        Commands::Doctor {
+            prune_downloads, ..
+        } => assert_eq!(prune_downloads, Some(Duration::from_seconds(30 * 86400))),
+        _ => panic!("Expected doctor"),
+    }
+
+    let cli = Cli::try_parse_from(["storystream", "doctor", "--duplicates"]).unwrap();
+    match cli.command {
+        Commands::Doctor { duplicates, .. } => assert!(duplicates),
+        _ => panic!("Expected doctor"),
+    }
+
+    let cli = Cli::try_parse_from(["storystream", "doctor", "--maintenance"]).unwrap();
+    match cli.command {
+        Commands::Doctor { maintenance, .. } => assert!(maintenance),
+        _ => panic!("Expected doctor"),
+    }
+
+    let cli = Cli::try_parse_from(["storystream", "doctor", "--clean"]).unwrap();
+    match cli.command {
+        Commands::Doctor { clean, .. } => assert!(clean),
+        _ => panic!("Expected doctor"),
+    }
+}
+
+#[test]
+fn test_doctor_full_requires_verify_files() {
+    assert!(Cli::try_parse_from(["storystream", "doctor", "--full"]).is_err());
+
+    let cli = Cli::try_parse_from(["storystream", "doctor", "--verify-files", "--full"]).unwrap();
+    match cli.command {
+        Commands::Doctor {
+            verify_files, full, ..
+        } => {
+            assert!(verify_files);
+            assert!(full);
+        }
+        _ => panic!("Expected doctor"),
+    }
+}
+
+#[test]
+fn test_verify_fix_flag() {
+    let cli = Cli::try_parse_from(["storystream", "verify"]).unwrap();
+    assert!(matches!(cli.command, Commands::Verify { fix: false }));
+
+    let cli = Cli::try_parse_from(["storystream", "verify", "--fix"]).unwrap();
+    assert!(matches!(cli.command, Commands::Verify { fix: true }));
+}
+
+#[test]
+fn test_config_subcommands_parse() {
+    let cli = Cli::try_parse_from(["storystream", "config", "--full"]).unwrap();
+    assert!(matches!(
+        cli.command,
+        Commands::Config {
+            action: None,
+            full: true
+        }
+    ));
+
+    let cli = Cli::try_parse_from(["storystream", "config", "validate"]).unwrap();
+    assert!(matches!(
+        cli.command,
+        Commands::Config {
+            action: Some(ConfigAction::Validate),
+            ..
+        }
+    ));
+
+    let cli = Cli::try_parse_from(["storystream", "--json", "config", "schema"]).unwrap();
+    assert!(matches!(
+        cli.command,
+        Commands::Config {
+            action: Some(ConfigAction::Schema),
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_completions_generate_for_every_shell() {
+    use clap::ValueEnum;
+    use clap_complete::Shell;
+
+    for shell in Shell::value_variants() {
+        let mut out = Vec::new();
+        completions::write_completions(*shell, &mut out).unwrap();
+        let script = String::from_utf8(out).unwrap();
+        assert!(script.contains("storystream"), "empty script for {}", shell);
+    }
+}
+
+#[test]
+fn test_manpage_renders() {
+    let mut out = Vec::new();
+    completions::write_manpage(&mut out).unwrap();
+    let page = String::from_utf8(out).unwrap();
+    assert!(page.contains(".TH storystream"));
+}
+
+#[test]
+fn test_import_strategy_defaults_to_skip() {
+    let cli = Cli::try_parse_from(["storystream", "import", "library.json"]).unwrap();
+    match cli.command {
+        Commands::Import {
+            file,
+            strategy,
+            yes,
+        } => {
+            assert_eq!(file, PathBuf::from("library.json"));
+            assert_eq!(strategy, ImportStrategyArg::Skip);
+            assert!(!yes);
+        }
+        _ => panic!("Expected import"),
+    }
+
+    assert!(
+        Cli::try_parse_from(["storystream", "import", "x.json", "--strategy", "replace"]).is_err()
+    );
+    assert!(Cli::try_parse_from(["storystream", "export"]).is_err());
+}
+
+#[test]
+fn test_import_progress_requires_format() {
+    let cli = Cli::try_parse_from([
+        "storystream",
+        "import-progress",
+        "abs.json",
+        "--format",
+        "abs",
+        "--dry-run",
+    ])
+    .unwrap();
+    match cli.command {
+        Commands::ImportProgress {
+            file,
+            format,
+            dry_run,
+        } => {
+            assert_eq!(file, PathBuf::from("abs.json"));
+            assert_eq!(format, ProgressFormat::Abs);
+            assert!(dry_run);
+        }
+        _ => panic!("Expected import-progress"),
+    }
+
+    assert!(Cli::try_parse_from(["storystream", "import-progress", "progress.csv"]).is_err());
+    assert!(Cli::try_parse_from([
+        "storystream",
+        "import-progress",
+        "progress.csv",
+        "--format",
+        "smart-audiobook-player"
+    ])
+    .is_err());
+}
+
+#[test]
+fn test_global_output_flags() {
+    let cli = Cli::try_parse_from(["storystream", "stats", "--json"]).unwrap();
+    assert!(cli.json);
+    assert!(!cli.quiet);
+
+    let cli = Cli::try_parse_from(["storystream", "-q", "list"]).unwrap();
+    assert!(cli.quiet);
+    let out = Output::new(cli.json, cli.quiet);
+    assert!(!out.is_json());
+    assert!(!out.shows_progress());
+
+    assert!(Cli::try_parse_from(["storystream", "list", "--json", "--quiet"]).is_err());
+}
+
+#[test]
+fn test_config_override_flag() {
+    let cli = Cli::try_parse_from([
+        "storystream",
+        "list",
+        "--config-override",
+        "player.default_volume=80",
+        "--config-override",
+        "library.paths=/a:/b",
+    ])
+    .unwrap();
+    assert_eq!(
+        cli.config_overrides,
+        vec![
+            ("player.default_volume".to_string(), "80".to_string()),
+            ("library.paths".to_string(), "/a:/b".to_string()),
+        ]
+    );
+
+    let cli = Cli::try_parse_from([
+        "storystream",
+        "--config-override",
+        "app.device_name=",
+        "list",
+    ])
+    .unwrap();
+    assert_eq!(cli.config_overrides[0].1, "");
+
+    assert!(Cli::try_parse_from(["storystream", "list", "--config-override", "volume"]).is_err());
+    assert!(Cli::try_parse_from(["storystream", "list", "--config-override", "=80"]).is_err());
+}
+
+#[test]
+fn test_envelope_schema() {
+    let value = serde_json::to_value(output::Envelope::success(vec![1, 2])).unwrap();
+    assert_eq!(value["ok"], true);
+    assert_eq!(value["data"], serde_json::json!([1, 2]));
+    assert!(value["error"].is_null());
+
+    let error = anyhow::Error::new(storystream_core::AppError::RecordNotFound {
+        entity: "Book".to_string(),
+        identifier: "abc".to_string(),
+    })
+    .context("Failed to load book");
+    let value = serde_json::to_value(output::Envelope::<()>::failure(None, &error)).unwrap();
+    assert_eq!(value["ok"], false);
+    assert!(value["data"].is_null());
+    assert_eq!(value["error"]["code"], "RECORD_NOT_FOUND");
+    assert!(value["error"]["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("Failed to load book"));
+
+    let plain = anyhow::anyhow!("something broke");
+    let body = output::ErrorBody::from_error(&plain);
+    assert_eq!(body.code, output::GENERIC_ERROR_CODE);
+}
+
+#[tokio::test]
+async fn test_list_json_schema() {
+    let (pool, _temp) = setup_test_db().await;
+    let book = create_sample_book(&pool, "Listed").await;
+
+    let records = library::list_records(&pool, None, false, Default::default(), -1)
+        .await
+        .unwrap();
+    let value = serde_json::to_value(output::Envelope::success(&records)).unwrap();
+
+    let entry = &value["data"][0];
+    assert_eq!(entry["id"], book.id.as_string());
+    assert_eq!(entry["title"], "Listed");
+    assert_eq!(entry["author"], "Test Author");
+    assert_eq!(entry["duration_secs"], 3600);
+    assert_eq!(entry["favorite"], false);
+    assert_eq!(entry["play_count"], 0);
+    assert_eq!(entry["position_secs"], 0);
+    assert_eq!(entry["progress_percent"], 0.0);
+    assert!(entry["file_path"].is_string());
+
+    let favorites = library::list_records(&pool, None, true, Default::default(), -1)
+        .await
+        .unwrap();
+    assert!(favorites.is_empty());
+}
+
+#[tokio::test]
+async fn test_list_sorts_limits_and_shows_progress() {
+    use storystream_core::PlaybackState;
+    use storystream_database::queries::{books::BookSort, playback};
+
+    let (pool, _temp) = setup_test_db().await;
+    let mut short = create_sample_book(&pool, "Short").await;
+    short.duration = Duration::from_seconds(600);
+    books::update_book(&pool, &short).await.unwrap();
+    let long = create_sample_book(&pool, "Long").await;
+    let mut state = PlaybackState::new(long.id);
+    state.position = Duration::from_seconds(900);
+    playback::create_playback_state(&pool, &state)
+        .await
+        .unwrap();
+
+    let records = library::list_records(&pool, None, false, BookSort::Title, -1)
+        .await
+        .unwrap();
+    let titles: Vec<&str> = records.iter().map(|r| r.title.as_str()).collect();
+    assert_eq!(titles, ["Long", "Short"]);
+    assert_eq!(records[0].position_secs, 900);
+    assert_eq!(records[0].progress_percent, 25.0);
+    assert_eq!(records[1].progress_percent, 0.0);
+
+    let longest = library::list_records(&pool, None, false, BookSort::DurationDesc, 1)
+        .await
+        .unwrap();
+    assert_eq!(longest.len(), 1);
+    assert_eq!(longest[0].title, "Long");
+
+    let by_author = library::list_records(&pool, Some("Nobody"), false, BookSort::Title, -1)
+        .await
+        .unwrap();
+    assert!(by_author.is_empty());
+}
+
+#[test]
+fn test_list_parses_sort_and_limit() {
+    let cli =
+        Cli::try_parse_from(["storystream", "list", "--sort", "-added", "--limit", "5"]).unwrap();
+    match cli.command {
+        Commands::List { sort, limit, .. } => {
+            assert_eq!(sort.as_deref(), Some("-added"));
+            assert_eq!(limit, Some(5));
+        }
+        _ => panic!("Expected list"),
+    }
+}
+
+#[test]
+fn test_exit_codes_follow_the_app_error() {
+    let missing = anyhow::Error::new(AppError::FileNotFound {
+        path: PathBuf::from("/nowhere"),
+    })
+    .context("Cannot scan /nowhere");
+    assert_eq!(output::exit_code(&missing), 66);
+
+    let locked = anyhow::Error::new(AppError::DatabaseLocked {
+        operation: "list".to_string(),
+    });
+    assert_eq!(output::exit_code(&locked), 69);
+    assert_eq!(output::exit_code(&anyhow::anyhow!("something broke")), 1);
+}
+
+#[tokio::test]
+async fn test_search_json_schema() {
+    let (pool, _temp) = setup_test_db().await;
+    create_sample_book(&pool, "Great Expectations").await;
+    create_sample_book(&pool, "Moby Dick").await;
+
+    let hits = library::search_records(&pool, Some("Great"), &Default::default(), None, 10)
+        .await
+        .unwrap();
+    let value = serde_json::to_value(output::Envelope::success(&hits)).unwrap();
+
+    let data = value["data"].as_array().unwrap();
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0]["title"], "Great Expectations");
+    assert!(data[0]["rank"].is_number());
+    assert!(data[0]["id"].is_string());
+}
+
+#[tokio::test]
+async fn test_stats_json_schema() {
+    let (pool, _temp) = setup_test_db().await;
+    create_sample_book(&pool, "Counted").await;
+
+    let report = stats::collect_report(&pool, None).await.unwrap();
+    let value = serde_json::to_value(output::Envelope::success(&report)).unwrap();
+
+    let data = &value["data"];
+    assert!(data["library"].is_object());
+    assert!(data["listening"].is_object());
+    assert!(data["top_authors"].is_array());
+    assert_eq!(data["library"]["books"], 1);
+}
+
+#[test]
+fn test_playlist_subcommands_parse() {
+    let cli = Cli::try_parse_from([
+        "storystream",
+        "playlist",
+        "create",
+        "Austen",
+        "--smart",
+        r#"{"authors": ["Jane Austen"]}"#,
+    ])
+    .unwrap();
+    match cli.command {
+        Commands::Playlist {
+            action: PlaylistAction::Create { name, smart },
+        } => {
+            assert_eq!(name, "Austen");
+            assert!(smart.unwrap().contains("Jane Austen"));
+        }
+        _ => panic!("Expected playlist create"),
+    }
+
+    let cli =
+        Cli::try_parse_from(["storystream", "playlist", "add", "Trip", "--book", "Emma"]).unwrap();
+    match cli.command {
+        Commands::Playlist {
+            action: PlaylistAction::Add { name, book },
+        } => {
+            assert_eq!(name, "Trip");
+            assert_eq!(book, "Emma");
+        }
+        _ => panic!("Expected playlist add"),
+    }
+
+    assert!(Cli::try_parse_from(["storystream", "playlist", "add", "Trip"]).is_err());
+
+    let cli =
+        Cli::try_parse_from(["storystream", "playlist", "play", "Trip", "--shuffle"]).unwrap();
+    match cli.command {
+        Commands::Playlist {
+            action: PlaylistAction::Play { name, shuffle },
+        } => {
+            assert_eq!(name, "Trip");
+            assert!(shuffle);
+        }
+        _ => panic!("Expected playlist play"),
+    }
+}
+
+#[tokio::test]
+async fn test_resolve_unknown_book_is_not_found() {
+    let (pool, _temp) = setup_test_db().await;
+    create_sample_book(&pool, "Emma").await;
+
+    assert_eq!(resolve_book(&pool, "emma").await.unwrap().title, "Emma");
+
+    let error = resolve_book(&pool, "Persuasion").await.unwrap_err();
+    assert_eq!(
+        output::ErrorBody::from_error(&error).code,
+        "RECORD_NOT_FOUND"
+    );
+    assert_eq!(error.to_string(), "No book matching 'Persuasion'");
+}
+
+#[test]
+fn test_scan_plan_flags() {
+    let cli = Cli::try_parse_from([
+        "storystream",
+        "scan",
+        "/books",
+        "--dry-run",
+        "--save-plan",
+        "plan.json",
+    ])
+    .unwrap();
+    match cli.command {
+        Commands::Scan {
+            path,
+            dry_run,
+            save_plan,
+            full,
+            commit_plan,
+        } => {
+            assert_eq!(path.as_deref(), Some("/books"));
+            assert!(dry_run);
+            assert_eq!(save_plan, Some(PathBuf::from("plan.json")));
+            assert!(!full);
+            assert!(commit_plan.is_none());
+        }
+        _ => panic!("Expected scan"),
+    }
+
+    assert!(Cli::try_parse_from(["storystream", "scan", "--commit-plan", "plan.json"]).is_ok());
+    assert!(Cli::try_parse_from([
+        "storystream",
+        "scan",
+        "/books",
+        "--commit-plan",
+        "plan.json"
+    ])
+    .is_err());
+}
+
+#[test]
+fn test_scan_full_flag() {
+    let cli = Cli::try_parse_from(["storystream", "scan", "--full"]).unwrap();
+    assert!(matches!(cli.command, Commands::Scan { full: true, .. }));
+    assert!(Cli::try_parse_from([
+        "storystream",
+        "scan",
+        "--full",
+        "--commit-plan",
+        "plan.json"
+    ])
+    .is_err());
+}
+
+#[test]
+fn test_scan_plan_lines() {
+    use storystream_library::{PlannedAction, PlannedImport, SkipReason};
+
+    let book = Book::new(
+        "Emma".to_string(),
+        PathBuf::from("/books/emma.mp3"),
+        1_000,
+        Duration::from_seconds(60),
+    );
+    let mut item = PlannedImport {
+        path: book.file_path.clone(),
+        action: PlannedAction::Create,
+        book: Some(book.clone()),
+        file_hash: Some("abc".to_string()),
+        include: true,
+    };
+    assert_eq!(scan::plan_line(&item), "  + Emma (/books/emma.mp3)");
+
+    item.action = PlannedAction::Update {
+        book_id: book.id,
+        changes: vec!["title".to_string(), "duration".to_string()],
+    };
+    assert_eq!(scan::plan_line(&item), "  ~ Emma (title, duration changed)");
+
+    item.include = false;
+    assert_eq!(scan::plan_line(&item), "  - Emma (excluded)");
+
+    let skipped = PlannedImport {
+        path: PathBuf::from("/books/notes.txt"),
+        action: PlannedAction::Skip(SkipReason::Unsupported),
+        book: None,
+        file_hash: None,
+        include: false,
+    };
+    assert_eq!(
+        scan::plan_line(&skipped),
+        "  - notes.txt: not a supported audio format"
+    );
+}
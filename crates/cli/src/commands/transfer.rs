@@ -0,0 +1,219 @@
+// crates/cli/src/commands/transfer.rs
+//! Library export and import commands
+
+use super::{confirm, format_duration, open_database, ImportStrategyArg, Output, ProgressFormat};
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::path::Path;
+use storystream_database::export::{
+    export_library, import_library, preview_import, ExportOptions, ImportCounts, ImportStrategy,
+    ImportSummary, LibraryExport,
+};
+use storystream_library::{
+    AudiobookshelfImporter, CsvImporter, ExternalImporter, ProgressImportReport, ProgressImporter,
+};
+
+impl From<ImportStrategyArg> for ImportStrategy {
+    fn from(arg: ImportStrategyArg) -> Self {
+        match arg {
+            ImportStrategyArg::Skip => ImportStrategy::Skip,
+            ImportStrategyArg::Overwrite => ImportStrategy::Overwrite,
+            ImportStrategyArg::Merge => ImportStrategy::Merge,
+        }
+    }
+}
+
+/// Counts reported by `export`
+#[derive(Debug, Serialize)]
+struct ExportReport {
+    path: String,
+    books: usize,
+    positions: usize,
+    bookmarks: usize,
+}
+
+/// Outcome reported by `import`
+#[derive(Debug, Serialize)]
+struct ImportReport {
+    preview: ImportCounts,
+    imported: Option<ImportSummary>,
+}
+
+/// Writes the library to `path` as JSON
+pub async fn export(
+    out: &Output,
+    path: &Path,
+    include_positions: bool,
+    include_bookmarks: bool,
+) -> Result<()> {
+    let pool = open_database().await?;
+    let export = export_library(
+        &pool,
+        ExportOptions {
+            include_positions,
+            include_bookmarks,
+        },
+    )
+    .await?;
+
+    let json = serde_json::to_string_pretty(&export)?;
+    std::fs::write(path, json).with_context(|| format!("Failed to write {}", path.display()))?;
+
+    let report = ExportReport {
+        path: path.display().to_string(),
+        books: export.books.len(),
+        positions: export.positions.len(),
+        bookmarks: export.bookmarks.len(),
+    };
+    out.result(&report, || {
+        println!(
+            "Exported {} book{}, {} position{} and {} bookmark{} to {}",
+            report.books,
+            plural(report.books),
+            report.positions,
+            plural(report.positions),
+            report.bookmarks,
+            plural(report.bookmarks),
+            report.path
+        )
+    })
+}
+
+/// Imports an export file after showing what will change
+pub async fn import(
+    out: &Output,
+    file: &Path,
+    strategy: ImportStrategyArg,
+    yes: bool,
+) -> Result<()> {
+    let content = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to read {}", file.display()))?;
+    let export: LibraryExport = serde_json::from_str(&content)
+        .with_context(|| format!("{} is not a StoryStream library export", file.display()))?;
+
+    let pool = open_database().await?;
+    let strategy = ImportStrategy::from(strategy);
+
+    let preview = preview_import(&pool, &export, strategy).await?;
+    out.info(format!(
+        "Will add {} book{}, update {}, skip {}",
+        preview.added,
+        plural(preview.added),
+        preview.updated,
+        preview.skipped
+    ));
+
+    if preview.added + preview.updated == 0 {
+        let report = ImportReport {
+            preview,
+            imported: None,
+        };
+        return out.result(&report, || println!("Nothing to import."));
+    }
+
+    if !yes {
+        // There is nobody to answer the prompt when output is machine-read
+        if out.is_json() {
+            bail!("Import needs confirmation; pass --yes to import with --json");
+        }
+        if !confirm("Continue?")? {
+            println!("Import cancelled.");
+            return Ok(());
+        }
+    }
+
+    let summary = import_library(&pool, &export, strategy).await?;
+    let report = ImportReport {
+        preview,
+        imported: Some(summary),
+    };
+    out.result(&report, || {
+        println!(
+            "Imported: {} added, {} updated, {} skipped, {} position{} and {} bookmark{} restored",
+            summary.books.added,
+            summary.books.updated,
+            summary.books.skipped,
+            summary.positions,
+            plural(summary.positions),
+            summary.bookmarks,
+            plural(summary.bookmarks)
+        )
+    })
+}
+
+/// Imports positions, finished flags and bookmarks from another app's export
+pub async fn import_progress(
+    out: &Output,
+    file: &Path,
+    format: ProgressFormat,
+    dry_run: bool,
+) -> Result<()> {
+    let content = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to read {}", file.display()))?;
+    let importer: &dyn ExternalImporter = match format {
+        ProgressFormat::Abs => &AudiobookshelfImporter,
+        ProgressFormat::Csv => &CsvImporter,
+    };
+
+    let pool = open_database().await?;
+    let report = ProgressImporter::new(pool)
+        .import(importer, &content, dry_run)
+        .await
+        .with_context(|| format!("Failed to import {}", file.display()))?;
+
+    for entry in &report.matched {
+        let position = match entry.position {
+            Some(_) if entry.finished => "finished".to_string(),
+            Some(position) => format_duration(position.as_seconds()),
+            None => "kept newer progress".to_string(),
+        };
+        out.info(format!(
+            "  {} -> {} ({})",
+            entry.source, entry.title, position
+        ));
+    }
+    for entry in &report.unmatched {
+        out.warn(format!(
+            "Unmatched {}{}: {}",
+            entry.source,
+            entry
+                .title
+                .as_deref()
+                .map(|t| format!(" \"{}\"", t))
+                .unwrap_or_default(),
+            entry.reason
+        ));
+    }
+
+    out.result(&report, || print_progress_report(&report))
+}
+
+fn print_progress_report(report: &ProgressImportReport) {
+    let verb = if report.dry_run {
+        "Would import"
+    } else {
+        "Imported"
+    };
+    println!(
+        "{} {} position{} and {} bookmark{} from {} ({} matched, {} unmatched)",
+        verb,
+        report.positions,
+        plural(report.positions),
+        report.bookmarks,
+        plural(report.bookmarks),
+        report.format,
+        report.matched.len(),
+        report.unmatched.len()
+    );
+    if report.dry_run {
+        println!("Dry run: nothing was saved.");
+    }
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 {
+        ""
+    } else {
+        "s"
+    }
+}
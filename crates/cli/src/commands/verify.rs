@@ -0,0 +1,127 @@
+// crates/cli/src/commands/verify.rs
+//! Cross-checking the library against the files on disk
+
+use super::{config_manager, open_database, truncate, Output};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use storystream_library::{LibraryManager, LibraryReport};
+
+/// Entries listed per section before the rest are summed up
+const MAX_LISTED: usize = 10;
+
+/// Stable JSON schema for `verify --json`
+#[derive(Debug, Serialize)]
+struct VerifyOutput {
+    #[serde(flatten)]
+    report: LibraryReport,
+    /// Only with `--fix`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fixed: Option<Fixed>,
+}
+
+#[derive(Debug, Serialize)]
+struct Fixed {
+    marked_unavailable: usize,
+    imported: usize,
+    refreshed: usize,
+}
+
+/// Compares the library with the configured library paths
+///
+/// With `fix`, missing books are marked unavailable, orphaned files are
+/// imported and modified files are read again.
+pub async fn run(out: &Output, fix: bool) -> Result<()> {
+    let config = config_manager()?.load_effective();
+    let directories = config
+        .library
+        .library_paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect();
+    let manager =
+        LibraryManager::with_pool(open_database().await?).with_watch_directories(directories);
+
+    let report = manager
+        .verify_library()
+        .await
+        .context("Failed to verify the library")?;
+
+    let fixed = if fix {
+        let marked_unavailable = manager.mark_missing_as_unavailable(&report).await?;
+        let imported = manager
+            .import_orphans(&report)
+            .await
+            .context("Failed to import orphaned files")?
+            .len();
+        let refreshed = manager
+            .refresh_modified(&report)
+            .await
+            .context("Failed to refresh modified files")?
+            .len();
+        Some(Fixed {
+            marked_unavailable,
+            imported,
+            refreshed,
+        })
+    } else {
+        None
+    };
+
+    let output = VerifyOutput { report, fixed };
+    out.result(&output, || print_output(&output))
+}
+
+fn print_output(output: &VerifyOutput) {
+    let report = &output.report;
+    println!("Checked {} book(s)", report.checked);
+    println!("  {:<10}  {:>6}", "Missing", report.missing.len());
+    println!("  {:<10}  {:>6}", "Orphaned", report.orphaned.len());
+    println!("  {:<10}  {:>6}", "Modified", report.modified.len());
+
+    print_section(
+        "Missing",
+        report
+            .missing
+            .iter()
+            .map(|book| format!("{} ({})", book.title, book.file_path.display())),
+    );
+    print_section(
+        "Orphaned",
+        report
+            .orphaned
+            .iter()
+            .map(|path| path.display().to_string()),
+    );
+    print_section(
+        "Modified",
+        report.modified.iter().map(|modified| {
+            format!(
+                "{} ({} -> {} bytes)",
+                modified.book.title, modified.book.file_size, modified.size
+            )
+        }),
+    );
+
+    match &output.fixed {
+        Some(fixed) => println!(
+            "\nMarked {} book(s) unavailable, imported {} file(s), refreshed {} book(s)",
+            fixed.marked_unavailable, fixed.imported, fixed.refreshed
+        ),
+        None if report.is_clean() => println!("\nThe library matches the files on disk."),
+        None => println!("\nRun 'storystream verify --fix' to bring the library up to date."),
+    }
+}
+
+fn print_section(heading: &str, entries: impl ExactSizeIterator<Item = String>) {
+    let count = entries.len();
+    if count == 0 {
+        return;
+    }
+    println!("\n{}", heading);
+    for entry in entries.take(MAX_LISTED) {
+        println!("  {}", truncate(&entry, 100));
+    }
+    if count > MAX_LISTED {
+        println!("  ... and {} more", count - MAX_LISTED);
+    }
+}
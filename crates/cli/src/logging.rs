@@ -0,0 +1,103 @@
+// crates/cli/src/logging.rs
+//! Diagnostic logging
+//!
+//! Logging stays off unless `--log-file`, `--log-level` or `RUST_LOG` asks
+//! for it. The TUI draws over the whole terminal, so it only ever logs to a
+//! file: `--log-file`, or `storystream.log` in the config directory.
+
+use crate::commands::config_manager;
+use anyhow::{anyhow, Context, Result};
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::sync::Mutex;
+use storystream_config::AppConfig;
+use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
+
+/// Log file the TUI writes to when no `--log-file` is given
+const TUI_LOG_FILE: &str = "storystream.log";
+
+/// Installs the global subscriber if logging was requested
+pub fn init(log_file: Option<&Path>, log_level: Option<&str>, tui: bool) -> Result<()> {
+    let env = std::env::var("RUST_LOG").ok();
+    let env = env.as_deref().filter(|f| !f.trim().is_empty());
+    if log_file.is_none() && log_level.is_none() && env.is_none() {
+        return Ok(());
+    }
+
+    let manager = config_manager();
+    let app = manager.as_ref().ok().map(|m| m.load_effective().app);
+    let directives = filter_directives(log_level, env, app.as_ref());
+    let filter = EnvFilter::try_new(&directives)
+        .with_context(|| format!("Invalid log filter '{}'", directives))?;
+
+    // Closing spans logs their duration, which is what finds a slow import
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(FmtSpan::CLOSE);
+
+    let path = match log_file {
+        Some(path) => Some(path.to_path_buf()),
+        None if tui => Some(manager?.config_dir().join(TUI_LOG_FILE)),
+        None => None,
+    };
+    let installed = match path {
+        Some(path) => {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .with_context(|| format!("Cannot open log file {}", path.display()))?;
+            builder
+                .with_ansi(false)
+                .with_writer(Mutex::new(file))
+                .try_init()
+        }
+        None => builder.with_writer(std::io::stderr).try_init(),
+    };
+    installed.map_err(|e| anyhow!("Cannot start logging: {}", e))
+}
+
+/// `--log-level` wins over `RUST_LOG`, which wins over the config file
+fn filter_directives(
+    log_level: Option<&str>,
+    env: Option<&str>,
+    app: Option<&AppConfig>,
+) -> String {
+    if let Some(filter) = log_level.or(env) {
+        return filter.to_string();
+    }
+    match app {
+        Some(app) => app
+            .log_filter
+            .clone()
+            .unwrap_or_else(|| app.log_level.to_string()),
+        None => "info".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use storystream_config::app_config::LogLevel;
+
+    #[test]
+    fn test_filter_precedence() {
+        let mut app = AppConfig {
+            log_level: LogLevel::Warn,
+            ..Default::default()
+        };
+        assert_eq!(filter_directives(None, None, Some(&app)), "warn");
+
+        app.log_filter = Some("info,storystream_library=debug".to_string());
+        assert_eq!(
+            filter_directives(None, None, Some(&app)),
+            "info,storystream_library=debug"
+        );
+        assert_eq!(filter_directives(None, Some("trace"), Some(&app)), "trace");
+        assert_eq!(
+            filter_directives(Some("debug"), Some("trace"), Some(&app)),
+            "debug"
+        );
+        assert_eq!(filter_directives(None, None, None), "info");
+    }
+}
@@ -2,97 +2,171 @@
 //! StoryStream CLI - Command-line interface for the audiobook player
 
 mod commands;
+mod logging;
 mod player;
 mod tui_mode;
 
 use anyhow::Result;
 use clap::Parser;
-use commands::{Cli, Commands};
+use commands::{Cli, Commands, Output};
+use storystream_config::ConfigOverride;
+use storystream_library::{ScanMode, VerifyDepth};
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() {
     // Parse command-line arguments
     let cli = Cli::parse();
+    let out = Output::new(cli.json, cli.quiet);
+
+    let mut overrides = ConfigOverride::new();
+    for (key, value) in &cli.config_overrides {
+        overrides.set(key, value);
+    }
+    commands::set_config_overrides(&out, overrides);
+
+    let tui = matches!(cli.command, Commands::Tui { .. });
+    if let Err(error) = logging::init(cli.log_file.as_deref(), cli.log_level.as_deref(), tui) {
+        out.error(&error);
+        std::process::exit(1);
+    }
 
     // Execute the requested command
-    match cli.command {
-        Commands::Tui => {
+    if let Err(error) = run(cli.command, &out).await {
+        out.error(&error);
+        std::process::exit(commands::output::exit_code(&error));
+    }
+}
+
+async fn run(command: Commands, out: &Output) -> Result<()> {
+    match command {
+        Commands::Tui { accessible } => {
             // Launch integrated TUI mode with real audio playback
-            tui_mode::run_tui().await?;
+            tui_mode::run_tui(accessible).await
         }
         Commands::Play {
             book,
             speed,
             volume,
         } => {
-            println!("Playing: {}", book);
+            let mut message = format!("Playing: {}", book);
             if let Some(s) = speed {
-                println!("  Speed: {}x", s);
+                message.push_str(&format!("\n  Speed: {}x", s));
             }
             if let Some(v) = volume {
-                println!("  Volume: {}%", v);
+                message.push_str(&format!("\n  Volume: {}%", v));
             }
-            println!("\nNote: Use 'storystream tui' for full interactive experience");
+            out.done(message)?;
+            out.info("\nNote: Use 'storystream tui' for full interactive experience");
+            Ok(())
         }
         Commands::Pause => {
-            println!("Pausing playback");
-            println!("\nNote: Use 'storystream tui' for full interactive experience");
+            out.done("Pausing playback")?;
+            out.info("\nNote: Use 'storystream tui' for full interactive experience");
+            Ok(())
         }
         Commands::Resume => {
-            println!("Resuming playback");
-            println!("\nNote: Use 'storystream tui' for full interactive experience");
+            out.done("Resuming playback")?;
+            out.info("\nNote: Use 'storystream tui' for full interactive experience");
+            Ok(())
         }
         Commands::Stop => {
-            println!("Stopping playback");
-            println!("\nNote: Use 'storystream tui' for full interactive experience");
-        }
-        Commands::List { author, favorites } => {
-            println!("Listing audiobooks:");
-            if let Some(a) = author {
-                println!("  Filtered by author: {}", a);
-            }
-            if favorites {
-                println!("  Showing favorites only");
-            }
-            println!("\nNote: Use 'storystream tui' for full interactive library browser");
+            out.done("Stopping playback")?;
+            out.info("\nNote: Use 'storystream tui' for full interactive experience");
+            Ok(())
         }
-        Commands::Scan { path } => {
-            if let Some(p) = path {
-                println!("Scanning path: {}", p);
-            } else {
-                println!("Scanning configured library paths");
-            }
-            println!("\nNote: Use 'storystream tui' for full interactive experience");
-        }
-        Commands::Search { query } => {
-            println!("Searching for: {}", query);
-            println!("\nNote: Use 'storystream tui' for full interactive search");
+        Commands::List {
+            author,
+            favorites,
+            sort,
+            limit,
+        } => {
+            commands::library::list(out, author.as_deref(), favorites, sort.as_deref(), limit).await
         }
-        Commands::Bookmark { title } => {
-            if let Some(t) = title {
-                println!("Adding bookmark: {}", t);
-            } else {
-                println!("Adding bookmark at current position");
+        Commands::Scan {
+            path,
+            dry_run,
+            save_plan,
+            full,
+            commit_plan,
+        } => match commit_plan {
+            Some(file) => commands::scan::commit(out, &file).await,
+            None => {
+                let mode = if full {
+                    ScanMode::Full
+                } else {
+                    ScanMode::Incremental
+                };
+                commands::scan::run(out, path.as_deref(), dry_run, save_plan.as_deref(), mode).await
             }
-            println!("\nNote: Use 'storystream tui' for full bookmark management");
-        }
-        Commands::Status => {
-            println!("Current Status:");
-            println!("  Playback: Stopped");
-            println!("  Position: 00:00:00 / 00:00:00");
-            println!("\nNote: Use 'storystream tui' for real-time status display");
+        },
+        Commands::Search {
+            query,
+            filter,
+            sort,
+            limit,
+        } => {
+            commands::library::search(out, query.as_deref(), &filter, sort.as_deref(), limit).await
         }
-        Commands::Config { full } => {
-            println!("StoryStream Configuration:");
-            if full {
-                println!("  (Full configuration would be displayed here)");
+        Commands::Bookmark { action } => commands::bookmark::run(out, action).await,
+        Commands::Open { link } => commands::bookmark::open(out, &link).await,
+        Commands::Playlist { action } => commands::playlist::run(out, action).await,
+        Commands::Feed { action } => commands::feed::run(out, action).await,
+        Commands::Source { action } => commands::source::run(out, action).await,
+        Commands::Download { id, dest } => commands::source::download(out, &id, dest).await,
+        Commands::Export {
+            out: path,
+            include_positions,
+            include_bookmarks,
+        } => commands::transfer::export(out, &path, include_positions, include_bookmarks).await,
+        Commands::Import {
+            file,
+            strategy,
+            yes,
+        } => commands::transfer::import(out, &file, strategy, yes).await,
+        Commands::ImportProgress {
+            file,
+            format,
+            dry_run,
+        } => commands::transfer::import_progress(out, &file, format, dry_run).await,
+        Commands::Doctor {
+            fix,
+            verify_audio,
+            verify_files,
+            full,
+            prune_downloads,
+            duplicates,
+            maintenance,
+            clean,
+        } => {
+            let files = verify_files.then_some(if full {
+                VerifyDepth::Full
             } else {
-                println!("  Database: ~/.local/share/storystream/storystream.db");
-                println!("  Config: ~/.config/storystream/config.toml");
-                println!("\nUse --full to see complete configuration");
-            }
+                VerifyDepth::Hash
+            });
+            let options = commands::doctor::Options {
+                fix,
+                verify_audio,
+                verify_files: files,
+                prune_downloads,
+                duplicates,
+                maintenance,
+                clean,
+            };
+            commands::doctor::run(out, options).await
         }
+        Commands::Verify { fix } => commands::verify::run(out, fix).await,
+        Commands::Completions { shell } => commands::completions::completions(out, shell),
+        Commands::Manpage => commands::completions::manpage(out),
+        Commands::Stats {
+            csv,
+            since,
+            loudness,
+        } => commands::stats::run(out, csv.as_deref(), since, loudness).await,
+        Commands::Status => commands::status::run(out).await,
+        Commands::Config {
+            action: Some(action),
+            ..
+        } => commands::config::run(out, action),
+        Commands::Config { action: None, full } => commands::show_config(out, full),
     }
-
-    Ok(())
 }
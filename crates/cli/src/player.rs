@@ -127,7 +127,15 @@ async fn run_player_ui(
             if let Ok(mut state) = save_state.try_lock() {
                 state.position = core_duration_from_std(current_position);
 
-                if let Err(e) = update_playback_state(&save_pool, book_id, state.position).await {
+                if let Err(e) = update_playback_state(
+                    &save_pool,
+                    book_id,
+                    state.position,
+                    state.speed,
+                    state.volume,
+                )
+                .await
+                {
                     eprintln!("Warning: Failed to save position: {}", e);
                 }
             }
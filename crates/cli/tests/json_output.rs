@@ -0,0 +1,176 @@
+// crates/cli/tests/json_output.rs
+//! The `--json` output of the `storystream` binary, as scripts read it
+//!
+//! Each test runs the binary with its config and database in a temporary
+//! home, and parses stdout as the single JSON document it must be.
+
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use storystream_core::{Book, Duration, PlaybackState, Timestamp};
+use storystream_database::{
+    connection::{connect, DatabaseConfig},
+    migrations::run_migrations,
+    queries::{books, playback},
+};
+use tempfile::TempDir;
+
+fn database_path(home: &Path) -> PathBuf {
+    home.join("library.db")
+}
+
+/// Runs `storystream --json <args>` inside `home`
+fn storystream(home: &Path, args: &[&str]) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_storystream"));
+    // Settings from the shell running the tests must not leak into them
+    for (key, _) in std::env::vars_os() {
+        if key.to_string_lossy().starts_with("STORYSTREAM_") {
+            command.env_remove(key);
+        }
+    }
+    command
+        .arg("--json")
+        .args(args)
+        .env("HOME", home)
+        .env("XDG_CONFIG_HOME", home.join("config"))
+        .env("STORYSTREAM_LIBRARY_DATABASE_PATH", database_path(home))
+        .env_remove("RUST_LOG")
+        .output()
+        .expect("failed to run storystream")
+}
+
+/// Parses stdout, which must hold one JSON document and nothing else
+fn envelope(output: &Output) -> Value {
+    serde_json::from_slice(&output.stdout).unwrap_or_else(|e| {
+        panic!(
+            "stdout is not JSON ({}): {}",
+            e,
+            String::from_utf8_lossy(&output.stdout)
+        )
+    })
+}
+
+/// Data of a successful run
+fn data(output: &Output) -> Value {
+    assert!(
+        output.status.success(),
+        "storystream failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let value = envelope(output);
+    assert_eq!(value["ok"], true);
+    assert!(value["error"].is_null());
+    value["data"].clone()
+}
+
+/// A library of one favorite book, a quarter listened to
+async fn seed_library(home: &Path) -> Book {
+    let pool = connect(DatabaseConfig::new(database_path(home).to_str().unwrap()))
+        .await
+        .unwrap();
+    run_migrations(&pool).await.unwrap();
+
+    let mut book = Book::new(
+        "The Way of Kings".to_string(),
+        PathBuf::from("/books/the-way-of-kings.m4b"),
+        1_000_000,
+        Duration::from_seconds(3600),
+    );
+    book.author = Some("Brandon Sanderson".to_string());
+    book.is_favorite = true;
+    book.last_played = Some(Timestamp::now());
+    books::create_book(&pool, &book).await.unwrap();
+
+    let mut state = PlaybackState::new(book.id);
+    state.position = Duration::from_seconds(900);
+    playback::create_playback_state(&pool, &state)
+        .await
+        .unwrap();
+
+    pool.close().await;
+    book
+}
+
+#[tokio::test]
+async fn test_list_reports_books_with_progress() {
+    let home = TempDir::new().unwrap();
+    let book = seed_library(home.path()).await;
+
+    let books = data(&storystream(home.path(), &["list"]));
+    let books = books.as_array().unwrap();
+    assert_eq!(books.len(), 1);
+    let entry = &books[0];
+    assert_eq!(entry["id"], book.id.as_string());
+    assert_eq!(entry["title"], "The Way of Kings");
+    assert_eq!(entry["author"], "Brandon Sanderson");
+    assert_eq!(entry["duration_secs"], 3600);
+    assert_eq!(entry["position_secs"], 900);
+    assert_eq!(entry["progress_percent"], 25.0);
+    assert_eq!(entry["favorite"], true);
+    assert_eq!(entry["file_path"], "/books/the-way-of-kings.m4b");
+
+    let none = data(&storystream(home.path(), &["list", "--author", "Nobody"]));
+    assert_eq!(none, Value::Array(Vec::new()));
+}
+
+#[tokio::test]
+async fn test_search_reports_ranked_hits() {
+    let home = TempDir::new().unwrap();
+    seed_library(home.path()).await;
+
+    let hits = data(&storystream(home.path(), &["search", "Kings"]));
+    let hits = hits.as_array().unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0]["title"], "The Way of Kings");
+    assert_eq!(hits[0]["progress_percent"], 25.0);
+    assert!(hits[0]["rank"].is_number());
+}
+
+#[tokio::test]
+async fn test_status_reports_the_last_played_book() {
+    let home = TempDir::new().unwrap();
+    seed_library(home.path()).await;
+
+    let status = data(&storystream(home.path(), &["status"]));
+    assert_eq!(status["playing"], false);
+    assert_eq!(status["last_played"]["title"], "The Way of Kings");
+    assert_eq!(status["last_played"]["position_secs"], 900);
+}
+
+#[test]
+fn test_config_reports_paths_and_settings() {
+    let home = TempDir::new().unwrap();
+
+    let paths = data(&storystream(home.path(), &["config"]));
+    assert_eq!(
+        paths["database_path"],
+        database_path(home.path()).to_str().unwrap()
+    );
+    assert!(paths["config_path"].is_string());
+
+    let config = data(&storystream(home.path(), &["config", "--full"]));
+    assert!(config["library"].is_object());
+    assert!(config["player"]["default_volume"].is_number());
+}
+
+#[tokio::test]
+async fn test_errors_are_reported_as_json_with_a_failing_exit_code() {
+    let home = TempDir::new().unwrap();
+
+    // No library yet
+    let output = storystream(home.path(), &["list"]);
+    assert_eq!(output.status.code(), Some(66));
+    let value = envelope(&output);
+    assert_eq!(value["ok"], false);
+    assert!(value["data"].is_null());
+    assert_eq!(value["error"]["code"], "FILE_NOT_FOUND");
+    assert!(value["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("storystream scan"));
+
+    seed_library(home.path()).await;
+    let output = storystream(home.path(), &["list", "--sort", "sideways"]);
+    assert_eq!(output.status.code(), Some(64));
+    assert_eq!(envelope(&output)["error"]["code"], "INVALID_ARGUMENT");
+}
@@ -2,6 +2,7 @@
 
 use crate::validation::{ConfigSection, ValidationError, Validator};
 use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
 use std::path::PathBuf;
 
 /// Log level for application logging
@@ -37,6 +38,9 @@ pub struct AppConfig {
     /// Log level for application output
     pub log_level: LogLevel,
 
+    /// Per-crate log filter in `RUST_LOG` syntax, used instead of `log_level`
+    pub log_filter: Option<String>,
+
     /// Enable debug mode (additional logging and checks)
     pub debug_mode: bool,
 
@@ -52,6 +56,40 @@ pub struct AppConfig {
     /// Maximum number of recent books to track
     pub max_recent_books: usize,
 
+    /// Minutes of listening a day needs to count toward the streak
+    pub daily_goal_minutes: u32,
+
+    /// Folder shared between devices for syncing positions, sync is off when unset
+    pub sync_folder: Option<PathBuf>,
+
+    /// Name other devices show for this one, such as "Laptop"
+    pub device_name: Option<String>,
+
+    /// Find and sync with paired devices on the local network
+    pub lan_sync: bool,
+
+    /// Disk space covers, saved searches and other caches may use together, in MB
+    pub cache_max_mb: u64,
+
+    /// Announce playback, chapter and selection changes as text on a line
+    /// screen readers follow
+    pub accessible: bool,
+
+    /// How much accessibility mode announces
+    pub announce_verbosity: AnnounceVerbosity,
+
+    /// Serve the remote control API and page while the TUI runs
+    pub remote_control: bool,
+
+    /// Address and port the remote control listens on
+    pub remote_bind: String,
+
+    /// Token remote control requests must carry, any request is accepted when unset
+    pub remote_token: Option<String>,
+
+    /// Days between database maintenance runs at startup, never when unset
+    pub maintenance_interval_days: Option<u32>,
+
     /// Enable experimental features
     pub experimental_features: bool,
 }
@@ -75,21 +113,62 @@ impl std::fmt::Display for ColorScheme {
     }
 }
 
+/// How much accessibility mode announces, each level adding to the one before
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum AnnounceVerbosity {
+    /// Playback starting and pausing, and errors
+    Terse,
+    /// Also chapter changes, views and selected items
+    Normal,
+    /// Also every status message
+    Verbose,
+}
+
+impl std::fmt::Display for AnnounceVerbosity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnnounceVerbosity::Terse => write!(f, "terse"),
+            AnnounceVerbosity::Normal => write!(f, "normal"),
+            AnnounceVerbosity::Verbose => write!(f, "verbose"),
+        }
+    }
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
             database_path: PathBuf::from("storystream.db"),
             log_level: LogLevel::Info,
+            log_filter: None,
             debug_mode: false,
             check_updates: true,
             telemetry_enabled: false,
             color_scheme: ColorScheme::Auto,
             max_recent_books: 10,
+            daily_goal_minutes: 30,
+            sync_folder: None,
+            device_name: None,
+            lan_sync: false,
+            cache_max_mb: 500,
+            accessible: false,
+            announce_verbosity: AnnounceVerbosity::Normal,
+            remote_control: false,
+            remote_bind: "127.0.0.1:8910".to_string(),
+            remote_token: None,
+            maintenance_interval_days: None,
             experimental_features: false,
         }
     }
 }
 
+impl AppConfig {
+    /// The cache budget in bytes
+    pub fn cache_max_bytes(&self) -> u64 {
+        self.cache_max_mb * 1024 * 1024
+    }
+}
+
 impl ConfigSection for AppConfig {
     fn validate(&self) -> Result<(), Vec<ValidationError>> {
         let mut results = Vec::new();
@@ -102,6 +181,49 @@ impl ConfigSection for AppConfig {
             )));
         }
 
+        if let Some(filter) = &self.log_filter {
+            if filter.trim().is_empty() {
+                results.push(Err(ValidationError::new(
+                    "app.log_filter",
+                    "must not be empty (remove it to use log_level)",
+                )));
+            }
+        }
+
+        if let Some(folder) = &self.sync_folder {
+            if folder.as_os_str().is_empty() {
+                results.push(Err(ValidationError::new(
+                    "app.sync_folder",
+                    "must not be empty (remove it to turn sync off)",
+                )));
+            }
+        }
+
+        if let Some(name) = &self.device_name {
+            if name.trim().is_empty() {
+                results.push(Err(ValidationError::new(
+                    "app.device_name",
+                    "must not be empty",
+                )));
+            }
+        }
+
+        if self.remote_bind.parse::<SocketAddr>().is_err() {
+            results.push(Err(ValidationError::new(
+                "app.remote_bind",
+                "must be an address and port, such as 127.0.0.1:8910",
+            )));
+        }
+
+        if let Some(token) = &self.remote_token {
+            if token.trim().is_empty() {
+                results.push(Err(ValidationError::new(
+                    "app.remote_token",
+                    "must not be empty (remove it to accept any request)",
+                )));
+            }
+        }
+
         // Validate max_recent_books is reasonable
         results.push(Validator::in_range(
             self.max_recent_books,
@@ -110,23 +232,159 @@ impl ConfigSection for AppConfig {
             "app.max_recent_books",
         ));
 
+        // A day has 1440 minutes
+        results.push(Validator::in_range(
+            self.daily_goal_minutes,
+            1,
+            1440,
+            "app.daily_goal_minutes",
+        ));
+
+        results.push(Validator::in_range(
+            self.cache_max_mb,
+            10,
+            100_000,
+            "app.cache_max_mb",
+        ));
+
+        if let Some(days) = self.maintenance_interval_days {
+            results.push(Validator::in_range(
+                days,
+                1,
+                365,
+                "app.maintenance_interval_days",
+            ));
+        }
+
         Validator::collect_errors(results)
     }
 
     fn merge(&mut self, other: Self) {
         self.database_path = other.database_path;
         self.log_level = other.log_level;
+        self.log_filter = other.log_filter;
         self.debug_mode = other.debug_mode;
         self.check_updates = other.check_updates;
         self.telemetry_enabled = other.telemetry_enabled;
         self.color_scheme = other.color_scheme;
         self.max_recent_books = other.max_recent_books;
+        self.daily_goal_minutes = other.daily_goal_minutes;
+        self.sync_folder = other.sync_folder;
+        self.device_name = other.device_name;
+        self.lan_sync = other.lan_sync;
+        self.cache_max_mb = other.cache_max_mb;
+        self.accessible = other.accessible;
+        self.announce_verbosity = other.announce_verbosity;
+        self.remote_control = other.remote_control;
+        self.remote_bind = other.remote_bind;
+        self.remote_token = other.remote_token;
+        self.maintenance_interval_days = other.maintenance_interval_days;
         self.experimental_features = other.experimental_features;
     }
 
     fn section_name(&self) -> &'static str {
         "app"
     }
+
+    fn json_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "description": "Application-level settings",
+            "properties": {
+                "database_path": {
+                    "type": "string",
+                    "description": "Path to the database file"
+                },
+                "log_level": {
+                    "type": "string",
+                    "enum": ["error", "warn", "info", "debug", "trace"],
+                    "description": "Logging verbosity level"
+                },
+                "log_filter": {
+                    "type": ["string", "null"],
+                    "description": "Per-crate log filter in RUST_LOG syntax"
+                },
+                "debug_mode": {
+                    "type": "boolean",
+                    "description": "Enable debug mode"
+                },
+                "check_updates": {
+                    "type": "boolean",
+                    "description": "Check for updates on startup"
+                },
+                "telemetry_enabled": {
+                    "type": "boolean",
+                    "description": "Send anonymous usage statistics"
+                },
+                "color_scheme": {
+                    "type": "string",
+                    "enum": ["auto", "light", "dark"],
+                    "description": "UI color scheme"
+                },
+                "max_recent_books": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 100,
+                    "description": "Maximum recent books to track"
+                },
+                "daily_goal_minutes": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 1440,
+                    "description": "Daily listening goal in minutes"
+                },
+                "sync_folder": {
+                    "type": ["string", "null"],
+                    "description": "Folder shared between devices for syncing positions"
+                },
+                "device_name": {
+                    "type": ["string", "null"],
+                    "description": "Name other devices show for this device"
+                },
+                "lan_sync": {
+                    "type": "boolean",
+                    "description": "Find and sync with paired devices on the local network"
+                },
+                "cache_max_mb": {
+                    "type": "integer",
+                    "minimum": 10,
+                    "maximum": 100000,
+                    "description": "Disk space all caches may use together, in MB"
+                },
+                "accessible": {
+                    "type": "boolean",
+                    "description": "Announce state changes for screen readers"
+                },
+                "announce_verbosity": {
+                    "type": "string",
+                    "enum": ["terse", "normal", "verbose"],
+                    "description": "How much accessibility mode announces"
+                },
+                "remote_control": {
+                    "type": "boolean",
+                    "description": "Serve the remote control API and page"
+                },
+                "remote_bind": {
+                    "type": "string",
+                    "description": "Address and port the remote control listens on"
+                },
+                "remote_token": {
+                    "type": ["string", "null"],
+                    "description": "Token remote control requests must carry"
+                },
+                "maintenance_interval_days": {
+                    "type": ["integer", "null"],
+                    "minimum": 1,
+                    "maximum": 365,
+                    "description": "Days between database maintenance runs at startup"
+                },
+                "experimental_features": {
+                    "type": "boolean",
+                    "description": "Enable experimental features"
+                }
+            }
+        })
+    }
 }
 
 #[cfg(test)]
@@ -156,6 +414,90 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_invalid_daily_goal() {
+        let mut config = AppConfig {
+            daily_goal_minutes: 0,
+            ..AppConfig::default()
+        };
+        assert!(config.validate().is_err());
+
+        config.daily_goal_minutes = 1441;
+        assert!(config.validate().is_err());
+
+        config.daily_goal_minutes = 1440;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_cache_budget() {
+        let mut config = AppConfig {
+            cache_max_mb: 5,
+            ..AppConfig::default()
+        };
+        assert!(config.validate().is_err());
+
+        config.cache_max_mb = 2_000;
+        assert!(config.validate().is_ok());
+        assert_eq!(config.cache_max_bytes(), 2_000 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_empty_log_filter() {
+        let mut config = AppConfig {
+            log_filter: Some(" ".to_string()),
+            ..AppConfig::default()
+        };
+        assert!(config.validate().is_err());
+
+        config.log_filter = Some("storystream_library=debug,sqlx=warn".to_string());
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_empty_sync_settings() {
+        let mut config = AppConfig {
+            sync_folder: Some(PathBuf::new()),
+            ..AppConfig::default()
+        };
+        assert!(config.validate().is_err());
+
+        config.sync_folder = Some(PathBuf::from("/home/me/Dropbox/StoryStream"));
+        config.device_name = Some(String::new());
+        assert!(config.validate().is_err());
+
+        config.device_name = Some("Laptop".to_string());
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_remote_settings() {
+        let mut config = AppConfig {
+            remote_bind: "localhost".to_string(),
+            ..AppConfig::default()
+        };
+        assert!(config.validate().is_err());
+
+        config.remote_bind = "0.0.0.0:8910".to_string();
+        config.remote_token = Some(" ".to_string());
+        assert!(config.validate().is_err());
+
+        config.remote_token = Some("s3cret".to_string());
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_maintenance_interval() {
+        let mut config = AppConfig {
+            maintenance_interval_days: Some(0),
+            ..AppConfig::default()
+        };
+        assert!(config.validate().is_err());
+
+        config.maintenance_interval_days = Some(7);
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn test_merge() {
         let mut base = AppConfig::default();
@@ -181,4 +523,11 @@ mod tests {
         assert_eq!(ColorScheme::Auto.to_string(), "auto");
         assert_eq!(ColorScheme::Dark.to_string(), "dark");
     }
+
+    #[test]
+    fn test_announce_verbosity_order() {
+        assert!(AnnounceVerbosity::Terse < AnnounceVerbosity::Normal);
+        assert!(AnnounceVerbosity::Normal < AnnounceVerbosity::Verbose);
+        assert_eq!(AnnounceVerbosity::Verbose.to_string(), "verbose");
+    }
 }
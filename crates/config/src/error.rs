@@ -45,6 +45,21 @@ pub enum ConfigError {
         source: std::io::Error,
     },
 
+    /// Config file was written by a newer version of StoryStream
+    #[error(
+        "Config file at {path} is version {version}, but this build only reads up to version \
+         {supported}; update StoryStream or restore an older config"
+    )]
+    UnsupportedVersion {
+        path: PathBuf,
+        version: u32,
+        supported: u32,
+    },
+
+    /// Config file could not be upgraded to the current version
+    #[error("Failed to migrate config from version {from} to {to}: {reason}")]
+    MigrationError { from: u32, to: u32, reason: String },
+
     /// Config directory path could not be determined
     #[error("Could not determine config directory path: {reason}")]
     PathResolutionError { reason: String },
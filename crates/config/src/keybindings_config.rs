@@ -0,0 +1,70 @@
+//! Keybindings configuration section
+
+use crate::validation::{ConfigSection, ValidationError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Keys chosen in place of the TUI's defaults, by action name
+///
+/// `play_pause = "space"` binds an action to one key and
+/// `volume_up = "+ ="` to several. Actions left out keep their defaults.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(transparent)]
+pub struct KeybindingsConfig {
+    /// Keys, separated by spaces, by the name of the action they run
+    pub bindings: BTreeMap<String, String>,
+}
+
+impl KeybindingsConfig {
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
+
+    /// The keys given for `action`, if any
+    pub fn get(&self, action: &str) -> Option<&str> {
+        self.bindings.get(action).map(String::as_str)
+    }
+}
+
+impl ConfigSection for KeybindingsConfig {
+    fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        // Action and key names belong to the TUI, which checks them when it
+        // starts and keeps its defaults for ones it cannot read
+        Ok(())
+    }
+
+    fn merge(&mut self, other: Self) {
+        self.bindings = other.bindings;
+    }
+
+    fn section_name(&self) -> &'static str {
+        "keybindings"
+    }
+
+    fn json_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "description": "Keys the TUI's actions are bound to, by action name",
+            "properties": {},
+            "additionalProperties": {
+                "type": "string",
+                "description": "Keys separated by spaces, such as \"space\", \"l\" or \"ctrl+e\""
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bindings_read_as_a_table() {
+        let config: KeybindingsConfig =
+            toml::from_str("play_pause = \"space\"\nseek_forward = \"l\"\n").unwrap();
+        assert_eq!(config.get("seek_forward"), Some("l"));
+        assert_eq!(config.get("quit"), None);
+        assert!(config.validate().is_ok());
+        assert!(KeybindingsConfig::default().is_empty());
+    }
+}
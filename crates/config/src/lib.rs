@@ -29,6 +29,7 @@
 mod error;
 mod manager;
 mod migration;
+mod overrides;
 mod persistence;
 mod validation;
 
@@ -39,17 +40,23 @@ pub mod watcher;
 
 // Config sections
 pub mod app_config;
+mod keybindings_config;
 mod library_config;
+mod limits_config;
 mod player_config;
 
 pub use error::{ConfigError, ConfigResult, ValidationError}; // Add ValidationError here
 pub use manager::ConfigManager;
+pub use migration::{Migration, Migrations, RenameKey};
+pub use overrides::{ConfigOverride, EnvOverride};
 pub use validation::{ConfigSection, Validator}; // Remove ValidationError from here
 
 // Re-export config sections
-pub use app_config::AppConfig;
+pub use app_config::{AnnounceVerbosity, AppConfig};
+pub use keybindings_config::KeybindingsConfig;
 pub use library_config::LibraryConfig;
-pub use player_config::PlayerConfig;
+pub use limits_config::LimitsConfig;
+pub use player_config::{DeviceProfile, PlayerConfig};
 
 use serde::{Deserialize, Serialize};
 
@@ -74,6 +81,12 @@ pub struct Config {
 
     /// Library and import settings
     pub library: LibraryConfig,
+
+    /// Daily listening limits
+    pub limits: LimitsConfig,
+
+    /// Keys of the TUI's actions, where they differ from the defaults
+    pub keybindings: KeybindingsConfig,
 }
 
 impl Config {
@@ -100,6 +113,14 @@ impl Config {
             errors.append(&mut e);
         }
 
+        if let Err(mut e) = self.limits.validate() {
+            errors.append(&mut e);
+        }
+
+        if let Err(mut e) = self.keybindings.validate() {
+            errors.append(&mut e);
+        }
+
         if errors.is_empty() {
             Ok(())
         } else {
@@ -107,6 +128,52 @@ impl Config {
         }
     }
 
+    /// JSON Schemas of the sections, by the name of their table
+    ///
+    /// Sections are listed here alongside [`validate`](Self::validate) and
+    /// [`merge`](Self::merge); the schema of the whole file is built from
+    /// this list.
+    pub fn section_schemas() -> Vec<(&'static str, serde_json::Value)> {
+        vec![
+            section_schema::<AppConfig>(),
+            section_schema::<PlayerConfig>(),
+            section_schema::<LibraryConfig>(),
+            section_schema::<LimitsConfig>(),
+            section_schema::<KeybindingsConfig>(),
+        ]
+    }
+
+    /// The value of a setting such as `player.default_volume`, as text
+    /// [`with_setting`](Self::with_setting) reads back
+    ///
+    /// Lists are joined like `PATH`. Returns `None` for unset optional
+    /// settings, settings that are tables and keys that name no setting.
+    pub fn setting(&self, key: &str) -> Option<String> {
+        overrides::setting(self, key)
+    }
+
+    /// This config with a setting such as `player.default_volume` set from
+    /// text, read as the setting's type like a [`ConfigOverride`] value
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ConfigError::ValidationError`] when the key names no
+    /// setting, the text does not fit its type, or the new value fails the
+    /// section's validation.
+    pub fn with_setting(&self, key: &str, value: &str) -> ConfigResult<Config> {
+        overrides::apply(self, key, value)
+    }
+
+    /// This config with a setting back at its default value; optional
+    /// settings are unset
+    ///
+    /// # Errors
+    ///
+    /// Fails like [`with_setting`](Self::with_setting).
+    pub fn without_setting(&self, key: &str) -> ConfigResult<Config> {
+        overrides::reset(self, key)
+    }
+
     /// Merges this config with another, preferring values from `other`
     ///
     /// This is used for override chains: defaults < file < env vars < CLI args
@@ -114,9 +181,15 @@ impl Config {
         self.app.merge(other.app);
         self.player.merge(other.player);
         self.library.merge(other.library);
+        self.limits.merge(other.limits);
+        self.keybindings.merge(other.keybindings);
     }
 }
 
+fn section_schema<S: ConfigSection>() -> (&'static str, serde_json::Value) {
+    (S::default().section_name(), S::json_schema())
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -124,6 +197,8 @@ impl Default for Config {
             app: AppConfig::default(),
             player: PlayerConfig::default(),
             library: LibraryConfig::default(),
+            limits: LimitsConfig::default(),
+            keybindings: KeybindingsConfig::default(),
         }
     }
 }
@@ -153,4 +228,45 @@ mod tests {
         base.merge(override_config);
         assert_eq!(base.player.default_volume, 75);
     }
+
+    #[test]
+    fn test_settings_read_and_written_as_text() {
+        let config = Config::default();
+        assert_eq!(
+            config.setting("player.default_volume").as_deref(),
+            Some("70")
+        );
+        assert_eq!(config.setting("player.default_speed").as_deref(), Some("1"));
+        assert_eq!(config.setting("app.color_scheme").as_deref(), Some("auto"));
+        assert_eq!(config.setting("app.sync_folder"), None);
+        assert_eq!(config.setting("player.equalizer_presets"), None);
+        assert_eq!(config.setting("player.no_such_field"), None);
+
+        let config = config
+            .with_setting("player.default_speed", "1.1")
+            .and_then(|config| config.with_setting("library.paths", "/a:/b"))
+            .and_then(|config| config.with_setting("app.sync_folder", "/sync"))
+            .expect("Should set");
+        assert_eq!(
+            config.setting("player.default_speed").as_deref(),
+            Some("1.1")
+        );
+        if cfg!(unix) {
+            assert_eq!(config.setting("library.paths").as_deref(), Some("/a:/b"));
+        }
+
+        let error = config
+            .with_setting("player.default_volume", "101")
+            .unwrap_err();
+        assert!(error.to_string().contains("must be between 0 and 100"));
+
+        let config = config
+            .without_setting("app.sync_folder")
+            .expect("Should unset");
+        assert_eq!(config.app.sync_folder, None);
+        let config = config
+            .without_setting("player.default_speed")
+            .expect("Should reset");
+        assert_eq!(config.player.default_speed, 1.0);
+    }
 }
@@ -38,11 +38,22 @@ pub struct LibraryConfig {
     /// Follow symbolic links when scanning
     pub follow_symlinks: bool,
 
+    /// Glob patterns for paths to skip when scanning and watching, such as
+    /// `**/@eaDir`
+    pub exclude_patterns: Vec<String>,
+
+    /// Skip hidden files and folders when scanning and watching
+    pub ignore_hidden: bool,
+
     /// Organize imported files by author/title
     pub organize_files: bool,
 
     /// Target directory for organized files (if organize_files is true)
     pub organization_target: Option<PathBuf>,
+
+    /// Browse and play without changing the library, such as while a
+    /// backup is being restored into it
+    pub read_only: bool,
 }
 
 impl Default for LibraryConfig {
@@ -66,8 +77,11 @@ impl Default for LibraryConfig {
             max_scan_depth: 0,         // unlimited
             min_file_size_bytes: 1024, // 1 KB
             follow_symlinks: false,
+            exclude_patterns: Vec::new(),
+            ignore_hidden: true,
             organize_files: false,
             organization_target: None,
+            read_only: false,
         }
     }
 }
@@ -94,6 +108,13 @@ impl ConfigSection for LibraryConfig {
             ));
         }
 
+        for (i, pattern) in self.exclude_patterns.iter().enumerate() {
+            results.push(Validator::not_empty(
+                pattern,
+                &format!("library.exclude_patterns[{}]", i),
+            ));
+        }
+
         // Validate organization target if organize_files is enabled
         if self.organize_files {
             if let Some(ref target) = self.organization_target {
@@ -127,13 +148,83 @@ impl ConfigSection for LibraryConfig {
         self.max_scan_depth = other.max_scan_depth;
         self.min_file_size_bytes = other.min_file_size_bytes;
         self.follow_symlinks = other.follow_symlinks;
+        self.exclude_patterns = other.exclude_patterns;
+        self.ignore_hidden = other.ignore_hidden;
         self.organize_files = other.organize_files;
         self.organization_target = other.organization_target;
+        self.read_only = other.read_only;
     }
 
     fn section_name(&self) -> &'static str {
         "library"
     }
+
+    fn json_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "description": "Library and import settings",
+            "properties": {
+                "library_paths": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Directories to scan for audiobooks"
+                },
+                "supported_extensions": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Supported audio file extensions"
+                },
+                "auto_import": {
+                    "type": "boolean",
+                    "description": "Auto-import discovered files"
+                },
+                "extract_metadata": {
+                    "type": "boolean",
+                    "description": "Extract metadata from files"
+                },
+                "recursive_scan": {
+                    "type": "boolean",
+                    "description": "Recurse into subdirectories"
+                },
+                "max_scan_depth": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "description": "Maximum recursion depth (0=unlimited)"
+                },
+                "min_file_size_bytes": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": 104857600,
+                    "description": "Minimum file size in bytes"
+                },
+                "follow_symlinks": {
+                    "type": "boolean",
+                    "description": "Follow symbolic links"
+                },
+                "exclude_patterns": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Glob patterns for paths to skip"
+                },
+                "ignore_hidden": {
+                    "type": "boolean",
+                    "description": "Skip hidden files and folders"
+                },
+                "organize_files": {
+                    "type": "boolean",
+                    "description": "Organize by author/title"
+                },
+                "organization_target": {
+                    "type": ["string", "null"],
+                    "description": "Target directory for organized files"
+                },
+                "read_only": {
+                    "type": "boolean",
+                    "description": "Open the library without changing it"
+                }
+            }
+        })
+    }
 }
 
 #[cfg(test)]
@@ -173,6 +264,16 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_empty_exclude_pattern() {
+        let mut config = LibraryConfig::default();
+        assert!(config.ignore_hidden);
+        config.exclude_patterns.push("**/@eaDir".to_string());
+        assert!(config.validate().is_ok());
+        config.exclude_patterns.push(String::new());
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_organize_files_without_target() {
         let mut config = LibraryConfig::default();
@@ -214,4 +315,4 @@ mod tests {
         assert!(!base.extract_metadata);
         assert_eq!(base.library_paths, vec![PathBuf::from("/books")]);
     }
-}
\ No newline at end of file
+}
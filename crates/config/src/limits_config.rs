@@ -0,0 +1,146 @@
+//! Listening limits configuration section
+
+use crate::validation::{ConfigSection, ValidationError, Validator};
+use serde::{Deserialize, Serialize};
+
+/// Daily listening limits, such as for a child's profile
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct LimitsConfig {
+    /// Minutes of listening allowed per day (0 = no cap)
+    pub daily_minutes: u32,
+
+    /// Time of day listening is allowed, as "HH:MM-HH:MM" in local time
+    pub allowed_hours: Option<String>,
+
+    /// SHA-256 of the password that lifts the limits for a session,
+    /// as lowercase hex
+    pub override_password_sha256: Option<String>,
+}
+
+impl LimitsConfig {
+    /// Whether any limit is set
+    pub fn is_limited(&self) -> bool {
+        self.daily_minutes > 0 || self.allowed_hours.is_some()
+    }
+
+    /// Allowed window as minutes after midnight, start then end
+    ///
+    /// The end may come before the start for a window that spans midnight.
+    /// `None` when no window is set or it does not parse.
+    pub fn allowed_window(&self) -> Option<(u32, u32)> {
+        let (start, end) = self.allowed_hours.as_deref()?.split_once('-')?;
+        Some((minute_of_day(start)?, minute_of_day(end)?))
+    }
+}
+
+/// Reads "HH:MM" as minutes after midnight
+fn minute_of_day(text: &str) -> Option<u32> {
+    let (hours, minutes) = text.trim().split_once(':')?;
+    let hours: u32 = hours.parse().ok()?;
+    let minutes: u32 = minutes.parse().ok()?;
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+impl ConfigSection for LimitsConfig {
+    fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut results = vec![Validator::in_range(
+            self.daily_minutes,
+            0,
+            24 * 60,
+            "limits.daily_minutes",
+        )];
+
+        if self.allowed_hours.is_some() && self.allowed_window().is_none() {
+            results.push(Err(ValidationError::new(
+                "limits.allowed_hours",
+                "must look like \"07:00-19:30\"",
+            )));
+        }
+
+        if let Some(hash) = &self.override_password_sha256 {
+            if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                results.push(Err(ValidationError::new(
+                    "limits.override_password_sha256",
+                    "must be 64 hex digits",
+                )));
+            }
+        }
+
+        Validator::collect_errors(results)
+    }
+
+    fn merge(&mut self, other: Self) {
+        self.daily_minutes = other.daily_minutes;
+        self.allowed_hours = other.allowed_hours;
+        self.override_password_sha256 = other.override_password_sha256;
+    }
+
+    fn section_name(&self) -> &'static str {
+        "limits"
+    }
+
+    fn json_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "description": "Daily listening limits",
+            "properties": {
+                "daily_minutes": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": 1440,
+                    "description": "Minutes of listening allowed per day (0=no cap)"
+                },
+                "allowed_hours": {
+                    "type": ["string", "null"],
+                    "pattern": "^\\s*\\d{1,2}:\\d{2}\\s*-\\s*\\d{1,2}:\\d{2}\\s*$",
+                    "description": "Time of day listening is allowed, such as 07:00-19:30"
+                },
+                "override_password_sha256": {
+                    "type": ["string", "null"],
+                    "description": "SHA-256 of the password that lifts the limits"
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_has_no_limits() {
+        let config = LimitsConfig::default();
+        assert!(!config.is_limited());
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_allowed_window() {
+        let config = LimitsConfig {
+            allowed_hours: Some("07:00-19:30".to_string()),
+            ..Default::default()
+        };
+        assert!(config.is_limited());
+        assert_eq!(config.allowed_window(), Some((7 * 60, 19 * 60 + 30)));
+
+        // Windows may span midnight
+        let config = LimitsConfig {
+            allowed_hours: Some("20:00 - 06:00".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(config.allowed_window(), Some((20 * 60, 6 * 60)));
+    }
+
+    #[test]
+    fn test_validation() {
+        let config = LimitsConfig {
+            daily_minutes: 2000,
+            allowed_hours: Some("7am-7pm".to_string()),
+            override_password_sha256: Some("secret".to_string()),
+        };
+        let errors = config.validate().unwrap_err();
+        assert_eq!(errors.len(), 3);
+    }
+}
@@ -1,9 +1,12 @@
 //! Configuration manager - main API for config operations
 
 use crate::persistence::ConfigPersistence;
-use crate::{Config, ConfigError, ConfigResult};
+use crate::watcher::{self, WatchHandle};
+use crate::{Config, ConfigError, ConfigOverride, ConfigResult, EnvOverride};
 use directories::ProjectDirs;
 use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
 
 /// Main configuration manager
 ///
@@ -12,6 +15,13 @@ use std::path::PathBuf;
 pub struct ConfigManager {
     persistence: ConfigPersistence,
     config_dir: PathBuf,
+    /// Settings given on the command line, over everything else
+    overrides: ConfigOverride,
+    /// Contents of the config file as this manager last wrote it, which
+    /// subscribers are not told about
+    own_write: Arc<Mutex<Option<String>>>,
+    /// Watchers sending changes to subscribers, stopped when dropped
+    subscriptions: Mutex<Vec<WatchHandle>>,
 }
 
 impl ConfigManager {
@@ -34,9 +44,19 @@ impl ConfigManager {
         Ok(Self {
             persistence,
             config_dir,
+            overrides: ConfigOverride::new(),
+            own_write: Arc::new(Mutex::new(None)),
+            subscriptions: Mutex::new(Vec::new()),
         })
     }
 
+    /// Applies `overrides` over the file and environment in
+    /// [`load_effective`](Self::load_effective)
+    pub fn with_overrides(mut self, overrides: ConfigOverride) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
     /// Returns the default config directory based on the platform
     fn default_config_dir() -> ConfigResult<PathBuf> {
         ProjectDirs::from("", "", "storystream")
@@ -51,6 +71,11 @@ impl ConfigManager {
         &self.config_dir
     }
 
+    /// Returns the directory holding the caches
+    pub fn cache_dir(&self) -> PathBuf {
+        self.config_dir.join("cache")
+    }
+
     /// Returns the full config file path
     pub fn config_path(&self) -> PathBuf {
         self.config_dir.join("config.toml")
@@ -83,7 +108,42 @@ impl ConfigManager {
     /// This performs validation before saving and uses atomic writes
     /// to prevent corruption.
     pub fn save(&self, config: &Config) -> ConfigResult<()> {
-        self.persistence.save(config)
+        self.persistence.save(config)?;
+        self.remember_own_write();
+        Ok(())
+    }
+
+    /// Notes the file as just written, so subscribers are not sent it
+    fn remember_own_write(&self) {
+        if let (Ok(contents), Ok(mut own)) = (
+            std::fs::read_to_string(self.config_path()),
+            self.own_write.lock(),
+        ) {
+            *own = Some(contents);
+        }
+    }
+
+    /// Sends the configuration each time the config file is edited
+    ///
+    /// A change is sent once the file settles, and saves made through this
+    /// manager are left out. Each config is validated, then layered like
+    /// [`load_effective`](Self::load_effective). An edit that fails is sent
+    /// as the error, with the first validation error, so the config in use
+    /// can be kept. Watching stops when the receiver or this manager is
+    /// dropped.
+    pub fn subscribe(&self) -> Receiver<ConfigResult<Config>> {
+        let (tx, rx) = mpsc::channel();
+        let overrides = self.overrides.clone();
+        let layer = move |mut config: Config| {
+            EnvOverride::load().merge_into(&mut config);
+            overrides.merge_into(&mut config);
+            config
+        };
+        let handle = watcher::subscribe(self.config_path(), Arc::clone(&self.own_write), layer, tx);
+        if let Ok(mut subscriptions) = self.subscriptions.lock() {
+            subscriptions.push(handle);
+        }
+        rx
     }
 
     /// Updates the configuration using a closure
@@ -122,6 +182,7 @@ impl ConfigManager {
         }
 
         self.persistence.generate_default_with_comments()?;
+        self.remember_own_write();
         Ok(true)
     }
 
@@ -151,50 +212,33 @@ impl ConfigManager {
     /// Example: STORYSTREAM_PLAYER_DEFAULT_VOLUME=80
     ///
     /// This is useful for containerized deployments or CI/CD environments.
+    /// Variables with values that do not fit their field are ignored with a
+    /// warning.
     pub fn load_with_env_overrides(&self) -> ConfigResult<Config> {
         let mut config = self.load()?;
-
-        // Check for environment variable overrides
-        // Format: STORYSTREAM_SECTION_FIELD=value
-
-        // Player overrides
-        if let Ok(volume) = std::env::var("STORYSTREAM_PLAYER_DEFAULT_VOLUME") {
-            if let Ok(v) = volume.parse::<u8>() {
-                config.player.default_volume = v;
-            }
-        }
-
-        if let Ok(speed) = std::env::var("STORYSTREAM_PLAYER_DEFAULT_SPEED") {
-            if let Ok(s) = speed.parse::<f32>() {
-                config.player.default_speed = s;
-            }
-        }
-
-        // App overrides
-        if let Ok(log_level) = std::env::var("STORYSTREAM_APP_LOG_LEVEL") {
-            // This is simplified - in production you'd parse the string to LogLevel enum
-            log::info!("Log level override: {}", log_level);
-        }
-
-        if let Ok(db_path) = std::env::var("STORYSTREAM_APP_DATABASE_PATH") {
-            config.app.database_path = PathBuf::from(db_path);
-        }
-
-        // Validate after applying overrides
-        if let Err(errors) = config.validate() {
-            log::warn!(
-                "Config validation warnings after env overrides: {:?}",
-                errors
-            );
-        }
-
+        EnvOverride::load().merge_into(&mut config);
         Ok(config)
     }
+
+    /// Loads the configuration in effect, never failing
+    ///
+    /// Layers the file, or the defaults if it cannot be read, then the
+    /// `STORYSTREAM_*` environment variables, then the overrides given with
+    /// [`with_overrides`](Self::with_overrides). Settings that do not fit
+    /// are ignored with a warning.
+    pub fn load_effective(&self) -> Config {
+        let mut config = self.load_or_default();
+        EnvOverride::load().merge_into(&mut config);
+        self.overrides.merge_into(&mut config);
+        config
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::watcher::SETTLE_TIME;
+    use std::time::Duration;
     use tempfile::TempDir;
 
     fn setup_test_manager() -> (TempDir, ConfigManager) {
@@ -329,4 +373,50 @@ mod tests {
         let (_temp_dir, manager) = setup_test_manager();
         assert!(manager.config_path().ends_with("config.toml"));
     }
+
+    /// Writes `config` the way an editor would, behind the manager's back
+    fn edit_file(manager: &ConfigManager, config: &Config) {
+        let contents = toml::to_string_pretty(config).expect("Should serialize");
+        std::fs::write(manager.config_path(), contents).expect("Should write");
+    }
+
+    #[test]
+    fn test_subscribe_sends_each_edit_once() {
+        let (_temp_dir, manager) = setup_test_manager();
+        manager.save(&Config::default()).expect("Should save");
+        let changes = manager.subscribe();
+
+        let mut edited = Config::default();
+        edited.player.default_volume = 85;
+        edit_file(&manager, &edited);
+
+        let config = changes
+            .recv_timeout(Duration::from_secs(5))
+            .expect("Should send the edit")
+            .expect("Edit is valid");
+        assert_eq!(config.player.default_volume, 85);
+        assert!(changes.recv_timeout(SETTLE_TIME * 3).is_err());
+    }
+
+    #[test]
+    fn test_subscribe_skips_own_saves_and_reports_invalid_edits() {
+        let (_temp_dir, manager) = setup_test_manager();
+        let changes = manager.subscribe();
+
+        manager
+            .update(|config| config.player.default_volume = 60)
+            .expect("Should update");
+        assert!(changes.recv_timeout(SETTLE_TIME * 3).is_err());
+
+        let mut edited = Config::default();
+        edited.player.default_volume = 150;
+        edit_file(&manager, &edited);
+
+        let error = changes
+            .recv_timeout(Duration::from_secs(5))
+            .expect("Should send the edit")
+            .expect_err("Edit is invalid");
+        assert!(matches!(error, ConfigError::ValidationError(_)));
+        assert!(error.to_string().contains("player.default_volume"));
+    }
 }
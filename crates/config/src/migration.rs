@@ -2,143 +2,224 @@
 //! Configuration migration system
 //!
 //! This module handles upgrading old config file formats to newer versions.
-//! When CONFIG_VERSION is incremented, add a migration function here.
+//! Migrations work on the file's TOML before it is read into a [`Config`],
+//! so settings under keys the current format no longer has are carried over
+//! rather than dropped. When CONFIG_VERSION is incremented, register a
+//! migration from the previous version in [`registry`].
 
 use crate::{Config, ConfigError, ConfigResult, CONFIG_VERSION};
+use std::path::Path;
+use toml::Value;
 
-/// Trait for config migrations
+/// One step in the history of the config file format
 pub trait Migration {
-    /// Returns the version this migration upgrades TO
+    /// Version of the files this migration reads
+    #[allow(clippy::wrong_self_convention)] // Names a version, not a conversion
+    fn from_version(&self) -> u32;
+
+    /// Version of the files this migration writes
+    fn to_version(&self) -> u32;
+
+    /// Rewrites a config file's contents from `from_version` to `to_version`
     ///
-    /// Note: This method is part of the public API and will be used
-    /// when the migration system is fully implemented. Currently unused
-    /// but kept for API stability.
-    #[allow(dead_code)]
-    fn target_version(&self) -> u32;
-
-    /// Performs the migration
-    fn migrate(&self, value: &mut toml::Value) -> ConfigResult<()>;
+    /// The `version` key is updated afterwards and need not be touched.
+    fn migrate(&self, value: Value) -> ConfigResult<Value>;
 }
 
-/// Migrates a config from its current version to the latest version
-pub fn migrate_to_latest(config: Config) -> ConfigResult<Config> {
-    if config.version == CONFIG_VERSION {
-        return Ok(config);
-    }
-
-    if config.version > CONFIG_VERSION {
-        log::warn!(
-            "Config version {} is newer than supported version {}. Attempting to use as-is.",
-            config.version,
-            CONFIG_VERSION
-        );
-        return Ok(config);
-    }
-
-    log::info!(
-        "Migrating config from version {} to {}",
-        config.version,
-        CONFIG_VERSION
-    );
-
-    // Convert to TOML Value for manipulation
-    let mut value = toml::to_string(&config)
-        .map_err(ConfigError::SerializeError)
-        .and_then(|s| {
-            toml::from_str(&s).map_err(|e| ConfigError::ValidationError(e.to_string()))
-        })?;
-
-    // Apply migrations in sequence
-    let mut current_version = config.version;
-
-    while current_version < CONFIG_VERSION {
-        let next_version = current_version + 1;
-
-        if let Some(migration) = get_migration(next_version) {
-            migration.migrate(&mut value)?;
-            log::info!("Applied migration to version {}", next_version);
-        } else {
-            log::warn!(
-                "No migration defined for version {}, skipping",
-                next_version
-            );
+/// Every migration of the config file format, oldest first
+fn registry() -> Vec<Box<dyn Migration>> {
+    // When the format changes, add the step that upgrades the old files:
+    // vec![Box::new(RenameKey::new(1, "player", "volume", "default_volume"))]
+    Vec::new()
+}
+
+/// Moves a setting to a new key within its section
+///
+/// The most common change to the format; a migration renaming
+/// `player.volume` to `player.default_volume` from version 1 is
+/// `RenameKey::new(1, "player", "volume", "default_volume")`.
+pub struct RenameKey {
+    from_version: u32,
+    section: &'static str,
+    from: &'static str,
+    to: &'static str,
+}
+
+impl RenameKey {
+    /// Renames `section.from` to `section.to` in files at `from_version`
+    pub fn new(
+        from_version: u32,
+        section: &'static str,
+        from: &'static str,
+        to: &'static str,
+    ) -> Self {
+        Self {
+            from_version,
+            section,
+            from,
+            to,
         }
+    }
+}
 
-        current_version = next_version;
+impl Migration for RenameKey {
+    fn from_version(&self) -> u32 {
+        self.from_version
     }
 
-    // Update version number
-    if let Some(table) = value.as_table_mut() {
-        table.insert(
-            "version".to_string(),
-            toml::Value::Integer(CONFIG_VERSION as i64),
-        );
+    fn to_version(&self) -> u32 {
+        self.from_version + 1
     }
 
-    // Convert back to Config
-    let migrated: Config =
-        toml::from_str(&toml::to_string(&value).map_err(ConfigError::SerializeError)?)
-            .map_err(|e| ConfigError::ValidationError(e.to_string()))?;
+    fn migrate(&self, mut value: Value) -> ConfigResult<Value> {
+        if let Some(section) = value.get_mut(self.section).and_then(Value::as_table_mut) {
+            // A file that already has the new key keeps its value
+            if let Some(old) = section.remove(self.from) {
+                section.entry(self.to).or_insert(old);
+            }
+        }
+        Ok(value)
+    }
+}
 
-    Ok(migrated)
+/// The registered migrations and the version they lead to
+pub struct Migrations {
+    steps: Vec<Box<dyn Migration>>,
+    target: u32,
 }
 
-/// Returns the migration for a specific version, if one exists
-fn get_migration(version: u32) -> Option<Box<dyn Migration>> {
-    match version {
-        // Example migrations for future versions:
-        // 2 => Some(Box::new(MigrationV2)),
-        // 3 => Some(Box::new(MigrationV3)),
-        _ => None,
+impl Default for Migrations {
+    fn default() -> Self {
+        Self::new(registry(), CONFIG_VERSION)
     }
 }
 
-// Example migration (for when version 2 is needed):
-// struct MigrationV2;
-//
-// impl Migration for MigrationV2 {
-//     fn target_version(&self) -> u32 {
-//         2
-//     }
-//
-//     fn migrate(&self, value: &mut toml::Value) -> ConfigResult<()> {
-//         // Example: Rename a field
-//         if let Some(table) = value.as_table_mut() {
-//             if let Some(player) = table.get_mut("player") {
-//                 if let Some(player_table) = player.as_table_mut() {
-//                     // Rename old_field to new_field
-//                     if let Some(old_value) = player_table.remove("old_field") {
-//                         player_table.insert("new_field".to_string(), old_value);
-//                     }
-//                 }
-//             }
-//         }
-//         Ok(())
-//     }
-// }
+impl Migrations {
+    /// Migrations leading to `target` rather than to [`CONFIG_VERSION`]
+    pub fn new(steps: Vec<Box<dyn Migration>>, target: u32) -> Self {
+        Self { steps, target }
+    }
+
+    /// The newest version these migrations can read
+    pub fn target_version(&self) -> u32 {
+        self.target
+    }
+
+    /// Brings a config file's contents at `version` up to the target
+    /// version, one migration at a time
+    ///
+    /// Fails when a version in between has no migration from it, rather
+    /// than reading the file as if it were current.
+    pub fn migrate(&self, mut value: Value, version: u32) -> ConfigResult<Value> {
+        let mut current = version;
+        while current < self.target {
+            let step = self
+                .steps
+                .iter()
+                .find(|step| step.from_version() == current && step.to_version() > current)
+                .ok_or_else(|| ConfigError::MigrationError {
+                    from: current,
+                    to: self.target,
+                    reason: format!("no migration is registered from version {}", current),
+                })?;
+            let next = step.to_version().min(self.target);
+
+            value = step.migrate(value)?;
+            let table = value
+                .as_table_mut()
+                .ok_or_else(|| ConfigError::MigrationError {
+                    from: current,
+                    to: next,
+                    reason: "the migrated config is not a table".to_string(),
+                })?;
+            table.insert("version".to_string(), Value::Integer(next as i64));
+            log::info!("Migrated config from version {} to {}", current, next);
+            current = next;
+        }
+        Ok(value)
+    }
+
+    /// Reads config file `contents`, migrating them from an older version
+    ///
+    /// Returns the config with the version the file was written in. Files
+    /// without a version are taken to be current, and files newer than the
+    /// target version are refused.
+    pub(crate) fn read(&self, path: &Path, contents: &str) -> ConfigResult<(Config, u32)> {
+        let parse_error = |source| ConfigError::ParseError {
+            path: path.to_path_buf(),
+            source,
+        };
+        let value: Value = toml::from_str(contents).map_err(parse_error)?;
+        let version = match value.get("version") {
+            None => self.target,
+            Some(version) => version
+                .as_integer()
+                .and_then(|version| u32::try_from(version).ok())
+                .ok_or_else(|| {
+                    ConfigError::ValidationError(format!(
+                        "version must be a whole number, found {}",
+                        version
+                    ))
+                })?,
+        };
+
+        if version > self.target {
+            return Err(ConfigError::UnsupportedVersion {
+                path: path.to_path_buf(),
+                version,
+                supported: self.target,
+            });
+        }
+        if version == self.target {
+            // Parsed again for errors that point at the line in the file
+            let config = toml::from_str(contents).map_err(parse_error)?;
+            return Ok((config, version));
+        }
+
+        let config = self
+            .migrate(value, version)?
+            .try_into()
+            .map_err(parse_error)?;
+        Ok((config, version))
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn sample_migrations() -> Migrations {
+        Migrations::new(
+            vec![Box::new(RenameKey::new(
+                1,
+                "player",
+                "volume",
+                "default_volume",
+            ))],
+            2,
+        )
+    }
+
     #[test]
     fn test_migrate_same_version() {
         let config = Config::default();
-        let result = migrate_to_latest(config.clone());
-        assert!(result.is_ok());
-        let migrated = result.expect("Should migrate");
-        assert_eq!(migrated.version, config.version);
+        let contents = toml::to_string(&config).expect("Should serialize");
+        let (migrated, version) = Migrations::default()
+            .read(Path::new("config.toml"), &contents)
+            .expect("Should read");
+        assert_eq!(version, CONFIG_VERSION);
+        assert_eq!(migrated, config);
     }
 
     #[test]
     fn test_migrate_newer_version() {
-        let mut config = Config::default();
-        config.version = CONFIG_VERSION + 1;
-
-        let result = migrate_to_latest(config.clone());
-        assert!(result.is_ok());
-        let migrated = result.expect("Should handle newer version");
-        assert_eq!(migrated.version, CONFIG_VERSION + 1);
+        let contents = format!("version = {}\n", CONFIG_VERSION + 1);
+        let result = Migrations::default().read(Path::new("config.toml"), &contents);
+        assert!(matches!(
+            result,
+            Err(ConfigError::UnsupportedVersion { version, supported, .. })
+                if version == CONFIG_VERSION + 1 && supported == CONFIG_VERSION
+        ));
     }
 
     #[test]
@@ -150,9 +231,64 @@ mod tests {
     }
 
     #[test]
-    fn test_get_migration_returns_none() {
-        // Currently no migrations defined
-        assert!(get_migration(2).is_none());
-        assert!(get_migration(999).is_none());
+    fn test_registry_reaches_current_version() {
+        let oldest = registry()
+            .iter()
+            .map(|step| step.from_version())
+            .min()
+            .unwrap_or(CONFIG_VERSION);
+        let value = Value::Table(Default::default());
+        let migrated = Migrations::default()
+            .migrate(value, oldest)
+            .expect("Every version should have a migration");
+        if oldest < CONFIG_VERSION {
+            assert_eq!(
+                migrated.get("version").and_then(Value::as_integer),
+                Some(CONFIG_VERSION as i64)
+            );
+        }
+    }
+
+    #[test]
+    fn test_rename_key_round_trip() {
+        let old = "version = 1\n\n[player]\nvolume = 80\nskip_silence = true\n";
+        let migrations = sample_migrations();
+
+        let (config, version) = migrations
+            .read(Path::new("config.toml"), old)
+            .expect("Should migrate");
+        assert_eq!(version, 1);
+        assert_eq!(config.version, 2);
+        assert_eq!(config.player.default_volume, 80);
+        assert!(config.player.skip_silence);
+
+        // The upgraded file reads back unchanged and is not migrated again
+        let upgraded = toml::to_string_pretty(&config).expect("Should serialize");
+        let (reread, version) = migrations
+            .read(Path::new("config.toml"), &upgraded)
+            .expect("Should read");
+        assert_eq!(version, 2);
+        assert_eq!(reread, config);
+    }
+
+    #[test]
+    fn test_rename_key_keeps_new_key() {
+        let value: Value =
+            toml::from_str("version = 1\n[player]\nvolume = 80\ndefault_volume = 60\n").unwrap();
+        let migrated = sample_migrations().migrate(value, 1).unwrap();
+        let player = migrated.get("player").unwrap();
+        assert_eq!(player.get("default_volume"), Some(&Value::Integer(60)));
+        assert!(player.get("volume").is_none());
+        assert_eq!(migrated.get("version"), Some(&Value::Integer(2)));
+    }
+
+    #[test]
+    fn test_missing_migration_fails() {
+        let migrations = Migrations::new(Vec::new(), 3);
+        let value = Value::Table(Default::default());
+        assert!(matches!(
+            migrations.migrate(value, 1),
+            Err(ConfigError::MigrationError { from: 1, to: 3, .. })
+        ));
     }
 }
@@ -0,0 +1,387 @@
+//! Settings layered over the config file
+//!
+//! Configuration is read as defaults < file < environment variables <
+//! command line. The upper two layers are [`ConfigOverride`]s: settings
+//! given as `section.field` keys with text values, each read as the type of
+//! the field it sets when the layer is merged.
+
+use crate::{Config, ConfigError, ConfigResult};
+use toml::Value;
+
+/// Config sections that settings can be given for
+const SECTIONS: [&str; 4] = ["app", "player", "library", "limits"];
+
+/// What separates the items of a list, as in `PATH`
+const LIST_SEPARATOR: &str = if cfg!(windows) { ";" } else { ":" };
+
+/// A layer of settings over the config file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigOverride {
+    /// `(key, value)` in the order given; later settings win
+    settings: Vec<(String, String)>,
+}
+
+impl ConfigOverride {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets `key`, such as `player.default_volume`, to `value`
+    ///
+    /// Lists take several values separated as in `PATH`, so
+    /// `library.paths` can be `/a:/b`. Nothing is checked until the layer is
+    /// merged.
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.settings.push((key.into(), value.into()));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.settings.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.settings.len()
+    }
+
+    /// Applies the settings over `config`, leaving out those that do not fit
+    ///
+    /// A setting is left out with a warning when its key names no setting,
+    /// its value cannot be read as the field's type, or the result fails
+    /// validation. Returns why each one was left out; the rest still apply.
+    pub fn merge_into(&self, config: &mut Config) -> Vec<ConfigError> {
+        let mut rejected = Vec::new();
+        for (key, value) in &self.settings {
+            match apply(config, key, value) {
+                Ok(updated) => *config = updated,
+                Err(e) => {
+                    log::warn!("Ignoring setting {}={}: {}", key, value, e);
+                    rejected.push(e);
+                }
+            }
+        }
+        rejected
+    }
+}
+
+/// Reads a [`ConfigOverride`] from `STORYSTREAM_<SECTION>_<FIELD>`
+/// environment variables
+///
+/// `STORYSTREAM_PLAYER_DEFAULT_VOLUME=80` sets `player.default_volume`.
+/// Variables set to nothing count as unset, and those that name no config
+/// section are left to whatever else reads them.
+pub struct EnvOverride;
+
+impl EnvOverride {
+    /// Prefix of the variables read
+    pub const PREFIX: &'static str = "STORYSTREAM_";
+
+    /// Reads the settings from this process's environment
+    pub fn load() -> ConfigOverride {
+        Self::from_vars(std::env::vars_os().filter_map(|(name, value)| {
+            Some((name.into_string().ok()?, value.into_string().ok()?))
+        }))
+    }
+
+    /// Reads the settings from `vars`, as name and value
+    pub fn from_vars(vars: impl IntoIterator<Item = (String, String)>) -> ConfigOverride {
+        let mut vars: Vec<(String, String)> = vars
+            .into_iter()
+            .filter(|(name, value)| name.starts_with(Self::PREFIX) && !value.is_empty())
+            .collect();
+        vars.sort();
+
+        let mut overrides = ConfigOverride::new();
+        for (name, value) in vars {
+            let name = name[Self::PREFIX.len()..].to_lowercase();
+            let Some((section, field)) = name.split_once('_') else {
+                continue;
+            };
+            if SECTIONS.contains(&section) && !field.is_empty() {
+                overrides.set(format!("{}.{}", section, field), value);
+            }
+        }
+        overrides
+    }
+}
+
+/// Splits `key` into its section and field
+fn split_key(key: &str) -> ConfigResult<(&str, &str)> {
+    key.split_once('.')
+        .filter(|(section, field)| SECTIONS.contains(section) && !field.is_empty())
+        .ok_or_else(|| {
+            ConfigError::ValidationError(format!(
+                "{}: not a setting; expected <section>.<field>",
+                key
+            ))
+        })
+}
+
+/// The value of `key` in `config` as text [`apply`] reads back
+///
+/// Lists are joined like `PATH`. `None` for unset optional fields, tables
+/// and keys that name no setting.
+pub(crate) fn setting(config: &Config, key: &str) -> Option<String> {
+    let (section, field) = split_key(key).ok()?;
+    let root = Value::try_from(config).ok()?;
+    match root.get(section)?.get(field)? {
+        Value::String(text) => Some(text.clone()),
+        // Every float setting is an f32, which reads best at that precision
+        Value::Float(number) => Some((*number as f32).to_string()),
+        Value::Array(items) => {
+            let items: Option<Vec<&str>> = items.iter().map(Value::as_str).collect();
+            Some(items?.join(LIST_SEPARATOR))
+        }
+        Value::Table(_) => None,
+        value => Some(value.to_string()),
+    }
+}
+
+/// `config` with `key` back at its default, unset if it is optional
+pub(crate) fn reset(config: &Config, key: &str) -> ConfigResult<Config> {
+    let (section, field) = split_key(key)?;
+    let invalid = |message: String| ConfigError::ValidationError(format!("{}: {}", key, message));
+
+    let mut root = Value::try_from(config).map_err(|e| invalid(e.to_string()))?;
+    if let Some(table) = root.get_mut(section).and_then(Value::as_table_mut) {
+        table.remove(field);
+    }
+    let updated: Config = root
+        .try_into()
+        .map_err(|e| invalid(e.message().to_string()))?;
+    check_field(&updated, key)?;
+    Ok(updated)
+}
+
+/// Fails with the first validation error of `key` in `config`
+fn check_field(config: &Config, key: &str) -> ConfigResult<()> {
+    if let Err(errors) = config.validate() {
+        let index = format!("{}[", key);
+        if let Some(e) = errors
+            .into_iter()
+            .find(|e| e.field == key || e.field.starts_with(&index))
+        {
+            return Err(ConfigError::ValidationError(e.to_string()));
+        }
+    }
+    Ok(())
+}
+
+/// `config` with `key` set to `value`
+pub(crate) fn apply(config: &Config, key: &str, value: &str) -> ConfigResult<Config> {
+    let invalid = |message: String| ConfigError::ValidationError(format!("{}: {}", key, message));
+    let (section, field) = split_key(key)?;
+
+    let root = Value::try_from(config).map_err(|e| invalid(e.to_string()))?;
+    let current = root.get(section).and_then(|table| table.get(field));
+    // Unset optional fields are missing from the serialized config, so
+    // their type is found by trying what the value could be
+    let candidates = match current {
+        Some(current) => vec![parse_as(current, value).map_err(invalid)?],
+        None => guesses(value),
+    };
+
+    let mut error = invalid("not a setting".to_string());
+    for candidate in candidates {
+        let mut root = root.clone();
+        if let Some(table) = root.get_mut(section).and_then(Value::as_table_mut) {
+            table.insert(field.to_string(), candidate);
+        }
+        let updated: Config = match root.try_into() {
+            Ok(updated) => updated,
+            Err(e) => {
+                error = invalid(e.message().to_string());
+                continue;
+            }
+        };
+        // Unknown fields are dropped when deserializing rather than refused
+        let kept = Value::try_from(&updated)
+            .ok()
+            .is_some_and(|root| root.get(section).and_then(|t| t.get(field)).is_some());
+        if !kept {
+            continue;
+        }
+        check_field(&updated, key)?;
+        return Ok(updated);
+    }
+    Err(error)
+}
+
+/// Reads `value` as the type of the `current` value of a field
+fn parse_as(current: &Value, value: &str) -> Result<Value, String> {
+    match current {
+        Value::String(_) => Ok(Value::String(value.to_string())),
+        Value::Integer(_) => value
+            .trim()
+            .parse()
+            .map(Value::Integer)
+            .map_err(|_| format!("'{}' is not a whole number", value)),
+        Value::Float(_) => value
+            .trim()
+            .parse()
+            .map(Value::Float)
+            .map_err(|_| format!("'{}' is not a number", value)),
+        Value::Boolean(_) => {
+            parse_bool(value).ok_or_else(|| format!("'{}' is not true or false", value))
+        }
+        Value::Array(_) => Ok(split_list(value)),
+        _ => Err("cannot be set from a single value".to_string()),
+    }
+}
+
+/// What `value` could be, most specific first
+fn guesses(value: &str) -> Vec<Value> {
+    let trimmed = value.trim();
+    let mut guesses = Vec::new();
+    if let Ok(integer) = trimmed.parse() {
+        guesses.push(Value::Integer(integer));
+    }
+    if let Ok(float) = trimmed.parse() {
+        guesses.push(Value::Float(float));
+    }
+    if let Some(boolean) = parse_bool(value) {
+        guesses.push(boolean);
+    }
+    guesses.push(Value::String(value.to_string()));
+    guesses
+}
+
+fn parse_bool(value: &str) -> Option<Value> {
+    match value.trim().to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some(Value::Boolean(true)),
+        "false" | "no" | "off" | "0" => Some(Value::Boolean(false)),
+        _ => None,
+    }
+}
+
+/// A list separated like `PATH`
+fn split_list(value: &str) -> Value {
+    Value::Array(
+        std::env::split_paths(value)
+            .map(|item| item.to_string_lossy().into_owned())
+            .filter(|item| !item.is_empty())
+            .map(Value::String)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_env_vars_become_settings() {
+        let env = EnvOverride::from_vars(vars(&[
+            ("STORYSTREAM_PLAYER_DEFAULT_VOLUME", "80"),
+            ("STORYSTREAM_LIBRARY_PATHS", "/a:/b"),
+            ("STORYSTREAM_APP_DEBUG_MODE", "yes"),
+            ("STORYSTREAM_LIMITS_ALLOWED_HOURS", "07:00-20:00"),
+            ("STORYSTREAM_LIBRARY_AUTO_IMPORT", ""),
+            ("STORYSTREAM_HOME", "/elsewhere"),
+            ("PATH", "/usr/bin"),
+        ]));
+        assert_eq!(env.len(), 4);
+
+        let mut config = Config::default();
+        let rejected = env.merge_into(&mut config);
+        assert!(rejected.is_empty(), "{:?}", rejected);
+        assert_eq!(config.player.default_volume, 80);
+        assert!(config.app.debug_mode);
+        assert_eq!(config.limits.allowed_hours.as_deref(), Some("07:00-20:00"));
+        assert!(!config.library.auto_import);
+        if cfg!(unix) {
+            assert_eq!(config.library.paths, vec!["/a", "/b"]);
+        }
+    }
+
+    #[test]
+    fn test_unset_values_keep_the_file_values() {
+        let mut config = Config::default();
+        config.player.default_volume = 40;
+        config.library.library_paths = vec![PathBuf::from("/books")];
+        let from_file = config.clone();
+
+        let mut layer = ConfigOverride::new();
+        layer.set("player.default_speed", "1.5");
+        assert!(layer.merge_into(&mut config).is_empty());
+
+        assert_eq!(config.player.default_speed, 1.5);
+        assert_eq!(config.player.default_volume, 40);
+        assert_eq!(
+            config.library.library_paths,
+            from_file.library.library_paths
+        );
+
+        let mut unchanged = from_file.clone();
+        assert!(EnvOverride::from_vars(Vec::new())
+            .merge_into(&mut unchanged)
+            .is_empty());
+        assert_eq!(unchanged, from_file);
+    }
+
+    #[test]
+    fn test_later_layers_win() {
+        let mut config = Config::default();
+        config.player.default_volume = 40;
+
+        let env = EnvOverride::from_vars(vars(&[("STORYSTREAM_PLAYER_DEFAULT_VOLUME", "60")]));
+        let mut cli = ConfigOverride::new();
+        cli.set("player.default_volume", "90");
+
+        env.merge_into(&mut config);
+        assert_eq!(config.player.default_volume, 60);
+        cli.merge_into(&mut config);
+        assert_eq!(config.player.default_volume, 90);
+    }
+
+    #[test]
+    fn test_invalid_settings_are_left_out() {
+        let mut config = Config::default();
+        config.player.default_volume = 40;
+
+        let mut layer = ConfigOverride::new();
+        layer.set("player.default_volume", "loud");
+        layer.set("player.default_volume", "300");
+        layer.set("player.default_volume", "150");
+        layer.set("player.no_such_field", "1");
+        layer.set("volume", "1");
+        layer.set("app.color_scheme", "plaid");
+        layer.set("player.equalizer_presets", "flat");
+        layer.set("player.auto_resume", "false");
+
+        let rejected = layer.merge_into(&mut config);
+        assert_eq!(rejected.len(), 7);
+        assert!(rejected
+            .iter()
+            .all(|e| matches!(e, ConfigError::ValidationError(_))));
+        assert!(rejected[0].to_string().contains("player.default_volume"));
+
+        assert_eq!(config.player.default_volume, 40);
+        assert!(!config.player.auto_resume);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_unset_optional_fields_take_their_type() {
+        let mut config = Config::default();
+        let mut layer = ConfigOverride::new();
+        layer.set("app.maintenance_interval_days", "7");
+        layer.set("app.device_name", "1234");
+        layer.set("library.organization_target", "/sorted");
+
+        assert!(layer.merge_into(&mut config).is_empty());
+        assert_eq!(config.app.maintenance_interval_days, Some(7));
+        assert_eq!(config.app.device_name.as_deref(), Some("1234"));
+        assert_eq!(
+            config.library.organization_target,
+            Some(PathBuf::from("/sorted"))
+        );
+    }
+}
@@ -7,7 +7,8 @@
 //! - Graceful error handling
 //! - NO PANICS - all errors are handled via Result types
 
-use crate::{Config, ConfigError, ConfigResult, CONFIG_VERSION};
+use crate::migration::Migrations;
+use crate::{Config, ConfigError, ConfigResult};
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
@@ -16,18 +17,33 @@ use tempfile::NamedTempFile;
 /// Handles configuration file persistence
 pub struct ConfigPersistence {
     config_path: PathBuf,
+    migrations: Migrations,
 }
 
 impl ConfigPersistence {
     /// Creates a new persistence handler for the given config file path
     pub fn new(config_path: PathBuf) -> Self {
-        Self { config_path }
+        Self {
+            config_path,
+            migrations: Migrations::default(),
+        }
+    }
+
+    /// Uses `migrations` to upgrade older config files
+    #[cfg(test)]
+    pub(crate) fn with_migrations(mut self, migrations: Migrations) -> Self {
+        self.migrations = migrations;
+        self
     }
 
     /// Loads configuration from file
     ///
     /// If the file doesn't exist, returns the default config.
-    /// If the file is empty or corrupted, returns an error.
+    /// If the file is empty or corrupted, or was written by a newer version
+    /// of StoryStream, returns an error. Older files are migrated: the
+    /// original is kept next to the config file as
+    /// `config.toml.v<version>.backup` and the upgraded config written in
+    /// its place.
     pub fn load(&self) -> ConfigResult<Config> {
         if !self.config_path.exists() {
             log::info!(
@@ -55,24 +71,9 @@ impl ConfigPersistence {
             });
         }
 
-        let mut config: Config =
-            toml::from_str(&contents).map_err(|e| ConfigError::ParseError {
-                path: self.config_path.clone(),
-                source: e,
-            })?;
-
-        // Migrate if needed
-        if config.version < CONFIG_VERSION {
-            log::info!(
-                "Config version {} is older than current version {}, migrating...",
-                config.version,
-                CONFIG_VERSION
-            );
-            config = crate::migration::migrate_to_latest(config)?;
-
-            // Save migrated config
-            log::info!("Saving migrated config");
-            self.save(&config)?;
+        let (config, version) = self.migrations.read(&self.config_path, &contents)?;
+        if version < self.migrations.target_version() {
+            self.write_migrated(&config, version);
         }
 
         // Validate the loaded config
@@ -112,6 +113,7 @@ impl ConfigPersistence {
 
         // Backup existing config if it exists
         if self.config_path.exists() {
+            self.refuse_newer_file()?;
             self.backup_config()?;
         }
 
@@ -147,6 +149,48 @@ impl ConfigPersistence {
         Ok(())
     }
 
+    /// Keeps the original of a migrated config file and writes the upgraded
+    /// config in its place
+    ///
+    /// Failing either only costs the upgrade being made again on the next
+    /// load, so problems are logged rather than returned.
+    fn write_migrated(&self, config: &Config, version: u32) {
+        let backup_path = self
+            .config_path
+            .with_extension(format!("toml.v{}.backup", version));
+        let written = fs::copy(&self.config_path, &backup_path)
+            .map_err(|e| ConfigError::BackupError { source: e })
+            .and_then(|_| Ok(toml::to_string_pretty(config)?))
+            .and_then(|contents| self.write_atomic(self.create_temp_file()?, &contents));
+        match written {
+            Ok(()) => log::info!(
+                "Upgraded config from version {}, keeping the original at {}",
+                version,
+                backup_path.display()
+            ),
+            Err(e) => log::warn!("Could not save the upgraded config: {}", e),
+        }
+    }
+
+    /// Fails if the config file on disk is newer than this build reads, so
+    /// saving does not throw away settings it does not know about
+    fn refuse_newer_file(&self) -> ConfigResult<()> {
+        let version = fs::read_to_string(&self.config_path)
+            .ok()
+            .and_then(|contents| toml::from_str::<toml::Value>(&contents).ok())
+            .and_then(|value| value.get("version")?.as_integer());
+        match version {
+            Some(version) if version > self.migrations.target_version() as i64 => {
+                Err(ConfigError::UnsupportedVersion {
+                    path: self.config_path.clone(),
+                    version: version as u32,
+                    supported: self.migrations.target_version(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Creates a temporary file in the same directory as the config file
     fn create_temp_file(&self) -> ConfigResult<NamedTempFile> {
         let dir = self
@@ -258,6 +302,59 @@ mod tests {
         assert!(backup_path.exists());
     }
 
+    #[test]
+    fn test_older_config_is_migrated_and_backed_up() {
+        use crate::{Migrations, RenameKey};
+
+        let (_temp_dir, config_path) = setup_test_dir();
+        let original = "version = 1\n\n[player]\nvolume = 80\n";
+        fs::write(&config_path, original).expect("Should write file");
+        let migrations = Migrations::new(
+            vec![Box::new(RenameKey::new(
+                1,
+                "player",
+                "volume",
+                "default_volume",
+            ))],
+            2,
+        );
+        let persistence = ConfigPersistence::new(config_path.clone()).with_migrations(migrations);
+
+        let config = persistence.load().expect("Should migrate");
+        assert_eq!(config.version, 2);
+        assert_eq!(config.player.default_volume, 80);
+
+        let backup = fs::read_to_string(config_path.with_extension("toml.v1.backup"))
+            .expect("Should keep the original");
+        assert_eq!(backup, original);
+        let upgraded: Config =
+            toml::from_str(&fs::read_to_string(&config_path).unwrap()).expect("Should parse");
+        assert_eq!(upgraded, config);
+
+        assert_eq!(persistence.load().expect("Should load"), config);
+    }
+
+    #[test]
+    fn test_newer_config_is_refused() {
+        let (_temp_dir, config_path) = setup_test_dir();
+        let newer = format!(
+            "version = {}\n\n[player]\nfuture_setting = true\n",
+            crate::CONFIG_VERSION + 1
+        );
+        fs::write(&config_path, &newer).expect("Should write file");
+        let persistence = ConfigPersistence::new(config_path.clone());
+
+        assert!(matches!(
+            persistence.load(),
+            Err(ConfigError::UnsupportedVersion { .. })
+        ));
+        assert!(matches!(
+            persistence.save(&Config::default()),
+            Err(ConfigError::UnsupportedVersion { .. })
+        ));
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), newer);
+    }
+
     #[test]
     fn test_invalid_config_returns_error() {
         let (_temp_dir, config_path) = setup_test_dir();
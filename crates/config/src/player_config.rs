@@ -2,6 +2,8 @@
 
 use crate::validation::{ConfigSection, ValidationError, Validator};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 
 /// Player preferences and behavior
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -19,9 +21,23 @@ pub struct PlayerConfig {
     /// Resume playback from last position on start
     pub auto_resume: bool,
 
+    /// Load the most recently played unfinished book when the player starts
+    pub resume_on_startup: bool,
+
+    /// Start playing the book loaded by `resume_on_startup` instead of
+    /// waiting paused at its position
+    pub resume_autoplay: bool,
+
+    /// Play on by itself once an interrupted output device is back
+    pub resume_after_interruption: bool,
+
     /// Skip silence automatically
     pub skip_silence: bool,
 
+    /// Even out loudness between books, measuring each one the first time
+    /// it plays; the volume setting still applies on top
+    pub normalize_volume: bool,
+
     /// Rewind seconds when resuming playback
     pub resume_rewind_secs: u64,
 
@@ -33,6 +49,39 @@ pub struct PlayerConfig {
 
     /// Playback speed change step
     pub speed_step: f32,
+
+    /// Equalizer preset for books that have not picked their own
+    pub equalizer_preset: String,
+
+    /// User equalizer presets: ten band gains in dB, 32 Hz band first
+    pub equalizer_presets: BTreeMap<String, Vec<f32>>,
+
+    /// Seconds playback must stay paused before an auto-bookmark is placed
+    /// (0 disables them)
+    pub auto_bookmark_pause_secs: u64,
+
+    /// Auto-bookmarks kept per book; the oldest are removed first
+    pub max_auto_bookmarks: usize,
+
+    /// Folder bookmark exports are written to; next to the book when unset
+    pub bookmark_export_dir: Option<PathBuf>,
+
+    /// Volume and equalizer remembered for each output device
+    pub device_profiles: Vec<DeviceProfile>,
+}
+
+/// Volume and equalizer remembered for one output device
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeviceProfile {
+    /// Identifier the audio backend reports for the device
+    pub id: String,
+    /// Device name, matched when the identifier is not recognized
+    pub name: String,
+    /// Percentage points added to `default_volume` on this device
+    pub volume_offset: i16,
+    /// Equalizer preset switched to on this device
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equalizer_preset: Option<String>,
 }
 
 impl Default for PlayerConfig {
@@ -42,18 +91,82 @@ impl Default for PlayerConfig {
             default_speed: 1.0,
             autosave_interval_secs: 5,
             auto_resume: true,
+            resume_on_startup: false,
+            resume_autoplay: false,
+            resume_after_interruption: true,
             skip_silence: false,
+            normalize_volume: true,
             resume_rewind_secs: 3,
             ui_refresh_ms: 100,
             volume_step: 5,
             speed_step: 0.1,
+            equalizer_preset: "Flat".to_string(),
+            equalizer_presets: BTreeMap::new(),
+            auto_bookmark_pause_secs: 30,
+            max_auto_bookmarks: 10,
+            bookmark_export_dir: None,
+            device_profiles: Vec::new(),
         }
     }
 }
 
+impl PlayerConfig {
+    /// Profile of an output device, matched by identifier, else by name
+    ///
+    /// Not every platform keeps a device's identifier across reconnects; its
+    /// name is the fallback.
+    pub fn device_profile(&self, id: &str, name: &str) -> Option<&DeviceProfile> {
+        self.device_profile_index(id, name)
+            .map(|index| &self.device_profiles[index])
+    }
+
+    /// Volume (0-100) for an output device: `default_volume` plus its offset
+    pub fn device_volume(&self, id: &str, name: &str) -> u8 {
+        let offset = self
+            .device_profile(id, name)
+            .map_or(0, |profile| profile.volume_offset);
+        (self.default_volume as i16 + offset).clamp(0, 100) as u8
+    }
+
+    /// Remembers `volume` (0-100) as the volume for an output device
+    ///
+    /// The profile is re-keyed to `id` and `name`, so a device found by name
+    /// is matched by its new identifier from then on. Returns the offset
+    /// stored.
+    pub fn remember_device_volume(&mut self, id: &str, name: &str, volume: u8) -> i16 {
+        let offset = volume.min(100) as i16 - self.default_volume as i16;
+        match self.device_profile_index(id, name) {
+            Some(index) => {
+                let profile = &mut self.device_profiles[index];
+                profile.id = id.to_string();
+                profile.name = name.to_string();
+                profile.volume_offset = offset;
+            }
+            None => self.device_profiles.push(DeviceProfile {
+                id: id.to_string(),
+                name: name.to_string(),
+                volume_offset: offset,
+                equalizer_preset: None,
+            }),
+        }
+        offset
+    }
+
+    fn device_profile_index(&self, id: &str, name: &str) -> Option<usize> {
+        self.device_profiles
+            .iter()
+            .position(|profile| profile.id == id)
+            .or_else(|| {
+                self.device_profiles
+                    .iter()
+                    .position(|profile| profile.name == name)
+            })
+    }
+}
+
 impl ConfigSection for PlayerConfig {
     fn validate(&self) -> Result<(), Vec<ValidationError>> {
-        Validator::collect_errors(vec![
+        let mut results = vec![
             Validator::in_range(self.default_volume, 0, 100, "player.default_volume"),
             Validator::in_range(self.default_speed, 0.5, 2.0, "player.default_speed"),
             Validator::in_range(
@@ -66,7 +179,63 @@ impl ConfigSection for PlayerConfig {
             Validator::in_range(self.ui_refresh_ms, 16, 1000, "player.ui_refresh_ms"),
             Validator::in_range(self.volume_step, 1, 50, "player.volume_step"),
             Validator::in_range(self.speed_step, 0.05, 0.5, "player.speed_step"),
-        ])
+            Validator::in_range(
+                self.auto_bookmark_pause_secs,
+                0,
+                3600,
+                "player.auto_bookmark_pause_secs",
+            ),
+            Validator::in_range(self.max_auto_bookmarks, 1, 100, "player.max_auto_bookmarks"),
+        ];
+
+        if let Some(dir) = &self.bookmark_export_dir {
+            if dir.as_os_str().is_empty() {
+                results.push(Err(ValidationError::new(
+                    "player.bookmark_export_dir",
+                    "must not be empty (remove it to export next to the book)",
+                )));
+            }
+        }
+
+        if self.equalizer_preset.trim().is_empty() {
+            results.push(Err(ValidationError::new(
+                "player.equalizer_preset",
+                "must not be empty",
+            )));
+        }
+        for (name, gains) in &self.equalizer_presets {
+            let field = format!("player.equalizer_presets.{}", name);
+            if name.trim().is_empty() {
+                results.push(Err(ValidationError::new(field, "needs a name")));
+            } else if gains.len() != 10 {
+                results.push(Err(ValidationError::new(
+                    field,
+                    format!("needs 10 band gains, found {}", gains.len()),
+                )));
+            } else if gains.iter().any(|gain| !(-12.0..=12.0).contains(gain)) {
+                results.push(Err(ValidationError::new(
+                    field,
+                    "band gains must be between -12 and 12 dB",
+                )));
+            }
+        }
+
+        for (i, profile) in self.device_profiles.iter().enumerate() {
+            let field = format!("player.device_profiles[{}]", i);
+            if profile.id.trim().is_empty() && profile.name.trim().is_empty() {
+                results.push(Err(ValidationError::new(
+                    field,
+                    "needs a device id or name",
+                )));
+            } else if !(-100..=100).contains(&profile.volume_offset) {
+                results.push(Err(ValidationError::new(
+                    field,
+                    "volume_offset must be between -100 and 100",
+                )));
+            }
+        }
+
+        Validator::collect_errors(results)
     }
 
     fn merge(&mut self, other: Self) {
@@ -74,16 +243,140 @@ impl ConfigSection for PlayerConfig {
         self.default_speed = other.default_speed;
         self.autosave_interval_secs = other.autosave_interval_secs;
         self.auto_resume = other.auto_resume;
+        self.resume_on_startup = other.resume_on_startup;
+        self.resume_autoplay = other.resume_autoplay;
+        self.resume_after_interruption = other.resume_after_interruption;
         self.skip_silence = other.skip_silence;
+        self.normalize_volume = other.normalize_volume;
         self.resume_rewind_secs = other.resume_rewind_secs;
         self.ui_refresh_ms = other.ui_refresh_ms;
         self.volume_step = other.volume_step;
         self.speed_step = other.speed_step;
+        self.equalizer_preset = other.equalizer_preset;
+        self.equalizer_presets = other.equalizer_presets;
+        self.auto_bookmark_pause_secs = other.auto_bookmark_pause_secs;
+        self.max_auto_bookmarks = other.max_auto_bookmarks;
+        self.bookmark_export_dir = other.bookmark_export_dir;
+        self.device_profiles = other.device_profiles;
     }
 
     fn section_name(&self) -> &'static str {
         "player"
     }
+
+    fn json_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "description": "Player preferences",
+            "properties": {
+                "default_volume": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": 100,
+                    "description": "Default volume level"
+                },
+                "default_speed": {
+                    "type": "number",
+                    "minimum": 0.5,
+                    "maximum": 2.0,
+                    "description": "Default playback speed"
+                },
+                "autosave_interval_secs": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 300,
+                    "description": "Auto-save interval in seconds"
+                },
+                "auto_resume": {
+                    "type": "boolean",
+                    "description": "Resume from last position"
+                },
+                "resume_on_startup": {
+                    "type": "boolean",
+                    "description": "Load the last unfinished book on startup"
+                },
+                "resume_autoplay": {
+                    "type": "boolean",
+                    "description": "Play the resumed book instead of starting paused"
+                },
+                "resume_after_interruption": {
+                    "type": "boolean",
+                    "description": "Play on once an interrupted audio device is back"
+                },
+                "skip_silence": {
+                    "type": "boolean",
+                    "description": "Skip silence automatically"
+                },
+                "resume_rewind_secs": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": 60,
+                    "description": "Rewind seconds when resuming"
+                },
+                "ui_refresh_ms": {
+                    "type": "integer",
+                    "minimum": 16,
+                    "maximum": 1000,
+                    "description": "UI refresh rate in milliseconds"
+                },
+                "volume_step": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 50,
+                    "description": "Volume increment/decrement step"
+                },
+                "speed_step": {
+                    "type": "number",
+                    "minimum": 0.05,
+                    "maximum": 0.5,
+                    "description": "Speed increment/decrement step"
+                },
+                "equalizer_preset": {
+                    "type": "string",
+                    "description": "Default equalizer preset name"
+                },
+                "equalizer_presets": {
+                    "type": "object",
+                    "additionalProperties": {
+                        "type": "array",
+                        "items": { "type": "number", "minimum": -12, "maximum": 12 },
+                        "minItems": 10,
+                        "maxItems": 10
+                    },
+                    "description": "User equalizer presets by name"
+                },
+                "auto_bookmark_pause_secs": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": 3600,
+                    "description": "Pause length before an auto-bookmark, 0 to disable"
+                },
+                "max_auto_bookmarks": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 100,
+                    "description": "Auto-bookmarks kept per book"
+                },
+                "bookmark_export_dir": {
+                    "type": ["string", "null"],
+                    "description": "Folder bookmark exports are written to"
+                },
+                "device_profiles": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "id": { "type": "string" },
+                            "name": { "type": "string" },
+                            "volume_offset": { "type": "integer", "minimum": -100, "maximum": 100 },
+                            "equalizer_preset": { "type": "string" }
+                        }
+                    },
+                    "description": "Volume and equalizer remembered per output device"
+                }
+            }
+        })
+    }
 }
 
 #[cfg(test)]
@@ -117,16 +410,105 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_invalid_auto_bookmark_settings() {
+        let mut config = PlayerConfig {
+            auto_bookmark_pause_secs: 0,
+            ..PlayerConfig::default()
+        };
+        assert!(config.validate().is_ok());
+
+        config.max_auto_bookmarks = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_invalid_bookmark_export_dir() {
+        let mut config = PlayerConfig {
+            bookmark_export_dir: Some(PathBuf::from("/home/me/Notes")),
+            ..PlayerConfig::default()
+        };
+        assert!(config.validate().is_ok());
+
+        config.bookmark_export_dir = Some(PathBuf::new());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_invalid_equalizer_presets() {
+        let mut config = PlayerConfig::default();
+        config
+            .equalizer_presets
+            .insert("Old LibriVox".to_string(), vec![2.0; 10]);
+        assert!(config.validate().is_ok());
+
+        config
+            .equalizer_presets
+            .insert("Short".to_string(), vec![0.0; 9]);
+        config
+            .equalizer_presets
+            .insert("Loud".to_string(), vec![15.0; 10]);
+        assert_eq!(config.validate().unwrap_err().len(), 2);
+    }
+
+    #[test]
+    fn test_device_volume_is_remembered_per_device() {
+        let mut config = PlayerConfig::default();
+        assert_eq!(config.device_volume("a1", "Headphones"), 70);
+
+        assert_eq!(config.remember_device_volume("b2", "Speaker", 90), 20);
+        assert_eq!(config.device_volume("b2", "Speaker"), 90);
+        assert_eq!(config.device_volume("a1", "Headphones"), 70);
+
+        // The offset follows the base volume
+        config.default_volume = 50;
+        assert_eq!(config.device_volume("b2", "Speaker"), 70);
+        config.default_volume = 95;
+        assert_eq!(config.device_volume("b2", "Speaker"), 100);
+    }
+
+    #[test]
+    fn test_device_profile_falls_back_to_name() {
+        let mut config = PlayerConfig::default();
+        config.remember_device_volume("old-id", "Speaker", 80);
+
+        // Reconnected under a new identifier
+        assert_eq!(config.device_volume("new-id", "Speaker"), 80);
+        config.remember_device_volume("new-id", "Speaker", 85);
+        assert_eq!(config.device_profiles.len(), 1);
+        assert_eq!(config.device_profiles[0].id, "new-id");
+
+        // Identifiers win over names
+        config.remember_device_volume("other", "Speaker (2)", 40);
+        assert_eq!(
+            config
+                .device_profile("other", "Speaker")
+                .unwrap()
+                .volume_offset,
+            -30
+        );
+
+        config.device_profiles[0].volume_offset = 150;
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_merge() {
         let mut base = PlayerConfig::default();
         let mut other = PlayerConfig::default();
         other.default_volume = 80;
         other.auto_resume = false;
+        other.resume_on_startup = true;
+        other.resume_after_interruption = false;
+        other.normalize_volume = false;
 
         base.merge(other);
         assert_eq!(base.default_volume, 80);
         assert!(!base.auto_resume);
+        assert!(base.resume_on_startup);
+        assert!(!base.resume_autoplay);
+        assert!(!base.resume_after_interruption);
+        assert!(!base.normalize_volume);
     }
 
     #[test]
@@ -3,7 +3,7 @@
 //! This module generates JSON Schema and documented TOML templates
 //! for IDE autocomplete and documentation purposes.
 
-use crate::Config;
+use crate::{Config, CONFIG_VERSION};
 
 /// Generates a documented TOML config template
 ///
@@ -27,6 +27,9 @@ pub fn generate_documented_toml() -> String {
     output.push_str("# Logging level: error, warn, info, debug, trace\n");
     output.push_str("log_level = \"info\"\n\n");
 
+    output.push_str("# Per-crate log filter in RUST_LOG syntax (overrides log_level)\n");
+    output.push_str("# log_filter = \"info,storystream_library=debug,sqlx=warn\"\n\n");
+
     output.push_str("# Enable debug mode for additional logging and checks\n");
     output.push_str("debug_mode = false\n\n");
 
@@ -43,6 +46,42 @@ pub fn generate_documented_toml() -> String {
     output.push_str("# Range: 1-100\n");
     output.push_str("max_recent_books = 10\n\n");
 
+    output.push_str("# Minutes of listening a day needs to count toward the streak\n");
+    output.push_str("# Range: 1-1440\n");
+    output.push_str("daily_goal_minutes = 30\n\n");
+
+    output.push_str("# Folder shared with your other devices for syncing positions\n");
+    output.push_str("# sync_folder = \"/home/me/Dropbox/StoryStream\"\n\n");
+
+    output.push_str("# Name other devices show for this one\n");
+    output.push_str("# device_name = \"Laptop\"\n\n");
+
+    output.push_str("# Find and sync with paired devices on the local network\n");
+    output.push_str("lan_sync = false\n\n");
+
+    output.push_str("# Disk space all caches (covers, saved searches) may use together, in MB\n");
+    output.push_str("# Range: 10-100000\n");
+    output.push_str("cache_max_mb = 500\n\n");
+
+    output.push_str("# Announce playback, chapters and selections for screen readers\n");
+    output.push_str("accessible = false\n\n");
+
+    output.push_str("# How much to announce: terse, normal, verbose\n");
+    output.push_str("announce_verbosity = \"normal\"\n\n");
+
+    output.push_str("# Control playback from a browser, such as your phone's\n");
+    output.push_str("remote_control = false\n\n");
+
+    output.push_str("# Address the remote control listens on; 0.0.0.0 opens it to the network\n");
+    output.push_str("remote_bind = \"127.0.0.1:8910\"\n\n");
+
+    output.push_str("# Token remote control requests must carry\n");
+    output.push_str("# remote_token = \"change-me\"\n\n");
+
+    output.push_str("# Tidy the database at startup once this many days have passed\n");
+    output.push_str("# Range: 1-365\n");
+    output.push_str("# maintenance_interval_days = 7\n\n");
+
     output.push_str("# Enable experimental features (may be unstable)\n");
     output.push_str("experimental_features = false\n\n");
 
@@ -62,9 +101,21 @@ pub fn generate_documented_toml() -> String {
     output.push_str("# Automatically resume playback from last position\n");
     output.push_str("auto_resume = true\n\n");
 
+    output.push_str("# Load the last unfinished book when the player starts\n");
+    output.push_str("resume_on_startup = false\n\n");
+
+    output.push_str("# Start playing that book right away instead of waiting paused\n");
+    output.push_str("resume_autoplay = false\n\n");
+
+    output.push_str("# Play on once an interrupted audio device is back\n");
+    output.push_str("resume_after_interruption = true\n\n");
+
     output.push_str("# Automatically skip silence in audio\n");
     output.push_str("skip_silence = false\n\n");
 
+    output.push_str("# Even out loudness between books; each is measured when first played\n");
+    output.push_str("normalize_volume = true\n\n");
+
     output.push_str("# Rewind this many seconds when resuming playback\n");
     output.push_str("# Helps you remember where you left off\n");
     output.push_str("# Range: 0-60\n");
@@ -83,6 +134,34 @@ pub fn generate_documented_toml() -> String {
     output.push_str("# Range: 0.05-0.5\n");
     output.push_str("speed_step = 0.1\n\n");
 
+    output.push_str("# Equalizer preset for books without their own: Flat, Bass Boost,\n");
+    output.push_str("# Voice Boost, or one of your presets below\n");
+    output.push_str("equalizer_preset = \"Flat\"\n\n");
+
+    output
+        .push_str("# Your equalizer presets: ten band gains in dB (-12 to 12), 32 Hz to 16 kHz\n");
+    output.push_str("# [player.equalizer_presets]\n");
+    output.push_str("# \"Old LibriVox\" = [-3, -2, 0, 2, 4, 4, 3, 1, -1, -3]\n\n");
+
+    output.push_str("# Place an auto-bookmark once playback has been paused this long\n");
+    output.push_str("# Range: 0-3600 seconds (0 disables)\n");
+    output.push_str("auto_bookmark_pause_secs = 30\n\n");
+
+    output.push_str("# Auto-bookmarks kept per book, oldest removed first\n");
+    output.push_str("# Range: 1-100\n");
+    output.push_str("max_auto_bookmarks = 10\n\n");
+
+    output.push_str("# Folder bookmark exports (Ctrl+E) go to, next to the book if unset\n");
+    output.push_str("# bookmark_export_dir = \"/home/me/Notes/Audiobooks\"\n\n");
+
+    output.push_str("# Volume remembered per output device, as points above or below\n");
+    output.push_str("# default_volume; filled in as you change the volume on each device\n");
+    output.push_str("# [[player.device_profiles]]\n");
+    output.push_str("# id = \"5d41402abc4b2a76\"\n");
+    output.push_str("# name = \"Bluetooth Speaker\"\n");
+    output.push_str("# volume_offset = 20\n");
+    output.push_str("# equalizer_preset = \"Voice Boost\"\n\n");
+
     // Library section
     output.push_str("[library]\n");
     output.push_str("# Paths to scan for audiobooks\n");
@@ -115,12 +194,49 @@ pub fn generate_documented_toml() -> String {
     output.push_str("# Warning: Can cause infinite loops if links are circular\n");
     output.push_str("follow_symlinks = false\n\n");
 
+    output.push_str("# Glob patterns for paths to skip when scanning and watching\n");
+    output.push_str("# * matches within a folder name, ** across folders\n");
+    output.push_str("exclude_patterns = []\n");
+    output.push_str("# Example:\n");
+    output.push_str("# exclude_patterns = [\"**/@eaDir\", \"**/*sample*\"]\n\n");
+
+    output.push_str("# Skip hidden files and folders, such as .stfolder\n");
+    output.push_str("ignore_hidden = true\n\n");
+
     output.push_str("# Organize imported files by author/title\n");
     output.push_str("organize_files = false\n\n");
 
     output.push_str("# Target directory for organized files (required if organize_files = true)\n");
     output.push_str("# organization_target = \"/path/to/organized/audiobooks\"\n\n");
 
+    output.push_str("# Browse and play without changing the library\n");
+    output.push_str("# Also used automatically when the database file is not writable\n");
+    output.push_str("read_only = false\n\n");
+
+    // Limits section
+    output.push_str("[limits]\n");
+    output.push_str("# Minutes of listening allowed per day, reset at local midnight\n");
+    output.push_str("# 0 = no cap\n");
+    output.push_str("daily_minutes = 0\n\n");
+
+    output.push_str("# Time of day listening is allowed, in local time\n");
+    output.push_str("# allowed_hours = \"07:00-19:30\"\n\n");
+
+    output.push_str("# SHA-256 (hex) of a password that lifts the limits until restart\n");
+    output.push_str("# Create one with: printf '%s' 'password' | sha256sum\n");
+    output.push_str("# override_password_sha256 = \"...\"\n\n");
+
+    // Keybindings section
+    output.push_str("[keybindings]\n");
+    output.push_str("# Keys for TUI actions in place of the defaults, separated by spaces\n");
+    output.push_str("# Names: space, enter, tab, esc, left, right, home, end, f1-f12, with\n");
+    output.push_str("# ctrl+, alt+ or shift+ in front; uppercase letters need no shift+\n");
+    output.push_str("# The Help view (h) lists every action with the keys in use\n");
+    output.push_str("# Example, for vim users:\n");
+    output.push_str("# seek_backward = \"h\"\n");
+    output.push_str("# seek_forward = \"l\"\n");
+    output.push_str("# toggle_help = \"?\"\n\n");
+
     output
 }
 
@@ -128,170 +244,82 @@ pub fn generate_documented_toml() -> String {
 ///
 /// This can be used by IDEs and editors for autocomplete and validation.
 pub fn generate_json_schema() -> String {
+    json_schema().to_string()
+}
+
+/// The JSON Schema for the configuration, built from each section's
+/// [`ConfigSection::json_schema`](crate::ConfigSection::json_schema)
+pub fn json_schema() -> serde_json::Value {
+    let mut required = vec!["version"];
+    let mut properties = serde_json::Map::new();
+    properties.insert(
+        "version".to_string(),
+        serde_json::json!({
+            "type": "integer",
+            "description": "Configuration file format version",
+            "const": CONFIG_VERSION
+        }),
+    );
+    for (name, schema) in Config::section_schemas() {
+        required.push(name);
+        properties.insert(name.to_string(), schema);
+    }
+
     serde_json::json!({
         "$schema": "http://json-schema.org/draft-07/schema#",
         "title": "StoryStream Configuration",
         "description": "Configuration file for StoryStream audiobook player",
         "type": "object",
-        "required": ["version", "app", "player", "library"],
-        "properties": {
-            "version": {
-                "type": "integer",
-                "description": "Configuration file format version",
-                "const": 1
-            },
-            "app": {
-                "type": "object",
-                "description": "Application-level settings",
-                "properties": {
-                    "database_path": {
-                        "type": "string",
-                        "description": "Path to the database file"
-                    },
-                    "log_level": {
-                        "type": "string",
-                        "enum": ["error", "warn", "info", "debug", "trace"],
-                        "description": "Logging verbosity level"
-                    },
-                    "debug_mode": {
-                        "type": "boolean",
-                        "description": "Enable debug mode"
-                    },
-                    "check_updates": {
-                        "type": "boolean",
-                        "description": "Check for updates on startup"
-                    },
-                    "telemetry_enabled": {
-                        "type": "boolean",
-                        "description": "Send anonymous usage statistics"
-                    },
-                    "color_scheme": {
-                        "type": "string",
-                        "enum": ["auto", "light", "dark"],
-                        "description": "UI color scheme"
-                    },
-                    "max_recent_books": {
-                        "type": "integer",
-                        "minimum": 1,
-                        "maximum": 100,
-                        "description": "Maximum recent books to track"
-                    },
-                    "experimental_features": {
-                        "type": "boolean",
-                        "description": "Enable experimental features"
-                    }
-                }
-            },
-            "player": {
-                "type": "object",
-                "description": "Player preferences",
-                "properties": {
-                    "default_volume": {
-                        "type": "integer",
-                        "minimum": 0,
-                        "maximum": 100,
-                        "description": "Default volume level"
-                    },
-                    "default_speed": {
-                        "type": "number",
-                        "minimum": 0.5,
-                        "maximum": 2.0,
-                        "description": "Default playback speed"
-                    },
-                    "autosave_interval_secs": {
-                        "type": "integer",
-                        "minimum": 1,
-                        "maximum": 300,
-                        "description": "Auto-save interval in seconds"
-                    },
-                    "auto_resume": {
-                        "type": "boolean",
-                        "description": "Resume from last position"
-                    },
-                    "skip_silence": {
-                        "type": "boolean",
-                        "description": "Skip silence automatically"
-                    },
-                    "resume_rewind_secs": {
-                        "type": "integer",
-                        "minimum": 0,
-                        "maximum": 60,
-                        "description": "Rewind seconds when resuming"
-                    },
-                    "ui_refresh_ms": {
-                        "type": "integer",
-                        "minimum": 16,
-                        "maximum": 1000,
-                        "description": "UI refresh rate in milliseconds"
-                    },
-                    "volume_step": {
-                        "type": "integer",
-                        "minimum": 1,
-                        "maximum": 50,
-                        "description": "Volume increment/decrement step"
-                    },
-                    "speed_step": {
-                        "type": "number",
-                        "minimum": 0.05,
-                        "maximum": 0.5,
-                        "description": "Speed increment/decrement step"
-                    }
-                }
-            },
-            "library": {
-                "type": "object",
-                "description": "Library and import settings",
-                "properties": {
-                    "library_paths": {
-                        "type": "array",
-                        "items": { "type": "string" },
-                        "description": "Directories to scan for audiobooks"
-                    },
-                    "supported_extensions": {
-                        "type": "array",
-                        "items": { "type": "string" },
-                        "description": "Supported audio file extensions"
-                    },
-                    "auto_import": {
-                        "type": "boolean",
-                        "description": "Auto-import discovered files"
-                    },
-                    "extract_metadata": {
-                        "type": "boolean",
-                        "description": "Extract metadata from files"
-                    },
-                    "recursive_scan": {
-                        "type": "boolean",
-                        "description": "Recurse into subdirectories"
-                    },
-                    "max_scan_depth": {
-                        "type": "integer",
-                        "minimum": 0,
-                        "description": "Maximum recursion depth (0=unlimited)"
-                    },
-                    "min_file_size_bytes": {
-                        "type": "integer",
-                        "minimum": 0,
-                        "maximum": 104857600,
-                        "description": "Minimum file size in bytes"
-                    },
-                    "follow_symlinks": {
-                        "type": "boolean",
-                        "description": "Follow symbolic links"
-                    },
-                    "organize_files": {
-                        "type": "boolean",
-                        "description": "Organize by author/title"
-                    },
-                    "organization_target": {
-                        "type": ["string", "null"],
-                        "description": "Target directory for organized files"
-                    }
-                }
-            }
-        }
+        "required": required,
+        "properties": properties
     })
-    .to_string()
+}
+
+/// What the schema allows for a setting, such as `0 to 100`
+///
+/// `key` is a path such as `player.default_volume`. Returns `None` for
+/// settings the schema puts no bounds on.
+pub fn accepted_values(key: &str) -> Option<String> {
+    if let Some(options) = options(key) {
+        return Some(format!("one of {}", options.join(", ")));
+    }
+    let field = field_schema(key)?;
+    match (field.get("minimum"), field.get("maximum")) {
+        (Some(min), Some(max)) => Some(format!("{} to {}", min, max)),
+        (Some(min), None) => Some(format!("at least {}", min)),
+        (None, Some(max)) => Some(format!("at most {}", max)),
+        (None, None) => None,
+    }
+}
+
+/// Lowest and highest value of a numeric setting such as
+/// `player.default_volume`, `None` unless the schema gives both
+pub fn range(key: &str) -> Option<(f64, f64)> {
+    let field = field_schema(key)?;
+    Some((
+        field.get("minimum")?.as_f64()?,
+        field.get("maximum")?.as_f64()?,
+    ))
+}
+
+/// Values a setting such as `app.color_scheme` takes, `None` for settings
+/// that are not one of a fixed set
+pub fn options(key: &str) -> Option<Vec<String>> {
+    let field = field_schema(key)?;
+    let options = field.get("enum")?.as_array()?;
+    Some(
+        options
+            .iter()
+            .map(|option| option.as_str().map_or(option.to_string(), str::to_string))
+            .collect(),
+    )
+}
+
+fn field_schema(key: &str) -> Option<serde_json::Value> {
+    let schema = json_schema();
+    key.split('.')
+        .try_fold(&schema, |schema, name| schema.get("properties")?.get(name))
+        .cloned()
 }
 
 /// Generates a config with all possible values set to demonstrate options
@@ -323,6 +351,8 @@ mod tests {
         assert!(toml.contains("[app]"));
         assert!(toml.contains("[player]"));
         assert!(toml.contains("[library]"));
+        assert!(toml.contains("[limits]"));
+        assert!(toml.contains("[keybindings]"));
 
         // Should contain comments
         assert!(toml.contains("# Default volume"));
@@ -349,6 +379,55 @@ mod tests {
         assert!(json["properties"]["library"].is_object());
     }
 
+    #[test]
+    fn test_every_section_is_in_the_schema() {
+        let schema = json_schema();
+        for (name, _) in Config::section_schemas() {
+            assert!(schema["properties"][name]["properties"].is_object());
+            assert!(schema["required"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|required| required == name));
+        }
+        assert!(schema["properties"]["limits"].is_object());
+    }
+
+    #[test]
+    fn test_accepted_values() {
+        assert_eq!(
+            accepted_values("player.default_volume").as_deref(),
+            Some("0 to 100")
+        );
+        assert_eq!(
+            accepted_values("app.color_scheme").as_deref(),
+            Some("one of auto, light, dark")
+        );
+        assert_eq!(
+            accepted_values("library.max_scan_depth").as_deref(),
+            Some("at least 0")
+        );
+        assert_eq!(accepted_values("app.device_name"), None);
+        assert_eq!(accepted_values("player.no_such_field"), None);
+        assert_eq!(accepted_values("player.device_profiles[0]"), None);
+    }
+
+    #[test]
+    fn test_ranges_and_options() {
+        assert_eq!(range("player.default_speed"), Some((0.5, 2.0)));
+        assert_eq!(range("library.max_scan_depth"), None);
+        assert_eq!(range("app.color_scheme"), None);
+        assert_eq!(
+            options("app.color_scheme"),
+            Some(vec![
+                "auto".to_string(),
+                "light".to_string(),
+                "dark".to_string()
+            ])
+        );
+        assert_eq!(options("player.default_volume"), None);
+    }
+
     #[test]
     fn test_generate_example_config() {
         let config = generate_example_config();
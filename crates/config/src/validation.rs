@@ -24,6 +24,12 @@ pub trait ConfigSection: Default {
 
     /// Returns the section name for error reporting
     fn section_name(&self) -> &'static str;
+
+    /// Describes the section's table as a JSON Schema object
+    ///
+    /// Bounds given as `minimum`, `maximum` or `enum` are also what
+    /// [`schema::accepted_values`](crate::schema::accepted_values) reports.
+    fn json_schema() -> serde_json::Value;
 }
 
 /// Common validators for config values
@@ -3,11 +3,19 @@
 //! This module provides optional hot-reload functionality for long-running processes.
 //! When enabled, config changes are automatically detected and reloaded.
 
+use crate::migration::Migrations;
 use crate::{Config, ConfigError, ConfigResult};
-use std::path::PathBuf;
-use std::sync::{Arc, RwLock};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How long the config file must stay the same before a change is sent
+pub(crate) const SETTLE_TIME: Duration = Duration::from_millis(300);
+
+/// How often subscriptions look at the config file
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 /// Configuration watcher that detects file changes
 ///
@@ -84,11 +92,7 @@ impl ConfigWatcher {
                 source: e,
             })?;
 
-        let new_config: Config =
-            toml::from_str(&contents).map_err(|e| ConfigError::ParseError {
-                path: self.config_path.clone(),
-                source: e,
-            })?;
+        let (new_config, _) = Migrations::default().read(&self.config_path, &contents)?;
 
         // Validate before applying
         if let Err(errors) = new_config.validate() {
@@ -143,6 +147,87 @@ impl ConfigWatcher {
     }
 }
 
+/// Sends the config at `path` to `tx` each time the file changes, until
+/// `tx`'s receiver is dropped or the returned handle is
+///
+/// A change is sent once the file has stayed the same for [`SETTLE_TIME`],
+/// so an editor saving in several steps sends one. Contents equal to
+/// `own_write`, what the manager itself last wrote, are not sent. Valid
+/// configs go through `layer` first; an edit that cannot be used is sent as
+/// the error, with only the first validation error.
+pub(crate) fn subscribe<L>(
+    path: PathBuf,
+    own_write: Arc<Mutex<Option<String>>>,
+    layer: L,
+    tx: Sender<ConfigResult<Config>>,
+) -> WatchHandle
+where
+    L: Fn(Config) -> Config + Send + 'static,
+{
+    let (stop_tx, stop_rx) = std::sync::mpsc::channel();
+    let mut last_seen = std::fs::read_to_string(&path).ok();
+
+    let handle = thread::spawn(move || {
+        // Contents that differ from the last seen, and since when
+        let mut pending: Option<(String, Instant)> = None;
+        // Sleeps between looks, and stops when asked or the manager is gone
+        while let Err(RecvTimeoutError::Timeout) = stop_rx.recv_timeout(POLL_INTERVAL) {
+            let Ok(contents) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            if last_seen.as_ref() == Some(&contents) {
+                pending = None;
+                continue;
+            }
+            match &pending {
+                Some((waiting, since)) if *waiting == contents => {
+                    if since.elapsed() < SETTLE_TIME {
+                        continue;
+                    }
+                }
+                _ => {
+                    pending = Some((contents, Instant::now()));
+                    continue;
+                }
+            }
+
+            pending = None;
+            let own = own_write
+                .lock()
+                .is_ok_and(|own| own.as_ref() == Some(&contents));
+            let update = read_config(&path, &contents).map(&layer);
+            last_seen = Some(contents);
+            if own {
+                continue;
+            }
+            match &update {
+                Ok(_) => log::info!("Config file changed, reloading"),
+                Err(e) => log::warn!("Ignoring config file change: {}", e),
+            }
+            if tx.send(update).is_err() {
+                break;
+            }
+        }
+    });
+
+    WatchHandle {
+        stop_tx,
+        thread_handle: Some(handle),
+    }
+}
+
+/// Reads config file `contents` for a reload, refusing configs that fail
+/// validation
+fn read_config(path: &Path, contents: &str) -> ConfigResult<Config> {
+    let (config, _) = Migrations::default().read(path, contents)?;
+    match config.validate() {
+        Err(errors) if !errors.is_empty() => {
+            Err(ConfigError::ValidationError(errors[0].to_string()))
+        }
+        _ => Ok(config),
+    }
+}
+
 /// Handle for a running config watcher
 ///
 /// Dropping this handle will stop the watcher thread.
@@ -0,0 +1,473 @@
+// FILE: crates/content-sources/src/aggregated.rs
+
+use crate::{
+    ContentSource, ImportPlan, Provenance, SearchQuery, SearchResult, SourceError, SourceMetadata,
+    SourceResult,
+};
+use std::collections::HashMap;
+use std::sync::{mpsc, Arc};
+use std::time::{Duration as StdDuration, Instant};
+use storystream_resilience::with_timeout;
+
+/// Searches several sources at once and merges what they find
+///
+/// Each source runs on its own thread, since sources use blocking HTTP. A
+/// source that fails or overruns its timeout is reported in
+/// [`AggregatedSearch::failures`] without discarding the others' results.
+pub struct AggregatedSource {
+    sources: Vec<SourceEntry>,
+    timeout: StdDuration,
+}
+
+struct SourceEntry {
+    source: Arc<dyn ContentSource>,
+    /// Overrides the aggregate's timeout for this source
+    timeout: Option<StdDuration>,
+}
+
+/// Merged results of a search across sources
+#[derive(Debug, Clone)]
+pub struct AggregatedSearch {
+    /// Results ranked by relevance and capped by the query limit
+    pub results: Vec<SearchResult>,
+    /// Sources that could not be searched
+    pub failures: Vec<SourceFailure>,
+}
+
+impl AggregatedSearch {
+    /// Returns true if some sources failed
+    pub fn is_partial(&self) -> bool {
+        !self.failures.is_empty()
+    }
+}
+
+/// A source that failed or timed out during a search
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceFailure {
+    pub source: String,
+    pub error: SourceError,
+}
+
+impl AggregatedSource {
+    /// Slightly under the sources' own HTTP timeouts
+    const DEFAULT_TIMEOUT: StdDuration = StdDuration::from_secs(20);
+
+    /// Creates an aggregate with no sources
+    pub fn new() -> Self {
+        Self {
+            sources: Vec::new(),
+            timeout: Self::DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Adds a source searched under the aggregate's timeout
+    pub fn with_source(mut self, source: impl ContentSource + 'static) -> Self {
+        self.sources.push(SourceEntry {
+            source: Arc::new(source),
+            timeout: None,
+        });
+        self
+    }
+
+    /// Adds an already boxed source
+    pub fn with_boxed_source(mut self, source: Box<dyn ContentSource>) -> Self {
+        self.sources.push(SourceEntry {
+            source: Arc::from(source),
+            timeout: None,
+        });
+        self
+    }
+
+    /// Adds a source with a timeout of its own
+    pub fn with_source_timeout(
+        mut self,
+        source: impl ContentSource + 'static,
+        timeout: StdDuration,
+    ) -> Self {
+        self.sources.push(SourceEntry {
+            source: Arc::new(source),
+            timeout: Some(timeout),
+        });
+        self
+    }
+
+    /// Sets the timeout for sources without one of their own
+    pub fn with_timeout(mut self, timeout: StdDuration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Number of sources searched
+    pub fn source_count(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// Searches every source, keeping whatever results arrive in time
+    pub fn search_all(&self, query: &SearchQuery) -> AggregatedSearch {
+        let started = Instant::now();
+        let (tx, rx) = mpsc::channel();
+
+        let mut deadlines = Vec::with_capacity(self.sources.len());
+        for (index, entry) in self.sources.iter().enumerate() {
+            let timeout = entry.timeout.unwrap_or(self.timeout);
+            deadlines.push(Some(started + timeout));
+
+            let source = Arc::clone(&entry.source);
+            let query = query.clone();
+            let tx = tx.clone();
+            // Detached, so a hung source cannot hold up the search
+            std::thread::spawn(move || {
+                let outcome = with_timeout(timeout, || source.search(&query))
+                    .map_err(|e| SourceError::Unavailable(e.to_string()))
+                    .and_then(|found| found);
+                let _ = tx.send((index, outcome));
+            });
+        }
+        drop(tx);
+
+        let mut outcomes: Vec<Option<SourceResult<Vec<SearchResult>>>> =
+            self.sources.iter().map(|_| None).collect();
+        while let Some(deadline) = deadlines.iter().flatten().min().copied() {
+            match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok((index, outcome)) => {
+                    if deadlines[index].take().is_some() {
+                        outcomes[index] = Some(outcome);
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    let now = Instant::now();
+                    for (index, deadline) in deadlines.iter_mut().enumerate() {
+                        if deadline.is_some_and(|d| d <= now) {
+                            *deadline = None;
+                            let timeout = self.sources[index].timeout.unwrap_or(self.timeout);
+                            outcomes[index] = Some(Err(SourceError::Unavailable(format!(
+                                "No response within {:?}",
+                                timeout
+                            ))));
+                        }
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        let mut found = Vec::new();
+        let mut failures = Vec::new();
+        for (entry, outcome) in self.sources.iter().zip(outcomes) {
+            // A source that panicked never reports back
+            let outcome = outcome.unwrap_or_else(|| {
+                Err(SourceError::Unavailable(
+                    "Search stopped without a result".to_string(),
+                ))
+            });
+            match outcome {
+                Ok(results) => found.extend(results),
+                Err(error) => failures.push(SourceFailure {
+                    source: entry.source.metadata().name,
+                    error,
+                }),
+            }
+        }
+
+        let mut results = merge(found);
+        rank(&mut results, query);
+        results.truncate(query.limit);
+
+        AggregatedSearch { results, failures }
+    }
+}
+
+impl Default for AggregatedSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ContentSource for AggregatedSource {
+    /// Fails only when no source could be searched
+    fn search(&self, query: &SearchQuery) -> SourceResult<Vec<SearchResult>> {
+        if self.sources.is_empty() {
+            return Err(SourceError::Unavailable(
+                "No sources configured".to_string(),
+            ));
+        }
+
+        let search = self.search_all(query);
+        if search.results.is_empty() && search.failures.len() == self.sources.len() {
+            return Err(search.failures[0].error.clone());
+        }
+        Ok(search.results)
+    }
+
+    fn metadata(&self) -> SourceMetadata {
+        SourceMetadata {
+            name: "All Sources".to_string(),
+            description: format!("Combined search across {} sources", self.sources.len()),
+            base_url: String::new(),
+            requires_auth: false,
+        }
+    }
+
+    fn is_available(&self) -> bool {
+        self.sources.iter().any(|entry| entry.source.is_available())
+    }
+
+    /// Imports from the source a result is primarily attributed to
+    fn plan_import(&self, result: &SearchResult) -> SourceResult<ImportPlan> {
+        self.sources
+            .iter()
+            .find(|entry| entry.source.metadata().name == result.source)
+            .ok_or_else(|| SourceError::Unavailable(format!("Unknown source '{}'", result.source)))?
+            .source
+            .plan_import(result)
+    }
+}
+
+/// Lowercased alphanumeric words, so punctuation and spacing differences
+/// between catalogues do not matter
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Collapses results with the same title and author into one entry that
+/// records every source listing it; the first source to list a book wins
+fn merge(found: Vec<SearchResult>) -> Vec<SearchResult> {
+    let mut merged: Vec<SearchResult> = Vec::new();
+    let mut seen: HashMap<(Vec<String>, Vec<String>), usize> = HashMap::new();
+
+    for mut result in found {
+        let key = (words(&result.title), words(&result.author));
+        match seen.get(&key) {
+            Some(&index) => {
+                let kept = &mut merged[index];
+                kept.provenance.push(Provenance::of(&result));
+                kept.description = kept.description.take().or(result.description);
+                kept.duration = kept.duration.or(result.duration);
+                kept.language = kept.language.take().or(result.language);
+            }
+            None => {
+                if result.provenance.is_empty() {
+                    result.provenance.push(Provenance::of(&result));
+                }
+                seen.insert(key, merged.len());
+                merged.push(result);
+            }
+        }
+    }
+    merged
+}
+
+/// Orders results by how well they match the query, keeping source order
+/// among equals
+fn rank(results: &mut [SearchResult], query: &SearchQuery) {
+    let terms = words(&query.text);
+    let wanted_author = query.author.as_deref().map(words);
+
+    let score = |result: &SearchResult| {
+        let title = words(&result.title);
+        let author = words(&result.author);
+        let description = result.description.as_deref().map(words).unwrap_or_default();
+
+        let mut score = 0;
+        for term in &terms {
+            if title.contains(term) {
+                score += 3;
+            } else if author.contains(term) {
+                score += 2;
+            } else if description.contains(term) {
+                score += 1;
+            }
+        }
+        if !terms.is_empty() && title == terms {
+            score += 5;
+        }
+        if let Some(wanted) = &wanted_author {
+            if wanted.iter().all(|word| author.contains(word)) {
+                score += 3;
+            }
+        }
+        // Books several catalogues agree on are likelier to be wanted
+        score + result.provenance.len().saturating_sub(1)
+    };
+
+    results.sort_by_cached_key(|result| std::cmp::Reverse(score(result)));
+}
+
+#[cfg(test)]
+mod aggregated_tests {
+    use super::*;
+
+    /// Source answering every search with fixed results after a delay
+    struct FixedSource {
+        name: &'static str,
+        outcome: SourceResult<Vec<SearchResult>>,
+        delay: StdDuration,
+    }
+
+    impl FixedSource {
+        fn new(name: &'static str, books: &[(&str, &str)]) -> Self {
+            let results = books
+                .iter()
+                .enumerate()
+                .map(|(index, (title, author))| SearchResult {
+                    id: format!("{}-{}", name, index),
+                    title: title.to_string(),
+                    author: author.to_string(),
+                    description: None,
+                    duration: None,
+                    language: None,
+                    url: format!("https://{}.example.com/{}", name, index),
+                    source: name.to_string(),
+                    provenance: Vec::new(),
+                })
+                .collect();
+            Self {
+                name,
+                outcome: Ok(results),
+                delay: StdDuration::ZERO,
+            }
+        }
+
+        fn failing(name: &'static str, error: SourceError) -> Self {
+            Self {
+                name,
+                outcome: Err(error),
+                delay: StdDuration::ZERO,
+            }
+        }
+
+        fn with_delay(mut self, delay: StdDuration) -> Self {
+            self.delay = delay;
+            self
+        }
+    }
+
+    impl ContentSource for FixedSource {
+        fn search(&self, _query: &SearchQuery) -> SourceResult<Vec<SearchResult>> {
+            std::thread::sleep(self.delay);
+            self.outcome.clone()
+        }
+
+        fn metadata(&self) -> SourceMetadata {
+            SourceMetadata {
+                name: self.name.to_string(),
+                description: String::new(),
+                base_url: String::new(),
+                requires_auth: false,
+            }
+        }
+
+        fn is_available(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_duplicates_merge_with_provenance() {
+        let aggregate = AggregatedSource::new()
+            .with_source(FixedSource::new(
+                "LibriVox",
+                &[("Emma", "Jane Austen"), ("Persuasion", "Jane Austen")],
+            ))
+            .with_source(FixedSource::new(
+                "Internet Archive",
+                &[("EMMA", "Austen, Jane"), ("Emma!", "jane austen")],
+            ));
+
+        let search = aggregate.search_all(&SearchQuery::new("emma".to_string()));
+        assert!(!search.is_partial());
+
+        // "Austen, Jane" reads differently, so only the exact match merges
+        let titles: Vec<&str> = search.results.iter().map(|r| r.title.as_str()).collect();
+        assert_eq!(titles, ["Emma", "EMMA", "Persuasion"]);
+
+        let emma = &search.results[0];
+        assert_eq!(emma.source, "LibriVox");
+        let sources: Vec<&str> = emma.provenance.iter().map(|p| p.source.as_str()).collect();
+        assert_eq!(sources, ["LibriVox", "Internet Archive"]);
+        assert_eq!(emma.provenance[1].id, "Internet Archive-1");
+        assert_eq!(search.results[2].provenance.len(), 1);
+    }
+
+    #[test]
+    fn test_failed_and_slow_sources_are_partial_failures() {
+        let aggregate = AggregatedSource::new()
+            .with_source_timeout(
+                FixedSource::new("LibriVox", &[("Emma", "Jane Austen")])
+                    .with_delay(StdDuration::from_secs(5)),
+                StdDuration::from_millis(100),
+            )
+            .with_source(FixedSource::failing("Broken", SourceError::RateLimited))
+            .with_source(FixedSource::new(
+                "Internet Archive",
+                &[("Emma", "Jane Austen")],
+            ));
+
+        let started = Instant::now();
+        let search = aggregate.search_all(&SearchQuery::new("emma".to_string()));
+        assert!(started.elapsed() < StdDuration::from_secs(2));
+
+        assert_eq!(search.results.len(), 1);
+        assert_eq!(search.results[0].source, "Internet Archive");
+        assert_eq!(search.failures.len(), 2);
+        assert_eq!(search.failures[0].source, "LibriVox");
+        assert!(matches!(
+            search.failures[0].error,
+            SourceError::Unavailable(_)
+        ));
+        assert_eq!(
+            search.failures[1],
+            SourceFailure {
+                source: "Broken".to_string(),
+                error: SourceError::RateLimited,
+            }
+        );
+        assert!(aggregate
+            .search(&SearchQuery::new("emma".to_string()))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_search_fails_only_when_every_source_does() {
+        let aggregate = AggregatedSource::new()
+            .with_source(FixedSource::failing("A", SourceError::NotFound))
+            .with_source(FixedSource::failing("B", SourceError::RateLimited));
+        assert!(matches!(
+            aggregate.search(&SearchQuery::new("emma".to_string())),
+            Err(SourceError::NotFound)
+        ));
+
+        assert!(AggregatedSource::new()
+            .search(&SearchQuery::new("emma".to_string()))
+            .is_err());
+    }
+
+    #[test]
+    fn test_results_ranked_and_capped() {
+        let aggregate = AggregatedSource::new()
+            .with_source(FixedSource::new(
+                "LibriVox",
+                &[
+                    ("Collected Stories", "Various"),
+                    ("The Time Machine and Other Stories", "H. G. Wells"),
+                    ("The Time Machine", "H. G. Wells"),
+                ],
+            ))
+            .with_source(FixedSource::new(
+                "Internet Archive",
+                &[("The Time Machine and Other Stories", "H. G. Wells")],
+            ));
+
+        let query = SearchQuery::new("The Time Machine".to_string()).with_limit(2);
+        let results = aggregate.search(&query).unwrap();
+
+        let titles: Vec<&str> = results.iter().map(|r| r.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            ["The Time Machine", "The Time Machine and Other Stories"]
+        );
+    }
+}
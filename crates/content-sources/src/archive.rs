@@ -3,20 +3,111 @@
 
 use crate::{ContentSource, SearchQuery, SearchResult, SourceError, SourceMetadata, SourceResult};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration as StdDuration;
+use storystream_media_formats::AudioFormat;
+use storystream_resilience::RateLimiter;
 
 /// Internet Archive content source
 pub struct ArchiveSource {
     base_url: String,
+    client: Option<reqwest::blocking::Client>,
+    limiter: RateLimiter,
 }
 
 impl ArchiveSource {
     const API_BASE: &'static str = "https://archive.org/advancedsearch.php";
 
+    /// Item metadata, including the list of files
+    const METADATA_BASE: &'static str = "https://archive.org/metadata";
+
+    /// Files of an item, served with range support
+    const DOWNLOAD_BASE: &'static str = "https://archive.org/download";
+
+    /// The Archive asks API clients to keep to a modest request rate
+    const REQUESTS_PER_SECOND: usize = 1;
+
     pub fn new() -> Self {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(StdDuration::from_secs(30))
+            .user_agent(concat!(
+                env!("CARGO_PKG_NAME"),
+                "/",
+                env!("CARGO_PKG_VERSION"),
+            ))
+            .build()
+            .ok();
+
         Self {
             base_url: Self::API_BASE.to_string(),
+            client,
+            limiter: RateLimiter::new(Self::REQUESTS_PER_SECOND, StdDuration::from_secs(1)),
         }
     }
+
+    /// Sends a GET request once the rate limiter allows it
+    fn send(&self, url: &str) -> SourceResult<reqwest::blocking::Response> {
+        let client = self
+            .client
+            .as_ref()
+            .ok_or_else(|| SourceError::NetworkError("HTTP client not available".to_string()))?;
+
+        while self.limiter.try_acquire().is_err() {
+            std::thread::sleep(StdDuration::from_millis(100));
+        }
+
+        let response = client
+            .get(url)
+            .send()
+            .map_err(|e| SourceError::NetworkError(format!("Request failed: {}", e)))?;
+
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(SourceError::RateLimited);
+        }
+
+        Ok(response)
+    }
+
+    /// Lists the audio files of an item, in playback order
+    ///
+    /// Where the Archive derived other formats from an audio file, only the
+    /// uploaded original is listed. Dark and removed items give
+    /// [`SourceError::NotFound`].
+    pub fn get_item_files(&self, identifier: &str) -> SourceResult<Vec<ArchiveFile>> {
+        if identifier.trim().is_empty() {
+            return Err(SourceError::InvalidQuery("Empty identifier".to_string()));
+        }
+
+        let url = format!("{}/{}", Self::METADATA_BASE, encode_path(identifier));
+        let response = self.send(&url)?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(SourceError::NotFound);
+        }
+        if !response.status().is_success() {
+            return Err(SourceError::NetworkError(format!(
+                "HTTP {}",
+                response.status().as_u16()
+            )));
+        }
+
+        let metadata: ArchiveMetadata = response
+            .json()
+            .map_err(|e| SourceError::ParseError(format!("JSON parse error: {}", e)))?;
+
+        audio_files(identifier, metadata)
+    }
+
+    /// Returns the URL that streams or downloads one file of an item
+    pub fn get_stream_url(&self, identifier: &str, filename: &str) -> String {
+        format!(
+            "{}/{}/{}",
+            Self::DOWNLOAD_BASE,
+            encode_path(identifier),
+            encode_path(filename)
+        )
+    }
 }
 
 impl Default for ArchiveSource {
@@ -75,6 +166,158 @@ impl ArchiveItem {
     }
 }
 
+/// A playable file of an Internet Archive item
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchiveFile {
+    /// Path of the file within the item
+    pub name: String,
+    /// Download and streaming URL
+    pub url: String,
+    pub format: AudioFormat,
+    /// Size in bytes, when listed
+    pub size: Option<u64>,
+    /// Running time, when the Archive measured it
+    pub duration: Option<StdDuration>,
+    /// Track title from the file's tags, when listed
+    pub title: Option<String>,
+    /// Whether this is the uploaded file rather than one the Archive derived
+    pub original: bool,
+}
+
+/// Response of the metadata API; a dark or unknown item has no files
+#[derive(Debug, Deserialize)]
+struct ArchiveMetadata {
+    #[serde(default)]
+    is_dark: bool,
+    #[serde(default)]
+    files: Vec<ArchiveFileEntry>,
+}
+
+/// One file in the metadata API's listing; numbers arrive as strings
+#[derive(Debug, Deserialize)]
+struct ArchiveFileEntry {
+    name: String,
+    #[serde(default)]
+    source: String,
+    /// For derivatives, the file they were made from
+    #[serde(default)]
+    original: Option<String>,
+    #[serde(default)]
+    size: Option<String>,
+    #[serde(default)]
+    length: Option<String>,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    track: Option<String>,
+}
+
+/// Picks the playable files out of an item's listing
+///
+/// Keeps the original of every audio file the Archive also derived, and of
+/// the derivatives of anything else only the largest, so an item carrying
+/// each chapter in several bit rates lists every chapter once.
+fn audio_files(identifier: &str, metadata: ArchiveMetadata) -> SourceResult<Vec<ArchiveFile>> {
+    if metadata.is_dark || metadata.files.is_empty() {
+        return Err(SourceError::NotFound);
+    }
+
+    let mut originals = Vec::new();
+    let mut derived: HashMap<String, ArchiveFileEntry> = HashMap::new();
+    for entry in metadata.files {
+        if AudioFormat::from_path(Path::new(&entry.name)).is_none() {
+            continue;
+        }
+        match entry
+            .original
+            .clone()
+            .filter(|_| entry.source == "derivative")
+        {
+            Some(source) => {
+                let larger = derived
+                    .get(&source)
+                    .is_none_or(|kept| parse_size(&entry) > parse_size(kept));
+                if larger {
+                    derived.insert(source, entry);
+                }
+            }
+            None => originals.push(entry),
+        }
+    }
+    derived.retain(|source, _| !originals.iter().any(|o| &o.name == source));
+
+    let mut entries: Vec<(bool, ArchiveFileEntry)> = originals
+        .into_iter()
+        .map(|entry| (true, entry))
+        .chain(derived.into_values().map(|entry| (false, entry)))
+        .collect();
+    entries.sort_by(|(_, a), (_, b)| {
+        track_number(a)
+            .cmp(&track_number(b))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    let files = entries
+        .into_iter()
+        .filter_map(|(original, entry)| {
+            let format = AudioFormat::from_path(Path::new(&entry.name))?;
+            Some(ArchiveFile {
+                url: format!(
+                    "{}/{}/{}",
+                    ArchiveSource::DOWNLOAD_BASE,
+                    encode_path(identifier),
+                    encode_path(&entry.name)
+                ),
+                format,
+                size: parse_size(&entry),
+                duration: entry.length.as_deref().and_then(parse_length),
+                title: entry.title.filter(|t| !t.trim().is_empty()),
+                original,
+                name: entry.name,
+            })
+        })
+        .collect();
+    Ok(files)
+}
+
+fn parse_size(entry: &ArchiveFileEntry) -> Option<u64> {
+    entry.size.as_deref().and_then(|s| s.trim().parse().ok())
+}
+
+/// Track number from tags such as "3" or "3/12"; untracked files sort last
+fn track_number(entry: &ArchiveFileEntry) -> u32 {
+    entry
+        .track
+        .as_deref()
+        .and_then(|t| t.split('/').next())
+        .and_then(|t| t.trim().parse().ok())
+        .unwrap_or(u32::MAX)
+}
+
+/// Parses a listed length, given in seconds ("1834.52") or as "[HH:]MM:SS"
+fn parse_length(length: &str) -> Option<StdDuration> {
+    let mut seconds = 0.0;
+    for part in length.trim().split(':') {
+        let value: f64 = part.trim().parse().ok()?;
+        seconds = seconds * 60.0 + value;
+    }
+    StdDuration::try_from_secs_f64(seconds).ok()
+}
+
+/// Percent-encodes a path for a URL, keeping its '/' separators
+fn encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
 #[cfg(test)]
 mod archive_tests {
     use super::*;
@@ -106,6 +349,91 @@ mod archive_tests {
         assert!(item.is_audio());
     }
 
+    /// Listing shaped like a LibriVox upload: 128k MP3 originals with 64k
+    /// MP3 and Ogg derivatives, plus the usual non-audio files
+    const LISTING: &str = r#"{
+        "created": 1700000000,
+        "files": [
+            {"name": "emma_01_austen_128kb.mp3", "source": "original", "format": "128Kbps MP3",
+             "size": "15728640", "length": "982.71", "title": "Chapter 1", "track": "1"},
+            {"name": "emma_01_austen_64kb.mp3", "source": "derivative", "format": "64Kbps MP3",
+             "original": "emma_01_austen_128kb.mp3", "size": "7864320", "length": "982.71"},
+            {"name": "emma_01_austen.ogg", "source": "derivative", "format": "Ogg Vorbis",
+             "original": "emma_01_austen_128kb.mp3", "size": "9000000", "length": "982.7"},
+            {"name": "emma_02_austen_128kb.mp3", "source": "original", "format": "128Kbps MP3",
+             "size": "14680064", "length": "15:17", "title": "Chapter 2", "track": "2/3"},
+            {"name": "extras/emma 03.flac", "source": "original", "format": "Flac",
+             "size": "104857600", "length": "01:00:00", "track": "3"},
+            {"name": "emma_session.shn", "source": "original", "format": "Shorten"},
+            {"name": "emma_session_64kb.mp3", "source": "derivative",
+             "original": "emma_session.shn", "size": "1000"},
+            {"name": "emma_session_vbr.mp3", "source": "derivative",
+             "original": "emma_session.shn", "size": "2000", "length": "bad"},
+            {"name": "emma_meta.xml", "source": "original", "format": "Metadata"},
+            {"name": "__ia_thumb.jpg", "source": "original", "format": "Item Tile"}
+        ],
+        "metadata": {"identifier": "emma_librivox", "mediatype": "audio"}
+    }"#;
+
+    #[test]
+    fn test_item_files_prefer_originals() {
+        let metadata: ArchiveMetadata = serde_json::from_str(LISTING).unwrap();
+        let files = audio_files("emma_librivox", metadata).unwrap();
+
+        let names: Vec<&str> = files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "emma_01_austen_128kb.mp3",
+                "emma_02_austen_128kb.mp3",
+                "extras/emma 03.flac",
+                "emma_session_vbr.mp3"
+            ]
+        );
+
+        assert!(files[0].original);
+        assert_eq!(files[0].format, AudioFormat::Mp3);
+        assert_eq!(files[0].size, Some(15_728_640));
+        assert_eq!(files[0].duration, Some(StdDuration::from_millis(982_710)));
+        assert_eq!(files[0].title.as_deref(), Some("Chapter 1"));
+        assert_eq!(files[1].duration, Some(StdDuration::from_secs(917)));
+        assert_eq!(files[2].format, AudioFormat::Flac);
+        assert_eq!(
+            files[2].url,
+            "https://archive.org/download/emma_librivox/extras/emma%2003.flac"
+        );
+        assert_eq!(files[2].duration, Some(StdDuration::from_secs(3600)));
+
+        // Derived from a format that cannot be played; the larger one stays
+        assert!(!files[3].original);
+        assert_eq!(files[3].duration, None);
+    }
+
+    #[test]
+    fn test_dark_or_empty_items_are_not_found() {
+        for json in [r#"{}"#, r#"{"is_dark": true, "files": []}"#] {
+            let metadata: ArchiveMetadata = serde_json::from_str(json).unwrap();
+            assert_eq!(audio_files("gone", metadata), Err(SourceError::NotFound));
+        }
+
+        let only_text = r#"{"files": [{"name": "book.pdf", "source": "original"}]}"#;
+        let metadata: ArchiveMetadata = serde_json::from_str(only_text).unwrap();
+        assert_eq!(audio_files("text", metadata), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn test_stream_url_encodes_names() {
+        let source = ArchiveSource::new();
+        assert_eq!(
+            source.get_stream_url("some_item", "Disc 1/01 - Café.mp3"),
+            "https://archive.org/download/some_item/Disc%201/01%20-%20Caf%C3%A9.mp3"
+        );
+        assert!(matches!(
+            source.get_item_files("  "),
+            Err(SourceError::InvalidQuery(_))
+        ));
+    }
+
     #[test]
     fn test_archive_item_audio_detection() {
         let mut item = ArchiveItem::new("test".to_string(), "Test".to_string());
@@ -1,15 +1,20 @@
 // FILE: crates/content-sources/src/lib.rs
 
+mod aggregated;
 mod archive;
 mod librivox;
 mod local;
 mod traits;
 
-pub use archive::{ArchiveItem, ArchiveSource};
+pub use aggregated::{AggregatedSearch, AggregatedSource, SourceFailure};
+pub use archive::{ArchiveFile, ArchiveItem, ArchiveSource};
 pub use librivox::{LibriVoxBook, LibriVoxSource};
 pub use local::LocalSource;
 use std::fmt;
-pub use traits::{ContentSource, SearchQuery, SearchResult, SourceMetadata};
+pub use traits::{
+    language_matches, ContentSource, ImportPlan, PlannedFile, Provenance, SearchQuery,
+    SearchResult, SourceMetadata,
+};
 
 /// Result type for content source operations
 pub type SourceResult<T> = Result<T, SourceError>;
@@ -1,18 +1,26 @@
 // FILE: crates/content-sources/src/librivox.rs
 
-use crate::{ContentSource, SearchQuery, SearchResult, SourceError, SourceMetadata, SourceResult};
+use crate::{
+    ContentSource, ImportPlan, PlannedFile, SearchQuery, SearchResult, SourceError, SourceMetadata,
+    SourceResult,
+};
 use serde::{Deserialize, Serialize};
 use std::time::Duration as StdDuration;
+use storystream_resilience::RateLimiter;
 
 /// LibriVox content source for free public domain audiobooks
 pub struct LibriVoxSource {
     base_url: String,
     client: Option<reqwest::blocking::Client>,
+    limiter: RateLimiter,
 }
 
 impl LibriVoxSource {
     const API_BASE: &'static str = "https://librivox.org/api/feed/audiobooks";
 
+    /// Keeps bulk searches and fetches from hammering the public API
+    const REQUESTS_PER_SECOND: usize = 1;
+
     /// Create a new LibriVox source with HTTP client
     pub fn new() -> Self {
         let client = reqwest::blocking::Client::builder()
@@ -28,20 +36,39 @@ impl LibriVoxSource {
         Self {
             base_url: Self::API_BASE.to_string(),
             client,
+            limiter: RateLimiter::new(Self::REQUESTS_PER_SECOND, StdDuration::from_secs(1)),
         }
     }
 
+    /// Sends a GET request once the rate limiter allows it
+    fn send(&self, url: &str) -> SourceResult<reqwest::blocking::Response> {
+        let client = self
+            .client
+            .as_ref()
+            .ok_or_else(|| SourceError::NetworkError("HTTP client not available".to_string()))?;
+
+        while self.limiter.try_acquire().is_err() {
+            std::thread::sleep(StdDuration::from_millis(100));
+        }
+
+        let response = client
+            .get(url)
+            .send()
+            .map_err(|e| SourceError::NetworkError(format!("Request failed: {}", e)))?;
+
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(SourceError::RateLimited);
+        }
+
+        Ok(response)
+    }
+
     /// Search LibriVox catalog by title or author
     pub fn search_books(&self, query: &str, limit: usize) -> SourceResult<Vec<LibriVoxBook>> {
         if query.is_empty() {
             return Err(SourceError::InvalidQuery("Empty query".to_string()));
         }
 
-        let client = self
-            .client
-            .as_ref()
-            .ok_or_else(|| SourceError::NetworkError("HTTP client not available".to_string()))?;
-
         // Build search URL with parameters
         let url = format!(
             "{}?title=^{}^&format=json&limit={}",
@@ -51,10 +78,7 @@ impl LibriVoxSource {
         );
 
         // Make HTTP request
-        let response = client
-            .get(&url)
-            .send()
-            .map_err(|e| SourceError::NetworkError(format!("Request failed: {}", e)))?;
+        let response = self.send(&url)?;
 
         if !response.status().is_success() {
             return Err(SourceError::NetworkError(format!(
@@ -74,17 +98,13 @@ impl LibriVoxSource {
 
     /// Get book details by ID
     pub fn get_book(&self, book_id: &str) -> SourceResult<LibriVoxBook> {
-        let client = self
-            .client
-            .as_ref()
-            .ok_or_else(|| SourceError::NetworkError("HTTP client not available".to_string()))?;
-
-        let url = format!("{}?id={}&format=json", self.base_url, book_id);
+        let url = format!(
+            "{}?id={}&extended=1&format=json",
+            self.base_url,
+            urlencoding::encode(book_id)
+        );
 
-        let response = client
-            .get(&url)
-            .send()
-            .map_err(|e| SourceError::NetworkError(format!("Request failed: {}", e)))?;
+        let response = self.send(&url)?;
 
         if !response.status().is_success() {
             return Err(SourceError::NotFound);
@@ -103,17 +123,9 @@ impl LibriVoxSource {
 
     /// Get latest releases from LibriVox
     pub fn latest_releases(&self, limit: usize) -> SourceResult<Vec<LibriVoxBook>> {
-        let client = self
-            .client
-            .as_ref()
-            .ok_or_else(|| SourceError::NetworkError("HTTP client not available".to_string()))?;
-
         let url = format!("{}?format=json&limit={}", self.base_url, limit);
 
-        let response = client
-            .get(&url)
-            .send()
-            .map_err(|e| SourceError::NetworkError(format!("Request failed: {}", e)))?;
+        let response = self.send(&url)?;
 
         if !response.status().is_success() {
             return Err(SourceError::NetworkError(format!(
@@ -135,11 +147,6 @@ impl LibriVoxSource {
             return Err(SourceError::InvalidQuery("Empty author".to_string()));
         }
 
-        let client = self
-            .client
-            .as_ref()
-            .ok_or_else(|| SourceError::NetworkError("HTTP client not available".to_string()))?;
-
         let url = format!(
             "{}?author=^{}^&format=json&limit={}",
             self.base_url,
@@ -147,10 +154,7 @@ impl LibriVoxSource {
             limit
         );
 
-        let response = client
-            .get(&url)
-            .send()
-            .map_err(|e| SourceError::NetworkError(format!("Request failed: {}", e)))?;
+        let response = self.send(&url)?;
 
         if !response.status().is_success() {
             return Err(SourceError::NetworkError(format!(
@@ -203,6 +207,7 @@ impl ContentSource for LibriVoxSource {
         // Convert to SearchResult - match ACTUAL SearchResult structure
         let results = books
             .into_iter()
+            .filter(|book| query.matches_language(&book.language))
             .map(|book| SearchResult {
                 id: book.id.clone(),
                 title: book.title.clone(),
@@ -213,8 +218,10 @@ impl ContentSource for LibriVoxSource {
                     Some(book.description.clone())
                 },
                 duration: book.duration_seconds().map(StdDuration::from_secs),
+                language: Some(book.language.clone()),
                 url: book.url_librivox.clone(),
                 source: "LibriVox".to_string(),
+                provenance: Vec::new(),
             })
             .collect();
 
@@ -233,6 +240,18 @@ impl ContentSource for LibriVoxSource {
     fn is_available(&self) -> bool {
         self.client.is_some()
     }
+
+    fn plan_import(&self, result: &SearchResult) -> SourceResult<ImportPlan> {
+        let book = self.get_book(&result.id)?;
+        let plan = book.import_plan();
+        if plan.files.is_empty() {
+            return Err(SourceError::Unavailable(format!(
+                "'{}' has no downloadable sections",
+                book.title
+            )));
+        }
+        Ok(plan)
+    }
 }
 
 /// LibriVox API response structure
@@ -281,6 +300,30 @@ pub struct LibriVoxBook {
     /// Number of sections/chapters
     #[serde(default)]
     pub num_sections: String,
+
+    /// Per-section audio files (only returned by extended queries)
+    #[serde(default)]
+    pub sections: Vec<LibriVoxSection>,
+}
+
+/// A single recorded section (usually a chapter) of a LibriVox book
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibriVoxSection {
+    /// Position of the section within the book
+    #[serde(default)]
+    pub section_number: String,
+
+    /// Section title
+    #[serde(default)]
+    pub title: String,
+
+    /// Direct MP3 URL
+    #[serde(default)]
+    pub listen_url: String,
+
+    /// Duration in seconds
+    #[serde(default)]
+    pub playtime: String,
 }
 
 fn default_language() -> String {
@@ -301,6 +344,7 @@ impl LibriVoxBook {
             url_zip_file: String::new(),
             totaltime: String::new(),
             num_sections: String::new(),
+            sections: Vec::new(),
         }
     }
 
@@ -335,6 +379,38 @@ impl LibriVoxBook {
     pub fn chapter_count(&self) -> Option<usize> {
         self.num_sections.parse().ok()
     }
+
+    /// Builds a download plan with one file per section
+    pub fn import_plan(&self) -> ImportPlan {
+        let width = self.sections.len().to_string().len().max(2);
+        let files = self
+            .sections
+            .iter()
+            .filter(|section| !section.listen_url.is_empty())
+            .enumerate()
+            .map(|(index, section)| {
+                let number = section.section_number.parse::<usize>().unwrap_or(index + 1);
+                let name = if section.title.is_empty() {
+                    format!("Section {}", number)
+                } else {
+                    section.title.clone()
+                };
+                PlannedFile {
+                    url: section.listen_url.clone(),
+                    file_name: format!("{:0width$} {}.mp3", number, name, width = width),
+                    title: format!("{} - {}", self.title, name),
+                    duration: section.playtime.parse().ok().map(StdDuration::from_secs),
+                }
+            })
+            .collect();
+
+        ImportPlan {
+            title: self.title.clone(),
+            author: self.author.clone(),
+            source: "LibriVox".to_string(),
+            files,
+        }
+    }
 }
 
 // Helper module for URL encoding
@@ -451,6 +527,26 @@ mod tests {
         assert_eq!(book.chapter_count(), None);
     }
 
+    #[test]
+    fn test_import_plan_from_sections() {
+        let json = r#"{"books":[{"id":"47","title":"Dracula","author":"Bram Stoker",
+            "language":"English","sections":[
+              {"section_number":"1","title":"Jonathan Harker's Journal","listen_url":"https://example.org/dracula_01.mp3","playtime":"1800"},
+              {"section_number":"2","title":"","listen_url":"https://example.org/dracula_02.mp3","playtime":""},
+              {"section_number":"3","title":"Missing","listen_url":"","playtime":"60"}
+            ]}]}"#;
+        let response: LibriVoxApiResponse = serde_json::from_str(json).unwrap();
+        let plan = response.books[0].import_plan();
+
+        assert_eq!(plan.source, "LibriVox");
+        assert_eq!(plan.files.len(), 2);
+        assert_eq!(plan.files[0].file_name, "01 Jonathan Harker's Journal.mp3");
+        assert_eq!(plan.files[0].title, "Dracula - Jonathan Harker's Journal");
+        assert_eq!(plan.files[0].duration, Some(StdDuration::from_secs(1800)));
+        assert_eq!(plan.files[1].file_name, "02 Section 2.mp3");
+        assert_eq!(plan.files[1].duration, None);
+    }
+
     #[test]
     fn test_url_encoding() {
         let encoded = urlencoding::encode("Pride and Prejudice");
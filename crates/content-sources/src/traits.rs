@@ -1,7 +1,8 @@
 // FILE: src/traits.rs
 // ============================================================================
 
-use crate::SourceResult;
+use crate::{SourceError, SourceResult};
+use serde::{Deserialize, Serialize};
 
 /// Content source trait
 pub trait ContentSource: Send + Sync {
@@ -13,6 +14,15 @@ pub trait ContentSource: Send + Sync {
 
     /// Check if source is available
     fn is_available(&self) -> bool;
+
+    /// Resolve a search result into the files that make up the audiobook
+    fn plan_import(&self, result: &SearchResult) -> SourceResult<ImportPlan> {
+        Err(SourceError::Unavailable(format!(
+            "{} does not support downloading '{}'",
+            self.metadata().name,
+            result.title
+        )))
+    }
 }
 
 /// Search query
@@ -21,6 +31,8 @@ pub struct SearchQuery {
     pub text: String,
     pub author: Option<String>,
     pub title: Option<String>,
+    /// Language code or name to restrict results to (e.g. "en" or "English")
+    pub language: Option<String>,
     pub limit: usize,
 }
 
@@ -30,6 +42,7 @@ impl SearchQuery {
             text,
             author: None,
             title: None,
+            language: None,
             limit: 20,
         }
     }
@@ -39,22 +52,113 @@ impl SearchQuery {
         self
     }
 
+    pub fn with_language(mut self, language: String) -> Self {
+        self.language = Some(language);
+        self
+    }
+
     pub fn with_limit(mut self, limit: usize) -> Self {
         self.limit = limit;
         self
     }
+
+    /// Returns true if `language` satisfies this query's language filter
+    pub fn matches_language(&self, language: &str) -> bool {
+        match &self.language {
+            Some(wanted) => language_matches(language, wanted),
+            None => true,
+        }
+    }
+}
+
+/// Compares a source's language (often a full name like "English") with a
+/// user-supplied code or name
+pub fn language_matches(language: &str, wanted: &str) -> bool {
+    const NAMES: &[(&str, &str)] = &[
+        ("de", "german"),
+        ("el", "greek"),
+        ("en", "english"),
+        ("es", "spanish"),
+        ("fi", "finnish"),
+        ("fr", "french"),
+        ("it", "italian"),
+        ("ja", "japanese"),
+        ("la", "latin"),
+        ("nl", "dutch"),
+        ("pl", "polish"),
+        ("pt", "portuguese"),
+        ("ru", "russian"),
+        ("sv", "swedish"),
+        ("zh", "chinese"),
+    ];
+
+    let normalize = |s: &str| {
+        let s = s.trim().to_lowercase();
+        NAMES
+            .iter()
+            .find(|(code, _)| *code == s)
+            .map(|(_, name)| name.to_string())
+            .unwrap_or(s)
+    };
+
+    normalize(language) == normalize(wanted)
 }
 
 /// Search result
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResult {
     pub id: String,
     pub title: String,
     pub author: String,
     pub description: Option<String>,
     pub duration: Option<std::time::Duration>,
+    pub language: Option<String>,
     pub url: String,
     pub source: String,
+    /// Every source that listed this book, when results were merged
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub provenance: Vec<Provenance>,
+}
+
+/// Where one source lists a search result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Provenance {
+    pub source: String,
+    pub id: String,
+    pub url: String,
+}
+
+impl Provenance {
+    /// The listing a single source's result describes
+    pub fn of(result: &SearchResult) -> Self {
+        Self {
+            source: result.source.clone(),
+            id: result.id.clone(),
+            url: result.url.clone(),
+        }
+    }
+}
+
+/// Files to download for a search result, in playback order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportPlan {
+    pub title: String,
+    pub author: String,
+    pub source: String,
+    pub files: Vec<PlannedFile>,
+}
+
+/// A single downloadable file in an import plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedFile {
+    /// Download URL
+    pub url: String,
+    /// Suggested file name, including extension
+    pub file_name: String,
+    /// Title to give the imported file
+    pub title: String,
+    /// Expected duration, if the source reports one
+    pub duration: Option<std::time::Duration>,
 }
 
 /// Source metadata
@@ -86,5 +190,17 @@ mod trait_tests {
         let query = SearchQuery::new("test".to_string());
         assert_eq!(query.limit, 20);
         assert_eq!(query.author, None);
+        assert!(query.matches_language("Klingon"));
+    }
+
+    #[test]
+    fn test_language_matching() {
+        let query = SearchQuery::new("test".to_string()).with_language("en".to_string());
+        assert!(query.matches_language("English"));
+        assert!(query.matches_language("en"));
+        assert!(!query.matches_language("French"));
+
+        assert!(language_matches("german", "DE"));
+        assert!(language_matches("Esperanto", "esperanto"));
     }
 }
@@ -0,0 +1,492 @@
+//! Disk caches with a shared size budget
+//!
+//! Features that keep derived files on disk, such as scaled cover art or
+//! saved search results, each get a namespace: a directory under one cache
+//! root. The manager tracks the size of every namespace together and removes
+//! the least recently used entries once the total exceeds its budget.
+//!
+//! Use is tracked while the manager is open. When it opens, entries count as
+//! last used when they were written.
+
+use crate::error::AppError;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Usage of one namespace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamespaceStats {
+    pub name: String,
+    pub entries: usize,
+    pub bytes: u64,
+}
+
+/// Usage of the whole cache, for settings screens
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheStats {
+    pub total_bytes: u64,
+    pub max_bytes: u64,
+    /// Namespaces holding entries, by name
+    pub namespaces: Vec<NamespaceStats>,
+    /// Corrupt entries deleted since the manager opened
+    pub recovered: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    size: u64,
+    /// Tick of the last read or write; higher is more recent
+    used: u64,
+}
+
+#[derive(Debug, Default)]
+struct Index {
+    entries: HashMap<(String, String), Entry>,
+    total: u64,
+    clock: u64,
+    recovered: u64,
+}
+
+impl Index {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn insert(&mut self, namespace: &str, key: &str, size: u64) {
+        let used = self.tick();
+        let previous = self.entries.insert(
+            (namespace.to_string(), key.to_string()),
+            Entry { size, used },
+        );
+        self.total = self.total - previous.map_or(0, |e| e.size) + size;
+    }
+
+    fn remove(&mut self, namespace: &str, key: &str) -> Option<Entry> {
+        let entry = self
+            .entries
+            .remove(&(namespace.to_string(), key.to_string()))?;
+        self.total -= entry.size;
+        Some(entry)
+    }
+
+    /// The least recently used entry other than `keep`
+    fn oldest_except(&self, keep: &(String, String)) -> Option<(String, String)> {
+        self.entries
+            .iter()
+            .filter(|(id, _)| *id != keep)
+            .min_by_key(|(_, entry)| entry.used)
+            .map(|(id, _)| id.clone())
+    }
+}
+
+/// Namespaced cache directories under one root, kept within a size budget
+#[derive(Debug)]
+pub struct CacheManager {
+    root: PathBuf,
+    max_bytes: u64,
+    index: Mutex<Index>,
+}
+
+impl CacheManager {
+    /// Opens the cache at `root`, creating it if needed
+    ///
+    /// Existing entries are counted toward the budget, and files left
+    /// behind by interrupted writes are removed.
+    pub fn open(root: impl Into<PathBuf>, max_bytes: u64) -> Result<Self, AppError> {
+        let root = root.into();
+        fs::create_dir_all(&root).map_err(|e| write_failed(&root, e))?;
+
+        let mut found = Vec::new();
+        for namespace in read_dir(&root)? {
+            let Ok(name) = namespace.file_name().into_string() else {
+                continue;
+            };
+            if !namespace.path().is_dir() || check_name("namespace", &name).is_err() {
+                continue;
+            }
+            for file in read_dir(&namespace.path())? {
+                let Ok(key) = file.file_name().into_string() else {
+                    continue;
+                };
+                if key.starts_with('.') {
+                    let _ = fs::remove_file(file.path());
+                    continue;
+                }
+                if let Some(metadata) = file.metadata().ok().filter(|m| m.is_file()) {
+                    found.push((metadata.modified().ok(), name.clone(), key, metadata.len()));
+                }
+            }
+        }
+
+        let mut index = Index::default();
+        found.sort_by_key(|(modified, ..)| *modified);
+        for (_, namespace, key, size) in found {
+            index.insert(&namespace, &key, size);
+        }
+
+        let cache = Self {
+            root,
+            max_bytes,
+            index: Mutex::new(index),
+        };
+        cache.evict(None);
+        Ok(cache)
+    }
+
+    /// Directory holding the namespaces
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The size budget in bytes
+    pub fn max_bytes(&self) -> u64 {
+        self.max_bytes
+    }
+
+    /// Directory of `namespace`, created if needed
+    pub fn dir(&self, namespace: &str) -> Result<PathBuf, AppError> {
+        check_name("namespace", namespace)?;
+        let dir = self.root.join(namespace);
+        fs::create_dir_all(&dir).map_err(|e| write_failed(&dir, e))?;
+        Ok(dir)
+    }
+
+    /// Path of a stored entry, marking it as used
+    pub fn get(&self, namespace: &str, key: &str) -> Option<PathBuf> {
+        check_name("namespace", namespace).ok()?;
+        check_name("key", key).ok()?;
+        let path = self.root.join(namespace).join(key);
+
+        let mut index = self.index.lock().unwrap();
+        if !path.is_file() {
+            index.remove(namespace, key);
+            return None;
+        }
+        let used = index.tick();
+        match index
+            .entries
+            .get_mut(&(namespace.to_string(), key.to_string()))
+        {
+            Some(entry) => entry.used = used,
+            None => {
+                // Written by hand or by another process since opening
+                let size = fs::metadata(&path).map_or(0, |m| m.len());
+                index.insert(namespace, key, size);
+            }
+        }
+        Some(path)
+    }
+
+    /// Reads an entry, deleting it if it cannot be read or `decode` rejects it
+    ///
+    /// A deleted entry reads as missing, so the caller recreates it.
+    pub fn load<T>(
+        &self,
+        namespace: &str,
+        key: &str,
+        decode: impl FnOnce(&[u8]) -> Result<T, String>,
+    ) -> Option<T> {
+        let path = self.get(namespace, key)?;
+        let decoded = fs::read(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|data| decode(&data));
+        match decoded {
+            Ok(value) => Some(value),
+            Err(reason) => {
+                self.recover(&AppError::CacheCorrupted { path, reason });
+                None
+            }
+        }
+    }
+
+    /// Stores `data` as an entry and returns its path
+    ///
+    /// Older entries are evicted as needed to stay within the budget. The
+    /// file is replaced in one step, so readers never see half an entry.
+    pub fn put(&self, namespace: &str, key: &str, data: &[u8]) -> Result<PathBuf, AppError> {
+        check_name("key", key)?;
+        let dir = self.dir(namespace)?;
+        let path = dir.join(key);
+        let partial = dir.join(format!(".{}.partial", key));
+        fs::write(&partial, data)
+            .and_then(|_| fs::rename(&partial, &path))
+            .map_err(|e| {
+                let _ = fs::remove_file(&partial);
+                write_failed(&path, e)
+            })?;
+
+        self.index
+            .lock()
+            .unwrap()
+            .insert(namespace, key, data.len() as u64);
+        self.evict(Some((namespace.to_string(), key.to_string())));
+        Ok(path)
+    }
+
+    /// Deletes an entry
+    pub fn remove(&self, namespace: &str, key: &str) {
+        if check_name("namespace", namespace).is_err() || check_name("key", key).is_err() {
+            return;
+        }
+        let _ = fs::remove_file(self.root.join(namespace).join(key));
+        self.index.lock().unwrap().remove(namespace, key);
+    }
+
+    /// Carries out the recovery for a corrupt cache entry: deleting it
+    ///
+    /// Returns `false` for other errors and for paths outside this cache.
+    pub fn recover(&self, error: &AppError) -> bool {
+        let AppError::CacheCorrupted { path, .. } = error else {
+            return false;
+        };
+        let Some((namespace, key)) = self.entry_of(path) else {
+            return false;
+        };
+        self.remove(&namespace, &key);
+        self.index.lock().unwrap().recovered += 1;
+        true
+    }
+
+    /// Deletes every entry of `namespace` and returns the bytes freed
+    pub fn clear(&self, namespace: &str) -> Result<u64, AppError> {
+        check_name("namespace", namespace)?;
+        let dir = self.root.join(namespace);
+        match fs::remove_dir_all(&dir) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(write_failed(&dir, e)),
+        }
+
+        let mut index = self.index.lock().unwrap();
+        let keys: Vec<String> = index
+            .entries
+            .keys()
+            .filter(|(ns, _)| ns == namespace)
+            .map(|(_, key)| key.clone())
+            .collect();
+        Ok(keys
+            .iter()
+            .filter_map(|key| index.remove(namespace, key))
+            .map(|entry| entry.size)
+            .sum())
+    }
+
+    /// Current usage
+    pub fn stats(&self) -> CacheStats {
+        let index = self.index.lock().unwrap();
+        let mut namespaces: HashMap<&str, NamespaceStats> = HashMap::new();
+        for ((namespace, _), entry) in &index.entries {
+            let stats = namespaces
+                .entry(namespace)
+                .or_insert_with(|| NamespaceStats {
+                    name: namespace.clone(),
+                    entries: 0,
+                    bytes: 0,
+                });
+            stats.entries += 1;
+            stats.bytes += entry.size;
+        }
+        let mut namespaces: Vec<NamespaceStats> = namespaces.into_values().collect();
+        namespaces.sort_by(|a, b| a.name.cmp(&b.name));
+
+        CacheStats {
+            total_bytes: index.total,
+            max_bytes: self.max_bytes,
+            namespaces,
+            recovered: index.recovered,
+        }
+    }
+
+    /// Namespace and key of a path inside the cache
+    fn entry_of(&self, path: &Path) -> Option<(String, String)> {
+        let relative = path.strip_prefix(&self.root).ok()?;
+        let mut parts = relative.iter().map(|part| part.to_str());
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(Some(namespace)), Some(Some(key)), None) => {
+                Some((namespace.to_string(), key.to_string()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Removes least recently used entries, other than `keep`, until the
+    /// cache fits its budget
+    fn evict(&self, keep: Option<(String, String)>) {
+        let keep = keep.unwrap_or_default();
+        let mut index = self.index.lock().unwrap();
+        while index.total > self.max_bytes {
+            let Some((namespace, key)) = index.oldest_except(&keep) else {
+                break;
+            };
+            let _ = fs::remove_file(self.root.join(&namespace).join(&key));
+            index.remove(&namespace, &key);
+        }
+    }
+}
+
+/// Namespaces and keys are single file names; a leading dot marks the
+/// cache's own partial writes
+fn check_name(argument: &str, name: &str) -> Result<(), AppError> {
+    let reason = if name.is_empty() {
+        "must not be empty"
+    } else if name.contains(['/', '\\']) {
+        "must not contain path separators"
+    } else if name.starts_with('.') {
+        "must not start with a dot"
+    } else {
+        return Ok(());
+    };
+    Err(AppError::InvalidArgument {
+        argument: argument.to_string(),
+        reason: format!("'{}' {}", name, reason),
+    })
+}
+
+fn read_dir(dir: &Path) -> Result<Vec<fs::DirEntry>, AppError> {
+    fs::read_dir(dir)
+        .and_then(|entries| entries.collect())
+        .map_err(|e| write_failed(dir, e))
+}
+
+fn write_failed(path: &Path, error: io::Error) -> AppError {
+    AppError::CacheWriteFailed {
+        reason: format!("{}: {}", path.display(), error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory under the system temp dir, removed on drop
+    struct TempRoot(PathBuf);
+
+    impl TempRoot {
+        fn new() -> Self {
+            let path =
+                std::env::temp_dir().join(format!("storystream-cache-{}", uuid::Uuid::new_v4()));
+            Self(path)
+        }
+    }
+
+    impl Drop for TempRoot {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_put_get_and_stats() {
+        let root = TempRoot::new();
+        let cache = CacheManager::open(&root.0, 1_000).unwrap();
+
+        let path = cache.put("covers", "a.jpg", &[1; 100]).unwrap();
+        assert_eq!(path, root.0.join("covers/a.jpg"));
+        cache.put("sources", "last.json", &[2; 50]).unwrap();
+        assert_eq!(cache.get("covers", "a.jpg"), Some(path));
+        assert_eq!(cache.get("covers", "missing.jpg"), None);
+
+        let stats = cache.stats();
+        assert_eq!(stats.total_bytes, 150);
+        assert_eq!(stats.max_bytes, 1_000);
+        let names: Vec<&str> = stats.namespaces.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, vec!["covers", "sources"]);
+
+        // Replacing an entry counts its new size only
+        cache.put("covers", "a.jpg", &[1; 10]).unwrap();
+        assert_eq!(cache.stats().total_bytes, 60);
+
+        assert_eq!(cache.clear("covers").unwrap(), 10);
+        assert_eq!(cache.get("covers", "a.jpg"), None);
+        assert_eq!(cache.stats().total_bytes, 50);
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used_past_budget() {
+        let root = TempRoot::new();
+        let cache = CacheManager::open(&root.0, 250).unwrap();
+
+        cache.put("covers", "old", &[0; 100]).unwrap();
+        cache.put("covers", "newer", &[0; 100]).unwrap();
+        // Reading makes the oldest entry the most recently used
+        cache.get("covers", "old").unwrap();
+        cache.put("sources", "newest", &[0; 100]).unwrap();
+
+        assert!(cache.get("covers", "old").is_some());
+        assert!(cache.get("covers", "newer").is_none());
+        assert!(!root.0.join("covers/newer").exists());
+        assert_eq!(cache.stats().total_bytes, 200);
+
+        // An entry over the whole budget pushes out everything else
+        cache.put("covers", "huge", &[0; 300]).unwrap();
+        assert_eq!(cache.stats().total_bytes, 300);
+        assert!(cache.get("covers", "huge").is_some());
+    }
+
+    #[test]
+    fn test_reopening_counts_existing_entries() {
+        let root = TempRoot::new();
+        {
+            let cache = CacheManager::open(&root.0, 1_000).unwrap();
+            cache.put("covers", "a.jpg", &[0; 300]).unwrap();
+        }
+        fs::write(root.0.join("covers/.b.jpg.partial"), [0; 10]).unwrap();
+
+        let cache = CacheManager::open(&root.0, 1_000).unwrap();
+        assert_eq!(cache.stats().total_bytes, 300);
+        assert!(!root.0.join("covers/.b.jpg.partial").exists());
+
+        // A smaller budget takes effect on opening
+        let cache = CacheManager::open(&root.0, 100).unwrap();
+        assert_eq!(cache.stats().total_bytes, 0);
+        assert!(!root.0.join("covers/a.jpg").exists());
+    }
+
+    #[test]
+    fn test_corrupt_entries_are_deleted() {
+        let root = TempRoot::new();
+        let cache = CacheManager::open(&root.0, 1_000).unwrap();
+        cache.put("sources", "last.json", b"{not json").unwrap();
+
+        let decode = |data: &[u8]| {
+            std::str::from_utf8(data)
+                .map_err(|e| e.to_string())
+                .and_then(|text| text.parse::<u32>().map_err(|e| e.to_string()))
+        };
+        assert_eq!(cache.load("sources", "last.json", decode), None);
+        assert!(!root.0.join("sources/last.json").exists());
+        assert_eq!(cache.stats().recovered, 1);
+
+        cache.put("sources", "last.json", b"42").unwrap();
+        assert_eq!(cache.load("sources", "last.json", decode), Some(42));
+
+        // Corruption found by a consumer goes through the same recovery
+        let path = cache.get("sources", "last.json").unwrap();
+        let error = AppError::CacheCorrupted {
+            path,
+            reason: "truncated".to_string(),
+        };
+        assert!(cache.recover(&error));
+        assert!(cache.get("sources", "last.json").is_none());
+        let outside = AppError::CacheCorrupted {
+            path: PathBuf::from("/elsewhere/file"),
+            reason: "truncated".to_string(),
+        };
+        assert!(!cache.recover(&outside));
+    }
+
+    #[test]
+    fn test_rejects_names_that_leave_the_namespace() {
+        let root = TempRoot::new();
+        let cache = CacheManager::open(&root.0, 1_000).unwrap();
+
+        assert!(cache.put("covers", "../escape", b"x").is_err());
+        assert!(cache.put("..", "key", b"x").is_err());
+        assert!(cache.put("covers", "", b"x").is_err());
+        assert!(cache.dir("a/b").is_err());
+        assert_eq!(cache.dir("covers").unwrap(), root.0.join("covers"));
+    }
+}
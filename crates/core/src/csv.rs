@@ -0,0 +1,23 @@
+//! Helpers for the CSV files StoryStream exports
+
+/// Quotes a CSV field holding a separator, quote or line break
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("Dune"), "Dune");
+        assert_eq!(csv_field("Herbert, Frank"), "\"Herbert, Frank\"");
+        assert_eq!(csv_field("The \"Spice\""), "\"The \"\"Spice\"\"\"");
+        assert_eq!(csv_field("line\r\nbreak"), "\"line\r\nbreak\"");
+    }
+}
@@ -66,6 +66,9 @@ impl fmt::Display for ErrorSeverity {
     }
 }
 
+/// What SQLite says when a write reaches a database opened read-only
+const READ_ONLY_MESSAGE: &str = "attempt to write a readonly database";
+
 /// Main error type for StoryStream
 #[derive(Error, Debug)]
 pub enum AppError {
@@ -111,6 +114,10 @@ pub enum AppError {
     #[error("Database locked: {operation}")]
     DatabaseLocked { operation: String },
 
+    /// Database was opened read-only, so a write was refused
+    #[error("Library is read-only: {operation}")]
+    DatabaseReadOnly { operation: String },
+
     /// Record not found in database
     #[error("Record not found: {entity} with {identifier}")]
     RecordNotFound { entity: String, identifier: String },
@@ -344,6 +351,9 @@ impl AppError {
             Self::MigrationFailed { .. } => {
                 "Failed to update the app's database. Restoring from backup...".to_string()
             }
+            Self::DatabaseReadOnly { .. } => {
+                "Your library is read-only, so changes can't be saved.".to_string()
+            }
             Self::RecordNotFound { .. } => "The requested item was not found.".to_string(),
 
             Self::UnsupportedFormat { format, .. } => {
@@ -424,6 +434,50 @@ impl AppError {
         }
     }
 
+    /// Returns a stable machine-readable code for this error
+    ///
+    /// Codes are part of the CLI's JSON output and must not change once released.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NetworkError { .. } => "NETWORK_ERROR",
+            Self::NetworkTimeout { .. } => "NETWORK_TIMEOUT",
+            Self::ConnectionLost { .. } => "CONNECTION_LOST",
+            Self::InvalidUrl { .. } => "INVALID_URL",
+            Self::DatabaseError { .. } => "DATABASE_ERROR",
+            Self::DatabaseCorrupted { .. } => "DATABASE_CORRUPTED",
+            Self::MigrationFailed { .. } => "MIGRATION_FAILED",
+            Self::DatabaseLocked { .. } => "DATABASE_LOCKED",
+            Self::DatabaseReadOnly { .. } => "DATABASE_READ_ONLY",
+            Self::RecordNotFound { .. } => "RECORD_NOT_FOUND",
+            Self::UnsupportedFormat { .. } => "UNSUPPORTED_FORMAT",
+            Self::AudioDecodeError { .. } => "AUDIO_DECODE_ERROR",
+            Self::CorruptedAudioFile { .. } => "CORRUPTED_AUDIO_FILE",
+            Self::PlaybackDeviceError { .. } => "PLAYBACK_DEVICE_ERROR",
+            Self::InvalidPosition { .. } => "INVALID_POSITION",
+            Self::FileNotFound { .. } => "FILE_NOT_FOUND",
+            Self::PermissionDenied { .. } => "PERMISSION_DENIED",
+            Self::DiskFull { .. } => "DISK_FULL",
+            Self::IoError { .. } => "IO_ERROR",
+            Self::MetadataParseError { .. } => "METADATA_PARSE_ERROR",
+            Self::InvalidMetadata { .. } => "INVALID_METADATA",
+            Self::MissingMetadata { .. } => "MISSING_METADATA",
+            Self::ContentSourceUnavailable { .. } => "CONTENT_SOURCE_UNAVAILABLE",
+            Self::InvalidContentResponse { .. } => "INVALID_CONTENT_RESPONSE",
+            Self::ContentNotFound { .. } => "CONTENT_NOT_FOUND",
+            Self::InvalidConfiguration { .. } => "INVALID_CONFIGURATION",
+            Self::ConfigurationCorrupted { .. } => "CONFIGURATION_CORRUPTED",
+            Self::SyncConflict { .. } => "SYNC_CONFLICT",
+            Self::SyncAuthFailed { .. } => "SYNC_AUTH_FAILED",
+            Self::CacheWriteFailed { .. } => "CACHE_WRITE_FAILED",
+            Self::CacheCorrupted { .. } => "CACHE_CORRUPTED",
+            Self::OutOfMemory { .. } => "OUT_OF_MEMORY",
+            Self::TooManyOpenFiles { .. } => "TOO_MANY_OPEN_FILES",
+            Self::InternalError { .. } => "INTERNAL_ERROR",
+            Self::Cancelled { .. } => "CANCELLED",
+            Self::InvalidArgument { .. } => "INVALID_ARGUMENT",
+        }
+    }
+
     /// Returns true if this error should be logged at ERROR level
     pub fn is_critical(&self) -> bool {
         self.severity() == ErrorSeverity::Fatal
@@ -449,10 +503,18 @@ impl AppError {
     }
 
     /// Helper to create a database error from any error type
+    ///
+    /// Writes refused because the database was opened read-only become
+    /// [`AppError::DatabaseReadOnly`].
     pub fn database<E: std::error::Error + Send + Sync + 'static>(
         message: impl Into<String>,
         source: E,
     ) -> Self {
+        if source.to_string().contains(READ_ONLY_MESSAGE) {
+            return Self::DatabaseReadOnly {
+                operation: message.into(),
+            };
+        }
         Self::DatabaseError {
             message: message.into(),
             source: Some(Box::new(source)),
@@ -649,6 +711,14 @@ mod tests {
         let err = AppError::database("Query failed", inner_err);
 
         assert!(matches!(err, AppError::DatabaseError { .. }));
+
+        let inner_err = io::Error::other("attempt to write a readonly database");
+        let err = AppError::database("Failed to create book", inner_err);
+        assert_eq!(err.code(), "DATABASE_READ_ONLY");
+        assert_eq!(
+            err.to_string(),
+            "Library is read-only: Failed to create book"
+        );
     }
 
     #[test]
@@ -862,4 +932,16 @@ mod tests {
         };
         assert!(!err.is_retryable());
     }
+
+    #[test]
+    fn test_error_codes() {
+        let err = AppError::RecordNotFound {
+            entity: "Book".to_string(),
+            identifier: "42".to_string(),
+        };
+        assert_eq!(err.code(), "RECORD_NOT_FOUND");
+
+        let err = AppError::database("query failed", io::Error::other("boom"));
+        assert_eq!(err.code(), "DATABASE_ERROR");
+    }
 }
@@ -1,12 +1,18 @@
+pub mod cache;
+pub mod csv;
 pub mod error;
 pub mod types;
 
 // Re-export commonly used types
+pub use cache::{CacheManager, CacheStats, NamespaceStats};
+pub use csv::csv_field;
 pub use error::{AppError, ErrorSeverity, RecoveryAction, Result};
 pub use types::{
-    AudioFormat, AudioMetadata, Book, BookId, Bookmark, BookmarkId, Chapter, ChapterId, Duration,
-    LibraryStats, PlaybackSpeed, PlaybackState, PlaybackStats, Playlist, PlaylistId, PlaylistItem,
-    PlaylistType, Timestamp,
+    AudioFormat, AudioMetadata, AutoBookmarkTrigger, Book, BookId, Bookmark, BookmarkId,
+    BookmarkKind, Chapter, ChapterId, CoverArt, DownloadPolicy, Duration, EpisodeId, LibraryStats,
+    PlaybackSpeed, PlaybackState, PlaybackStats, Playlist, PlaylistId, PlaylistItem,
+    PlaylistSession, PlaylistType, Podcast, PodcastEpisode, PodcastId, SharedBookmark,
+    SmartPlaylistCriteria, SourceRef, SpeedRamp, Timestamp,
 };
 pub fn add(left: u64, right: u64) -> u64 {
     left + right
@@ -85,6 +85,7 @@ pub struct Book {
     pub publisher: Option<String>,
     pub published_date: Option<String>,
     pub isbn: Option<String>,
+    pub genre: Option<String>,
     pub duration: Duration,
     pub file_path: PathBuf,
     pub file_size: u64,
@@ -113,6 +114,7 @@ impl Book {
             publisher: None,
             published_date: None,
             isbn: None,
+            genre: None,
             duration,
             file_path,
             file_size,
@@ -1,6 +1,6 @@
 //! Bookmark domain model
 
-use crate::types::{BookId, Duration, Timestamp, Validator};
+use crate::types::{Book, BookId, Duration, SharedBookmark, Timestamp, Validator};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -37,7 +37,56 @@ impl std::fmt::Display for BookmarkId {
     }
 }
 
-/// Represents a user bookmark in an audiobook
+/// Whether a bookmark was placed by the user or by the player
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BookmarkKind {
+    #[default]
+    User,
+    Auto,
+}
+
+impl BookmarkKind {
+    /// Name stored in the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Auto => "auto",
+        }
+    }
+
+    /// Parses a stored name, treating anything unknown as a user bookmark
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "auto" => Self::Auto,
+            _ => Self::User,
+        }
+    }
+}
+
+/// What made the player place an auto-bookmark
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoBookmarkTrigger {
+    /// Playback stayed paused past the configured threshold
+    Paused,
+    /// A sleep timer stopped playback
+    SleepTimer,
+    /// The player was closed
+    Exit,
+}
+
+impl AutoBookmarkTrigger {
+    /// Title given to bookmarks placed by this trigger
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::Paused => "Paused",
+            Self::SleepTimer => "Sleep timer",
+            Self::Exit => "Closed player",
+        }
+    }
+}
+
+/// Represents a bookmark in an audiobook
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Bookmark {
     pub id: BookmarkId,
@@ -45,6 +94,9 @@ pub struct Bookmark {
     pub position: Duration,
     pub title: Option<String>,
     pub note: Option<String>,
+    /// Exports from before auto-bookmarks existed hold only user bookmarks
+    #[serde(default)]
+    pub kind: BookmarkKind,
     pub created_at: Timestamp,
     pub updated_at: Timestamp,
 }
@@ -59,6 +111,7 @@ impl Bookmark {
             position,
             title: None,
             note: None,
+            kind: BookmarkKind::User,
             created_at: now,
             updated_at: now,
         }
@@ -71,6 +124,25 @@ impl Bookmark {
         bookmark
     }
 
+    /// Creates an auto-bookmark titled after its trigger
+    pub fn auto(book_id: BookId, position: Duration, trigger: AutoBookmarkTrigger) -> Self {
+        let mut bookmark = Self::with_title(book_id, position, trigger.title().to_string());
+        bookmark.kind = BookmarkKind::Auto;
+        bookmark
+    }
+
+    /// Returns true if the player placed this bookmark
+    pub fn is_auto(&self) -> bool {
+        self.kind == BookmarkKind::Auto
+    }
+
+    /// Text to send someone so they can listen from this bookmark
+    ///
+    /// `book` is the bookmarked book, whose file has `file_hash`.
+    pub fn to_share_string(&self, book: &Book, file_hash: Option<&str>) -> String {
+        SharedBookmark::new(book, self.position, file_hash).to_share_string()
+    }
+
     /// Updates the bookmark's note
     pub fn set_note(&mut self, note: String) {
         self.note = Some(note);
@@ -92,6 +164,27 @@ impl Bookmark {
     pub fn has_title(&self) -> bool {
         self.title.as_ref().map_or(false, |t| !t.trim().is_empty())
     }
+
+    /// Validates the bookmark and checks it falls within the book's duration
+    pub fn validate_for_book(&self, book_duration: Duration) -> Result<(), Vec<String>> {
+        let mut errors = match self.validate() {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors,
+        };
+
+        if self.position > book_duration {
+            errors.push(format!(
+                "Bookmark position {} exceeds book duration {}",
+                self.position, book_duration
+            ));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
 }
 
 impl Validator for Bookmark {
@@ -170,6 +263,25 @@ mod tests {
         assert!(bookmark.has_title());
     }
 
+    #[test]
+    fn test_auto_bookmark() {
+        let book_id = BookId::new();
+        let bookmark = Bookmark::auto(
+            book_id,
+            Duration::from_seconds(60),
+            AutoBookmarkTrigger::Paused,
+        );
+
+        assert!(bookmark.is_auto());
+        assert_eq!(bookmark.title.as_deref(), Some("Paused"));
+        assert!(!Bookmark::new(book_id, Duration::from_seconds(60)).is_auto());
+        assert_eq!(
+            BookmarkKind::parse(BookmarkKind::Auto.as_str()),
+            BookmarkKind::Auto
+        );
+        assert_eq!(BookmarkKind::parse("other"), BookmarkKind::User);
+    }
+
     #[test]
     fn test_bookmark_set_note() {
         let mut bookmark = Bookmark::new(BookId::new(), Duration::from_seconds(100));
@@ -247,4 +359,36 @@ mod tests {
         let bookmark = Bookmark::new(BookId::new(), Duration::from_seconds(100));
         assert_eq!(bookmark.created_at, bookmark.updated_at);
     }
+
+    #[test]
+    fn test_bookmark_share_string() {
+        let book = Book::new(
+            "Emma".to_string(),
+            std::path::PathBuf::from("/books/emma.mp3"),
+            1024,
+            Duration::from_seconds(3600),
+        );
+        let bookmark = Bookmark::new(book.id, Duration::from_seconds(125));
+        let text = bookmark.to_share_string(&book, None);
+        assert_eq!(
+            text,
+            "Listen to \"Emma\" at 0:02:05: storystream://bookmark?title=Emma&at=0:02:05"
+        );
+        let shared = SharedBookmark::parse(&text).unwrap();
+        assert_eq!(shared.position, bookmark.position);
+    }
+
+    #[test]
+    fn test_bookmark_validate_for_book() {
+        let bookmark = Bookmark::new(BookId::new(), Duration::from_seconds(100));
+        assert!(bookmark
+            .validate_for_book(Duration::from_seconds(100))
+            .is_ok());
+
+        let errors = bookmark
+            .validate_for_book(Duration::from_seconds(60))
+            .unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("exceeds book duration"));
+    }
 }
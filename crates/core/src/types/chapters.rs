@@ -0,0 +1,315 @@
+//! Chapter list normalization and editing
+//!
+//! A normalized chapter list is sorted by start time, numbered from 0 and
+//! contiguous: each chapter ends where the next one starts and the last one
+//! ends with the book. The editing operations expect and keep that shape.
+
+use crate::types::{Book, Chapter, Duration};
+
+/// Shortest chapter the editing operations will leave behind, in milliseconds
+pub const MIN_CHAPTER_MS: u64 = 1000;
+
+/// Sorts, renumbers and closes the gaps in a book's chapter list
+///
+/// `book_duration` ends the last chapter; pass zero when it is unknown to
+/// keep the last chapter's own end time. Returns every problem found, such as
+/// empty titles, chapters sharing a start time or chapters of another book.
+pub fn normalize_chapters(
+    chapters: &mut [Chapter],
+    book_duration: Duration,
+) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+    let Some(book_id) = chapters.first().map(|c| c.book_id) else {
+        return Ok(());
+    };
+
+    chapters.sort_by_key(|c| c.start_time);
+    for (index, chapter) in chapters.iter_mut().enumerate() {
+        chapter.index = index as u32;
+        chapter.title = chapter.title.trim().to_string();
+        if chapter.title.is_empty() {
+            errors.push(format!("Chapter {} has no title", index + 1));
+        }
+        if chapter.book_id != book_id {
+            errors.push(format!("Chapter {} belongs to another book", index + 1));
+        }
+    }
+
+    let count = chapters.len();
+    for index in 0..count {
+        let end = match chapters.get(index + 1) {
+            Some(next) => next.start_time,
+            None if !book_duration.is_zero() => book_duration,
+            None => chapters[index].end_time,
+        };
+        let chapter = &mut chapters[index];
+        chapter.end_time = end;
+        if chapter.end_time <= chapter.start_time {
+            errors.push(if index + 1 == count && !book_duration.is_zero() {
+                format!("Chapter {} starts after the end of the book", index + 1)
+            } else {
+                format!("Chapter {} has no length", index + 1)
+            });
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Moves the start of a chapter by `offset_ms`, along with the end of the one before
+///
+/// The move is clamped so neither chapter gets shorter than
+/// [`MIN_CHAPTER_MS`]. Returns whether the start changed.
+pub fn nudge_chapter(chapters: &mut [Chapter], index: usize, offset_ms: i64) -> bool {
+    let Some(chapter) = chapters.get(index) else {
+        return false;
+    };
+
+    let earliest = match index.checked_sub(1) {
+        Some(previous) => chapters[previous].start_time.as_millis() + MIN_CHAPTER_MS,
+        None => 0,
+    };
+    let latest = chapter
+        .end_time
+        .as_millis()
+        .saturating_sub(MIN_CHAPTER_MS)
+        .max(earliest);
+    let current = chapter.start_time.as_millis();
+    let target = current
+        .saturating_add_signed(offset_ms)
+        .clamp(earliest, latest);
+    if target == current {
+        return false;
+    }
+
+    let start = Duration::from_millis(target);
+    chapters[index].start_time = start;
+    if let Some(previous) = index.checked_sub(1) {
+        chapters[previous].end_time = start;
+    }
+    true
+}
+
+/// Splits the chapter playing at `at` into two
+///
+/// The second half is titled after the first with "(continued)". Returns the
+/// new chapter's index, or `None` when `at` is too close to a chapter
+/// boundary.
+pub fn split_chapter(chapters: &mut Vec<Chapter>, at: Duration) -> Option<usize> {
+    let index = chapters
+        .iter()
+        .position(|c| c.start_time <= at && at < c.end_time)?;
+    let chapter = &chapters[index];
+    let at_ms = at.as_millis();
+    if at_ms < chapter.start_time.as_millis() + MIN_CHAPTER_MS
+        || at_ms + MIN_CHAPTER_MS > chapter.end_time.as_millis()
+    {
+        return None;
+    }
+
+    let second = Chapter::new(
+        chapter.book_id,
+        format!("{} (continued)", chapter.title),
+        0,
+        at,
+        chapter.end_time,
+    );
+    chapters[index].end_time = at;
+    chapters.insert(index + 1, second);
+    renumber(chapters);
+    Some(index + 1)
+}
+
+/// Joins a chapter with the one after it, keeping the first title
+///
+/// Returns whether there was a next chapter to merge.
+pub fn merge_with_next(chapters: &mut Vec<Chapter>, index: usize) -> bool {
+    if index + 1 >= chapters.len() {
+        return false;
+    }
+
+    let next = chapters.remove(index + 1);
+    chapters[index].end_time = next.end_time;
+    renumber(chapters);
+    true
+}
+
+/// Writes a CUE sheet with one track per chapter
+///
+/// The sheet names the book's audio file without its directory, so it is
+/// meant to be saved next to that file.
+pub fn chapters_to_cue(book: &Book, chapters: &[Chapter]) -> String {
+    let file_name = book
+        .file_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let file_type = match book
+        .file_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .as_deref()
+    {
+        Some("mp3") => "MP3",
+        Some("aif" | "aiff") => "AIFF",
+        _ => "WAVE",
+    };
+
+    let mut cue = String::new();
+    if let Some(author) = &book.author {
+        cue.push_str(&format!("PERFORMER \"{}\"\n", cue_text(author)));
+    }
+    cue.push_str(&format!("TITLE \"{}\"\n", cue_text(&book.title)));
+    cue.push_str(&format!(
+        "FILE \"{}\" {}\n",
+        cue_text(&file_name),
+        file_type
+    ));
+    for (number, chapter) in chapters.iter().enumerate() {
+        cue.push_str(&format!("  TRACK {:02} AUDIO\n", number + 1));
+        cue.push_str(&format!("    TITLE \"{}\"\n", cue_text(&chapter.title)));
+        cue.push_str(&format!("    INDEX 01 {}\n", cue_time(chapter.start_time)));
+    }
+    cue
+}
+
+fn renumber(chapters: &mut [Chapter]) {
+    for (index, chapter) in chapters.iter_mut().enumerate() {
+        chapter.index = index as u32;
+    }
+}
+
+/// CUE strings are double-quoted with no escape sequence
+fn cue_text(text: &str) -> String {
+    text.replace('"', "'")
+}
+
+/// Formats `MM:SS:FF` with 75 frames per second
+fn cue_time(time: Duration) -> String {
+    let millis = time.as_millis();
+    let frames = (millis % 1000) * 75 / 1000;
+    let seconds = millis / 1000;
+    format!("{:02}:{:02}:{:02}", seconds / 60, seconds % 60, frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::BookId;
+
+    fn chapters(book_id: BookId, starts: &[u64]) -> Vec<Chapter> {
+        starts
+            .iter()
+            .enumerate()
+            .map(|(i, &start)| {
+                Chapter::new(
+                    book_id,
+                    format!("Chapter {}", i + 1),
+                    i as u32,
+                    Duration::from_seconds(start),
+                    Duration::from_seconds(start + 1),
+                )
+            })
+            .collect()
+    }
+
+    fn starts(chapters: &[Chapter]) -> Vec<u64> {
+        chapters.iter().map(|c| c.start_time.as_millis()).collect()
+    }
+
+    #[test]
+    fn test_normalize_sorts_and_closes_gaps() {
+        let book_id = BookId::new();
+        let mut list = chapters(book_id, &[300, 0, 100]);
+        list[0].title = "  Ending ".to_string();
+
+        normalize_chapters(&mut list, Duration::from_seconds(400)).unwrap();
+        assert_eq!(starts(&list), vec![0, 100_000, 300_000]);
+        let ends: Vec<u64> = list.iter().map(|c| c.end_time.as_millis()).collect();
+        assert_eq!(ends, vec![100_000, 300_000, 400_000]);
+        let indexes: Vec<u32> = list.iter().map(|c| c.index).collect();
+        assert_eq!(indexes, vec![0, 1, 2]);
+        assert_eq!(list[2].title, "Ending");
+    }
+
+    #[test]
+    fn test_normalize_reports_problems() {
+        let book_id = BookId::new();
+        let mut list = chapters(book_id, &[0, 100, 100, 500]);
+        list[0].title = " ".to_string();
+        list[1].book_id = BookId::new();
+
+        let errors = normalize_chapters(&mut list, Duration::from_seconds(400)).unwrap_err();
+        assert_eq!(errors.len(), 4, "{:?}", errors);
+        assert!(errors.iter().any(|e| e.contains("after the end")));
+    }
+
+    #[test]
+    fn test_nudge_moves_neighbouring_boundary() {
+        let book_id = BookId::new();
+        let mut list = chapters(book_id, &[0, 100]);
+        normalize_chapters(&mut list, Duration::from_seconds(200)).unwrap();
+
+        assert!(nudge_chapter(&mut list, 1, 1000));
+        assert_eq!(list[1].start_time, Duration::from_seconds(101));
+        assert_eq!(list[0].end_time, Duration::from_seconds(101));
+
+        // Clamped so the first chapter keeps a second
+        assert!(nudge_chapter(&mut list, 1, -500_000));
+        assert_eq!(list[1].start_time, Duration::from_seconds(1));
+        assert!(!nudge_chapter(&mut list, 1, -1000));
+        assert!(!nudge_chapter(&mut list, 5, 1000));
+    }
+
+    #[test]
+    fn test_split_and_merge() {
+        let book_id = BookId::new();
+        let mut list = chapters(book_id, &[0, 100]);
+        normalize_chapters(&mut list, Duration::from_seconds(200)).unwrap();
+
+        assert_eq!(
+            split_chapter(&mut list, Duration::from_seconds(150)),
+            Some(2)
+        );
+        assert_eq!(starts(&list), vec![0, 100_000, 150_000]);
+        assert_eq!(list[2].title, "Chapter 2 (continued)");
+        assert_eq!(list[2].index, 2);
+        assert_eq!(
+            split_chapter(&mut list, Duration::from_millis(150_500)),
+            None
+        );
+
+        assert!(merge_with_next(&mut list, 1));
+        assert_eq!(starts(&list), vec![0, 100_000]);
+        assert_eq!(list[1].end_time, Duration::from_seconds(200));
+        assert!(!merge_with_next(&mut list, 1));
+    }
+
+    #[test]
+    fn test_cue_export() {
+        let mut book = Book::new(
+            "The \"Warden\"".to_string(),
+            "/books/warden.mp3".into(),
+            1,
+            Duration::from_seconds(4000),
+        );
+        book.author = Some("Anthony Trollope".to_string());
+        let mut list = chapters(book.id, &[0, 3725]);
+        list[1].start_time = Duration::from_millis(3_725_520);
+
+        let cue = chapters_to_cue(&book, &list);
+        assert_eq!(
+            cue,
+            "PERFORMER \"Anthony Trollope\"\n\
+             TITLE \"The 'Warden'\"\n\
+             FILE \"warden.mp3\" MP3\n  \
+             TRACK 01 AUDIO\n    TITLE \"Chapter 1\"\n    INDEX 01 00:00:00\n  \
+             TRACK 02 AUDIO\n    TITLE \"Chapter 2\"\n    INDEX 01 62:05:39\n"
+        );
+    }
+}
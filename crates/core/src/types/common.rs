@@ -2,6 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::str::FromStr;
 
 /// Timestamp in milliseconds since Unix epoch
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
@@ -95,6 +96,72 @@ impl fmt::Display for Duration {
     }
 }
 
+impl FromStr for Duration {
+    type Err = String;
+
+    /// Parses a human-entered duration
+    ///
+    /// Accepts unit form (`1h23m`, `45m 10s`, `90s`, `7d`), clock form
+    /// (`1:23:45`, `23:45`), or a bare number of seconds (`90`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let input = s.trim();
+        if input.is_empty() {
+            return Err("Duration cannot be empty".to_string());
+        }
+
+        let invalid = || format!("Invalid duration '{}'", input);
+
+        if input.contains(':') {
+            let parts: Vec<&str> = input.split(':').collect();
+            if parts.len() > 3 {
+                return Err(invalid());
+            }
+            let mut seconds: u64 = 0;
+            for (i, part) in parts.iter().enumerate() {
+                let value: u64 = part.parse().map_err(|_| invalid())?;
+                if i > 0 && value >= 60 {
+                    return Err(invalid());
+                }
+                seconds = seconds * 60 + value;
+            }
+            return Ok(Self::from_seconds(seconds));
+        }
+
+        if let Ok(seconds) = input.parse::<u64>() {
+            return Ok(Self::from_seconds(seconds));
+        }
+
+        let mut seconds: u64 = 0;
+        let mut digits = String::new();
+        let mut seen_unit = false;
+        for c in input.chars() {
+            match c {
+                '0'..='9' => digits.push(c),
+                'd' | 'h' | 'm' | 's' => {
+                    let value: u64 = digits.parse().map_err(|_| invalid())?;
+                    digits.clear();
+                    let multiplier = match c {
+                        'd' => 86_400,
+                        'h' => 3600,
+                        'm' => 60,
+                        _ => 1,
+                    };
+                    seconds += value * multiplier;
+                    seen_unit = true;
+                }
+                c if c.is_whitespace() => {}
+                _ => return Err(invalid()),
+            }
+        }
+
+        if !digits.is_empty() || !seen_unit {
+            return Err(invalid());
+        }
+
+        Ok(Self::from_seconds(seconds))
+    }
+}
+
 impl From<std::time::Duration> for Duration {
     fn from(d: std::time::Duration) -> Self {
         Self(d.as_millis() as u64)
@@ -176,7 +243,7 @@ mod tests {
     #[test]
     fn test_duration_as_hms_without_hours() {
         let d = Duration::from_seconds(125); // 2m 5s
-        // FIXED: Now always shows hours, so "0:02:05" instead of "2:05"
+                                             // FIXED: Now always shows hours, so "0:02:05" instead of "2:05"
         assert_eq!(d.as_hms(), "0:02:05");
     }
 
@@ -208,6 +275,46 @@ mod tests {
         assert_eq!(d.as_seconds(), 42);
     }
 
+    #[test]
+    fn test_duration_parse_units() {
+        assert_eq!(
+            "1h23m".parse::<Duration>(),
+            Ok(Duration::from_seconds(4980))
+        );
+        assert_eq!(
+            "45m 10s".parse::<Duration>(),
+            Ok(Duration::from_seconds(2710))
+        );
+        assert_eq!("90s".parse::<Duration>(), Ok(Duration::from_seconds(90)));
+        assert_eq!("2h".parse::<Duration>(), Ok(Duration::from_seconds(7200)));
+        assert_eq!(
+            "7d".parse::<Duration>(),
+            Ok(Duration::from_seconds(604_800))
+        );
+    }
+
+    #[test]
+    fn test_duration_parse_clock_and_seconds() {
+        assert_eq!(
+            "1:23:45".parse::<Duration>(),
+            Ok(Duration::from_seconds(5025))
+        );
+        assert_eq!(
+            "23:45".parse::<Duration>(),
+            Ok(Duration::from_seconds(1425))
+        );
+        assert_eq!("90".parse::<Duration>(), Ok(Duration::from_seconds(90)));
+    }
+
+    #[test]
+    fn test_duration_parse_invalid() {
+        assert!("".parse::<Duration>().is_err());
+        assert!("abc".parse::<Duration>().is_err());
+        assert!("1h23".parse::<Duration>().is_err());
+        assert!("1:75".parse::<Duration>().is_err());
+        assert!("1x".parse::<Duration>().is_err());
+    }
+
     #[test]
     fn test_validator_trait() {
         struct TestType {
@@ -230,4 +337,4 @@ mod tests {
         assert!(valid.is_valid());
         assert!(!invalid.is_valid());
     }
-}
\ No newline at end of file
+}
@@ -1,5 +1,6 @@
 //! Audio format and metadata domain models
 
+use crate::error::AppError;
 use crate::types::Duration;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -196,6 +197,55 @@ impl CoverArt {
     pub fn is_larger_than(&self, bytes: usize) -> bool {
         self.data.len() > bytes
     }
+
+    /// Wraps image bytes, detecting the MIME type from their signature
+    ///
+    /// Returns `None` if the bytes are not a known image format.
+    pub fn from_bytes(data: Vec<u8>) -> Option<Self> {
+        let mime_type = match data.as_slice() {
+            [0xFF, 0xD8, 0xFF, ..] => "image/jpeg",
+            [0x89, b'P', b'N', b'G', ..] => "image/png",
+            [b'G', b'I', b'F', b'8', ..] => "image/gif",
+            [b'B', b'M', ..] => "image/bmp",
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "image/webp",
+            _ => return None,
+        };
+        Some(Self::new(data, mime_type.to_string()))
+    }
+
+    /// Returns a copy scaled to fit within `max_px` x `max_px`
+    ///
+    /// PNG art stays PNG so transparency survives; everything else is
+    /// re-encoded as JPEG. Art that already fits is returned unchanged.
+    pub fn thumbnail(&self, max_px: u32) -> Result<CoverArt, AppError> {
+        let invalid = |reason: String| AppError::InvalidMetadata {
+            field: "cover_art".to_string(),
+            value: reason,
+        };
+
+        let image = image::load_from_memory(&self.data).map_err(|e| invalid(e.to_string()))?;
+        if image.width() <= max_px && image.height() <= max_px {
+            return Ok(self.clone());
+        }
+
+        let scaled = image.thumbnail(max_px.max(1), max_px.max(1));
+        let (format, mime_type) = if self.mime_type == "image/png" {
+            (image::ImageFormat::Png, "image/png")
+        } else {
+            (image::ImageFormat::Jpeg, "image/jpeg")
+        };
+        // JPEG has no alpha channel
+        let scaled = match format {
+            image::ImageFormat::Jpeg => image::DynamicImage::ImageRgb8(scaled.to_rgb8()),
+            _ => scaled,
+        };
+
+        let mut data = std::io::Cursor::new(Vec::new());
+        scaled
+            .write_to(&mut data, format)
+            .map_err(|e| invalid(e.to_string()))?;
+        Ok(CoverArt::new(data.into_inner(), mime_type.to_string()))
+    }
 }
 
 #[cfg(test)]
@@ -342,6 +392,45 @@ mod tests {
         assert!(!art.is_larger_than(10));
     }
 
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let image = image::RgbaImage::from_pixel(width, height, image::Rgba([200, 40, 40, 255]));
+        let mut data = std::io::Cursor::new(Vec::new());
+        image.write_to(&mut data, image::ImageFormat::Png).unwrap();
+        data.into_inner()
+    }
+
+    #[test]
+    fn test_cover_art_from_bytes() {
+        let art = CoverArt::from_bytes(png(2, 2)).unwrap();
+        assert_eq!(art.mime_type, "image/png");
+
+        let jpeg = CoverArt::from_bytes(vec![0xFF, 0xD8, 0xFF, 0xE0]).unwrap();
+        assert_eq!(jpeg.image_format(), Some("jpeg"));
+
+        assert!(CoverArt::from_bytes(b"not an image".to_vec()).is_none());
+        assert!(CoverArt::from_bytes(Vec::new()).is_none());
+    }
+
+    #[test]
+    fn test_cover_art_thumbnail() {
+        let art = CoverArt::from_bytes(png(400, 200)).unwrap();
+        let thumb = art.thumbnail(100).unwrap();
+        assert_eq!(thumb.mime_type, "image/png");
+
+        let decoded = image::load_from_memory(&thumb.data).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (100, 50));
+
+        // Already small enough: returned as-is
+        let small = CoverArt::from_bytes(png(50, 50)).unwrap();
+        assert_eq!(small.thumbnail(100).unwrap().data, small.data);
+    }
+
+    #[test]
+    fn test_cover_art_thumbnail_rejects_garbage() {
+        let art = CoverArt::new(vec![0xFF, 0xD8, 0xFF, 0x00], "image/jpeg".to_string());
+        assert!(art.thumbnail(64).is_err());
+    }
+
     #[test]
     fn test_all_formats_have_extensions() {
         let formats = [
@@ -2,31 +2,42 @@
 //!
 //! This module contains all domain models organized by responsibility:
 //! - `book`: Book and Chapter types
+//! - `chapters`: Chapter list normalization and editing
 //! - `playback`: Playback state and audio settings
 //! - `bookmark`: User bookmarks
 //! - `playlist`: Playlists and playlist items
+//! - `podcast`: Feed subscriptions and episodes
+//! - `share`: Bookmarks shared as `storystream://` links
 //! - `metadata`: Audio format detection and metadata
-//! - `stats`: Library statistics
+//! - `stats`: Library statistics and listening streaks
 //! - `common`: Shared traits and utilities
 
 pub mod book;
 mod bookmark;
+pub mod chapters;
 mod common;
 mod metadata;
 mod playback;
 mod playlist;
+mod podcast;
+mod share;
 mod stats;
 
 // Re-export all public types
 pub use book::{Book, BookId, Chapter, ChapterId};
-pub use bookmark::{Bookmark, BookmarkId};
+pub use bookmark::{AutoBookmarkTrigger, Bookmark, BookmarkId, BookmarkKind};
 pub use common::{Duration, Timestamp, Validator};
 pub use metadata::{AudioFormat, AudioMetadata, CoverArt};
 pub use playback::{
-    EqualizerBand, EqualizerPreset, PlaybackSpeed, PlaybackState, SleepTimer, SleepTimerState,
+    EqualizerBand, EqualizerPreset, PlaybackSpeed, PlaybackState, SleepTimer, SleepTimerMode,
+    SleepTimerState, SpeedRamp, EQUALIZER_FREQUENCIES,
 };
-pub use playlist::{Playlist, PlaylistId, PlaylistItem, PlaylistType, SmartPlaylistCriteria};
-pub use stats::{LibraryStats, PlaybackStats};
+pub use playlist::{
+    Playlist, PlaylistId, PlaylistItem, PlaylistSession, PlaylistType, SmartPlaylistCriteria,
+};
+pub use podcast::{DownloadPolicy, EpisodeId, Podcast, PodcastEpisode, PodcastId};
+pub use share::{SharedBookmark, SourceRef};
+pub use stats::{current_streak, goal_completion, longest_streak, LibraryStats, PlaybackStats};
 
 #[cfg(test)]
 mod tests {
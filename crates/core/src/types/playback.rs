@@ -15,6 +15,9 @@ pub struct PlaybackState {
     pub sleep_timer: Option<SleepTimer>,
     pub skip_silence: bool,
     pub volume_boost: u8, // 0-100, additional boost for quiet recordings
+    /// Gradual speed increase, `None` when the book plays at a fixed speed
+    #[serde(default)]
+    pub speed_ramp: Option<SpeedRamp>,
     pub last_updated: Timestamp,
 }
 
@@ -31,6 +34,7 @@ impl PlaybackState {
             sleep_timer: None,
             skip_silence: false,
             volume_boost: 0,
+            speed_ramp: None,
             last_updated: Timestamp::now(),
         }
     }
@@ -148,6 +152,67 @@ impl Validator for PlaybackSpeed {
     }
 }
 
+/// Playback speed that moves from `start` toward `target` while the book plays
+///
+/// The speed changes by `per_hour` for every hour listened, one step at
+/// each whole minute, and stays at `target` once it gets there.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SpeedRamp {
+    pub start: f32,
+    pub target: f32,
+    /// Speed change per hour of listening
+    pub per_hour: f32,
+    /// Listening time counted so far
+    pub listened: Duration,
+}
+
+impl SpeedRamp {
+    /// Creates a ramp that has not counted any listening yet
+    ///
+    /// Both speeds must be valid [`PlaybackSpeed`]s, and `per_hour` a
+    /// positive change no larger than the whole speed range.
+    pub fn new(start: f32, target: f32, per_hour: f32) -> Result<Self, String> {
+        PlaybackSpeed::new(start)?;
+        PlaybackSpeed::new(target)?;
+        if !per_hour.is_finite() || per_hour <= 0.0 || per_hour > 2.5 {
+            return Err("Speed change per hour must be above 0 and at most 2.5".to_string());
+        }
+        Ok(Self {
+            start,
+            target,
+            per_hour,
+            listened: Duration::from_millis(0),
+        })
+    }
+
+    /// Speed for the listening counted so far, to two decimals
+    pub fn speed(&self) -> f32 {
+        let minutes = self.listened.as_millis() / 60_000;
+        let change = self.per_hour * minutes as f32 / 60.0;
+        let speed = if self.target >= self.start {
+            (self.start + change).min(self.target)
+        } else {
+            (self.start - change).max(self.target)
+        };
+        (speed * 100.0).round() / 100.0
+    }
+
+    /// Whether the ramp has reached its target
+    pub fn is_complete(&self) -> bool {
+        (self.speed() - self.target).abs() < 0.005
+    }
+
+    /// Counts `elapsed` listening, returning whether the speed changed
+    pub fn advance(&mut self, elapsed: Duration) -> bool {
+        let before = self.speed();
+        self.listened = Duration::from_millis(self.listened.as_millis() + elapsed.as_millis());
+        self.speed() != before
+    }
+}
+
+/// Center frequencies of the ten equalizer bands, in Hz
+pub const EQUALIZER_FREQUENCIES: [u32; 10] = [32, 64, 125, 250, 500, 1000, 2000, 4000, 8000, 16000];
+
 /// 10-band equalizer preset
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EqualizerPreset {
@@ -156,6 +221,62 @@ pub struct EqualizerPreset {
 }
 
 impl EqualizerPreset {
+    /// Name of band gains that are not a named preset
+    pub const CUSTOM: &'static str = "Custom";
+
+    /// Creates a preset from ten band gains in dB
+    pub fn from_gains(name: impl Into<String>, gains: [f32; 10]) -> Self {
+        let mut bands = [EqualizerBand::default(); 10];
+        for ((band, frequency), gain) in bands.iter_mut().zip(EQUALIZER_FREQUENCIES).zip(gains) {
+            *band = EqualizerBand::new(frequency, gain);
+        }
+        Self {
+            name: name.into(),
+            bands,
+        }
+    }
+
+    /// Creates unnamed band gains, kept as-is rather than looked up by name
+    pub fn custom(gains: [f32; 10]) -> Self {
+        Self::from_gains(Self::CUSTOM, gains)
+    }
+
+    /// Whether these are raw band gains rather than a named preset
+    pub fn is_custom(&self) -> bool {
+        self.name == Self::CUSTOM
+    }
+
+    /// Band gains in dB, lowest band first
+    pub fn gains(&self) -> [f32; 10] {
+        self.bands.map(|band| band.gain)
+    }
+
+    /// Presets that always exist
+    pub fn builtin() -> Vec<Self> {
+        vec![Self::flat(), Self::bass_boost(), Self::voice_boost()]
+    }
+
+    /// Looks up this preset by name among `available`
+    ///
+    /// Named presets pick up the current bands of the preset with that name,
+    /// so edits to a preset reach every book using it. A name that no longer
+    /// exists falls back to [`EqualizerPreset::flat`]; custom gains are kept.
+    pub fn resolve(&self, available: &[Self]) -> Self {
+        if self.is_custom() {
+            return self.clone();
+        }
+        Self::find(&self.name, available)
+    }
+
+    /// The preset called `name` among `available`, or flat if there is none
+    pub fn find(name: &str, available: &[Self]) -> Self {
+        available
+            .iter()
+            .find(|preset| preset.name.eq_ignore_ascii_case(name))
+            .cloned()
+            .unwrap_or_else(Self::flat)
+    }
+
     /// Creates a flat equalizer (all bands at 0dB)
     pub fn flat() -> Self {
         Self {
@@ -233,17 +354,18 @@ pub struct SleepTimer {
     pub fade_duration: Duration,
     pub started_at: Timestamp,
     pub state: SleepTimerState,
+    /// What the timer waits for; `duration` is unused at end of chapter
+    #[serde(default)]
+    pub mode: SleepTimerMode,
 }
 
 impl SleepTimer {
+    /// Fixed lengths, in minutes, players offer for a timer
+    pub const PRESET_MINUTES: [u64; 4] = [15, 30, 45, 60];
+
     /// Creates a new sleep timer
     pub fn new(duration: Duration) -> Self {
-        Self {
-            duration,
-            fade_duration: Duration::from_seconds(10),
-            started_at: Timestamp::now(),
-            state: SleepTimerState::Active,
-        }
+        Self::with_fade(duration, Duration::from_seconds(10))
     }
 
     /// Creates a sleep timer with custom fade duration
@@ -253,9 +375,28 @@ impl SleepTimer {
             fade_duration,
             started_at: Timestamp::now(),
             state: SleepTimerState::Active,
+            mode: SleepTimerMode::Duration,
         }
     }
 
+    /// Creates a sleep timer that runs until the current chapter ends
+    pub fn end_of_chapter() -> Self {
+        Self {
+            mode: SleepTimerMode::EndOfChapter,
+            ..Self::new(Duration::from_millis(0))
+        }
+    }
+
+    /// Returns true if the timer runs until the end of a chapter
+    pub fn is_end_of_chapter(&self) -> bool {
+        self.mode == SleepTimerMode::EndOfChapter
+    }
+
+    /// Lengthens a timer by `by`
+    pub fn extend(&mut self, by: Duration) {
+        self.duration = Duration::from_millis(self.duration.as_millis() + by.as_millis());
+    }
+
     /// Returns the remaining time in milliseconds
     pub fn remaining_millis(&self) -> i64 {
         let elapsed = Timestamp::now().as_millis() - self.started_at.as_millis();
@@ -282,6 +423,16 @@ pub enum SleepTimerState {
     Expired,
 }
 
+/// What a sleep timer waits for before pausing playback
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SleepTimerMode {
+    /// A fixed length of time
+    #[default]
+    Duration,
+    /// The end of the chapter playing when the timer was set
+    EndOfChapter,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -394,6 +545,24 @@ mod tests {
         assert!(eq.bands[5].gain > 0.0); // 1000 Hz
     }
 
+    #[test]
+    fn test_equalizer_resolve() {
+        let available = EqualizerPreset::builtin();
+
+        let stored = EqualizerPreset::from_gains("voice boost", [0.0; 10]);
+        assert_eq!(stored.resolve(&available), EqualizerPreset::voice_boost());
+
+        // A deleted preset plays flat instead of failing
+        let deleted = EqualizerPreset::from_gains("Old Radio", [3.0; 10]);
+        assert_eq!(deleted.resolve(&available), EqualizerPreset::flat());
+
+        let custom = EqualizerPreset::custom([1.0; 10]);
+        assert!(custom.is_custom());
+        assert_eq!(custom.resolve(&available), custom);
+        assert_eq!(custom.gains(), [1.0; 10]);
+        assert_eq!(custom.bands[9].frequency, 16000);
+    }
+
     #[test]
     fn test_equalizer_band_default() {
         let band = EqualizerBand::default();
@@ -444,6 +613,7 @@ mod tests {
             fade_duration, // Just the variable, no Some()
             started_at: Timestamp::now(),
             state: SleepTimerState::Active,
+            mode: SleepTimerMode::Duration,
         };
 
         // Not fading at start
@@ -462,4 +632,55 @@ mod tests {
         );
         assert!(timer.is_fading());
     }
+
+    #[test]
+    fn test_sleep_timer_modes() {
+        let mut timer = SleepTimer::new(Duration::from_seconds(900));
+        assert!(!timer.is_end_of_chapter());
+        timer.extend(Duration::from_seconds(300));
+        assert_eq!(timer.duration.as_seconds(), 1200);
+
+        let chapter = SleepTimer::end_of_chapter();
+        assert!(chapter.is_end_of_chapter());
+        assert_eq!(chapter.fade_duration.as_seconds(), 10);
+
+        // Timers saved before modes existed wait for their duration
+        let json = r#"{"duration":60000,"fade_duration":10000,"started_at":0,"state":"Active"}"#;
+        let old: SleepTimer = serde_json::from_str(json).unwrap();
+        assert_eq!(old.mode, SleepTimerMode::Duration);
+    }
+
+    #[test]
+    fn test_speed_ramp_validation() {
+        assert!(SpeedRamp::new(1.25, 1.75, 0.1).is_ok());
+        assert!(SpeedRamp::new(0.4, 1.75, 0.1).is_err());
+        assert!(SpeedRamp::new(1.25, 3.5, 0.1).is_err());
+        assert!(SpeedRamp::new(1.25, 1.75, 0.0).is_err());
+        assert!(SpeedRamp::new(1.25, 1.75, f32::NAN).is_err());
+    }
+
+    #[test]
+    fn test_speed_ramp_steps_at_minutes() {
+        let mut ramp = SpeedRamp::new(1.25, 1.75, 0.6).unwrap();
+        assert_eq!(ramp.speed(), 1.25);
+
+        // 0.6 an hour is 0.01 a minute; part of a minute changes nothing
+        assert!(!ramp.advance(Duration::from_seconds(59)));
+        assert!(ramp.advance(Duration::from_seconds(1)));
+        assert_eq!(ramp.speed(), 1.26);
+
+        ramp.advance(Duration::from_seconds(60 * 60));
+        assert_eq!(ramp.speed(), 1.75);
+        assert!(ramp.is_complete());
+        assert!(!ramp.advance(Duration::from_seconds(60)));
+    }
+
+    #[test]
+    fn test_speed_ramp_down() {
+        let mut ramp = SpeedRamp::new(2.0, 1.5, 1.2).unwrap();
+        ramp.advance(Duration::from_seconds(5 * 60));
+        assert_eq!(ramp.speed(), 1.9);
+        ramp.advance(Duration::from_seconds(60 * 60));
+        assert_eq!(ramp.speed(), 1.5);
+    }
 }
@@ -1,6 +1,6 @@
 //! Playlist domain models
 
-use crate::types::{BookId, Timestamp, Validator};
+use crate::types::{Book, BookId, Timestamp, Validator};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -117,6 +117,10 @@ impl Validator for Playlist {
             errors.push("Smart playlist must have criteria".to_string());
         }
 
+        if let Some(Err(criteria_errors)) = self.smart_criteria.as_ref().map(|c| c.validate()) {
+            errors.extend(criteria_errors);
+        }
+
         if errors.is_empty() {
             Ok(())
         } else {
@@ -126,7 +130,11 @@ impl Validator for Playlist {
 }
 
 /// Criteria for smart playlists
+///
+/// Omitted fields take their [`Default`] values when deserializing, and
+/// unknown fields are rejected so that typos do not silently match everything.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct SmartPlaylistCriteria {
     pub favorite_only: bool,
     pub unfinished_only: bool,
@@ -196,6 +204,70 @@ impl SmartPlaylistCriteria {
     }
 }
 
+impl SmartPlaylistCriteria {
+    /// Returns true if `book` satisfies every criterion
+    ///
+    /// `finished` tells whether the book has been listened to the end, which
+    /// is tracked in playback state rather than on the book itself. Name lists
+    /// match case-insensitively and an empty list matches any book.
+    pub fn matches(&self, book: &Book, finished: bool) -> bool {
+        fn any_of(wanted: &[String], value: Option<&str>) -> bool {
+            wanted.is_empty()
+                || value.is_some_and(|v| wanted.iter().any(|w| w.eq_ignore_ascii_case(v)))
+        }
+
+        if self.favorite_only && !book.is_favorite {
+            return false;
+        }
+        if self.unfinished_only && finished {
+            return false;
+        }
+        if let Some(min_rating) = self.min_rating {
+            if book.rating.is_none_or(|rating| rating < min_rating) {
+                return false;
+            }
+        }
+
+        any_of(&self.authors, book.author.as_deref())
+            && any_of(&self.narrators, book.narrator.as_deref())
+            && any_of(&self.series, book.series.as_deref())
+            && (self.tags.is_empty() || book.tags.iter().any(|t| any_of(&self.tags, Some(t))))
+    }
+}
+
+impl Validator for SmartPlaylistCriteria {
+    fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        if let Some(rating) = self.min_rating {
+            if !(1..=5).contains(&rating) {
+                errors.push("Minimum rating must be between 1 and 5".to_string());
+            }
+        }
+
+        if self.max_results == Some(0) {
+            errors.push("Maximum results must be greater than zero".to_string());
+        }
+
+        for (field, values) in [
+            ("authors", &self.authors),
+            ("narrators", &self.narrators),
+            ("tags", &self.tags),
+            ("series", &self.series),
+        ] {
+            if values.iter().any(|v| v.trim().is_empty()) {
+                errors.push(format!("Criteria {} cannot contain empty names", field));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
 impl Default for SmartPlaylistCriteria {
     fn default() -> Self {
         Self {
@@ -232,6 +304,69 @@ impl PlaylistItem {
     }
 }
 
+/// A playlist being played: its queue and how far playback has got
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlaylistSession {
+    pub playlist_id: PlaylistId,
+    /// Books in playback order, shuffled when `shuffle` is set
+    pub queue: Vec<BookId>,
+    /// Index into `queue` of the book playing now
+    pub current_index: usize,
+    pub shuffle: bool,
+    pub updated_at: Timestamp,
+}
+
+impl PlaylistSession {
+    /// Starts a session at the first book of `queue`
+    pub fn new(playlist_id: PlaylistId, mut queue: Vec<BookId>, shuffle: bool) -> Self {
+        if shuffle {
+            shuffle_in_place(&mut queue);
+        }
+        Self {
+            playlist_id,
+            queue,
+            current_index: 0,
+            shuffle,
+            updated_at: Timestamp::now(),
+        }
+    }
+
+    /// Returns the book playing now, `None` once the queue is done
+    pub fn current(&self) -> Option<BookId> {
+        self.queue.get(self.current_index).copied()
+    }
+
+    /// Moves to the next book and returns it, `None` at the end of the queue
+    pub fn advance(&mut self) -> Option<BookId> {
+        if self.current_index < self.queue.len() {
+            self.current_index += 1;
+        }
+        self.updated_at = Timestamp::now();
+        self.current()
+    }
+
+    /// Returns whether every book in the queue has been played
+    pub fn is_finished(&self) -> bool {
+        self.current_index >= self.queue.len()
+    }
+
+    /// Books left after the current one
+    pub fn remaining(&self) -> usize {
+        self.queue.len().saturating_sub(self.current_index + 1)
+    }
+}
+
+/// Fisher-Yates shuffle seeded from the standard library's random hasher keys
+fn shuffle_in_place(queue: &mut [BookId]) {
+    use std::hash::BuildHasher;
+
+    let state = std::collections::hash_map::RandomState::new();
+    for i in (1..queue.len()).rev() {
+        let j = (state.hash_one(i) % (i as u64 + 1)) as usize;
+        queue.swap(i, j);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -355,6 +490,70 @@ mod tests {
         assert_eq!(criteria.max_results, Some(100));
     }
 
+    #[test]
+    fn test_smart_criteria_partial_json_uses_defaults() {
+        let criteria: SmartPlaylistCriteria =
+            serde_json::from_str(r#"{"authors": ["Jane Austen"]}"#).unwrap();
+        assert_eq!(criteria.authors, vec!["Jane Austen".to_string()]);
+        assert!(!criteria.favorite_only);
+        assert_eq!(criteria.max_results, Some(100));
+
+        let typo = serde_json::from_str::<SmartPlaylistCriteria>(r#"{"author": ["X"]}"#);
+        assert!(typo.is_err());
+    }
+
+    #[test]
+    fn test_smart_criteria_validation() {
+        assert!(SmartPlaylistCriteria::highly_rated(4).is_valid());
+        assert!(!SmartPlaylistCriteria::highly_rated(6).is_valid());
+
+        let mut criteria = SmartPlaylistCriteria::by_authors(vec![" ".to_string()]);
+        assert!(!criteria.is_valid());
+        criteria.authors.clear();
+        criteria.max_results = Some(0);
+        assert!(!criteria.is_valid());
+
+        let playlist =
+            Playlist::new_smart("Bad".to_string(), SmartPlaylistCriteria::highly_rated(0));
+        assert!(!playlist.is_valid());
+    }
+
+    #[test]
+    fn test_smart_criteria_matches() {
+        use crate::types::Duration;
+        use std::path::PathBuf;
+
+        let mut book = Book::new(
+            "Emma".to_string(),
+            PathBuf::from("/books/emma.mp3"),
+            1000,
+            Duration::from_seconds(100),
+        );
+        book.author = Some("Jane Austen".to_string());
+        book.rating = Some(4);
+        book.tags = vec!["classic".to_string()];
+
+        assert!(SmartPlaylistCriteria::default().matches(&book, false));
+        assert!(
+            SmartPlaylistCriteria::by_authors(vec!["jane austen".to_string()])
+                .matches(&book, false)
+        );
+        assert!(
+            !SmartPlaylistCriteria::by_authors(vec!["Dickens".to_string()]).matches(&book, false)
+        );
+        assert!(SmartPlaylistCriteria::highly_rated(4).matches(&book, false));
+        assert!(!SmartPlaylistCriteria::highly_rated(5).matches(&book, false));
+        assert!(!SmartPlaylistCriteria::favorites().matches(&book, false));
+        assert!(SmartPlaylistCriteria::unfinished().matches(&book, false));
+        assert!(!SmartPlaylistCriteria::unfinished().matches(&book, true));
+
+        let tagged = SmartPlaylistCriteria {
+            tags: vec!["Classic".to_string()],
+            ..SmartPlaylistCriteria::default()
+        };
+        assert!(tagged.matches(&book, false));
+    }
+
     #[test]
     fn test_playlist_item_new() {
         let playlist_id = PlaylistId::new();
@@ -376,4 +575,39 @@ mod tests {
         assert!(item1.position < item2.position);
         assert!(item2.position < item3.position);
     }
+
+    #[test]
+    fn test_playlist_session_advances_through_queue() {
+        let books = vec![BookId::new(), BookId::new(), BookId::new()];
+        let mut session = PlaylistSession::new(PlaylistId::new(), books.clone(), false);
+
+        assert_eq!(session.current(), Some(books[0]));
+        assert_eq!(session.remaining(), 2);
+        assert_eq!(session.advance(), Some(books[1]));
+        assert_eq!(session.advance(), Some(books[2]));
+        assert!(!session.is_finished());
+        assert_eq!(session.advance(), None);
+        assert!(session.is_finished());
+
+        // Advancing past the end stays at the end
+        assert_eq!(session.advance(), None);
+        assert_eq!(session.current_index, 3);
+    }
+
+    #[test]
+    fn test_playlist_session_shuffle_keeps_books() {
+        let books: Vec<BookId> = (0..20).map(|_| BookId::new()).collect();
+        let session = PlaylistSession::new(PlaylistId::new(), books.clone(), true);
+
+        assert!(session.shuffle);
+        let mut shuffled = session
+            .queue
+            .iter()
+            .map(|b| b.as_string())
+            .collect::<Vec<_>>();
+        let mut original = books.iter().map(|b| b.as_string()).collect::<Vec<_>>();
+        shuffled.sort();
+        original.sort();
+        assert_eq!(shuffled, original);
+    }
 }
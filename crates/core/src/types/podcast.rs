@@ -0,0 +1,257 @@
+//! Podcast feed subscriptions and episodes
+
+use crate::types::{BookId, Duration, Timestamp, Validator};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Unique identifier for a podcast subscription
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PodcastId(Uuid);
+
+impl PodcastId {
+    /// Creates a new random PodcastId
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+
+    /// Creates a PodcastId from a UUID string
+    pub fn from_string(s: &str) -> Result<Self, uuid::Error> {
+        Ok(Self(Uuid::parse_str(s)?))
+    }
+
+    /// Returns the PodcastId as a string
+    pub fn as_string(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+impl Default for PodcastId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for PodcastId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Unique identifier for a podcast episode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EpisodeId(Uuid);
+
+impl EpisodeId {
+    /// Creates a new random EpisodeId
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+
+    /// Creates an EpisodeId from a UUID string
+    pub fn from_string(s: &str) -> Result<Self, uuid::Error> {
+        Ok(Self(Uuid::parse_str(s)?))
+    }
+
+    /// Returns the EpisodeId as a string
+    pub fn as_string(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+impl Default for EpisodeId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for EpisodeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Automatic download and cleanup rules for one subscription
+///
+/// The default policy leaves episodes alone; downloads then only happen on
+/// request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DownloadPolicy {
+    /// Number of newest unplayed episodes to keep downloaded
+    pub max_episodes: usize,
+    /// Download new episodes after each refresh
+    pub auto_download: bool,
+    /// Delete an episode's file once it has been listened to
+    pub delete_finished: bool,
+    /// Only download while the connection is unmetered
+    pub unmetered_only: bool,
+}
+
+impl DownloadPolicy {
+    /// Whether applying the policy can change anything
+    pub fn is_active(&self) -> bool {
+        self.auto_download || self.delete_finished
+    }
+}
+
+impl Default for DownloadPolicy {
+    fn default() -> Self {
+        Self {
+            max_episodes: 3,
+            auto_download: false,
+            delete_finished: false,
+            unmetered_only: true,
+        }
+    }
+}
+
+/// A subscribed podcast or audiobook feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Podcast {
+    pub id: PodcastId,
+    pub feed_url: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub author: Option<String>,
+    pub image_url: Option<String>,
+    /// When the feed was last fetched successfully
+    pub last_fetched: Option<Timestamp>,
+    /// `ETag` from the last fetch, for conditional requests
+    pub etag: Option<String>,
+    /// `Last-Modified` from the last fetch, for conditional requests
+    pub last_modified: Option<String>,
+    pub policy: DownloadPolicy,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
+}
+
+impl Podcast {
+    /// Creates a new subscription for a feed URL
+    pub fn new(feed_url: String, title: String) -> Self {
+        let now = Timestamp::now();
+        Self {
+            id: PodcastId::new(),
+            feed_url,
+            title,
+            description: None,
+            author: None,
+            image_url: None,
+            last_fetched: None,
+            etag: None,
+            last_modified: None,
+            policy: DownloadPolicy::default(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+impl Validator for Podcast {
+    fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        if self.title.trim().is_empty() {
+            errors.push("Podcast title cannot be empty".to_string());
+        }
+
+        if !(self.feed_url.starts_with("http://") || self.feed_url.starts_with("https://")) {
+            errors.push(format!("Feed URL must be http(s): {}", self.feed_url));
+        }
+
+        if self.policy.auto_download && self.policy.max_episodes == 0 {
+            errors.push("Auto-download needs at least one episode to keep".to_string());
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// A single episode of a subscribed feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PodcastEpisode {
+    pub id: EpisodeId,
+    pub podcast_id: PodcastId,
+    /// Feed-provided identifier used to detect already-known episodes
+    pub guid: Option<String>,
+    pub title: String,
+    pub description: Option<String>,
+    pub audio_url: String,
+    pub duration: Option<Duration>,
+    pub published: Option<Timestamp>,
+    /// Local path once the enclosure has been downloaded
+    pub file_path: Option<String>,
+    pub is_downloaded: bool,
+    /// Listened to the end; stays set after the file is removed
+    pub is_played: bool,
+    /// Library book the downloaded file was imported as
+    pub book_id: Option<BookId>,
+    pub created_at: Timestamp,
+}
+
+impl PodcastEpisode {
+    /// Creates a new, not yet downloaded episode
+    pub fn new(podcast_id: PodcastId, title: String, audio_url: String) -> Self {
+        Self {
+            id: EpisodeId::new(),
+            podcast_id,
+            guid: None,
+            title,
+            description: None,
+            audio_url,
+            duration: None,
+            published: None,
+            file_path: None,
+            is_downloaded: false,
+            is_played: false,
+            book_id: None,
+            created_at: Timestamp::now(),
+        }
+    }
+
+    /// Returns the identity key used to de-duplicate episodes across refreshes
+    pub fn identity(&self) -> &str {
+        self.guid.as_deref().unwrap_or(&self.audio_url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_podcast_validation() {
+        let podcast = Podcast::new("https://example.com/feed.xml".into(), "Show".into());
+        assert!(podcast.validate().is_ok());
+
+        let bad = Podcast::new("ftp://example.com/feed".into(), " ".into());
+        assert_eq!(bad.validate().unwrap_err().len(), 2);
+
+        let mut greedy = podcast.clone();
+        greedy.policy.auto_download = true;
+        greedy.policy.max_episodes = 0;
+        assert!(greedy.validate().is_err());
+    }
+
+    #[test]
+    fn test_episode_identity() {
+        let mut episode = PodcastEpisode::new(
+            PodcastId::new(),
+            "Ep 1".into(),
+            "https://example.com/1.mp3".into(),
+        );
+        assert_eq!(episode.identity(), "https://example.com/1.mp3");
+
+        episode.guid = Some("ep-1".into());
+        assert_eq!(episode.identity(), "ep-1");
+    }
+
+    #[test]
+    fn test_default_policy_is_inactive() {
+        let podcast = Podcast::new("https://example.com/feed.xml".into(), "Show".into());
+        assert!(!podcast.policy.is_active());
+        assert!(podcast.policy.unmetered_only);
+    }
+}
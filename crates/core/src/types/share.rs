@@ -0,0 +1,264 @@
+//! Bookmarks shared with other people
+//!
+//! A shared bookmark names the book by title and author, plus the hash of
+//! its file and the online catalogue it came from when those are known, so
+//! the receiving library can find its own copy or fetch one. It travels as
+//! a `storystream://` link, usually inside a line of text such as
+//! `Listen to "The Time Machine" by H. G. Wells at 2:14:05: storystream://…`.
+
+use crate::types::{Book, Duration};
+use std::fmt;
+use std::str::FromStr;
+
+/// Scheme and host of shared bookmark links
+const LINK_PREFIX: &str = "storystream://bookmark?";
+
+/// Online catalogue a book was fetched from, recorded as a book tag
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceRef {
+    /// Catalogue key, one of [`SourceRef::SOURCES`]
+    pub source: String,
+    /// The book's identifier in that catalogue
+    pub id: String,
+}
+
+impl SourceRef {
+    /// Catalogues a book can be fetched from again by its identifier
+    pub const SOURCES: [&'static str; 2] = ["librivox", "archive"];
+
+    /// Creates a reference to `id` in `source`
+    pub fn new(source: impl Into<String>, id: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            id: id.into(),
+        }
+    }
+
+    /// Reference recorded among a book's tags, if any
+    pub fn from_tags(tags: &[String]) -> Option<Self> {
+        tags.iter().find_map(|tag| tag.parse().ok())
+    }
+
+    /// Tag that records this reference on a book
+    pub fn tag(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for SourceRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.source, self.id)
+    }
+}
+
+impl FromStr for SourceRef {
+    type Err = String;
+
+    /// Parses "source:id", such as "librivox:59"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (source, id) = s
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid source '{}'", s))?;
+        if !Self::SOURCES.contains(&source) {
+            return Err(format!("Unknown source '{}'", source));
+        }
+        if id.is_empty() {
+            return Err(format!("Invalid source '{}'", s));
+        }
+        Ok(Self::new(source, id))
+    }
+}
+
+/// A position in a book, as sent to someone else
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedBookmark {
+    pub title: String,
+    pub author: Option<String>,
+    /// Catalogue the book can be fetched from
+    pub source: Option<SourceRef>,
+    /// SHA-256 of the book's file, as the library records it
+    pub file_hash: Option<String>,
+    pub position: Duration,
+}
+
+impl SharedBookmark {
+    /// Shares `position` in `book`, whose file has `file_hash`
+    pub fn new(book: &Book, position: Duration, file_hash: Option<&str>) -> Self {
+        Self {
+            title: book.title.clone(),
+            author: book.author.clone(),
+            source: SourceRef::from_tags(&book.tags),
+            file_hash: file_hash.map(str::to_string),
+            position,
+        }
+    }
+
+    /// The `storystream://` link
+    pub fn to_uri(&self) -> String {
+        let mut uri = format!("{}title={}", LINK_PREFIX, encode(&self.title));
+        if let Some(author) = &self.author {
+            uri.push_str(&format!("&author={}", encode(author)));
+        }
+        if let Some(source) = &self.source {
+            uri.push_str(&format!("&source={}", encode(&source.to_string())));
+        }
+        if let Some(hash) = &self.file_hash {
+            uri.push_str(&format!("&hash={}", encode(hash)));
+        }
+        uri.push_str(&format!("&at={}", self.position.as_hms()));
+        uri
+    }
+
+    /// A line of text for people, ending with the link
+    pub fn to_share_string(&self) -> String {
+        let by = self
+            .author
+            .as_ref()
+            .map(|author| format!(" by {}", author))
+            .unwrap_or_default();
+        format!(
+            "Listen to \"{}\"{} at {}: {}",
+            self.title,
+            by,
+            self.position.as_hms(),
+            self.to_uri()
+        )
+    }
+
+    /// Reads a shared bookmark from a link, or from text that contains one
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let start = text
+            .find(LINK_PREFIX)
+            .ok_or_else(|| "No StoryStream link found".to_string())?;
+        let query = text[start + LINK_PREFIX.len()..]
+            .split_whitespace()
+            .next()
+            .unwrap_or_default();
+
+        let mut title = None;
+        let mut author = None;
+        let mut source = None;
+        let mut file_hash = None;
+        let mut position = None;
+        for pair in query.split('&') {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = decode(value)?;
+            match key {
+                "title" => title = Some(value),
+                "author" => author = Some(value),
+                "source" => source = Some(value.parse()?),
+                "hash" => file_hash = Some(value),
+                "at" => position = Some(value.parse()?),
+                // Links from newer versions may carry more
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            title: title
+                .filter(|title| !title.is_empty())
+                .ok_or_else(|| "Link has no book title".to_string())?,
+            author: author.filter(|author| !author.is_empty()),
+            source,
+            file_hash: file_hash.filter(|hash| !hash.is_empty()),
+            position: position.ok_or_else(|| "Link has no position".to_string())?,
+        })
+    }
+}
+
+/// Percent-encodes everything but unreserved characters and ':'
+fn encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b':' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+fn decode(value: &str) -> Result<String, String> {
+    let invalid = || format!("Invalid link text '{}'", value);
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.bytes();
+    while let Some(byte) = rest.next() {
+        match byte {
+            b'%' => {
+                let hex = [
+                    rest.next().ok_or_else(invalid)?,
+                    rest.next().ok_or_else(invalid)?,
+                ];
+                let hex = std::str::from_utf8(&hex).map_err(|_| invalid())?;
+                bytes.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+            }
+            b'+' => bytes.push(b' '),
+            _ => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn book() -> Book {
+        let mut book = Book::new(
+            "The Time Machine".to_string(),
+            PathBuf::from("/books/time_machine.mp3"),
+            1024,
+            Duration::from_seconds(4 * 3600),
+        );
+        book.author = Some("H. G. Wells".to_string());
+        book.tags = vec!["classics".to_string(), "librivox:59".to_string()];
+        book
+    }
+
+    #[test]
+    fn test_source_ref_from_tags() {
+        assert_eq!(
+            SourceRef::from_tags(&book().tags),
+            Some(SourceRef::new("librivox", "59"))
+        );
+        assert_eq!(SourceRef::from_tags(&["corrupt".to_string()]), None);
+        assert!("gutenberg:12".parse::<SourceRef>().is_err());
+        assert!("archive:".parse::<SourceRef>().is_err());
+    }
+
+    #[test]
+    fn test_share_string_round_trip() {
+        let shared = SharedBookmark::new(&book(), Duration::from_seconds(8045), Some("ab12"));
+        let text = shared.to_share_string();
+        assert_eq!(
+            text,
+            "Listen to \"The Time Machine\" by H. G. Wells at 2:14:05: \
+             storystream://bookmark?title=The%20Time%20Machine&author=H.%20G.%20Wells\
+             &source=librivox:59&hash=ab12&at=2:14:05"
+        );
+
+        assert_eq!(SharedBookmark::parse(&text), Ok(shared.clone()));
+        assert_eq!(SharedBookmark::parse(&shared.to_uri()), Ok(shared));
+    }
+
+    #[test]
+    fn test_parse_minimal_link() {
+        let shared =
+            SharedBookmark::parse("storystream://bookmark?title=Emma+%26+Co&at=45:10").unwrap();
+        assert_eq!(shared.title, "Emma & Co");
+        assert_eq!(shared.author, None);
+        assert_eq!(shared.source, None);
+        assert_eq!(shared.position, Duration::from_seconds(45 * 60 + 10));
+    }
+
+    #[test]
+    fn test_parse_rejects_broken_links() {
+        assert!(SharedBookmark::parse("just some text").is_err());
+        assert!(SharedBookmark::parse("storystream://bookmark?at=1:00").is_err());
+        assert!(SharedBookmark::parse("storystream://bookmark?title=Emma").is_err());
+        assert!(SharedBookmark::parse("storystream://bookmark?title=%E&at=1:00").is_err());
+    }
+}
@@ -1,4 +1,4 @@
-//! Library and playback statistics
+//! Library and playback statistics, and daily listening streaks
 
 use crate::types::Duration;
 use serde::{Deserialize, Serialize};
@@ -18,6 +18,15 @@ pub struct LibraryStats {
     pub authors_count: usize,
     pub narrators_count: usize,
     pub series_count: usize,
+    /// Time spent listening, from recorded listening sessions
+    pub listening_time: Duration,
+    /// Consecutive days with some listening, up to today
+    pub current_streak_days: u32,
+    pub longest_streak_days: u32,
+    /// Authors by time spent listening to them, most first
+    pub top_listened_authors: Vec<(String, Duration)>,
+    /// Book titles by time spent listening to them, most first
+    pub top_listened_books: Vec<(String, Duration)>,
 }
 
 impl LibraryStats {
@@ -36,6 +45,11 @@ impl LibraryStats {
             authors_count: 0,
             narrators_count: 0,
             series_count: 0,
+            listening_time: Duration::from_millis(0),
+            current_streak_days: 0,
+            longest_streak_days: 0,
+            top_listened_authors: Vec::new(),
+            top_listened_books: Vec::new(),
         }
     }
 
@@ -157,10 +171,60 @@ impl Default for PlaybackStats {
     }
 }
 
+/// Consecutive days meeting the daily goal, counting back from today
+///
+/// `daily_minutes` holds minutes listened per day, oldest first, ending with
+/// today. Today only breaks the streak once it is over: until the goal is
+/// met the streak counts from yesterday.
+pub fn current_streak(daily_minutes: &[u32], goal_minutes: u32) -> u32 {
+    let goal = goal_minutes.max(1);
+    let mut days = daily_minutes.iter().rev().peekable();
+    if days.peek().is_some_and(|&&today| today < goal) {
+        days.next();
+    }
+    days.take_while(|&&minutes| minutes >= goal).count() as u32
+}
+
+/// Longest run of consecutive days meeting the daily goal
+pub fn longest_streak(daily_minutes: &[u32], goal_minutes: u32) -> u32 {
+    let goal = goal_minutes.max(1);
+    daily_minutes
+        .split(|&minutes| minutes < goal)
+        .map(|run| run.len() as u32)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Fraction of the daily goal reached, from 0.0 to 1.0
+pub fn goal_completion(minutes: u32, goal_minutes: u32) -> f64 {
+    (minutes as f64 / goal_minutes.max(1) as f64).min(1.0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_streaks() {
+        let days = [40, 35, 0, 30, 31, 45, 10];
+        // Today is short of the goal, so the streak runs through yesterday
+        assert_eq!(current_streak(&days, 30), 3);
+        assert_eq!(current_streak(&[30, 0, 30], 30), 1);
+        assert_eq!(current_streak(&[30, 0, 5], 30), 0);
+        assert_eq!(current_streak(&[], 30), 0);
+
+        assert_eq!(longest_streak(&days, 30), 3);
+        assert_eq!(longest_streak(&days, 40), 1);
+        assert_eq!(longest_streak(&[0, 0], 30), 0);
+    }
+
+    #[test]
+    fn test_goal_completion() {
+        assert_eq!(goal_completion(15, 30), 0.5);
+        assert_eq!(goal_completion(90, 30), 1.0);
+        assert_eq!(goal_completion(0, 0), 0.0);
+    }
+
     #[test]
     fn test_library_stats_empty() {
         let stats = LibraryStats::empty();
@@ -0,0 +1,13 @@
+-- Migration 006: Feed subscriptions
+-- Adds conditional-fetch validators to podcasts and feed GUIDs to episodes
+
+ALTER TABLE podcasts ADD COLUMN etag TEXT;
+ALTER TABLE podcasts ADD COLUMN last_modified TEXT;
+
+ALTER TABLE podcast_episodes ADD COLUMN guid TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_podcast_episodes_podcast ON podcast_episodes(podcast_id, published_date DESC);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_podcast_episodes_guid ON podcast_episodes(podcast_id, guid) WHERE guid IS NOT NULL;
+
+-- Insert migration version
+INSERT OR IGNORE INTO schema_migrations (version) VALUES (6);
@@ -0,0 +1,15 @@
+-- Migration 007: Playlist session
+-- Remembers the playlist being played, its queue order and the current book
+
+CREATE TABLE IF NOT EXISTS playlist_session (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    playlist_id TEXT NOT NULL,
+    queue TEXT NOT NULL, -- JSON array of book IDs
+    current_index INTEGER NOT NULL DEFAULT 0,
+    shuffle INTEGER NOT NULL DEFAULT 0,
+    updated_at INTEGER NOT NULL,
+    FOREIGN KEY (playlist_id) REFERENCES playlists(id) ON DELETE CASCADE
+);
+
+-- Insert migration version
+INSERT OR IGNORE INTO schema_migrations (version) VALUES (7);
@@ -0,0 +1,12 @@
+-- Migration 008: Subscription download policies
+-- Stores each feed's auto-download rules and which episodes have been played
+
+ALTER TABLE podcasts ADD COLUMN max_episodes INTEGER NOT NULL DEFAULT 3;
+ALTER TABLE podcasts ADD COLUMN auto_download INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE podcasts ADD COLUMN delete_finished INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE podcasts ADD COLUMN unmetered_only INTEGER NOT NULL DEFAULT 1;
+
+ALTER TABLE podcast_episodes ADD COLUMN is_played INTEGER NOT NULL DEFAULT 0;
+
+-- Insert migration version
+INSERT OR IGNORE INTO schema_migrations (version) VALUES (8);
@@ -0,0 +1,8 @@
+-- Migration 009: User-edited book metadata
+-- Lists the metadata fields a user has corrected, as a JSON array of column
+-- names, so re-importing a file does not overwrite them with its tags
+
+ALTER TABLE books ADD COLUMN edited_fields TEXT NOT NULL DEFAULT '[]';
+
+-- Insert migration version
+INSERT OR IGNORE INTO schema_migrations (version) VALUES (9);
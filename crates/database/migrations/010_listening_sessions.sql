@@ -0,0 +1,17 @@
+-- Migration 010: Listening sessions
+-- One row per stretch of continuous playback, used for daily listening
+-- totals and streaks
+
+CREATE TABLE IF NOT EXISTS listening_sessions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    book_id TEXT NOT NULL,
+    started_at INTEGER NOT NULL, -- Unix timestamp in milliseconds
+    ended_at INTEGER NOT NULL,
+    FOREIGN KEY (book_id) REFERENCES books(id) ON DELETE CASCADE,
+    CHECK (ended_at >= started_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_listening_sessions_ended_at ON listening_sessions(ended_at);
+
+-- Insert migration version
+INSERT OR IGNORE INTO schema_migrations (version) VALUES (10);
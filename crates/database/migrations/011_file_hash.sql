@@ -0,0 +1,8 @@
+-- Migration 011: Stored file hashes
+-- SHA-256 of each book's audio file, taken at import, so later verification
+-- can tell when a file has changed or rotted on disk
+
+ALTER TABLE books ADD COLUMN file_hash TEXT;
+
+-- Insert migration version
+INSERT OR IGNORE INTO schema_migrations (version) VALUES (11);
@@ -0,0 +1,10 @@
+-- Migration 012: Bookmark kinds
+-- Auto-bookmarks placed by the player on pause and exit are kept apart from
+-- the ones users add, so they can be capped and cleared in bulk
+
+ALTER TABLE bookmarks ADD COLUMN kind TEXT NOT NULL DEFAULT 'user';
+
+CREATE INDEX IF NOT EXISTS idx_bookmarks_kind ON bookmarks(book_id, kind);
+
+-- Insert migration version
+INSERT OR IGNORE INTO schema_migrations (version) VALUES (12);
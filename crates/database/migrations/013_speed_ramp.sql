@@ -0,0 +1,8 @@
+-- Migration 013: Speed ramps
+-- A book can play at a speed that climbs gradually as it is listened to; the
+-- ramp and how far along it is are kept with the book's other playback state
+
+ALTER TABLE playback_state ADD COLUMN speed_ramp TEXT;
+
+-- Insert migration version
+INSERT OR IGNORE INTO schema_migrations (version) VALUES (13);
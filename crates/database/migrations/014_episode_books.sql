@@ -0,0 +1,10 @@
+-- Migration 014: Episode books
+-- Links each downloaded feed episode to the book it was imported as, so a
+-- refresh or a finished book can be traced back to the episode
+
+ALTER TABLE podcast_episodes ADD COLUMN book_id TEXT REFERENCES books(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_podcast_episodes_book ON podcast_episodes(book_id);
+
+-- Insert migration version
+INSERT OR IGNORE INTO schema_migrations (version) VALUES (14);
@@ -0,0 +1,45 @@
+-- Migration 015: Book search index
+-- Recreates the books full-text index so accented letters match their plain
+-- forms ("Bronte" finds "Brontë") and short prefixes are indexed for
+-- search-as-you-type. Only edits to searched columns touch the index.
+
+DROP TRIGGER IF EXISTS books_fts_insert;
+DROP TRIGGER IF EXISTS books_fts_update;
+DROP TRIGGER IF EXISTS books_fts_delete;
+DROP TABLE IF EXISTS books_fts;
+
+CREATE VIRTUAL TABLE books_fts USING fts5(
+    title,
+    author,
+    narrator,
+    series,
+    description,
+    tags,
+    content=books,
+    content_rowid=rowid,
+    tokenize='unicode61 remove_diacritics 2',
+    prefix='2 3'
+);
+
+CREATE TRIGGER books_fts_insert AFTER INSERT ON books BEGIN
+    INSERT INTO books_fts(rowid, title, author, narrator, series, description, tags)
+    VALUES (NEW.rowid, NEW.title, NEW.author, NEW.narrator, NEW.series, NEW.description, NEW.tags);
+END;
+
+CREATE TRIGGER books_fts_update
+AFTER UPDATE OF title, author, narrator, series, description, tags ON books BEGIN
+    INSERT INTO books_fts(books_fts, rowid, title, author, narrator, series, description, tags)
+    VALUES ('delete', OLD.rowid, OLD.title, OLD.author, OLD.narrator, OLD.series, OLD.description, OLD.tags);
+    INSERT INTO books_fts(rowid, title, author, narrator, series, description, tags)
+    VALUES (NEW.rowid, NEW.title, NEW.author, NEW.narrator, NEW.series, NEW.description, NEW.tags);
+END;
+
+CREATE TRIGGER books_fts_delete AFTER DELETE ON books BEGIN
+    INSERT INTO books_fts(books_fts, rowid, title, author, narrator, series, description, tags)
+    VALUES ('delete', OLD.rowid, OLD.title, OLD.author, OLD.narrator, OLD.series, OLD.description, OLD.tags);
+END;
+
+INSERT INTO books_fts(books_fts) VALUES ('rebuild');
+
+-- Insert migration version
+INSERT OR IGNORE INTO schema_migrations (version) VALUES (15);
@@ -0,0 +1,11 @@
+-- Migration 016: Book genre
+-- Genre read from the file's tags, and indexes for browsing by narrator and
+-- genre the way series and author already are
+
+ALTER TABLE books ADD COLUMN genre TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_books_narrator ON books(narrator) WHERE deleted_at IS NULL AND narrator IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_books_genre ON books(genre) WHERE deleted_at IS NULL AND genre IS NOT NULL;
+
+-- Insert migration version
+INSERT OR IGNORE INTO schema_migrations (version) VALUES (16);
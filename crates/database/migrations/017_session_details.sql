@@ -0,0 +1,13 @@
+-- Migration 017: Listening session details
+-- Time actually listened and the speed it was played at, for per-book
+-- statistics. Existing sessions were played straight through at 1x.
+
+ALTER TABLE listening_sessions ADD COLUMN seconds_listened INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE listening_sessions ADD COLUMN speed REAL NOT NULL DEFAULT 1.0;
+
+UPDATE listening_sessions SET seconds_listened = (ended_at - started_at) / 1000;
+
+CREATE INDEX IF NOT EXISTS idx_listening_sessions_book ON listening_sessions(book_id);
+
+-- Insert migration version
+INSERT OR IGNORE INTO schema_migrations (version) VALUES (17);
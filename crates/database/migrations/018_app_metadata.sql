@@ -0,0 +1,12 @@
+-- Migration 018: App metadata
+-- Small named values the app keeps about the database itself, such as when
+-- maintenance last ran
+
+CREATE TABLE IF NOT EXISTS app_metadata (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL,
+    updated_at INTEGER NOT NULL -- Unix timestamp in milliseconds
+);
+
+-- Insert migration version
+INSERT OR IGNORE INTO schema_migrations (version) VALUES (18);
@@ -0,0 +1,8 @@
+-- Migration 019: Book loudness
+-- Gated average loudness of each book's audio in dB relative to full
+-- scale, NULL until the book has been analyzed.
+
+ALTER TABLE books ADD COLUMN loudness_db REAL;
+
+-- Insert migration version
+INSERT OR IGNORE INTO schema_migrations (version) VALUES (19);
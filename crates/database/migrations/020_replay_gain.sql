@@ -0,0 +1,13 @@
+-- Migration 020: Replay gain
+-- Gain in dB that brings a book to the playback loudness target, worked
+-- out when its loudness is measured. NULL until then.
+
+ALTER TABLE books ADD COLUMN replay_gain_db REAL;
+
+-- Books measured before this column existed get the gain their loudness
+-- calls for; their peak was not kept, so playback limits any clipping
+UPDATE books SET replay_gain_db = MIN(-18.0 - loudness_db, 12.0)
+WHERE loudness_db IS NOT NULL;
+
+-- Insert migration version
+INSERT OR IGNORE INTO schema_migrations (version) VALUES (20);
@@ -0,0 +1,12 @@
+-- Migration 021: Content fingerprints
+-- Hash of the start of each book's file together with its size and
+-- duration. Cheap enough to take on every scan, and unchanged when a file
+-- is moved or renamed, so a rescan can find books whose folders were
+-- reorganized. NULL for books not imported since.
+
+ALTER TABLE books ADD COLUMN content_hash TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_books_content_hash ON books(content_hash);
+
+-- Insert migration version
+INSERT OR IGNORE INTO schema_migrations (version) VALUES (21);
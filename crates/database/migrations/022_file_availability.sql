@@ -0,0 +1,12 @@
+-- Migration 022: File availability
+-- Books whose file could not be found when the library was last verified
+-- are marked unavailable instead of being deleted, so they keep their
+-- history and come back once their drive does. The file's modification
+-- time is kept at import to tell when it changed on disk; NULL for books
+-- imported before.
+
+ALTER TABLE books ADD COLUMN is_available INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE books ADD COLUMN file_modified_ms INTEGER;
+
+-- Insert migration version
+INSERT OR IGNORE INTO schema_migrations (version) VALUES (22);
@@ -0,0 +1,318 @@
+//! Database backups
+//!
+//! Backups are complete copies of the database written with `VACUUM INTO`,
+//! so they are consistent even while the library is in use. A backup is
+//! checked with [`verify_integrity`] before it replaces the live database.
+
+use crate::connection::{connect, DatabaseConfig};
+use crate::migrations::{verify_integrity, CURRENT_VERSION};
+use crate::DbPool;
+use chrono::{TimeZone, Utc};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use storystream_core::{AppError, Timestamp};
+
+/// Start of the file names [`create_backup`] gives backups
+const BACKUP_PREFIX: &str = "storystream-";
+
+/// Extension of backup files
+const BACKUP_EXTENSION: &str = "db";
+
+/// A backup file and what it holds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BackupInfo {
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    pub created_at: Timestamp,
+    /// Migrations applied to the backed up database
+    pub schema_version: i64,
+}
+
+/// Backs up the database to `dest`
+///
+/// When `dest` is a folder the backup is written into it under a name that
+/// sorts by time, which [`prune_backups`] relies on. An existing file is never
+/// overwritten.
+pub async fn create_backup(pool: &DbPool, dest: &Path) -> Result<BackupInfo, AppError> {
+    let created_at = Timestamp::now();
+    let path = if dest.is_dir() {
+        dest.join(backup_file_name(created_at))
+    } else {
+        dest.to_path_buf()
+    };
+
+    let schema_version = schema_version(pool).await?;
+    sqlx::query("VACUUM INTO ?")
+        .bind(path.to_string_lossy().into_owned())
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::database(format!("Failed to back up to {}", path.display()), e))?;
+
+    let size_bytes = fs::metadata(&path)
+        .map_err(|e| AppError::database("Failed to read the backup size", e))?
+        .len();
+    tracing::info!("Backed up the library to {}", path.display());
+    Ok(BackupInfo {
+        path,
+        size_bytes,
+        created_at,
+        schema_version,
+    })
+}
+
+/// Checks that `path` is an intact backup this build can restore
+pub async fn verify_backup(path: &Path) -> Result<BackupInfo, AppError> {
+    let metadata = fs::metadata(path)
+        .map_err(|e| AppError::database(format!("Cannot read the backup {}", path.display()), e))?;
+    let created_at = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|since| Timestamp::from_millis(since.as_millis() as i64))
+        .unwrap_or_else(|| Timestamp::from_millis(0));
+
+    let config = DatabaseConfig::new(path.to_string_lossy())
+        .with_read_only(true)
+        .with_max_connections(1);
+    let pool = connect(config).await?;
+    let checked = async {
+        verify_integrity(&pool).await?;
+        schema_version(&pool).await
+    }
+    .await;
+    pool.close().await;
+    let schema_version = checked?;
+
+    if schema_version > CURRENT_VERSION {
+        return Err(AppError::MigrationFailed {
+            version: schema_version.to_string(),
+            reason: format!(
+                "the backup is newer than schema version {} this version supports",
+                CURRENT_VERSION
+            ),
+        });
+    }
+
+    Ok(BackupInfo {
+        path: path.to_path_buf(),
+        size_bytes: metadata.len(),
+        created_at,
+        schema_version,
+    })
+}
+
+/// Replaces the database at `db_path` with the backup at `src`
+///
+/// The backup is verified first and the live database is left alone if it
+/// fails. The database must not be open while it is restored; an older
+/// backup is migrated the next time it is opened.
+pub async fn restore_backup(src: &Path, db_path: &Path) -> Result<BackupInfo, AppError> {
+    let info = verify_backup(src).await?;
+
+    // Copy next to the database first, so the swap itself cannot half-fail
+    let folder = db_path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let mut staged = tempfile::NamedTempFile::new_in(folder)
+        .map_err(|e| AppError::database("Failed to stage the backup", e))?;
+    let mut backup =
+        fs::File::open(src).map_err(|e| AppError::database("Failed to open the backup", e))?;
+    std::io::copy(&mut backup, staged.as_file_mut())
+        .and_then(|_| staged.as_file().sync_all())
+        .map_err(|e| AppError::database("Failed to copy the backup", e))?;
+
+    // A leftover write-ahead log would be replayed onto the restored file
+    for suffix in ["-wal", "-shm"] {
+        let mut journal = db_path.as_os_str().to_owned();
+        journal.push(suffix);
+        match fs::remove_file(PathBuf::from(journal)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(AppError::database("Failed to remove the old journal", e));
+            }
+            _ => {}
+        }
+    }
+    staged
+        .persist(db_path)
+        .map_err(|e| AppError::database("Failed to replace the database", e.error))?;
+
+    tracing::info!(
+        "Restored {} from {}",
+        db_path.display(),
+        info.path.display()
+    );
+    Ok(info)
+}
+
+/// Deletes all but the newest `keep` backups in `dir`, returning the paths
+/// removed
+///
+/// Only files named by [`create_backup`] are considered.
+pub fn prune_backups(dir: &Path, keep: usize) -> Result<Vec<PathBuf>, AppError> {
+    let entries = fs::read_dir(dir).map_err(|e| AppError::database("Failed to list backups", e))?;
+    let mut backups = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| is_backup_name(path))
+        .collect::<Vec<_>>();
+    // Names sort by the time they were taken
+    backups.sort_unstable_by(|a, b| b.cmp(a));
+
+    let stale = backups.split_off(keep.min(backups.len()));
+    for path in &stale {
+        fs::remove_file(path)
+            .map_err(|e| AppError::database(format!("Failed to delete {}", path.display()), e))?;
+    }
+    Ok(stale)
+}
+
+/// File name for a backup taken at `at`, e.g. `storystream-20250314-093000-125.db`
+///
+/// The time is UTC, so names keep sorting by age when local clocks fall back.
+fn backup_file_name(at: Timestamp) -> String {
+    let utc = Utc
+        .timestamp_millis_opt(at.as_millis())
+        .single()
+        .unwrap_or_else(Utc::now);
+    format!(
+        "{}{}.{}",
+        BACKUP_PREFIX,
+        utc.format("%Y%m%d-%H%M%S-%3f"),
+        BACKUP_EXTENSION
+    )
+}
+
+fn is_backup_name(path: &Path) -> bool {
+    path.is_file()
+        && path.extension().is_some_and(|ext| ext == BACKUP_EXTENSION)
+        && path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with(BACKUP_PREFIX))
+}
+
+/// Newest migration applied to the database
+async fn schema_version(pool: &DbPool) -> Result<i64, AppError> {
+    sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM schema_migrations")
+        .fetch_one(pool)
+        .await
+        .map_err(|e| AppError::database("Failed to read the schema version", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations::run_migrations;
+    use crate::queries::books::{create_book, list_books};
+    use storystream_core::{Book, Duration};
+    use tempfile::TempDir;
+
+    /// An on-disk library, as an in-memory one backs up into memory
+    async fn library_with_book(dir: &Path) -> DbPool {
+        let path = dir.join("source.db");
+        let pool = connect(DatabaseConfig::new(path.to_string_lossy()))
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+        let book = Book::new(
+            "Backed Up".to_string(),
+            PathBuf::from("/test/backed_up.mp3"),
+            1_000_000,
+            Duration::from_seconds(3600),
+        );
+        create_book(&pool, &book).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_backup_and_restore() {
+        let dir = TempDir::new().unwrap();
+        let pool = library_with_book(dir.path()).await;
+
+        let info = create_backup(&pool, dir.path()).await.unwrap();
+        assert!(info.path.starts_with(dir.path()));
+        assert!(info.size_bytes > 0);
+        assert_eq!(info.schema_version, CURRENT_VERSION);
+        // The same file is never overwritten
+        assert!(create_backup(&pool, &info.path).await.is_err());
+
+        let db_path = dir.path().join("library.db");
+        let restored = restore_backup(&info.path, &db_path).await.unwrap();
+        assert_eq!(restored.schema_version, CURRENT_VERSION);
+
+        let live = connect(DatabaseConfig::new(db_path.to_string_lossy()))
+            .await
+            .unwrap();
+        run_migrations(&live).await.unwrap();
+        let books = list_books(&live).await.unwrap();
+        assert_eq!(books.len(), 1);
+        assert_eq!(books[0].title, "Backed Up");
+    }
+
+    #[tokio::test]
+    async fn test_restore_refuses_bad_backups() {
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("library.db");
+        fs::write(&db_path, b"live").unwrap();
+
+        let garbage = dir.path().join("garbage.db");
+        fs::write(&garbage, vec![7u8; 8192]).unwrap();
+        assert!(restore_backup(&garbage, &db_path).await.is_err());
+
+        // A backup from a newer version of the app
+        let pool = library_with_book(dir.path()).await;
+        let newer = dir.path().join("newer.db");
+        create_backup(&pool, &newer).await.unwrap();
+        let backup = connect(DatabaseConfig::new(newer.to_string_lossy()))
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO schema_migrations (version) VALUES (?)")
+            .bind(CURRENT_VERSION + 1)
+            .execute(&backup)
+            .await
+            .unwrap();
+        backup.close().await;
+        assert!(matches!(
+            restore_backup(&newer, &db_path).await,
+            Err(AppError::MigrationFailed { .. })
+        ));
+
+        assert_eq!(fs::read(&db_path).unwrap(), b"live");
+    }
+
+    #[test]
+    fn test_prune_backups() {
+        let dir = TempDir::new().unwrap();
+        let names = [
+            "storystream-20250101-080000-000.db",
+            "storystream-20250102-080000-000.db",
+            "storystream-20250103-080000-000.db",
+            "library.db",
+        ];
+        for name in names {
+            fs::write(dir.path().join(name), b"").unwrap();
+        }
+
+        let removed = prune_backups(dir.path(), 2).unwrap();
+        assert_eq!(removed, vec![dir.path().join(names[0])]);
+        assert!(dir.path().join(names[1]).exists());
+        assert!(dir.path().join(names[2]).exists());
+        assert!(dir.path().join("library.db").exists());
+
+        assert!(prune_backups(dir.path(), 5).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_backup_names_are_utc() {
+        // 01:30 UTC on the day Europe falls back, then the hour after
+        let first = Timestamp::from_millis(1_761_442_200_000);
+        let second = Timestamp::from_millis(first.as_millis() + 3_600_000);
+        assert_eq!(
+            backup_file_name(first),
+            "storystream-20251026-013000-000.db"
+        );
+        assert!(backup_file_name(first) < backup_file_name(second));
+    }
+}
@@ -2,6 +2,7 @@
 
 use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous};
 use sqlx::{Pool, Sqlite};
+use std::fs::OpenOptions;
 use std::path::Path;
 use std::str::FromStr;
 use storystream_core::AppError;
@@ -20,6 +21,12 @@ pub struct DatabaseConfig {
     pub enable_wal: bool,
     /// Create database if it doesn't exist
     pub create_if_missing: bool,
+    /// Open without changing anything; every write fails with
+    /// [`AppError::DatabaseReadOnly`]
+    ///
+    /// [`connect`] also opens read-only when the file or its folder is not
+    /// writable, such as on a read-only network share.
+    pub read_only: bool,
 }
 
 impl Default for DatabaseConfig {
@@ -29,6 +36,7 @@ impl Default for DatabaseConfig {
             max_connections: 10,
             enable_wal: true,
             create_if_missing: true,
+            read_only: false,
         }
     }
 }
@@ -59,20 +67,37 @@ impl DatabaseConfig {
         self.create_if_missing = create;
         self
     }
+
+    /// Sets whether to open the database read-only
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
 }
 
 /// Establishes a connection pool to the database
 pub async fn connect(config: DatabaseConfig) -> Result<DbPool, AppError> {
+    let read_only = config.read_only || !is_writable(Path::new(&config.path));
+    if read_only && !config.read_only {
+        tracing::info!("{} is not writable, opening it read-only", config.path);
+    }
+
     // Build connection options
     let mut options = SqliteConnectOptions::from_str(&format!("sqlite:{}", config.path))
-        .map_err(|e| AppError::database("Invalid database path", e))?
-        .create_if_missing(config.create_if_missing);
-
-    // Configure WAL mode for better concurrency
-    if config.enable_wal {
-        options = options
-            .journal_mode(SqliteJournalMode::Wal)
-            .synchronous(SqliteSynchronous::Normal);
+        .map_err(|e| AppError::database("Invalid database path", e))?;
+
+    if read_only {
+        // query_only lets is_read_only tell from any connection
+        options = options.read_only(true).pragma("query_only", "ON");
+    } else {
+        options = options.create_if_missing(config.create_if_missing);
+
+        // Configure WAL mode for better concurrency
+        if config.enable_wal {
+            options = options
+                .journal_mode(SqliteJournalMode::Wal)
+                .synchronous(SqliteSynchronous::Normal);
+        }
     }
 
     // Create connection pool
@@ -91,6 +116,31 @@ pub async fn connect(config: DatabaseConfig) -> Result<DbPool, AppError> {
     Ok(pool)
 }
 
+/// Whether the pool refuses writes, as opened by [`connect`] for a
+/// read-only configuration or an unwritable file
+pub async fn is_read_only(pool: &DbPool) -> Result<bool, AppError> {
+    let query_only: i64 = sqlx::query_scalar("PRAGMA query_only")
+        .fetch_one(pool)
+        .await
+        .map_err(|e| AppError::database("Failed to check read-only mode", e))?;
+    Ok(query_only != 0)
+}
+
+/// Whether the database at `path` and the folder SQLite keeps its journal in
+/// can be written
+///
+/// A database that does not exist yet counts as writable, so it gets created.
+fn is_writable(path: &Path) -> bool {
+    if !path.exists() {
+        return true;
+    }
+    let folder = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    OpenOptions::new().write(true).open(path).is_ok() && tempfile::tempfile_in(folder).is_ok()
+}
+
 /// Creates an in-memory database for testing
 #[cfg(test)]
 pub async fn create_test_db() -> Result<DbPool, AppError> {
@@ -203,6 +253,44 @@ mod tests {
         assert!(!database_exists("/nonexistent/path/to/db.sqlite"));
     }
 
+    #[tokio::test]
+    async fn test_read_only_database() {
+        use crate::migrations::run_migrations;
+        use crate::queries::books::{create_book, list_books};
+        use std::path::PathBuf;
+        use storystream_core::{Book, Duration};
+
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().to_str().unwrap().to_string();
+        let book = |title: &str| {
+            Book::new(
+                title.to_string(),
+                PathBuf::from(format!("/books/{}.mp3", title)),
+                1000,
+                Duration::from_seconds(60),
+            )
+        };
+
+        let pool = connect(DatabaseConfig::new(path.clone())).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        create_book(&pool, &book("Dune")).await.unwrap();
+        assert!(!is_read_only(&pool).await.unwrap());
+        close(pool).await;
+
+        let pool = connect(DatabaseConfig::new(path).with_read_only(true))
+            .await
+            .unwrap();
+        assert!(is_read_only(&pool).await.unwrap());
+        // An up-to-date library needs no migrating
+        run_migrations(&pool).await.unwrap();
+        assert_eq!(list_books(&pool).await.unwrap().len(), 1);
+
+        let err = create_book(&pool, &book("Emma")).await.unwrap_err();
+        assert!(matches!(err, AppError::DatabaseReadOnly { .. }));
+        assert_eq!(list_books(&pool).await.unwrap().len(), 1);
+        close(pool).await;
+    }
+
     #[tokio::test]
     async fn test_config_default() {
         let config = DatabaseConfig::default();
@@ -211,5 +299,6 @@ mod tests {
         assert_eq!(config.max_connections, 10);
         assert!(config.enable_wal);
         assert!(config.create_if_missing);
+        assert!(!config.read_only);
     }
 }
@@ -0,0 +1,414 @@
+//! Library export and import for backups and moving between devices
+
+use crate::migrations::run_migrations;
+use crate::queries::{bookmarks, books, playback};
+use crate::DbPool;
+use serde::{Deserialize, Serialize};
+use storystream_core::{AppError, Book, Bookmark, PlaybackState, Timestamp};
+
+/// Version of the export file layout written by [`export_library`]
+pub const EXPORT_FORMAT_VERSION: u32 = 1;
+
+/// A portable snapshot of the library
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryExport {
+    pub format_version: u32,
+    pub exported_at: Timestamp,
+    pub books: Vec<Book>,
+    #[serde(default)]
+    pub positions: Vec<PlaybackState>,
+    #[serde(default)]
+    pub bookmarks: Vec<Bookmark>,
+}
+
+/// What to include in an export besides the books themselves
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExportOptions {
+    pub include_positions: bool,
+    pub include_bookmarks: bool,
+}
+
+/// How to handle imported books that already exist in the library
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportStrategy {
+    /// Keep the existing book untouched
+    #[default]
+    Skip,
+    /// Replace the existing book with the imported one
+    Overwrite,
+    /// Fill in missing metadata and keep the furthest listening progress
+    Merge,
+}
+
+/// Book counts for an import, either planned or applied
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ImportCounts {
+    pub added: usize,
+    pub updated: usize,
+    pub skipped: usize,
+}
+
+/// Result of applying an import
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ImportSummary {
+    pub books: ImportCounts,
+    pub positions: usize,
+    pub bookmarks: usize,
+}
+
+/// What importing a single book will do
+enum BookAction {
+    Add,
+    Update(Box<Book>),
+    Skip,
+}
+
+/// Builds an export of every book in the library
+pub async fn export_library(
+    pool: &DbPool,
+    options: ExportOptions,
+) -> Result<LibraryExport, AppError> {
+    let books = books::list_books(pool).await?;
+
+    let positions = if options.include_positions {
+        playback::list_playback_states(pool).await?
+    } else {
+        Vec::new()
+    };
+
+    let bookmarks = if options.include_bookmarks {
+        bookmarks::list_bookmarks(pool).await?
+    } else {
+        Vec::new()
+    };
+
+    Ok(LibraryExport {
+        format_version: EXPORT_FORMAT_VERSION,
+        exported_at: Timestamp::now(),
+        books,
+        positions,
+        bookmarks,
+    })
+}
+
+/// Counts what [`import_library`] would do without changing anything
+pub async fn preview_import(
+    pool: &DbPool,
+    export: &LibraryExport,
+    strategy: ImportStrategy,
+) -> Result<ImportCounts, AppError> {
+    check_version(export)?;
+    run_migrations(pool).await?;
+
+    let mut counts = ImportCounts::default();
+    for book in &export.books {
+        match plan_book(pool, book, strategy).await? {
+            BookAction::Add => counts.added += 1,
+            BookAction::Update(_) => counts.updated += 1,
+            BookAction::Skip => counts.skipped += 1,
+        }
+    }
+    Ok(counts)
+}
+
+/// Imports an export into the library
+///
+/// Runs migrations first so an empty database can be restored into directly.
+/// Positions and bookmarks are only restored for books that were added or
+/// updated; bookmarks that already exist are left alone.
+pub async fn import_library(
+    pool: &DbPool,
+    export: &LibraryExport,
+    strategy: ImportStrategy,
+) -> Result<ImportSummary, AppError> {
+    check_version(export)?;
+    run_migrations(pool).await?;
+
+    let mut summary = ImportSummary::default();
+    let mut touched = std::collections::HashSet::new();
+
+    for book in &export.books {
+        match plan_book(pool, book, strategy).await? {
+            BookAction::Add => {
+                books::create_book(pool, book).await?;
+                summary.books.added += 1;
+                touched.insert(book.id);
+            }
+            BookAction::Update(updated) => {
+                books::update_book(pool, &updated).await?;
+                summary.books.updated += 1;
+                touched.insert(book.id);
+            }
+            BookAction::Skip => summary.books.skipped += 1,
+        }
+    }
+
+    for position in export
+        .positions
+        .iter()
+        .filter(|p| touched.contains(&p.book_id))
+    {
+        if strategy == ImportStrategy::Merge {
+            if let Ok(existing) = playback::get_playback_state(pool, position.book_id).await {
+                if existing.last_updated.as_millis() >= position.last_updated.as_millis() {
+                    continue;
+                }
+            }
+        }
+        playback::create_playback_state(pool, position).await?;
+        summary.positions += 1;
+    }
+
+    for bookmark in export
+        .bookmarks
+        .iter()
+        .filter(|b| touched.contains(&b.book_id))
+    {
+        if bookmarks::get_bookmark(pool, bookmark.id).await.is_err() {
+            bookmarks::create_bookmark(pool, bookmark).await?;
+            summary.bookmarks += 1;
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Saves playback positions and adds bookmarks in one transaction
+///
+/// Used when listening data comes from somewhere other than a StoryStream
+/// export: either everything is written or nothing is. Positions replace the
+/// whole stored state, so callers should start from the existing state to
+/// keep speed and volume settings.
+pub async fn restore_progress(
+    pool: &DbPool,
+    positions: &[PlaybackState],
+    bookmarks: &[Bookmark],
+) -> Result<(), AppError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| AppError::database("Failed to begin transaction", e))?;
+
+    for position in positions {
+        playback::save_playback_state(&mut *tx, position).await?;
+    }
+    for bookmark in bookmarks {
+        bookmarks::insert_bookmark(&mut *tx, bookmark).await?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::database("Failed to commit transaction", e))?;
+
+    Ok(())
+}
+
+fn check_version(export: &LibraryExport) -> Result<(), AppError> {
+    if export.format_version > EXPORT_FORMAT_VERSION {
+        return Err(AppError::InvalidArgument {
+            argument: "format_version".to_string(),
+            reason: format!(
+                "export version {} is newer than supported version {}",
+                export.format_version, EXPORT_FORMAT_VERSION
+            ),
+        });
+    }
+    Ok(())
+}
+
+async fn plan_book(
+    pool: &DbPool,
+    book: &Book,
+    strategy: ImportStrategy,
+) -> Result<BookAction, AppError> {
+    let existing = match books::get_book(pool, book.id).await {
+        Ok(existing) => existing,
+        Err(AppError::RecordNotFound { .. }) => return Ok(BookAction::Add),
+        Err(e) => return Err(e),
+    };
+
+    Ok(match strategy {
+        ImportStrategy::Skip => BookAction::Skip,
+        ImportStrategy::Overwrite => BookAction::Update(Box::new(book.clone())),
+        ImportStrategy::Merge => {
+            let merged = merge_book(&existing, book);
+            if same_book(&merged, &existing) {
+                BookAction::Skip
+            } else {
+                BookAction::Update(Box::new(merged))
+            }
+        }
+    })
+}
+
+/// Combines an existing book with an imported copy of it
+///
+/// Existing metadata wins; the import only fills gaps. Listening history keeps
+/// whichever side has more.
+fn merge_book(existing: &Book, imported: &Book) -> Book {
+    let mut merged = existing.clone();
+
+    merged.author = merged.author.or_else(|| imported.author.clone());
+    merged.narrator = merged.narrator.or_else(|| imported.narrator.clone());
+    merged.series = merged.series.or_else(|| imported.series.clone());
+    merged.series_position = merged.series_position.or(imported.series_position);
+    merged.description = merged.description.or_else(|| imported.description.clone());
+    merged.language = merged.language.or_else(|| imported.language.clone());
+    merged.publisher = merged.publisher.or_else(|| imported.publisher.clone());
+    merged.published_date = merged
+        .published_date
+        .or_else(|| imported.published_date.clone());
+    merged.isbn = merged.isbn.or_else(|| imported.isbn.clone());
+    merged.genre = merged.genre.or_else(|| imported.genre.clone());
+    merged.cover_art_path = merged
+        .cover_art_path
+        .or_else(|| imported.cover_art_path.clone());
+    merged.rating = merged.rating.or(imported.rating);
+
+    merged.play_count = merged.play_count.max(imported.play_count);
+    merged.is_favorite |= imported.is_favorite;
+    merged.last_played = match (existing.last_played, imported.last_played) {
+        (Some(a), Some(b)) if b.as_millis() > a.as_millis() => Some(b),
+        (None, b) => b,
+        (a, _) => a,
+    };
+
+    for tag in &imported.tags {
+        if !merged.tags.contains(tag) {
+            merged.tags.push(tag.clone());
+        }
+    }
+
+    merged
+}
+
+fn same_book(a: &Book, b: &Book) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::create_test_db;
+    use std::path::PathBuf;
+    use storystream_core::Duration;
+
+    fn sample_book(title: &str) -> Book {
+        Book::new(
+            title.to_string(),
+            PathBuf::from(format!("/test/{}.mp3", title)),
+            1000,
+            Duration::from_seconds(3600),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_import_strategies() {
+        let pool = create_test_db().await.unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        let mut existing = sample_book("Existing");
+        existing.play_count = 2;
+        books::create_book(&pool, &existing).await.unwrap();
+
+        let mut imported = existing.clone();
+        imported.title = "Renamed".to_string();
+        imported.author = Some("Author".to_string());
+        imported.play_count = 5;
+
+        let export = LibraryExport {
+            format_version: EXPORT_FORMAT_VERSION,
+            exported_at: Timestamp::now(),
+            books: vec![imported, sample_book("New")],
+            positions: Vec::new(),
+            bookmarks: Vec::new(),
+        };
+
+        let preview = preview_import(&pool, &export, ImportStrategy::Skip)
+            .await
+            .unwrap();
+        assert_eq!(
+            preview,
+            ImportCounts {
+                added: 1,
+                updated: 0,
+                skipped: 1
+            }
+        );
+
+        let summary = import_library(&pool, &export, ImportStrategy::Merge)
+            .await
+            .unwrap();
+        assert_eq!(summary.books.added, 1);
+        assert_eq!(summary.books.updated, 1);
+
+        let merged = books::get_book(&pool, existing.id).await.unwrap();
+        assert_eq!(merged.title, "Existing");
+        assert_eq!(merged.author.as_deref(), Some("Author"));
+        assert_eq!(merged.play_count, 5);
+
+        // Merging again changes nothing
+        let again = preview_import(&pool, &export, ImportStrategy::Merge)
+            .await
+            .unwrap();
+        assert_eq!(again.skipped, 2);
+
+        import_library(&pool, &export, ImportStrategy::Overwrite)
+            .await
+            .unwrap();
+        let overwritten = books::get_book(&pool, existing.id).await.unwrap();
+        assert_eq!(overwritten.title, "Renamed");
+    }
+
+    #[tokio::test]
+    async fn test_restore_progress_is_all_or_nothing() {
+        let pool = create_test_db().await.unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        let book = sample_book("Restored");
+        books::create_book(&pool, &book).await.unwrap();
+
+        let mut position = PlaybackState::new(book.id);
+        position.set_position(Duration::from_seconds(600));
+        let bookmark = Bookmark::new(book.id, Duration::from_seconds(60));
+        restore_progress(&pool, &[position], std::slice::from_ref(&bookmark))
+            .await
+            .unwrap();
+
+        let saved = playback::get_playback_state(&pool, book.id).await.unwrap();
+        assert_eq!(saved.position, Duration::from_seconds(600));
+        assert_eq!(
+            bookmarks::get_book_bookmarks(&pool, book.id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // The duplicate bookmark fails the batch, so the position is not saved either
+        let mut later = PlaybackState::new(book.id);
+        later.set_position(Duration::from_seconds(900));
+        assert!(restore_progress(&pool, &[later], &[bookmark])
+            .await
+            .is_err());
+        let saved = playback::get_playback_state(&pool, book.id).await.unwrap();
+        assert_eq!(saved.position, Duration::from_seconds(600));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_newer_format() {
+        let pool = create_test_db().await.unwrap();
+        let export = LibraryExport {
+            format_version: EXPORT_FORMAT_VERSION + 1,
+            exported_at: Timestamp::now(),
+            books: Vec::new(),
+            positions: Vec::new(),
+            bookmarks: Vec::new(),
+        };
+
+        assert!(import_library(&pool, &export, ImportStrategy::Skip)
+            .await
+            .is_err());
+    }
+}
@@ -3,13 +3,20 @@
 //! This crate provides database operations for the StoryStream audiobook player.
 //! It uses SQLite with sqlx for type-safe database queries.
 
+pub mod backup;
 pub mod connection;
+pub mod export;
+pub mod maintenance;
 pub mod migrations;
 pub mod queries;
 pub mod search;
 
+pub use backup::{create_backup, prune_backups, restore_backup, verify_backup, BackupInfo};
 pub use connection::DbPool;
-pub use migrations::{current_version, optimize, run_migrations, verify_integrity};
+pub use maintenance::{MaintenanceReport, MaintenanceStep};
+pub use migrations::{
+    current_version, optimize, optimize_if_due, run_migrations, verify_integrity,
+};
 
 #[cfg(test)]
 mod tests {
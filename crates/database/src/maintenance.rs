@@ -0,0 +1,359 @@
+//! Consistency checks, repairs and routine upkeep for an existing database
+
+use crate::DbPool;
+use serde::Serialize;
+use std::time::Instant;
+use storystream_core::{AppError, Timestamp};
+
+/// Full-text search tables kept in sync with their content tables by triggers
+const FTS_TABLES: [&str; 3] = ["books_fts", "chapters_fts", "bookmarks_fts"];
+
+/// Share of the file left as free pages above which a full `VACUUM` runs
+const VACUUM_FREE_RATIO: f64 = 0.25;
+
+/// `app_metadata` key holding when maintenance last ran
+const LAST_MAINTENANCE_KEY: &str = "last_maintenance";
+
+/// One step of a maintenance run and how long it took
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MaintenanceStep {
+    pub name: &'static str,
+    /// What the step found or did, such as "reclaimed 2.1 MB"
+    pub detail: String,
+    pub elapsed_ms: u64,
+}
+
+/// What [`crate::optimize`] did
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct MaintenanceReport {
+    pub steps: Vec<MaintenanceStep>,
+    /// Bytes the database file shrank by
+    pub reclaimed_bytes: u64,
+}
+
+impl MaintenanceReport {
+    /// Time the whole run took
+    pub fn elapsed_ms(&self) -> u64 {
+        self.steps.iter().map(|step| step.elapsed_ms).sum()
+    }
+
+    pub(crate) fn push(&mut self, name: &'static str, started: Instant, detail: String) {
+        tracing::debug!("Maintenance step {}: {}", name, detail);
+        self.steps.push(MaintenanceStep {
+            name,
+            detail,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        });
+    }
+}
+
+/// Rows whose parent row no longer exists
+///
+/// Foreign keys are only enforced on connections that enabled them, so rows
+/// written by other tools or older builds can outlive their parent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct OrphanCounts {
+    pub chapters: u64,
+    pub bookmarks: u64,
+    pub playback_states: u64,
+    pub playlist_items: u64,
+    pub podcast_episodes: u64,
+}
+
+impl OrphanCounts {
+    /// Total number of orphaned rows
+    pub fn total(&self) -> u64 {
+        self.chapters
+            + self.bookmarks
+            + self.playback_states
+            + self.playlist_items
+            + self.podcast_episodes
+    }
+}
+
+/// `(table, condition)` pairs selecting orphaned rows
+const ORPHAN_QUERIES: [(&str, &str); 5] = [
+    ("chapters", "book_id NOT IN (SELECT id FROM books)"),
+    ("bookmarks", "book_id NOT IN (SELECT id FROM books)"),
+    ("playback_state", "book_id NOT IN (SELECT id FROM books)"),
+    (
+        "playlist_items",
+        "book_id NOT IN (SELECT id FROM books) OR playlist_id NOT IN (SELECT id FROM playlists)",
+    ),
+    (
+        "podcast_episodes",
+        "podcast_id NOT IN (SELECT id FROM podcasts)",
+    ),
+];
+
+fn counts_from(values: [u64; 5]) -> OrphanCounts {
+    OrphanCounts {
+        chapters: values[0],
+        bookmarks: values[1],
+        playback_states: values[2],
+        playlist_items: values[3],
+        podcast_episodes: values[4],
+    }
+}
+
+/// Counts rows that reference a missing book, playlist or podcast
+pub async fn find_orphans(pool: &DbPool) -> Result<OrphanCounts, AppError> {
+    let mut values = [0u64; 5];
+
+    for (value, (table, condition)) in values.iter_mut().zip(ORPHAN_QUERIES) {
+        let count: i64 = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM {} WHERE {}",
+            table, condition
+        ))
+        .fetch_one(pool)
+        .await
+        .map_err(|e| AppError::database(format!("Failed to count orphans in {}", table), e))?;
+        *value = count as u64;
+    }
+
+    Ok(counts_from(values))
+}
+
+/// Deletes orphaned rows, returning how many were removed from each table
+pub async fn purge_orphans(pool: &DbPool) -> Result<OrphanCounts, AppError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| AppError::database("Failed to begin transaction", e))?;
+    let mut values = [0u64; 5];
+
+    for (value, (table, condition)) in values.iter_mut().zip(ORPHAN_QUERIES) {
+        let result = sqlx::query(&format!("DELETE FROM {} WHERE {}", table, condition))
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::database(format!("Failed to purge orphans in {}", table), e))?;
+        *value = result.rows_affected();
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::database("Failed to commit orphan purge", e))?;
+
+    Ok(counts_from(values))
+}
+
+/// Returns the full-text search tables whose index disagrees with their content
+pub async fn check_search_index(pool: &DbPool) -> Result<Vec<&'static str>, AppError> {
+    let mut damaged = Vec::new();
+
+    for table in FTS_TABLES {
+        let check = format!("INSERT INTO {0}({0}) VALUES('integrity-check')", table);
+        if sqlx::query(&check).execute(pool).await.is_err() {
+            damaged.push(table);
+        }
+    }
+
+    Ok(damaged)
+}
+
+/// Rebuilds every full-text search index from its content table
+pub async fn rebuild_search_index(pool: &DbPool) -> Result<(), AppError> {
+    for table in FTS_TABLES {
+        sqlx::query(&format!("INSERT INTO {0}({0}) VALUES('rebuild')", table))
+            .execute(pool)
+            .await
+            .map_err(|e| AppError::database(format!("Failed to rebuild {}", table), e))?;
+    }
+
+    Ok(())
+}
+
+/// Returns free pages to the filesystem, returning what was done and the
+/// bytes reclaimed
+///
+/// Databases created with incremental auto-vacuum give back their free pages
+/// cheaply; others are rebuilt with `VACUUM` once enough of the file is free.
+pub(crate) async fn reclaim_space(pool: &DbPool) -> Result<(String, u64), AppError> {
+    let page_size = pragma(pool, "page_size").await?;
+    let pages_before = pragma(pool, "page_count").await?;
+    let free_pages = pragma(pool, "freelist_count").await?;
+    if free_pages == 0 {
+        return Ok(("no free pages".to_string(), 0));
+    }
+
+    let incremental = pragma(pool, "auto_vacuum").await? == 2;
+    let ratio = free_pages as f64 / pages_before.max(1) as f64;
+    let (statement, how) = if incremental {
+        ("PRAGMA incremental_vacuum", "incremental vacuum")
+    } else if ratio > VACUUM_FREE_RATIO {
+        ("VACUUM", "vacuum")
+    } else {
+        return Ok((
+            format!(
+                "{} free page(s), {:.0}% of the file",
+                free_pages,
+                ratio * 100.0
+            ),
+            0,
+        ));
+    };
+    sqlx::query(statement)
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::database(format!("Failed to run {}", how), e))?;
+
+    let pages_after = pragma(pool, "page_count").await?;
+    let reclaimed = (pages_before - pages_after).max(0) as u64 * page_size as u64;
+    Ok((format!("{} reclaimed {} bytes", how, reclaimed), reclaimed))
+}
+
+/// Rebuilds the search index if it disagrees with its tables
+pub(crate) async fn repair_search_index(pool: &DbPool) -> Result<String, AppError> {
+    let indexed: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?")
+            .bind(FTS_TABLES[0])
+            .fetch_one(pool)
+            .await
+            .map_err(|e| AppError::database("Failed to look for the search index", e))?;
+    // SQLite built without FTS5 has no index to repair
+    if indexed == 0 {
+        return Ok("no full-text index".to_string());
+    }
+
+    let damaged = check_search_index(pool).await?;
+    if damaged.is_empty() {
+        return Ok("consistent".to_string());
+    }
+    rebuild_search_index(pool).await?;
+    Ok(format!(
+        "rebuilt after {} failed its check",
+        damaged.join(", ")
+    ))
+}
+
+/// Copies the write-ahead log into the database file and empties it
+pub(crate) async fn checkpoint(pool: &DbPool) -> Result<String, AppError> {
+    let (busy, log_pages, written): (i64, i64, i64) =
+        sqlx::query_as("PRAGMA wal_checkpoint(TRUNCATE)")
+            .fetch_one(pool)
+            .await
+            .map_err(|e| AppError::database("Failed to checkpoint the write-ahead log", e))?;
+
+    Ok(if log_pages < 0 {
+        "not in WAL mode".to_string()
+    } else if busy != 0 {
+        format!(
+            "{} of {} page(s) written, readers kept the rest",
+            written, log_pages
+        )
+    } else {
+        format!("{} page(s) written", written)
+    })
+}
+
+/// When maintenance last ran on this database
+pub async fn last_maintenance(pool: &DbPool) -> Result<Option<Timestamp>, AppError> {
+    let value: Option<String> = sqlx::query_scalar("SELECT value FROM app_metadata WHERE key = ?")
+        .bind(LAST_MAINTENANCE_KEY)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| AppError::database("Failed to read when maintenance last ran", e))?;
+
+    Ok(value
+        .and_then(|millis| millis.parse().ok())
+        .map(Timestamp::from_millis))
+}
+
+/// Whether at least `interval_days` have passed since maintenance last ran,
+/// or it never has
+pub async fn maintenance_due(pool: &DbPool, interval_days: u32) -> Result<bool, AppError> {
+    let interval_ms = i64::from(interval_days) * 24 * 60 * 60 * 1000;
+    Ok(match last_maintenance(pool).await? {
+        Some(last) => Timestamp::now().as_millis() - last.as_millis() >= interval_ms,
+        None => true,
+    })
+}
+
+/// Remembers that maintenance ran at `at`
+pub(crate) async fn record_maintenance(pool: &DbPool, at: Timestamp) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        INSERT INTO app_metadata (key, value, updated_at)
+        VALUES (?, ?, ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(LAST_MAINTENANCE_KEY)
+    .bind(at.as_millis().to_string())
+    .bind(Timestamp::now().as_millis())
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to record the maintenance run", e))?;
+
+    Ok(())
+}
+
+async fn pragma(pool: &DbPool, name: &str) -> Result<i64, AppError> {
+    sqlx::query_scalar(&format!("PRAGMA {}", name))
+        .fetch_one(pool)
+        .await
+        .map_err(|e| AppError::database(format!("Failed to read {}", name), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::create_test_db;
+    use crate::migrations::run_migrations;
+    use crate::queries::bookmarks::create_bookmark;
+    use crate::queries::books::create_book;
+    use std::path::PathBuf;
+    use storystream_core::{Book, Bookmark, Duration};
+
+    async fn orphaned_bookmark_db() -> DbPool {
+        let pool = create_test_db().await.unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        let book = Book::new(
+            "Orphaned".to_string(),
+            PathBuf::from("/test/orphaned.mp3"),
+            1000,
+            Duration::from_seconds(60),
+        );
+        create_book(&pool, &book).await.unwrap();
+        create_bookmark(&pool, &Bookmark::new(book.id, Duration::from_seconds(5)))
+            .await
+            .unwrap();
+
+        // Remove the parent without cascading, as a connection without
+        // foreign keys enabled would
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM books")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_find_and_purge_orphans() {
+        let pool = orphaned_bookmark_db().await;
+
+        let found = find_orphans(&pool).await.unwrap();
+        assert_eq!(found.bookmarks, 1);
+        assert_eq!(found.total(), 1);
+
+        let purged = purge_orphans(&pool).await.unwrap();
+        assert_eq!(purged, found);
+        assert_eq!(find_orphans(&pool).await.unwrap().total(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_search_index_rebuild() {
+        let pool = create_test_db().await.unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        assert!(check_search_index(&pool).await.unwrap().is_empty());
+        rebuild_search_index(&pool).await.unwrap();
+        assert!(check_search_index(&pool).await.unwrap().is_empty());
+    }
+}
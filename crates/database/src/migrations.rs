@@ -1,7 +1,11 @@
 //! Database migrations
 
+use crate::connection::is_read_only;
+use crate::maintenance::{self, MaintenanceReport};
 use crate::DbPool;
-use storystream_core::AppError;
+use sqlx::SqliteConnection;
+use std::time::Instant;
+use storystream_core::{AppError, Timestamp};
 
 /// Migration 001: Initial schema
 const MIGRATION_001: &str = include_str!("../migrations/001_initial_schema.sql");
@@ -18,8 +22,59 @@ const MIGRATION_004: &str = include_str!("../migrations/004_add_indexes.sql");
 /// Migration 005: Populate FTS tables
 const MIGRATION_005: &str = include_str!("../migrations/005_populate_fts.sql");
 
+/// Migration 006: Feed subscription columns
+const MIGRATION_006: &str = include_str!("../migrations/006_feed_subscriptions.sql");
+
+/// Migration 007: Playlist session
+const MIGRATION_007: &str = include_str!("../migrations/007_playlist_session.sql");
+
+/// Migration 008: Subscription download policies
+const MIGRATION_008: &str = include_str!("../migrations/008_download_policy.sql");
+
+/// Migration 009: User-edited book metadata
+const MIGRATION_009: &str = include_str!("../migrations/009_user_edited_fields.sql");
+
+/// Migration 010: Listening sessions
+const MIGRATION_010: &str = include_str!("../migrations/010_listening_sessions.sql");
+
+/// Migration 011: Stored file hashes
+const MIGRATION_011: &str = include_str!("../migrations/011_file_hash.sql");
+
+/// Migration 012: Bookmark kinds
+const MIGRATION_012: &str = include_str!("../migrations/012_bookmark_kind.sql");
+
+/// Migration 013: Speed ramps
+const MIGRATION_013: &str = include_str!("../migrations/013_speed_ramp.sql");
+
+/// Migration 014: Episode books
+const MIGRATION_014: &str = include_str!("../migrations/014_episode_books.sql");
+
+/// Migration 015: Book search index
+const MIGRATION_015: &str = include_str!("../migrations/015_book_search_index.sql");
+
+/// Migration 016: Book genre
+const MIGRATION_016: &str = include_str!("../migrations/016_book_genre.sql");
+
+/// Migration 017: Listening session details
+const MIGRATION_017: &str = include_str!("../migrations/017_session_details.sql");
+
+/// Migration 018: App metadata
+const MIGRATION_018: &str = include_str!("../migrations/018_app_metadata.sql");
+
+/// Migration 019: Book loudness
+const MIGRATION_019: &str = include_str!("../migrations/019_loudness.sql");
+
+/// Migration 020: Replay gain
+const MIGRATION_020: &str = include_str!("../migrations/020_replay_gain.sql");
+
+/// Migration 021: Content fingerprints
+const MIGRATION_021: &str = include_str!("../migrations/021_content_hash.sql");
+
+/// Migration 022: File availability
+const MIGRATION_022: &str = include_str!("../migrations/022_file_availability.sql");
+
 /// Current database schema version
-pub const CURRENT_VERSION: i64 = 5;
+pub const CURRENT_VERSION: i64 = 22;
 
 /// Returns the current migration version
 pub fn current_version() -> i64 {
@@ -27,7 +82,22 @@ pub fn current_version() -> i64 {
 }
 
 /// Runs all pending migrations
+///
+/// Migrations run on a single pooled connection. Other connections that had
+/// already loaded the FTS tables can fail with "no such table" on their first
+/// write after a migration alters the schema. A read-only database cannot be
+/// migrated, so it must already be up to date.
 pub async fn run_migrations(pool: &DbPool) -> Result<(), AppError> {
+    if is_read_only(pool).await? {
+        return check_read_only_version(pool).await;
+    }
+
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|e| AppError::database("Failed to acquire migration connection", e))?;
+    let conn = &mut *conn;
+
     // Create migrations table if it doesn't exist
     sqlx::query(
         r#"
@@ -37,27 +107,67 @@ pub async fn run_migrations(pool: &DbPool) -> Result<(), AppError> {
         )
         "#,
     )
-    .execute(pool)
+    .execute(&mut *conn)
     .await
     .map_err(|e| AppError::database("Failed to create migrations table", e))?;
 
     // Run each migration
-    run_migration(pool, 1, MIGRATION_001).await?;
-    run_migration(pool, 2, MIGRATION_002).await?;
-    run_migration(pool, 3, MIGRATION_003).await?;
-    run_migration(pool, 4, MIGRATION_004).await?;
-    run_migration(pool, 5, MIGRATION_005).await?;
+    run_migration(conn, 1, MIGRATION_001).await?;
+    run_migration(conn, 2, MIGRATION_002).await?;
+    run_migration(conn, 3, MIGRATION_003).await?;
+    run_migration(conn, 4, MIGRATION_004).await?;
+    run_migration(conn, 5, MIGRATION_005).await?;
+    run_migration(conn, 6, MIGRATION_006).await?;
+    run_migration(conn, 7, MIGRATION_007).await?;
+    run_migration(conn, 8, MIGRATION_008).await?;
+    run_migration(conn, 9, MIGRATION_009).await?;
+    run_migration(conn, 10, MIGRATION_010).await?;
+    run_migration(conn, 11, MIGRATION_011).await?;
+    run_migration(conn, 12, MIGRATION_012).await?;
+    run_migration(conn, 13, MIGRATION_013).await?;
+    run_migration(conn, 14, MIGRATION_014).await?;
+    run_migration(conn, 15, MIGRATION_015).await?;
+    run_migration(conn, 16, MIGRATION_016).await?;
+    run_migration(conn, 17, MIGRATION_017).await?;
+    run_migration(conn, 18, MIGRATION_018).await?;
+    run_migration(conn, 19, MIGRATION_019).await?;
+    run_migration(conn, 20, MIGRATION_020).await?;
+    run_migration(conn, 21, MIGRATION_021).await?;
+    run_migration(conn, 22, MIGRATION_022).await?;
 
     Ok(())
 }
 
+/// Fails unless every migration has been applied to a read-only database
+async fn check_read_only_version(pool: &DbPool) -> Result<(), AppError> {
+    let version: i64 =
+        sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM schema_migrations")
+            .fetch_one(pool)
+            .await
+            .map_err(|e| AppError::database("Failed to read the schema version", e))?;
+    if version < CURRENT_VERSION {
+        return Err(AppError::DatabaseReadOnly {
+            operation: format!(
+                "cannot upgrade it from schema version {} to {}",
+                version, CURRENT_VERSION
+            ),
+        });
+    }
+    Ok(())
+}
+
 /// Runs a single migration if not already applied
-async fn run_migration(pool: &DbPool, version: i64, sql: &str) -> Result<(), AppError> {
+#[tracing::instrument(level = "debug", name = "migration", skip(conn, sql))]
+async fn run_migration(
+    conn: &mut SqliteConnection,
+    version: i64,
+    sql: &str,
+) -> Result<(), AppError> {
     // Check if migration already applied
     let applied: Option<i64> =
         sqlx::query_scalar("SELECT version FROM schema_migrations WHERE version = ?")
             .bind(version)
-            .fetch_optional(pool)
+            .fetch_optional(&mut *conn)
             .await
             .map_err(|e| AppError::database("Failed to check migration status", e))?;
 
@@ -67,9 +177,10 @@ async fn run_migration(pool: &DbPool, version: i64, sql: &str) -> Result<(), App
 
     // Execute migration
     sqlx::query(sql)
-        .execute(pool)
+        .execute(&mut *conn)
         .await
         .map_err(|e| AppError::database(&format!("Failed to run migration {}", version), e))?;
+    tracing::info!("Applied migration {}", version);
 
     Ok(())
 }
@@ -91,14 +202,60 @@ pub async fn verify_integrity(pool: &DbPool) -> Result<(), AppError> {
     Ok(())
 }
 
-/// Optimizes the database
-pub async fn optimize(pool: &DbPool) -> Result<(), AppError> {
+/// Runs routine maintenance on the database
+///
+/// Refreshes the query planner's statistics, gives free pages back to the
+/// filesystem, rebuilds the search index if it disagrees with its tables and
+/// checkpoints the write-ahead log. Each step is timed in the report, and the
+/// run is remembered for [`maintenance::maintenance_due`].
+pub async fn optimize(pool: &DbPool) -> Result<MaintenanceReport, AppError> {
+    let mut report = MaintenanceReport::default();
+
+    let started = Instant::now();
     sqlx::query("PRAGMA optimize")
         .execute(pool)
         .await
         .map_err(|e| AppError::database("Failed to optimize database", e))?;
+    report.push("optimize", started, "done".to_string());
 
-    Ok(())
+    let started = Instant::now();
+    sqlx::query("ANALYZE")
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::database("Failed to analyze database", e))?;
+    report.push("analyze", started, "statistics refreshed".to_string());
+
+    let started = Instant::now();
+    let (detail, reclaimed) = maintenance::reclaim_space(pool).await?;
+    report.reclaimed_bytes = reclaimed;
+    report.push("vacuum", started, detail);
+
+    let started = Instant::now();
+    let detail = maintenance::repair_search_index(pool).await?;
+    report.push("search index", started, detail);
+
+    let started = Instant::now();
+    let detail = maintenance::checkpoint(pool).await?;
+    report.push("checkpoint", started, detail);
+
+    maintenance::record_maintenance(pool, Timestamp::now()).await?;
+    tracing::info!(
+        "Database maintenance took {} ms, reclaiming {} bytes",
+        report.elapsed_ms(),
+        report.reclaimed_bytes
+    );
+    Ok(report)
+}
+
+/// Runs [`optimize`] if `interval_days` have passed since it last ran
+pub async fn optimize_if_due(
+    pool: &DbPool,
+    interval_days: u32,
+) -> Result<Option<MaintenanceReport>, AppError> {
+    if !maintenance::maintenance_due(pool, interval_days).await? {
+        return Ok(None);
+    }
+    optimize(pool).await.map(Some)
 }
 
 #[cfg(test)]
@@ -118,7 +275,10 @@ mod tests {
                 .await
                 .unwrap();
 
-        assert_eq!(versions, vec![1, 2, 3, 4, 5]);
+        assert_eq!(
+            versions,
+            vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22]
+        );
     }
 
     #[tokio::test]
@@ -133,7 +293,46 @@ mod tests {
     async fn test_optimize() {
         let pool = create_test_db().await.unwrap();
         run_migrations(&pool).await.unwrap();
+        assert!(maintenance::maintenance_due(&pool, 7).await.unwrap());
 
-        optimize(&pool).await.unwrap();
+        // Leave most of the file as free pages
+        sqlx::query("CREATE TABLE filler (data BLOB)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        for _ in 0..200 {
+            sqlx::query("INSERT INTO filler VALUES (zeroblob(8192))")
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        sqlx::query("DROP TABLE filler")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let report = optimize(&pool).await.unwrap();
+        let steps: Vec<&str> = report.steps.iter().map(|step| step.name).collect();
+        assert_eq!(
+            steps,
+            vec![
+                "optimize",
+                "analyze",
+                "vacuum",
+                "search index",
+                "checkpoint"
+            ]
+        );
+        assert!(report.reclaimed_bytes > 1_000_000);
+        assert_eq!(report.steps[3].detail, "consistent");
+
+        assert!(maintenance::last_maintenance(&pool)
+            .await
+            .unwrap()
+            .is_some());
+        assert!(!maintenance::maintenance_due(&pool, 7).await.unwrap());
+        assert!(optimize_if_due(&pool, 7).await.unwrap().is_none());
+        // Nothing left to reclaim the second time
+        assert_eq!(optimize(&pool).await.unwrap().reclaimed_bytes, 0);
     }
 }
@@ -1,14 +1,22 @@
 //! Bookmark database operations
 
 use crate::DbPool;
-use storystream_core::{AppError, BookId, Bookmark, BookmarkId, Duration, Timestamp};
+use storystream_core::{AppError, BookId, Bookmark, BookmarkId, BookmarkKind, Duration, Timestamp};
 
 /// Creates a new bookmark
 pub async fn create_bookmark(pool: &DbPool, bookmark: &Bookmark) -> Result<(), AppError> {
+    insert_bookmark(pool, bookmark).await
+}
+
+/// Inserts a bookmark on a pool or inside a transaction
+pub(crate) async fn insert_bookmark<'e, E>(executor: E, bookmark: &Bookmark) -> Result<(), AppError>
+where
+    E: sqlx::SqliteExecutor<'e>,
+{
     sqlx::query(
         r#"
-        INSERT INTO bookmarks (id, book_id, position_ms, title, note, created_at, updated_at)
-        VALUES (?, ?, ?, ?, ?, ?, ?)
+        INSERT INTO bookmarks (id, book_id, position_ms, title, note, kind, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
         "#,
     )
     .bind(bookmark.id.as_string())
@@ -16,9 +24,10 @@ pub async fn create_bookmark(pool: &DbPool, bookmark: &Bookmark) -> Result<(), A
     .bind(bookmark.position.as_millis() as i64)
     .bind(&bookmark.title)
     .bind(&bookmark.note)
+    .bind(bookmark.kind.as_str())
     .bind(bookmark.created_at.as_millis())
     .bind(bookmark.updated_at.as_millis())
-    .execute(pool)
+    .execute(executor)
     .await
     .map_err(|e| AppError::database("Failed to create bookmark", e))?;
 
@@ -28,7 +37,7 @@ pub async fn create_bookmark(pool: &DbPool, bookmark: &Bookmark) -> Result<(), A
 /// Gets a bookmark by ID
 pub async fn get_bookmark(pool: &DbPool, id: BookmarkId) -> Result<Bookmark, AppError> {
     let row = sqlx::query(
-        "SELECT id, book_id, position_ms, title, note, created_at, updated_at FROM bookmarks WHERE id = ?"
+        "SELECT id, book_id, position_ms, title, note, kind, created_at, updated_at FROM bookmarks WHERE id = ?"
     )
         .bind(id.as_string())
         .fetch_optional(pool)
@@ -45,7 +54,7 @@ pub async fn get_bookmark(pool: &DbPool, id: BookmarkId) -> Result<Bookmark, App
 /// Gets all bookmarks for a book
 pub async fn get_book_bookmarks(pool: &DbPool, book_id: BookId) -> Result<Vec<Bookmark>, AppError> {
     let rows = sqlx::query(
-        "SELECT id, book_id, position_ms, title, note, created_at, updated_at FROM bookmarks WHERE book_id = ? ORDER BY position_ms"
+        "SELECT id, book_id, position_ms, title, note, kind, created_at, updated_at FROM bookmarks WHERE book_id = ? ORDER BY position_ms"
     )
         .bind(book_id.as_string())
         .fetch_all(pool)
@@ -55,6 +64,30 @@ pub async fn get_book_bookmarks(pool: &DbPool, book_id: BookId) -> Result<Vec<Bo
     rows.into_iter().map(row_to_bookmark).collect()
 }
 
+/// Lists all bookmarks across the library, grouped by book
+pub async fn list_bookmarks(pool: &DbPool) -> Result<Vec<Bookmark>, AppError> {
+    let rows = sqlx::query(
+        "SELECT id, book_id, position_ms, title, note, kind, created_at, updated_at FROM bookmarks ORDER BY book_id, position_ms"
+    )
+        .fetch_all(pool)
+        .await
+        .map_err(|e| AppError::database("Failed to list bookmarks", e))?;
+
+    rows.into_iter().map(row_to_bookmark).collect()
+}
+
+/// Deletes every auto-bookmark of a book, returning how many were removed
+pub async fn delete_auto_bookmarks(pool: &DbPool, book_id: BookId) -> Result<u64, AppError> {
+    let result = sqlx::query("DELETE FROM bookmarks WHERE book_id = ? AND kind = ?")
+        .bind(book_id.as_string())
+        .bind(BookmarkKind::Auto.as_str())
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::database("Failed to delete auto-bookmarks", e))?;
+
+    Ok(result.rows_affected())
+}
+
 /// Deletes a bookmark
 pub async fn delete_bookmark(pool: &DbPool, id: BookmarkId) -> Result<(), AppError> {
     sqlx::query("DELETE FROM bookmarks WHERE id = ?")
@@ -95,8 +128,12 @@ pub(crate) fn row_to_bookmark(row: sqlx::sqlite::SqliteRow) -> Result<Bookmark,
         id,
         book_id,
         position: Duration::from_millis(position_ms as u64),
-        title: row.try_get("title").ok(),
-        note: row.try_get("note").ok(),
+        title: row.try_get::<Option<String>, _>("title").ok().flatten(),
+        note: row.try_get::<Option<String>, _>("note").ok().flatten(),
+        kind: row
+            .try_get::<String, _>("kind")
+            .map(|kind| BookmarkKind::parse(&kind))
+            .unwrap_or_default(),
         created_at: Timestamp::from_millis(created_at_ms),
         updated_at: Timestamp::from_millis(updated_at_ms),
     })
@@ -135,6 +172,8 @@ mod tests {
 
         let retrieved = get_bookmark(&pool, bookmark.id).await.unwrap();
         assert_eq!(retrieved.position, Duration::from_seconds(50));
+        assert!(retrieved.title.is_none());
+        assert!(retrieved.note.is_none());
     }
 
     #[tokio::test]
@@ -179,4 +218,66 @@ mod tests {
         let result = get_bookmark(&pool, bookmark.id).await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_list_bookmarks() {
+        let pool = setup().await;
+
+        let book1 = Book::new(
+            "One".to_string(),
+            PathBuf::from("/one.mp3"),
+            1000,
+            Duration::from_seconds(100),
+        );
+        let book2 = Book::new(
+            "Two".to_string(),
+            PathBuf::from("/two.mp3"),
+            1000,
+            Duration::from_seconds(100),
+        );
+        create_book(&pool, &book1).await.unwrap();
+        create_book(&pool, &book2).await.unwrap();
+
+        create_bookmark(&pool, &Bookmark::new(book1.id, Duration::from_seconds(10)))
+            .await
+            .unwrap();
+        create_bookmark(&pool, &Bookmark::new(book2.id, Duration::from_seconds(20)))
+            .await
+            .unwrap();
+
+        let bookmarks = list_bookmarks(&pool).await.unwrap();
+        assert_eq!(bookmarks.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_auto_bookmarks() {
+        use storystream_core::AutoBookmarkTrigger;
+
+        let pool = setup().await;
+        let book = Book::new(
+            "Test".to_string(),
+            PathBuf::from("/test.mp3"),
+            1000,
+            Duration::from_seconds(100),
+        );
+        create_book(&pool, &book).await.unwrap();
+
+        let manual = Bookmark::new(book.id, Duration::from_seconds(10));
+        let paused = Bookmark::auto(
+            book.id,
+            Duration::from_seconds(20),
+            AutoBookmarkTrigger::Paused,
+        );
+        create_bookmark(&pool, &manual).await.unwrap();
+        create_bookmark(&pool, &paused).await.unwrap();
+
+        let saved = get_bookmark(&pool, paused.id).await.unwrap();
+        assert_eq!(saved.kind, BookmarkKind::Auto);
+        assert_eq!(saved.title.as_deref(), Some("Paused"));
+
+        assert_eq!(delete_auto_bookmarks(&pool, book.id).await.unwrap(), 1);
+        let remaining = get_book_bookmarks(&pool, book.id).await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert!(!remaining[0].is_auto());
+    }
 }
@@ -1,11 +1,191 @@
 //! Book database operations
 
 use crate::DbPool;
-use std::path::PathBuf;
-use storystream_core::{AppError, Book, BookId, Duration, Timestamp};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use storystream_core::{AppError, Book, BookId, Chapter, Duration, Timestamp};
 
 /// Creates a new book in the database
 pub async fn create_book(pool: &DbPool, book: &Book) -> Result<(), AppError> {
+    insert_book(pool, book).await
+}
+
+/// Creates many books in one transaction, storing the file hashes given
+///
+/// Either every book is created or none is.
+pub async fn create_books(
+    pool: &DbPool,
+    books: &[Book],
+    hashes: &HashMap<BookId, String>,
+) -> Result<(), AppError> {
+    save_books(pool, books, &[], hashes).await
+}
+
+/// Creates and updates books in one transaction, storing the file hashes
+/// given
+///
+/// Either every change is made or none is.
+pub async fn save_books(
+    pool: &DbPool,
+    created: &[Book],
+    updated: &[Book],
+    hashes: &HashMap<BookId, String>,
+) -> Result<(), AppError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| AppError::database("Failed to begin transaction", e))?;
+
+    insert_books(&mut tx, created).await?;
+    for book in updated {
+        write_book(&mut *tx, book).await?;
+    }
+    for book in created.iter().chain(updated) {
+        if let Some(hash) = hashes.get(&book.id) {
+            sqlx::query("UPDATE books SET file_hash = ? WHERE id = ?")
+                .bind(hash)
+                .bind(book.id.as_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| AppError::database("Failed to store file hash", e))?;
+        }
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::database("Failed to commit transaction", e))?;
+
+    Ok(())
+}
+
+/// Creates many books in one transaction using multi-row INSERTs
+///
+/// Returns the number of books created. Either every book is created or
+/// none is; the error names the book that could not be.
+///
+/// # Errors
+///
+/// Returns `AppError::InvalidArgument` without touching the database if two
+/// books share a file path.
+pub async fn create_books_batch(pool: &DbPool, books: &[Book]) -> Result<usize, AppError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| AppError::database("Failed to begin transaction", e))?;
+
+    insert_books(&mut tx, books).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::database("Failed to commit transaction", e))?;
+
+    Ok(books.len())
+}
+
+/// Columns of a multi-row INSERT, in the order each row binds them
+const INSERT_COLUMNS: [&str; 23] = [
+    "id",
+    "title",
+    "author",
+    "narrator",
+    "series",
+    "series_position",
+    "description",
+    "language",
+    "publisher",
+    "published_date",
+    "isbn",
+    "genre",
+    "duration_ms",
+    "file_path",
+    "file_size",
+    "cover_art_path",
+    "added_date",
+    "last_played",
+    "play_count",
+    "is_favorite",
+    "rating",
+    "tags",
+    "deleted_at",
+];
+
+/// Rows per INSERT, so a statement stays under SQLite's default limit of 999
+/// bound parameters
+const INSERT_ROWS: usize = 999 / INSERT_COLUMNS.len();
+
+async fn insert_books(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    books: &[Book],
+) -> Result<(), AppError> {
+    let mut paths = HashSet::new();
+    if let Some(book) = books.iter().find(|book| !paths.insert(&book.file_path)) {
+        return Err(AppError::InvalidArgument {
+            argument: "books".to_string(),
+            reason: format!("{} is listed more than once", book.file_path.display()),
+        });
+    }
+
+    for chunk in books.chunks(INSERT_ROWS) {
+        let tags = chunk
+            .iter()
+            .map(|book| serde_json::to_string(&book.tags))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::database("Failed to serialize tags", e))?;
+
+        let mut query = sqlx::QueryBuilder::<sqlx::Sqlite>::new(format!(
+            "INSERT INTO books ({}) ",
+            INSERT_COLUMNS.join(", ")
+        ));
+        query.push_values(chunk.iter().zip(tags), |mut row, (book, tags_json)| {
+            row.push_bind(book.id.as_string())
+                .push_bind(&book.title)
+                .push_bind(&book.author)
+                .push_bind(&book.narrator)
+                .push_bind(&book.series)
+                .push_bind(book.series_position)
+                .push_bind(&book.description)
+                .push_bind(&book.language)
+                .push_bind(&book.publisher)
+                .push_bind(&book.published_date)
+                .push_bind(&book.isbn)
+                .push_bind(&book.genre)
+                .push_bind(book.duration.as_millis() as i64)
+                .push_bind(book.file_path.to_str())
+                .push_bind(book.file_size as i64)
+                .push_bind(book.cover_art_path.as_ref().and_then(|p| p.to_str()))
+                .push_bind(book.added_date.as_millis())
+                .push_bind(book.last_played.map(|t| t.as_millis()))
+                .push_bind(book.play_count as i64)
+                .push_bind(book.is_favorite as i64)
+                .push_bind(book.rating.map(|r| r as i64))
+                .push_bind(tags_json)
+                .push_bind(book.deleted_at.map(|t| t.as_millis()));
+        });
+
+        match query.build().execute(&mut **tx).await {
+            Ok(_) => {}
+            // A book breaking a constraint undoes only this statement; one
+            // book at a time finds the culprit
+            Err(sqlx::Error::Database(e)) if e.kind() != sqlx::error::ErrorKind::Other => {
+                tracing::warn!(
+                    "Inserting {} books at once failed, retrying one by one: {}",
+                    chunk.len(),
+                    e
+                );
+                for book in chunk {
+                    insert_book(&mut **tx, book).await?;
+                }
+            }
+            // Anything else, such as a statement not matching the schema,
+            // fails the same way for every book
+            Err(e) => return Err(AppError::database("Failed to insert books", e)),
+        }
+    }
+
+    Ok(())
+}
+
+async fn insert_book(executor: impl sqlx::SqliteExecutor<'_>, book: &Book) -> Result<(), AppError> {
     let tags_json = serde_json::to_string(&book.tags)
         .map_err(|e| AppError::database("Failed to serialize tags", e))?;
 
@@ -13,10 +193,10 @@ pub async fn create_book(pool: &DbPool, book: &Book) -> Result<(), AppError> {
         r#"
         INSERT INTO books (
             id, title, author, narrator, series, series_position,
-            description, language, publisher, published_date, isbn,
+            description, language, publisher, published_date, isbn, genre,
             duration_ms, file_path, file_size, cover_art_path,
             added_date, last_played, play_count, is_favorite, rating, tags, deleted_at
-        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#,
     )
     .bind(book.id.as_string())
@@ -30,6 +210,7 @@ pub async fn create_book(pool: &DbPool, book: &Book) -> Result<(), AppError> {
     .bind(&book.publisher)
     .bind(&book.published_date)
     .bind(&book.isbn)
+    .bind(&book.genre)
     .bind(book.duration.as_millis() as i64)
     .bind(book.file_path.to_str())
     .bind(book.file_size as i64)
@@ -41,9 +222,18 @@ pub async fn create_book(pool: &DbPool, book: &Book) -> Result<(), AppError> {
     .bind(book.rating.map(|r| r as i64))
     .bind(tags_json)
     .bind(book.deleted_at.map(|t| t.as_millis()))
-    .execute(pool)
+    .execute(executor)
     .await
-    .map_err(|e| AppError::database("Failed to create book", e))?;
+    .map_err(|e| {
+        AppError::database(
+            format!(
+                "Failed to create book '{}' ({})",
+                book.title,
+                book.file_path.display()
+            ),
+            e,
+        )
+    })?;
 
     Ok(())
 }
@@ -53,7 +243,7 @@ pub async fn get_book(pool: &DbPool, id: BookId) -> Result<Book, AppError> {
     let row = sqlx::query(
         r#"
         SELECT id, title, author, narrator, series, series_position,
-               description, language, publisher, published_date, isbn,
+               description, language, publisher, published_date, isbn, genre,
                duration_ms, file_path, file_size, cover_art_path,
                added_date, last_played, play_count, is_favorite, rating, tags, deleted_at
         FROM books WHERE id = ?
@@ -71,8 +261,31 @@ pub async fn get_book(pool: &DbPool, id: BookId) -> Result<Book, AppError> {
     row_to_book(row)
 }
 
+/// Finds the book, outside the trash, whose file is at `path`
+pub async fn find_book_by_path(pool: &DbPool, path: &Path) -> Result<Option<Book>, AppError> {
+    let row = sqlx::query(
+        r#"
+        SELECT id, title, author, narrator, series, series_position,
+               description, language, publisher, published_date, isbn, genre,
+               duration_ms, file_path, file_size, cover_art_path,
+               added_date, last_played, play_count, is_favorite, rating, tags, deleted_at
+        FROM books WHERE file_path = ? AND deleted_at IS NULL
+        "#,
+    )
+    .bind(path.to_str())
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to fetch book", e))?;
+
+    row.map(row_to_book).transpose()
+}
+
 /// Updates an existing book
 pub async fn update_book(pool: &DbPool, book: &Book) -> Result<(), AppError> {
+    write_book(pool, book).await
+}
+
+async fn write_book(executor: impl sqlx::SqliteExecutor<'_>, book: &Book) -> Result<(), AppError> {
     let tags_json = serde_json::to_string(&book.tags)
         .map_err(|e| AppError::database("Failed to serialize tags", e))?;
 
@@ -81,7 +294,7 @@ pub async fn update_book(pool: &DbPool, book: &Book) -> Result<(), AppError> {
         UPDATE books SET
             title = ?, author = ?, narrator = ?, series = ?, series_position = ?,
             description = ?, language = ?, publisher = ?, published_date = ?, isbn = ?,
-            duration_ms = ?, file_path = ?, file_size = ?, cover_art_path = ?,
+            genre = ?, duration_ms = ?, file_path = ?, file_size = ?, cover_art_path = ?,
             last_played = ?, play_count = ?, is_favorite = ?, rating = ?, tags = ?, deleted_at = ?
         WHERE id = ?
         "#,
@@ -96,6 +309,7 @@ pub async fn update_book(pool: &DbPool, book: &Book) -> Result<(), AppError> {
     .bind(&book.publisher)
     .bind(&book.published_date)
     .bind(&book.isbn)
+    .bind(&book.genre)
     .bind(book.duration.as_millis() as i64)
     .bind(book.file_path.to_str())
     .bind(book.file_size as i64)
@@ -107,7 +321,7 @@ pub async fn update_book(pool: &DbPool, book: &Book) -> Result<(), AppError> {
     .bind(tags_json)
     .bind(book.deleted_at.map(|t| t.as_millis()))
     .bind(book.id.as_string())
-    .execute(pool)
+    .execute(executor)
     .await
     .map_err(|e| AppError::database("Failed to update book", e))?;
 
@@ -125,87 +339,1210 @@ pub async fn delete_book(pool: &DbPool, id: BookId) -> Result<(), AppError> {
     Ok(())
 }
 
-/// Lists all books (excluding soft-deleted)
+/// Lists all books (excluding soft-deleted), most recently added first
 pub async fn list_books(pool: &DbPool) -> Result<Vec<Book>, AppError> {
+    list_books_page(pool, BookSort::Added, -1, 0).await
+}
+
+/// Gets books by author
+pub async fn get_books_by_author(pool: &DbPool, author: &str) -> Result<Vec<Book>, AppError> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, title, author, narrator, series, series_position,
+               description, language, publisher, published_date, isbn, genre,
+               duration_ms, file_path, file_size, cover_art_path,
+               added_date, last_played, play_count, is_favorite, rating, tags, deleted_at
+        FROM books
+        WHERE author = ? AND deleted_at IS NULL
+        ORDER BY title
+        "#,
+    )
+    .bind(author)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to get books by author", e))?;
+
+    rows.into_iter().map(row_to_book).collect()
+}
+
+/// Gets the books of a series in reading order
+///
+/// Books without a position come last, by title.
+pub async fn get_books_by_series(pool: &DbPool, series: &str) -> Result<Vec<Book>, AppError> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, title, author, narrator, series, series_position,
+               description, language, publisher, published_date, isbn, genre,
+               duration_ms, file_path, file_size, cover_art_path,
+               added_date, last_played, play_count, is_favorite, rating, tags, deleted_at
+        FROM books
+        WHERE series = ? COLLATE NOCASE AND deleted_at IS NULL
+        ORDER BY series_position IS NULL, series_position, title
+        "#,
+    )
+    .bind(series)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to get books by series", e))?;
+
+    rows.into_iter().map(row_to_book).collect()
+}
+
+/// Gets the books read by a narrator, grouped by series in reading order
+/// and followed by books outside any series
+pub async fn get_books_by_narrator(pool: &DbPool, narrator: &str) -> Result<Vec<Book>, AppError> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, title, author, narrator, series, series_position,
+               description, language, publisher, published_date, isbn, genre,
+               duration_ms, file_path, file_size, cover_art_path,
+               added_date, last_played, play_count, is_favorite, rating, tags, deleted_at
+        FROM books
+        WHERE narrator = ? COLLATE NOCASE AND deleted_at IS NULL
+        ORDER BY series IS NULL, series COLLATE NOCASE,
+                 series_position IS NULL, series_position, title
+        "#,
+    )
+    .bind(narrator)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to get books by narrator", e))?;
+
+    rows.into_iter().map(row_to_book).collect()
+}
+
+/// Gets favorite books
+pub async fn get_favorite_books(pool: &DbPool) -> Result<Vec<Book>, AppError> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, title, author, narrator, series, series_position,
+               description, language, publisher, published_date, isbn, genre,
+               duration_ms, file_path, file_size, cover_art_path,
+               added_date, last_played, play_count, is_favorite, rating, tags, deleted_at
+        FROM books
+        WHERE is_favorite = 1 AND deleted_at IS NULL
+        ORDER BY title
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to get favorite books", e))?;
+
+    rows.into_iter().map(row_to_book).collect()
+}
+
+/// Gets recently played books, the last one listened to first
+///
+/// A book counts as played once it has a saved position, so the book to
+/// continue with comes first.
+pub async fn get_recently_played_books(pool: &DbPool, limit: i64) -> Result<Vec<Book>, AppError> {
+    let rows = sqlx::query(
+        r#"
+        SELECT b.id, b.title, b.author, b.narrator, b.series, b.series_position,
+               b.description, b.language, b.publisher, b.published_date, b.isbn, b.genre,
+               b.duration_ms, b.file_path, b.file_size, b.cover_art_path,
+               b.added_date, b.last_played, b.play_count, b.is_favorite, b.rating, b.tags, b.deleted_at
+        FROM books b
+        LEFT JOIN playback_state ps ON ps.book_id = b.id AND ps.position_ms > 0
+        WHERE (b.last_played IS NOT NULL OR ps.book_id IS NOT NULL) AND b.deleted_at IS NULL
+        ORDER BY MAX(COALESCE(b.last_played, 0), COALESCE(ps.last_updated, 0)) DESC
+        LIMIT ?
+        "#,
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to get recently played books", e))?;
+
+    rows.into_iter().map(row_to_book).collect()
+}
+
+/// Changes to some of a book's metadata, leaving other columns alone
+///
+/// Unlike [`update_book`], this never writes back stale values read before
+/// the change. Text fields set to `None` are cleared.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BookUpdate {
+    title: Option<String>,
+    author: Option<Option<String>>,
+    narrator: Option<Option<String>>,
+    series: Option<Option<String>>,
+    series_position: Option<Option<f32>>,
+    user_edited: bool,
+}
+
+impl BookUpdate {
+    /// Creates an update that changes nothing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the title
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Sets or clears the author
+    pub fn author(mut self, author: Option<String>) -> Self {
+        self.author = Some(author);
+        self
+    }
+
+    /// Sets or clears the narrator
+    pub fn narrator(mut self, narrator: Option<String>) -> Self {
+        self.narrator = Some(narrator);
+        self
+    }
+
+    /// Sets or clears the series
+    pub fn series(mut self, series: Option<String>) -> Self {
+        self.series = Some(series);
+        self
+    }
+
+    /// Sets or clears the position within the series
+    pub fn series_position(mut self, position: Option<f32>) -> Self {
+        self.series_position = Some(position);
+        self
+    }
+
+    /// Records the changed columns as edited by the user
+    ///
+    /// Re-importing the file keeps user-edited values; see
+    /// [`get_user_edited_fields`].
+    pub fn user_edited(mut self) -> Self {
+        self.user_edited = true;
+        self
+    }
+
+    /// Returns the columns this update changes
+    pub fn columns(&self) -> Vec<&'static str> {
+        [
+            ("title", self.title.is_some()),
+            ("author", self.author.is_some()),
+            ("narrator", self.narrator.is_some()),
+            ("series", self.series.is_some()),
+            ("series_position", self.series_position.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(column, set)| set.then_some(column))
+        .collect()
+    }
+
+    /// Returns true if the update changes nothing
+    pub fn is_empty(&self) -> bool {
+        self.columns().is_empty()
+    }
+}
+
+/// Applies a partial update to a book
+pub async fn update_book_fields(
+    pool: &DbPool,
+    id: BookId,
+    update: &BookUpdate,
+) -> Result<(), AppError> {
+    if update.is_empty() {
+        return Ok(());
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| AppError::database("Failed to begin transaction", e))?;
+
+    let mut query = sqlx::QueryBuilder::<sqlx::Sqlite>::new("UPDATE books SET ");
+    let mut set = query.separated(", ");
+    if let Some(title) = &update.title {
+        set.push("title = ").push_bind_unseparated(title);
+    }
+    if let Some(author) = &update.author {
+        set.push("author = ").push_bind_unseparated(author);
+    }
+    if let Some(narrator) = &update.narrator {
+        set.push("narrator = ").push_bind_unseparated(narrator);
+    }
+    if let Some(series) = &update.series {
+        set.push("series = ").push_bind_unseparated(series);
+    }
+    if let Some(position) = update.series_position {
+        set.push("series_position = ")
+            .push_bind_unseparated(position);
+    }
+    query.push(" WHERE id = ").push_bind(id.as_string());
+
+    let result = query
+        .build()
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::database("Failed to update book", e))?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::RecordNotFound {
+            entity: "Book".to_string(),
+            identifier: id.to_string(),
+        });
+    }
+
+    if update.user_edited {
+        let edited: String = sqlx::query_scalar("SELECT edited_fields FROM books WHERE id = ?")
+            .bind(id.as_string())
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| AppError::database("Failed to read edited fields", e))?;
+        let mut edited: Vec<String> = serde_json::from_str(&edited)
+            .map_err(|e| AppError::database("Failed to deserialize edited fields", e))?;
+        for column in update.columns() {
+            if !edited.iter().any(|c| c == column) {
+                edited.push(column.to_string());
+            }
+        }
+        let edited = serde_json::to_string(&edited)
+            .map_err(|e| AppError::database("Failed to serialize edited fields", e))?;
+
+        sqlx::query("UPDATE books SET edited_fields = ? WHERE id = ?")
+            .bind(edited)
+            .bind(id.as_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::database("Failed to mark edited fields", e))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::database("Failed to commit transaction", e))?;
+
+    Ok(())
+}
+
+/// Gets the metadata columns of a book that the user has edited
+pub async fn get_user_edited_fields(pool: &DbPool, id: BookId) -> Result<Vec<String>, AppError> {
+    let edited: String = sqlx::query_scalar("SELECT edited_fields FROM books WHERE id = ?")
+        .bind(id.as_string())
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| AppError::database("Failed to read edited fields", e))?
+        .ok_or_else(|| AppError::RecordNotFound {
+            entity: "Book".to_string(),
+            identifier: id.to_string(),
+        })?;
+
+    serde_json::from_str(&edited)
+        .map_err(|e| AppError::database("Failed to deserialize edited fields", e))
+}
+
+/// Stores the hash of a book's file, or clears it with `None`
+pub async fn set_file_hash(pool: &DbPool, id: BookId, hash: Option<&str>) -> Result<(), AppError> {
+    let result = sqlx::query("UPDATE books SET file_hash = ? WHERE id = ?")
+        .bind(hash)
+        .bind(id.as_string())
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::database("Failed to store file hash", e))?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::RecordNotFound {
+            entity: "Book".to_string(),
+            identifier: id.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Stores the hashes of many books' files in one transaction
+pub async fn set_file_hashes(
+    pool: &DbPool,
+    hashes: &HashMap<BookId, String>,
+) -> Result<(), AppError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| AppError::database("Failed to begin transaction", e))?;
+
+    for (id, hash) in hashes {
+        sqlx::query("UPDATE books SET file_hash = ? WHERE id = ?")
+            .bind(hash)
+            .bind(id.as_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::database("Failed to store file hash", e))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::database("Failed to commit transaction", e))?;
+
+    Ok(())
+}
+
+/// Gets the file path of every book, including those in the trash
+pub async fn get_file_paths(pool: &DbPool) -> Result<HashSet<PathBuf>, AppError> {
+    let paths: Vec<String> = sqlx::query_scalar("SELECT file_path FROM books")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| AppError::database("Failed to read file paths", e))?;
+
+    Ok(paths.into_iter().map(PathBuf::from).collect())
+}
+
+/// Gets the stored file hash of every book that has one
+pub async fn get_file_hashes(pool: &DbPool) -> Result<HashMap<BookId, String>, AppError> {
+    let rows: Vec<(String, String)> =
+        sqlx::query_as("SELECT id, file_hash FROM books WHERE file_hash IS NOT NULL")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| AppError::database("Failed to read file hashes", e))?;
+
+    rows.into_iter()
+        .map(|(id, hash)| {
+            let id =
+                BookId::from_string(&id).map_err(|e| AppError::database("Invalid book ID", e))?;
+            Ok((id, hash))
+        })
+        .collect()
+}
+
+/// Stores the content fingerprint of a book's file, or clears it with `None`
+pub async fn set_content_hash(
+    pool: &DbPool,
+    id: BookId,
+    hash: Option<&str>,
+) -> Result<(), AppError> {
+    let result = sqlx::query("UPDATE books SET content_hash = ? WHERE id = ?")
+        .bind(hash)
+        .bind(id.as_string())
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::database("Failed to store content hash", e))?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::RecordNotFound {
+            entity: "Book".to_string(),
+            identifier: id.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Stores the content fingerprints of many books in one transaction
+pub async fn set_content_hashes(
+    pool: &DbPool,
+    hashes: &HashMap<BookId, String>,
+) -> Result<(), AppError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| AppError::database("Failed to begin transaction", e))?;
+
+    for (id, hash) in hashes {
+        sqlx::query("UPDATE books SET content_hash = ? WHERE id = ?")
+            .bind(hash)
+            .bind(id.as_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::database("Failed to store content hash", e))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::database("Failed to commit transaction", e))?;
+
+    Ok(())
+}
+
+/// Gets the content fingerprint of every book that has one, excluding
+/// soft-deleted books
+pub async fn get_content_hashes(pool: &DbPool) -> Result<HashMap<BookId, String>, AppError> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT id, content_hash FROM books \
+         WHERE content_hash IS NOT NULL AND deleted_at IS NULL",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to read content hashes", e))?;
+
+    rows.into_iter()
+        .map(|(id, hash)| {
+            let id =
+                BookId::from_string(&id).map_err(|e| AppError::database("Invalid book ID", e))?;
+            Ok((id, hash))
+        })
+        .collect()
+}
+
+/// Gets the books whose file has this content fingerprint, oldest first
+pub async fn find_books_by_content_hash(pool: &DbPool, hash: &str) -> Result<Vec<Book>, AppError> {
     let rows = sqlx::query(
         r#"
         SELECT id, title, author, narrator, series, series_position,
-               description, language, publisher, published_date, isbn,
+               description, language, publisher, published_date, isbn, genre,
                duration_ms, file_path, file_size, cover_art_path,
                added_date, last_played, play_count, is_favorite, rating, tags, deleted_at
         FROM books
-        WHERE deleted_at IS NULL
-        ORDER BY added_date DESC
+        WHERE content_hash = ? AND deleted_at IS NULL
+        ORDER BY added_date
+        "#,
+    )
+    .bind(hash)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to find books by content hash", e))?;
+
+    rows.into_iter().map(row_to_book).collect()
+}
+
+/// Books whose files have the same contents at different paths
+#[derive(Debug, Clone)]
+pub struct ContentDuplicates {
+    pub content_hash: String,
+    /// Oldest first
+    pub books: Vec<Book>,
+}
+
+/// Groups the books that share a content fingerprint
+///
+/// Groups come back in the order of their oldest book.
+pub async fn find_duplicate_books(pool: &DbPool) -> Result<Vec<ContentDuplicates>, AppError> {
+    use sqlx::Row;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT id, title, author, narrator, series, series_position,
+               description, language, publisher, published_date, isbn, genre,
+               duration_ms, file_path, file_size, cover_art_path,
+               added_date, last_played, play_count, is_favorite, rating, tags, deleted_at,
+               content_hash
+        FROM books
+        WHERE deleted_at IS NULL AND content_hash IN (
+            SELECT content_hash FROM books
+            WHERE content_hash IS NOT NULL AND deleted_at IS NULL
+            GROUP BY content_hash HAVING COUNT(*) > 1
+        )
+        ORDER BY added_date
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to find duplicate books", e))?;
+
+    let mut groups: Vec<ContentDuplicates> = Vec::new();
+    for row in rows {
+        let hash: String = row
+            .try_get("content_hash")
+            .map_err(|e| AppError::database("Missing content hash", e))?;
+        let book = row_to_book(row)?;
+        match groups.iter_mut().find(|group| group.content_hash == hash) {
+            Some(group) => group.books.push(book),
+            None => groups.push(ContentDuplicates {
+                content_hash: hash,
+                books: vec![book],
+            }),
+        }
+    }
+    Ok(groups)
+}
+
+/// Points a book at the new location of its file
+///
+/// Only the path changes, so bookmarks, the playback position and listening
+/// history stay with the book. The book counts as available again, and the
+/// recorded modification time of its file is forgotten, since a copy gets a
+/// new one.
+pub async fn relocate_book(pool: &DbPool, id: BookId, path: &Path) -> Result<(), AppError> {
+    let result = sqlx::query(
+        "UPDATE books SET file_path = ?, file_modified_ms = NULL, is_available = 1 WHERE id = ?",
+    )
+    .bind(path.to_str())
+    .bind(id.as_string())
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to relocate book", e))?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::RecordNotFound {
+            entity: "Book".to_string(),
+            identifier: id.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Marks exactly the books in `ids` as unavailable, and every other book as
+/// available, in one transaction
+pub async fn set_unavailable_books(pool: &DbPool, ids: &[BookId]) -> Result<(), AppError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| AppError::database("Failed to begin transaction", e))?;
+
+    sqlx::query("UPDATE books SET is_available = 1 WHERE is_available = 0")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::database("Failed to update availability", e))?;
+    for id in ids {
+        sqlx::query("UPDATE books SET is_available = 0 WHERE id = ?")
+            .bind(id.as_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::database("Failed to update availability", e))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::database("Failed to commit transaction", e))?;
+
+    Ok(())
+}
+
+/// Marks one book as available or not, leaving the others as they are
+pub async fn set_book_available(
+    pool: &DbPool,
+    id: BookId,
+    available: bool,
+) -> Result<(), AppError> {
+    let result = sqlx::query("UPDATE books SET is_available = ? WHERE id = ?")
+        .bind(available)
+        .bind(id.as_string())
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::database("Failed to update availability", e))?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::RecordNotFound {
+            entity: "Book".to_string(),
+            identifier: id.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Gets the books marked unavailable
+pub async fn get_unavailable_books(pool: &DbPool) -> Result<HashSet<BookId>, AppError> {
+    let ids: Vec<String> = sqlx::query_scalar("SELECT id FROM books WHERE is_available = 0")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| AppError::database("Failed to read unavailable books", e))?;
+
+    ids.iter()
+        .map(|id| BookId::from_string(id).map_err(|e| AppError::database("Invalid book ID", e)))
+        .collect()
+}
+
+/// Stores the modification time, in milliseconds since the epoch, that
+/// many books' files had when they were read
+pub async fn set_file_modified(
+    pool: &DbPool,
+    modified: &HashMap<BookId, i64>,
+) -> Result<(), AppError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| AppError::database("Failed to begin transaction", e))?;
+
+    for (id, millis) in modified {
+        sqlx::query("UPDATE books SET file_modified_ms = ? WHERE id = ?")
+            .bind(millis)
+            .bind(id.as_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::database("Failed to store file modification time", e))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::database("Failed to commit transaction", e))?;
+
+    Ok(())
+}
+
+/// Gets the stored file modification time of every book that has one
+pub async fn get_file_modified(pool: &DbPool) -> Result<HashMap<BookId, i64>, AppError> {
+    let rows: Vec<(String, i64)> =
+        sqlx::query_as("SELECT id, file_modified_ms FROM books WHERE file_modified_ms IS NOT NULL")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| AppError::database("Failed to read file modification times", e))?;
+
+    rows.into_iter()
+        .map(|(id, millis)| {
+            let id =
+                BookId::from_string(&id).map_err(|e| AppError::database("Invalid book ID", e))?;
+            Ok((id, millis))
+        })
+        .collect()
+}
+
+/// Merges duplicate books into `keep` in one transaction
+///
+/// Bookmarks, listening sessions, playlist memberships and tags move to the
+/// kept book, which also takes the furthest playback position of the group.
+/// The other books are then soft-deleted.
+pub async fn merge_books(pool: &DbPool, keep: BookId, remove: &[BookId]) -> Result<(), AppError> {
+    if remove.contains(&keep) {
+        return Err(AppError::InvalidArgument {
+            argument: "remove".to_string(),
+            reason: "cannot merge a book into itself".to_string(),
+        });
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| AppError::database("Failed to begin transaction", e))?;
+
+    let mut tags = read_tags(&mut tx, keep).await?;
+    let deleted_at = Timestamp::now().as_millis();
+
+    for id in remove {
+        for (table, what) in [
+            ("bookmarks", "bookmarks"),
+            ("listening_sessions", "listening sessions"),
+        ] {
+            sqlx::query(&format!(
+                "UPDATE {} SET book_id = ? WHERE book_id = ?",
+                table
+            ))
+            .bind(keep.as_string())
+            .bind(id.as_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::database(format!("Failed to move {}", what), e))?;
+        }
+
+        // Books already in a playlist with the kept book just drop out of it
+        sqlx::query("UPDATE OR IGNORE playlist_items SET book_id = ? WHERE book_id = ?")
+            .bind(keep.as_string())
+            .bind(id.as_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::database("Failed to move playlist items", e))?;
+        sqlx::query("DELETE FROM playlist_items WHERE book_id = ?")
+            .bind(id.as_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::database("Failed to remove playlist items", e))?;
+
+        let kept_position = playback_position(&mut tx, keep).await?;
+        let position = playback_position(&mut tx, *id).await?;
+        if position.is_some() && position > kept_position {
+            sqlx::query("DELETE FROM playback_state WHERE book_id = ?")
+                .bind(keep.as_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| AppError::database("Failed to replace playback state", e))?;
+            sqlx::query("UPDATE playback_state SET book_id = ? WHERE book_id = ?")
+                .bind(keep.as_string())
+                .bind(id.as_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| AppError::database("Failed to move playback state", e))?;
+        } else {
+            sqlx::query("DELETE FROM playback_state WHERE book_id = ?")
+                .bind(id.as_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| AppError::database("Failed to remove playback state", e))?;
+        }
+
+        for tag in read_tags(&mut tx, *id).await? {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+
+        let result =
+            sqlx::query("UPDATE books SET deleted_at = ? WHERE id = ? AND deleted_at IS NULL")
+                .bind(deleted_at)
+                .bind(id.as_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| AppError::database("Failed to delete merged book", e))?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::RecordNotFound {
+                entity: "Book".to_string(),
+                identifier: id.to_string(),
+            });
+        }
+    }
+
+    let tags = serde_json::to_string(&tags)
+        .map_err(|e| AppError::database("Failed to serialize tags", e))?;
+    sqlx::query("UPDATE books SET tags = ? WHERE id = ?")
+        .bind(tags)
+        .bind(keep.as_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::database("Failed to merge tags", e))?;
+
+    replace_in_session_queue(&mut tx, keep, remove).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::database("Failed to commit transaction", e))?;
+
+    Ok(())
+}
+
+/// Positions moved when a book's file was replaced
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MovedPositions {
+    /// Bookmarks whose position changed
+    pub bookmarks: u64,
+    /// Whether the saved playback position changed
+    pub playback: bool,
+}
+
+/// Points a book at another copy of its audio in one transaction
+///
+/// `book` carries the new file's path, size and duration. The stored file
+/// hash is replaced and the content fingerprint, measured loudness and
+/// replay gain cleared, since they described the old file. With `chapters` the book's chapters are
+/// replaced as well.
+/// Bookmarks and the playback position are multiplied by `scale` and kept
+/// within the new duration.
+pub async fn replace_source(
+    pool: &DbPool,
+    book: &Book,
+    file_hash: Option<&str>,
+    chapters: Option<&[Chapter]>,
+    scale: f64,
+) -> Result<MovedPositions, AppError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| AppError::database("Failed to begin transaction", e))?;
+
+    let result = sqlx::query(
+        "UPDATE books SET file_path = ?, file_size = ?, duration_ms = ?, file_hash = ?, \
+         content_hash = NULL, file_modified_ms = NULL, loudness_db = NULL, replay_gain_db = NULL \
+         WHERE id = ?",
+    )
+    .bind(book.file_path.to_str())
+    .bind(book.file_size as i64)
+    .bind(book.duration.as_millis() as i64)
+    .bind(file_hash)
+    .bind(book.id.as_string())
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| AppError::database("Failed to update book file", e))?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::RecordNotFound {
+            entity: "Book".to_string(),
+            identifier: book.id.to_string(),
+        });
+    }
+
+    if let Some(chapters) = chapters {
+        super::chapters::write_book_chapters(&mut tx, book.id, chapters).await?;
+    }
+
+    let duration_ms = book.duration.as_millis() as i64;
+    let bookmarks = scale_positions(&mut tx, "bookmarks", book.id, scale, duration_ms).await?;
+    let playback = scale_positions(&mut tx, "playback_state", book.id, scale, duration_ms).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::database("Failed to commit transaction", e))?;
+
+    Ok(MovedPositions {
+        bookmarks,
+        playback: playback > 0,
+    })
+}
+
+type Transaction<'a> = sqlx::Transaction<'a, sqlx::Sqlite>;
+
+/// Reads a book's tags, failing if the book does not exist
+async fn read_tags(tx: &mut Transaction<'_>, id: BookId) -> Result<Vec<String>, AppError> {
+    let tags: Option<String> = sqlx::query_scalar("SELECT tags FROM books WHERE id = ?")
+        .bind(id.as_string())
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(|e| AppError::database("Failed to read tags", e))?
+        .ok_or_else(|| AppError::RecordNotFound {
+            entity: "Book".to_string(),
+            identifier: id.to_string(),
+        })?;
+
+    match tags {
+        Some(tags) => serde_json::from_str(&tags)
+            .map_err(|e| AppError::database("Failed to deserialize tags", e)),
+        None => Ok(Vec::new()),
+    }
+}
+
+async fn playback_position(tx: &mut Transaction<'_>, id: BookId) -> Result<Option<i64>, AppError> {
+    sqlx::query_scalar("SELECT position_ms FROM playback_state WHERE book_id = ?")
+        .bind(id.as_string())
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(|e| AppError::database("Failed to read playback position", e))
+}
+
+/// Multiplies the positions a book has in `table` by `scale`, capped at
+/// `duration_ms`, and returns how many changed
+async fn scale_positions(
+    tx: &mut Transaction<'_>,
+    table: &str,
+    id: BookId,
+    scale: f64,
+    duration_ms: i64,
+) -> Result<u64, AppError> {
+    let scaled = "MIN(CAST(ROUND(position_ms * ?) AS INTEGER), ?)";
+    let result = sqlx::query(&format!(
+        "UPDATE {table} SET position_ms = {scaled} WHERE book_id = ? AND position_ms != {scaled}"
+    ))
+    .bind(scale)
+    .bind(duration_ms)
+    .bind(id.as_string())
+    .bind(scale)
+    .bind(duration_ms)
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| AppError::database(format!("Failed to move positions in {}", table), e))?;
+    Ok(result.rows_affected())
+}
+
+/// Points the playlist session's queue at the kept book, dropping repeats
+async fn replace_in_session_queue(
+    tx: &mut Transaction<'_>,
+    keep: BookId,
+    remove: &[BookId],
+) -> Result<(), AppError> {
+    let session: Option<(String, i64)> =
+        sqlx::query_as("SELECT queue, current_index FROM playlist_session WHERE id = 1")
+            .fetch_optional(&mut **tx)
+            .await
+            .map_err(|e| AppError::database("Failed to read playlist session", e))?;
+    let Some((queue_json, current_index)) = session else {
+        return Ok(());
+    };
+
+    let queue: Vec<String> = serde_json::from_str(&queue_json)
+        .map_err(|e| AppError::database("Invalid playlist queue", e))?;
+    let removed: Vec<String> = remove.iter().map(|id| id.as_string()).collect();
+    if !queue.iter().any(|id| removed.contains(id)) {
+        return Ok(());
+    }
+
+    let current = queue.get(current_index as usize).cloned();
+    let mut merged: Vec<String> = Vec::with_capacity(queue.len());
+    for id in queue {
+        let id = if removed.contains(&id) {
+            keep.as_string()
+        } else {
+            id
+        };
+        if !merged.contains(&id) {
+            merged.push(id);
+        }
+    }
+    let current_index = current
+        .map(|id| {
+            if removed.contains(&id) {
+                keep.as_string()
+            } else {
+                id
+            }
+        })
+        .and_then(|id| merged.iter().position(|m| *m == id))
+        .unwrap_or(0);
+
+    let queue_json = serde_json::to_string(&merged)
+        .map_err(|e| AppError::database("Failed to serialize playlist queue", e))?;
+    sqlx::query("UPDATE playlist_session SET queue = ?, current_index = ? WHERE id = 1")
+        .bind(queue_json)
+        .bind(current_index as i64)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| AppError::database("Failed to update playlist session", e))?;
+    Ok(())
+}
+
+/// Gets the book that comes after `after_position` in a series
+pub async fn get_next_in_series(
+    pool: &DbPool,
+    series: &str,
+    after_position: f32,
+) -> Result<Option<Book>, AppError> {
+    let row = sqlx::query(
+        r#"
+        SELECT id, title, author, narrator, series, series_position, description,
+               language, publisher, published_date, isbn, genre, duration_ms, file_path,
+               file_size, cover_art_path, added_date, last_played, play_count,
+               is_favorite, rating, tags, deleted_at
+        FROM books
+        WHERE series = ? COLLATE NOCASE AND series_position > ? AND deleted_at IS NULL
+        ORDER BY series_position
+        LIMIT 1
         "#,
     )
-    .fetch_all(pool)
+    .bind(series)
+    .bind(after_position)
+    .fetch_optional(pool)
     .await
-    .map_err(|e| AppError::database("Failed to list books", e))?;
+    .map_err(|e| AppError::database("Failed to get next book in series", e))?;
 
-    rows.into_iter().map(row_to_book).collect()
+    row.map(row_to_book).transpose()
 }
 
-/// Gets books by author
-pub async fn get_books_by_author(pool: &DbPool, author: &str) -> Result<Vec<Book>, AppError> {
-    let rows = sqlx::query(
+/// Gets the most recently added book by `author` that has never been started
+pub async fn get_newest_unplayed_by_author(
+    pool: &DbPool,
+    author: &str,
+    exclude: BookId,
+) -> Result<Option<Book>, AppError> {
+    let row = sqlx::query(
         r#"
-        SELECT id, title, author, narrator, series, series_position,
-               description, language, publisher, published_date, isbn,
-               duration_ms, file_path, file_size, cover_art_path,
-               added_date, last_played, play_count, is_favorite, rating, tags, deleted_at
+        SELECT id, title, author, narrator, series, series_position, description,
+               language, publisher, published_date, isbn, genre, duration_ms, file_path,
+               file_size, cover_art_path, added_date, last_played, play_count,
+               is_favorite, rating, tags, deleted_at
         FROM books
-        WHERE author = ? AND deleted_at IS NULL
-        ORDER BY title
+        WHERE author = ? COLLATE NOCASE AND id != ? AND deleted_at IS NULL
+        AND last_played IS NULL
+        AND NOT EXISTS (
+            SELECT 1 FROM playback_state ps WHERE ps.book_id = books.id AND ps.position_ms > 0
+        )
+        ORDER BY added_date DESC
+        LIMIT 1
         "#,
     )
     .bind(author)
-    .fetch_all(pool)
+    .bind(exclude.as_string())
+    .fetch_optional(pool)
     .await
-    .map_err(|e| AppError::database("Failed to get books by author", e))?;
+    .map_err(|e| AppError::database("Failed to get unplayed book by author", e))?;
 
-    rows.into_iter().map(row_to_book).collect()
+    row.map(row_to_book).transpose()
 }
 
-/// Gets favorite books
-pub async fn get_favorite_books(pool: &DbPool) -> Result<Vec<Book>, AppError> {
-    let rows = sqlx::query(
+/// Sort order for paged book listings
+///
+/// Every order ends on the book ID, so books that tie keep their places
+/// from one page to the next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BookSort {
+    /// Alphabetical by title
+    #[default]
+    Title,
+    /// Reverse alphabetical by title
+    TitleDesc,
+    /// Alphabetical by author, then title; unknown authors last
+    Author,
+    /// Reverse alphabetical by author, then title; unknown authors last
+    AuthorDesc,
+    /// Most recently added first
+    Added,
+    /// Least recently added first
+    AddedOldest,
+    /// Most recently played first; unplayed books last
+    LastPlayed,
+    /// Least recently played first; unplayed books last
+    LastPlayedOldest,
+    /// Shortest first
+    Duration,
+    /// Longest first
+    DurationDesc,
+}
+
+impl BookSort {
+    /// Parses a sort key such as `"title"` or `"last_played"`
+    ///
+    /// A leading `-` reverses the order, as in `"-duration"` for the
+    /// longest books first.
+    pub fn parse(key: &str) -> Result<Self, AppError> {
+        let key = key.trim().to_ascii_lowercase();
+        let (reverse, name) = match key.strip_prefix('-') {
+            Some(name) => (true, name),
+            None => (false, key.as_str()),
+        };
+        let sort = match name {
+            "" | "title" => Self::Title,
+            "author" => Self::Author,
+            "added" => Self::Added,
+            "last_played" | "recent" => Self::LastPlayed,
+            "duration" | "length" => Self::Duration,
+            other => {
+                return Err(AppError::InvalidArgument {
+                    argument: "sort".to_string(),
+                    reason: format!(
+                        "unknown sort '{}'; expected title, author, added, last_played or \
+                         duration, with a leading '-' to reverse it",
+                        other
+                    ),
+                })
+            }
+        };
+        Ok(if reverse { sort.reversed() } else { sort })
+    }
+
+    /// The same key in the opposite direction
+    pub fn reversed(self) -> Self {
+        match self {
+            Self::Title => Self::TitleDesc,
+            Self::TitleDesc => Self::Title,
+            Self::Author => Self::AuthorDesc,
+            Self::AuthorDesc => Self::Author,
+            Self::Added => Self::AddedOldest,
+            Self::AddedOldest => Self::Added,
+            Self::LastPlayed => Self::LastPlayedOldest,
+            Self::LastPlayedOldest => Self::LastPlayed,
+            Self::Duration => Self::DurationDesc,
+            Self::DurationDesc => Self::Duration,
+        }
+    }
+
+    pub(crate) fn order_by(self) -> &'static str {
+        match self {
+            Self::Title => "title COLLATE NOCASE, id",
+            Self::TitleDesc => "title COLLATE NOCASE DESC, id",
+            Self::Author => "author IS NULL, author COLLATE NOCASE, title COLLATE NOCASE, id",
+            Self::AuthorDesc => {
+                "author IS NULL, author COLLATE NOCASE DESC, title COLLATE NOCASE, id"
+            }
+            Self::Added => "added_date DESC, id",
+            Self::AddedOldest => "added_date, id",
+            Self::LastPlayed => "last_played IS NULL, last_played DESC, title COLLATE NOCASE, id",
+            Self::LastPlayedOldest => "last_played IS NULL, last_played, title COLLATE NOCASE, id",
+            Self::Duration => "duration_ms, title COLLATE NOCASE, id",
+            Self::DurationDesc => "duration_ms DESC, title COLLATE NOCASE, id",
+        }
+    }
+}
+
+/// Lists one page of books (excluding soft-deleted)
+///
+/// A negative `limit` lists every book from `offset` on.
+pub async fn list_books_page(
+    pool: &DbPool,
+    sort: BookSort,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<Book>, AppError> {
+    select_page(pool, sort, limit, offset).await
+}
+
+async fn select_page(
+    executor: impl sqlx::SqliteExecutor<'_>,
+    sort: BookSort,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<Book>, AppError> {
+    let sql = format!(
         r#"
         SELECT id, title, author, narrator, series, series_position,
-               description, language, publisher, published_date, isbn,
+               description, language, publisher, published_date, isbn, genre,
                duration_ms, file_path, file_size, cover_art_path,
                added_date, last_played, play_count, is_favorite, rating, tags, deleted_at
         FROM books
-        WHERE is_favorite = 1 AND deleted_at IS NULL
-        ORDER BY title
+        WHERE deleted_at IS NULL
+        ORDER BY {}
+        LIMIT ? OFFSET ?
         "#,
-    )
-    .fetch_all(pool)
-    .await
-    .map_err(|e| AppError::database("Failed to get favorite books", e))?;
+        sort.order_by()
+    );
+
+    let rows = sqlx::query(&sql)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(executor)
+        .await
+        .map_err(|e| AppError::database("Failed to list books", e))?;
 
     rows.into_iter().map(row_to_book).collect()
 }
 
-/// Gets recently played books
-pub async fn get_recently_played_books(pool: &DbPool, limit: i64) -> Result<Vec<Book>, AppError> {
+/// One page of the library and the number of books in all
+#[derive(Debug, Clone)]
+pub struct PagedBooks {
+    pub books: Vec<Book>,
+    /// Books in the library (excluding soft-deleted), on every page
+    pub total: i64,
+}
+
+/// Lists `limit` books from `offset` on in `sort` order, with the total
+/// count (excluding soft-deleted)
+///
+/// The page and the count are read in one transaction, so they agree.
+pub async fn list_books_paged(
+    pool: &DbPool,
+    offset: i64,
+    limit: i64,
+    sort: BookSort,
+) -> Result<PagedBooks, AppError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| AppError::database("Failed to begin transaction", e))?;
+
+    let total = sqlx::query_scalar("SELECT COUNT(*) FROM books WHERE deleted_at IS NULL")
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AppError::database("Failed to count books", e))?;
+    let books = select_page(&mut *tx, sort, limit, offset).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::database("Failed to commit transaction", e))?;
+
+    Ok(PagedBooks { books, total })
+}
+
+/// Counts books (excluding soft-deleted)
+pub async fn count_books(pool: &DbPool) -> Result<i64, AppError> {
+    sqlx::query_scalar("SELECT COUNT(*) FROM books WHERE deleted_at IS NULL")
+        .fetch_one(pool)
+        .await
+        .map_err(|e| AppError::database("Failed to count books", e))
+}
+
+/// A started but unfinished book with its saved position
+#[derive(Debug, Clone)]
+pub struct InProgressBook {
+    pub book: Book,
+    pub position: Duration,
+}
+
+/// Gets started, unfinished books, most recently listened first
+pub async fn get_in_progress_books(
+    pool: &DbPool,
+    limit: i64,
+) -> Result<Vec<InProgressBook>, AppError> {
+    use sqlx::Row;
+
     let rows = sqlx::query(
         r#"
-        SELECT id, title, author, narrator, series, series_position,
-               description, language, publisher, published_date, isbn,
-               duration_ms, file_path, file_size, cover_art_path,
-               added_date, last_played, play_count, is_favorite, rating, tags, deleted_at
-        FROM books
-        WHERE last_played IS NOT NULL AND deleted_at IS NULL
-        ORDER BY last_played DESC
+        SELECT b.id, b.title, b.author, b.narrator, b.series, b.series_position,
+               b.description, b.language, b.publisher, b.published_date, b.isbn, b.genre,
+               b.duration_ms, b.file_path, b.file_size, b.cover_art_path,
+               b.added_date, b.last_played, b.play_count, b.is_favorite, b.rating, b.tags, b.deleted_at,
+               ps.position_ms AS position_ms
+        FROM books b
+        JOIN playback_state ps ON ps.book_id = b.id
+        WHERE b.deleted_at IS NULL
+        AND ps.position_ms > 0
+        AND ps.position_ms < b.duration_ms * ?
+        ORDER BY ps.last_updated DESC
         LIMIT ?
         "#,
     )
+    .bind(crate::queries::stats::FINISHED_THRESHOLD)
     .bind(limit)
     .fetch_all(pool)
     .await
-    .map_err(|e| AppError::database("Failed to get recently played books", e))?;
+    .map_err(|e| AppError::database("Failed to get in-progress books", e))?;
 
-    rows.into_iter().map(row_to_book).collect()
+    rows.into_iter()
+        .map(|row| {
+            let position_ms: i64 = row
+                .try_get("position_ms")
+                .map_err(|e| AppError::database("Missing position", e))?;
+            Ok(InProgressBook {
+                book: row_to_book(row)?,
+                position: Duration::from_millis(position_ms as u64),
+            })
+        })
+        .collect()
 }
 
 /// Converts a database row to a Book
@@ -235,35 +1572,42 @@ pub(crate) fn row_to_book(row: sqlx::sqlite::SqliteRow) -> Result<Book, AppError
         .try_get("added_date")
         .map_err(|e| AppError::database("Missing added date", e))?;
 
-    let last_played_ms: Option<i64> = row.try_get("last_played").ok();
+    let last_played_ms: Option<i64> = row.try_get::<Option<i64>, _>("last_played").ok().flatten();
     let play_count: i64 = row
         .try_get("play_count")
         .map_err(|e| AppError::database("Missing play count", e))?;
     let is_favorite: i64 = row
         .try_get("is_favorite")
         .map_err(|e| AppError::database("Missing is_favorite", e))?;
-    let rating: Option<i64> = row.try_get("rating").ok();
+    let rating: Option<i64> = row.try_get::<Option<i64>, _>("rating").ok().flatten();
     let file_size: i64 = row
         .try_get("file_size")
         .map_err(|e| AppError::database("Missing file size", e))?;
-    let deleted_at_ms: Option<i64> = row.try_get("deleted_at").ok();
+    let deleted_at_ms: Option<i64> = row.try_get::<Option<i64>, _>("deleted_at").ok().flatten();
 
-    let cover_art_path_str: Option<String> = row.try_get("cover_art_path").ok();
+    let cover_art_path_str: Option<String> = row
+        .try_get::<Option<String>, _>("cover_art_path")
+        .ok()
+        .flatten();
 
     Ok(Book {
         id,
         title: row
             .try_get("title")
             .map_err(|e| AppError::database("Missing title", e))?,
-        author: row.try_get("author").ok(),
-        narrator: row.try_get("narrator").ok(),
-        series: row.try_get("series").ok(),
-        series_position: row.try_get("series_position").ok(),
-        description: row.try_get("description").ok(),
-        language: row.try_get("language").ok(),
-        publisher: row.try_get("publisher").ok(),
-        published_date: row.try_get("published_date").ok(),
-        isbn: row.try_get("isbn").ok(),
+        author: optional_text(&row, "author"),
+        narrator: optional_text(&row, "narrator"),
+        series: optional_text(&row, "series"),
+        series_position: row
+            .try_get::<Option<f32>, _>("series_position")
+            .ok()
+            .flatten(),
+        description: optional_text(&row, "description"),
+        language: optional_text(&row, "language"),
+        publisher: optional_text(&row, "publisher"),
+        published_date: optional_text(&row, "published_date"),
+        isbn: optional_text(&row, "isbn"),
+        genre: optional_text(&row, "genre"),
         duration: Duration::from_millis(duration_ms as u64),
         file_path: PathBuf::from(file_path_str),
         file_size: file_size as u64,
@@ -278,6 +1622,13 @@ pub(crate) fn row_to_book(row: sqlx::sqlite::SqliteRow) -> Result<Book, AppError
     })
 }
 
+/// Reads a nullable text column, treating SQL NULL as `None`
+fn optional_text(row: &sqlx::sqlite::SqliteRow, column: &str) -> Option<String> {
+    use sqlx::Row;
+
+    row.try_get::<Option<String>, _>(column).ok().flatten()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -313,6 +1664,21 @@ mod tests {
         assert_eq!(retrieved.title, book.title);
     }
 
+    #[tokio::test]
+    async fn test_null_columns_read_as_none() {
+        let pool = setup().await.expect("Failed to setup database");
+        let book = create_test_book_with_path("/test/nulls.mp3");
+        create_book(&pool, &book)
+            .await
+            .expect("Failed to create book");
+
+        let retrieved = get_book(&pool, book.id).await.expect("Failed to get book");
+        assert!(!retrieved.is_deleted());
+        assert!(retrieved.last_played.is_none());
+        assert!(retrieved.narrator.is_none());
+        assert!(retrieved.cover_art_path.is_none());
+    }
+
     #[tokio::test]
     async fn test_update_book() {
         let pool = setup().await.expect("Failed to setup database");
@@ -324,6 +1690,7 @@ mod tests {
 
         book.title = "Updated Title".to_string();
         book.is_favorite = true;
+        book.genre = Some("Mystery".to_string());
         update_book(&pool, &book)
             .await
             .expect("Failed to update book");
@@ -333,6 +1700,7 @@ mod tests {
             .expect("Failed to get updated book");
         assert_eq!(retrieved.title, "Updated Title");
         assert!(retrieved.is_favorite);
+        assert_eq!(retrieved.genre.as_deref(), Some("Mystery"));
     }
 
     #[tokio::test]
@@ -395,6 +1763,61 @@ mod tests {
         assert_eq!(books.len(), 2);
     }
 
+    #[tokio::test]
+    async fn test_get_books_by_series_and_narrator() {
+        let pool = setup().await.expect("Failed to setup database");
+
+        let mut books = Vec::new();
+        for (title, series, position) in [
+            ("The Two Towers", Some("The Lord of the Rings"), Some(2.0)),
+            (
+                "The Fellowship of the Ring",
+                Some("The Lord of the Rings"),
+                Some(1.0),
+            ),
+            ("Unfinished Tales", Some("The Lord of the Rings"), None),
+            ("The Hobbit", None, None),
+        ] {
+            let mut book = create_test_book_with_path(&format!("/test/{}.mp3", title));
+            book.title = title.to_string();
+            book.series = series.map(str::to_string);
+            book.series_position = position;
+            book.narrator = Some("Andy Serkis".to_string());
+            book.genre = Some("Fantasy".to_string());
+            books.push(book);
+        }
+        create_books(&pool, &books, &HashMap::new())
+            .await
+            .expect("Failed to create books");
+
+        let titles = |books: Vec<Book>| books.into_iter().map(|b| b.title).collect::<Vec<_>>();
+        let series = get_books_by_series(&pool, "the lord of the rings")
+            .await
+            .expect("Failed to get books by series");
+        assert_eq!(
+            titles(series),
+            vec![
+                "The Fellowship of the Ring",
+                "The Two Towers",
+                "Unfinished Tales"
+            ]
+        );
+
+        let narrated = get_books_by_narrator(&pool, "Andy Serkis")
+            .await
+            .expect("Failed to get books by narrator");
+        assert_eq!(narrated[0].genre.as_deref(), Some("Fantasy"));
+        assert_eq!(
+            titles(narrated),
+            vec![
+                "The Fellowship of the Ring",
+                "The Two Towers",
+                "Unfinished Tales",
+                "The Hobbit"
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_get_favorite_books() {
         let pool = setup().await.expect("Failed to setup database");
@@ -439,5 +1862,599 @@ mod tests {
             .expect("Failed to get recently played books");
         assert_eq!(recent.len(), 1);
         assert_eq!(recent[0].id, book1.id);
+
+        // Listening saves a position, which makes the book the latest one
+        use crate::queries::playback::update_playback_state;
+        use storystream_core::PlaybackSpeed;
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        update_playback_state(
+            &pool,
+            book2.id,
+            Duration::from_seconds(60),
+            PlaybackSpeed::default(),
+            100,
+        )
+        .await
+        .expect("Failed to save position");
+        let recent = get_recently_played_books(&pool, 10)
+            .await
+            .expect("Failed to get recently played books");
+        assert_eq!(
+            recent.iter().map(|book| book.id).collect::<Vec<_>>(),
+            [book2.id, book1.id]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_books_page_sorts_and_pages() {
+        let pool = setup().await.expect("Failed to setup database");
+
+        for (title, author) in [("Charlie", "Zed"), ("alpha", "Young"), ("Bravo", "Xavier")] {
+            let mut book = create_test_book_with_path(&format!("/test/page_{}.mp3", title));
+            book.title = title.to_string();
+            book.author = Some(author.to_string());
+            create_book(&pool, &book)
+                .await
+                .expect("Failed to create book");
+        }
+
+        let first = list_books_page(&pool, BookSort::Title, 2, 0)
+            .await
+            .expect("Failed to list first page");
+        let titles: Vec<_> = first.iter().map(|b| b.title.as_str()).collect();
+        assert_eq!(titles, ["alpha", "Bravo"]);
+
+        let second = list_books_page(&pool, BookSort::Title, 2, 2)
+            .await
+            .expect("Failed to list second page");
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].title, "Charlie");
+
+        let by_author = list_books_page(&pool, BookSort::Author, 10, 0)
+            .await
+            .expect("Failed to list by author");
+        assert_eq!(by_author[0].title, "Bravo");
+        assert_eq!(count_books(&pool).await.expect("Failed to count"), 3);
+    }
+
+    #[tokio::test]
+    async fn test_list_books_paged() {
+        let pool = setup().await.expect("Failed to setup database");
+
+        // Equal durations fall back to the title, then the ID
+        for (title, minutes) in [("b", 30), ("A", 90), ("c", 30), ("D", 60), ("a", 90)] {
+            let mut book = create_test_book_with_path(&format!("/test/paged_{}.mp3", title));
+            book.title = title.to_string();
+            book.duration = Duration::from_seconds(minutes * 60);
+            create_book(&pool, &book)
+                .await
+                .expect("Failed to create book");
+        }
+
+        let mut longest = Vec::new();
+        for offset in [0, 2, 4] {
+            let page = list_books_paged(&pool, offset, 2, BookSort::DurationDesc)
+                .await
+                .expect("Failed to list page");
+            assert_eq!(page.total, 5);
+            longest.extend(page.books.into_iter().map(|b| b.title.to_lowercase()));
+        }
+        assert_eq!(longest, ["a", "a", "d", "b", "c"]);
+
+        let by_title = list_books_paged(&pool, 0, 10, BookSort::TitleDesc)
+            .await
+            .expect("Failed to list by title");
+        let titles: Vec<_> = by_title.books.iter().map(|b| b.title.as_str()).collect();
+        assert_eq!(&titles[..3], ["D", "c", "b"]);
+
+        let past_end = list_books_paged(&pool, 10, 2, BookSort::Title)
+            .await
+            .expect("Failed to list past the end");
+        assert!(past_end.books.is_empty());
+        assert_eq!(past_end.total, 5);
+        assert_eq!(list_books(&pool).await.unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_book_sort_parse() {
+        assert_eq!(BookSort::parse("").unwrap(), BookSort::Title);
+        assert_eq!(BookSort::parse("Author").unwrap(), BookSort::Author);
+        assert_eq!(BookSort::parse("recent").unwrap(), BookSort::LastPlayed);
+        assert_eq!(
+            BookSort::parse("-duration").unwrap(),
+            BookSort::DurationDesc
+        );
+        assert_eq!(BookSort::parse("-added").unwrap(), BookSort::AddedOldest);
+        assert!(matches!(
+            BookSort::parse("size"),
+            Err(AppError::InvalidArgument { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_in_progress_books() {
+        use crate::queries::playback::create_playback_state;
+        use storystream_core::PlaybackState;
+
+        let pool = setup().await.expect("Failed to setup database");
+
+        let started = create_test_book_with_path("/test/progress_started.mp3");
+        let finished = create_test_book_with_path("/test/progress_finished.mp3");
+        let untouched = create_test_book_with_path("/test/progress_untouched.mp3");
+        for book in [&started, &finished, &untouched] {
+            create_book(&pool, book)
+                .await
+                .expect("Failed to create book");
+        }
+
+        let mut state = PlaybackState::new(started.id);
+        state.position = Duration::from_seconds(600);
+        create_playback_state(&pool, &state)
+            .await
+            .expect("Failed to save started state");
+
+        let mut state = PlaybackState::new(finished.id);
+        state.position = Duration::from_seconds(3600);
+        create_playback_state(&pool, &state)
+            .await
+            .expect("Failed to save finished state");
+
+        let in_progress = get_in_progress_books(&pool, 10)
+            .await
+            .expect("Failed to get in-progress books");
+        assert_eq!(in_progress.len(), 1);
+        assert_eq!(in_progress[0].book.id, started.id);
+        assert_eq!(in_progress[0].position, Duration::from_seconds(600));
+    }
+
+    #[tokio::test]
+    async fn test_update_book_fields_changes_only_given_columns() {
+        let pool = setup().await.expect("Failed to setup database");
+        let mut book = create_test_book_with_path("/test/partial.mp3");
+        book.author = Some("Jane Austin".to_string());
+        book.narrator = Some("Reader".to_string());
+        create_book(&pool, &book)
+            .await
+            .expect("Failed to create book");
+
+        let update = BookUpdate::new()
+            .author(Some("Jane Austen".to_string()))
+            .series(None);
+        assert_eq!(update.columns(), vec!["author", "series"]);
+        update_book_fields(&pool, book.id, &update)
+            .await
+            .expect("Failed to update book");
+
+        let retrieved = get_book(&pool, book.id).await.expect("Failed to get book");
+        assert_eq!(retrieved.author.as_deref(), Some("Jane Austen"));
+        assert_eq!(retrieved.title, book.title);
+        assert_eq!(retrieved.narrator.as_deref(), Some("Reader"));
+        assert!(get_user_edited_fields(&pool, book.id)
+            .await
+            .expect("Failed to read edited fields")
+            .is_empty());
+
+        let missing = update_book_fields(&pool, BookId::new(), &update).await;
+        assert!(matches!(missing, Err(AppError::RecordNotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_update_book_fields_marks_user_edits() {
+        let pool = setup().await.expect("Failed to setup database");
+        let book = create_test_book_with_path("/test/edited.mp3");
+        create_book(&pool, &book)
+            .await
+            .expect("Failed to create book");
+
+        for update in [
+            BookUpdate::new().title("Persuasion").user_edited(),
+            BookUpdate::new()
+                .title("Persuasion")
+                .narrator(Some("Juliet Stevenson".to_string()))
+                .user_edited(),
+        ] {
+            update_book_fields(&pool, book.id, &update)
+                .await
+                .expect("Failed to update book");
+        }
+
+        let edited = get_user_edited_fields(&pool, book.id)
+            .await
+            .expect("Failed to read edited fields");
+        assert_eq!(edited, vec!["title", "narrator"]);
+    }
+
+    #[tokio::test]
+    async fn test_file_hashes() {
+        let pool = setup().await.expect("Failed to setup database");
+        let hashed = create_test_book_with_path("/test/hashed.mp3");
+        let unhashed = create_test_book_with_path("/test/unhashed.mp3");
+        for book in [&hashed, &unhashed] {
+            create_book(&pool, book)
+                .await
+                .expect("Failed to create book");
+        }
+
+        set_file_hash(&pool, hashed.id, Some("abc123"))
+            .await
+            .expect("Failed to store hash");
+        let hashes = get_file_hashes(&pool).await.expect("Failed to read hashes");
+        assert_eq!(hashes.len(), 1);
+        assert_eq!(hashes[&hashed.id], "abc123");
+
+        set_file_hash(&pool, hashed.id, None)
+            .await
+            .expect("Failed to clear hash");
+        assert!(get_file_hashes(&pool).await.unwrap().is_empty());
+
+        let missing = set_file_hash(&pool, BookId::new(), Some("abc123")).await;
+        assert!(matches!(missing, Err(AppError::RecordNotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_availability_and_file_modified() {
+        let pool = setup().await.expect("Failed to setup database");
+        let present = create_test_book_with_path("/test/present.mp3");
+        let gone = create_test_book_with_path("/test/gone.mp3");
+        for book in [&present, &gone] {
+            create_book(&pool, book)
+                .await
+                .expect("Failed to create book");
+        }
+        assert!(get_unavailable_books(&pool).await.unwrap().is_empty());
+
+        set_unavailable_books(&pool, &[gone.id])
+            .await
+            .expect("Failed to mark books");
+        assert_eq!(
+            get_unavailable_books(&pool).await.unwrap(),
+            HashSet::from([gone.id])
+        );
+
+        // The drive came back
+        set_unavailable_books(&pool, &[]).await.unwrap();
+        assert!(get_unavailable_books(&pool).await.unwrap().is_empty());
+
+        set_book_available(&pool, present.id, false).await.unwrap();
+        assert!(get_unavailable_books(&pool)
+            .await
+            .unwrap()
+            .contains(&present.id));
+        set_book_available(&pool, present.id, true).await.unwrap();
+        assert!(get_unavailable_books(&pool).await.unwrap().is_empty());
+        assert!(set_book_available(&pool, BookId::new(), false)
+            .await
+            .is_err());
+
+        let found = find_book_by_path(&pool, Path::new("/test/present.mp3"))
+            .await
+            .unwrap()
+            .expect("Book should be found by path");
+        assert_eq!(found.id, present.id);
+        assert!(find_book_by_path(&pool, Path::new("/test/other.mp3"))
+            .await
+            .unwrap()
+            .is_none());
+
+        set_file_modified(&pool, &HashMap::from([(present.id, 1_700_000_000_000)]))
+            .await
+            .expect("Failed to store modification time");
+        let modified = get_file_modified(&pool).await.unwrap();
+        assert_eq!(modified.len(), 1);
+        assert_eq!(modified[&present.id], 1_700_000_000_000);
+    }
+
+    #[tokio::test]
+    async fn test_content_hashes_find_moves_and_duplicates() {
+        let pool = setup().await.expect("Failed to setup database");
+        let original = create_test_book_with_path("/old/book.mp3");
+        let copy = create_test_book_with_path("/backup/book.mp3");
+        let other = create_test_book_with_path("/old/other.mp3");
+        for book in [&original, &copy, &other] {
+            create_book(&pool, book)
+                .await
+                .expect("Failed to create book");
+        }
+
+        set_content_hash(&pool, original.id, Some("same"))
+            .await
+            .expect("Failed to store content hash");
+        set_content_hashes(
+            &pool,
+            &HashMap::from([
+                (copy.id, "same".to_string()),
+                (other.id, "other".to_string()),
+            ]),
+        )
+        .await
+        .expect("Failed to store content hashes");
+        assert_eq!(get_content_hashes(&pool).await.unwrap().len(), 3);
+
+        let matches = find_books_by_content_hash(&pool, "same").await.unwrap();
+        let mut ids: Vec<BookId> = matches.iter().map(|book| book.id).collect();
+        ids.sort_by_key(|id| id.as_string());
+        let mut expected = vec![original.id, copy.id];
+        expected.sort_by_key(|id| id.as_string());
+        assert_eq!(ids, expected);
+
+        let duplicates = find_duplicate_books(&pool).await.unwrap();
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].content_hash, "same");
+        assert_eq!(duplicates[0].books.len(), 2);
+
+        relocate_book(&pool, original.id, Path::new("/new/book.mp3"))
+            .await
+            .expect("Failed to relocate book");
+        let moved = get_book(&pool, original.id).await.unwrap();
+        assert_eq!(moved.file_path, PathBuf::from("/new/book.mp3"));
+        assert_eq!(moved.title, original.title);
+
+        // Trashed books are neither matched nor reported
+        merge_books(&pool, original.id, &[copy.id]).await.unwrap();
+        assert!(find_duplicate_books(&pool).await.unwrap().is_empty());
+        assert_eq!(
+            find_books_by_content_hash(&pool, "same")
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let missing = relocate_book(&pool, BookId::new(), Path::new("/x.mp3")).await;
+        assert!(matches!(missing, Err(AppError::RecordNotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_create_books_in_bulk() {
+        let pool = setup().await.expect("Failed to setup database");
+        let first = create_test_book_with_path("/test/bulk_1.mp3");
+        let second = create_test_book_with_path("/test/bulk_2.mp3");
+        let hashes = HashMap::from([(second.id, "def456".to_string())]);
+
+        create_books(&pool, &[first.clone(), second.clone()], &hashes)
+            .await
+            .expect("Failed to create books");
+        assert_eq!(list_books(&pool).await.unwrap().len(), 2);
+        assert_eq!(get_file_hashes(&pool).await.unwrap(), hashes);
+
+        // A clashing path rolls back the whole batch
+        let third = create_test_book_with_path("/test/bulk_3.mp3");
+        let clash = create_test_book_with_path("/test/bulk_1.mp3");
+        assert!(create_books(&pool, &[third, clash], &HashMap::new())
+            .await
+            .is_err());
+        assert_eq!(list_books(&pool).await.unwrap().len(), 2);
+
+        delete_book(&pool, first.id).await.unwrap();
+        let mut trashed = second.clone();
+        trashed.deleted_at = Some(Timestamp::now());
+        update_book(&pool, &trashed).await.unwrap();
+        let paths = get_file_paths(&pool).await.unwrap();
+        assert_eq!(paths, HashSet::from([PathBuf::from("/test/bulk_2.mp3")]));
+    }
+
+    #[tokio::test]
+    async fn test_create_books_batch() {
+        let pool = setup().await.expect("Failed to setup database");
+        let batch: Vec<Book> = (0..INSERT_ROWS * 2 + 7)
+            .map(|i| create_test_book_with_path(&format!("/test/batch_{}.mp3", i)))
+            .collect();
+
+        let created = create_books_batch(&pool, &batch)
+            .await
+            .expect("Failed to create books");
+        assert_eq!(created, batch.len());
+        assert_eq!(count_books(&pool).await.unwrap(), batch.len() as i64);
+        let last = get_book(&pool, batch[batch.len() - 1].id).await.unwrap();
+        assert_eq!(last.file_path, batch[batch.len() - 1].file_path);
+
+        // A path already in the library rolls back the batch, naming the book
+        let mut clash = create_test_book_with_path("/test/batch_3.mp3");
+        clash.title = "Clash".to_string();
+        let fresh = create_test_book_with_path("/test/batch_fresh.mp3");
+        let err = create_books_batch(&pool, &[fresh.clone(), clash])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("'Clash' (/test/batch_3.mp3)"));
+        assert!(get_book(&pool, fresh.id).await.is_err());
+
+        // Repeats within the batch are caught before the database sees them
+        let twice = create_test_book_with_path("/test/twice.mp3");
+        let again = create_test_book_with_path("/test/twice.mp3");
+        assert!(matches!(
+            create_books_batch(&pool, &[twice, again]).await,
+            Err(AppError::InvalidArgument { .. })
+        ));
+        assert_eq!(count_books(&pool).await.unwrap(), batch.len() as i64);
+    }
+
+    #[tokio::test]
+    async fn test_save_books_all_or_nothing() {
+        let pool = setup().await.expect("Failed to setup database");
+        let existing = create_test_book_with_path("/test/save_1.mp3");
+        create_book(&pool, &existing).await.unwrap();
+
+        let mut renamed = existing.clone();
+        renamed.title = "Renamed".to_string();
+        let added = create_test_book_with_path("/test/save_2.mp3");
+        let hashes = HashMap::from([(renamed.id, "abc123".to_string())]);
+        save_books(
+            &pool,
+            std::slice::from_ref(&added),
+            &[renamed.clone()],
+            &hashes,
+        )
+        .await
+        .expect("Failed to save books");
+        assert_eq!(get_book(&pool, existing.id).await.unwrap().title, "Renamed");
+        assert_eq!(get_file_hashes(&pool).await.unwrap(), hashes);
+
+        // An update onto a path already taken undoes the book created with it
+        let third = create_test_book_with_path("/test/save_3.mp3");
+        let mut clash = renamed.clone();
+        clash.file_path = PathBuf::from("/test/save_2.mp3");
+        assert!(save_books(&pool, &[third], &[clash], &HashMap::new())
+            .await
+            .is_err());
+        assert_eq!(list_books(&pool).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_merge_books() {
+        use crate::queries::{bookmarks, playback, playlists};
+        use storystream_core::{Bookmark, PlaybackState, Playlist, PlaylistItem, PlaylistSession};
+
+        let pool = setup().await.expect("Failed to setup database");
+        let mut keep = create_test_book_with_path("/test/keep.m4b");
+        keep.tags = vec!["fantasy".to_string()];
+        let mut copy = create_test_book_with_path("/test/copy.mp3");
+        copy.tags = vec!["fantasy".to_string(), "re-read".to_string()];
+        for book in [&keep, &copy] {
+            create_book(&pool, book)
+                .await
+                .expect("Failed to create book");
+        }
+
+        bookmarks::create_bookmark(&pool, &Bookmark::new(copy.id, Duration::from_seconds(60)))
+            .await
+            .expect("Failed to create bookmark");
+        let mut behind = PlaybackState::new(keep.id);
+        behind.position = Duration::from_seconds(100);
+        let mut ahead = PlaybackState::new(copy.id);
+        ahead.position = Duration::from_seconds(900);
+        for state in [&behind, &ahead] {
+            playback::create_playback_state(&pool, state)
+                .await
+                .expect("Failed to save playback state");
+        }
+
+        let both = Playlist::new_manual("Both".to_string());
+        let one = Playlist::new_manual("Copy only".to_string());
+        for playlist in [&both, &one] {
+            playlists::create_playlist(&pool, playlist)
+                .await
+                .expect("Failed to create playlist");
+        }
+        for item in [
+            PlaylistItem::new(both.id, keep.id, 0),
+            PlaylistItem::new(both.id, copy.id, 1),
+            PlaylistItem::new(one.id, copy.id, 0),
+        ] {
+            playlists::add_book_to_playlist(&pool, &item)
+                .await
+                .expect("Failed to add playlist item");
+        }
+        let session = PlaylistSession::new(both.id, vec![keep.id, copy.id], false);
+        playlists::save_playlist_session(&pool, &session)
+            .await
+            .expect("Failed to save session");
+
+        merge_books(&pool, keep.id, &[copy.id])
+            .await
+            .expect("Failed to merge books");
+
+        let books = list_books(&pool).await.expect("Failed to list books");
+        assert_eq!(books.len(), 1);
+        assert_eq!(books[0].id, keep.id);
+        assert_eq!(books[0].tags, vec!["fantasy", "re-read"]);
+        assert!(get_book(&pool, copy.id).await.unwrap().is_deleted());
+
+        let marks = bookmarks::get_book_bookmarks(&pool, keep.id).await.unwrap();
+        assert_eq!(marks.len(), 1);
+        let state = playback::get_playback_state(&pool, keep.id).await.unwrap();
+        assert_eq!(state.position, Duration::from_seconds(900));
+        assert!(playback::get_playback_state(&pool, copy.id).await.is_err());
+
+        for playlist in [&both, &one] {
+            let books = playlists::get_playlist_books(&pool, playlist.id)
+                .await
+                .unwrap();
+            assert_eq!(books.len(), 1);
+            assert_eq!(books[0].id, keep.id);
+        }
+        let session = playlists::get_playlist_session(&pool)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(session.queue, vec![keep.id]);
+
+        let itself = merge_books(&pool, keep.id, &[keep.id]).await;
+        assert!(matches!(itself, Err(AppError::InvalidArgument { .. })));
+        let again = merge_books(&pool, keep.id, &[copy.id]).await;
+        assert!(matches!(again, Err(AppError::RecordNotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_replace_source() {
+        use crate::queries::{bookmarks, chapters, loudness, playback};
+        use storystream_core::{Bookmark, PlaybackState};
+
+        let pool = setup().await.expect("Failed to setup database");
+        let mut book = create_test_book_with_path("/test/low.mp3");
+        create_book(&pool, &book)
+            .await
+            .expect("Failed to create book");
+        set_file_hash(&pool, book.id, Some("old")).await.unwrap();
+        loudness::set_loudness(&pool, book.id, Some(-18.0))
+            .await
+            .unwrap();
+        loudness::set_replay_gain(&pool, book.id, Some(0.0))
+            .await
+            .unwrap();
+        for seconds in [0, 1800] {
+            bookmarks::create_bookmark(
+                &pool,
+                &Bookmark::new(book.id, Duration::from_seconds(seconds)),
+            )
+            .await
+            .expect("Failed to create bookmark");
+        }
+        let mut state = PlaybackState::new(book.id);
+        state.position = Duration::from_seconds(3000);
+        playback::create_playback_state(&pool, &state)
+            .await
+            .expect("Failed to save playback state");
+
+        book.file_path = PathBuf::from("/test/high.flac");
+        book.file_size = 9_000_000;
+        book.duration = Duration::from_seconds(3636);
+        let chapter = Chapter::new(
+            book.id,
+            "Whole book".to_string(),
+            0,
+            Duration::from_seconds(0),
+            book.duration,
+        );
+        let moved = replace_source(&pool, &book, Some("new"), Some(&[chapter]), 1.01)
+            .await
+            .expect("Failed to replace source");
+
+        assert_eq!(moved.bookmarks, 1);
+        assert!(moved.playback);
+        let stored = get_book(&pool, book.id).await.unwrap();
+        assert_eq!(stored.file_path, book.file_path);
+        assert_eq!(stored.duration, book.duration);
+        assert_eq!(get_file_hashes(&pool).await.unwrap()[&book.id], "new");
+        assert_eq!(loudness::get_loudness(&pool, book.id).await.unwrap(), None);
+        assert_eq!(
+            loudness::get_replay_gain(&pool, book.id).await.unwrap(),
+            None
+        );
+        let mut marks: Vec<u64> = bookmarks::get_book_bookmarks(&pool, book.id)
+            .await
+            .unwrap()
+            .iter()
+            .map(|b| b.position.as_seconds())
+            .collect();
+        marks.sort();
+        assert_eq!(marks, vec![0, 1818]);
+        let state = playback::get_playback_state(&pool, book.id).await.unwrap();
+        assert_eq!(state.position, Duration::from_seconds(3030));
+        let stored = chapters::get_book_chapters(&pool, book.id).await.unwrap();
+        assert_eq!(stored.len(), 1);
+
+        let gone = create_test_book_with_path("/test/gone.mp3");
+        let missing = replace_source(&pool, &gone, None, None, 1.0).await;
+        assert!(matches!(missing, Err(AppError::RecordNotFound { .. })));
     }
 }
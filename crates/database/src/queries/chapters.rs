@@ -56,6 +56,92 @@ pub async fn get_book_chapters(pool: &DbPool, book_id: BookId) -> Result<Vec<Cha
     rows.into_iter().map(row_to_chapter).collect()
 }
 
+/// Updates a chapter's title, position and image
+pub async fn update_chapter(pool: &DbPool, chapter: &Chapter) -> Result<(), AppError> {
+    let result = sqlx::query(
+        r#"
+        UPDATE chapters
+        SET title = ?, index_number = ?, start_time_ms = ?, end_time_ms = ?, image_path = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(&chapter.title)
+    .bind(chapter.index as i64)
+    .bind(chapter.start_time.as_millis() as i64)
+    .bind(chapter.end_time.as_millis() as i64)
+    .bind(chapter.image_path.as_ref().and_then(|p| p.to_str()))
+    .bind(chapter.id.as_string())
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to update chapter", e))?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::RecordNotFound {
+            entity: "Chapter".to_string(),
+            identifier: chapter.id.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Replaces all chapters of a book in one transaction
+///
+/// Chapters missing from `chapters` are deleted, so a split or merge is saved
+/// by passing the whole edited list.
+pub async fn replace_book_chapters(
+    pool: &DbPool,
+    book_id: BookId,
+    chapters: &[Chapter],
+) -> Result<(), AppError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| AppError::database("Failed to begin transaction", e))?;
+
+    write_book_chapters(&mut tx, book_id, chapters).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::database("Failed to commit transaction", e))?;
+
+    Ok(())
+}
+
+/// Replaces all chapters of a book within a transaction
+pub(crate) async fn write_book_chapters(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    book_id: BookId,
+    chapters: &[Chapter],
+) -> Result<(), AppError> {
+    sqlx::query("DELETE FROM chapters WHERE book_id = ?")
+        .bind(book_id.as_string())
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| AppError::database("Failed to clear book chapters", e))?;
+
+    for chapter in chapters {
+        sqlx::query(
+            r#"
+            INSERT INTO chapters (id, book_id, title, index_number, start_time_ms, end_time_ms, image_path)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+            .bind(chapter.id.as_string())
+            .bind(book_id.as_string())
+            .bind(&chapter.title)
+            .bind(chapter.index as i64)
+            .bind(chapter.start_time.as_millis() as i64)
+            .bind(chapter.end_time.as_millis() as i64)
+            .bind(chapter.image_path.as_ref().and_then(|p| p.to_str()))
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| AppError::database("Failed to insert chapter", e))?;
+    }
+
+    Ok(())
+}
+
 /// Deletes a chapter
 pub async fn delete_chapter(pool: &DbPool, id: ChapterId) -> Result<(), AppError> {
     sqlx::query("DELETE FROM chapters WHERE id = ?")
@@ -210,4 +296,91 @@ mod tests {
         let result = get_chapter(&pool, chapter.id).await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_update_chapter() {
+        let pool = setup().await;
+
+        let book = Book::new(
+            "Test".to_string(),
+            PathBuf::from("/test.mp3"),
+            1000,
+            Duration::from_seconds(100),
+        );
+        create_book(&pool, &book).await.unwrap();
+
+        let mut chapter = Chapter::new(
+            book.id,
+            "Chaptr 1".to_string(),
+            1,
+            Duration::from_seconds(0),
+            Duration::from_seconds(100),
+        );
+        create_chapter(&pool, &chapter).await.unwrap();
+
+        chapter.title = "Chapter 1".to_string();
+        chapter.start_time = Duration::from_seconds(2);
+        update_chapter(&pool, &chapter).await.unwrap();
+
+        let retrieved = get_chapter(&pool, chapter.id).await.unwrap();
+        assert_eq!(retrieved.title, "Chapter 1");
+        assert_eq!(retrieved.start_time, Duration::from_seconds(2));
+
+        let missing = Chapter::new(
+            book.id,
+            "Missing".to_string(),
+            2,
+            Duration::from_seconds(0),
+            Duration::from_seconds(1),
+        );
+        assert!(update_chapter(&pool, &missing).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_replace_book_chapters() {
+        let pool = setup().await;
+
+        let book = Book::new(
+            "Test".to_string(),
+            PathBuf::from("/test.mp3"),
+            1000,
+            Duration::from_seconds(200),
+        );
+        create_book(&pool, &book).await.unwrap();
+
+        let old = Chapter::new(
+            book.id,
+            "Whole book".to_string(),
+            0,
+            Duration::from_seconds(0),
+            Duration::from_seconds(200),
+        );
+        create_chapter(&pool, &old).await.unwrap();
+
+        let replacement = vec![
+            Chapter::new(
+                book.id,
+                "Part 1".to_string(),
+                0,
+                Duration::from_seconds(0),
+                Duration::from_seconds(120),
+            ),
+            Chapter::new(
+                book.id,
+                "Part 2".to_string(),
+                1,
+                Duration::from_seconds(120),
+                Duration::from_seconds(200),
+            ),
+        ];
+        replace_book_chapters(&pool, book.id, &replacement)
+            .await
+            .unwrap();
+
+        let chapters = get_book_chapters(&pool, book.id).await.unwrap();
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[0].title, "Part 1");
+        assert_eq!(chapters[1].start_time, Duration::from_seconds(120));
+        assert!(get_chapter(&pool, old.id).await.is_err());
+    }
 }
@@ -0,0 +1,309 @@
+//! Measured loudness of books
+//!
+//! Loudness is measured once per book by the library's analysis job and
+//! kept in `books.loudness_db`, along with the gain that evens it out in
+//! `books.replay_gain_db`, so playback can even out levels without
+//! decoding anything first.
+
+use crate::DbPool;
+use serde::Serialize;
+use std::collections::HashMap;
+use storystream_core::{AppError, BookId};
+
+/// How the loudness of the analyzed books is spread
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LoudnessSummary {
+    /// Books with a measured loudness
+    pub analyzed: usize,
+    /// Books not measured yet
+    pub pending: usize,
+    /// Loudness levels in dB, `None` until a book has been analyzed
+    pub quietest_db: Option<f32>,
+    pub loudest_db: Option<f32>,
+    pub median_db: Option<f32>,
+    /// A tenth of the books are quieter than this
+    pub p10_db: Option<f32>,
+    /// A tenth of the books are louder than this
+    pub p90_db: Option<f32>,
+}
+
+/// A book much louder or quieter than the rest of the library
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LoudnessOutlier {
+    pub book_id: BookId,
+    pub title: String,
+    pub loudness_db: f32,
+    /// Difference from the library's median, positive when louder
+    pub deviation_db: f32,
+}
+
+/// Stores a book's measured loudness, or clears it with `None`
+///
+/// Clearing it clears the book's replay gain as well, since the gain was
+/// worked out from it.
+pub async fn set_loudness(
+    pool: &DbPool,
+    id: BookId,
+    loudness_db: Option<f32>,
+) -> Result<(), AppError> {
+    let result = sqlx::query(
+        "UPDATE books SET loudness_db = ?1, \
+         replay_gain_db = CASE WHEN ?1 IS NULL THEN NULL ELSE replay_gain_db END \
+         WHERE id = ?2",
+    )
+    .bind(loudness_db.map(f64::from))
+    .bind(id.as_string())
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to store loudness", e))?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::RecordNotFound {
+            entity: "Book".to_string(),
+            identifier: id.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Gets a book's measured loudness, `None` if it has not been analyzed
+pub async fn get_loudness(pool: &DbPool, id: BookId) -> Result<Option<f32>, AppError> {
+    let loudness: Option<Option<f64>> =
+        sqlx::query_scalar("SELECT loudness_db FROM books WHERE id = ?")
+            .bind(id.as_string())
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| AppError::database("Failed to read loudness", e))?;
+
+    match loudness {
+        Some(loudness) => Ok(loudness.map(|db| db as f32)),
+        None => Err(AppError::RecordNotFound {
+            entity: "Book".to_string(),
+            identifier: id.to_string(),
+        }),
+    }
+}
+
+/// Stores the gain in dB that brings a book to the playback loudness target
+pub async fn set_replay_gain(
+    pool: &DbPool,
+    id: BookId,
+    replay_gain_db: Option<f32>,
+) -> Result<(), AppError> {
+    let result = sqlx::query("UPDATE books SET replay_gain_db = ? WHERE id = ?")
+        .bind(replay_gain_db.map(f64::from))
+        .bind(id.as_string())
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::database("Failed to store replay gain", e))?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::RecordNotFound {
+            entity: "Book".to_string(),
+            identifier: id.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Gets a book's replay gain in dB, `None` if it has not been analyzed
+pub async fn get_replay_gain(pool: &DbPool, id: BookId) -> Result<Option<f32>, AppError> {
+    let gain: Option<Option<f64>> =
+        sqlx::query_scalar("SELECT replay_gain_db FROM books WHERE id = ?")
+            .bind(id.as_string())
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| AppError::database("Failed to read replay gain", e))?;
+
+    match gain {
+        Some(gain) => Ok(gain.map(|db| db as f32)),
+        None => Err(AppError::RecordNotFound {
+            entity: "Book".to_string(),
+            identifier: id.to_string(),
+        }),
+    }
+}
+
+/// Gets the measured loudness of every book that has one
+pub async fn get_loudness_levels(pool: &DbPool) -> Result<HashMap<BookId, f32>, AppError> {
+    let rows: Vec<(String, f64)> = sqlx::query_as(
+        "SELECT id, loudness_db FROM books WHERE loudness_db IS NOT NULL AND deleted_at IS NULL",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to read loudness levels", e))?;
+
+    rows.into_iter()
+        .map(|(id, db)| {
+            let id =
+                BookId::from_string(&id).map_err(|e| AppError::database("Invalid book ID", e))?;
+            Ok((id, db as f32))
+        })
+        .collect()
+}
+
+/// Sums up the loudness of the library's books
+pub async fn loudness_summary(pool: &DbPool) -> Result<LoudnessSummary, AppError> {
+    let levels: Vec<f64> = sqlx::query_scalar(
+        r#"
+        SELECT loudness_db FROM books
+        WHERE loudness_db IS NOT NULL AND deleted_at IS NULL
+        ORDER BY loudness_db
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to read loudness levels", e))?;
+    let pending: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM books WHERE loudness_db IS NULL AND deleted_at IS NULL",
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to count unanalyzed books", e))?;
+
+    let at = |fraction: f64| percentile(&levels, fraction);
+    Ok(LoudnessSummary {
+        analyzed: levels.len(),
+        pending: pending as usize,
+        quietest_db: at(0.0),
+        loudest_db: at(1.0),
+        median_db: at(0.5),
+        p10_db: at(0.1),
+        p90_db: at(0.9),
+    })
+}
+
+/// Lists books more than `threshold_db` louder or quieter than the median,
+/// furthest off first
+pub async fn loudness_outliers(
+    pool: &DbPool,
+    threshold_db: f32,
+) -> Result<Vec<LoudnessOutlier>, AppError> {
+    let rows: Vec<(String, String, f64)> = sqlx::query_as(
+        r#"
+        SELECT id, title, loudness_db FROM books
+        WHERE loudness_db IS NOT NULL AND deleted_at IS NULL
+        ORDER BY loudness_db
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to read loudness levels", e))?;
+
+    let levels: Vec<f64> = rows.iter().map(|(_, _, db)| *db).collect();
+    let Some(median) = percentile(&levels, 0.5) else {
+        return Ok(Vec::new());
+    };
+
+    let mut outliers = Vec::new();
+    for (id, title, db) in rows {
+        let deviation_db = db as f32 - median;
+        if deviation_db.abs() > threshold_db {
+            outliers.push(LoudnessOutlier {
+                book_id: BookId::from_string(&id)
+                    .map_err(|e| AppError::database("Invalid book ID", e))?,
+                title,
+                loudness_db: db as f32,
+                deviation_db,
+            });
+        }
+    }
+    outliers.sort_by(|a, b| b.deviation_db.abs().total_cmp(&a.deviation_db.abs()));
+    Ok(outliers)
+}
+
+/// The level `fraction` of the way up `sorted`, interpolating between books
+fn percentile(sorted: &[f64], fraction: f64) -> Option<f32> {
+    let last = sorted.len().checked_sub(1)?;
+    let position = fraction.clamp(0.0, 1.0) * last as f64;
+    let below = sorted[position.floor() as usize];
+    let above = sorted[position.ceil() as usize];
+    Some((below + (above - below) * position.fract()) as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::create_test_db;
+    use crate::migrations::run_migrations;
+    use crate::queries::books::create_book;
+    use std::path::PathBuf;
+    use storystream_core::{Book, Duration};
+
+    async fn library(levels: &[Option<f32>]) -> (DbPool, Vec<BookId>) {
+        let pool = create_test_db().await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        let mut ids = Vec::new();
+        for (i, level) in levels.iter().enumerate() {
+            let book = Book::new(
+                format!("Book {}", i),
+                PathBuf::from(format!("/test/{}.mp3", i)),
+                1000,
+                Duration::from_seconds(3600),
+            );
+            create_book(&pool, &book).await.unwrap();
+            set_loudness(&pool, book.id, *level).await.unwrap();
+            ids.push(book.id);
+        }
+        (pool, ids)
+    }
+
+    #[tokio::test]
+    async fn test_loudness_round_trip() {
+        let (pool, ids) = library(&[Some(-18.5), None]).await;
+        assert_eq!(get_loudness(&pool, ids[0]).await.unwrap(), Some(-18.5));
+        assert_eq!(get_loudness(&pool, ids[1]).await.unwrap(), None);
+        assert_eq!(
+            get_loudness_levels(&pool).await.unwrap(),
+            HashMap::from([(ids[0], -18.5)])
+        );
+
+        set_replay_gain(&pool, ids[0], Some(0.5)).await.unwrap();
+        assert_eq!(get_replay_gain(&pool, ids[0]).await.unwrap(), Some(0.5));
+        assert_eq!(get_replay_gain(&pool, ids[1]).await.unwrap(), None);
+
+        // A cleared loudness takes its gain with it
+        set_loudness(&pool, ids[0], None).await.unwrap();
+        assert!(get_loudness_levels(&pool).await.unwrap().is_empty());
+        assert_eq!(get_replay_gain(&pool, ids[0]).await.unwrap(), None);
+        assert!(matches!(
+            set_loudness(&pool, BookId::new(), Some(-20.0)).await,
+            Err(AppError::RecordNotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_summary_and_outliers() {
+        let (pool, ids) = library(&[
+            Some(-20.0),
+            Some(-19.0),
+            Some(-18.0),
+            Some(-30.0),
+            Some(-10.0),
+            None,
+        ])
+        .await;
+
+        let summary = loudness_summary(&pool).await.unwrap();
+        assert_eq!((summary.analyzed, summary.pending), (5, 1));
+        assert_eq!(summary.quietest_db, Some(-30.0));
+        assert_eq!(summary.loudest_db, Some(-10.0));
+        assert_eq!(summary.median_db, Some(-19.0));
+        assert_eq!(summary.p10_db, Some(-26.0));
+
+        let outliers = loudness_outliers(&pool, 6.0).await.unwrap();
+        let found: Vec<_> = outliers
+            .iter()
+            .map(|outlier| (outlier.book_id, outlier.deviation_db))
+            .collect();
+        assert_eq!(found, [(ids[3], -11.0), (ids[4], 9.0)]);
+
+        let empty = create_test_db().await.unwrap();
+        run_migrations(&empty).await.unwrap();
+        assert_eq!(
+            loudness_summary(&empty).await.unwrap(),
+            LoudnessSummary::default()
+        );
+        assert!(loudness_outliers(&empty, 6.0).await.unwrap().is_empty());
+    }
+}
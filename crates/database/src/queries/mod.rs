@@ -3,18 +3,52 @@
 pub mod bookmarks;
 pub mod books;
 pub mod chapters;
+pub mod loudness;
 pub mod playback;
 pub mod playlists;
+pub mod podcasts;
+pub mod stats;
 
 // Re-export commonly used query functions
-pub use bookmarks::{create_bookmark, delete_bookmark, get_book_bookmarks, get_bookmark};
+pub use bookmarks::{
+    create_bookmark, delete_auto_bookmarks, delete_bookmark, get_book_bookmarks, get_bookmark,
+    list_bookmarks,
+};
 pub use books::{
-    create_book, delete_book, get_book, get_books_by_author, get_favorite_books,
-    get_recently_played_books, list_books, update_book,
+    create_book, create_books, create_books_batch, delete_book, find_book_by_path,
+    find_books_by_content_hash, find_duplicate_books, get_book, get_books_by_author,
+    get_books_by_narrator, get_books_by_series, get_content_hashes, get_favorite_books,
+    get_file_hashes, get_file_modified, get_file_paths, get_newest_unplayed_by_author,
+    get_next_in_series, get_recently_played_books, get_unavailable_books, get_user_edited_fields,
+    list_books, list_books_paged, merge_books, relocate_book, replace_source, save_books,
+    set_book_available, set_content_hash, set_content_hashes, set_file_hash, set_file_hashes,
+    set_file_modified, set_unavailable_books, update_book, update_book_fields, BookSort,
+    BookUpdate, ContentDuplicates, MovedPositions, PagedBooks,
+};
+pub use chapters::{
+    create_chapter, delete_chapter, get_book_chapters, get_chapter, replace_book_chapters,
+    update_chapter,
+};
+pub use loudness::{
+    get_loudness, get_loudness_levels, get_replay_gain, loudness_outliers, loudness_summary,
+    set_loudness, set_replay_gain, LoudnessOutlier, LoudnessSummary,
+};
+pub use playback::{
+    create_playback_state, get_equalizer, get_playback_state, list_playback_states, set_equalizer,
+    update_playback_state,
 };
-pub use chapters::{create_chapter, delete_chapter, get_book_chapters, get_chapter};
-pub use playback::{create_playback_state, get_playback_state, update_playback_state};
 pub use playlists::{
-    add_book_to_playlist, create_playlist, delete_playlist, get_playlist, get_playlist_books,
-    remove_book_from_playlist,
+    add_book_to_playlist, clear_playlist_session, create_playlist, delete_playlist,
+    get_next_unfinished_in_playlist, get_playlist, get_playlist_books, get_playlist_session,
+    remove_book_from_playlist, save_playlist_session,
+};
+pub use podcasts::{
+    add_episode_if_new, clear_episode_download, count_unplayed_episodes, create_podcast,
+    delete_podcast, find_finished_episodes, find_podcast_by_url, get_podcast, get_podcast_episodes,
+    link_episode_book, list_podcasts, mark_episode_downloaded, mark_episode_played,
+    update_last_checked, update_podcast,
+};
+pub use stats::{
+    daily_listening, get_book_stats, get_library_stats, get_playback_stats, get_top_authors,
+    record_listening_session, BookStats, DailyListening,
 };
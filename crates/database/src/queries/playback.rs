@@ -1,10 +1,23 @@
 //! Playback state database operations
 
 use crate::DbPool;
+use std::collections::HashMap;
+use storystream_core::types::{EqualizerPreset, SpeedRamp};
 use storystream_core::{AppError, BookId, Duration, PlaybackSpeed, PlaybackState, Timestamp};
 
 /// Creates or updates playback state for a book
 pub async fn create_playback_state(pool: &DbPool, state: &PlaybackState) -> Result<(), AppError> {
+    save_playback_state(pool, state).await
+}
+
+/// Upserts playback state on a pool or inside a transaction
+pub(crate) async fn save_playback_state<'e, E>(
+    executor: E,
+    state: &PlaybackState,
+) -> Result<(), AppError>
+where
+    E: sqlx::SqliteExecutor<'e>,
+{
     let equalizer_json = state
         .equalizer
         .as_ref()
@@ -19,12 +32,19 @@ pub async fn create_playback_state(pool: &DbPool, state: &PlaybackState) -> Resu
         .transpose()
         .map_err(|e| AppError::database("Failed to serialize sleep timer", e))?;
 
+    let speed_ramp_json = state
+        .speed_ramp
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| AppError::database("Failed to serialize speed ramp", e))?;
+
     sqlx::query(
         r#"
         INSERT INTO playback_state (
             book_id, position_ms, speed, pitch_correction, volume, is_playing,
-            equalizer_preset, sleep_timer, skip_silence, volume_boost, last_updated
-        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            equalizer_preset, sleep_timer, skip_silence, volume_boost, speed_ramp, last_updated
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         ON CONFLICT(book_id) DO UPDATE SET
             position_ms = excluded.position_ms,
             speed = excluded.speed,
@@ -35,6 +55,7 @@ pub async fn create_playback_state(pool: &DbPool, state: &PlaybackState) -> Resu
             sleep_timer = excluded.sleep_timer,
             skip_silence = excluded.skip_silence,
             volume_boost = excluded.volume_boost,
+            speed_ramp = excluded.speed_ramp,
             last_updated = excluded.last_updated
         "#,
     )
@@ -48,8 +69,9 @@ pub async fn create_playback_state(pool: &DbPool, state: &PlaybackState) -> Resu
     .bind(sleep_timer_json)
     .bind(state.skip_silence as i64)
     .bind(state.volume_boost as i64)
+    .bind(speed_ramp_json)
     .bind(state.last_updated.as_millis())
-    .execute(pool)
+    .execute(executor)
     .await
     .map_err(|e| AppError::database("Failed to save playback state", e))?;
 
@@ -61,7 +83,8 @@ pub async fn get_playback_state(pool: &DbPool, book_id: BookId) -> Result<Playba
     let row = sqlx::query(
         r#"
         SELECT book_id, position_ms, speed, pitch_correction, volume, is_playing,
-               equalizer_preset, sleep_timer, skip_silence, volume_boost, last_updated
+               equalizer_preset, sleep_timer, skip_silence, volume_boost, speed_ramp,
+               last_updated
         FROM playback_state WHERE book_id = ?
         "#,
     )
@@ -77,19 +100,165 @@ pub async fn get_playback_state(pool: &DbPool, book_id: BookId) -> Result<Playba
     row_to_playback_state(row)
 }
 
-/// Updates playback position (for frequent saves)
+/// Lists playback state for every book that has been opened
+pub async fn list_playback_states(pool: &DbPool) -> Result<Vec<PlaybackState>, AppError> {
+    let rows = sqlx::query(
+        r#"
+        SELECT book_id, position_ms, speed, pitch_correction, volume, is_playing,
+               equalizer_preset, sleep_timer, skip_silence, volume_boost, speed_ramp,
+               last_updated
+        FROM playback_state ORDER BY last_updated DESC
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to list playback states", e))?;
+
+    rows.into_iter().map(row_to_playback_state).collect()
+}
+
+/// Gets the saved position of every book that has been opened
+pub async fn get_positions(pool: &DbPool) -> Result<HashMap<BookId, Duration>, AppError> {
+    let rows: Vec<(String, i64)> =
+        sqlx::query_as("SELECT book_id, position_ms FROM playback_state")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| AppError::database("Failed to read playback positions", e))?;
+
+    rows.into_iter()
+        .map(|(id, position_ms)| {
+            let id =
+                BookId::from_string(&id).map_err(|e| AppError::database("Invalid book ID", e))?;
+            Ok((id, Duration::from_millis(position_ms.max(0) as u64)))
+        })
+        .collect()
+}
+
+/// Saves a book's position, speed and volume as it plays
+///
+/// Called often, so the equalizer and other settings are left alone.
+/// Creates the book's playback state if it has none yet.
 pub async fn update_playback_state(
     pool: &DbPool,
     book_id: BookId,
     position: Duration,
+    speed: PlaybackSpeed,
+    volume: u8,
 ) -> Result<(), AppError> {
-    sqlx::query("UPDATE playback_state SET position_ms = ?, last_updated = ? WHERE book_id = ?")
-        .bind(position.as_millis() as i64)
-        .bind(Timestamp::now().as_millis())
-        .bind(book_id.as_string())
-        .execute(pool)
-        .await
-        .map_err(|e| AppError::database("Failed to update playback position", e))?;
+    sqlx::query(
+        r#"
+        INSERT INTO playback_state (book_id, position_ms, speed, volume, last_updated)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(book_id) DO UPDATE SET
+            position_ms = excluded.position_ms,
+            speed = excluded.speed,
+            volume = excluded.volume,
+            last_updated = excluded.last_updated
+        "#,
+    )
+    .bind(book_id.as_string())
+    .bind(position.as_millis() as i64)
+    .bind(speed.value() as f64)
+    .bind(volume.min(100) as i64)
+    .bind(Timestamp::now().as_millis())
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to update playback position", e))?;
+
+    Ok(())
+}
+
+/// Gets the equalizer a book plays with, `None` if it uses the default
+pub async fn get_equalizer(
+    pool: &DbPool,
+    book_id: BookId,
+) -> Result<Option<EqualizerPreset>, AppError> {
+    let json: Option<Option<String>> =
+        sqlx::query_scalar("SELECT equalizer_preset FROM playback_state WHERE book_id = ?")
+            .bind(book_id.as_string())
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| AppError::database("Failed to fetch equalizer", e))?;
+
+    json.flatten()
+        .filter(|s| !s.is_empty())
+        .map(|json| serde_json::from_str(&json))
+        .transpose()
+        .map_err(|e| AppError::database("Failed to deserialize equalizer", e))
+}
+
+/// Sets the equalizer a book plays with, `None` to use the default
+///
+/// Creates the book's playback state if it has none yet.
+pub async fn set_equalizer(
+    pool: &DbPool,
+    book_id: BookId,
+    equalizer: Option<&EqualizerPreset>,
+) -> Result<(), AppError> {
+    let json = equalizer
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| AppError::database("Failed to serialize equalizer", e))?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO playback_state (book_id, equalizer_preset, last_updated)
+        VALUES (?, ?, ?)
+        ON CONFLICT(book_id) DO UPDATE SET equalizer_preset = excluded.equalizer_preset
+        "#,
+    )
+    .bind(book_id.as_string())
+    .bind(json)
+    .bind(Timestamp::now().as_millis())
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to save equalizer", e))?;
+
+    Ok(())
+}
+
+/// Gets a book's speed ramp, `None` if it plays at a fixed speed
+pub async fn get_speed_ramp(pool: &DbPool, book_id: BookId) -> Result<Option<SpeedRamp>, AppError> {
+    let json: Option<Option<String>> =
+        sqlx::query_scalar("SELECT speed_ramp FROM playback_state WHERE book_id = ?")
+            .bind(book_id.as_string())
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| AppError::database("Failed to fetch speed ramp", e))?;
+
+    json.flatten()
+        .filter(|s| !s.is_empty())
+        .map(|json| serde_json::from_str(&json))
+        .transpose()
+        .map_err(|e| AppError::database("Failed to deserialize speed ramp", e))
+}
+
+/// Sets a book's speed ramp and its progress, `None` for a fixed speed
+///
+/// Creates the book's playback state if it has none yet.
+pub async fn set_speed_ramp(
+    pool: &DbPool,
+    book_id: BookId,
+    ramp: Option<&SpeedRamp>,
+) -> Result<(), AppError> {
+    let json = ramp
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| AppError::database("Failed to serialize speed ramp", e))?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO playback_state (book_id, speed_ramp, last_updated)
+        VALUES (?, ?, ?)
+        ON CONFLICT(book_id) DO UPDATE SET speed_ramp = excluded.speed_ramp
+        "#,
+    )
+    .bind(book_id.as_string())
+    .bind(json)
+    .bind(Timestamp::now().as_millis())
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to save speed ramp", e))?;
 
     Ok(())
 }
@@ -142,6 +311,13 @@ fn row_to_playback_state(row: sqlx::sqlite::SqliteRow) -> Result<PlaybackState,
         .transpose()
         .map_err(|e| AppError::database("Failed to deserialize sleep timer", e))?;
 
+    let speed_ramp_json: Option<String> = row.try_get("speed_ramp").ok();
+    let speed_ramp = speed_ramp_json
+        .filter(|s| !s.is_empty())
+        .map(|json| serde_json::from_str(&json))
+        .transpose()
+        .map_err(|e| AppError::database("Failed to deserialize speed ramp", e))?;
+
     let speed_obj =
         PlaybackSpeed::new_unchecked(speed as f32).with_pitch_correction(pitch_correction != 0);
 
@@ -155,6 +331,7 @@ fn row_to_playback_state(row: sqlx::sqlite::SqliteRow) -> Result<PlaybackState,
         sleep_timer,
         skip_silence: skip_silence != 0,
         volume_boost: volume_boost as u8,
+        speed_ramp,
         last_updated: Timestamp::from_millis(last_updated_ms),
     })
 }
@@ -207,14 +384,136 @@ mod tests {
         );
         create_book(&pool, &book).await.unwrap();
 
-        let state = PlaybackState::new(book.id);
+        let mut state = PlaybackState::new(book.id);
+        state.equalizer = Some(EqualizerPreset::voice_boost());
         create_playback_state(&pool, &state).await.unwrap();
 
-        update_playback_state(&pool, book.id, Duration::from_seconds(50))
+        let speed = PlaybackSpeed::new(1.5).unwrap();
+        update_playback_state(&pool, book.id, Duration::from_seconds(50), speed, 80)
             .await
             .unwrap();
 
         let retrieved = get_playback_state(&pool, book.id).await.unwrap();
         assert_eq!(retrieved.position, Duration::from_seconds(50));
+        assert_eq!(retrieved.speed.value(), 1.5);
+        assert_eq!(retrieved.volume, 80);
+        assert_eq!(retrieved.equalizer, state.equalizer);
+
+        // The first save of a book creates its state
+        let other = Book::new(
+            "Other".to_string(),
+            PathBuf::from("/other.mp3"),
+            1000,
+            Duration::from_seconds(100),
+        );
+        create_book(&pool, &other).await.unwrap();
+        update_playback_state(&pool, other.id, Duration::from_seconds(7), speed, 100)
+            .await
+            .unwrap();
+        let retrieved = get_playback_state(&pool, other.id).await.unwrap();
+        assert_eq!(retrieved.position, Duration::from_seconds(7));
+    }
+
+    #[tokio::test]
+    async fn test_list_playback_states() {
+        let pool = setup().await;
+
+        let book = Book::new(
+            "Test".to_string(),
+            PathBuf::from("/test.mp3"),
+            1000,
+            Duration::from_seconds(100),
+        );
+        create_book(&pool, &book).await.unwrap();
+        assert!(list_playback_states(&pool).await.unwrap().is_empty());
+
+        create_playback_state(&pool, &PlaybackState::new(book.id))
+            .await
+            .unwrap();
+
+        let states = list_playback_states(&pool).await.unwrap();
+        assert_eq!(states.len(), 1);
+        assert_eq!(states[0].book_id, book.id);
+    }
+
+    #[tokio::test]
+    async fn test_get_positions() {
+        let pool = setup().await;
+        assert!(get_positions(&pool).await.unwrap().is_empty());
+
+        let book = Book::new(
+            "Test".to_string(),
+            PathBuf::from("/test.mp3"),
+            1000,
+            Duration::from_seconds(100),
+        );
+        create_book(&pool, &book).await.unwrap();
+        let speed = PlaybackSpeed::new(1.0).unwrap();
+        update_playback_state(&pool, book.id, Duration::from_seconds(40), speed, 100)
+            .await
+            .unwrap();
+
+        let positions = get_positions(&pool).await.unwrap();
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[&book.id], Duration::from_seconds(40));
+    }
+
+    #[tokio::test]
+    async fn test_book_equalizer() {
+        let pool = setup().await;
+
+        let book = Book::new(
+            "Test".to_string(),
+            PathBuf::from("/test.mp3"),
+            1000,
+            Duration::from_seconds(100),
+        );
+        create_book(&pool, &book).await.unwrap();
+        assert_eq!(get_equalizer(&pool, book.id).await.unwrap(), None);
+
+        // Works before the book has any playback state
+        let voice = EqualizerPreset::voice_boost();
+        set_equalizer(&pool, book.id, Some(&voice)).await.unwrap();
+        assert_eq!(get_equalizer(&pool, book.id).await.unwrap(), Some(voice));
+
+        update_playback_state(
+            &pool,
+            book.id,
+            Duration::from_seconds(50),
+            PlaybackSpeed::default(),
+            100,
+        )
+        .await
+        .unwrap();
+        set_equalizer(&pool, book.id, None).await.unwrap();
+        let state = get_playback_state(&pool, book.id).await.unwrap();
+        assert_eq!(state.equalizer, None);
+        assert_eq!(state.position, Duration::from_seconds(50));
+    }
+
+    #[tokio::test]
+    async fn test_book_speed_ramp() {
+        let pool = setup().await;
+
+        let book = Book::new(
+            "Test".to_string(),
+            PathBuf::from("/test.mp3"),
+            1000,
+            Duration::from_seconds(100),
+        );
+        create_book(&pool, &book).await.unwrap();
+        assert_eq!(get_speed_ramp(&pool, book.id).await.unwrap(), None);
+
+        let mut ramp = SpeedRamp::new(1.25, 1.75, 0.1).unwrap();
+        ramp.advance(Duration::from_seconds(600));
+        set_speed_ramp(&pool, book.id, Some(&ramp)).await.unwrap();
+        assert_eq!(get_speed_ramp(&pool, book.id).await.unwrap(), Some(ramp));
+
+        // Saving the whole state keeps the ramp's progress
+        let mut state = get_playback_state(&pool, book.id).await.unwrap();
+        assert_eq!(state.speed_ramp, Some(ramp));
+        state.speed_ramp = None;
+        create_playback_state(&pool, &state).await.unwrap();
+        assert_eq!(get_speed_ramp(&pool, book.id).await.unwrap(), None);
     }
 }
@@ -1,7 +1,11 @@
 //! Playlist database operations
 
+use crate::queries::stats::FINISHED_THRESHOLD;
 use crate::DbPool;
-use storystream_core::{AppError, BookId, Playlist, PlaylistId, PlaylistItem, Timestamp};
+use storystream_core::{
+    AppError, Book, BookId, Playlist, PlaylistId, PlaylistItem, PlaylistSession,
+    SmartPlaylistCriteria, Timestamp,
+};
 
 /// Creates a new playlist
 pub async fn create_playlist(pool: &DbPool, playlist: &Playlist) -> Result<(), AppError> {
@@ -54,6 +58,34 @@ pub async fn get_playlist(pool: &DbPool, id: PlaylistId) -> Result<Playlist, App
     row_to_playlist(row)
 }
 
+/// Lists all playlists ordered by name
+pub async fn list_playlists(pool: &DbPool) -> Result<Vec<Playlist>, AppError> {
+    let rows = sqlx::query(
+        "SELECT id, name, description, playlist_type, smart_criteria, created_at, updated_at FROM playlists ORDER BY name COLLATE NOCASE"
+    )
+        .fetch_all(pool)
+        .await
+        .map_err(|e| AppError::database("Failed to list playlists", e))?;
+
+    rows.into_iter().map(row_to_playlist).collect()
+}
+
+/// Finds a playlist by name, ignoring case
+pub async fn find_playlist_by_name(
+    pool: &DbPool,
+    name: &str,
+) -> Result<Option<Playlist>, AppError> {
+    let row = sqlx::query(
+        "SELECT id, name, description, playlist_type, smart_criteria, created_at, updated_at FROM playlists WHERE name = ? COLLATE NOCASE"
+    )
+        .bind(name.trim())
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| AppError::database("Failed to find playlist", e))?;
+
+    row.map(row_to_playlist).transpose()
+}
+
 /// Deletes a playlist
 pub async fn delete_playlist(pool: &DbPool, id: PlaylistId) -> Result<(), AppError> {
     sqlx::query("DELETE FROM playlists WHERE id = ?")
@@ -81,6 +113,89 @@ pub async fn add_book_to_playlist(pool: &DbPool, item: &PlaylistItem) -> Result<
     Ok(())
 }
 
+/// Adds a book after the last item of a playlist
+///
+/// Returns false, leaving the playlist as it was, when the book is already
+/// in it.
+pub async fn append_book_to_playlist(
+    pool: &DbPool,
+    playlist_id: PlaylistId,
+    book_id: BookId,
+) -> Result<bool, AppError> {
+    let result = sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO playlist_items (playlist_id, book_id, position, added_at)
+        SELECT ?, ?, COALESCE(MAX(position) + 1, 0), ?
+        FROM playlist_items WHERE playlist_id = ?
+        "#,
+    )
+    .bind(playlist_id.as_string())
+    .bind(book_id.as_string())
+    .bind(Timestamp::now().as_millis())
+    .bind(playlist_id.as_string())
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to add book to playlist", e))?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Puts a playlist's books in the order of `book_ids`
+///
+/// Positions are renumbered from zero. Books of the playlist missing from
+/// `book_ids` follow the listed ones in their old order, and listed books
+/// not in the playlist are ignored.
+pub async fn reorder_playlist_items(
+    pool: &DbPool,
+    playlist_id: PlaylistId,
+    book_ids: &[BookId],
+) -> Result<(), AppError> {
+    use sqlx::Row;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| AppError::database("Failed to begin transaction", e))?;
+
+    let rows =
+        sqlx::query("SELECT book_id FROM playlist_items WHERE playlist_id = ? ORDER BY position")
+            .bind(playlist_id.as_string())
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| AppError::database("Failed to get playlist items", e))?;
+    let mut current = Vec::with_capacity(rows.len());
+    for row in rows {
+        let id: String = row
+            .try_get("book_id")
+            .map_err(|e| AppError::database("Missing book id", e))?;
+        current.push(id);
+    }
+
+    let listed: Vec<String> = book_ids
+        .iter()
+        .map(BookId::as_string)
+        .filter(|id| current.contains(id))
+        .collect();
+    let rest = current.iter().filter(|id| !listed.contains(id)).cloned();
+    let order: Vec<String> = listed.iter().cloned().chain(rest).collect();
+
+    for (position, book_id) in order.iter().enumerate() {
+        sqlx::query("UPDATE playlist_items SET position = ? WHERE playlist_id = ? AND book_id = ?")
+            .bind(position as i64)
+            .bind(playlist_id.as_string())
+            .bind(book_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::database("Failed to reorder playlist", e))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::database("Failed to commit transaction", e))?;
+
+    Ok(())
+}
+
 /// Removes a book from a playlist
 pub async fn remove_book_from_playlist(
     pool: &DbPool,
@@ -97,6 +212,69 @@ pub async fn remove_book_from_playlist(
     Ok(())
 }
 
+/// Returns the position after the last item of a playlist
+pub async fn next_playlist_position(
+    pool: &DbPool,
+    playlist_id: PlaylistId,
+) -> Result<u32, AppError> {
+    use sqlx::Row;
+
+    let row = sqlx::query(
+        "SELECT COALESCE(MAX(position) + 1, 0) AS next FROM playlist_items WHERE playlist_id = ?",
+    )
+    .bind(playlist_id.as_string())
+    .fetch_one(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to get playlist position", e))?;
+
+    let next: i64 = row
+        .try_get("next")
+        .map_err(|e| AppError::database("Missing playlist position", e))?;
+    Ok(next as u32)
+}
+
+/// Gets the books matching a smart playlist's criteria, ordered by title
+pub async fn get_smart_playlist_books(
+    pool: &DbPool,
+    criteria: &SmartPlaylistCriteria,
+) -> Result<Vec<Book>, AppError> {
+    use sqlx::Row;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT b.id, b.title, b.author, b.narrator, b.series, b.series_position,
+               b.description, b.language, b.publisher, b.published_date, b.isbn, b.genre,
+               b.duration_ms, b.file_path, b.file_size, b.cover_art_path,
+               b.added_date, b.last_played, b.play_count, b.is_favorite, b.rating, b.tags, b.deleted_at,
+               COALESCE(ps.position_ms >= b.duration_ms * ?, 0) AS finished
+        FROM books b
+        LEFT JOIN playback_state ps ON ps.book_id = b.id
+        WHERE b.deleted_at IS NULL
+        ORDER BY b.title COLLATE NOCASE
+        "#,
+    )
+        .bind(FINISHED_THRESHOLD)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| AppError::database("Failed to get smart playlist books", e))?;
+
+    let mut books = Vec::new();
+    for row in rows {
+        let finished: bool = row
+            .try_get("finished")
+            .map_err(|e| AppError::database("Missing finished flag", e))?;
+        let book = crate::queries::books::row_to_book(row)?;
+        if criteria.matches(&book, finished) {
+            books.push(book);
+        }
+    }
+
+    if let Some(max) = criteria.max_results {
+        books.truncate(max);
+    }
+    Ok(books)
+}
+
 /// Gets all books in a playlist
 pub async fn get_playlist_books(
     pool: &DbPool,
@@ -105,7 +283,7 @@ pub async fn get_playlist_books(
     let rows = sqlx::query(
         r#"
         SELECT b.id, b.title, b.author, b.narrator, b.series, b.series_position,
-               b.description, b.language, b.publisher, b.published_date, b.isbn,
+               b.description, b.language, b.publisher, b.published_date, b.isbn, b.genre,
                b.duration_ms, b.file_path, b.file_size, b.cover_art_path,
                b.added_date, b.last_played, b.play_count, b.is_favorite, b.rating, b.tags, b.deleted_at
         FROM books b
@@ -124,6 +302,130 @@ pub async fn get_playlist_books(
         .collect()
 }
 
+/// Gets the first unfinished book placed after `after` in a playlist
+///
+/// When `after` is not in the playlist the search starts at the top.
+pub async fn get_next_unfinished_in_playlist(
+    pool: &DbPool,
+    playlist_id: PlaylistId,
+    after: BookId,
+) -> Result<Option<Book>, AppError> {
+    let row = sqlx::query(
+        r#"
+        SELECT b.id, b.title, b.author, b.narrator, b.series, b.series_position,
+               b.description, b.language, b.publisher, b.published_date, b.isbn, b.genre,
+               b.duration_ms, b.file_path, b.file_size, b.cover_art_path,
+               b.added_date, b.last_played, b.play_count, b.is_favorite, b.rating, b.tags, b.deleted_at
+        FROM playlist_items pi
+        JOIN books b ON b.id = pi.book_id
+        LEFT JOIN playback_state ps ON ps.book_id = b.id
+        WHERE pi.playlist_id = ? AND b.id != ? AND b.deleted_at IS NULL
+        AND pi.position > COALESCE(
+            (SELECT position FROM playlist_items WHERE playlist_id = ? AND book_id = ?),
+            -1
+        )
+        AND COALESCE(ps.position_ms, 0) < b.duration_ms * ?
+        ORDER BY pi.position
+        LIMIT 1
+        "#,
+    )
+    .bind(playlist_id.as_string())
+    .bind(after.as_string())
+    .bind(playlist_id.as_string())
+    .bind(after.as_string())
+    .bind(FINISHED_THRESHOLD)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to get next playlist book", e))?;
+
+    row.map(crate::queries::books::row_to_book).transpose()
+}
+
+/// Saves the playlist session, replacing any previous one
+pub async fn save_playlist_session(
+    pool: &DbPool,
+    session: &PlaylistSession,
+) -> Result<(), AppError> {
+    let queue: Vec<String> = session.queue.iter().map(|id| id.as_string()).collect();
+    let queue_json = serde_json::to_string(&queue)
+        .map_err(|e| AppError::database("Failed to serialize playlist queue", e))?;
+
+    sqlx::query(
+        r#"
+        INSERT OR REPLACE INTO playlist_session (id, playlist_id, queue, current_index, shuffle, updated_at)
+        VALUES (1, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(session.playlist_id.as_string())
+    .bind(queue_json)
+    .bind(session.current_index as i64)
+    .bind(session.shuffle)
+    .bind(session.updated_at.as_millis())
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to save playlist session", e))?;
+
+    Ok(())
+}
+
+/// Gets the playlist session, if a playlist is being played
+pub async fn get_playlist_session(pool: &DbPool) -> Result<Option<PlaylistSession>, AppError> {
+    use sqlx::Row;
+
+    let Some(row) = sqlx::query(
+        "SELECT playlist_id, queue, current_index, shuffle, updated_at FROM playlist_session WHERE id = 1",
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to fetch playlist session", e))?
+    else {
+        return Ok(None);
+    };
+
+    let playlist_id: String = row
+        .try_get("playlist_id")
+        .map_err(|e| AppError::database("Missing session playlist", e))?;
+    let queue_json: String = row
+        .try_get("queue")
+        .map_err(|e| AppError::database("Missing session queue", e))?;
+    let current_index: i64 = row
+        .try_get("current_index")
+        .map_err(|e| AppError::database("Missing session index", e))?;
+    let shuffle: bool = row
+        .try_get("shuffle")
+        .map_err(|e| AppError::database("Missing session shuffle", e))?;
+    let updated_at: i64 = row
+        .try_get("updated_at")
+        .map_err(|e| AppError::database("Missing session timestamp", e))?;
+
+    let queue: Vec<String> = serde_json::from_str(&queue_json)
+        .map_err(|e| AppError::database("Invalid playlist queue", e))?;
+    let queue = queue
+        .iter()
+        .map(|id| BookId::from_string(id))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::database("Invalid book ID in playlist queue", e))?;
+
+    Ok(Some(PlaylistSession {
+        playlist_id: PlaylistId::from_string(&playlist_id)
+            .map_err(|e| AppError::database("Invalid session playlist ID", e))?,
+        queue,
+        current_index: current_index.max(0) as usize,
+        shuffle,
+        updated_at: Timestamp::from_millis(updated_at),
+    }))
+}
+
+/// Forgets the playlist session
+pub async fn clear_playlist_session(pool: &DbPool) -> Result<(), AppError> {
+    sqlx::query("DELETE FROM playlist_session")
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::database("Failed to clear playlist session", e))?;
+
+    Ok(())
+}
+
 fn row_to_playlist(row: sqlx::sqlite::SqliteRow) -> Result<Playlist, AppError> {
     use sqlx::Row;
 
@@ -251,6 +553,134 @@ mod tests {
         assert_eq!(books.len(), 0);
     }
 
+    #[tokio::test]
+    async fn test_find_and_list_playlists() {
+        let pool = setup().await;
+
+        create_playlist(&pool, &Playlist::new_manual("Road Trip".to_string()))
+            .await
+            .unwrap();
+        create_playlist(&pool, &Playlist::new_manual("bedtime".to_string()))
+            .await
+            .unwrap();
+
+        let found = find_playlist_by_name(&pool, "road trip").await.unwrap();
+        assert_eq!(found.unwrap().name, "Road Trip");
+        assert!(find_playlist_by_name(&pool, "Missing")
+            .await
+            .unwrap()
+            .is_none());
+
+        let names: Vec<String> = list_playlists(&pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(names, vec!["bedtime", "Road Trip"]);
+    }
+
+    #[tokio::test]
+    async fn test_next_playlist_position() {
+        let pool = setup().await;
+
+        let playlist = Playlist::new_manual("Queue".to_string());
+        create_playlist(&pool, &playlist).await.unwrap();
+        assert_eq!(next_playlist_position(&pool, playlist.id).await.unwrap(), 0);
+
+        let book = Book::new(
+            "Test".to_string(),
+            PathBuf::from("/test.mp3"),
+            1000,
+            Duration::from_seconds(100),
+        );
+        create_book(&pool, &book).await.unwrap();
+        add_book_to_playlist(&pool, &PlaylistItem::new(playlist.id, book.id, 4))
+            .await
+            .unwrap();
+
+        assert_eq!(next_playlist_position(&pool, playlist.id).await.unwrap(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_append_and_reorder_playlist_books() {
+        let pool = setup().await;
+
+        let playlist = Playlist::new_manual("Queue".to_string());
+        create_playlist(&pool, &playlist).await.unwrap();
+
+        let mut ids = Vec::new();
+        for title in ["A", "B", "C"] {
+            let book = Book::new(
+                title.to_string(),
+                PathBuf::from(format!("/{}.mp3", title)),
+                1000,
+                Duration::from_seconds(100),
+            );
+            create_book(&pool, &book).await.unwrap();
+            assert!(append_book_to_playlist(&pool, playlist.id, book.id)
+                .await
+                .unwrap());
+            ids.push(book.id);
+        }
+        // Adding a book twice leaves it where it was
+        assert!(!append_book_to_playlist(&pool, playlist.id, ids[0])
+            .await
+            .unwrap());
+
+        let order = |books: Vec<Book>| books.iter().map(|b| b.id).collect::<Vec<_>>();
+        let books = get_playlist_books(&pool, playlist.id).await.unwrap();
+        assert_eq!(order(books), ids);
+
+        // Unlisted books follow in their old order, unknown ones are ignored
+        reorder_playlist_items(&pool, playlist.id, &[ids[2], BookId::new()])
+            .await
+            .unwrap();
+        let books = get_playlist_books(&pool, playlist.id).await.unwrap();
+        assert_eq!(order(books), vec![ids[2], ids[0], ids[1]]);
+        assert_eq!(next_playlist_position(&pool, playlist.id).await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_smart_playlist_books() {
+        use crate::queries::playback::create_playback_state;
+        use storystream_core::PlaybackState;
+
+        let pool = setup().await;
+
+        let mut done = Book::new(
+            "Alpha".to_string(),
+            PathBuf::from("/alpha.mp3"),
+            1000,
+            Duration::from_seconds(100),
+        );
+        done.author = Some("Austen".to_string());
+        create_book(&pool, &done).await.unwrap();
+
+        let mut state = PlaybackState::new(done.id);
+        state.set_position(Duration::from_seconds(100));
+        create_playback_state(&pool, &state).await.unwrap();
+
+        let mut pending = Book::new(
+            "Beta".to_string(),
+            PathBuf::from("/beta.mp3"),
+            1000,
+            Duration::from_seconds(100),
+        );
+        pending.author = Some("Austen".to_string());
+        create_book(&pool, &pending).await.unwrap();
+
+        let by_author = SmartPlaylistCriteria::by_authors(vec!["austen".to_string()]);
+        let books = get_smart_playlist_books(&pool, &by_author).await.unwrap();
+        assert_eq!(books.len(), 2);
+        assert_eq!(books[0].title, "Alpha");
+
+        let unfinished = SmartPlaylistCriteria::unfinished();
+        let books = get_smart_playlist_books(&pool, &unfinished).await.unwrap();
+        assert_eq!(books.len(), 1);
+        assert_eq!(books[0].id, pending.id);
+    }
+
     #[tokio::test]
     async fn test_delete_playlist() {
         let pool = setup().await;
@@ -263,4 +693,37 @@ mod tests {
         let result = get_playlist(&pool, playlist.id).await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_playlist_session_roundtrip() {
+        let pool = setup().await;
+        assert!(get_playlist_session(&pool).await.unwrap().is_none());
+
+        let playlist = Playlist::new_manual("Queue".to_string());
+        create_playlist(&pool, &playlist).await.unwrap();
+
+        let queue = vec![BookId::new(), BookId::new()];
+        let mut session = PlaylistSession::new(playlist.id, queue.clone(), false);
+        save_playlist_session(&pool, &session).await.unwrap();
+
+        session.advance();
+        save_playlist_session(&pool, &session).await.unwrap();
+        let loaded = get_playlist_session(&pool).await.unwrap().unwrap();
+        assert_eq!(loaded.playlist_id, playlist.id);
+        assert_eq!(loaded.queue, queue);
+        assert_eq!(loaded.current_index, 1);
+        assert!(!loaded.shuffle);
+
+        // Deleting the playlist ends its session
+        delete_playlist(&pool, playlist.id).await.unwrap();
+        assert!(get_playlist_session(&pool).await.unwrap().is_none());
+
+        let other = Playlist::new_manual("Other".to_string());
+        create_playlist(&pool, &other).await.unwrap();
+        save_playlist_session(&pool, &PlaylistSession::new(other.id, queue, true))
+            .await
+            .unwrap();
+        clear_playlist_session(&pool).await.unwrap();
+        assert!(get_playlist_session(&pool).await.unwrap().is_none());
+    }
 }
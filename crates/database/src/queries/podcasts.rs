@@ -0,0 +1,701 @@
+//! Podcast subscription and episode database operations
+
+use crate::queries::stats::FINISHED_THRESHOLD;
+use crate::DbPool;
+use sqlx::Row;
+use std::collections::HashMap;
+use storystream_core::{
+    AppError, BookId, DownloadPolicy, Duration, EpisodeId, Podcast, PodcastEpisode, PodcastId,
+    Timestamp,
+};
+
+const PODCAST_COLUMNS: &str = "id, feed_url, title, description, author, image_url, last_fetched, etag, last_modified, max_episodes, auto_download, delete_finished, unmetered_only, created_at, updated_at";
+
+const EPISODE_COLUMNS: &str = "id, podcast_id, guid, title, description, audio_url, duration_ms, published_date, file_path, is_downloaded, is_played, book_id, created_at";
+
+/// Creates a new podcast subscription
+pub async fn create_podcast(pool: &DbPool, podcast: &Podcast) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        INSERT INTO podcasts (id, feed_url, title, description, author, image_url, last_fetched, etag, last_modified, max_episodes, auto_download, delete_finished, unmetered_only, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(podcast.id.as_string())
+    .bind(&podcast.feed_url)
+    .bind(&podcast.title)
+    .bind(&podcast.description)
+    .bind(&podcast.author)
+    .bind(&podcast.image_url)
+    .bind(podcast.last_fetched.map(|t| t.as_millis()))
+    .bind(&podcast.etag)
+    .bind(&podcast.last_modified)
+    .bind(podcast.policy.max_episodes as i64)
+    .bind(podcast.policy.auto_download)
+    .bind(podcast.policy.delete_finished)
+    .bind(podcast.policy.unmetered_only)
+    .bind(podcast.created_at.as_millis())
+    .bind(podcast.updated_at.as_millis())
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to create podcast", e))?;
+
+    Ok(())
+}
+
+/// Gets a podcast by ID
+pub async fn get_podcast(pool: &DbPool, id: PodcastId) -> Result<Podcast, AppError> {
+    let row = sqlx::query(&format!(
+        "SELECT {} FROM podcasts WHERE id = ?",
+        PODCAST_COLUMNS
+    ))
+    .bind(id.as_string())
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to fetch podcast", e))?
+    .ok_or_else(|| AppError::RecordNotFound {
+        entity: "Podcast".to_string(),
+        identifier: id.to_string(),
+    })?;
+
+    row_to_podcast(row)
+}
+
+/// Finds the subscription for a feed URL, if any
+pub async fn find_podcast_by_url(
+    pool: &DbPool,
+    feed_url: &str,
+) -> Result<Option<Podcast>, AppError> {
+    let row = sqlx::query(&format!(
+        "SELECT {} FROM podcasts WHERE feed_url = ?",
+        PODCAST_COLUMNS
+    ))
+    .bind(feed_url)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to fetch podcast", e))?;
+
+    row.map(row_to_podcast).transpose()
+}
+
+/// Lists all podcast subscriptions ordered by title
+pub async fn list_podcasts(pool: &DbPool) -> Result<Vec<Podcast>, AppError> {
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM podcasts ORDER BY title COLLATE NOCASE",
+        PODCAST_COLUMNS
+    ))
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to list podcasts", e))?;
+
+    rows.into_iter().map(row_to_podcast).collect()
+}
+
+/// Updates a podcast's metadata, fetch state and download policy
+pub async fn update_podcast(pool: &DbPool, podcast: &Podcast) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        UPDATE podcasts
+        SET title = ?, description = ?, author = ?, image_url = ?, last_fetched = ?,
+            etag = ?, last_modified = ?, max_episodes = ?, auto_download = ?,
+            delete_finished = ?, unmetered_only = ?, updated_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(&podcast.title)
+    .bind(&podcast.description)
+    .bind(&podcast.author)
+    .bind(&podcast.image_url)
+    .bind(podcast.last_fetched.map(|t| t.as_millis()))
+    .bind(&podcast.etag)
+    .bind(&podcast.last_modified)
+    .bind(podcast.policy.max_episodes as i64)
+    .bind(podcast.policy.auto_download)
+    .bind(podcast.policy.delete_finished)
+    .bind(podcast.policy.unmetered_only)
+    .bind(podcast.updated_at.as_millis())
+    .bind(podcast.id.as_string())
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to update podcast", e))?;
+
+    Ok(())
+}
+
+/// Records a fetch of a podcast's feed along with the validators for the next one
+///
+/// Leaves the rest of the subscription untouched, unlike [`update_podcast`].
+pub async fn update_last_checked(
+    pool: &DbPool,
+    id: PodcastId,
+    checked: Timestamp,
+    etag: Option<&str>,
+    last_modified: Option<&str>,
+) -> Result<(), AppError> {
+    sqlx::query(
+        "UPDATE podcasts SET last_fetched = ?, etag = ?, last_modified = ?, updated_at = ? WHERE id = ?",
+    )
+    .bind(checked.as_millis())
+    .bind(etag)
+    .bind(last_modified)
+    .bind(Timestamp::now().as_millis())
+    .bind(id.as_string())
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to update podcast", e))?;
+
+    Ok(())
+}
+
+/// Deletes a podcast and its episodes
+pub async fn delete_podcast(pool: &DbPool, id: PodcastId) -> Result<(), AppError> {
+    sqlx::query("DELETE FROM podcasts WHERE id = ?")
+        .bind(id.as_string())
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::database("Failed to delete podcast", e))?;
+
+    Ok(())
+}
+
+/// Stores an episode unless one with the same GUID (or audio URL) already exists
+///
+/// Returns `true` if the episode was inserted.
+pub async fn add_episode_if_new(pool: &DbPool, episode: &PodcastEpisode) -> Result<bool, AppError> {
+    let existing: Option<String> = sqlx::query_scalar(
+        "SELECT id FROM podcast_episodes WHERE podcast_id = ? AND (guid = ? OR audio_url = ?)",
+    )
+    .bind(episode.podcast_id.as_string())
+    .bind(&episode.guid)
+    .bind(&episode.audio_url)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to check for existing episode", e))?;
+
+    if existing.is_some() {
+        return Ok(false);
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO podcast_episodes (id, podcast_id, guid, title, description, audio_url, duration_ms, published_date, file_path, is_downloaded, is_played, book_id, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(episode.id.as_string())
+    .bind(episode.podcast_id.as_string())
+    .bind(&episode.guid)
+    .bind(&episode.title)
+    .bind(&episode.description)
+    .bind(&episode.audio_url)
+    .bind(episode.duration.map(|d| d.as_millis() as i64))
+    .bind(episode.published.map(|t| t.as_millis()))
+    .bind(&episode.file_path)
+    .bind(episode.is_downloaded)
+    .bind(episode.is_played)
+    .bind(episode.book_id.map(|id| id.as_string()))
+    .bind(episode.created_at.as_millis())
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to create episode", e))?;
+
+    Ok(true)
+}
+
+/// Gets a podcast's episodes, newest first
+pub async fn get_podcast_episodes(
+    pool: &DbPool,
+    podcast_id: PodcastId,
+) -> Result<Vec<PodcastEpisode>, AppError> {
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM podcast_episodes WHERE podcast_id = ? ORDER BY published_date DESC, created_at DESC",
+        EPISODE_COLUMNS
+    ))
+    .bind(podcast_id.as_string())
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to get podcast episodes", e))?;
+
+    rows.into_iter().map(row_to_episode).collect()
+}
+
+/// Records that an episode's enclosure has been downloaded to `file_path`
+pub async fn mark_episode_downloaded(
+    pool: &DbPool,
+    id: EpisodeId,
+    file_path: &str,
+) -> Result<(), AppError> {
+    sqlx::query("UPDATE podcast_episodes SET file_path = ?, is_downloaded = 1 WHERE id = ?")
+        .bind(file_path)
+        .bind(id.as_string())
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::database("Failed to update episode", e))?;
+
+    Ok(())
+}
+
+/// Links an episode to the library book its download was imported as
+pub async fn link_episode_book(
+    pool: &DbPool,
+    id: EpisodeId,
+    book_id: BookId,
+) -> Result<(), AppError> {
+    sqlx::query("UPDATE podcast_episodes SET book_id = ? WHERE id = ?")
+        .bind(book_id.as_string())
+        .bind(id.as_string())
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::database("Failed to update episode", e))?;
+
+    Ok(())
+}
+
+/// Forgets an episode's local file, keeping the episode and whether it was played
+pub async fn clear_episode_download(pool: &DbPool, id: EpisodeId) -> Result<(), AppError> {
+    sqlx::query("UPDATE podcast_episodes SET file_path = NULL, is_downloaded = 0 WHERE id = ?")
+        .bind(id.as_string())
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::database("Failed to update episode", e))?;
+
+    Ok(())
+}
+
+/// Records that an episode has been listened to
+pub async fn mark_episode_played(pool: &DbPool, id: EpisodeId) -> Result<(), AppError> {
+    sqlx::query("UPDATE podcast_episodes SET is_played = 1 WHERE id = ?")
+        .bind(id.as_string())
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::database("Failed to update episode", e))?;
+
+    Ok(())
+}
+
+/// Counts each podcast's episodes not yet played
+///
+/// Podcasts with nothing left to play are absent from the map.
+pub async fn count_unplayed_episodes(pool: &DbPool) -> Result<HashMap<PodcastId, usize>, AppError> {
+    let rows: Vec<(String, i64)> = sqlx::query_as(
+        "SELECT podcast_id, COUNT(*) FROM podcast_episodes WHERE is_played = 0 GROUP BY podcast_id",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to count unplayed episodes", e))?;
+
+    rows.into_iter()
+        .map(|(id, count)| {
+            let id = PodcastId::from_string(&id)
+                .map_err(|e| AppError::database("Invalid podcast ID", e))?;
+            Ok((id, count as usize))
+        })
+        .collect()
+}
+
+/// Finds downloaded episodes not yet marked played whose imported book is finished
+///
+/// Episodes are matched to books by their linked book, or else by file path.
+pub async fn find_finished_episodes(
+    pool: &DbPool,
+    podcast_id: PodcastId,
+) -> Result<Vec<EpisodeId>, AppError> {
+    let ids: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT DISTINCT e.id
+        FROM podcast_episodes e
+        JOIN books b ON b.id = e.book_id OR b.file_path = e.file_path
+        JOIN playback_state ps ON ps.book_id = b.id
+        WHERE e.podcast_id = ? AND e.is_downloaded = 1 AND e.is_played = 0
+          AND b.duration_ms > 0 AND ps.position_ms >= b.duration_ms * ?
+        "#,
+    )
+    .bind(podcast_id.as_string())
+    .bind(FINISHED_THRESHOLD)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to find finished episodes", e))?;
+
+    ids.iter()
+        .map(|id| {
+            EpisodeId::from_string(id).map_err(|e| AppError::database("Invalid episode ID", e))
+        })
+        .collect()
+}
+
+fn row_to_podcast(row: sqlx::sqlite::SqliteRow) -> Result<Podcast, AppError> {
+    let id_str: String = row
+        .try_get("id")
+        .map_err(|e| AppError::database("Missing podcast ID", e))?;
+    let id =
+        PodcastId::from_string(&id_str).map_err(|e| AppError::database("Invalid podcast ID", e))?;
+
+    let created_at_ms: i64 = row
+        .try_get("created_at")
+        .map_err(|e| AppError::database("Missing created_at", e))?;
+    let updated_at_ms: i64 = row
+        .try_get("updated_at")
+        .map_err(|e| AppError::database("Missing updated_at", e))?;
+
+    Ok(Podcast {
+        id,
+        feed_url: row
+            .try_get("feed_url")
+            .map_err(|e| AppError::database("Missing feed_url", e))?,
+        title: row
+            .try_get("title")
+            .map_err(|e| AppError::database("Missing title", e))?,
+        description: optional_text(&row, "description"),
+        author: optional_text(&row, "author"),
+        image_url: optional_text(&row, "image_url"),
+        last_fetched: row
+            .try_get::<Option<i64>, _>("last_fetched")
+            .ok()
+            .flatten()
+            .map(Timestamp::from_millis),
+        etag: optional_text(&row, "etag"),
+        last_modified: optional_text(&row, "last_modified"),
+        policy: row_to_policy(&row),
+        created_at: Timestamp::from_millis(created_at_ms),
+        updated_at: Timestamp::from_millis(updated_at_ms),
+    })
+}
+
+fn row_to_episode(row: sqlx::sqlite::SqliteRow) -> Result<PodcastEpisode, AppError> {
+    let id_str: String = row
+        .try_get("id")
+        .map_err(|e| AppError::database("Missing episode ID", e))?;
+    let id =
+        EpisodeId::from_string(&id_str).map_err(|e| AppError::database("Invalid episode ID", e))?;
+
+    let podcast_id_str: String = row
+        .try_get("podcast_id")
+        .map_err(|e| AppError::database("Missing podcast ID", e))?;
+    let podcast_id = PodcastId::from_string(&podcast_id_str)
+        .map_err(|e| AppError::database("Invalid podcast ID", e))?;
+
+    let created_at_ms: i64 = row
+        .try_get("created_at")
+        .map_err(|e| AppError::database("Missing created_at", e))?;
+
+    Ok(PodcastEpisode {
+        id,
+        podcast_id,
+        guid: optional_text(&row, "guid"),
+        title: row
+            .try_get("title")
+            .map_err(|e| AppError::database("Missing title", e))?,
+        description: optional_text(&row, "description"),
+        audio_url: row
+            .try_get("audio_url")
+            .map_err(|e| AppError::database("Missing audio_url", e))?,
+        duration: row
+            .try_get::<Option<i64>, _>("duration_ms")
+            .ok()
+            .flatten()
+            .map(|ms| Duration::from_millis(ms as u64)),
+        published: row
+            .try_get::<Option<i64>, _>("published_date")
+            .ok()
+            .flatten()
+            .map(Timestamp::from_millis),
+        file_path: optional_text(&row, "file_path"),
+        is_downloaded: row.try_get("is_downloaded").unwrap_or(false),
+        is_played: row.try_get("is_played").unwrap_or(false),
+        book_id: optional_text(&row, "book_id")
+            .map(|id| BookId::from_string(&id))
+            .transpose()
+            .map_err(|e| AppError::database("Invalid book ID", e))?,
+        created_at: Timestamp::from_millis(created_at_ms),
+    })
+}
+
+fn row_to_policy(row: &sqlx::sqlite::SqliteRow) -> DownloadPolicy {
+    let default = DownloadPolicy::default();
+    DownloadPolicy {
+        max_episodes: row
+            .try_get::<i64, _>("max_episodes")
+            .map(|n| n.max(0) as usize)
+            .unwrap_or(default.max_episodes),
+        auto_download: row
+            .try_get("auto_download")
+            .unwrap_or(default.auto_download),
+        delete_finished: row
+            .try_get("delete_finished")
+            .unwrap_or(default.delete_finished),
+        unmetered_only: row
+            .try_get("unmetered_only")
+            .unwrap_or(default.unmetered_only),
+    }
+}
+
+fn optional_text(row: &sqlx::sqlite::SqliteRow, column: &str) -> Option<String> {
+    row.try_get::<Option<String>, _>(column).ok().flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::create_test_db;
+    use crate::migrations::run_migrations;
+
+    async fn setup() -> DbPool {
+        let pool = create_test_db().await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_create_and_find_podcast() {
+        let pool = setup().await;
+
+        let mut podcast = Podcast::new(
+            "https://example.com/feed.xml".to_string(),
+            "Example".to_string(),
+        );
+        create_podcast(&pool, &podcast).await.unwrap();
+
+        let found = find_podcast_by_url(&pool, "https://example.com/feed.xml")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.id, podcast.id);
+        assert!(found.etag.is_none());
+        assert!(found.last_fetched.is_none());
+
+        podcast.etag = Some("\"abc\"".to_string());
+        podcast.last_fetched = Some(Timestamp::now());
+        update_podcast(&pool, &podcast).await.unwrap();
+
+        let updated = get_podcast(&pool, podcast.id).await.unwrap();
+        assert_eq!(updated.etag.as_deref(), Some("\"abc\""));
+        assert!(updated.last_fetched.is_some());
+
+        assert!(find_podcast_by_url(&pool, "https://other.example/feed")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_episodes_are_deduplicated() {
+        let pool = setup().await;
+
+        let podcast = Podcast::new(
+            "https://example.com/feed.xml".to_string(),
+            "Example".to_string(),
+        );
+        create_podcast(&pool, &podcast).await.unwrap();
+
+        let mut episode = PodcastEpisode::new(
+            podcast.id,
+            "Episode 1".to_string(),
+            "https://example.com/1.mp3".to_string(),
+        );
+        episode.guid = Some("ep-1".to_string());
+
+        assert!(add_episode_if_new(&pool, &episode).await.unwrap());
+
+        let mut again = episode.clone();
+        again.id = EpisodeId::new();
+        assert!(!add_episode_if_new(&pool, &again).await.unwrap());
+
+        let episodes = get_podcast_episodes(&pool, podcast.id).await.unwrap();
+        assert_eq!(episodes.len(), 1);
+        assert!(!episodes[0].is_downloaded);
+
+        mark_episode_downloaded(&pool, episode.id, "/tmp/1.mp3")
+            .await
+            .unwrap();
+        let episodes = get_podcast_episodes(&pool, podcast.id).await.unwrap();
+        assert!(episodes[0].is_downloaded);
+        assert_eq!(episodes[0].file_path.as_deref(), Some("/tmp/1.mp3"));
+    }
+
+    #[tokio::test]
+    async fn test_policy_is_stored_with_subscription() {
+        let pool = setup().await;
+
+        let mut podcast = Podcast::new(
+            "https://example.com/feed.xml".to_string(),
+            "Example".to_string(),
+        );
+        create_podcast(&pool, &podcast).await.unwrap();
+        assert_eq!(
+            get_podcast(&pool, podcast.id).await.unwrap().policy,
+            DownloadPolicy::default()
+        );
+
+        podcast.policy = DownloadPolicy {
+            max_episodes: 5,
+            auto_download: true,
+            delete_finished: true,
+            unmetered_only: false,
+        };
+        update_podcast(&pool, &podcast).await.unwrap();
+        assert_eq!(
+            get_podcast(&pool, podcast.id).await.unwrap().policy,
+            podcast.policy
+        );
+    }
+
+    #[tokio::test]
+    async fn test_finished_episodes_are_found_by_book() {
+        use crate::queries::{books, playback};
+        use storystream_core::{Book, PlaybackState};
+
+        let pool = setup().await;
+        let podcast = Podcast::new(
+            "https://example.com/feed.xml".to_string(),
+            "Example".to_string(),
+        );
+        create_podcast(&pool, &podcast).await.unwrap();
+
+        let mut episodes = Vec::new();
+        for n in 1..=2 {
+            let episode = PodcastEpisode::new(
+                podcast.id,
+                format!("Episode {}", n),
+                format!("https://example.com/{}.mp3", n),
+            );
+            add_episode_if_new(&pool, &episode).await.unwrap();
+            let path = format!("/podcasts/{}.mp3", n);
+            mark_episode_downloaded(&pool, episode.id, &path)
+                .await
+                .unwrap();
+
+            let book = Book::new(
+                episode.title.clone(),
+                path.into(),
+                1024,
+                Duration::from_seconds(600),
+            );
+            books::create_book(&pool, &book).await.unwrap();
+            let mut state = PlaybackState::new(book.id);
+            state.position = Duration::from_seconds(if n == 1 { 600 } else { 60 });
+            playback::create_playback_state(&pool, &state)
+                .await
+                .unwrap();
+            episodes.push(episode);
+        }
+
+        let finished = find_finished_episodes(&pool, podcast.id).await.unwrap();
+        assert_eq!(finished, vec![episodes[0].id]);
+
+        mark_episode_played(&pool, episodes[0].id).await.unwrap();
+        clear_episode_download(&pool, episodes[0].id).await.unwrap();
+        assert!(find_finished_episodes(&pool, podcast.id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let stored = get_podcast_episodes(&pool, podcast.id).await.unwrap();
+        let first = stored.iter().find(|e| e.id == episodes[0].id).unwrap();
+        assert!(first.is_played);
+        assert!(!first.is_downloaded);
+        assert!(first.file_path.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_linked_book_and_unplayed_counts() {
+        use crate::queries::{books, playback};
+        use storystream_core::{Book, PlaybackState};
+
+        let pool = setup().await;
+        let podcast = Podcast::new(
+            "https://example.com/feed.xml".to_string(),
+            "Example".to_string(),
+        );
+        create_podcast(&pool, &podcast).await.unwrap();
+
+        let mut episodes = Vec::new();
+        for n in 1..=3 {
+            let episode = PodcastEpisode::new(
+                podcast.id,
+                format!("Episode {}", n),
+                format!("https://example.com/{}.mp3", n),
+            );
+            add_episode_if_new(&pool, &episode).await.unwrap();
+            episodes.push(episode);
+        }
+        mark_episode_played(&pool, episodes[2].id).await.unwrap();
+
+        let counts = count_unplayed_episodes(&pool).await.unwrap();
+        assert_eq!(counts.get(&podcast.id), Some(&2));
+
+        // The book was moved after import, so only the link ties it to the episode
+        mark_episode_downloaded(&pool, episodes[0].id, "/downloads/1.mp3")
+            .await
+            .unwrap();
+        let book = Book::new(
+            "Episode 1".to_string(),
+            "/library/1.mp3".into(),
+            1024,
+            Duration::from_seconds(600),
+        );
+        books::create_book(&pool, &book).await.unwrap();
+        link_episode_book(&pool, episodes[0].id, book.id)
+            .await
+            .unwrap();
+        let mut state = PlaybackState::new(book.id);
+        state.position = Duration::from_seconds(600);
+        playback::create_playback_state(&pool, &state)
+            .await
+            .unwrap();
+
+        let stored = get_podcast_episodes(&pool, podcast.id).await.unwrap();
+        let first = stored.iter().find(|e| e.id == episodes[0].id).unwrap();
+        assert_eq!(first.book_id, Some(book.id));
+        assert_eq!(
+            find_finished_episodes(&pool, podcast.id).await.unwrap(),
+            vec![episodes[0].id]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_last_checked_keeps_policy() {
+        let pool = setup().await;
+
+        let mut podcast = Podcast::new(
+            "https://example.com/feed.xml".to_string(),
+            "Example".to_string(),
+        );
+        podcast.policy.auto_download = true;
+        create_podcast(&pool, &podcast).await.unwrap();
+
+        let checked = Timestamp::from_millis(1_700_000_000_000);
+        update_last_checked(&pool, podcast.id, checked, Some("\"v2\""), None)
+            .await
+            .unwrap();
+
+        let stored = get_podcast(&pool, podcast.id).await.unwrap();
+        assert_eq!(stored.last_fetched, Some(checked));
+        assert_eq!(stored.etag.as_deref(), Some("\"v2\""));
+        assert!(stored.last_modified.is_none());
+        assert!(stored.policy.auto_download);
+    }
+
+    #[tokio::test]
+    async fn test_delete_podcast_removes_episodes() {
+        let pool = setup().await;
+
+        let podcast = Podcast::new(
+            "https://example.com/feed.xml".to_string(),
+            "Example".to_string(),
+        );
+        create_podcast(&pool, &podcast).await.unwrap();
+        let episode = PodcastEpisode::new(
+            podcast.id,
+            "Episode 1".to_string(),
+            "https://example.com/1.mp3".to_string(),
+        );
+        add_episode_if_new(&pool, &episode).await.unwrap();
+
+        delete_podcast(&pool, podcast.id).await.unwrap();
+        assert!(list_podcasts(&pool).await.unwrap().is_empty());
+        assert!(get_podcast_episodes(&pool, podcast.id)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
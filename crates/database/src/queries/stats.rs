@@ -0,0 +1,722 @@
+//! Library and listening statistics
+
+use crate::DbPool;
+use chrono::{Days, Local, NaiveDate, NaiveTime, TimeZone};
+use sqlx::Row;
+use std::collections::BTreeSet;
+use storystream_core::{AppError, BookId, Duration, LibraryStats, PlaybackStats, Timestamp};
+
+/// Fraction of a book that must be heard before it counts as finished
+pub const FINISHED_THRESHOLD: f64 = 0.98;
+
+/// Sessions shorter than this are accidental plays and are not recorded
+pub const MIN_SESSION_SECS: i64 = 10;
+
+/// Computes library-wide statistics over non-deleted books
+pub async fn get_library_stats(pool: &DbPool) -> Result<LibraryStats, AppError> {
+    let row = sqlx::query(
+        r#"
+        SELECT
+            COUNT(*) AS total_books,
+            COALESCE(SUM(duration_ms), 0) AS total_duration_ms,
+            COALESCE(SUM(file_size), 0) AS total_size,
+            COALESCE(SUM(is_favorite), 0) AS favorite_count,
+            COUNT(DISTINCT author) AS authors_count,
+            COUNT(DISTINCT narrator) AS narrators_count,
+            COUNT(DISTINCT series) AS series_count
+        FROM books
+        WHERE deleted_at IS NULL
+        "#,
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to compute library totals", e))?;
+
+    let progress = sqlx::query(
+        r#"
+        SELECT
+            COALESCE(SUM(CASE WHEN ps.position_ms >= b.duration_ms * ? THEN 1 ELSE 0 END), 0) AS finished,
+            COALESCE(SUM(CASE WHEN ps.position_ms > 0 AND ps.position_ms < b.duration_ms * ? THEN 1 ELSE 0 END), 0) AS unfinished
+        FROM playback_state ps
+        JOIN books b ON b.id = ps.book_id
+        WHERE b.deleted_at IS NULL
+        "#,
+    )
+    .bind(FINISHED_THRESHOLD)
+    .bind(FINISHED_THRESHOLD)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to compute completion counts", e))?;
+
+    let total_chapters = count(pool, "SELECT COUNT(*) FROM chapters").await?;
+    let total_bookmarks = count(pool, "SELECT COUNT(*) FROM bookmarks").await?;
+    let total_playlists = count(pool, "SELECT COUNT(*) FROM playlists").await?;
+
+    let listened_secs: i64 =
+        sqlx::query_scalar("SELECT COALESCE(SUM(seconds_listened), 0) FROM listening_sessions")
+            .fetch_one(pool)
+            .await
+            .map_err(|e| AppError::database("Failed to compute listening time", e))?;
+
+    let sessions: Vec<(i64, i64)> =
+        sqlx::query_as("SELECT started_at, ended_at FROM listening_sessions")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| AppError::database("Failed to load listening sessions", e))?;
+    let (current_streak_days, longest_streak_days) =
+        listening_streaks(&Local, Local::now().date_naive(), &sessions);
+
+    let author_rows: Vec<(String, i64)> = sqlx::query_as(
+        r#"
+        SELECT b.author, SUM(ls.seconds_listened) AS seconds
+        FROM listening_sessions ls
+        JOIN books b ON b.id = ls.book_id
+        WHERE b.deleted_at IS NULL AND b.author IS NOT NULL
+        GROUP BY b.author
+        ORDER BY seconds DESC, b.author
+        LIMIT 5
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to compute most listened authors", e))?;
+
+    let book_rows: Vec<(String, i64)> = sqlx::query_as(
+        r#"
+        SELECT b.title, SUM(ls.seconds_listened) AS seconds
+        FROM listening_sessions ls
+        JOIN books b ON b.id = ls.book_id
+        WHERE b.deleted_at IS NULL
+        GROUP BY b.id
+        ORDER BY seconds DESC, b.title
+        LIMIT 5
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to compute most listened books", e))?;
+
+    Ok(LibraryStats {
+        total_books: get_i64(&row, "total_books")? as usize,
+        total_chapters,
+        total_bookmarks,
+        total_playlists,
+        total_duration: Duration::from_millis(get_i64(&row, "total_duration_ms")? as u64),
+        total_size_bytes: get_i64(&row, "total_size")? as u64,
+        favorite_count: get_i64(&row, "favorite_count")? as usize,
+        unfinished_count: get_i64(&progress, "unfinished")? as usize,
+        finished_count: get_i64(&progress, "finished")? as usize,
+        authors_count: get_i64(&row, "authors_count")? as usize,
+        narrators_count: get_i64(&row, "narrators_count")? as usize,
+        series_count: get_i64(&row, "series_count")? as usize,
+        listening_time: Duration::from_seconds(listened_secs.max(0) as u64),
+        current_streak_days,
+        longest_streak_days,
+        top_listened_authors: author_rows
+            .into_iter()
+            .map(|(author, seconds)| (author, Duration::from_seconds(seconds.max(0) as u64)))
+            .collect(),
+        top_listened_books: book_rows
+            .into_iter()
+            .map(|(title, seconds)| (title, Duration::from_seconds(seconds.max(0) as u64)))
+            .collect(),
+    })
+}
+
+/// Computes listening statistics, optionally restricted to books played since `since`
+///
+/// Listening time is derived from saved playback positions, so it reflects
+/// progress through each book rather than wall-clock session time.
+pub async fn get_playback_stats(
+    pool: &DbPool,
+    since: Option<Timestamp>,
+) -> Result<PlaybackStats, AppError> {
+    let since_ms = since.map(|t| t.as_millis()).unwrap_or(0);
+
+    let row = sqlx::query(
+        r#"
+        SELECT
+            COALESCE(SUM(MIN(ps.position_ms, b.duration_ms)), 0) AS listened_ms,
+            COALESCE(SUM(CASE WHEN ps.position_ms > 0 THEN 1 ELSE 0 END), 0) AS started,
+            COALESCE(SUM(CASE WHEN ps.position_ms >= b.duration_ms * ? THEN 1 ELSE 0 END), 0) AS finished
+        FROM playback_state ps
+        JOIN books b ON b.id = ps.book_id
+        WHERE b.deleted_at IS NULL AND ps.last_updated >= ?
+        "#,
+    )
+    .bind(FINISHED_THRESHOLD)
+    .bind(since_ms)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to compute listening totals", e))?;
+
+    let author_rows = sqlx::query(
+        r#"
+        SELECT b.author AS author
+        FROM playback_state ps
+        JOIN books b ON b.id = ps.book_id
+        WHERE b.deleted_at IS NULL AND b.author IS NOT NULL
+          AND ps.position_ms > 0 AND ps.last_updated >= ?
+        GROUP BY b.author
+        ORDER BY SUM(MIN(ps.position_ms, b.duration_ms)) DESC, b.author
+        LIMIT 5
+        "#,
+    )
+    .bind(since_ms)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to compute favorite authors", e))?;
+
+    let narrator_rows = sqlx::query(
+        r#"
+        SELECT b.narrator AS narrator
+        FROM playback_state ps
+        JOIN books b ON b.id = ps.book_id
+        WHERE b.deleted_at IS NULL AND b.narrator IS NOT NULL
+          AND ps.position_ms > 0 AND ps.last_updated >= ?
+        GROUP BY b.narrator
+        ORDER BY SUM(MIN(ps.position_ms, b.duration_ms)) DESC, b.narrator
+        LIMIT 5
+        "#,
+    )
+    .bind(since_ms)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to compute favorite narrators", e))?;
+
+    let most_played: Option<String> = sqlx::query_scalar(
+        "SELECT id FROM books WHERE deleted_at IS NULL AND play_count > 0 ORDER BY play_count DESC LIMIT 1",
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to find most played book", e))?;
+
+    let started = get_i64(&row, "started")? as usize;
+    let listened = Duration::from_millis(get_i64(&row, "listened_ms")? as u64);
+
+    Ok(PlaybackStats {
+        total_listening_time: listened,
+        books_started: started,
+        books_finished: get_i64(&row, "finished")? as usize,
+        total_sessions: started,
+        average_session_duration: if started == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_millis(listened.as_millis() / started as u64)
+        },
+        favorite_authors: author_rows
+            .iter()
+            .filter_map(|r| r.try_get("author").ok())
+            .collect(),
+        favorite_narrators: narrator_rows
+            .iter()
+            .filter_map(|r| r.try_get("narrator").ok())
+            .collect(),
+        most_played_book_id: most_played,
+    })
+}
+
+/// Returns the authors with the most books in the library
+pub async fn get_top_authors(pool: &DbPool, limit: i64) -> Result<Vec<(String, usize)>, AppError> {
+    let rows = sqlx::query(
+        r#"
+        SELECT author, COUNT(*) AS book_count
+        FROM books
+        WHERE deleted_at IS NULL AND author IS NOT NULL
+        GROUP BY author
+        ORDER BY book_count DESC, author
+        LIMIT ?
+        "#,
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to get top authors", e))?;
+
+    rows.iter()
+        .map(|row| {
+            let author: String = row
+                .try_get("author")
+                .map_err(|e| AppError::database("Missing author", e))?;
+            Ok((author, get_i64(row, "book_count")? as usize))
+        })
+        .collect()
+}
+
+/// Minutes listened on one calendar day
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DailyListening {
+    pub date: NaiveDate,
+    pub minutes: u32,
+}
+
+/// Listening history of one book
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookStats {
+    pub sessions: usize,
+    /// Time spent listening, whatever the speed
+    pub listened: Duration,
+    pub first_listened: Option<Timestamp>,
+    pub last_listened: Option<Timestamp>,
+    /// Playback speed averaged over the time listened
+    pub average_speed: f32,
+    /// The saved position has reached [`FINISHED_THRESHOLD`]
+    pub finished: bool,
+}
+
+/// Records a stretch of listening to a book at `speed`
+///
+/// Sessions shorter than [`MIN_SESSION_SECS`] are ignored, as are backwards
+/// ones.
+pub async fn record_listening_session(
+    pool: &DbPool,
+    book_id: BookId,
+    started_at: Timestamp,
+    ended_at: Timestamp,
+    speed: f32,
+) -> Result<(), AppError> {
+    let seconds_listened = (ended_at.as_millis() - started_at.as_millis()) / 1000;
+    if seconds_listened < MIN_SESSION_SECS {
+        return Ok(());
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO listening_sessions (book_id, started_at, ended_at, seconds_listened, speed)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(book_id.as_string())
+    .bind(started_at.as_millis())
+    .bind(ended_at.as_millis())
+    .bind(seconds_listened)
+    .bind(speed)
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to record listening session", e))?;
+
+    Ok(())
+}
+
+/// Computes the listening history of one book
+pub async fn get_book_stats(pool: &DbPool, book_id: BookId) -> Result<BookStats, AppError> {
+    let row = sqlx::query(
+        r#"
+        SELECT
+            COUNT(*) AS sessions,
+            COALESCE(SUM(seconds_listened), 0) AS seconds,
+            MIN(started_at) AS first_listened,
+            MAX(ended_at) AS last_listened,
+            SUM(seconds_listened * speed) / NULLIF(SUM(seconds_listened), 0) AS average_speed
+        FROM listening_sessions
+        WHERE book_id = ?
+        "#,
+    )
+    .bind(book_id.as_string())
+    .fetch_one(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to compute book listening totals", e))?;
+
+    let finished: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS(
+            SELECT 1
+            FROM playback_state ps
+            JOIN books b ON b.id = ps.book_id
+            WHERE ps.book_id = ? AND ps.position_ms >= b.duration_ms * ?
+        )
+        "#,
+    )
+    .bind(book_id.as_string())
+    .bind(FINISHED_THRESHOLD)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to check whether the book is finished", e))?;
+
+    let timestamp = |column: &str| -> Result<Option<Timestamp>, AppError> {
+        let millis: Option<i64> = row
+            .try_get(column)
+            .map_err(|e| AppError::database(format!("Missing {}", column), e))?;
+        Ok(millis.map(Timestamp::from_millis))
+    };
+    let average_speed: Option<f64> = row
+        .try_get("average_speed")
+        .map_err(|e| AppError::database("Missing average_speed", e))?;
+
+    Ok(BookStats {
+        sessions: get_i64(&row, "sessions")? as usize,
+        listened: Duration::from_seconds(get_i64(&row, "seconds")?.max(0) as u64),
+        first_listened: timestamp("first_listened")?,
+        last_listened: timestamp("last_listened")?,
+        average_speed: average_speed.unwrap_or(1.0) as f32,
+        finished,
+    })
+}
+
+/// Minutes listened on each of the last `last_n_days` local calendar days
+///
+/// Returns one entry per day, oldest first and ending today, including days
+/// with no listening. Sessions that cross midnight count toward both days.
+pub async fn daily_listening(
+    pool: &DbPool,
+    last_n_days: u32,
+) -> Result<Vec<DailyListening>, AppError> {
+    if last_n_days == 0 {
+        return Ok(Vec::new());
+    }
+    let today = Local::now().date_naive();
+    let first_day = today
+        .checked_sub_days(Days::new(u64::from(last_n_days - 1)))
+        .unwrap_or(NaiveDate::MIN);
+
+    let window_start = day_start(&Local, first_day);
+    let sessions: Vec<(i64, i64)> = sqlx::query_as(
+        r#"
+        SELECT started_at, ended_at
+        FROM listening_sessions
+        WHERE ended_at > ?
+        "#,
+    )
+    .bind(window_start)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::database("Failed to load listening sessions", e))?;
+
+    Ok(split_by_day(&Local, first_day, last_n_days, &sessions))
+}
+
+/// Spreads `(start_ms, end_ms)` sessions over consecutive days in `tz`
+fn split_by_day<Tz: TimeZone>(
+    tz: &Tz,
+    first_day: NaiveDate,
+    days: u32,
+    sessions: &[(i64, i64)],
+) -> Vec<DailyListening> {
+    first_day
+        .iter_days()
+        .take(days as usize)
+        .map(|date| {
+            let start = day_start(tz, date);
+            let end = date.succ_opt().map_or(i64::MAX, |next| day_start(tz, next));
+            let millis: i64 = sessions
+                .iter()
+                .map(|&(from, to)| (to.min(end) - from.max(start)).max(0))
+                .sum();
+            DailyListening {
+                date,
+                minutes: (millis / 60_000) as u32,
+            }
+        })
+        .collect()
+}
+
+/// Current and longest runs of consecutive days in `tz` with some listening
+///
+/// As with the daily goal, today without listening yet does not break the
+/// current run; it counts back from yesterday.
+fn listening_streaks<Tz: TimeZone>(
+    tz: &Tz,
+    today: NaiveDate,
+    sessions: &[(i64, i64)],
+) -> (u32, u32) {
+    let date = |millis: i64| {
+        tz.timestamp_millis_opt(millis)
+            .single()
+            .map(|t| t.date_naive())
+    };
+    let days: BTreeSet<NaiveDate> = sessions
+        .iter()
+        .filter_map(|&(from, to)| Some((date(from)?, date(to.max(from + 1) - 1)?)))
+        .flat_map(|(first, last)| first.iter_days().take_while(move |day| *day <= last))
+        .collect();
+
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for &day in &days {
+        run = if previous.and_then(|p| p.succ_opt()) == Some(day) {
+            run + 1
+        } else {
+            1
+        };
+        longest = longest.max(run);
+        previous = Some(day);
+    }
+
+    let mut current = 0;
+    let mut day = if days.contains(&today) {
+        Some(today)
+    } else {
+        today.pred_opt()
+    };
+    while let Some(d) = day.filter(|d| days.contains(d)) {
+        current += 1;
+        day = d.pred_opt();
+    }
+    (current, longest)
+}
+
+/// Milliseconds since the epoch at which `date` begins in `tz`
+fn day_start<Tz: TimeZone>(tz: &Tz, date: NaiveDate) -> i64 {
+    let midnight = date.and_time(NaiveTime::MIN);
+    // Where a DST change skips midnight, the day starts an hour later
+    tz.from_local_datetime(&midnight)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(midnight + chrono::Duration::hours(1)))
+                .earliest()
+        })
+        .map_or_else(
+            || midnight.and_utc().timestamp_millis(),
+            |t| t.timestamp_millis(),
+        )
+}
+
+async fn count(pool: &DbPool, sql: &str) -> Result<usize, AppError> {
+    let n: i64 = sqlx::query_scalar(sql)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| AppError::database("Failed to count rows", e))?;
+    Ok(n as usize)
+}
+
+fn get_i64(row: &sqlx::sqlite::SqliteRow, column: &str) -> Result<i64, AppError> {
+    row.try_get(column)
+        .map_err(|e| AppError::database(format!("Missing {}", column), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::create_test_db;
+    use crate::migrations::run_migrations;
+    use crate::queries::books::create_book;
+    use crate::queries::playback::create_playback_state;
+    use std::path::PathBuf;
+    use storystream_core::{Book, PlaybackState};
+
+    async fn setup() -> DbPool {
+        let pool = create_test_db().await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool
+    }
+
+    async fn add_book(pool: &DbPool, title: &str, author: &str, position_secs: u64) -> Book {
+        let mut book = Book::new(
+            title.to_string(),
+            PathBuf::from(format!("/{}.mp3", title)),
+            1000,
+            Duration::from_seconds(100),
+        );
+        book.author = Some(author.to_string());
+        create_book(pool, &book).await.unwrap();
+
+        if position_secs > 0 {
+            let mut state = PlaybackState::new(book.id);
+            state.set_position(Duration::from_seconds(position_secs));
+            create_playback_state(pool, &state).await.unwrap();
+        }
+        book
+    }
+
+    #[tokio::test]
+    async fn test_library_stats_empty() {
+        let pool = setup().await;
+        let stats = get_library_stats(&pool).await.unwrap();
+        assert_eq!(stats.total_books, 0);
+        assert!(stats.total_duration.is_zero());
+    }
+
+    #[tokio::test]
+    async fn test_library_stats_counts() {
+        let pool = setup().await;
+        add_book(&pool, "One", "Austen", 100).await;
+        add_book(&pool, "Two", "Austen", 30).await;
+        add_book(&pool, "Three", "Melville", 0).await;
+
+        let stats = get_library_stats(&pool).await.unwrap();
+        assert_eq!(stats.total_books, 3);
+        assert_eq!(stats.total_duration, Duration::from_seconds(300));
+        assert_eq!(stats.authors_count, 2);
+        assert_eq!(stats.finished_count, 1);
+        assert_eq!(stats.unfinished_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_playback_stats() {
+        let pool = setup().await;
+        add_book(&pool, "One", "Austen", 100).await;
+        add_book(&pool, "Two", "Melville", 30).await;
+
+        let stats = get_playback_stats(&pool, None).await.unwrap();
+        assert_eq!(stats.total_listening_time, Duration::from_seconds(130));
+        assert_eq!(stats.books_started, 2);
+        assert_eq!(stats.books_finished, 1);
+        assert_eq!(stats.favorite_authors, vec!["Austen", "Melville"]);
+
+        let future = Timestamp::from_millis(Timestamp::now().as_millis() + 60_000);
+        let recent = get_playback_stats(&pool, Some(future)).await.unwrap();
+        assert!(recent.total_listening_time.is_zero());
+    }
+
+    #[tokio::test]
+    async fn test_top_authors() {
+        let pool = setup().await;
+        add_book(&pool, "One", "Austen", 0).await;
+        add_book(&pool, "Two", "Austen", 0).await;
+        add_book(&pool, "Three", "Melville", 0).await;
+
+        let top = get_top_authors(&pool, 1).await.unwrap();
+        assert_eq!(top, vec![("Austen".to_string(), 2)]);
+    }
+
+    #[test]
+    fn test_split_sessions_across_midnight() {
+        let tz = chrono::FixedOffset::east_opt(2 * 3600).unwrap();
+        let first_day = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let at = |day: u32, hour: u32, minute: u32| {
+            tz.with_ymd_and_hms(2024, 3, day, hour, minute, 0)
+                .unwrap()
+                .timestamp_millis()
+        };
+
+        let sessions = [
+            (at(1, 23, 40), at(2, 0, 25)),
+            (at(2, 9, 0), at(2, 9, 10)),
+            (at(4, 12, 0), at(4, 12, 30)),
+        ];
+        let days = split_by_day(&tz, first_day, 3, &sessions);
+
+        let minutes: Vec<u32> = days.iter().map(|d| d.minutes).collect();
+        assert_eq!(minutes, vec![20, 35, 0]);
+        assert_eq!(days[2].date, NaiveDate::from_ymd_opt(2024, 3, 3).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_daily_listening() {
+        let pool = setup().await;
+        let book = add_book(&pool, "One", "Austen", 0).await;
+
+        let now = Timestamp::now().as_millis();
+        let today_start = day_start(&Local, Local::now().date_naive());
+        let start = today_start.max(now - 10 * 60_000);
+        record_listening_session(
+            &pool,
+            book.id,
+            Timestamp::from_millis(start),
+            Timestamp::from_millis(start + 5 * 60_000),
+            1.0,
+        )
+        .await
+        .unwrap();
+        // Ignored: ends before it starts
+        record_listening_session(
+            &pool,
+            book.id,
+            Timestamp::from_millis(now),
+            Timestamp::from_millis(now - 1),
+            1.0,
+        )
+        .await
+        .unwrap();
+
+        let days = daily_listening(&pool, 7).await.unwrap();
+        assert_eq!(days.len(), 7);
+        assert_eq!(days[6].date, Local::now().date_naive());
+        assert_eq!(days[6].minutes, 5);
+        assert!(days[..6].iter().all(|d| d.minutes == 0));
+
+        assert!(daily_listening(&pool, 0).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_book_stats() {
+        let pool = setup().await;
+        let book = add_book(&pool, "One", "Austen", 99).await;
+        let other = add_book(&pool, "Two", "Melville", 10).await;
+
+        let hour_ago = Timestamp::now().as_millis() - 3_600_000;
+        let session = |book: &Book, start_secs: i64, secs: i64, speed: f32| {
+            let start = hour_ago + start_secs * 1000;
+            record_listening_session(
+                &pool,
+                book.id,
+                Timestamp::from_millis(start),
+                Timestamp::from_millis(start + secs * 1000),
+                speed,
+            )
+        };
+        session(&book, 0, 600, 1.0).await.unwrap();
+        session(&book, 900, 300, 1.5).await.unwrap();
+        // Too short to count
+        session(&book, 1500, 5, 2.0).await.unwrap();
+        session(&other, 1800, 1200, 1.0).await.unwrap();
+
+        let stats = get_book_stats(&pool, book.id).await.unwrap();
+        assert_eq!(stats.sessions, 2);
+        assert_eq!(stats.listened, Duration::from_seconds(900));
+        assert_eq!(stats.first_listened, Some(Timestamp::from_millis(hour_ago)));
+        assert_eq!(
+            stats.last_listened,
+            Some(Timestamp::from_millis(hour_ago + 1_200_000))
+        );
+        assert!((stats.average_speed - 7.0 / 6.0).abs() < 0.001);
+        assert!(stats.finished);
+        assert!(!get_book_stats(&pool, other.id).await.unwrap().finished);
+
+        let library = get_library_stats(&pool).await.unwrap();
+        assert_eq!(library.listening_time, Duration::from_seconds(2100));
+        assert_eq!(library.finished_count, 1);
+        // Two days if the sessions straddle midnight
+        assert!(library.current_streak_days >= 1);
+        assert_eq!(library.longest_streak_days, library.current_streak_days);
+        assert_eq!(
+            library.top_listened_authors,
+            vec![
+                ("Melville".to_string(), Duration::from_seconds(1200)),
+                ("Austen".to_string(), Duration::from_seconds(900)),
+            ]
+        );
+        assert_eq!(
+            library.top_listened_books,
+            vec![
+                ("Two".to_string(), Duration::from_seconds(1200)),
+                ("One".to_string(), Duration::from_seconds(900)),
+            ]
+        );
+
+        let unheard = add_book(&pool, "Three", "Austen", 0).await;
+        let stats = get_book_stats(&pool, unheard.id).await.unwrap();
+        assert_eq!(stats.sessions, 0);
+        assert_eq!(stats.first_listened, None);
+        assert_eq!(stats.average_speed, 1.0);
+    }
+
+    #[test]
+    fn test_listening_streaks() {
+        let tz = chrono::FixedOffset::east_opt(2 * 3600).unwrap();
+        let today = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        let at = |day: u32, hour: u32| {
+            tz.with_ymd_and_hms(2024, 3, day, hour, 0, 0)
+                .unwrap()
+                .timestamp_millis()
+        };
+        let minutes =
+            |day: u32, hour: u32, length: i64| (at(day, hour), at(day, hour) + length * 60_000);
+
+        // Four days running, as one session crosses midnight, then a gap
+        let sessions = [
+            minutes(2, 20, 30),
+            minutes(3, 23, 90),
+            minutes(5, 9, 10),
+            minutes(8, 9, 10),
+            minutes(9, 9, 10),
+        ];
+        // Nothing yet today, so the current run counts back from yesterday
+        assert_eq!(listening_streaks(&tz, today, &sessions), (2, 4));
+
+        let next_day = today.succ_opt().unwrap();
+        assert_eq!(listening_streaks(&tz, next_day, &sessions), (0, 4));
+        assert_eq!(listening_streaks(&tz, today, &[]), (0, 0));
+    }
+}
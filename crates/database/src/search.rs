@@ -1,7 +1,8 @@
 //! Full-text search using FTS5
 
+use crate::queries::{books::BookSort, stats::FINISHED_THRESHOLD};
 use crate::DbPool;
-use storystream_core::{AppError, Book, Bookmark, Chapter};
+use storystream_core::{AppError, Book, Bookmark, Chapter, Duration};
 
 /// Search result with relevance ranking
 #[derive(Debug, Clone)]
@@ -10,32 +11,93 @@ pub struct SearchResult<T> {
     pub rank: f64,
 }
 
-/// Searches books by text query
+/// Relevance of a book to the search, lower is better
+///
+/// Weighs the columns of `books_fts` in order: a hit in the title counts
+/// most, then author and series, narrator, tags and the description.
+const BOOK_RANK: &str = "bm25(books_fts, 10.0, 6.0, 4.0, 6.0, 1.0, 2.0)";
+
+/// Searches books by text query, best matches first
+///
+/// Every word must appear in the title, author, narrator, series, tags or
+/// description, and matches as a prefix. A query without words finds
+/// nothing.
 pub async fn search_books(
     pool: &DbPool,
     query: &str,
     limit: i64,
 ) -> Result<Vec<SearchResult<Book>>, AppError> {
-    let rows = sqlx::query(
-        r#"
-        SELECT b.id, b.title, b.author, b.narrator, b.series, b.series_position,
-               b.description, b.language, b.publisher, b.published_date, b.isbn,
-               b.duration_ms, b.file_path, b.file_size, b.cover_art_path,
-               b.added_date, b.last_played, b.play_count, b.is_favorite, b.rating, b.tags, b.deleted_at,
-               bm.rank as rank
-        FROM books_fts bm
-        JOIN books b ON bm.rowid = b.rowid
-        WHERE books_fts MATCH ?
-        AND b.deleted_at IS NULL
-        ORDER BY rank
-        LIMIT ?
-        "#,
-    )
-        .bind(query)
-        .bind(limit)
+    if search_words(query).is_empty() {
+        return Ok(Vec::new());
+    }
+    search_books_filtered(pool, Some(query), &SearchFilter::default(), None, limit).await
+}
+
+/// Structured constraints for [`search_books_filtered`]
+///
+/// Every constraint that is set must hold; unset ones match any book.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchFilter {
+    /// Author contains this text (case-insensitive)
+    pub author: Option<String>,
+    /// Narrator contains this text (case-insensitive)
+    pub narrator: Option<String>,
+    /// Book carries this tag (case-insensitive)
+    pub tag: Option<String>,
+    /// Only favorites
+    pub favorite: bool,
+    /// Only finished (`Some(true)`) or only unfinished (`Some(false)`) books
+    pub finished: Option<bool>,
+    /// Shortest book to include
+    pub min_duration: Option<Duration>,
+    /// Longest book to include
+    pub max_duration: Option<Duration>,
+    /// File extension without the dot, such as `m4b`
+    pub format: Option<String>,
+}
+
+impl SearchFilter {
+    /// Whether no constraint is set
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Searches books by optional text combined with structured constraints
+///
+/// Text is matched word by word against the full-text index, and hits carry
+/// its rank; without text every book passing `filter` is returned with rank 0.
+/// A `sort` of `None` puts the best matches first, or sorts by title when
+/// there is no text.
+pub async fn search_books_filtered(
+    pool: &DbPool,
+    query: Option<&str>,
+    filter: &SearchFilter,
+    sort: Option<BookSort>,
+    limit: i64,
+) -> Result<Vec<SearchResult<Book>>, AppError> {
+    let words = query.map(search_words).unwrap_or_default();
+    let text = (!words.is_empty()).then(|| TextMatch::Index(fts_query(&words)));
+
+    let rows = match filtered_query(text.as_ref(), filter, sort, limit)
+        .build()
         .fetch_all(pool)
         .await
-        .map_err(|e| AppError::database("Failed to search books", e))?;
+    {
+        Err(e) if is_missing_index(&e) => {
+            tracing::warn!(
+                "Book search index unavailable, matching text directly: {}",
+                e
+            );
+            let text = TextMatch::Like(words);
+            filtered_query(Some(&text), filter, sort, limit)
+                .build()
+                .fetch_all(pool)
+                .await
+        }
+        rows => rows,
+    }
+    .map_err(|e| AppError::database("Failed to search books", e))?;
 
     rows.into_iter()
         .map(|row| {
@@ -47,6 +109,158 @@ pub async fn search_books(
         .collect()
 }
 
+/// How the text of a search is matched
+enum TextMatch<'a> {
+    /// An FTS5 query against the search index
+    Index(String),
+    /// Words to find with LIKE, for when the index is unavailable
+    Like(Vec<&'a str>),
+}
+
+/// Builds the query behind [`search_books_filtered`]
+fn filtered_query<'a>(
+    text: Option<&TextMatch>,
+    filter: &SearchFilter,
+    sort: Option<BookSort>,
+    limit: i64,
+) -> sqlx::QueryBuilder<'a, sqlx::Sqlite> {
+    let mut sql = sqlx::QueryBuilder::<sqlx::Sqlite>::new(
+        r#"
+        SELECT b.id, b.title, b.author, b.narrator, b.series, b.series_position,
+               b.description, b.language, b.publisher, b.published_date, b.isbn, b.genre,
+               b.duration_ms, b.file_path, b.file_size, b.cover_art_path,
+               b.added_date, b.last_played, b.play_count, b.is_favorite, b.rating, b.tags, b.deleted_at,
+        "#,
+    );
+    match text {
+        Some(TextMatch::Index(query)) => {
+            sql.push(format!(
+                " m.rank AS rank FROM books b JOIN (SELECT rowid, {} AS rank FROM books_fts WHERE books_fts MATCH ",
+                BOOK_RANK
+            ))
+            .push_bind(query.clone())
+            .push(") m ON m.rowid = b.rowid");
+        }
+        _ => {
+            sql.push(" 0.0 AS rank FROM books b");
+        }
+    }
+    sql.push(" LEFT JOIN playback_state ps ON ps.book_id = b.id WHERE b.deleted_at IS NULL");
+
+    if let Some(TextMatch::Like(words)) = text {
+        for word in words {
+            let pattern = contains_pattern(word);
+            sql.push(" AND (");
+            let mut columns = sql.separated(" OR ");
+            for column in SEARCHED_COLUMNS {
+                columns
+                    .push(format!("b.{} LIKE ", column))
+                    .push_bind_unseparated(pattern.clone())
+                    .push_unseparated(" ESCAPE '\\'");
+            }
+            sql.push(")");
+        }
+    }
+    if let Some(author) = &filter.author {
+        sql.push(" AND b.author LIKE ")
+            .push_bind(contains_pattern(author))
+            .push(" ESCAPE '\\'");
+    }
+    if let Some(narrator) = &filter.narrator {
+        sql.push(" AND b.narrator LIKE ")
+            .push_bind(contains_pattern(narrator))
+            .push(" ESCAPE '\\'");
+    }
+    if let Some(tag) = &filter.tag {
+        sql.push(" AND EXISTS (SELECT 1 FROM json_each(b.tags) WHERE json_each.value = ")
+            .push_bind(tag.trim().to_string())
+            .push(" COLLATE NOCASE)");
+    }
+    if filter.favorite {
+        sql.push(" AND b.is_favorite = 1");
+    }
+    if let Some(finished) = filter.finished {
+        sql.push(" AND COALESCE(ps.position_ms >= b.duration_ms * ")
+            .push_bind(FINISHED_THRESHOLD)
+            .push(", 0) = ")
+            .push_bind(finished);
+    }
+    if let Some(min) = filter.min_duration {
+        sql.push(" AND b.duration_ms >= ")
+            .push_bind(min.as_millis() as i64);
+    }
+    if let Some(max) = filter.max_duration {
+        sql.push(" AND b.duration_ms <= ")
+            .push_bind(max.as_millis() as i64);
+    }
+    if let Some(format) = &filter.format {
+        let extension = format.trim().trim_start_matches('.');
+        sql.push(" AND b.file_path LIKE ")
+            .push_bind(format!("%.{}", escape_like(extension)))
+            .push(" ESCAPE '\\'");
+    }
+
+    sql.push(" ORDER BY ");
+    match (sort, text) {
+        (Some(sort), _) => sql.push(sort.order_by()),
+        (None, Some(TextMatch::Index(_))) => sql.push("rank, b.title COLLATE NOCASE"),
+        (None, _) => sql.push(BookSort::Title.order_by()),
+    };
+    sql.push(" LIMIT ").push_bind(limit);
+    sql
+}
+
+/// Columns of `books` that text searches look in
+const SEARCHED_COLUMNS: [&str; 6] = [
+    "title",
+    "author",
+    "narrator",
+    "series",
+    "description",
+    "tags",
+];
+
+/// Whether a search failed because the full-text index is missing, such as
+/// when SQLite was built without FTS5
+fn is_missing_index(error: &sqlx::Error) -> bool {
+    let message = error.to_string();
+    message.contains("no such table: books_fts")
+        || message.contains("no such module: fts5")
+        || message.contains("no such function: bm25")
+}
+
+/// Words of typed text worth searching for; ones with no letters or digits
+/// are dropped
+fn search_words(text: &str) -> Vec<&str> {
+    text.split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .collect()
+}
+
+/// Turns search words into an FTS5 query matching every word as a prefix
+///
+/// Each word is quoted so characters such as `-` or `"` are not read as
+/// query syntax.
+fn fts_query(words: &[&str]) -> String {
+    words
+        .iter()
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// LIKE pattern matching values that contain `text`
+fn contains_pattern(text: &str) -> String {
+    format!("%{}%", escape_like(text.trim()))
+}
+
+/// Escapes LIKE wildcards so `text` matches literally
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
 /// Searches chapters by text query
 pub async fn search_chapters(
     pool: &DbPool,
@@ -91,8 +305,8 @@ pub async fn search_bookmarks(
 ) -> Result<Vec<SearchResult<Bookmark>>, AppError> {
     let rows = sqlx::query(
         r#"
-        SELECT bm.id, bm.book_id, bm.position_ms, bm.title, bm.note, bm.created_at, bm.updated_at,
-               bmf.rank as rank
+        SELECT bm.id, bm.book_id, bm.position_ms, bm.title, bm.note, bm.kind, bm.created_at,
+               bm.updated_at, bmf.rank as rank
         FROM bookmarks_fts bmf
         JOIN bookmarks bm ON bmf.rowid = bm.rowid
         WHERE bookmarks_fts MATCH ?
@@ -124,9 +338,10 @@ mod tests {
     use super::*;
     use crate::connection::create_test_db;
     use crate::migrations::run_migrations;
-    use crate::queries::books::create_book;
+    use crate::queries::books::{create_book, update_book};
+    use crate::queries::playback::create_playback_state;
     use std::path::PathBuf;
-    use storystream_core::{Book, Duration};
+    use storystream_core::{Book, Duration, PlaybackState};
 
     async fn setup() -> DbPool {
         let pool = create_test_db().await.unwrap();
@@ -161,4 +376,261 @@ mod tests {
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].item.title, "The Great Adventure");
     }
+
+    /// Books with their words spread over several columns
+    async fn search_library(pool: &DbPool) -> (Book, Book) {
+        let mut gatsby = Book::new(
+            "The Great Gatsby".to_string(),
+            PathBuf::from("/books/gatsby.m4b"),
+            1000,
+            Duration::from_seconds(5 * 3600),
+        );
+        gatsby.author = Some("F. Scott Fitzgerald".to_string());
+
+        let mut tender = Book::new(
+            "Tender Is the Night".to_string(),
+            PathBuf::from("/books/tender.m4b"),
+            1000,
+            Duration::from_seconds(12 * 3600),
+        );
+        tender.author = Some("F. Scott Fitzgerald".to_string());
+        tender.description = Some("Written after The Great Gatsby".to_string());
+
+        let mut jane = Book::new(
+            "Jane Eyre".to_string(),
+            PathBuf::from("/books/jane_eyre.mp3"),
+            1000,
+            Duration::from_seconds(19 * 3600),
+        );
+        jane.author = Some("Charlotte Brontë".to_string());
+
+        for book in [&gatsby, &tender, &jane] {
+            create_book(pool, book).await.unwrap();
+        }
+        (gatsby, tender)
+    }
+
+    #[tokio::test]
+    async fn test_search_multiple_terms_and_prefixes() {
+        let pool = setup().await;
+        search_library(&pool).await;
+
+        // No single column holds every word
+        let results = search_books(&pool, "great gatsby fitzgerald", 10)
+            .await
+            .unwrap();
+        assert_eq!(
+            titles(&results),
+            vec!["The Great Gatsby", "Tender Is the Night"]
+        );
+        // A title hit ranks above one in the description
+        assert!(results[0].rank < results[1].rank);
+
+        let results = search_books(&pool, "gats fitz", 10).await.unwrap();
+        assert_eq!(results[0].item.title, "The Great Gatsby");
+        assert_eq!(
+            titles(&search_books(&pool, "te ni", 10).await.unwrap()),
+            vec!["Tender Is the Night"]
+        );
+
+        // Accents are optional
+        assert_eq!(
+            titles(&search_books(&pool, "bronte", 10).await.unwrap()),
+            vec!["Jane Eyre"]
+        );
+        assert!(search_books(&pool, "- \"", 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_skips_deleted_books() {
+        let pool = setup().await;
+        let (mut gatsby, _) = search_library(&pool).await;
+
+        gatsby.deleted_at = Some(storystream_core::Timestamp::now());
+        update_book(&pool, &gatsby).await.unwrap();
+        assert_eq!(
+            titles(&search_books(&pool, "gatsby", 10).await.unwrap()),
+            vec!["Tender Is the Night"]
+        );
+
+        let fitzgerald = SearchFilter {
+            author: Some("fitzgerald".to_string()),
+            ..Default::default()
+        };
+        let results = search_books_filtered(&pool, None, &fitzgerald, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(titles(&results), vec!["Tender Is the Night"]);
+    }
+
+    #[tokio::test]
+    async fn test_search_without_index_matches_text_directly() {
+        let pool = setup().await;
+        let (mut gatsby, _) = search_library(&pool).await;
+        gatsby.deleted_at = Some(storystream_core::Timestamp::now());
+        update_book(&pool, &gatsby).await.unwrap();
+
+        for sql in [
+            "DROP TRIGGER books_fts_insert",
+            "DROP TRIGGER books_fts_update",
+            "DROP TRIGGER books_fts_delete",
+            "DROP TABLE books_fts",
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+
+        let results = search_books(&pool, "great fitzger", 10).await.unwrap();
+        assert_eq!(titles(&results), vec!["Tender Is the Night"]);
+        assert_eq!(results[0].rank, 0.0);
+        assert!(search_books(&pool, "100%", 10).await.unwrap().is_empty());
+    }
+
+    /// Three books; the Stormlight one is finished
+    async fn filtered_library(pool: &DbPool) -> (Book, Book, Book) {
+        let mut way = Book::new(
+            "The Way of Kings".to_string(),
+            PathBuf::from("/books/way_of_kings.m4b"),
+            1000,
+            Duration::from_seconds(45 * 3600),
+        );
+        way.author = Some("Brandon Sanderson".to_string());
+        way.narrator = Some("Michael Kramer".to_string());
+        way.tags = vec!["Fantasy".to_string()];
+
+        let mut mistborn = Book::new(
+            "Mistborn: The Final Empire".to_string(),
+            PathBuf::from("/books/mistborn.m4b"),
+            1000,
+            Duration::from_seconds(24 * 3600),
+        );
+        mistborn.author = Some("Brandon Sanderson".to_string());
+        mistborn.narrator = Some("Michael Kramer".to_string());
+        mistborn.tags = vec!["fantasy".to_string(), "heist".to_string()];
+        mistborn.is_favorite = true;
+
+        let mut emperor = Book::new(
+            "The Emperor's Soul".to_string(),
+            PathBuf::from("/books/emperors_soul.mp3"),
+            1000,
+            Duration::from_seconds(5 * 3600),
+        );
+        emperor.author = Some("Brandon Sanderson".to_string());
+        emperor.narrator = Some("Kate Reading".to_string());
+
+        for book in [&way, &mistborn, &emperor] {
+            create_book(pool, book).await.unwrap();
+        }
+        let mut state = PlaybackState::new(way.id);
+        state.position = Duration::from_seconds(45 * 3600);
+        create_playback_state(pool, &state).await.unwrap();
+
+        (way, mistborn, emperor)
+    }
+
+    fn titles(results: &[SearchResult<Book>]) -> Vec<&str> {
+        results.iter().map(|r| r.item.title.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_search_filter_only() {
+        let pool = setup().await;
+        filtered_library(&pool).await;
+
+        let filter = SearchFilter {
+            author: Some("sanderson".to_string()),
+            finished: Some(false),
+            min_duration: Some(Duration::from_seconds(20 * 3600)),
+            ..Default::default()
+        };
+        let results = search_books_filtered(&pool, None, &filter, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(titles(&results), vec!["Mistborn: The Final Empire"]);
+        assert_eq!(results[0].rank, 0.0);
+
+        let filter = SearchFilter {
+            tag: Some("FANTASY".to_string()),
+            format: Some(".m4b".to_string()),
+            ..Default::default()
+        };
+        let results = search_books_filtered(&pool, None, &filter, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(
+            titles(&results),
+            vec!["Mistborn: The Final Empire", "The Way of Kings"]
+        );
+
+        let filter = SearchFilter {
+            finished: Some(true),
+            ..Default::default()
+        };
+        let results = search_books_filtered(&pool, None, &filter, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(titles(&results), vec!["The Way of Kings"]);
+
+        // LIKE wildcards in the filter text match literally
+        let filter = SearchFilter {
+            narrator: Some("%".to_string()),
+            ..Default::default()
+        };
+        let results = search_books_filtered(&pool, None, &filter, None, 10)
+            .await
+            .unwrap();
+        assert!(results.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_text_only() {
+        let pool = setup().await;
+        filtered_library(&pool).await;
+
+        let filter = SearchFilter::default();
+        assert!(filter.is_empty());
+        let results = search_books_filtered(&pool, Some("kramer"), &filter, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+
+        // Prefixes match, and query syntax characters are taken literally
+        let results = search_books_filtered(&pool, Some("emper \"soul -"), &filter, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(titles(&results), vec!["The Emperor's Soul"]);
+
+        let results = search_books_filtered(&pool, Some("  "), &filter, None, 2)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_search_text_and_filter() {
+        let pool = setup().await;
+        filtered_library(&pool).await;
+
+        let filter = SearchFilter {
+            narrator: Some("Michael".to_string()),
+            favorite: true,
+            ..Default::default()
+        };
+        let results = search_books_filtered(&pool, Some("sanderson"), &filter, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(titles(&results), vec!["Mistborn: The Final Empire"]);
+
+        let filter = SearchFilter {
+            max_duration: Some(Duration::from_seconds(30 * 3600)),
+            ..Default::default()
+        };
+        let results =
+            search_books_filtered(&pool, Some("brandon"), &filter, Some(BookSort::Title), 10)
+                .await
+                .unwrap();
+        assert_eq!(
+            titles(&results),
+            vec!["Mistborn: The Final Empire", "The Emperor's Soul"]
+        );
+    }
 }
@@ -0,0 +1,95 @@
+//! Round-trips a library through an export file into a fresh database
+
+use std::path::PathBuf;
+use storystream_core::{Book, Bookmark, Duration, PlaybackState};
+use storystream_database::connection::{connect, DatabaseConfig};
+use storystream_database::export::{
+    export_library, import_library, preview_import, ExportOptions, ImportStrategy, LibraryExport,
+};
+use storystream_database::queries::{bookmarks, books, playback};
+use storystream_database::run_migrations;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_export_import_round_trip() {
+    let dir = TempDir::new().unwrap();
+
+    let source = connect(DatabaseConfig::new(
+        dir.path().join("source.db").to_string_lossy(),
+    ))
+    .await
+    .unwrap();
+    run_migrations(&source).await.unwrap();
+
+    let book = Book::new(
+        "Round Trip".to_string(),
+        PathBuf::from("/books/round-trip.m4b"),
+        4096,
+        Duration::from_seconds(7200),
+    );
+    books::create_book(&source, &book).await.unwrap();
+
+    let mut state = PlaybackState::new(book.id);
+    state.set_position(Duration::from_seconds(1234));
+    playback::create_playback_state(&source, &state)
+        .await
+        .unwrap();
+
+    let bookmark = Bookmark::with_title(book.id, Duration::from_seconds(60), "Intro".into());
+    bookmarks::create_bookmark(&source, &bookmark)
+        .await
+        .unwrap();
+
+    let export = export_library(
+        &source,
+        ExportOptions {
+            include_positions: true,
+            include_bookmarks: true,
+        },
+    )
+    .await
+    .unwrap();
+    let json = serde_json::to_string(&export).unwrap();
+
+    // The target is a brand new file; import must migrate it itself
+    let target = connect(DatabaseConfig::new(
+        dir.path().join("target.db").to_string_lossy(),
+    ))
+    .await
+    .unwrap();
+    let parsed: LibraryExport = serde_json::from_str(&json).unwrap();
+
+    let preview = preview_import(&target, &parsed, ImportStrategy::Skip)
+        .await
+        .unwrap();
+    assert_eq!(preview.added, 1);
+
+    let summary = import_library(&target, &parsed, ImportStrategy::Skip)
+        .await
+        .unwrap();
+    assert_eq!(summary.books.added, 1);
+    assert_eq!(summary.positions, 1);
+    assert_eq!(summary.bookmarks, 1);
+
+    let restored = books::get_book(&target, book.id).await.unwrap();
+    assert_eq!(restored.title, "Round Trip");
+    assert_eq!(restored.file_path, book.file_path);
+
+    let restored_state = playback::get_playback_state(&target, book.id)
+        .await
+        .unwrap();
+    assert_eq!(restored_state.position.as_seconds(), 1234);
+
+    let restored_bookmarks = bookmarks::get_book_bookmarks(&target, book.id)
+        .await
+        .unwrap();
+    assert_eq!(restored_bookmarks.len(), 1);
+    assert_eq!(restored_bookmarks[0].title.as_deref(), Some("Intro"));
+
+    // Importing the same file again skips everything
+    let again = import_library(&target, &parsed, ImportStrategy::Skip)
+        .await
+        .unwrap();
+    assert_eq!(again.books.skipped, 1);
+    assert_eq!(again.bookmarks, 0);
+}
@@ -3,6 +3,7 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 /// Type of feed format
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -30,6 +31,8 @@ pub struct Feed {
     pub author: Option<String>,
     /// Feed language
     pub language: Option<String>,
+    /// Cover art URL
+    pub image_url: Option<String>,
     /// Last update time
     pub updated: Option<DateTime<Utc>>,
     /// Feed items/episodes
@@ -46,6 +49,7 @@ impl Feed {
             url: None,
             author: None,
             language: None,
+            image_url: None,
             updated: None,
             items: Vec::new(),
         }
@@ -100,6 +104,16 @@ pub struct FeedItem {
     pub guid: Option<String>,
     /// Audio/video enclosure
     pub enclosure: Option<Enclosure>,
+    /// Running time, as the feed states it
+    pub duration: Option<Duration>,
+    /// Episode number
+    pub episode: Option<u32>,
+    /// Season number
+    pub season: Option<u32>,
+    /// Episode artwork URL, when it differs from the feed's
+    pub image_url: Option<String>,
+    /// Whether the episode is marked explicit
+    pub explicit: Option<bool>,
 }
 
 impl FeedItem {
@@ -113,6 +127,11 @@ impl FeedItem {
             author: None,
             guid: None,
             enclosure: None,
+            duration: None,
+            episode: None,
+            season: None,
+            image_url: None,
+            explicit: None,
         }
     }
 
@@ -29,10 +29,12 @@
 
 mod error;
 mod feed;
+mod opml;
 mod parser;
 
 pub use error::{FeedError, FeedResult};
 pub use feed::{Enclosure, Feed, FeedItem, FeedType};
+pub use opml::{parse_opml, write_opml, OpmlOutline};
 pub use parser::FeedParser;
 
 #[cfg(test)]
@@ -0,0 +1,165 @@
+// crates/feed-parser/src/opml.rs
+//! OPML subscription list import and export
+
+use crate::error::{FeedError, FeedResult};
+use quick_xml::escape::escape;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+/// A single feed entry in an OPML document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpmlOutline {
+    /// Display title of the feed
+    pub title: String,
+    /// Feed URL (`xmlUrl`)
+    pub xml_url: String,
+    /// Website URL (`htmlUrl`)
+    pub html_url: Option<String>,
+}
+
+impl OpmlOutline {
+    /// Creates a new outline for a feed URL
+    pub fn new(title: impl Into<String>, xml_url: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            xml_url: xml_url.into(),
+            html_url: None,
+        }
+    }
+}
+
+/// Parses every feed outline (any `<outline>` with an `xmlUrl`) from an OPML document
+///
+/// Nested category outlines are flattened.
+pub fn parse_opml(content: &str) -> FeedResult<Vec<OpmlOutline>> {
+    if !content.contains("<opml") {
+        return Err(FeedError::UnsupportedFormat(
+            "Not an OPML document".to_string(),
+        ));
+    }
+
+    let mut reader = Reader::from_str(content);
+    let mut outlines = Vec::new();
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => {
+                if e.name().as_ref() != b"outline" {
+                    buf.clear();
+                    continue;
+                }
+
+                let mut title = None;
+                let mut text = None;
+                let mut xml_url = None;
+                let mut html_url = None;
+
+                for attr in e.attributes().flatten() {
+                    let value = match attr.unescape_value() {
+                        Ok(v) => v.to_string(),
+                        Err(_) => String::from_utf8_lossy(&attr.value).to_string(),
+                    };
+                    match attr.key.as_ref() {
+                        b"title" => title = Some(value),
+                        b"text" => text = Some(value),
+                        b"xmlUrl" => xml_url = Some(value),
+                        b"htmlUrl" => html_url = Some(value),
+                        _ => {}
+                    }
+                }
+
+                if let Some(xml_url) = xml_url.filter(|u| !u.trim().is_empty()) {
+                    outlines.push(OpmlOutline {
+                        title: title.or(text).unwrap_or_else(|| xml_url.clone()),
+                        xml_url,
+                        html_url,
+                    });
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                return Err(FeedError::XmlParse(format!(
+                    "Error at position {}: {}",
+                    reader.buffer_position(),
+                    e
+                )))
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(outlines)
+}
+
+/// Renders an OPML 2.0 document listing the given feeds
+pub fn write_opml(title: &str, outlines: &[OpmlOutline]) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<opml version=\"2.0\">\n");
+    out.push_str("  <head>\n");
+    out.push_str(&format!("    <title>{}</title>\n", escape(title)));
+    out.push_str("  </head>\n");
+    out.push_str("  <body>\n");
+
+    for outline in outlines {
+        let title = escape(outline.title.as_str());
+        out.push_str(&format!(
+            "    <outline type=\"rss\" text=\"{}\" title=\"{}\" xmlUrl=\"{}\"",
+            title,
+            title,
+            escape(outline.xml_url.as_str())
+        ));
+        if let Some(ref html_url) = outline.html_url {
+            out.push_str(&format!(" htmlUrl=\"{}\"", escape(html_url.as_str())));
+        }
+        out.push_str("/>\n");
+    }
+
+    out.push_str("  </body>\n");
+    out.push_str("</opml>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_opml_nested() {
+        let opml = r#"<?xml version="1.0"?>
+<opml version="2.0">
+  <head><title>Subs</title></head>
+  <body>
+    <outline text="Audiobooks">
+      <outline type="rss" text="LibriVox" xmlUrl="https://librivox.org/rss/1"/>
+    </outline>
+    <outline type="rss" title="Tom &amp; Friends" xmlUrl="https://example.com/feed.xml" htmlUrl="https://example.com"/>
+  </body>
+</opml>"#;
+
+        let outlines = parse_opml(opml).unwrap();
+        assert_eq!(outlines.len(), 2);
+        assert_eq!(outlines[0].title, "LibriVox");
+        assert_eq!(outlines[1].title, "Tom & Friends");
+        assert_eq!(outlines[1].html_url.as_deref(), Some("https://example.com"));
+    }
+
+    #[test]
+    fn test_parse_opml_rejects_other_documents() {
+        assert!(parse_opml("<rss version=\"2.0\"></rss>").is_err());
+    }
+
+    #[test]
+    fn test_opml_round_trip() {
+        let outlines = vec![
+            OpmlOutline::new("A \"quoted\" <feed>", "https://example.com/a?x=1&y=2"),
+            OpmlOutline::new("Plain", "https://example.com/b"),
+        ];
+
+        let xml = write_opml("StoryStream", &outlines);
+        let parsed = parse_opml(&xml).unwrap();
+        assert_eq!(parsed, outlines);
+    }
+}
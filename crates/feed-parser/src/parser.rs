@@ -7,8 +7,9 @@
 use crate::error::{FeedError, FeedResult};
 use crate::feed::{Enclosure, Feed, FeedItem, FeedType};
 use chrono::DateTime;
-use quick_xml::events::Event;
+use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
+use std::time::Duration;
 
 /// Feed parser
 pub struct FeedParser;
@@ -52,6 +53,8 @@ impl FeedParser {
         let mut current_item: Option<FeedItem> = None;
         let mut text_buffer = String::new();
         let mut in_item = false;
+        // Inside the channel's <image>, whose title and link are not the feed's
+        let mut in_image = false;
 
         let mut buf = Vec::new();
 
@@ -63,6 +66,16 @@ impl FeedParser {
                     if element_name == "item" {
                         in_item = true;
                         current_item = Some(FeedItem::new(String::new()));
+                    } else if element_name == "image" && !in_item {
+                        in_image = true;
+                    } else if element_name == "itunes:image" {
+                        // The channel's own <image> may come first; iTunes art is larger
+                        if let Some(href) = Self::attribute(&e, "href") {
+                            match current_item.as_mut() {
+                                Some(item) => item.image_url = Some(href),
+                                None => feed.image_url = Some(href),
+                            }
+                        }
                     } else if element_name == "enclosure" {
                         // Parse enclosure attributes
                         let mut url = String::new();
@@ -102,6 +115,10 @@ impl FeedParser {
                         text_buffer.push_str(&unescaped);
                     }
                 }
+                Ok(Event::CData(e)) => {
+                    // Show notes are commonly wrapped in CDATA, which is never escaped
+                    text_buffer.push_str(&String::from_utf8_lossy(&e));
+                }
                 Ok(Event::End(e)) => {
                     let element_name = String::from_utf8_lossy(e.name().as_ref()).to_string();
 
@@ -123,6 +140,13 @@ impl FeedParser {
                                         .map(|dt| dt.with_timezone(&chrono::Utc));
                                 }
                                 "guid" => item.guid = Some(trimmed.to_string()),
+                                "itunes:author" if item.author.is_none() => {
+                                    item.author = Some(trimmed.to_string())
+                                }
+                                "itunes:duration" => item.duration = parse_duration(trimmed),
+                                "itunes:episode" => item.episode = trimmed.parse().ok(),
+                                "itunes:season" => item.season = trimmed.parse().ok(),
+                                "itunes:explicit" => item.explicit = parse_explicit(trimmed),
                                 _ => {}
                             }
                         }
@@ -138,10 +162,19 @@ impl FeedParser {
                         let trimmed = text_buffer.trim();
 
                         match element_name.as_str() {
+                            "image" => in_image = false,
+                            "url" if in_image && feed.image_url.is_none() => {
+                                feed.image_url = Some(trimmed.to_string())
+                            }
+                            _ if in_image => {}
                             "title" if feed.title.is_empty() => feed.title = trimmed.to_string(),
                             "description" => feed.description = Some(trimmed.to_string()),
+                            "itunes:summary" if feed.description.is_none() => {
+                                feed.description = Some(trimmed.to_string())
+                            }
                             "link" => feed.url = Some(trimmed.to_string()),
                             "language" => feed.language = Some(trimmed.to_string()),
+                            "itunes:author" => feed.author = Some(trimmed.to_string()),
                             _ => {}
                         }
                     }
@@ -214,6 +247,10 @@ impl FeedParser {
                         text_buffer.push_str(&unescaped);
                     }
                 }
+                Ok(Event::CData(e)) => {
+                    // Show notes are commonly wrapped in CDATA, which is never escaped
+                    text_buffer.push_str(&String::from_utf8_lossy(&e));
+                }
                 Ok(Event::End(e)) => {
                     let element_name = String::from_utf8_lossy(e.name().as_ref()).to_string();
 
@@ -272,6 +309,49 @@ impl FeedParser {
 
         Ok(feed)
     }
+
+    /// Returns the value of an element's attribute
+    fn attribute(element: &BytesStart, name: &str) -> Option<String> {
+        element
+            .attributes()
+            .flatten()
+            .find(|attr| attr.key.as_ref() == name.as_bytes())
+            .map(|attr| String::from_utf8_lossy(&attr.value).trim().to_string())
+            .filter(|value| !value.is_empty())
+    }
+}
+
+/// Parses an `itunes:duration`, given in seconds or as `[HH:]MM:SS`
+///
+/// Values that fit neither form give `None`.
+fn parse_duration(text: &str) -> Option<Duration> {
+    let parts: Vec<&str> = text.split(':').map(str::trim).collect();
+    let (seconds, larger) = parts.split_last()?;
+    if larger.len() > 2 {
+        return None;
+    }
+
+    let seconds: f64 = seconds.parse().ok()?;
+    if !seconds.is_finite() || seconds < 0.0 || (!larger.is_empty() && seconds >= 60.0) {
+        return None;
+    }
+
+    let mut minutes: u64 = 0;
+    for part in larger {
+        let value: u64 = part.parse().ok()?;
+        minutes = minutes.checked_mul(60)?.checked_add(value)?;
+    }
+    Duration::from_secs(minutes.checked_mul(60)?)
+        .checked_add(Duration::try_from_secs_f64(seconds).ok()?)
+}
+
+/// Parses an `itunes:explicit` flag
+fn parse_explicit(text: &str) -> Option<bool> {
+    match text.to_ascii_lowercase().as_str() {
+        "yes" | "true" | "explicit" => Some(true),
+        "no" | "false" | "clean" => Some(false),
+        _ => None,
+    }
 }
 
 #[cfg(test)]
@@ -395,6 +475,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_duration_forms() {
+        assert_eq!(parse_duration("3723"), Some(Duration::from_secs(3723)));
+        assert_eq!(parse_duration("1:02:03"), Some(Duration::from_secs(3723)));
+        assert_eq!(parse_duration("62:03"), Some(Duration::from_secs(3723)));
+        assert_eq!(parse_duration(" 00:45 "), Some(Duration::from_secs(45)));
+        assert_eq!(parse_duration("90.5"), Some(Duration::from_millis(90_500)));
+
+        for malformed in ["", "abc", "1:2:3:4", "-5", "10:75", "1::00", "NaN"] {
+            assert_eq!(parse_duration(malformed), None, "{:?}", malformed);
+        }
+    }
+
+    #[test]
+    fn test_parse_explicit() {
+        assert_eq!(parse_explicit("Yes"), Some(true));
+        assert_eq!(parse_explicit("clean"), Some(false));
+        assert_eq!(parse_explicit("maybe"), None);
+    }
+
     #[test]
     fn test_html_entity_unescaping() {
         let rss = r#"<?xml version="1.0"?>
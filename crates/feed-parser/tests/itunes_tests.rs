@@ -0,0 +1,97 @@
+// crates/feed-parser/tests/itunes_tests.rs
+//! iTunes podcast namespace tags, checked against fixtures shaped like real feeds
+
+use std::time::Duration;
+use storystream_feed_parser::FeedParser;
+
+const HOSTED_PODCAST: &str = include_str!("fixtures/hosted_podcast.xml");
+const AUDIOBOOK_FEED: &str = include_str!("fixtures/audiobook_feed.xml");
+const MALFORMED_ITUNES: &str = include_str!("fixtures/malformed_itunes.xml");
+
+#[test]
+fn test_hosted_podcast_channel_metadata() {
+    let feed = FeedParser::parse(HOSTED_PODCAST).expect("Should parse hosted podcast");
+
+    assert_eq!(feed.title, "The Night Library");
+    assert_eq!(feed.author.as_deref(), Some("Night Library Productions"));
+    assert_eq!(
+        feed.image_url.as_deref(),
+        Some("https://cdn.example.com/night-library/cover-3000.jpg")
+    );
+    // The <image> block's own link must not replace the channel's
+    assert_eq!(
+        feed.url.as_deref(),
+        Some("https://nightlibrary.example.com")
+    );
+    assert_eq!(
+        feed.description.as_deref(),
+        Some("<p>Public domain stories read slowly, for falling asleep.</p>")
+    );
+}
+
+#[test]
+fn test_hosted_podcast_episode_metadata() {
+    let feed = FeedParser::parse(HOSTED_PODCAST).expect("Should parse hosted podcast");
+    assert_eq!(feed.item_count(), 3);
+
+    let latest = &feed.items[0];
+    assert_eq!(latest.title, "The Canterville Ghost, Part 2");
+    assert_eq!(latest.duration, Some(Duration::from_secs(4018)));
+    assert_eq!(latest.episode, Some(12));
+    assert_eq!(latest.season, Some(2));
+    assert_eq!(latest.explicit, Some(false));
+    assert_eq!(
+        latest.image_url.as_deref(),
+        Some("https://cdn.example.com/night-library/canterville.jpg")
+    );
+    assert!(latest
+        .description
+        .as_deref()
+        .is_some_and(|d| d.contains("The ghost tries again")));
+
+    assert_eq!(feed.items[1].duration, Some(Duration::from_secs(3501)));
+    assert_eq!(feed.items[1].image_url, None);
+
+    let trailer = &feed.items[2];
+    assert_eq!(trailer.duration, Some(Duration::from_secs(105)));
+    assert_eq!(trailer.episode, None);
+    assert!(trailer.has_audio());
+}
+
+#[test]
+fn test_audiobook_feed_with_cdata_titles() {
+    let feed = FeedParser::parse(AUDIOBOOK_FEED).expect("Should parse audiobook feed");
+
+    assert_eq!(
+        feed.title,
+        "Sense and Sensibility by Jane Austen (1775 - 1817)"
+    );
+    assert_eq!(feed.author.as_deref(), Some("Jane Austen"));
+    assert_eq!(
+        feed.description.as_deref(),
+        Some("A volunteer reading of the complete novel.")
+    );
+    assert_eq!(feed.image_url, None);
+
+    let titles: Vec<&str> = feed.items.iter().map(|i| i.title.as_str()).collect();
+    assert_eq!(titles, ["01 - Chapter 1", "02 - Chapter 2"]);
+    assert_eq!(feed.items[0].author.as_deref(), Some("Jane Austen"));
+    assert_eq!(feed.items[0].duration, Some(Duration::from_secs(931)));
+    assert_eq!(feed.items[0].explicit, Some(false));
+    assert_eq!(feed.items[1].duration, Some(Duration::from_secs(902)));
+}
+
+#[test]
+fn test_malformed_itunes_values_are_ignored() {
+    let feed = FeedParser::parse(MALFORMED_ITUNES).expect("Malformed values must not fail");
+
+    assert_eq!(feed.item_count(), 2);
+    assert_eq!(feed.image_url, None);
+    for item in &feed.items {
+        assert_eq!(item.duration, None, "{}", item.title);
+        assert_eq!(item.episode, None, "{}", item.title);
+        assert_eq!(item.season, None, "{}", item.title);
+        assert_eq!(item.explicit, None, "{}", item.title);
+        assert!(item.has_audio());
+    }
+}
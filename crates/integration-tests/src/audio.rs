@@ -0,0 +1,50 @@
+// FILE: crates/integration-tests/src/audio.rs
+//! Generated audio for test fixtures
+//!
+//! Tests need real audio files, but small ones, so they are written when the
+//! test runs rather than checked in. The WAV encoding itself is shared with
+//! the other crates' tests through `storystream_media_formats::wav`.
+
+use std::f32::consts::PI;
+use std::io;
+use std::path::Path;
+use storystream_media_formats::wav::wav_from_samples;
+
+/// Sample rate of generated files
+pub const SAMPLE_RATE: u32 = 8000;
+
+/// Generates `duration_secs` of a sine wave at `freq` Hz, in mono samples
+/// between -1.0 and 1.0
+pub fn sine_wave(freq: f32, duration_secs: f32, sample_rate: u32) -> Vec<f32> {
+    let count = (duration_secs * sample_rate as f32) as usize;
+    (0..count)
+        .map(|i| (2.0 * PI * freq * i as f32 / sample_rate as f32).sin())
+        .collect()
+}
+
+/// Writes mono samples to `path` as a 16-bit PCM WAV file
+pub fn write_wav(path: &Path, samples: &[f32], sample_rate: u32) -> io::Result<()> {
+    let pcm = samples
+        .iter()
+        .map(|sample| (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16);
+    std::fs::write(path, wav_from_samples(pcm, sample_rate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_wav_size() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("tone.wav");
+        let samples = sine_wave(440.0, 2.0, SAMPLE_RATE);
+
+        write_wav(&path, &samples, SAMPLE_RATE).unwrap();
+
+        assert_eq!(samples.len(), 2 * SAMPLE_RATE as usize);
+        let len = std::fs::metadata(&path).unwrap().len();
+        assert_eq!(len, 44 + 2 * samples.len() as u64);
+    }
+}
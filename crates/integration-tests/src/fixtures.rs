@@ -0,0 +1,63 @@
+// FILE: crates/integration-tests/src/fixtures.rs
+//! A library in a temporary folder
+
+use crate::audio::{sine_wave, write_wav, SAMPLE_RATE};
+use std::fs;
+use std::path::{Path, PathBuf};
+use storystream_database::DbPool;
+use storystream_library::{LibraryConfig, LibraryManager, LibraryResult, PipelineReport};
+use tempfile::TempDir;
+
+/// A library whose database and books live in a temporary folder, removed
+/// when it is dropped
+pub struct TestLibrary {
+    dir: TempDir,
+    manager: LibraryManager,
+}
+
+impl TestLibrary {
+    /// Creates an empty library watching its `books` folder
+    pub async fn new() -> LibraryResult<Self> {
+        let dir = TempDir::new()?;
+        let books = dir.path().join("books");
+        fs::create_dir_all(&books)?;
+        let config = LibraryConfig {
+            database_path: dir.path().join("library.db").display().to_string(),
+            watch_directories: vec![books.display().to_string()],
+            ..LibraryConfig::default()
+        };
+        let manager = LibraryManager::new(config).await?;
+        Ok(Self { dir, manager })
+    }
+
+    /// The folder the library scans
+    pub fn books_dir(&self) -> PathBuf {
+        self.dir.path().join("books")
+    }
+
+    /// Writes a book of `secs` seconds of tone at `name`, relative to the
+    /// books folder, and returns its path
+    pub fn add_book(&self, name: impl AsRef<Path>, secs: u32) -> LibraryResult<PathBuf> {
+        let path = self.books_dir().join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let samples = sine_wave(440.0, secs as f32, SAMPLE_RATE);
+        write_wav(&path, &samples, SAMPLE_RATE)?;
+        Ok(path)
+    }
+
+    /// Scans the books folder and imports what it finds
+    pub async fn import(&self) -> LibraryResult<PipelineReport> {
+        let manager = &self.manager;
+        manager.import_pipeline().run(manager.scanner()).await
+    }
+
+    pub fn manager(&self) -> &LibraryManager {
+        &self.manager
+    }
+
+    pub fn pool(&self) -> &DbPool {
+        self.manager.pool()
+    }
+}
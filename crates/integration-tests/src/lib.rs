@@ -0,0 +1,13 @@
+// FILE: crates/integration-tests/src/lib.rs
+//! Tests that run StoryStream's crates together
+//!
+//! Each crate tests itself; the tests here follow a book through the whole
+//! app instead: scanned from a folder, imported, queried, played and its
+//! position saved. The modules are the fixtures those tests share: audio
+//! files generated on the fly and a library in a temporary folder.
+
+pub mod audio;
+pub mod fixtures;
+
+pub use audio::{sine_wave, write_wav};
+pub use fixtures::TestLibrary;
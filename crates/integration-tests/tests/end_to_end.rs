@@ -0,0 +1,142 @@
+// FILE: crates/integration-tests/tests/end_to_end.rs
+//! A book's way through the app: scanned, imported, queried, played and its
+//! position saved
+
+use media_engine::{EngineConfig, MediaEngine, OutputTarget};
+use std::time::{Duration as StdDuration, Instant};
+use storystream_core::{Book, Bookmark, Duration, PlaybackSpeed, Timestamp};
+use storystream_database::queries::{bookmarks, books, playback, stats};
+use storystream_integration_tests::TestLibrary;
+
+/// Waits up to five seconds for `done` to hold
+fn wait_for(engine: &MediaEngine, done: impl Fn(&MediaEngine) -> bool) -> bool {
+    let deadline = Instant::now() + StdDuration::from_secs(5);
+    while Instant::now() < deadline {
+        if done(engine) {
+            return true;
+        }
+        std::thread::sleep(StdDuration::from_millis(20));
+    }
+    false
+}
+
+fn engine_for(book: &Book) -> MediaEngine {
+    let mut engine = MediaEngine::new(EngineConfig::default())
+        .unwrap()
+        .with_output(OutputTarget::Null);
+    engine
+        .load_book(book.file_path.to_str().unwrap(), None)
+        .unwrap();
+    engine
+}
+
+async fn flush_position(library: &TestLibrary, book: &Book, engine: &MediaEngine) -> Duration {
+    let position = Duration::from(engine.position());
+    playback::update_playback_state(
+        library.pool(),
+        book.id,
+        position,
+        PlaybackSpeed::default(),
+        80,
+    )
+    .await
+    .unwrap();
+    position
+}
+
+#[tokio::test]
+async fn test_scan_import_play_and_persist() {
+    let library = TestLibrary::new().await.unwrap();
+    library.add_book("First Book.wav", 20).unwrap();
+    library.add_book("Series/Second Book.wav", 10).unwrap();
+
+    // Scan and import
+    let report = library.import().await.unwrap();
+    assert_eq!(report.imported, 2, "failed: {:?}", report.failed);
+    assert!(report.failed.is_empty());
+    let again = library.import().await.unwrap();
+    assert_eq!((again.imported, again.skipped), (0, 2));
+
+    // Query
+    let all = books::list_books(library.pool()).await.unwrap();
+    assert_eq!(all.len(), 2);
+    let book = all.into_iter().find(|b| b.title == "First Book").unwrap();
+    assert_eq!(book.duration.as_seconds(), 20);
+    assert!(book.file_path.starts_with(library.books_dir()));
+
+    // Play until the position moves, then save it as the app does
+    let mut engine = engine_for(&book);
+    engine.play().unwrap();
+    assert!(wait_for(&engine, |e| e.position() >= StdDuration::from_millis(500)));
+    engine.pause().unwrap();
+    let played = flush_position(&library, &book, &engine).await;
+    let state = playback::get_playback_state(library.pool(), book.id)
+        .await
+        .unwrap();
+    assert_eq!(state.position, played);
+
+    // A seek is saved on the next flush
+    engine.seek(StdDuration::from_secs(3)).unwrap();
+    assert!(wait_for(&engine, |e| e.position() >= StdDuration::from_secs(3)));
+    let sought = flush_position(&library, &book, &engine).await;
+    let state = playback::get_playback_state(library.pool(), book.id)
+        .await
+        .unwrap();
+    assert_eq!(state.position, sought);
+    assert!(state.position >= Duration::from_seconds(3));
+    drop(engine);
+
+    // Reopening the book resumes where it was saved
+    let mut engine = engine_for(&book);
+    let resume = StdDuration::from_millis(state.position.as_millis());
+    engine.seek(resume).unwrap();
+    assert!(wait_for(&engine, |e| Duration::from(e.position()) == sought));
+
+    // Statistics and bookmarks
+    let ended = Timestamp::now();
+    let started = Timestamp::from_millis(ended.as_millis() - 60_000);
+    stats::record_listening_session(library.pool(), book.id, started, ended, 1.5)
+        .await
+        .unwrap();
+    bookmarks::create_bookmark(library.pool(), &Bookmark::new(book.id, sought))
+        .await
+        .unwrap();
+
+    let book_stats = stats::get_book_stats(library.pool(), book.id)
+        .await
+        .unwrap();
+    assert_eq!(book_stats.sessions, 1);
+    assert_eq!(book_stats.listened, Duration::from_seconds(60));
+    assert_eq!(book_stats.average_speed, 1.5);
+    assert!(!book_stats.finished);
+    let saved = bookmarks::get_book_bookmarks(library.pool(), book.id)
+        .await
+        .unwrap();
+    assert_eq!(saved.len(), 1);
+    assert_eq!(saved[0].position, sought);
+
+    // Reaching the end marks the book finished
+    engine.seek(StdDuration::from_secs(20)).unwrap();
+    assert!(wait_for(&engine, |e| e.position() >= StdDuration::from_secs(20)));
+    flush_position(&library, &book, &engine).await;
+    let book_stats = stats::get_book_stats(library.pool(), book.id)
+        .await
+        .unwrap();
+    assert!(book_stats.finished);
+
+    // The other book was never touched
+    let other = books::list_books(library.pool())
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|b| b.title == "Second Book")
+        .unwrap();
+    let other_stats = stats::get_book_stats(library.pool(), other.id)
+        .await
+        .unwrap();
+    assert_eq!(other_stats.sessions, 0);
+    assert!(bookmarks::get_book_bookmarks(library.pool(), other.id)
+        .await
+        .unwrap()
+        .is_empty());
+}
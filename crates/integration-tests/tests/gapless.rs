@@ -0,0 +1,88 @@
+// FILE: crates/integration-tests/tests/gapless.rs
+//! A book split over several files plays through them as one
+
+use media_engine::{EngineConfig, MediaEngine, MediaEvent, OutputTarget};
+use std::time::{Duration, Instant};
+use storystream_integration_tests::audio::{sine_wave, write_wav, SAMPLE_RATE};
+use tempfile::TempDir;
+
+#[test]
+fn test_queue_plays_through_its_files() {
+    let dir = TempDir::new().unwrap();
+    let parts: Vec<String> = (1..=3)
+        .map(|part| {
+            let path = dir.path().join(format!("Part {}.wav", part));
+            let samples = sine_wave(440.0, 1.0, SAMPLE_RATE);
+            write_wav(&path, &samples, SAMPLE_RATE).unwrap();
+            path.display().to_string()
+        })
+        .collect();
+
+    let mut engine = MediaEngine::new(EngineConfig::default())
+        .unwrap()
+        .with_output(OutputTarget::Null);
+    engine.load_book(&parts[0], None).unwrap();
+    for part in &parts[1..] {
+        engine.enqueue(part).unwrap();
+    }
+    engine.set_aggregate_position(true);
+
+    assert_eq!(engine.queue().len(), 3);
+    assert_eq!(engine.queue_duration(), Duration::from_secs(3));
+    assert_eq!(engine.duration, Some(Duration::from_secs(3)));
+
+    // Play to the end, noting each file the queue moves on to
+    engine.play().unwrap();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let mut changes = Vec::new();
+    let mut ended = false;
+    while !ended && Instant::now() < deadline {
+        if let Some(MediaEvent::TrackChanged { index }) = engine.take_track_changed() {
+            changes.push(index);
+        }
+        ended = engine.take_playback_ended().is_some();
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    assert!(
+        ended,
+        "playback did not end; stopped at {:?}",
+        engine.position()
+    );
+    // Short files may pass between two polls; the last one is always seen
+    assert_eq!(changes.last(), Some(&2));
+    assert!(changes.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(engine.current_track(), 2);
+    assert!(engine.position() >= Duration::from_millis(2900));
+}
+
+#[test]
+fn test_seek_across_files() {
+    let dir = TempDir::new().unwrap();
+    let parts: Vec<String> = [2.0, 3.0]
+        .iter()
+        .enumerate()
+        .map(|(index, secs)| {
+            let path = dir.path().join(format!("{:02}.wav", index + 1));
+            let samples = sine_wave(220.0, *secs, SAMPLE_RATE);
+            write_wav(&path, &samples, SAMPLE_RATE).unwrap();
+            path.display().to_string()
+        })
+        .collect();
+
+    let mut engine = MediaEngine::new(EngineConfig::default())
+        .unwrap()
+        .with_output(OutputTarget::Null);
+    engine.load_book(&parts[0], None).unwrap();
+    engine.enqueue(&parts[1]).unwrap();
+    engine.set_aggregate_position(true);
+
+    engine.seek(Duration::from_millis(3500)).unwrap();
+    assert_eq!(engine.current_track(), 1);
+    assert_eq!(engine.track_position(), Duration::from_millis(1500));
+    assert_eq!(engine.position(), Duration::from_millis(3500));
+
+    engine.skip_to(0).unwrap();
+    assert_eq!(engine.position(), Duration::ZERO);
+    assert!(engine.skip_to(2).is_err());
+}
@@ -0,0 +1,58 @@
+// FILE: crates/integration-tests/tests/output_device.rs
+//! Moving playback to another output device while it runs
+
+use media_engine::{EngineConfig, MediaEngine, OutputTarget};
+use std::time::{Duration, Instant};
+use storystream_integration_tests::audio::{sine_wave, write_wav, SAMPLE_RATE};
+use tempfile::TempDir;
+
+/// Waits up to five seconds for `done` to hold
+fn wait_for(engine: &MediaEngine, done: impl Fn(&MediaEngine) -> bool) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while Instant::now() < deadline {
+        if done(engine) {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    false
+}
+
+#[test]
+fn test_switching_device_keeps_position_and_state() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("book.wav");
+    write_wav(&path, &sine_wave(440.0, 20.0, SAMPLE_RATE), SAMPLE_RATE).unwrap();
+
+    let mut engine = MediaEngine::new(EngineConfig::default())
+        .unwrap()
+        .with_output(OutputTarget::Null);
+    let devices = engine.list_output_devices().unwrap();
+    assert_eq!(devices.len(), 1);
+    assert!(engine.set_output_device(Some("no-such-device")).is_err());
+
+    engine.load(path.to_str().unwrap()).unwrap();
+    engine.play().unwrap();
+    assert!(wait_for(&engine, |e| e.position() >= Duration::from_millis(500)));
+
+    let before = engine.position();
+    engine.set_output_device(Some(&devices[0].id)).unwrap();
+    assert_eq!(
+        engine.selected_output_device().map(|d| d.id),
+        Some(devices[0].id.clone())
+    );
+    assert!(wait_for(&engine, |e| e.position() > before));
+    assert!(engine.is_playing());
+    assert_eq!(
+        engine.output_device().map(|d| d.id),
+        Some(devices[0].id.clone())
+    );
+
+    // Paused playback stays paused on the default device
+    engine.pause().unwrap();
+    engine.set_output_device(None).unwrap();
+    std::thread::sleep(Duration::from_millis(200));
+    assert!(!engine.is_playing());
+    assert!(engine.selected_output_device().is_none());
+    assert!(engine.take_device_error().is_none());
+}
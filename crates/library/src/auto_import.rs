@@ -0,0 +1,477 @@
+//! Importing changes to the watched folders as they happen
+//!
+//! The scanner reports every file system event, and copying one audiobook
+//! in produces dozens of them. [`AutoImporter`] waits for a file to go
+//! quiet, then for its size to hold still, before reading it once. Files
+//! that disappear leave their books in the library, marked unavailable.
+
+use crate::import::{BookImporter, ImportOptions};
+use crate::scanner::{ScanCancel, ScanEvent};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use storystream_database::{queries::books, DbPool};
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+/// How long a file must go without events before it is looked at
+const DEFAULT_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// How long a file's size must hold still before it is read
+const DEFAULT_SETTLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Longest the importer goes without checking for cancellation
+const CANCEL_POLL: Duration = Duration::from_millis(250);
+
+/// What the importer did with a file, for showing as a status message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportEvent {
+    /// A new book was added
+    Imported { path: PathBuf, title: String },
+    /// A book's file changed and its metadata was read again
+    Updated { path: PathBuf, title: String },
+    /// A book's file was removed
+    Unavailable { path: PathBuf, title: String },
+    /// The file could not be imported; the importer carries on
+    Failed { path: PathBuf, error: String },
+}
+
+impl fmt::Display for ImportEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Imported { title, .. } => write!(f, "Imported {}", title),
+            Self::Updated { title, .. } => write!(f, "Updated {}", title),
+            Self::Unavailable { title, .. } => write!(f, "{} is no longer available", title),
+            Self::Failed { path, error } => {
+                let name = path.file_name().unwrap_or(path.as_os_str());
+                write!(f, "Could not import {}: {}", name.to_string_lossy(), error)
+            }
+        }
+    }
+}
+
+/// What an [`AutoImporter`] did before it stopped
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AutoImportReport {
+    pub imported: usize,
+    pub updated: usize,
+    pub unavailable: usize,
+    pub failed: usize,
+}
+
+impl AutoImportReport {
+    fn count(&mut self, event: &ImportEvent) {
+        match event {
+            ImportEvent::Imported { .. } => self.imported += 1,
+            ImportEvent::Updated { .. } => self.updated += 1,
+            ImportEvent::Unavailable { .. } => self.unavailable += 1,
+            ImportEvent::Failed { .. } => self.failed += 1,
+        }
+    }
+}
+
+/// A file with changes not acted on yet
+struct Pending {
+    /// When to look at the file next
+    due: Instant,
+    /// Size at the last look, `None` before the first
+    size: Option<u64>,
+}
+
+/// Keeps the library in step with the scanner's events
+///
+/// Start it with [`run`](Self::run) on the receiver from
+/// [`LibraryScanner::start`](crate::LibraryScanner::start). It stops when
+/// the scanner stops or the importer is cancelled, finishing the file in
+/// hand; changes still settling are left for the next scan.
+pub struct AutoImporter {
+    pool: DbPool,
+    importer: BookImporter,
+    options: ImportOptions,
+    debounce: Duration,
+    settle_interval: Duration,
+    cancel: ScanCancel,
+    events: Option<mpsc::Sender<ImportEvent>>,
+}
+
+impl AutoImporter {
+    pub fn new(pool: DbPool) -> Self {
+        Self {
+            importer: BookImporter::new(pool.clone()),
+            pool,
+            options: ImportOptions::default(),
+            debounce: DEFAULT_DEBOUNCE,
+            settle_interval: DEFAULT_SETTLE_INTERVAL,
+            cancel: ScanCancel::new(),
+            events: None,
+        }
+    }
+
+    /// Imports new files with `options`, such as tags to add
+    pub fn with_options(mut self, options: ImportOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Waits until a file has had no events for `quiet` before looking at it
+    pub fn with_debounce(mut self, quiet: Duration) -> Self {
+        self.debounce = quiet;
+        self
+    }
+
+    /// Reads a file once its size is the same across `interval`
+    pub fn with_settle_interval(mut self, interval: Duration) -> Self {
+        self.settle_interval = interval;
+        self
+    }
+
+    /// Uses `cancel` to stop the importer instead of its own token
+    pub fn with_cancel(mut self, cancel: ScanCancel) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Sends what happens to each file to `events`
+    pub fn with_events(mut self, events: mpsc::Sender<ImportEvent>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Returns a token that stops this importer
+    pub fn cancel_handle(&self) -> ScanCancel {
+        self.cancel.clone()
+    }
+
+    /// Acts on `changes` until the scanner stops or the importer is cancelled
+    ///
+    /// A file that fails to import is reported and passed over.
+    pub async fn run(self, mut changes: mpsc::Receiver<ScanEvent>) -> AutoImportReport {
+        let mut pending: HashMap<PathBuf, Pending> = HashMap::new();
+        let mut report = AutoImportReport::default();
+        info!("Importing changes to the watched folders");
+
+        while !self.cancel.is_cancelled() {
+            let now = Instant::now();
+            let wait = pending
+                .values()
+                .map(|file| file.due.saturating_duration_since(now))
+                .min()
+                .map_or(CANCEL_POLL, |wait| wait.min(CANCEL_POLL));
+            tokio::select! {
+                change = changes.recv() => match change {
+                    Some(change) => self.note(change, &mut pending),
+                    None => break,
+                },
+                _ = tokio::time::sleep(wait) => {}
+            }
+            self.settle_due(&mut pending, &mut report).await;
+        }
+
+        if !pending.is_empty() {
+            info!(
+                "Stopped importing with {} change(s) still settling",
+                pending.len()
+            );
+        }
+        report
+    }
+
+    /// Puts off looking at the file the event is about
+    ///
+    /// Every event restarts the wait, so a burst is acted on once.
+    fn note(&self, change: ScanEvent, pending: &mut HashMap<PathBuf, Pending>) {
+        let path = match change {
+            ScanEvent::FileAdded(path)
+            | ScanEvent::FileModified(path)
+            | ScanEvent::FileRemoved(path) => path,
+            ScanEvent::ScanError(error) => {
+                warn!("Watcher error: {}", error);
+                return;
+            }
+            ScanEvent::ScanCompleted { .. } => return,
+        };
+        pending.insert(
+            path,
+            Pending {
+                due: Instant::now() + self.debounce,
+                size: None,
+            },
+        );
+    }
+
+    /// Acts on the files whose wait is over and whose size held still
+    async fn settle_due(
+        &self,
+        pending: &mut HashMap<PathBuf, Pending>,
+        report: &mut AutoImportReport,
+    ) {
+        let now = Instant::now();
+        let due: Vec<PathBuf> = pending
+            .iter()
+            .filter(|(_, file)| file.due <= now)
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        for path in due {
+            if self.cancel.is_cancelled() {
+                return;
+            }
+            let size = std::fs::metadata(&path)
+                .ok()
+                .filter(|metadata| metadata.is_file())
+                .map(|metadata| metadata.len());
+            if let (Some(size), Some(file)) = (size, pending.get_mut(&path)) {
+                if file.size != Some(size) {
+                    // Still being copied, or not measured yet
+                    file.size = Some(size);
+                    file.due = now + self.settle_interval;
+                    continue;
+                }
+            }
+            pending.remove(&path);
+
+            let event = match size {
+                Some(_) => self.import(&path).await,
+                None => self.remove(&path).await,
+            };
+            if let Some(event) = event {
+                report.count(&event);
+                // Status messages are passing, so a front end that falls
+                // behind misses some rather than holding up the imports
+                if let Some(tx) = &self.events {
+                    let _ = tx.try_send(event);
+                }
+            }
+        }
+    }
+
+    /// Imports a new file, or reads a known one again
+    async fn import(&self, path: &Path) -> Option<ImportEvent> {
+        let path = library_path(path);
+        let failed = |error: String| ImportEvent::Failed {
+            path: path.clone(),
+            error,
+        };
+
+        let known = match books::get_file_paths(&self.pool).await {
+            Ok(known) => known,
+            Err(e) => return Some(failed(e.to_string())),
+        };
+        if path
+            .ancestors()
+            .skip(1)
+            .any(|folder| known.contains(folder))
+        {
+            debug!("{} belongs to a folder book", path.display());
+            return None;
+        }
+        let existing = match books::find_book_by_path(&self.pool, &path).await {
+            Ok(existing) => existing,
+            Err(e) => return Some(failed(e.to_string())),
+        };
+
+        let options = self
+            .options
+            .clone()
+            .with_overwrite_existing(existing.is_some());
+        let book = match self.importer.import_file(&path, options).await {
+            Ok(book) => book,
+            Err(e) => {
+                warn!("Could not import {}: {}", path.display(), e);
+                return Some(failed(e.to_string()));
+            }
+        };
+        if existing.is_none() {
+            return Some(ImportEvent::Imported {
+                path,
+                title: book.title,
+            });
+        }
+
+        // A file that went away and came back plays again
+        if let Err(e) = books::set_book_available(&self.pool, book.id, true).await {
+            warn!("Could not mark {} available: {}", book.title, e);
+        }
+        Some(ImportEvent::Updated {
+            path,
+            title: book.title,
+        })
+    }
+
+    /// Marks the book of a removed file unavailable
+    async fn remove(&self, path: &Path) -> Option<ImportEvent> {
+        let path = library_path(path);
+        let book = match books::find_book_by_path(&self.pool, &path).await {
+            Ok(Some(book)) => book,
+            // Never imported, or not an audiobook after all
+            Ok(None) => return None,
+            Err(e) => {
+                return Some(ImportEvent::Failed {
+                    path,
+                    error: e.to_string(),
+                })
+            }
+        };
+
+        match books::set_book_available(&self.pool, book.id, false).await {
+            Ok(()) => {
+                info!("{} was removed from {}", book.title, path.display());
+                Some(ImportEvent::Unavailable {
+                    path,
+                    title: book.title,
+                })
+            }
+            Err(e) => Some(ImportEvent::Failed {
+                path,
+                error: e.to_string(),
+            }),
+        }
+    }
+}
+
+/// The path a book for `path` is stored under
+///
+/// Books keep canonical paths, and a removed file can no longer be
+/// canonicalized, so its folder is instead.
+fn library_path(path: &Path) -> PathBuf {
+    if let Ok(canonical) = path.canonicalize() {
+        return canonical;
+    }
+    match (path.parent().map(Path::canonicalize), path.file_name()) {
+        (Some(Ok(folder)), Some(name)) => folder.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use storystream_database::{
+        connection::{connect, DatabaseConfig},
+        migrations::run_migrations,
+    };
+    use storystream_media_formats::wav::wav_bytes;
+    use tempfile::{NamedTempFile, TempDir};
+
+    async fn setup_test_db() -> (DbPool, NamedTempFile) {
+        let temp_file = NamedTempFile::new().unwrap();
+        let config = DatabaseConfig::new(temp_file.path().to_str().unwrap());
+        let pool = connect(config).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        (pool, temp_file)
+    }
+
+    fn fast_importer(pool: DbPool, events: mpsc::Sender<ImportEvent>) -> AutoImporter {
+        AutoImporter::new(pool)
+            .with_debounce(Duration::from_millis(50))
+            .with_settle_interval(Duration::from_millis(50))
+            .with_events(events)
+    }
+
+    #[tokio::test]
+    async fn test_changes_are_imported_updated_and_removed() {
+        let (pool, _db) = setup_test_db().await;
+        let dir = TempDir::new().unwrap();
+        let book = dir.path().canonicalize().unwrap().join("book.wav");
+        let broken = dir.path().join("broken.wav");
+
+        let (changes_tx, changes) = mpsc::channel(16);
+        let (events_tx, mut events) = mpsc::channel(16);
+        let task = tokio::spawn(fast_importer(pool.clone(), events_tx).run(changes));
+
+        // A copy shows up as a burst of events
+        std::fs::write(&book, wav_bytes(8000, 1)).unwrap();
+        changes_tx
+            .send(ScanEvent::FileAdded(book.clone()))
+            .await
+            .unwrap();
+        for _ in 0..5 {
+            changes_tx
+                .send(ScanEvent::FileModified(book.clone()))
+                .await
+                .unwrap();
+        }
+        std::fs::write(&broken, b"not audio").unwrap();
+        changes_tx.send(ScanEvent::FileAdded(broken)).await.unwrap();
+
+        let mut first = [events.recv().await.unwrap(), events.recv().await.unwrap()];
+        first.sort_by_key(|event| matches!(event, ImportEvent::Failed { .. }));
+        assert!(matches!(&first[0], ImportEvent::Imported { path, .. } if *path == book));
+        assert!(matches!(&first[1], ImportEvent::Failed { .. }));
+
+        std::fs::write(&book, wav_bytes(16000, 1)).unwrap();
+        changes_tx
+            .send(ScanEvent::FileModified(book.clone()))
+            .await
+            .unwrap();
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            ImportEvent::Updated { .. }
+        ));
+        let stored = books::find_book_by_path(&pool, &book)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.duration.as_seconds(), 2);
+
+        std::fs::remove_file(&book).unwrap();
+        changes_tx
+            .send(ScanEvent::FileRemoved(book.clone()))
+            .await
+            .unwrap();
+        let removed = events.recv().await.unwrap();
+        assert!(matches!(removed, ImportEvent::Unavailable { .. }));
+        assert!(removed.to_string().ends_with("is no longer available"));
+        assert!(books::get_unavailable_books(&pool)
+            .await
+            .unwrap()
+            .contains(&stored.id));
+
+        // Stopping the scanner stops the importer
+        drop(changes_tx);
+        let report = task.await.unwrap();
+        assert_eq!(
+            report,
+            AutoImportReport {
+                imported: 1,
+                updated: 1,
+                unavailable: 1,
+                failed: 1,
+            }
+        );
+        assert_eq!(books::list_books(&pool).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_growing_file_waits_until_copied() {
+        let (pool, _db) = setup_test_db().await;
+        let dir = TempDir::new().unwrap();
+        let book = dir.path().join("book.wav");
+        let (changes_tx, changes) = mpsc::channel(16);
+        let (events_tx, mut events) = mpsc::channel(16);
+        let importer =
+            fast_importer(pool.clone(), events_tx).with_settle_interval(Duration::from_millis(300));
+        let cancel = importer.cancel_handle();
+        let task = tokio::spawn(importer.run(changes));
+
+        // The copy carries on without the watcher reporting it
+        let wav = wav_bytes(8000, 1);
+        std::fs::write(&book, &wav[..1000]).unwrap();
+        changes_tx
+            .send(ScanEvent::FileAdded(book.clone()))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        std::fs::write(&book, &wav).unwrap();
+
+        let event = events.recv().await.unwrap();
+        assert!(matches!(event, ImportEvent::Imported { .. }), "{:?}", event);
+        let stored = books::list_books(&pool).await.unwrap();
+        assert_eq!(stored[0].file_size, wav.len() as u64);
+
+        cancel.cancel();
+        assert_eq!(task.await.unwrap().imported, 1);
+        drop(changes_tx);
+    }
+}
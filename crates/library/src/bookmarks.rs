@@ -0,0 +1,505 @@
+//! Bookmarks kept in step between the player and the database
+//!
+//! While a book plays, the engine's [`BookmarkManager`] answers which
+//! bookmark is nearest and which auto-bookmark goes once there are too many;
+//! the `bookmarks` table is what lasts. A [`BookmarkStore`] holds the loaded
+//! book's bookmarks in both. Engine bookmarks carry the database
+//! [`BookmarkId`] as their string id, so either side can name a bookmark to
+//! the other.
+//!
+//! Bookmarks also leave as files for notes apps, one book at a time with
+//! [`export_book`] or the whole library with [`export_all_bookmarks`].
+
+use crate::error::{LibraryError, Result};
+use crate::organize::sanitize_segment;
+use media_engine::{
+    bookmarks::CSV_HEADER, Bookmark as EngineBookmark, BookmarkManager, BookmarkType, ChapterList,
+    ChapterMarker,
+};
+use std::path::Path;
+use std::time::{Duration, UNIX_EPOCH};
+use storystream_core::{BookId, Bookmark, BookmarkId, Chapter};
+use storystream_database::{
+    queries::{bookmarks, books, chapters},
+    DbPool,
+};
+use tracing::warn;
+
+/// File format bookmarks are exported in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BookmarkFormat {
+    /// Every field of each bookmark, for reading back in
+    Json,
+    /// A section per book, for notes apps
+    #[default]
+    Markdown,
+    /// A row per bookmark, for spreadsheets
+    Csv,
+}
+
+impl BookmarkFormat {
+    /// Extension of files in this format
+    pub fn extension(self) -> &'static str {
+        match self {
+            BookmarkFormat::Json => "json",
+            BookmarkFormat::Markdown => "md",
+            BookmarkFormat::Csv => "csv",
+        }
+    }
+}
+
+/// Engine copy of a stored bookmark, sharing its id and creation time
+pub fn engine_bookmark(bookmark: &Bookmark) -> EngineBookmark {
+    let kind = if bookmark.is_auto() {
+        BookmarkType::Auto
+    } else {
+        BookmarkType::User
+    };
+    let mut engine =
+        EngineBookmark::new(Duration::from_millis(bookmark.position.as_millis()), kind);
+    engine.id = bookmark.id.as_string();
+    engine.title = bookmark.title.clone();
+    engine.note = bookmark.note.clone();
+    engine.created_at =
+        UNIX_EPOCH + Duration::from_millis(bookmark.created_at.as_millis().max(0) as u64);
+    engine
+}
+
+/// Database id of an engine bookmark; `None` for one the engine made up
+pub fn stored_id(bookmark: &EngineBookmark) -> Option<BookmarkId> {
+    BookmarkId::from_string(&bookmark.id).ok()
+}
+
+/// Engine copy of a book's chapters, for naming the chapter of a bookmark
+pub fn chapter_list(chapters: &[Chapter]) -> ChapterList {
+    ChapterList::with_chapters(
+        chapters
+            .iter()
+            .enumerate()
+            .map(|(index, chapter)| {
+                ChapterMarker::new(
+                    index,
+                    chapter.title.clone(),
+                    chapter.start_time.as_millis() as f64 / 1000.0,
+                    chapter.end_time.as_millis() as f64 / 1000.0,
+                )
+            })
+            .collect(),
+    )
+}
+
+/// Name of the file a book's bookmarks are exported to
+pub fn export_file_name(book_title: &str, format: BookmarkFormat) -> String {
+    let title = sanitize_segment(book_title);
+    if title.is_empty() {
+        format!("Bookmarks.{}", format.extension())
+    } else {
+        format!("{} - Bookmarks.{}", title, format.extension())
+    }
+}
+
+/// One book's bookmarks as a file in `format`
+pub fn export_book(
+    format: BookmarkFormat,
+    book_title: &str,
+    engine: &BookmarkManager,
+    chapters: Option<&ChapterList>,
+) -> Result<String> {
+    Ok(match format {
+        BookmarkFormat::Json => engine.export_json().map_err(LibraryError::Other)?,
+        BookmarkFormat::Markdown => engine.export_markdown(book_title, chapters),
+        BookmarkFormat::Csv => engine.export_csv(book_title, chapters),
+    })
+}
+
+/// Writes the bookmarks of every book in the library to `dest`, grouped
+/// by book in title order
+///
+/// Books without bookmarks are left out. Returns how many bookmarks were
+/// written.
+pub async fn export_all_bookmarks(
+    pool: &DbPool,
+    format: BookmarkFormat,
+    dest: &Path,
+) -> Result<usize> {
+    let mut library = books::list_books(pool).await?;
+    library.sort_by_key(|book| book.title.to_lowercase());
+
+    let mut count = 0;
+    let mut markdown = String::from("# Bookmarks\n");
+    let mut csv = format!("{}\n", CSV_HEADER);
+    let mut json = Vec::new();
+    for book in library {
+        let stored = bookmarks::get_book_bookmarks(pool, book.id).await?;
+        if stored.is_empty() {
+            continue;
+        }
+        count += stored.len();
+        let mut engine = BookmarkManager::new();
+        for bookmark in &stored {
+            let _ = engine.add_bookmark(engine_bookmark(bookmark));
+        }
+        let chapters = chapter_list(&chapters::get_book_chapters(pool, book.id).await?);
+
+        match format {
+            BookmarkFormat::Json => json.push(serde_json::json!({
+                "book": book.title,
+                "bookmarks": engine.get_all_bookmarks(),
+            })),
+            BookmarkFormat::Markdown => {
+                markdown.push('\n');
+                markdown.push_str(&engine.export_markdown(&book.title, Some(&chapters)));
+            }
+            BookmarkFormat::Csv => {
+                csv.push_str(&engine.export_csv_rows(&book.title, Some(&chapters)))
+            }
+        }
+    }
+
+    let contents = match format {
+        BookmarkFormat::Json => serde_json::to_string_pretty(&json)
+            .map_err(|e| LibraryError::Other(format!("Failed to export bookmarks: {}", e)))?,
+        BookmarkFormat::Markdown => markdown,
+        BookmarkFormat::Csv => csv,
+    };
+    if let Some(parent) = dest
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(dest, contents).await?;
+    Ok(count)
+}
+
+/// The loaded book's bookmarks, in the database and in the engine
+pub struct BookmarkStore {
+    pool: DbPool,
+    book_id: Option<BookId>,
+    /// Stored bookmarks, by position
+    bookmarks: Vec<Bookmark>,
+    engine: BookmarkManager,
+    max_auto_bookmarks: usize,
+    /// Length of the loaded book, past which nothing is bookmarked
+    duration: Option<Duration>,
+}
+
+impl BookmarkStore {
+    /// A store with no book loaded, keeping up to `max_auto_bookmarks`
+    /// auto-bookmarks per book
+    pub fn new(pool: DbPool, max_auto_bookmarks: usize) -> Self {
+        Self {
+            pool,
+            book_id: None,
+            bookmarks: Vec::new(),
+            engine: BookmarkManager::new(),
+            max_auto_bookmarks,
+            duration: None,
+        }
+    }
+
+    /// The loaded book
+    pub fn book_id(&self) -> Option<BookId> {
+        self.book_id
+    }
+
+    /// Stored bookmarks of the loaded book, by position
+    pub fn bookmarks(&self) -> &[Bookmark] {
+        &self.bookmarks
+    }
+
+    /// The engine's copy, for snapping seeks and exports
+    pub fn engine(&self) -> &BookmarkManager {
+        &self.engine
+    }
+
+    /// Changes how many auto-bookmarks are kept, from the next load on
+    pub fn set_max_auto_bookmarks(&mut self, max: usize) {
+        self.max_auto_bookmarks = max;
+    }
+
+    /// Loads a book's bookmarks into the engine
+    ///
+    /// Auto-bookmarks go in oldest first, so any past the limit (after it
+    /// was lowered, or from another device) are deleted from the database
+    /// too.
+    pub async fn load(&mut self, book_id: BookId, duration: Option<Duration>) -> Result<()> {
+        self.book_id = Some(book_id);
+        self.duration = duration.filter(|d| !d.is_zero());
+        self.engine = BookmarkManager::new();
+        // The player decides when to bookmark; only the engine's limit is used
+        self.engine
+            .configure_auto_bookmarks(true, 0, self.max_auto_bookmarks);
+        if let Some(duration) = self.duration {
+            self.engine.set_duration(duration);
+        }
+
+        let stored = bookmarks::get_book_bookmarks(&self.pool, book_id).await?;
+        let mut autos: Vec<&Bookmark> = stored.iter().filter(|b| b.is_auto()).collect();
+        autos.sort_by_key(|b| b.created_at);
+        let mut evicted = Vec::new();
+        for bookmark in autos {
+            if let Ok(removed) = self.engine.add_auto_bookmark(engine_bookmark(bookmark)) {
+                evicted.extend(removed);
+            }
+        }
+        for bookmark in stored.iter().filter(|b| !b.is_auto()) {
+            // Positions past a shortened file stay listed, but are not snapped to
+            let _ = self.engine.add_bookmark(engine_bookmark(bookmark));
+        }
+        self.bookmarks = stored;
+
+        if !evicted.is_empty() {
+            self.delete_evicted(evicted).await;
+        }
+        Ok(())
+    }
+
+    /// Forgets the loaded book
+    pub fn unload(&mut self) {
+        self.book_id = None;
+        self.bookmarks.clear();
+        self.engine.clear();
+    }
+
+    /// Reads the loaded book's bookmarks again, picking up those added or
+    /// deleted elsewhere
+    pub async fn refresh(&mut self) -> Result<()> {
+        match self.book_id {
+            Some(book_id) => self.load(book_id, self.duration).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Saves a bookmark of the loaded book
+    ///
+    /// Adding an auto-bookmark past the limit deletes the oldest one. A
+    /// bookmark past the end of the book is refused.
+    pub async fn add(&mut self, bookmark: Bookmark) -> Result<()> {
+        let copy = engine_bookmark(&bookmark);
+        let evicted = if bookmark.is_auto() {
+            self.engine.add_auto_bookmark(copy)
+        } else {
+            self.engine.add_bookmark(copy).map(|_| Vec::new())
+        }
+        .map_err(LibraryError::Other)?;
+
+        if let Err(e) = bookmarks::create_bookmark(&self.pool, &bookmark).await {
+            let _ = self.engine.remove_bookmark(&bookmark.id.as_string());
+            return Err(e.into());
+        }
+        self.delete_evicted(evicted).await;
+        self.reload_list().await
+    }
+
+    /// Deletes a bookmark from the database and the engine
+    pub async fn remove(&mut self, id: BookmarkId) -> Result<()> {
+        bookmarks::delete_bookmark(&self.pool, id).await?;
+        let _ = self.engine.remove_bookmark(&id.as_string());
+        self.reload_list().await
+    }
+
+    /// Deletes every auto-bookmark of the loaded book, returning how many
+    pub async fn clear_auto(&mut self) -> Result<u64> {
+        let Some(book_id) = self.book_id else {
+            return Ok(0);
+        };
+        let count = bookmarks::delete_auto_bookmarks(&self.pool, book_id).await?;
+        self.engine.clear_by_type(BookmarkType::Auto);
+        self.reload_list().await?;
+        Ok(count)
+    }
+
+    /// Deletes auto-bookmarks the engine dropped to stay within the limit
+    async fn delete_evicted(&mut self, evicted: Vec<EngineBookmark>) {
+        for id in evicted.iter().filter_map(stored_id) {
+            match bookmarks::delete_bookmark(&self.pool, id).await {
+                Ok(()) => self.bookmarks.retain(|b| b.id != id),
+                Err(e) => warn!("Could not delete old auto-bookmark {}: {}", id, e),
+            }
+        }
+    }
+
+    async fn reload_list(&mut self) -> Result<()> {
+        if let Some(book_id) = self.book_id {
+            self.bookmarks = bookmarks::get_book_bookmarks(&self.pool, book_id).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use storystream_core::{AutoBookmarkTrigger, Book, Duration as BookDuration, Timestamp};
+    use storystream_database::{
+        connection::{connect, DatabaseConfig},
+        migrations::run_migrations,
+    };
+    use tempfile::{NamedTempFile, TempDir};
+
+    async fn setup() -> (DbPool, NamedTempFile, BookId) {
+        let temp_file = NamedTempFile::new().unwrap();
+        let config = DatabaseConfig::new(temp_file.path().to_str().unwrap());
+        let pool = connect(config).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        let book = Book::new(
+            "Book".to_string(),
+            "/books/book.mp3".into(),
+            1_000,
+            BookDuration::from_seconds(3600),
+        );
+        books::create_book(&pool, &book).await.unwrap();
+        (pool, temp_file, book.id)
+    }
+
+    fn at(secs: u64) -> BookDuration {
+        BookDuration::from_seconds(secs)
+    }
+
+    #[tokio::test]
+    async fn test_bookmarks_reach_both_sides() {
+        let (pool, _db, book_id) = setup().await;
+        let mut store = BookmarkStore::new(pool.clone(), 10);
+        store
+            .load(book_id, Some(Duration::from_secs(3600)))
+            .await
+            .unwrap();
+
+        let bookmark = Bookmark::new(book_id, at(60));
+        store.add(bookmark.clone()).await.unwrap();
+        let stored = bookmarks::get_book_bookmarks(&pool, book_id).await.unwrap();
+        assert_eq!(stored.len(), 1);
+        let copy = store
+            .engine()
+            .get_bookmark(&bookmark.id.as_string())
+            .unwrap();
+        assert_eq!(stored_id(copy), Some(bookmark.id));
+        assert_eq!(copy.position, Duration::from_secs(60));
+
+        // Past the end of the book nothing is saved
+        assert!(store.add(Bookmark::new(book_id, at(7200))).await.is_err());
+        assert_eq!(store.bookmarks().len(), 1);
+
+        store.remove(bookmark.id).await.unwrap();
+        assert!(store.bookmarks().is_empty());
+        assert!(store.engine().is_empty());
+        assert!(bookmarks::get_book_bookmarks(&pool, book_id)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_load_fills_engine_and_trims_auto_bookmarks() {
+        let (pool, _db, book_id) = setup().await;
+        let user = Bookmark::new(book_id, at(30));
+        bookmarks::create_bookmark(&pool, &user).await.unwrap();
+        let mut autos = Vec::new();
+        for minute in 1..=3 {
+            let mut auto = Bookmark::auto(book_id, at(minute * 60), AutoBookmarkTrigger::Paused);
+            auto.created_at = Timestamp::from_millis(minute as i64 * 1000);
+            bookmarks::create_bookmark(&pool, &auto).await.unwrap();
+            autos.push(auto);
+        }
+
+        let mut store = BookmarkStore::new(pool.clone(), 2);
+        store.load(book_id, None).await.unwrap();
+        assert_eq!(store.engine().count(), 3);
+        assert!(store.engine().get_bookmark(&user.id.as_string()).is_some());
+
+        // The oldest auto-bookmark went from both sides
+        let stored: Vec<BookmarkId> = bookmarks::get_book_bookmarks(&pool, book_id)
+            .await
+            .unwrap()
+            .iter()
+            .map(|b| b.id)
+            .collect();
+        assert_eq!(stored, vec![user.id, autos[1].id, autos[2].id]);
+        let listed: Vec<BookmarkId> = store.bookmarks().iter().map(|b| b.id).collect();
+        assert_eq!(listed, stored);
+
+        // Adding one more drops the next oldest
+        let newest = Bookmark::auto(book_id, at(600), AutoBookmarkTrigger::Exit);
+        store.add(newest.clone()).await.unwrap();
+        let listed: Vec<BookmarkId> = store.bookmarks().iter().map(|b| b.id).collect();
+        assert_eq!(listed, vec![user.id, autos[2].id, newest.id]);
+        assert!(store
+            .engine()
+            .get_bookmark(&autos[1].id.as_string())
+            .is_none());
+
+        // Deleted elsewhere, gone from the engine after a refresh
+        bookmarks::delete_bookmark(&pool, user.id).await.unwrap();
+        store.refresh().await.unwrap();
+        assert!(store.engine().get_bookmark(&user.id.as_string()).is_none());
+
+        assert_eq!(store.clear_auto().await.unwrap(), 2);
+        assert!(store.bookmarks().is_empty());
+        assert!(store.engine().is_empty());
+    }
+
+    #[test]
+    fn test_export_file_name() {
+        assert_eq!(
+            export_file_name("Dune: Part One", BookmarkFormat::Markdown),
+            "Dune_ Part One - Bookmarks.md"
+        );
+        assert_eq!(export_file_name("..", BookmarkFormat::Csv), "Bookmarks.csv");
+    }
+
+    #[tokio::test]
+    async fn test_export_all_bookmarks_groups_by_book() {
+        let (pool, _db, book_id) = setup().await;
+        let chapter = Chapter::new(book_id, "Opening".to_string(), 0, at(0), at(3600));
+        chapters::create_chapter(&pool, &chapter).await.unwrap();
+        let mut quote = Bookmark::new(book_id, at(3725));
+        quote.title = Some("Quote".to_string());
+        quote.note = Some("Said, softly".to_string());
+        bookmarks::create_bookmark(&pool, &quote).await.unwrap();
+        bookmarks::create_bookmark(&pool, &Bookmark::new(book_id, at(90)))
+            .await
+            .unwrap();
+
+        let other = Book::new(
+            "Another".to_string(),
+            "/books/another.mp3".into(),
+            1_000,
+            at(600),
+        );
+        books::create_book(&pool, &other).await.unwrap();
+        bookmarks::create_bookmark(&pool, &Bookmark::new(other.id, at(5)))
+            .await
+            .unwrap();
+        let unmarked = Book::new("Unmarked".to_string(), "/books/u.mp3".into(), 1, at(60));
+        books::create_book(&pool, &unmarked).await.unwrap();
+
+        let dir = TempDir::new().unwrap();
+        let dest = dir.path().join("exports/bookmarks.md");
+        let count = export_all_bookmarks(&pool, BookmarkFormat::Markdown, &dest)
+            .await
+            .unwrap();
+        assert_eq!(count, 3);
+        let markdown = std::fs::read_to_string(&dest).unwrap();
+        assert!(markdown.starts_with("# Bookmarks\n\n## Another\n"));
+        assert!(markdown.contains("## Book\n\n- **0:01:30** · Opening\n- **1:02:05** · Quote\n"));
+        assert!(!markdown.contains("Unmarked"));
+
+        let dest = dir.path().join("bookmarks.csv");
+        export_all_bookmarks(&pool, BookmarkFormat::Csv, &dest)
+            .await
+            .unwrap();
+        let csv = std::fs::read_to_string(&dest).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                CSV_HEADER,
+                "Another,,0:00:05,,",
+                "Book,Opening,0:01:30,,",
+                "Book,,1:02:05,Quote,\"Said, softly\"",
+            ]
+        );
+    }
+}
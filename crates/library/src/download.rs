@@ -0,0 +1,446 @@
+//! Downloading books from online catalogues into the library
+//!
+//! Each book gets a folder of its own. Files are fetched as `<name>.part` and
+//! renamed once complete, so a run cut short by a crash continues the partial
+//! files where they stopped and skips the ones already finished. When every
+//! file is in, the folder is imported as one book.
+
+use crate::error::{LibraryError, Result};
+use crate::import::{BookImporter, ImportOptions};
+use crate::organize::sanitize_segment;
+use crate::scanner::ScanCancel;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use storystream_content_sources::ImportPlan;
+use storystream_database::DbPool;
+use storystream_network::{
+    AdvancedDownloadManager, DownloadStatus, DownloadTask, ProgressCallback,
+};
+use storystream_resilience::RetryPolicy;
+use tracing::{info, instrument, warn};
+
+/// How often a running download is checked on
+const DOWNLOAD_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Attempts per file, including the first
+const DEFAULT_ATTEMPTS: usize = 3;
+
+/// Extension added to files still being downloaded
+const PARTIAL_EXTENSION: &str = "part";
+
+/// Progress of a book's download
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookDownloadProgress {
+    /// Index of the file being downloaded
+    pub file: usize,
+    /// Files in the book
+    pub files: usize,
+    /// Title of the file being downloaded
+    pub title: String,
+    /// Bytes of that file on disk, including any resumed part
+    pub downloaded: u64,
+    /// Size of that file, when the server reports it
+    pub total: Option<u64>,
+}
+
+/// Downloads books through the download manager and imports them
+///
+/// The manager's queue has to be running for downloads to make progress.
+pub struct ContentDownloader {
+    pool: DbPool,
+    downloads: Arc<AdvancedDownloadManager>,
+    retry: RetryPolicy,
+    cancel: ScanCancel,
+    tags: Vec<String>,
+}
+
+impl ContentDownloader {
+    pub fn new(pool: DbPool, downloads: Arc<AdvancedDownloadManager>) -> Self {
+        Self {
+            pool,
+            downloads,
+            retry: RetryPolicy::new(DEFAULT_ATTEMPTS),
+            cancel: ScanCancel::new(),
+            tags: Vec::new(),
+        }
+    }
+
+    /// Retries failed files as `policy` allows, continuing what they got
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Uses `cancel` to stop downloads instead of the downloader's own token
+    pub fn with_cancel(mut self, cancel: ScanCancel) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Tags every downloaded book with `tag`
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Returns a token that cancels this downloader's runs
+    pub fn cancel_handle(&self) -> ScanCancel {
+        self.cancel.clone()
+    }
+
+    /// Returns the folder `plan` is downloaded into under `dest_dir`
+    pub fn book_folder(plan: &ImportPlan, dest_dir: &Path) -> PathBuf {
+        let name = sanitize_segment(&format!("{} - {}", plan.author, plan.title));
+        if name.is_empty() {
+            dest_dir.join("Untitled")
+        } else {
+            dest_dir.join(name)
+        }
+    }
+
+    /// Downloads the files of `plan` and imports them as one book
+    ///
+    /// Returns the downloaded files in playback order. Cancelling removes
+    /// the partial files; finished files stay for the next run.
+    #[instrument(skip_all, fields(title = %plan.title, files = plan.files.len()))]
+    pub async fn download_book(
+        &self,
+        plan: &ImportPlan,
+        dest_dir: &Path,
+        progress: impl Fn(&BookDownloadProgress) + Send + Sync + 'static,
+    ) -> Result<Vec<PathBuf>> {
+        if plan.files.is_empty() {
+            return Err(LibraryError::ImportFailed(format!(
+                "'{}' has no files to download",
+                plan.title
+            )));
+        }
+
+        let folder = Self::book_folder(plan, dest_dir);
+        tokio::fs::create_dir_all(&folder).await?;
+        let paths: Vec<PathBuf> = plan
+            .files
+            .iter()
+            .enumerate()
+            .map(|(index, file)| match sanitize_segment(&file.file_name) {
+                name if name.is_empty() => folder.join(format!("{:03}", index + 1)),
+                name => folder.join(name),
+            })
+            .collect();
+
+        let progress = Arc::new(progress);
+        for (index, (file, path)) in plan.files.iter().zip(&paths).enumerate() {
+            if tokio::fs::try_exists(path).await? {
+                continue;
+            }
+
+            let report = Arc::clone(&progress);
+            let (files, title) = (plan.files.len(), file.title.clone());
+            let callback: ProgressCallback = Arc::new(move |downloaded, total| {
+                report(&BookDownloadProgress {
+                    file: index,
+                    files,
+                    title: title.clone(),
+                    downloaded,
+                    total,
+                })
+            });
+
+            let partial = partial_path(path);
+            match self.fetch(&file.url, &partial, callback).await {
+                Ok(()) => tokio::fs::rename(&partial, path).await?,
+                Err(LibraryError::Cancelled) => {
+                    remove_partials(&folder, &paths).await;
+                    return Err(LibraryError::Cancelled);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        let mut options = ImportOptions::new()
+            .with_title(plan.title.clone())
+            .with_author(plan.author.clone())
+            .with_overwrite_existing(true);
+        for tag in &self.tags {
+            options = options.with_tag(tag.clone());
+        }
+        let book = BookImporter::new(self.pool.clone())
+            .import_folder(&folder, options)
+            .await?;
+        info!(
+            "Downloaded '{}' from {} into {}",
+            book.title,
+            plan.source,
+            folder.display()
+        );
+        Ok(paths)
+    }
+
+    /// Downloads `url` to `partial`, continuing a file left by an earlier run
+    async fn fetch(&self, url: &str, partial: &Path, progress: ProgressCallback) -> Result<()> {
+        let id = partial.to_string_lossy().to_string();
+        let task = DownloadTask::new(id.clone(), url.to_string(), partial.to_path_buf())
+            .with_progress_callback(progress);
+        self.downloads
+            .enqueue_resumed(task)
+            .await
+            .map_err(|e| LibraryError::ImportFailed(e.to_string()))?;
+
+        let mut attempt = 1;
+        loop {
+            if self.cancel.is_cancelled() {
+                let _ = self.downloads.cancel(&id).await;
+                return Err(LibraryError::Cancelled);
+            }
+
+            match self.downloads.get_status(&id).await {
+                Some(DownloadStatus::Completed) => return Ok(()),
+                Some(DownloadStatus::Failed(error)) if attempt < self.retry.max_attempts() => {
+                    warn!("Download of {} failed, retrying: {}", url, error);
+                    tokio::time::sleep(self.retry.delay_for_attempt(attempt)).await;
+                    attempt += 1;
+                    self.downloads
+                        .retry_failed(&id)
+                        .await
+                        .map_err(|e| LibraryError::ImportFailed(e.to_string()))?;
+                }
+                Some(DownloadStatus::Failed(error)) => {
+                    return Err(LibraryError::ImportFailed(format!(
+                        "Download of {} failed after {} attempts: {}",
+                        url, attempt, error
+                    )))
+                }
+                Some(DownloadStatus::Cancelled) | None => return Err(LibraryError::Cancelled),
+                Some(_) => tokio::time::sleep(DOWNLOAD_POLL_INTERVAL).await,
+            }
+        }
+    }
+}
+
+/// `path` with the partial download extension added
+fn partial_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(PARTIAL_EXTENSION);
+    path.with_file_name(name)
+}
+
+/// Removes the partial files of `paths`, and `folder` too if that empties it
+async fn remove_partials(folder: &Path, paths: &[PathBuf]) {
+    for path in paths {
+        let partial = partial_path(path);
+        if let Err(e) = tokio::fs::remove_file(&partial).await {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                warn!("Could not remove {}: {}", partial.display(), e);
+            }
+        }
+    }
+    // Fails, as intended, while finished files are left in it
+    let _ = tokio::fs::remove_dir(folder).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::sync::Mutex;
+    use storystream_content_sources::PlannedFile;
+    use storystream_database::connection::{connect, DatabaseConfig};
+    use storystream_database::migrations::run_migrations;
+    use storystream_database::queries::{books, chapters};
+    use storystream_media_formats::wav::wav_bytes;
+    use storystream_network::{Client, DownloadManagerConfig};
+    use tempfile::TempDir;
+
+    /// Path and range offset of each request a test server answered
+    type Requests = Arc<Mutex<Vec<(String, usize)>>>;
+
+    /// Serves `files` by name, honouring ranges and recording each request's
+    /// path and offset; the first request for `flaky` is cut off halfway
+    fn file_server(files: Vec<(&'static str, Vec<u8>)>, flaky: &'static str) -> (String, Requests) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&requests);
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut request = [0; 2048];
+                let read = stream.read(&mut request).unwrap_or(0);
+                let request = String::from_utf8_lossy(&request[..read]).to_lowercase();
+                let path = request.split_whitespace().nth(1).unwrap_or("/").to_string();
+                let offset: usize = request
+                    .split("range: bytes=")
+                    .nth(1)
+                    .and_then(|rest| rest.split('-').next())
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or(0);
+                let first = !seen.lock().unwrap().iter().any(|(p, _)| *p == path);
+                seen.lock().unwrap().push((path.clone(), offset));
+
+                let Some((_, body)) = files.iter().find(|(name, _)| path == format!("/{}", name))
+                else {
+                    let _ = stream.write_all(
+                        b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    );
+                    continue;
+                };
+                let head = if offset > 0 {
+                    format!(
+                        "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {}-{}/{}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        offset,
+                        body.len() - 1,
+                        body.len(),
+                        body.len() - offset
+                    )
+                } else {
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        body.len()
+                    )
+                };
+                let _ = stream.write_all(head.as_bytes());
+                if first && path == format!("/{}", flaky) {
+                    let _ = stream.write_all(&body[..body.len() / 2]);
+                } else {
+                    let _ = stream.write_all(&body[offset..]);
+                }
+            }
+        });
+        (format!("http://{}", addr), requests)
+    }
+
+    fn plan(base: &str) -> ImportPlan {
+        let file = |n: usize, title: &str| PlannedFile {
+            url: format!("{}/{:02}.wav", base, n),
+            file_name: format!("{:02} {}.wav", n, title),
+            title: title.to_string(),
+            duration: None,
+        };
+        ImportPlan {
+            title: "The Book".to_string(),
+            author: "An Author".to_string(),
+            source: "librivox".to_string(),
+            files: vec![file(1, "Opening"), file(2, "Ending")],
+        }
+    }
+
+    async fn downloader(
+        dir: &TempDir,
+    ) -> (DbPool, Arc<AdvancedDownloadManager>, ContentDownloader) {
+        let db_path = dir.path().join("library.db");
+        let pool = connect(DatabaseConfig::new(db_path.to_str().unwrap()))
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        let downloads = Arc::new(AdvancedDownloadManager::new(
+            Client::new().unwrap(),
+            DownloadManagerConfig::default(),
+        ));
+        let queue = Arc::clone(&downloads);
+        tokio::spawn(async move { queue.start().await });
+        let downloader = ContentDownloader::new(pool.clone(), Arc::clone(&downloads))
+            .with_retry(RetryPolicy::new(2).with_initial_delay(Duration::from_millis(10)))
+            .with_tag("librivox:59");
+        (pool, downloads, downloader)
+    }
+
+    #[tokio::test]
+    async fn test_download_resumes_retries_and_imports() {
+        let opening = wav_bytes(8_000, 0);
+        let (base, requests) = file_server(
+            vec![
+                ("01.wav", opening.clone()),
+                ("02.wav", wav_bytes(16_000, 0)),
+            ],
+            "02.wav",
+        );
+        let dir = TempDir::new().unwrap();
+        let (pool, _downloads, downloader) = downloader(&dir).await;
+        let plan = plan(&base);
+
+        // An earlier run crashed halfway through the first file
+        let folder = ContentDownloader::book_folder(&plan, dir.path());
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(folder.join("01 Opening.wav.part"), &opening[..1000]).unwrap();
+
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let recorder = Arc::clone(&reported);
+        let paths = downloader
+            .download_book(&plan, dir.path(), move |p| {
+                recorder.lock().unwrap().push(p.clone())
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            paths,
+            vec![folder.join("01 Opening.wav"), folder.join("02 Ending.wav")]
+        );
+        assert_eq!(std::fs::read(&paths[0]).unwrap(), opening);
+        assert_eq!(std::fs::metadata(&paths[1]).unwrap().len(), 32_044);
+        assert!(!folder.join("01 Opening.wav.part").exists());
+        assert!(!folder.join("02 Ending.wav.part").exists());
+
+        let requests = requests.lock().unwrap().clone();
+        assert_eq!(requests[0], ("/01.wav".to_string(), 1000));
+        let ending: Vec<usize> = requests
+            .iter()
+            .filter(|(path, _)| path == "/02.wav")
+            .map(|(_, offset)| *offset)
+            .collect();
+        assert_eq!(ending, vec![0, 16_022]);
+
+        let last = reported.lock().unwrap().last().cloned().unwrap();
+        assert_eq!((last.file, last.files), (1, 2));
+        assert_eq!(last.title, "Ending");
+        assert_eq!(last.downloaded, 32_044);
+
+        let stored = books::list_books(&pool).await.unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].title, "The Book");
+        assert_eq!(stored[0].author.as_deref(), Some("An Author"));
+        assert_eq!(stored[0].tags, vec!["librivox:59".to_string()]);
+        let chapters = chapters::get_book_chapters(&pool, stored[0].id)
+            .await
+            .unwrap();
+        assert_eq!(chapters.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_removes_partial_files() {
+        let (base, _) = file_server(vec![("01.wav", wav_bytes(8_000, 0))], "");
+        let dir = TempDir::new().unwrap();
+        let (pool, _downloads, downloader) = downloader(&dir).await;
+        let plan = plan(&base);
+        let folder = ContentDownloader::book_folder(&plan, dir.path());
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(folder.join("01 Opening.wav.part"), b"RIFF").unwrap();
+
+        downloader.cancel_handle().cancel();
+        let result = downloader.download_book(&plan, dir.path(), |_| {}).await;
+        assert!(matches!(result, Err(LibraryError::Cancelled)));
+        assert!(!folder.exists());
+        assert!(books::list_books(&pool).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_file_gives_up_after_retries() {
+        let (base, requests) = file_server(vec![("01.wav", wav_bytes(8_000, 0))], "");
+        let dir = TempDir::new().unwrap();
+        let (_pool, _downloads, downloader) = downloader(&dir).await;
+
+        let result = downloader
+            .download_book(&plan(&base), dir.path(), |_| {})
+            .await;
+        assert!(matches!(result, Err(LibraryError::ImportFailed(_))));
+        let missing = requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(path, _)| path == "/02.wav")
+            .count();
+        assert!(missing >= 2);
+    }
+}
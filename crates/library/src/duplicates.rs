@@ -0,0 +1,231 @@
+// FILE: crates/library/src/duplicates.rs
+//! Finding books imported more than once
+//!
+//! Copies of the same file share a hash. Copies encoded differently are
+//! matched by title and author, as long as their durations are within 1%
+//! of each other.
+
+use std::collections::HashMap;
+use storystream_core::{Book, BookId};
+
+/// Largest difference between the durations of two copies, as a fraction
+const DURATION_TOLERANCE: f64 = 0.01;
+
+/// Why the books of a group are thought to be the same
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateReason {
+    /// The files are byte-for-byte identical
+    IdenticalFile,
+    /// Same title, author and length, in different files
+    SameBook,
+}
+
+impl DuplicateReason {
+    /// Short description for reports
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Self::IdenticalFile => "identical files",
+            Self::SameBook => "same title, author and length",
+        }
+    }
+}
+
+/// Books that appear to be copies of one another
+#[derive(Debug, Clone)]
+pub struct DuplicateGroup {
+    pub reason: DuplicateReason,
+    /// At least two books, oldest import first
+    pub books: Vec<Book>,
+}
+
+impl DuplicateGroup {
+    /// The copy worth keeping: the largest file, then the earliest import
+    pub fn suggested_keep(&self) -> BookId {
+        self.books
+            .iter()
+            .max_by(|a, b| {
+                a.file_size
+                    .cmp(&b.file_size)
+                    .then(b.added_date.cmp(&a.added_date))
+            })
+            .map(|b| b.id)
+            .expect("a duplicate group has books")
+    }
+
+    /// Every book but `keep`
+    pub fn others(&self, keep: BookId) -> Vec<BookId> {
+        self.books
+            .iter()
+            .map(|b| b.id)
+            .filter(|id| *id != keep)
+            .collect()
+    }
+}
+
+/// Groups `books` that are copies of one another
+///
+/// `hashes` holds the stored file hash of the books that have one. Groups
+/// come back in the order of their oldest book.
+pub fn group_duplicates(books: &[Book], hashes: &HashMap<BookId, String>) -> Vec<DuplicateGroup> {
+    let mut sets = DisjointSets::new(books.len());
+
+    let mut by_hash: HashMap<&str, usize> = HashMap::new();
+    for (i, book) in books.iter().enumerate() {
+        if let Some(hash) = hashes.get(&book.id) {
+            match by_hash.get(hash.as_str()) {
+                Some(&first) => sets.union(first, i),
+                None => {
+                    by_hash.insert(hash, i);
+                }
+            }
+        }
+    }
+
+    let mut by_name: HashMap<(String, String), Vec<usize>> = HashMap::new();
+    for (i, book) in books.iter().enumerate() {
+        let key = (
+            normalize(&book.title),
+            normalize(book.author.as_deref().unwrap_or_default()),
+        );
+        by_name.entry(key).or_default().push(i);
+    }
+    for indices in by_name.values() {
+        for (n, &a) in indices.iter().enumerate() {
+            for &b in &indices[n + 1..] {
+                if similar_length(&books[a], &books[b]) {
+                    sets.union(a, b);
+                }
+            }
+        }
+    }
+
+    let mut members: HashMap<usize, Vec<&Book>> = HashMap::new();
+    for (i, book) in books.iter().enumerate() {
+        members.entry(sets.find(i)).or_default().push(book);
+    }
+
+    let mut groups: Vec<DuplicateGroup> = members
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .map(|mut group| {
+            group.sort_by_key(|b| b.added_date);
+            let first = hashes.get(&group[0].id);
+            let reason = if first.is_some() && group.iter().all(|b| hashes.get(&b.id) == first) {
+                DuplicateReason::IdenticalFile
+            } else {
+                DuplicateReason::SameBook
+            };
+            DuplicateGroup {
+                reason,
+                books: group.into_iter().cloned().collect(),
+            }
+        })
+        .collect();
+    groups.sort_by_key(|g| g.books[0].added_date);
+    groups
+}
+
+/// Lowercases and drops punctuation, so "The Hobbit:" matches "the hobbit"
+fn normalize(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn similar_length(a: &Book, b: &Book) -> bool {
+    let (a, b) = (a.duration.as_millis() as f64, b.duration.as_millis() as f64);
+    (a - b).abs() <= a.max(b) * DURATION_TOLERANCE
+}
+
+/// Union-find over book indices
+struct DisjointSets {
+    parents: Vec<usize>,
+}
+
+impl DisjointSets {
+    fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, i: usize) -> usize {
+        let parent = self.parents[i];
+        if parent == i {
+            return i;
+        }
+        let root = self.find(parent);
+        self.parents[i] = root;
+        root
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parents[b] = a;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use storystream_core::Duration;
+
+    fn book(title: &str, author: &str, path: &str, seconds: u64) -> Book {
+        let mut book = Book::new(
+            title.to_string(),
+            PathBuf::from(path),
+            seconds * 8_000,
+            Duration::from_seconds(seconds),
+        );
+        book.author = Some(author.to_string());
+        book
+    }
+
+    #[test]
+    fn test_identical_files_are_grouped() {
+        let a = book("Dune", "Frank Herbert", "/a/dune.mp3", 3_600);
+        let b = book("dune (unabridged)", "F. Herbert", "/b/dune.mp3", 3_600);
+        let other = book("Emma", "Jane Austen", "/emma.mp3", 3_600);
+        let hashes = HashMap::from([
+            (a.id, "h1".to_string()),
+            (b.id, "h1".to_string()),
+            (other.id, "h2".to_string()),
+        ]);
+
+        let groups = group_duplicates(&[a.clone(), other, b.clone()], &hashes);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].reason, DuplicateReason::IdenticalFile);
+        let ids: Vec<BookId> = groups[0].books.iter().map(|b| b.id).collect();
+        assert_eq!(ids, vec![a.id, b.id]);
+    }
+
+    #[test]
+    fn test_reencoded_copies_match_on_title_author_and_length() {
+        let mp3 = book("The Hobbit", "J.R.R. Tolkien", "/hobbit.mp3", 40_000);
+        let mut m4b = book("The Hobbit:", "j.r.r. tolkien", "/hobbit.m4b", 40_300);
+        m4b.file_size = mp3.file_size * 2;
+        let abridged = book("The Hobbit", "J.R.R. Tolkien", "/short.mp3", 20_000);
+        let hashes = HashMap::from([(mp3.id, "h1".to_string()), (m4b.id, "h2".to_string())]);
+
+        let groups = group_duplicates(&[mp3.clone(), m4b.clone(), abridged], &hashes);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].reason, DuplicateReason::SameBook);
+        assert_eq!(groups[0].books.len(), 2);
+        assert_eq!(groups[0].suggested_keep(), m4b.id);
+        assert_eq!(groups[0].others(m4b.id), vec![mp3.id]);
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize("  The Hobbit: Or There & Back"),
+            "the hobbit or there back"
+        );
+        assert_eq!(normalize(""), "");
+    }
+}
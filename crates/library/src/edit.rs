@@ -0,0 +1,285 @@
+// FILE: crates/library/src/edit.rs
+//! User corrections to book metadata
+//!
+//! Edits are saved to the database first and marked as user-edited, so a
+//! later re-import keeps them. Writing them back into the file's tags is
+//! optional and needs the `write-tags` feature.
+
+use crate::error::{LibraryError, Result};
+use storystream_core::Book;
+use storystream_database::queries::books::BookUpdate;
+
+/// Metadata corrections for a book
+///
+/// Fields left as `None` are unchanged. An empty author, narrator or series
+/// clears it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetadataEdit {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub narrator: Option<String>,
+    pub series: Option<String>,
+    /// Also write the edited book's metadata into the audio file's tags
+    pub write_tags: bool,
+}
+
+impl MetadataEdit {
+    /// Create an edit that changes nothing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the title
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Set the author; empty clears it
+    pub fn with_author(mut self, author: impl Into<String>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    /// Set the narrator; empty clears it
+    pub fn with_narrator(mut self, narrator: impl Into<String>) -> Self {
+        self.narrator = Some(narrator.into());
+        self
+    }
+
+    /// Set the series; empty clears it
+    pub fn with_series(mut self, series: impl Into<String>) -> Self {
+        self.series = Some(series.into());
+        self
+    }
+
+    /// Set whether to write the changes into the file's tags
+    pub fn with_write_tags(mut self, write_tags: bool) -> Self {
+        self.write_tags = write_tags;
+        self
+    }
+
+    /// Build the database update for the fields that differ from `book`
+    pub(crate) fn to_update(&self, book: &Book) -> Result<BookUpdate> {
+        let mut update = BookUpdate::new();
+
+        if let Some(title) = &self.title {
+            let title = title.trim();
+            if title.is_empty() {
+                return Err(LibraryError::InvalidMetadata(
+                    "title cannot be empty".to_string(),
+                ));
+            }
+            if title != book.title {
+                update = update.title(title);
+            }
+        }
+        if let Some(author) = changed(&self.author, &book.author) {
+            update = update.author(author);
+        }
+        if let Some(narrator) = changed(&self.narrator, &book.narrator) {
+            update = update.narrator(narrator);
+        }
+        if let Some(series) = changed(&self.series, &book.series) {
+            update = update.series(series);
+        }
+
+        Ok(update)
+    }
+}
+
+/// The trimmed new value of an optional field, if it differs from `current`
+fn changed(edit: &Option<String>, current: &Option<String>) -> Option<Option<String>> {
+    let value = edit.as_deref()?.trim();
+    let value = (!value.is_empty()).then(|| value.to_string());
+    (value != *current).then_some(value)
+}
+
+/// What happened to the file's tags during an edit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagWrite {
+    /// Tags were not asked for or nothing changed
+    Skipped,
+    /// The file's tags now match the edited book
+    Written,
+    /// The database edit stands, but the file keeps its old tags
+    Failed(String),
+}
+
+/// Result of [`LibraryManager::edit_metadata`](crate::LibraryManager::edit_metadata)
+#[derive(Debug, Clone)]
+pub struct MetadataEditOutcome {
+    /// The book as saved after the edit
+    pub book: Book,
+    /// Columns that changed
+    pub changed: Vec<&'static str>,
+    /// Whether the file's tags were rewritten
+    pub tags: TagWrite,
+}
+
+/// Copy user-edited fields of `existing` over a freshly imported `book`
+pub(crate) fn keep_user_edits(book: &mut Book, existing: &Book, edited: &[String]) {
+    for field in edited {
+        match field.as_str() {
+            "title" => book.title = existing.title.clone(),
+            "author" => book.author = existing.author.clone(),
+            "narrator" => book.narrator = existing.narrator.clone(),
+            "series" => book.series = existing.series.clone(),
+            "series_position" => book.series_position = existing.series_position,
+            _ => {}
+        }
+    }
+}
+
+/// Write the book's metadata into the tags of its audio file
+///
+/// The tags are written to a copy next to the file, which then replaces the
+/// original, so a failure part way through leaves the file untouched.
+#[cfg(feature = "write-tags")]
+pub(crate) fn write_tags(book: &Book) -> std::result::Result<(), String> {
+    let path = book.file_path.as_path();
+    let temp = temp_path(path).ok_or_else(|| format!("Invalid file name: {}", path.display()))?;
+
+    std::fs::copy(path, &temp).map_err(|e| format!("Failed to copy {}: {}", path.display(), e))?;
+    let result = save_tags(&temp, book).and_then(|()| {
+        std::fs::rename(&temp, path)
+            .map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+    });
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+#[cfg(not(feature = "write-tags"))]
+pub(crate) fn write_tags(_book: &Book) -> std::result::Result<(), String> {
+    Err("this build cannot write tags (enable the `write-tags` feature)".to_string())
+}
+
+/// Temporary file in the same directory, keeping the extension for format detection
+#[cfg(feature = "write-tags")]
+fn temp_path(path: &std::path::Path) -> Option<std::path::PathBuf> {
+    let stem = path.file_stem()?.to_string_lossy();
+    let name = match path.extension() {
+        Some(ext) => format!(".{}.tags-tmp.{}", stem, ext.to_string_lossy()),
+        None => format!(".{}.tags-tmp", stem),
+    };
+    Some(path.with_file_name(name))
+}
+
+#[cfg(feature = "write-tags")]
+fn save_tags(path: &std::path::Path, book: &Book) -> std::result::Result<(), String> {
+    use lofty::config::WriteOptions;
+    use lofty::prelude::*;
+    use lofty::tag::Tag;
+
+    let mut tagged_file =
+        lofty::read_from_path(path).map_err(|e| format!("Failed to read tags: {}", e))?;
+    if tagged_file.primary_tag().is_none() {
+        let tag_type = tagged_file.primary_tag_type();
+        tagged_file.insert_tag(Tag::new(tag_type));
+    }
+    let tag = tagged_file
+        .primary_tag_mut()
+        .ok_or_else(|| "File format has no writable tags".to_string())?;
+
+    // The same items `MetadataExtractor` reads the fields from
+    tag.set_title(book.title.clone());
+    match &book.author {
+        Some(author) => tag.set_artist(author.clone()),
+        None => tag.remove_artist(),
+    }
+    match &book.narrator {
+        Some(narrator) => {
+            tag.insert_text(ItemKey::Composer, narrator.clone());
+        }
+        None => tag.remove_key(&ItemKey::Composer),
+    }
+    match &book.series {
+        Some(series) => tag.set_album(series.clone()),
+        None => tag.remove_album(),
+    }
+
+    tag.save_to_path(path, WriteOptions::default())
+        .map_err(|e| format!("Failed to write tags: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use storystream_core::Duration;
+
+    fn book() -> Book {
+        let mut book = Book::new(
+            "Pride and Prejudise".to_string(),
+            PathBuf::from("/books/pride.m4b"),
+            1000,
+            Duration::from_seconds(60),
+        );
+        book.author = Some("Jane Austen".to_string());
+        book.narrator = Some("Rosamund Pike".to_string());
+        book
+    }
+
+    #[test]
+    fn test_to_update_only_includes_changes() {
+        let book = book();
+        let edit = MetadataEdit::new()
+            .with_title(" Pride and Prejudice ")
+            .with_author("Jane Austen")
+            .with_narrator("")
+            .with_series("Austen Collection");
+
+        let update = edit.to_update(&book).unwrap();
+        assert_eq!(update.columns(), vec!["title", "narrator", "series"]);
+        assert_eq!(
+            update,
+            BookUpdate::new()
+                .title("Pride and Prejudice")
+                .narrator(None)
+                .series(Some("Austen Collection".to_string()))
+        );
+
+        assert!(MetadataEdit::new().to_update(&book).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_to_update_rejects_empty_title() {
+        let result = MetadataEdit::new().with_title("  ").to_update(&book());
+        assert!(matches!(result, Err(LibraryError::InvalidMetadata(_))));
+    }
+
+    #[test]
+    fn test_keep_user_edits() {
+        let mut existing = book();
+        existing.title = "Pride and Prejudice".to_string();
+        existing.series = Some("Collection".to_string());
+        let mut reimported = book();
+
+        keep_user_edits(&mut reimported, &existing, &["title".to_string()]);
+        assert_eq!(reimported.title, "Pride and Prejudice");
+        assert_eq!(reimported.series, None);
+    }
+
+    #[cfg(feature = "write-tags")]
+    #[test]
+    fn test_temp_path_keeps_extension() {
+        let temp = temp_path(std::path::Path::new("/books/pride.m4b")).unwrap();
+        assert_eq!(temp, PathBuf::from("/books/.pride.tags-tmp.m4b"));
+    }
+
+    #[cfg(feature = "write-tags")]
+    #[test]
+    fn test_write_tags_failure_leaves_file_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("book.mp3");
+        std::fs::write(&path, b"not really audio").unwrap();
+        let mut book = book();
+        book.file_path = path.clone();
+
+        assert!(write_tags(&book).is_err());
+        assert_eq!(std::fs::read(&path).unwrap(), b"not really audio");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
@@ -1,5 +1,6 @@
 // FILE: crates/library/src/error.rs
 
+use std::path::PathBuf;
 use storystream_core::error::AppError;
 use thiserror::Error;
 
@@ -32,10 +33,85 @@ pub enum LibraryError {
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
+    #[error("Permission denied: {0}")]
+    PermissionDenied(PathBuf),
+
+    #[error("Scan cancelled")]
+    Cancelled,
+
+    #[error("Nothing to play in playlist '{0}'")]
+    EmptyPlaylist(String),
+
+    #[error("Invalid metadata: {0}")]
+    InvalidMetadata(String),
+
+    #[error("Invalid chapters: {}", .0.join("; "))]
+    InvalidChapters(Vec<String>),
+
+    #[error("Invalid organize template: {0}")]
+    InvalidTemplate(String),
+
+    #[error("Feed error: {0}")]
+    Feed(String),
+
+    #[error("Import plan is out of date: {} changed since it was made", .0.display())]
+    StalePlan(PathBuf),
+
+    #[error("Not enough disk space: need {needed} bytes, {available} available")]
+    DiskFull { needed: u64, available: u64 },
+
     #[error("{0}")]
     Other(String),
 }
 
+impl From<LibraryError> for AppError {
+    fn from(err: LibraryError) -> Self {
+        match err {
+            LibraryError::Database(e) => e,
+            LibraryError::FileNotFound(path) => AppError::FileNotFound { path: path.into() },
+            LibraryError::UnsupportedFormat(format) => AppError::UnsupportedFormat {
+                format,
+                file: PathBuf::new(),
+            },
+            LibraryError::MetadataError(reason) => AppError::MetadataParseError {
+                file: PathBuf::new(),
+                reason,
+            },
+            LibraryError::Io(e) => e.into(),
+            LibraryError::PermissionDenied(path) => AppError::PermissionDenied {
+                operation: "scan library".to_string(),
+                path,
+            },
+            LibraryError::Cancelled => AppError::Cancelled {
+                operation: "library scan".to_string(),
+            },
+            LibraryError::EmptyPlaylist(name) => AppError::InvalidArgument {
+                argument: "playlist".to_string(),
+                reason: format!("nothing to play in '{}'", name),
+            },
+            LibraryError::InvalidMetadata(reason) => AppError::InvalidArgument {
+                argument: "metadata".to_string(),
+                reason,
+            },
+            LibraryError::InvalidChapters(problems) => AppError::InvalidArgument {
+                argument: "chapters".to_string(),
+                reason: problems.join("; "),
+            },
+            LibraryError::InvalidTemplate(reason) => AppError::InvalidArgument {
+                argument: "template".to_string(),
+                reason,
+            },
+            LibraryError::DiskFull { needed, available } => AppError::DiskFull {
+                needed_bytes: needed,
+                available_bytes: available,
+            },
+            other => AppError::InternalError {
+                message: other.to_string(),
+            },
+        }
+    }
+}
+
 // Both type aliases for convenience
 pub type Result<T> = std::result::Result<T, LibraryError>;
 pub type LibraryResult<T> = std::result::Result<T, LibraryError>;
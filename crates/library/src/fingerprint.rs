@@ -0,0 +1,166 @@
+//! Content fingerprints for spotting moved files
+//!
+//! A fingerprint hashes the start of a file along with its size and
+//! duration. Unlike the full file hash kept for verification it costs the
+//! same for a ten-hour book as for a short story, so every scan can take
+//! one, and it does not depend on where the file lives.
+
+use crate::error::{LibraryError, Result};
+use crate::metadata::MetadataExtractor;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use storystream_core::{Book, BookId, Duration};
+use storystream_database::{queries::books, DbPool};
+use tracing::debug;
+
+/// How much of the start of a file goes into its fingerprint
+pub const FINGERPRINT_BYTES: u64 = 4 << 20;
+
+/// Fingerprint of an audio file, as lowercase hex
+///
+/// `duration` is the length read from the file's metadata; two files only
+/// match if their size and duration agree as well as their first
+/// [`FINGERPRINT_BYTES`].
+pub fn content_hash(path: &Path, duration: Duration) -> Result<String> {
+    let file = File::open(path)?;
+    let size = file.metadata()?.len();
+
+    let mut hasher = Sha256::new();
+    let mut head = Vec::new();
+    file.take(FINGERPRINT_BYTES).read_to_end(&mut head)?;
+    hasher.update(&head);
+    hasher.update(size.to_le_bytes());
+    hasher.update(duration.as_millis().to_le_bytes());
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+/// Books whose file is gone, by content fingerprint
+///
+/// Books imported before fingerprints were kept are left out, since there
+/// is nothing to recognise their files by.
+pub(crate) async fn missing_books(pool: &DbPool) -> Result<HashMap<String, Book>> {
+    let hashes = books::get_content_hashes(pool).await?;
+    let mut missing = HashMap::new();
+    for book in books::list_books(pool).await? {
+        if let Some(hash) = hashes.get(&book.id) {
+            if !book.file_path.exists() {
+                missing.entry(hash.clone()).or_insert(book);
+            }
+        }
+    }
+    Ok(missing)
+}
+
+/// Looks through `files` for the books in `missing`, returning each book
+/// found with its file's new path
+///
+/// Only files of the size a missing book had are read, so searching a large
+/// folder for a few books costs little more than listing it. Files already
+/// in `known` belong to other books.
+pub(crate) fn find_moved_files(
+    files: Vec<PathBuf>,
+    mut missing: HashMap<String, Book>,
+    known: &HashSet<PathBuf>,
+) -> Result<Vec<(Book, PathBuf)>> {
+    let sizes: HashSet<u64> = missing.values().map(|book| book.file_size).collect();
+    let extractor = MetadataExtractor::new().map_err(|e| LibraryError::Other(e.to_string()))?;
+
+    let mut found = Vec::new();
+    for file in files {
+        if missing.is_empty() {
+            break;
+        }
+        let Ok(path) = file.canonicalize() else {
+            continue;
+        };
+        let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        if known.contains(&path) || !sizes.contains(&size) {
+            continue;
+        }
+        let duration = match extractor.extract(&path) {
+            Ok(metadata) => metadata.duration,
+            Err(e) => {
+                debug!("Skipping {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        if let Some(book) = content_hash(&path, duration)
+            .ok()
+            .and_then(|hash| missing.remove(&hash))
+        {
+            found.push((book, path));
+        }
+    }
+    Ok(found)
+}
+
+/// A book pointed at the new location of its file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relocation {
+    pub book_id: BookId,
+    pub title: String,
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_fingerprint_ignores_location_but_not_contents() {
+        let dir = TempDir::new().unwrap();
+        let original = dir.path().join("book.mp3");
+        std::fs::write(&original, b"ID3 audio data").unwrap();
+        let length = Duration::from_seconds(60);
+
+        let hash = content_hash(&original, length).unwrap();
+        assert_eq!(hash.len(), 64);
+
+        let moved = dir.path().join("renamed").join("Book.mp3");
+        std::fs::create_dir_all(moved.parent().unwrap()).unwrap();
+        std::fs::rename(&original, &moved).unwrap();
+        assert_eq!(content_hash(&moved, length).unwrap(), hash);
+
+        assert_ne!(
+            content_hash(&moved, Duration::from_seconds(61)).unwrap(),
+            hash
+        );
+        std::fs::write(&moved, b"ID3 other data").unwrap();
+        assert_ne!(content_hash(&moved, length).unwrap(), hash);
+    }
+
+    #[test]
+    fn test_fingerprint_reads_only_the_start() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("long.m4b");
+        let mut data = vec![0u8; FINGERPRINT_BYTES as usize + 16];
+        std::fs::write(&path, &data).unwrap();
+        let length = Duration::from_seconds(3600);
+        let hash = content_hash(&path, length).unwrap();
+
+        // Past the fingerprinted start only the size counts
+        *data.last_mut().unwrap() = 1;
+        std::fs::write(&path, &data).unwrap();
+        assert_eq!(content_hash(&path, length).unwrap(), hash);
+
+        data.push(0);
+        std::fs::write(&path, &data).unwrap();
+        assert_ne!(content_hash(&path, length).unwrap(), hash);
+    }
+
+    #[test]
+    fn test_fingerprint_of_missing_file_fails() {
+        let dir = TempDir::new().unwrap();
+        assert!(content_hash(&dir.path().join("gone.mp3"), Duration::from_seconds(1)).is_err());
+    }
+}
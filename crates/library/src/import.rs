@@ -1,11 +1,28 @@
 // FILE: crates/library/src/import.rs
 
+use crate::edit::keep_user_edits;
 use crate::error::{LibraryError, Result};
+use crate::fingerprint::content_hash;
+use crate::metadata::chapters::{to_chapters, ChapterMarker};
 use crate::metadata::{ExtractedMetadata, MetadataExtractor};
-use log::{debug, info, warn};
+use crate::organize::{self, OrganizeTemplate};
+use crate::parts::folder_files;
+use crate::reconcile::{known_files, record_file_state};
+use crate::scanner::{KnownFile, ScanMode};
+use crate::verify::hash_file;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::path::{Path, PathBuf};
-use storystream_core::Book;
-use storystream_database::{queries::books, DbPool};
+use storystream_core::{Book, BookId, Chapter, Duration, Timestamp};
+use storystream_database::{
+    queries::{books, chapters},
+    DbPool,
+};
+use tracing::{debug, field, info, instrument, warn, Span};
+
+/// Imports of more files than this create their new books in one batch
+const BATCH_IMPORT_THRESHOLD: usize = 10;
 
 /// Book import options
 #[derive(Debug, Clone)]
@@ -20,6 +37,10 @@ pub struct ImportOptions {
     pub overwrite_existing: bool,
     /// Whether to skip files with errors instead of failing the whole import
     pub skip_on_error: bool,
+    /// File imported books under the library root instead of where they are
+    pub organize: Option<OrganizeTemplate>,
+    /// Tags to give imported books, on top of any they have
+    pub tags: Vec<String>,
 }
 
 impl Default for ImportOptions {
@@ -30,6 +51,8 @@ impl Default for ImportOptions {
             extract_cover: true,
             overwrite_existing: false,
             skip_on_error: false,
+            organize: None,
+            tags: Vec::new(),
         }
     }
 }
@@ -69,12 +92,155 @@ impl ImportOptions {
         self.skip_on_error = skip;
         self
     }
+
+    /// Copy or move imported files into the template's layout
+    pub fn with_organize(mut self, template: OrganizeTemplate) -> Self {
+        self.organize = Some(template);
+        self
+    }
+
+    /// Add a tag to imported books
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+}
+
+/// Why a staged import leaves a file alone
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// Not an audio format the library can play
+    Unsupported,
+    /// In the library already, and the file has not changed since
+    Unchanged,
+    /// The same file was listed more than once
+    Duplicate,
+    /// The file could not be read
+    Unreadable(String),
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported => write!(f, "not a supported audio format"),
+            Self::Unchanged => write!(f, "already in the library, unchanged"),
+            Self::Duplicate => write!(f, "listed more than once"),
+            Self::Unreadable(reason) => write!(f, "unreadable: {}", reason),
+        }
+    }
+}
+
+/// What committing a plan does with one file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlannedAction {
+    /// Add a new book
+    Create,
+    /// Refresh a book already in the library from its file
+    Update {
+        book_id: BookId,
+        /// Fields whose stored value the file would replace
+        changes: Vec<String>,
+    },
+    /// Leave the file alone
+    Skip(SkipReason),
+}
+
+/// One file of an [`ImportPlan`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedImport {
+    pub path: PathBuf,
+    pub action: PlannedAction,
+    /// The book as read from the file; `None` when the file is skipped
+    pub book: Option<Book>,
+    /// Hash of the file when the plan was made
+    pub file_hash: Option<String>,
+    /// Whether committing the plan applies this entry
+    pub include: bool,
+}
+
+impl PlannedImport {
+    fn skipped(path: PathBuf, reason: SkipReason) -> Self {
+        Self {
+            path,
+            action: PlannedAction::Skip(reason),
+            book: None,
+            file_hash: None,
+            include: false,
+        }
+    }
+
+    /// The book's title, or the file name for skipped files
+    pub fn title(&self) -> String {
+        match &self.book {
+            Some(book) => book.title.clone(),
+            None => self
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| self.path.display().to_string()),
+        }
+    }
+}
+
+/// What importing a set of files would do, worked out without touching the
+/// database
+///
+/// Plans serialize, so one can be saved, reviewed and committed later with
+/// [`BookImporter::commit`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportPlan {
+    /// One entry per file, in the order the files were found
+    pub items: Vec<PlannedImport>,
+}
+
+impl ImportPlan {
+    /// Included entries that add a book
+    pub fn creates(&self) -> usize {
+        self.count(|action| matches!(action, PlannedAction::Create))
+    }
+
+    /// Included entries that refresh a book
+    pub fn updates(&self) -> usize {
+        self.count(|action| matches!(action, PlannedAction::Update { .. }))
+    }
+
+    /// Files the plan leaves alone
+    pub fn skipped(&self) -> usize {
+        self.items
+            .iter()
+            .filter(|item| matches!(item.action, PlannedAction::Skip(_)))
+            .count()
+    }
+
+    /// Includes or excludes an entry, returning whether it is now included
+    ///
+    /// Skipped files stay excluded.
+    pub fn toggle(&mut self, index: usize) -> bool {
+        match self.items.get_mut(index) {
+            Some(item) if !matches!(item.action, PlannedAction::Skip(_)) => {
+                item.include = !item.include;
+                item.include
+            }
+            _ => false,
+        }
+    }
+
+    fn count(&self, action: impl Fn(&PlannedAction) -> bool) -> usize {
+        self.items
+            .iter()
+            .filter(|item| item.include && action(&item.action))
+            .count()
+    }
 }
 
 /// Book importer for adding audiobooks to the library
 pub struct BookImporter {
     pool: DbPool,
     metadata_extractor: MetadataExtractor,
+    /// Whether previews pass over books whose file looks untouched
+    scan_mode: ScanMode,
 }
 
 impl BookImporter {
@@ -86,10 +252,22 @@ impl BookImporter {
         Self {
             pool,
             metadata_extractor,
+            scan_mode: ScanMode::default(),
         }
     }
 
+    /// Sets whether [`preview`](Self::preview) reads every file again, or
+    /// passes over those whose size and modification time did not change
+    pub fn with_scan_mode(mut self, mode: ScanMode) -> Self {
+        self.scan_mode = mode;
+        self
+    }
+
     /// Import a single audiobook file
+    #[instrument(
+        skip_all,
+        fields(path = %path.as_ref().display(), book_id = field::Empty, bytes = field::Empty)
+    )]
     pub async fn import_file<P: AsRef<Path>>(
         &self,
         path: P,
@@ -99,6 +277,150 @@ impl BookImporter {
 
         info!("Importing audiobook from: {}", path.display());
 
+        let (mut book, existing, content_hash) = self.prepare_file(path, &options).await?;
+        Span::current()
+            .record("book_id", field::display(book.id))
+            .record("bytes", book.file_size);
+
+        match existing {
+            Some(existing) => {
+                self.keep_library_state(&mut book, existing).await?;
+                books::update_book(&self.pool, &book)
+                    .await
+                    .map_err(LibraryError::Database)?;
+            }
+            None => {
+                books::create_book(&self.pool, &book)
+                    .await
+                    .map_err(LibraryError::Database)?;
+            }
+        }
+
+        // Remember the file's contents so verification can spot bit rot
+        match hash_file(&book.file_path) {
+            Ok(hash) => books::set_file_hash(&self.pool, book.id, Some(&hash))
+                .await
+                .map_err(LibraryError::Database)?,
+            Err(e) => warn!("Could not hash {}: {}", book.file_path.display(), e),
+        }
+        if let Some(hash) = &content_hash {
+            books::set_content_hash(&self.pool, book.id, Some(hash))
+                .await
+                .map_err(LibraryError::Database)?;
+        }
+        record_file_state(&self.pool, [&book]).await?;
+        let found = self.metadata_extractor.read_chapters(&book);
+        store_chapters(&self.pool, &book, &found).await?;
+
+        info!("Successfully imported: {}", book.title);
+
+        Ok(book)
+    }
+
+    /// Imports the audio files in `folder` as one book
+    ///
+    /// The files play in name order, each as a chapter named after its title
+    /// tag or its file name. Without a title in `options` the book is named
+    /// after the folder; its other details come from the first file's tags.
+    /// The folder stays where it is, whatever `options.organize` says.
+    #[instrument(
+        skip_all,
+        fields(path = %folder.as_ref().display(), book_id = field::Empty, files = field::Empty)
+    )]
+    pub async fn import_folder<P: AsRef<Path>>(
+        &self,
+        folder: P,
+        options: ImportOptions,
+    ) -> Result<Book> {
+        let folder = folder.as_ref();
+
+        info!("Importing audiobook folder: {}", folder.display());
+
+        let files = folder_files(folder)?;
+        Span::current().record("files", files.len());
+        let canonical_path = self.canonicalize_path(folder)?;
+        let existing = self.find_by_path(&canonical_path).await?;
+        if let (Some(existing_book), false) = (&existing, options.overwrite_existing) {
+            return Err(LibraryError::ImportFailed(format!(
+                "Book already exists in library: {}",
+                existing_book.title
+            )));
+        }
+
+        let mut parts = Vec::with_capacity(files.len());
+        for file in &files {
+            parts.push(self.extract_metadata(file)?);
+        }
+        let mut metadata = parts[0].clone();
+        metadata.title = Some(
+            folder
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "Unknown".to_string()),
+        );
+        metadata.duration =
+            Duration::from_millis(parts.iter().map(|p| p.duration.as_millis()).sum());
+        metadata.file_size = parts.iter().map(|p| p.file_size).sum();
+        let metadata = self.apply_options(metadata, &options);
+
+        let mut book = self.metadata_extractor.to_book(&canonical_path, metadata);
+        book.tags = options.tags.clone();
+        Span::current().record("book_id", field::display(book.id));
+        match existing {
+            Some(existing) => {
+                self.keep_library_state(&mut book, existing).await?;
+                books::update_book(&self.pool, &book)
+                    .await
+                    .map_err(LibraryError::Database)?;
+            }
+            None => {
+                books::create_book(&self.pool, &book)
+                    .await
+                    .map_err(LibraryError::Database)?;
+            }
+        }
+
+        let mut start = 0;
+        let markers = files
+            .iter()
+            .zip(&parts)
+            .map(|(file, part)| {
+                let title = part.title.clone().unwrap_or_else(|| {
+                    file.file_stem()
+                        .map(|stem| stem.to_string_lossy().into_owned())
+                        .unwrap_or_default()
+                });
+                let marker = ChapterMarker {
+                    title,
+                    start: Duration::from_millis(start),
+                    end: Some(Duration::from_millis(start + part.duration.as_millis())),
+                };
+                start += part.duration.as_millis();
+                marker
+            })
+            .collect();
+        store_chapters(&self.pool, &book, &to_chapters(&book, markers)).await?;
+
+        info!(
+            "Successfully imported {} files as: {}",
+            files.len(),
+            book.title
+        );
+
+        Ok(book)
+    }
+
+    /// Reads a file into a book and files it away, without storing it
+    ///
+    /// Returns the book, the library's copy it replaces, if any, and the
+    /// file's content fingerprint. A book whose file went missing and has
+    /// turned up at `path` counts as the library's copy, even without
+    /// `overwrite_existing`.
+    async fn prepare_file(
+        &self,
+        path: &Path,
+        options: &ImportOptions,
+    ) -> Result<(Book, Option<Book>, Option<String>)> {
         // Validate file exists
         if !path.exists() {
             return Err(LibraryError::FileNotFound(path.display().to_string()));
@@ -126,7 +448,31 @@ impl BookImporter {
 
         // Check if book already exists in database (by file path)
         let canonical_path = self.canonicalize_path(path)?;
-        if let Some(existing_book) = self.find_by_path(&canonical_path).await? {
+        let mut existing = self.find_by_path(&canonical_path).await?;
+
+        // Extract metadata
+        let metadata = self.extract_metadata(path)?;
+
+        // Apply any overrides from options
+        let metadata = self.apply_options(metadata, options);
+
+        // Convert metadata to Book
+        let mut book = self.metadata_extractor.to_book(path, metadata);
+
+        // Use canonical path for storage
+        book.file_path = canonical_path;
+        book.tags = options.tags.clone();
+
+        // A copy filed away by an earlier import is the same book
+        let target = options
+            .organize
+            .as_ref()
+            .map(|template| template.destination(&book));
+        if let (None, Some(target)) = (&existing, &target) {
+            existing = self.find_by_path(target).await?;
+        }
+
+        if let Some(existing_book) = &existing {
             if !options.overwrite_existing {
                 return Err(LibraryError::ImportFailed(format!(
                     "Book already exists in library: {}",
@@ -136,29 +482,243 @@ impl BookImporter {
             debug!("Overwriting existing book: {}", existing_book.title);
         }
 
-        // Extract metadata
-        let metadata = self.extract_metadata(path)?;
+        let content_hash = content_hash(&book.file_path, book.duration)
+            .map_err(|e| warn!("Could not fingerprint {}: {}", path.display(), e))
+            .ok();
+        if let (None, Some(hash)) = (&existing, &content_hash) {
+            existing = self.find_moved(hash).await?;
+        }
 
-        // Apply any overrides from options
-        let metadata = self.apply_options(metadata, &options);
+        if let (Some(template), Some(target)) = (&options.organize, target) {
+            if target != book.file_path {
+                // Replace the file of the book being overwritten, not a stranger's
+                let target = match &existing {
+                    Some(existing) if existing.file_path == target => target,
+                    _ => organize::free_destination(&target, &HashSet::new()),
+                };
+                organize::place_file(&book.file_path, &target, template.mode())?;
+                book.file_path = self.canonicalize_path(&target)?;
+            }
+        }
 
-        // Convert metadata to Book
-        let mut book = self.metadata_extractor.to_book(path, metadata);
+        Ok((book, existing, content_hash))
+    }
 
-        // Use canonical path for storage
-        book.file_path = canonical_path;
+    /// Imports one file of a large import, leaving new books in `new_books`
+    /// and their fingerprints in `content_hashes` for
+    /// [`create_staged`](Self::create_staged); `staged_paths` holds the
+    /// paths of `new_books`
+    ///
+    /// Books already in the library are updated right away.
+    #[instrument(skip_all, fields(path = %path.display()))]
+    async fn stage_file(
+        &self,
+        path: &Path,
+        options: &ImportOptions,
+        new_books: &mut Vec<Book>,
+        staged_paths: &mut HashSet<PathBuf>,
+        content_hashes: &mut HashMap<BookId, String>,
+    ) -> Result<Book> {
+        let (mut book, existing, content_hash) = self.prepare_file(path, options).await?;
+        match existing {
+            Some(existing) => {
+                self.keep_library_state(&mut book, existing).await?;
+                books::update_book(&self.pool, &book)
+                    .await
+                    .map_err(LibraryError::Database)?;
+                match hash_file(&book.file_path) {
+                    Ok(hash) => books::set_file_hash(&self.pool, book.id, Some(&hash))
+                        .await
+                        .map_err(LibraryError::Database)?,
+                    Err(e) => warn!("Could not hash {}: {}", book.file_path.display(), e),
+                }
+                if let Some(hash) = &content_hash {
+                    books::set_content_hash(&self.pool, book.id, Some(hash))
+                        .await
+                        .map_err(LibraryError::Database)?;
+                }
+                record_file_state(&self.pool, [&book]).await?;
+                let found = self.metadata_extractor.read_chapters(&book);
+                store_chapters(&self.pool, &book, &found).await?;
+            }
+            None => {
+                if !staged_paths.insert(book.file_path.clone()) {
+                    return Err(LibraryError::ImportFailed(format!(
+                        "{} is listed more than once",
+                        book.file_path.display()
+                    )));
+                }
+                if let Some(hash) = content_hash {
+                    content_hashes.insert(book.id, hash);
+                }
+                new_books.push(book.clone());
+            }
+        }
+        Ok(book)
+    }
+
+    /// Creates the books [`stage_file`](Self::stage_file) left, in one
+    /// transaction, and stores their hashes and chapters
+    async fn create_staged(
+        &self,
+        new_books: &[Book],
+        content_hashes: &HashMap<BookId, String>,
+    ) -> Result<()> {
+        let mut hashes = HashMap::new();
+        for book in new_books {
+            match hash_file(&book.file_path) {
+                Ok(hash) => {
+                    hashes.insert(book.id, hash);
+                }
+                Err(e) => warn!("Could not hash {}: {}", book.file_path.display(), e),
+            }
+        }
+
+        let created = books::create_books_batch(&self.pool, new_books)
+            .await
+            .map_err(LibraryError::Database)?;
+        books::set_file_hashes(&self.pool, &hashes)
+            .await
+            .map_err(LibraryError::Database)?;
+        books::set_content_hashes(&self.pool, content_hashes)
+            .await
+            .map_err(LibraryError::Database)?;
+        record_file_state(&self.pool, new_books).await?;
+        for book in new_books {
+            let found = self.metadata_extractor.read_chapters(book);
+            store_chapters(&self.pool, book, &found).await?;
+        }
+        info!("Created {} books in one batch", created);
+        Ok(())
+    }
+
+    /// Works out what importing `paths` would do, without touching the
+    /// database
+    ///
+    /// Directories are searched for audio files. Each file that would be
+    /// imported is hashed, so [`commit`](Self::commit) can tell whether it
+    /// changed in the meantime. In [`ScanMode::Incremental`], the default,
+    /// books whose file kept its size and modification time are skipped as
+    /// unchanged without reading the file.
+    #[instrument(skip_all, fields(paths = paths.len()))]
+    pub async fn preview<P: AsRef<Path>>(&self, paths: &[P]) -> Result<ImportPlan> {
+        let mut files = Vec::new();
+        for path in paths {
+            let path = path.as_ref();
+            if path.is_dir() {
+                files.extend(self.scan_directory(path)?);
+            } else {
+                files.push(path.to_path_buf());
+            }
+        }
+
+        let library = books::list_books(&self.pool)
+            .await
+            .map_err(LibraryError::Database)?;
+        let library: HashMap<PathBuf, Book> = library
+            .into_iter()
+            .map(|book| (book.file_path.clone(), book))
+            .collect();
+        let hashes = books::get_file_hashes(&self.pool)
+            .await
+            .map_err(LibraryError::Database)?;
+        let known = match self.scan_mode {
+            ScanMode::Incremental => known_files(&self.pool).await?,
+            ScanMode::Full => HashMap::new(),
+        };
+
+        let mut plan = ImportPlan::default();
+        let mut seen = HashSet::new();
+        for file in files {
+            let item = self
+                .plan_file(&file, &library, &hashes, &known, &mut seen)
+                .await?;
+            plan.items.push(item);
+        }
+
+        info!(
+            "Import plan: {} to add, {} to update, {} skipped",
+            plan.creates(),
+            plan.updates(),
+            plan.skipped()
+        );
+        Ok(plan)
+    }
 
-        // Insert into database
-        books::create_book(&self.pool, &book)
+    /// Applies the included entries of `plan` in a single transaction
+    ///
+    /// Returns the books created and updated.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LibraryError::StalePlan` without changing anything if an
+    /// included file is gone or its contents changed since the plan was
+    /// made, or if the library no longer matches the plan.
+    #[instrument(skip_all, fields(items = plan.items.len()))]
+    pub async fn commit(&self, plan: &ImportPlan) -> Result<Vec<Book>> {
+        let paths = books::get_file_paths(&self.pool)
             .await
             .map_err(LibraryError::Database)?;
 
-        info!("Successfully imported: {}", book.title);
+        let mut created = Vec::new();
+        let mut updated = Vec::new();
+        let mut hashes = HashMap::new();
+        for item in plan.items.iter().filter(|item| item.include) {
+            let (Some(book), Some(expected)) = (&item.book, &item.file_hash) else {
+                continue;
+            };
+            if hash_file(&item.path).ok().as_ref() != Some(expected) {
+                return Err(LibraryError::StalePlan(item.path.clone()));
+            }
 
-        Ok(book)
+            let mut book = book.clone();
+            match &item.action {
+                PlannedAction::Create => {
+                    if paths.contains(&book.file_path) {
+                        return Err(LibraryError::StalePlan(item.path.clone()));
+                    }
+                    book.added_date = Timestamp::now();
+                    hashes.insert(book.id, expected.clone());
+                    created.push(book);
+                }
+                PlannedAction::Update { book_id, .. } => {
+                    let existing = books::get_book(&self.pool, *book_id)
+                        .await
+                        .map_err(|_| LibraryError::StalePlan(item.path.clone()))?;
+                    self.keep_library_state(&mut book, existing).await?;
+                    hashes.insert(book.id, expected.clone());
+                    updated.push(book);
+                }
+                PlannedAction::Skip(_) => {}
+            }
+        }
+
+        books::save_books(&self.pool, &created, &updated, &hashes)
+            .await
+            .map_err(LibraryError::Database)?;
+        record_file_state(&self.pool, created.iter().chain(&updated)).await?;
+        for book in created.iter().chain(&updated) {
+            let found = self.metadata_extractor.read_chapters(book);
+            store_chapters(&self.pool, book, &found).await?;
+        }
+        info!(
+            "Committed import plan: {} added, {} updated",
+            created.len(),
+            updated.len()
+        );
+
+        created.extend(updated);
+        Ok(created)
+    }
+
+    /// Whether a book with this file is already in the library
+    pub async fn is_imported<P: AsRef<Path>>(&self, path: P) -> Result<bool> {
+        let canonical_path = self.canonicalize_path(path.as_ref())?;
+        Ok(self.find_by_path(&canonical_path).await?.is_some())
     }
 
     /// Import multiple audiobook files
+    #[instrument(skip_all, fields(files = paths.len()))]
     pub async fn import_files<P: AsRef<Path>>(
         &self,
         paths: &[P],
@@ -166,6 +726,11 @@ impl BookImporter {
     ) -> Result<Vec<Book>> {
         info!("Importing {} files", paths.len());
 
+        // Large imports create their new books together at the end
+        let batch = paths.len() > BATCH_IMPORT_THRESHOLD;
+        let mut new_books = Vec::new();
+        let mut staged_paths = HashSet::new();
+        let mut content_hashes = HashMap::new();
         let mut books = Vec::new();
         let mut errors = Vec::new();
 
@@ -178,7 +743,19 @@ impl BookImporter {
                 path.display()
             );
 
-            match self.import_file(path, options.clone()).await {
+            let imported = if batch {
+                self.stage_file(
+                    path,
+                    &options,
+                    &mut new_books,
+                    &mut staged_paths,
+                    &mut content_hashes,
+                )
+                .await
+            } else {
+                self.import_file(path, options.clone()).await
+            };
+            match imported {
                 Ok(book) => {
                     books.push(book);
                 }
@@ -197,6 +774,10 @@ impl BookImporter {
             }
         }
 
+        if !new_books.is_empty() {
+            self.create_staged(&new_books, &content_hashes).await?;
+        }
+
         if !errors.is_empty() {
             warn!(
                 "Imported {}/{} files successfully ({} errors)",
@@ -212,6 +793,7 @@ impl BookImporter {
     }
 
     /// Import all audiobooks from a directory recursively
+    #[instrument(skip_all, fields(directory = %directory.as_ref().display()))]
     pub async fn import_directory<P: AsRef<Path>>(
         &self,
         directory: P,
@@ -272,6 +854,92 @@ impl BookImporter {
         Ok(audio_files)
     }
 
+    /// Plans the import of one file against the library as it is now
+    async fn plan_file(
+        &self,
+        path: &Path,
+        library: &HashMap<PathBuf, Book>,
+        hashes: &HashMap<BookId, String>,
+        known: &HashMap<PathBuf, KnownFile>,
+        seen: &mut HashSet<PathBuf>,
+    ) -> Result<PlannedImport> {
+        if !MetadataExtractor::is_supported(path) {
+            return Ok(PlannedImport::skipped(
+                path.to_path_buf(),
+                SkipReason::Unsupported,
+            ));
+        }
+        let unreadable = |e: &dyn fmt::Display| SkipReason::Unreadable(e.to_string());
+        let path = match self.canonicalize_path(path) {
+            Ok(path) => path,
+            Err(e) => return Ok(PlannedImport::skipped(path.to_path_buf(), unreadable(&e))),
+        };
+        if !seen.insert(path.clone()) {
+            return Ok(PlannedImport::skipped(path, SkipReason::Duplicate));
+        }
+        if known
+            .get(&path)
+            .is_some_and(|file| file.is_unchanged(&path))
+        {
+            return Ok(PlannedImport::skipped(path, SkipReason::Unchanged));
+        }
+
+        let file_hash = match hash_file(&path) {
+            Ok(hash) => hash,
+            Err(e) => return Ok(PlannedImport::skipped(path, unreadable(&e))),
+        };
+        let existing = library.get(&path);
+        if existing.is_some_and(|book| hashes.get(&book.id) == Some(&file_hash)) {
+            return Ok(PlannedImport::skipped(path, SkipReason::Unchanged));
+        }
+
+        let metadata = match self.extract_metadata(&path) {
+            Ok(metadata) => metadata,
+            Err(e) => return Ok(PlannedImport::skipped(path, unreadable(&e))),
+        };
+        let mut book = self.metadata_extractor.to_book(&path, metadata);
+
+        let action = match existing {
+            Some(existing) => {
+                self.keep_library_state(&mut book, existing.clone()).await?;
+                PlannedAction::Update {
+                    book_id: existing.id,
+                    changes: changed_fields(existing, &book),
+                }
+            }
+            None => PlannedAction::Create,
+        };
+        Ok(PlannedImport {
+            path,
+            action,
+            book: Some(book),
+            file_hash: Some(file_hash),
+            include: true,
+        })
+    }
+
+    /// Carries the user's corrections and the listening history of the
+    /// library's copy of a book over to `book`, freshly read from its file
+    async fn keep_library_state(&self, book: &mut Book, existing: Book) -> Result<()> {
+        let edited = books::get_user_edited_fields(&self.pool, existing.id)
+            .await
+            .map_err(LibraryError::Database)?;
+        keep_user_edits(book, &existing, &edited);
+        book.id = existing.id;
+        book.added_date = existing.added_date;
+        book.last_played = existing.last_played;
+        book.play_count = existing.play_count;
+        book.is_favorite = existing.is_favorite;
+        book.rating = existing.rating;
+        let added = std::mem::replace(&mut book.tags, existing.tags);
+        for tag in added {
+            if !book.tags.contains(&tag) {
+                book.tags.push(tag);
+            }
+        }
+        Ok(())
+    }
+
     /// Extract metadata from an audio file
     fn extract_metadata(&self, path: &Path) -> Result<ExtractedMetadata> {
         debug!("Extracting metadata from: {}", path.display());
@@ -308,6 +976,18 @@ impl BookImporter {
     }
 
     /// Find a book by its file path
+    /// Finds a book with this content fingerprint whose file is gone
+    async fn find_moved(&self, content_hash: &str) -> Result<Option<Book>> {
+        let matches = books::find_books_by_content_hash(&self.pool, content_hash)
+            .await
+            .map_err(LibraryError::Database)?;
+        let moved = matches.into_iter().find(|book| !book.file_path.exists());
+        if let Some(book) = &moved {
+            info!("{} moved from {}", book.title, book.file_path.display());
+        }
+        Ok(moved)
+    }
+
     async fn find_by_path(&self, path: &Path) -> Result<Option<Book>> {
         let path_str = path.to_string_lossy().to_string();
 
@@ -325,14 +1005,66 @@ impl BookImporter {
     }
 }
 
+/// Stores the chapters read from a book's file
+///
+/// Books that already have chapters keep them, since they may have been
+/// edited by hand since the book was first imported.
+pub(crate) async fn store_chapters(pool: &DbPool, book: &Book, found: &[Chapter]) -> Result<()> {
+    if found.is_empty() {
+        return Ok(());
+    }
+    let stored = chapters::get_book_chapters(pool, book.id)
+        .await
+        .map_err(LibraryError::Database)?;
+    if !stored.is_empty() {
+        debug!("Keeping the stored chapters of {}", book.title);
+        return Ok(());
+    }
+    chapters::replace_book_chapters(pool, book.id, found)
+        .await
+        .map_err(LibraryError::Database)?;
+    debug!("Stored {} chapters for {}", found.len(), book.title);
+    Ok(())
+}
+
+/// Names of the fields whose values differ between the library's copy of a
+/// book and the same book read again from its file
+fn changed_fields(existing: &Book, book: &Book) -> Vec<String> {
+    let fields = [
+        ("title", existing.title != book.title),
+        ("author", existing.author != book.author),
+        ("narrator", existing.narrator != book.narrator),
+        ("series", existing.series != book.series),
+        (
+            "series_position",
+            existing.series_position != book.series_position,
+        ),
+        ("description", existing.description != book.description),
+        ("genre", existing.genre != book.genre),
+        ("duration", existing.duration != book.duration),
+        ("file_size", existing.file_size != book.file_size),
+    ];
+    fields
+        .into_iter()
+        .filter(|(_, changed)| *changed)
+        .map(|(name, _)| name.to_string())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
     use storystream_database::{
         connection::{connect, DatabaseConfig},
         migrations::run_migrations,
     };
+    use storystream_media_formats::wav::wav_bytes;
     use tempfile::{NamedTempFile, TempDir};
+    use tracing::field::Field;
+    use tracing::span::{Attributes, Id};
+    use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
 
     async fn setup_test_db() -> Result<(DbPool, NamedTempFile)> {
         let temp_file = NamedTempFile::new().map_err(LibraryError::Io)?;
@@ -369,13 +1101,15 @@ mod tests {
             .with_author("Custom Author")
             .with_extract_cover(false)
             .with_overwrite_existing(true)
-            .with_skip_on_error(true);
+            .with_skip_on_error(true)
+            .with_tag("librivox:59");
 
         assert_eq!(options.title, Some("Custom Title".to_string()));
         assert_eq!(options.author, Some("Custom Author".to_string()));
         assert!(!options.extract_cover);
         assert!(options.overwrite_existing);
         assert!(options.skip_on_error);
+        assert_eq!(options.tags, vec!["librivox:59".to_string()]);
     }
 
     #[tokio::test]
@@ -460,6 +1194,47 @@ mod tests {
         Ok(())
     }
 
+    /// Names and fields of the spans created while it is the default subscriber
+    #[derive(Clone, Default)]
+    struct SpanRecorder(Arc<Mutex<Vec<(&'static str, String)>>>);
+
+    impl<S: tracing::Subscriber> Layer<S> for SpanRecorder {
+        fn on_new_span(&self, attrs: &Attributes<'_>, _: &Id, _: Context<'_, S>) {
+            let mut fields = Vec::new();
+            attrs.record(&mut |field: &Field, value: &dyn fmt::Debug| {
+                fields.push(format!("{}={:?}", field.name(), value));
+            });
+            self.0
+                .lock()
+                .unwrap()
+                .push((attrs.metadata().name(), fields.join(" ")));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_import_spans() -> Result<()> {
+        let (pool, _temp) = setup_test_db().await?;
+        let importer = BookImporter::new(pool);
+        let missing = PathBuf::from("/nonexistent.mp3");
+
+        let recorder = SpanRecorder::default();
+        let subscriber = tracing_subscriber::registry().with(recorder.clone());
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let options = ImportOptions::new().with_skip_on_error(true);
+        importer.import_files(&[&missing], options).await?;
+
+        let spans = recorder.0.lock().unwrap().clone();
+        assert_eq!(
+            spans,
+            vec![
+                ("import_files", "files=1".to_string()),
+                ("import_file", "path=/nonexistent.mp3".to_string()),
+            ]
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_import_files_without_skip_fails_on_first_error() -> Result<()> {
         let (pool, _temp) = setup_test_db().await?;
@@ -479,6 +1254,83 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_large_import_creates_books_in_one_batch() -> Result<()> {
+        let (pool, _temp) = setup_test_db().await?;
+        let importer = BookImporter::new(pool.clone());
+        let dir = TempDir::new()?;
+        let mut paths = Vec::new();
+        for i in 0..BATCH_IMPORT_THRESHOLD + 2 {
+            let path = dir.path().join(format!("part_{:02}.wav", i));
+            std::fs::write(&path, wav_bytes(800, i as u8))?;
+            paths.push(path);
+        }
+        // Listed twice; the second is skipped, not sent to the database
+        paths.push(paths[0].clone());
+
+        let options = ImportOptions::new().with_skip_on_error(true);
+        let imported = importer.import_files(&paths, options).await?;
+
+        assert_eq!(imported.len(), BATCH_IMPORT_THRESHOLD + 2);
+        assert_eq!(
+            books::count_books(&pool).await?,
+            (BATCH_IMPORT_THRESHOLD + 2) as i64
+        );
+        assert_eq!(
+            books::get_file_hashes(&pool).await?.len(),
+            BATCH_IMPORT_THRESHOLD + 2
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_moved_file_keeps_its_book() -> Result<()> {
+        use storystream_core::Bookmark;
+        use storystream_database::queries::bookmarks;
+
+        let (pool, _db_file) = setup_test_db().await?;
+        let importer = BookImporter::new(pool.clone());
+        let dir = TempDir::new()?;
+        let old_path = dir.path().join("Unsorted").join("book.wav");
+        std::fs::create_dir_all(old_path.parent().unwrap())?;
+        std::fs::write(&old_path, wav_bytes(8_000, 3))?;
+
+        let book = importer
+            .import_file(&old_path, ImportOptions::new())
+            .await?;
+        let mut favorite = book.clone();
+        favorite.is_favorite = true;
+        books::update_book(&pool, &favorite).await?;
+        bookmarks::create_bookmark(&pool, &Bookmark::new(book.id, Duration::from_millis(500)))
+            .await?;
+
+        let new_path = dir.path().join("Author").join("Title.wav");
+        std::fs::create_dir_all(new_path.parent().unwrap())?;
+        std::fs::rename(&old_path, &new_path)?;
+        let moved = importer
+            .import_file(&new_path, ImportOptions::new())
+            .await?;
+
+        assert_eq!(moved.id, book.id);
+        assert_eq!(moved.file_path, new_path.canonicalize()?);
+        assert!(moved.is_favorite);
+        assert_eq!(books::count_books(&pool).await?, 1);
+        assert_eq!(
+            bookmarks::get_book_bookmarks(&pool, book.id).await?.len(),
+            1
+        );
+
+        // A copy next to a file still in place is a new book, reported as a duplicate
+        let copy = dir.path().join("Backup.wav");
+        std::fs::copy(&new_path, &copy)?;
+        let second = importer.import_file(&copy, ImportOptions::new()).await?;
+        assert_ne!(second.id, book.id);
+        let duplicates = books::find_duplicate_books(&pool).await?;
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].books.len(), 2);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_scan_empty_directory() -> Result<()> {
         let (pool, _temp) = setup_test_db().await?;
@@ -492,6 +1344,236 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_preview_then_commit() -> Result<()> {
+        let (pool, _temp) = setup_test_db().await?;
+        let importer = BookImporter::new(pool.clone());
+        let dir = TempDir::new()?;
+        for (name, fill) in [("kept.wav", 1), ("new.wav", 2), ("excluded.wav", 3)] {
+            std::fs::write(dir.path().join(name), wav_bytes(800, fill))?;
+        }
+        let changed = dir.path().join("changed.wav");
+        std::fs::write(&changed, wav_bytes(800, 4))?;
+        importer
+            .import_files(
+                &[dir.path().join("kept.wav"), changed.clone()],
+                ImportOptions::default(),
+            )
+            .await?;
+        std::fs::write(&changed, wav_bytes(16_000, 4))?;
+        let notes = dir.path().join("notes.txt");
+        std::fs::write(&notes, b"not audio")?;
+
+        let mut plan = importer.preview(&[dir.path(), notes.as_path()]).await?;
+        assert_eq!(books::list_books(&pool).await?.len(), 2);
+        assert_eq!((plan.creates(), plan.updates(), plan.skipped()), (2, 1, 2));
+
+        let action = |plan: &ImportPlan, name: &str| {
+            plan.items
+                .iter()
+                .find(|item| item.path.ends_with(name))
+                .map(|item| item.action.clone())
+        };
+        assert_eq!(
+            action(&plan, "kept.wav"),
+            Some(PlannedAction::Skip(SkipReason::Unchanged))
+        );
+        assert_eq!(
+            action(&plan, "notes.txt"),
+            Some(PlannedAction::Skip(SkipReason::Unsupported))
+        );
+        assert!(matches!(
+            action(&plan, "changed.wav"),
+            Some(PlannedAction::Update { changes, .. }) if changes == ["duration", "file_size"]
+        ));
+
+        let excluded = plan
+            .items
+            .iter()
+            .position(|item| item.path.ends_with("excluded.wav"))
+            .unwrap();
+        assert!(!plan.toggle(excluded));
+
+        // Plans survive being saved and loaded
+        let json = serde_json::to_string(&plan).unwrap();
+        let plan: ImportPlan = serde_json::from_str(&json).unwrap();
+
+        let committed = importer.commit(&plan).await?;
+        assert_eq!(committed.len(), 2);
+        let library = books::list_books(&pool).await?;
+        assert_eq!(library.len(), 3);
+        assert!(!library
+            .iter()
+            .any(|book| book.file_path.ends_with("excluded.wav")));
+        assert_eq!(
+            books::get_file_hashes(&pool).await?.len(),
+            3,
+            "committed files are hashed"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_incremental_preview_trusts_size_and_mtime() -> Result<()> {
+        let (pool, _temp) = setup_test_db().await?;
+        let dir = TempDir::new()?;
+        let touched = dir.path().join("touched.wav");
+        let resized = dir.path().join("resized.wav");
+        std::fs::write(&touched, wav_bytes(800, 1))?;
+        std::fs::write(&resized, wav_bytes(800, 2))?;
+        BookImporter::new(pool.clone())
+            .import_files(&[&touched, &resized], ImportOptions::default())
+            .await?;
+
+        // Retagged in place, with the modification time put back
+        let modified = std::fs::metadata(&touched)?.modified()?;
+        std::fs::write(&touched, wav_bytes(800, 9))?;
+        std::fs::File::options()
+            .write(true)
+            .open(&touched)?
+            .set_modified(modified)?;
+        std::fs::write(&resized, wav_bytes(1600, 2))?;
+        std::fs::write(dir.path().join("added.wav"), wav_bytes(800, 3))?;
+
+        let action = |plan: &ImportPlan, name: &str| {
+            plan.items
+                .iter()
+                .find(|item| item.path.ends_with(name))
+                .map(|item| item.action.clone())
+        };
+        let plan = BookImporter::new(pool.clone())
+            .preview(&[dir.path()])
+            .await?;
+        assert_eq!(
+            action(&plan, "touched.wav"),
+            Some(PlannedAction::Skip(SkipReason::Unchanged))
+        );
+        assert!(matches!(
+            action(&plan, "resized.wav"),
+            Some(PlannedAction::Update { .. })
+        ));
+        assert_eq!(action(&plan, "added.wav"), Some(PlannedAction::Create));
+
+        // A full scan reads every file and sees through the old timestamp
+        let plan = BookImporter::new(pool)
+            .with_scan_mode(ScanMode::Full)
+            .preview(&[dir.path()])
+            .await?;
+        assert!(matches!(
+            action(&plan, "touched.wav"),
+            Some(PlannedAction::Update { .. })
+        ));
+        assert_eq!((plan.creates(), plan.updates()), (1, 2));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_commit_refuses_changed_files() -> Result<()> {
+        let (pool, _temp) = setup_test_db().await?;
+        let importer = BookImporter::new(pool.clone());
+        let dir = TempDir::new()?;
+        let first = dir.path().join("first.wav");
+        let second = dir.path().join("second.wav");
+        std::fs::write(&first, wav_bytes(800, 1))?;
+        std::fs::write(&second, wav_bytes(800, 2))?;
+
+        let plan = importer.preview(&[dir.path()]).await?;
+        assert_eq!(plan.creates(), 2);
+        std::fs::write(&second, wav_bytes(800, 9))?;
+
+        let result = importer.commit(&plan).await;
+        assert!(
+            matches!(&result, Err(LibraryError::StalePlan(path)) if path.ends_with("second.wav"))
+        );
+        assert!(books::list_books(&pool).await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_store_chapters_keeps_stored_ones() -> Result<()> {
+        let (pool, _db_file) = setup_test_db().await?;
+        let book = Book::new(
+            "Chaptered".to_string(),
+            PathBuf::from("/books/chaptered.m4b"),
+            1000,
+            storystream_core::Duration::from_seconds(600),
+        );
+        books::create_book(&pool, &book).await?;
+        let chapter = |title: &str, start: u64, end: u64| {
+            Chapter::new(
+                book.id,
+                title.to_string(),
+                0,
+                storystream_core::Duration::from_seconds(start),
+                storystream_core::Duration::from_seconds(end),
+            )
+        };
+
+        // A file without chapters stores nothing
+        store_chapters(&pool, &book, &[]).await?;
+        assert!(chapters::get_book_chapters(&pool, book.id)
+            .await?
+            .is_empty());
+
+        store_chapters(&pool, &book, &[chapter("From the file", 0, 600)]).await?;
+        let mut edited = chapters::get_book_chapters(&pool, book.id).await?;
+        assert_eq!(edited.len(), 1);
+
+        // Chapters edited since are not replaced by a re-import
+        edited[0].title = "Renamed".to_string();
+        chapters::update_chapter(&pool, &edited[0]).await?;
+        store_chapters(&pool, &book, &[chapter("From the file", 0, 600)]).await?;
+        let stored = chapters::get_book_chapters(&pool, book.id).await?;
+        assert_eq!(stored[0].title, "Renamed");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_import_folder_as_one_book() -> Result<()> {
+        let (pool, _db_file) = setup_test_db().await?;
+        let dir = TempDir::new()?;
+        let folder = dir.path().join("Downloaded");
+        std::fs::create_dir(&folder)?;
+        std::fs::write(folder.join("02 The Middle.wav"), wav_bytes(16_000, 0))?;
+        std::fs::write(folder.join("01 Opening.wav"), wav_bytes(8_000, 0))?;
+        std::fs::write(folder.join("cover.jpg"), b"not audio")?;
+
+        let importer = BookImporter::new(pool.clone());
+        let options = ImportOptions::new()
+            .with_title("The Book")
+            .with_author("An Author")
+            .with_tag("librivox:59");
+        let book = importer.import_folder(&folder, options.clone()).await?;
+        assert_eq!(book.title, "The Book");
+        assert_eq!(book.author.as_deref(), Some("An Author"));
+        assert_eq!(book.file_path, folder.canonicalize()?);
+        assert_eq!(book.duration, Duration::from_seconds(3));
+        assert_eq!(book.tags, vec!["librivox:59".to_string()]);
+
+        let stored = chapters::get_book_chapters(&pool, book.id).await?;
+        let titles: Vec<&str> = stored.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, ["01 Opening", "02 The Middle"]);
+        assert_eq!(stored[1].start_time, Duration::from_seconds(1));
+        assert_eq!(stored[1].end_time, Duration::from_seconds(3));
+
+        assert!(matches!(
+            importer.import_folder(&folder, options.clone()).await,
+            Err(LibraryError::ImportFailed(_))
+        ));
+        let again = importer
+            .import_folder(&folder, options.with_overwrite_existing(true))
+            .await?;
+        assert_eq!(again.id, book.id);
+
+        let empty = dir.path().join("Empty");
+        std::fs::create_dir(&empty)?;
+        assert!(matches!(
+            importer.import_folder(&empty, ImportOptions::new()).await,
+            Err(LibraryError::InvalidFile(_))
+        ));
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_import_directory_nonexistent() -> Result<()> {
         let (pool, _temp) = setup_test_db().await?;
@@ -0,0 +1,284 @@
+//! Audiobookshelf exports
+//!
+//! Reads the JSON the Audiobookshelf server API returns: library items (from
+//! `/api/libraries/<id>/items`, optionally with `userMediaProgress`), and the
+//! `mediaProgress` and `bookmarks` lists from `/api/me`. A dump can hold any
+//! of these together; progress and bookmarks are joined to their items by
+//! library item ID.
+
+use super::{ExternalBookmark, ExternalImporter, ExternalProgress};
+use crate::error::{LibraryError, Result};
+use serde::Deserialize;
+use std::path::PathBuf;
+use storystream_core::{Duration, Timestamp};
+
+/// Reads Audiobookshelf API dumps
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AudiobookshelfImporter;
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AbsExport {
+    Items(Vec<AbsItem>),
+    Dump(AbsDump),
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct AbsDump {
+    #[serde(alias = "results", alias = "items")]
+    library_items: Vec<AbsItem>,
+    media_progress: Vec<AbsProgress>,
+    bookmarks: Vec<AbsBookmark>,
+    user: Option<AbsUser>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct AbsUser {
+    media_progress: Vec<AbsProgress>,
+    bookmarks: Vec<AbsBookmark>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AbsItem {
+    id: String,
+    #[serde(default)]
+    path: Option<PathBuf>,
+    #[serde(default)]
+    media: AbsMedia,
+    #[serde(default)]
+    user_media_progress: Option<AbsProgress>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct AbsMedia {
+    metadata: AbsMetadata,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct AbsMetadata {
+    title: Option<String>,
+    author_name: Option<String>,
+    authors: Vec<AbsAuthor>,
+}
+
+#[derive(Deserialize)]
+struct AbsAuthor {
+    name: String,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AbsProgress {
+    library_item_id: String,
+    #[serde(default)]
+    episode_id: Option<String>,
+    /// Seconds
+    #[serde(default)]
+    current_time: f64,
+    #[serde(default)]
+    is_finished: bool,
+    /// Milliseconds since the epoch
+    #[serde(default)]
+    last_update: Option<i64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AbsBookmark {
+    library_item_id: String,
+    /// Seconds
+    time: f64,
+    #[serde(default)]
+    title: Option<String>,
+}
+
+impl ExternalImporter for AudiobookshelfImporter {
+    fn name(&self) -> &'static str {
+        "Audiobookshelf"
+    }
+
+    fn parse(&self, content: &str) -> Result<Vec<ExternalProgress>> {
+        let export: AbsExport = serde_json::from_str(content).map_err(|e| {
+            LibraryError::ImportFailed(format!("Not an Audiobookshelf export: {}", e))
+        })?;
+        let dump = match export {
+            AbsExport::Items(library_items) => AbsDump {
+                library_items,
+                ..Default::default()
+            },
+            AbsExport::Dump(dump) => dump,
+        };
+
+        let user = dump.user.unwrap_or_default();
+        let mut progress = dump.media_progress;
+        progress.extend(user.media_progress);
+        let mut bookmarks = dump.bookmarks;
+        bookmarks.extend(user.bookmarks);
+
+        if dump.library_items.is_empty() && progress.is_empty() && bookmarks.is_empty() {
+            return Err(LibraryError::ImportFailed(
+                "No library items, progress or bookmarks found in the Audiobookshelf export"
+                    .to_string(),
+            ));
+        }
+
+        // Podcast episode progress has no book of its own to land on
+        progress.retain(|p| p.episode_id.is_none());
+
+        let mut entries: Vec<ExternalProgress> = Vec::new();
+        for item in dump.library_items {
+            let metadata = item.media.metadata;
+            let author = metadata.author_name.or_else(|| {
+                let names: Vec<String> = metadata.authors.into_iter().map(|a| a.name).collect();
+                (!names.is_empty()).then(|| names.join(", "))
+            });
+            let mut entry = ExternalProgress {
+                source: item.id,
+                path: item.path,
+                title: metadata.title,
+                author,
+                ..Default::default()
+            };
+            if let Some(item_progress) = item.user_media_progress {
+                apply_progress(&mut entry, &item_progress);
+            }
+            entries.push(entry);
+        }
+
+        for item_progress in &progress {
+            let index = entry_index(&mut entries, &item_progress.library_item_id);
+            apply_progress(&mut entries[index], item_progress);
+        }
+        for bookmark in bookmarks {
+            let Some(position) = seconds(bookmark.time) else {
+                continue;
+            };
+            let index = entry_index(&mut entries, &bookmark.library_item_id);
+            entries[index].bookmarks.push(ExternalBookmark {
+                position,
+                title: bookmark.title,
+            });
+        }
+
+        entries.retain(|e| e.position.is_some() || e.finished || !e.bookmarks.is_empty());
+        Ok(entries)
+    }
+}
+
+/// Index of the entry for a library item, adding a bare one when the dump has no item
+fn entry_index(entries: &mut Vec<ExternalProgress>, item_id: &str) -> usize {
+    match entries.iter().position(|e| e.source == item_id) {
+        Some(index) => index,
+        None => {
+            entries.push(ExternalProgress {
+                source: item_id.to_string(),
+                ..Default::default()
+            });
+            entries.len() - 1
+        }
+    }
+}
+
+/// Keeps the most recently updated progress when an item has several
+fn apply_progress(entry: &mut ExternalProgress, progress: &AbsProgress) {
+    let updated = progress.last_update.map(Timestamp::from_millis);
+    if let (Some(current), Some(new)) = (entry.last_updated, updated) {
+        if current.as_millis() >= new.as_millis() {
+            return;
+        }
+    }
+    entry.position = seconds(progress.current_time);
+    entry.finished = progress.is_finished;
+    entry.last_updated = updated;
+}
+
+fn seconds(value: f64) -> Option<Duration> {
+    (value.is_finite() && value > 0.0)
+        .then(|| Duration::from_millis((value * 1000.0).round() as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_items_with_me_dump() {
+        let json = r#"{
+            "libraryItems": [
+                {
+                    "id": "li_1",
+                    "path": "/audiobooks/Jane Austen/Emma",
+                    "media": { "metadata": { "title": "Emma", "authorName": "Jane Austen" } }
+                },
+                {
+                    "id": "li_2",
+                    "path": "/audiobooks/Unstarted",
+                    "media": { "metadata": { "title": "Unstarted" } }
+                }
+            ],
+            "user": {
+                "mediaProgress": [
+                    { "libraryItemId": "li_1", "currentTime": 125.5, "isFinished": false, "lastUpdate": 1700000000000 },
+                    { "libraryItemId": "li_3", "currentTime": 10, "isFinished": true },
+                    { "libraryItemId": "li_4", "episodeId": "ep_1", "currentTime": 30 }
+                ],
+                "bookmarks": [
+                    { "libraryItemId": "li_1", "time": 60, "title": "Ball" }
+                ]
+            }
+        }"#;
+
+        let entries = AudiobookshelfImporter.parse(json).unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let emma = &entries[0];
+        assert_eq!(emma.title.as_deref(), Some("Emma"));
+        assert_eq!(emma.author.as_deref(), Some("Jane Austen"));
+        assert_eq!(
+            emma.path,
+            Some(PathBuf::from("/audiobooks/Jane Austen/Emma"))
+        );
+        assert_eq!(emma.position, Some(Duration::from_millis(125_500)));
+        assert_eq!(
+            emma.last_updated,
+            Some(Timestamp::from_millis(1_700_000_000_000))
+        );
+        assert_eq!(
+            emma.bookmarks,
+            vec![ExternalBookmark {
+                position: Duration::from_seconds(60),
+                title: Some("Ball".to_string()),
+            }]
+        );
+
+        // Progress without an item is kept so it shows up as unmatched
+        assert_eq!(entries[1].source, "li_3");
+        assert!(entries[1].finished);
+        assert_eq!(entries[1].title, None);
+    }
+
+    #[test]
+    fn test_parse_item_list_with_embedded_progress() {
+        let json = r#"[{
+            "id": "li_1",
+            "media": { "metadata": { "title": "Persuasion", "authors": [{ "name": "Jane Austen" }] } },
+            "userMediaProgress": { "libraryItemId": "li_1", "currentTime": 42, "isFinished": true }
+        }]"#;
+
+        let entries = AudiobookshelfImporter.parse(json).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].author.as_deref(), Some("Jane Austen"));
+        assert!(entries[0].finished);
+    }
+
+    #[test]
+    fn test_parse_rejects_other_json() {
+        assert!(AudiobookshelfImporter.parse("{}").is_err());
+        assert!(AudiobookshelfImporter.parse("not json").is_err());
+    }
+}
@@ -0,0 +1,226 @@
+//! Generic CSV progress files
+//!
+//! The first row names the columns, in any order and case:
+//!
+//! - `path` (or `file`, `file_path`): the audio file or its folder
+//! - `title` and `author`, used when the path does not match
+//! - `position` (or `position_secs`): seconds, or `h:mm:ss`
+//! - `finished` (or `completed`): `true`/`false`, `yes`/`no` or `1`/`0`
+//!
+//! A `path` or `title` column is required; the others are optional. The
+//! per-book CSV written by `storystream stats --csv` reads back as-is.
+
+use super::{ExternalImporter, ExternalProgress};
+use crate::error::{LibraryError, Result};
+use std::path::PathBuf;
+use storystream_core::Duration;
+
+/// Reads progress from CSV files
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CsvImporter;
+
+/// Column positions found in the header row
+#[derive(Default)]
+struct Columns {
+    path: Option<usize>,
+    title: Option<usize>,
+    author: Option<usize>,
+    position: Option<usize>,
+    finished: Option<usize>,
+}
+
+impl Columns {
+    fn from_header(header: &[String]) -> Result<Self> {
+        let mut columns = Columns::default();
+        for (index, name) in header.iter().enumerate() {
+            let name = name.trim().to_lowercase().replace([' ', '-'], "_");
+            let slot = match name.as_str() {
+                "path" | "file" | "file_path" => &mut columns.path,
+                "title" => &mut columns.title,
+                "author" => &mut columns.author,
+                "position" | "position_secs" | "position_seconds" => &mut columns.position,
+                "finished" | "is_finished" | "completed" => &mut columns.finished,
+                _ => continue,
+            };
+            slot.get_or_insert(index);
+        }
+
+        if columns.path.is_none() && columns.title.is_none() {
+            return Err(LibraryError::ImportFailed(
+                "CSV header needs a path or title column".to_string(),
+            ));
+        }
+        Ok(columns)
+    }
+}
+
+impl ExternalImporter for CsvImporter {
+    fn name(&self) -> &'static str {
+        "CSV"
+    }
+
+    fn parse(&self, content: &str) -> Result<Vec<ExternalProgress>> {
+        let mut records = parse_records(content)?.into_iter();
+        let Some((_, header)) = records.next() else {
+            return Err(LibraryError::ImportFailed("CSV file is empty".to_string()));
+        };
+        let columns = Columns::from_header(&header)?;
+
+        let mut entries = Vec::new();
+        for (line, record) in records {
+            if record.iter().all(|field| field.trim().is_empty()) {
+                continue;
+            }
+            let field = |index: Option<usize>| {
+                index
+                    .and_then(|i| record.get(i))
+                    .map(|value| value.trim())
+                    .filter(|value| !value.is_empty())
+            };
+            let invalid = |what: &str, value: &str| {
+                LibraryError::ImportFailed(format!("Line {}: invalid {} '{}'", line, what, value))
+            };
+
+            let position = field(columns.position)
+                .map(|value| parse_position(value).ok_or_else(|| invalid("position", value)))
+                .transpose()?;
+            let finished = field(columns.finished)
+                .map(|value| parse_flag(value).ok_or_else(|| invalid("finished flag", value)))
+                .transpose()?
+                .unwrap_or(false);
+
+            entries.push(ExternalProgress {
+                source: format!("line {}", line),
+                path: field(columns.path).map(PathBuf::from),
+                title: field(columns.title).map(str::to_string),
+                author: field(columns.author).map(str::to_string),
+                position,
+                finished,
+                last_updated: None,
+                bookmarks: Vec::new(),
+            });
+        }
+        Ok(entries)
+    }
+}
+
+/// Seconds, possibly fractional, or any duration `Duration` parses
+fn parse_position(value: &str) -> Option<Duration> {
+    match value.parse::<f64>() {
+        Ok(secs) if secs.is_finite() && secs >= 0.0 => {
+            Some(Duration::from_millis((secs * 1000.0).round() as u64))
+        }
+        Ok(_) => None,
+        Err(_) => value.parse().ok(),
+    }
+}
+
+fn parse_flag(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "y" | "1" | "x" => Some(true),
+        "false" | "no" | "n" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Splits CSV text into records, each with the line number it starts on
+///
+/// Handles quoted fields with doubled quotes, commas and line breaks.
+fn parse_records(content: &str) -> Result<Vec<(usize, Vec<String>)>> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut start_line = 1;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\n' {
+            line += 1;
+        }
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                c => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push((start_line, std::mem::take(&mut record)));
+                start_line = line;
+            }
+            c => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(LibraryError::ImportFailed(format!(
+            "Line {}: unterminated quoted field",
+            start_line
+        )));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((start_line, record));
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rows() {
+        let csv = "Title,Author,Position,Finished,Path\r\n\
+                   \"Emma, Volume 1\",Jane Austen,125.5,no,\n\
+                   Persuasion,,1:02:03,yes,/books/persuasion.mp3\n\
+                   \n\
+                   \"A \"\"Quoted\"\"\nTitle\",,,,\n";
+
+        let entries = CsvImporter.parse(csv).unwrap();
+        assert_eq!(entries.len(), 3);
+
+        assert_eq!(entries[0].source, "line 2");
+        assert_eq!(entries[0].title.as_deref(), Some("Emma, Volume 1"));
+        assert_eq!(entries[0].author.as_deref(), Some("Jane Austen"));
+        assert_eq!(entries[0].position, Some(Duration::from_millis(125_500)));
+        assert!(!entries[0].finished);
+        assert_eq!(entries[0].path, None);
+
+        assert_eq!(entries[1].position, Some(Duration::from_seconds(3723)));
+        assert!(entries[1].finished);
+        assert_eq!(
+            entries[1].path,
+            Some(PathBuf::from("/books/persuasion.mp3"))
+        );
+
+        assert_eq!(entries[2].source, "line 5");
+        assert_eq!(entries[2].title.as_deref(), Some("A \"Quoted\"\nTitle"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(CsvImporter.parse("").is_err());
+        assert!(CsvImporter.parse("position,finished\n10,yes\n").is_err());
+
+        let err = CsvImporter
+            .parse("title,position\nEmma,soon\n")
+            .unwrap_err();
+        assert!(err.to_string().contains("Line 2"), "{}", err);
+
+        assert!(CsvImporter.parse("title\n\"Emma\n").is_err());
+    }
+}
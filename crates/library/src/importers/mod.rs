@@ -0,0 +1,612 @@
+//! Listening progress from other audiobook apps
+//!
+//! An [`ExternalImporter`] reads another app's export into
+//! [`ExternalProgress`] entries. [`ProgressImporter`] matches those to books
+//! in the library, first by file path and then by comparing title and author
+//! loosely, and saves positions, finished flags and bookmarks in one
+//! transaction.
+
+mod audiobookshelf;
+mod csv;
+
+pub use self::audiobookshelf::AudiobookshelfImporter;
+pub use self::csv::CsvImporter;
+
+use crate::error::Result;
+use serde::Serialize;
+use std::collections::hash_map::{Entry, HashMap};
+use std::path::PathBuf;
+use storystream_core::{Book, BookId, Bookmark, Duration, PlaybackState, Timestamp};
+use storystream_database::{
+    export::restore_progress,
+    queries::{bookmarks, books, playback},
+    DbPool,
+};
+use tracing::info;
+
+/// Lowest title similarity (0 to 1) accepted as the same book
+const TITLE_THRESHOLD: f64 = 0.8;
+
+/// Lowest author similarity accepted when both sides name an author
+const AUTHOR_THRESHOLD: f64 = 0.5;
+
+/// Scores closer than this to the best match make it ambiguous
+const AMBIGUITY_MARGIN: f64 = 0.05;
+
+/// Bookmarks this close to an existing one are treated as duplicates
+const BOOKMARK_TOLERANCE_MS: u64 = 1000;
+
+/// Listening data for one book as read from another app
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExternalProgress {
+    /// Where the entry came from, such as a line number or item ID
+    pub source: String,
+    pub path: Option<PathBuf>,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub position: Option<Duration>,
+    pub finished: bool,
+    /// When the other app last saved this progress, if it says
+    pub last_updated: Option<Timestamp>,
+    pub bookmarks: Vec<ExternalBookmark>,
+}
+
+/// A bookmark read from another app
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalBookmark {
+    pub position: Duration,
+    pub title: Option<String>,
+}
+
+/// Reads listening data exported by another app
+pub trait ExternalImporter {
+    /// Name of the app or format, for reports
+    fn name(&self) -> &'static str;
+
+    /// Reads every entry in an export
+    fn parse(&self, content: &str) -> Result<Vec<ExternalProgress>>;
+}
+
+/// How an entry was matched to a book
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchKind {
+    /// The book's file, or the folder holding it
+    Path,
+    /// Title and author
+    Title,
+}
+
+/// An entry matched to a library book
+#[derive(Debug, Clone, Serialize)]
+pub struct MatchedProgress {
+    pub source: String,
+    pub book_id: BookId,
+    pub title: String,
+    pub matched_by: MatchKind,
+    /// Position saved for the book; `None` when the library's own progress is newer
+    pub position: Option<Duration>,
+    pub finished: bool,
+    /// Bookmarks added, not counting ones the book already has
+    pub bookmarks: usize,
+}
+
+/// An entry that could not be matched to a book
+#[derive(Debug, Clone, Serialize)]
+pub struct UnmatchedProgress {
+    pub source: String,
+    pub title: Option<String>,
+    pub reason: String,
+}
+
+/// What a progress import matched and saved
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProgressImportReport {
+    pub format: &'static str,
+    /// Nothing was saved
+    pub dry_run: bool,
+    pub matched: Vec<MatchedProgress>,
+    pub unmatched: Vec<UnmatchedProgress>,
+    /// Books whose position was saved
+    pub positions: usize,
+    pub bookmarks: usize,
+}
+
+/// Imports listening progress from other apps into the library
+pub struct ProgressImporter {
+    pool: DbPool,
+}
+
+impl ProgressImporter {
+    /// Create an importer writing to `pool`
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Matches an export's entries to books and saves their progress
+    ///
+    /// A position only replaces the library's own when it is newer, or, for
+    /// entries without a timestamp, further into the book. With `dry_run`
+    /// the report is built but nothing is saved.
+    pub async fn import(
+        &self,
+        importer: &dyn ExternalImporter,
+        content: &str,
+        dry_run: bool,
+    ) -> Result<ProgressImportReport> {
+        let entries = importer.parse(content)?;
+        let library = books::list_books(&self.pool).await?;
+        let matcher = BookMatcher::new(&library);
+
+        let mut states: HashMap<BookId, PlaybackState> = playback::list_playback_states(&self.pool)
+            .await?
+            .into_iter()
+            .map(|state| (state.book_id, state))
+            .collect();
+        let mut known_bookmarks: HashMap<BookId, Vec<Duration>> = HashMap::new();
+        let mut positions: Vec<BookId> = Vec::new();
+        let mut new_bookmarks: Vec<Bookmark> = Vec::new();
+
+        let mut report = ProgressImportReport {
+            format: importer.name(),
+            dry_run,
+            ..Default::default()
+        };
+
+        for entry in entries {
+            let (book, matched_by) = match matcher.find(&entry) {
+                Ok(found) => found,
+                Err(reason) => {
+                    report.unmatched.push(UnmatchedProgress {
+                        source: entry.source,
+                        title: entry.title,
+                        reason,
+                    });
+                    continue;
+                }
+            };
+
+            let position = progress_position(&entry, book)
+                .filter(|&position| replaces(&entry, position, states.get(&book.id)));
+            if let Some(position) = position {
+                let state = states
+                    .entry(book.id)
+                    .or_insert_with(|| PlaybackState::new(book.id));
+                state.position = position;
+                state.is_playing = false;
+                state.last_updated = entry.last_updated.unwrap_or_else(Timestamp::now);
+                if !positions.contains(&book.id) {
+                    positions.push(book.id);
+                }
+            }
+
+            let mut added = 0;
+            if !entry.bookmarks.is_empty() {
+                let known = match known_bookmarks.entry(book.id) {
+                    Entry::Occupied(known) => known.into_mut(),
+                    Entry::Vacant(slot) => slot.insert(
+                        bookmarks::get_book_bookmarks(&self.pool, book.id)
+                            .await?
+                            .into_iter()
+                            .map(|b| b.position)
+                            .collect(),
+                    ),
+                };
+                for external in &entry.bookmarks {
+                    if !book.duration.is_zero() && external.position > book.duration {
+                        continue;
+                    }
+                    if known.iter().any(|&p| near(p, external.position)) {
+                        continue;
+                    }
+                    let mut bookmark = Bookmark::new(book.id, external.position);
+                    bookmark.title = external.title.clone();
+                    known.push(external.position);
+                    new_bookmarks.push(bookmark);
+                    added += 1;
+                }
+            }
+
+            report.matched.push(MatchedProgress {
+                source: entry.source,
+                book_id: book.id,
+                title: book.title.clone(),
+                matched_by,
+                position,
+                finished: entry.finished,
+                bookmarks: added,
+            });
+        }
+
+        report.positions = positions.len();
+        report.bookmarks = new_bookmarks.len();
+
+        if !dry_run && (report.positions > 0 || report.bookmarks > 0) {
+            let positions: Vec<PlaybackState> = positions
+                .iter()
+                .filter_map(|id| states.remove(id))
+                .collect();
+            restore_progress(&self.pool, &positions, &new_bookmarks).await?;
+            info!(
+                "Imported {} position(s) and {} bookmark(s) from {}",
+                report.positions,
+                report.bookmarks,
+                importer.name()
+            );
+        }
+
+        Ok(report)
+    }
+}
+
+/// The position an entry puts the book at; finished books go to the end
+fn progress_position(entry: &ExternalProgress, book: &Book) -> Option<Duration> {
+    if entry.finished && !book.duration.is_zero() {
+        return Some(book.duration);
+    }
+    let position = entry.position.filter(|p| !p.is_zero())?;
+    if book.duration.is_zero() {
+        Some(position)
+    } else {
+        Some(position.min(book.duration))
+    }
+}
+
+/// Whether an imported position should replace the saved state
+fn replaces(entry: &ExternalProgress, position: Duration, current: Option<&PlaybackState>) -> bool {
+    let Some(current) = current else {
+        return true;
+    };
+    match entry.last_updated {
+        Some(updated) => updated.as_millis() > current.last_updated.as_millis(),
+        None => position > current.position,
+    }
+}
+
+fn near(a: Duration, b: Duration) -> bool {
+    a.as_millis().abs_diff(b.as_millis()) < BOOKMARK_TOLERANCE_MS
+}
+
+/// Finds the library book an external entry refers to
+struct BookMatcher<'a> {
+    books: &'a [Book],
+    /// Title key and normalized author of each book, in the same order
+    keys: Vec<(TitleKey, Option<String>)>,
+}
+
+impl<'a> BookMatcher<'a> {
+    fn new(books: &'a [Book]) -> Self {
+        let keys = books
+            .iter()
+            .map(|b| {
+                (
+                    TitleKey::new(&b.title),
+                    b.author.as_deref().map(normalize_author),
+                )
+            })
+            .collect();
+        Self { books, keys }
+    }
+
+    /// Match by path, then by title and author; the error says why neither worked
+    fn find(&self, entry: &ExternalProgress) -> std::result::Result<(&'a Book, MatchKind), String> {
+        if let Some(path) = &entry.path {
+            if let Some(book) = self.books.iter().find(|b| &b.file_path == path) {
+                return Ok((book, MatchKind::Path));
+            }
+            // Apps that treat a folder as one book record the folder
+            let mut in_folder = self
+                .books
+                .iter()
+                .filter(|b| b.file_path.parent() == Some(path.as_path()));
+            if let (Some(book), None) = (in_folder.next(), in_folder.next()) {
+                return Ok((book, MatchKind::Path));
+            }
+        }
+
+        let Some(title) = entry.title.as_deref().filter(|t| !t.trim().is_empty()) else {
+            return Err(match &entry.path {
+                Some(path) => format!("no book at {}", path.display()),
+                None => "no path or title to match".to_string(),
+            });
+        };
+        let title = TitleKey::new(title);
+        let author = entry.author.as_deref().map(normalize_author);
+
+        let mut scored: Vec<(f64, usize)> = self
+            .keys
+            .iter()
+            .enumerate()
+            .filter_map(|(index, (book_title, book_author))| {
+                let title_score = title.similarity(book_title);
+                if title_score < TITLE_THRESHOLD {
+                    return None;
+                }
+                let author_score = match (&author, book_author) {
+                    (Some(a), Some(b)) => similarity(a, b),
+                    _ => 0.0,
+                };
+                if author.is_some() && book_author.is_some() && author_score < AUTHOR_THRESHOLD {
+                    return None;
+                }
+                Some((title_score + author_score / 4.0, index))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        match scored.as_slice() {
+            [] => Err("no book with a similar title".to_string()),
+            [(best, index), (second, other), ..] if best - second < AMBIGUITY_MARGIN => {
+                Err(format!(
+                    "matches several books: {}, {}",
+                    self.books[*index].title, self.books[*other].title
+                ))
+            }
+            [(_, index), ..] => Ok((&self.books[*index], MatchKind::Title)),
+        }
+    }
+}
+
+/// Lowercases, drops punctuation and a leading article
+fn normalize(text: &str) -> String {
+    let cleaned: String = text
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect();
+    let words: Vec<&str> = cleaned.split_whitespace().collect();
+    let words = match words.as_slice() {
+        [first, rest @ ..] if !rest.is_empty() && matches!(*first, "the" | "a" | "an") => rest,
+        words => words,
+    };
+    words.join(" ")
+}
+
+/// Author names with their words sorted, so "Austen, Jane" matches "Jane Austen"
+fn normalize_author(author: &str) -> String {
+    let normalized = normalize(author);
+    let mut words: Vec<&str> = normalized.split_whitespace().collect();
+    words.sort_unstable();
+    words.join(" ")
+}
+
+/// A normalized title, plus the part before its subtitle when it has one
+struct TitleKey {
+    full: String,
+    main: Option<String>,
+}
+
+impl TitleKey {
+    fn new(title: &str) -> Self {
+        let main = title
+            .split_once([':', '('])
+            .or_else(|| title.split_once(" - "))
+            .map(|(main, _)| normalize(main))
+            .filter(|main| !main.is_empty());
+        Self {
+            full: normalize(title),
+            main,
+        }
+    }
+
+    /// Compares whole titles, and titles with their subtitles dropped
+    fn similarity(&self, other: &TitleKey) -> f64 {
+        let whole = similarity(&self.full, &other.full);
+        let main = match (&self.main, &other.main) {
+            (Some(a), Some(b)) => similarity(a, b),
+            (Some(a), None) => similarity(a, &other.full),
+            (None, Some(b)) => similarity(&self.full, b),
+            (None, None) => 0.0,
+        };
+        whole.max(main)
+    }
+}
+
+/// Dice coefficient over the letter pairs in each word, from 0 to 1
+fn similarity(a: &str, b: &str) -> f64 {
+    if a == b {
+        return 1.0;
+    }
+    let a = letter_pairs(a);
+    let mut b = letter_pairs(b);
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    let total = a.len() + b.len();
+    let mut shared = 0;
+    for pair in &a {
+        if let Some(index) = b.iter().position(|p| p == pair) {
+            b.swap_remove(index);
+            shared += 1;
+        }
+    }
+    2.0 * shared as f64 / total as f64
+}
+
+fn letter_pairs(text: &str) -> Vec<(char, char)> {
+    text.split_whitespace()
+        .flat_map(|word| {
+            let chars: Vec<char> = word.chars().collect();
+            chars
+                .windows(2)
+                .map(|pair| (pair[0], pair[1]))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::LibraryError;
+    use storystream_database::{
+        connection::{connect, DatabaseConfig},
+        migrations::run_migrations,
+    };
+    use tempfile::NamedTempFile;
+
+    async fn setup_test_db() -> Result<(DbPool, NamedTempFile)> {
+        let temp_file = NamedTempFile::new().map_err(LibraryError::Io)?;
+        let db_path = temp_file
+            .path()
+            .to_str()
+            .ok_or_else(|| LibraryError::InvalidFile("Invalid path encoding".to_string()))?;
+
+        let pool = connect(DatabaseConfig::new(db_path)).await?;
+        run_migrations(&pool).await?;
+        Ok((pool, temp_file))
+    }
+
+    fn book(title: &str, author: &str, path: &str) -> Book {
+        let mut book = Book::new(
+            title.to_string(),
+            PathBuf::from(path),
+            1000,
+            Duration::from_seconds(3600),
+        );
+        book.author = Some(author.to_string());
+        book
+    }
+
+    fn entry(title: &str, author: Option<&str>) -> ExternalProgress {
+        ExternalProgress {
+            source: title.to_string(),
+            title: Some(title.to_string()),
+            author: author.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_matcher_prefers_path_then_title() {
+        let library = vec![
+            book(
+                "Pride and Prejudice",
+                "Jane Austen",
+                "/books/austen/pride.m4b",
+            ),
+            book("Emma", "Jane Austen", "/books/emma/emma.mp3"),
+            book("Dune (Unabridged)", "Frank Herbert", "/books/dune.m4b"),
+            book("Dune Messiah", "Frank Herbert", "/books/messiah.m4b"),
+        ];
+        let matcher = BookMatcher::new(&library);
+        let title = |found: std::result::Result<(&Book, MatchKind), String>| {
+            found.map(|(book, kind)| (book.title.clone(), kind))
+        };
+
+        let mut by_folder = entry("Something else entirely", None);
+        by_folder.path = Some(PathBuf::from("/books/emma"));
+        assert_eq!(
+            title(matcher.find(&by_folder)),
+            Ok(("Emma".to_string(), MatchKind::Path))
+        );
+
+        let fuzzy = entry("Pride & Prejudice", Some("Austen, Jane"));
+        assert_eq!(
+            title(matcher.find(&fuzzy)),
+            Ok(("Pride and Prejudice".to_string(), MatchKind::Title))
+        );
+
+        let subtitled = entry("Dune: Dune Chronicles, Book 1", Some("Frank Herbert"));
+        assert_eq!(
+            title(matcher.find(&subtitled)),
+            Ok(("Dune (Unabridged)".to_string(), MatchKind::Title))
+        );
+
+        assert!(matcher
+            .find(&entry("Pride and Prejudice", Some("Seth Grahame-Smith")))
+            .is_err());
+        assert!(matcher.find(&entry("Persuasion", None)).is_err());
+        assert!(matcher.find(&ExternalProgress::default()).is_err());
+    }
+
+    #[test]
+    fn test_matcher_reports_ambiguous_titles() {
+        let library = vec![
+            book("Emma", "Jane Austen", "/books/a.mp3"),
+            book("Emma", "Jane Austen", "/books/b.mp3"),
+        ];
+        let err = BookMatcher::new(&library)
+            .find(&entry("Emma", None))
+            .unwrap_err();
+        assert!(err.contains("several"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_import_saves_progress_and_reports_unmatched() -> Result<()> {
+        let (pool, _temp) = setup_test_db().await?;
+        let emma = book("Emma", "Jane Austen", "/books/emma.mp3");
+        let pride = book("Pride and Prejudice", "Jane Austen", "/books/pride.mp3");
+        books::create_book(&pool, &emma).await?;
+        books::create_book(&pool, &pride).await?;
+
+        // Local progress further along than the CSV's wins
+        let mut local = PlaybackState::new(pride.id);
+        local.set_position(Duration::from_seconds(1200));
+        local.volume = 40;
+        playback::create_playback_state(&pool, &local).await?;
+
+        let csv = "title,author,position,finished\n\
+                   Emma,Jane Austen,600,no\n\
+                   Pride and Prejudice,,300,no\n\
+                   Persuasion,Jane Austen,10,yes\n";
+        let importer = ProgressImporter::new(pool.clone());
+
+        let preview = importer.import(&CsvImporter, csv, true).await?;
+        assert!(preview.dry_run);
+        assert_eq!(preview.positions, 1);
+        assert!(playback::get_playback_state(&pool, emma.id).await.is_err());
+
+        let report = importer.import(&CsvImporter, csv, false).await?;
+        assert_eq!(report.matched.len(), 2);
+        assert_eq!(report.matched[1].position, None);
+        assert_eq!(report.unmatched.len(), 1);
+        assert_eq!(report.unmatched[0].source, "line 4");
+
+        let saved = playback::get_playback_state(&pool, emma.id).await?;
+        assert_eq!(saved.position, Duration::from_seconds(600));
+        let kept = playback::get_playback_state(&pool, pride.id).await?;
+        assert_eq!(kept.position, Duration::from_seconds(1200));
+
+        // Finishing the book raises the position to its end and keeps settings
+        let finished = "title,finished\nPride and Prejudice,yes\n";
+        importer.import(&CsvImporter, finished, false).await?;
+        let done = playback::get_playback_state(&pool, pride.id).await?;
+        assert_eq!(done.position, pride.duration);
+        assert_eq!(done.volume, 40);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_import_skips_duplicate_bookmarks() -> Result<()> {
+        let (pool, _temp) = setup_test_db().await?;
+        let emma = book("Emma", "Jane Austen", "/audiobooks/Emma/emma.mp3");
+        books::create_book(&pool, &emma).await?;
+
+        let json = r#"{
+            "libraryItems": [{ "id": "li_1", "path": "/audiobooks/Emma", "media": { "metadata": { "title": "Emma" } } }],
+            "bookmarks": [
+                { "libraryItemId": "li_1", "time": 60, "title": "Ball" },
+                { "libraryItemId": "li_1", "time": 60.4, "title": "Ball again" },
+                { "libraryItemId": "li_1", "time": 90000, "title": "Past the end" }
+            ]
+        }"#;
+        let importer = ProgressImporter::new(pool.clone());
+
+        let report = importer
+            .import(&AudiobookshelfImporter, json, false)
+            .await?;
+        assert_eq!(report.matched[0].matched_by, MatchKind::Path);
+        assert_eq!(report.bookmarks, 1);
+
+        let again = importer
+            .import(&AudiobookshelfImporter, json, false)
+            .await?;
+        assert_eq!(again.bookmarks, 0);
+        let saved = bookmarks::get_book_bookmarks(&pool, emma.id).await?;
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].title.as_deref(), Some("Ball"));
+        Ok(())
+    }
+}
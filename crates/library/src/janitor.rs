@@ -0,0 +1,422 @@
+//! Cleanup of files a crash leaves behind
+//!
+//! Writes that replace a file in one step go through a temporary file first:
+//! the config and the download history write `.tmp` files, the caches write
+//! `.partial` entries, and downloaders keep `.part` files until they finish.
+//! A crash in between leaves those temporaries behind, as it can leave a
+//! SQLite `-wal`/`-shm` pair next to a database that has since been moved
+//! or deleted.
+//!
+//! [`Janitor`] finds such leftovers and removes the ones old enough that no
+//! running writer can still own them. Sidecars of a database that exists are
+//! never removed: SQLite needs them to recover transactions, and the active
+//! database is one of those. Neither are `.part` files the download history
+//! can still resume, once [`Janitor::protect_resumable`] has read it.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use storystream_config::Config;
+use storystream_network::{DownloadHistory, NetworkResult};
+use tracing::{info, warn};
+use walkdir::WalkDir;
+
+/// Folders under the first library folder that downloads are saved to
+pub const DOWNLOAD_FOLDERS: [&str; 2] = ["Podcasts", "Audiobooks"];
+
+/// Suffixes SQLite adds to a database's path for its sidecar files
+const DATABASE_SIDECARS: [&str; 3] = ["-wal", "-shm", "-journal"];
+
+/// What a leftover file was for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LeftoverKind {
+    /// Temporary file of an atomic write
+    AtomicWrite,
+    /// Download that never finished
+    PartialDownload,
+    /// Cache entry that was never completed
+    PartialCacheEntry,
+    /// SQLite sidecar of a database that no longer exists
+    DatabaseSidecar,
+}
+
+impl LeftoverKind {
+    /// Returns a short description for reports
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Self::AtomicWrite => "temporary file",
+            Self::PartialDownload => "partial download",
+            Self::PartialCacheEntry => "partial cache entry",
+            Self::DatabaseSidecar => "orphaned database file",
+        }
+    }
+}
+
+/// A leftover file the janitor found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Leftover {
+    pub path: PathBuf,
+    pub kind: LeftoverKind,
+    pub bytes: u64,
+    /// Time since the file was last written
+    pub age: Duration,
+}
+
+/// What a cleanup removed
+#[derive(Debug, Clone, Default)]
+pub struct JanitorReport {
+    pub removed: Vec<Leftover>,
+    /// Leftovers that could not be removed, with the reason
+    pub failed: Vec<(Leftover, String)>,
+}
+
+impl JanitorReport {
+    /// Total size of the removed files
+    pub fn bytes_freed(&self) -> u64 {
+        self.removed.iter().map(|leftover| leftover.bytes).sum()
+    }
+}
+
+/// Finds and removes files left behind by interrupted writes
+#[derive(Debug, Clone)]
+pub struct Janitor {
+    max_age: Duration,
+    /// Folders to look in, and whether to look in their subfolders
+    dirs: Vec<(PathBuf, bool)>,
+    /// Databases whose folder is checked for orphaned sidecars
+    databases: Vec<PathBuf>,
+    /// Files never removed, such as downloads that will be resumed
+    protected: HashSet<PathBuf>,
+}
+
+impl Janitor {
+    /// How old a temporary must be before it counts as left behind
+    pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+    /// Creates a janitor with no folders to look in
+    pub fn new() -> Self {
+        Self {
+            max_age: Self::DEFAULT_MAX_AGE,
+            dirs: Vec::new(),
+            databases: Vec::new(),
+            protected: HashSet::new(),
+        }
+    }
+
+    /// Looks in the folders a configuration writes to: the config folder,
+    /// its caches, the download folders and the database's folder
+    ///
+    /// Downloads that can still be resumed are only kept once
+    /// [`protect_resumable`](Self::protect_resumable) has read the history.
+    pub fn for_config(config_dir: &Path, config: &Config) -> Self {
+        let mut janitor = Self::new()
+            .with_dir(config_dir)
+            .with_tree(config_dir.join("cache"))
+            .with_database(&config.library.database_path);
+        if let Some(root) = config.library.library_paths.first() {
+            for folder in DOWNLOAD_FOLDERS {
+                janitor = janitor.with_tree(root.join(folder));
+            }
+        }
+        janitor
+    }
+
+    /// Sets how old a temporary must be before it is removed
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Looks in `dir` itself
+    pub fn with_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dirs.push((dir.into(), false));
+        self
+    }
+
+    /// Looks in `dir` and every folder under it
+    pub fn with_tree(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dirs.push((dir.into(), true));
+        self
+    }
+
+    /// Checks the folder of the database at `path` for sidecars of
+    /// databases that are gone; this database's own are kept
+    pub fn with_database(mut self, path: impl Into<PathBuf>) -> Self {
+        self.databases.push(path.into());
+        self
+    }
+
+    /// Never removes `path`, whatever its name or age
+    pub fn protect(mut self, path: impl Into<PathBuf>) -> Self {
+        self.protected.insert(path.into());
+        self
+    }
+
+    /// Never removes the partial files of downloads that failed, as
+    /// `history` records them, since retrying continues them
+    ///
+    /// # Errors
+    ///
+    /// Returns the error reading the history; cleaning up without it could
+    /// remove a download that is still wanted.
+    pub async fn protect_resumable(mut self, history: &DownloadHistory) -> NetworkResult<Self> {
+        for path in history.resumable().await? {
+            self = self.protect(path);
+        }
+        Ok(self)
+    }
+
+    /// Lists the leftovers old enough to remove, without removing them
+    pub fn find(&self) -> Vec<Leftover> {
+        let mut found = Vec::new();
+        let mut seen = HashSet::new();
+        for (dir, recursive) in &self.dirs {
+            let depth = if *recursive { usize::MAX } else { 1 };
+            self.scan(dir, depth, false, &mut seen, &mut found);
+        }
+        // Only sidecars are looked for next to databases, which may sit in
+        // the working directory among the user's own files
+        for database in &self.databases {
+            let dir = match database.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir,
+                _ => Path::new("."),
+            };
+            self.scan(dir, 1, true, &mut seen, &mut found);
+        }
+        found
+    }
+
+    /// Adds the leftovers under `dir` to `found`, skipping files in `seen`
+    fn scan(
+        &self,
+        dir: &Path,
+        depth: usize,
+        sidecars: bool,
+        seen: &mut HashSet<PathBuf>,
+        found: &mut Vec<Leftover>,
+    ) {
+        let now = SystemTime::now();
+        let walk = WalkDir::new(dir).min_depth(1).max_depth(depth);
+        for entry in walk.into_iter().filter_map(Result::ok) {
+            if !entry.file_type().is_file() || seen.contains(entry.path()) {
+                continue;
+            }
+            let kind = if sidecars {
+                self.classify_sidecar(entry.path())
+            } else {
+                self.classify(entry.path())
+            };
+            let (Some(kind), Ok(metadata)) = (kind, entry.metadata()) else {
+                continue;
+            };
+            let age = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .unwrap_or_default();
+            if age >= self.max_age {
+                seen.insert(entry.path().to_path_buf());
+                found.push(Leftover {
+                    path: entry.path().to_path_buf(),
+                    kind,
+                    bytes: metadata.len(),
+                    age,
+                });
+            }
+        }
+    }
+
+    /// Removes the leftovers old enough to remove and logs what it did
+    pub fn clean(&self) -> JanitorReport {
+        let mut report = JanitorReport::default();
+        for leftover in self.find() {
+            match fs::remove_file(&leftover.path) {
+                Ok(()) => report.removed.push(leftover),
+                Err(e) => {
+                    warn!("Could not remove {}: {}", leftover.path.display(), e);
+                    report.failed.push((leftover, e.to_string()));
+                }
+            }
+        }
+        if !report.removed.is_empty() {
+            info!(
+                "Removed {} leftover file(s), {} bytes",
+                report.removed.len(),
+                report.bytes_freed()
+            );
+        }
+        report
+    }
+
+    /// Works out what a temporary file was for, `None` for files to keep
+    fn classify(&self, path: &Path) -> Option<LeftoverKind> {
+        if self.protected.contains(path) {
+            return None;
+        }
+        let name = path.file_name()?.to_str()?;
+        if name.ends_with(".part") {
+            Some(LeftoverKind::PartialDownload)
+        } else if name.starts_with('.') && name.ends_with(".partial") {
+            Some(LeftoverKind::PartialCacheEntry)
+        } else if name.ends_with(".tmp") || name.starts_with(".tmp") {
+            Some(LeftoverKind::AtomicWrite)
+        } else {
+            None
+        }
+    }
+
+    /// Returns `DatabaseSidecar` for sidecars whose database is gone
+    fn classify_sidecar(&self, path: &Path) -> Option<LeftoverKind> {
+        if self.protected.contains(path) {
+            return None;
+        }
+        let name = path.file_name()?.to_str()?;
+        let database = DATABASE_SIDECARS
+            .iter()
+            .find_map(|suffix| name.strip_suffix(suffix))?;
+        let main = path.with_file_name(database);
+        let orphaned = !main.exists() && !self.databases.contains(&main);
+        orphaned.then_some(LeftoverKind::DatabaseSidecar)
+    }
+}
+
+impl Default for Janitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use storystream_network::{DownloadOutcome, DownloadRecord};
+    use tempfile::TempDir;
+
+    /// Writes `name` under `dir` and backdates it by `age`
+    fn seed(dir: &Path, name: &str, age: Duration) -> PathBuf {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, b"leftover").unwrap();
+        let file = File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() - age).unwrap();
+        path
+    }
+
+    const OLD: Duration = Duration::from_secs(2 * 24 * 60 * 60);
+    const FRESH: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn test_removes_only_old_leftovers() {
+        let config = TempDir::new().unwrap();
+        let downloads = TempDir::new().unwrap();
+
+        let stale_tmp = seed(config.path(), ".tmpA1b2C3", OLD);
+        let stale_history = seed(config.path(), "downloads.jsonl.tmp", OLD);
+        let stale_entry = seed(config.path(), "cache/covers/.abc.partial", OLD);
+        let stale_part = seed(downloads.path(), "Show/episode.mp3.part", OLD);
+        let fresh_tmp = seed(config.path(), ".tmpXyZ", FRESH);
+        let config_file = seed(config.path(), "config.toml", OLD);
+        let cover = seed(config.path(), "cache/covers/abc", OLD);
+        let episode = seed(downloads.path(), "Show/episode.mp3", OLD);
+
+        let report = Janitor::new()
+            .with_dir(config.path())
+            .with_tree(config.path().join("cache"))
+            .with_tree(downloads.path())
+            .clean();
+
+        assert_eq!(report.removed.len(), 4);
+        assert!(report.failed.is_empty());
+        assert_eq!(report.bytes_freed(), 4 * 8);
+        for removed in [&stale_tmp, &stale_history, &stale_entry, &stale_part] {
+            assert!(!removed.exists(), "{} was kept", removed.display());
+        }
+        for kept in [&fresh_tmp, &config_file, &cover, &episode] {
+            assert!(kept.exists(), "{} was removed", kept.display());
+        }
+    }
+
+    #[test]
+    fn test_keeps_protected_files() {
+        let downloads = TempDir::new().unwrap();
+        let resumable = seed(downloads.path(), "book.m4b.part", OLD);
+        let abandoned = seed(downloads.path(), "other.m4b.part", OLD);
+
+        let janitor = Janitor::new()
+            .with_tree(downloads.path())
+            .protect(&resumable);
+        let found: Vec<PathBuf> = janitor.find().into_iter().map(|l| l.path).collect();
+
+        assert_eq!(found, vec![abandoned]);
+    }
+
+    #[tokio::test]
+    async fn test_config_keeps_resumable_downloads() {
+        let config_dir = TempDir::new().unwrap();
+        let library = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.library.library_paths = vec![library.path().to_path_buf()];
+        config.library.database_path = config_dir
+            .path()
+            .join("storystream.db")
+            .display()
+            .to_string();
+        let resumable = seed(library.path(), "Audiobooks/Book/01.mp3.part", OLD);
+        let abandoned = seed(library.path(), "Podcasts/Show/02.mp3.part", OLD);
+
+        let history =
+            DownloadHistory::new(config_dir.path().join(DownloadHistory::FILE_NAME)).unwrap();
+        history
+            .record(&DownloadRecord {
+                task_id: "book-01".to_string(),
+                url: "https://example.com/01.mp3".to_string(),
+                source_url: None,
+                destination: resumable.clone(),
+                bytes: 8,
+                duration_ms: 100,
+                attempts: 3,
+                outcome: DownloadOutcome::Failed,
+                error_code: Some(503),
+                error: Some("HTTP 503".to_string()),
+                finished_at: chrono::Utc::now(),
+            })
+            .await
+            .unwrap();
+
+        let janitor = Janitor::for_config(config_dir.path(), &config);
+        let mut found: Vec<PathBuf> = janitor.find().into_iter().map(|l| l.path).collect();
+        found.sort();
+        assert_eq!(found, vec![resumable.clone(), abandoned.clone()]);
+
+        let report = janitor.protect_resumable(&history).await.unwrap().clean();
+        assert_eq!(report.removed.len(), 1);
+        assert!(resumable.exists());
+        assert!(!abandoned.exists());
+    }
+
+    #[test]
+    fn test_database_sidecars_only_when_orphaned() {
+        let data = TempDir::new().unwrap();
+        let database = seed(data.path(), "storystream.db", OLD);
+        let wal = seed(data.path(), "storystream.db-wal", OLD);
+        let shm = seed(data.path(), "storystream.db-shm", OLD);
+        let other = seed(data.path(), "old.db", OLD);
+        let other_wal = seed(data.path(), "old.db-wal", OLD);
+        let orphan_wal = seed(data.path(), "moved.db-wal", OLD);
+        let orphan_shm = seed(data.path(), "moved.db-shm", OLD);
+
+        let leftovers = Janitor::new().with_database(&database).find();
+        let mut found: Vec<PathBuf> = leftovers.iter().map(|l| l.path.clone()).collect();
+        found.sort();
+
+        assert_eq!(found, vec![orphan_shm, orphan_wal]);
+        assert!(leftovers
+            .iter()
+            .all(|l| l.kind == LeftoverKind::DatabaseSidecar));
+        for kept in [&database, &wal, &shm, &other, &other_wal] {
+            assert!(kept.exists());
+        }
+    }
+}
@@ -3,17 +3,74 @@
 //! High-level orchestration layer that coordinates core, database, and media-engine.
 //! Provides business logic for book management, import, and playback.
 
+pub mod auto_import;
+pub mod bookmarks;
+pub mod download;
+pub mod duplicates;
+pub mod edit;
 pub mod error;
+pub mod fingerprint;
 pub mod import;
+pub mod importers;
+pub mod janitor;
+pub mod limits;
+pub mod loudness;
 pub mod manager;
 pub mod metadata;
+pub mod organize;
+pub mod parts;
+pub mod pipeline;
+pub mod reconcile;
+pub mod replace;
 pub mod scanner;
+pub mod share;
+pub mod silence;
+pub mod subscriptions;
+pub mod verify;
+pub mod volumes;
 
+pub use auto_import::{AutoImportReport, AutoImporter, ImportEvent};
+pub use bookmarks::{
+    export_all_bookmarks, export_book, export_file_name, BookmarkFormat, BookmarkStore,
+};
+pub use download::{BookDownloadProgress, ContentDownloader};
+pub use duplicates::{DuplicateGroup, DuplicateReason};
+pub use edit::{MetadataEdit, MetadataEditOutcome, TagWrite};
 pub use error::{LibraryError, LibraryResult};
-pub use import::{BookImporter, ImportOptions};
-pub use manager::{LibraryConfig as OtherLibraryConfig, LibraryManager};
+pub use fingerprint::{content_hash, Relocation};
+pub use import::{
+    BookImporter, ImportOptions, ImportPlan, PlannedAction, PlannedImport, SkipReason,
+};
+pub use importers::{
+    AudiobookshelfImporter, CsvImporter, ExternalImporter, ProgressImportReport, ProgressImporter,
+};
+pub use janitor::{Janitor, JanitorReport, Leftover, LeftoverKind};
+pub use limits::{LimitReached, ListeningLimits};
+pub use loudness::{LoudnessAnalyzer, LoudnessEvent, LoudnessFailure, LoudnessReport};
+pub use manager::{
+    LibraryConfig as OtherLibraryConfig, LibraryManager, NextSuggestion, PlaylistEvent,
+    PlaylistProgress, SuggestionReason,
+};
 pub use metadata::MetadataExtractor;
-pub use scanner::LibraryScanner;
+pub use organize::{OrganizeMode, OrganizePlan, OrganizeTemplate, PlannedMove};
+pub use parts::{book_files, folder_files};
+pub use pipeline::{
+    ImportPipeline, PipelineEvent, PipelineProgress, PipelineReport, PipelineStage, QueueDepths,
+};
+pub use reconcile::{known_files, LibraryReport, ModifiedFile};
+pub use replace::{ChapterOutcome, SourceReplacement};
+pub use scanner::{KnownFile, LibraryScanner, ScanCancel, ScanMode, ScanSummary, ScannerConfig};
+pub use share::{resolve_shared, SharedTarget};
+pub use silence::{ChapterSuggester, SilenceOptions, SuggestedChapter};
+pub use subscriptions::{
+    apply_feed_metadata, refresh_feed, store_episodes, FeedRefresh, PolicyAction, PolicyReport,
+    RefreshOutcome, RemovalReason, SubscriptionManager, PODCAST_TAG,
+};
+pub use verify::{
+    FileIssue, FileProblem, FileVerifier, SuggestedAction, VerifyDepth, VerifyEvent, VerifyReport,
+    VerifyScope,
+};
+pub use volumes::{VolumeChange, VolumeMonitor};
 
 /// Library configuration
 #[derive(Debug, Clone)]
@@ -24,6 +81,12 @@ pub struct LibraryConfig {
     pub watch_directories: Vec<String>,
     /// Automatically import new files
     pub auto_import: bool,
+    /// Open the database without changing it
+    pub read_only: bool,
+    /// Glob patterns for paths in the watch directories to leave alone
+    pub exclude_patterns: Vec<String>,
+    /// Leave alone hidden files and folders in the watch directories
+    pub ignore_hidden: bool,
 }
 
 impl Default for LibraryConfig {
@@ -32,6 +95,9 @@ impl Default for LibraryConfig {
             database_path: "storystream.db".to_string(),
             watch_directories: Vec::new(),
             auto_import: false,
+            read_only: false,
+            exclude_patterns: Vec::new(),
+            ignore_hidden: true,
         }
     }
 }
@@ -53,6 +119,29 @@ impl LibraryConfig {
         self.auto_import = enabled;
         self
     }
+
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    pub fn with_exclude_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.exclude_patterns.push(pattern.into());
+        self
+    }
+
+    pub fn with_ignore_hidden(mut self, ignore: bool) -> Self {
+        self.ignore_hidden = ignore;
+        self
+    }
+
+    /// Scanner settings for `watch_paths` that leave out what this
+    /// configuration excludes
+    pub fn scanner_config(&self, watch_paths: Vec<String>) -> ScannerConfig {
+        ScannerConfig::new(watch_paths)
+            .with_exclude_patterns(self.exclude_patterns.clone())
+            .with_ignore_hidden(self.ignore_hidden)
+    }
 }
 
 #[cfg(test)]
@@ -65,6 +154,7 @@ mod tests {
         assert_eq!(config.database_path, "storystream.db");
         assert!(config.watch_directories.is_empty());
         assert!(!config.auto_import);
+        assert!(config.ignore_hidden);
     }
 
     #[test]
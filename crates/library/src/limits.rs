@@ -0,0 +1,281 @@
+//! Daily listening limits
+//!
+//! [`ListeningLimits`] decides whether playback may go on under the
+//! `limits` config section: a number of minutes per local calendar day and
+//! a window of hours in which listening is allowed. It starts from what the
+//! history table holds for today, so restarting does not reset the budget,
+//! and counts the sessions the player records from then on.
+
+use crate::error::Result;
+use chrono::{Duration as ChronoDuration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::time::Duration;
+use storystream_config::LimitsConfig;
+use storystream_database::{queries::stats, DbPool};
+
+/// Why playback is not allowed right now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitReached {
+    /// Today's minutes are used up; they come back at midnight
+    BudgetSpent { resets_in: Duration },
+    /// It is outside the allowed hours
+    OutsideHours { opens_in: Duration },
+}
+
+impl LimitReached {
+    /// Time until listening is allowed again
+    pub fn wait(&self) -> Duration {
+        match self {
+            Self::BudgetSpent { resets_in } => *resets_in,
+            Self::OutsideHours { opens_in } => *opens_in,
+        }
+    }
+}
+
+impl fmt::Display for LimitReached {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BudgetSpent { .. } => write!(f, "Today's listening time is used up"),
+            Self::OutsideHours { .. } => write!(f, "It's not listening time right now"),
+        }
+    }
+}
+
+/// Listening allowed today and how much of it has been used
+#[derive(Debug, Clone)]
+pub struct ListeningLimits {
+    config: LimitsConfig,
+    /// Local day `listened` counts
+    day: NaiveDate,
+    listened: Duration,
+    /// Lifted with the override password until the player exits
+    unlocked: bool,
+}
+
+impl ListeningLimits {
+    /// Limits that have counted `listened` on the day of `now`
+    pub fn new(config: LimitsConfig, now: NaiveDateTime, listened: Duration) -> Self {
+        Self {
+            config,
+            day: now.date(),
+            listened,
+            unlocked: false,
+        }
+    }
+
+    /// Limits that have counted today's sessions in the history table
+    pub async fn load(pool: &DbPool, config: LimitsConfig) -> Result<Self> {
+        let today = stats::daily_listening(pool, 1).await?;
+        let minutes = today.first().map_or(0, |day| day.minutes);
+        Ok(Self::new(
+            config,
+            chrono::Local::now().naive_local(),
+            Duration::from_secs(u64::from(minutes) * 60),
+        ))
+    }
+
+    /// Whether any limit applies
+    pub fn is_active(&self) -> bool {
+        self.config.is_limited() && !self.unlocked
+    }
+
+    /// Whether a password can lift the limits
+    pub fn can_unlock(&self) -> bool {
+        self.config.override_password_sha256.is_some()
+    }
+
+    /// Counts a listening session that ran from `start` to `end`
+    ///
+    /// Only the part on the day of `end` counts; earlier days are over.
+    pub fn add_session(&mut self, start: NaiveDateTime, end: NaiveDateTime) {
+        self.roll_over(end);
+        let from = start.max(midnight(end.date()));
+        if let Ok(listened) = (end - from).to_std() {
+            self.listened += listened;
+        }
+    }
+
+    /// Checks whether playback may go on at `now`
+    ///
+    /// `playing_since` is the start of the session in progress, which has
+    /// not been added yet.
+    pub fn check(
+        &mut self,
+        now: NaiveDateTime,
+        playing_since: Option<NaiveDateTime>,
+    ) -> std::result::Result<(), LimitReached> {
+        self.roll_over(now);
+        if !self.is_active() {
+            return Ok(());
+        }
+
+        if let Some((start, end)) = self.config.allowed_window() {
+            let minute = now.hour() * 60 + now.minute();
+            let inside = if start <= end {
+                (start..end).contains(&minute)
+            } else {
+                minute >= start || minute < end
+            };
+            if !inside {
+                return Err(LimitReached::OutsideHours {
+                    opens_in: until_minute(now, start),
+                });
+            }
+        }
+
+        if self.config.daily_minutes > 0 {
+            if let Some(remaining) = self.remaining(now, playing_since) {
+                if remaining.is_zero() {
+                    return Err(LimitReached::BudgetSpent {
+                        resets_in: until_minute(now, 0),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Listening time left today, `None` without a daily cap
+    pub fn remaining(
+        &self,
+        now: NaiveDateTime,
+        playing_since: Option<NaiveDateTime>,
+    ) -> Option<Duration> {
+        if self.config.daily_minutes == 0 || !self.is_active() {
+            return None;
+        }
+        let in_progress = playing_since
+            .map(|since| since.max(midnight(now.date())))
+            .and_then(|since| (now - since).to_std().ok())
+            .unwrap_or_default();
+        let budget = Duration::from_secs(u64::from(self.config.daily_minutes) * 60);
+        Some(budget.saturating_sub(self.listened + in_progress))
+    }
+
+    /// Lifts the limits until the player exits if `password` matches
+    pub fn unlock(&mut self, password: &str) -> bool {
+        let Some(expected) = &self.config.override_password_sha256 else {
+            return false;
+        };
+        let hash = format!("{:x}", Sha256::digest(password.as_bytes()));
+        self.unlocked = hash.eq_ignore_ascii_case(expected);
+        self.unlocked
+    }
+
+    /// Starts counting afresh when `now` is on a later day
+    fn roll_over(&mut self, now: NaiveDateTime) {
+        if now.date() > self.day {
+            self.day = now.date();
+            self.listened = Duration::ZERO;
+        }
+    }
+}
+
+fn midnight(date: NaiveDate) -> NaiveDateTime {
+    date.and_time(NaiveTime::MIN)
+}
+
+/// Time from `now` until the next time the clock reads `minute` past midnight
+fn until_minute(now: NaiveDateTime, minute: u32) -> Duration {
+    let target = midnight(now.date()) + ChronoDuration::minutes(i64::from(minute));
+    let target = if target > now {
+        target
+    } else {
+        target + ChronoDuration::days(1)
+    };
+    (target - now).to_std().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 3, 14)
+            .and_then(|date| date.and_hms_opt(hour, minute, 0))
+            .unwrap()
+    }
+
+    fn minutes(n: u64) -> Duration {
+        Duration::from_secs(n * 60)
+    }
+
+    fn config(daily_minutes: u32, allowed_hours: Option<&str>) -> LimitsConfig {
+        LimitsConfig {
+            daily_minutes,
+            allowed_hours: allowed_hours.map(str::to_string),
+            // SHA-256 of "grown-up"
+            override_password_sha256: Some(format!("{:x}", Sha256::digest(b"grown-up"))),
+        }
+    }
+
+    #[test]
+    fn test_budget_counts_recorded_and_running_sessions() {
+        let mut limits = ListeningLimits::new(config(60, None), at(9, 0), minutes(30));
+        assert!(limits.check(at(9, 0), None).is_ok());
+
+        limits.add_session(at(9, 0), at(9, 20));
+        assert_eq!(limits.remaining(at(9, 20), None), Some(minutes(10)));
+
+        // The session in progress counts before it is recorded
+        assert!(limits.check(at(9, 29), Some(at(9, 20))).is_ok());
+        assert_eq!(
+            limits.check(at(9, 30), Some(at(9, 20))),
+            Err(LimitReached::BudgetSpent {
+                resets_in: Duration::from_secs((14 * 60 + 30) * 60)
+            })
+        );
+    }
+
+    #[test]
+    fn test_budget_resets_at_midnight() {
+        let mut limits = ListeningLimits::new(config(30, None), at(22, 0), minutes(30));
+        assert!(limits.check(at(23, 0), None).is_err());
+
+        let tomorrow = at(0, 10) + ChronoDuration::days(1);
+        // Only the part after midnight counts toward the new day
+        limits.add_session(at(23, 50), tomorrow);
+        assert_eq!(limits.remaining(tomorrow, None), Some(minutes(20)));
+        assert!(limits.check(tomorrow, None).is_ok());
+    }
+
+    #[test]
+    fn test_allowed_hours() {
+        let mut limits = ListeningLimits::new(config(0, Some("07:00-19:30")), at(6, 0), minutes(0));
+        assert_eq!(
+            limits.check(at(6, 0), None),
+            Err(LimitReached::OutsideHours {
+                opens_in: minutes(60)
+            })
+        );
+        assert!(limits.check(at(7, 0), None).is_ok());
+        assert!(limits.check(at(19, 29), None).is_ok());
+        assert_eq!(
+            limits.check(at(19, 30), None).map_err(|limit| limit.wait()),
+            Err(minutes(11 * 60 + 30))
+        );
+        // No daily cap is set
+        assert_eq!(limits.remaining(at(12, 0), None), None);
+
+        let mut overnight =
+            ListeningLimits::new(config(0, Some("20:00-06:00")), at(12, 0), minutes(0));
+        assert!(overnight.check(at(12, 0), None).is_err());
+        assert!(overnight.check(at(23, 0), None).is_ok());
+        assert!(overnight.check(at(5, 59), None).is_ok());
+    }
+
+    #[test]
+    fn test_password_lifts_limits() {
+        let mut limits = ListeningLimits::new(config(10, None), at(9, 0), minutes(10));
+        assert!(limits.can_unlock());
+        assert!(limits.check(at(9, 0), None).is_err());
+
+        assert!(!limits.unlock("please"));
+        assert!(limits.check(at(9, 0), None).is_err());
+
+        assert!(limits.unlock("grown-up"));
+        assert!(!limits.is_active());
+        assert!(limits.check(at(9, 0), None).is_ok());
+    }
+}
@@ -0,0 +1,389 @@
+//! Loudness analysis of the library
+//!
+//! Decodes each book once to measure how loud it is and stores the result
+//! with the book, together with the replay gain that brings it to the
+//! playback target, so levels can be evened out at playback without
+//! measuring first. Books measured before are skipped, which makes an interrupted run
+//! pick up where it stopped. Reads are paced to leave the disk to playback.
+
+use crate::error::{LibraryError, Result};
+use crate::scanner::ScanCancel;
+use crate::verify::VerifyScope;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use storystream_core::{Book, BookId};
+use storystream_database::queries::{books, loudness, LoudnessSummary};
+use storystream_database::DbPool;
+use storystream_media_formats::AudioAnalyzer;
+use storystream_resilience::Bulkhead;
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+use tracing::{info, instrument, warn};
+
+/// Books analyzed at once
+const DEFAULT_CONCURRENCY: usize = 2;
+
+/// Bytes read per second by all workers together
+const DEFAULT_READ_LIMIT: u64 = 8 * 1024 * 1024;
+
+/// Progress of an analysis run
+#[derive(Debug, Clone)]
+pub enum LoudnessEvent {
+    /// Analysis of `total` books began
+    Started { total: usize },
+    /// A book has been measured, or failed to
+    Analyzed {
+        done: usize,
+        total: usize,
+        title: String,
+    },
+    /// The run ended, having stored `analyzed` books
+    Finished { analyzed: usize, cancelled: bool },
+}
+
+/// A book whose loudness could not be measured
+#[derive(Debug, Clone)]
+pub struct LoudnessFailure {
+    pub book_id: BookId,
+    pub title: String,
+    pub error: String,
+}
+
+/// Outcome of an analysis run
+#[derive(Debug, Clone, Default)]
+pub struct LoudnessReport {
+    /// Books measured and stored by this run
+    pub analyzed: usize,
+    /// Books skipped because they were measured before
+    pub already_analyzed: usize,
+    /// Books that decoded to nothing but silence, which stay unmeasured
+    pub silent: usize,
+    pub failures: Vec<LoudnessFailure>,
+    /// Whether the run was cancelled before measuring every book
+    pub cancelled: bool,
+    /// Spread of loudness across the whole library after the run
+    pub summary: LoudnessSummary,
+}
+
+/// Measures the loudness of books in the background
+pub struct LoudnessAnalyzer {
+    pool: DbPool,
+    concurrency: usize,
+    read_limit: u64,
+    cancel: ScanCancel,
+    events: Option<mpsc::Sender<LoudnessEvent>>,
+}
+
+impl LoudnessAnalyzer {
+    pub fn new(pool: DbPool) -> Self {
+        Self {
+            pool,
+            concurrency: DEFAULT_CONCURRENCY,
+            read_limit: DEFAULT_READ_LIMIT,
+            cancel: ScanCancel::new(),
+            events: None,
+        }
+    }
+
+    /// Analyzes up to `books` books at once
+    pub fn with_concurrency(mut self, books: usize) -> Self {
+        self.concurrency = books;
+        self
+    }
+
+    /// Reads at most `bytes_per_sec` across all books, or as fast as the
+    /// disk allows with 0
+    pub fn with_read_limit(mut self, bytes_per_sec: u64) -> Self {
+        self.read_limit = bytes_per_sec;
+        self
+    }
+
+    /// Uses `cancel` to stop analysis instead of the analyzer's own token
+    pub fn with_cancel(mut self, cancel: ScanCancel) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Sends progress to `events` as books are measured
+    pub fn with_events(mut self, events: mpsc::Sender<LoudnessEvent>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Returns a token that cancels this analyzer's runs
+    pub fn cancel_handle(&self) -> ScanCancel {
+        self.cancel.clone()
+    }
+
+    /// Measures the books in `scope` that have no loudness stored yet
+    ///
+    /// Each result is stored as soon as it is measured, so cancelling loses
+    /// only the books being decoded at the time. Clear a book's value with
+    /// [`loudness::set_loudness`] to have it measured again.
+    #[instrument(name = "analyze_loudness", skip(self, scope))]
+    pub async fn analyze(&self, scope: VerifyScope) -> Result<LoudnessReport> {
+        let measured = loudness::get_loudness_levels(&self.pool).await?;
+        let (library, already_analyzed): (Vec<Book>, Vec<Book>) = self
+            .books_in(scope)
+            .await?
+            .into_iter()
+            .partition(|book| !measured.contains_key(&book.id));
+        let total = library.len();
+        info!(
+            "Analyzing loudness of {} book(s), {} already done",
+            total,
+            already_analyzed.len()
+        );
+        self.send(LoudnessEvent::Started { total }).await;
+
+        let analyzer =
+            Arc::new(AudioAnalyzer::new().map_err(|e| LibraryError::Other(e.to_string()))?);
+        let titles: HashMap<BookId, String> =
+            library.iter().map(|b| (b.id, b.title.clone())).collect();
+        let bytes_per_sec = self.read_limit / self.concurrency.max(1) as u64;
+
+        // Closing it on cancel is final, so each run gets its own
+        let bulkhead = Bulkhead::new(self.concurrency);
+        let mut tasks = JoinSet::new();
+        for book in library {
+            let bulkhead = bulkhead.clone();
+            let cancel = self.cancel.clone();
+            let analyzer = Arc::clone(&analyzer);
+            tasks.spawn(async move {
+                let _permit = bulkhead.acquire().await.ok()?;
+                if cancel.is_cancelled() {
+                    return None;
+                }
+                let path = book.file_path;
+                let stop = cancel.clone();
+                let measured = tokio::task::spawn_blocking(move || {
+                    let mut pace = Pace::new(bytes_per_sec);
+                    analyzer.loudness(&path, |bytes| {
+                        pace.wait(bytes);
+                        !stop.is_cancelled()
+                    })
+                })
+                .await
+                .ok()?;
+                // A cancelled pass measures nothing, not silence
+                if matches!(measured, Ok(None)) && cancel.is_cancelled() {
+                    return None;
+                }
+                Some((book.id, measured))
+            });
+        }
+
+        let mut report = LoudnessReport {
+            already_analyzed: already_analyzed.len(),
+            ..Default::default()
+        };
+        let mut done = 0;
+        while let Some(joined) = tasks.join_next().await {
+            if self.cancel.is_cancelled() && !bulkhead.is_closed() {
+                // Wakes the tasks still waiting for a slot so they give up
+                bulkhead.close();
+            }
+            let Some((book_id, measured)) = joined.ok().flatten() else {
+                continue;
+            };
+            let title = titles[&book_id].clone();
+
+            match measured {
+                Ok(Some(level)) => {
+                    loudness::set_loudness(&self.pool, book_id, Some(level.integrated_db)).await?;
+                    loudness::set_replay_gain(&self.pool, book_id, Some(level.replay_gain_db()))
+                        .await?;
+                    report.analyzed += 1;
+                }
+                Ok(None) => report.silent += 1,
+                Err(e) => {
+                    warn!("Could not measure loudness of {}: {}", title, e);
+                    report.failures.push(LoudnessFailure {
+                        book_id,
+                        title: title.clone(),
+                        error: e.to_string(),
+                    });
+                }
+            }
+            done += 1;
+            self.send(LoudnessEvent::Analyzed { done, total, title })
+                .await;
+        }
+
+        report.cancelled = self.cancel.is_cancelled() && done < total;
+        report.failures.sort_by(|a, b| a.title.cmp(&b.title));
+        report.summary = loudness::loudness_summary(&self.pool).await?;
+        self.send(LoudnessEvent::Finished {
+            analyzed: report.analyzed,
+            cancelled: report.cancelled,
+        })
+        .await;
+        Ok(report)
+    }
+
+    async fn books_in(&self, scope: VerifyScope) -> Result<Vec<Book>> {
+        match scope {
+            VerifyScope::All => Ok(books::list_books(&self.pool).await?),
+            VerifyScope::Books(ids) => {
+                let mut library = Vec::with_capacity(ids.len());
+                for id in ids {
+                    library.push(books::get_book(&self.pool, id).await?);
+                }
+                Ok(library)
+            }
+        }
+    }
+
+    async fn send(&self, event: LoudnessEvent) {
+        if let Some(tx) = &self.events {
+            let _ = tx.send(event).await;
+        }
+    }
+}
+
+/// Holds one worker's reads to a steady rate
+struct Pace {
+    bytes_per_sec: u64,
+    started: Instant,
+    read: u64,
+}
+
+impl Pace {
+    fn new(bytes_per_sec: u64) -> Self {
+        Self {
+            bytes_per_sec,
+            started: Instant::now(),
+            read: 0,
+        }
+    }
+
+    /// Counts `bytes` as read, sleeping while the worker is ahead of its rate
+    fn wait(&mut self, bytes: usize) {
+        if self.bytes_per_sec == 0 {
+            return;
+        }
+        self.read += bytes as u64;
+        let due = Duration::from_secs_f64(self.read as f64 / self.bytes_per_sec as f64);
+        if let Some(ahead) = due.checked_sub(self.started.elapsed()) {
+            std::thread::sleep(ahead);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use storystream_core::Duration as BookDuration;
+    use storystream_database::{
+        connection::{connect, DatabaseConfig},
+        migrations::run_migrations,
+    };
+    use storystream_media_formats::wav::wav_from_samples;
+    use tempfile::TempDir;
+
+    async fn setup(dir: &TempDir) -> DbPool {
+        let db_path = dir.path().join("library.db");
+        let pool = connect(DatabaseConfig::new(db_path.to_str().unwrap()))
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool
+    }
+
+    /// A 0.5s 8 kHz mono WAV file holding one repeated sample value
+    async fn add_book(pool: &DbPool, dir: &TempDir, name: &str, sample: i16) -> Book {
+        let wav = wav_from_samples(std::iter::repeat_n(sample, 4000), 8000);
+
+        let path = dir.path().join(name);
+        std::fs::write(&path, &wav).unwrap();
+        let book = Book::new(
+            name.to_string(),
+            path,
+            wav.len() as u64,
+            BookDuration::from_millis(500),
+        );
+        books::create_book(pool, &book).await.unwrap();
+        book
+    }
+
+    #[tokio::test]
+    async fn test_analyze_stores_and_resumes() {
+        let dir = TempDir::new().unwrap();
+        let pool = setup(&dir).await;
+        let loud = add_book(&pool, &dir, "loud.wav", i16::MAX / 2).await;
+        let quiet = add_book(&pool, &dir, "quiet.wav", i16::MAX / 20).await;
+        let silent = add_book(&pool, &dir, "silent.wav", 0).await;
+        let broken = add_book(&pool, &dir, "broken.wav", 0).await;
+        std::fs::write(&broken.file_path, [0x5a; 64]).unwrap();
+
+        let (tx, mut rx) = mpsc::channel(32);
+        let analyzer = LoudnessAnalyzer::new(pool.clone()).with_events(tx);
+        let report = analyzer.analyze(VerifyScope::All).await.unwrap();
+        assert_eq!((report.analyzed, report.silent), (2, 1));
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].book_id, broken.id);
+        assert!(!report.cancelled);
+
+        let stored = |id| loudness::get_loudness(&pool, id);
+        assert!((stored(loud.id).await.unwrap().unwrap() + 6.02).abs() < 0.05);
+        assert!((stored(quiet.id).await.unwrap().unwrap() + 26.02).abs() < 0.05);
+        assert_eq!(stored(silent.id).await.unwrap(), None);
+        // Both are brought to -18 dB
+        let gain = |id| loudness::get_replay_gain(&pool, id);
+        assert!((gain(loud.id).await.unwrap().unwrap() + 11.98).abs() < 0.05);
+        assert!((gain(quiet.id).await.unwrap().unwrap() - 8.02).abs() < 0.05);
+        assert_eq!(gain(silent.id).await.unwrap(), None);
+        assert_eq!(report.summary.analyzed, 2);
+        assert_eq!(report.summary.pending, 2);
+
+        // Measured books are not decoded again
+        let report = analyzer.analyze(VerifyScope::All).await.unwrap();
+        assert_eq!((report.analyzed, report.already_analyzed), (0, 2));
+
+        let mut events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            events.push(event);
+        }
+        assert!(matches!(events[0], LoudnessEvent::Started { total: 4 }));
+        assert!(matches!(
+            events.last(),
+            Some(LoudnessEvent::Finished {
+                analyzed: 0,
+                cancelled: false
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_analyze_cancelled() {
+        let dir = TempDir::new().unwrap();
+        let pool = setup(&dir).await;
+        add_book(&pool, &dir, "one.wav", 1000).await;
+
+        let analyzer = LoudnessAnalyzer::new(pool.clone());
+        analyzer.cancel_handle().cancel();
+        let report = analyzer.analyze(VerifyScope::All).await.unwrap();
+        assert!(report.cancelled);
+        assert_eq!(report.analyzed, 0);
+        assert!(loudness::get_loudness_levels(&pool)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_pace_holds_the_rate() {
+        let started = Instant::now();
+        let mut pace = Pace::new(10_000);
+        for _ in 0..10 {
+            pace.wait(200);
+        }
+        assert!(started.elapsed() >= Duration::from_millis(200));
+
+        let started = Instant::now();
+        let mut unlimited = Pace::new(0);
+        unlimited.wait(1 << 30);
+        assert!(started.elapsed() < Duration::from_millis(100));
+    }
+}
@@ -1,27 +1,70 @@
 // FILE: crates/library/src/manager.rs
 
+use crate::auto_import::{AutoImportReport, AutoImporter, ImportEvent};
+use crate::duplicates::{group_duplicates, DuplicateGroup};
+use crate::edit::{write_tags, MetadataEdit, MetadataEditOutcome, TagWrite};
 use crate::error::{LibraryError, Result};
-use crate::import::{BookImporter, ImportOptions};
-use crate::scanner::LibraryScanner;
+use crate::fingerprint::{self, Relocation};
+use crate::import::{BookImporter, ImportOptions, ImportPlan};
+use crate::loudness::{LoudnessAnalyzer, LoudnessReport};
+use crate::organize::{self, OrganizeMode, OrganizePlan, OrganizeTemplate};
+use crate::pipeline::ImportPipeline;
+use crate::reconcile::{self, LibraryReport};
+use crate::replace::{self, ChapterOutcome, NewSource, SourceReplacement};
+use crate::scanner::{LibraryScanner, ScanCancel};
+use crate::silence::{ChapterSuggester, SuggestedChapter};
+use crate::verify::{FileVerifier, VerifyDepth, VerifyReport, VerifyScope};
 pub use crate::LibraryConfig;
-use log::info;
+use std::future::Future;
 use std::path::Path;
-use storystream_core::{Book, BookId, Duration};
+use std::sync::Arc;
+use storystream_core::types::chapters::normalize_chapters;
+use storystream_core::{
+    AppError, Book, BookId, CacheManager, Chapter, Duration, PlaybackState, PlaylistId,
+    PlaylistSession,
+};
 use storystream_database::{
-    connection::{connect, DatabaseConfig},
+    connection::{connect, is_read_only, DatabaseConfig},
     migrations::run_migrations,
-    queries::books,
+    queries::{books, chapters, playback, playlists},
     search::search_books,
     DbPool,
 }; // Changed from tracing::info
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Auto-import events held until the front end takes them
+const IMPORT_EVENT_BUFFER: usize = 64;
 
 /// High-level library management
 pub struct LibraryManager {
     pool: DbPool,
-    #[allow(dead_code)]
     config: LibraryConfig,
     importer: BookImporter,
     scanner: Option<LibraryScanner>,
+    /// Disk cache for slow analyses, such as chapter suggestions
+    cache: Option<Arc<CacheManager>>,
+    /// Import of the scanner's changes, while watching with `auto_import`
+    auto_import: Option<AutoImportTask>,
+    /// What the auto-import did, until taken by the front end
+    import_events: Option<mpsc::Receiver<ImportEvent>>,
+}
+
+/// The scanner over `config`'s watch directories, if it has any
+fn watch_scanner(config: &LibraryConfig) -> Option<LibraryScanner> {
+    if config.watch_directories.is_empty() {
+        return None;
+    }
+    Some(LibraryScanner::with_config(
+        config.scanner_config(config.watch_directories.clone()),
+    ))
+}
+
+/// An [`AutoImporter`] running on the watcher's events
+struct AutoImportTask {
+    cancel: ScanCancel,
+    task: JoinHandle<AutoImportReport>,
 }
 
 impl LibraryManager {
@@ -33,7 +76,7 @@ impl LibraryManager {
         );
 
         // Connect to database
-        let db_config = DatabaseConfig::new(&config.database_path);
+        let db_config = DatabaseConfig::new(&config.database_path).with_read_only(config.read_only);
         let pool = connect(db_config).await?;
 
         // Run migrations
@@ -42,20 +85,45 @@ impl LibraryManager {
         let importer = BookImporter::new(pool.clone());
 
         // Initialize scanner if watch directories configured
-        let scanner = if !config.watch_directories.is_empty() {
-            Some(LibraryScanner::new(config.watch_directories.clone()))
-        } else {
-            None
-        };
+        let scanner = watch_scanner(&config);
 
         Ok(Self {
             pool,
             config,
             importer,
             scanner,
+            cache: None,
+            auto_import: None,
+            import_events: None,
         })
     }
 
+    /// Create a library manager on an already migrated pool
+    pub fn with_pool(pool: DbPool) -> Self {
+        Self {
+            importer: BookImporter::new(pool.clone()),
+            pool,
+            config: LibraryConfig::default(),
+            scanner: None,
+            cache: None,
+            auto_import: None,
+            import_events: None,
+        }
+    }
+
+    /// Keeps the results of slow analyses in `cache`
+    pub fn with_cache(mut self, cache: Arc<CacheManager>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Looks for new files in `directories` rather than the configured
+    /// watch directories
+    pub fn with_watch_directories(mut self, directories: Vec<String>) -> Self {
+        self.config.watch_directories = directories;
+        self
+    }
+
     /// Import a book from a file
     pub async fn import_book<P: AsRef<Path>>(
         &self,
@@ -86,6 +154,52 @@ impl LibraryManager {
             .map_err(|_| LibraryError::BookNotFound(id.to_string()))
     }
 
+    /// Get the chapters of a book in playback order
+    pub async fn get_chapters(&self, book_id: BookId) -> Result<Vec<Chapter>> {
+        Ok(chapters::get_book_chapters(&self.pool, book_id).await?)
+    }
+
+    /// Replace the chapters of a book with an edited list
+    ///
+    /// The list is normalized against the book's duration before it is saved,
+    /// and the saved list is returned. A player that has the book loaded
+    /// should reload its chapters from the result.
+    pub async fn update_chapters(
+        &self,
+        book_id: BookId,
+        mut edited: Vec<Chapter>,
+    ) -> Result<Vec<Chapter>> {
+        let book = self.get_book(book_id).await?;
+        if let Some(stray) = edited.iter().find(|c| c.book_id != book_id) {
+            return Err(LibraryError::InvalidChapters(vec![format!(
+                "'{}' belongs to another book",
+                stray.title
+            )]));
+        }
+        normalize_chapters(&mut edited, book.duration).map_err(LibraryError::InvalidChapters)?;
+
+        chapters::replace_book_chapters(&self.pool, book_id, &edited).await?;
+        info!("Saved {} chapters for '{}'", edited.len(), book.title);
+        Ok(edited)
+    }
+
+    /// Suggests chapter starts for a book from the long silences in its audio
+    ///
+    /// Meant for books without chapter marks. Use
+    /// [`LibraryManager::chapter_suggester`] to cancel.
+    pub async fn suggest_chapters(&self, book_id: BookId) -> Result<Vec<SuggestedChapter>> {
+        self.chapter_suggester().suggest(book_id).await
+    }
+
+    /// Creates a chapter suggester over this library's books
+    pub fn chapter_suggester(&self) -> ChapterSuggester {
+        let suggester = ChapterSuggester::new(self.pool.clone());
+        match &self.cache {
+            Some(cache) => suggester.with_cache(Arc::clone(cache)),
+            None => suggester,
+        }
+    }
+
     /// Search for books
     pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<Book>> {
         let results = search_books(&self.pool, query, limit as i64).await?;
@@ -97,6 +211,55 @@ impl LibraryManager {
         Ok(books::update_book(&self.pool, book).await?)
     }
 
+    /// Correct a book's title, author, narrator or series
+    ///
+    /// Changed fields are marked as user-edited so re-importing the file keeps
+    /// them. With `write_tags` the file's tags are rewritten afterwards; a
+    /// failure there is reported in the outcome and leaves the database edit
+    /// in place.
+    pub async fn edit_metadata(
+        &self,
+        id: BookId,
+        edit: MetadataEdit,
+    ) -> Result<MetadataEditOutcome> {
+        let book = self.get_book(id).await?;
+        let update = edit.to_update(&book)?;
+        let changed = update.columns();
+        if changed.is_empty() {
+            return Ok(MetadataEditOutcome {
+                book,
+                changed,
+                tags: TagWrite::Skipped,
+            });
+        }
+
+        books::update_book_fields(&self.pool, id, &update.user_edited()).await?;
+        let book = self.get_book(id).await?;
+        info!("Edited {} of '{}'", changed.join(", "), book.title);
+
+        let tags = if edit.write_tags {
+            match write_tags(&book) {
+                Ok(()) => TagWrite::Written,
+                Err(e) => {
+                    warn!(
+                        "Could not write tags to {}: {}",
+                        book.file_path.display(),
+                        e
+                    );
+                    TagWrite::Failed(e)
+                }
+            }
+        } else {
+            TagWrite::Skipped
+        };
+
+        Ok(MetadataEditOutcome {
+            book,
+            changed,
+            tags,
+        })
+    }
+
     /// Delete a book (hard delete)
     pub async fn delete_book(&self, id: BookId) -> Result<()> {
         // Check if book exists first to provide better error
@@ -156,27 +319,596 @@ impl LibraryManager {
     }
 
     /// Start watching directories for changes
+    ///
+    /// With `auto_import` configured, the changes are imported as they
+    /// settle; [`take_import_events`](Self::take_import_events) says what
+    /// happened to each file.
     pub async fn start_watching(&mut self) -> Result<()> {
-        if let Some(scanner) = &mut self.scanner {
-            scanner.start().await?;
+        let Some(scanner) = &mut self.scanner else {
+            return Ok(());
+        };
+        let changes = scanner.start().await?;
+        if self.config.auto_import {
+            let (tx, rx) = mpsc::channel(IMPORT_EVENT_BUFFER);
+            let importer = AutoImporter::new(self.pool.clone()).with_events(tx);
+            self.auto_import = Some(AutoImportTask {
+                cancel: importer.cancel_handle(),
+                task: tokio::spawn(importer.run(changes)),
+            });
+            self.import_events = Some(rx);
         }
         Ok(())
     }
 
     /// Stop watching directories
+    ///
+    /// Waits for the auto-import to finish the file it is reading.
     pub async fn stop_watching(&mut self) -> Result<()> {
         if let Some(scanner) = &mut self.scanner {
             scanner.stop().await?;
         }
+        if let Some(auto_import) = self.auto_import.take() {
+            auto_import.cancel.cancel();
+            let report = auto_import
+                .task
+                .await
+                .map_err(|e| LibraryError::Other(e.to_string()))?;
+            info!(
+                "Auto-import stopped: {} imported, {} updated, {} unavailable, {} failed",
+                report.imported, report.updated, report.unavailable, report.failed
+            );
+        }
         Ok(())
     }
 
+    /// The directories scanned and watched for new books
+    pub fn watch_directories(&self) -> &[String] {
+        &self.config.watch_directories
+    }
+
+    /// Switches to `directories` for scanning and watching, such as after
+    /// the library paths were edited
+    ///
+    /// Watching stops; call [`start_watching`](Self::start_watching) again
+    /// to watch the new directories.
+    pub async fn change_watch_directories(&mut self, directories: Vec<String>) -> Result<()> {
+        self.stop_watching().await?;
+        self.config.watch_directories = directories;
+        self.scanner = watch_scanner(&self.config);
+        Ok(())
+    }
+
+    /// Takes the events of the auto-import started by
+    /// [`start_watching`](Self::start_watching), once
+    pub fn take_import_events(&mut self) -> Option<mpsc::Receiver<ImportEvent>> {
+        self.import_events.take()
+    }
+
+    /// Start playing a playlist from its first book
+    ///
+    /// Books whose file is missing are skipped with a `BookSkipped` event.
+    /// The session is saved so [`advance_playlist`](Self::advance_playlist)
+    /// can pick it up, also from another process.
+    pub async fn start_playlist(&self, id: PlaylistId, shuffle: bool) -> Result<PlaylistProgress> {
+        let playlist = playlists::get_playlist(&self.pool, id).await?;
+        let books = match playlist.smart_criteria {
+            Some(ref criteria) => playlists::get_smart_playlist_books(&self.pool, criteria).await?,
+            None => playlists::get_playlist_books(&self.pool, id).await?,
+        };
+
+        let queue = books
+            .iter()
+            .filter(|b| !b.is_deleted())
+            .map(|b| b.id)
+            .collect::<Vec<_>>();
+        info!(
+            "Starting playlist '{}' with {} books{}",
+            playlist.name,
+            queue.len(),
+            if shuffle { " (shuffled)" } else { "" }
+        );
+
+        let progress = self
+            .settle_playlist(PlaylistSession::new(id, queue, shuffle), Vec::new())
+            .await?;
+        if progress.book.is_none() {
+            return Err(LibraryError::EmptyPlaylist(playlist.name));
+        }
+        Ok(progress)
+    }
+
+    /// Mark the current playlist book finished and move to the next one
+    ///
+    /// Returns `None` when no playlist is playing. When the last book
+    /// finishes the session is cleared and the progress has no book.
+    pub async fn advance_playlist(&self) -> Result<Option<PlaylistProgress>> {
+        let Some(mut session) = playlists::get_playlist_session(&self.pool).await? else {
+            return Ok(None);
+        };
+
+        if let Some(book_id) = session.current() {
+            self.record_finished(book_id).await?;
+        }
+        session.advance();
+        self.settle_playlist(session, Vec::new()).await.map(Some)
+    }
+
+    /// Get the playlist being played, if any
+    pub async fn active_playlist(&self) -> Result<Option<PlaylistSession>> {
+        Ok(playlists::get_playlist_session(&self.pool).await?)
+    }
+
+    /// Stop playing the active playlist
+    pub async fn stop_playlist(&self) -> Result<()> {
+        Ok(playlists::clear_playlist_session(&self.pool).await?)
+    }
+
+    /// Suggests what to play once `after` is finished
+    ///
+    /// Tries the next book of its series, then the next unfinished book of
+    /// the active playlist, then the newest unplayed book by the same author.
+    pub async fn suggest_next(&self, after: BookId) -> Result<Option<NextSuggestion>> {
+        let finished = self.get_book(after).await?;
+
+        if let (Some(series), Some(position)) = (&finished.series, finished.series_position) {
+            if let Some(book) = books::get_next_in_series(&self.pool, series, position).await? {
+                return Ok(Some(NextSuggestion {
+                    book,
+                    reason: SuggestionReason::NextInSeries,
+                }));
+            }
+        }
+
+        if let Some(session) = playlists::get_playlist_session(&self.pool).await? {
+            let next =
+                playlists::get_next_unfinished_in_playlist(&self.pool, session.playlist_id, after)
+                    .await?;
+            if let Some(book) = next {
+                return Ok(Some(NextSuggestion {
+                    book,
+                    reason: SuggestionReason::NextInPlaylist,
+                }));
+            }
+        }
+
+        if let Some(author) = &finished.author {
+            if let Some(book) =
+                books::get_newest_unplayed_by_author(&self.pool, author, after).await?
+            {
+                return Ok(Some(NextSuggestion {
+                    book,
+                    reason: SuggestionReason::SameAuthor,
+                }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Moves `session` past books that cannot be played and saves it
+    async fn settle_playlist(
+        &self,
+        mut session: PlaylistSession,
+        mut events: Vec<PlaylistEvent>,
+    ) -> Result<PlaylistProgress> {
+        while let Some(book_id) = session.current() {
+            let reason = match books::get_book(&self.pool, book_id).await {
+                Ok(book) if book.is_deleted() => {
+                    format!("'{}' was removed from the library", book.title)
+                }
+                Ok(book) if !book.file_path.exists() => format!(
+                    "'{}': file not found at {}",
+                    book.title,
+                    book.file_path.display()
+                ),
+                Ok(book) => {
+                    playlists::save_playlist_session(&self.pool, &session).await?;
+                    events.push(PlaylistEvent::BookStarted {
+                        index: session.current_index,
+                        book_id,
+                    });
+                    return Ok(PlaylistProgress {
+                        session,
+                        book: Some(book),
+                        events,
+                    });
+                }
+                Err(AppError::RecordNotFound { .. }) => {
+                    format!("book {} is no longer in the library", book_id)
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            warn!("Skipping playlist entry: {}", reason);
+            events.push(PlaylistEvent::BookSkipped { book_id, reason });
+            session.advance();
+        }
+
+        playlists::clear_playlist_session(&self.pool).await?;
+        events.push(PlaylistEvent::Finished);
+        Ok(PlaylistProgress {
+            session,
+            book: None,
+            events,
+        })
+    }
+
+    /// Saves a finished book's position at its end
+    async fn record_finished(&self, book_id: BookId) -> Result<()> {
+        let book = match books::get_book(&self.pool, book_id).await {
+            Ok(book) => book,
+            Err(AppError::RecordNotFound { .. }) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut state = match playback::get_playback_state(&self.pool, book_id).await {
+            Ok(state) => state,
+            Err(AppError::RecordNotFound { .. }) => PlaybackState::new(book_id),
+            Err(e) => return Err(e.into()),
+        };
+        state.position = book.duration;
+        state.is_playing = false;
+        Ok(playback::create_playback_state(&self.pool, &state).await?)
+    }
+
+    /// Re-checks the files of the books in `scope` against their stored hashes
+    ///
+    /// Use [`LibraryManager::file_verifier`] to follow progress or cancel.
+    pub async fn verify_files(
+        &self,
+        scope: VerifyScope,
+        depth: VerifyDepth,
+    ) -> Result<VerifyReport> {
+        self.file_verifier().verify(scope, depth).await
+    }
+
+    /// Creates a verifier over this library's books
+    pub fn file_verifier(&self) -> FileVerifier {
+        FileVerifier::new(self.pool.clone())
+    }
+
+    /// Measures the loudness of the books in `scope` not measured yet
+    ///
+    /// Use [`LibraryManager::loudness_analyzer`] to follow progress, cancel,
+    /// or change how fast files are read.
+    pub async fn analyze_loudness(&self, scope: VerifyScope) -> Result<LoudnessReport> {
+        self.loudness_analyzer().analyze(scope).await
+    }
+
+    /// Creates a loudness analyzer over this library's books
+    pub fn loudness_analyzer(&self) -> LoudnessAnalyzer {
+        LoudnessAnalyzer::new(self.pool.clone())
+    }
+
+    /// Creates a pipeline that bulk-imports into this library
+    ///
+    /// Run it on [`LibraryManager::scanner`] to import the watched folders.
+    pub fn import_pipeline(&self) -> ImportPipeline {
+        ImportPipeline::new(self.pool.clone())
+    }
+
+    /// A scanner over the library's watch directories
+    pub fn scanner(&self) -> LibraryScanner {
+        LibraryScanner::with_config(
+            self.config
+                .scanner_config(self.config.watch_directories.clone()),
+        )
+    }
+
+    /// Plans an import of the watch directories without touching the library
+    ///
+    /// The returned future owns what it needs, so it can run on its own task
+    /// while the plan is worked out.
+    pub fn preview_import(&self) -> impl Future<Output = Result<ImportPlan>> + Send + 'static {
+        let importer = BookImporter::new(self.pool.clone());
+        let directories = self.config.watch_directories.clone();
+        async move { importer.preview(&directories).await }
+    }
+
+    /// Applies the included entries of a plan from
+    /// [`preview_import`](Self::preview_import)
+    pub async fn commit_import(&self, plan: &ImportPlan) -> Result<Vec<Book>> {
+        self.importer.commit(plan).await
+    }
+
+    /// Files every book's file where `template` puts it
+    ///
+    /// With `dry_run` nothing is touched and the plan is a preview of the
+    /// renames. Otherwise each book's path is updated as soon as its file is
+    /// in place, so a failure part way leaves the library consistent.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LibraryError::DiskFull` before copying anything if the
+    /// destination volume cannot hold the copies.
+    pub async fn reorganize_existing(
+        &self,
+        template: &OrganizeTemplate,
+        dry_run: bool,
+    ) -> Result<OrganizePlan> {
+        let books = books::list_books(&self.pool).await?;
+        let plan = template.plan(&books);
+        info!(
+            "Reorganizing {} file(s), {} already in place{}",
+            plan.moves.len(),
+            plan.in_place,
+            if dry_run { " (dry run)" } else { "" }
+        );
+        if dry_run || plan.moves.is_empty() {
+            return Ok(plan);
+        }
+
+        // Moves on the same volume need no space; those across are checked per file
+        if template.mode() == OrganizeMode::Copy {
+            organize::ensure_space(template.root(), plan.total_bytes())?;
+        }
+
+        for planned in &plan.moves {
+            organize::place_file(&planned.from, &planned.to, template.mode())?;
+            let mut book = books::get_book(&self.pool, planned.book_id).await?;
+            book.file_path = planned.to.clone();
+            books::update_book(&self.pool, &book).await?;
+        }
+
+        Ok(plan)
+    }
+
+    /// Groups books that look like copies of one another
+    pub async fn find_duplicates(&self) -> Result<Vec<DuplicateGroup>> {
+        let books = books::list_books(&self.pool).await?;
+        let hashes = books::get_file_hashes(&self.pool).await?;
+        Ok(group_duplicates(&books, &hashes))
+    }
+
+    /// Finds the books whose file went missing under `search_roots` and
+    /// points them at where their files are now
+    ///
+    /// Files are recognised by their content fingerprint, so renamed files
+    /// are found too. Bookmarks, the playback position and listening history
+    /// stay with each book. Books imported before fingerprints were kept
+    /// cannot be found this way.
+    pub async fn relocate_missing_files<P: AsRef<Path>>(
+        &self,
+        search_roots: &[P],
+    ) -> Result<Vec<Relocation>> {
+        let missing = fingerprint::missing_books(&self.pool).await?;
+        if missing.is_empty() {
+            return Ok(Vec::new());
+        }
+        info!("Looking for {} missing book file(s)", missing.len());
+
+        let roots = search_roots
+            .iter()
+            .map(|root| root.as_ref().display().to_string())
+            .collect();
+        let files = LibraryScanner::with_config(self.config.scanner_config(roots))
+            .scan()
+            .await?;
+        let known = books::get_file_paths(&self.pool).await?;
+        let found = tokio::task::spawn_blocking(move || {
+            fingerprint::find_moved_files(files, missing, &known)
+        })
+        .await
+        .map_err(|e| LibraryError::Other(e.to_string()))??;
+
+        let mut relocations = Vec::with_capacity(found.len());
+        for (book, path) in found {
+            books::relocate_book(&self.pool, book.id, &path).await?;
+            info!(
+                "Relocated {} from {} to {}",
+                book.title,
+                book.file_path.display(),
+                path.display()
+            );
+            relocations.push(Relocation {
+                book_id: book.id,
+                title: book.title,
+                from: book.file_path,
+                to: path,
+            });
+        }
+        Ok(relocations)
+    }
+
+    /// Cross-checks the library against the files on disk
+    ///
+    /// Books whose file is gone are missing, audio files in the watch
+    /// directories that no book points at are orphaned, and books whose file
+    /// changed size or modification time since it was read are modified.
+    /// Nothing is changed; see [`mark_missing_as_unavailable`],
+    /// [`import_orphans`] and [`refresh_modified`] to act on the report.
+    ///
+    /// [`mark_missing_as_unavailable`]: Self::mark_missing_as_unavailable
+    /// [`import_orphans`]: Self::import_orphans
+    /// [`refresh_modified`]: Self::refresh_modified
+    pub async fn verify_library(&self) -> Result<LibraryReport> {
+        let library = books::list_books(&self.pool).await?;
+        let known = books::get_file_paths(&self.pool).await?;
+        let file_modified = books::get_file_modified(&self.pool).await?;
+        let files = if self.config.watch_directories.is_empty() {
+            Vec::new()
+        } else {
+            self.scanner().scan().await?
+        };
+
+        let report = tokio::task::spawn_blocking(move || {
+            reconcile::reconcile(library, files, &known, &file_modified)
+        })
+        .await
+        .map_err(|e| LibraryError::Other(e.to_string()))?;
+        info!(
+            "Checked {} book(s): {} missing, {} orphaned, {} modified",
+            report.checked,
+            report.missing.len(),
+            report.orphaned.len(),
+            report.modified.len()
+        );
+        Ok(report)
+    }
+
+    /// Marks the report's missing books as unavailable, keeping them and
+    /// their history in the library
+    ///
+    /// Books marked by an earlier report whose files are back become
+    /// available again. Returns how many books are now unavailable.
+    pub async fn mark_missing_as_unavailable(&self, report: &LibraryReport) -> Result<usize> {
+        let ids: Vec<BookId> = report.missing.iter().map(|book| book.id).collect();
+        books::set_unavailable_books(&self.pool, &ids).await?;
+        Ok(ids.len())
+    }
+
+    /// Imports the report's orphaned files, passing over those that fail
+    pub async fn import_orphans(&self, report: &LibraryReport) -> Result<Vec<Book>> {
+        let options = ImportOptions::new().with_skip_on_error(true);
+        self.importer.import_files(&report.orphaned, options).await
+    }
+
+    /// Reads the metadata and duration of the report's modified files again
+    ///
+    /// Each book keeps its id, playback state, bookmarks and user edits.
+    pub async fn refresh_modified(&self, report: &LibraryReport) -> Result<Vec<Book>> {
+        let paths: Vec<&Path> = report
+            .modified
+            .iter()
+            .map(|modified| modified.book.file_path.as_path())
+            .collect();
+        let options = ImportOptions::new()
+            .with_overwrite_existing(true)
+            .with_skip_on_error(true);
+        self.importer.import_files(&paths, options).await
+    }
+
+    /// Folds the books in `remove` into `keep`, then soft-deletes them
+    ///
+    /// Bookmarks, listening history, playlist memberships and tags move to
+    /// the kept book, which also takes the furthest playback position. It
+    /// all happens in one transaction, so a failure changes nothing.
+    pub async fn merge_books(&self, keep: BookId, remove: Vec<BookId>) -> Result<()> {
+        info!("Merging {} duplicate(s) into book {}", remove.len(), keep);
+        Ok(books::merge_books(&self.pool, keep, &remove).await?)
+    }
+
+    /// Points a book at another copy of its audio, such as a better rip
+    ///
+    /// The new file must last about as long as the old one. Bookmarks and
+    /// the playback position are scaled to the new duration, and so are the
+    /// chapters while they still fit; otherwise the new file's chapters are
+    /// read. The old file is left on disk.
+    pub async fn replace_source(
+        &self,
+        id: BookId,
+        new_path: impl AsRef<Path>,
+    ) -> Result<SourceReplacement> {
+        let book = self.get_book(id).await?;
+        let path = replace::check_new_path(&book, new_path.as_ref())?;
+        if books::get_file_paths(&self.pool).await?.contains(&path) {
+            return Err(LibraryError::ImportFailed(format!(
+                "{} already belongs to another book",
+                path.display()
+            )));
+        }
+
+        let reading = book.clone();
+        let source = tokio::task::spawn_blocking(move || NewSource::read(&reading, path))
+            .await
+            .map_err(|e| LibraryError::Other(e.to_string()))??;
+        let scale = replace::duration_scale(book.duration, source.duration)?;
+
+        let stored = chapters::get_book_chapters(&self.pool, id).await?;
+        let (new_chapters, outcome) = if stored.is_empty() {
+            match source.chapters.len() {
+                0 => (None, ChapterOutcome::None),
+                count => (Some(source.chapters), ChapterOutcome::Reextracted(count)),
+            }
+        } else {
+            match replace::scale_chapters(&stored, scale, source.duration) {
+                Some(scaled) if scale == 1.0 => (Some(scaled), ChapterOutcome::Kept(stored.len())),
+                Some(scaled) => (Some(scaled), ChapterOutcome::Adjusted(stored.len())),
+                None => {
+                    let count = source.chapters.len();
+                    (Some(source.chapters), ChapterOutcome::Reextracted(count))
+                }
+            }
+        };
+
+        let mut replaced = book.clone();
+        replaced.file_path = source.path;
+        replaced.file_size = source.file_size;
+        replaced.duration = source.duration;
+        let moved = books::replace_source(
+            &self.pool,
+            &replaced,
+            source.hash.as_deref(),
+            new_chapters.as_deref(),
+            scale,
+        )
+        .await?;
+        info!(
+            "Replaced the file of '{}' with {}",
+            book.title,
+            replaced.file_path.display()
+        );
+
+        Ok(SourceReplacement {
+            book: replaced,
+            old_path: book.file_path,
+            old_duration: book.duration,
+            chapters: outcome,
+            moved,
+        })
+    }
+
+    /// Whether the library refuses changes, either as configured or
+    /// because its database file is not writable
+    pub async fn is_read_only(&self) -> Result<bool> {
+        Ok(is_read_only(&self.pool).await?)
+    }
+
     /// Get database pool for advanced operations
     pub fn pool(&self) -> &DbPool {
         &self.pool
     }
 }
 
+/// Something that happened while starting or advancing a playlist
+#[derive(Debug, Clone)]
+pub enum PlaylistEvent {
+    /// A book could not be played and was passed over
+    BookSkipped { book_id: BookId, reason: String },
+    /// Playback moved to the book at `index` of the queue
+    BookStarted { index: usize, book_id: BookId },
+    /// The last book of the playlist has been played
+    Finished,
+}
+
+/// Where a playlist stands after starting or advancing it
+#[derive(Debug, Clone)]
+pub struct PlaylistProgress {
+    pub session: PlaylistSession,
+    /// The book to play now, `None` once the playlist is done
+    pub book: Option<Book>,
+    /// What happened on the way, in order
+    pub events: Vec<PlaylistEvent>,
+}
+
+/// Why a book is suggested to play next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuggestionReason {
+    /// It follows the finished book in its series
+    NextInSeries,
+    /// It is the next unfinished book of the active playlist
+    NextInPlaylist,
+    /// It is the newest unplayed book by the same author
+    SameAuthor,
+}
+
+/// A book to play after finishing another
+#[derive(Debug, Clone)]
+pub struct NextSuggestion {
+    pub book: Book,
+    pub reason: SuggestionReason,
+}
+
 #[derive(Debug, Clone)]
 pub struct LibraryStats {
     pub total_books: usize,
@@ -189,6 +921,7 @@ pub struct LibraryStats {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use storystream_media_formats::wav::wav_bytes;
     use tempfile::NamedTempFile;
 
     async fn setup_test_manager() -> Result<(LibraryManager, NamedTempFile)> {
@@ -211,6 +944,23 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_change_watch_directories() -> Result<()> {
+        let (mut manager, _temp) = setup_test_manager().await?;
+        let dir = tempfile::TempDir::new().map_err(LibraryError::Io)?;
+        let path = dir.path().display().to_string();
+
+        manager.change_watch_directories(vec![path.clone()]).await?;
+        assert_eq!(manager.watch_directories(), [path]);
+        manager.start_watching().await?;
+        assert!(manager.scanner.as_ref().is_some());
+
+        manager.change_watch_directories(Vec::new()).await?;
+        assert!(manager.watch_directories().is_empty());
+        assert!(manager.scanner.is_none());
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_list_books_empty() -> Result<()> {
         let (manager, _temp) = setup_test_manager().await?;
@@ -280,6 +1030,404 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_reorganize_existing() -> Result<()> {
+        let (manager, _temp) = setup_test_manager().await?;
+        let source = tempfile::tempdir()?;
+        let root = tempfile::tempdir()?;
+        let mut book = add_book(&manager, source.path(), "Dune", true).await;
+        book.author = Some("Frank Herbert".to_string());
+        manager.update_book(&book).await?;
+        add_book(&manager, source.path(), "Gone", false).await;
+
+        let template = OrganizeTemplate::new(root.path()).with_mode(OrganizeMode::Move);
+        let destination = root
+            .path()
+            .join("Frank Herbert")
+            .join("Dune")
+            .join("Dune.m4b");
+
+        let preview = manager.reorganize_existing(&template, true).await?;
+        assert_eq!(preview.moves.len(), 1);
+        assert_eq!(preview.moves[0].to, destination);
+        assert_eq!(preview.missing.len(), 1);
+        assert!(book.file_path.exists());
+
+        manager.reorganize_existing(&template, false).await?;
+        assert!(!book.file_path.exists());
+        assert!(destination.exists());
+        assert_eq!(manager.get_book(book.id).await?.file_path, destination);
+
+        let again = manager.reorganize_existing(&template, false).await?;
+        assert!(again.moves.is_empty());
+        assert_eq!(again.in_place, 1);
+        Ok(())
+    }
+
+    /// Adds a book whose file exists only if `on_disk` is set
+    async fn add_book(manager: &LibraryManager, dir: &Path, name: &str, on_disk: bool) -> Book {
+        let path = dir.join(format!("{}.m4b", name));
+        if on_disk {
+            std::fs::write(&path, b"audio").unwrap();
+        }
+        let book = Book::new(name.to_string(), path, 5, Duration::from_seconds(60));
+        books::create_book(manager.pool(), &book).await.unwrap();
+        book
+    }
+
+    async fn add_playlist(manager: &LibraryManager, books: &[&Book]) -> PlaylistId {
+        let playlist = storystream_core::Playlist::new_manual("Queue".to_string());
+        playlists::create_playlist(manager.pool(), &playlist)
+            .await
+            .unwrap();
+        for (position, book) in books.iter().enumerate() {
+            let item = storystream_core::PlaylistItem::new(playlist.id, book.id, position as u32);
+            playlists::add_book_to_playlist(manager.pool(), &item)
+                .await
+                .unwrap();
+        }
+        playlist.id
+    }
+
+    #[tokio::test]
+    async fn test_start_playlist_skips_missing_books() -> Result<()> {
+        let (manager, _temp) = setup_test_manager().await?;
+        let dir = tempfile::tempdir().map_err(LibraryError::Io)?;
+        let missing = add_book(&manager, dir.path(), "missing", false).await;
+        let first = add_book(&manager, dir.path(), "first", true).await;
+        let second = add_book(&manager, dir.path(), "second", true).await;
+        let id = add_playlist(&manager, &[&missing, &first, &second]).await;
+
+        let progress = manager.start_playlist(id, false).await?;
+        assert_eq!(progress.book.as_ref().map(|b| b.id), Some(first.id));
+        assert!(matches!(
+            progress.events[0],
+            PlaylistEvent::BookSkipped { book_id, .. } if book_id == missing.id
+        ));
+        assert!(matches!(
+            progress.events[1],
+            PlaylistEvent::BookStarted { index: 1, .. }
+        ));
+
+        let active = manager.active_playlist().await?.unwrap();
+        assert_eq!(active.playlist_id, id);
+        assert_eq!(active.current_index, 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_advance_playlist_to_end() -> Result<()> {
+        let (manager, _temp) = setup_test_manager().await?;
+        let dir = tempfile::tempdir().map_err(LibraryError::Io)?;
+        let first = add_book(&manager, dir.path(), "first", true).await;
+        let second = add_book(&manager, dir.path(), "second", true).await;
+        let id = add_playlist(&manager, &[&first, &second]).await;
+
+        manager.start_playlist(id, false).await?;
+        let next = manager.advance_playlist().await?.unwrap();
+        assert_eq!(next.book.map(|b| b.id), Some(second.id));
+
+        // The finished book is saved at its end
+        let state = playback::get_playback_state(manager.pool(), first.id).await?;
+        assert_eq!(state.position, first.duration);
+
+        let done = manager.advance_playlist().await?.unwrap();
+        assert!(done.book.is_none());
+        assert!(matches!(done.events.last(), Some(PlaylistEvent::Finished)));
+        assert!(manager.active_playlist().await?.is_none());
+        assert!(manager.advance_playlist().await?.is_none());
+        Ok(())
+    }
+
+    /// Adds an on-disk book by `author`, imported `added` milliseconds into 2024
+    async fn add_authored(
+        manager: &LibraryManager,
+        dir: &Path,
+        title: &str,
+        author: &str,
+        series: Option<(&str, f32)>,
+        added: i64,
+    ) -> Book {
+        let mut book = add_book(manager, dir, title, true).await;
+        book.author = Some(author.to_string());
+        book.series = series.map(|(name, _)| name.to_string());
+        book.series_position = series.map(|(_, position)| position);
+        book.added_date = storystream_core::Timestamp::from_millis(1_704_067_200_000 + added);
+        manager.update_book(&book).await.unwrap();
+        book
+    }
+
+    #[tokio::test]
+    async fn test_suggest_next_tiers() -> Result<()> {
+        let (manager, _temp) = setup_test_manager().await?;
+        let dir = tempfile::tempdir().map_err(LibraryError::Io)?;
+        let series = "Stormlight Archive";
+        let author = "Brandon Sanderson";
+        let kings = add_authored(
+            &manager,
+            dir.path(),
+            "Way of Kings",
+            author,
+            Some((series, 1.0)),
+            0,
+        )
+        .await;
+        let radiance = add_authored(
+            &manager,
+            dir.path(),
+            "Words of Radiance",
+            author,
+            Some((series, 2.0)),
+            1,
+        )
+        .await;
+        let elantris = add_authored(&manager, dir.path(), "Elantris", author, None, 2).await;
+        let mistborn = add_authored(&manager, dir.path(), "Mistborn", author, None, 3).await;
+        let started = add_authored(&manager, dir.path(), "Warbreaker", author, None, 4).await;
+        let emma = add_authored(&manager, dir.path(), "Emma", "Jane Austen", None, 5).await;
+        let heard = add_authored(&manager, dir.path(), "Persuasion", "Jane Austen", None, 6).await;
+
+        let mut state = PlaybackState::new(started.id);
+        state.position = Duration::from_seconds(5);
+        playback::create_playback_state(manager.pool(), &state).await?;
+        let mut state = PlaybackState::new(heard.id);
+        state.position = heard.duration;
+        playback::create_playback_state(manager.pool(), &state).await?;
+
+        // The series comes first
+        let next = manager.suggest_next(kings.id).await?.unwrap();
+        assert_eq!(next.book.id, radiance.id);
+        assert_eq!(next.reason, SuggestionReason::NextInSeries);
+
+        // At the end of the series, the playlist, skipping what was heard
+        let playlist = add_playlist(&manager, &[&radiance, &heard, &emma]).await;
+        manager.start_playlist(playlist, false).await?;
+        let next = manager.suggest_next(radiance.id).await?.unwrap();
+        assert_eq!(next.book.id, emma.id);
+        assert_eq!(next.reason, SuggestionReason::NextInPlaylist);
+
+        // Without a playlist, the newest book by the author not yet started
+        manager.stop_playlist().await?;
+        let next = manager.suggest_next(radiance.id).await?.unwrap();
+        assert_eq!(next.book.id, mistborn.id);
+        assert_eq!(next.reason, SuggestionReason::SameAuthor);
+        assert_ne!(next.book.id, elantris.id);
+
+        assert!(manager.suggest_next(emma.id).await?.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_start_playlist_with_nothing_to_play() -> Result<()> {
+        let (manager, _temp) = setup_test_manager().await?;
+        let dir = tempfile::tempdir().map_err(LibraryError::Io)?;
+        let missing = add_book(&manager, dir.path(), "missing", false).await;
+        let id = add_playlist(&manager, &[&missing]).await;
+
+        let result = manager.start_playlist(id, true).await;
+        assert!(matches!(result, Err(LibraryError::EmptyPlaylist(_))));
+        assert!(manager.active_playlist().await?.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_update_chapters_normalizes_and_saves() -> Result<()> {
+        let (manager, _temp) = setup_test_manager().await?;
+        let dir = tempfile::tempdir().map_err(LibraryError::Io)?;
+        let book = add_book(&manager, dir.path(), "book", true).await;
+
+        let edited = vec![
+            Chapter::new(
+                book.id,
+                " Second ".to_string(),
+                7,
+                Duration::from_seconds(30),
+                Duration::from_seconds(31),
+            ),
+            Chapter::new(
+                book.id,
+                "First".to_string(),
+                3,
+                Duration::from_seconds(0),
+                Duration::from_seconds(10),
+            ),
+        ];
+        let saved = manager.update_chapters(book.id, edited).await?;
+        assert_eq!(saved[0].title, "First");
+        assert_eq!(saved[0].end_time, Duration::from_seconds(30));
+        assert_eq!(saved[1].index, 1);
+        assert_eq!(saved[1].end_time, book.duration);
+
+        let stored = manager.get_chapters(book.id).await?;
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored[1].title, "Second");
+
+        let invalid = vec![Chapter::new(
+            book.id,
+            String::new(),
+            0,
+            Duration::from_seconds(0),
+            Duration::from_seconds(60),
+        )];
+        let result = manager.update_chapters(book.id, invalid).await;
+        assert!(matches!(result, Err(LibraryError::InvalidChapters(_))));
+        assert_eq!(manager.get_chapters(book.id).await?.len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_edit_metadata_marks_user_edits() -> Result<()> {
+        let (manager, _temp) = setup_test_manager().await?;
+        let dir = tempfile::tempdir().map_err(LibraryError::Io)?;
+        let book = add_book(&manager, dir.path(), "Emma", true).await;
+
+        let edit = MetadataEdit::new()
+            .with_title("Emma")
+            .with_author("Jane Austen")
+            .with_narrator("Juliet Stevenson");
+        let outcome = manager.edit_metadata(book.id, edit).await?;
+        assert_eq!(outcome.changed, vec!["author", "narrator"]);
+        assert_eq!(outcome.tags, TagWrite::Skipped);
+        assert_eq!(outcome.book.author.as_deref(), Some("Jane Austen"));
+
+        let edited = books::get_user_edited_fields(manager.pool(), book.id).await?;
+        assert_eq!(edited, vec!["author", "narrator"]);
+        Ok(())
+    }
+
+    #[cfg(not(feature = "write-tags"))]
+    #[tokio::test]
+    async fn test_edit_metadata_keeps_edit_when_tags_fail() -> Result<()> {
+        let (manager, _temp) = setup_test_manager().await?;
+        let dir = tempfile::tempdir().map_err(LibraryError::Io)?;
+        let book = add_book(&manager, dir.path(), "Emma", true).await;
+
+        let edit = MetadataEdit::new()
+            .with_series("Austen")
+            .with_write_tags(true);
+        let outcome = manager.edit_metadata(book.id, edit).await?;
+        assert!(matches!(outcome.tags, TagWrite::Failed(_)));
+        assert_eq!(
+            manager.get_book(book.id).await?.series.as_deref(),
+            Some("Austen")
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_replace_source_refuses_unusable_files() -> Result<()> {
+        let (manager, _temp) = setup_test_manager().await?;
+        let dir = tempfile::tempdir().map_err(LibraryError::Io)?;
+        let book = add_book(&manager, dir.path(), "Emma", true).await;
+        let other = add_book(&manager, dir.path(), "Persuasion", true).await;
+        let notes = dir.path().join("notes.txt");
+        std::fs::write(&notes, b"not audio")?;
+
+        let missing = manager
+            .replace_source(book.id, dir.path().join("gone.flac"))
+            .await;
+        assert!(matches!(missing, Err(LibraryError::FileNotFound(_))));
+        let text = manager.replace_source(book.id, &notes).await;
+        assert!(matches!(text, Err(LibraryError::UnsupportedFormat(_))));
+        let same = manager.replace_source(book.id, &book.file_path).await;
+        assert!(matches!(same, Err(LibraryError::InvalidFile(_))));
+        let taken = manager.replace_source(book.id, &other.file_path).await;
+        assert!(matches!(taken, Err(LibraryError::ImportFailed(_))));
+        Ok(())
+    }
+
+    /// Writes a mono 8 kHz WAV file of one second, every sample byte set to
+    /// `fill`
+    fn write_wav(path: &Path, fill: u8) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, wav_bytes(8_000, fill)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_relocate_missing_files() -> Result<()> {
+        let (manager, _temp) = setup_test_manager().await?;
+        let dir = tempfile::tempdir().map_err(LibraryError::Io)?;
+        let kept = dir.path().join("old/kept.wav");
+        let lost = dir.path().join("old/lost.wav");
+        write_wav(&kept, 1);
+        write_wav(&lost, 2);
+        let kept_book = manager.import_book(&kept, ImportOptions::new()).await?;
+        let lost_book = manager.import_book(&lost, ImportOptions::new()).await?;
+        assert!(manager
+            .relocate_missing_files(&[dir.path()])
+            .await?
+            .is_empty());
+
+        let renamed = dir.path().join("sorted/Author/Renamed.wav");
+        std::fs::create_dir_all(renamed.parent().unwrap())?;
+        std::fs::rename(&kept, &renamed)?;
+        std::fs::remove_file(&lost)?;
+        // Same size and length, different audio
+        write_wav(&dir.path().join("sorted/decoy.wav"), 9);
+
+        let relocations = manager
+            .relocate_missing_files(&[dir.path().join("sorted")])
+            .await?;
+        assert_eq!(relocations.len(), 1);
+        assert_eq!(relocations[0].book_id, kept_book.id);
+        assert_eq!(relocations[0].to, renamed.canonicalize()?);
+        assert_eq!(
+            manager.get_book(kept_book.id).await?.file_path,
+            renamed.canonicalize()?
+        );
+        assert_eq!(
+            manager.get_book(lost_book.id).await?.file_path,
+            lost_book.file_path
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_verify_library_and_fix() -> Result<()> {
+        let dir = tempfile::tempdir().map_err(LibraryError::Io)?;
+        let root = dir.path().canonicalize()?;
+        let (manager, _temp) = setup_test_manager().await?;
+        let manager = manager.with_watch_directories(vec![root.display().to_string()]);
+        let kept = root.join("kept.wav");
+        let gone = root.join("gone.wav");
+        let edited = root.join("edited.wav");
+        for (path, fill) in [(&kept, 1), (&gone, 2), (&edited, 3)] {
+            write_wav(path, fill);
+            manager.import_book(path, ImportOptions::new()).await?;
+        }
+        assert!(manager.verify_library().await?.is_clean());
+
+        std::fs::remove_file(&gone)?;
+        let mut longer = std::fs::read(&edited)?;
+        longer.extend_from_slice(&[0; 64]);
+        std::fs::write(&edited, longer)?;
+        let orphan = root.join("new/orphan.wav");
+        write_wav(&orphan, 4);
+
+        let report = manager.verify_library().await?;
+        assert_eq!(report.checked, 3);
+        assert_eq!(report.missing.len(), 1);
+        assert_eq!(report.missing[0].file_path, gone);
+        assert_eq!(report.orphaned, vec![orphan.clone()]);
+        assert_eq!(report.modified.len(), 1);
+        assert_eq!(report.modified[0].book.file_path, edited);
+
+        assert_eq!(manager.mark_missing_as_unavailable(&report).await?, 1);
+        let unavailable = books::get_unavailable_books(manager.pool()).await?;
+        assert!(unavailable.contains(&report.missing[0].id));
+        assert_eq!(manager.import_orphans(&report).await?.len(), 1);
+        let refreshed = manager.refresh_modified(&report).await?;
+        assert_eq!(refreshed[0].id, report.modified[0].book.id);
+        assert_eq!(refreshed[0].file_size, report.modified[0].size);
+
+        let report = manager.verify_library().await?;
+        assert_eq!(report.checked, 4);
+        assert_eq!(report.missing.len(), 1);
+        assert!(report.orphaned.is_empty());
+        assert!(report.modified.is_empty());
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_delete_nonexistent() -> Result<()> {
         let (manager, _temp) = setup_test_manager().await?;
@@ -5,8 +5,11 @@ use lofty::file::TaggedFile;
 use lofty::prelude::*;
 use lofty::probe::Probe;
 use std::path::Path;
-use storystream_core::{Book, Duration};
+use storystream_core::{Book, Chapter, Duration};
 use storystream_media_formats::{AudioAnalyzer, AudioFormat as MediaFormat, FormatDetector};
+use tracing::warn;
+
+pub mod chapters;
 
 /// Audio metadata extractor
 pub struct MetadataExtractor {
@@ -23,6 +26,7 @@ pub struct ExtractedMetadata {
     pub description: Option<String>,
     pub series: Option<String>,
     pub series_position: Option<f32>,
+    pub genre: Option<String>,
     pub duration: Duration,
     pub file_size: u64,
     pub format: MediaFormat,
@@ -65,7 +69,7 @@ impl MetadataExtractor {
         // Convert std::time::Duration to our Duration
         let duration = Duration::from_seconds(duration.as_secs());
 
-        let (title, author, narrator, description, series, series_position, cover_art) =
+        let (title, author, narrator, description, series, series_position, genre, cover_art) =
             self.extract_tags(path)?;
 
         Ok(ExtractedMetadata {
@@ -75,6 +79,7 @@ impl MetadataExtractor {
             description,
             series,
             series_position,
+            genre,
             duration,
             file_size,
             format,
@@ -95,6 +100,7 @@ impl MetadataExtractor {
         Option<String>,
         Option<String>,
         Option<f32>,
+        Option<String>,
         Option<Vec<u8>>,
     )> {
         let tagged_file = match Probe::open(path)
@@ -103,14 +109,14 @@ impl MetadataExtractor {
         {
             Ok(file) => file,
             Err(_) => {
-                return Ok((None, None, None, None, None, None, None));
+                return Ok((None, None, None, None, None, None, None, None));
             }
         };
 
         let tag = match tagged_file.primary_tag() {
             Some(t) => t,
             None => {
-                return Ok((None, None, None, None, None, None, None));
+                return Ok((None, None, None, None, None, None, None, None));
             }
         };
 
@@ -127,6 +133,8 @@ impl MetadataExtractor {
 
         let series_position = tag.track().or_else(|| tag.disk()).map(|n| n as f32);
 
+        let genre = tag.genre().map(|s| s.to_string());
+
         let cover_art = self.extract_cover_art(&tagged_file);
 
         Ok((
@@ -136,6 +144,7 @@ impl MetadataExtractor {
             description,
             series,
             series_position,
+            genre,
             cover_art,
         ))
     }
@@ -177,9 +186,28 @@ impl MetadataExtractor {
         book.description = metadata.description;
         book.series = metadata.series;
         book.series_position = metadata.series_position;
+        book.genre = metadata.genre;
 
         book
     }
+
+    /// Reads the chapters marked in a book's file
+    ///
+    /// Files without chapters give an empty list. Chapter data that cannot
+    /// be read is logged and skipped, so it never fails an import.
+    pub fn read_chapters(&self, book: &Book) -> Vec<Chapter> {
+        match chapters::read_markers(&book.file_path) {
+            Ok(markers) => chapters::to_chapters(book, markers),
+            Err(e) => {
+                warn!(
+                    "Could not read the chapters of {}: {}",
+                    book.file_path.display(),
+                    e
+                );
+                Vec::new()
+            }
+        }
+    }
 }
 
 impl Default for MetadataExtractor {
@@ -235,6 +263,7 @@ mod tests {
             description: Some("Test description".to_string()),
             series: Some("Test Series".to_string()),
             series_position: Some(1.0),
+            genre: Some("Fantasy".to_string()),
             duration: Duration::from_seconds(3600),
             file_size: 1024000,
             format: MediaFormat::Mp3,
@@ -250,6 +279,7 @@ mod tests {
         assert_eq!(book.narrator, Some("Test Narrator".to_string()));
         assert_eq!(book.series, Some("Test Series".to_string()));
         assert_eq!(book.series_position, Some(1.0));
+        assert_eq!(book.genre.as_deref(), Some("Fantasy"));
     }
 
     #[test]
@@ -264,6 +294,7 @@ mod tests {
             description: None,
             series: None,
             series_position: None,
+            genre: None,
             duration: Duration::from_seconds(3600),
             file_size: 1024000,
             format: MediaFormat::Mp3,
@@ -290,7 +321,7 @@ mod tests {
         let result = extractor.extract_tags(temp_file.path());
         assert!(result.is_ok());
 
-        let (title, author, _, _, _, _, _) = result.expect("Should not fail");
+        let (title, author, _, _, _, _, _, _) = result.expect("Should not fail");
         assert!(title.is_none());
         assert!(author.is_none());
     }
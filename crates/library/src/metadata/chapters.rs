@@ -0,0 +1,734 @@
+//! Chapter markers embedded in audio files
+//!
+//! M4B and M4A files mark chapters either with a text track that the audio
+//! track refers to (as iTunes writes them) or with a Nero `chpl` atom. MP3
+//! files carry ID3v2 `CHAP` frames. Only the container is read here; no
+//! audio is decoded.
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use storystream_core::types::chapters::normalize_chapters;
+use storystream_core::{Book, Chapter, Duration};
+use tracing::warn;
+
+/// Largest `moov` atom or ID3v2 tag read into memory
+const MAX_HEADER_BYTES: u64 = 256 * 1024 * 1024;
+
+/// A chapter as marked in the file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChapterMarker {
+    pub title: String,
+    pub start: Duration,
+    /// Where the file says the chapter ends, when it says
+    pub end: Option<Duration>,
+}
+
+/// Reads the chapter markers of an MP3 or MP4 file, in the file's order
+///
+/// Files of other formats, or without chapters, give an empty list.
+///
+/// # Errors
+///
+/// Fails if the file cannot be read or its chapter data is malformed.
+pub fn read_markers(path: &Path) -> io::Result<Vec<ChapterMarker>> {
+    let mut file = File::open(path)?;
+    let mut magic = [0; 8];
+    let read = file.read(&mut magic)?;
+    file.seek(SeekFrom::Start(0))?;
+
+    if read >= 3 && &magic[..3] == b"ID3" {
+        id3_markers(&mut file)
+    } else if read == 8 && &magic[4..] == b"ftyp" {
+        mp4_markers(&mut file)
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+/// Turns a book's markers into a chapter list ready to store
+///
+/// Markers past the end of the book or sharing a start time with an earlier
+/// one are dropped, and untitled chapters are numbered. A list that still
+/// does not hold together is logged and left out.
+pub fn to_chapters(book: &Book, mut markers: Vec<ChapterMarker>) -> Vec<Chapter> {
+    markers.sort_by_key(|marker| marker.start);
+    markers.dedup_by_key(|marker| marker.start);
+    if !book.duration.is_zero() {
+        markers.retain(|marker| marker.start < book.duration);
+    }
+
+    let mut chapters: Vec<Chapter> = markers
+        .into_iter()
+        .enumerate()
+        .map(|(index, marker)| {
+            let title = match marker.title.trim() {
+                "" => format!("Chapter {}", index + 1),
+                title => title.to_string(),
+            };
+            let end = marker.end.unwrap_or(marker.start);
+            Chapter::new(book.id, title, index as u32, marker.start, end)
+        })
+        .collect();
+
+    match normalize_chapters(&mut chapters, book.duration) {
+        Ok(()) => chapters,
+        Err(problems) => {
+            warn!(
+                "Ignoring the chapters of {}: {}",
+                book.file_path.display(),
+                problems.join("; ")
+            );
+            Vec::new()
+        }
+    }
+}
+
+fn malformed(what: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("malformed {}", what))
+}
+
+/// Reads big-endian fields from a byte slice
+struct Cursor<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Cursor<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn take(&mut self, count: usize) -> io::Result<&'a [u8]> {
+        if count > self.data.len() {
+            return Err(malformed("chapter data"));
+        }
+        let (taken, rest) = self.data.split_at(count);
+        self.data = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> io::Result<u16> {
+        Ok(u16::from_be_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_be_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_be_bytes(self.array()?))
+    }
+
+    fn array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+}
+
+// MP4
+
+/// The atoms directly inside `data`, as (type, body) pairs
+fn atoms(data: &[u8]) -> io::Result<Vec<([u8; 4], &[u8])>> {
+    let mut atoms = Vec::new();
+    let mut cursor = Cursor::new(data);
+    while cursor.data.len() >= 8 {
+        let size = cursor.u32()? as u64;
+        let kind = cursor.array()?;
+        let body_len = match size {
+            0 => cursor.data.len() as u64,
+            1 => cursor.u64()?.checked_sub(16).ok_or(malformed("MP4 atom"))?,
+            size => size.checked_sub(8).ok_or(malformed("MP4 atom"))?,
+        };
+        atoms.push((kind, cursor.take(body_len as usize)?));
+    }
+    Ok(atoms)
+}
+
+/// The first atom of type `kind` directly inside `data`
+fn child<'a>(data: &'a [u8], kind: &[u8; 4]) -> io::Result<Option<&'a [u8]>> {
+    Ok(atoms(data)?
+        .into_iter()
+        .find(|(found, _)| found == kind)
+        .map(|(_, body)| body))
+}
+
+/// The atom at the end of `path` below `data`
+fn descend<'a>(data: &'a [u8], path: &[&[u8; 4]]) -> io::Result<Option<&'a [u8]>> {
+    let mut current = data;
+    for kind in path {
+        match child(current, kind)? {
+            Some(body) => current = body,
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(current))
+}
+
+fn mp4_markers(file: &mut File) -> io::Result<Vec<ChapterMarker>> {
+    let Some(moov) = read_moov(file)? else {
+        return Ok(Vec::new());
+    };
+    let tracks: Vec<&[u8]> = atoms(&moov)?
+        .into_iter()
+        .filter(|(kind, _)| kind == b"trak")
+        .map(|(_, body)| body)
+        .collect();
+
+    // The text track an audio track names as its chapters
+    for track in &tracks {
+        let Some(tref) = descend(track, &[b"tref", b"chap"])? else {
+            continue;
+        };
+        let mut ids = Cursor::new(tref);
+        while let Ok(id) = ids.u32() {
+            for candidate in &tracks {
+                if track_id(candidate)? == Some(id) {
+                    return text_track_markers(file, candidate);
+                }
+            }
+        }
+    }
+
+    match descend(&moov, &[b"udta", b"chpl"])? {
+        Some(chpl) => nero_markers(chpl),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Finds the top-level `moov` atom and reads it whole
+fn read_moov(file: &mut File) -> io::Result<Option<Vec<u8>>> {
+    let length = file.metadata()?.len();
+    let mut position = 0;
+    while position + 8 <= length {
+        file.seek(SeekFrom::Start(position))?;
+        let mut header = [0; 8];
+        file.read_exact(&mut header)?;
+        let size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as u64;
+        let (header_len, size) = match size {
+            0 => (8, length - position),
+            1 => {
+                let mut large = [0; 8];
+                file.read_exact(&mut large)?;
+                (16, u64::from_be_bytes(large))
+            }
+            size => (8, size),
+        };
+        if size < header_len {
+            return Err(malformed("MP4 atom"));
+        }
+
+        if &header[4..] == b"moov" {
+            let body_len = size - header_len;
+            if body_len > MAX_HEADER_BYTES {
+                return Err(malformed("MP4 movie header"));
+            }
+            let mut moov = vec![0; body_len as usize];
+            file.read_exact(&mut moov)?;
+            return Ok(Some(moov));
+        }
+        position += size;
+    }
+    Ok(None)
+}
+
+fn track_id(track: &[u8]) -> io::Result<Option<u32>> {
+    let Some(tkhd) = child(track, b"tkhd")? else {
+        return Ok(None);
+    };
+    let mut cursor = Cursor::new(tkhd);
+    // Creation and modification times are 32 or 64 bits by version
+    let skip = if cursor.u8()? == 1 { 3 + 16 } else { 3 + 8 };
+    cursor.take(skip)?;
+    Ok(Some(cursor.u32()?))
+}
+
+/// Chapters stored as the samples of a text track
+fn text_track_markers(file: &mut File, track: &[u8]) -> io::Result<Vec<ChapterMarker>> {
+    let mdhd = descend(track, &[b"mdia", b"mdhd"])?.ok_or(malformed("MP4 media header"))?;
+    let mut cursor = Cursor::new(mdhd);
+    let skip = if cursor.u8()? == 1 { 3 + 16 } else { 3 + 8 };
+    cursor.take(skip)?;
+    let timescale = u64::from(cursor.u32()?);
+    if timescale == 0 {
+        return Err(malformed("MP4 media header"));
+    }
+
+    let stbl =
+        descend(track, &[b"mdia", b"minf", b"stbl"])?.ok_or(malformed("MP4 sample table"))?;
+    let times = sample_times(stbl)?;
+    let locations = sample_locations(stbl)?;
+
+    let to_duration = |ticks: u64| Duration::from_millis(ticks * 1000 / timescale);
+    let mut markers = Vec::with_capacity(times.len());
+    for ((start, length), (offset, size)) in times.into_iter().zip(locations) {
+        file.seek(SeekFrom::Start(offset))?;
+        let mut sample = vec![0; size.min(64 * 1024) as usize];
+        file.read_exact(&mut sample)?;
+        let mut cursor = Cursor::new(&sample);
+        let text_len = cursor.u16()? as usize;
+        let text = cursor.take(text_len.min(cursor.data.len()))?;
+        markers.push(ChapterMarker {
+            title: decode_text_sample(text),
+            start: to_duration(start),
+            end: Some(to_duration(start + length)),
+        });
+    }
+    Ok(markers)
+}
+
+/// Start and length of each sample, in the track's time units
+fn sample_times(stbl: &[u8]) -> io::Result<Vec<(u64, u64)>> {
+    let stts = child(stbl, b"stts")?.ok_or(malformed("MP4 sample times"))?;
+    let mut cursor = Cursor::new(stts);
+    cursor.take(4)?;
+    let mut times = Vec::new();
+    let mut start = 0;
+    for _ in 0..cursor.u32()? {
+        let (count, delta) = (cursor.u32()?, u64::from(cursor.u32()?));
+        for _ in 0..count.min(u16::MAX as u32) {
+            times.push((start, delta));
+            start += delta;
+        }
+    }
+    Ok(times)
+}
+
+/// File offset and size of each sample
+fn sample_locations(stbl: &[u8]) -> io::Result<Vec<(u64, u64)>> {
+    let stsz = child(stbl, b"stsz")?.ok_or(malformed("MP4 sample sizes"))?;
+    let mut cursor = Cursor::new(stsz);
+    cursor.take(4)?;
+    let fixed_size = cursor.u32()?;
+    let count = cursor.u32()?.min(u16::MAX as u32);
+    let sizes = (0..count)
+        .map(|_| match fixed_size {
+            0 => cursor.u32().map(u64::from),
+            size => Ok(u64::from(size)),
+        })
+        .collect::<io::Result<Vec<u64>>>()?;
+
+    let chunks = match (child(stbl, b"stco")?, child(stbl, b"co64")?) {
+        (Some(stco), _) => {
+            let mut cursor = Cursor::new(stco);
+            cursor.take(4)?;
+            (0..cursor.u32()?)
+                .map(|_| cursor.u32().map(u64::from))
+                .collect::<io::Result<Vec<u64>>>()?
+        }
+        (None, Some(co64)) => {
+            let mut cursor = Cursor::new(co64);
+            cursor.take(4)?;
+            (0..cursor.u32()?)
+                .map(|_| cursor.u64())
+                .collect::<io::Result<Vec<u64>>>()?
+        }
+        (None, None) => return Err(malformed("MP4 chunk offsets")),
+    };
+
+    // Runs of chunks holding the same number of samples
+    let stsc = child(stbl, b"stsc")?.ok_or(malformed("MP4 sample chunks"))?;
+    let mut cursor = Cursor::new(stsc);
+    cursor.take(4)?;
+    let mut runs = Vec::new();
+    for _ in 0..cursor.u32()? {
+        let (first_chunk, samples) = (cursor.u32()?, cursor.u32()?);
+        cursor.take(4)?;
+        runs.push((first_chunk.saturating_sub(1) as usize, samples as usize));
+    }
+
+    let mut locations = Vec::with_capacity(sizes.len());
+    let mut sizes = sizes.into_iter();
+    for (run, (first_chunk, samples)) in runs.iter().enumerate() {
+        let last_chunk = runs.get(run + 1).map_or(chunks.len(), |next| next.0);
+        for offset in chunks.get(*first_chunk..last_chunk).unwrap_or_default() {
+            let mut offset = *offset;
+            for size in sizes.by_ref().take(*samples) {
+                locations.push((offset, size));
+                offset += size;
+            }
+        }
+    }
+    Ok(locations)
+}
+
+/// The title in a text track sample, UTF-16 when it starts with a BOM
+fn decode_text_sample(text: &[u8]) -> String {
+    match text {
+        [0xfe, 0xff, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        _ => String::from_utf8_lossy(text).into_owned(),
+    }
+}
+
+/// Chapters of a Nero `chpl` atom, whose times count 100 ns units
+fn nero_markers(chpl: &[u8]) -> io::Result<Vec<ChapterMarker>> {
+    let mut cursor = Cursor::new(chpl);
+    let version = cursor.u8()?;
+    cursor.take(3)?;
+    if version > 0 {
+        cursor.take(4)?;
+    }
+    let mut markers = Vec::new();
+    for _ in 0..cursor.u8()? {
+        let start = cursor.u64()?;
+        let title_len = cursor.u8()? as usize;
+        let title = cursor.take(title_len)?;
+        markers.push(ChapterMarker {
+            title: String::from_utf8_lossy(title).into_owned(),
+            start: Duration::from_millis(start / 10_000),
+            end: None,
+        });
+    }
+    Ok(markers)
+}
+
+// ID3v2
+
+fn id3_markers(file: &mut File) -> io::Result<Vec<ChapterMarker>> {
+    let mut header = [0; 10];
+    file.read_exact(&mut header)?;
+    let version = header[3];
+    let flags = header[5];
+    let size = syncsafe(&header[6..10]);
+    // Chapter frames arrived with ID3v2.3
+    if !(3..=4).contains(&version) {
+        return Ok(Vec::new());
+    }
+    if u64::from(size) > MAX_HEADER_BYTES {
+        return Err(malformed("ID3v2 tag"));
+    }
+
+    let mut tag = vec![0; size as usize];
+    file.read_exact(&mut tag)?;
+    if version == 3 && flags & 0x80 != 0 {
+        tag = resynchronise(&tag);
+    }
+    let mut frames_start = 0;
+    if flags & 0x40 != 0 {
+        let mut cursor = Cursor::new(&tag);
+        frames_start = match version {
+            3 => cursor.u32()? as usize + 4,
+            _ => syncsafe(cursor.take(4)?) as usize,
+        };
+    }
+
+    let frames_data = tag.get(frames_start..).ok_or(malformed("ID3v2 tag"))?;
+    let mut markers = Vec::new();
+    for (id, body) in frames(frames_data, version)? {
+        if &id != b"CHAP" {
+            continue;
+        }
+        let mut cursor = Cursor::new(&body);
+        while cursor.u8()? != 0 {}
+        let start = cursor.u32()?;
+        let end = cursor.u32()?;
+        cursor.take(8)?;
+
+        let title = frames(cursor.data, version)?
+            .into_iter()
+            .find(|(id, _)| id == b"TIT2")
+            .map(|(_, text)| decode_text_frame(&text))
+            .unwrap_or_default();
+        markers.push(ChapterMarker {
+            title,
+            start: Duration::from_millis(u64::from(start)),
+            end: (end > start).then(|| Duration::from_millis(u64::from(end))),
+        });
+    }
+    Ok(markers)
+}
+
+/// The frames in `data` with their bodies as stored, skipping compressed
+/// and encrypted ones
+fn frames(data: &[u8], version: u8) -> io::Result<Vec<([u8; 4], Vec<u8>)>> {
+    let mut frames = Vec::new();
+    let mut cursor = Cursor::new(data);
+    while cursor.data.len() >= 10 && cursor.data[0] != 0 {
+        let id: [u8; 4] = cursor.array()?;
+        let size = match version {
+            3 => cursor.u32()?,
+            _ => syncsafe(cursor.take(4)?),
+        };
+        let flags = cursor.u16()?;
+        let mut body = cursor.take(size as usize)?;
+
+        let (compressed, encrypted) = match version {
+            3 => (flags & 0x0080 != 0, flags & 0x0040 != 0),
+            _ => (flags & 0x0008 != 0, flags & 0x0004 != 0),
+        };
+        if compressed || encrypted {
+            continue;
+        }
+        if version == 4 {
+            // Group byte, then the data length indicator
+            let extra = usize::from(flags & 0x0040 != 0) + 4 * usize::from(flags & 0x0001 != 0);
+            body = body.get(extra..).ok_or(malformed("ID3v2 frame"))?;
+        } else if flags & 0x0020 != 0 {
+            body = body.get(1..).ok_or(malformed("ID3v2 frame"))?;
+        }
+        let body = if version == 4 && flags & 0x0002 != 0 {
+            resynchronise(body)
+        } else {
+            body.to_vec()
+        };
+        frames.push((id, body));
+    }
+    Ok(frames)
+}
+
+fn syncsafe(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .fold(0, |size, byte| (size << 7) | u32::from(byte & 0x7f))
+}
+
+/// Undoes ID3 unsynchronisation, which follows every 0xFF with a 0x00
+fn resynchronise(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut previous = 0;
+    for &byte in data {
+        if !(previous == 0xff && byte == 0) {
+            out.push(byte);
+        }
+        previous = byte;
+    }
+    out
+}
+
+/// The text of a text frame such as `TIT2`
+fn decode_text_frame(frame: &[u8]) -> String {
+    let Some((&encoding, text)) = frame.split_first() else {
+        return String::new();
+    };
+    let text = match encoding {
+        0 => text.iter().map(|&byte| char::from(byte)).collect(),
+        1 => match text {
+            [0xff, 0xfe, rest @ ..] => utf16(rest, u16::from_le_bytes),
+            [0xfe, 0xff, rest @ ..] => utf16(rest, u16::from_be_bytes),
+            _ => utf16(text, u16::from_le_bytes),
+        },
+        2 => utf16(text, u16::from_be_bytes),
+        _ => String::from_utf8_lossy(text).into_owned(),
+    };
+    text.trim_end_matches('\0').to_string()
+}
+
+fn utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| unit([pair[0], pair[1]]))
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn atom(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut atom = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+        atom.extend_from_slice(kind);
+        atom.extend_from_slice(body);
+        atom
+    }
+
+    fn full_atom(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        atom(kind, &[&[0u8; 4][..], body].concat())
+    }
+
+    fn words(values: &[u32]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_be_bytes())
+            .collect()
+    }
+
+    fn id3_frame(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut frame = id.to_vec();
+        frame.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        frame.extend_from_slice(&[0, 0]);
+        frame.extend_from_slice(body);
+        frame
+    }
+
+    fn chap(element: &str, start: u32, end: u32, title: Option<&str>) -> Vec<u8> {
+        let mut body = element.as_bytes().to_vec();
+        body.push(0);
+        body.extend(words(&[start, end, u32::MAX, u32::MAX]));
+        if let Some(title) = title {
+            body.extend(id3_frame(b"TIT2", &[&[3], title.as_bytes()].concat()));
+        }
+        id3_frame(b"CHAP", &body)
+    }
+
+    fn id3_file(dir: &TempDir, frames: &[Vec<u8>]) -> std::path::PathBuf {
+        let frames = frames.concat();
+        let size = frames.len() as u32;
+        let mut tag = b"ID3\x03\x00\x00".to_vec();
+        tag.extend((0..4).rev().map(|i| ((size >> (7 * i)) & 0x7f) as u8));
+        tag.extend(frames);
+        // An MPEG frame header follows the tag
+        tag.extend_from_slice(&[0xff, 0xfb, 0x90, 0x00]);
+        let path = dir.path().join("book.mp3");
+        std::fs::write(&path, tag).unwrap();
+        path
+    }
+
+    fn book(duration_ms: u64) -> Book {
+        Book::new(
+            "Book".to_string(),
+            "/books/book.m4b".into(),
+            1000,
+            Duration::from_millis(duration_ms),
+        )
+    }
+
+    #[test]
+    fn test_id3_chapters() {
+        let dir = TempDir::new().unwrap();
+        let path = id3_file(
+            &dir,
+            &[
+                id3_frame(b"TIT2", b"\x03The Book"),
+                chap("ch1", 60_000, 125_000, Some("Chapter Two")),
+                chap("ch0", 0, 60_000, Some("Opening Credits")),
+                chap("ch2", 125_000, 0, None),
+            ],
+        );
+
+        let markers = read_markers(&path).unwrap();
+        assert_eq!(markers.len(), 3);
+        assert_eq!(markers[0].title, "Chapter Two");
+        assert_eq!(markers[0].start, Duration::from_millis(60_000));
+        assert_eq!(markers[0].end, Some(Duration::from_millis(125_000)));
+        assert_eq!(markers[2].end, None);
+
+        let chapters = to_chapters(&book(200_000), markers);
+        let titles: Vec<_> = chapters.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, ["Opening Credits", "Chapter Two", "Chapter 3"]);
+        assert_eq!(chapters[2].end_time, Duration::from_millis(200_000));
+    }
+
+    #[test]
+    fn test_mp4_chapter_track() {
+        let dir = TempDir::new().unwrap();
+        // Two chapter titles stored back to back in one chunk
+        let samples = [&b"\x00\x05Intro"[..], &b"\x00\x09The Shire"[..]].concat();
+        let ftyp = atom(b"ftyp", b"M4B \x00\x00\x00\x00");
+        let mdat_offset = (ftyp.len() + 8) as u32;
+        let mdat = atom(b"mdat", &samples);
+
+        let audio = atom(
+            b"trak",
+            &[
+                full_atom(b"tkhd", &words(&[0, 0, 1])),
+                atom(b"tref", &atom(b"chap", &words(&[2]))),
+            ]
+            .concat(),
+        );
+        let stbl = [
+            full_atom(b"stts", &words(&[2, 1, 30_000, 1, 90_000])),
+            full_atom(b"stsz", &words(&[0, 2, 7, 11])),
+            full_atom(b"stsc", &words(&[1, 1, 2, 1])),
+            full_atom(b"stco", &words(&[1, mdat_offset])),
+        ]
+        .concat();
+        let text = atom(
+            b"trak",
+            &[
+                full_atom(b"tkhd", &words(&[0, 0, 2])),
+                atom(
+                    b"mdia",
+                    &[
+                        full_atom(b"mdhd", &words(&[0, 0, 1000, 120_000])),
+                        atom(b"minf", &atom(b"stbl", &stbl)),
+                    ]
+                    .concat(),
+                ),
+            ]
+            .concat(),
+        );
+        let moov = atom(b"moov", &[audio, text].concat());
+
+        let path = dir.path().join("book.m4b");
+        std::fs::write(&path, [ftyp, mdat, moov].concat()).unwrap();
+        let markers = read_markers(&path).unwrap();
+        assert_eq!(
+            markers,
+            [
+                ChapterMarker {
+                    title: "Intro".to_string(),
+                    start: Duration::ZERO,
+                    end: Some(Duration::from_millis(30_000)),
+                },
+                ChapterMarker {
+                    title: "The Shire".to_string(),
+                    start: Duration::from_millis(30_000),
+                    end: Some(Duration::from_millis(120_000)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nero_chapters() {
+        let dir = TempDir::new().unwrap();
+        let mut chpl = vec![1, 0, 0, 0, 0, 0, 0, 0, 2];
+        for (start_secs, title) in [(0u64, "One"), (90, "Two")] {
+            chpl.extend((start_secs * 10_000_000).to_be_bytes());
+            chpl.push(title.len() as u8);
+            chpl.extend_from_slice(title.as_bytes());
+        }
+        let file = [
+            atom(b"ftyp", b"M4A \x00\x00\x00\x00"),
+            atom(b"moov", &atom(b"udta", &atom(b"chpl", &chpl))),
+            atom(b"mdat", &[0; 16]),
+        ]
+        .concat();
+        let path = dir.path().join("book.m4a");
+        std::fs::write(&path, file).unwrap();
+
+        let chapters = to_chapters(&book(120_000), read_markers(&path).unwrap());
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[1].title, "Two");
+        assert_eq!(chapters[1].start_time, Duration::from_millis(90_000));
+        assert_eq!(chapters[0].end_time, Duration::from_millis(90_000));
+    }
+
+    #[test]
+    fn test_files_without_or_with_broken_chapters() {
+        let dir = TempDir::new().unwrap();
+        let path = id3_file(&dir, &[id3_frame(b"TIT2", b"\x03No chapters")]);
+        assert!(read_markers(&path).unwrap().is_empty());
+
+        let wav = dir.path().join("book.wav");
+        std::fs::write(&wav, b"RIFF\x00\x00\x00\x00WAVE").unwrap();
+        assert!(read_markers(&wav).unwrap().is_empty());
+
+        // A chapter frame cut short
+        let path = id3_file(&dir, &[id3_frame(b"CHAP", b"ch0\x00\x00\x00")]);
+        assert!(read_markers(&path).is_err());
+
+        // Chapters past the end of the book are dropped
+        let markers = vec![
+            ChapterMarker {
+                title: "In".to_string(),
+                start: Duration::ZERO,
+                end: None,
+            },
+            ChapterMarker {
+                title: "Out".to_string(),
+                start: Duration::from_millis(5_000),
+                end: None,
+            },
+        ];
+        let chapters = to_chapters(&book(4_000), markers);
+        assert_eq!(chapters.len(), 1);
+        assert_eq!(chapters[0].end_time, Duration::from_millis(4_000));
+    }
+}
@@ -0,0 +1,447 @@
+// FILE: crates/library/src/organize.rs
+//! Managed storage: filing book files under a library root
+//!
+//! A template such as `{author}/{series}/{title}` names the folder below the
+//! root that a book's file goes into. Files keep their own names, and each
+//! folder gets a breadcrumb recording where its files came from.
+
+use crate::error::{LibraryError, Result};
+use crate::verify::hash_file;
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use storystream_core::{Book, BookId};
+use tracing::{debug, info};
+
+/// Breadcrumb listing the original location of each file in a folder
+pub const ORIGIN_FILE: &str = ".storystream-origin";
+
+/// Placeholders a template may use, named after the book fields they read
+pub const TOKENS: [&str; 6] = ["author", "narrator", "series", "position", "title", "year"];
+
+/// Folder used when a book has no author
+const UNKNOWN_AUTHOR: &str = "Unknown Author";
+
+/// Whether organizing leaves the original file behind
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OrganizeMode {
+    /// Copy the file and keep the original
+    #[default]
+    Copy,
+    /// Move the file; across volumes it is copied, verified, then deleted
+    Move,
+}
+
+/// Where and how book files are filed
+#[derive(Debug, Clone)]
+pub struct OrganizeTemplate {
+    root: PathBuf,
+    pattern: String,
+    mode: OrganizeMode,
+}
+
+impl OrganizeTemplate {
+    /// Layout used unless another pattern is given
+    pub const DEFAULT_PATTERN: &'static str = "{author}/{series}/{title}";
+
+    /// Files books below `root`, which should be an absolute path
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            pattern: Self::DEFAULT_PATTERN.to_string(),
+            mode: OrganizeMode::default(),
+        }
+    }
+
+    /// Sets the folder pattern
+    ///
+    /// # Errors
+    ///
+    /// Returns `LibraryError::InvalidTemplate` for an unknown or unclosed token.
+    pub fn with_pattern(mut self, pattern: impl Into<String>) -> Result<Self> {
+        let pattern = pattern.into();
+        validate_pattern(&pattern)?;
+        self.pattern = pattern;
+        Ok(self)
+    }
+
+    /// Sets whether files are copied or moved
+    pub fn with_mode(mut self, mode: OrganizeMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn mode(&self) -> OrganizeMode {
+        self.mode
+    }
+
+    /// Folder the template puts `book` in
+    ///
+    /// Segments that render empty, such as `{series}` for a standalone book,
+    /// are left out.
+    pub fn folder(&self, book: &Book) -> PathBuf {
+        let mut folder = self.root.clone();
+        for segment in self.pattern.split(['/', '\\']) {
+            let name = sanitize_segment(&render_segment(segment, book));
+            if !name.is_empty() {
+                folder.push(name);
+            }
+        }
+        folder
+    }
+
+    /// Path the template gives the book's file
+    pub fn destination(&self, book: &Book) -> PathBuf {
+        match book.file_path.file_name() {
+            Some(name) => self.folder(book).join(name),
+            None => self.folder(book),
+        }
+    }
+
+    /// Works out where each book's file would go, without touching any
+    ///
+    /// Destinations already taken on disk or by an earlier book in the plan
+    /// get a numbered suffix.
+    pub fn plan(&self, books: &[Book]) -> OrganizePlan {
+        let mut plan = OrganizePlan::default();
+        let mut taken = HashSet::new();
+
+        for book in books {
+            if !book.file_path.is_file() {
+                plan.missing.push(book.id);
+                continue;
+            }
+            let destination = self.destination(book);
+            if destination == book.file_path {
+                plan.in_place += 1;
+                taken.insert(destination);
+                continue;
+            }
+
+            let to = free_destination(&destination, &taken);
+            taken.insert(to.clone());
+            plan.moves.push(PlannedMove {
+                book_id: book.id,
+                title: book.title.clone(),
+                from: book.file_path.clone(),
+                to,
+                bytes: book.file_size,
+            });
+        }
+
+        plan
+    }
+}
+
+/// A file organizing would move or copy
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedMove {
+    pub book_id: BookId,
+    pub title: String,
+    pub from: PathBuf,
+    pub to: PathBuf,
+    pub bytes: u64,
+}
+
+/// What reorganizing the library does, or would do on a dry run
+#[derive(Debug, Clone, Default)]
+pub struct OrganizePlan {
+    /// Files to file away, in library order
+    pub moves: Vec<PlannedMove>,
+    /// Books already where the template puts them
+    pub in_place: usize,
+    /// Books whose file is missing; they are left alone
+    pub missing: Vec<BookId>,
+}
+
+impl OrganizePlan {
+    /// Bytes written if every file has to be copied
+    pub fn total_bytes(&self) -> u64 {
+        self.moves.iter().map(|m| m.bytes).sum()
+    }
+}
+
+/// Checks that every `{token}` in `pattern` is known
+fn validate_pattern(pattern: &str) -> Result<()> {
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            return Err(LibraryError::InvalidTemplate(format!(
+                "unclosed '{{' in '{}'",
+                pattern
+            )));
+        };
+        let token = &rest[start + 1..start + len];
+        if !TOKENS.contains(&token) {
+            return Err(LibraryError::InvalidTemplate(format!(
+                "unknown token {{{}}}; use one of {}",
+                token,
+                TOKENS.map(|t| format!("{{{}}}", t)).join(", ")
+            )));
+        }
+        rest = &rest[start + len + 1..];
+    }
+    Ok(())
+}
+
+/// Replaces the tokens in one folder segment with the book's values
+fn render_segment(segment: &str, book: &Book) -> String {
+    let year = book
+        .published_date
+        .as_deref()
+        .and_then(|date| date.get(..4))
+        .filter(|year| year.chars().all(|c| c.is_ascii_digit()))
+        .unwrap_or_default();
+    let position = book
+        .series_position
+        .map(|p| p.to_string())
+        .unwrap_or_default();
+
+    segment
+        .replace("{author}", book.author.as_deref().unwrap_or(UNKNOWN_AUTHOR))
+        .replace("{narrator}", book.narrator.as_deref().unwrap_or_default())
+        .replace("{series}", book.series.as_deref().unwrap_or_default())
+        .replace("{position}", &position)
+        .replace("{title}", &book.title)
+        .replace("{year}", year)
+}
+
+/// Makes a rendered segment safe as a single folder name
+pub(crate) fn sanitize_segment(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    // Trailing dots and spaces break Windows paths, and ".." would escape the root
+    name.trim().trim_matches('.').trim().to_string()
+}
+
+/// `destination`, or the first free "name (n).ext" next to it
+pub(crate) fn free_destination(destination: &Path, taken: &HashSet<PathBuf>) -> PathBuf {
+    let is_free = |path: &Path| !path.exists() && !taken.contains(path);
+    if is_free(destination) {
+        return destination.to_path_buf();
+    }
+
+    let stem = destination
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = destination
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    (2..)
+        .map(|n| destination.with_file_name(format!("{} ({}){}", stem, n, extension)))
+        .find(|path| is_free(path))
+        .expect("some suffix is free")
+}
+
+/// Files `from` at `to` and leaves a breadcrumb in the folder
+///
+/// # Errors
+///
+/// Returns `LibraryError::DiskFull` if the destination volume cannot hold
+/// the file; nothing is left half-written.
+pub(crate) fn place_file(from: &Path, to: &Path, mode: OrganizeMode) -> Result<()> {
+    let folder = to
+        .parent()
+        .ok_or_else(|| LibraryError::InvalidFile(format!("No folder in {}", to.display())))?;
+    fs::create_dir_all(folder)?;
+
+    match mode {
+        OrganizeMode::Copy => copy_verified(from, to)?,
+        OrganizeMode::Move => match fs::rename(from, to) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+                debug!("{} is on another volume, copying", to.display());
+                copy_verified(from, to)?;
+                fs::remove_file(from)?;
+            }
+            Err(e) => return Err(e.into()),
+        },
+    }
+
+    let mut breadcrumb = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(folder.join(ORIGIN_FILE))?;
+    writeln!(
+        breadcrumb,
+        "{}\t{}",
+        to.file_name().unwrap_or_default().to_string_lossy(),
+        from.display()
+    )?;
+
+    info!("Filed {} at {}", from.display(), to.display());
+    Ok(())
+}
+
+/// Copies `from` to `to` and checks the copy against the original
+fn copy_verified(from: &Path, to: &Path) -> Result<()> {
+    let needed = fs::metadata(from)?.len();
+    ensure_space(to, needed)?;
+
+    if let Err(e) = fs::copy(from, to) {
+        let _ = fs::remove_file(to);
+        return Err(match e.kind() {
+            io::ErrorKind::StorageFull => LibraryError::DiskFull {
+                needed,
+                available: available_space(to).unwrap_or(0),
+            },
+            _ => e.into(),
+        });
+    }
+
+    if hash_file(from)? != hash_file(to)? {
+        let _ = fs::remove_file(to);
+        return Err(LibraryError::InvalidFile(format!(
+            "Copy of {} does not match the original",
+            from.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Fails with `DiskFull` unless the volume holding `path` has `needed` bytes free
+pub(crate) fn ensure_space(path: &Path, needed: u64) -> Result<()> {
+    let available = available_space(path)?;
+    if available < needed {
+        return Err(LibraryError::DiskFull { needed, available });
+    }
+    Ok(())
+}
+
+/// Free bytes on the volume of `path`, or of its nearest existing ancestor
+fn available_space(path: &Path) -> Result<u64> {
+    let existing = path
+        .ancestors()
+        .find(|p| p.exists())
+        .unwrap_or_else(|| Path::new("."));
+    Ok(fs4::available_space(existing)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use storystream_core::Duration;
+    use tempfile::TempDir;
+
+    fn book(title: &str, path: PathBuf) -> Book {
+        Book::new(title.to_string(), path, 4, Duration::from_seconds(60))
+    }
+
+    #[test]
+    fn test_folder_skips_empty_segments() {
+        let template = OrganizeTemplate::new("/library");
+        let mut book = book("Dune", PathBuf::from("/in/dune.mp3"));
+        book.author = Some("Frank Herbert".to_string());
+
+        assert_eq!(
+            template.destination(&book),
+            PathBuf::from("/library/Frank Herbert/Dune/dune.mp3")
+        );
+
+        book.series = Some("Dune".to_string());
+        book.series_position = Some(1.0);
+        let template = template
+            .with_pattern("{author}/{series} {position}/{title}")
+            .unwrap();
+        assert_eq!(
+            template.folder(&book),
+            PathBuf::from("/library/Frank Herbert/Dune 1/Dune")
+        );
+
+        book.author = None;
+        book.title = "What/If?".to_string();
+        assert_eq!(
+            template.folder(&book),
+            PathBuf::from("/library/Unknown Author/Dune 1/What_If_")
+        );
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        let template = OrganizeTemplate::new("/library");
+        assert!(matches!(
+            template.clone().with_pattern("{author}/{genre}"),
+            Err(LibraryError::InvalidTemplate(_))
+        ));
+        assert!(matches!(
+            template.with_pattern("{author"),
+            Err(LibraryError::InvalidTemplate(_))
+        ));
+    }
+
+    #[test]
+    fn test_plan_suffixes_collisions() {
+        let source = TempDir::new().unwrap();
+        let root = TempDir::new().unwrap();
+        let template = OrganizeTemplate::new(root.path())
+            .with_pattern("{author}")
+            .unwrap();
+
+        let mut books = Vec::new();
+        for dir in ["a", "b"] {
+            let path = source.path().join(dir).join("book.mp3");
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, b"data").unwrap();
+            books.push(book("Book", path));
+        }
+        books.push(book("Gone", source.path().join("gone.mp3")));
+
+        let plan = template.plan(&books);
+        assert_eq!(plan.missing, vec![books[2].id]);
+        assert_eq!(plan.moves.len(), 2);
+        let folder = root.path().join(UNKNOWN_AUTHOR);
+        assert_eq!(plan.moves[0].to, folder.join("book.mp3"));
+        assert_eq!(plan.moves[1].to, folder.join("book (2).mp3"));
+        assert_eq!(plan.total_bytes(), 8);
+    }
+
+    #[test]
+    fn test_place_file_leaves_breadcrumb() {
+        let dir = TempDir::new().unwrap();
+        let from = dir.path().join("book.mp3");
+        fs::write(&from, b"audio").unwrap();
+
+        let copied = dir.path().join("copy").join("book.mp3");
+        place_file(&from, &copied, OrganizeMode::Copy).unwrap();
+        assert!(from.exists());
+        assert_eq!(fs::read(&copied).unwrap(), b"audio");
+
+        let moved = dir.path().join("moved").join("book.mp3");
+        place_file(&from, &moved, OrganizeMode::Move).unwrap();
+        assert!(!from.exists());
+
+        let breadcrumb = fs::read_to_string(moved.parent().unwrap().join(ORIGIN_FILE)).unwrap();
+        assert_eq!(breadcrumb, format!("book.mp3\t{}\n", from.display()));
+    }
+
+    #[test]
+    fn test_ensure_space() {
+        let dir = TempDir::new().unwrap();
+        let missing = dir.path().join("not").join("yet");
+        assert!(ensure_space(&missing, 1).is_ok());
+        assert!(matches!(
+            ensure_space(&missing, u64::MAX),
+            Err(LibraryError::DiskFull {
+                needed: u64::MAX,
+                ..
+            })
+        ));
+    }
+}
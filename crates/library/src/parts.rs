@@ -0,0 +1,146 @@
+// FILE: crates/library/src/parts.rs
+//! The files a book plays from
+//!
+//! Most books are a single file. A book stored as a folder is split over
+//! the audio files in it, typically one per part or chapter. Those play in
+//! the order of their names, with runs of digits compared by value so that
+//! "Part 2" comes before "Part 10" however the numbers were padded.
+
+use crate::error::{LibraryError, Result};
+use crate::metadata::MetadataExtractor;
+use std::cmp::Ordering;
+use std::fs;
+use std::path::{Path, PathBuf};
+use storystream_core::Book;
+
+/// Lists the files of `book` in the order they play
+///
+/// Fails when a folder book holds no audio files.
+pub fn book_files(book: &Book) -> Result<Vec<PathBuf>> {
+    if !book.file_path.is_dir() {
+        return Ok(vec![book.file_path.clone()]);
+    }
+    folder_files(&book.file_path)
+}
+
+/// Lists the audio files in `folder` in the order they play
+///
+/// Fails when the folder holds no audio files.
+pub fn folder_files(folder: &Path) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = fs::read_dir(folder)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && MetadataExtractor::is_supported(path))
+        .collect();
+    if files.is_empty() {
+        return Err(LibraryError::InvalidFile(format!(
+            "{} holds no audio files",
+            folder.display()
+        )));
+    }
+    files.sort_by(|a, b| natural_cmp(&sort_name(a), &sort_name(b)));
+    Ok(files)
+}
+
+fn sort_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+/// Compares names so that numbers in them sort by value
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a = a.chars().peekable();
+    let mut b = b.chars().peekable();
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let number = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+                    let mut digits = String::new();
+                    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                        digits.push(digit);
+                    }
+                    digits
+                };
+                let (x, y) = (number(&mut a), number(&mut b));
+                let (x_value, y_value) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+                let order = x_value
+                    .len()
+                    .cmp(&y_value.len())
+                    .then_with(|| x_value.cmp(y_value))
+                    .then_with(|| x.len().cmp(&y.len()));
+                if order != Ordering::Equal {
+                    return order;
+                }
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(&y);
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use storystream_core::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_natural_order() {
+        let mut names = vec!["part 10.mp3", "part 2.mp3", "part 01.mp3", "intro.mp3"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+
+        assert_eq!(
+            names,
+            vec!["intro.mp3", "part 01.mp3", "part 2.mp3", "part 10.mp3"]
+        );
+    }
+
+    #[test]
+    fn test_book_files() {
+        let dir = TempDir::new().unwrap();
+        for name in [
+            "Chapter 10.mp3",
+            "Chapter 9.mp3",
+            "cover.jpg",
+            "Chapter 1.mp3",
+        ] {
+            fs::write(dir.path().join(name), b"audio").unwrap();
+        }
+        let folder = Book::new(
+            "Folder".to_string(),
+            dir.path().to_path_buf(),
+            0,
+            Duration::from_seconds(0),
+        );
+        let names: Vec<String> = book_files(&folder)
+            .unwrap()
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["Chapter 1.mp3", "Chapter 9.mp3", "Chapter 10.mp3"]);
+
+        let single = Book::new(
+            "Single".to_string(),
+            dir.path().join("Chapter 1.mp3"),
+            5,
+            Duration::from_seconds(0),
+        );
+        assert_eq!(book_files(&single).unwrap(), vec![single.file_path.clone()]);
+
+        let empty = TempDir::new().unwrap();
+        let mut book = folder;
+        book.file_path = empty.path().to_path_buf();
+        assert!(matches!(
+            book_files(&book),
+            Err(LibraryError::InvalidFile(_))
+        ));
+    }
+}
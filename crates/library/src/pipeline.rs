@@ -0,0 +1,649 @@
+//! Bulk import of whole libraries
+//!
+//! The scanner feeds found files to a pool of workers that read their
+//! metadata, and the results are written to the database in batches. Every
+//! stage is bounded: when one falls behind, the stages before it wait
+//! instead of opening more files or piling up books in memory.
+
+use crate::error::{LibraryError, Result};
+use crate::fingerprint::{content_hash, missing_books};
+use crate::import::store_chapters;
+use crate::metadata::MetadataExtractor;
+use crate::reconcile::record_file_state;
+use crate::scanner::{LibraryScanner, ScanCancel};
+use crate::verify::hash_file;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use storystream_core::{Book, BookId, Chapter};
+use storystream_database::{queries::books, DbPool};
+use storystream_resilience::Bulkhead;
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+use tracing::{info, instrument, warn};
+
+/// Files read at once
+const DEFAULT_WORKERS: usize = 4;
+
+/// Most books written in one transaction
+const DEFAULT_BATCH_SIZE: usize = 100;
+
+/// Files or books waiting between two stages
+const DEFAULT_QUEUE_CAPACITY: usize = 64;
+
+/// A stage of the pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PipelineStage {
+    /// Walking the library folders
+    Scan,
+    /// Reading tags and hashing files
+    Extract,
+    /// Storing books in the database
+    Write,
+}
+
+impl PipelineStage {
+    /// Short description for progress displays
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Self::Scan => "scanning folders",
+            Self::Extract => "reading metadata",
+            Self::Write => "writing to the database",
+        }
+    }
+}
+
+/// How much work is waiting at each stage
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueueDepths {
+    /// Files found that no worker has picked up yet
+    pub scanned: usize,
+    /// Files being read by a worker
+    pub extracting: usize,
+    /// Books read but not yet written
+    pub writing: usize,
+}
+
+impl QueueDepths {
+    /// The stage holding the others up
+    ///
+    /// Work piles up in front of the slowest stage; with both queues empty,
+    /// the later stages are waiting for the scanner.
+    pub fn bottleneck(&self) -> PipelineStage {
+        if self.writing > 0 && self.writing >= self.scanned {
+            PipelineStage::Write
+        } else if self.scanned > 0 {
+            PipelineStage::Extract
+        } else {
+            PipelineStage::Scan
+        }
+    }
+}
+
+/// Counts of an import so far
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PipelineProgress {
+    /// Files handed over by the scanner
+    pub found: usize,
+    /// Books added to the library
+    pub imported: usize,
+    /// Files already in the library
+    pub skipped: usize,
+    /// Files that could not be read
+    pub failed: usize,
+    pub queues: QueueDepths,
+}
+
+/// Progress of a pipelined import
+#[derive(Debug, Clone)]
+pub enum PipelineEvent {
+    /// A file was read, skipped or failed, or a batch was written
+    Progress(PipelineProgress),
+    /// The import ended, after adding `imported` books
+    Finished { imported: usize, cancelled: bool },
+}
+
+/// Outcome of a pipelined import
+#[derive(Debug, Clone, Default)]
+pub struct PipelineReport {
+    /// Files handed over by the scanner
+    pub found: usize,
+    /// Books added to the library
+    pub imported: usize,
+    /// Files already in the library, or found twice
+    pub skipped: usize,
+    /// Books whose file turned up somewhere else, now pointed at it
+    pub relocated: usize,
+    /// Files that could not be read, with the reason
+    pub failed: Vec<(PathBuf, String)>,
+    /// Whether the import was cancelled before every file was read
+    pub cancelled: bool,
+}
+
+/// A file read by a worker, with its hash and content fingerprint
+struct ReadFile {
+    book: Book,
+    hash: Option<String>,
+    content_hash: Option<String>,
+    chapters: Vec<Chapter>,
+}
+
+/// What a worker made of one file
+enum FileOutcome {
+    Read(Box<ReadFile>),
+    Known,
+    Failed(PathBuf, String),
+}
+
+/// Imports everything a scanner finds, reading several files at once
+///
+/// Only adds files that are not in the library yet, except that a file
+/// with the same contents as a book whose file went missing moves that book
+/// to it. Use [`BookImporter`] to refresh or organize single books.
+///
+/// [`BookImporter`]: crate::import::BookImporter
+pub struct ImportPipeline {
+    pool: DbPool,
+    workers: usize,
+    batch_size: usize,
+    queue_capacity: usize,
+    cancel: ScanCancel,
+    events: Option<mpsc::Sender<PipelineEvent>>,
+}
+
+impl ImportPipeline {
+    pub fn new(pool: DbPool) -> Self {
+        Self {
+            pool,
+            workers: DEFAULT_WORKERS,
+            batch_size: DEFAULT_BATCH_SIZE,
+            queue_capacity: DEFAULT_QUEUE_CAPACITY,
+            cancel: ScanCancel::new(),
+            events: None,
+        }
+    }
+
+    /// Reads up to `files` files at once
+    pub fn with_workers(mut self, files: usize) -> Self {
+        self.workers = files.max(1);
+        self
+    }
+
+    /// Writes at most `books` books per transaction
+    pub fn with_batch_size(mut self, books: usize) -> Self {
+        self.batch_size = books.max(1);
+        self
+    }
+
+    /// Lets up to `items` files or books wait between two stages
+    pub fn with_queue_capacity(mut self, items: usize) -> Self {
+        self.queue_capacity = items.max(1);
+        self
+    }
+
+    /// Uses `cancel` to stop imports instead of the pipeline's own token
+    pub fn with_cancel(mut self, cancel: ScanCancel) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Sends progress to `events` as files go through the pipeline
+    pub fn with_events(mut self, events: mpsc::Sender<PipelineEvent>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Returns a token that cancels this pipeline's imports
+    pub fn cancel_handle(&self) -> ScanCancel {
+        self.cancel.clone()
+    }
+
+    /// Imports the new files `scanner` finds
+    ///
+    /// Books are written as soon as a batch is full or no more are ready, so
+    /// they show up in the library while the import runs. Cancelling stops
+    /// the scan and the workers; the books already read are still written.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the scan or a database write fails. Files that cannot be
+    /// read are listed in the report instead.
+    #[instrument(name = "import_pipeline", skip_all, fields(workers = self.workers))]
+    pub async fn run(&self, scanner: LibraryScanner) -> Result<PipelineReport> {
+        let known = Arc::new(Mutex::new(books::get_file_paths(&self.pool).await?));
+        let mut missing = missing_books(&self.pool).await?;
+        let extractor =
+            Arc::new(MetadataExtractor::new().map_err(|e| LibraryError::Other(e.to_string()))?);
+
+        // Closing them on cancel is final, so each run gets its own. Files
+        // hold a queue slot from when they are taken from the scanner until
+        // their book is handed to the writer, and a worker slot while read.
+        let queue = Bulkhead::new(self.workers + self.queue_capacity);
+        let workers = Bulkhead::new(self.workers);
+        let found = Arc::new(AtomicUsize::new(0));
+
+        let scanner = scanner.with_cancel(self.cancel.clone());
+        let (files_tx, mut files_rx) = mpsc::channel(1);
+        let scan = tokio::spawn(async move { scanner.scan_into(files_tx).await });
+
+        let (books_tx, mut books_rx) = mpsc::channel(self.queue_capacity);
+        let dispatch = {
+            let (queue, workers) = (queue.clone(), workers.clone());
+            let found = Arc::clone(&found);
+            let cancel = self.cancel.clone();
+            tokio::spawn(async move {
+                let mut tasks = JoinSet::new();
+                while let Some(path) = files_rx.recv().await {
+                    found.fetch_add(1, Ordering::SeqCst);
+                    let Ok(queued) = queue.acquire().await else {
+                        break;
+                    };
+                    let workers = workers.clone();
+                    let cancel = cancel.clone();
+                    let (known, extractor) = (Arc::clone(&known), Arc::clone(&extractor));
+                    let books_tx = books_tx.clone();
+                    tasks.spawn(async move {
+                        let _queued = queued;
+                        let Ok(_permit) = workers.acquire().await else {
+                            return;
+                        };
+                        if cancel.is_cancelled() {
+                            return;
+                        }
+                        let outcome = tokio::task::spawn_blocking(move || {
+                            read_file(path, &extractor, &known)
+                        })
+                        .await;
+                        if let Ok(outcome) = outcome {
+                            let _ = books_tx.send(outcome).await;
+                        }
+                    });
+                    while tasks.try_join_next().is_some() {}
+                }
+                while tasks.join_next().await.is_some() {}
+            })
+        };
+
+        let mut report = PipelineReport::default();
+        let mut batch = Vec::new();
+        let mut hashes = HashMap::new();
+        let mut content_hashes = HashMap::new();
+        let mut chapters = HashMap::new();
+        while let Some(outcome) = books_rx.recv().await {
+            if self.cancel.is_cancelled() && !queue.is_closed() {
+                // Wakes the files still waiting for a slot so they give up
+                queue.close();
+                workers.close();
+            }
+            match outcome {
+                FileOutcome::Read(read) => {
+                    let ReadFile {
+                        book,
+                        hash,
+                        content_hash,
+                        chapters: found,
+                    } = *read;
+                    let moved = content_hash.as_ref().and_then(|hash| missing.remove(hash));
+                    if let Some(moved) = moved {
+                        info!(
+                            "{} moved from {} to {}",
+                            moved.title,
+                            moved.file_path.display(),
+                            book.file_path.display()
+                        );
+                        let relocated =
+                            books::relocate_book(&self.pool, moved.id, &book.file_path).await;
+                        if let Err(e) = relocated {
+                            scan.abort();
+                            dispatch.abort();
+                            return Err(e.into());
+                        }
+                        report.relocated += 1;
+                    } else {
+                        if let Some(hash) = hash {
+                            hashes.insert(book.id, hash);
+                        }
+                        if let Some(content_hash) = content_hash {
+                            content_hashes.insert(book.id, content_hash);
+                        }
+                        if !found.is_empty() {
+                            chapters.insert(book.id, found);
+                        }
+                        batch.push(book);
+                    }
+                }
+                FileOutcome::Known => report.skipped += 1,
+                FileOutcome::Failed(path, reason) => {
+                    warn!("Could not import {}: {}", path.display(), reason);
+                    report.failed.push((path, reason));
+                }
+            }
+
+            // Nothing else ready means the writer is not the slow stage, so
+            // waiting for a full batch would only delay the books
+            if batch.len() >= self.batch_size || books_rx.is_empty() {
+                let written = self
+                    .write(
+                        &mut batch,
+                        &mut hashes,
+                        &mut content_hashes,
+                        &mut chapters,
+                        &mut report,
+                    )
+                    .await;
+                if let Err(e) = written {
+                    scan.abort();
+                    dispatch.abort();
+                    return Err(e);
+                }
+            }
+
+            let extracting = workers.max_concurrent() - workers.available();
+            let queues = QueueDepths {
+                scanned: (queue.max_concurrent() - queue.available()).saturating_sub(extracting),
+                extracting,
+                writing: books_rx.len() + batch.len(),
+            };
+            self.send(PipelineEvent::Progress(PipelineProgress {
+                found: found.load(Ordering::SeqCst),
+                imported: report.imported,
+                skipped: report.skipped,
+                failed: report.failed.len(),
+                queues,
+            }))
+            .await;
+        }
+        self.write(
+            &mut batch,
+            &mut hashes,
+            &mut content_hashes,
+            &mut chapters,
+            &mut report,
+        )
+        .await?;
+
+        report.found = found.load(Ordering::SeqCst);
+        report.cancelled = self.cancel.is_cancelled();
+        match scan.await {
+            Ok(Ok(_)) | Ok(Err(LibraryError::Cancelled)) => {}
+            Ok(Err(e)) => return Err(e),
+            Err(e) => return Err(LibraryError::Other(e.to_string())),
+        }
+
+        info!(
+            "Imported {} of {} file(s) ({} already in the library, {} moved, {} failed)",
+            report.imported,
+            report.found,
+            report.skipped,
+            report.relocated,
+            report.failed.len()
+        );
+        self.send(PipelineEvent::Finished {
+            imported: report.imported,
+            cancelled: report.cancelled,
+        })
+        .await;
+        Ok(report)
+    }
+
+    /// Writes the batch in one transaction, then its fingerprints and
+    /// chapters, and empties it
+    async fn write(
+        &self,
+        batch: &mut Vec<Book>,
+        hashes: &mut HashMap<BookId, String>,
+        content_hashes: &mut HashMap<BookId, String>,
+        chapters: &mut HashMap<BookId, Vec<Chapter>>,
+        report: &mut PipelineReport,
+    ) -> Result<()> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+        books::create_books(&self.pool, batch, hashes).await?;
+        books::set_content_hashes(&self.pool, content_hashes).await?;
+        record_file_state(&self.pool, batch.iter()).await?;
+        for book in batch.iter() {
+            if let Some(found) = chapters.get(&book.id) {
+                store_chapters(&self.pool, book, found).await?;
+            }
+        }
+        report.imported += batch.len();
+        batch.clear();
+        hashes.clear();
+        content_hashes.clear();
+        chapters.clear();
+        Ok(())
+    }
+
+    async fn send(&self, event: PipelineEvent) {
+        if let Some(tx) = &self.events {
+            let _ = tx.send(event).await;
+        }
+    }
+}
+
+/// Reads a file's book, unless another book already has the file
+fn read_file(
+    path: PathBuf,
+    extractor: &MetadataExtractor,
+    known: &Mutex<HashSet<PathBuf>>,
+) -> FileOutcome {
+    let canonical = match path.canonicalize() {
+        Ok(canonical) => canonical,
+        Err(e) => return FileOutcome::Failed(path, e.to_string()),
+    };
+    // A file found through two library folders is only imported once
+    let new = known
+        .lock()
+        .map(|mut known| known.insert(canonical.clone()))
+        .unwrap_or(false);
+    if !new {
+        return FileOutcome::Known;
+    }
+
+    let metadata = match extractor.extract(&path) {
+        Ok(metadata) => metadata,
+        Err(e) => return FileOutcome::Failed(path, e.to_string()),
+    };
+    let mut book = extractor.to_book(&path, metadata);
+    book.file_path = canonical;
+
+    let hash = hash_file(&book.file_path)
+        .map_err(|e| warn!("Could not hash {}: {}", book.file_path.display(), e))
+        .ok();
+    let content_hash = content_hash(&book.file_path, book.duration)
+        .map_err(|e| warn!("Could not fingerprint {}: {}", book.file_path.display(), e))
+        .ok();
+    let chapters = extractor.read_chapters(&book);
+    FileOutcome::Read(Box::new(ReadFile {
+        book,
+        hash,
+        content_hash,
+        chapters,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::{BookImporter, ImportOptions};
+    use std::path::Path;
+    use std::time::Instant;
+    use storystream_database::{
+        connection::{connect, DatabaseConfig},
+        migrations::run_migrations,
+    };
+    use storystream_media_formats::wav::wav_bytes;
+    use tempfile::TempDir;
+
+    async fn setup(dir: &Path) -> DbPool {
+        let db_path = dir.join("library.db");
+        let pool = connect(DatabaseConfig::new(db_path.to_str().unwrap()))
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool
+    }
+
+    /// Writes `count` distinct WAV files of 0.1s under `dir`
+    fn synthetic_library(dir: &Path, count: usize) -> Vec<PathBuf> {
+        let books = dir.join("books");
+        std::fs::create_dir_all(&books).unwrap();
+        (0..count)
+            .map(|i| {
+                let path = books.join(format!("book-{:03}.wav", i));
+                std::fs::write(&path, wav_bytes(800, i as u8)).unwrap();
+                path
+            })
+            .collect()
+    }
+
+    fn scanner(dir: &Path) -> LibraryScanner {
+        LibraryScanner::new(vec![dir.join("books").display().to_string()])
+    }
+
+    #[test]
+    fn test_bottleneck() {
+        let depths = |scanned, writing| QueueDepths {
+            scanned,
+            extracting: 4,
+            writing,
+        };
+        assert_eq!(depths(0, 0).bottleneck(), PipelineStage::Scan);
+        assert_eq!(depths(30, 2).bottleneck(), PipelineStage::Extract);
+        assert_eq!(depths(30, 60).bottleneck(), PipelineStage::Write);
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_imports_new_files_once() {
+        let dir = TempDir::new().unwrap();
+        let pool = setup(dir.path()).await;
+        synthetic_library(dir.path(), 12);
+        std::fs::write(dir.path().join("books/broken.mp3"), [0x5a; 2048]).unwrap();
+
+        let (tx, mut rx) = mpsc::channel(64);
+        let pipeline = ImportPipeline::new(pool.clone())
+            .with_workers(3)
+            .with_batch_size(5)
+            .with_queue_capacity(4)
+            .with_events(tx);
+        let collect = async {
+            let mut events = Vec::new();
+            while let Some(event) = rx.recv().await {
+                events.push(event);
+            }
+            events
+        };
+        // The pipeline owns the sender, so the events end with the run
+        let library = scanner(dir.path());
+        let (report, events) = tokio::join!(async move { pipeline.run(library).await }, collect);
+        let report = report.unwrap();
+        assert_eq!(report.found, 13);
+        assert_eq!(report.imported, 12);
+        assert_eq!(report.failed.len(), 1);
+        assert!(!report.cancelled);
+        assert_eq!(books::list_books(&pool).await.unwrap().len(), 12);
+        assert_eq!(books::get_file_hashes(&pool).await.unwrap().len(), 12);
+
+        // No stage ever holds more than it is allowed
+        for event in &events {
+            if let PipelineEvent::Progress(progress) = event {
+                assert!(progress.queues.extracting <= 3);
+                assert!(progress.queues.scanned + progress.queues.extracting <= 7);
+                assert!(progress.queues.writing <= 4 + 5);
+            }
+        }
+        assert!(matches!(
+            events.last(),
+            Some(PipelineEvent::Finished {
+                imported: 12,
+                cancelled: false
+            })
+        ));
+
+        let report = ImportPipeline::new(pool.clone())
+            .run(scanner(dir.path()))
+            .await
+            .unwrap();
+        assert_eq!(report.imported, 0);
+        assert_eq!(report.skipped, 12);
+    }
+
+    #[tokio::test]
+    async fn test_rescan_after_reorganizing_moves_books() {
+        let dir = TempDir::new().unwrap();
+        let pool = setup(dir.path()).await;
+        let paths = synthetic_library(dir.path(), 3);
+        let pipeline = ImportPipeline::new(pool.clone());
+        pipeline.run(scanner(dir.path())).await.unwrap();
+        let before = books::list_books(&pool).await.unwrap();
+
+        let sorted = dir.path().join("books/Some Author/First Book.wav");
+        std::fs::create_dir_all(sorted.parent().unwrap()).unwrap();
+        std::fs::rename(&paths[0], &sorted).unwrap();
+
+        let report = pipeline.run(scanner(dir.path())).await.unwrap();
+        assert_eq!((report.imported, report.relocated), (0, 1));
+
+        let after = books::list_books(&pool).await.unwrap();
+        assert_eq!(after.len(), 3);
+        let moved = after
+            .iter()
+            .find(|book| book.file_path == sorted.canonicalize().unwrap())
+            .expect("moved book keeps its row");
+        assert!(before.iter().any(|book| book.id == moved.id));
+        assert!(books::find_duplicate_books(&pool).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_pipeline_imports_nothing_more() {
+        let dir = TempDir::new().unwrap();
+        let pool = setup(dir.path()).await;
+        synthetic_library(dir.path(), 8);
+
+        let pipeline = ImportPipeline::new(pool.clone());
+        pipeline.cancel_handle().cancel();
+        let report = pipeline.run(scanner(dir.path())).await.unwrap();
+        assert!(report.cancelled);
+        assert_eq!(report.imported, 0);
+        assert!(books::list_books(&pool).await.unwrap().is_empty());
+    }
+
+    /// Imports the same synthetic library one file at a time and through
+    /// the pipeline; run with `--nocapture` to see the timings
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_pipelined_import_outpaces_serial() {
+        const FILES: usize = 60;
+
+        let serial_dir = TempDir::new().unwrap();
+        let serial_pool = setup(serial_dir.path()).await;
+        let files = synthetic_library(serial_dir.path(), FILES);
+        let started = Instant::now();
+        let importer = BookImporter::new(serial_pool);
+        for file in &files {
+            importer
+                .import_file(file, ImportOptions::new())
+                .await
+                .unwrap();
+        }
+        let serial = started.elapsed();
+
+        let pipelined_dir = TempDir::new().unwrap();
+        let pipelined_pool = setup(pipelined_dir.path()).await;
+        synthetic_library(pipelined_dir.path(), FILES);
+        let started = Instant::now();
+        let report = ImportPipeline::new(pipelined_pool)
+            .run(scanner(pipelined_dir.path()))
+            .await
+            .unwrap();
+        let pipelined = started.elapsed();
+        assert_eq!(report.imported, FILES);
+
+        println!(
+            "{} files: serial {:?}, pipelined {:?}",
+            FILES, serial, pipelined
+        );
+        assert!(pipelined < serial);
+    }
+}
@@ -0,0 +1,218 @@
+//! Cross-checking the library against the files on disk
+//!
+//! After drives are moved around, books can point at files that are gone
+//! and the library folders can hold files no book points at. A
+//! [`LibraryReport`] lists both, along with files that changed since they
+//! were read, using only what a directory listing tells: existence, size
+//! and modification time.
+
+use crate::error::Result;
+use crate::scanner::KnownFile;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use storystream_core::{Book, BookId, Timestamp};
+use storystream_database::{queries::books, DbPool};
+
+/// How the library and the files on disk disagree
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LibraryReport {
+    /// Books checked, not counting those in the trash
+    pub checked: usize,
+    /// Books whose file is gone
+    pub missing: Vec<Book>,
+    /// Audio files in the library folders that no book points at
+    pub orphaned: Vec<PathBuf>,
+    /// Books whose file changed since it was read
+    pub modified: Vec<ModifiedFile>,
+}
+
+impl LibraryReport {
+    /// Whether the library and the disk agree
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty() && self.orphaned.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// A book whose file changed on disk
+#[derive(Debug, Clone, Serialize)]
+pub struct ModifiedFile {
+    pub book: Book,
+    /// Size of the file now; the book holds the size when it was read
+    pub size: u64,
+    /// When the file was last changed, if the file system says
+    pub modified_at: Option<Timestamp>,
+}
+
+/// Modification time of a file, in milliseconds since the epoch
+pub(crate) fn modified_millis(path: &Path) -> Option<i64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_millis() as i64)
+}
+
+/// Remembers the modification time of the books' files, so a later
+/// [`LibraryReport`] can tell when they change
+pub(crate) async fn record_file_state<'a>(
+    pool: &DbPool,
+    read: impl IntoIterator<Item = &'a Book>,
+) -> Result<()> {
+    let modified: HashMap<BookId, i64> = read
+        .into_iter()
+        .filter_map(|book| Some((book.id, modified_millis(&book.file_path)?)))
+        .collect();
+    if !modified.is_empty() {
+        books::set_file_modified(pool, &modified).await?;
+    }
+    Ok(())
+}
+
+/// The files of the library's books, with their size and modification
+/// time when read, for an incremental [`LibraryScanner`](crate::LibraryScanner)
+pub async fn known_files(pool: &DbPool) -> Result<HashMap<PathBuf, KnownFile>> {
+    let modified = books::get_file_modified(pool).await?;
+    Ok(books::list_books(pool)
+        .await?
+        .into_iter()
+        .map(|book| {
+            let known = KnownFile {
+                size: book.file_size,
+                modified_ms: modified.get(&book.id).copied(),
+            };
+            (book.file_path, known)
+        })
+        .collect())
+}
+
+/// Compares `library` with the disk
+///
+/// `files` are the audio files found in the library folders and `known` the
+/// paths of every book, including those in the trash. `file_modified` holds
+/// the modification times recorded when books were read; books without one
+/// are only checked for a change in size. Books made of a folder of files
+/// are only checked for existence, and the files inside them are not
+/// orphans.
+pub(crate) fn reconcile(
+    library: Vec<Book>,
+    files: Vec<PathBuf>,
+    known: &HashSet<PathBuf>,
+    file_modified: &HashMap<BookId, i64>,
+) -> LibraryReport {
+    let mut report = LibraryReport {
+        checked: library.len(),
+        ..LibraryReport::default()
+    };
+
+    let folders: Vec<&Path> = known
+        .iter()
+        .filter(|path| path.is_dir())
+        .map(PathBuf::as_path)
+        .collect();
+    let mut seen = HashSet::new();
+    for file in files {
+        let path = file.canonicalize().unwrap_or(file);
+        let in_book_folder = folders.iter().any(|folder| path.starts_with(folder));
+        if !known.contains(&path) && !in_book_folder && seen.insert(path.clone()) {
+            report.orphaned.push(path);
+        }
+    }
+    report.orphaned.sort();
+
+    for book in library {
+        let Ok(metadata) = std::fs::metadata(&book.file_path) else {
+            report.missing.push(book);
+            continue;
+        };
+        if metadata.is_dir() {
+            continue;
+        }
+        let modified = modified_millis(&book.file_path);
+        let size_changed = metadata.len() != book.file_size;
+        let time_changed = matches!(
+            (file_modified.get(&book.id), modified),
+            (Some(stored), Some(now)) if *stored != now
+        );
+        if size_changed || time_changed {
+            report.modified.push(ModifiedFile {
+                size: metadata.len(),
+                modified_at: modified.map(Timestamp::from_millis),
+                book,
+            });
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use storystream_core::Duration;
+    use tempfile::TempDir;
+
+    fn book_at(path: &Path, size: u64) -> Book {
+        Book::new(
+            "Book".to_string(),
+            path.to_path_buf(),
+            size,
+            Duration::from_seconds(60),
+        )
+    }
+
+    #[test]
+    fn test_reconcile_sorts_files_into_lists() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let write = |name: &str, bytes: &[u8]| {
+            let path = root.join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, bytes).unwrap();
+            path
+        };
+
+        let same = write("same.mp3", b"12345");
+        let resized = write("resized.mp3", b"123456789");
+        let touched = write("touched.mp3", b"12345");
+        let orphan = write("new/orphan.mp3", b"12345");
+        let part = write("Folder Book/01.mp3", b"12345");
+        let gone = root.join("gone.mp3");
+
+        let library = vec![
+            book_at(&same, 5),
+            book_at(&resized, 5),
+            book_at(&touched, 5),
+            book_at(&gone, 5),
+            book_at(&root.join("Folder Book"), 5),
+        ];
+        let known: HashSet<PathBuf> = library.iter().map(|b| b.file_path.clone()).collect();
+        let file_modified = HashMap::from([
+            (library[0].id, modified_millis(&same).unwrap()),
+            (library[2].id, modified_millis(&touched).unwrap() - 60_000),
+        ]);
+        let files = vec![
+            same.clone(),
+            resized.clone(),
+            touched.clone(),
+            orphan.clone(),
+            orphan.clone(),
+            part,
+        ];
+
+        let report = reconcile(library.clone(), files, &known, &file_modified);
+        assert_eq!(report.checked, 5);
+        assert!(!report.is_clean());
+        assert_eq!(report.orphaned, vec![orphan]);
+        let missing: Vec<BookId> = report.missing.iter().map(|b| b.id).collect();
+        assert_eq!(missing, vec![library[3].id]);
+        let modified: Vec<BookId> = report.modified.iter().map(|m| m.book.id).collect();
+        assert_eq!(modified, vec![library[1].id, library[2].id]);
+        assert_eq!(report.modified[0].size, 9);
+        assert!(report.modified[1].modified_at.is_some());
+    }
+
+    #[test]
+    fn test_empty_library_is_clean() {
+        let report = reconcile(Vec::new(), Vec::new(), &HashSet::new(), &HashMap::new());
+        assert!(report.is_clean());
+        assert_eq!(report.checked, 0);
+    }
+}
@@ -0,0 +1,244 @@
+// FILE: crates/library/src/replace.rs
+//! Swapping a book's file for another copy of the same recording
+//!
+//! An upgraded rip is a new file but the same book, so the listener's
+//! position, bookmarks and statistics stay with it. Only what describes the
+//! file changes: path, size, duration and hash. Copies rarely last exactly
+//! as long as each other, so positions are scaled by the ratio of the two
+//! durations, and copies that differ by more than
+//! [`DURATION_TOLERANCE`] are refused as different recordings.
+
+use crate::error::{LibraryError, Result};
+use crate::metadata::MetadataExtractor;
+use crate::verify::hash_file;
+use std::path::{Path, PathBuf};
+use storystream_core::types::chapters::normalize_chapters;
+use storystream_core::{Book, Chapter, Duration};
+use storystream_database::queries::books::MovedPositions;
+use tracing::warn;
+
+/// Largest difference between the two files' durations, as a fraction of
+/// the book's current duration
+pub const DURATION_TOLERANCE: f64 = 0.03;
+
+/// What happened to a book's chapters when its file was replaced
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChapterOutcome {
+    /// The book had no chapters and the new file has none either
+    None,
+    /// The stored chapters still fit the new file unchanged
+    Kept(usize),
+    /// The stored chapters were stretched to the new duration
+    Adjusted(usize),
+    /// The stored chapters did not fit, so the new file's were read
+    Reextracted(usize),
+}
+
+/// The result of replacing a book's file
+#[derive(Debug, Clone)]
+pub struct SourceReplacement {
+    /// The book as it is now stored
+    pub book: Book,
+    /// The file the book used before; it is left on disk
+    pub old_path: PathBuf,
+    pub old_duration: Duration,
+    pub chapters: ChapterOutcome,
+    /// Bookmarks and playback position that moved with the new duration
+    pub moved: MovedPositions,
+}
+
+impl SourceReplacement {
+    /// Describes what was kept and what was adjusted, one line per item
+    pub fn summary(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "Now playing from {} ({})",
+            self.book.file_path.display(),
+            describe_change(self.old_duration, self.book.duration)
+        )];
+        lines.push(match self.chapters {
+            ChapterOutcome::None => "No chapters".to_string(),
+            ChapterOutcome::Kept(count) => format!("Kept {} chapter(s)", count),
+            ChapterOutcome::Adjusted(count) => format!("Adjusted {} chapter(s)", count),
+            ChapterOutcome::Reextracted(count) => {
+                format!("Read {} chapter(s) from the new file", count)
+            }
+        });
+        if self.moved.bookmarks > 0 || self.moved.playback {
+            let mut moved = Vec::new();
+            if self.moved.bookmarks > 0 {
+                moved.push(format!("{} bookmark(s)", self.moved.bookmarks));
+            }
+            if self.moved.playback {
+                moved.push("the playback position".to_string());
+            }
+            lines.push(format!("Adjusted {}", moved.join(" and ")));
+        } else {
+            lines.push("Kept bookmarks and position".to_string());
+        }
+        lines
+    }
+}
+
+/// The audio of a replacement file, read before anything is stored
+pub(crate) struct NewSource {
+    pub path: PathBuf,
+    pub file_size: u64,
+    pub duration: Duration,
+    pub hash: Option<String>,
+    /// Chapters marked in the new file
+    pub chapters: Vec<Chapter>,
+}
+
+impl NewSource {
+    /// Reads and hashes `path` as the new file of `book`
+    ///
+    /// Blocks while the file is read, so it belongs on a blocking thread.
+    pub fn read(book: &Book, path: PathBuf) -> Result<Self> {
+        let extractor =
+            MetadataExtractor::new().map_err(|e| LibraryError::MetadataError(e.to_string()))?;
+        let metadata = extractor
+            .extract(&path)
+            .map_err(|e| LibraryError::MetadataError(e.to_string()))?;
+        let hash = match hash_file(&path) {
+            Ok(hash) => Some(hash),
+            Err(e) => {
+                warn!("Could not hash {}: {}", path.display(), e);
+                None
+            }
+        };
+        let mut probe = book.clone();
+        probe.file_path = path.clone();
+        probe.duration = metadata.duration;
+        let chapters = extractor.read_chapters(&probe);
+        Ok(Self {
+            path,
+            file_size: metadata.file_size,
+            duration: metadata.duration,
+            hash,
+            chapters,
+        })
+    }
+}
+
+/// Checks that `path` can replace the file of a book and returns its
+/// canonical form
+pub(crate) fn check_new_path(book: &Book, path: &Path) -> Result<PathBuf> {
+    if !path.is_file() {
+        return Err(LibraryError::FileNotFound(path.display().to_string()));
+    }
+    if !MetadataExtractor::is_supported(path) {
+        return Err(LibraryError::UnsupportedFormat(path.display().to_string()));
+    }
+    let path = path.canonicalize()?;
+    if path == book.file_path {
+        return Err(LibraryError::InvalidFile(format!(
+            "{} is already the file of '{}'",
+            path.display(),
+            book.title
+        )));
+    }
+    Ok(path)
+}
+
+/// Returns the factor that maps positions in `old` onto `new`
+///
+/// Fails when the durations differ by more than [`DURATION_TOLERANCE`]. A
+/// book whose duration was never known takes the new one unscaled.
+pub(crate) fn duration_scale(old: Duration, new: Duration) -> Result<f64> {
+    if old.is_zero() || old == new {
+        return Ok(1.0);
+    }
+    let scale = new.as_millis() as f64 / old.as_millis() as f64;
+    if (scale - 1.0).abs() > DURATION_TOLERANCE {
+        return Err(LibraryError::InvalidFile(format!(
+            "the new file lasts {} but the book lasts {}, so it is probably a different recording",
+            new, old
+        )));
+    }
+    Ok(scale)
+}
+
+/// Stretches chapters by `scale` and ends them with `duration`, or `None`
+/// when the result is no longer a valid chapter list
+pub(crate) fn scale_chapters(
+    chapters: &[Chapter],
+    scale: f64,
+    duration: Duration,
+) -> Option<Vec<Chapter>> {
+    let mut scaled = chapters.to_vec();
+    for chapter in &mut scaled {
+        chapter.start_time = scale_position(chapter.start_time, scale);
+        chapter.end_time = scale_position(chapter.end_time, scale);
+    }
+    normalize_chapters(&mut scaled, duration).ok()?;
+    Some(scaled)
+}
+
+fn scale_position(position: Duration, scale: f64) -> Duration {
+    Duration::from_millis((position.as_millis() as f64 * scale).round() as u64)
+}
+
+/// Describes how the duration changed, e.g. "42 seconds longer"
+fn describe_change(old: Duration, new: Duration) -> String {
+    let (difference, direction) = if new >= old {
+        (new.as_millis() - old.as_millis(), "longer")
+    } else {
+        (old.as_millis() - new.as_millis(), "shorter")
+    };
+    match difference / 1000 {
+        0 => "same length".to_string(),
+        seconds => format!("{} seconds {}", seconds, direction),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use storystream_core::BookId;
+
+    #[test]
+    fn test_duration_scale() {
+        let hour = Duration::from_seconds(3600);
+
+        assert_eq!(duration_scale(hour, hour).unwrap(), 1.0);
+        assert_eq!(
+            duration_scale(Duration::from_seconds(0), hour).unwrap(),
+            1.0
+        );
+        let scale = duration_scale(hour, Duration::from_seconds(3636)).unwrap();
+        assert!((scale - 1.01).abs() < 1e-9);
+        assert!(matches!(
+            duration_scale(hour, Duration::from_seconds(4000)),
+            Err(LibraryError::InvalidFile(_))
+        ));
+    }
+
+    #[test]
+    fn test_scale_chapters() {
+        let book = BookId::new();
+        let chapters = vec![
+            Chapter::new(
+                book,
+                "One".to_string(),
+                0,
+                Duration::from_seconds(0),
+                Duration::from_seconds(1000),
+            ),
+            Chapter::new(
+                book,
+                "Two".to_string(),
+                1,
+                Duration::from_seconds(1000),
+                Duration::from_seconds(3600),
+            ),
+        ];
+
+        let scaled = scale_chapters(&chapters, 1.01, Duration::from_seconds(3636)).unwrap();
+        assert_eq!(scaled[1].start_time, Duration::from_seconds(1010));
+        assert_eq!(scaled[1].end_time, Duration::from_seconds(3636));
+
+        let mut broken = chapters.clone();
+        broken[1].title = String::new();
+        assert!(scale_chapters(&broken, 1.0, Duration::from_seconds(3600)).is_none());
+    }
+}
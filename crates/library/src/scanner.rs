@@ -1,15 +1,17 @@
 // FILE: crates/library/src/scanner.rs
 
 use crate::error::{LibraryError, Result};
-use log::{debug, error, info, warn};
+use crate::reconcile::modified_millis;
+use crate::volumes::is_connected;
 use notify::{Error as NotifyError, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use std::collections::HashSet;
-use std::path::{Path, PathBuf};
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc, Mutex,
 };
 use tokio::sync::mpsc;
+use tracing::{debug, error, info, instrument, warn};
 use walkdir::WalkDir;
 
 const DEFAULT_DEBOUNCE_MS: u64 = 500;
@@ -20,6 +22,57 @@ const SUPPORTED_EXTENSIONS: &[&str] = &[
     "mp3", "m4a", "m4b", "flac", "ogg", "opus", "aac", "wma", "wav", "aiff", "ape", "wv",
 ];
 
+/// How a scan treats files the library already has
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScanMode {
+    /// Hand over every file found
+    Full,
+    /// Pass over files whose size and modification time are those the
+    /// library recorded when it read them
+    #[default]
+    Incremental,
+}
+
+/// Size and modification time of a file when the library read it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KnownFile {
+    pub size: u64,
+    /// Milliseconds since the epoch, `None` if it was not recorded
+    pub modified_ms: Option<i64>,
+}
+
+impl KnownFile {
+    /// Whether the file at `path` still has this size and modification time
+    ///
+    /// Without a recorded modification time there is no telling, so the
+    /// file counts as changed.
+    pub fn is_unchanged(&self, path: &Path) -> bool {
+        let Some(modified_ms) = self.modified_ms else {
+            return false;
+        };
+        std::fs::metadata(path).is_ok_and(|metadata| metadata.len() == self.size)
+            && modified_millis(path) == Some(modified_ms)
+    }
+}
+
+/// What a scan found, by whether the library has seen the files before
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanSummary {
+    /// Files the library has no book for
+    pub new: usize,
+    /// Files the library has, handed over again; in a full scan, all of them
+    pub changed: usize,
+    /// Unchanged files passed over by an incremental scan
+    pub skipped: usize,
+}
+
+impl ScanSummary {
+    /// Files handed over by the scan
+    pub fn sent(&self) -> usize {
+        self.new + self.changed
+    }
+}
+
 /// Configuration for library scanner
 #[derive(Debug, Clone)]
 pub struct ScannerConfig {
@@ -35,6 +88,12 @@ pub struct ScannerConfig {
     pub supported_extensions: HashSet<String>,
     /// Debounce duration for file system events (milliseconds)
     pub debounce_ms: u64,
+    /// Whether scans pass over files the library already has unchanged
+    pub mode: ScanMode,
+    /// Glob patterns for paths to leave alone, matched against the full path
+    pub exclude_patterns: Vec<String>,
+    /// Leave alone files and folders whose name starts with a dot
+    pub ignore_hidden: bool,
 }
 
 impl Default for ScannerConfig {
@@ -46,6 +105,9 @@ impl Default for ScannerConfig {
             follow_symlinks: false,
             supported_extensions: SUPPORTED_EXTENSIONS.iter().map(|s| s.to_string()).collect(),
             debounce_ms: DEFAULT_DEBOUNCE_MS,
+            mode: ScanMode::default(),
+            exclude_patterns: Vec::new(),
+            ignore_hidden: true,
         }
     }
 }
@@ -77,6 +139,145 @@ impl ScannerConfig {
         self.supported_extensions = extensions.into_iter().collect();
         self
     }
+
+    pub fn with_mode(mut self, mode: ScanMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Leaves alone paths matching any of `patterns`
+    ///
+    /// `*` and `?` match within one path component and `**` across any
+    /// number of them, so `**/@eaDir` leaves out every `@eaDir` folder and
+    /// `**/*sample*` every file with "sample" in its name.
+    pub fn with_exclude_patterns(mut self, patterns: Vec<String>) -> Self {
+        self.exclude_patterns = patterns;
+        self
+    }
+
+    pub fn with_ignore_hidden(mut self, ignore: bool) -> Self {
+        self.ignore_hidden = ignore;
+        self
+    }
+
+    /// Whether scans and the watcher leave `path` alone
+    ///
+    /// A path is excluded when it or a folder it is in below the watch path
+    /// is hidden or matches an exclude pattern.
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        let relative = self
+            .watch_paths
+            .iter()
+            .find_map(|root| path.strip_prefix(root).ok())
+            .unwrap_or_else(|| path.file_name().map(Path::new).unwrap_or(path));
+
+        let is_hidden = |component: Component| match component {
+            Component::Normal(name) => name.to_string_lossy().starts_with('.'),
+            _ => false,
+        };
+        if self.ignore_hidden && relative.components().any(is_hidden) {
+            return true;
+        }
+        if self.exclude_patterns.is_empty() {
+            return false;
+        }
+
+        // The path and each folder it is in, up to the watch path
+        let depth = relative.components().count();
+        path.ancestors().take(depth.max(1)).any(|candidate| {
+            let text = path_text(candidate);
+            self.exclude_patterns
+                .iter()
+                .any(|pattern| glob_match(pattern, &text))
+        })
+    }
+}
+
+/// A path as matched by exclude patterns, with `/` between components
+fn path_text(path: &Path) -> String {
+    let text = path.to_string_lossy();
+    if cfg!(windows) {
+        text.replace('\\', "/")
+    } else {
+        text.into_owned()
+    }
+}
+
+/// Whether `text` matches the glob `pattern`
+///
+/// `**/` matches any number of leading folders, including none.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    enum Token {
+        Literal(char),
+        One,
+        Component,
+        Folders,
+        Anything,
+    }
+
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                if chars.get(i + 2) == Some(&'/') {
+                    tokens.push(Token::Folders);
+                    i += 3;
+                } else {
+                    tokens.push(Token::Anything);
+                    i += 2;
+                }
+                continue;
+            }
+            '*' => tokens.push(Token::Component),
+            '?' => tokens.push(Token::One),
+            c => tokens.push(Token::Literal(c)),
+        }
+        i += 1;
+    }
+
+    // matched[j]: the tokens so far match the first j characters of text
+    let text: Vec<char> = text.chars().collect();
+    let mut matched = vec![false; text.len() + 1];
+    matched[0] = true;
+    for token in &tokens {
+        let mut next = vec![false; text.len() + 1];
+        for start in (0..=text.len()).filter(|&j| matched[j]) {
+            match token {
+                Token::Literal(c) => {
+                    if text.get(start) == Some(c) {
+                        next[start + 1] = true;
+                    }
+                }
+                Token::One => {
+                    if text.get(start).is_some_and(|&c| c != '/') {
+                        next[start + 1] = true;
+                    }
+                }
+                Token::Component => {
+                    next[start] = true;
+                    for end in start..text.len() {
+                        if text[end] == '/' {
+                            break;
+                        }
+                        next[end + 1] = true;
+                    }
+                }
+                Token::Folders => {
+                    next[start] = true;
+                    for end in start..text.len() {
+                        if text[end] == '/' {
+                            next[end + 1] = true;
+                        }
+                    }
+                }
+                Token::Anything => next[start..].fill(true),
+            }
+        }
+        matched = next;
+    }
+    matched[text.len()]
 }
 
 /// Events emitted by the scanner
@@ -88,18 +289,50 @@ pub enum ScanEvent {
     FileModified(PathBuf),
     /// File was removed
     FileRemoved(PathBuf),
-    /// Scan completed with file count
-    ScanCompleted(usize),
+    /// Scan completed, with how many files were new, changed and skipped
+    ScanCompleted {
+        new: usize,
+        changed: usize,
+        skipped: usize,
+    },
     /// Error occurred during scanning
     ScanError(String),
 }
 
+/// Stops a running [`LibraryScanner::scan`] from another task or thread
+#[derive(Debug, Clone, Default)]
+pub struct ScanCancel(Arc<AtomicBool>);
+
+impl ScanCancel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests cancellation; the scan stops at the next file
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+impl From<Arc<AtomicBool>> for ScanCancel {
+    fn from(flag: Arc<AtomicBool>) -> Self {
+        Self(flag)
+    }
+}
+
 /// Library scanner for monitoring file changes
 pub struct LibraryScanner {
     config: ScannerConfig,
     running: Arc<AtomicBool>,
+    cancel: ScanCancel,
     watcher: Arc<Mutex<Option<RecommendedWatcher>>>,
     event_tx: Option<mpsc::Sender<ScanEvent>>,
+    /// Files the library has, by canonical path
+    known: HashMap<PathBuf, KnownFile>,
 }
 
 impl LibraryScanner {
@@ -113,29 +346,114 @@ impl LibraryScanner {
         Self {
             config,
             running: Arc::new(AtomicBool::new(false)),
+            cancel: ScanCancel::new(),
             watcher: Arc::new(Mutex::new(None)),
             event_tx: None,
+            known: HashMap::new(),
         }
     }
 
+    /// Uses `cancel` to stop scans instead of the scanner's own token
+    pub fn with_cancel(mut self, cancel: ScanCancel) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Tells scans which files the library has and what they were like
+    /// when read
+    ///
+    /// Without this every file found is new, whatever the mode.
+    pub fn with_known_files(mut self, known: HashMap<PathBuf, KnownFile>) -> Self {
+        self.known = known;
+        self
+    }
+
+    /// Returns a token that cancels this scanner's scans
+    pub fn cancel_handle(&self) -> ScanCancel {
+        self.cancel.clone()
+    }
+
     /// Scan all configured paths and return found audio files
+    ///
+    /// An incremental scan leaves out the unchanged files the library has.
     pub async fn scan(&self) -> Result<Vec<PathBuf>> {
+        Ok(self.scan_with_summary().await?.0)
+    }
+
+    /// Scans like [`scan`](Self::scan), also counting the files found
+    #[instrument(name = "library_scan", skip_all, fields(paths = self.config.watch_paths.len()))]
+    pub async fn scan_with_summary(&self) -> Result<(Vec<PathBuf>, ScanSummary)> {
         info!(
             "Starting library scan of {} paths",
             self.config.watch_paths.len()
         );
 
-        let mut found_files = Vec::new();
+        let (tx, mut rx) = mpsc::channel(CHANNEL_BUFFER_SIZE);
+        let collect = async {
+            let mut files = Vec::new();
+            while let Some(path) = rx.recv().await {
+                files.push(path);
+            }
+            files
+        };
+        let (scanned, found_files) = tokio::join!(self.scan_into(tx), collect);
+
+        let summary = match scanned {
+            Ok(summary) => summary,
+            Err(e) => {
+                if matches!(e, LibraryError::Cancelled) {
+                    info!(
+                        "Scan cancelled after finding {} audio files",
+                        found_files.len()
+                    );
+                }
+                return Err(e);
+            }
+        };
+
+        info!(
+            "Scan completed: {} new, {} changed and {} unchanged audio files",
+            summary.new, summary.changed, summary.skipped
+        );
+
+        // Send completion event if we have a channel
+        if let Some(tx) = &self.event_tx {
+            let _ = tx
+                .send(ScanEvent::ScanCompleted {
+                    new: summary.new,
+                    changed: summary.changed,
+                    skipped: summary.skipped,
+                })
+                .await;
+        }
+
+        Ok((found_files, summary))
+    }
+
+    /// Scans like [`scan`](Self::scan), sending each file to `found` as soon
+    /// as it is seen
+    ///
+    /// Waits while `found` is full, so a slow consumer slows the walk down.
+    /// Returns how many files were sent, and how many were passed over.
+    pub async fn scan_into(&self, found: mpsc::Sender<PathBuf>) -> Result<ScanSummary> {
+        let mut summary = ScanSummary::default();
         let mut scanned_paths = HashSet::new();
 
         for watch_path in &self.config.watch_paths {
             let path = PathBuf::from(watch_path);
 
-            // Skip if path doesn't exist
+            // Skip if path doesn't exist, or is a drive that isn't plugged in
             if !path.exists() {
                 warn!("Watch path does not exist: {}", watch_path);
                 continue;
             }
+            if !is_connected(&path) {
+                warn!(
+                    "Watch path is on a drive that is not connected: {}",
+                    watch_path
+                );
+                continue;
+            }
 
             // Skip if we've already scanned this path (handles duplicate paths)
             let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
@@ -147,36 +465,79 @@ impl LibraryScanner {
 
             // If it's a file, check if it's valid and add it
             if path.is_file() {
-                if self.is_valid_audio_file(&path)? {
-                    found_files.push(path);
+                if self.is_valid_audio_file(&path)?
+                    && !self.hand_over(path, &found, &mut summary).await
+                {
+                    break;
                 }
                 continue;
             }
 
+            // Unreadable roots usually mean missing storage permission
+            if let Err(e) = std::fs::read_dir(&path) {
+                if e.kind() == std::io::ErrorKind::PermissionDenied {
+                    return Err(LibraryError::PermissionDenied(path));
+                }
+            }
+
             // It's a directory - walk it
-            let files = self.scan_directory(&path).await?;
-            found_files.extend(files);
+            self.scan_directory(&path, &found, &mut summary).await;
         }
 
-        info!("Scan completed: found {} audio files", found_files.len());
-
-        // Send completion event if we have a channel
-        if let Some(tx) = &self.event_tx {
-            let _ = tx.send(ScanEvent::ScanCompleted(found_files.len())).await;
+        if self.cancel.is_cancelled() {
+            return Err(LibraryError::Cancelled);
         }
-
-        Ok(found_files)
+        Ok(summary)
     }
 
-    /// Scan a single directory recursively
-    async fn scan_directory(&self, path: &Path) -> Result<Vec<PathBuf>> {
-        let mut files = Vec::new();
+    /// Sends `path` to `found` unless an incremental scan can pass over it,
+    /// counting it in `summary`
+    ///
+    /// Returns false once nobody is listening any more.
+    async fn hand_over(
+        &self,
+        path: PathBuf,
+        found: &mpsc::Sender<PathBuf>,
+        summary: &mut ScanSummary,
+    ) -> bool {
+        let known = if self.known.is_empty() {
+            None
+        } else {
+            let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+            self.known.get(&canonical)
+        };
+        match known {
+            Some(known)
+                if self.config.mode == ScanMode::Incremental && known.is_unchanged(&path) =>
+            {
+                summary.skipped += 1;
+                return true;
+            }
+            Some(_) => summary.changed += 1,
+            None => summary.new += 1,
+        }
+        found.send(path).await.is_ok()
+    }
 
+    /// Scan a single directory recursively, counting the files in `summary`
+    async fn scan_directory(
+        &self,
+        path: &Path,
+        found: &mpsc::Sender<PathBuf>,
+        summary: &mut ScanSummary,
+    ) {
+        // Excluded folders are pruned, so nothing inside them is read
         let walker = WalkDir::new(path)
             .follow_links(self.config.follow_symlinks)
-            .max_depth(self.config.max_depth.unwrap_or(usize::MAX));
+            .max_depth(self.config.max_depth.unwrap_or(usize::MAX))
+            .into_iter()
+            .filter_entry(|entry| entry.depth() == 0 || !self.config.is_excluded(entry.path()));
 
         for entry in walker {
+            if self.cancel.is_cancelled() {
+                break;
+            }
+
             // Check if we should stop (scanner was stopped)
             if !self.running.load(Ordering::Relaxed) && self.is_running().await {
                 // If we're in the middle of a watch operation that got stopped
@@ -200,7 +561,15 @@ impl LibraryScanner {
 
             // Check if valid audio file
             match self.is_valid_audio_file(entry_path) {
-                Ok(true) => files.push(entry_path.to_path_buf()),
+                Ok(true) => {
+                    // Nobody is listening any more
+                    if !self
+                        .hand_over(entry_path.to_path_buf(), found, summary)
+                        .await
+                    {
+                        break;
+                    }
+                }
                 Ok(false) => {}
                 Err(e) => {
                     debug!("Error checking file {}: {}", entry_path.display(), e);
@@ -208,12 +577,10 @@ impl LibraryScanner {
             }
 
             // Yield to allow other tasks to run periodically
-            if files.len() % 100 == 0 {
+            if (summary.sent() + summary.skipped).is_multiple_of(100) {
                 tokio::task::yield_now().await;
             }
         }
-
-        Ok(files)
     }
 
     /// Check if a file is a valid audio file based on extension and size
@@ -336,10 +703,12 @@ impl LibraryScanner {
 
 /// Handle a file system event and send appropriate scan events
 fn handle_fs_event(
-    event: Event,
+    mut event: Event,
     tx: &mpsc::Sender<ScanEvent>,
     config: &ScannerConfig,
 ) -> Result<()> {
+    event.paths.retain(|path| !config.is_excluded(path));
+
     match event.kind {
         EventKind::Create(_) => {
             for path in event.paths {
@@ -486,6 +855,74 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_incremental_scan_skips_unchanged_files() -> Result<()> {
+        let temp_dir = TempDir::new().map_err(LibraryError::Io)?;
+        let root = temp_dir.path().canonicalize()?;
+        let unchanged = create_test_audio_file(&root, "unchanged.mp3");
+        let resized = create_test_audio_file(&root, "resized.mp3");
+        let unrecorded = create_test_audio_file(&root, "unrecorded.mp3");
+        let added = create_test_audio_file(&root, "added.mp3");
+        let known = |path: &Path, size| KnownFile {
+            size,
+            modified_ms: modified_millis(path),
+        };
+        let library = HashMap::from([
+            (unchanged.clone(), known(&unchanged, 2048)),
+            (resized.clone(), known(&resized, 1024)),
+            (
+                unrecorded.clone(),
+                KnownFile {
+                    size: 2048,
+                    modified_ms: None,
+                },
+            ),
+        ]);
+        let watch = vec![root.display().to_string()];
+
+        let scanner = LibraryScanner::new(watch.clone()).with_known_files(library.clone());
+        let (mut files, summary) = scanner.scan_with_summary().await?;
+        files.sort();
+        assert_eq!(files, vec![added.clone(), resized, unrecorded]);
+        assert_eq!(
+            summary,
+            ScanSummary {
+                new: 1,
+                changed: 2,
+                skipped: 1,
+            }
+        );
+
+        let config = ScannerConfig::new(watch).with_mode(ScanMode::Full);
+        let scanner = LibraryScanner::with_config(config).with_known_files(library);
+        let (files, summary) = scanner.scan_with_summary().await?;
+        assert_eq!(files.len(), 4);
+        assert_eq!(summary.skipped, 0);
+        assert_eq!(summary.sent(), 4);
+        assert!(files.contains(&added));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_scan() -> Result<()> {
+        let temp_dir = TempDir::new().map_err(LibraryError::Io)?;
+        create_test_directory_structure(temp_dir.path());
+
+        let cancel = ScanCancel::new();
+        let scanner = LibraryScanner::new(vec![temp_dir.path().display().to_string()])
+            .with_cancel(cancel.clone());
+        cancel.cancel();
+
+        let result = scanner.scan().await;
+        assert!(matches!(result, Err(LibraryError::Cancelled)));
+        assert!(scanner.cancel_handle().is_cancelled());
+
+        let app_error: storystream_core::AppError = LibraryError::Cancelled.into();
+        assert_eq!(app_error.code(), "CANCELLED");
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_scan_max_depth() -> Result<()> {
         let temp_dir = TempDir::new().map_err(LibraryError::Io)?;
@@ -548,6 +985,50 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_scan_prunes_excluded_folders() -> Result<()> {
+        let temp_dir = TempDir::new().map_err(LibraryError::Io)?;
+        for folder in ["Book/@eaDir", ".stfolder", "Book/.hidden"] {
+            fs::create_dir_all(temp_dir.path().join(folder)).unwrap();
+        }
+        create_test_audio_file(temp_dir.path(), "Book/01.mp3");
+        create_test_audio_file(temp_dir.path(), "Book/sample.mp3");
+        create_test_audio_file(temp_dir.path(), "Book/@eaDir/01.mp3");
+        create_test_audio_file(temp_dir.path(), ".stfolder/synced.mp3");
+        create_test_audio_file(temp_dir.path(), "Book/.hidden/02.mp3");
+        create_test_audio_file(temp_dir.path(), "Book/.partial.mp3");
+
+        let path = temp_dir.path().display().to_string();
+        let config = ScannerConfig::new(vec![path.clone()])
+            .with_exclude_patterns(vec!["**/@eaDir".to_string(), "**/*sample*".to_string()]);
+        let files = LibraryScanner::with_config(config).scan().await?;
+        assert_eq!(files, vec![temp_dir.path().join("Book/01.mp3")]);
+
+        let config = ScannerConfig::new(vec![path]).with_ignore_hidden(false);
+        let files = LibraryScanner::with_config(config).scan().await?;
+        assert_eq!(files.len(), 6);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_exclude_patterns() {
+        assert!(glob_match("**/@eaDir", "/nas/books/@eaDir"));
+        assert!(glob_match("**/@eaDir/**", "/nas/books/@eaDir/thumb.jpg"));
+        assert!(glob_match("/nas/**/sample.mp3", "/nas/sample.mp3"));
+        assert!(glob_match("/nas/*/book?.mp3", "/nas/a/book1.mp3"));
+        assert!(!glob_match("/nas/*/book?.mp3", "/nas/a/b/book1.mp3"));
+        assert!(!glob_match("**/@eaDir", "/nas/books/@eaDir2"));
+
+        let config = ScannerConfig::new(vec!["/nas/.books".to_string()])
+            .with_exclude_patterns(vec!["**/@eaDir".to_string()]);
+        assert!(!config.is_excluded(Path::new("/nas/.books/Book/01.mp3")));
+        assert!(config.is_excluded(Path::new("/nas/.books/Book/.01.mp3")));
+        assert!(config.is_excluded(Path::new("/nas/.books/@eaDir/Book/01.mp3")));
+        // Outside the watch paths only the name counts
+        assert!(!config.is_excluded(Path::new("/other/.cache/01.mp3")));
+    }
+
     #[tokio::test]
     async fn test_scan_nonexistent_directory() -> Result<()> {
         let scanner = LibraryScanner::new(vec!["/nonexistent/path".to_string()]);
@@ -0,0 +1,141 @@
+// FILE: crates/library/src/share.rs
+//! Finding the book a shared bookmark points at
+//!
+//! The sender's copy of the file is found by its hash. Other copies are
+//! matched by title, and by author when both sides know it. A book that is
+//! not in the library may still be fetched from the catalogue the link
+//! names.
+
+use crate::error::Result;
+use std::collections::HashMap;
+use storystream_core::{Book, BookId, SharedBookmark, SourceRef};
+use storystream_database::{queries::books, DbPool};
+
+/// Where a shared bookmark can be listened to
+#[derive(Debug, Clone)]
+pub enum SharedTarget {
+    /// The library has the book
+    Local(Box<Book>),
+    /// The book is not in the library, but can be fetched from this catalogue
+    Fetch(SourceRef),
+    /// The book is not in the library and the link names no catalogue
+    Missing,
+}
+
+/// Looks for the book a shared bookmark points at in the library
+pub async fn resolve_shared(pool: &DbPool, shared: &SharedBookmark) -> Result<SharedTarget> {
+    let all_books = books::list_books(pool).await?;
+    let hashes = books::get_file_hashes(pool).await?;
+    Ok(match match_shared(shared, &all_books, &hashes) {
+        Some(book) => SharedTarget::Local(Box::new(book.clone())),
+        None => match &shared.source {
+            Some(source) => SharedTarget::Fetch(source.clone()),
+            None => SharedTarget::Missing,
+        },
+    })
+}
+
+/// The book among `books` that a shared bookmark points at
+fn match_shared<'a>(
+    shared: &SharedBookmark,
+    books: &'a [Book],
+    hashes: &HashMap<BookId, String>,
+) -> Option<&'a Book> {
+    if let Some(hash) = &shared.file_hash {
+        let same_file = books.iter().find(|book| {
+            hashes
+                .get(&book.id)
+                .is_some_and(|h| h.eq_ignore_ascii_case(hash))
+        });
+        if same_file.is_some() {
+            return same_file;
+        }
+    }
+
+    if let Some(source) = &shared.source {
+        let tag = source.tag();
+        if let Some(book) = books.iter().find(|book| book.tags.contains(&tag)) {
+            return Some(book);
+        }
+    }
+
+    let title = shared.title.to_lowercase();
+    books.iter().find(|book| {
+        let author_matches = match (&shared.author, &book.author) {
+            (Some(shared), Some(author)) => shared.eq_ignore_ascii_case(author),
+            _ => true,
+        };
+        book.title.to_lowercase() == title && author_matches
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use storystream_core::Duration;
+
+    fn book(title: &str, author: &str) -> Book {
+        let mut book = Book::new(
+            title.to_string(),
+            PathBuf::from(format!("/books/{}.mp3", title)),
+            1024,
+            Duration::from_seconds(3_600),
+        );
+        book.author = Some(author.to_string());
+        book
+    }
+
+    fn shared(title: &str, author: Option<&str>) -> SharedBookmark {
+        SharedBookmark {
+            title: title.to_string(),
+            author: author.map(str::to_string),
+            source: None,
+            file_hash: None,
+            position: Duration::from_seconds(60),
+        }
+    }
+
+    #[test]
+    fn test_hash_wins_over_title() {
+        let renamed = book("Time Machine, The", "Wells");
+        let same_title = book("The Time Machine", "H. G. Wells");
+        let hashes = HashMap::from([(renamed.id, "ABC".to_string())]);
+        let books = [same_title, renamed.clone()];
+
+        let mut link = shared("The Time Machine", Some("H. G. Wells"));
+        link.file_hash = Some("abc".to_string());
+        assert_eq!(
+            match_shared(&link, &books, &hashes).map(|b| b.id),
+            Some(renamed.id)
+        );
+    }
+
+    #[test]
+    fn test_title_and_author_match() {
+        let emma = book("Emma", "Jane Austen");
+        let books = [book("Emma", "Someone Else"), emma.clone()];
+        let hashes = HashMap::new();
+
+        let link = shared("EMMA", Some("jane austen"));
+        assert_eq!(
+            match_shared(&link, &books, &hashes).map(|b| b.id),
+            Some(emma.id)
+        );
+        assert!(match_shared(&shared("Dune", None), &books, &hashes).is_none());
+    }
+
+    #[test]
+    fn test_source_tag_match() {
+        let mut fetched = book("La Machine à explorer le temps", "Wells");
+        fetched.tags.push("librivox:59".to_string());
+        let books = [fetched.clone()];
+
+        let mut link = shared("The Time Machine", None);
+        link.source = Some(SourceRef::new("librivox", "59"));
+        assert_eq!(
+            match_shared(&link, &books, &HashMap::new()).map(|b| b.id),
+            Some(fetched.id)
+        );
+    }
+}
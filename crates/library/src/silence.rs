@@ -0,0 +1,354 @@
+//! Chapter suggestions from long silences
+//!
+//! Narrators usually leave a few seconds of quiet between chapters. For a
+//! book without chapter marks, [`ChapterSuggester`] decodes the file into a
+//! [`Waveform`], finds the stretches that stay quiet for long enough and
+//! proposes a chapter start in the middle of each. Decoding takes as long as
+//! a full verification, so waveforms are cached under the file's hash and a
+//! second run only rereads the file to hash it.
+
+use crate::error::{LibraryError, Result};
+use crate::scanner::ScanCancel;
+use crate::verify::hash_file_cancellable;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use storystream_core::{BookId, CacheManager};
+use storystream_database::{queries::books, DbPool};
+use storystream_media_formats::{AudioAnalyzer, Waveform};
+use tracing::{info, instrument, warn};
+
+/// Cache namespace of decoded waveforms, keyed by file hash
+pub const WAVEFORM_NAMESPACE: &str = "waveforms";
+
+/// Length of audio each waveform peak covers
+const WINDOW: Duration = Duration::from_millis(100);
+
+/// A proposed chapter start
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SuggestedChapter {
+    /// Where the chapter would start, in the middle of the silence
+    pub start: Duration,
+    /// Length of the silence the start was found in
+    pub silence: Duration,
+    /// How likely this is a real chapter break, from 0.0 to 1.0
+    ///
+    /// Mostly the length of the silence, and partly how quiet it is.
+    pub confidence: f32,
+}
+
+/// What counts as a chapter break
+#[derive(Debug, Clone, PartialEq)]
+pub struct SilenceOptions {
+    /// Shortest silence that may separate chapters
+    pub min_silence: Duration,
+    /// Peak level below which audio counts as silent, from 0.0 to 1.0
+    pub level: f32,
+    /// Shortest chapter to suggest; weaker breaks closer than this to a
+    /// stronger one are dropped
+    pub min_chapter: Duration,
+}
+
+impl Default for SilenceOptions {
+    fn default() -> Self {
+        Self {
+            min_silence: Duration::from_secs(2),
+            level: 0.02,
+            min_chapter: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Finds chapter breaks in a book's waveform, in playback order
+///
+/// Silences at the very start or end of the book are lead-in and lead-out,
+/// not breaks.
+pub fn find_chapter_breaks(waveform: &Waveform, options: &SilenceOptions) -> Vec<SuggestedChapter> {
+    let window = waveform.window;
+    if window.is_zero() {
+        return Vec::new();
+    }
+    let min_windows = options
+        .min_silence
+        .as_millis()
+        .div_ceil(window.as_millis())
+        .max(1) as usize;
+    let peaks = &waveform.peaks;
+
+    let mut candidates = Vec::new();
+    let mut run_start = None;
+    for (index, &peak) in peaks.iter().enumerate() {
+        match (peak < options.level, run_start) {
+            (true, None) => run_start = Some(index),
+            (false, Some(start)) => {
+                run_start = None;
+                if start == 0 || index - start < min_windows {
+                    continue;
+                }
+                let run = &peaks[start..index];
+                let silence = window * run.len() as u32;
+                let mean = run.iter().sum::<f32>() / run.len() as f32;
+                let length_score =
+                    (silence.as_secs_f32() / (options.min_silence.as_secs_f32() * 3.0)).min(1.0);
+                let depth_score = (1.0 - mean / options.level).clamp(0.0, 1.0);
+                candidates.push(SuggestedChapter {
+                    start: waveform.time_of(start) + silence / 2,
+                    silence,
+                    confidence: 0.75 * length_score + 0.25 * depth_score,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    // The strongest breaks win when they crowd each other
+    candidates.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    let end = waveform.duration();
+    let mut kept: Vec<SuggestedChapter> = Vec::new();
+    for candidate in candidates {
+        let far_from_ends = candidate.start >= options.min_chapter
+            && end.saturating_sub(candidate.start) >= options.min_chapter;
+        let far_from_kept = kept
+            .iter()
+            .all(|k| k.start.abs_diff(candidate.start) >= options.min_chapter);
+        if far_from_ends && far_from_kept {
+            kept.push(candidate);
+        }
+    }
+    kept.sort_by_key(|suggestion| suggestion.start);
+    kept
+}
+
+/// Suggests chapters for a library's books from their audio
+pub struct ChapterSuggester {
+    pool: DbPool,
+    cache: Option<Arc<CacheManager>>,
+    cancel: ScanCancel,
+    options: SilenceOptions,
+}
+
+impl ChapterSuggester {
+    pub fn new(pool: DbPool) -> Self {
+        Self {
+            pool,
+            cache: None,
+            cancel: ScanCancel::new(),
+            options: SilenceOptions::default(),
+        }
+    }
+
+    /// Keeps decoded waveforms in `cache`, so later runs skip decoding
+    pub fn with_cache(mut self, cache: Arc<CacheManager>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Uses `cancel` to stop suggestions in progress
+    pub fn with_cancel(mut self, cancel: ScanCancel) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Changes what counts as a chapter break
+    pub fn with_options(mut self, options: SilenceOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Handle that cancels the suggestion in progress
+    pub fn cancel_handle(&self) -> ScanCancel {
+        self.cancel.clone()
+    }
+
+    /// Suggests chapter starts for a book, in playback order
+    ///
+    /// Fails with [`LibraryError::Cancelled`] when cancelled.
+    #[instrument(skip(self))]
+    pub async fn suggest(&self, book_id: BookId) -> Result<Vec<SuggestedChapter>> {
+        let book = books::get_book(&self.pool, book_id)
+            .await
+            .map_err(|_| LibraryError::BookNotFound(book_id.to_string()))?;
+
+        let path = book.file_path.clone();
+        let cache = self.cache.clone();
+        let cancel = self.cancel.clone();
+        let waveform =
+            tokio::task::spawn_blocking(move || load_waveform(&path, cache.as_deref(), &cancel))
+                .await
+                .map_err(|e| LibraryError::Other(e.to_string()))??;
+
+        let suggestions = find_chapter_breaks(&waveform, &self.options);
+        info!(
+            "Found {} possible chapter break(s) in '{}'",
+            suggestions.len(),
+            book.title
+        );
+        Ok(suggestions)
+    }
+}
+
+/// Reads a file's waveform from the cache, or decodes and caches it
+fn load_waveform(
+    path: &Path,
+    cache: Option<&CacheManager>,
+    cancel: &ScanCancel,
+) -> Result<Waveform> {
+    let key = match cache {
+        Some(_) => Some(hash_file_cancellable(path, cancel)?.ok_or(LibraryError::Cancelled)?),
+        None => None,
+    };
+    if let (Some(cache), Some(key)) = (cache, &key) {
+        if let Some(waveform) = cache.load(WAVEFORM_NAMESPACE, key, Waveform::from_bytes) {
+            return Ok(waveform);
+        }
+    }
+
+    let analyzer = AudioAnalyzer::new().map_err(|e| LibraryError::Other(e.to_string()))?;
+    let waveform = analyzer
+        .waveform(path, WINDOW, || cancel.is_cancelled())
+        .map_err(|e| LibraryError::InvalidFile(format!("{}: {}", path.display(), e)))?
+        .ok_or(LibraryError::Cancelled)?;
+
+    if let (Some(cache), Some(key)) = (cache, &key) {
+        if let Err(e) = cache.put(WAVEFORM_NAMESPACE, key, &waveform.to_bytes()) {
+            warn!("Could not cache the waveform of {}: {}", path.display(), e);
+        }
+    }
+    Ok(waveform)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use storystream_core::Book;
+    use storystream_database::connection::{connect, DatabaseConfig};
+    use storystream_database::migrations::run_migrations;
+    use storystream_media_formats::wav::wav_from_samples;
+    use tempfile::TempDir;
+
+    /// A waveform of `loud` and `quiet` windows, one second each
+    fn waveform(sections: &[(bool, usize)]) -> Waveform {
+        Waveform {
+            window: Duration::from_secs(1),
+            peaks: sections
+                .iter()
+                .flat_map(|&(loud, seconds)| {
+                    std::iter::repeat_n(if loud { 0.5 } else { 0.0 }, seconds)
+                })
+                .collect(),
+        }
+    }
+
+    fn options(min_chapter: u64) -> SilenceOptions {
+        SilenceOptions {
+            min_chapter: Duration::from_secs(min_chapter),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_breaks_at_long_silences() {
+        let waveform = waveform(&[
+            (false, 5),
+            (true, 100),
+            (false, 6),
+            (true, 100),
+            (false, 1),
+            (true, 100),
+            (false, 2),
+            (true, 100),
+            (false, 8),
+        ]);
+        let breaks = find_chapter_breaks(&waveform, &options(60));
+
+        // The one-second pause is too short, lead-in and lead-out don't count
+        let starts: Vec<_> = breaks.iter().map(|b| b.start.as_secs()).collect();
+        assert_eq!(starts, vec![108, 313]);
+        assert_eq!(breaks[0].silence, Duration::from_secs(6));
+        assert!((breaks[0].confidence - 1.0).abs() < 1e-6);
+        assert!(breaks[1].confidence < breaks[0].confidence);
+    }
+
+    #[test]
+    fn test_stronger_break_wins_when_crowded() {
+        let waveform = waveform(&[(true, 100), (false, 2), (true, 30), (false, 6), (true, 100)]);
+        let breaks = find_chapter_breaks(&waveform, &options(60));
+        assert_eq!(breaks.len(), 1);
+        assert_eq!(breaks[0].start, Duration::from_secs(135));
+
+        // Both stand once chapters may be shorter than the gap between them
+        assert_eq!(find_chapter_breaks(&waveform, &options(20)).len(), 2);
+    }
+
+    /// An 8 kHz WAV file of `sections`, loud or silent, one second each
+    fn wav_bytes(sections: &[(bool, usize)]) -> Vec<u8> {
+        let samples = sections.iter().flat_map(|&(loud, seconds)| {
+            (0..seconds * 8000).map(move |i| if loud && i % 20 < 10 { 16000 } else { 0 })
+        });
+        wav_from_samples(samples, 8000)
+    }
+
+    async fn add_book(dir: &TempDir, content: &[u8]) -> (DbPool, Book) {
+        let db_path = dir.path().join("library.db");
+        let pool = connect(DatabaseConfig::new(db_path.to_str().unwrap()))
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        let path = dir.path().join("book.wav");
+        std::fs::write(&path, content).unwrap();
+        let book = Book::new(
+            "Book".to_string(),
+            path,
+            content.len() as u64,
+            storystream_core::Duration::from_seconds(9),
+        );
+        books::create_book(&pool, &book).await.unwrap();
+        (pool, book)
+    }
+
+    #[tokio::test]
+    async fn test_suggest_caches_the_waveform() {
+        let dir = TempDir::new().unwrap();
+        let (pool, book) = add_book(&dir, &wav_bytes(&[(true, 3), (false, 3), (true, 3)])).await;
+        let cache = Arc::new(CacheManager::open(dir.path().join("cache"), 1 << 20).unwrap());
+        let suggester = ChapterSuggester::new(pool)
+            .with_cache(Arc::clone(&cache))
+            .with_options(SilenceOptions {
+                min_silence: Duration::from_secs(2),
+                level: 0.02,
+                min_chapter: Duration::from_secs(2),
+            });
+
+        let breaks = suggester.suggest(book.id).await.unwrap();
+        assert_eq!(breaks.len(), 1);
+        assert_eq!(breaks[0].start, Duration::from_millis(4500));
+
+        // A second run reads the cached waveform instead of decoding
+        let hash = crate::verify::hash_file(&book.file_path).unwrap();
+        let flat = Waveform {
+            window: WINDOW,
+            peaks: vec![0.5; 90],
+        };
+        cache
+            .put(WAVEFORM_NAMESPACE, &hash, &flat.to_bytes())
+            .unwrap();
+        assert!(suggester.suggest(book.id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_suggest_can_be_cancelled() {
+        let dir = TempDir::new().unwrap();
+        let (pool, book) = add_book(&dir, &wav_bytes(&[(true, 3)])).await;
+        let suggester = ChapterSuggester::new(pool);
+        suggester.cancel_handle().cancel();
+        assert!(matches!(
+            suggester.suggest(book.id).await,
+            Err(LibraryError::Cancelled)
+        ));
+        assert!(matches!(
+            suggester.suggest(BookId::new()).await,
+            Err(LibraryError::BookNotFound(_))
+        ));
+    }
+}
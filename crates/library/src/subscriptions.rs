@@ -0,0 +1,927 @@
+// FILE: crates/library/src/subscriptions.rs
+
+//! Refreshes feed subscriptions and applies their download policies
+
+use crate::error::{LibraryError, Result};
+use crate::import::{BookImporter, ImportOptions};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use storystream_core::{BookId, EpisodeId, Podcast, PodcastEpisode, PodcastId, Timestamp};
+use storystream_database::{queries::podcasts, DbPool};
+use storystream_feed_parser::{Feed, FeedParser};
+use storystream_network::{
+    AdvancedDownloadManager, Client, ConditionalResponse, DownloadStatus, DownloadTask,
+};
+use storystream_resilience::RateLimiter;
+use tracing::{info, warn};
+
+/// How often [`SubscriptionManager::wait_for_downloads`] checks on downloads
+const DOWNLOAD_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Refreshes [`SubscriptionManager::refresh_all`] makes of each feed per
+/// [`DEFAULT_REFRESH_WINDOW`]
+const DEFAULT_REFRESHES_PER_WINDOW: usize = 1;
+
+/// Window of the per-feed refresh limit
+const DEFAULT_REFRESH_WINDOW: Duration = Duration::from_secs(15 * 60);
+
+/// Tag marking books imported from podcast episodes
+pub const PODCAST_TAG: &str = "podcast";
+
+/// Decides where an episode of a subscription is saved
+type EpisodePath = Box<dyn Fn(&Podcast, &PodcastEpisode) -> PathBuf + Send + Sync>;
+
+/// Result of refreshing a single feed
+#[derive(Debug, Clone)]
+pub enum RefreshOutcome {
+    /// The server reported the feed unchanged since the last refresh
+    NotModified,
+    /// The feed was fetched; `new_episodes` were not known before
+    Updated { new_episodes: Vec<PodcastEpisode> },
+}
+
+impl RefreshOutcome {
+    /// Number of episodes the refresh discovered
+    pub fn new_episodes(&self) -> usize {
+        match self {
+            Self::NotModified => 0,
+            Self::Updated { new_episodes } => new_episodes.len(),
+        }
+    }
+}
+
+/// Fetches a feed if it changed since the last refresh and records new episodes
+///
+/// The feed's ETag and Last-Modified are sent along, so an unchanged feed
+/// costs a single 304. `podcast` is updated with the feed's metadata and the
+/// validators for the next refresh, and saved.
+pub async fn refresh_feed(
+    pool: &DbPool,
+    client: &Client,
+    podcast: &mut Podcast,
+) -> Result<RefreshOutcome> {
+    let response = client
+        .get_conditional(
+            &podcast.feed_url,
+            podcast.etag.as_deref(),
+            podcast.last_modified.as_deref(),
+        )
+        .await
+        .map_err(|e| LibraryError::Feed(format!("Failed to fetch {}: {}", podcast.feed_url, e)))?;
+
+    let outcome = match response {
+        ConditionalResponse::NotModified => RefreshOutcome::NotModified,
+        ConditionalResponse::Modified {
+            response,
+            etag,
+            last_modified,
+        } => {
+            let body = response
+                .text()
+                .await
+                .map_err(|e| LibraryError::Feed(format!("Failed to read feed: {}", e)))?;
+            let feed = FeedParser::parse(&body)
+                .map_err(|e| LibraryError::Feed(format!("Failed to parse feed: {}", e)))?;
+
+            apply_feed_metadata(podcast, &feed);
+            podcast.etag = etag;
+            podcast.last_modified = last_modified;
+            podcasts::update_podcast(pool, podcast).await?;
+
+            let new_episodes = store_episodes(pool, podcast, &feed).await?;
+            RefreshOutcome::Updated { new_episodes }
+        }
+    };
+
+    let now = Timestamp::now();
+    podcasts::update_last_checked(
+        pool,
+        podcast.id,
+        now,
+        podcast.etag.as_deref(),
+        podcast.last_modified.as_deref(),
+    )
+    .await?;
+    podcast.last_fetched = Some(now);
+    podcast.updated_at = now;
+
+    Ok(outcome)
+}
+
+/// Copies a fetched feed's title, description, author and artwork onto its
+/// subscription
+///
+/// Fields the feed leaves out keep their previous values.
+pub fn apply_feed_metadata(podcast: &mut Podcast, feed: &Feed) {
+    if !feed.title.trim().is_empty() {
+        podcast.title = feed.title.clone();
+    }
+    podcast.description = feed.description.clone().or(podcast.description.take());
+    podcast.author = feed.author.clone().or(podcast.author.take());
+    podcast.image_url = feed.image_url.clone().or(podcast.image_url.take());
+}
+
+/// Records the feed's audio items, returning the ones not seen before
+///
+/// Items are matched to stored episodes by GUID, or by audio URL for feeds
+/// without GUIDs, so refreshing the same feed twice adds nothing.
+pub async fn store_episodes(
+    pool: &DbPool,
+    podcast: &Podcast,
+    feed: &Feed,
+) -> Result<Vec<PodcastEpisode>> {
+    let mut added = Vec::new();
+    for item in feed.audio_items() {
+        let Some(audio_url) = item.audio_url() else {
+            continue;
+        };
+
+        let mut episode =
+            PodcastEpisode::new(podcast.id, item.title.clone(), audio_url.to_string());
+        episode.guid = item.guid.clone();
+        episode.description = item.description.clone();
+        episode.published = item
+            .published
+            .map(|dt| Timestamp::from_millis(dt.timestamp_millis()));
+        episode.duration = item.duration.map(Into::into);
+
+        if podcasts::add_episode_if_new(pool, &episode).await? {
+            added.push(episode);
+        }
+    }
+    Ok(added)
+}
+
+/// Why an episode's file was deleted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemovalReason {
+    /// The episode was listened to the end
+    Finished,
+    /// Newer episodes fill the subscription's download limit
+    OverLimit,
+}
+
+/// One change made while applying a policy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum PolicyAction {
+    /// Download handed to the download manager
+    Queued {
+        episode_id: EpisodeId,
+        title: String,
+    },
+    /// Waiting for an unmetered connection
+    Held {
+        episode_id: EpisodeId,
+        title: String,
+    },
+    /// Finished download recorded and imported into the library
+    Downloaded {
+        episode_id: EpisodeId,
+        title: String,
+        book_id: Option<BookId>,
+    },
+    /// Listened to the end
+    Played {
+        episode_id: EpisodeId,
+        title: String,
+    },
+    /// Local file deleted; the episode and its history stay in the database
+    Removed {
+        episode_id: EpisodeId,
+        title: String,
+        reason: RemovalReason,
+    },
+    /// Download or cleanup failed
+    Failed {
+        episode_id: EpisodeId,
+        title: String,
+        error: String,
+    },
+}
+
+/// What applying one subscription's policy did
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PolicyReport {
+    pub podcast_id: PodcastId,
+    pub title: String,
+    pub actions: Vec<PolicyAction>,
+}
+
+impl PolicyReport {
+    fn new(podcast: &Podcast) -> Self {
+        Self {
+            podcast_id: podcast.id,
+            title: podcast.title.clone(),
+            actions: Vec::new(),
+        }
+    }
+
+    /// Whether nothing needed doing
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// Number of downloads queued by this run
+    pub fn queued(&self) -> usize {
+        self.actions
+            .iter()
+            .filter(|a| matches!(a, PolicyAction::Queued { .. }))
+            .count()
+    }
+}
+
+/// What [`SubscriptionManager::refresh_all`] did with one subscription
+#[derive(Debug)]
+pub enum FeedRefresh {
+    /// The feed was checked and its policy applied when it changed
+    Refreshed {
+        podcast: Podcast,
+        outcome: RefreshOutcome,
+        report: Option<PolicyReport>,
+    },
+    /// Checked too recently for the feed's rate limit; left alone
+    Limited { podcast: Podcast },
+    /// Fetching or storing the feed failed
+    Failed {
+        podcast: Podcast,
+        error: LibraryError,
+    },
+}
+
+impl FeedRefresh {
+    /// The subscription this result is for
+    pub fn podcast(&self) -> &Podcast {
+        match self {
+            Self::Refreshed { podcast, .. }
+            | Self::Limited { podcast }
+            | Self::Failed { podcast, .. } => podcast,
+        }
+    }
+}
+
+/// Keeps subscriptions' downloads in line with their [`DownloadPolicy`]
+///
+/// Applying a policy is idempotent: a second run only reports what changed
+/// since the first, such as downloads that have finished in between.
+///
+/// [`DownloadPolicy`]: storystream_core::DownloadPolicy
+pub struct SubscriptionManager {
+    pool: DbPool,
+    downloads: Arc<AdvancedDownloadManager>,
+    importer: BookImporter,
+    episode_path: EpisodePath,
+    /// Download task IDs queued by this manager
+    queued: Mutex<HashSet<String>>,
+    /// Refreshes allowed per window, for each feed
+    refresh_limit: (usize, Duration),
+    limiters: Mutex<HashMap<PodcastId, RateLimiter>>,
+}
+
+impl SubscriptionManager {
+    /// Create a manager queuing downloads on `downloads`
+    ///
+    /// `episode_path` decides where each episode is saved; parent
+    /// directories are created as needed.
+    pub fn new(
+        pool: DbPool,
+        downloads: Arc<AdvancedDownloadManager>,
+        episode_path: impl Fn(&Podcast, &PodcastEpisode) -> PathBuf + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            importer: BookImporter::new(pool.clone()),
+            pool,
+            downloads,
+            episode_path: Box::new(episode_path),
+            queued: Mutex::new(HashSet::new()),
+            refresh_limit: (DEFAULT_REFRESHES_PER_WINDOW, DEFAULT_REFRESH_WINDOW),
+            limiters: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Lets [`refresh_all`](Self::refresh_all) check each feed at most
+    /// `refreshes` times per `window`
+    pub fn with_refresh_limit(mut self, refreshes: usize, window: Duration) -> Self {
+        self.refresh_limit = (refreshes, window);
+        self
+    }
+
+    /// Refresh a subscription's feed, then apply its policy to any new episodes
+    ///
+    /// The policy is only applied when the feed changed and the policy is
+    /// active, so refreshing an unchanged feed leaves downloads alone.
+    pub async fn refresh(
+        &self,
+        client: &Client,
+        podcast: &mut Podcast,
+    ) -> Result<(RefreshOutcome, Option<PolicyReport>)> {
+        let outcome = refresh_feed(&self.pool, client, podcast).await?;
+        let report = match outcome {
+            RefreshOutcome::Updated { .. } if podcast.policy.is_active() => {
+                Some(self.apply_policy(podcast).await?)
+            }
+            _ => None,
+        };
+        Ok((outcome, report))
+    }
+
+    /// Refresh every subscription, continuing past feeds that fail
+    ///
+    /// Feeds refreshed through this manager within their rate limit are
+    /// skipped, so calling this on a timer never hammers a server.
+    pub async fn refresh_all(&self, client: &Client) -> Result<Vec<FeedRefresh>> {
+        let mut results = Vec::new();
+        for mut podcast in podcasts::list_podcasts(&self.pool).await? {
+            if !self.may_refresh(podcast.id) {
+                results.push(FeedRefresh::Limited { podcast });
+                continue;
+            }
+            results.push(match self.refresh(client, &mut podcast).await {
+                Ok((outcome, report)) => FeedRefresh::Refreshed {
+                    podcast,
+                    outcome,
+                    report,
+                },
+                Err(error) => {
+                    warn!("Failed to refresh '{}': {}", podcast.title, error);
+                    FeedRefresh::Failed { podcast, error }
+                }
+            });
+        }
+        Ok(results)
+    }
+
+    /// Takes a refresh from the feed's rate limit, if one is left
+    fn may_refresh(&self, id: PodcastId) -> bool {
+        let (refreshes, window) = self.refresh_limit;
+        self.limiters
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(id)
+            .or_insert_with(|| RateLimiter::new(refreshes, window))
+            .try_acquire()
+            .is_ok()
+    }
+
+    /// Apply the policy of every subscription
+    ///
+    /// Subscriptions without an active policy are skipped.
+    pub async fn apply_policies(&self) -> Result<Vec<PolicyReport>> {
+        let mut reports = Vec::new();
+        for podcast in podcasts::list_podcasts(&self.pool).await? {
+            if podcast.policy.is_active() {
+                reports.push(self.apply_policy(&podcast).await?);
+            }
+        }
+        Ok(reports)
+    }
+
+    /// Apply one subscription's policy
+    pub async fn apply_policy(&self, podcast: &Podcast) -> Result<PolicyReport> {
+        let policy = podcast.policy;
+        let mut report = PolicyReport::new(podcast);
+        let mut episodes = podcasts::get_podcast_episodes(&self.pool, podcast.id).await?;
+
+        // Record downloads that finished since the last run
+        for episode in episodes.iter_mut().filter(|e| !e.is_downloaded) {
+            if let Some(DownloadStatus::Completed) =
+                self.downloads.get_status(&task_id(episode)).await
+            {
+                let action = self.record_download(podcast, episode).await?;
+                report.actions.push(action);
+            }
+        }
+
+        let finished = podcasts::find_finished_episodes(&self.pool, podcast.id).await?;
+        for episode in episodes.iter_mut().filter(|e| finished.contains(&e.id)) {
+            podcasts::mark_episode_played(&self.pool, episode.id).await?;
+            episode.is_played = true;
+            report.actions.push(PolicyAction::Played {
+                episode_id: episode.id,
+                title: episode.title.clone(),
+            });
+        }
+
+        if policy.delete_finished {
+            for episode in episodes
+                .iter_mut()
+                .filter(|e| e.is_downloaded && e.is_played)
+            {
+                let action = self.remove(episode, RemovalReason::Finished).await?;
+                report.actions.push(action);
+            }
+        }
+
+        if !policy.auto_download {
+            return Ok(report);
+        }
+
+        // Episodes come newest first; the newest unplayed ones are kept
+        let keep: HashSet<EpisodeId> = episodes
+            .iter()
+            .filter(|e| !e.is_played)
+            .take(policy.max_episodes)
+            .map(|e| e.id)
+            .collect();
+
+        for episode in episodes
+            .iter_mut()
+            .filter(|e| e.is_downloaded && !e.is_played && !keep.contains(&e.id))
+        {
+            let action = self.remove(episode, RemovalReason::OverLimit).await?;
+            report.actions.push(action);
+        }
+
+        for episode in episodes
+            .iter()
+            .filter(|e| keep.contains(&e.id) && !e.is_downloaded)
+        {
+            if let Some(action) = self.queue(podcast, episode).await {
+                report.actions.push(action);
+            }
+        }
+
+        if !report.is_empty() {
+            info!(
+                "Applied download policy of '{}': {} change(s)",
+                podcast.title,
+                report.actions.len()
+            );
+        }
+        Ok(report)
+    }
+
+    /// Wait until every download queued by this manager has stopped
+    ///
+    /// Apply the policies again afterwards to record the finished downloads.
+    pub async fn wait_for_downloads(&self) {
+        loop {
+            let ids: Vec<String> = self
+                .queued
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .iter()
+                .cloned()
+                .collect();
+
+            let mut pending = false;
+            for id in &ids {
+                if matches!(
+                    self.downloads.get_status(id).await,
+                    Some(DownloadStatus::Queued | DownloadStatus::InProgress)
+                ) {
+                    pending = true;
+                    break;
+                }
+            }
+            if !pending {
+                return;
+            }
+            tokio::time::sleep(DOWNLOAD_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Queue an episode unless it is already on its way
+    async fn queue(&self, podcast: &Podcast, episode: &PodcastEpisode) -> Option<PolicyAction> {
+        let id = task_id(episode);
+        match self.downloads.get_status(&id).await {
+            Some(DownloadStatus::Failed(error)) => {
+                return Some(PolicyAction::Failed {
+                    episode_id: episode.id,
+                    title: episode.title.clone(),
+                    error,
+                })
+            }
+            Some(_) => return None,
+            None => {}
+        }
+
+        if podcast.policy.unmetered_only && self.downloads.is_metered() {
+            return Some(PolicyAction::Held {
+                episode_id: episode.id,
+                title: episode.title.clone(),
+            });
+        }
+
+        let destination = (self.episode_path)(podcast, episode);
+        let queued = match create_parent(&destination).await {
+            Ok(()) => {
+                let task = DownloadTask::new(id.clone(), episode.audio_url.clone(), destination);
+                self.downloads
+                    .enqueue(task)
+                    .await
+                    .map_err(|e| e.to_string())
+            }
+            Err(e) => Err(e.to_string()),
+        };
+
+        Some(match queued {
+            Ok(()) => {
+                self.queued
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(id);
+                PolicyAction::Queued {
+                    episode_id: episode.id,
+                    title: episode.title.clone(),
+                }
+            }
+            Err(error) => PolicyAction::Failed {
+                episode_id: episode.id,
+                title: episode.title.clone(),
+                error,
+            },
+        })
+    }
+
+    /// Mark a finished download as downloaded and import it as a book
+    async fn record_download(
+        &self,
+        podcast: &Podcast,
+        episode: &mut PodcastEpisode,
+    ) -> Result<PolicyAction> {
+        let path = (self.episode_path)(podcast, episode);
+        let path_str = path.to_string_lossy().to_string();
+        podcasts::mark_episode_downloaded(&self.pool, episode.id, &path_str).await?;
+        episode.is_downloaded = true;
+        episode.file_path = Some(path_str);
+
+        let author = podcast
+            .author
+            .clone()
+            .unwrap_or_else(|| podcast.title.clone());
+        let options = ImportOptions::new()
+            .with_title(episode.title.clone())
+            .with_author(author)
+            .with_tag(PODCAST_TAG);
+        let book_id = match self.importer.import_file(&path, options).await {
+            Ok(book) => {
+                podcasts::link_episode_book(&self.pool, episode.id, book.id).await?;
+                episode.book_id = Some(book.id);
+                Some(book.id)
+            }
+            Err(e) => {
+                warn!("Downloaded '{}' but import failed: {}", episode.title, e);
+                None
+            }
+        };
+
+        Ok(PolicyAction::Downloaded {
+            episode_id: episode.id,
+            title: episode.title.clone(),
+            book_id,
+        })
+    }
+
+    /// Delete an episode's file, keeping the episode record
+    async fn remove(
+        &self,
+        episode: &mut PodcastEpisode,
+        reason: RemovalReason,
+    ) -> Result<PolicyAction> {
+        if let Some(path) = &episode.file_path {
+            match tokio::fs::remove_file(path).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => {
+                    return Ok(PolicyAction::Failed {
+                        episode_id: episode.id,
+                        title: episode.title.clone(),
+                        error: format!("Failed to delete {}: {}", path, e),
+                    })
+                }
+            }
+        }
+
+        podcasts::clear_episode_download(&self.pool, episode.id).await?;
+        episode.is_downloaded = false;
+        episode.file_path = None;
+
+        Ok(PolicyAction::Removed {
+            episode_id: episode.id,
+            title: episode.title.clone(),
+            reason,
+        })
+    }
+}
+
+fn task_id(episode: &PodcastEpisode) -> String {
+    format!("episode-{}", episode.id)
+}
+
+async fn create_parent(path: &Path) -> std::io::Result<()> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => tokio::fs::create_dir_all(parent).await,
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use storystream_core::{Book, Duration as BookDuration, PlaybackState};
+    use storystream_database::connection::{connect, DatabaseConfig};
+    use storystream_database::migrations::run_migrations;
+    use storystream_database::queries::{books, playback};
+    use storystream_network::{Client, DownloadManagerConfig};
+    use tempfile::TempDir;
+
+    struct Fixture {
+        pool: DbPool,
+        downloads: Arc<AdvancedDownloadManager>,
+        manager: SubscriptionManager,
+        podcast: Podcast,
+        episodes: Vec<PodcastEpisode>,
+        dir: TempDir,
+    }
+
+    /// A subscription with five episodes, newest first
+    async fn fixture() -> Fixture {
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("library.db");
+        let pool = connect(DatabaseConfig::new(db_path.to_str().unwrap()))
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        let mut podcast = Podcast::new("https://example.com/feed.xml".into(), "Show".into());
+        podcast.policy.auto_download = true;
+        podcast.policy.delete_finished = true;
+        podcast.policy.max_episodes = 3;
+        podcasts::create_podcast(&pool, &podcast).await.unwrap();
+
+        let mut episodes = Vec::new();
+        for n in (1..=5).rev() {
+            let mut episode = PodcastEpisode::new(
+                podcast.id,
+                format!("Episode {}", n),
+                format!("http://127.0.0.1:9/{}.mp3", n),
+            );
+            episode.published = Some(storystream_core::Timestamp::from_millis(n * 1000));
+            podcasts::add_episode_if_new(&pool, &episode).await.unwrap();
+            episodes.push(episode);
+        }
+
+        let root = dir.path().to_path_buf();
+        let downloads = Arc::new(AdvancedDownloadManager::new(
+            Client::new().unwrap(),
+            DownloadManagerConfig::default(),
+        ));
+        let manager =
+            SubscriptionManager::new(pool.clone(), Arc::clone(&downloads), move |_, e| {
+                root.join(format!("{}.mp3", e.title))
+            });
+
+        Fixture {
+            pool,
+            downloads,
+            manager,
+            podcast,
+            episodes,
+            dir,
+        }
+    }
+
+    async fn download(f: &Fixture, episode: &PodcastEpisode) -> PathBuf {
+        let path = f.dir.path().join(format!("{}.mp3", episode.title));
+        std::fs::write(&path, b"audio").unwrap();
+        podcasts::mark_episode_downloaded(&f.pool, episode.id, &path.to_string_lossy())
+            .await
+            .unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn test_newest_episodes_are_queued_once() {
+        let f = fixture().await;
+
+        let report = f.manager.apply_policy(&f.podcast).await.unwrap();
+        let queued: Vec<&str> = report
+            .actions
+            .iter()
+            .map(|a| match a {
+                PolicyAction::Queued { title, .. } => title.as_str(),
+                other => panic!("unexpected {:?}", other),
+            })
+            .collect();
+        assert_eq!(queued, vec!["Episode 5", "Episode 4", "Episode 3"]);
+        assert_eq!(f.downloads.queue_length().await, 3);
+
+        // Nothing changed, so nothing is done again
+        let again = f.manager.apply_policy(&f.podcast).await.unwrap();
+        assert!(again.is_empty(), "{:?}", again.actions);
+        assert_eq!(f.downloads.queue_length().await, 3);
+    }
+
+    #[tokio::test]
+    async fn test_metered_connection_holds_downloads() {
+        let f = fixture().await;
+        f.downloads.set_metered(true).await;
+
+        let report = f.manager.apply_policy(&f.podcast).await.unwrap();
+        assert_eq!(report.queued(), 0);
+        assert!(report
+            .actions
+            .iter()
+            .all(|a| matches!(a, PolicyAction::Held { .. })));
+        assert_eq!(f.downloads.queue_length().await, 0);
+
+        let mut podcast = f.podcast.clone();
+        podcast.policy.unmetered_only = false;
+        let report = f.manager.apply_policy(&podcast).await.unwrap();
+        assert_eq!(report.queued(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_finished_and_surplus_episodes_are_removed() {
+        let f = fixture().await;
+
+        // Episode 5 was listened to, episode 1 is older than the limit
+        let finished = download(&f, &f.episodes[0]).await;
+        let surplus = download(&f, &f.episodes[4]).await;
+        let book = Book::new(
+            "Episode 5".into(),
+            finished.clone(),
+            5,
+            BookDuration::from_seconds(60),
+        );
+        books::create_book(&f.pool, &book).await.unwrap();
+        let mut state = PlaybackState::new(book.id);
+        state.position = BookDuration::from_seconds(60);
+        playback::create_playback_state(&f.pool, &state)
+            .await
+            .unwrap();
+
+        let report = f.manager.apply_policy(&f.podcast).await.unwrap();
+        assert!(report.actions.contains(&PolicyAction::Played {
+            episode_id: f.episodes[0].id,
+            title: "Episode 5".into(),
+        }));
+        assert!(report.actions.contains(&PolicyAction::Removed {
+            episode_id: f.episodes[0].id,
+            title: "Episode 5".into(),
+            reason: RemovalReason::Finished,
+        }));
+        assert!(report.actions.contains(&PolicyAction::Removed {
+            episode_id: f.episodes[4].id,
+            title: "Episode 1".into(),
+            reason: RemovalReason::OverLimit,
+        }));
+        assert_eq!(report.queued(), 3);
+        assert!(!finished.exists());
+        assert!(!surplus.exists());
+
+        // The played episode keeps its history and is not fetched again
+        let stored = podcasts::get_podcast_episodes(&f.pool, f.podcast.id)
+            .await
+            .unwrap();
+        assert!(stored[0].is_played && !stored[0].is_downloaded);
+        assert!(books::get_book(&f.pool, book.id).await.is_ok());
+        assert!(f.manager.apply_policy(&f.podcast).await.unwrap().is_empty());
+    }
+
+    const FEED: &str = r#"<?xml version="1.0"?>
+<rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd"><channel>
+  <title>Renamed Show</title>
+  <description>Weekly episodes</description>
+  <itunes:image href="http://127.0.0.1:9/cover.jpg"/>
+  <item>
+    <title>Episode 6</title>
+    <guid>ep-6</guid>
+    <itunes:duration>12:30</itunes:duration>
+    <enclosure url="http://127.0.0.1:9/6.mp3" type="audio/mpeg" length="1"/>
+  </item>
+  <item>
+    <title>Episode 5 (remastered)</title>
+    <guid>ep-5</guid>
+    <enclosure url="http://127.0.0.1:9/5b.mp3" type="audio/mpeg" length="1"/>
+  </item>
+</channel></rss>"#;
+
+    /// Serves [`FEED`] with an ETag, answering `304` once the client has it
+    fn feed_server() -> String {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut request = [0; 2048];
+                let read = stream.read(&mut request).unwrap_or(0);
+                let request = String::from_utf8_lossy(&request[..read]).to_lowercase();
+                let response = if request.contains("if-none-match: \"v1\"") {
+                    "HTTP/1.1 304 Not Modified\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                        .to_string()
+                } else {
+                    format!(
+                        "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        FEED.len(),
+                        FEED
+                    )
+                };
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        format!("http://{}/feed.xml", addr)
+    }
+
+    #[tokio::test]
+    async fn test_stored_episodes_are_deduplicated_by_guid() {
+        let f = fixture().await;
+        let mut feed = FeedParser::parse(FEED).unwrap();
+
+        let new_episodes = store_episodes(&f.pool, &f.podcast, &feed).await.unwrap();
+        let titles: Vec<&str> = new_episodes.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, vec!["Episode 6", "Episode 5 (remastered)"]);
+
+        // The publisher moved the file; the GUID still identifies the episode
+        if let Some(enclosure) = feed.items[0].enclosure.as_mut() {
+            enclosure.url = "http://127.0.0.1:9/6-fixed.mp3".to_string();
+        }
+        assert!(store_episodes(&f.pool, &f.podcast, &feed)
+            .await
+            .unwrap()
+            .is_empty());
+        let stored = podcasts::get_podcast_episodes(&f.pool, f.podcast.id)
+            .await
+            .unwrap();
+        assert_eq!(stored.len(), f.episodes.len() + 2);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_records_new_episodes_then_not_modified() {
+        let f = fixture().await;
+        let mut podcast = f.podcast.clone();
+        podcast.feed_url = feed_server();
+        podcast.policy = Default::default();
+        podcasts::update_podcast(&f.pool, &podcast).await.unwrap();
+        let client = Client::new().unwrap();
+
+        let (outcome, report) = f.manager.refresh(&client, &mut podcast).await.unwrap();
+        assert_eq!(outcome.new_episodes(), 2);
+        assert!(report.is_none());
+        assert_eq!(podcast.title, "Renamed Show");
+        assert_eq!(podcast.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(
+            podcast.image_url.as_deref(),
+            Some("http://127.0.0.1:9/cover.jpg")
+        );
+
+        let stored = podcasts::get_podcast(&f.pool, podcast.id).await.unwrap();
+        assert_eq!(stored.title, "Renamed Show");
+        assert_eq!(stored.etag.as_deref(), Some("\"v1\""));
+        assert!(stored.last_fetched.is_some());
+
+        let (outcome, _) = f.manager.refresh(&client, &mut podcast).await.unwrap();
+        assert!(matches!(outcome, RefreshOutcome::NotModified));
+        let episodes = podcasts::get_podcast_episodes(&f.pool, podcast.id)
+            .await
+            .unwrap();
+        assert_eq!(episodes.len(), 7);
+        let newest = episodes.iter().find(|e| e.title == "Episode 6").unwrap();
+        assert_eq!(newest.duration, Some(BookDuration::from_seconds(750)));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_all_respects_each_feeds_rate_limit() {
+        let f = fixture().await;
+        podcasts::delete_podcast(&f.pool, f.podcast.id)
+            .await
+            .unwrap();
+        let podcast = Podcast::new(feed_server(), "Show".into());
+        let broken = Podcast::new("http://127.0.0.1:9/feed.xml".into(), "Gone".into());
+        podcasts::create_podcast(&f.pool, &podcast).await.unwrap();
+        podcasts::create_podcast(&f.pool, &broken).await.unwrap();
+        let client = Client::new().unwrap();
+
+        let results = f.manager.refresh_all(&client).await.unwrap();
+        assert_eq!(results.len(), 2);
+        for result in &results {
+            match result {
+                FeedRefresh::Refreshed { outcome, .. } => assert_eq!(outcome.new_episodes(), 2),
+                FeedRefresh::Failed { podcast, .. } => assert_eq!(podcast.id, broken.id),
+                FeedRefresh::Limited { .. } => panic!("first refresh was limited"),
+            }
+        }
+
+        let results = f.manager.refresh_all(&client).await.unwrap();
+        assert!(results
+            .iter()
+            .all(|r| matches!(r, FeedRefresh::Limited { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_inactive_policies_are_skipped() {
+        let f = fixture().await;
+        let mut podcast = f.podcast.clone();
+        podcast.policy = Default::default();
+        podcasts::update_podcast(&f.pool, &podcast).await.unwrap();
+
+        assert!(f.manager.apply_policies().await.unwrap().is_empty());
+        assert_eq!(f.downloads.queue_length().await, 0);
+    }
+}
@@ -0,0 +1,576 @@
+//! File integrity verification
+//!
+//! Re-hashes book files and compares them with the SHA-256 stored at import,
+//! optionally decoding every file in full. Books imported before hashes were
+//! stored get theirs recorded on their first verification.
+
+use crate::error::{LibraryError, Result};
+use crate::import::{BookImporter, ImportOptions};
+use crate::scanner::ScanCancel;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use storystream_core::{Book, BookId};
+use storystream_database::{queries::books, DbPool};
+use storystream_media_formats::AudioAnalyzer;
+use storystream_resilience::Bulkhead;
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+use tracing::{info, instrument, warn};
+
+/// Files verified at once; more mostly thrashes the disk
+const DEFAULT_CONCURRENCY: usize = 2;
+
+/// Tag added to books marked as corrupt
+pub const CORRUPT_TAG: &str = "corrupt";
+
+/// Which books to verify
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum VerifyScope {
+    /// Every book in the library not already marked as corrupt
+    #[default]
+    All,
+    /// Only these books
+    Books(Vec<BookId>),
+}
+
+/// How thoroughly to check each file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VerifyDepth {
+    /// Compare the file's hash with the stored one
+    #[default]
+    Hash,
+    /// Also decode the whole file
+    Full,
+}
+
+/// What is wrong with a book's file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileProblem {
+    /// The file is gone
+    Missing,
+    /// The file differs from when it was hashed but still decodes
+    Changed,
+    /// The file cannot be read or decoded
+    Corrupt(String),
+}
+
+/// A way to resolve a [`FileIssue`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuggestedAction {
+    /// Accept the file as it is now and store its new hash
+    UpdateHash,
+    /// Re-import the file's metadata, which also stores its new hash
+    RefreshMetadata,
+    /// Tag the book as corrupt
+    MarkCorrupt,
+    /// Restore the file from a backup or re-import it from where it moved
+    Relocate,
+}
+
+impl SuggestedAction {
+    /// Short description for reports
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Self::UpdateHash => "update the stored hash",
+            Self::RefreshMetadata => "refresh metadata",
+            Self::MarkCorrupt => "mark as corrupt",
+            Self::Relocate => "restore or relocate the file",
+        }
+    }
+
+    /// Whether [`FileVerifier::apply`] can carry the action out
+    pub fn is_automatic(&self) -> bool {
+        !matches!(self, Self::Relocate)
+    }
+}
+
+/// A book whose file failed verification
+#[derive(Debug, Clone)]
+pub struct FileIssue {
+    pub book_id: BookId,
+    pub title: String,
+    pub path: PathBuf,
+    pub problem: FileProblem,
+    /// Hash of the file as it is now, when it could be read
+    pub current_hash: Option<String>,
+    /// Ways to resolve the problem, most likely first
+    pub actions: Vec<SuggestedAction>,
+}
+
+/// Progress of a verification run
+#[derive(Debug, Clone)]
+pub enum VerifyEvent {
+    /// Verification of `total` books began
+    Started { total: usize },
+    /// A book's file has been checked
+    Checked {
+        done: usize,
+        total: usize,
+        title: String,
+    },
+    /// The run ended, after `checked` books
+    Finished { checked: usize, cancelled: bool },
+}
+
+/// Outcome of a verification run
+#[derive(Debug, Clone, Default)]
+pub struct VerifyReport {
+    pub depth: VerifyDepth,
+    /// Books whose files were checked
+    pub checked: usize,
+    /// Files that matched their stored hash (and decoded, for a full run)
+    pub ok: usize,
+    /// Files hashed for the first time, whose hashes were stored
+    pub baselined: usize,
+    /// Books left out because they are already marked as corrupt
+    pub skipped: usize,
+    pub issues: Vec<FileIssue>,
+    /// Whether the run was cancelled before checking every book
+    pub cancelled: bool,
+}
+
+impl VerifyReport {
+    pub fn count(&self, matches: impl Fn(&FileProblem) -> bool) -> usize {
+        self.issues.iter().filter(|i| matches(&i.problem)).count()
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Result of checking a single file
+enum Outcome {
+    Ok,
+    Baselined(String),
+    Problem(FileProblem, Option<String>),
+}
+
+/// Verifies book files against their stored hashes
+pub struct FileVerifier {
+    pool: DbPool,
+    concurrency: usize,
+    cancel: ScanCancel,
+    events: Option<mpsc::Sender<VerifyEvent>>,
+}
+
+impl FileVerifier {
+    pub fn new(pool: DbPool) -> Self {
+        Self {
+            pool,
+            concurrency: DEFAULT_CONCURRENCY,
+            cancel: ScanCancel::new(),
+            events: None,
+        }
+    }
+
+    /// Checks up to `files` files at once
+    pub fn with_concurrency(mut self, files: usize) -> Self {
+        self.concurrency = files;
+        self
+    }
+
+    /// Uses `cancel` to stop verification instead of the verifier's own token
+    pub fn with_cancel(mut self, cancel: ScanCancel) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Sends progress to `events` as files are checked
+    pub fn with_events(mut self, events: mpsc::Sender<VerifyEvent>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Returns a token that cancels this verifier's runs
+    pub fn cancel_handle(&self) -> ScanCancel {
+        self.cancel.clone()
+    }
+
+    /// Checks the files of the books in `scope`
+    ///
+    /// Hashes of files checked for the first time are stored. Cancelling
+    /// stops the run after the files being checked and returns what was found
+    /// so far.
+    #[instrument(name = "verify_files", skip(self, scope))]
+    pub async fn verify(&self, scope: VerifyScope, depth: VerifyDepth) -> Result<VerifyReport> {
+        let (library, skipped) = self.books_in(scope).await?;
+        let mut stored = books::get_file_hashes(&self.pool).await?;
+        let total = library.len();
+        info!("Verifying {} book file(s) ({:?})", total, depth);
+        self.send(VerifyEvent::Started { total }).await;
+
+        let analyzer =
+            Arc::new(AudioAnalyzer::new().map_err(|e| LibraryError::Other(e.to_string()))?);
+        let titles: HashMap<BookId, (String, PathBuf)> = library
+            .iter()
+            .map(|b| (b.id, (b.title.clone(), b.file_path.clone())))
+            .collect();
+
+        // Closing it on cancel is final, so each run gets its own
+        let bulkhead = Bulkhead::new(self.concurrency);
+        let mut tasks = JoinSet::new();
+        for book in library {
+            let stored_hash = stored.remove(&book.id);
+            let bulkhead = bulkhead.clone();
+            let cancel = self.cancel.clone();
+            let analyzer = Arc::clone(&analyzer);
+            tasks.spawn(async move {
+                let _permit = bulkhead.acquire().await.ok()?;
+                if cancel.is_cancelled() {
+                    return None;
+                }
+                let path = book.file_path;
+                let outcome = tokio::task::spawn_blocking(move || {
+                    check_file(&path, stored_hash.as_deref(), depth, &analyzer, &cancel)
+                })
+                .await
+                .ok()??;
+                Some((book.id, outcome))
+            });
+        }
+
+        let mut report = VerifyReport {
+            depth,
+            skipped,
+            ..Default::default()
+        };
+        while let Some(joined) = tasks.join_next().await {
+            if self.cancel.is_cancelled() && !bulkhead.is_closed() {
+                // Wakes the tasks still waiting for a slot so they give up
+                bulkhead.close();
+            }
+            let Some((book_id, outcome)) = joined.ok().flatten() else {
+                continue;
+            };
+            let (title, path) = titles[&book_id].clone();
+
+            match outcome {
+                Outcome::Ok => report.ok += 1,
+                Outcome::Baselined(hash) => {
+                    books::set_file_hash(&self.pool, book_id, Some(&hash)).await?;
+                    report.baselined += 1;
+                }
+                Outcome::Problem(problem, current_hash) => {
+                    warn!("{}: {:?}", path.display(), problem);
+                    report.issues.push(FileIssue {
+                        book_id,
+                        title: title.clone(),
+                        path,
+                        actions: suggested_actions(&problem),
+                        problem,
+                        current_hash,
+                    });
+                }
+            }
+            report.checked += 1;
+            self.send(VerifyEvent::Checked {
+                done: report.checked,
+                total,
+                title,
+            })
+            .await;
+        }
+
+        report.cancelled = self.cancel.is_cancelled() && report.checked < total;
+        report.issues.sort_by(|a, b| a.title.cmp(&b.title));
+        self.send(VerifyEvent::Finished {
+            checked: report.checked,
+            cancelled: report.cancelled,
+        })
+        .await;
+        Ok(report)
+    }
+
+    /// Carries out one of the issue's suggested actions
+    ///
+    /// # Errors
+    ///
+    /// Returns `LibraryError::InvalidFile` for actions that need the user,
+    /// such as relocating a file.
+    pub async fn apply(&self, issue: &FileIssue, action: SuggestedAction) -> Result<()> {
+        match action {
+            SuggestedAction::UpdateHash => {
+                let hash = match &issue.current_hash {
+                    Some(hash) => hash.clone(),
+                    None => hash_file(&issue.path)?,
+                };
+                books::set_file_hash(&self.pool, issue.book_id, Some(&hash)).await?;
+            }
+            SuggestedAction::RefreshMetadata => {
+                let options = ImportOptions {
+                    overwrite_existing: true,
+                    ..Default::default()
+                };
+                BookImporter::new(self.pool.clone())
+                    .import_file(&issue.path, options)
+                    .await?;
+            }
+            SuggestedAction::MarkCorrupt => {
+                let mut book = books::get_book(&self.pool, issue.book_id).await?;
+                if !book.tags.iter().any(|t| t == CORRUPT_TAG) {
+                    book.tags.push(CORRUPT_TAG.to_string());
+                    books::update_book(&self.pool, &book).await?;
+                }
+            }
+            SuggestedAction::Relocate => {
+                return Err(LibraryError::InvalidFile(format!(
+                    "{} must be restored or re-imported by hand",
+                    issue.path.display()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The books to check, and how many were skipped as corrupt
+    async fn books_in(&self, scope: VerifyScope) -> Result<(Vec<Book>, usize)> {
+        match scope {
+            VerifyScope::All => {
+                let (corrupt, library): (Vec<Book>, Vec<Book>) = books::list_books(&self.pool)
+                    .await?
+                    .into_iter()
+                    .partition(|b| b.tags.iter().any(|t| t == CORRUPT_TAG));
+                Ok((library, corrupt.len()))
+            }
+            VerifyScope::Books(ids) => {
+                let mut library = Vec::with_capacity(ids.len());
+                for id in ids {
+                    library.push(books::get_book(&self.pool, id).await?);
+                }
+                Ok((library, 0))
+            }
+        }
+    }
+
+    async fn send(&self, event: VerifyEvent) {
+        if let Some(tx) = &self.events {
+            let _ = tx.send(event).await;
+        }
+    }
+}
+
+/// SHA-256 of a file, as lowercase hex
+pub fn hash_file(path: &Path) -> Result<String> {
+    hash_file_cancellable(path, &ScanCancel::new())?.ok_or(LibraryError::Cancelled)
+}
+
+/// Hashes a file, returning `None` if cancelled part way through
+pub(crate) fn hash_file_cancellable(path: &Path, cancel: &ScanCancel) -> Result<Option<String>> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 1 << 20];
+    loop {
+        if cancel.is_cancelled() {
+            return Ok(None);
+        }
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(Some(
+        hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect(),
+    ))
+}
+
+/// Checks one file, returning `None` if cancelled
+fn check_file(
+    path: &Path,
+    stored_hash: Option<&str>,
+    depth: VerifyDepth,
+    analyzer: &AudioAnalyzer,
+    cancel: &ScanCancel,
+) -> Option<Outcome> {
+    if !path.exists() {
+        return Some(Outcome::Problem(FileProblem::Missing, None));
+    }
+    let hash = match hash_file_cancellable(path, cancel) {
+        Ok(Some(hash)) => hash,
+        Ok(None) => return None,
+        Err(e) => return Some(Outcome::Problem(FileProblem::Corrupt(e.to_string()), None)),
+    };
+
+    let changed = stored_hash.is_some_and(|stored| stored != hash);
+    // Changed files are always decoded, to tell an edit from damage
+    if changed || depth == VerifyDepth::Full {
+        if let Err(e) = analyzer.verify_decode(path) {
+            return Some(Outcome::Problem(
+                FileProblem::Corrupt(e.to_string()),
+                Some(hash),
+            ));
+        }
+    }
+
+    Some(match stored_hash {
+        None => Outcome::Baselined(hash),
+        Some(_) if changed => Outcome::Problem(FileProblem::Changed, Some(hash)),
+        Some(_) => Outcome::Ok,
+    })
+}
+
+fn suggested_actions(problem: &FileProblem) -> Vec<SuggestedAction> {
+    match problem {
+        FileProblem::Missing => vec![SuggestedAction::Relocate],
+        FileProblem::Changed => vec![
+            SuggestedAction::UpdateHash,
+            SuggestedAction::RefreshMetadata,
+        ],
+        FileProblem::Corrupt(_) => vec![SuggestedAction::Relocate, SuggestedAction::MarkCorrupt],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use storystream_core::Duration;
+    use storystream_database::{
+        connection::{connect, DatabaseConfig},
+        migrations::run_migrations,
+    };
+    use storystream_media_formats::wav::wav_bytes;
+    use tempfile::TempDir;
+
+    async fn setup(dir: &TempDir) -> DbPool {
+        let db_path = dir.path().join("library.db");
+        let pool = connect(DatabaseConfig::new(db_path.to_str().unwrap()))
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool
+    }
+
+    async fn add_book(pool: &DbPool, dir: &TempDir, name: &str, content: &[u8]) -> Book {
+        let path = dir.path().join(name);
+        std::fs::write(&path, content).unwrap();
+        let book = Book::new(
+            name.to_string(),
+            path,
+            content.len() as u64,
+            Duration::from_millis(100),
+        );
+        books::create_book(pool, &book).await.unwrap();
+        book
+    }
+
+    #[test]
+    fn test_hash_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("abc.txt");
+        std::fs::write(&path, "abc").unwrap();
+        assert_eq!(
+            hash_file(&path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verify_reports_changed_missing_and_corrupt() {
+        let dir = TempDir::new().unwrap();
+        let pool = setup(&dir).await;
+
+        let intact = add_book(&pool, &dir, "intact.wav", &wav_bytes(800, 0)).await;
+        let edited = add_book(&pool, &dir, "edited.wav", &wav_bytes(800, 0)).await;
+        let rotted = add_book(&pool, &dir, "rotted.wav", &wav_bytes(800, 0)).await;
+        let gone = add_book(&pool, &dir, "gone.wav", &wav_bytes(800, 0)).await;
+
+        let (tx, mut rx) = mpsc::channel(32);
+        let verifier = FileVerifier::new(pool.clone()).with_events(tx);
+
+        // The first run records the hashes
+        let report = verifier
+            .verify(VerifyScope::All, VerifyDepth::Hash)
+            .await
+            .unwrap();
+        assert_eq!(report.baselined, 4);
+        assert!(report.is_clean());
+
+        std::fs::write(&edited.file_path, wav_bytes(800, 1)).unwrap();
+        std::fs::write(&rotted.file_path, [0x5a; 64]).unwrap();
+        std::fs::remove_file(&gone.file_path).unwrap();
+
+        let report = verifier
+            .verify(VerifyScope::All, VerifyDepth::Full)
+            .await
+            .unwrap();
+        assert_eq!(report.checked, 4);
+        assert_eq!(report.ok, 1);
+        assert!(!report.cancelled);
+
+        let issue = |id: BookId| report.issues.iter().find(|i| i.book_id == id).unwrap();
+        assert!(report.issues.iter().all(|i| i.book_id != intact.id));
+        assert_eq!(issue(edited.id).problem, FileProblem::Changed);
+        assert_eq!(issue(edited.id).actions[0], SuggestedAction::UpdateHash);
+        assert!(matches!(issue(rotted.id).problem, FileProblem::Corrupt(_)));
+        assert_eq!(issue(gone.id).problem, FileProblem::Missing);
+
+        // Accepting the edit stops it being reported
+        verifier
+            .apply(issue(edited.id), SuggestedAction::UpdateHash)
+            .await
+            .unwrap();
+        verifier
+            .apply(issue(rotted.id), SuggestedAction::MarkCorrupt)
+            .await
+            .unwrap();
+        assert!(verifier
+            .apply(issue(gone.id), SuggestedAction::Relocate)
+            .await
+            .is_err());
+
+        let report = verifier
+            .verify(VerifyScope::Books(vec![edited.id]), VerifyDepth::Hash)
+            .await
+            .unwrap();
+        assert_eq!((report.checked, report.ok), (1, 1));
+        let tags = books::get_book(&pool, rotted.id).await.unwrap().tags;
+        assert_eq!(tags, vec![CORRUPT_TAG]);
+
+        // Books marked corrupt are left out until the tag is removed
+        let report = verifier
+            .verify(VerifyScope::All, VerifyDepth::Hash)
+            .await
+            .unwrap();
+        assert_eq!((report.checked, report.skipped), (3, 1));
+
+        let mut events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            events.push(event);
+        }
+        assert!(matches!(events[0], VerifyEvent::Started { total: 4 }));
+        assert!(matches!(
+            events.last(),
+            Some(VerifyEvent::Finished {
+                checked: 3,
+                cancelled: false
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_verify_cancelled() {
+        let dir = TempDir::new().unwrap();
+        let pool = setup(&dir).await;
+        add_book(&pool, &dir, "one.wav", &wav_bytes(800, 0)).await;
+        add_book(&pool, &dir, "two.wav", &wav_bytes(800, 0)).await;
+
+        let verifier = FileVerifier::new(pool.clone());
+        verifier.cancel_handle().cancel();
+        let report = verifier
+            .verify(VerifyScope::All, VerifyDepth::Hash)
+            .await
+            .unwrap();
+        assert!(report.cancelled);
+        assert_eq!(report.checked, 0);
+        assert!(books::get_file_hashes(&pool).await.unwrap().is_empty());
+    }
+}
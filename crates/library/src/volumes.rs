@@ -0,0 +1,139 @@
+//! Library folders on removable and network drives
+//!
+//! A watch folder on a USB drive or a network share disappears while the
+//! drive is away. [`VolumeMonitor`] tells that apart from a deleted book:
+//! books under a disconnected folder are unavailable, not missing, and come
+//! back on their own once a probe sees the folder again, without a rescan.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// A library folder coming or going
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VolumeChange {
+    Disconnected(PathBuf),
+    Reconnected(PathBuf),
+}
+
+/// Watches whether the library folders can be reached
+#[derive(Debug, Clone, Default)]
+pub struct VolumeMonitor {
+    roots: Vec<PathBuf>,
+    /// Whether each root was unreachable at the last probe
+    offline: Vec<bool>,
+}
+
+impl VolumeMonitor {
+    /// Starts watching `roots`, probing them once
+    pub fn new<P: Into<PathBuf>>(roots: impl IntoIterator<Item = P>) -> Self {
+        let roots: Vec<PathBuf> = roots.into_iter().map(Into::into).collect();
+        let offline = roots.iter().map(|root| !is_connected(root)).collect();
+        Self { roots, offline }
+    }
+
+    /// Checks every root again, returning those that came or went
+    ///
+    /// Cheap enough to run every few seconds: a root costs one directory
+    /// read, which is the first thing to fail when a drive is unplugged.
+    pub fn probe(&mut self) -> Vec<VolumeChange> {
+        let mut changes = Vec::new();
+        for (root, offline) in self.roots.iter().zip(self.offline.iter_mut()) {
+            let now_offline = !is_connected(root);
+            if now_offline == *offline {
+                continue;
+            }
+            *offline = now_offline;
+            if now_offline {
+                info!("Library folder disconnected: {}", root.display());
+                changes.push(VolumeChange::Disconnected(root.clone()));
+            } else {
+                info!("Library folder reconnected: {}", root.display());
+                changes.push(VolumeChange::Reconnected(root.clone()));
+            }
+        }
+        changes
+    }
+
+    /// Whether `path` lies in a library folder that is not connected
+    pub fn is_unavailable(&self, path: &Path) -> bool {
+        self.unavailable_roots().any(|root| path.starts_with(root))
+    }
+
+    /// Library folders that were unreachable at the last probe
+    pub fn unavailable_roots(&self) -> impl Iterator<Item = &Path> {
+        self.roots
+            .iter()
+            .zip(&self.offline)
+            .filter(|(_, offline)| **offline)
+            .map(|(root, _)| root.as_path())
+    }
+}
+
+/// Whether the library folder `root` can be reached
+///
+/// An unmounted drive usually leaves its mount point behind as an empty
+/// folder, so an empty folder counts as disconnected too. A root that is a
+/// single file is connected while it exists.
+pub fn is_connected(root: &Path) -> bool {
+    match fs::metadata(root) {
+        Ok(metadata) if metadata.is_dir() => fs::read_dir(root)
+            .map(|mut entries| entries.next().is_some())
+            .unwrap_or(false),
+        Ok(_) => true,
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_monitor_follows_drives() {
+        let dir = TempDir::new().unwrap();
+        let mounted = dir.path().join("usb");
+        let unmounted = dir.path().join("nas");
+        fs::create_dir(&mounted).unwrap();
+        fs::write(mounted.join("book.m4b"), b"audio").unwrap();
+        fs::create_dir(&unmounted).unwrap();
+
+        let mut monitor = VolumeMonitor::new([&mounted, &unmounted]);
+        assert!(!monitor.is_unavailable(&mounted.join("book.m4b")));
+        assert!(monitor.is_unavailable(&unmounted.join("Dune/dune.mp3")));
+        assert_eq!(
+            monitor.unavailable_roots().collect::<Vec<_>>(),
+            [unmounted.as_path()]
+        );
+        assert!(monitor.probe().is_empty());
+
+        // The first drive is unplugged and the second one mounted
+        fs::remove_file(mounted.join("book.m4b")).unwrap();
+        fs::remove_dir(&mounted).unwrap();
+        fs::write(unmounted.join("dune.mp3"), b"audio").unwrap();
+        assert_eq!(
+            monitor.probe(),
+            [
+                VolumeChange::Disconnected(mounted.clone()),
+                VolumeChange::Reconnected(unmounted.clone()),
+            ]
+        );
+        assert!(monitor.is_unavailable(&mounted.join("book.m4b")));
+        assert!(!monitor.is_unavailable(&unmounted.join("dune.mp3")));
+        // Books outside the library folders are never unavailable
+        assert!(!monitor.is_unavailable(Path::new("/elsewhere/book.mp3")));
+    }
+
+    #[test]
+    fn test_is_connected() {
+        let dir = TempDir::new().unwrap();
+        // An empty folder is a mount point with nothing mounted on it
+        assert!(!is_connected(dir.path()));
+        let file = dir.path().join("single.m4b");
+        fs::write(&file, b"audio").unwrap();
+        assert!(is_connected(&file));
+        assert!(is_connected(dir.path()));
+        assert!(!is_connected(&dir.path().join("gone")));
+    }
+}
@@ -178,6 +178,7 @@ fn test_to_book_with_full_metadata() -> Result<()> {
         description: Some("A classic American novel".to_string()),
         series: Some("The Great American Novels".to_string()),
         series_position: Some(1.0),
+        genre: None,
         duration: storystream_core::Duration::from_seconds(3600 * 8), // 8 hours
         file_size: 250_000_000,                                       // 250 MB
         format: storystream_media_formats::AudioFormat::Mp3,
@@ -216,6 +217,7 @@ fn test_to_book_with_minimal_metadata() -> Result<()> {
         description: None,
         series: None,
         series_position: None,
+        genre: None,
         duration: storystream_core::Duration::from_seconds(300),
         file_size: 5_000_000,
         format: storystream_media_formats::AudioFormat::Mp3,
@@ -250,6 +252,7 @@ fn test_to_book_fallback_title_from_filename() -> Result<()> {
         description: None,
         series: None,
         series_position: None,
+        genre: None,
         duration: storystream_core::Duration::from_seconds(1800),
         file_size: 10_000_000,
         format: storystream_media_formats::AudioFormat::Mp3,
@@ -278,6 +281,7 @@ fn test_to_book_with_unicode_path() -> Result<()> {
         description: None,
         series: None,
         series_position: None,
+        genre: None,
         duration: storystream_core::Duration::from_seconds(1200),
         file_size: 8_000_000,
         format: storystream_media_formats::AudioFormat::Mp3,
@@ -307,6 +311,7 @@ fn test_to_book_preserves_all_audio_properties() -> Result<()> {
         description: None,
         series: None,
         series_position: None,
+        genre: None,
         duration: storystream_core::Duration::from_seconds(7200),
         file_size: 500_000_000,
         format: storystream_media_formats::AudioFormat::Flac,
@@ -380,6 +385,7 @@ fn test_series_position_as_float() -> Result<()> {
         description: None,
         series: Some("My Series".to_string()),
         series_position: Some(2.5), // Fractional position
+        genre: None,
         duration: storystream_core::Duration::from_seconds(1000),
         file_size: 1_000_000,
         format: storystream_media_formats::AudioFormat::Mp3,
@@ -408,6 +414,7 @@ fn test_zero_duration_handled() -> Result<()> {
         description: None,
         series: None,
         series_position: None,
+        genre: None,
         duration: storystream_core::Duration::from_seconds(0),
         file_size: 1000,
         format: storystream_media_formats::AudioFormat::Mp3,
@@ -438,6 +445,7 @@ fn test_large_file_size() -> Result<()> {
         description: None,
         series: None,
         series_position: None,
+        genre: None,
         duration: storystream_core::Duration::from_seconds(36000),
         file_size: large_size,
         format: storystream_media_formats::AudioFormat::Flac,
@@ -467,6 +475,7 @@ fn test_mono_audio() -> Result<()> {
         description: None,
         series: None,
         series_position: None,
+        genre: None,
         duration: storystream_core::Duration::from_seconds(600),
         file_size: 5_000_000,
         format: storystream_media_formats::AudioFormat::Mp3,
@@ -497,6 +506,7 @@ fn test_very_long_title() -> Result<()> {
         description: None,
         series: None,
         series_position: None,
+        genre: None,
         duration: storystream_core::Duration::from_seconds(100),
         file_size: 1_000_000,
         format: storystream_media_formats::AudioFormat::Mp3,
@@ -525,6 +535,7 @@ fn test_special_characters_in_metadata() -> Result<()> {
         description: Some("Description with\nnewlines\tand\ttabs".to_string()),
         series: None,
         series_position: None,
+        genre: None,
         duration: storystream_core::Duration::from_seconds(1500),
         file_size: 10_000_000,
         format: storystream_media_formats::AudioFormat::Mp3,
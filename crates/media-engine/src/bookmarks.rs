@@ -1,9 +1,14 @@
 // crates/media-engine/src/bookmarks.rs
 // NEW FILE - Complete bookmark management system
 
+use crate::chapters::ChapterList;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use storystream_core::csv_field;
+
+/// Columns of [`BookmarkManager::export_csv`]
+pub const CSV_HEADER: &str = "book,chapter,timestamp,title,note";
 
 /// Type of bookmark
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -225,8 +230,12 @@ impl BookmarkManager {
             .map(|(_, bookmark)| bookmark)
     }
 
-    /// Get the nearest bookmark to a position
+    /// Get the nearest bookmark to a position, which may be at the position
     pub fn get_nearest_bookmark(&self, position: Duration) -> Option<&Bookmark> {
+        if let Some(bookmark) = self.bookmarks.get(&position) {
+            return Some(bookmark);
+        }
+
         let next = self.get_next_bookmark(position);
         let prev = self.get_previous_bookmark(position);
 
@@ -275,8 +284,24 @@ impl BookmarkManager {
         self.add_bookmark(bookmark)
     }
 
-    /// Clean up old auto-bookmarks to maintain the limit
-    fn cleanup_old_auto_bookmarks(&mut self) {
+    /// Adds an auto-bookmark, returning the old ones removed to stay within the limit
+    ///
+    /// Callers that persist bookmarks delete the returned ones from storage.
+    pub fn add_auto_bookmark(&mut self, mut bookmark: Bookmark) -> Result<Vec<Bookmark>, String> {
+        if let Some(duration) = self.duration {
+            if bookmark.position > duration {
+                return Err("Bookmark position exceeds audiobook duration".to_string());
+            }
+        }
+
+        bookmark.bookmark_type = BookmarkType::Auto;
+        let removed = self.cleanup_old_auto_bookmarks();
+        self.bookmarks.insert(bookmark.position, bookmark);
+        Ok(removed)
+    }
+
+    /// Clean up old auto-bookmarks to maintain the limit, returning the removed ones
+    fn cleanup_old_auto_bookmarks(&mut self) -> Vec<Bookmark> {
         let auto_bookmarks: Vec<_> = self
             .bookmarks
             .iter()
@@ -290,10 +315,13 @@ impl BookmarkManager {
             sorted.sort_by_key(|(_, created)| *created);
 
             let to_remove = sorted.len() - self.max_auto_bookmarks + 1;
-            for (pos, _) in sorted.iter().take(to_remove) {
-                self.bookmarks.remove(pos);
-            }
+            return sorted
+                .iter()
+                .take(to_remove)
+                .filter_map(|(pos, _)| self.bookmarks.remove(pos))
+                .collect();
         }
+        Vec::new()
     }
 
     /// Clear all bookmarks
@@ -324,6 +352,60 @@ impl BookmarkManager {
             .map_err(|e| format!("Failed to export bookmarks: {}", e))
     }
 
+    /// Export bookmarks as a Markdown section headed by the book's title
+    ///
+    /// Each bookmark is a list item with its time, chapter and title, and
+    /// its note quoted beneath. Chapters are named from `chapters` when the
+    /// book has them.
+    pub fn export_markdown(&self, book_title: &str, chapters: Option<&ChapterList>) -> String {
+        let mut output = format!("## {}\n\n", book_title);
+        for bookmark in self.get_all_bookmarks() {
+            let mut line = format!("- **{}**", clock_time(bookmark.position));
+            if let Some(chapter) = chapter_name(bookmark, chapters) {
+                line.push_str(&format!(" · {}", chapter));
+            }
+            if let Some(title) = &bookmark.title {
+                line.push_str(&format!(" · {}", title));
+            }
+            output.push_str(&line);
+            output.push('\n');
+            if let Some(note) = &bookmark.note {
+                for note_line in note.lines() {
+                    output.push_str(&format!("  > {}\n", note_line));
+                }
+            }
+        }
+        output
+    }
+
+    /// Export bookmarks as CSV, one row per bookmark under [`CSV_HEADER`]
+    pub fn export_csv(&self, book_title: &str, chapters: Option<&ChapterList>) -> String {
+        format!(
+            "{}\n{}",
+            CSV_HEADER,
+            self.export_csv_rows(book_title, chapters)
+        )
+    }
+
+    /// The rows of [`export_csv`](Self::export_csv) without the header, for
+    /// joining several books into one file
+    pub fn export_csv_rows(&self, book_title: &str, chapters: Option<&ChapterList>) -> String {
+        self.get_all_bookmarks()
+            .into_iter()
+            .map(|bookmark| {
+                let fields = [
+                    book_title,
+                    chapter_name(bookmark, chapters).unwrap_or_default(),
+                    &clock_time(bookmark.position),
+                    bookmark.title.as_deref().unwrap_or_default(),
+                    bookmark.note.as_deref().unwrap_or_default(),
+                ];
+                let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+                row.join(",") + "\n"
+            })
+            .collect()
+    }
+
     /// Import bookmarks from JSON
     pub fn import_json(&mut self, json: &str) -> Result<usize, String> {
         let bookmarks: Vec<Bookmark> =
@@ -356,6 +438,21 @@ impl Default for BookmarkManager {
     }
 }
 
+/// A position as `H:MM:SS`
+pub fn clock_time(position: Duration) -> String {
+    let secs = position.as_secs();
+    format!("{}:{:02}:{:02}", secs / 3600, (secs % 3600) / 60, secs % 60)
+}
+
+/// Title of the chapter a bookmark falls in
+fn chapter_name<'a>(bookmark: &Bookmark, chapters: Option<&'a ChapterList>) -> Option<&'a str> {
+    let chapters = chapters?;
+    chapters
+        .chapter_at_position(bookmark.position.as_secs_f64())
+        .or_else(|| chapters.get_chapter(bookmark.chapter_index?))
+        .map(|chapter| chapter.title.as_str())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -438,6 +535,9 @@ mod tests {
         let nearest = manager.get_nearest_bookmark(Duration::from_secs(180));
         assert!(nearest.is_some());
         assert_eq!(nearest.unwrap().position, Duration::from_secs(200));
+
+        let nearest = manager.get_nearest_bookmark(Duration::from_secs(300));
+        assert_eq!(nearest.unwrap().position, Duration::from_secs(300));
     }
 
     #[test]
@@ -466,6 +566,30 @@ mod tests {
         assert_eq!(auto_bookmarks.len(), 3); // Should only keep 3
     }
 
+    #[test]
+    fn test_add_auto_bookmark_returns_removed() {
+        let mut manager = BookmarkManager::new();
+        manager.configure_auto_bookmarks(true, 60, 2);
+        manager
+            .add_bookmark(Bookmark::new(Duration::from_secs(5), BookmarkType::User))
+            .unwrap();
+
+        let mut removed = Vec::new();
+        for i in 1..=3 {
+            let mut bookmark = Bookmark::new(Duration::from_secs(i * 60), BookmarkType::Auto);
+            bookmark.id = format!("auto-{}", i);
+            bookmark.created_at = UNIX_EPOCH + Duration::from_secs(i);
+            removed.push(manager.add_auto_bookmark(bookmark).unwrap());
+        }
+
+        assert!(removed[0].is_empty());
+        assert!(removed[1].is_empty());
+        assert_eq!(removed[2].len(), 1);
+        assert_eq!(removed[2][0].id, "auto-1");
+        assert_eq!(manager.get_bookmarks_by_type(BookmarkType::Auto).len(), 2);
+        assert_eq!(manager.get_bookmarks_by_type(BookmarkType::User).len(), 1);
+    }
+
     #[test]
     fn test_bookmark_types() {
         let mut manager = BookmarkManager::new();
@@ -566,4 +690,59 @@ mod tests {
         let b2 = Bookmark::new(Duration::from_secs(0), BookmarkType::User);
         assert_ne!(b1.id, b2.id); // IDs should be unique
     }
+
+    fn quoted_book() -> (BookmarkManager, ChapterList) {
+        use crate::chapters::ChapterMarker;
+
+        let mut manager = BookmarkManager::new();
+        manager
+            .add_bookmark(
+                Bookmark::new(Duration::from_secs(3725), BookmarkType::User)
+                    .with_title("The lighthouse")
+                    .with_note("\"It was, in a sense, home.\"\nSecond line"),
+            )
+            .unwrap();
+        manager
+            .add_bookmark(Bookmark::new(Duration::from_secs(65), BookmarkType::Auto))
+            .unwrap();
+        let chapters = ChapterList::with_chapters(vec![
+            ChapterMarker::new(0, "Prologue".to_string(), 0.0, 600.0),
+            ChapterMarker::new(1, "Chapter 1".to_string(), 600.0, 7200.0),
+        ]);
+        (manager, chapters)
+    }
+
+    #[test]
+    fn test_export_markdown() {
+        let (manager, chapters) = quoted_book();
+        let markdown = manager.export_markdown("Island", Some(&chapters));
+        let expected = [
+            "## Island\n\n",
+            "- **0:01:05** · Prologue\n",
+            "- **1:02:05** · Chapter 1 · The lighthouse\n",
+            "  > \"It was, in a sense, home.\"\n",
+            "  > Second line\n",
+        ];
+        assert_eq!(markdown, expected.concat());
+
+        let without_chapters = manager.export_markdown("Island", None);
+        assert!(without_chapters.contains("- **0:01:05**\n"));
+    }
+
+    #[test]
+    fn test_export_csv() {
+        let (manager, chapters) = quoted_book();
+        let csv = manager.export_csv("Island", Some(&chapters));
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], CSV_HEADER);
+        assert_eq!(lines[1], "Island,Prologue,0:01:05,,");
+        assert_eq!(
+            &csv[csv.find("Island,Chapter 1").unwrap()..],
+            "Island,Chapter 1,1:02:05,The lighthouse,\"\"\"It was, in a sense, home.\"\"\nSecond line\"\n"
+        );
+        assert_eq!(
+            manager.export_csv_rows("Island", Some(&chapters)),
+            csv[CSV_HEADER.len() + 1..]
+        );
+    }
 }
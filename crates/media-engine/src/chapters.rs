@@ -109,6 +109,26 @@ impl ChapterList {
         self.chapters.iter().find(|ch| ch.contains(position))
     }
 
+    /// Index of the chapter playing at `position`
+    ///
+    /// That is the last chapter starting at or before it, found by binary
+    /// search over the start times. `None` before the first chapter.
+    pub fn index_at(&self, position: f64) -> Option<usize> {
+        self.chapters
+            .partition_point(|ch| ch.start_time <= position)
+            .checked_sub(1)
+    }
+
+    /// Fraction of the chapter at `position` already played, 0.0 to 1.0
+    pub fn progress_at(&self, position: f64) -> Option<f32> {
+        let chapter = self.chapters.get(self.index_at(position)?)?;
+        if chapter.duration() <= 0.0 {
+            return Some(1.0);
+        }
+        let elapsed = (position - chapter.start_time) / chapter.duration();
+        Some(elapsed.clamp(0.0, 1.0) as f32)
+    }
+
     /// Updates current chapter based on position
     pub fn update_position(&mut self, position: f64) {
         if let Some(idx) = self.chapters.iter().position(|ch| ch.contains(position)) {
@@ -341,4 +361,34 @@ mod tests {
 
         assert!(list.go_to_previous().is_none());
     }
+
+    #[test]
+    fn test_index_at() {
+        let list = ChapterList::with_chapters(create_test_chapters());
+
+        assert_eq!(list.index_at(0.0), Some(0));
+        assert_eq!(list.index_at(299.9), Some(0));
+        assert_eq!(list.index_at(300.0), Some(1));
+        assert_eq!(list.index_at(1700.0), Some(3));
+        assert_eq!(list.index_at(5000.0), Some(3));
+
+        let late_start = ChapterList::with_chapters(vec![ChapterMarker::new(
+            0,
+            "Chapter 1".to_string(),
+            10.0,
+            60.0,
+        )]);
+        assert_eq!(late_start.index_at(5.0), None);
+        assert_eq!(ChapterList::new().index_at(5.0), None);
+    }
+
+    #[test]
+    fn test_progress_at() {
+        let list = ChapterList::with_chapters(create_test_chapters());
+
+        assert_eq!(list.progress_at(0.0), Some(0.0));
+        assert_eq!(list.progress_at(600.0), Some(0.5));
+        assert_eq!(list.progress_at(1800.0), Some(1.0));
+        assert_eq!(ChapterList::new().progress_at(10.0), None);
+    }
 }
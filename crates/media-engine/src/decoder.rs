@@ -1,20 +1,34 @@
 // FILE: crates/media-engine/src/decoder.rs
 
 use crate::error::{EngineError, EngineResult};
-use std::path::Path;
-use symphonia::core::audio::{AudioBufferRef, SampleBuffer, Signal, SignalSpec};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use symphonia::core::audio::{AudioBufferRef, SampleBuffer, SignalSpec};
 pub(crate) use symphonia::core::codecs::{Decoder, DecoderOptions};
 use symphonia::core::errors::Error as SymphoniaError;
-use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo};
+use symphonia::core::formats::{FormatOptions, FormatReader, Packet, SeekMode, SeekTo};
 use symphonia::core::io::MediaSourceStream;
 use symphonia::core::meta::MetadataOptions;
 use symphonia::core::probe::Hint;
+use symphonia::core::units::TimeBase;
+
+/// Most samples a single [`AudioDecoder::decode_chunk`] call returns
+///
+/// Larger requests are clamped, so a caller cannot make the decoder buffer
+/// an unbounded stretch of audio at once.
+pub const MAX_CHUNK_SAMPLES: usize = 1 << 16;
 
 pub struct AudioDecoder {
+    path: PathBuf,
     reader: Box<dyn FormatReader>,
     decoder: Box<dyn Decoder>,
     track_id: u32,
     spec: SignalSpec,
+    /// Interleaving buffer, reused for every packet and grown only when a
+    /// packet larger than any seen before arrives
+    sample_buf: Option<SampleBuffer<f32>>,
+    /// Decoded samples that did not fit in the last chunk
+    pending: Vec<f32>,
 }
 
 pub struct DecodedAudio {
@@ -24,26 +38,7 @@ pub struct DecodedAudio {
 
 impl AudioDecoder {
     pub fn new(path: &Path) -> EngineResult<Self> {
-        let file = std::fs::File::open(path)
-            .map_err(|e| EngineError::DecodeError(format!("Failed to open file: {}", e)))?;
-
-        let mss = MediaSourceStream::new(Box::new(file), Default::default());
-
-        let mut hint = Hint::new();
-        if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
-            hint.with_extension(extension);
-        }
-
-        let probed = symphonia::default::get_probe()
-            .format(
-                &hint,
-                mss,
-                &FormatOptions::default(),
-                &MetadataOptions::default(),
-            )
-            .map_err(|e| EngineError::DecodeError(format!("Failed to probe format: {}", e)))?;
-
-        let reader = probed.format;
+        let reader = open_reader(path)?;
 
         let track = reader
             .default_track()
@@ -62,38 +57,64 @@ impl AudioDecoder {
         );
 
         Ok(Self {
+            path: path.to_path_buf(),
             reader,
             decoder,
             track_id,
             spec,
+            sample_buf: None,
+            pending: Vec::new(),
         })
     }
 
     pub fn decode_next(&mut self) -> EngineResult<Option<DecodedAudio>> {
         loop {
-            let packet = match self.reader.next_packet() {
-                Ok(packet) => packet,
-                Err(SymphoniaError::IoError(e))
-                    if e.kind() == std::io::ErrorKind::UnexpectedEof =>
-                {
-                    return Ok(None);
+            let Some(packet) = next_track_packet(self.reader.as_mut(), self.track_id)? else {
+                return Ok(None);
+            };
+
+            let decoded = match self.decoder.decode(&packet) {
+                Ok(decoded) => decoded,
+                Err(SymphoniaError::DecodeError(e)) => {
+                    tracing::warn!("Decode error, skipping packet: {}", e);
+                    continue;
                 }
                 Err(e) => {
                     return Err(EngineError::DecodeError(format!(
-                        "Failed to read packet: {}",
+                        "Failed to decode packet: {}",
                         e
                     )));
                 }
             };
 
-            if packet.track_id() != self.track_id {
-                continue;
-            }
+            let spec = *decoded.spec();
+            let samples = interleave(&mut self.sample_buf, decoded).to_vec();
+
+            return Ok(Some(DecodedAudio { samples, spec }));
+        }
+    }
+
+    /// Decodes up to `max_samples` interleaved samples, clamped to
+    /// [`MAX_CHUNK_SAMPLES`]
+    ///
+    /// Samples past the limit are kept for the next call rather than dropped.
+    /// Returns an empty buffer at the end of the stream.
+    pub fn decode_chunk(&mut self, max_samples: usize) -> EngineResult<Vec<f32>> {
+        let max_samples = max_samples.min(MAX_CHUNK_SAMPLES);
+        let mut output = Vec::with_capacity(max_samples);
+
+        let carried = self.pending.len().min(max_samples);
+        output.extend(self.pending.drain(..carried));
+
+        while output.len() < max_samples {
+            let Some(packet) = next_track_packet(self.reader.as_mut(), self.track_id)? else {
+                break;
+            };
 
             let decoded = match self.decoder.decode(&packet) {
                 Ok(decoded) => decoded,
                 Err(SymphoniaError::DecodeError(e)) => {
-                    log::warn!("Decode error, skipping packet: {}", e);
+                    tracing::warn!("Decode error, skipping packet: {}", e);
                     continue;
                 }
                 Err(e) => {
@@ -104,11 +125,37 @@ impl AudioDecoder {
                 }
             };
 
-            let samples = convert_to_f32(&decoded)?;
-            let spec = *decoded.spec();
-
-            return Ok(Some(DecodedAudio { samples, spec }));
+            let samples = interleave(&mut self.sample_buf, decoded);
+            let fits = samples.len().min(max_samples - output.len());
+            output.extend_from_slice(&samples[..fits]);
+            self.pending.extend_from_slice(&samples[fits..]);
         }
+
+        Ok(output)
+    }
+
+    /// Total length of the stream
+    ///
+    /// Taken from the container when it records a frame count. Otherwise the
+    /// file is scanned once, packet by packet, summing packet durations.
+    pub fn duration(&self) -> Option<Duration> {
+        let params = &self
+            .reader
+            .tracks()
+            .iter()
+            .find(|t| t.id == self.track_id)?
+            .codec_params;
+        let time_base = params
+            .time_base
+            .unwrap_or_else(|| TimeBase::new(1, self.spec.rate));
+
+        let frames = match params.n_frames {
+            Some(frames) => frames,
+            None => scan_frames(&self.path, self.track_id).ok()?,
+        };
+
+        let time = time_base.calc_time(frames);
+        Some(Duration::from_secs(time.seconds) + Duration::from_secs_f64(time.frac))
     }
 
     pub fn spec(&self) -> &SignalSpec {
@@ -130,54 +177,84 @@ impl AudioDecoder {
             .map_err(|e| EngineError::SeekError(format!("Failed to seek: {}", e)))?;
 
         self.decoder.reset();
+        self.pending.clear();
 
         Ok(())
     }
 }
 
-fn convert_to_f32(decoded: &AudioBufferRef) -> EngineResult<Vec<f32>> {
-    match decoded {
-        AudioBufferRef::F32(buf) => {
-            let mut samples = Vec::with_capacity(buf.frames() * buf.spec().channels.count());
-            for plane in buf.planes().planes() {
-                samples.extend_from_slice(plane);
+fn open_reader(path: &Path) -> EngineResult<Box<dyn FormatReader>> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| EngineError::DecodeError(format!("Failed to open file: {}", e)))?;
+
+    let mss = MediaSourceStream::new(Box::new(file), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(extension);
+    }
+
+    let probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            mss,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(|e| EngineError::DecodeError(format!("Failed to probe format: {}", e)))?;
+
+    Ok(probed.format)
+}
+
+/// Reads the next packet of `track_id`, or `None` at the end of the stream
+fn next_track_packet(reader: &mut dyn FormatReader, track_id: u32) -> EngineResult<Option<Packet>> {
+    loop {
+        match reader.next_packet() {
+            Ok(packet) if packet.track_id() == track_id => return Ok(Some(packet)),
+            Ok(_) => continue,
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                return Ok(None);
+            }
+            Err(e) => {
+                return Err(EngineError::DecodeError(format!(
+                    "Failed to read packet: {}",
+                    e
+                )));
             }
-            Ok(samples)
-        }
-        AudioBufferRef::S16(buf) => {
-            let mut sample_buf = SampleBuffer::<f32>::new(buf.capacity() as u64, *buf.spec());
-            sample_buf.copy_interleaved_ref(AudioBufferRef::S16(buf.clone()));
-            Ok(sample_buf.samples().to_vec())
-        }
-        AudioBufferRef::S32(buf) => {
-            let mut sample_buf = SampleBuffer::<f32>::new(buf.capacity() as u64, *buf.spec());
-            sample_buf.copy_interleaved_ref(AudioBufferRef::S32(buf.clone()));
-            Ok(sample_buf.samples().to_vec())
-        }
-        AudioBufferRef::U8(buf) => {
-            let mut sample_buf = SampleBuffer::<f32>::new(buf.capacity() as u64, *buf.spec());
-            sample_buf.copy_interleaved_ref(AudioBufferRef::U8(buf.clone()));
-            Ok(sample_buf.samples().to_vec())
-        }
-        AudioBufferRef::U16(buf) => {
-            let mut sample_buf = SampleBuffer::<f32>::new(buf.capacity() as u64, *buf.spec());
-            sample_buf.copy_interleaved_ref(AudioBufferRef::U16(buf.clone()));
-            Ok(sample_buf.samples().to_vec())
-        }
-        AudioBufferRef::U32(buf) => {
-            let mut sample_buf = SampleBuffer::<f32>::new(buf.capacity() as u64, *buf.spec());
-            sample_buf.copy_interleaved_ref(AudioBufferRef::U32(buf.clone()));
-            Ok(sample_buf.samples().to_vec())
         }
-        _ => Err(EngineError::DecodeError(
-            "Unsupported sample format".to_string(),
-        )),
     }
 }
 
+/// Counts the frames of `track_id` without decoding or keeping any packets
+fn scan_frames(path: &Path, track_id: u32) -> EngineResult<u64> {
+    let mut reader = open_reader(path)?;
+    let mut frames = 0;
+    while let Some(packet) = next_track_packet(reader.as_mut(), track_id)? {
+        frames += packet.dur();
+    }
+    Ok(frames)
+}
+
+/// Copies `decoded` into `slot` as interleaved f32 samples
+///
+/// The buffer is sized by the decoder's packet capacity, so it is allocated
+/// once per stream in practice and never shrinks.
+fn interleave<'a>(slot: &'a mut Option<SampleBuffer<f32>>, decoded: AudioBufferRef) -> &'a [f32] {
+    let spec = *decoded.spec();
+    let needed = decoded.capacity() * spec.channels.count();
+
+    if slot.as_ref().is_none_or(|buf| buf.capacity() < needed) {
+        *slot = None;
+    }
+    let sample_buf = slot.get_or_insert_with(|| SampleBuffer::new(decoded.capacity() as u64, spec));
+    sample_buf.copy_interleaved_ref(decoded);
+    sample_buf.samples()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use storystream_media_formats::wav::wav_from_samples;
 
     #[test]
     fn test_decoder_nonexistent_file() {
@@ -189,4 +266,43 @@ mod tests {
         // Just test that the function exists and compiles
         // Real testing would require actual audio data
     }
+
+    /// Writes a 16-bit mono WAV with a ramp of `frames` samples
+    fn write_wav(path: &Path, rate: u32, frames: u32) {
+        let ramp = (0..frames).map(|i| (i % 1000) as i16 * 16);
+        std::fs::write(path, wav_from_samples(ramp, rate)).unwrap();
+    }
+
+    #[test]
+    fn test_decode_chunk_carries_overflow() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ramp.wav");
+        write_wav(&path, 8000, 5000);
+
+        let mut decoder = AudioDecoder::new(&path).unwrap();
+        let mut decoded = Vec::new();
+        loop {
+            let chunk = decoder.decode_chunk(300).unwrap();
+            if chunk.is_empty() {
+                break;
+            }
+            assert!(chunk.len() <= 300);
+            decoded.extend(chunk);
+        }
+
+        // Nothing dropped at chunk boundaries
+        assert_eq!(decoded.len(), 5000);
+        assert_eq!(decoded[1001], 16.0 / 32768.0);
+    }
+
+    #[test]
+    fn test_duration_scan_matches_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ramp.wav");
+        write_wav(&path, 8000, 12_000);
+
+        let decoder = AudioDecoder::new(&path).unwrap();
+        assert_eq!(decoder.duration(), Some(Duration::from_millis(1500)));
+        assert_eq!(scan_frames(&path, decoder.track_id).unwrap(), 12_000);
+    }
 }
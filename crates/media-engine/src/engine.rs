@@ -1,19 +1,34 @@
 // crates/media-engine/src/engine.rs
 // PANIC-FREE IMPLEMENTATION - Zero unwrap/expect calls, all errors handled gracefully
 
-use crate::chapters::ChapterList;
+use crate::audio_device::AudioDeviceInfo;
+use crate::bookmarks::BookmarkManager;
+use crate::chapters::{ChapterList, ChapterMarker};
 use crate::decoder::AudioDecoder;
 use crate::equalizer::Equalizer;
-use crate::playback::PlaybackState;
+use crate::output::OutputTarget;
+use crate::playback::{PlaybackDeviceError, PlaybackState, PlaybackStatus};
 use crate::playback_thread::{
-    self, AudioDecoder as PlaybackAudioDecoder, Equalizer as PlaybackEqualizer, PlaybackCommand,
+    self, AudioDecoder as PlaybackAudioDecoder, Equalizer as PlaybackEqualizer, Interruption,
+    PlaybackCommand, PlaybackShared,
 };
+use crate::queue::{PlaybackQueue, QueuedTrack};
+use crate::sleep::{ArmedSleepTimer, SleepDeadline};
+use crate::snap::{self, SnappedPosition};
 use crate::speed::Speed;
+use crate::types::MediaEvent;
+use std::fmt;
 use std::path::Path;
+use std::sync::atomic::Ordering;
 use std::sync::mpsc::{channel, Sender};
 use std::sync::{Arc, Mutex, PoisonError};
 use std::thread::JoinHandle;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use storystream_core::types::{EqualizerPreset, SleepTimer, SpeedRamp};
+
+/// Seconds into a chapter after which going back restarts it instead of
+/// moving to the chapter before
+const CHAPTER_RESTART_SECS: f64 = 3.0;
 
 /// Configuration for the media engine
 #[derive(Debug, Clone)]
@@ -44,8 +59,46 @@ pub struct MediaEngine {
     current_status: Arc<Mutex<bool>>,
     chapters: Arc<Mutex<ChapterList>>,
     volume: Arc<Mutex<f32>>,
+    /// Linear gain evening out the loaded book's loudness, read by the playback thread
+    replay_gain: Arc<Mutex<f32>>,
+    /// The same gain in dB, `None` when loudness is left as recorded
+    replay_gain_db: Option<f32>,
+    /// Device the playback thread opened, `None` until playback starts
+    output_device: Arc<Mutex<Option<AudioDeviceInfo>>>,
+    /// Device chosen to play on, `None` for the system default
+    selected_device: Arc<Mutex<Option<AudioDeviceInfo>>>,
     pub speed: Arc<Mutex<Speed>>,
     equalizer: Arc<Mutex<Equalizer>>,
+    /// Band gains the playback thread reads while it runs
+    playback_equalizer: Arc<Mutex<PlaybackEqualizer>>,
+    /// Presets books can use
+    equalizer_presets: Vec<EqualizerPreset>,
+    /// Preset for books that have not picked one
+    default_equalizer: String,
+    /// Preset currently applied
+    active_equalizer: EqualizerPreset,
+    /// Ramp moving the speed, if one is running
+    speed_ramp: Option<SpeedRamp>,
+    /// Speed chosen before the ramp started, restored when it ends
+    fixed_speed: Option<Speed>,
+    /// When the ramp last counted playing time
+    ramp_checked: Option<Instant>,
+    /// Whether reaching the end of the file has been reported
+    end_reported: bool,
+    /// Output interruptions the playback thread detects
+    interruption: Arc<Interruption>,
+    /// Whether the current interruption has been reported
+    interruption_reported: bool,
+    /// Sleep timer the playback thread counts down, if one is armed
+    sleep_timer: Arc<Mutex<Option<ArmedSleepTimer>>>,
+    /// Why `play()` is refused, if it is
+    play_lock: Option<String>,
+    /// Where the playback thread plays audio
+    output_target: OutputTarget,
+    /// Files played one after another, the loaded one first
+    queue: Arc<Mutex<PlaybackQueue>>,
+    /// The queued file last reported as playing
+    track_reported: usize,
     thread_handle: Option<JoinHandle<()>>,
     playback_state: Arc<Mutex<PlaybackState>>,
     pub duration: Option<Duration>,
@@ -77,8 +130,27 @@ impl MediaEngine {
             current_status: Arc::new(Mutex::new(false)),
             chapters: Arc::new(Mutex::new(ChapterList::new())),
             volume: Arc::new(Mutex::new(1.0)),
+            replay_gain: Arc::new(Mutex::new(1.0)),
+            replay_gain_db: None,
+            output_device: Arc::new(Mutex::new(None)),
+            selected_device: Arc::new(Mutex::new(None)),
             speed: Arc::new(Mutex::new(Speed::default())),
             equalizer: Arc::new(Mutex::new(Equalizer::default())),
+            playback_equalizer: Arc::new(Mutex::new(PlaybackEqualizer::default())),
+            equalizer_presets: EqualizerPreset::builtin(),
+            default_equalizer: EqualizerPreset::flat().name,
+            active_equalizer: EqualizerPreset::flat(),
+            speed_ramp: None,
+            fixed_speed: None,
+            ramp_checked: None,
+            end_reported: false,
+            interruption: Arc::new(Interruption::default()),
+            interruption_reported: false,
+            sleep_timer: Arc::new(Mutex::new(None)),
+            play_lock: None,
+            output_target: OutputTarget::default(),
+            queue: Arc::new(Mutex::new(PlaybackQueue::default())),
+            track_reported: 0,
             thread_handle: None,
             playback_state: Arc::new(Mutex::new(PlaybackState::new())),
             duration: None,
@@ -91,6 +163,14 @@ impl MediaEngine {
         Self::new(EngineConfig::default())
     }
 
+    /// Plays through `target` instead of the sound device
+    ///
+    /// Takes effect from the next file loaded.
+    pub fn with_output(mut self, target: OutputTarget) -> Self {
+        self.output_target = target;
+        self
+    }
+
     /// Loads an audio file for playback
     /// Returns Err with actionable message on failure - NEVER PANICS
     pub fn load(&mut self, path: &str) -> Result<(), String> {
@@ -109,7 +189,9 @@ impl MediaEngine {
         if let Some(handle) = self.thread_handle.take() {
             // Join returns Err only if thread panicked - handle gracefully
             if let Err(_) = handle.join() {
-                return Err("Previous playback thread panicked - engine state may be corrupted".to_string());
+                return Err(
+                    "Previous playback thread panicked - engine state may be corrupted".to_string(),
+                );
             }
         }
 
@@ -128,6 +210,13 @@ impl MediaEngine {
         self.decoder = Some(decoder);
         self.loaded_file = Some(path.to_string());
 
+        // A new file starts a new queue
+        match self.queue.lock() {
+            Ok(mut queue) => *queue = PlaybackQueue::new(QueuedTrack::new(path, duration)),
+            Err(e) => return Err(format!("Failed to reset the queue: mutex poisoned - {}", e)),
+        }
+        self.track_reported = 0;
+
         // Update playback state with proper error handling
         match self.playback_state.lock() {
             Ok(mut state) => {
@@ -135,7 +224,10 @@ impl MediaEngine {
                 *state = PlaybackState::stopped();
             }
             Err(e) => {
-                return Err(format!("Failed to update playback state: mutex poisoned - {}", e));
+                return Err(format!(
+                    "Failed to update playback state: mutex poisoned - {}",
+                    e
+                ));
             }
         }
 
@@ -155,9 +247,35 @@ impl MediaEngine {
         Ok(())
     }
 
+    /// Loads a book's file and applies its equalizer
+    ///
+    /// Books without a preset of their own get the default preset. A preset
+    /// that no longer exists plays flat rather than failing the load.
+    /// Returns Err with actionable message on failure - NEVER PANICS
+    pub fn load_book(
+        &mut self,
+        path: &str,
+        equalizer: Option<&EqualizerPreset>,
+    ) -> Result<(), String> {
+        self.load(path)?;
+        let preset = self.book_equalizer(equalizer);
+        self.apply_equalizer_preset(preset)
+    }
+
+    /// The preset a book with `equalizer` stored plays with
+    fn book_equalizer(&self, equalizer: Option<&EqualizerPreset>) -> EqualizerPreset {
+        match equalizer {
+            Some(preset) => preset.resolve(&self.equalizer_presets),
+            None => EqualizerPreset::find(&self.default_equalizer, &self.equalizer_presets),
+        }
+    }
+
     /// Starts playback
     /// Returns Err with actionable message on failure - NEVER PANICS
     pub fn play(&mut self) -> Result<(), String> {
+        if let Some(reason) = &self.play_lock {
+            return Err(format!("Playback locked: {}", reason));
+        }
         if self.loaded_file.is_none() {
             return Err("Cannot play: no file loaded. Call load() first".to_string());
         }
@@ -165,7 +283,12 @@ impl MediaEngine {
         let tx = match self.command_tx.lock() {
             Ok(guard) => match guard.as_ref() {
                 Some(tx) => tx.clone(),
-                None => return Err("Cannot play: playback thread not running. Try reloading the file".to_string()),
+                None => {
+                    return Err(
+                        "Cannot play: playback thread not running. Try reloading the file"
+                            .to_string(),
+                    )
+                }
             },
             Err(e) => return Err(format!("Cannot play: command channel poisoned - {}", e)),
         };
@@ -227,6 +350,7 @@ impl MediaEngine {
         if let Ok(mut status) = self.current_status.lock() {
             *status = false;
         }
+        self.interruption.active.store(false, Ordering::Relaxed);
 
         // Stop always succeeds - errors are non-critical
         Ok(())
@@ -250,6 +374,15 @@ impl MediaEngine {
             }
         }
 
+        // Positions across the queue may fall in another file
+        let position = match self.locate_in_queue(position) {
+            Some((index, within)) if index != self.current_track() => {
+                return self.skip_to_position(index, within);
+            }
+            Some((_, within)) => within,
+            None => position,
+        };
+
         let tx = match self.command_tx.lock() {
             Ok(guard) => match guard.as_ref() {
                 Some(tx) => tx.clone(),
@@ -269,6 +402,147 @@ impl MediaEngine {
         Ok(())
     }
 
+    /// Finds the file a seek lands in and the position within it, `None`
+    /// when positions are per file
+    fn locate_in_queue(&self, position: Duration) -> Option<(usize, Duration)> {
+        let queue = self.queue.lock().ok()?;
+        if !queue.is_aggregate() {
+            return None;
+        }
+        queue.locate(position)
+    }
+
+    /// Adds a file to play after the ones already queued
+    ///
+    /// Loads it when nothing is loaded yet. The playback thread opens each
+    /// file ahead of time and moves on to it when the one before ends.
+    /// Returns Err with actionable message on failure - NEVER PANICS
+    pub fn enqueue(&mut self, path: &str) -> Result<(), String> {
+        if path.is_empty() {
+            return Err("Cannot enqueue: path is empty".to_string());
+        }
+        if self.loaded_file.is_none() {
+            return self.load(path);
+        }
+
+        let decoder = AudioDecoder::new(Path::new(path))
+            .map_err(|e| format!("Failed to create decoder: {:?}", e))?;
+        let duration = decoder.duration().unwrap_or(Duration::ZERO);
+        let mut queue = self
+            .queue
+            .lock()
+            .map_err(|e| format!("Cannot enqueue: queue poisoned - {}", e))?;
+        queue.push(QueuedTrack::new(path, duration));
+        if queue.is_aggregate() {
+            self.duration = Some(queue.total_duration());
+        }
+        Ok(())
+    }
+
+    /// Returns the queued files, the loaded one included - NEVER PANICS
+    pub fn queue(&self) -> Vec<QueuedTrack> {
+        self.queue
+            .lock()
+            .map(|queue| queue.tracks().to_vec())
+            .unwrap_or_default()
+    }
+
+    /// Returns the index in the queue of the file playing - NEVER PANICS
+    pub fn current_track(&self) -> usize {
+        self.queue.lock().map(|queue| queue.current()).unwrap_or(0)
+    }
+
+    /// Jumps to the start of a queued file
+    /// Returns Err with actionable message on failure - NEVER PANICS
+    pub fn skip_to(&mut self, index: usize) -> Result<(), String> {
+        if self.loaded_file.is_none() {
+            return Err("Cannot skip: no file loaded".to_string());
+        }
+        let count = self.queue.lock().map(|queue| queue.len()).unwrap_or(0);
+        if index >= count {
+            return Err(format!(
+                "Cannot skip to file {}: the queue holds {}",
+                index + 1,
+                count
+            ));
+        }
+        self.skip_to_position(index, Duration::ZERO)
+    }
+
+    /// Plays the queued file at `index` from `position` within it
+    fn skip_to_position(&mut self, index: usize, position: Duration) -> Result<(), String> {
+        let tx = match self.command_tx.lock() {
+            Ok(guard) => match guard.as_ref() {
+                Some(tx) => tx.clone(),
+                None => return Err("Cannot skip: playback thread not running".to_string()),
+            },
+            Err(e) => return Err(format!("Cannot skip: command channel poisoned - {}", e)),
+        };
+
+        tx.send(PlaybackCommand::SkipTo { index, position })
+            .map_err(|e| format!("Failed to send skip command: {}", e))?;
+
+        // Report the new file straight away; the thread confirms it
+        if let Ok(mut queue) = self.queue.lock() {
+            queue.set_current(index);
+            if let Ok(mut pos) = self.current_position.lock() {
+                *pos = position;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets whether positions cover the whole queue - NEVER PANICS
+    ///
+    /// For a book split over the queued files: `position()`, `seek()` and
+    /// `duration` then count from the start of the first file, so the
+    /// book's saved position and chapters work as for a single file. Off
+    /// by default, and after each load.
+    pub fn set_aggregate_position(&mut self, enabled: bool) {
+        if let Ok(mut queue) = self.queue.lock() {
+            queue.set_aggregate(enabled);
+            self.duration = if enabled {
+                Some(queue.total_duration())
+            } else {
+                queue.current_track().map(|track| track.duration)
+            };
+        }
+    }
+
+    /// Returns the position across the whole queue - NEVER PANICS
+    pub fn queue_position(&self) -> Duration {
+        let Ok(queue) = self.queue.lock() else {
+            return Duration::ZERO;
+        };
+        queue.queue_position(self.track_position())
+    }
+
+    /// Returns how long the whole queue plays - NEVER PANICS
+    pub fn queue_duration(&self) -> Duration {
+        self.queue
+            .lock()
+            .map(|queue| queue.total_duration())
+            .unwrap_or(Duration::ZERO)
+    }
+
+    /// Reports the queue moving on to another file - NEVER PANICS
+    ///
+    /// Returns `MediaEvent::TrackChanged` with the file playing when it
+    /// differs from the one last reported. With positions per file,
+    /// `duration` follows the file playing.
+    pub fn take_track_changed(&mut self) -> Option<MediaEvent> {
+        let queue = self.queue.lock().ok()?;
+        let index = queue.current();
+        if index == self.track_reported {
+            return None;
+        }
+        self.track_reported = index;
+        if !queue.is_aggregate() {
+            self.duration = queue.current_track().map(|track| track.duration);
+        }
+        Some(MediaEvent::TrackChanged { index })
+    }
+
     /// Sets the playback volume (0.0 to 1.0)
     /// Returns Err with actionable message on invalid input - NEVER PANICS
     pub fn set_volume(&mut self, volume: f32) -> Result<(), String> {
@@ -302,6 +576,31 @@ impl MediaEngine {
         Ok(())
     }
 
+    /// Evens out the loaded book's loudness by `gain_db`, or stops with `None`
+    ///
+    /// The gain applies before the volume, which stays the listener's own
+    /// setting, and peaks it would push past full scale are rounded off. It
+    /// is kept across loads, so set it again for each book.
+    /// Returns Err with actionable message on invalid input - NEVER PANICS
+    pub fn set_replay_gain(&mut self, gain_db: Option<f32>) -> Result<(), String> {
+        if let Some(db) = gain_db.filter(|db| !db.is_finite()) {
+            return Err(format!("Invalid replay gain: {} dB", db));
+        }
+
+        let linear = gain_db.map_or(1.0, |db| 10f32.powf(db / 20.0));
+        match self.replay_gain.lock() {
+            Ok(mut gain) => *gain = linear,
+            Err(e) => return Err(format!("Failed to set replay gain: mutex poisoned - {}", e)),
+        }
+        self.replay_gain_db = gain_db;
+        Ok(())
+    }
+
+    /// Returns the gain evening out the loaded book's loudness, in dB
+    pub fn replay_gain(&self) -> Option<f32> {
+        self.replay_gain_db
+    }
+
     /// Sets the playback speed
     /// Returns Err with actionable message on failure - NEVER PANICS
     pub fn set_speed(&mut self, speed: Speed) -> Result<(), String> {
@@ -321,9 +620,182 @@ impl MediaEngine {
         Ok(())
     }
 
+    /// Starts ramping the speed from `start` toward `target`
+    ///
+    /// The speed changes by `per_hour_increment` for each hour of playback,
+    /// in steps at minute boundaries; see [`MediaEngine::advance_speed_ramp`].
+    /// Returns Err with actionable message on invalid input - NEVER PANICS
+    pub fn set_speed_ramp(
+        &mut self,
+        start: Speed,
+        target: Speed,
+        per_hour_increment: f32,
+    ) -> Result<(), String> {
+        let ramp = SpeedRamp::new(start.value(), target.value(), per_hour_increment)?;
+        self.resume_speed_ramp(ramp)
+    }
+
+    /// Continues a ramp saved earlier, from the speed it had reached
+    /// Returns Err with actionable message on invalid input - NEVER PANICS
+    pub fn resume_speed_ramp(&mut self, ramp: SpeedRamp) -> Result<(), String> {
+        // Every step is a speed like any other, so it gets the same bounds
+        Speed::new(ramp.start)?;
+        Speed::new(ramp.target)?;
+        let speed = Speed::new(ramp.speed())?;
+
+        if self.speed_ramp.is_none() {
+            self.fixed_speed = self.speed.lock().ok().map(|speed| *speed);
+        }
+        self.set_speed(speed)?;
+        self.speed_ramp = Some(ramp);
+        self.ramp_checked = None;
+        Ok(())
+    }
+
+    /// Ends the ramp and restores the speed set before it began
+    ///
+    /// Returns the ramp as it stood, `None` if there was none.
+    /// Returns Err with actionable message on failure - NEVER PANICS
+    pub fn clear_speed_ramp(&mut self) -> Result<Option<SpeedRamp>, String> {
+        let ramp = self.speed_ramp.take();
+        self.ramp_checked = None;
+        if let Some(speed) = self.fixed_speed.take() {
+            self.set_speed(speed)?;
+        }
+        Ok(ramp)
+    }
+
+    /// The running speed ramp and its progress
+    pub fn speed_ramp(&self) -> Option<&SpeedRamp> {
+        self.speed_ramp.as_ref()
+    }
+
+    /// Counts playing time toward the speed ramp
+    ///
+    /// Call it regularly while a ramp runs; time spent paused is not counted.
+    /// Returns `MediaEvent::SpeedChanged` when the ramp takes a step.
+    /// Returns Err with actionable message on failure - NEVER PANICS
+    pub fn advance_speed_ramp(&mut self) -> Result<Option<MediaEvent>, String> {
+        if self.speed_ramp.is_none() || !self.is_playing() {
+            self.ramp_checked = None;
+            return Ok(None);
+        }
+
+        let now = Instant::now();
+        let elapsed = self
+            .ramp_checked
+            .replace(now)
+            .map_or(Duration::ZERO, |since| now.duration_since(since));
+        self.step_speed_ramp(elapsed)
+    }
+
+    /// Counts `elapsed` toward the ramp and applies the speed it reaches
+    fn step_speed_ramp(&mut self, elapsed: Duration) -> Result<Option<MediaEvent>, String> {
+        let Some(ramp) = self.speed_ramp.as_mut() else {
+            return Ok(None);
+        };
+        let listened = storystream_core::Duration::from_millis(elapsed.as_millis() as u64);
+        if !ramp.advance(listened) {
+            return Ok(None);
+        }
+
+        let speed = Speed::new(ramp.speed())?;
+        self.set_speed(speed)?;
+        Ok(Some(MediaEvent::SpeedChanged {
+            speed: speed.value(),
+            ramping: true,
+        }))
+    }
+
+    /// Reports that playback ran to the end of the last queued file
+    /// - NEVER PANICS
+    ///
+    /// Returns `MediaEvent::PlaybackEnded` once each time the end is reached;
+    /// seeking back or loading another file arms it again.
+    pub fn take_playback_ended(&mut self) -> Option<MediaEvent> {
+        let state = self.get_playback_state();
+        let last = self
+            .queue
+            .lock()
+            .map(|queue| queue.is_last())
+            .unwrap_or(true);
+        let at_end = state.status == PlaybackStatus::Stopped
+            && last
+            && state
+                .duration
+                .or(self.duration)
+                .is_some_and(|d| d > Duration::ZERO && state.position >= d);
+        if !at_end {
+            self.end_reported = false;
+            return None;
+        }
+        if std::mem::replace(&mut self.end_reported, true) {
+            None
+        } else {
+            Some(MediaEvent::PlaybackEnded)
+        }
+    }
+
+    /// Sets whether playback resumes by itself once an interrupted output
+    /// device is back - NEVER PANICS
+    /// On by default; when off, playback stays paused
+    pub fn set_auto_resume(&mut self, enabled: bool) {
+        self.interruption
+            .auto_resume
+            .store(enabled, Ordering::Relaxed);
+    }
+
+    /// Returns whether playback is held because the output device is gone
+    /// - NEVER PANICS
+    pub fn is_interrupted(&self) -> bool {
+        self.interruption.active.load(Ordering::Relaxed)
+    }
+
+    /// Makes `play()` refuse with `reason` until cleared with `None` - NEVER PANICS
+    ///
+    /// Playback already running is left alone; pause it to stop listening.
+    pub fn set_play_lock(&mut self, reason: Option<String>) {
+        self.play_lock = reason;
+    }
+
+    /// Returns why `play()` is refused, if it is - NEVER PANICS
+    pub fn play_lock(&self) -> Option<&str> {
+        self.play_lock.as_deref()
+    }
+
+    /// Reports output interruptions - NEVER PANICS
+    ///
+    /// Returns `MediaEvent::Interrupted` once when the output device stops
+    /// taking audio, and `MediaEvent::InterruptionEnded` once when it is back.
+    pub fn take_interruption(&mut self) -> Option<MediaEvent> {
+        match (self.is_interrupted(), self.interruption_reported) {
+            (true, false) => {
+                self.interruption_reported = true;
+                Some(MediaEvent::Interrupted)
+            }
+            (false, true) => {
+                self.interruption_reported = false;
+                Some(MediaEvent::InterruptionEnded {
+                    resumed: self.is_playing(),
+                })
+            }
+            _ => None,
+        }
+    }
+
     /// Sets the equalizer
     /// Returns Err with actionable message on failure - NEVER PANICS
     pub fn set_equalizer(&mut self, equalizer: Equalizer) -> Result<(), String> {
+        match self.playback_equalizer.lock() {
+            Ok(mut playback) => {
+                for (band, gain) in equalizer.gains().into_iter().enumerate() {
+                    playback.set_band_gain(band, gain);
+                }
+                playback.set_enabled(equalizer.is_enabled());
+            }
+            Err(e) => return Err(format!("Failed to set equalizer: mutex poisoned - {}", e)),
+        }
+
         match self.equalizer.lock() {
             Ok(mut eq) => {
                 *eq = equalizer;
@@ -333,9 +805,68 @@ impl MediaEngine {
         }
     }
 
+    /// Sets the presets books can use and the name of the default one
+    pub fn set_equalizer_presets(&mut self, presets: Vec<EqualizerPreset>, default: &str) {
+        self.equalizer_presets = presets;
+        self.default_equalizer = default.to_string();
+    }
+
+    /// Presets books can use
+    pub fn equalizer_presets(&self) -> &[EqualizerPreset] {
+        &self.equalizer_presets
+    }
+
+    /// The equalizer preset currently applied
+    pub fn equalizer_preset(&self) -> &EqualizerPreset {
+        &self.active_equalizer
+    }
+
+    /// Applies an equalizer preset's band gains
+    /// Returns Err with actionable message on failure - NEVER PANICS
+    pub fn apply_equalizer_preset(&mut self, preset: EqualizerPreset) -> Result<(), String> {
+        let mut equalizer = Equalizer::default();
+        let gains = preset.gains();
+        for (band, gain) in gains.into_iter().enumerate() {
+            equalizer.set_band_gain(band, gain);
+        }
+        equalizer.set_enabled(gains.iter().any(|gain| *gain != 0.0));
+
+        self.set_equalizer(equalizer)?;
+        self.active_equalizer = preset;
+        Ok(())
+    }
+
+    /// Switches to the preset after the current one, wrapping around
+    /// Returns Err with actionable message on failure - NEVER PANICS
+    pub fn next_equalizer_preset(&mut self) -> Result<&EqualizerPreset, String> {
+        let next = self
+            .equalizer_presets
+            .iter()
+            .position(|preset| preset.name == self.active_equalizer.name)
+            .map_or(0, |index| (index + 1) % self.equalizer_presets.len());
+        let preset = self
+            .equalizer_presets
+            .get(next)
+            .cloned()
+            .unwrap_or_else(EqualizerPreset::flat);
+
+        self.apply_equalizer_preset(preset)?;
+        Ok(&self.active_equalizer)
+    }
+
     /// Returns the current playback position - NEVER PANICS
+    /// Within the file playing, or across the queue when positions are
+    /// aggregated; see [`MediaEngine::set_aggregate_position`]
     /// Returns Duration::ZERO if position cannot be retrieved
     pub fn position(&self) -> Duration {
+        match self.queue.lock() {
+            Ok(queue) if queue.is_aggregate() => queue.queue_position(self.track_position()),
+            _ => self.track_position(),
+        }
+    }
+
+    /// Returns the position within the file playing - NEVER PANICS
+    pub fn track_position(&self) -> Duration {
         self.current_position
             .lock()
             .map(|pos| *pos)
@@ -363,10 +894,75 @@ impl MediaEngine {
     /// Returns the current volume - NEVER PANICS
     /// Returns 1.0 if volume cannot be retrieved
     pub fn volume(&self) -> f32 {
-        self.volume
+        self.volume.lock().map(|vol| *vol).unwrap_or(1.0)
+    }
+
+    /// Returns the output device playback was last started on - NEVER PANICS
+    /// Without a device chosen, every load reopens the system's default
+    /// device, so this changes when another device, such as a Bluetooth
+    /// speaker, has taken over
+    pub fn output_device(&self) -> Option<AudioDeviceInfo> {
+        self.output_device
+            .lock()
+            .map(|device| device.clone())
+            .unwrap_or(None)
+    }
+
+    /// Lists the devices audio can be played on, the default first
+    /// Returns Err with actionable message on failure - NEVER PANICS
+    pub fn list_output_devices(&self) -> Result<Vec<AudioDeviceInfo>, String> {
+        self.output_target
+            .devices()
+            .map_err(|e| format!("Cannot list output devices: {}", e))
+    }
+
+    /// Plays on the device with `device_id`, or on the system default
+    /// when `None`
+    ///
+    /// A running playback moves to the device where it is, playing or
+    /// paused as it was. The choice holds for later loads until the device
+    /// goes away, when playback pauses and falls back to the default.
+    /// Returns Err with actionable message on failure - NEVER PANICS
+    pub fn set_output_device(&mut self, device_id: Option<&str>) -> Result<(), String> {
+        let device = match device_id {
+            Some(id) => Some(
+                self.list_output_devices()?
+                    .into_iter()
+                    .find(|device| device.id == id)
+                    .ok_or_else(|| format!("Output device not found: {}", id))?,
+            ),
+            None => None,
+        };
+        match self.selected_device.lock() {
+            Ok(mut selected) => *selected = device.clone(),
+            Err(e) => return Err(format!("Cannot select device: mutex poisoned - {}", e)),
+        }
+
+        // The next load opens the device if nothing is playing
+        if let Ok(guard) = self.command_tx.lock() {
+            if let Some(tx) = guard.as_ref() {
+                tx.send(PlaybackCommand::SetOutputDevice(device))
+                    .map_err(|e| format!("Failed to send device command: {}", e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the device chosen to play on, `None` for the system
+    /// default - NEVER PANICS
+    pub fn selected_output_device(&self) -> Option<AudioDeviceInfo> {
+        self.selected_device
             .lock()
-            .map(|vol| *vol)
-            .unwrap_or(1.0)
+            .map(|device| device.clone())
+            .unwrap_or(None)
+    }
+
+    /// Reports playback losing its output device - NEVER PANICS
+    ///
+    /// Returns the error once, after playback paused and moved to the
+    /// system default.
+    pub fn take_device_error(&mut self) -> Option<PlaybackDeviceError> {
+        self.playback_state.lock().ok()?.device_error.take()
     }
 
     /// Returns the current playback state - NEVER PANICS
@@ -379,9 +975,22 @@ impl MediaEngine {
     }
 
     /// Loads chapters from metadata or file - NEVER PANICS
-    pub fn load_chapters(&mut self, _chapters: Vec<(String, Duration, Duration)>) {
-        // Placeholder until chapter integration is complete
-        // This method cannot fail, so it never returns an error
+    /// Replaces any chapters already loaded, so it also applies edited chapters
+    pub fn load_chapters(&mut self, chapters: Vec<(String, Duration, Duration)>) {
+        let markers = chapters
+            .into_iter()
+            .enumerate()
+            .map(|(index, (title, start, end))| {
+                ChapterMarker::new(index, title, start.as_secs_f64(), end.as_secs_f64())
+            })
+            .collect();
+        let mut list = ChapterList::with_chapters(markers);
+        list.update_position(self.position().as_secs_f64());
+
+        // A poisoned lock keeps the old chapters; this method cannot fail
+        if let Ok(mut current) = self.chapters.lock() {
+            *current = list;
+        }
     }
 
     /// Returns the list of chapters - NEVER PANICS
@@ -393,24 +1002,187 @@ impl MediaEngine {
             .unwrap_or_else(|_| ChapterList::new())
     }
 
+    /// Snaps a seek target to the nearest chapter start or bookmark within
+    /// `window` - NEVER PANICS
+    /// The target is returned unsnapped if the chapters cannot be read
+    pub fn snap_position(
+        &self,
+        target: Duration,
+        window: Duration,
+        bookmarks: &BookmarkManager,
+    ) -> SnappedPosition {
+        match self.chapters.lock() {
+            Ok(chapters) => snap::snap_position(target, window, &chapters, bookmarks),
+            Err(_) => SnappedPosition::unsnapped(target),
+        }
+    }
+
     /// Returns the current chapter based on playback position - NEVER PANICS
     pub fn current_chapter(&self) -> Option<usize> {
-        None
+        let position = self.position().as_secs_f64();
+        self.chapters.lock().ok()?.index_at(position)
     }
 
-    /// Jumps to the next chapter - NEVER PANICS
+    /// Jumps to the start of the next chapter
+    /// Returns Err with actionable message on failure - NEVER PANICS
     pub fn next_chapter(&mut self) -> Result<(), String> {
-        Err("Chapter navigation not yet implemented".to_string())
+        let position = self.position().as_secs_f64();
+        let (index, start) = {
+            let chapters = self
+                .chapters
+                .lock()
+                .map_err(|e| format!("Cannot change chapter: chapters poisoned - {}", e))?;
+            if !chapters.has_chapters() {
+                return Err("Cannot change chapter: this book has no chapters".to_string());
+            }
+            let next = chapters.index_at(position).map_or(0, |index| index + 1);
+            match chapters.get_chapter(next) {
+                Some(chapter) => (next, chapter.start_time),
+                None => return Err("Already in the last chapter".to_string()),
+            }
+        };
+        self.seek_to_chapter(index, start)
     }
 
-    /// Jumps to the previous chapter - NEVER PANICS
+    /// Restarts the current chapter, or jumps to the previous one when
+    /// within the first few seconds of it
+    /// Returns Err with actionable message on failure - NEVER PANICS
     pub fn previous_chapter(&mut self) -> Result<(), String> {
-        Err("Chapter navigation not yet implemented".to_string())
+        let position = self.position().as_secs_f64();
+        let (index, start) = {
+            let chapters = self
+                .chapters
+                .lock()
+                .map_err(|e| format!("Cannot change chapter: chapters poisoned - {}", e))?;
+            if !chapters.has_chapters() {
+                return Err("Cannot change chapter: this book has no chapters".to_string());
+            }
+            match chapters.index_at(position) {
+                // Before the first chapter there is nothing to go back to
+                None => (0, 0.0),
+                Some(index) => {
+                    let current = chapters.get_chapter(index).map_or(0.0, |ch| ch.start_time);
+                    if index == 0 || position - current > CHAPTER_RESTART_SECS {
+                        (index, current)
+                    } else {
+                        let previous = chapters
+                            .get_chapter(index - 1)
+                            .map_or(0.0, |ch| ch.start_time);
+                        (index - 1, previous)
+                    }
+                }
+            }
+        };
+        self.seek_to_chapter(index, start)
     }
 
-    /// Returns progress through the current chapter as a percentage - NEVER PANICS
+    /// Returns how much of the current chapter has played, from 0.0 to 1.0
+    /// - NEVER PANICS
     pub fn chapter_progress(&self) -> Option<f32> {
-        None
+        let position = self.position().as_secs_f64();
+        self.chapters.lock().ok()?.progress_at(position)
+    }
+
+    /// Arms a sleep timer, replacing any armed one
+    ///
+    /// Fixed timers count down from now; end-of-chapter timers wait for the
+    /// chapter playing now to end. Either way playback fades out and pauses.
+    /// Returns Err with actionable message on failure - NEVER PANICS
+    pub fn set_sleep_timer(&mut self, timer: SleepTimer) -> Result<(), String> {
+        let deadline = if timer.is_end_of_chapter() {
+            let position = self.position().as_secs_f64();
+            let chapters = self
+                .chapters
+                .lock()
+                .map_err(|e| format!("Cannot set sleep timer: chapters poisoned - {}", e))?;
+            let end = chapters
+                .index_at(position)
+                .and_then(|index| chapters.get_chapter(index))
+                .map(|chapter| chapter.end_time)
+                .ok_or_else(|| {
+                    "Cannot sleep at the end of the chapter: no chapter is playing".to_string()
+                })?;
+            SleepDeadline::Position(Duration::from_secs_f64(end.max(0.0)))
+        } else {
+            SleepDeadline::At(Instant::now() + Duration::from_millis(timer.duration.as_millis()))
+        };
+
+        let mut armed = self
+            .sleep_timer
+            .lock()
+            .map_err(|e| format!("Cannot set sleep timer: mutex poisoned - {}", e))?;
+        *armed = Some(ArmedSleepTimer::new(timer, deadline));
+        Ok(())
+    }
+
+    /// Gives the armed sleep timer `by` more time
+    /// Returns Err with actionable message on failure - NEVER PANICS
+    pub fn extend_sleep_timer(&mut self, by: Duration) -> Result<(), String> {
+        let position = self.position();
+        let speed = self.speed_value();
+        let mut armed = self
+            .sleep_timer
+            .lock()
+            .map_err(|e| format!("Cannot extend sleep timer: mutex poisoned - {}", e))?;
+        match armed.as_mut().filter(|timer| !timer.is_expired()) {
+            Some(timer) => {
+                timer.extend(by, Instant::now(), position, speed);
+                Ok(())
+            }
+            None => Err("Cannot extend sleep timer: no timer is set".to_string()),
+        }
+    }
+
+    /// Disarms the sleep timer, leaving playback as it is - NEVER PANICS
+    pub fn cancel_sleep_timer(&mut self) {
+        if let Ok(mut armed) = self.sleep_timer.lock() {
+            *armed = None;
+        }
+    }
+
+    /// Returns the armed sleep timer, expired ones included - NEVER PANICS
+    pub fn sleep_timer(&self) -> Option<SleepTimer> {
+        self.sleep_timer.lock().ok()?.map(|armed| armed.timer)
+    }
+
+    /// Returns the time left before the sleep timer pauses playback - NEVER PANICS
+    /// Returns None when no timer is armed or it has already run out
+    pub fn sleep_timer_remaining(&self) -> Option<Duration> {
+        let position = self.position();
+        let speed = self.speed_value();
+        let armed = (*self.sleep_timer.lock().ok()?)?;
+        if armed.is_expired() {
+            return None;
+        }
+        Some(armed.remaining(Instant::now(), position, speed))
+    }
+
+    /// Disarms the sleep timer if it has paused playback - NEVER PANICS
+    ///
+    /// Returns true once for each timer that ran out, so callers can say so
+    /// or bookmark where listening stopped.
+    pub fn take_sleep_timer_expired(&mut self) -> bool {
+        match self.sleep_timer.lock() {
+            Ok(mut armed) if armed.is_some_and(|timer| timer.is_expired()) => {
+                *armed = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Current speed multiplier, 1.0 if it cannot be read
+    fn speed_value(&self) -> f32 {
+        self.speed.lock().map(|speed| speed.value()).unwrap_or(1.0)
+    }
+
+    /// Seeks to a chapter's start and makes it the current chapter
+    fn seek_to_chapter(&mut self, index: usize, start: f64) -> Result<(), String> {
+        self.seek(Duration::from_secs_f64(start.max(0.0)))?;
+        if let Ok(mut chapters) = self.chapters.lock() {
+            chapters.go_to_chapter(index);
+        }
+        Ok(())
     }
 
     /// Internal method to start the playback thread
@@ -426,7 +1198,12 @@ impl MediaEngine {
         let (tx, rx) = channel();
         match self.command_tx.lock() {
             Ok(mut guard) => *guard = Some(tx),
-            Err(e) => return Err(format!("Cannot start playback: command channel poisoned - {}", e)),
+            Err(e) => {
+                return Err(format!(
+                    "Cannot start playback: command channel poisoned - {}",
+                    e
+                ))
+            }
         }
 
         // Get file path safely
@@ -439,18 +1216,26 @@ impl MediaEngine {
         let playback_decoder = PlaybackAudioDecoder::new(path)
             .map_err(|e| format!("Failed to create playback decoder: {:?}", e))?;
 
-        let playback_equalizer = Arc::new(Mutex::new(PlaybackEqualizer::default()));
-
+        let shared = PlaybackShared {
+            current_position: Arc::clone(&self.current_position),
+            current_status: Arc::clone(&self.current_status),
+            playback_state: Arc::clone(&self.playback_state),
+            volume: Arc::clone(&self.volume),
+            replay_gain: Arc::clone(&self.replay_gain),
+            output_device: Arc::clone(&self.output_device),
+            selected_device: Arc::clone(&self.selected_device),
+            speed: Arc::clone(&self.speed),
+            equalizer: Arc::clone(&self.playback_equalizer),
+            interruption: Arc::clone(&self.interruption),
+            sleep_timer: Arc::clone(&self.sleep_timer),
+            queue: Arc::clone(&self.queue),
+        };
         let handle = playback_thread::start_playback_thread(
             playback_decoder,
             rx,
             duration,
-            self.current_position.clone(),
-            self.current_status.clone(),
-            self.playback_state.clone(),
-            self.volume.clone(),
-            self.speed.clone(),
-            playback_equalizer,
+            shared,
+            self.output_target,
         );
 
         self.thread_handle = Some(handle);
@@ -472,6 +1257,19 @@ impl Drop for MediaEngine {
     }
 }
 
+impl fmt::Debug for MediaEngine {
+    /// Shows what is loaded without locking the shared playback state
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MediaEngine")
+            .field("config", &self.config)
+            .field("loaded_file", &self.loaded_file)
+            .field("duration", &self.duration)
+            .field("output_target", &self.output_target)
+            .field("play_lock", &self.play_lock)
+            .finish_non_exhaustive()
+    }
+}
+
 // Helper function to handle PoisonError gracefully - NEVER PANICS
 fn _handle_poison_error<T>(err: PoisonError<T>) -> String {
     format!("Mutex poisoned: {}", err)
@@ -490,7 +1288,7 @@ mod engine_tests {
     #[test]
     fn test_invalid_config_never_panics() {
         let config = EngineConfig {
-            sample_rate: 0,  // Invalid!
+            sample_rate: 0, // Invalid!
             channels: 2,
             buffer_size: 4096,
         };
@@ -511,13 +1309,29 @@ mod engine_tests {
         }
     }
 
+    #[test]
+    fn test_replay_gain_leaves_volume() {
+        if let Ok(mut engine) = MediaEngine::with_defaults() {
+            engine.set_volume(0.6).unwrap();
+            engine.set_replay_gain(Some(-6.0)).unwrap();
+            assert_eq!(engine.replay_gain(), Some(-6.0));
+            assert_eq!(engine.volume(), 0.6);
+            assert!((*engine.replay_gain.lock().unwrap() - 0.501).abs() < 0.001);
+
+            assert!(engine.set_replay_gain(Some(f32::NAN)).is_err());
+            assert_eq!(engine.replay_gain(), Some(-6.0));
+            engine.set_replay_gain(None).unwrap();
+            assert_eq!(*engine.replay_gain.lock().unwrap(), 1.0);
+        }
+    }
+
     #[test]
     fn test_operations_without_file_never_panic() {
         if let Ok(mut engine) = MediaEngine::with_defaults() {
             assert!(engine.play().is_err());
             assert!(engine.pause().is_err());
             assert!(engine.seek(Duration::from_secs(10)).is_err());
-            assert!(engine.stop().is_ok());  // Stop always succeeds
+            assert!(engine.stop().is_ok()); // Stop always succeeds
         }
     }
 
@@ -533,6 +1347,7 @@ mod engine_tests {
     #[test]
     fn test_seek_beyond_duration_never_panics() {
         if let Ok(mut engine) = MediaEngine::with_defaults() {
+            engine.loaded_file = Some("book.mp3".to_string());
             engine.duration = Some(Duration::from_secs(100));
             let result = engine.seek(Duration::from_secs(200));
             assert!(result.is_err());
@@ -554,6 +1369,265 @@ mod engine_tests {
         }
     }
 
+    #[test]
+    fn test_chapter_navigation_without_chapters() {
+        if let Ok(mut engine) = MediaEngine::with_defaults() {
+            assert_eq!(engine.current_chapter(), None);
+            assert_eq!(engine.chapter_progress(), None);
+            assert!(engine.next_chapter().unwrap_err().contains("no chapters"));
+            assert!(engine
+                .previous_chapter()
+                .unwrap_err()
+                .contains("no chapters"));
+        }
+    }
+
+    #[test]
+    fn test_current_chapter_follows_position() {
+        if let Ok(mut engine) = MediaEngine::with_defaults() {
+            engine.load_chapters(vec![
+                (
+                    "Part 1".to_string(),
+                    Duration::from_secs(0),
+                    Duration::from_secs(100),
+                ),
+                (
+                    "Part 2".to_string(),
+                    Duration::from_secs(100),
+                    Duration::from_secs(200),
+                ),
+            ]);
+            assert_eq!(engine.current_chapter(), Some(0));
+
+            if let Ok(mut pos) = engine.current_position.lock() {
+                *pos = Duration::from_secs(150);
+            }
+            assert_eq!(engine.current_chapter(), Some(1));
+            assert_eq!(engine.chapter_progress(), Some(0.5));
+            assert!(engine.next_chapter().unwrap_err().contains("last chapter"));
+        }
+    }
+
+    #[test]
+    fn test_sleep_timer_arm_extend_cancel() {
+        if let Ok(mut engine) = MediaEngine::with_defaults() {
+            assert_eq!(engine.sleep_timer_remaining(), None);
+            assert!(engine
+                .set_sleep_timer(SleepTimer::end_of_chapter())
+                .is_err());
+            assert!(engine.extend_sleep_timer(Duration::from_secs(60)).is_err());
+
+            let timer = SleepTimer::new(Duration::from_secs(15 * 60).into());
+            assert!(engine.set_sleep_timer(timer).is_ok());
+            let remaining = engine.sleep_timer_remaining().unwrap_or_default();
+            assert!(remaining > Duration::from_secs(14 * 60));
+
+            assert!(engine
+                .extend_sleep_timer(Duration::from_secs(15 * 60))
+                .is_ok());
+            let remaining = engine.sleep_timer_remaining().unwrap_or_default();
+            assert!(remaining > Duration::from_secs(29 * 60));
+            assert!(!engine.take_sleep_timer_expired());
+
+            engine.cancel_sleep_timer();
+            assert!(engine.sleep_timer().is_none());
+        }
+    }
+
+    #[test]
+    fn test_load_chapters_replaces_list() {
+        if let Ok(mut engine) = MediaEngine::with_defaults() {
+            engine.load_chapters(vec![(
+                "Whole".to_string(),
+                Duration::from_secs(0),
+                Duration::from_secs(200),
+            )]);
+            engine.load_chapters(vec![
+                (
+                    "Part 1".to_string(),
+                    Duration::from_secs(0),
+                    Duration::from_secs(90),
+                ),
+                (
+                    "Part 2".to_string(),
+                    Duration::from_secs(90),
+                    Duration::from_secs(200),
+                ),
+            ]);
+
+            let chapters = engine.chapters();
+            assert_eq!(chapters.chapter_count(), 2);
+            assert_eq!(chapters.chapters()[1].title, "Part 2");
+            assert_eq!(chapters.chapters()[1].start_time, 90.0);
+        }
+    }
+
+    #[test]
+    fn test_equalizer_presets_never_panic() {
+        if let Ok(mut engine) = MediaEngine::with_defaults() {
+            let old_radio = EqualizerPreset::from_gains("Old Radio", [2.0; 10]);
+            let mut presets = EqualizerPreset::builtin();
+            presets.push(old_radio.clone());
+            engine.set_equalizer_presets(presets, "Voice Boost");
+
+            assert_eq!(engine.book_equalizer(None), EqualizerPreset::voice_boost());
+            assert_eq!(engine.book_equalizer(Some(&old_radio)), old_radio);
+
+            // A book whose preset was deleted plays flat
+            let deleted = EqualizerPreset::from_gains("Deleted", [5.0; 10]);
+            assert_eq!(
+                engine.book_equalizer(Some(&deleted)),
+                EqualizerPreset::flat()
+            );
+
+            assert!(engine.apply_equalizer_preset(old_radio).is_ok());
+            let next = engine
+                .next_equalizer_preset()
+                .map(|preset| preset.name.clone());
+            assert_eq!(next, Ok("Flat".to_string()));
+            assert_eq!(engine.equalizer_preset().name, "Flat");
+
+            engine.set_equalizer_presets(Vec::new(), "Flat");
+            assert!(engine.next_equalizer_preset().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_speed_ramp_never_panics() {
+        if let Ok(mut engine) = MediaEngine::with_defaults() {
+            let (Ok(fixed), Ok(start), Ok(target)) =
+                (Speed::new(1.1), Speed::new(1.25), Speed::new(1.75))
+            else {
+                return;
+            };
+            assert!(engine.set_speed(fixed).is_ok());
+            assert!(engine.set_speed_ramp(start, target, 0.0).is_err());
+            assert!(engine.set_speed_ramp(start, target, 0.6).is_ok());
+            assert_eq!(engine.speed.lock().ok().map(|s| s.value()), Some(1.25));
+
+            // Not playing, so nothing is counted
+            assert!(matches!(engine.advance_speed_ramp(), Ok(None)));
+
+            assert!(matches!(
+                engine.step_speed_ramp(Duration::from_secs(30)),
+                Ok(None)
+            ));
+            let event = engine.step_speed_ramp(Duration::from_secs(30));
+            assert!(matches!(
+                event,
+                Ok(Some(MediaEvent::SpeedChanged { ramping: true, .. }))
+            ));
+            assert_eq!(engine.speed.lock().ok().map(|s| s.value()), Some(1.26));
+
+            let ramp = engine.clear_speed_ramp();
+            assert!(matches!(ramp, Ok(Some(_))));
+            assert_eq!(engine.speed.lock().ok().map(|s| s.value()), Some(1.1));
+            assert!(engine.speed_ramp().is_none());
+        }
+    }
+
+    #[test]
+    fn test_playback_ended_reported_once() {
+        if let Ok(mut engine) = MediaEngine::with_defaults() {
+            assert!(engine.take_playback_ended().is_none());
+
+            let duration = Duration::from_secs(10);
+            engine.duration = Some(duration);
+            let at = |position| PlaybackState {
+                status: PlaybackStatus::Stopped,
+                position,
+                duration: Some(duration),
+                device_error: None,
+            };
+            if let Ok(mut state) = engine.playback_state.lock() {
+                *state = at(duration);
+            }
+            assert!(matches!(
+                engine.take_playback_ended(),
+                Some(MediaEvent::PlaybackEnded)
+            ));
+            assert!(engine.take_playback_ended().is_none());
+
+            // Seeking back and playing to the end again reports it again
+            if let Ok(mut state) = engine.playback_state.lock() {
+                *state = at(Duration::from_secs(5));
+            }
+            assert!(engine.take_playback_ended().is_none());
+            if let Ok(mut state) = engine.playback_state.lock() {
+                *state = at(duration);
+            }
+            assert!(engine.take_playback_ended().is_some());
+        }
+    }
+
+    #[test]
+    fn test_interruption_reported_once_each_way() {
+        if let Ok(mut engine) = MediaEngine::with_defaults() {
+            assert!(engine.take_interruption().is_none());
+
+            engine.interruption.active.store(true, Ordering::Relaxed);
+            assert!(engine.is_interrupted());
+            assert!(matches!(
+                engine.take_interruption(),
+                Some(MediaEvent::Interrupted)
+            ));
+            assert!(engine.take_interruption().is_none());
+
+            engine.interruption.active.store(false, Ordering::Relaxed);
+            assert!(matches!(
+                engine.take_interruption(),
+                Some(MediaEvent::InterruptionEnded { resumed: false })
+            ));
+            assert!(engine.take_interruption().is_none());
+
+            engine.set_auto_resume(false);
+            assert!(!engine.interruption.auto_resume.load(Ordering::Relaxed));
+        }
+    }
+
+    #[test]
+    fn test_play_lock_refuses_play() {
+        if let Ok(mut engine) = MediaEngine::with_defaults() {
+            engine.set_play_lock(Some("Today's listening time is used up".to_string()));
+            let result = engine.play();
+            assert!(result.is_err());
+            assert!(result.unwrap_err().starts_with("Playback locked"));
+
+            engine.set_play_lock(None);
+            assert!(engine.play_lock().is_none());
+            assert!(engine.play().unwrap_err().contains("no file loaded"));
+        }
+    }
+
+    #[test]
+    fn test_queue_without_file() {
+        if let Ok(mut engine) = MediaEngine::with_defaults() {
+            assert!(engine.queue().is_empty());
+            assert!(engine.enqueue("").is_err());
+            assert!(engine.enqueue("/nonexistent/part 1.mp3").is_err());
+            assert!(engine.skip_to(0).unwrap_err().contains("no file loaded"));
+            assert!(engine.take_track_changed().is_none());
+            assert_eq!(engine.queue_duration(), Duration::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_device_error_reported_once() {
+        if let Ok(mut engine) = MediaEngine::with_defaults() {
+            assert!(engine.take_device_error().is_none());
+
+            let error = PlaybackDeviceError {
+                lost: "Headphones".to_string(),
+                fallback: "Speakers".to_string(),
+            };
+            if let Ok(mut state) = engine.playback_state.lock() {
+                state.device_error = Some(error.clone());
+            }
+            assert_eq!(engine.take_device_error(), Some(error));
+            assert!(engine.take_device_error().is_none());
+        }
+    }
+
     #[test]
     fn test_multiple_stops_never_panic() {
         if let Ok(mut engine) = MediaEngine::with_defaults() {
@@ -562,4 +1636,4 @@ mod engine_tests {
             assert!(engine.stop().is_ok());
         }
     }
-}
\ No newline at end of file
+}
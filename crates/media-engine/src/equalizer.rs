@@ -107,6 +107,11 @@ impl Equalizer {
     pub fn reset(&mut self) {
         self.bands = Self::default_bands();
     }
+
+    /// Band gains in dB, lowest band first
+    pub fn gains(&self) -> Vec<f32> {
+        self.bands.iter().map(|band| band.gain).collect()
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
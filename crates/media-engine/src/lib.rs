@@ -11,6 +11,15 @@
 //! - Gapless playback
 //! - Audio device selection
 //! - Bookmark management
+//!
+//! # Memory
+//!
+//! Decoding memory does not grow with the length of a file. The decoder keeps
+//! one interleaving buffer sized by the largest packet seen so far, whatever
+//! part of a packet did not fit in the previous chunk, and the chunk it hands
+//! back, which holds at most [`decoder::MAX_CHUNK_SAMPLES`] samples. Together
+//! with the demuxer's read buffer this stays under 4 MiB even for books that
+//! run for days; `tests/decode_memory_tests.rs` holds the decoder to that.
 
 pub mod audio_device;
 pub mod bookmarks;
@@ -22,6 +31,9 @@ pub mod error;
 pub mod output;
 pub mod playback;
 pub mod playback_thread;
+pub mod queue;
+pub mod sleep;
+pub mod snap;
 pub mod speed;
 pub mod state;
 mod types;
@@ -34,9 +46,15 @@ pub use decoder::AudioDecoder;
 pub use engine::{EngineConfig, MediaEngine};
 pub use equalizer::{Equalizer, EqualizerBand, EqualizerPreset};
 pub use error::{EngineError, EngineResult};
-pub use output::{AudioOutput, AudioOutputConfig};
-pub use playback::{PlaybackState, PlaybackStatus};
+#[cfg(feature = "null-output")]
+pub use output::NullOutput;
+pub use output::{AudioOutput, AudioOutputConfig, AudioSink, OutputTarget};
+pub use playback::{PlaybackDeviceError, PlaybackState, PlaybackStatus};
+pub use queue::{PlaybackQueue, QueuedTrack};
+pub use sleep::{ArmedSleepTimer, SleepDeadline};
+pub use snap::{SnapPoint, SnappedPosition};
 pub use speed::{Speed, SpeedProcessor};
+pub use types::MediaEvent;
 
 #[cfg(test)]
 mod tests {
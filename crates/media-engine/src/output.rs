@@ -9,6 +9,70 @@ use crossbeam_channel::{Receiver, TryRecvError};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
+/// Where the playback thread sends its samples
+pub trait AudioSink {
+    /// The device samples go to
+    fn device_info(&self) -> &AudioDeviceInfo;
+
+    /// Starts taking interleaved samples from `rx`, clearing `running` when
+    /// the sender goes away
+    fn play(&mut self, rx: Receiver<Vec<f32>>, running: Arc<AtomicBool>) -> EngineResult<()>;
+
+    /// Whether the device stopped taking audio since playback started
+    fn is_interrupted(&self) -> bool;
+
+    /// Starts taking samples again after an interruption
+    fn reopen(&mut self) -> EngineResult<()>;
+
+    /// Whether the device is still connected
+    fn is_device_available(&self) -> bool;
+
+    /// Stops taking samples
+    fn stop(&mut self);
+}
+
+/// Where the engine plays its audio
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputTarget {
+    /// The selected sound device
+    #[default]
+    Device,
+    /// No device at all; see [`NullOutput`]
+    #[cfg(feature = "null-output")]
+    Null,
+}
+
+impl OutputTarget {
+    /// Opens a sink for audio in this format on `device_id`, or on the
+    /// system default device when `None`
+    pub fn open(
+        self,
+        device_id: Option<&str>,
+        sample_rate: u32,
+        channels: u16,
+    ) -> EngineResult<Box<dyn AudioSink>> {
+        match self {
+            Self::Device => Ok(Box::new(AudioOutput::with_config(AudioOutputConfig {
+                sample_rate,
+                channels,
+                device_id: device_id.map(str::to_string),
+                ..Default::default()
+            })?)),
+            #[cfg(feature = "null-output")]
+            Self::Null => Ok(Box::new(NullOutput::new(sample_rate, channels))),
+        }
+    }
+
+    /// Lists the devices audio can be played on, the default first
+    pub fn devices(self) -> EngineResult<Vec<AudioDeviceInfo>> {
+        match self {
+            Self::Device => Ok(AudioDeviceManager::new()?.list_devices()),
+            #[cfg(feature = "null-output")]
+            Self::Null => Ok(vec![NullOutput::new(48000, 2).device_info.clone()]),
+        }
+    }
+}
+
 /// Audio output configuration
 #[derive(Debug, Clone)]
 pub struct AudioOutputConfig {
@@ -37,6 +101,10 @@ pub struct AudioOutput {
     stream: Option<Stream>,
     sample_rate: u32,
     manager: AudioDeviceManager,
+    /// Samples and run flag of the current stream, kept to rebuild it
+    source: Option<(Receiver<Vec<f32>>, Arc<AtomicBool>)>,
+    /// Set by the stream's error callback when the device stops taking audio
+    interrupted: Arc<AtomicBool>,
 }
 
 impl AudioOutput {
@@ -72,7 +140,7 @@ impl AudioOutput {
         if !device_info.sample_rates.is_empty()
             && !device_info.sample_rates.contains(&config.sample_rate)
         {
-            log::warn!(
+            tracing::warn!(
                 "Sample rate {} may not be optimal for device {}",
                 config.sample_rate,
                 device_info.name
@@ -95,6 +163,8 @@ impl AudioOutput {
             stream: None,
             sample_rate: config.sample_rate,
             manager,
+            source: None,
+            interrupted: Arc::new(AtomicBool::new(false)),
         })
     }
 
@@ -132,10 +202,14 @@ impl AudioOutput {
             )));
         }
 
+        self.source = Some((rx.clone(), Arc::clone(&running)));
+        self.interrupted.store(false, Ordering::Relaxed);
+
         let mut buffer = Vec::new();
         let mut position = 0;
 
         let device_name = self.device_info.name.clone();
+        let interrupted = Arc::clone(&self.interrupted);
 
         let stream = self
             .device
@@ -170,7 +244,14 @@ impl AudioOutput {
                     }
                 },
                 move |err| {
-                    log::error!("Audio output error on device '{}': {}", device_name, err);
+                    // Unplugged devices and invalidated streams both stop the
+                    // sound; the playback thread pauses and rebuilds the stream
+                    tracing::warn!(
+                        "Audio output interrupted on device '{}': {}",
+                        device_name,
+                        err
+                    );
+                    interrupted.store(true, Ordering::Relaxed);
                 },
                 None,
             )
@@ -181,17 +262,40 @@ impl AudioOutput {
             .map_err(|e| EngineError::OutputError(format!("Failed to start stream: {}", e)))?;
 
         self.stream = Some(stream);
-        log::info!(
+        tracing::info!(
             "Audio playback started on device: {}",
             self.device_info.name
         );
         Ok(())
     }
 
+    /// Whether the device stopped taking audio since playback started
+    ///
+    /// Set when the device is unplugged or the system invalidates the
+    /// stream, such as when another application takes the device over.
+    pub fn is_interrupted(&self) -> bool {
+        self.interrupted.load(Ordering::Relaxed)
+    }
+
+    /// Rebuilds the stream after an interruption
+    ///
+    /// Fails while the device is still missing; audio queued before the
+    /// interruption plays on from where the old stream stopped.
+    pub fn reopen(&mut self) -> EngineResult<()> {
+        let (rx, running) = self
+            .source
+            .clone()
+            .ok_or_else(|| EngineError::OutputError("No stream to reopen".to_string()))?;
+        self.stream = None;
+        self.device = self.manager.get_output_device()?;
+        self.play(rx, running)
+    }
+
     /// Stop playing audio
     pub fn stop(&mut self) {
+        self.source = None;
         if self.stream.take().is_some() {
-            log::info!(
+            tracing::info!(
                 "Audio playback stopped on device: {}",
                 self.device_info.name
             );
@@ -204,8 +308,137 @@ impl AudioOutput {
     }
 }
 
+impl AudioSink for AudioOutput {
+    fn device_info(&self) -> &AudioDeviceInfo {
+        AudioOutput::device_info(self)
+    }
+
+    fn play(&mut self, rx: Receiver<Vec<f32>>, running: Arc<AtomicBool>) -> EngineResult<()> {
+        AudioOutput::play(self, rx, running)
+    }
+
+    fn is_interrupted(&self) -> bool {
+        AudioOutput::is_interrupted(self)
+    }
+
+    fn reopen(&mut self) -> EngineResult<()> {
+        AudioOutput::reopen(self)
+    }
+
+    fn is_device_available(&self) -> bool {
+        AudioOutput::is_device_available(self)
+    }
+
+    fn stop(&mut self) {
+        AudioOutput::stop(self)
+    }
+}
+
 impl Drop for AudioOutput {
     fn drop(&mut self) {
         self.stop();
     }
 }
+
+/// Output that needs no sound device and plays nothing
+///
+/// Samples are taken as fast as a real device would take them and dropped,
+/// so positions advance in real time. Meant for tests and CI machines.
+#[cfg(feature = "null-output")]
+pub struct NullOutput {
+    device_info: AudioDeviceInfo,
+    /// Samples taken per second, over all channels
+    rate: f64,
+    /// Samples and run flag of the current playback, kept to reopen it
+    source: Option<(Receiver<Vec<f32>>, Arc<AtomicBool>)>,
+    /// Tells the worker taking samples to finish
+    stopped: Arc<AtomicBool>,
+    worker: Option<std::thread::JoinHandle<()>>,
+}
+
+#[cfg(feature = "null-output")]
+impl NullOutput {
+    /// Creates an output that takes audio in this format
+    pub fn new(sample_rate: u32, channels: u16) -> Self {
+        Self {
+            device_info: AudioDeviceInfo {
+                id: "null".to_string(),
+                name: "Null output".to_string(),
+                is_default: true,
+                sample_rates: vec![sample_rate],
+                min_channels: channels,
+                max_channels: channels,
+                default_sample_rate: sample_rate,
+                default_channels: channels,
+            },
+            rate: f64::from(sample_rate) * f64::from(channels.max(1)),
+            source: None,
+            stopped: Arc::new(AtomicBool::new(true)),
+            worker: None,
+        }
+    }
+}
+
+#[cfg(feature = "null-output")]
+impl AudioSink for NullOutput {
+    fn device_info(&self) -> &AudioDeviceInfo {
+        &self.device_info
+    }
+
+    fn play(&mut self, rx: Receiver<Vec<f32>>, running: Arc<AtomicBool>) -> EngineResult<()> {
+        use crossbeam_channel::RecvTimeoutError;
+        use std::time::Duration;
+
+        self.stop();
+        self.source = Some((rx.clone(), Arc::clone(&running)));
+        let stopped = Arc::new(AtomicBool::new(false));
+        self.stopped = Arc::clone(&stopped);
+        let rate = self.rate;
+        self.worker = Some(std::thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                match rx.recv_timeout(Duration::from_millis(50)) {
+                    Ok(samples) => {
+                        std::thread::sleep(Duration::from_secs_f64(samples.len() as f64 / rate))
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => {
+                        running.store(false, Ordering::Relaxed);
+                        break;
+                    }
+                }
+            }
+        }));
+        Ok(())
+    }
+
+    fn is_interrupted(&self) -> bool {
+        false
+    }
+
+    fn reopen(&mut self) -> EngineResult<()> {
+        let (rx, running) = self
+            .source
+            .clone()
+            .ok_or_else(|| EngineError::OutputError("No stream to reopen".to_string()))?;
+        self.play(rx, running)
+    }
+
+    fn is_device_available(&self) -> bool {
+        true
+    }
+
+    fn stop(&mut self) {
+        self.source = None;
+        self.stopped.store(true, Ordering::Relaxed);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(feature = "null-output")]
+impl Drop for NullOutput {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
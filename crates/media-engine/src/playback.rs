@@ -1,3 +1,4 @@
+use std::fmt;
 use std::time::Duration;
 
 /// Represents the current playback status
@@ -11,12 +12,33 @@ pub enum PlaybackStatus {
     Stopped,
 }
 
+/// Playback lost its output device and moved to the system default
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlaybackDeviceError {
+    /// Name of the device that went away
+    pub lost: String,
+    /// Name of the device playback moved to
+    pub fallback: String,
+}
+
+impl fmt::Display for PlaybackDeviceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Output device changed: '{}' is gone, paused on '{}'",
+            self.lost, self.fallback
+        )
+    }
+}
+
 /// Tracks the current playback state including position and status
 #[derive(Debug, Clone)]
 pub struct PlaybackState {
     pub status: PlaybackStatus,
     pub position: Duration,
     pub duration: Option<Duration>,
+    /// Set when the output device went away, until the engine reports it
+    pub device_error: Option<PlaybackDeviceError>,
 }
 
 impl PlaybackState {
@@ -26,6 +48,7 @@ impl PlaybackState {
             status: PlaybackStatus::Stopped,
             position: Duration::from_secs(0),
             duration: None,
+            device_error: None,
         }
     }
 
@@ -35,6 +58,7 @@ impl PlaybackState {
             status: PlaybackStatus::Playing,
             position,
             duration,
+            device_error: None,
         }
     }
 
@@ -44,6 +68,7 @@ impl PlaybackState {
             status: PlaybackStatus::Paused,
             position,
             duration,
+            device_error: None,
         }
     }
 
@@ -53,6 +78,7 @@ impl PlaybackState {
             status: PlaybackStatus::Stopped,
             position: Duration::from_secs(0),
             duration: None,
+            device_error: None,
         }
     }
 
@@ -118,6 +144,18 @@ impl Default for PlaybackState {
 mod playback_tests {
     use super::*;
 
+    #[test]
+    fn test_device_error_display() {
+        let error = PlaybackDeviceError {
+            lost: "Headphones".to_string(),
+            fallback: "Speakers".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Output device changed: 'Headphones' is gone, paused on 'Speakers'"
+        );
+    }
+
     #[test]
     fn test_playback_state_new() {
         let state = PlaybackState::new();
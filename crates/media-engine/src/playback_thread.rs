@@ -1,14 +1,18 @@
 // crates/media-engine/src/playback_thread.rs
 
-use crate::output::AudioOutput;
-use crate::playback::{PlaybackState, PlaybackStatus};
+use crate::audio_device::AudioDeviceInfo;
+use crate::output::{AudioSink, OutputTarget};
+use crate::playback::{PlaybackDeviceError, PlaybackState, PlaybackStatus};
+use crate::queue::PlaybackQueue;
+use crate::sleep::ArmedSleepTimer;
 use crate::speed::{Speed, SpeedProcessor};
-use crossbeam_channel::{bounded, Sender};
+use crossbeam_channel::{bounded, Receiver as AudioReceiver, Sender};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::Receiver;
 use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
 use std::time::Instant;
+use storystream_core::types::SleepTimerState;
 
 /// Commands that can be sent to the playback thread
 #[derive(Debug, Clone)]
@@ -17,37 +21,88 @@ pub enum PlaybackCommand {
     Pause,
     Stop,
     Seek(std::time::Duration),
+    /// Plays the queued file at `index` from `position` within it
+    SkipTo {
+        index: usize,
+        position: std::time::Duration,
+    },
     SetVolume(f32),
     SetSpeed(Speed),
+    /// Moves playback to another device, `None` for the system default
+    SetOutputDevice(Option<AudioDeviceInfo>),
+}
+
+/// How often a missing output device is looked for again
+const REOPEN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Level above which samples raised by replay gain are rounded off
+const LIMIT_THRESHOLD: f32 = 0.9;
+
+/// Output interruptions, shared between the engine and the playback thread
+#[derive(Debug)]
+pub struct Interruption {
+    /// Set while the output device is gone and playback is held
+    pub active: AtomicBool,
+    /// Whether playback picks up again once the device is back
+    pub auto_resume: AtomicBool,
+}
+
+impl Default for Interruption {
+    fn default() -> Self {
+        Self {
+            active: AtomicBool::new(false),
+            auto_resume: AtomicBool::new(true),
+        }
+    }
 }
 
 /// Audio processing pipeline state
 struct AudioPipeline {
     decoder: AudioDecoder,
+    sample_rate: u32,
+    channels: u16,
     speed_processor: SpeedProcessor,
     equalizer: Equalizer,
-    output: AudioOutput,
+    target: OutputTarget,
+    output: Box<dyn AudioSink>,
+    /// Device the output plays on, `None` for the system default
+    device_id: Option<String>,
+    /// Linear gain evening out the book's loudness, applied before the volume
+    replay_gain: f32,
     volume: f32,
+    /// Gain a sleep timer's fade-out applies on top of the volume
+    fade: f32,
     is_playing: bool,
     running: Arc<AtomicBool>,
 }
 
 impl AudioPipeline {
-    fn new(decoder: AudioDecoder, sample_rate: u32, channels: u16) -> Result<Self, String> {
+    fn new(
+        decoder: AudioDecoder,
+        sample_rate: u32,
+        channels: u16,
+        target: OutputTarget,
+        output: Box<dyn AudioSink>,
+        device_id: Option<String>,
+    ) -> Self {
         let speed_processor = SpeedProcessor::new(sample_rate, channels);
         let equalizer = Equalizer::default();
-        let output = AudioOutput::new(sample_rate, channels)
-            .map_err(|e| format!("Failed to create audio output: {}", e))?;
 
-        Ok(Self {
+        Self {
             decoder,
+            sample_rate,
+            channels,
             speed_processor,
             equalizer,
+            target,
             output,
+            device_id,
+            replay_gain: 1.0,
             volume: 1.0,
+            fade: 1.0,
             is_playing: false,
             running: Arc::new(AtomicBool::new(true)),
-        })
+        }
     }
 
     fn process_audio_chunk(&mut self, tx: &Sender<Vec<f32>>) -> Result<bool, String> {
@@ -68,10 +123,22 @@ impl AudioPipeline {
         // Apply equalizer (for now just pass through since process method doesn't exist)
         let equalized = self.equalizer.apply(&speed_adjusted);
 
+        // Even out the book's loudness; a boost could clip, so peaks are limited
+        let normalized: Vec<f32> = if self.replay_gain == 1.0 {
+            equalized
+        } else {
+            let replay_gain = self.replay_gain;
+            equalized
+                .into_iter()
+                .map(|s| soft_limit(s * replay_gain))
+                .collect()
+        };
+
         // Apply volume
-        let final_audio: Vec<f32> = equalized
+        let gain = self.volume * self.fade;
+        let final_audio: Vec<f32> = normalized
             .into_iter()
-            .map(|s| (s * self.volume).clamp(-1.0, 1.0))
+            .map(|s| (s * gain).clamp(-1.0, 1.0))
             .collect();
 
         // Send to output
@@ -91,46 +158,222 @@ impl AudioPipeline {
 
         Ok(())
     }
+
+    /// Carries on from `decoder`, keeping the output open
+    ///
+    /// Audio already buffered keeps playing, so a file that follows the
+    /// last one plays without a gap. Only a file in another sample format
+    /// needs the output reopened, which is heard as a short break.
+    fn switch_decoder(
+        &mut self,
+        decoder: AudioDecoder,
+        audio_rx: &AudioReceiver<Vec<f32>>,
+    ) -> Result<(), String> {
+        let (sample_rate, channels) = decoder.get_format().map_err(|e| e.to_string())?;
+        let channels = channels as u16;
+        if (sample_rate, channels) != (self.sample_rate, self.channels) {
+            tracing::info!(
+                "Next file plays at {} Hz with {} channel(s), reopening the output",
+                sample_rate,
+                channels
+            );
+            self.output.stop();
+            self.output = self
+                .target
+                .open(self.device_id.as_deref(), sample_rate, channels)
+                .map_err(|e| format!("Failed to create audio output: {}", e))?;
+            self.output
+                .play(audio_rx.clone(), self.running.clone())
+                .map_err(|e| format!("Failed to start audio output: {}", e))?;
+            self.speed_processor = SpeedProcessor::new(sample_rate, channels);
+            self.sample_rate = sample_rate;
+            self.channels = channels;
+        }
+        self.decoder = decoder;
+        Ok(())
+    }
+
+    /// Moves playback to `device`, or to the system default when `None`
+    ///
+    /// Audio already decoded plays on the new device, so the position and
+    /// whether playback is running stay as they were. When the new device
+    /// cannot be opened the old one is kept.
+    fn switch_output(
+        &mut self,
+        device: Option<&AudioDeviceInfo>,
+        audio_rx: &AudioReceiver<Vec<f32>>,
+    ) -> Result<(), String> {
+        let device_id = device.map(|device| device.id.clone());
+        let mut output = self
+            .target
+            .open(device_id.as_deref(), self.sample_rate, self.channels)
+            .map_err(|e| format!("Failed to create audio output: {}", e))?;
+        self.output.stop();
+        if let Err(e) = output.play(audio_rx.clone(), self.running.clone()) {
+            // Gone devices fail here too; playback then stays interrupted
+            let _ = self.output.play(audio_rx.clone(), self.running.clone());
+            return Err(format!("Failed to start audio output: {}", e));
+        }
+        self.output = output;
+        self.device_id = device_id;
+        Ok(())
+    }
+}
+
+/// Opens `chosen`, or the system default when it is `None` or cannot be
+/// opened; falling back is reported as a device error
+fn open_output(
+    target: OutputTarget,
+    chosen: Option<&AudioDeviceInfo>,
+    sample_rate: u32,
+    channels: u16,
+) -> Result<(Box<dyn AudioSink>, Option<PlaybackDeviceError>), String> {
+    let open = |device_id: Option<&str>| {
+        target
+            .open(device_id, sample_rate, channels)
+            .map_err(|e| format!("Failed to create audio output: {}", e))
+    };
+    let Some(chosen) = chosen else {
+        return Ok((open(None)?, None));
+    };
+    match open(Some(&chosen.id)) {
+        Ok(output) => Ok((output, None)),
+        Err(e) => {
+            tracing::warn!("{}, playing on the default device instead", e);
+            let output = open(None)?;
+            let error = PlaybackDeviceError {
+                lost: chosen.name.clone(),
+                fallback: output.device_info().name.clone(),
+            };
+            Ok((output, Some(error)))
+        }
+    }
+}
+
+/// Opens the decoder of a queued file, logging why it cannot be played
+fn open_track(path: &std::path::Path) -> Option<AudioDecoder> {
+    match AudioDecoder::new(path) {
+        Ok(decoder) => Some(decoder),
+        Err(e) => {
+            tracing::error!("Cannot play {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Rounds off a sample above [`LIMIT_THRESHOLD`] so it stays within full scale
+fn soft_limit(sample: f32) -> f32 {
+    let level = sample.abs();
+    if level <= LIMIT_THRESHOLD {
+        return sample;
+    }
+    let knee = 1.0 - LIMIT_THRESHOLD;
+    sample.signum() * (LIMIT_THRESHOLD + knee * ((level - LIMIT_THRESHOLD) / knee).tanh())
+}
+
+/// State the engine shares with the playback thread
+///
+/// Named fields keep two handles of the same type from being swapped.
+#[derive(Clone)]
+pub struct PlaybackShared {
+    pub current_position: Arc<Mutex<Duration>>,
+    pub current_status: Arc<Mutex<bool>>,
+    pub playback_state: Arc<Mutex<PlaybackState>>,
+    pub volume: Arc<Mutex<f32>>,
+    /// Linear gain evening out the book's loudness
+    pub replay_gain: Arc<Mutex<f32>>,
+    /// Device the thread opened, `None` until it has
+    pub output_device: Arc<Mutex<Option<AudioDeviceInfo>>>,
+    /// Device chosen to play on, `None` for the system default
+    pub selected_device: Arc<Mutex<Option<AudioDeviceInfo>>>,
+    pub speed: Arc<Mutex<Speed>>,
+    pub equalizer: Arc<Mutex<Equalizer>>,
+    pub interruption: Arc<Interruption>,
+    pub sleep_timer: Arc<Mutex<Option<ArmedSleepTimer>>>,
+    pub queue: Arc<Mutex<PlaybackQueue>>,
 }
 
 /// Starts the playback thread with real audio processing
 pub fn start_playback_thread(
     decoder: AudioDecoder,
     command_rx: Receiver<PlaybackCommand>,
-    duration: Duration,
-    current_position: Arc<Mutex<Duration>>,
-    current_status: Arc<Mutex<bool>>,
-    playback_state: Arc<Mutex<PlaybackState>>,
-    volume: Arc<Mutex<f32>>,
-    speed: Arc<Mutex<Speed>>,
-    equalizer: Arc<Mutex<Equalizer>>,
+    mut duration: Duration,
+    shared: PlaybackShared,
+    output_target: OutputTarget,
 ) -> JoinHandle<()> {
     thread::spawn(move || {
+        let PlaybackShared {
+            current_position,
+            current_status,
+            playback_state,
+            volume,
+            replay_gain,
+            output_device,
+            selected_device,
+            speed,
+            equalizer,
+            interruption,
+            sleep_timer,
+            queue,
+        } = shared;
+
         // Get audio format info from decoder
         let (sample_rate, channels) = match decoder.get_format() {
             Ok(fmt) => fmt,
             Err(e) => {
-                log::error!("Failed to get audio format: {}", e);
+                tracing::error!("Failed to get audio format: {}", e);
                 return;
             }
         };
 
-        // Create audio pipeline
-        let mut pipeline = match AudioPipeline::new(decoder, sample_rate, channels as u16) {
-            Ok(p) => p,
-            Err(e) => {
-                log::error!("Failed to create audio pipeline: {}", e);
-                return;
+        // Open the chosen output device
+        let channels = channels as u16;
+        let chosen = selected_device
+            .lock()
+            .ok()
+            .and_then(|device| device.clone());
+        let (output, device_error) =
+            match open_output(output_target, chosen.as_ref(), sample_rate, channels) {
+                Ok(opened) => opened,
+                Err(e) => {
+                    tracing::error!("Failed to create audio pipeline: {}", e);
+                    return;
+                }
+            };
+        if device_error.is_some() {
+            if let Ok(mut selected) = selected_device.lock() {
+                *selected = None;
             }
-        };
+        }
+        let device_id = chosen
+            .filter(|_| device_error.is_none())
+            .map(|device| device.id);
+
+        // Create audio pipeline
+        let mut pipeline = AudioPipeline::new(
+            decoder,
+            sample_rate,
+            channels,
+            output_target,
+            output,
+            device_id,
+        );
+
+        // Start at the engine's volume, which may be set per output device
+        if let Ok(vol) = volume.lock() {
+            pipeline.volume = *vol;
+        }
+        if let Ok(mut device) = output_device.lock() {
+            *device = Some(pipeline.output.device_info().clone());
+        }
 
         // Create channel for audio data
         let (audio_tx, audio_rx) = bounded::<Vec<f32>>(16); // Buffer up to 16 chunks
 
         // Start audio output stream
         let running = pipeline.running.clone();
-        if let Err(e) = pipeline.output.play(audio_rx, running.clone()) {
-            log::error!("Failed to start audio output: {}", e);
+        if let Err(e) = pipeline.output.play(audio_rx.clone(), running.clone()) {
+            tracing::error!("Failed to start audio output: {}", e);
             return;
         }
 
@@ -138,10 +381,38 @@ pub fn start_playback_thread(
         if let Ok(mut state) = playback_state.lock() {
             state.set_duration(duration);
             state.set_status(PlaybackStatus::Stopped);
+            if device_error.is_some() {
+                state.device_error = device_error;
+            }
         }
 
         let mut last_position_update = Instant::now();
         let mut accumulated_samples = 0u64;
+        // Whether to play again when an interrupted device comes back
+        let mut resume_playing = false;
+        let mut last_reopen = Instant::now();
+
+        // The queued file playing, and the next one opened ahead of time
+        let mut track = queue.lock().map(|queue| queue.current()).unwrap_or(0);
+        let mut preloaded: Option<(usize, Option<AudioDecoder>)> = None;
+        let track_info = |index: usize| {
+            let queue = queue.lock().ok()?;
+            let track = queue.tracks().get(index)?;
+            Some((track.path.clone(), track.duration))
+        };
+        // Makes the file at `index` the one playing, from `position`
+        let enter_track = |index: usize, position: Duration, duration: Duration| {
+            if let Ok(mut queue) = queue.lock() {
+                queue.set_current(index);
+                if let Ok(mut pos) = current_position.lock() {
+                    *pos = position;
+                }
+            }
+            if let Ok(mut state) = playback_state.lock() {
+                state.set_duration(duration);
+                state.set_position(position);
+            }
+        };
 
         // Main playback loop
         while running.load(Ordering::Relaxed) {
@@ -159,6 +430,7 @@ pub fn start_playback_thread(
                     }
                     PlaybackCommand::Pause => {
                         pipeline.is_playing = false;
+                        resume_playing = false;
                         if let Ok(mut state) = playback_state.lock() {
                             state.set_status(PlaybackStatus::Paused);
                         }
@@ -176,10 +448,10 @@ pub fn start_playback_thread(
                     }
                     PlaybackCommand::Seek(position) => {
                         if let Err(e) = pipeline.seek(position) {
-                            log::error!("Seek failed: {}", e);
+                            tracing::error!("Seek failed: {}", e);
                         } else {
                             accumulated_samples =
-                                (position.as_secs_f64() * sample_rate as f64) as u64;
+                                (position.as_secs_f64() * pipeline.sample_rate as f64) as u64;
                             if let Ok(mut pos) = current_position.lock() {
                                 *pos = position;
                             }
@@ -188,6 +460,40 @@ pub fn start_playback_thread(
                             }
                         }
                     }
+                    PlaybackCommand::SkipTo { index, position } => {
+                        let decoder = match preloaded.take() {
+                            Some((preloaded, decoder)) if preloaded == index => decoder,
+                            _ => track_info(index).and_then(|(path, _)| open_track(&path)),
+                        };
+                        match decoder.map(|decoder| pipeline.switch_decoder(decoder, &audio_rx)) {
+                            Some(Ok(())) => {
+                                let position = if position.is_zero() {
+                                    pipeline.speed_processor.reset();
+                                    position
+                                } else if let Err(e) = pipeline.seek(position) {
+                                    tracing::error!("Seek failed: {}", e);
+                                    Duration::ZERO
+                                } else {
+                                    position
+                                };
+                                track = index;
+                                duration = track_info(index).map_or(Duration::ZERO, |(_, d)| d);
+                                accumulated_samples =
+                                    (position.as_secs_f64() * pipeline.sample_rate as f64) as u64;
+                                enter_track(index, position, duration);
+                            }
+                            Some(Err(e)) => {
+                                tracing::error!(
+                                    "Cannot play file {} of the queue: {}",
+                                    index + 1,
+                                    e
+                                )
+                            }
+                            None => {
+                                tracing::error!("Cannot skip to file {} of the queue", index + 1)
+                            }
+                        }
+                    }
                     PlaybackCommand::SetVolume(vol) => {
                         pipeline.volume = vol;
                         if let Ok(mut v) = volume.lock() {
@@ -196,11 +502,140 @@ pub fn start_playback_thread(
                     }
                     PlaybackCommand::SetSpeed(new_speed) => {
                         if let Err(e) = pipeline.speed_processor.set_speed(new_speed) {
-                            log::error!("Failed to set speed: {}", e);
+                            tracing::error!("Failed to set speed: {}", e);
                         } else if let Ok(mut s) = speed.lock() {
                             *s = new_speed;
                         }
                     }
+                    PlaybackCommand::SetOutputDevice(device) => {
+                        match pipeline.switch_output(device.as_ref(), &audio_rx) {
+                            Ok(()) => {
+                                let info = pipeline.output.device_info().clone();
+                                tracing::info!("Output moved to '{}'", info.name);
+                                if let Ok(mut device) = output_device.lock() {
+                                    *device = Some(info);
+                                }
+                                // A held playback stays paused on the new device
+                                if interruption.active.swap(false, Ordering::Relaxed) {
+                                    resume_playing = false;
+                                }
+                            }
+                            Err(e) => tracing::error!("Cannot change output device: {}", e),
+                        }
+                    }
+                }
+            }
+
+            // Hold playback while the output device is gone
+            if pipeline.output.is_interrupted() {
+                if pipeline.is_playing {
+                    // Also catches a Play sent during the interruption
+                    pipeline.is_playing = false;
+                    resume_playing = true;
+                    if let Ok(mut state) = playback_state.lock() {
+                        state.set_status(PlaybackStatus::Paused);
+                    }
+                    if let Ok(mut status) = current_status.lock() {
+                        *status = false;
+                    }
+                }
+                if !interruption.active.swap(true, Ordering::Relaxed) {
+                    tracing::warn!("Audio output interrupted, playback paused");
+                    last_reopen = Instant::now();
+                }
+                if last_reopen.elapsed() >= REOPEN_INTERVAL
+                    && !pipeline.output.is_device_available()
+                {
+                    // The device is gone: move to the default and stay paused
+                    last_reopen = Instant::now();
+                    let lost = pipeline.output.device_info().name.clone();
+                    match pipeline.switch_output(None, &audio_rx) {
+                        Ok(()) => {
+                            let fallback = pipeline.output.device_info().clone();
+                            tracing::warn!(
+                                "Output device '{}' is gone, moved to '{}'",
+                                lost,
+                                fallback.name
+                            );
+                            resume_playing = false;
+                            interruption.active.store(false, Ordering::Relaxed);
+                            if let Ok(mut selected) = selected_device.lock() {
+                                *selected = None;
+                            }
+                            if let Ok(mut state) = playback_state.lock() {
+                                state.device_error = Some(PlaybackDeviceError {
+                                    lost,
+                                    fallback: fallback.name.clone(),
+                                });
+                            }
+                            if let Ok(mut device) = output_device.lock() {
+                                *device = Some(fallback);
+                            }
+                        }
+                        Err(e) => tracing::debug!("No output device to move to: {}", e),
+                    }
+                } else if last_reopen.elapsed() >= REOPEN_INTERVAL {
+                    last_reopen = Instant::now();
+                    match pipeline.output.reopen() {
+                        Ok(()) => {
+                            interruption.active.store(false, Ordering::Relaxed);
+                            let resume = std::mem::take(&mut resume_playing)
+                                && interruption.auto_resume.load(Ordering::Relaxed);
+                            tracing::info!("Audio output is back (resuming: {})", resume);
+                            if resume {
+                                pipeline.is_playing = true;
+                                if let Ok(mut state) = playback_state.lock() {
+                                    state.set_status(PlaybackStatus::Playing);
+                                }
+                                if let Ok(mut status) = current_status.lock() {
+                                    *status = true;
+                                }
+                            }
+                        }
+                        Err(e) => tracing::debug!("Audio output still unavailable: {}", e),
+                    }
+                }
+                thread::sleep(Duration::from_millis(10));
+                continue;
+            }
+
+            // Open the next queued file early so it follows without a gap
+            let next = track + 1;
+            if preloaded.as_ref().is_none_or(|(index, _)| *index != next) {
+                if let Some((path, _)) = track_info(next) {
+                    preloaded = Some((next, open_track(&path)));
+                }
+            }
+
+            // Fade out over a sleep timer's last seconds, then pause
+            pipeline.fade = 1.0;
+            if let Ok(mut armed) = sleep_timer.lock() {
+                if let Some(timer) = armed.as_mut().filter(|timer| !timer.is_expired()) {
+                    let position = Duration::from_secs_f64(
+                        accumulated_samples as f64 / pipeline.sample_rate as f64,
+                    );
+                    // Book positions count from the start of the queue
+                    let position = match queue.lock() {
+                        Ok(queue) if queue.is_aggregate() => queue.queue_position(position),
+                        _ => position,
+                    };
+                    let current_speed = speed.lock().map(|s| s.value()).unwrap_or(1.0);
+                    let remaining = timer.remaining(Instant::now(), position, current_speed);
+                    if remaining.is_zero() {
+                        timer.timer.state = SleepTimerState::Expired;
+                        if pipeline.is_playing {
+                            tracing::info!("Sleep timer ran out, playback paused");
+                            pipeline.is_playing = false;
+                            if let Ok(mut state) = playback_state.lock() {
+                                state.set_status(PlaybackStatus::Paused);
+                            }
+                            if let Ok(mut status) = current_status.lock() {
+                                *status = false;
+                            }
+                        }
+                    } else {
+                        pipeline.fade = timer.fade_gain(remaining);
+                    }
                 }
             }
 
@@ -208,6 +643,9 @@ pub fn start_playback_thread(
             if let Ok(eq) = equalizer.lock() {
                 pipeline.equalizer = eq.clone();
             }
+            if let Ok(gain) = replay_gain.lock() {
+                pipeline.replay_gain = *gain;
+            }
 
             // Process audio if playing
             if pipeline.is_playing {
@@ -224,7 +662,7 @@ pub fn start_playback_thread(
                         // Update position periodically (not every chunk for performance)
                         if last_position_update.elapsed() > Duration::from_millis(100) {
                             let new_position = Duration::from_secs_f64(
-                                accumulated_samples as f64 / sample_rate as f64,
+                                accumulated_samples as f64 / pipeline.sample_rate as f64,
                             );
 
                             if let Ok(mut pos) = current_position.lock() {
@@ -238,10 +676,35 @@ pub fn start_playback_thread(
                         }
                     }
                     Ok(false) => {
-                        // End of file reached
-                        log::info!("Playback completed");
+                        // Carry on with the next queued file, if there is one
+                        let next = track + 1;
+                        let decoder = match preloaded.take() {
+                            Some((preloaded, decoder)) if preloaded == next => decoder,
+                            _ => track_info(next).and_then(|(path, _)| open_track(&path)),
+                        };
+                        let switched = match decoder {
+                            Some(decoder) => pipeline
+                                .switch_decoder(decoder, &audio_rx)
+                                .map_err(|e| tracing::error!("Cannot play the next file: {}", e))
+                                .is_ok(),
+                            None => false,
+                        };
+                        if switched {
+                            tracing::info!("Playing file {} of the queue", next + 1);
+                            track = next;
+                            duration = track_info(next).map_or(Duration::ZERO, |(_, d)| d);
+                            accumulated_samples = 0;
+                            enter_track(next, Duration::ZERO, duration);
+                            continue;
+                        }
+
+                        // End of the last file reached
+                        tracing::info!("Playback completed");
                         pipeline.is_playing = false;
 
+                        if let Ok(mut pos) = current_position.lock() {
+                            *pos = duration;
+                        }
                         if let Ok(mut state) = playback_state.lock() {
                             state.set_status(PlaybackStatus::Stopped);
                             state.set_position(duration);
@@ -251,7 +714,7 @@ pub fn start_playback_thread(
                         }
                     }
                     Err(e) => {
-                        log::error!("Audio processing error: {}", e);
+                        tracing::error!("Audio processing error: {}", e);
                         pipeline.is_playing = false;
 
                         if let Ok(mut state) = playback_state.lock() {
@@ -272,7 +735,7 @@ pub fn start_playback_thread(
 
         // Cleanup
         pipeline.output.stop();
-        log::info!("Playback thread terminated");
+        tracing::info!("Playback thread terminated");
     })
 }
 
@@ -292,13 +755,31 @@ mod tests {
         let _pause = PlaybackCommand::Pause;
         let _stop = PlaybackCommand::Stop;
         let _seek = PlaybackCommand::Seek(Duration::from_secs(10));
+        let _skip = PlaybackCommand::SkipTo {
+            index: 1,
+            position: Duration::ZERO,
+        };
         let _volume = PlaybackCommand::SetVolume(0.5);
         let _speed = PlaybackCommand::SetSpeed(Speed::default());
+        let _device = PlaybackCommand::SetOutputDevice(None);
+    }
+
+    #[test]
+    fn test_soft_limit() {
+        assert_eq!(soft_limit(0.5), 0.5);
+        assert_eq!(soft_limit(-0.9), -0.9);
+        for sample in [0.95, 1.5, 4.0, 100.0] {
+            let limited = soft_limit(sample);
+            assert!(limited > LIMIT_THRESHOLD && limited <= 1.0, "{}", limited);
+            assert_eq!(soft_limit(-sample), -limited);
+        }
+        assert!(soft_limit(1.5) > soft_limit(0.95));
     }
 }
 
 // crates/media-engine/src/decoder.rs
 
+use crate::decoder::MAX_CHUNK_SAMPLES;
 use crate::error::{EngineError, EngineResult};
 use std::fs::File;
 use std::path::Path;
@@ -320,6 +801,8 @@ pub struct AudioDecoder {
     sample_rate: u32,
     channels: usize,
     sample_buffer: Option<SampleBuffer<f32>>,
+    /// Decoded samples that did not fit in the last chunk
+    pending: Vec<f32>,
 }
 
 impl AudioDecoder {
@@ -384,13 +867,21 @@ impl AudioDecoder {
             sample_rate,
             channels,
             sample_buffer: None,
+            pending: Vec::new(),
         })
     }
 
     /// Decode a chunk of audio samples
+    ///
+    /// Requests are clamped to [`MAX_CHUNK_SAMPLES`]; samples past the limit
+    /// are kept for the next call.
     pub fn decode_chunk(&mut self, max_samples: usize) -> EngineResult<Vec<f32>> {
+        let max_samples = max_samples.min(MAX_CHUNK_SAMPLES);
         let mut output = Vec::with_capacity(max_samples);
 
+        let carried = self.pending.len().min(max_samples);
+        output.extend(self.pending.drain(..carried));
+
         while output.len() < max_samples {
             // Get next packet
             let packet = match self.format.next_packet() {
@@ -426,20 +917,25 @@ impl AudioDecoder {
 
             // Convert to f32 samples
             let buffer_spec = *decoded.spec();
-            let buffer_duration = decoded.frames() as usize;
+            let buffer_duration = decoded.capacity();
             let buffer_channels = buffer_spec.channels.count();
 
-            // Create or resize sample buffer
-            if self.sample_buffer.is_none()
-                || self.sample_buffer.as_ref().unwrap().len() != buffer_duration * buffer_channels
+            // Grow the sample buffer only for a packet larger than any before
+            if self
+                .sample_buffer
+                .as_ref()
+                .is_none_or(|buf| buf.capacity() < buffer_duration * buffer_channels)
             {
                 self.sample_buffer = Some(SampleBuffer::new(buffer_duration as u64, buffer_spec));
             }
 
-            // Copy to sample buffer
+            // Copy to sample buffer, keeping what does not fit for next time
             if let Some(ref mut sample_buf) = self.sample_buffer {
                 sample_buf.copy_interleaved_ref(decoded);
-                output.extend_from_slice(sample_buf.samples());
+                let samples = sample_buf.samples();
+                let fits = samples.len().min(max_samples - output.len());
+                output.extend_from_slice(&samples[..fits]);
+                self.pending.extend_from_slice(&samples[fits..]);
             } else {
                 return Err(EngineError::DecodeError(
                     "Failed to create sample buffer".to_string(),
@@ -447,8 +943,6 @@ impl AudioDecoder {
             }
         }
 
-        // Truncate to requested size
-        output.truncate(max_samples);
         Ok(output)
     }
 
@@ -493,6 +987,7 @@ impl AudioDecoder {
 
         // Reset decoder after seek
         self.decoder.reset();
+        self.pending.clear();
 
         Ok(())
     }
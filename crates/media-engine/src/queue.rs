@@ -0,0 +1,197 @@
+// crates/media-engine/src/queue.rs
+//! Files the engine plays one after another
+//!
+//! A book split over several files, one per part or chapter, plays as a
+//! queue: when a file ends the playback thread carries on with the next one
+//! without stopping the output. The engine and the thread share the queue,
+//! the engine adding files and the thread moving through them.
+//!
+//! Positions are reported within the file playing. When the files together
+//! make up one book, the queue can report them across all of its files
+//! instead, so a saved position means the same wherever it falls.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// A file in the queue
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedTrack {
+    pub path: PathBuf,
+    pub duration: Duration,
+}
+
+impl QueuedTrack {
+    pub fn new(path: impl Into<PathBuf>, duration: Duration) -> Self {
+        Self {
+            path: path.into(),
+            duration,
+        }
+    }
+}
+
+/// The files queued for playback and which of them is playing
+#[derive(Debug, Clone, Default)]
+pub struct PlaybackQueue {
+    tracks: Vec<QueuedTrack>,
+    current: usize,
+    /// Whether positions cover the whole queue instead of one file
+    aggregate: bool,
+}
+
+impl PlaybackQueue {
+    /// Creates a queue holding only `first`
+    pub fn new(first: QueuedTrack) -> Self {
+        Self {
+            tracks: vec![first],
+            current: 0,
+            aggregate: false,
+        }
+    }
+
+    pub fn tracks(&self) -> &[QueuedTrack] {
+        &self.tracks
+    }
+
+    pub fn len(&self) -> usize {
+        self.tracks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+
+    /// Index of the file playing
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    pub fn current_track(&self) -> Option<&QueuedTrack> {
+        self.tracks.get(self.current)
+    }
+
+    /// The file after the one playing, with its index
+    pub fn next_track(&self) -> Option<(usize, &QueuedTrack)> {
+        let index = self.current + 1;
+        self.tracks.get(index).map(|track| (index, track))
+    }
+
+    /// Whether the file playing is the last one, or nothing is queued
+    pub fn is_last(&self) -> bool {
+        self.current + 1 >= self.tracks.len()
+    }
+
+    /// Adds a file after the ones already queued
+    pub fn push(&mut self, track: QueuedTrack) {
+        self.tracks.push(track);
+    }
+
+    /// Makes the file at `index` the one playing; ignored past the end
+    pub fn set_current(&mut self, index: usize) {
+        if index < self.tracks.len() {
+            self.current = index;
+        }
+    }
+
+    pub fn is_aggregate(&self) -> bool {
+        self.aggregate
+    }
+
+    /// Sets whether positions cover the whole queue
+    pub fn set_aggregate(&mut self, aggregate: bool) {
+        self.aggregate = aggregate;
+    }
+
+    /// Where the file at `index` starts in the queue
+    pub fn offset_of(&self, index: usize) -> Duration {
+        self.tracks
+            .iter()
+            .take(index)
+            .map(|track| track.duration)
+            .sum()
+    }
+
+    /// How long the whole queue plays
+    pub fn total_duration(&self) -> Duration {
+        self.offset_of(self.tracks.len())
+    }
+
+    /// Turns a position in the file playing into one across the queue
+    pub fn queue_position(&self, track_position: Duration) -> Duration {
+        self.offset_of(self.current) + track_position
+    }
+
+    /// Finds the file a position across the queue falls in, and the
+    /// position within that file
+    ///
+    /// Positions past the end fall at the end of the last file. Returns
+    /// `None` when nothing is queued.
+    pub fn locate(&self, position: Duration) -> Option<(usize, Duration)> {
+        let mut start = Duration::ZERO;
+        for (index, track) in self.tracks.iter().enumerate() {
+            if position < start + track.duration {
+                return Some((index, position - start));
+            }
+            start += track.duration;
+        }
+        let last = self.tracks.len().checked_sub(1)?;
+        Some((last, self.tracks[last].duration))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue() -> PlaybackQueue {
+        let mut queue = PlaybackQueue::new(QueuedTrack::new("01.mp3", Duration::from_secs(60)));
+        queue.push(QueuedTrack::new("02.mp3", Duration::from_secs(90)));
+        queue.push(QueuedTrack::new("03.mp3", Duration::from_secs(30)));
+        queue
+    }
+
+    #[test]
+    fn test_offsets_and_total() {
+        let queue = queue();
+
+        assert_eq!(queue.offset_of(0), Duration::ZERO);
+        assert_eq!(queue.offset_of(2), Duration::from_secs(150));
+        assert_eq!(queue.total_duration(), Duration::from_secs(180));
+    }
+
+    #[test]
+    fn test_locate() {
+        let queue = queue();
+
+        assert_eq!(queue.locate(Duration::ZERO), Some((0, Duration::ZERO)));
+        assert_eq!(
+            queue.locate(Duration::from_secs(60)),
+            Some((1, Duration::ZERO))
+        );
+        assert_eq!(
+            queue.locate(Duration::from_secs(100)),
+            Some((1, Duration::from_secs(40)))
+        );
+        assert_eq!(
+            queue.locate(Duration::from_secs(500)),
+            Some((2, Duration::from_secs(30)))
+        );
+        assert_eq!(PlaybackQueue::default().locate(Duration::ZERO), None);
+    }
+
+    #[test]
+    fn test_moving_through_the_queue() {
+        let mut queue = queue();
+
+        assert_eq!(queue.next_track().map(|(index, _)| index), Some(1));
+        queue.set_current(2);
+        assert!(queue.is_last());
+        assert!(queue.next_track().is_none());
+        assert_eq!(
+            queue.queue_position(Duration::from_secs(10)),
+            Duration::from_secs(160)
+        );
+
+        queue.set_current(7);
+        assert_eq!(queue.current(), 2);
+    }
+}
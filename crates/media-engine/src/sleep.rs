@@ -0,0 +1,148 @@
+// crates/media-engine/src/sleep.rs
+//! Sleep timers the playback thread counts down
+//!
+//! The engine arms a timer by working out when it should pause playback:
+//! a moment in time for fixed timers, or a book position for timers that
+//! run to the end of a chapter. The playback thread checks it as it plays,
+//! fades the volume out over the timer's fade duration and then pauses, so
+//! playing again picks up at the same spot.
+
+use std::time::{Duration, Instant};
+use storystream_core::types::{SleepTimer, SleepTimerState};
+
+/// When an armed sleep timer pauses playback
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SleepDeadline {
+    /// At a moment in time
+    At(Instant),
+    /// When playback reaches a position in the book
+    Position(Duration),
+}
+
+/// A sleep timer the engine has armed
+#[derive(Debug, Clone, Copy)]
+pub struct ArmedSleepTimer {
+    pub timer: SleepTimer,
+    pub deadline: SleepDeadline,
+}
+
+impl ArmedSleepTimer {
+    /// Arms `timer` to pause playback at `deadline`
+    pub fn new(mut timer: SleepTimer, deadline: SleepDeadline) -> Self {
+        timer.state = SleepTimerState::Active;
+        Self { timer, deadline }
+    }
+
+    /// Returns true once the timer has paused playback
+    pub fn is_expired(&self) -> bool {
+        self.timer.state == SleepTimerState::Expired
+    }
+
+    /// Time left before playback pauses
+    ///
+    /// A position deadline is reached sooner at higher speeds, so the book
+    /// time left is divided by `speed`.
+    pub fn remaining(&self, now: Instant, position: Duration, speed: f32) -> Duration {
+        if self.is_expired() {
+            return Duration::ZERO;
+        }
+        match self.deadline {
+            SleepDeadline::At(at) => at.saturating_duration_since(now),
+            SleepDeadline::Position(end) => end.saturating_sub(position).div_f32(speed.max(0.1)),
+        }
+    }
+
+    /// Volume factor while fading out: 1.0 until the fade starts, then
+    /// falling to 0.0 as the time left runs out
+    pub fn fade_gain(&self, remaining: Duration) -> f32 {
+        let fade = Duration::from_millis(self.timer.fade_duration.as_millis());
+        if fade.is_zero() || remaining >= fade {
+            return 1.0;
+        }
+        remaining.as_secs_f32() / fade.as_secs_f32()
+    }
+
+    /// Gives the timer `by` more time
+    ///
+    /// A timer waiting for the end of a chapter becomes a fixed one from
+    /// the time it had left, so extending never waits for a later chapter.
+    pub fn extend(&mut self, by: Duration, now: Instant, position: Duration, speed: f32) {
+        let remaining = self.remaining(now, position, speed);
+        if let SleepDeadline::Position(_) = self.deadline {
+            self.timer = SleepTimer::with_fade(remaining.into(), self.timer.fade_duration);
+        }
+        self.timer.extend(by.into());
+        self.deadline = SleepDeadline::At(now + remaining + by);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixed(secs: u64, now: Instant) -> ArmedSleepTimer {
+        let timer = SleepTimer::new(Duration::from_secs(secs).into());
+        ArmedSleepTimer::new(timer, SleepDeadline::At(now + Duration::from_secs(secs)))
+    }
+
+    #[test]
+    fn test_remaining_counts_down() {
+        let now = Instant::now();
+        let armed = fixed(900, now);
+
+        assert_eq!(
+            armed.remaining(now, Duration::ZERO, 1.0),
+            Duration::from_secs(900)
+        );
+        let later = now + Duration::from_secs(1000);
+        assert_eq!(armed.remaining(later, Duration::ZERO, 1.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_chapter_deadline_follows_speed() {
+        let armed = ArmedSleepTimer::new(
+            SleepTimer::end_of_chapter(),
+            SleepDeadline::Position(Duration::from_secs(600)),
+        );
+        let now = Instant::now();
+
+        assert_eq!(
+            armed.remaining(now, Duration::from_secs(500), 1.0),
+            Duration::from_secs(100)
+        );
+        assert_eq!(
+            armed.remaining(now, Duration::from_secs(500), 2.0),
+            Duration::from_secs(50)
+        );
+        assert_eq!(
+            armed.remaining(now, Duration::from_secs(700), 1.0),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn test_fade_gain() {
+        let armed = fixed(900, Instant::now());
+
+        assert_eq!(armed.fade_gain(Duration::from_secs(60)), 1.0);
+        assert_eq!(armed.fade_gain(Duration::from_secs(5)), 0.5);
+        assert_eq!(armed.fade_gain(Duration::ZERO), 0.0);
+    }
+
+    #[test]
+    fn test_extend_chapter_timer_becomes_fixed() {
+        let mut armed = ArmedSleepTimer::new(
+            SleepTimer::end_of_chapter(),
+            SleepDeadline::Position(Duration::from_secs(600)),
+        );
+        let now = Instant::now();
+        armed.extend(Duration::from_secs(300), now, Duration::from_secs(540), 1.0);
+
+        assert!(!armed.timer.is_end_of_chapter());
+        assert_eq!(armed.timer.duration.as_seconds(), 360);
+        assert_eq!(
+            armed.remaining(now, Duration::ZERO, 1.0),
+            Duration::from_secs(360)
+        );
+    }
+}
@@ -0,0 +1,200 @@
+// crates/media-engine/src/snap.rs
+//! Snapping seek targets to chapter starts and bookmarks
+//!
+//! While scrubbing, a target close to a chapter start or a bookmark lands
+//! exactly on it, so those points are easy to hit with a coarse pointer.
+
+use crate::bookmarks::BookmarkManager;
+use crate::chapters::ChapterList;
+use std::time::Duration;
+
+/// How much closer a bookmark must be than a chapter start to win over it
+///
+/// Bookmarks placed at a chapter boundary rarely sit exactly on it.
+const SAME_SPOT: Duration = Duration::from_millis(250);
+
+/// What a seek target was snapped to
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnapPoint {
+    /// The start of a chapter
+    ChapterStart { index: usize, title: String },
+    /// A bookmark
+    Bookmark { title: Option<String> },
+}
+
+impl SnapPoint {
+    /// Short label for scrubbing displays, such as "Chapter 7"
+    pub fn label(&self) -> String {
+        match self {
+            Self::ChapterStart { index, .. } => format!("Chapter {}", index + 1),
+            Self::Bookmark { title: Some(title) } => format!("Bookmark: {}", title),
+            Self::Bookmark { title: None } => "Bookmark".to_string(),
+        }
+    }
+}
+
+/// A seek target after snapping
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnappedPosition {
+    pub position: Duration,
+    /// What the target moved to, or `None` if nothing was close enough
+    pub snapped_to: Option<SnapPoint>,
+}
+
+impl SnappedPosition {
+    /// The target as given, snapped to nothing
+    pub fn unsnapped(target: Duration) -> Self {
+        Self {
+            position: target,
+            snapped_to: None,
+        }
+    }
+}
+
+/// Moves `target` onto the nearest chapter start or bookmark within `window`
+///
+/// A chapter start wins over a bookmark less than [`SAME_SPOT`] closer. The
+/// result is never further than `window` from `target`.
+pub fn snap_position(
+    target: Duration,
+    window: Duration,
+    chapters: &ChapterList,
+    bookmarks: &BookmarkManager,
+) -> SnappedPosition {
+    let chapter = chapters
+        .chapters()
+        .iter()
+        .map(|chapter| {
+            let start = Duration::from_secs_f64(chapter.start_time.max(0.0));
+            (start.abs_diff(target), start, chapter)
+        })
+        .filter(|(distance, _, _)| *distance <= window)
+        .min_by_key(|(distance, _, _)| *distance);
+    let bookmark = bookmarks
+        .get_nearest_bookmark(target)
+        .map(|bookmark| (bookmark.position.abs_diff(target), bookmark))
+        .filter(|(distance, _)| *distance <= window);
+
+    match (chapter, bookmark) {
+        (Some((to_chapter, start, chapter)), bookmark)
+            if bookmark.is_none_or(|(to_bookmark, _)| to_chapter <= to_bookmark + SAME_SPOT) =>
+        {
+            SnappedPosition {
+                position: start,
+                snapped_to: Some(SnapPoint::ChapterStart {
+                    index: chapter.index,
+                    title: chapter.title.clone(),
+                }),
+            }
+        }
+        (_, Some((_, bookmark))) => SnappedPosition {
+            position: bookmark.position,
+            snapped_to: Some(SnapPoint::Bookmark {
+                title: bookmark.title.clone(),
+            }),
+        },
+        _ => SnappedPosition::unsnapped(target),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bookmarks::{Bookmark, BookmarkType};
+    use crate::chapters::ChapterMarker;
+
+    fn chapters(starts: &[f64]) -> ChapterList {
+        ChapterList::with_chapters(
+            starts
+                .iter()
+                .enumerate()
+                .map(|(i, &start)| {
+                    ChapterMarker::new(i, format!("Part {}", i + 1), start, start + 600.0)
+                })
+                .collect(),
+        )
+    }
+
+    fn bookmarks(positions_ms: &[u64]) -> BookmarkManager {
+        let mut manager = BookmarkManager::new();
+        for &ms in positions_ms {
+            let bookmark = Bookmark::new(Duration::from_millis(ms), BookmarkType::User)
+                .with_title(format!("at {}", ms));
+            manager.add_bookmark(bookmark).unwrap();
+        }
+        manager
+    }
+
+    #[test]
+    fn test_snaps_to_nearest_point_within_window() {
+        let window = Duration::from_secs(5);
+        let list = chapters(&[0.0, 600.0, 1200.0]);
+        let marks = bookmarks(&[900_000]);
+
+        let snapped = snap_position(Duration::from_secs(597), window, &list, &marks);
+        assert_eq!(snapped.position, Duration::from_secs(600));
+        assert_eq!(snapped.snapped_to.unwrap().label(), "Chapter 2");
+
+        let snapped = snap_position(Duration::from_secs(903), window, &list, &marks);
+        assert_eq!(snapped.position, Duration::from_secs(900));
+        assert_eq!(snapped.snapped_to.unwrap().label(), "Bookmark: at 900000");
+
+        // Nothing close enough
+        let target = Duration::from_secs(700);
+        assert_eq!(
+            snap_position(target, window, &list, &marks),
+            SnappedPosition::unsnapped(target)
+        );
+    }
+
+    #[test]
+    fn test_never_moves_further_than_window() {
+        let window = Duration::from_secs(2);
+        let list = chapters(&[0.0, 600.0]);
+        let marks = bookmarks(&[300_000]);
+
+        let just_outside = Duration::from_millis(602_001);
+        assert!(snap_position(just_outside, window, &list, &marks)
+            .snapped_to
+            .is_none());
+        let edge = Duration::from_secs(602);
+        assert_eq!(
+            snap_position(edge, window, &list, &marks).position,
+            Duration::from_secs(600)
+        );
+        assert!(
+            snap_position(Duration::from_secs(300), Duration::ZERO, &list, &marks)
+                .snapped_to
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_chapter_wins_tie_with_nearby_bookmark() {
+        let window = Duration::from_secs(5);
+        let list = chapters(&[0.0, 600.0]);
+        // Placed a moment after the chapter began
+        let marks = bookmarks(&[600_100]);
+
+        // Equally close to both
+        let snapped = snap_position(Duration::from_millis(600_050), window, &list, &marks);
+        assert_eq!(snapped.position, Duration::from_secs(600));
+        assert!(matches!(
+            snapped.snapped_to,
+            Some(SnapPoint::ChapterStart { index: 1, .. })
+        ));
+
+        // The bookmark is a little closer, but it marks the same spot
+        let snapped = snap_position(Duration::from_millis(603_000), window, &list, &marks);
+        assert_eq!(snapped.position, Duration::from_secs(600));
+
+        // Well apart, the closer one wins
+        let marks = bookmarks(&[602_000]);
+        let snapped = snap_position(Duration::from_millis(603_000), window, &list, &marks);
+        assert_eq!(snapped.position, Duration::from_secs(602));
+        assert!(matches!(
+            snapped.snapped_to,
+            Some(SnapPoint::Bookmark { .. })
+        ));
+    }
+}
@@ -92,6 +92,21 @@ pub enum MediaEvent {
     StateChanged(PlaybackState),
     PositionChanged(Duration),
     ChapterChanged(usize),
+    /// Playback speed changed; `ramping` while a speed ramp is moving it
+    SpeedChanged {
+        speed: f32,
+        ramping: bool,
+    },
     PlaybackEnded,
+    /// The queue moved on to the file at `index`
+    TrackChanged {
+        index: usize,
+    },
+    /// The output device stopped taking audio and playback paused
+    Interrupted,
+    /// The output device is back; `resumed` if playback picked up again
+    InterruptionEnded {
+        resumed: bool,
+    },
     Error(String),
 }
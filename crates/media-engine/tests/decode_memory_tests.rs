@@ -0,0 +1,120 @@
+// FILE: crates/media-engine/tests/decode_memory_tests.rs
+//! Decoder memory regression test
+//!
+//! Decodes ten minutes from the start of a file whose header claims about
+//! seventy hours, counting every allocation made along the way. Memory held
+//! by the decoder must not depend on the length of the file.
+//!
+//! Lives in its own test binary because it installs a global allocator.
+
+use media_engine::AudioDecoder;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use storystream_media_formats::wav::wav_header;
+use tempfile::TempDir;
+
+type TestResult<T = ()> = Result<T, Box<dyn std::error::Error>>;
+
+/// Extra memory decoding may hold, as documented in the crate docs
+const DECODE_BUDGET: usize = 4 * 1024 * 1024;
+
+const SAMPLE_RATE: u32 = 8000;
+const CLAIMED_HOURS: u32 = 70;
+const DECODED_SECS: u32 = 600;
+
+// ============================================================================
+// Counting Allocator
+// ============================================================================
+
+struct CountingAllocator;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let now = CURRENT.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(now, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// ============================================================================
+// Test Audio
+// ============================================================================
+
+/// Writes a 16-bit mono WAV whose header claims `claimed_secs` of audio but
+/// which only holds `written_secs`
+///
+/// Keeps the fixture small on disk while the decoder sees a very long file.
+fn write_long_wav(path: &Path, claimed_secs: u32, written_secs: u32) -> TestResult {
+    let data_len = claimed_secs * SAMPLE_RATE * 2;
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+
+    file.write_all(&wav_header(data_len, SAMPLE_RATE))?;
+
+    let second: Vec<u8> = (0..SAMPLE_RATE)
+        .flat_map(|i| (((i % 80) as i16 - 40) * 400).to_le_bytes())
+        .collect();
+    for _ in 0..written_secs {
+        file.write_all(&second)?;
+    }
+    file.flush()?;
+    Ok(())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[test]
+fn test_decoding_long_file_stays_within_budget() -> TestResult {
+    let temp_dir = TempDir::new()?;
+    let path = temp_dir.path().join("very_long.wav");
+    write_long_wav(&path, CLAIMED_HOURS * 3600, DECODED_SECS + 5)?;
+
+    let baseline = CURRENT.load(Ordering::SeqCst);
+    PEAK.store(baseline, Ordering::SeqCst);
+
+    let mut decoder = AudioDecoder::new(&path)?;
+    assert_eq!(
+        decoder.duration(),
+        Some(Duration::from_secs(u64::from(CLAIMED_HOURS) * 3600))
+    );
+
+    let target = (DECODED_SECS * SAMPLE_RATE) as usize;
+    let mut decoded = 0;
+    while decoded < target {
+        // Asks for far more than a chunk may hold
+        let chunk = decoder.decode_chunk(usize::MAX)?;
+        if chunk.is_empty() {
+            break;
+        }
+        decoded += chunk.len();
+    }
+    assert!(decoded >= target, "decoded only {} samples", decoded);
+
+    let peak = PEAK.load(Ordering::SeqCst) - baseline;
+    assert!(
+        peak < DECODE_BUDGET,
+        "decoding peaked at {} bytes, budget is {}",
+        peak,
+        DECODE_BUDGET
+    );
+
+    Ok(())
+}
@@ -4,18 +4,25 @@ mod capabilities;
 mod detection;
 mod error;
 mod format;
+mod loudness;
 mod mime;
 mod properties;
 mod quality;
+mod waveform;
+
+#[cfg(any(test, feature = "test-audio"))]
+pub mod wav;
 
 // Re-export all types
 pub use capabilities::{FormatCapabilities, MetadataSupport, QualityLevel};
 pub use detection::FormatDetector;
 pub use error::{FormatError, FormatResult};
 pub use format::AudioFormat;
+pub use loudness::{Loudness, LoudnessMeter, MAX_REPLAY_GAIN_DB, TARGET_LOUDNESS_DB};
 pub use mime::MimeType;
 pub use properties::{AudioAnalyzer, AudioProperties, CodecInfo};
 pub use quality::{AudioQuality, QualityTier};
+pub use waveform::Waveform;
 
 pub mod prelude {
     pub use crate::{
@@ -1,11 +1,16 @@
 //! Audio properties extraction using Symphonia
 
-use crate::{AudioFormat, AudioQuality, FormatError, FormatResult, QualityTier};
+use crate::{
+    AudioFormat, AudioQuality, FormatError, FormatResult, Loudness, LoudnessMeter, QualityTier,
+    Waveform,
+};
 use std::fs::File;
 use std::path::Path;
 use std::time::Duration;
-use symphonia::core::codecs::CodecParameters;
-use symphonia::core::formats::FormatOptions;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{CodecParameters, Decoder, DecoderOptions};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader};
 use symphonia::core::io::MediaSourceStream;
 use symphonia::core::meta::MetadataOptions;
 use symphonia::core::probe::Hint;
@@ -126,6 +131,9 @@ impl CodecInfo {
     }
 }
 
+/// A file's format reader, the ID of its default track and a decoder for it
+type OpenTrack = (Box<dyn FormatReader>, u32, Box<dyn Decoder>);
+
 /// Audio analyzer using Symphonia
 pub struct AudioAnalyzer {
     format_opts: FormatOptions,
@@ -246,6 +254,196 @@ impl AudioAnalyzer {
         })
     }
 
+    /// Decodes every packet of the file's default track
+    ///
+    /// Much slower than [`analyze`](Self::analyze), which only reads the
+    /// headers, but finds damage anywhere in the audio stream.
+    pub fn verify_decode(&self, path: &Path) -> FormatResult<()> {
+        let (mut format_reader, track_id, mut decoder) = self.open_track(path)?;
+
+        loop {
+            let packet = match format_reader.next_packet() {
+                Ok(packet) => packet,
+                Err(SymphoniaError::IoError(e))
+                    if e.kind() == std::io::ErrorKind::UnexpectedEof =>
+                {
+                    return Ok(());
+                }
+                Err(e) => return Err(FormatError::corrupted(path.to_path_buf(), e.to_string())),
+            };
+            if packet.track_id() != track_id {
+                continue;
+            }
+            decoder
+                .decode(&packet)
+                .map_err(|e| FormatError::corrupted(path.to_path_buf(), e.to_string()))?;
+        }
+    }
+
+    /// Decodes the file's default track into the peak level of every
+    /// `window` of audio
+    ///
+    /// As slow as [`verify_decode`](Self::verify_decode). `cancelled` is
+    /// asked between packets; the pass stops with `None` once it says yes.
+    /// Packets that fail to decode are skipped rather than failing the pass.
+    pub fn waveform(
+        &self,
+        path: &Path,
+        window: Duration,
+        cancelled: impl Fn() -> bool,
+    ) -> FormatResult<Option<Waveform>> {
+        let (mut format_reader, track_id, mut decoder) = self.open_track(path)?;
+        let mut samples: Option<SampleBuffer<f32>> = None;
+        let mut frames_per_window = 0;
+        let mut frames = 0;
+        let mut peak = 0.0f32;
+        let mut peaks = Vec::new();
+
+        loop {
+            if cancelled() {
+                return Ok(None);
+            }
+            let packet = match format_reader.next_packet() {
+                Ok(packet) => packet,
+                Err(SymphoniaError::IoError(e))
+                    if e.kind() == std::io::ErrorKind::UnexpectedEof =>
+                {
+                    break;
+                }
+                Err(e) => return Err(FormatError::corrupted(path.to_path_buf(), e.to_string())),
+            };
+            if packet.track_id() != track_id {
+                continue;
+            }
+            let decoded = match decoder.decode(&packet) {
+                Ok(decoded) => decoded,
+                Err(SymphoniaError::DecodeError(_)) => continue,
+                Err(e) => return Err(FormatError::corrupted(path.to_path_buf(), e.to_string())),
+            };
+
+            let spec = *decoded.spec();
+            let needed = decoded.capacity() * spec.channels.count();
+            if samples
+                .as_ref()
+                .is_none_or(|buffer| buffer.capacity() < needed)
+            {
+                samples = Some(SampleBuffer::new(decoded.capacity() as u64, spec));
+            }
+            let Some(buffer) = samples.as_mut() else {
+                continue;
+            };
+            buffer.copy_interleaved_ref(decoded);
+            if frames_per_window == 0 {
+                frames_per_window =
+                    ((spec.rate as u128 * window.as_millis() / 1000) as usize).max(1);
+            }
+
+            for frame in buffer.samples().chunks(spec.channels.count().max(1)) {
+                let loudest = frame.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+                peak = peak.max(loudest);
+                frames += 1;
+                if frames == frames_per_window {
+                    peaks.push(peak.min(1.0));
+                    peak = 0.0;
+                    frames = 0;
+                }
+            }
+        }
+
+        if frames > 0 {
+            peaks.push(peak.min(1.0));
+        }
+        Ok(Some(Waveform { window, peaks }))
+    }
+
+    /// Decodes the file's default track to measure how loud it is
+    ///
+    /// As slow as [`verify_decode`](Self::verify_decode). `pace` is called
+    /// with the size of each packet before it is decoded; it may sleep to
+    /// slow the pass down, and the pass stops with `None` once it returns
+    /// `false`. Packets that fail to decode are skipped. A file that is
+    /// silent throughout has no loudness either.
+    pub fn loudness(
+        &self,
+        path: &Path,
+        mut pace: impl FnMut(usize) -> bool,
+    ) -> FormatResult<Option<Loudness>> {
+        let (mut format_reader, track_id, mut decoder) = self.open_track(path)?;
+        let mut samples: Option<SampleBuffer<f32>> = None;
+        let mut meter: Option<LoudnessMeter> = None;
+
+        loop {
+            let packet = match format_reader.next_packet() {
+                Ok(packet) => packet,
+                Err(SymphoniaError::IoError(e))
+                    if e.kind() == std::io::ErrorKind::UnexpectedEof =>
+                {
+                    break;
+                }
+                Err(e) => return Err(FormatError::corrupted(path.to_path_buf(), e.to_string())),
+            };
+            if packet.track_id() != track_id {
+                continue;
+            }
+            if !pace(packet.data.len()) {
+                return Ok(None);
+            }
+            let decoded = match decoder.decode(&packet) {
+                Ok(decoded) => decoded,
+                Err(SymphoniaError::DecodeError(_)) => continue,
+                Err(e) => return Err(FormatError::corrupted(path.to_path_buf(), e.to_string())),
+            };
+
+            let spec = *decoded.spec();
+            let needed = decoded.capacity() * spec.channels.count();
+            if samples
+                .as_ref()
+                .is_none_or(|buffer| buffer.capacity() < needed)
+            {
+                samples = Some(SampleBuffer::new(decoded.capacity() as u64, spec));
+            }
+            let Some(buffer) = samples.as_mut() else {
+                continue;
+            };
+            buffer.copy_interleaved_ref(decoded);
+            meter
+                .get_or_insert_with(|| LoudnessMeter::new(spec.rate, spec.channels.count()))
+                .push(buffer.samples());
+        }
+
+        Ok(meter.and_then(LoudnessMeter::finish))
+    }
+
+    /// Opens the file's default track and a decoder for it
+    fn open_track(&self, path: &Path) -> FormatResult<OpenTrack> {
+        let file = File::open(path).map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                FormatError::file_not_found(path.to_path_buf())
+            } else {
+                FormatError::read_error(path.to_path_buf(), e.to_string())
+            }
+        })?;
+        let mss = MediaSourceStream::new(Box::new(file), Default::default());
+
+        let mut hint = Hint::new();
+        if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+            hint.with_extension(ext);
+        }
+
+        let format_reader = symphonia::default::get_probe()
+            .format(&hint, mss, &self.format_opts, &self.metadata_opts)
+            .map_err(|e| FormatError::probe_error(path.to_path_buf(), format!("{:?}", e)))?
+            .format;
+        let track = format_reader
+            .default_track()
+            .ok_or_else(|| FormatError::probe_error(path.to_path_buf(), "No audio tracks found"))?;
+        let track_id = track.id;
+        let decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions::default())
+            .map_err(|e| FormatError::codec_error(e.to_string()))?;
+        Ok((format_reader, track_id, decoder))
+    }
+
     /// Quick format detection without full analysis
     pub fn detect_format(&self, path: &Path) -> FormatResult<AudioFormat> {
         AudioFormat::from_path(path).ok_or_else(|| {
@@ -299,6 +497,7 @@ fn format_size(bytes: u64) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::wav::{wav_bytes, wav_from_samples};
 
     #[test]
     fn test_analyzer_creation() {
@@ -330,4 +529,90 @@ mod tests {
         assert_eq!(info.name, "FLAC");
         assert!(info.is_lossless);
     }
+
+    #[test]
+    fn test_verify_decode() {
+        let dir = tempfile::tempdir().unwrap();
+        let analyzer = AudioAnalyzer::new().unwrap();
+
+        // 0.1s of 8 kHz mono 16-bit silence
+        let wav = wav_bytes(800, 0);
+        let good = dir.path().join("good.wav");
+        std::fs::write(&good, &wav).unwrap();
+        assert!(analyzer.verify_decode(&good).is_ok());
+
+        let garbage = dir.path().join("garbage.mp3");
+        std::fs::write(&garbage, [0x5a; 4096]).unwrap();
+        assert!(analyzer.verify_decode(&garbage).is_err());
+
+        let missing = dir.path().join("missing.mp3");
+        assert!(matches!(
+            analyzer.verify_decode(&missing),
+            Err(FormatError::FileNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_waveform() {
+        let dir = tempfile::tempdir().unwrap();
+        let analyzer = AudioAnalyzer::new().unwrap();
+
+        // 8 kHz mono 16-bit: 0.5s at half scale, then 0.5s of silence
+        let samples: Vec<i16> = (0..8000)
+            .map(|i| if i < 4000 { i16::MAX / 2 } else { 0 })
+            .collect();
+        let wav = wav_from_samples(samples, 8000);
+        let path = dir.path().join("half.wav");
+        std::fs::write(&path, &wav).unwrap();
+
+        let waveform = analyzer
+            .waveform(&path, Duration::from_millis(100), || false)
+            .unwrap()
+            .unwrap();
+        assert_eq!(waveform.peaks.len(), 10);
+        assert!(waveform.peaks[..5].iter().all(|&p| (p - 0.5).abs() < 0.01));
+        assert!(waveform.peaks[5..].iter().all(|&p| p == 0.0));
+
+        let cancelled = analyzer.waveform(&path, Duration::from_millis(100), || true);
+        assert!(matches!(cancelled, Ok(None)));
+    }
+
+    #[test]
+    fn test_loudness() {
+        let dir = tempfile::tempdir().unwrap();
+        let analyzer = AudioAnalyzer::new().unwrap();
+
+        // 8 kHz mono 16-bit: 1s of a half scale square wave
+        let samples: Vec<i16> = (0..8000)
+            .map(|i| {
+                if i % 20 < 10 {
+                    i16::MAX / 2
+                } else {
+                    -i16::MAX / 2
+                }
+            })
+            .collect();
+        let wav = wav_from_samples(samples.iter().copied(), 8000);
+        let path = dir.path().join("square.wav");
+        std::fs::write(&path, &wav).unwrap();
+
+        let mut read = 0;
+        let loudness = analyzer
+            .loudness(&path, |bytes| {
+                read += bytes;
+                true
+            })
+            .unwrap()
+            .unwrap();
+        // A square wave's mean square is its peak squared: 20·log10(0.5)
+        assert!(
+            (loudness.integrated_db + 6.02).abs() < 0.05,
+            "{:?}",
+            loudness
+        );
+        assert!((loudness.peak - 0.5).abs() < 0.01);
+        assert_eq!(read, samples.len() * 2);
+
+        assert!(matches!(analyzer.loudness(&path, |_| false), Ok(None)));
+    }
 }
@@ -0,0 +1,61 @@
+//! Generated WAV files for test fixtures
+//!
+//! Tests need real audio files, but small ones, so they are written when the
+//! test runs rather than checked in. Every crate builds them here, through
+//! the `test-audio` feature, so there is one WAV writer to get right.
+
+/// Sample rate of [`wav_bytes`] files
+pub const SAMPLE_RATE: u32 = 8000;
+
+/// The 44-byte header of a mono 16-bit PCM WAV file whose data chunk holds
+/// `data_len` bytes
+pub fn wav_header(data_len: u32, sample_rate: u32) -> Vec<u8> {
+    let mut header = Vec::with_capacity(44);
+    header.extend_from_slice(b"RIFF");
+    header.extend_from_slice(&(36 + data_len).to_le_bytes());
+    header.extend_from_slice(b"WAVEfmt ");
+    header.extend_from_slice(&16u32.to_le_bytes());
+    header.extend_from_slice(&1u16.to_le_bytes());
+    header.extend_from_slice(&1u16.to_le_bytes());
+    header.extend_from_slice(&sample_rate.to_le_bytes());
+    header.extend_from_slice(&(sample_rate * 2).to_le_bytes());
+    header.extend_from_slice(&2u16.to_le_bytes());
+    header.extend_from_slice(&16u16.to_le_bytes());
+    header.extend_from_slice(b"data");
+    header.extend_from_slice(&data_len.to_le_bytes());
+    header
+}
+
+/// A mono 16-bit PCM WAV file of `samples`
+pub fn wav_from_samples(samples: impl IntoIterator<Item = i16>, sample_rate: u32) -> Vec<u8> {
+    let data: Vec<u8> = samples.into_iter().flat_map(i16::to_le_bytes).collect();
+    let mut wav = wav_header(data.len() as u32, sample_rate);
+    wav.extend_from_slice(&data);
+    wav
+}
+
+/// A mono 8 kHz WAV file of `samples` samples, every byte set to `fill`
+///
+/// Different fills give files of the same length and duration but different
+/// content.
+pub fn wav_bytes(samples: u32, fill: u8) -> Vec<u8> {
+    let sample = i16::from_le_bytes([fill, fill]);
+    wav_from_samples(std::iter::repeat_n(sample, samples as usize), SAMPLE_RATE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wav_bytes_layout() {
+        let wav = wav_bytes(800, 7);
+
+        assert_eq!(wav.len(), 44 + 1600);
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(&wav[36..40], b"data");
+        assert_eq!(u32::from_le_bytes(wav[40..44].try_into().unwrap()), 1600);
+        assert!(wav[44..].iter().all(|&byte| byte == 7));
+    }
+}
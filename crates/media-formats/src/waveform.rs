@@ -0,0 +1,81 @@
+//! Peak levels of a whole file, for finding its quiet stretches
+
+use std::time::Duration;
+
+/// Peak level of every fixed-length window of a file's audio
+#[derive(Debug, Clone, PartialEq)]
+pub struct Waveform {
+    /// Length of each window; the last one may be shorter
+    pub window: Duration,
+    /// Highest absolute sample of each window, from 0.0 to 1.0
+    pub peaks: Vec<f32>,
+}
+
+impl Waveform {
+    /// Length of audio the waveform covers, to the end of the last window
+    pub fn duration(&self) -> Duration {
+        self.window * self.peaks.len() as u32
+    }
+
+    /// Start of the window at `index`
+    pub fn time_of(&self, index: usize) -> Duration {
+        self.window * index as u32
+    }
+
+    /// Compact form for caching: the window in milliseconds, then each
+    /// peak as a 16-bit level, all little-endian
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(4 + self.peaks.len() * 2);
+        bytes.extend_from_slice(&(self.window.as_millis() as u32).to_le_bytes());
+        for peak in &self.peaks {
+            let level = (peak.clamp(0.0, 1.0) * f32::from(u16::MAX)).round() as u16;
+            bytes.extend_from_slice(&level.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Reads the form written by [`to_bytes`](Self::to_bytes)
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let (window, levels) = bytes
+            .split_first_chunk::<4>()
+            .ok_or_else(|| "waveform is truncated".to_string())?;
+        let window = u32::from_le_bytes(*window);
+        if window == 0 || levels.len() % 2 != 0 {
+            return Err("waveform is malformed".to_string());
+        }
+        Ok(Self {
+            window: Duration::from_millis(u64::from(window)),
+            peaks: levels
+                .chunks_exact(2)
+                .map(|level| {
+                    f32::from(u16::from_le_bytes([level[0], level[1]])) / f32::from(u16::MAX)
+                })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let waveform = Waveform {
+            window: Duration::from_millis(250),
+            peaks: vec![0.0, 0.5, 1.0],
+        };
+        assert_eq!(waveform.duration(), Duration::from_millis(750));
+        assert_eq!(waveform.time_of(2), Duration::from_millis(500));
+
+        let read = Waveform::from_bytes(&waveform.to_bytes()).unwrap();
+        assert_eq!(read.window, waveform.window);
+        for (read, written) in read.peaks.iter().zip(&waveform.peaks) {
+            assert!((read - written).abs() < 1e-4);
+        }
+
+        assert!(Waveform::from_bytes(&[1, 0]).is_err());
+        assert!(Waveform::from_bytes(&[0, 0, 0, 0]).is_err());
+        assert!(Waveform::from_bytes(&[250, 0, 0, 0, 1]).is_err());
+    }
+}
@@ -10,7 +10,7 @@ use tokio::time::Duration;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    env_logger::init();
+    tracing_subscriber::fmt::init();
 
     println!("🚀 StoryStream Advanced Download Manager Demo\n");
 
@@ -25,6 +25,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         bandwidth_limit: Some(2_000_000), // 2 MB/s
         chunk_size: 8192,
         verify_integrity: false,
+        pause_on_metered: true,
     };
 
     println!("📋 Configuration:");
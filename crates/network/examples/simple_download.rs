@@ -5,7 +5,7 @@ use storystream_network::{Client, DownloadManager, ProgressTracker};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    env_logger::init();
+    tracing_subscriber::fmt::init();
 
     println!("📥 Simple Download Example\n");
 
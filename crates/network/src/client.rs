@@ -2,7 +2,8 @@
 //! HTTP client wrapper with resilience
 
 use crate::error::{NetworkError, NetworkResult};
-use reqwest::{Client as ReqwestClient, Response};
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RANGE};
+use reqwest::{Client as ReqwestClient, Response, StatusCode};
 use std::time::Duration;
 use storystream_resilience::{CircuitBreaker, CircuitBreakerConfig, RetryPolicy};
 
@@ -33,6 +34,19 @@ impl Default for ClientConfig {
     }
 }
 
+/// Outcome of a conditional GET request
+#[derive(Debug)]
+pub enum ConditionalResponse {
+    /// The server reported the resource unchanged (HTTP 304)
+    NotModified,
+    /// The resource was returned, along with its new cache validators
+    Modified {
+        response: Response,
+        etag: Option<String>,
+        last_modified: Option<String>,
+    },
+}
+
 /// HTTP client with resilience features
 #[derive(Clone)]
 pub struct Client {
@@ -74,6 +88,65 @@ impl Client {
             .await
     }
 
+    /// Performs a GET request for the bytes from `offset` onwards
+    ///
+    /// Servers without range support answer `200 OK` with the full body, so
+    /// callers must check for `206 Partial Content` before appending.
+    pub async fn get_range(&self, url: &str, offset: u64) -> NetworkResult<Response> {
+        self.request(|| async {
+            self.inner
+                .get(url)
+                .header(RANGE, format!("bytes={}-", offset))
+                .send()
+                .await
+        })
+        .await
+    }
+
+    /// Performs a GET request that is skipped by the server if the resource is unchanged
+    ///
+    /// `etag` and `last_modified` are the validators returned by a previous
+    /// fetch and are sent as `If-None-Match` / `If-Modified-Since`.
+    pub async fn get_conditional(
+        &self,
+        url: &str,
+        etag: Option<&str>,
+        last_modified: Option<&str>,
+    ) -> NetworkResult<ConditionalResponse> {
+        let response = self
+            .request(|| async {
+                let mut request = self.inner.get(url);
+                if let Some(etag) = etag {
+                    request = request.header(IF_NONE_MATCH, etag);
+                }
+                if let Some(last_modified) = last_modified {
+                    request = request.header(IF_MODIFIED_SINCE, last_modified);
+                }
+                request.send().await
+            })
+            .await?;
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(ConditionalResponse::NotModified);
+        }
+
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let etag = header(ETAG);
+        let last_modified = header(LAST_MODIFIED);
+
+        Ok(ConditionalResponse::Modified {
+            response,
+            etag,
+            last_modified,
+        })
+    }
+
     /// Performs a HEAD request
     pub async fn head(&self, url: &str) -> NetworkResult<Response> {
         self.request(|| async { self.inner.head(url).send().await })
@@ -121,16 +194,17 @@ impl Client {
                         cb.record_success();
                     }
 
-                    // Check for HTTP errors
-                    if response.status().is_success() {
+                    // Check for HTTP errors (304 is only returned to conditional requests)
+                    if response.status().is_success()
+                        || response.status() == StatusCode::NOT_MODIFIED
+                    {
                         return Ok(response);
                     } else {
                         let status = response.status();
-                        let error = NetworkError::Custom(format!(
-                            "HTTP {}: {}",
-                            status.as_u16(),
-                            status.canonical_reason().unwrap_or("Unknown")
-                        ));
+                        let error = NetworkError::Status {
+                            code: status.as_u16(),
+                            reason: status.canonical_reason().unwrap_or("Unknown").to_string(),
+                        };
 
                         // Don't retry client errors (4xx)
                         if status.is_client_error() {
@@ -3,11 +3,15 @@
 
 use crate::client::Client;
 use crate::error::{NetworkError, NetworkResult};
+use crate::history::{DownloadHistory, DownloadOutcome, DownloadRecord};
 use futures::StreamExt;
-use std::collections::{HashMap, VecDeque};
+use reqwest::StatusCode;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::fs::File;
+use std::time::Instant;
+use tokio::fs::{File, OpenOptions};
 use tokio::io::AsyncWriteExt;
 use tokio::sync::{mpsc, Mutex, RwLock, Semaphore};
 use tokio::task::JoinHandle;
@@ -35,6 +39,9 @@ pub enum DownloadStatus {
 /// Progress callback type
 pub type ProgressCallback = Arc<dyn Fn(u64, Option<u64>) + Send + Sync>;
 
+/// Status callback type, called with the task ID and its new status
+pub type StatusCallback = Arc<dyn Fn(&str, &DownloadStatus) + Send + Sync>;
+
 /// Download task configuration
 #[derive(Clone)]
 pub struct DownloadTask {
@@ -44,6 +51,7 @@ pub struct DownloadTask {
     pub priority: Priority,
     pub resume_allowed: bool,
     pub progress_callback: Option<ProgressCallback>,
+    pub status_callback: Option<StatusCallback>,
 }
 
 impl DownloadTask {
@@ -55,6 +63,7 @@ impl DownloadTask {
             priority: Priority::Normal,
             resume_allowed: true,
             progress_callback: None,
+            status_callback: None,
         }
     }
 
@@ -68,6 +77,11 @@ impl DownloadTask {
         self
     }
 
+    pub fn with_status_callback(mut self, callback: StatusCallback) -> Self {
+        self.status_callback = Some(callback);
+        self
+    }
+
     pub fn with_resume(mut self, allowed: bool) -> Self {
         self.resume_allowed = allowed;
         self
@@ -83,6 +97,8 @@ pub struct DownloadManagerConfig {
     pub bandwidth_limit: Option<u64>,
     pub chunk_size: usize,
     pub verify_integrity: bool,
+    /// Hold downloads while the connection is metered
+    pub pause_on_metered: bool,
 }
 
 impl Default for DownloadManagerConfig {
@@ -94,6 +110,7 @@ impl Default for DownloadManagerConfig {
             bandwidth_limit: None,
             chunk_size: 8192,
             verify_integrity: false,
+            pause_on_metered: true,
         }
     }
 }
@@ -103,6 +120,60 @@ pub struct DownloadManagerState {
     queue: VecDeque<DownloadTask>,
     active: HashMap<String, JoinHandle<NetworkResult<u64>>>,
     status: HashMap<String, DownloadStatus>,
+    /// Every task ever enqueued, so paused tasks can be queued again
+    tasks: HashMap<String, DownloadTask>,
+    /// Tasks with a partial file to continue from
+    partial: HashSet<String>,
+    /// Tasks paused because the connection became metered
+    metered_paused: HashSet<String>,
+    /// Times each task has been started
+    attempts: HashMap<String, u32>,
+}
+
+impl DownloadManagerState {
+    fn push_by_priority(&mut self, task: DownloadTask) {
+        let insert_pos = self
+            .queue
+            .iter()
+            .position(|t| t.priority < task.priority)
+            .unwrap_or(self.queue.len());
+        self.queue.insert(insert_pos, task);
+    }
+
+    /// Records a status; the returned change must be fired after the lock is released
+    fn set_status(&mut self, id: &str, status: DownloadStatus) -> StatusChange {
+        self.status.insert(id.to_string(), status.clone());
+        StatusChange {
+            callback: self.tasks.get(id).and_then(|t| t.status_callback.clone()),
+            id: id.to_string(),
+            status,
+        }
+    }
+}
+
+/// A queued, running or paused download, as listed by [`AdvancedDownloadManager::tasks`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadInfo {
+    pub id: String,
+    pub url: String,
+    pub destination: PathBuf,
+    pub status: DownloadStatus,
+    pub attempts: u32,
+}
+
+/// Pending status notification
+struct StatusChange {
+    callback: Option<StatusCallback>,
+    id: String,
+    status: DownloadStatus,
+}
+
+impl StatusChange {
+    fn fire(self) {
+        if let Some(callback) = self.callback {
+            callback(&self.id, &self.status);
+        }
+    }
 }
 
 pub struct AdvancedDownloadManager {
@@ -112,6 +183,8 @@ pub struct AdvancedDownloadManager {
     semaphore: Arc<Semaphore>,
     shutdown_tx: mpsc::Sender<()>,
     shutdown_rx: Arc<Mutex<mpsc::Receiver<()>>>,
+    metered: AtomicBool,
+    history: Option<Arc<DownloadHistory>>,
 }
 
 impl AdvancedDownloadManager {
@@ -122,6 +195,10 @@ impl AdvancedDownloadManager {
             queue: VecDeque::new(),
             active: HashMap::new(),
             status: HashMap::new(),
+            tasks: HashMap::new(),
+            partial: HashSet::new(),
+            metered_paused: HashSet::new(),
+            attempts: HashMap::new(),
         }));
 
         let (shutdown_tx, shutdown_rx) = mpsc::channel(1);
@@ -133,15 +210,37 @@ impl AdvancedDownloadManager {
             semaphore,
             shutdown_tx,
             shutdown_rx: Arc::new(Mutex::new(shutdown_rx)),
+            metered: AtomicBool::new(false),
+            history: None,
         }
     }
 
+    /// Records every completed and failed download in `history`
+    pub fn with_history(mut self, history: DownloadHistory) -> Self {
+        self.history = Some(Arc::new(history));
+        self
+    }
+
     /// Get the current configuration
     pub fn config(&self) -> &DownloadManagerConfig {
         &self.config
     }
 
     pub async fn enqueue(&self, task: DownloadTask) -> NetworkResult<()> {
+        self.enqueue_task(task, false).await
+    }
+
+    /// Queues a task that continues the partial file at its destination
+    ///
+    /// For downloads cut off by a crash or an earlier run, which this
+    /// manager never saw paused. Without a file to continue, or when the
+    /// task does not allow resuming, the download starts from the beginning.
+    pub async fn enqueue_resumed(&self, task: DownloadTask) -> NetworkResult<()> {
+        let partial = task.resume_allowed && tokio::fs::try_exists(&task.destination).await?;
+        self.enqueue_task(task, partial).await
+    }
+
+    async fn enqueue_task(&self, task: DownloadTask, partial: bool) -> NetworkResult<()> {
         let mut state = self.state.write().await;
 
         if state.status.contains_key(&task.id) {
@@ -151,32 +250,45 @@ impl AdvancedDownloadManager {
             )));
         }
 
-        let insert_pos = state
-            .queue
-            .iter()
-            .position(|t| t.priority < task.priority)
-            .unwrap_or(state.queue.len());
-
-        state.queue.insert(insert_pos, task.clone());
-        state.status.insert(task.id.clone(), DownloadStatus::Queued);
+        state.tasks.insert(task.id.clone(), task.clone());
+        if partial {
+            state.partial.insert(task.id.clone());
+        }
+        state.push_by_priority(task.clone());
+        let change = state.set_status(&task.id, DownloadStatus::Queued);
+        drop(state);
 
+        change.fire();
         Ok(())
     }
 
+    /// Returns true while the connection is reported as metered
+    pub fn is_metered(&self) -> bool {
+        self.metered.load(Ordering::SeqCst)
+    }
+
+    /// Returns true while queued downloads are held for a metered connection
+    pub fn is_held(&self) -> bool {
+        self.config.pause_on_metered && self.metered.load(Ordering::SeqCst)
+    }
+
     pub async fn start(&self) {
         let state = Arc::clone(&self.state);
         let client = self.client.clone();
         let semaphore = Arc::clone(&self.semaphore);
+        let history = self.history.clone();
         let mut shutdown_rx = self.shutdown_rx.lock().await;
 
         loop {
             tokio::select! {
                 _ = shutdown_rx.recv() => {
-                    log::info!("Download manager shutting down");
+                    tracing::info!("Download manager shutting down");
                     break;
                 }
                 _ = async {
-                    let task = {
+                    let task = if self.is_held() {
+                        None
+                    } else {
                         let mut state = state.write().await;
                         state.queue.pop_front()
                     };
@@ -187,16 +299,63 @@ impl AdvancedDownloadManager {
                         if let Some(_permit) = permit {
                             let task_id = task.id.clone();
                             let client = client.clone();
-                            let state = Arc::clone(&state);
 
+                            // Hold the lock until the handle is recorded, so a
+                            // fast task cannot report its outcome first
+                            let mut guard = state.write().await;
+                            let offset = if guard.partial.remove(&task_id) && task.resume_allowed {
+                                tokio::fs::metadata(&task.destination)
+                                    .await
+                                    .map(|m| m.len())
+                                    .unwrap_or(0)
+                            } else {
+                                0
+                            };
+
+                            let attempts = {
+                                let count = guard.attempts.entry(task_id.clone()).or_insert(0);
+                                *count += 1;
+                                *count
+                            };
+
+                            let task_state = Arc::clone(&state);
+                            let history = history.clone();
                             let handle = tokio::spawn(async move {
-                                let result = Self::download_task(&client, &task).await;
+                                let started = Instant::now();
+                                let mut source = None;
+                                let result =
+                                    Self::download_task(&client, &task, offset, &mut source).await;
                                 drop(_permit);
+
+                                let outcome = match &result {
+                                    Ok(_) => DownloadStatus::Completed,
+                                    Err(e) => DownloadStatus::Failed(e.to_string()),
+                                };
+                                let change = {
+                                    let mut state = task_state.write().await;
+                                    state.active.remove(&task.id);
+                                    state.set_status(&task.id, outcome)
+                                };
+                                if let Some(history) = history {
+                                    let record =
+                                        Self::record_for(&task, &result, source, attempts, started)
+                                            .await;
+                                    if let Err(e) = history.record(&record).await {
+                                        tracing::warn!(
+                                            "Failed to record download {} in history: {}",
+                                            task.id,
+                                            e
+                                        );
+                                    }
+                                }
+                                change.fire();
                                 result
                             });
 
-                            state.write().await.active.insert(task_id.clone(), handle);
-                            state.write().await.status.insert(task_id, DownloadStatus::InProgress);
+                            guard.active.insert(task_id.clone(), handle);
+                            let change = guard.set_status(&task_id, DownloadStatus::InProgress);
+                            drop(guard);
+                            change.fire();
                         }
                     } else {
                         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
@@ -206,13 +365,40 @@ impl AdvancedDownloadManager {
         }
     }
 
-    async fn download_task(client: &Client, task: &DownloadTask) -> NetworkResult<u64> {
-        let response = client.get(&task.url).await?;
-        let total_size = response.content_length();
-
-        let mut file = File::create(&task.destination).await?;
+    /// Downloads a task, continuing from `offset` bytes when the server allows it
+    ///
+    /// `source` is set to the URL the response came from once the server answers.
+    #[tracing::instrument(
+        name = "download",
+        skip_all,
+        fields(id = %task.id, url = %task.url, offset, bytes = tracing::field::Empty),
+        err
+    )]
+    async fn download_task(
+        client: &Client,
+        task: &DownloadTask,
+        offset: u64,
+        source: &mut Option<String>,
+    ) -> NetworkResult<u64> {
+        let response = if offset > 0 {
+            client.get_range(&task.url, offset).await?
+        } else {
+            client.get(&task.url).await?
+        };
+        *source = Some(response.url().to_string());
+
+        let resumed = offset > 0 && response.status() == StatusCode::PARTIAL_CONTENT;
+        let (mut file, mut downloaded) = if resumed {
+            let file = OpenOptions::new()
+                .append(true)
+                .open(&task.destination)
+                .await?;
+            (file, offset)
+        } else {
+            (File::create(&task.destination).await?, 0u64)
+        };
+        let total_size = response.content_length().map(|len| len + downloaded);
         let mut stream = response.bytes_stream();
-        let mut downloaded = 0u64;
 
         while let Some(chunk_result) = stream.next().await {
             let chunk = chunk_result.map_err(NetworkError::Http)?;
@@ -225,9 +411,50 @@ impl AdvancedDownloadManager {
         }
 
         file.flush().await?;
+        tracing::Span::current().record("bytes", downloaded);
+        tracing::debug!(resumed, total_size, "Download finished");
         Ok(downloaded)
     }
 
+    /// Builds the history record of a finished attempt
+    async fn record_for(
+        task: &DownloadTask,
+        result: &NetworkResult<u64>,
+        source_url: Option<String>,
+        attempts: u32,
+        started: Instant,
+    ) -> DownloadRecord {
+        let bytes = match result {
+            Ok(bytes) => *bytes,
+            Err(_) => tokio::fs::metadata(&task.destination)
+                .await
+                .map(|m| m.len())
+                .unwrap_or(0),
+        };
+        let (outcome, error_code, error) = match result {
+            Ok(_) => (DownloadOutcome::Completed, None, None),
+            Err(e) => (
+                DownloadOutcome::Failed,
+                e.status_code(),
+                Some(e.to_string()),
+            ),
+        };
+
+        DownloadRecord {
+            task_id: task.id.clone(),
+            url: task.url.clone(),
+            source_url,
+            destination: task.destination.clone(),
+            bytes,
+            duration_ms: started.elapsed().as_millis() as u64,
+            attempts,
+            outcome,
+            error_code,
+            error,
+            finished_at: chrono::Utc::now(),
+        }
+    }
+
     pub async fn cancel(&self, id: &str) -> NetworkResult<()> {
         let mut state = self.state.write().await;
         state.queue.retain(|t| t.id != id);
@@ -236,12 +463,214 @@ impl AdvancedDownloadManager {
             handle.abort();
         }
 
-        state
-            .status
-            .insert(id.to_string(), DownloadStatus::Cancelled);
+        state.partial.remove(id);
+        state.metered_paused.remove(id);
+        let change = state.set_status(id, DownloadStatus::Cancelled);
+        drop(state);
+
+        change.fire();
+        Ok(())
+    }
+
+    /// Pauses a queued or running download, keeping any partial file
+    pub async fn pause(&self, id: &str) -> NetworkResult<()> {
+        let mut state = self.state.write().await;
+        match state.status.get(id) {
+            Some(DownloadStatus::Queued) => state.queue.retain(|t| t.id != id),
+            Some(DownloadStatus::InProgress) => {
+                if let Some(handle) = state.active.remove(id) {
+                    handle.abort();
+                }
+                state.partial.insert(id.to_string());
+            }
+            Some(status) => {
+                return Err(NetworkError::Custom(format!(
+                    "Download {} cannot be paused while {:?}",
+                    id, status
+                )))
+            }
+            None => return Err(NetworkError::Custom(format!("Unknown download {}", id))),
+        }
+
+        state.metered_paused.remove(id);
+        let change = state.set_status(id, DownloadStatus::Paused);
+        drop(state);
+
+        change.fire();
+        Ok(())
+    }
+
+    /// Queues a paused download again
+    pub async fn resume(&self, id: &str) -> NetworkResult<()> {
+        let mut state = self.state.write().await;
+        match state.status.get(id) {
+            Some(DownloadStatus::Paused) => {}
+            Some(status) => {
+                return Err(NetworkError::Custom(format!(
+                    "Download {} is not paused ({:?})",
+                    id, status
+                )))
+            }
+            None => return Err(NetworkError::Custom(format!("Unknown download {}", id))),
+        }
+
+        let task = state
+            .tasks
+            .get(id)
+            .cloned()
+            .ok_or_else(|| NetworkError::Custom(format!("Unknown download {}", id)))?;
+        state.metered_paused.remove(id);
+        state.push_by_priority(task);
+        let change = state.set_status(id, DownloadStatus::Queued);
+        drop(state);
+
+        change.fire();
+        Ok(())
+    }
+
+    /// Reports whether the connection is metered
+    ///
+    /// With `pause_on_metered`, running downloads are paused and the queue is
+    /// held until the connection is unmetered again; downloads paused this
+    /// way resume automatically, while ones paused by the user stay paused.
+    pub async fn set_metered(&self, metered: bool) {
+        self.metered.store(metered, Ordering::SeqCst);
+        if !self.config.pause_on_metered {
+            return;
+        }
+
+        if metered {
+            let running: Vec<String> = self.state.read().await.active.keys().cloned().collect();
+            for id in running {
+                if self.pause(&id).await.is_ok() {
+                    self.state.write().await.metered_paused.insert(id);
+                }
+            }
+        } else {
+            let paused: Vec<String> = self.state.write().await.metered_paused.drain().collect();
+            for id in paused {
+                if let Err(e) = self.resume(&id).await {
+                    tracing::warn!("Failed to resume download {}: {}", id, e);
+                }
+            }
+        }
+    }
+
+    /// Queues a failed download again
+    ///
+    /// Tasks from an earlier run are rebuilt from their last history record,
+    /// so a download that failed overnight can be retried after a restart.
+    /// A partial file is continued when the task allows resuming.
+    pub async fn retry_failed(&self, task_id: &str) -> NetworkResult<()> {
+        let known = self.state.read().await.tasks.get(task_id).cloned();
+        let task = match known {
+            Some(task) => {
+                match self.get_status(task_id).await {
+                    Some(DownloadStatus::Failed(_)) => {}
+                    status => {
+                        return Err(NetworkError::Custom(format!(
+                            "Download {} has not failed ({:?})",
+                            task_id, status
+                        )))
+                    }
+                }
+                task
+            }
+            None => {
+                let history = self
+                    .history
+                    .as_ref()
+                    .ok_or_else(|| NetworkError::Custom(format!("Unknown download {}", task_id)))?;
+                match history.latest(task_id).await? {
+                    Some(record) if record.is_failed() => {
+                        DownloadTask::new(record.task_id, record.url, record.destination)
+                    }
+                    Some(_) => {
+                        return Err(NetworkError::Custom(format!(
+                            "Download {} has already completed",
+                            task_id
+                        )))
+                    }
+                    None => {
+                        return Err(NetworkError::Custom(format!(
+                            "Unknown download {}",
+                            task_id
+                        )))
+                    }
+                }
+            }
+        };
+
+        let partial = task.resume_allowed && tokio::fs::try_exists(&task.destination).await?;
+        let mut state = self.state.write().await;
+        state.tasks.insert(task.id.clone(), task.clone());
+        if partial {
+            state.partial.insert(task.id.clone());
+        }
+        state.push_by_priority(task);
+        let change = state.set_status(task_id, DownloadStatus::Queued);
+        drop(state);
+
+        change.fire();
         Ok(())
     }
 
+    /// Returns the recorded history, most recent first
+    ///
+    /// Empty when the manager was created without a history.
+    pub async fn history(&self, limit: usize) -> NetworkResult<Vec<DownloadRecord>> {
+        match &self.history {
+            Some(history) => history.recent(limit).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Removes history records older than `max_age`, returning how many were removed
+    pub async fn prune_history(&self, max_age: chrono::Duration) -> NetworkResult<usize> {
+        match &self.history {
+            Some(history) => history.prune(max_age).await,
+            None => Ok(0),
+        }
+    }
+
+    /// Lists running downloads, then queued ones in the order they will start, then paused ones
+    pub async fn tasks(&self) -> Vec<DownloadInfo> {
+        let state = self.state.read().await;
+        let info = |task: &DownloadTask| DownloadInfo {
+            id: task.id.clone(),
+            url: task.url.clone(),
+            destination: task.destination.clone(),
+            status: state
+                .status
+                .get(&task.id)
+                .cloned()
+                .unwrap_or(DownloadStatus::Queued),
+            attempts: state.attempts.get(&task.id).copied().unwrap_or(0),
+        };
+
+        let mut running: Vec<DownloadInfo> = state
+            .active
+            .keys()
+            .filter_map(|id| state.tasks.get(id))
+            .map(info)
+            .collect();
+        running.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut paused: Vec<DownloadInfo> = state
+            .tasks
+            .values()
+            .filter(|t| state.status.get(&t.id) == Some(&DownloadStatus::Paused))
+            .map(info)
+            .collect();
+        paused.sort_by(|a, b| a.id.cmp(&b.id));
+
+        running
+            .into_iter()
+            .chain(state.queue.iter().map(info))
+            .chain(paused)
+            .collect()
+    }
+
     pub async fn get_status(&self, id: &str) -> Option<DownloadStatus> {
         let state = self.state.read().await;
         state.status.get(id).cloned()
@@ -327,14 +756,14 @@ mod tests {
             "https://example.com/low".to_string(),
             PathBuf::from("/tmp/low"),
         )
-            .with_priority(Priority::Low);
+        .with_priority(Priority::Low);
 
         let high = DownloadTask::new(
             "high".to_string(),
             "https://example.com/high".to_string(),
             PathBuf::from("/tmp/high"),
         )
-            .with_priority(Priority::High);
+        .with_priority(Priority::High);
 
         manager.enqueue(low).await.unwrap();
         manager.enqueue(high).await.unwrap();
@@ -356,4 +785,192 @@ mod tests {
 
         assert_eq!(manager.config().max_concurrent, 5);
     }
-}
\ No newline at end of file
+
+    fn test_task(id: &str, priority: Priority) -> DownloadTask {
+        DownloadTask::new(
+            id.to_string(),
+            format!("https://example.com/{}", id),
+            PathBuf::from(format!("/tmp/{}", id)),
+        )
+        .with_priority(priority)
+    }
+
+    #[tokio::test]
+    async fn test_pause_and_resume_queued_task() {
+        let manager = AdvancedDownloadManager::new(Client::new().unwrap(), Default::default());
+        manager
+            .enqueue(test_task("a", Priority::Normal))
+            .await
+            .unwrap();
+        manager
+            .enqueue(test_task("b", Priority::High))
+            .await
+            .unwrap();
+
+        manager.pause("b").await.unwrap();
+        assert_eq!(manager.get_status("b").await, Some(DownloadStatus::Paused));
+        assert_eq!(manager.queue_length().await, 1);
+        assert!(manager.pause("b").await.is_err());
+
+        manager.resume("b").await.unwrap();
+        assert_eq!(manager.get_status("b").await, Some(DownloadStatus::Queued));
+        assert_eq!(manager.state.read().await.queue[0].id, "b");
+        assert!(manager.resume("b").await.is_err());
+        assert!(manager.resume("missing").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_resumed_continues_partial_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = AdvancedDownloadManager::new(Client::new().unwrap(), Default::default());
+        let task = |id: &str| {
+            DownloadTask::new(
+                id.to_string(),
+                format!("https://example.com/{}", id),
+                dir.path().join(id),
+            )
+        };
+        std::fs::write(dir.path().join("partial"), b"half").unwrap();
+
+        manager.enqueue_resumed(task("partial")).await.unwrap();
+        manager.enqueue_resumed(task("missing")).await.unwrap();
+        manager
+            .enqueue_resumed(task("none").with_resume(false))
+            .await
+            .unwrap();
+        assert!(manager.enqueue_resumed(task("partial")).await.is_err());
+
+        let state = manager.state.read().await;
+        assert_eq!(
+            state.partial.iter().collect::<Vec<_>>(),
+            vec![&"partial".to_string()]
+        );
+        assert_eq!(state.queue.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_status_callback_sees_transitions() {
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = Arc::clone(&seen);
+        let callback: StatusCallback = Arc::new(move |id: &str, status: &DownloadStatus| {
+            recorder
+                .lock()
+                .unwrap()
+                .push((id.to_string(), status.clone()));
+        });
+
+        let manager = AdvancedDownloadManager::new(Client::new().unwrap(), Default::default());
+        let task = test_task("a", Priority::Normal).with_status_callback(callback);
+        manager.enqueue(task).await.unwrap();
+        manager.pause("a").await.unwrap();
+        manager.cancel("a").await.unwrap();
+
+        let statuses: Vec<_> = seen
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, s)| s.clone())
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                DownloadStatus::Queued,
+                DownloadStatus::Paused,
+                DownloadStatus::Cancelled
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_metered_hold_respects_config() {
+        let manager = AdvancedDownloadManager::new(Client::new().unwrap(), Default::default());
+        manager.set_metered(true).await;
+        assert!(manager.is_held());
+        manager.set_metered(false).await;
+        assert!(!manager.is_held());
+
+        let config = DownloadManagerConfig {
+            pause_on_metered: false,
+            ..Default::default()
+        };
+        let manager = AdvancedDownloadManager::new(Client::new().unwrap(), config);
+        manager.set_metered(true).await;
+        assert!(!manager.is_held());
+        assert!(manager.is_metered());
+    }
+
+    /// Serves `503 Service Unavailable` to every request on a local port
+    fn unavailable_server() -> String {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request);
+                let _ = stream.write_all(
+                    b"HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                );
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_failed_download_is_recorded_and_retried() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = DownloadHistory::new(dir.path().join("history.jsonl")).unwrap();
+        let client = Client::with_config(crate::client::ClientConfig {
+            retry_policy: None,
+            circuit_breaker_config: None,
+            ..Default::default()
+        })
+        .unwrap();
+        let manager = Arc::new(
+            AdvancedDownloadManager::new(client, Default::default()).with_history(history),
+        );
+
+        let url = format!("{}/book.mp3", unavailable_server());
+        let task = DownloadTask::new("a".to_string(), url.clone(), dir.path().join("book.mp3"));
+        manager.enqueue(task).await.unwrap();
+        assert_eq!(manager.tasks().await[0].status, DownloadStatus::Queued);
+
+        let runner = Arc::clone(&manager);
+        let handle = tokio::spawn(async move { runner.start().await });
+        let failed =
+            |status: Option<DownloadStatus>| matches!(status, Some(DownloadStatus::Failed(_)));
+        for _ in 0..100 {
+            if failed(manager.get_status("a").await)
+                && !manager.history(1).await.unwrap().is_empty()
+            {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+
+        let records = manager.history(10).await.unwrap();
+        assert_eq!(records.len(), 1);
+        assert!(records[0].is_failed());
+        assert_eq!(records[0].url, url);
+        assert_eq!(records[0].error_code, Some(503));
+        assert_eq!(records[0].attempts, 1);
+        assert!(records[0].source_url.is_none());
+
+        manager.shutdown().await.unwrap();
+        handle.await.unwrap();
+
+        manager.retry_failed("a").await.unwrap();
+        assert_eq!(manager.get_status("a").await, Some(DownloadStatus::Queued));
+        assert!(manager.retry_failed("a").await.is_err());
+        assert_eq!(manager.tasks().await.len(), 1);
+
+        // A new manager picks the failure up from the history
+        let history = DownloadHistory::new(dir.path().join("history.jsonl")).unwrap();
+        let restarted = AdvancedDownloadManager::new(Client::new().unwrap(), Default::default())
+            .with_history(history);
+        restarted.retry_failed("a").await.unwrap();
+        assert_eq!(restarted.tasks().await[0].url, url);
+        assert!(restarted.retry_failed("missing").await.is_err());
+    }
+}
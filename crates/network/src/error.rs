@@ -17,6 +17,10 @@ pub enum NetworkError {
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
+    /// Server answered with an error status
+    #[error("HTTP {code}: {reason}")]
+    Status { code: u16, reason: String },
+
     /// Invalid URL
     #[error("Invalid URL: {0}")]
     InvalidUrl(String),
@@ -51,24 +55,23 @@ impl NetworkError {
         )
     }
 
+    /// Returns the HTTP status code behind the error, if there was one
+    pub fn status_code(&self) -> Option<u16> {
+        match self {
+            NetworkError::Status { code, .. } => Some(*code),
+            NetworkError::Http(e) => e.status().map(|status| status.as_u16()),
+            _ => None,
+        }
+    }
+
     /// Returns true if the error is a client error (4xx)
     pub fn is_client_error(&self) -> bool {
-        if let NetworkError::Http(e) = self {
-            if let Some(status) = e.status() {
-                return status.is_client_error();
-            }
-        }
-        false
+        matches!(self.status_code(), Some(400..=499))
     }
 
     /// Returns true if the error is a server error (5xx)
     pub fn is_server_error(&self) -> bool {
-        if let NetworkError::Http(e) = self {
-            if let Some(status) = e.status() {
-                return status.is_server_error();
-            }
-        }
-        false
+        matches!(self.status_code(), Some(500..=599))
     }
 }
 
@@ -94,6 +97,19 @@ mod tests {
         assert!(err.to_string().contains("unavailable"));
     }
 
+    #[test]
+    fn test_status_error() {
+        let err = NetworkError::Status {
+            code: 404,
+            reason: "Not Found".to_string(),
+        };
+        assert_eq!(err.to_string(), "HTTP 404: Not Found");
+        assert_eq!(err.status_code(), Some(404));
+        assert!(err.is_client_error());
+        assert!(!err.is_server_error());
+        assert_eq!(NetworkError::Timeout.status_code(), None);
+    }
+
     #[test]
     fn test_retryable_errors() {
         assert!(NetworkError::Timeout.is_retryable());
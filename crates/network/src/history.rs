@@ -0,0 +1,275 @@
+// crates/network/src/history.rs
+//! Persistent record of finished downloads
+//!
+//! Every completed or failed download is appended as one JSON line, so the
+//! reason a download failed overnight is still there in the morning.
+
+use crate::error::{NetworkError, NetworkResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::fs::{self, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+/// How a download ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadOutcome {
+    Completed,
+    Failed,
+}
+
+/// A finished download attempt
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DownloadRecord {
+    pub task_id: String,
+    /// URL the download was queued with
+    pub url: String,
+    /// URL the data actually came from after redirects, i.e. the mirror used
+    pub source_url: Option<String>,
+    pub destination: PathBuf,
+    /// Bytes on disk when the attempt ended
+    pub bytes: u64,
+    /// How long the last attempt ran
+    pub duration_ms: u64,
+    /// Number of times the task was started, including resumes and retries
+    pub attempts: u32,
+    pub outcome: DownloadOutcome,
+    /// HTTP status of the failure, when the server answered with one
+    pub error_code: Option<u16>,
+    pub error: Option<String>,
+    pub finished_at: DateTime<Utc>,
+}
+
+impl DownloadRecord {
+    /// Returns true if the download failed
+    pub fn is_failed(&self) -> bool {
+        self.outcome == DownloadOutcome::Failed
+    }
+}
+
+/// Download history kept in a JSON-lines file
+pub struct DownloadHistory {
+    path: PathBuf,
+    /// Serializes writers within this process
+    lock: Mutex<()>,
+}
+
+impl DownloadHistory {
+    /// File name used for the history in an application data directory
+    pub const FILE_NAME: &'static str = "download_history.jsonl";
+
+    /// Opens the history at `path`, creating its directory if needed
+    pub fn new(path: impl AsRef<Path>) -> NetworkResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        Ok(Self {
+            path,
+            lock: Mutex::new(()),
+        })
+    }
+
+    /// Path of the history file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends a record
+    pub async fn record(&self, record: &DownloadRecord) -> NetworkResult<()> {
+        let mut line =
+            serde_json::to_string(record).map_err(|e| NetworkError::Custom(e.to_string()))?;
+        line.push('\n');
+
+        let _guard = self.lock.lock().await;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(line.as_bytes()).await?;
+        file.flush().await?;
+        Ok(())
+    }
+
+    /// Returns up to `limit` records, most recent first
+    pub async fn recent(&self, limit: usize) -> NetworkResult<Vec<DownloadRecord>> {
+        let _guard = self.lock.lock().await;
+        let mut records = self.read_all().await?;
+        records.reverse();
+        records.truncate(limit);
+        Ok(records)
+    }
+
+    /// Returns the most recent record of a task
+    pub async fn latest(&self, task_id: &str) -> NetworkResult<Option<DownloadRecord>> {
+        let _guard = self.lock.lock().await;
+        Ok(self
+            .read_all()
+            .await?
+            .into_iter()
+            .rev()
+            .find(|r| r.task_id == task_id))
+    }
+
+    /// Files of downloads whose last attempt failed
+    ///
+    /// A retry continues such a file from where it stopped, so it is worth
+    /// keeping however old it gets.
+    pub async fn resumable(&self) -> NetworkResult<Vec<PathBuf>> {
+        let _guard = self.lock.lock().await;
+        let mut latest = HashMap::new();
+        for record in self.read_all().await? {
+            latest.insert(record.task_id.clone(), record);
+        }
+        Ok(latest
+            .into_values()
+            .filter(DownloadRecord::is_failed)
+            .map(|record| record.destination)
+            .collect())
+    }
+
+    /// Removes records that finished more than `max_age` ago
+    ///
+    /// Returns the number of records removed.
+    pub async fn prune(&self, max_age: chrono::Duration) -> NetworkResult<usize> {
+        let cutoff = Utc::now() - max_age;
+
+        let _guard = self.lock.lock().await;
+        let records = self.read_all().await?;
+        let (kept, removed): (Vec<_>, Vec<_>) =
+            records.into_iter().partition(|r| r.finished_at >= cutoff);
+        if removed.is_empty() {
+            return Ok(0);
+        }
+
+        let mut text = String::new();
+        for record in &kept {
+            text.push_str(
+                &serde_json::to_string(record).map_err(|e| NetworkError::Custom(e.to_string()))?,
+            );
+            text.push('\n');
+        }
+        let temp = self.path.with_extension("jsonl.tmp");
+        fs::write(&temp, text).await?;
+        fs::rename(&temp, &self.path).await?;
+
+        Ok(removed.len())
+    }
+
+    /// Reads every record, oldest first; damaged lines are skipped
+    async fn read_all(&self) -> NetworkResult<Vec<DownloadRecord>> {
+        let text = match fs::read_to_string(&self.path).await {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(record) => Some(record),
+                Err(e) => {
+                    tracing::warn!("Skipping damaged download history entry: {}", e);
+                    None
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(task_id: &str, outcome: DownloadOutcome, age_days: i64) -> DownloadRecord {
+        DownloadRecord {
+            task_id: task_id.to_string(),
+            url: format!("https://example.com/{}.mp3", task_id),
+            source_url: None,
+            destination: PathBuf::from(format!("/tmp/{}.mp3", task_id)),
+            bytes: 1024,
+            duration_ms: 500,
+            attempts: 1,
+            outcome,
+            error_code: (outcome == DownloadOutcome::Failed).then_some(503),
+            error: (outcome == DownloadOutcome::Failed).then(|| "HTTP 503".to_string()),
+            finished_at: Utc::now() - chrono::Duration::days(age_days),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_history_round_trip_and_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = DownloadHistory::new(dir.path().join("data").join("history.jsonl")).unwrap();
+        assert!(history.recent(10).await.unwrap().is_empty());
+
+        history
+            .record(&record("old", DownloadOutcome::Completed, 40))
+            .await
+            .unwrap();
+        history
+            .record(&record("a", DownloadOutcome::Failed, 1))
+            .await
+            .unwrap();
+        history
+            .record(&record("a", DownloadOutcome::Completed, 0))
+            .await
+            .unwrap();
+
+        let recent = history.recent(2).await.unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].outcome, DownloadOutcome::Completed);
+        assert!(recent[1].is_failed());
+        assert_eq!(recent[1].error_code, Some(503));
+
+        let latest = history.latest("a").await.unwrap().unwrap();
+        assert!(!latest.is_failed());
+        assert!(history.latest("missing").await.unwrap().is_none());
+
+        assert_eq!(history.prune(chrono::Duration::days(30)).await.unwrap(), 1);
+        assert_eq!(history.prune(chrono::Duration::days(30)).await.unwrap(), 0);
+        assert_eq!(history.recent(10).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_resumable_follows_the_latest_attempt() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = DownloadHistory::new(dir.path().join("history.jsonl")).unwrap();
+        for (task, outcome) in [
+            ("retried", DownloadOutcome::Failed),
+            ("retried", DownloadOutcome::Completed),
+            ("done", DownloadOutcome::Completed),
+            ("broken", DownloadOutcome::Completed),
+            ("broken", DownloadOutcome::Failed),
+        ] {
+            history.record(&record(task, outcome, 0)).await.unwrap();
+        }
+
+        assert_eq!(
+            history.resumable().await.unwrap(),
+            vec![PathBuf::from("/tmp/broken.mp3")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_damaged_lines_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.jsonl");
+        let history = DownloadHistory::new(&path).unwrap();
+        history
+            .record(&record("a", DownloadOutcome::Failed, 0))
+            .await
+            .unwrap();
+        let mut text = std::fs::read_to_string(&path).unwrap();
+        text.push_str("{not json\n");
+        std::fs::write(&path, text).unwrap();
+
+        assert_eq!(history.recent(10).await.unwrap().len(), 1);
+    }
+}
@@ -6,18 +6,20 @@ mod connectivity;
 mod download;
 mod download_manager;
 mod error;
+mod history;
 mod progress;
 mod resume;
 mod throttle;
 
-pub use client::{Client, ClientConfig};
+pub use client::{Client, ClientConfig, ConditionalResponse};
 pub use connectivity::ConnectivityChecker;
 pub use download::DownloadManager;
 pub use download_manager::{
-    AdvancedDownloadManager, DownloadManagerConfig, DownloadStatus, DownloadTask, Priority,
-    ProgressCallback,
+    AdvancedDownloadManager, DownloadInfo, DownloadManagerConfig, DownloadStatus, DownloadTask,
+    Priority, ProgressCallback, StatusCallback,
 };
 pub use error::{NetworkError, NetworkResult};
+pub use history::{DownloadHistory, DownloadOutcome, DownloadRecord};
 pub use progress::{DownloadProgress, ProgressTracker};
 pub use resume::{can_resume, ResumeInfo, ResumeManager};
 pub use throttle::{AdaptiveThrottle, BandwidthThrottle};
@@ -3,7 +3,8 @@
 
 use std::time::Duration;
 use storystream_network::{
-    Client, ClientConfig, ConnectivityChecker, DownloadManager, ProgressTracker,
+    Client, ClientConfig, ConditionalResponse, ConnectivityChecker, DownloadManager,
+    ProgressTracker,
 };
 
 #[tokio::test]
@@ -110,3 +111,77 @@ fn test_progress_tracker_thread_safety() {
     let progress = tracker.get().expect("Failed to get progress");
     assert_eq!(progress.downloaded_bytes, 5000);
 }
+
+/// Serves a single canned HTTP response and returns the request it received
+fn serve_once(response: &'static str) -> (String, std::thread::JoinHandle<String>) {
+    use std::io::{Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("Failed to bind");
+    let url = format!("http://{}/feed.xml", listener.local_addr().unwrap());
+
+    let handle = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().expect("Failed to accept");
+        let mut buf = [0u8; 4096];
+        let n = stream.read(&mut buf).expect("Failed to read request");
+        stream
+            .write_all(response.as_bytes())
+            .expect("Failed to write response");
+        String::from_utf8_lossy(&buf[..n]).to_string()
+    });
+
+    (url, handle)
+}
+
+fn no_retry_client() -> Client {
+    Client::with_config(ClientConfig {
+        retry_policy: None,
+        circuit_breaker_config: None,
+        ..ClientConfig::default()
+    })
+    .expect("Failed to create client")
+}
+
+#[tokio::test]
+async fn test_conditional_get_not_modified() {
+    let (url, server) =
+        serve_once("HTTP/1.1 304 Not Modified\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+
+    let result = no_retry_client()
+        .get_conditional(&url, Some("\"v1\""), Some("Tue, 01 Jan 2030 00:00:00 GMT"))
+        .await
+        .expect("Conditional request failed");
+
+    assert!(matches!(result, ConditionalResponse::NotModified));
+
+    let request = server.join().unwrap().to_lowercase();
+    assert!(request.contains("if-none-match: \"v1\""));
+    assert!(request.contains("if-modified-since: tue, 01 jan 2030 00:00:00 gmt"));
+}
+
+#[tokio::test]
+async fn test_conditional_get_modified_returns_validators() {
+    let (url, server) = serve_once(
+        "HTTP/1.1 200 OK\r\nETag: \"v2\"\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello",
+    );
+
+    let result = no_retry_client()
+        .get_conditional(&url, None, None)
+        .await
+        .expect("Conditional request failed");
+
+    match result {
+        ConditionalResponse::Modified {
+            response,
+            etag,
+            last_modified,
+        } => {
+            assert_eq!(etag.as_deref(), Some("\"v2\""));
+            assert!(last_modified.is_none());
+            assert_eq!(response.text().await.unwrap(), "hello");
+        }
+        ConditionalResponse::NotModified => panic!("Expected a modified response"),
+    }
+
+    let request = server.join().unwrap().to_lowercase();
+    assert!(!request.contains("if-none-match"));
+}
@@ -0,0 +1,144 @@
+// crates/resilience/src/bulkhead.rs
+//! Bulkhead (concurrency limit) implementation
+
+use crate::error::{ResilienceError, ResilienceResult};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, TryAcquireError};
+
+/// Caps how many operations run at once
+///
+/// Closing the bulkhead turns it into a cancellation point: operations
+/// already running finish, while waiting and new ones fail with
+/// [`ResilienceError::Cancelled`].
+#[derive(Debug, Clone)]
+pub struct Bulkhead {
+    max_concurrent: usize,
+    semaphore: Arc<Semaphore>,
+}
+
+/// A slot in a [`Bulkhead`], released when dropped
+#[derive(Debug)]
+pub struct BulkheadPermit {
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Bulkhead {
+    /// Creates a bulkhead allowing `max_concurrent` operations (at least one)
+    pub fn new(max_concurrent: usize) -> Self {
+        let max_concurrent = max_concurrent.max(1);
+        Self {
+            max_concurrent,
+            semaphore: Arc::new(Semaphore::new(max_concurrent)),
+        }
+    }
+
+    /// Gets the concurrency limit
+    pub fn max_concurrent(&self) -> usize {
+        self.max_concurrent
+    }
+
+    /// Gets the number of free slots
+    pub fn available(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+
+    /// Waits for a free slot
+    pub async fn acquire(&self) -> ResilienceResult<BulkheadPermit> {
+        Arc::clone(&self.semaphore)
+            .acquire_owned()
+            .await
+            .map(|permit| BulkheadPermit { _permit: permit })
+            .map_err(|_| ResilienceError::Cancelled)
+    }
+
+    /// Takes a free slot without waiting
+    pub fn try_acquire(&self) -> ResilienceResult<BulkheadPermit> {
+        match Arc::clone(&self.semaphore).try_acquire_owned() {
+            Ok(permit) => Ok(BulkheadPermit { _permit: permit }),
+            Err(TryAcquireError::NoPermits) => Err(ResilienceError::BulkheadFull {
+                limit: self.max_concurrent,
+            }),
+            Err(TryAcquireError::Closed) => Err(ResilienceError::Cancelled),
+        }
+    }
+
+    /// Runs `operation` once a slot is free
+    pub async fn execute<F, Fut, T>(&self, operation: F) -> ResilienceResult<T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let _permit = self.acquire().await?;
+        Ok(operation().await)
+    }
+
+    /// Stops handing out slots
+    pub fn close(&self) {
+        self.semaphore.close();
+    }
+
+    /// Checks whether the bulkhead has been closed
+    pub fn is_closed(&self) -> bool {
+        self.semaphore.is_closed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bulkhead_limits_slots() {
+        let bulkhead = Bulkhead::new(2);
+
+        let first = bulkhead.try_acquire().unwrap();
+        let _second = bulkhead.try_acquire().unwrap();
+        assert_eq!(bulkhead.available(), 0);
+        assert!(matches!(
+            bulkhead.try_acquire(),
+            Err(ResilienceError::BulkheadFull { limit: 2 })
+        ));
+
+        drop(first);
+        assert_eq!(bulkhead.available(), 1);
+        assert!(bulkhead.try_acquire().is_ok());
+    }
+
+    #[test]
+    fn test_bulkhead_minimum_one_slot() {
+        assert_eq!(Bulkhead::new(0).max_concurrent(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_bulkhead_execute() {
+        let bulkhead = Bulkhead::new(1);
+        let result = bulkhead.execute(|| async { 42 }).await;
+        assert_eq!(result.ok(), Some(42));
+        assert_eq!(bulkhead.available(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_closed_bulkhead_cancels() {
+        let bulkhead = Bulkhead::new(1);
+        let held = bulkhead.try_acquire().unwrap();
+
+        let waiting = {
+            let bulkhead = bulkhead.clone();
+            tokio::spawn(async move { bulkhead.acquire().await })
+        };
+        tokio::task::yield_now().await;
+        bulkhead.close();
+
+        assert!(bulkhead.is_closed());
+        assert!(matches!(
+            waiting.await.unwrap(),
+            Err(ResilienceError::Cancelled)
+        ));
+        assert!(matches!(
+            bulkhead.try_acquire(),
+            Err(ResilienceError::Cancelled)
+        ));
+        drop(held);
+    }
+}
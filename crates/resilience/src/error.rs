@@ -33,6 +33,10 @@ pub enum ResilienceError {
         window: std::time::Duration,
     },
 
+    /// Bulkhead has no free slot
+    #[error("Bulkhead is full (limit: {limit} concurrent)")]
+    BulkheadFull { limit: usize },
+
     /// Operation was cancelled
     #[error("Operation was cancelled")]
     Cancelled,
@@ -82,4 +86,11 @@ mod tests {
         assert!(err.to_string().contains("Rate limit"));
         assert!(err.to_string().contains("100"));
     }
+
+    #[test]
+    fn test_bulkhead_full_error() {
+        let err = ResilienceError::BulkheadFull { limit: 4 };
+        assert!(err.to_string().contains("Bulkhead"));
+        assert!(err.to_string().contains("4"));
+    }
 }
@@ -6,6 +6,7 @@
 //! - Circuit breaker
 //! - Timeout handling
 //! - Rate limiting
+//! - Bulkheads (concurrency limits)
 //!
 //! # Example
 //!
@@ -22,12 +23,14 @@
 //! let cb = CircuitBreaker::new(cb_config);
 //! ```
 
+mod bulkhead;
 mod circuit_breaker;
 mod error;
 mod rate_limiter;
 mod retry;
 mod timeout;
 
+pub use bulkhead::{Bulkhead, BulkheadPermit};
 pub use circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitState};
 pub use error::{ResilienceError, ResilienceResult};
 pub use rate_limiter::RateLimiter;
@@ -46,5 +49,6 @@ mod tests {
         let _: CircuitBreaker = CircuitBreaker::new(CircuitBreakerConfig::default());
         let _: RateLimiter = RateLimiter::new(100, std::time::Duration::from_secs(1));
         let _: Timeout = Timeout::new(std::time::Duration::from_secs(5));
+        let _: Bulkhead = Bulkhead::new(4);
     }
 }
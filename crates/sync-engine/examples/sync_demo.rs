@@ -65,6 +65,7 @@ fn demo_conflict_resolution() {
 
     let config = SyncConfig {
         device_id: DeviceId::from_string("laptop".to_string()),
+        device_name: Some("Laptop".to_string()),
         conflict_resolution: ConflictResolution::UseNewest,
         auto_sync: false,
     };
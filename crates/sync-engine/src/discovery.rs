@@ -0,0 +1,185 @@
+// crates/sync-engine/src/discovery.rs
+//! Finding other devices on the local network
+//!
+//! Each device advertises a `_storystream._tcp` service over mDNS/DNS-SD,
+//! with its device ID, name and protocol version in the TXT record and the
+//! port of its [`SyncServer`](crate::SyncServer).
+
+use crate::engine::SyncEngine;
+use crate::error::{SyncError, SyncResult};
+use crate::types::DeviceId;
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use std::net::{IpAddr, SocketAddr, TcpStream};
+use std::time::{Duration, Instant};
+
+/// DNS-SD service type devices advertise
+pub const SERVICE_TYPE: &str = "_storystream._tcp.local.";
+
+/// Version of the pairing and sync messages this device speaks
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// How long to wait for a peer to accept a connection when checking it
+const PROBE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Another device found on the local network
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Peer {
+    pub device_id: DeviceId,
+    pub name: Option<String>,
+    pub protocol_version: u32,
+    /// Where its sync server answers
+    pub address: SocketAddr,
+}
+
+impl Peer {
+    /// Name to show for the peer
+    pub fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or(self.device_id.as_str())
+    }
+
+    /// Whether the peer speaks this device's protocol
+    pub fn is_compatible(&self) -> bool {
+        self.protocol_version == PROTOCOL_VERSION
+    }
+}
+
+/// This device's service on the network; withdrawn when dropped
+pub struct Advertisement {
+    daemon: ServiceDaemon,
+    fullname: String,
+}
+
+impl Drop for Advertisement {
+    fn drop(&mut self) {
+        if let Err(e) = self.daemon.unregister(&self.fullname) {
+            tracing::debug!("Could not withdraw {}: {}", self.fullname, e);
+        }
+        let _ = self.daemon.shutdown();
+    }
+}
+
+impl SyncEngine {
+    /// Advertises this device with its sync server on `port`
+    pub fn advertise(&self, port: u16) -> SyncResult<Advertisement> {
+        let id = self.device_id().as_str();
+        let version = PROTOCOL_VERSION.to_string();
+        let mut properties = vec![("id", id), ("version", version.as_str())];
+        if let Some(name) = self.device_name() {
+            properties.push(("name", name));
+        }
+
+        let host = format!("{}.local.", id);
+        let info = ServiceInfo::new(SERVICE_TYPE, id, &host, "", port, &properties[..])
+            .map_err(network_error)?
+            .enable_addr_auto();
+        let fullname = info.get_fullname().to_string();
+
+        let daemon = ServiceDaemon::new().map_err(network_error)?;
+        daemon.register(info).map_err(network_error)?;
+        tracing::info!("Advertising {} on port {}", fullname, port);
+        Ok(Advertisement { daemon, fullname })
+    }
+
+    /// Browses the network for `timeout` and returns the peers that answer
+    ///
+    /// This device is left out, and so are peers whose advertised address
+    /// does not accept a connection.
+    pub fn discover_peers(&self, timeout: Duration) -> SyncResult<Vec<Peer>> {
+        let daemon = ServiceDaemon::new().map_err(network_error)?;
+        let events = daemon.browse(SERVICE_TYPE).map_err(network_error)?;
+
+        let mut found: Vec<(Peer, Vec<IpAddr>)> = Vec::new();
+        let deadline = Instant::now() + timeout;
+        while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+            let Ok(event) = events.recv_timeout(left) else {
+                break;
+            };
+            let ServiceEvent::ServiceResolved(info) = event else {
+                continue;
+            };
+            let Some(peer) = peer_from_service(&info) else {
+                continue;
+            };
+            if &peer.device_id == self.device_id()
+                || found
+                    .iter()
+                    .any(|(known, _)| known.device_id == peer.device_id)
+            {
+                continue;
+            }
+            // IPv4 first: link-local IPv6 addresses need a scope to connect
+            let mut addresses: Vec<IpAddr> = info.get_addresses().iter().copied().collect();
+            addresses.sort_by_key(|ip| !ip.is_ipv4());
+            found.push((peer, addresses));
+        }
+        let _ = daemon.stop_browse(SERVICE_TYPE);
+        let _ = daemon.shutdown();
+
+        Ok(found
+            .into_iter()
+            .filter_map(|(peer, addresses)| {
+                let address = addresses
+                    .into_iter()
+                    .map(|ip| SocketAddr::new(ip, peer.address.port()))
+                    .find(|address| TcpStream::connect_timeout(address, PROBE_TIMEOUT).is_ok())?;
+                Some(Peer { address, ..peer })
+            })
+            .collect())
+    }
+}
+
+/// Reads a peer from its service record
+///
+/// The address is left unspecified, with the advertised port.
+fn peer_from_service(info: &ServiceInfo) -> Option<Peer> {
+    Some(Peer {
+        device_id: DeviceId::from_string(info.get_property_val_str("id")?.to_string()),
+        name: info.get_property_val_str("name").map(str::to_string),
+        protocol_version: info.get_property_val_str("version")?.parse().ok()?,
+        address: SocketAddr::new(IpAddr::from([0, 0, 0, 0]), info.get_port()),
+    })
+}
+
+fn network_error(e: mdns_sd::Error) -> SyncError {
+    SyncError::Network(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(properties: &[(&str, &str)]) -> ServiceInfo {
+        ServiceInfo::new(
+            SERVICE_TYPE,
+            "phone",
+            "phone.local.",
+            "192.168.1.20",
+            4242,
+            properties,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_peer_from_service() {
+        let peer = peer_from_service(&service(&[
+            ("id", "phone-id"),
+            ("name", "Phone"),
+            ("version", "1"),
+        ]))
+        .unwrap();
+        assert_eq!(peer.device_id.as_str(), "phone-id");
+        assert_eq!(peer.label(), "Phone");
+        assert_eq!(peer.address.port(), 4242);
+        assert!(peer.is_compatible());
+
+        let unnamed =
+            peer_from_service(&service(&[("id", "tablet-id"), ("version", "2")])).unwrap();
+        assert_eq!(unnamed.label(), "tablet-id");
+        assert!(!unnamed.is_compatible());
+
+        // Services without an ID or version are not StoryStream devices
+        assert!(peer_from_service(&service(&[("version", "1")])).is_none());
+        assert!(peer_from_service(&service(&[("id", "x"), ("version", "one")])).is_none());
+    }
+}
@@ -5,7 +5,10 @@ use crate::conflict::ConflictResolver;
 use crate::error::{SyncError, SyncResult};
 use crate::protocol::{SyncRequest, SyncResponse};
 use crate::tracker::ChangeTracker;
-use crate::types::{Change, ConflictResolution, DeviceId, SyncState};
+use crate::types::{
+    Change, ChangeType, ConflictResolution, DeviceId, EntityType, RemotePosition, SyncState,
+};
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
 /// Configuration for the sync engine
@@ -13,6 +16,8 @@ use std::sync::{Arc, Mutex};
 pub struct SyncConfig {
     /// Device identifier
     pub device_id: DeviceId,
+    /// Name shown on other devices, e.g. "Laptop"
+    pub device_name: Option<String>,
     /// Conflict resolution strategy
     pub conflict_resolution: ConflictResolution,
     /// Whether to auto-sync on changes
@@ -23,6 +28,7 @@ impl Default for SyncConfig {
     fn default() -> Self {
         Self {
             device_id: DeviceId::new(),
+            device_name: None,
             conflict_resolution: ConflictResolution::UseNewest,
             auto_sync: false,
         }
@@ -35,6 +41,8 @@ pub struct SyncEngine {
     tracker: ChangeTracker,
     resolver: ConflictResolver,
     state: Arc<Mutex<SyncState>>,
+    /// Latest position other devices reported per book, until passed or dismissed
+    remote_positions: Arc<Mutex<HashMap<String, RemotePosition>>>,
 }
 
 impl SyncEngine {
@@ -48,6 +56,7 @@ impl SyncEngine {
             tracker,
             resolver,
             state: Arc::new(Mutex::new(SyncState::new())),
+            remote_positions: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -73,6 +82,11 @@ impl SyncEngine {
     }
 
     /// Performs a sync operation
+    #[tracing::instrument(
+        name = "sync_round",
+        skip_all,
+        fields(device = %self.config.device_id, remote = remote_changes.len())
+    )]
     pub fn sync(&self, remote_changes: Vec<Change>) -> SyncResult<Vec<Change>> {
         // Mark sync as in progress
         {
@@ -110,6 +124,13 @@ impl SyncEngine {
             if !has_conflict {
                 resolved_changes.push(remote.clone());
             }
+
+            if remote.device_id != self.config.device_id {
+                if let Some(mut position) = RemotePosition::from_change(remote) {
+                    position.conflict = has_conflict;
+                    self.note_remote_position(position)?;
+                }
+            }
         }
 
         // Add non-conflicting local changes
@@ -139,6 +160,12 @@ impl SyncEngine {
             state.pending_changes = 0;
             state.conflicts = self.resolver.unresolved_count();
             state.in_progress = false;
+            tracing::info!(
+                local = local_changes.len(),
+                resolved = resolved_changes.len(),
+                conflicts = state.conflicts,
+                "Sync round finished"
+            );
         }
 
         Ok(resolved_changes)
@@ -183,6 +210,78 @@ impl SyncEngine {
     pub fn device_id(&self) -> &DeviceId {
         &self.config.device_id
     }
+
+    /// Gets the name other devices show for this one
+    pub fn device_name(&self) -> Option<&str> {
+        self.config.device_name.as_deref()
+    }
+
+    /// Records this device's position in a book
+    ///
+    /// A remote position the new one reaches is no longer reported.
+    pub fn record_position(&self, entity_id: &str, position_ms: u64) -> SyncResult<()> {
+        self.record_change(
+            ChangeType::Update,
+            EntityType::Position,
+            entity_id.to_string(),
+            serde_json::json!({
+                "position": position_ms,
+                "device_name": self.config.device_name,
+            }),
+        )?;
+
+        let mut positions = self.lock_remote_positions()?;
+        if positions
+            .get(entity_id)
+            .is_some_and(|remote| position_ms >= remote.position_ms)
+        {
+            positions.remove(entity_id);
+        }
+        Ok(())
+    }
+
+    /// Latest position another device reported in a book
+    pub fn remote_position(&self, entity_id: &str) -> SyncResult<Option<RemotePosition>> {
+        Ok(self.lock_remote_positions()?.get(entity_id).cloned())
+    }
+
+    /// Stops reporting the remote position of a book until a newer one arrives
+    pub fn dismiss_remote_position(&self, entity_id: &str) -> SyncResult<()> {
+        self.lock_remote_positions()?.remove(entity_id);
+        Ok(())
+    }
+
+    /// Takes the remote position of a book as this device's own
+    ///
+    /// It is recorded as a local change, so the next sync settles every
+    /// device on it. Returns the adopted position.
+    pub fn adopt_remote_position(&self, entity_id: &str) -> SyncResult<Option<u64>> {
+        let Some(remote) = self.lock_remote_positions()?.remove(entity_id) else {
+            return Ok(None);
+        };
+        self.record_position(entity_id, remote.position_ms)?;
+        Ok(Some(remote.position_ms))
+    }
+
+    /// Keeps the newest remote position of each book
+    fn note_remote_position(&self, position: RemotePosition) -> SyncResult<()> {
+        let mut positions = self.lock_remote_positions()?;
+        let newer = positions
+            .get(&position.entity_id)
+            .is_none_or(|known| position.timestamp >= known.timestamp);
+        if newer {
+            positions.insert(position.entity_id.clone(), position);
+        }
+        Ok(())
+    }
+
+    fn lock_remote_positions(
+        &self,
+    ) -> SyncResult<std::sync::MutexGuard<'_, HashMap<String, RemotePosition>>> {
+        self.remote_positions
+            .lock()
+            .map_err(|_| SyncError::Custom("Lock poisoned".to_string()))
+    }
 }
 
 #[cfg(test)]
@@ -316,6 +415,68 @@ mod tests {
         assert!(result.is_err());
     }
 
+    fn remote_position_change(entity_id: &str, position_ms: u64) -> Change {
+        Change::new(
+            DeviceId::from_string("phone".to_string()),
+            ChangeType::Update,
+            EntityType::Position,
+            entity_id.to_string(),
+            serde_json::json!({"position": position_ms, "device_name": "Phone"}),
+        )
+    }
+
+    #[test]
+    fn test_remote_position_reported_until_passed() {
+        let engine = SyncEngine::new(SyncConfig::default());
+        engine
+            .sync(vec![remote_position_change("book-1", 60_000)])
+            .unwrap();
+
+        let remote = engine.remote_position("book-1").unwrap().unwrap();
+        assert_eq!(remote.position_ms, 60_000);
+        assert_eq!(remote.device_label(), "Phone");
+        assert!(!remote.conflict);
+        assert!(engine.remote_position("book-2").unwrap().is_none());
+
+        engine.record_position("book-1", 30_000).unwrap();
+        assert!(engine.remote_position("book-1").unwrap().is_some());
+        engine.record_position("book-1", 60_000).unwrap();
+        assert!(engine.remote_position("book-1").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_conflicting_remote_position() {
+        let engine = SyncEngine::new(SyncConfig::default());
+        engine.record_position("book-1", 10_000).unwrap();
+        engine
+            .sync(vec![remote_position_change("book-1", 90_000)])
+            .unwrap();
+
+        assert!(engine.remote_position("book-1").unwrap().unwrap().conflict);
+        engine.dismiss_remote_position("book-1").unwrap();
+        assert!(engine.remote_position("book-1").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_adopt_remote_position() {
+        let engine = SyncEngine::new(SyncConfig::default());
+        assert_eq!(engine.adopt_remote_position("book-1").unwrap(), None);
+
+        engine
+            .sync(vec![remote_position_change("book-1", 60_000)])
+            .unwrap();
+        assert_eq!(
+            engine.adopt_remote_position("book-1").unwrap(),
+            Some(60_000)
+        );
+        assert!(engine.remote_position("book-1").unwrap().is_none());
+
+        // The choice goes out with the next sync
+        let request = engine.create_sync_request().unwrap();
+        assert_eq!(request.changes.len(), 1);
+        assert_eq!(request.changes[0].data["position"], 60_000);
+    }
+
     #[test]
     fn test_concurrent_sync_blocked() {
         let config = SyncConfig::default();
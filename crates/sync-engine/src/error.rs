@@ -29,6 +29,10 @@ pub enum SyncError {
     #[error("Network error: {0}")]
     Network(String),
 
+    /// Pairing with another device failed or was refused
+    #[error("Pairing failed: {0}")]
+    Pairing(String),
+
     /// Storage error
     #[error("Storage error: {0}")]
     Storage(String),
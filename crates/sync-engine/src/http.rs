@@ -0,0 +1,439 @@
+// crates/sync-engine/src/http.rs
+//! Pairing and syncing with devices over HTTP on the local network
+//!
+//! Every device runs a [`SyncServer`]. Pairing takes two requests, one per
+//! step of [`PairingInitiator`]. A sync request carries the sender's device
+//! ID and an HMAC of the body made with the shared secret, and the answer
+//! is signed the same way; devices the user has not confirmed are refused.
+
+use crate::engine::SyncEngine;
+use crate::error::{SyncError, SyncResult};
+use crate::pairing::{
+    PairedDevice, PairedDevices, PairingHello, PairingInitiator, PairingReply, PairingResponder,
+    PairingReveal, PendingPair,
+};
+use crate::protocol::{SyncRequest, SyncResponse};
+use crate::types::{Change, DeviceId};
+use std::collections::HashMap;
+use std::io::Read;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+const DEVICE_HEADER: &str = "X-StoryStream-Device";
+const SIGNATURE_HEADER: &str = "X-StoryStream-Signature";
+
+/// Largest request or response body read, in bytes
+const MAX_BODY: u64 = 8 * 1024 * 1024;
+
+/// How long a request to another device may take
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Answers pairing and sync requests from other devices
+///
+/// Requests are served on a thread of their own until the server is dropped.
+pub struct SyncServer {
+    server: Arc<Server>,
+    port: u16,
+    state: Arc<ServerState>,
+    thread: Option<JoinHandle<()>>,
+}
+
+struct ServerState {
+    engine: Arc<SyncEngine>,
+    paired: Arc<Mutex<PairedDevices>>,
+    /// Pairings other devices started, waiting for their key
+    responders: Mutex<HashMap<DeviceId, PairingResponder>>,
+    /// Finished key exchanges waiting for the user to compare codes
+    requests: Mutex<Vec<PendingPair>>,
+}
+
+impl SyncServer {
+    /// Starts serving on any free port
+    ///
+    /// Syncs are accepted from the devices in `paired`; confirming a
+    /// pairing means adding its device there.
+    pub fn start(engine: Arc<SyncEngine>, paired: Arc<Mutex<PairedDevices>>) -> SyncResult<Self> {
+        let server = Server::http("0.0.0.0:0").map_err(|e| SyncError::Network(e.to_string()))?;
+        let port = server
+            .server_addr()
+            .to_ip()
+            .map(|addr| addr.port())
+            .ok_or_else(|| SyncError::Network("Server has no port".to_string()))?;
+
+        let server = Arc::new(server);
+        let state = Arc::new(ServerState {
+            engine,
+            paired,
+            responders: Mutex::new(HashMap::new()),
+            requests: Mutex::new(Vec::new()),
+        });
+        let thread = {
+            let server = Arc::clone(&server);
+            let state = Arc::clone(&state);
+            std::thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    state.handle(request);
+                }
+            })
+        };
+        tracing::info!("Sync server listening on port {}", port);
+
+        Ok(Self {
+            server,
+            port,
+            state,
+            thread: Some(thread),
+        })
+    }
+
+    /// Port to advertise
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Takes the pairings other devices finished since the last call
+    ///
+    /// Each is kept only if the user confirms its code.
+    pub fn take_pairing_requests(&self) -> Vec<PendingPair> {
+        lock(&self.state.requests)
+            .map(|mut requests| std::mem::take(&mut *requests))
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for SyncServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl ServerState {
+    fn handle(&self, mut request: Request) {
+        let mut body = Vec::new();
+        let read = request
+            .as_reader()
+            .take(MAX_BODY)
+            .read_to_end(&mut body)
+            .map_err(|e| SyncError::Network(e.to_string()));
+
+        let reply = read.and_then(|_| match (request.method(), request.url()) {
+            (Method::Post, "/pair/hello") => self.pair_hello(&body).map(|body| (body, None)),
+            (Method::Post, "/pair/reveal") => {
+                self.pair_reveal(&body).map(|()| (b"{}".to_vec(), None))
+            }
+            (Method::Post, "/sync") => {
+                let device = header(&request, DEVICE_HEADER);
+                let signature = header(&request, SIGNATURE_HEADER);
+                self.sync(&body, device, signature)
+                    .map(|(body, signature)| (body, Some(signature)))
+            }
+            _ => Err(SyncError::InvalidData(format!(
+                "No route for {}",
+                request.url()
+            ))),
+        });
+
+        let response = match reply {
+            Ok((body, signature)) => {
+                let mut response = Response::from_data(body);
+                if let Some(signature) = signature.and_then(|s| header_value(SIGNATURE_HEADER, &s))
+                {
+                    response.add_header(signature);
+                }
+                response
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Refused {} from {:?}: {}",
+                    request.url(),
+                    request.remote_addr(),
+                    e
+                );
+                let status = match e {
+                    SyncError::DeviceNotRegistered(_) => 403,
+                    SyncError::InvalidData(_)
+                    | SyncError::Pairing(_)
+                    | SyncError::Serialization(_) => 400,
+                    _ => 500,
+                };
+                Response::from_data(e.to_string().into_bytes()).with_status_code(status)
+            }
+        };
+        if let Err(e) = request.respond(response) {
+            tracing::debug!("Could not answer a sync request: {}", e);
+        }
+    }
+
+    fn pair_hello(&self, body: &[u8]) -> SyncResult<Vec<u8>> {
+        let hello: PairingHello = serde_json::from_slice(body)?;
+        let device_id = hello.device_id.clone();
+        let (responder, reply) = PairingResponder::new(&self.engine, hello);
+        lock(&self.responders)?.insert(device_id, responder);
+        Ok(serde_json::to_vec(&reply)?)
+    }
+
+    fn pair_reveal(&self, body: &[u8]) -> SyncResult<()> {
+        let reveal: PairingReveal = serde_json::from_slice(body)?;
+        let responder = lock(&self.responders)?
+            .remove(&reveal.device_id)
+            .ok_or_else(|| SyncError::Pairing("No pairing was started".to_string()))?;
+        let pending = responder.finish(reveal)?;
+        lock(&self.requests)?.push(pending);
+        Ok(())
+    }
+
+    /// Takes a paired device's changes and answers with this device's
+    fn sync(
+        &self,
+        body: &[u8],
+        device: Option<String>,
+        signature: Option<String>,
+    ) -> SyncResult<(Vec<u8>, String)> {
+        let device_id = DeviceId::from_string(device.unwrap_or_default());
+        let device = lock(&self.paired)?
+            .get(&device_id)
+            .cloned()
+            .ok_or_else(|| SyncError::DeviceNotRegistered(device_id.to_string()))?;
+        if !signature.is_some_and(|signature| device.verify(body, &signature)) {
+            return Err(SyncError::DeviceNotRegistered(format!(
+                "{} (bad signature)",
+                device_id
+            )));
+        }
+
+        let request: SyncRequest = serde_json::from_slice(body)?;
+        if request.device_id != device_id.as_str() {
+            return Err(SyncError::InvalidData(
+                "Request is for another device".to_string(),
+            ));
+        }
+        let outgoing = self.engine.create_sync_request()?.changes;
+        self.engine.sync(request.changes)?;
+
+        let body = serde_json::to_vec(&SyncResponse::success(outgoing))?;
+        let signature = device.sign(&body);
+        Ok((body, signature))
+    }
+}
+
+/// Exchanges changes with one paired device over HTTP
+pub struct HttpTransport {
+    address: SocketAddr,
+    device: PairedDevice,
+    agent: ureq::Agent,
+}
+
+impl HttpTransport {
+    /// Syncs with `device`, whose server answers at `address`
+    pub fn new(address: SocketAddr, device: PairedDevice) -> Self {
+        Self {
+            address,
+            device,
+            agent: agent(),
+        }
+    }
+
+    /// Runs the key exchange with the device at `address`
+    ///
+    /// Nothing is kept: the returned pairing holds the code to show, and
+    /// the device is paired once the user confirms it matches on both.
+    pub fn pair(address: SocketAddr, engine: &SyncEngine) -> SyncResult<PendingPair> {
+        let agent = agent();
+        let initiator = PairingInitiator::new(engine);
+        let body = serde_json::to_vec(initiator.hello())?;
+        let reply = post(&agent, address, "/pair/hello", &[], &body)?;
+        let reply: PairingReply = serde_json::from_slice(&reply.body)?;
+
+        let (reveal, pending) = initiator.finish(reply)?;
+        post(
+            &agent,
+            address,
+            "/pair/reveal",
+            &[],
+            &serde_json::to_vec(&reveal)?,
+        )?;
+        Ok(pending)
+    }
+
+    /// Runs one sync round: sends the engine's changes and feeds it the device's
+    pub fn sync(&self, engine: &SyncEngine) -> SyncResult<Vec<Change>> {
+        let body = serde_json::to_vec(&engine.create_sync_request()?)?;
+        let signature = self.device.sign(&body);
+        let reply = post(
+            &self.agent,
+            self.address,
+            "/sync",
+            &[
+                (DEVICE_HEADER, engine.device_id().as_str()),
+                (SIGNATURE_HEADER, &signature),
+            ],
+            &body,
+        )?;
+
+        let signed = reply
+            .signature
+            .is_some_and(|signature| self.device.verify(&reply.body, &signature));
+        if !signed {
+            return Err(SyncError::DeviceNotRegistered(format!(
+                "{} (bad signature)",
+                self.device.label()
+            )));
+        }
+        engine.process_sync_response(serde_json::from_slice(&reply.body)?)
+    }
+}
+
+struct Reply {
+    body: Vec<u8>,
+    signature: Option<String>,
+}
+
+fn post(
+    agent: &ureq::Agent,
+    address: SocketAddr,
+    path: &str,
+    headers: &[(&str, &str)],
+    body: &[u8],
+) -> SyncResult<Reply> {
+    let mut request = agent.post(&format!("http://{}{}", address, path));
+    for (name, value) in headers {
+        request = request.set(name, value);
+    }
+
+    let response = match request.send_bytes(body) {
+        Ok(response) => response,
+        Err(ureq::Error::Status(status, response)) => {
+            let message = response.into_string().unwrap_or_default();
+            return Err(SyncError::Network(format!("{} ({})", message, status)));
+        }
+        Err(e) => return Err(SyncError::Network(e.to_string())),
+    };
+    let signature = response.header(SIGNATURE_HEADER).map(str::to_string);
+    let mut body = Vec::new();
+    response
+        .into_reader()
+        .take(MAX_BODY)
+        .read_to_end(&mut body)
+        .map_err(|e| SyncError::Network(e.to_string()))?;
+    Ok(Reply { body, signature })
+}
+
+fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build()
+}
+
+fn header(request: &Request, name: &'static str) -> Option<String> {
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv(name))
+        .map(|h| h.value.to_string())
+}
+
+fn header_value(name: &str, value: &str) -> Option<Header> {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).ok()
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> SyncResult<MutexGuard<'_, T>> {
+    mutex
+        .lock()
+        .map_err(|_| SyncError::Custom("Lock poisoned".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::SyncConfig;
+
+    fn engine(name: &str) -> Arc<SyncEngine> {
+        Arc::new(SyncEngine::new(SyncConfig {
+            device_id: DeviceId::from_string(name.to_lowercase()),
+            device_name: Some(name.to_string()),
+            ..Default::default()
+        }))
+    }
+
+    fn paired_devices(dir: &std::path::Path, name: &str) -> Arc<Mutex<PairedDevices>> {
+        Arc::new(Mutex::new(
+            PairedDevices::load(dir.join(format!("{}.json", name))).unwrap(),
+        ))
+    }
+
+    #[test]
+    fn test_pair_then_sync_over_http() {
+        let dir = std::env::temp_dir().join(format!("storystream-http-{}", DeviceId::new()));
+        let laptop = engine("Laptop");
+        let phone = engine("Phone");
+        let phone_paired = paired_devices(&dir, "phone");
+        let server = SyncServer::start(Arc::clone(&phone), Arc::clone(&phone_paired)).unwrap();
+        let address: SocketAddr = ([127, 0, 0, 1], server.port()).into();
+
+        let on_laptop = HttpTransport::pair(address, &laptop).unwrap();
+        let requests = server.take_pairing_requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].code, on_laptop.code);
+        assert_eq!(requests[0].device.label(), "Laptop");
+        assert!(server.take_pairing_requests().is_empty());
+
+        // No data flows until the phone's user confirms
+        laptop.record_position("book-1", 5_000).unwrap();
+        let transport = HttpTransport::new(address, on_laptop.device);
+        assert!(transport.sync(&laptop).is_err());
+        assert!(phone.remote_position("book-1").unwrap().is_none());
+
+        phone_paired
+            .lock()
+            .unwrap()
+            .add(requests[0].device.clone())
+            .unwrap();
+        phone.record_position("book-2", 9_000).unwrap();
+        transport.sync(&laptop).unwrap();
+
+        let remote = phone.remote_position("book-1").unwrap().unwrap();
+        assert_eq!(remote.position_ms, 5_000);
+        assert_eq!(remote.device_label(), "Laptop");
+        let remote = laptop.remote_position("book-2").unwrap().unwrap();
+        assert_eq!(remote.position_ms, 9_000);
+
+        drop(server);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_server_refuses_bad_signatures() {
+        let dir = std::env::temp_dir().join(format!("storystream-http-{}", DeviceId::new()));
+        let laptop = engine("Laptop");
+        let phone = engine("Phone");
+        let phone_paired = paired_devices(&dir, "phone");
+        let server = SyncServer::start(Arc::clone(&phone), Arc::clone(&phone_paired)).unwrap();
+        let address: SocketAddr = ([127, 0, 0, 1], server.port()).into();
+
+        let on_laptop = HttpTransport::pair(address, &laptop).unwrap();
+        for request in server.take_pairing_requests() {
+            phone_paired.lock().unwrap().add(request.device).unwrap();
+        }
+
+        // Signed with a secret from a pairing the phone took no part in
+        let initiator = PairingInitiator::new(&laptop);
+        let (_, reply) = PairingResponder::new(&engine("Tablet"), initiator.hello().clone());
+        let (_, forged) = initiator.finish(reply).unwrap();
+        let mut device = forged.device;
+        device.device_id = on_laptop.device.device_id;
+
+        laptop.record_position("book-1", 5_000).unwrap();
+        let err = HttpTransport::new(address, device)
+            .sync(&laptop)
+            .unwrap_err();
+        assert!(err.to_string().contains("403"));
+        assert!(phone.remote_position("book-1").unwrap().is_none());
+
+        drop(server);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -6,6 +6,9 @@
 //! - Bookmark synchronization
 //! - Library metadata syncing
 //! - Conflict detection and resolution
+//! - Exchanging changes through a shared folder
+//! - Finding, pairing with and syncing to devices on the local network
+//!   (the `discovery` feature)
 //!
 //! # Example
 //!
@@ -14,6 +17,7 @@
 //!
 //! let config = SyncConfig {
 //!     device_id: storystream_sync_engine::DeviceId::new(),
+//!     device_name: Some("Laptop".to_string()),
 //!     conflict_resolution: ConflictResolution::UseNewest,
 //!     auto_sync: false,
 //! };
@@ -30,19 +34,37 @@
 //! ```
 
 mod conflict;
+#[cfg(feature = "discovery")]
+mod discovery;
 mod engine;
 mod error;
+#[cfg(feature = "discovery")]
+mod http;
+#[cfg(feature = "discovery")]
+mod pairing;
 mod protocol;
 mod tracker;
+mod transport;
 mod types;
 
 pub use conflict::ConflictResolver;
+#[cfg(feature = "discovery")]
+pub use discovery::{Advertisement, Peer, PROTOCOL_VERSION, SERVICE_TYPE};
 pub use engine::{SyncConfig, SyncEngine};
 pub use error::{SyncError, SyncResult};
+#[cfg(feature = "discovery")]
+pub use http::{HttpTransport, SyncServer};
+#[cfg(feature = "discovery")]
+pub use pairing::{
+    PairedDevice, PairedDevices, PairingHello, PairingInitiator, PairingReply, PairingResponder,
+    PairingReveal, PendingPair,
+};
 pub use protocol::{SyncRequest, SyncResponse};
 pub use tracker::ChangeTracker;
+pub use transport::FolderTransport;
 pub use types::{
-    Change, ChangeType, Conflict, ConflictResolution, DeviceId, EntityType, SyncState,
+    Change, ChangeType, Conflict, ConflictResolution, DeviceId, EntityType, RemotePosition,
+    SyncState,
 };
 
 #[cfg(test)]
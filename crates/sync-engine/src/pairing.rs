@@ -0,0 +1,390 @@
+// crates/sync-engine/src/pairing.rs
+//! Pairing with a device on the local network
+//!
+//! Two devices agree on a shared secret with an X25519 key exchange, and
+//! both show a six-digit code derived from it. The user compares the codes
+//! and confirms on each screen; only then is the other device kept as
+//! paired and allowed to sync. The device that starts commits to its key
+//! before it sees the other one, so a device in between cannot pick keys
+//! that make the two codes agree.
+
+use crate::engine::SyncEngine;
+use crate::error::{SyncError, SyncResult};
+use crate::types::DeviceId;
+use hmac::{Hmac, Mac};
+use rand_core::OsRng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use x25519_dalek::{EphemeralSecret, PublicKey};
+
+/// First message, from the device starting the pairing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairingHello {
+    pub device_id: DeviceId,
+    pub device_name: Option<String>,
+    /// SHA-256 of the starting device's public key, in hex
+    pub commitment: String,
+}
+
+/// Answer to a [`PairingHello`], with the other device's public key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairingReply {
+    pub device_id: DeviceId,
+    pub device_name: Option<String>,
+    /// Public key in hex
+    pub public_key: String,
+}
+
+/// Last message: the starting device's public key it committed to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairingReveal {
+    pub device_id: DeviceId,
+    /// Public key in hex
+    pub public_key: String,
+}
+
+/// Device paired with this one, and the secret both sign their syncs with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PairedDevice {
+    pub device_id: DeviceId,
+    pub name: Option<String>,
+    /// Shared secret in hex
+    secret: String,
+}
+
+/// Finished key exchange waiting for the user to compare codes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingPair {
+    /// Device to keep once the user confirms
+    pub device: PairedDevice,
+    /// Code both screens show, e.g. "042 917"
+    pub code: String,
+}
+
+/// Pairing as the device that starts it
+pub struct PairingInitiator {
+    secret: EphemeralSecret,
+    public: PublicKey,
+    hello: PairingHello,
+}
+
+/// Pairing as the device asked to pair
+pub struct PairingResponder {
+    secret: EphemeralSecret,
+    public: PublicKey,
+    hello: PairingHello,
+}
+
+impl PairingInitiator {
+    /// Starts pairing as `engine`'s device
+    pub fn new(engine: &SyncEngine) -> Self {
+        let secret = EphemeralSecret::random_from_rng(OsRng);
+        let public = PublicKey::from(&secret);
+        let hello = PairingHello {
+            device_id: engine.device_id().clone(),
+            device_name: engine.device_name().map(str::to_string),
+            commitment: to_hex(&Sha256::digest(public.as_bytes())),
+        };
+        Self {
+            secret,
+            public,
+            hello,
+        }
+    }
+
+    /// Message to send to the other device
+    pub fn hello(&self) -> &PairingHello {
+        &self.hello
+    }
+
+    /// Takes the other device's reply
+    ///
+    /// Returns the key to reveal to it and the pairing to confirm.
+    pub fn finish(self, reply: PairingReply) -> SyncResult<(PairingReveal, PendingPair)> {
+        let theirs = parse_public_key(&reply.public_key)?;
+        let shared = self.secret.diffie_hellman(&theirs);
+        let pending = PendingPair::derive(
+            shared.as_bytes(),
+            &self.public,
+            &theirs,
+            reply.device_id,
+            reply.device_name,
+        );
+        let reveal = PairingReveal {
+            device_id: self.hello.device_id,
+            public_key: to_hex(self.public.as_bytes()),
+        };
+        Ok((reveal, pending))
+    }
+}
+
+impl PairingResponder {
+    /// Answers `hello` as `engine`'s device
+    pub fn new(engine: &SyncEngine, hello: PairingHello) -> (Self, PairingReply) {
+        let secret = EphemeralSecret::random_from_rng(OsRng);
+        let public = PublicKey::from(&secret);
+        let reply = PairingReply {
+            device_id: engine.device_id().clone(),
+            device_name: engine.device_name().map(str::to_string),
+            public_key: to_hex(public.as_bytes()),
+        };
+        let responder = Self {
+            secret,
+            public,
+            hello,
+        };
+        (responder, reply)
+    }
+
+    /// Takes the starting device's key and returns the pairing to confirm
+    ///
+    /// # Errors
+    ///
+    /// Fails if the key is not the one the device committed to.
+    pub fn finish(self, reveal: PairingReveal) -> SyncResult<PendingPair> {
+        if reveal.device_id != self.hello.device_id {
+            return Err(SyncError::Pairing(
+                "Key came from another device".to_string(),
+            ));
+        }
+        let theirs = parse_public_key(&reveal.public_key)?;
+        if to_hex(&Sha256::digest(theirs.as_bytes())) != self.hello.commitment {
+            return Err(SyncError::Pairing(
+                "Key does not match the commitment".to_string(),
+            ));
+        }
+        let shared = self.secret.diffie_hellman(&theirs);
+        Ok(PendingPair::derive(
+            shared.as_bytes(),
+            &theirs,
+            &self.public,
+            self.hello.device_id,
+            self.hello.device_name,
+        ))
+    }
+}
+
+impl PendingPair {
+    /// Derives the secret and code both devices end up with
+    fn derive(
+        shared: &[u8],
+        initiator: &PublicKey,
+        responder: &PublicKey,
+        device_id: DeviceId,
+        name: Option<String>,
+    ) -> Self {
+        let digest = |label: &[u8]| {
+            let mut hasher = Sha256::new();
+            hasher.update(label);
+            hasher.update(shared);
+            hasher.update(initiator.as_bytes());
+            hasher.update(responder.as_bytes());
+            hasher.finalize()
+        };
+
+        let code = digest(b"storystream-pairing-code");
+        let code = u32::from_be_bytes([code[0], code[1], code[2], code[3]]) % 1_000_000;
+        Self {
+            device: PairedDevice {
+                device_id,
+                name,
+                secret: to_hex(&digest(b"storystream-pairing-secret")),
+            },
+            code: format!("{:03} {:03}", code / 1000, code % 1000),
+        }
+    }
+}
+
+impl PairedDevice {
+    /// Name to show for the device
+    pub fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or(self.device_id.as_str())
+    }
+
+    /// Signs a message body with the shared secret, in hex
+    pub fn sign(&self, body: &[u8]) -> String {
+        to_hex(&self.mac(body).finalize().into_bytes())
+    }
+
+    /// Checks a signature made by [`PairedDevice::sign`] on the other device
+    pub fn verify(&self, body: &[u8], signature: &str) -> bool {
+        from_hex(signature).is_some_and(|signature| self.mac(body).verify_slice(&signature).is_ok())
+    }
+
+    fn mac(&self, body: &[u8]) -> Hmac<Sha256> {
+        let key = from_hex(&self.secret).unwrap_or_default();
+        let mut mac = Hmac::<Sha256>::new_from_slice(&key).expect("HMAC takes keys of any length");
+        mac.update(body);
+        mac
+    }
+}
+
+/// Devices paired with this one, kept in a JSON file
+#[derive(Debug, Clone)]
+pub struct PairedDevices {
+    path: PathBuf,
+    devices: Vec<PairedDevice>,
+}
+
+impl PairedDevices {
+    /// Reads the devices kept at `path`; a missing file has none
+    pub fn load(path: impl Into<PathBuf>) -> SyncResult<Self> {
+        let path = path.into();
+        let devices = match fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(storage_error(&path, e)),
+        };
+        Ok(Self { path, devices })
+    }
+
+    /// All paired devices
+    pub fn devices(&self) -> &[PairedDevice] {
+        &self.devices
+    }
+
+    /// Paired device with the given ID
+    pub fn get(&self, device_id: &DeviceId) -> Option<&PairedDevice> {
+        self.devices.iter().find(|d| &d.device_id == device_id)
+    }
+
+    /// Keeps a device, replacing an earlier pairing with it
+    pub fn add(&mut self, device: PairedDevice) -> SyncResult<()> {
+        self.devices.retain(|d| d.device_id != device.device_id);
+        self.devices.push(device);
+        self.save()
+    }
+
+    /// Forgets a device; returns whether it was paired
+    pub fn remove(&mut self, device_id: &DeviceId) -> SyncResult<bool> {
+        let before = self.devices.len();
+        self.devices.retain(|d| &d.device_id != device_id);
+        if self.devices.len() == before {
+            return Ok(false);
+        }
+        self.save()?;
+        Ok(true)
+    }
+
+    fn save(&self) -> SyncResult<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| storage_error(parent, e))?;
+        }
+        let temp = self.path.with_extension("tmp");
+        fs::write(&temp, serde_json::to_string_pretty(&self.devices)?)
+            .map_err(|e| storage_error(&temp, e))?;
+        fs::rename(&temp, &self.path).map_err(|e| storage_error(&self.path, e))
+    }
+}
+
+fn parse_public_key(hex: &str) -> SyncResult<PublicKey> {
+    let bytes: [u8; 32] = from_hex(hex)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| SyncError::Pairing("Malformed public key".to_string()))?;
+    Ok(PublicKey::from(bytes))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn storage_error(path: &Path, e: std::io::Error) -> SyncError {
+    SyncError::Storage(format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::SyncConfig;
+
+    fn engine(name: &str) -> SyncEngine {
+        SyncEngine::new(SyncConfig {
+            device_id: DeviceId::from_string(name.to_lowercase()),
+            device_name: Some(name.to_string()),
+            ..Default::default()
+        })
+    }
+
+    fn pair(laptop: &SyncEngine, phone: &SyncEngine) -> (PendingPair, PendingPair) {
+        let initiator = PairingInitiator::new(laptop);
+        let (responder, reply) = PairingResponder::new(phone, initiator.hello().clone());
+        let (reveal, on_laptop) = initiator.finish(reply).unwrap();
+        let on_phone = responder.finish(reveal).unwrap();
+        (on_laptop, on_phone)
+    }
+
+    #[test]
+    fn test_both_devices_show_the_same_code() {
+        let laptop = engine("Laptop");
+        let phone = engine("Phone");
+        let (on_laptop, on_phone) = pair(&laptop, &phone);
+
+        assert_eq!(on_laptop.code, on_phone.code);
+        assert_eq!(on_laptop.code.len(), 7);
+        assert_eq!(on_laptop.device.label(), "Phone");
+        assert_eq!(on_phone.device.label(), "Laptop");
+        assert_eq!(on_laptop.device.secret, on_phone.device.secret);
+
+        // What one side signs, the other accepts
+        let body = b"{\"changes\":[]}";
+        let signature = on_laptop.device.sign(body);
+        assert!(on_phone.device.verify(body, &signature));
+        assert!(!on_phone.device.verify(b"{}", &signature));
+        assert!(!on_phone.device.verify(body, "not hex"));
+
+        // Another pairing has another secret
+        let (again, _) = pair(&laptop, &phone);
+        assert!(!again.device.verify(body, &signature));
+    }
+
+    #[test]
+    fn test_key_must_match_commitment() {
+        let laptop = engine("Laptop");
+        let phone = engine("Phone");
+        let initiator = PairingInitiator::new(&laptop);
+        let (responder, reply) = PairingResponder::new(&phone, initiator.hello().clone());
+        let (mut reveal, _) = initiator.finish(reply).unwrap();
+
+        let other = PublicKey::from(&EphemeralSecret::random_from_rng(OsRng));
+        reveal.public_key = to_hex(other.as_bytes());
+        assert!(matches!(
+            responder.finish(reveal),
+            Err(SyncError::Pairing(_))
+        ));
+    }
+
+    #[test]
+    fn test_paired_devices_persist() {
+        let path = std::env::temp_dir()
+            .join(format!("storystream-paired-{}", DeviceId::new()))
+            .join("paired.json");
+        let (on_laptop, _) = pair(&engine("Laptop"), &engine("Phone"));
+        let phone = on_laptop.device.device_id.clone();
+
+        let mut paired = PairedDevices::load(&path).unwrap();
+        assert!(paired.devices().is_empty());
+        paired.add(on_laptop.device.clone()).unwrap();
+        paired.add(on_laptop.device.clone()).unwrap();
+
+        let mut reloaded = PairedDevices::load(&path).unwrap();
+        assert_eq!(reloaded.devices().len(), 1);
+        assert_eq!(reloaded.get(&phone), Some(&on_laptop.device));
+        assert!(reloaded.remove(&phone).unwrap());
+        assert!(!reloaded.remove(&phone).unwrap());
+        assert!(PairedDevices::load(&path).unwrap().devices().is_empty());
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,185 @@
+// crates/sync-engine/src/transport.rs
+//! Syncing through a shared folder
+//!
+//! Each device keeps one file in a folder that a file-sync service shares
+//! between devices. The file holds the device's latest change to every
+//! entity, so it stays small however long the device is used.
+
+use crate::engine::SyncEngine;
+use crate::error::{SyncError, SyncResult};
+use crate::types::{Change, DeviceId};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Extension of the per-device change files
+const EXTENSION: &str = "jsonl";
+
+/// Exchanges changes with other devices through a shared folder
+pub struct FolderTransport {
+    dir: PathBuf,
+    device_id: DeviceId,
+    /// Newest remote change already pulled
+    pulled_until: Mutex<Option<DateTime<Utc>>>,
+}
+
+impl FolderTransport {
+    /// Uses `dir` for the exchange, creating it if needed
+    pub fn new(dir: impl Into<PathBuf>, device_id: DeviceId) -> SyncResult<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).map_err(|e| storage_error(&dir, e))?;
+        Ok(Self {
+            dir,
+            device_id,
+            pulled_until: Mutex::new(None),
+        })
+    }
+
+    /// Runs one sync round: publishes the engine's changes, then feeds it the others'
+    pub fn sync(&self, engine: &SyncEngine) -> SyncResult<Vec<Change>> {
+        let request = engine.create_sync_request()?;
+        self.push(&request.changes)?;
+        let remote = self.pull()?;
+        engine.sync(remote)
+    }
+
+    /// Publishes changes in this device's file
+    ///
+    /// Only the newest change to each entity is kept.
+    pub fn push(&self, changes: &[Change]) -> SyncResult<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let path = self.device_file(&self.device_id);
+        let mut latest: HashMap<String, Change> = HashMap::new();
+        for change in read_changes(&path)?
+            .into_iter()
+            .chain(changes.iter().cloned())
+        {
+            let key = format!("{:?}:{}", change.entity_type, change.entity_id);
+            match latest.get(&key) {
+                Some(known) if known.timestamp > change.timestamp => {}
+                _ => {
+                    latest.insert(key, change);
+                }
+            }
+        }
+
+        let mut changes: Vec<Change> = latest.into_values().collect();
+        changes.sort_by_key(|c| c.timestamp);
+        let mut text = String::new();
+        for change in &changes {
+            text.push_str(&serde_json::to_string(change)?);
+            text.push('\n');
+        }
+
+        // Write aside and rename so a syncing peer never reads half a file
+        let temp = self.dir.join(format!(".{}.tmp", self.device_id));
+        fs::write(&temp, text).map_err(|e| storage_error(&temp, e))?;
+        fs::rename(&temp, &path).map_err(|e| storage_error(&path, e))
+    }
+
+    /// Changes other devices published since the last pull, oldest first
+    pub fn pull(&self) -> SyncResult<Vec<Change>> {
+        let mut pulled_until = self
+            .pulled_until
+            .lock()
+            .map_err(|_| SyncError::Custom("Lock poisoned".to_string()))?;
+
+        let own = self.device_file(&self.device_id);
+        let entries = fs::read_dir(&self.dir).map_err(|e| storage_error(&self.dir, e))?;
+        let mut changes = Vec::new();
+        for entry in entries {
+            let path = entry.map_err(|e| storage_error(&self.dir, e))?.path();
+            if path == own || path.extension().is_none_or(|ext| ext != EXTENSION) {
+                continue;
+            }
+            changes.extend(
+                read_changes(&path)?
+                    .into_iter()
+                    .filter(|c| pulled_until.is_none_or(|until| c.timestamp > until)),
+            );
+        }
+
+        changes.sort_by_key(|c| c.timestamp);
+        if let Some(newest) = changes.last() {
+            *pulled_until = Some(newest.timestamp);
+        }
+        Ok(changes)
+    }
+
+    fn device_file(&self, device_id: &DeviceId) -> PathBuf {
+        self.dir.join(format!("{}.{}", device_id, EXTENSION))
+    }
+}
+
+/// Reads a change file; a missing file has no changes and bad lines are skipped
+fn read_changes(path: &Path) -> SyncResult<Vec<Change>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(storage_error(path, e)),
+    };
+
+    Ok(text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(change) => Some(change),
+            Err(e) => {
+                tracing::warn!("Skipping bad change in {}: {}", path.display(), e);
+                None
+            }
+        })
+        .collect())
+}
+
+fn storage_error(path: &Path, e: std::io::Error) -> SyncError {
+    SyncError::Storage(format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::SyncConfig;
+
+    fn engine(name: &str) -> SyncEngine {
+        SyncEngine::new(SyncConfig {
+            device_id: DeviceId::from_string(name.to_lowercase()),
+            device_name: Some(name.to_string()),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_positions_travel_through_the_folder() {
+        let dir = std::env::temp_dir().join(format!("storystream-sync-{}", DeviceId::new()));
+        let laptop = engine("Laptop");
+        let phone = engine("Phone");
+        let laptop_folder = FolderTransport::new(&dir, laptop.device_id().clone()).unwrap();
+        let phone_folder = FolderTransport::new(&dir, phone.device_id().clone()).unwrap();
+
+        phone.record_position("book-1", 1_000).unwrap();
+        phone.record_position("book-1", 5_000).unwrap();
+        phone_folder.sync(&phone).unwrap();
+
+        laptop_folder.sync(&laptop).unwrap();
+        let remote = laptop.remote_position("book-1").unwrap().unwrap();
+        assert_eq!(remote.position_ms, 5_000);
+        assert_eq!(remote.device_label(), "Phone");
+
+        // Nothing new, nothing pulled
+        laptop.dismiss_remote_position("book-1").unwrap();
+        laptop_folder.sync(&laptop).unwrap();
+        assert!(laptop.remote_position("book-1").unwrap().is_none());
+
+        // Only the newest change per book stays in the phone's file
+        let phone_file = phone_folder.device_file(phone.device_id());
+        assert_eq!(read_changes(&phone_file).unwrap().len(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
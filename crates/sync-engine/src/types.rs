@@ -3,6 +3,7 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 use uuid::Uuid;
 
 /// Unique device identifier
@@ -24,6 +25,25 @@ impl DeviceId {
     pub fn as_str(&self) -> &str {
         &self.0
     }
+
+    /// Reads the ID kept at `path`, storing a new one there if there is none
+    ///
+    /// A device keeps its ID across restarts so others recognise it.
+    pub fn load_or_create(path: &Path) -> std::io::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(id) if !id.trim().is_empty() => return Ok(Self(id.trim().to_string())),
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+
+        let id = Self::new();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, &id.0)?;
+        Ok(id)
+    }
 }
 
 impl Default for DeviceId {
@@ -115,6 +135,42 @@ impl Change {
     }
 }
 
+/// Where another device got to in a book, as learned from a sync
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemotePosition {
+    /// Book the position belongs to
+    pub entity_id: String,
+    pub device_id: DeviceId,
+    /// Name the other device goes by, if it sent one
+    pub device_name: Option<String>,
+    pub position_ms: u64,
+    /// Whether this device also moved in the book since the last sync
+    pub conflict: bool,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl RemotePosition {
+    /// Reads a position update; other changes give `None`
+    pub fn from_change(change: &Change) -> Option<Self> {
+        if change.entity_type != EntityType::Position || change.is_delete() {
+            return None;
+        }
+        Some(Self {
+            entity_id: change.entity_id.clone(),
+            device_id: change.device_id.clone(),
+            device_name: change.data["device_name"].as_str().map(str::to_string),
+            position_ms: change.data["position"].as_u64()?,
+            conflict: false,
+            timestamp: change.timestamp,
+        })
+    }
+
+    /// Name to show for the other device
+    pub fn device_label(&self) -> &str {
+        self.device_name.as_deref().unwrap_or("Another device")
+    }
+}
+
 /// Sync state for tracking progress
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncState {
@@ -218,6 +274,18 @@ mod tests {
         assert_ne!(id1, id2);
     }
 
+    #[test]
+    fn test_device_id_load_or_create() {
+        let path = std::env::temp_dir()
+            .join(format!("storystream-{}", Uuid::new_v4()))
+            .join("device_id");
+
+        let id = DeviceId::load_or_create(&path).unwrap();
+        assert_eq!(DeviceId::load_or_create(&path).unwrap(), id);
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
     #[test]
     fn test_device_id_from_string() {
         let id = DeviceId::from_string("test-device".to_string());
@@ -301,6 +369,29 @@ mod tests {
         assert!(conflict.is_resolved());
     }
 
+    #[test]
+    fn test_remote_position_from_change() {
+        let mut change = Change::new(
+            DeviceId::new(),
+            ChangeType::Update,
+            EntityType::Position,
+            "book-123".to_string(),
+            serde_json::json!({"position": 1000, "device_name": "Phone"}),
+        );
+
+        let remote = RemotePosition::from_change(&change).unwrap();
+        assert_eq!(remote.position_ms, 1000);
+        assert_eq!(remote.device_label(), "Phone");
+        assert!(!remote.conflict);
+
+        change.data = serde_json::json!({"position": 1000});
+        let remote = RemotePosition::from_change(&change).unwrap();
+        assert_eq!(remote.device_label(), "Another device");
+
+        change.entity_type = EntityType::Bookmark;
+        assert!(RemotePosition::from_change(&change).is_none());
+    }
+
     #[test]
     fn test_change_type_equality() {
         assert_eq!(ChangeType::Create, ChangeType::Create);
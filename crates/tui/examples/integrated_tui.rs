@@ -9,6 +9,11 @@
 //!
 //! Usage:
 //!   cargo run --example integrated_tui
+//!   cargo run --example integrated_tui -- 'storystream://bookmark?title=…&at=1:02:03'
+//!   cargo run --example integrated_tui -- --accessible
+//!
+//! A shared bookmark link opens its book at the shared position. `--accessible`
+//! announces playback and selection changes for screen readers.
 
 use storystream_tui::IntegratedTuiApp;
 
@@ -43,6 +48,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Create and run integrated TUI
     let mut app = IntegratedTuiApp::new().await?;
+    for arg in std::env::args().skip(1) {
+        if arg == "--accessible" {
+            app.set_accessible();
+        } else {
+            app.open_link(&arg).await?;
+        }
+    }
     app.run().await?;
 
     println!("\n═══════════════════════════════════════");
@@ -50,4 +62,4 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("═══════════════════════════════════════\n");
 
     Ok(())
-}
\ No newline at end of file
+}
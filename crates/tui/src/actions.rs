@@ -0,0 +1,806 @@
+// crates/tui/src/actions.rs
+//! Registry of the actions keys and the command palette run
+//!
+//! Each action names its default keys and the view those keys work in. Key
+//! handling looks pressed keys up in the [`KeyMap`](crate::keymap::KeyMap)
+//! built from them, and the palette lists the same actions, so a command
+//! picked from the palette does exactly what its key does.
+
+use crate::state::{AppState, View};
+use crossterm::event::{KeyCode, KeyModifiers};
+use std::fmt;
+
+/// Why actions that change the library are refused in read-only mode
+pub const READ_ONLY_REASON: &str = "The library is read-only";
+
+/// Key that opens the command palette from any view
+pub const PALETTE_KEY: KeyBinding = KeyBinding::ctrl(KeyCode::Char('p'));
+
+/// Key, with modifiers, that runs an action
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    /// Binds `code` pressed on its own
+    pub const fn plain(code: KeyCode) -> Self {
+        Self {
+            code,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    /// Binds `code` pressed with Ctrl
+    pub const fn ctrl(code: KeyCode) -> Self {
+        Self {
+            code,
+            modifiers: KeyModifiers::CONTROL,
+        }
+    }
+
+    /// Binds `code` pressed with Shift
+    pub const fn shift(code: KeyCode) -> Self {
+        Self {
+            code,
+            modifiers: KeyModifiers::SHIFT,
+        }
+    }
+
+    /// Reads a key as written in the `[keybindings]` config section
+    ///
+    /// A key is one character or a name such as `space`, `enter` or `f5`,
+    /// after any of `ctrl+`, `alt+` and `shift+`. With a letter, `shift+`
+    /// is the same as writing it in uppercase.
+    pub fn parse(text: &str) -> Option<Self> {
+        let prefixes = [
+            ("ctrl+", KeyModifiers::CONTROL),
+            ("alt+", KeyModifiers::ALT),
+            ("shift+", KeyModifiers::SHIFT),
+        ];
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        while let Some((prefix, modifier)) = prefixes.into_iter().find(|(prefix, _)| {
+            rest.len() > prefix.len()
+                && rest
+                    .get(..prefix.len())
+                    .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+        }) {
+            modifiers |= modifier;
+            rest = &rest[prefix.len()..];
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next()?, chars.next()) {
+            (c, None) if c.is_alphabetic() && modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers -= KeyModifiers::SHIFT;
+                KeyCode::Char(c.to_ascii_uppercase())
+            }
+            // Terminals send Ctrl and Alt letters lowercase
+            (c, None) if !modifiers.is_empty() => KeyCode::Char(c.to_ascii_lowercase()),
+            (c, None) => KeyCode::Char(c),
+            _ => match rest.to_ascii_lowercase().as_str() {
+                "space" => KeyCode::Char(' '),
+                "enter" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "esc" => KeyCode::Esc,
+                "backspace" => KeyCode::Backspace,
+                "delete" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                name => {
+                    let number = name.strip_prefix('f')?.parse().ok()?;
+                    (1..=12).contains(&number).then_some(KeyCode::F(number))?
+                }
+            },
+        };
+        Some(Self { code, modifiers })
+    }
+
+    /// Whether a key press is this binding
+    ///
+    /// Shift is only checked when the binding asks for it, since terminals
+    /// report it along with uppercase letters and symbols.
+    pub fn matches(self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        self.code == code
+            && modifiers.contains(self.modifiers)
+            && (modifiers - self.modifiers - KeyModifiers::SHIFT).is_empty()
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c)
+                if self
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                write!(f, "{}", c.to_ascii_uppercase())
+            }
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+/// Something the user can do, from a key or the command palette
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    TogglePlayback,
+    SeekBackward,
+    SeekForward,
+    VolumeUp,
+    VolumeDown,
+    SpeedDown,
+    SpeedUp,
+    NextChapter,
+    PreviousChapter,
+    NextView,
+    OpenView(View),
+    ToggleHelp,
+    ToggleTheme,
+    Search,
+    Quit,
+    ShowBookDetail,
+    ToggleFavorite,
+    DeleteBook,
+    EditChapters,
+    CycleEqualizer,
+    ToggleSpeedRamp,
+    CycleSleepTimer,
+    ExtendSleepTimer,
+    AddBookmark,
+    DeleteBookmark,
+    ClearAutoBookmarks,
+    ExportBookmarks,
+    ShareBookmark,
+    FilterSearch,
+    ShufflePlaylist,
+    NewPlaylist,
+    DeletePlaylist,
+    OpenPlaylist,
+    AddToPlaylist,
+    RemoveFromPlaylist,
+    MovePlaylistBookUp,
+    MovePlaylistBookDown,
+    RetryDownload,
+    RefreshStatistics,
+    ExportStatistics,
+    ImportLibrary,
+    PreviewImport,
+    VerifyFiles,
+    VerifyFilesFully,
+    FindDuplicates,
+    PruneDownloads,
+    ClearCache,
+    ResetSettings,
+    FindDevices,
+    PairDevice,
+    ChooseOutputDevice,
+}
+
+impl Action {
+    /// Every action, in the order the palette lists them
+    pub const ALL: [Action; 58] = [
+        Self::TogglePlayback,
+        Self::SeekBackward,
+        Self::SeekForward,
+        Self::VolumeUp,
+        Self::VolumeDown,
+        Self::SpeedDown,
+        Self::SpeedUp,
+        Self::NextChapter,
+        Self::PreviousChapter,
+        Self::Search,
+        Self::NextView,
+        Self::OpenView(View::Library),
+        Self::OpenView(View::Player),
+        Self::OpenView(View::Bookmarks),
+        Self::OpenView(View::Search),
+        Self::OpenView(View::Playlists),
+        Self::OpenView(View::Statistics),
+        Self::OpenView(View::Settings),
+        Self::OpenView(View::Downloads),
+        Self::ToggleHelp,
+        Self::ToggleTheme,
+        Self::ShowBookDetail,
+        Self::ToggleFavorite,
+        Self::DeleteBook,
+        Self::EditChapters,
+        Self::CycleEqualizer,
+        Self::ToggleSpeedRamp,
+        Self::CycleSleepTimer,
+        Self::ExtendSleepTimer,
+        Self::AddBookmark,
+        Self::DeleteBookmark,
+        Self::ClearAutoBookmarks,
+        Self::ExportBookmarks,
+        Self::ShareBookmark,
+        Self::FilterSearch,
+        Self::ShufflePlaylist,
+        Self::NewPlaylist,
+        Self::DeletePlaylist,
+        Self::OpenPlaylist,
+        Self::AddToPlaylist,
+        Self::RemoveFromPlaylist,
+        Self::MovePlaylistBookUp,
+        Self::MovePlaylistBookDown,
+        Self::RetryDownload,
+        Self::RefreshStatistics,
+        Self::ExportStatistics,
+        Self::ImportLibrary,
+        Self::PreviewImport,
+        Self::VerifyFiles,
+        Self::VerifyFilesFully,
+        Self::FindDuplicates,
+        Self::PruneDownloads,
+        Self::ClearCache,
+        Self::ResetSettings,
+        Self::FindDevices,
+        Self::PairDevice,
+        Self::ChooseOutputDevice,
+        Self::Quit,
+    ];
+
+    /// Returns the name shown in the palette; "…" means it asks for more
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::TogglePlayback => "Play / pause",
+            Self::SeekBackward => "Seek backward",
+            Self::SeekForward => "Seek forward",
+            Self::VolumeUp => "Volume up",
+            Self::VolumeDown => "Volume down",
+            Self::SpeedDown => "Speed down",
+            Self::SpeedUp => "Speed up",
+            Self::NextChapter => "Next chapter",
+            Self::PreviousChapter => "Restart / previous chapter",
+            Self::NextView => "Next view",
+            Self::OpenView(View::Library) => "Open library",
+            Self::OpenView(View::Player) => "Open player",
+            Self::OpenView(View::Bookmarks) => "Open bookmarks",
+            Self::OpenView(View::Search) => "Open search results",
+            Self::OpenView(View::Playlists) => "Open playlists",
+            Self::OpenView(View::Statistics) => "Open statistics",
+            Self::OpenView(View::Settings) => "Open settings",
+            Self::OpenView(View::Downloads) => "Open downloads",
+            Self::OpenView(View::Help) => "Open help",
+            Self::OpenView(View::Plugin) => "Open plugins",
+            Self::ToggleHelp => "Show / hide help",
+            Self::ToggleTheme => "Next color theme",
+            Self::Search => "Search library…",
+            Self::Quit => "Quit",
+            Self::ShowBookDetail => "Show book details",
+            Self::ToggleFavorite => "Toggle favorite",
+            Self::DeleteBook => "Move book to trash…",
+            Self::EditChapters => "Edit chapters",
+            Self::CycleEqualizer => "Next equalizer preset",
+            Self::ToggleSpeedRamp => "Start / stop speed ramp…",
+            Self::CycleSleepTimer => "Next sleep timer preset",
+            Self::ExtendSleepTimer => "Add 15 minutes to sleep timer",
+            Self::AddBookmark => "Add bookmark",
+            Self::DeleteBookmark => "Delete bookmark…",
+            Self::ClearAutoBookmarks => "Clear auto-bookmarks",
+            Self::ExportBookmarks => "Export bookmarks",
+            Self::ShareBookmark => "Copy bookmark share link",
+            Self::FilterSearch => "Filter search results",
+            Self::ShufflePlaylist => "Shuffle and play playlist",
+            Self::NewPlaylist => "New playlist…",
+            Self::DeletePlaylist => "Delete playlist…",
+            Self::OpenPlaylist => "Show / hide playlist books",
+            Self::AddToPlaylist => "Add selected library book to playlist",
+            Self::RemoveFromPlaylist => "Remove book from playlist",
+            Self::MovePlaylistBookUp => "Move book up in playlist",
+            Self::MovePlaylistBookDown => "Move book down in playlist",
+            Self::RetryDownload => "Retry download",
+            Self::RefreshStatistics => "Reload statistics",
+            Self::ExportStatistics => "Export statistics as CSV",
+            Self::ImportLibrary => "Import library folders",
+            Self::PreviewImport => "Preview import",
+            Self::VerifyFiles => "Verify book files",
+            Self::VerifyFilesFully => "Verify and decode book files",
+            Self::FindDuplicates => "Find duplicate books",
+            Self::PruneDownloads => "Prune download history",
+            Self::ClearCache => "Clear cache",
+            Self::ResetSettings => "Reset settings to defaults…",
+            Self::FindDevices => "Find devices on the network",
+            Self::PairDevice => "Pair with selected device",
+            Self::ChooseOutputDevice => "Choose output device…",
+        }
+    }
+
+    /// Returns the name the `[keybindings]` config section uses
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::TogglePlayback => "play_pause",
+            Self::SeekBackward => "seek_backward",
+            Self::SeekForward => "seek_forward",
+            Self::VolumeUp => "volume_up",
+            Self::VolumeDown => "volume_down",
+            Self::SpeedDown => "speed_down",
+            Self::SpeedUp => "speed_up",
+            Self::NextChapter => "next_chapter",
+            Self::PreviousChapter => "previous_chapter",
+            Self::NextView => "next_view",
+            Self::OpenView(View::Library) => "open_library",
+            Self::OpenView(View::Player) => "open_player",
+            Self::OpenView(View::Bookmarks) => "open_bookmarks",
+            Self::OpenView(View::Search) => "open_search",
+            Self::OpenView(View::Playlists) => "open_playlists",
+            Self::OpenView(View::Statistics) => "open_statistics",
+            Self::OpenView(View::Settings) => "open_settings",
+            Self::OpenView(View::Downloads) => "open_downloads",
+            Self::OpenView(View::Help) => "open_help",
+            Self::OpenView(View::Plugin) => "open_plugins",
+            Self::ToggleHelp => "toggle_help",
+            Self::ToggleTheme => "toggle_theme",
+            Self::Search => "search",
+            Self::Quit => "quit",
+            Self::ShowBookDetail => "show_book_detail",
+            Self::ToggleFavorite => "toggle_favorite",
+            Self::DeleteBook => "delete_book",
+            Self::EditChapters => "edit_chapters",
+            Self::CycleEqualizer => "cycle_equalizer",
+            Self::ToggleSpeedRamp => "toggle_speed_ramp",
+            Self::CycleSleepTimer => "cycle_sleep_timer",
+            Self::ExtendSleepTimer => "extend_sleep_timer",
+            Self::AddBookmark => "add_bookmark",
+            Self::DeleteBookmark => "delete_bookmark",
+            Self::ClearAutoBookmarks => "clear_auto_bookmarks",
+            Self::ExportBookmarks => "export_bookmarks",
+            Self::ShareBookmark => "share_bookmark",
+            Self::FilterSearch => "filter_search",
+            Self::ShufflePlaylist => "shuffle_playlist",
+            Self::NewPlaylist => "new_playlist",
+            Self::DeletePlaylist => "delete_playlist",
+            Self::OpenPlaylist => "open_playlist",
+            Self::AddToPlaylist => "add_to_playlist",
+            Self::RemoveFromPlaylist => "remove_from_playlist",
+            Self::MovePlaylistBookUp => "move_playlist_book_up",
+            Self::MovePlaylistBookDown => "move_playlist_book_down",
+            Self::RetryDownload => "retry_download",
+            Self::RefreshStatistics => "refresh_statistics",
+            Self::ExportStatistics => "export_statistics",
+            Self::ImportLibrary => "import_library",
+            Self::PreviewImport => "preview_import",
+            Self::VerifyFiles => "verify_files",
+            Self::VerifyFilesFully => "verify_files_fully",
+            Self::FindDuplicates => "find_duplicates",
+            Self::PruneDownloads => "prune_downloads",
+            Self::ClearCache => "clear_cache",
+            Self::ResetSettings => "reset_settings",
+            Self::FindDevices => "find_devices",
+            Self::PairDevice => "pair_device",
+            Self::ChooseOutputDevice => "choose_output_device",
+        }
+    }
+
+    /// Returns the action a `[keybindings]` name stands for
+    pub fn from_name(name: &str) -> Option<Action> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    /// Returns the keys bound to the action unless the config changes them,
+    /// the one shown first
+    pub fn default_keys(self) -> Vec<KeyBinding> {
+        use KeyCode::Char;
+
+        match self {
+            Self::TogglePlayback => vec![KeyBinding::plain(Char(' '))],
+            Self::SeekBackward => vec![KeyBinding::plain(KeyCode::Left)],
+            Self::SeekForward => vec![KeyBinding::plain(KeyCode::Right)],
+            Self::VolumeUp => vec![KeyBinding::plain(Char('+')), KeyBinding::plain(Char('='))],
+            Self::VolumeDown => vec![KeyBinding::plain(Char('-')), KeyBinding::plain(Char('_'))],
+            Self::SpeedDown => vec![KeyBinding::plain(Char('['))],
+            Self::SpeedUp => vec![KeyBinding::plain(Char(']'))],
+            Self::NextChapter => vec![KeyBinding::plain(Char('n'))],
+            Self::PreviousChapter => {
+                vec![KeyBinding::plain(Char('p')), KeyBinding::plain(Char('b'))]
+            }
+            Self::NextView => vec![KeyBinding::plain(KeyCode::Tab)],
+            Self::ToggleHelp => vec![KeyBinding::plain(Char('h'))],
+            Self::ToggleTheme => vec![KeyBinding::plain(Char('t'))],
+            Self::Search => vec![KeyBinding::plain(Char('/'))],
+            // The event loop handles it before any popup sees the key
+            Self::Quit => vec![KeyBinding::plain(Char('q'))],
+            Self::ShowBookDetail => vec![KeyBinding::plain(Char('i'))],
+            Self::ToggleFavorite => vec![KeyBinding::plain(Char('f'))],
+            Self::DeleteBook => vec![KeyBinding::plain(Char('d'))],
+            Self::EditChapters => vec![KeyBinding::plain(Char('e'))],
+            Self::CycleEqualizer => vec![KeyBinding::plain(Char('E'))],
+            Self::ToggleSpeedRamp => vec![KeyBinding::plain(Char('R'))],
+            Self::CycleSleepTimer => vec![KeyBinding::plain(Char('z'))],
+            Self::ExtendSleepTimer => vec![KeyBinding::plain(Char('Z'))],
+            Self::AddBookmark => vec![KeyBinding::plain(Char('b'))],
+            Self::DeleteBookmark => vec![KeyBinding::plain(Char('d'))],
+            Self::ClearAutoBookmarks => vec![KeyBinding::plain(Char('X'))],
+            Self::ExportBookmarks => vec![KeyBinding::ctrl(Char('e'))],
+            Self::ShareBookmark => vec![KeyBinding::plain(Char('s'))],
+            Self::FilterSearch => vec![KeyBinding::plain(Char('F'))],
+            Self::ShufflePlaylist => vec![KeyBinding::shift(KeyCode::Enter)],
+            Self::NewPlaylist => vec![KeyBinding::plain(Char('n'))],
+            Self::DeletePlaylist => vec![KeyBinding::plain(Char('d'))],
+            Self::OpenPlaylist => vec![KeyBinding::plain(Char('o'))],
+            Self::AddToPlaylist => vec![KeyBinding::plain(Char('a'))],
+            Self::RemoveFromPlaylist => vec![KeyBinding::plain(Char('x'))],
+            Self::MovePlaylistBookUp => vec![KeyBinding::plain(Char('K'))],
+            Self::MovePlaylistBookDown => vec![KeyBinding::plain(Char('J'))],
+            Self::RetryDownload => vec![KeyBinding::plain(Char('r'))],
+            Self::RefreshStatistics => vec![KeyBinding::plain(Char('r'))],
+            Self::ExportStatistics => vec![KeyBinding::plain(Char('e'))],
+            Self::ImportLibrary => vec![KeyBinding::plain(Char('i'))],
+            Self::PreviewImport => vec![KeyBinding::plain(Char('I'))],
+            Self::VerifyFiles => vec![KeyBinding::plain(Char('v'))],
+            Self::VerifyFilesFully => vec![KeyBinding::plain(Char('V'))],
+            Self::FindDuplicates => vec![KeyBinding::plain(Char('d'))],
+            Self::PruneDownloads => vec![KeyBinding::plain(Char('p'))],
+            Self::ClearCache => vec![KeyBinding::plain(Char('C'))],
+            Self::ResetSettings => vec![KeyBinding::plain(Char('r'))],
+            Self::FindDevices => vec![KeyBinding::plain(Char('D'))],
+            Self::PairDevice => vec![KeyBinding::plain(Char('P'))],
+            Self::ChooseOutputDevice => vec![KeyBinding::plain(Char('o'))],
+            Self::OpenView(_) => vec![],
+        }
+    }
+
+    /// Returns the view the action's keys work in, `None` for every view
+    pub fn view(self) -> Option<View> {
+        match self {
+            Self::ShowBookDetail | Self::ToggleFavorite | Self::DeleteBook => Some(View::Library),
+            Self::NextChapter
+            | Self::PreviousChapter
+            | Self::EditChapters
+            | Self::CycleEqualizer
+            | Self::ToggleSpeedRamp
+            | Self::CycleSleepTimer
+            | Self::ExtendSleepTimer => Some(View::Player),
+            Self::AddBookmark
+            | Self::DeleteBookmark
+            | Self::ClearAutoBookmarks
+            | Self::ExportBookmarks
+            | Self::ShareBookmark => Some(View::Bookmarks),
+            Self::FilterSearch => Some(View::Search),
+            Self::ShufflePlaylist
+            | Self::NewPlaylist
+            | Self::DeletePlaylist
+            | Self::OpenPlaylist
+            | Self::AddToPlaylist
+            | Self::RemoveFromPlaylist
+            | Self::MovePlaylistBookUp
+            | Self::MovePlaylistBookDown => Some(View::Playlists),
+            Self::RetryDownload => Some(View::Downloads),
+            Self::RefreshStatistics | Self::ExportStatistics => Some(View::Statistics),
+            Self::ImportLibrary
+            | Self::PreviewImport
+            | Self::VerifyFiles
+            | Self::VerifyFilesFully
+            | Self::FindDuplicates
+            | Self::PruneDownloads
+            | Self::ClearCache
+            | Self::ResetSettings
+            | Self::FindDevices
+            | Self::PairDevice
+            | Self::ChooseOutputDevice => Some(View::Settings),
+            _ => None,
+        }
+    }
+
+    /// Whether the action changes the library, which read-only mode refuses
+    pub fn changes_library(self) -> bool {
+        matches!(
+            self,
+            Self::ToggleFavorite
+                | Self::DeleteBook
+                | Self::EditChapters
+                | Self::AddBookmark
+                | Self::DeleteBookmark
+                | Self::ClearAutoBookmarks
+                | Self::ImportLibrary
+                | Self::NewPlaylist
+                | Self::DeletePlaylist
+                | Self::AddToPlaylist
+                | Self::RemoveFromPlaylist
+                | Self::MovePlaylistBookUp
+                | Self::MovePlaylistBookDown
+        )
+    }
+
+    /// Returns why the action cannot run in `state`, `None` if it can
+    pub fn unavailable_reason(self, state: &AppState) -> Option<&'static str> {
+        let book_loaded = state.playback.current_file.is_some();
+        match self.view() {
+            Some(view) if view != state.view => {
+                return Some(match view {
+                    View::Library => "Only in the library view",
+                    View::Player => "Only in the player view",
+                    View::Bookmarks => "Only in the bookmarks view",
+                    View::Search => "Only in the search view",
+                    View::Playlists => "Only in the playlists view",
+                    View::Downloads => "Only in the downloads view",
+                    View::Statistics => "Only in the statistics view",
+                    _ => "Only in the settings view",
+                });
+            }
+            _ => {}
+        }
+
+        match self {
+            _ if state.read_only && self.changes_library() => Some(READ_ONLY_REASON),
+            Self::TogglePlayback
+            | Self::SeekBackward
+            | Self::SeekForward
+            | Self::NextChapter
+            | Self::PreviousChapter
+            | Self::EditChapters
+            | Self::ToggleSpeedRamp
+            | Self::CycleSleepTimer
+            | Self::AddBookmark
+                if !book_loaded =>
+            {
+                Some("No book loaded")
+            }
+            Self::ExtendSleepTimer if state.playback.sleep_remaining.is_none() => {
+                Some("No sleep timer set")
+            }
+            Self::NextChapter | Self::PreviousChapter if state.chapters.is_empty() => {
+                Some("This book has no chapters")
+            }
+            Self::ShowBookDetail | Self::ToggleFavorite | Self::DeleteBook
+                if state.library_items_count == 0 =>
+            {
+                Some("The library is empty")
+            }
+            Self::DeleteBookmark
+            | Self::ClearAutoBookmarks
+            | Self::ExportBookmarks
+            | Self::ShareBookmark
+                if state.bookmarks.is_empty() =>
+            {
+                Some("No bookmarks")
+            }
+            Self::ShufflePlaylist
+            | Self::DeletePlaylist
+            | Self::OpenPlaylist
+            | Self::AddToPlaylist
+            | Self::RemoveFromPlaylist
+            | Self::MovePlaylistBookUp
+            | Self::MovePlaylistBookDown
+                if state.selected_playlist().is_none() =>
+            {
+                Some("No playlists")
+            }
+            Self::AddToPlaylist
+            | Self::RemoveFromPlaylist
+            | Self::MovePlaylistBookUp
+            | Self::MovePlaylistBookDown
+                if state
+                    .selected_playlist()
+                    .is_some_and(|playlist| playlist.smart) =>
+            {
+                Some("Smart playlists choose their own books")
+            }
+            Self::AddToPlaylist if state.library_items_count == 0 => Some("The library is empty"),
+            Self::RemoveFromPlaylist | Self::MovePlaylistBookUp | Self::MovePlaylistBookDown
+                if state
+                    .open_playlist
+                    .as_ref()
+                    .is_none_or(|open| open.books.is_empty()) =>
+            {
+                Some("No playlist books shown")
+            }
+            Self::RefreshStatistics if state.statistics_loading => Some("Already loading"),
+            Self::ExportStatistics if state.library_stats.is_none() => Some("Not loaded yet"),
+            Self::FindDevices | Self::PairDevice if state.lan.is_none() => Some("LAN sync is off"),
+            Self::PairDevice if state.lan.as_ref().is_some_and(|lan| lan.peers.is_empty()) => {
+                Some("No devices found")
+            }
+            Self::OpenView(view) if view == state.view => Some("Already open"),
+            _ => None,
+        }
+    }
+
+    /// Whether the action can run in `state`
+    pub fn is_available(self, state: &AppState) -> bool {
+        self.unavailable_reason(state).is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_key_runs_two_actions() {
+        let views = [
+            View::Library,
+            View::Player,
+            View::Bookmarks,
+            View::Search,
+            View::Playlists,
+            View::Statistics,
+            View::Settings,
+            View::Downloads,
+            View::Help,
+        ];
+        for view in views {
+            let mut bound = Vec::new();
+            for action in Action::ALL
+                .into_iter()
+                .filter(|a| a.view().is_none_or(|v| v == view))
+            {
+                for key in action.default_keys() {
+                    assert!(
+                        !bound.contains(&key),
+                        "{} is bound twice in {:?}",
+                        key,
+                        view
+                    );
+                    assert_ne!(key, PALETTE_KEY);
+                    bound.push(key);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_availability_follows_state() {
+        let mut state = AppState::new();
+        state.playback.current_file = None;
+        assert_eq!(
+            Action::TogglePlayback.unavailable_reason(&state),
+            Some("No book loaded")
+        );
+        assert_eq!(
+            Action::AddBookmark.unavailable_reason(&state),
+            Some("Only in the bookmarks view")
+        );
+        assert_eq!(
+            Action::OpenView(View::Library).unavailable_reason(&state),
+            Some("Already open")
+        );
+        assert!(Action::OpenView(View::Settings).is_available(&state));
+
+        state.playback.current_file = Some("Book".to_string());
+        state.set_view(View::Bookmarks);
+        assert!(Action::AddBookmark.is_available(&state));
+        assert!(!Action::ExportBookmarks.is_available(&state));
+
+        state.set_view(View::Settings);
+        assert_eq!(
+            Action::FindDevices.unavailable_reason(&state),
+            Some("LAN sync is off")
+        );
+        state.lan = Some(crate::state::LanDevices::default());
+        assert!(Action::FindDevices.is_available(&state));
+        assert_eq!(
+            Action::PairDevice.unavailable_reason(&state),
+            Some("No devices found")
+        );
+
+        state.set_view(View::Bookmarks);
+        state.read_only = true;
+        assert_eq!(
+            Action::AddBookmark.unavailable_reason(&state),
+            Some(READ_ONLY_REASON)
+        );
+        assert!(Action::TogglePlayback.is_available(&state));
+    }
+
+    #[test]
+    fn test_smart_playlists_refuse_changes() {
+        use crate::state::{OpenPlaylist, PlaylistRow};
+
+        let mut state = AppState::new();
+        state.set_view(View::Playlists);
+        assert_eq!(
+            Action::DeletePlaylist.unavailable_reason(&state),
+            Some("No playlists")
+        );
+        assert!(Action::NewPlaylist.is_available(&state));
+
+        let row = |smart| PlaylistRow {
+            id: storystream_core::PlaylistId::new(),
+            name: "List".to_string(),
+            smart,
+            books: 0,
+            duration: std::time::Duration::ZERO,
+        };
+        state.playlists = vec![row(false), row(true)];
+        assert!(Action::AddToPlaylist.is_available(&state));
+        assert_eq!(
+            Action::MovePlaylistBookUp.unavailable_reason(&state),
+            Some("No playlist books shown")
+        );
+
+        state.select_next();
+        assert_eq!(
+            Action::AddToPlaylist.unavailable_reason(&state),
+            Some("Smart playlists choose their own books")
+        );
+        state.open_playlist = Some(OpenPlaylist {
+            books: Vec::new(),
+            selected: 0,
+        });
+        assert!(!Action::RemoveFromPlaylist.is_available(&state));
+        assert!(Action::DeletePlaylist.is_available(&state));
+    }
+
+    #[test]
+    fn test_key_binding_display() {
+        assert_eq!(PALETTE_KEY.to_string(), "Ctrl+P");
+        assert_eq!(
+            Action::TogglePlayback.default_keys()[0].to_string(),
+            "Space"
+        );
+        assert_eq!(
+            Action::ShufflePlaylist.default_keys()[0].to_string(),
+            "Shift+Enter"
+        );
+        assert_eq!(Action::SeekBackward.default_keys()[0].to_string(), "←");
+    }
+
+    #[test]
+    fn test_key_binding_parse() {
+        let parse = KeyBinding::parse;
+        assert_eq!(parse("l"), Some(KeyBinding::plain(KeyCode::Char('l'))));
+        assert_eq!(parse("space"), Some(KeyBinding::plain(KeyCode::Char(' '))));
+        assert_eq!(parse("+"), Some(KeyBinding::plain(KeyCode::Char('+'))));
+        assert_eq!(parse("Ctrl++"), Some(KeyBinding::ctrl(KeyCode::Char('+'))));
+        assert_eq!(
+            parse("shift+b"),
+            Some(KeyBinding::plain(KeyCode::Char('B')))
+        );
+        assert_eq!(
+            parse("shift+enter"),
+            Some(KeyBinding::shift(KeyCode::Enter))
+        );
+        assert_eq!(parse("F5"), Some(KeyBinding::plain(KeyCode::F(5))));
+        assert_eq!(parse("f13"), None);
+        assert_eq!(parse("spacebar"), None);
+        assert_eq!(parse(""), None);
+
+        // What the palette and help show reads back as the same key
+        for key in [PALETTE_KEY, KeyBinding::ctrl(KeyCode::Char('e'))] {
+            assert_eq!(parse(&key.to_string()), Some(key));
+        }
+        let alt = parse("alt+x").unwrap();
+        assert_eq!(alt.to_string(), "Alt+X");
+        assert_eq!(parse(&alt.to_string()), Some(alt));
+    }
+
+    #[test]
+    fn test_names_are_unique() {
+        for action in Action::ALL {
+            assert_eq!(Action::from_name(action.name()), Some(action));
+        }
+        assert_eq!(
+            Action::from_name("play_pause"),
+            Some(Action::TogglePlayback)
+        );
+        assert_eq!(Action::from_name("PlayPause"), None);
+    }
+}
@@ -0,0 +1,354 @@
+//! Text announcements for screen readers
+//!
+//! In accessibility mode the [`Announcer`] watches the app state between
+//! frames and describes what changed — playback starting or pausing, a new
+//! chapter, the view or selected item, errors — in a line of text. The UI
+//! draws that line at the bottom of the screen with the cursor on it, which
+//! is where screen readers read from. When stderr is redirected the line is
+//! written there too, so `storystream 2> >(espeak)` speaks it.
+//!
+//! Announcements closer together than [`MIN_GAP`] are held back and only the
+//! latest is shown, so holding down an arrow key announces where it stops
+//! rather than every row passed on the way. Errors are never held back.
+
+use crate::state::{format_duration, AppState, View};
+use std::io::{self, IsTerminal, Write};
+use std::time::{Duration, Instant};
+use storystream_config::AnnounceVerbosity;
+
+/// Shortest time between two announcements
+pub const MIN_GAP: Duration = Duration::from_millis(500);
+
+/// What an announcement is about, which decides the verbosity it needs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnouncementKind {
+    Error,
+    Playback,
+    Chapter,
+    Selection,
+    Status,
+}
+
+impl AnnouncementKind {
+    /// Lowest verbosity that announces this kind
+    fn verbosity(self) -> AnnounceVerbosity {
+        match self {
+            Self::Error | Self::Playback => AnnounceVerbosity::Terse,
+            Self::Chapter | Self::Selection => AnnounceVerbosity::Normal,
+            Self::Status => AnnounceVerbosity::Verbose,
+        }
+    }
+}
+
+/// The parts of the app state announcements describe
+#[derive(Debug, Clone, PartialEq)]
+struct Snapshot {
+    view: View,
+    playing: bool,
+    position: Duration,
+    duration: Duration,
+    chapter: Option<String>,
+    selection: Option<String>,
+    status: Option<(String, bool)>,
+}
+
+impl Snapshot {
+    fn of(state: &AppState) -> Self {
+        let chapter = state.playback.chapter.and_then(|index| {
+            let chapter = state.chapters.get(index)?;
+            Some(format!("Chapter {}: {}", index + 1, chapter.title))
+        });
+        Self {
+            view: state.view,
+            playing: state.playback.is_playing,
+            position: state.playback.position,
+            duration: state.playback.duration,
+            chapter,
+            selection: selected_item(state),
+            status: state
+                .status_message
+                .clone()
+                .map(|message| (message, state.status_is_error)),
+        }
+    }
+
+    /// What changed from `before` to this snapshot, in announcement order
+    fn changes_since(&self, before: &Snapshot) -> Vec<(AnnouncementKind, String)> {
+        let mut changes = Vec::new();
+
+        if let Some((message, true)) = &self.status {
+            if self.status != before.status {
+                changes.push((AnnouncementKind::Error, message.clone()));
+            }
+        }
+        if self.playing != before.playing {
+            let text = if self.playing {
+                format!(
+                    "Playing at {} of {}",
+                    format_duration(self.position),
+                    format_duration(self.duration)
+                )
+            } else {
+                format!("Paused at {}", format_duration(self.position))
+            };
+            changes.push((AnnouncementKind::Playback, text));
+        }
+        if self.chapter != before.chapter {
+            if let Some(chapter) = &self.chapter {
+                changes.push((AnnouncementKind::Chapter, chapter.clone()));
+            }
+        }
+        if self.view != before.view {
+            changes.push((AnnouncementKind::Selection, view_name(self.view)));
+        }
+        if self.selection != before.selection || self.view != before.view {
+            if let Some(selection) = &self.selection {
+                changes.push((AnnouncementKind::Selection, selection.clone()));
+            }
+        }
+        if let Some((message, false)) = &self.status {
+            if self.status != before.status {
+                changes.push((AnnouncementKind::Status, message.clone()));
+            }
+        }
+        changes
+    }
+}
+
+/// Turns app state changes into text announcements
+#[derive(Debug, Clone)]
+pub struct Announcer {
+    verbosity: AnnounceVerbosity,
+    /// Whether announcements are also written to stderr
+    echo: bool,
+    seen: Option<Snapshot>,
+    /// Announcement held back by the rate limit
+    pending: Option<String>,
+    announced_at: Option<Instant>,
+}
+
+impl Announcer {
+    /// Creates an announcer that also writes to stderr when it is not the
+    /// terminal
+    pub fn new(verbosity: AnnounceVerbosity) -> Self {
+        Self {
+            verbosity,
+            echo: !io::stderr().is_terminal(),
+            seen: None,
+            pending: None,
+            announced_at: None,
+        }
+    }
+
+    /// Announces what changed in `state` since the last call, returning the
+    /// new line to show if there is one
+    ///
+    /// The first call only takes note of the state.
+    pub fn observe(&mut self, state: &AppState, now: Instant) -> Option<String> {
+        let snapshot = Snapshot::of(state);
+        let changes = match &self.seen {
+            Some(before) => snapshot.changes_since(before),
+            None => Vec::new(),
+        };
+        self.seen = Some(snapshot);
+
+        let urgent = changes
+            .iter()
+            .any(|(kind, _)| *kind == AnnouncementKind::Error);
+        let texts = changes
+            .into_iter()
+            .filter(|(kind, _)| kind.verbosity() <= self.verbosity)
+            .map(|(_, text)| text)
+            .collect::<Vec<_>>();
+        if !texts.is_empty() {
+            // A newer announcement replaces one still held back
+            self.pending = Some(texts.join(". "));
+        }
+
+        let due = urgent
+            || self
+                .announced_at
+                .is_none_or(|at| now.duration_since(at) >= MIN_GAP);
+        if !due {
+            return None;
+        }
+        let line = self.pending.take()?;
+        self.announced_at = Some(now);
+        if self.echo {
+            let _ = writeln!(io::stderr(), "{}", line);
+        }
+        Some(line)
+    }
+}
+
+/// Spoken name of a view
+fn view_name(view: View) -> String {
+    let name = match view {
+        View::Library => "Library",
+        View::Player => "Player",
+        View::Bookmarks => "Bookmarks",
+        View::Search => "Search results",
+        View::Playlists => "Playlists",
+        View::Statistics => "Statistics",
+        View::Settings => "Settings",
+        View::Downloads => "Downloads",
+        View::Help => "Help",
+        View::Plugin => "Plugins",
+    };
+    format!("{} view", name)
+}
+
+/// The selected item of the current view, with its place in the list
+fn selected_item(state: &AppState) -> Option<String> {
+    let index = state.selected_item;
+    let (name, count) = match state.view {
+        View::Library => {
+            let rows = state.library_rows.as_ref()?;
+            let row = rows.rows.get(index.checked_sub(rows.first)?)?.as_ref()?;
+            let mut name = match &row.author {
+                Some(author) => format!("{} by {}", row.title, author),
+                None => row.title.clone(),
+            };
+            if row.unavailable {
+                name.push_str(", drive not connected");
+            } else if row.continue_listening {
+                name.push_str(", continue listening");
+            }
+            (name, state.library_items_count)
+        }
+        View::Search => {
+            let book = state.search_results.get(index)?;
+            (book.title.clone(), state.search_results.len())
+        }
+        View::Bookmarks => {
+            let bookmark = state.bookmarks.get(index)?;
+            let at = format_duration(Duration::from_millis(bookmark.position.as_millis()));
+            let name = match &bookmark.title {
+                Some(title) => format!("{} at {}", title, at),
+                None => format!("Bookmark at {}", at),
+            };
+            (name, state.bookmarks.len())
+        }
+        _ => return None,
+    };
+    Some(format!("{}, {} of {}", name, index + 1, count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{BookRow, LibraryRows};
+
+    fn announcer(verbosity: AnnounceVerbosity) -> Announcer {
+        Announcer {
+            echo: false,
+            ..Announcer::new(verbosity)
+        }
+    }
+
+    fn library_state() -> AppState {
+        let mut state = AppState::new();
+        state.library_items_count = 2;
+        state.library_rows = Some(LibraryRows {
+            first: 0,
+            rows: vec![
+                Some(BookRow {
+                    title: "Dune".to_string(),
+                    author: Some("Frank Herbert".to_string()),
+                    duration: Duration::from_secs(3600),
+                    progress: 0,
+                    favorite: false,
+                    playing: false,
+                    unavailable: false,
+                    continue_listening: false,
+                }),
+                Some(BookRow {
+                    title: "Emma".to_string(),
+                    author: None,
+                    duration: Duration::from_secs(3600),
+                    progress: 0,
+                    favorite: false,
+                    playing: false,
+                    unavailable: false,
+                    continue_listening: false,
+                }),
+            ],
+        });
+        state
+    }
+
+    #[test]
+    fn test_playback_and_selection() {
+        let start = Instant::now();
+        let mut announcer = announcer(AnnounceVerbosity::Normal);
+        let mut state = library_state();
+        assert_eq!(announcer.observe(&state, start), None);
+
+        state.select_next();
+        assert_eq!(
+            announcer.observe(&state, start).as_deref(),
+            Some("Emma, 2 of 2")
+        );
+
+        state.playback.is_playing = true;
+        state.playback.position = Duration::from_secs(125);
+        state.playback.duration = Duration::from_secs(3600);
+        let later = start + MIN_GAP;
+        assert_eq!(
+            announcer.observe(&state, later).as_deref(),
+            Some("Playing at 02:05 of 01:00:00")
+        );
+
+        state.playback.is_playing = false;
+        state.set_view(View::Player);
+        assert_eq!(
+            announcer.observe(&state, later + MIN_GAP).as_deref(),
+            Some("Paused at 02:05. Player view")
+        );
+    }
+
+    #[test]
+    fn test_rate_limit_keeps_latest() {
+        let start = Instant::now();
+        let mut announcer = announcer(AnnounceVerbosity::Normal);
+        let mut state = library_state();
+        announcer.observe(&state, start);
+
+        state.select_next();
+        assert!(announcer.observe(&state, start).is_some());
+        state.select_previous();
+        let soon = start + MIN_GAP / 2;
+        assert_eq!(announcer.observe(&state, soon), None);
+
+        assert_eq!(
+            announcer.observe(&state, start + MIN_GAP).as_deref(),
+            Some("Dune by Frank Herbert, 1 of 2")
+        );
+
+        // Errors are not held back
+        state.set_error("Failed to save bookmark");
+        assert_eq!(
+            announcer.observe(&state, start + MIN_GAP).as_deref(),
+            Some("Failed to save bookmark")
+        );
+        assert_eq!(announcer.observe(&state, start + MIN_GAP * 3), None);
+    }
+
+    #[test]
+    fn test_verbosity() {
+        let start = Instant::now();
+        let mut terse = announcer(AnnounceVerbosity::Terse);
+        let mut verbose = announcer(AnnounceVerbosity::Verbose);
+        let mut state = library_state();
+        terse.observe(&state, start);
+        verbose.observe(&state, start);
+
+        state.select_next();
+        state.set_status("Theme: Dark");
+        assert_eq!(terse.observe(&state, start), None);
+        assert_eq!(
+            verbose.observe(&state, start).as_deref(),
+            Some("Emma, 2 of 2. Theme: Dark")
+        );
+    }
+}
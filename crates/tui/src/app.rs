@@ -32,7 +32,9 @@ impl App {
     /// Runs the application
     pub fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> TuiResult<()> {
         while !self.state.should_quit {
-            terminal.draw(|frame| ui::render(frame, &self.state, &self.theme))?;
+            terminal.draw(|frame| {
+                ui::render(frame, &self.state, &self.theme);
+            })?;
 
             match self.event_handler.next()? {
                 AppEvent::Key(key) => self.handle_key(key.code, key.modifiers)?,
@@ -81,8 +83,10 @@ impl App {
                         5 // Statistics
                     } else if col < 83 {
                         6 // Settings
+                    } else if col < 96 {
+                        7 // Downloads
                     } else {
-                        7 // Help
+                        8 // Help
                     };
 
                     // Switch to clicked tab (state preservation happens in set_view)
@@ -94,6 +98,7 @@ impl App {
                         4 => View::Playlists,
                         5 => View::Statistics,
                         6 => View::Settings,
+                        7 => View::Downloads,
                         _ => View::Help,
                     });
 
@@ -107,6 +112,7 @@ impl App {
                             View::Playlists => "Playlists",
                             View::Statistics => "Statistics",
                             View::Settings => "Settings",
+                            View::Downloads => "Downloads",
                             View::Help => "Help",
                             View::Plugin => "Plugin",
                         }
@@ -202,6 +208,7 @@ impl App {
             View::Playlists => self.handle_playlists_keys(code, modifiers)?,
             View::Statistics => self.handle_statistics_keys(code, modifiers)?,
             View::Settings => self.handle_settings_keys(code, modifiers)?,
+            View::Downloads => self.handle_downloads_keys(code, modifiers)?,
             View::Help => {
                 // Help view just needs Esc or h to go back
                 if let KeyCode::Esc = code {
@@ -479,6 +486,23 @@ impl App {
         Ok(())
     }
 
+    /// Handles downloads view keys
+    fn handle_downloads_keys(&mut self, code: KeyCode, _modifiers: KeyModifiers) -> TuiResult<()> {
+        match code {
+            KeyCode::Up => {
+                self.state.select_previous();
+            }
+            KeyCode::Down => {
+                self.state.select_next();
+            }
+            KeyCode::Char('r') => {
+                self.state.set_status("Retry failed download");
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
     /// Handles tick events
     fn handle_tick(&mut self) -> TuiResult<()> {
         // Update playback position if playing
@@ -502,7 +526,8 @@ impl App {
             View::Search => View::Playlists,
             View::Playlists => View::Statistics,
             View::Statistics => View::Settings,
-            View::Settings => View::Help,
+            View::Settings => View::Downloads,
+            View::Downloads => View::Help,
             View::Help => View::Library,
             View::Plugin => View::Library,
         };
@@ -521,6 +546,7 @@ impl App {
                 View::Playlists => "Playlists",
                 View::Statistics => "Statistics",
                 View::Settings => "Settings",
+                View::Downloads => "Downloads",
                 View::Help => "Help",
                 View::Plugin => "Plugin",
             }
@@ -537,7 +563,8 @@ impl App {
             View::Playlists => View::Search,
             View::Statistics => View::Playlists,
             View::Settings => View::Statistics,
-            View::Help => View::Settings,
+            View::Downloads => View::Settings,
+            View::Help => View::Downloads,
             View::Plugin => View::Help,
         };
 
@@ -587,6 +614,8 @@ mod tests {
         app.cycle_view();
         assert_eq!(app.state.view, View::Settings);
         app.cycle_view();
+        assert_eq!(app.state.view, View::Downloads);
+        app.cycle_view();
         assert_eq!(app.state.view, View::Help);
         app.cycle_view();
         assert_eq!(app.state.view, View::Library);
@@ -609,6 +638,7 @@ mod tests {
         app.cycle_view(); // To Playlists
         app.cycle_view(); // To Statistics
         app.cycle_view(); // To Settings
+        app.cycle_view(); // To Downloads
         app.cycle_view(); // To Help
         app.cycle_view(); // Back to Library
 
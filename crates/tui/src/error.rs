@@ -87,4 +87,4 @@ mod tests {
             assert!(!err.to_string().is_empty());
         }
     }
-}
\ No newline at end of file
+}
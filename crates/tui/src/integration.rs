@@ -7,26 +7,103 @@
 //! - Database for persistence
 //! - Config for settings
 
-use crate::{error::TuiResult, state::AppState, theme::{Theme, ThemeType}, ui, TuiError};
+use crate::{
+    actions::{Action, PALETTE_KEY, READ_ONLY_REASON},
+    announce::Announcer,
+    error::TuiResult,
+    lan::LanSync,
+    library_window::{LibraryWindow, LIBRARY_ROWS},
+    mpris::MprisServer,
+    palette::CommandPalette,
+    remote::{RemoteBook, RemoteCommand, RemoteReply, RemoteServer, RemoteStatus},
+    settings::{self, Setting, SettingKind},
+    state::{
+        format_duration, AppState, BookDetail, BookField, ChapterEditor, FilterPopup, InputPurpose,
+        ListeningLock, MaintenanceList, OpenPlaylist, OutputPicker, PlaylistRow, Scrub,
+        Subscription, SuggestionRow, SyncBanner, TextPrompt, UpNext,
+    },
+    theme::{Theme, ThemeType},
+    ui::{
+        self,
+        confirm::{ConfirmAction, ConfirmDialog},
+        text_input::InputOutcome,
+    },
+    TuiError,
+};
+use chrono::TimeZone;
 use crossterm::event::{
-    DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers, MouseEventKind,
+    DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers, MouseButton,
+    MouseEventKind,
 };
-use crossterm::{execute, terminal::*};
-use media_engine::{engine::EngineConfig, MediaEngine, Speed};
-use ratatui::{backend::CrosstermBackend, Terminal};
+use crossterm::{clipboard::CopyToClipboard, execute, terminal::*};
+use media_engine::{engine::EngineConfig, MediaEngine, MediaEvent, SnappedPosition, Speed};
+use ratatui::{backend::CrosstermBackend, layout::Rect, Terminal, TerminalOptions, Viewport};
 use std::{
+    collections::HashSet,
     io,
+    path::{Path, PathBuf},
+    sync::mpsc::Receiver,
     sync::{Arc, Mutex},
-    time::Duration,
+    time::{Duration, Instant},
+};
+use storystream_config::{
+    app_config::ColorScheme, AppConfig, Config, ConfigManager, ConfigResult, PlayerConfig,
+};
+use storystream_core::types::book::{Book, Chapter};
+use storystream_core::types::chapters::chapters_to_cue;
+use storystream_core::types::{EqualizerPreset, SleepTimer};
+use storystream_core::{
+    csv_field, AppError, AutoBookmarkTrigger, BookId, Bookmark, BookmarkId, CacheManager,
+    LibraryStats, PlaybackSpeed, Playlist, PlaylistId, PlaylistType, SharedBookmark, Timestamp,
 };
-use storystream_config::{app_config::ColorScheme, ConfigManager};
-use storystream_core::types::book::Book;
 use storystream_database::{
-    connection::{connect, DatabaseConfig},
-    queries::books,
+    connection::{connect, is_read_only, DatabaseConfig},
+    optimize_if_due,
+    queries::{books, loudness, playback, playlists, podcasts, stats},
+    search::{search_books, search_books_filtered, SearchFilter},
     DbPool,
 };
-use storystream_library::LibraryManager;
+use storystream_library::{
+    book_files, export_book, export_file_name, resolve_shared, BookmarkFormat, BookmarkStore,
+    DuplicateGroup, FileIssue, FileProblem, ImportEvent, ImportPlan, Janitor, LibraryError,
+    LibraryManager, LibraryResult, ListeningLimits, LoudnessReport, MetadataEdit, NextSuggestion,
+    PipelineEvent, PipelineProgress, PipelineReport, PlannedAction, PlannedImport, PlaylistEvent,
+    PlaylistProgress, ScanCancel, SharedTarget, SuggestedAction, SuggestedChapter,
+    SuggestionReason, TagWrite, VerifyDepth, VerifyEvent, VerifyReport, VerifyScope, VolumeChange,
+    VolumeMonitor,
+};
+use storystream_network::{
+    AdvancedDownloadManager, Client, DownloadHistory, DownloadManagerConfig,
+};
+use storystream_sync_engine::{DeviceId, FolderTransport, SyncConfig, SyncEngine};
+use tokio::{sync::mpsc, task::JoinHandle};
+
+/// Most books a search shows
+const SEARCH_LIMIT: i64 = 200;
+
+/// Finished downloads listed in the downloads view
+const DOWNLOAD_HISTORY_LIMIT: usize = 200;
+
+/// How often the downloads view reloads while it is shown
+const DOWNLOADS_REFRESH: Duration = Duration::from_secs(1);
+
+/// Help lines one notch of the mouse wheel scrolls
+const HELP_WHEEL_LINES: isize = 3;
+
+/// Progress bar columns a drag-seek may snap across
+const SNAP_COLUMNS: u32 = 2;
+
+/// Age after which the maintenance menu prunes download history
+const DOWNLOAD_HISTORY_MAX_AGE_DAYS: i64 = 30;
+
+/// How often the library folders are checked for drives coming and going
+const VOLUME_PROBE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often positions are exchanged through the sync folder
+const SYNC_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How far ahead another device must be before it is worth mentioning
+const SYNC_BANNER_THRESHOLD: Duration = Duration::from_secs(30);
 
 /// Convert ColorScheme to ThemeType
 fn color_scheme_to_theme(scheme: ColorScheme) -> ThemeType {
@@ -37,18 +114,254 @@ fn color_scheme_to_theme(scheme: ColorScheme) -> ThemeType {
     }
 }
 
+/// Local wall-clock time of a timestamp, which the listening limits count in
+fn local_time(timestamp: Timestamp) -> Option<chrono::NaiveDateTime> {
+    chrono::Local
+        .timestamp_millis_opt(timestamp.as_millis())
+        .single()
+        .map(|time| time.naive_local())
+}
+
+/// Reads "start target change-per-hour", such as "1.25 1.75 0.1"
+///
+/// A trailing "x" on a speed is allowed.
+fn parse_speed_ramp(text: &str) -> Result<(Speed, Speed, f32), String> {
+    let values = text
+        .split_whitespace()
+        .map(|value| value.trim_end_matches(['x', 'X', '×']).parse::<f32>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| "expected numbers like 1.25 1.75 0.1".to_string())?;
+    let [start, target, per_hour] = values[..] else {
+        return Err("expected a start speed, a target speed and a change per hour".to_string());
+    };
+    Ok((Speed::new(start)?, Speed::new(target)?, per_hour))
+}
+
+/// How much time extending the sleep timer adds
+const SLEEP_EXTENSION: Duration = Duration::from_secs(15 * 60);
+
+/// The sleep timer preset after `current`: the fixed lengths from shortest
+/// to longest, then the end of the chapter, then off
+fn next_sleep_timer(current: Option<&SleepTimer>, has_chapters: bool) -> Option<SleepTimer> {
+    let minutes = match current {
+        None => 0,
+        Some(timer) if timer.is_end_of_chapter() => return None,
+        Some(timer) => timer.duration.as_seconds() / 60,
+    };
+    match SleepTimer::PRESET_MINUTES
+        .iter()
+        .find(|&&preset| preset > minutes)
+    {
+        Some(&preset) => Some(SleepTimer::new(Duration::from_secs(preset * 60).into())),
+        None if has_chapters => Some(SleepTimer::end_of_chapter()),
+        None => None,
+    }
+}
+
+/// Built-in presets plus the ones defined in the config file
+///
+/// A user preset with a built-in's name replaces it.
+fn equalizer_presets(player: &PlayerConfig) -> Vec<EqualizerPreset> {
+    let mut presets = EqualizerPreset::builtin();
+    for (name, gains) in &player.equalizer_presets {
+        let Ok(gains) = <[f32; 10]>::try_from(gains.as_slice()) else {
+            continue;
+        };
+        let preset = EqualizerPreset::from_gains(name.clone(), gains);
+        match presets
+            .iter_mut()
+            .find(|p| p.name.eq_ignore_ascii_case(name))
+        {
+            Some(existing) => *existing = preset,
+            None => presets.push(preset),
+        }
+    }
+    presets
+}
+
 /// Integrated TUI application with real services
 pub struct IntegratedTuiApp {
     terminal: Terminal<CrosstermBackend<io::Stdout>>,
     state: AppState,
     theme: Theme,
+    /// Where the last frame drew what the mouse can click
+    click_areas: ui::ClickAreas,
     media_engine: Arc<Mutex<MediaEngine>>,
     library_manager: Arc<LibraryManager>,
     db_pool: DbPool,
-    current_books: Vec<Book>,
+    /// Library list, loaded a page at a time around the selection
+    library: LibraryWindow<DbPool>,
+    /// Book loaded into the media engine
+    current_book: Option<Book>,
+    playlists: Vec<Playlist>,
+    /// Whether the loaded book belongs to the library's playlist session
+    playing_playlist: bool,
+    /// Media key integration, `None` without a session bus
+    mpris: Option<MprisServer>,
+    /// Remote control over HTTP, `None` unless `app.remote_control` is on
+    remote: Option<RemoteServer>,
+    /// Book being listened to and when the current listening session began
+    listening_since: Option<(BookId, Timestamp)>,
+    /// When the loaded book's position was last saved
+    position_saved: Option<Instant>,
+    /// Daily listening time and allowed hours, counting today's sessions
+    limits: ListeningLimits,
+    /// File verification running in the background
+    verification: Option<Verification>,
+    /// Import of the library folders running in the background
+    import: Option<LibraryImport>,
+    /// What the auto-import did with changed files, `None` unless `library.auto_import` is on
+    auto_imports: Option<mpsc::Receiver<ImportEvent>>,
+    /// Import plan being worked out in the background
+    planning: Option<JoinHandle<LibraryResult<ImportPlan>>>,
+    /// Import plan awaiting review in the maintenance menu
+    import_plan: Option<ImportPlan>,
+    /// Statistics being read for the statistics view
+    statistics: Option<JoinHandle<StatisticsReport>>,
+    /// Chapter breaks being looked for from the chapter editor
+    chapter_suggestion: Option<ChapterSuggestion>,
+    /// Loudness of the loaded book being measured to even out its volume
+    loudness_measurement: Option<LoudnessMeasurement>,
+    /// Books measured this session, which are not measured again if it failed
+    loudness_measured: HashSet<BookId>,
+    /// Problems found by the last verification, in the order shown
+    file_issues: Vec<FileIssue>,
+    /// Duplicate groups awaiting review, in the order shown
+    duplicates: Vec<DuplicateGroup>,
+    /// Download queue, running for the whole session
+    downloads: Arc<AdvancedDownloadManager>,
+    /// When the downloads view was last reloaded
+    downloads_refreshed: Option<Instant>,
+    /// Bookmarks of the loaded book, kept within the auto-bookmark limit
+    bookmark_store: BookmarkStore,
+    /// Pause that earns an auto-bookmark, `None` when disabled
+    auto_bookmark_pause: Option<Duration>,
+    /// Start of the current pause, until it gets its auto-bookmark
+    paused_since: Option<Instant>,
+    /// Position sync with other devices, `None` without a sync folder
+    sync: Option<PositionSync>,
+    /// Disk caches shared with the CLI, `None` if the cache folder is unusable
+    cache: Option<Arc<CacheManager>>,
+    /// Library folders on drives that may be unplugged
+    volumes: VolumeMonitor,
+    /// When the library folders were last checked
+    volumes_probed: Instant,
+    config_manager: ConfigManager,
+    /// Edits of the config file, applied as they are saved
+    config_changes: Receiver<ConfigResult<Config>>,
+    /// Color scheme in the config file, which only replaces a theme toggled
+    /// in the app when it is edited
+    color_scheme: ColorScheme,
+    /// Player settings, including the volume remembered per output device
+    player: PlayerConfig,
+    /// Identifier and name of the output device whose profile was applied
+    output_device: Option<(String, String)>,
+    /// Screen reader announcements, `None` outside accessibility mode
+    announcer: Option<Announcer>,
     tick_rate: Duration,
 }
 
+/// Positions shared with other devices through the sync folder or the local network
+struct PositionSync {
+    engine: Arc<SyncEngine>,
+    /// Shared folder, `None` without `app.sync_folder`
+    transport: Option<FolderTransport>,
+    /// Paired devices on the network, `None` without `app.lan_sync`
+    lan: Option<LanSync>,
+    /// Last position recorded for the loaded book, in milliseconds
+    recorded: Option<u64>,
+    /// When positions were last exchanged, `None` to exchange on the next tick
+    exchanged: Option<Instant>,
+}
+
+impl PositionSync {
+    /// Joins the sync folder and the network as this device, whose ID is kept in `config_dir`
+    ///
+    /// Returns `None` when neither is configured.
+    fn open(config_dir: &Path, app: &AppConfig) -> Result<Option<Self>, String> {
+        if app.sync_folder.is_none() && !app.lan_sync {
+            return Ok(None);
+        }
+        let device_id = DeviceId::load_or_create(&config_dir.join("device_id"))
+            .map_err(|e| format!("Device ID error: {}", e))?;
+        let transport = match &app.sync_folder {
+            Some(folder) => Some(
+                FolderTransport::new(folder, device_id.clone())
+                    .map_err(|e| format!("Sync folder error: {}", e))?,
+            ),
+            None => None,
+        };
+        let engine = Arc::new(SyncEngine::new(SyncConfig {
+            device_id,
+            device_name: app.device_name.clone(),
+            ..Default::default()
+        }));
+        let lan = if app.lan_sync {
+            LanSync::start(Arc::clone(&engine), config_dir)
+        } else {
+            None
+        };
+        if transport.is_none() && lan.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            engine,
+            transport,
+            lan,
+            recorded: None,
+            exchanged: None,
+        }))
+    }
+}
+
+/// A file verification started from the maintenance menu
+struct Verification {
+    cancel: ScanCancel,
+    events: mpsc::Receiver<VerifyEvent>,
+    task: JoinHandle<LibraryResult<VerifyReport>>,
+}
+
+/// An import of the library folders started from the maintenance menu
+struct LibraryImport {
+    cancel: ScanCancel,
+    events: mpsc::Receiver<PipelineEvent>,
+    task: JoinHandle<LibraryResult<PipelineReport>>,
+}
+
+/// Everything the statistics view shows, as read in the background
+struct StatisticsReport {
+    daily: Result<Vec<stats::DailyListening>, AppError>,
+    library: Result<LibraryStats, AppError>,
+    /// Listening history of the loaded book, if one is loaded
+    book: Option<stats::BookStats>,
+}
+
+/// Chapter suggestions being worked out for a book from the chapter editor
+struct ChapterSuggestion {
+    book_id: BookId,
+    cancel: ScanCancel,
+    task: JoinHandle<LibraryResult<Vec<SuggestedChapter>>>,
+}
+
+/// Loudness being measured for a book the first time it plays
+struct LoudnessMeasurement {
+    book_id: BookId,
+    cancel: ScanCancel,
+    task: JoinHandle<LibraryResult<LoudnessReport>>,
+}
+
+/// How often an ongoing listening session is written out
+const LISTENING_CHECKPOINT_MS: i64 = 5 * 60 * 1000;
+
+/// How often the position of the playing book is saved
+const POSITION_SAVE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Days of listening history loaded for the statistics view
+const LISTENING_HISTORY_DAYS: u32 = 365;
+
+/// Confidence from which suggested chapter breaks start out accepted
+const ACCEPT_CONFIDENCE: f32 = 0.5;
+
 impl IntegratedTuiApp {
     /// Create a new integrated TUI application
     ///
@@ -56,16 +369,72 @@ impl IntegratedTuiApp {
     ///
     /// Returns `TuiError` if initialization fails for any component
     pub async fn new() -> TuiResult<Self> {
-        // Load configuration
         let config_manager = ConfigManager::new()
             .map_err(|e| TuiError::Initialization(format!("Config error: {}", e)))?;
-        let config = config_manager.load_or_default();
+        Self::with_config_manager(config_manager).await
+    }
+
+    /// Create the application with settings from `config_manager`, so the
+    /// overrides a caller layered onto it apply in the TUI too
+    ///
+    /// # Errors
+    ///
+    /// Returns `TuiError` if initialization fails for any component
+    pub async fn with_config_manager(config_manager: ConfigManager) -> TuiResult<Self> {
+        let mut app = Self::open(config_manager, Viewport::Fullscreen).await?;
+
+        // Take over the terminal once everything else is up
+        enable_raw_mode()?;
+        execute!(
+            app.terminal.backend_mut(),
+            EnterAlternateScreen,
+            EnableMouseCapture
+        )?;
+        Ok(app)
+    }
+
+    /// The application drawing into `viewport` of stdout, which is left in
+    /// its normal mode
+    async fn open(config_manager: ConfigManager, viewport: Viewport) -> TuiResult<Self> {
+        // Load configuration
+        let config = config_manager.load_effective();
+
+        // Clear out temporaries an earlier crash left, off the startup path,
+        // keeping the downloads a retry would continue
+        if !config.library.read_only {
+            let janitor = Janitor::for_config(config_manager.config_dir(), &config);
+            let history = config_manager.config_dir().join(DownloadHistory::FILE_NAME);
+            tokio::spawn(async move {
+                let janitor = async {
+                    janitor
+                        .protect_resumable(&DownloadHistory::new(history)?)
+                        .await
+                };
+                match janitor.await {
+                    Ok(janitor) => {
+                        let _ = tokio::task::spawn_blocking(move || janitor.clean()).await;
+                    }
+                    Err(e) => log::warn!("Leftover files are kept: {}", e),
+                }
+            });
+        }
 
         // Initialize database
-        let db_config = DatabaseConfig::new(&config.library.database_path);
+        let db_config = DatabaseConfig::new(&config.library.database_path)
+            .with_read_only(config.library.read_only);
         let db_pool = connect(db_config)
             .await
             .map_err(|e| TuiError::Initialization(format!("Database error: {}", e)))?;
+        // Also true when the database file turned out not to be writable
+        let read_only = is_read_only(&db_pool)
+            .await
+            .map_err(|e| TuiError::Initialization(format!("Database error: {}", e)))?;
+        // Opt-in upkeep; a failure only puts it off until the next start
+        if let (Some(days), false) = (config.app.maintenance_interval_days, read_only) {
+            if let Err(e) = optimize_if_due(&db_pool, days).await {
+                log::warn!("Database maintenance did not run: {}", e);
+            }
+        }
 
         // Initialize media engine
         let engine_config = EngineConfig {
@@ -73,48 +442,186 @@ impl IntegratedTuiApp {
             channels: 2,
             buffer_size: 4096,
         };
-        let media_engine = MediaEngine::new(engine_config)
+        let mut media_engine = MediaEngine::new(engine_config)
             .map_err(|e| TuiError::Initialization(format!("Media engine error: {}", e)))?;
+        media_engine.set_equalizer_presets(
+            equalizer_presets(&config.player),
+            &config.player.equalizer_preset,
+        );
+        media_engine.set_auto_resume(config.player.resume_after_interruption);
         let media_engine = Arc::new(Mutex::new(media_engine));
+        let mpris = MprisServer::start(Arc::clone(&media_engine)).await;
+        // A port in use only turns remote control off
+        let remote = if config.app.remote_control {
+            match RemoteServer::start(&config.app.remote_bind, config.app.remote_token.clone()) {
+                Ok(server) => Some(server),
+                Err(e) => {
+                    log::warn!("Remote control is off: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // Without a cache folder nothing is cached, which only costs time
+        let budget = config.app.cache_max_bytes();
+        let cache = match CacheManager::open(config_manager.cache_dir(), budget) {
+            Ok(cache) => Some(Arc::new(cache)),
+            Err(e) => {
+                log::warn!("Caching is off: {}", e);
+                None
+            }
+        };
 
         // Initialize library manager
         let library_config = storystream_library::LibraryConfig {
             database_path: config.library.database_path.clone(),
             watch_directories: config.library.paths.clone(),
             auto_import: config.library.auto_import,
+            read_only,
+            exclude_patterns: config.library.exclude_patterns.clone(),
+            ignore_hidden: config.library.ignore_hidden,
         };
-        let library_manager = LibraryManager::new(library_config)
+        let mut library_manager = LibraryManager::new(library_config)
             .await
             .map_err(|e| TuiError::Initialization(format!("Library error: {}", e)))?;
+        if let Some(cache) = &cache {
+            library_manager = library_manager.with_cache(Arc::clone(cache));
+        }
+        let mut auto_imports = None;
+        if config.library.auto_import && !read_only {
+            match library_manager.start_watching().await {
+                Ok(()) => auto_imports = library_manager.take_import_events(),
+                Err(e) => log::warn!("Auto-import is off: {}", e),
+            }
+        }
         let library_manager = Arc::new(library_manager);
 
-        // Load books from database
-        let current_books = books::list_books(&db_pool)
+        // Failed downloads are kept in the history the CLI also writes to
+        let history =
+            DownloadHistory::new(config_manager.config_dir().join(DownloadHistory::FILE_NAME))
+                .map_err(|e| TuiError::Initialization(format!("Download history error: {}", e)))?;
+        let client =
+            Client::new().map_err(|e| TuiError::Initialization(format!("Network error: {}", e)))?;
+        let downloads = Arc::new(
+            AdvancedDownloadManager::new(client, DownloadManagerConfig::default())
+                .with_history(history),
+        );
+        let runner = Arc::clone(&downloads);
+        tokio::spawn(async move { runner.start().await });
+
+        // A broken sync folder only turns sync off
+        let sync = match PositionSync::open(config_manager.config_dir(), &config.app) {
+            Ok(sync) => sync,
+            Err(e) => {
+                log::warn!("Position sync is off: {}", e);
+                None
+            }
+        };
+
+        // Count the books and read the first page
+        let library = LibraryWindow::open(db_pool.clone())
             .await
             .map_err(|e| TuiError::Initialization(format!("Failed to load books: {}", e)))?;
+        let playlists = playlists::list_playlists(&db_pool)
+            .await
+            .map_err(|e| TuiError::Initialization(format!("Failed to load playlists: {}", e)))?;
+        // Today's sessions count against the daily limit after a restart
+        let limits = ListeningLimits::load(&db_pool, config.limits.clone())
+            .await
+            .map_err(|e| {
+                TuiError::Initialization(format!("Failed to load listening time: {}", e))
+            })?;
 
-        // Setup terminal
-        enable_raw_mode()?;
-        let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-        let backend = CrosstermBackend::new(stdout);
-        let terminal = Terminal::new(backend)?;
+        let terminal = Terminal::with_options(
+            CrosstermBackend::new(io::stdout()),
+            TerminalOptions { viewport },
+        )?;
+
+        let volumes = VolumeMonitor::new(&config.library.paths);
+        for root in volumes.unavailable_roots() {
+            log::warn!("Library folder is not connected: {}", root.display());
+        }
 
         // Initialize TUI state
         let mut state = AppState::new();
-        state.library_items_count = current_books.len();
+        state.library_items_count = library.len();
         state.theme = color_scheme_to_theme(config.app.color_scheme);
+        state.daily_goal_minutes = config.app.daily_goal_minutes;
+        state.read_only = read_only;
+        state.accessible = config.app.accessible;
+        state.apply_keybindings(&config.keybindings);
+        let bookmark_store = BookmarkStore::new(db_pool.clone(), config.player.max_auto_bookmarks);
 
-        Ok(Self {
+        let mut app = Self {
             terminal,
             state,
             theme: Theme::new(color_scheme_to_theme(config.app.color_scheme)),
+            click_areas: ui::ClickAreas::default(),
             media_engine,
             library_manager,
             db_pool,
-            current_books,
+            library,
+            current_book: None,
+            playlists,
+            playing_playlist: false,
+            mpris,
+            remote,
+            listening_since: None,
+            position_saved: None,
+            limits,
+            verification: None,
+            import: None,
+            auto_imports,
+            planning: None,
+            statistics: None,
+            import_plan: None,
+            chapter_suggestion: None,
+            loudness_measurement: None,
+            loudness_measured: HashSet::new(),
+            file_issues: Vec::new(),
+            duplicates: Vec::new(),
+            downloads,
+            downloads_refreshed: None,
+            bookmark_store,
+            auto_bookmark_pause: (config.player.auto_bookmark_pause_secs > 0)
+                .then(|| Duration::from_secs(config.player.auto_bookmark_pause_secs)),
+            paused_since: None,
+            sync,
+            cache,
+            volumes,
+            volumes_probed: Instant::now(),
+            player: config.player.clone(),
+            config_changes: config_manager.subscribe(),
+            color_scheme: config.app.color_scheme,
+            config_manager,
+            output_device: None,
+            announcer: config
+                .app
+                .accessible
+                .then(|| Announcer::new(config.app.announce_verbosity)),
             tick_rate: Duration::from_millis(250),
-        })
+        };
+        app.refresh_daily_listening().await;
+        app.enforce_limits()?;
+        app.report_unavailable_volumes();
+        app.refresh_continue_listening().await;
+
+        if config.player.resume_on_startup {
+            app.resume_last_book(&config.player).await;
+        }
+
+        Ok(app)
+    }
+
+    /// Turns on accessibility mode, as `app.accessible` does
+    pub fn set_accessible(&mut self) {
+        if self.announcer.is_none() {
+            let verbosity = self.config_manager.load_or_default().app.announce_verbosity;
+            self.announcer = Some(Announcer::new(verbosity));
+        }
+        self.state.accessible = true;
     }
 
     /// Run the integrated TUI application
@@ -124,6 +631,22 @@ impl IntegratedTuiApp {
     /// Returns `TuiError` if the event loop encounters an error
     pub async fn run(&mut self) -> TuiResult<()> {
         let result = self.event_loop().await;
+        if result.is_ok() {
+            self.place_auto_bookmark(AutoBookmarkTrigger::Exit).await;
+        }
+        self.save_speed_ramp().await;
+        self.save_position().await;
+        if let Some(verification) = &self.verification {
+            verification.cancel.cancel();
+        }
+        if let Some(import) = &self.import {
+            import.cancel.cancel();
+        }
+        if let Some(measurement) = &self.loudness_measurement {
+            measurement.cancel.cancel();
+        }
+        self.end_listening_session().await;
+        let _ = self.downloads.shutdown().await;
         self.cleanup()?;
         result
     }
@@ -132,14 +655,72 @@ impl IntegratedTuiApp {
     async fn event_loop(&mut self) -> TuiResult<()> {
         loop {
             // Sync playback state from media engine
+            let was_playing = self.state.playback.is_playing;
             self.sync_playback_state()?;
+            self.follow_output_device()?;
+            self.track_listening().await;
+            self.track_position(was_playing).await;
+            self.enforce_limits()?;
+            self.poll_verification().await;
+            self.poll_import().await?;
+            self.poll_auto_import().await?;
+            self.poll_config().await;
+            self.poll_import_plan().await;
+            self.poll_statistics().await;
+            self.poll_chapter_suggestion().await;
+            self.poll_loudness_measurement().await;
+            self.poll_downloads().await;
+            self.track_pause(was_playing).await;
+            self.exchange_positions();
+            self.poll_lan();
+            self.poll_remote().await;
+            self.probe_volumes();
+            self.report_interruption();
+            self.report_device_error();
+            self.finish_sleep_timer().await;
+            self.advance_speed_ramp().await?;
+            self.suggest_up_next().await?;
+            if was_playing
+                && self.playing_playlist
+                && self.state.listening_lock.is_none()
+                && self.book_finished()
+            {
+                self.advance_playlist().await?;
+            }
+            if let Some(mpris) = &mut self.mpris {
+                mpris.refresh().await;
+                if mpris.quit_requested() {
+                    self.state.quit();
+                }
+            }
+
+            // Fetch the library pages around the selection, whichever view shows
+            self.library.show(self.state.library_selection());
+            self.library.poll();
+            self.library
+                .set_playing(self.current_book.as_ref().map(|book| book.id));
+            self.state.library_items_count = self.library.len();
+            let size = self.terminal.size()?;
+            let content = ui::content_area(Rect::new(0, 0, size.width, size.height), &self.state);
+            let visible = ui::library::visible_rows(content).min(LIBRARY_ROWS);
+            self.state.scroll_library(visible);
+            self.state.library_rows = Some(self.library.rows_from(
+                self.state.library_scroll,
+                visible,
+                &self.volumes,
+            ));
 
-            // Update library items count
-            self.state.library_items_count = self.current_books.len();
+            if let Some(announcer) = &mut self.announcer {
+                if let Some(line) = announcer.observe(&self.state, Instant::now()) {
+                    self.state.announcement = line;
+                }
+            }
 
             // Render UI
+            let mut click_areas = ui::ClickAreas::default();
             self.terminal
-                .draw(|frame| ui::render(frame, &self.state, &self.theme))?;
+                .draw(|frame| click_areas = ui::render(frame, &self.state, &self.theme))?;
+            self.click_areas = click_areas;
 
             // Check if we should quit
             if self.state.should_quit {
@@ -150,15 +731,18 @@ impl IntegratedTuiApp {
             if crossterm::event::poll(self.tick_rate)? {
                 match crossterm::event::read()? {
                     Event::Key(key) => {
-                        // Handle quit commands
-                        if key.code == KeyCode::Char('q')
+                        // Handle quit commands; the quit key is text while a prompt or the
+                        // palette is open
+                        let keymap = &self.state.keymap;
+                        if (keymap.runs(Action::Quit, key.code, key.modifiers)
+                            && !self.state.input_mode())
                             || (key.code == KeyCode::Char('c')
-                            && key.modifiers.contains(KeyModifiers::CONTROL))
+                                && key.modifiers.contains(KeyModifiers::CONTROL))
                         {
                             self.state.quit();
                             continue;
                         }
-                        self.handle_key(key.code).await?;
+                        self.handle_key(key.code, key.modifiers).await?;
                     }
                     Event::Mouse(mouse) => {
                         self.handle_mouse(mouse).await?;
@@ -179,6 +763,7 @@ impl IntegratedTuiApp {
             .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))?;
 
         self.state.playback.position = engine.position();
+        self.state.update_chapter();
         self.state.playback.is_playing = engine.is_playing();
         self.state.playback.volume = engine.volume();
 
@@ -186,6 +771,11 @@ impl IntegratedTuiApp {
         if let Ok(speed_guard) = engine.speed.lock() {
             self.state.playback.speed = speed_guard.value();
         }
+        self.state.playback.ramp_target = engine.speed_ramp().map(|ramp| ramp.target);
+        self.state.playback.sleep_remaining = engine.sleep_timer_remaining();
+        self.state.playback.sleep_at_chapter_end = engine
+            .sleep_timer()
+            .is_some_and(|timer| timer.is_end_of_chapter());
 
         // Get duration if we have a current file
         if self.state.playback.current_file.is_some() {
@@ -197,286 +787,3886 @@ impl IntegratedTuiApp {
         Ok(())
     }
 
-    /// Handle keyboard input
-    async fn handle_key(&mut self, code: KeyCode) -> TuiResult<()> {
-        match code {
-            KeyCode::Tab => self.cycle_view(),
-            KeyCode::Char('h') => self.toggle_help(),
-            KeyCode::Char('t') => self.toggle_theme(),
-            KeyCode::Up | KeyCode::Char('k') => self.state.select_previous(),
-            KeyCode::Down | KeyCode::Char('j') => self.state.select_next(),
-            KeyCode::Char(' ') => self.toggle_playback().await?,
-            KeyCode::Enter => self.handle_select().await?,
-            KeyCode::Left => self.seek_backward().await?,
-            KeyCode::Right => self.seek_forward().await?,
-            KeyCode::Char('+') | KeyCode::Char('=') => self.volume_up().await?,
-            KeyCode::Char('-') | KeyCode::Char('_') => self.volume_down().await?,
-            KeyCode::Char('[') => self.speed_down().await?,
-            KeyCode::Char(']') => self.speed_up().await?,
-            _ => {}
-        }
-        Ok(())
-    }
+    /// Records listening sessions as playback starts, stops or changes book
+    async fn track_listening(&mut self) {
+        let now = Timestamp::now();
+        let playing = self
+            .current_book
+            .as_ref()
+            .filter(|_| self.state.playback.is_playing)
+            .map(|book| book.id);
 
-    /// Handle mouse input
-    async fn handle_mouse(&mut self, mouse: crossterm::event::MouseEvent) -> TuiResult<()> {
-        match mouse.kind {
-            MouseEventKind::ScrollDown => self.state.select_next(),
-            MouseEventKind::ScrollUp => self.state.select_previous(),
-            MouseEventKind::Down(_) => {
-                // Handle click events based on view
-                // TODO: Implement click-to-select based on row
+        match (self.listening_since, playing) {
+            (Some((book_id, since)), Some(current)) if book_id == current => {
+                // Checkpoint long sessions so a crash loses little
+                if now.as_millis() - since.as_millis() >= LISTENING_CHECKPOINT_MS {
+                    self.record_listening(book_id, since, now).await;
+                    self.listening_since = Some((book_id, now));
+                }
             }
-            _ => {}
+            (Some((book_id, since)), _) => {
+                self.record_listening(book_id, since, now).await;
+                self.listening_since = playing.map(|current| (current, now));
+            }
+            (None, Some(current)) => self.listening_since = Some((current, now)),
+            (None, None) => {}
         }
-        Ok(())
     }
 
-    /// Cycle to next view
-    fn cycle_view(&mut self) {
-        use crate::state::View;
+    /// Saves the position every few seconds of playback and whenever it stops
+    async fn track_position(&mut self, was_playing: bool) {
+        let stopped = was_playing && !self.state.playback.is_playing;
+        let due = self.state.playback.is_playing
+            && self
+                .position_saved
+                .is_none_or(|at| at.elapsed() >= POSITION_SAVE_INTERVAL);
+        if stopped || due {
+            self.save_position().await;
+        }
+        if stopped {
+            self.refresh_continue_listening().await;
+        }
+    }
 
-        let next_view = match self.state.view {
-            View::Library => View::Player,
-            View::Player => View::Bookmarks,
-            View::Bookmarks => View::Search,
-            View::Search => View::Playlists,
-            View::Playlists => View::Statistics,
-            View::Statistics => View::Settings,
-            View::Settings => View::Help,
-            View::Help => View::Library,
-            View::Plugin => View::Library,
+    /// Saves where the loaded book is, with its speed and volume
+    ///
+    /// A book played to its end keeps its position there, which counts it
+    /// as finished; playing it again starts from the beginning.
+    async fn save_position(&mut self) {
+        let Some(book) = &self.current_book else {
+            return;
         };
+        self.position_saved = Some(Instant::now());
+        if self.state.read_only {
+            return;
+        }
+        let playback = &self.state.playback;
+        let position =
+            storystream_core::Duration::from_millis(playback.position.as_millis() as u64);
+        let speed = PlaybackSpeed::new(playback.speed).unwrap_or_default();
+        let volume = (playback.volume * 100.0).round().clamp(0.0, 100.0) as u8;
+        match playback::update_playback_state(&self.db_pool, book.id, position, speed, volume).await
+        {
+            Ok(()) => self.library.set_position(book.id, position),
+            Err(e) => log::warn!("Could not save the position of '{}': {}", book.title, e),
+        }
+    }
 
-        self.state.set_view(next_view);
-        self.state
-            .set_status(format!("Switched to {:?} view", next_view));
+    /// Where to pick `book` up again, a little before it was left
+    ///
+    /// Books never started or already finished start from the beginning.
+    async fn saved_position(&self, book: &Book) -> Duration {
+        let saved = match playback::get_playback_state(&self.db_pool, book.id).await {
+            Ok(state) => state.position.as_millis(),
+            Err(AppError::RecordNotFound { .. }) => return Duration::ZERO,
+            Err(e) => {
+                log::warn!("Could not load the position of '{}': {}", book.title, e);
+                return Duration::ZERO;
+            }
+        };
+        if saved as f64 >= book.duration.as_millis() as f64 * stats::FINISHED_THRESHOLD {
+            return Duration::ZERO;
+        }
+        Duration::from_millis(saved)
+            .saturating_sub(Duration::from_secs(self.player.resume_rewind_secs))
     }
 
-    /// Toggle help view
-    fn toggle_help(&mut self) {
-        use crate::state::View;
+    /// Marks the book last left part way through in the library view
+    async fn refresh_continue_listening(&mut self) {
+        match books::get_in_progress_books(&self.db_pool, 1).await {
+            Ok(mut in_progress) => self
+                .library
+                .set_continue_listening(in_progress.pop().map(|last| last.book.id)),
+            Err(e) => log::warn!("Could not look up the book to continue: {}", e),
+        }
+    }
 
-        if self.state.view == View::Help {
-            self.state.set_view(View::Library);
-        } else {
-            self.state.set_view(View::Help);
+    /// Records the session in progress, if any
+    async fn end_listening_session(&mut self) {
+        if let Some((book_id, since)) = self.listening_since.take() {
+            self.record_listening(book_id, since, Timestamp::now())
+                .await;
         }
     }
 
-    /// Toggle theme
-    fn toggle_theme(&mut self) {
-        self.state.next_theme();
-        self.theme = Theme::new(self.state.theme);
-        self.state
-            .set_status(format!("Theme: {:?}", self.state.theme));
+    async fn record_listening(&mut self, book_id: BookId, since: Timestamp, until: Timestamp) {
+        if let (Some(start), Some(end)) = (local_time(since), local_time(until)) {
+            self.limits.add_session(start, end);
+        }
+        // Counted toward the limits above, but there is nowhere to keep it
+        if self.state.read_only {
+            return;
+        }
+        let speed = self.state.playback.speed;
+        if let Err(e) =
+            stats::record_listening_session(&self.db_pool, book_id, since, until, speed).await
+        {
+            self.state
+                .set_error(format!("Failed to record listening time: {}", e));
+        }
     }
 
-    /// Toggle playback
-    async fn toggle_playback(&mut self) -> TuiResult<()> {
+    /// Pauses playback and shows the lock screen while the listening limits
+    /// forbid it, and lifts both once they allow it again
+    fn enforce_limits(&mut self) -> TuiResult<()> {
+        let now = chrono::Local::now().naive_local();
+        let playing_since = self
+            .listening_since
+            .and_then(|(_, since)| local_time(since));
+        let reached = self.limits.check(now, playing_since).err();
+
         let mut engine = self
             .media_engine
             .lock()
             .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))?;
-
-        if engine.is_playing() {
-            engine
-                .pause()
-                .map_err(|e| TuiError::PlaybackError(format!("Pause error: {}", e)))?;
-            self.state.set_status("Paused");
-        } else {
-            engine
-                .play()
-                .map_err(|e| TuiError::PlaybackError(format!("Play error: {}", e)))?;
-            self.state.set_status("Playing");
+        match reached {
+            Some(limit) => {
+                if engine.is_playing() {
+                    engine
+                        .pause()
+                        .map_err(|e| TuiError::PlaybackError(format!("Pause error: {}", e)))?;
+                }
+                engine.set_play_lock(Some(limit.to_string()));
+                self.state.listening_lock = Some(ListeningLock {
+                    message: limit.to_string(),
+                    wait: limit.wait(),
+                    unlockable: self.limits.can_unlock(),
+                });
+            }
+            None if self.state.listening_lock.is_some() => {
+                engine.set_play_lock(None);
+                self.state.listening_lock = None;
+            }
+            None => {}
         }
-
         Ok(())
     }
 
-    /// Handle selection in current view
-    async fn handle_select(&mut self) -> TuiResult<()> {
-        use crate::state::View;
+    /// Handles keys while the lock screen is shown; only unlocking is possible
+    fn handle_lock_key(&mut self, code: KeyCode) {
+        let unlockable = self
+            .state
+            .listening_lock
+            .as_ref()
+            .is_some_and(|lock| lock.unlockable);
+        if unlockable && matches!(code, KeyCode::Char('u') | KeyCode::Char('U')) {
+            self.state.input = Some(TextPrompt::new("Password", "", InputPurpose::UnlockLimits));
+        }
+    }
 
-        match self.state.view {
-            View::Library => {
-                // Clone the book to avoid borrow checker issues
-                if let Some(book) = self.current_books.get(self.state.selected_item).cloned() {
-                    self.load_book(&book).await?;
-                }
-            }
-            View::Player => {
-                self.toggle_playback().await?;
-            }
-            _ => {
-                self.state.set_status("Selection not implemented for this view");
-            }
+    /// Reloads the per-day listening totals shown in the statistics view
+    async fn refresh_daily_listening(&mut self) {
+        self.record_listening_so_far().await;
+        match stats::daily_listening(&self.db_pool, LISTENING_HISTORY_DAYS).await {
+            Ok(days) => self.state.daily_minutes = days.iter().map(|day| day.minutes).collect(),
+            Err(e) => self
+                .state
+                .set_error(format!("Failed to load listening history: {}", e)),
         }
+    }
 
-        Ok(())
+    /// Records the session in progress up to now, unless so short it would
+    /// be dropped, so totals read next include it
+    async fn record_listening_so_far(&mut self) {
+        if let Some((book_id, since)) = self.listening_since {
+            let now = Timestamp::now();
+            if now.as_millis() - since.as_millis() >= stats::MIN_SESSION_SECS * 1000 {
+                self.record_listening(book_id, since, now).await;
+                self.listening_since = Some((book_id, now));
+            }
+        }
     }
 
-    /// Load and play a book
-    ///
-    /// # Errors
+    /// Starts reading everything the statistics view shows
     ///
-    /// Returns `TuiError::PlaybackError` if loading or playing fails
-    async fn load_book(&mut self, book: &Book) -> TuiResult<()> {
-        let mut engine = self
-            .media_engine
-            .lock()
-            .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))?;
+    /// The queries run in the background so a long listening history does
+    /// not hold up drawing; [`Self::poll_statistics`] shows the results.
+    async fn refresh_statistics(&mut self) {
+        if self.statistics.is_some() {
+            return;
+        }
+        self.record_listening_so_far().await;
 
-        // Load the audio file (book.file_path is PathBuf, load expects &PathBuf)
-        engine
-            .load(&book.file_path)
-            .map_err(|e| TuiError::PlaybackError(format!("Load error: {}", e)))?;
+        let pool = self.db_pool.clone();
+        let book_id = self.current_book.as_ref().map(|book| book.id);
+        self.statistics = Some(tokio::spawn(async move {
+            let book = match book_id {
+                Some(book_id) => stats::get_book_stats(&pool, book_id).await.ok(),
+                None => None,
+            };
+            StatisticsReport {
+                daily: stats::daily_listening(&pool, LISTENING_HISTORY_DAYS).await,
+                library: stats::get_library_stats(&pool).await,
+                book,
+            }
+        }));
+        self.state.statistics_loading = true;
+    }
 
-        self.state.playback.current_file = Some(book.title.clone());
+    /// Shows the statistics once the background read finishes
+    async fn poll_statistics(&mut self) {
+        if !self
+            .statistics
+            .as_ref()
+            .is_some_and(|task| task.is_finished())
+        {
+            return;
+        }
+        let Some(task) = self.statistics.take() else {
+            return;
+        };
+        self.state.statistics_loading = false;
 
-        // Get duration from the engine after loading
-        if let Some(duration) = engine.duration {
-            self.state.playback.duration = duration;
+        let report = match task.await {
+            Ok(report) => report,
+            Err(e) => {
+                self.state
+                    .set_error(format!("Loading statistics stopped: {}", e));
+                return;
+            }
+        };
+        match report.daily {
+            Ok(days) => self.state.daily_minutes = days.iter().map(|day| day.minutes).collect(),
+            Err(e) => self
+                .state
+                .set_error(format!("Failed to load listening history: {}", e)),
+        }
+        match report.library {
+            Ok(library) => self.state.library_stats = Some(library),
+            Err(e) => self
+                .state
+                .set_error(format!("Failed to load statistics: {}", e)),
         }
-        self.state.playback.position = Duration::ZERO;
+        self.state.book_stats = report.book;
+    }
 
-        engine
-            .play()
-            .map_err(|e| TuiError::PlaybackError(format!("Play error: {}", e)))?;
+    /// Writes the totals the statistics view shows to a CSV file
+    ///
+    /// The file goes to `player.bookmark_export_dir`, or the config folder
+    /// when that is not set.
+    fn export_statistics(&mut self) {
+        let Some(library) = &self.state.library_stats else {
+            self.state.set_status("Statistics have not loaded yet");
+            return;
+        };
+        let dir = match &self.player.bookmark_export_dir {
+            Some(dir) => dir.clone(),
+            None => self.config_manager.config_dir().clone(),
+        };
+        let today = chrono::Local::now().date_naive();
+        let path = dir.join(format!(
+            "storystream-statistics-{}.csv",
+            today.format("%Y-%m-%d")
+        ));
+        let csv = statistics_csv(library, &self.state.daily_minutes, today);
+        match std::fs::create_dir_all(&dir).and_then(|()| std::fs::write(&path, csv)) {
+            Ok(()) => self
+                .state
+                .set_status(format!("Exported statistics to {}", path.display())),
+            Err(e) => self
+                .state
+                .set_error(format!("Could not write {}: {}", path.display(), e)),
+        }
+    }
 
-        self.state.set_view(crate::state::View::Player);
-        self.state.set_status(format!("Playing: {}", book.title));
+    /// Handle keyboard input
+    async fn handle_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> TuiResult<()> {
+        if self.state.input.is_some() {
+            self.handle_input_key(code, modifiers).await;
+            return Ok(());
+        }
+        if self.state.listening_lock.is_some() {
+            self.handle_lock_key(code);
+            return Ok(());
+        }
+        if self.state.pairing.is_some() {
+            self.handle_pairing_key(code);
+            return Ok(());
+        }
+        if self.state.output_picker.is_some() {
+            self.handle_output_picker_key(code);
+            return Ok(());
+        }
+        if self.state.confirm.is_some() {
+            self.handle_confirm_key(code).await;
+            return Ok(());
+        }
+        if self.state.palette.is_some() {
+            return self.handle_palette_key(code, modifiers).await;
+        }
+        if PALETTE_KEY.matches(code, modifiers) {
+            self.state.palette = Some(CommandPalette::default());
+            return Ok(());
+        }
+        if self.state.book_detail.is_some() {
+            self.handle_detail_key(code);
+            return Ok(());
+        }
+        if self.state.filter_popup.is_some() {
+            self.handle_filter_key(code).await;
+            return Ok(());
+        }
+        if self.state.view == crate::state::View::Settings
+            && (self.handle_maintenance_key(code).await? || self.handle_setting_key(code).await)
+        {
+            return Ok(());
+        }
+        if self.state.chapter_editor.is_some()
+            && self.state.view == crate::state::View::Player
+            && self.handle_chapter_edit_key(code).await?
+        {
+            return Ok(());
+        }
+        if self.state.view == crate::state::View::Help && self.handle_help_key(code) {
+            return Ok(());
+        }
 
+        match code {
+            KeyCode::Enter
+                if self.state.view == crate::state::View::Player
+                    && self.state.up_next.is_some() =>
+            {
+                self.play_up_next().await?
+            }
+            KeyCode::Esc
+                if self.state.view == crate::state::View::Player
+                    && self.state.up_next.is_some() =>
+            {
+                self.state.up_next = None
+            }
+            KeyCode::Char('g')
+                if self.state.view == crate::state::View::Player
+                    && self.state.sync_banner.is_some() =>
+            {
+                self.jump_to_remote_position().await?
+            }
+            KeyCode::Esc
+                if self.state.view == crate::state::View::Player
+                    && self.state.sync_banner.is_some() =>
+            {
+                self.dismiss_sync_banner()
+            }
+            KeyCode::Char('K') | KeyCode::Char('J')
+                if self.state.view == crate::state::View::Settings
+                    && self
+                        .state
+                        .lan
+                        .as_ref()
+                        .is_some_and(|lan| !lan.peers.is_empty()) =>
+            {
+                self.select_lan_device(code == KeyCode::Char('J'))
+            }
+            KeyCode::Esc
+                if self.state.view == crate::state::View::Playlists
+                    && self.state.open_playlist.is_some() =>
+            {
+                self.state.open_playlist = None
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.state.select_previous(),
+            KeyCode::Down | KeyCode::Char('j') => self.state.select_next(),
+            _ => match self
+                .state
+                .keymap
+                .action_for(code, modifiers, self.state.view)
+            {
+                Some(action) => self.run_action(action).await?,
+                None if code == KeyCode::Enter => self.handle_select().await?,
+                None => {}
+            },
+        }
         Ok(())
     }
 
-    /// Seek backward
-    async fn seek_backward(&mut self) -> TuiResult<()> {
+    /// Run an action picked by its key or from the command palette
+    async fn run_action(&mut self, action: Action) -> TuiResult<()> {
+        if action.changes_library() && self.refuse_read_only() {
+            return Ok(());
+        }
+        match action {
+            Action::TogglePlayback => self.toggle_playback().await?,
+            Action::SeekBackward => self.seek_backward().await?,
+            Action::SeekForward => self.seek_forward().await?,
+            Action::VolumeUp => self.volume_up().await?,
+            Action::VolumeDown => self.volume_down().await?,
+            Action::SpeedDown => self.speed_down().await?,
+            Action::SpeedUp => self.speed_up().await?,
+            Action::NextChapter => self.change_chapter(true).await?,
+            Action::PreviousChapter => self.change_chapter(false).await?,
+            Action::NextView => self.cycle_view().await,
+            Action::OpenView(view) => self.open_view(view).await,
+            Action::ToggleHelp => self.toggle_help(),
+            Action::ToggleTheme => self.toggle_theme(),
+            Action::Search => self.prompt_search(),
+            Action::Quit => self.state.quit(),
+            Action::ShowBookDetail => self.show_book_detail(),
+            Action::ToggleFavorite => self.toggle_favorite().await,
+            Action::DeleteBook => self.confirm_delete_book(),
+            Action::EditChapters => self.edit_chapters(),
+            Action::CycleEqualizer => self.cycle_equalizer().await?,
+            Action::ToggleSpeedRamp => self.toggle_speed_ramp().await?,
+            Action::CycleSleepTimer => self.cycle_sleep_timer()?,
+            Action::ExtendSleepTimer => self.extend_sleep_timer()?,
+            Action::AddBookmark => self.add_bookmark().await,
+            Action::DeleteBookmark => self.confirm_delete_bookmark(),
+            Action::ClearAutoBookmarks => self.clear_auto_bookmarks().await,
+            Action::ExportBookmarks => self.export_bookmarks(),
+            Action::ShareBookmark => self.share_bookmark().await,
+            Action::FilterSearch => self.state.filter_popup = Some(FilterPopup::default()),
+            Action::ShufflePlaylist => self.play_playlist(true).await?,
+            Action::NewPlaylist => self.prompt_new_playlist(),
+            Action::DeletePlaylist => self.confirm_delete_playlist(),
+            Action::OpenPlaylist => self.toggle_open_playlist().await,
+            Action::AddToPlaylist => self.add_to_playlist().await,
+            Action::RemoveFromPlaylist => self.remove_from_playlist().await,
+            Action::MovePlaylistBookUp => self.move_playlist_book(false).await,
+            Action::MovePlaylistBookDown => self.move_playlist_book(true).await,
+            Action::RetryDownload => self.retry_download().await,
+            Action::RefreshStatistics if self.statistics.is_some() => {
+                self.state.set_status("Statistics are already loading")
+            }
+            Action::RefreshStatistics => {
+                self.refresh_statistics().await;
+                self.state.set_status("Reloading statistics...");
+            }
+            Action::ExportStatistics => self.export_statistics(),
+            Action::ImportLibrary => self.start_import(),
+            Action::PreviewImport => self.start_import_plan(),
+            Action::VerifyFiles => self.start_verification(VerifyDepth::Hash),
+            Action::VerifyFilesFully => self.start_verification(VerifyDepth::Full),
+            Action::FindDuplicates => self.find_duplicates().await,
+            Action::PruneDownloads => self.prune_download_history().await,
+            Action::ClearCache => self.clear_cache(),
+            Action::ResetSettings => {
+                let message = "Reset every setting to its default? The config file is overwritten.";
+                let action = ConfirmAction::ResetSettings;
+                self.state.confirm = Some(ConfirmDialog::new(message, action).dangerous());
+            }
+            Action::FindDevices => self.find_devices(),
+            Action::PairDevice => self.pair_device(),
+            Action::ChooseOutputDevice => self.open_output_picker(),
+        }
+        Ok(())
+    }
+
+    /// Shows why nothing happens if the library is read-only, which callers
+    /// check before changing it
+    fn refuse_read_only(&mut self) -> bool {
+        if self.state.read_only {
+            self.state.set_status(READ_ONLY_REASON);
+        }
+        self.state.read_only
+    }
+
+    /// Handle a key while the command palette is open
+    async fn handle_palette_key(
+        &mut self,
+        code: KeyCode,
+        modifiers: KeyModifiers,
+    ) -> TuiResult<()> {
+        let Some(palette) = &self.state.palette else {
+            return Ok(());
+        };
+        if code == KeyCode::Enter {
+            match palette.confirm(&self.state) {
+                Ok(action) => {
+                    self.state.palette = None;
+                    self.run_action(action).await?;
+                }
+                // Stays open so another command can be picked
+                Err(reason) => self.state.set_status(reason),
+            }
+            return Ok(());
+        }
+
+        let count = palette.entries(&self.state).len();
+        let Some(palette) = self.state.palette.as_mut() else {
+            return Ok(());
+        };
+        match code {
+            _ if PALETTE_KEY.matches(code, modifiers) => self.state.palette = None,
+            KeyCode::Esc => self.state.palette = None,
+            KeyCode::Up => palette.select_previous(),
+            KeyCode::Down => palette.select_next(count),
+            KeyCode::Backspace => palette.pop(),
+            KeyCode::Char(c) => palette.push(c),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Handle mouse input
+    async fn handle_mouse(&mut self, mouse: crossterm::event::MouseEvent) -> TuiResult<()> {
+        match mouse.kind {
+            MouseEventKind::ScrollDown | MouseEventKind::ScrollUp
+                if self.state.view == crate::state::View::Help =>
+            {
+                let lines = if mouse.kind == MouseEventKind::ScrollUp {
+                    -HELP_WHEEL_LINES
+                } else {
+                    HELP_WHEEL_LINES
+                };
+                self.scroll_help(lines, self.help_page());
+            }
+            MouseEventKind::ScrollDown => self.state.select_next(),
+            MouseEventKind::ScrollUp => self.state.select_previous(),
+            MouseEventKind::Down(MouseButton::Left)
+                if self.click_areas.now_playing.is_some_and(|bar| {
+                    crate::events::mouse_in_area(mouse.column, mouse.row, bar)
+                }) =>
+            {
+                self.open_view(crate::state::View::Player).await
+            }
+            MouseEventKind::Down(MouseButton::Left) | MouseEventKind::Drag(MouseButton::Left)
+                if self.state.view == crate::state::View::Player =>
+            {
+                let snap = !mouse.modifiers.contains(KeyModifiers::SHIFT);
+                self.scrub_to(mouse.column, mouse.row, snap)?;
+            }
+            MouseEventKind::Up(MouseButton::Left) if self.state.scrub.is_some() => {
+                self.finish_scrub().await?;
+            }
+            MouseEventKind::Down(_) => {
+                // Handle click events based on view
+                // TODO: Implement click-to-select based on row
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Moves the drag-seek target to the progress bar column under the pointer
+    ///
+    /// The target snaps to a chapter start or bookmark within a couple of
+    /// columns unless `snap` is false. A drag must begin on the bar itself,
+    /// or on a chapter marker below it to start from that chapter exactly.
+    /// Nothing happens without a loaded book.
+    fn scrub_to(&mut self, column: u16, row: u16, snap: bool) -> TuiResult<()> {
+        let duration = self.state.playback.duration;
+        let Some(bar) = self.click_areas.progress_bar else {
+            return Ok(());
+        };
+        if self.state.playback.current_file.is_none() || duration.is_zero() || bar.width == 0 {
+            return Ok(());
+        }
+        if self.state.scrub.is_none() {
+            if row == bar.bottom() {
+                if let Some(chapter) = ui::player::chapter_marker_at(bar, column, &self.state) {
+                    self.state.scrub = Some(Scrub {
+                        position: Duration::from_millis(chapter.start_time.as_millis()),
+                        snapped_to: Some(chapter.title.clone()),
+                    });
+                    return Ok(());
+                }
+            }
+            if !crate::events::mouse_in_area(column, row, bar) {
+                return Ok(());
+            }
+        }
+
+        let target = ui::player::bar_position(bar, column, duration);
+        let snapped = if snap {
+            let window = duration * SNAP_COLUMNS / u32::from(bar.width);
+            self.media_engine
+                .lock()
+                .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))?
+                .snap_position(target, window, self.bookmark_store.engine())
+        } else {
+            SnappedPosition::unsnapped(target)
+        };
+        self.state.scrub = Some(Scrub {
+            position: snapped.position,
+            snapped_to: snapped.snapped_to.map(|point| point.label()),
+        });
+        Ok(())
+    }
+
+    /// Seeks to where the progress bar drag was released
+    async fn finish_scrub(&mut self) -> TuiResult<()> {
+        let Some(scrub) = self.state.scrub.take() else {
+            return Ok(());
+        };
+        self.media_engine
+            .lock()
+            .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))?
+            .seek(scrub.position)
+            .map_err(|e| TuiError::PlaybackError(format!("Seek error: {}", e)))?;
+        self.state.playback.position = scrub.position;
+        self.state.update_chapter();
+        self.state.set_status(format!("Seek to {}", scrub.label()));
+
+        if let Some(mpris) = &self.mpris {
+            mpris.seeked(scrub.position).await;
+        }
+        Ok(())
+    }
+
+    /// Cycle to next view
+    async fn cycle_view(&mut self) {
+        use crate::state::View;
+
+        let next_view = match self.state.view {
+            View::Library => View::Player,
+            View::Player => View::Bookmarks,
+            View::Bookmarks => View::Search,
+            View::Search => View::Playlists,
+            View::Playlists => View::Statistics,
+            View::Statistics => View::Settings,
+            View::Settings => View::Downloads,
+            View::Downloads => View::Help,
+            View::Help => View::Library,
+            View::Plugin => View::Library,
+        };
+        self.open_view(next_view).await;
+    }
+
+    /// Switch to `view`, loading what it shows
+    async fn open_view(&mut self, view: crate::state::View) {
+        use crate::state::View;
+
+        if view == View::Statistics {
+            self.refresh_statistics().await;
+        }
+        if view == View::Settings {
+            self.state.cache = self.cache.as_ref().map(|cache| cache.stats());
+        }
+        self.state.set_view(view);
+        if view == View::Search {
+            self.run_search().await;
+        }
+        if view == View::Downloads {
+            self.refresh_downloads().await;
+        }
+        if view == View::Bookmarks {
+            self.refresh_bookmarks().await;
+        }
+        if view == View::Playlists {
+            self.refresh_playlists().await;
+            self.refresh_subscriptions().await;
+        }
+        self.state
+            .set_status(format!("Switched to {:?} view", view));
+        if view == View::Settings {
+            self.load_settings();
+        }
+    }
+
+    /// Reads the config file into the settings view
+    fn load_settings(&mut self) {
+        self.state.setting_error = None;
+        match self.config_manager.load() {
+            Ok(config) => self.state.config = config,
+            Err(e) => self.state.set_error(format!("Config file not read: {}", e)),
+        }
+    }
+
+    /// Handle a key that changes the selected setting, returning whether it did
+    ///
+    /// ←/→ step numbers and choices rather than seek while settings show.
+    async fn handle_setting_key(&mut self, code: KeyCode) -> bool {
+        let setting = self.state.selected_setting();
+        let kind = setting.kind();
+        let forward = match code {
+            KeyCode::Right => true,
+            KeyCode::Left => false,
+            KeyCode::Char(' ') if kind == SettingKind::Toggle => true,
+            KeyCode::Enter if matches!(kind, SettingKind::Toggle | SettingKind::Choice) => true,
+            KeyCode::Enter => {
+                let text = setting.text(&self.state.config);
+                let purpose = InputPurpose::EditSetting(setting);
+                self.state.input = Some(TextPrompt::new(setting.label(), text, purpose));
+                return true;
+            }
+            _ => return false,
+        };
+        if setting.step(&self.state.config, forward).is_some() {
+            self.change_setting(setting, |config| {
+                setting
+                    .step(config, forward)
+                    .unwrap_or_else(|| Ok(config.clone()))
+            })
+            .await;
+        }
+        true
+    }
+
+    /// Changes a setting in the config file and switches to it where possible
+    ///
+    /// The change is made to the file as it is now, keeping edits made to it
+    /// elsewhere. A refused change is shown under the setting and the file
+    /// is left alone.
+    async fn change_setting(
+        &mut self,
+        setting: Setting,
+        change: impl FnOnce(&Config) -> ConfigResult<Config>,
+    ) {
+        let result = self
+            .config_manager
+            .load()
+            .and_then(|config| change(&config))
+            .and_then(|config| self.config_manager.save(&config).map(|()| config));
+        let config = match result {
+            Ok(config) => config,
+            Err(e) => {
+                self.state.setting_error = Some((setting, settings::refusal(&e)));
+                return;
+            }
+        };
+
+        let value = setting.display(&config);
+        self.state.config = config;
+        self.state.setting_error = None;
+        let effective = self.config_manager.load_effective();
+        self.apply_config(effective).await;
+        if setting.applies_live() {
+            self.state
+                .set_status(format!("{}: {}", setting.label(), value));
+        } else {
+            self.state.set_status(format!(
+                "{}: {} (used from the next start)",
+                setting.label(),
+                value
+            ));
+        }
+    }
+
+    /// Switch to the search view and prompt for the search text
+    fn prompt_search(&mut self) {
+        self.state.set_view(crate::state::View::Search);
+        self.state.input = Some(TextPrompt::new(
+            "Search",
+            self.state.search_query.clone(),
+            InputPurpose::Search,
+        ));
+    }
+
+    /// Handle a key while the search filter popup is open
+    async fn handle_filter_key(&mut self, code: KeyCode) {
+        let Some(popup) = self.state.filter_popup.as_mut() else {
+            return;
+        };
+        let field = popup.selected_field();
+
+        match code {
+            KeyCode::Esc | KeyCode::Char('F') => self.state.filter_popup = None,
+            KeyCode::Up | KeyCode::Char('k') => popup.select_previous(),
+            KeyCode::Down | KeyCode::Char('j') => popup.select_next(),
+            KeyCode::Enter if field.is_toggle() => {
+                field.toggle(&mut self.state.search_filter);
+                self.run_search().await;
+            }
+            KeyCode::Enter => {
+                self.state.input = Some(TextPrompt::new(
+                    format!("Filter by {}", field.label().to_lowercase()),
+                    field.value(&self.state.search_filter),
+                    InputPurpose::SearchFilter(field),
+                ));
+            }
+            KeyCode::Char('x') => {
+                field.clear(&mut self.state.search_filter);
+                self.run_search().await;
+            }
+            KeyCode::Char('c') => {
+                self.state.search_filter = SearchFilter::default();
+                self.run_search().await;
+            }
+            _ => {}
+        }
+    }
+
+    /// Search the library with the current text and filters
+    ///
+    /// With neither, every book is listed by title.
+    async fn run_search(&mut self) {
+        let query = Some(self.state.search_query.trim()).filter(|q| !q.is_empty());
+        let found = search_books_filtered(
+            &self.db_pool,
+            query,
+            &self.state.search_filter,
+            None,
+            SEARCH_LIMIT,
+        )
+        .await;
+
+        match found {
+            Ok(results) => {
+                self.state.search_results = results.into_iter().map(|r| r.item).collect();
+                self.state.reset_selection();
+                self.state
+                    .set_status(format!("{} books found", self.state.search_results.len()));
+            }
+            Err(e) => self.state.set_error(format!("Search failed: {}", e)),
+        }
+    }
+
+    /// Scrolls the help or changes its section, returning whether the key
+    /// was one of those
+    fn handle_help_key(&mut self, code: KeyCode) -> bool {
+        use crate::ui::help::HelpSection;
+
+        let page = self.help_page();
+        let page_lines = isize::try_from(page).unwrap_or(isize::MAX);
+        let lines = match code {
+            KeyCode::Up | KeyCode::Char('k') => -1,
+            KeyCode::Down | KeyCode::Char('j') => 1,
+            KeyCode::PageUp => -page_lines,
+            KeyCode::PageDown => page_lines,
+            KeyCode::Home => isize::MIN,
+            KeyCode::End => isize::MAX,
+            KeyCode::Left => {
+                self.state
+                    .show_help_section(self.state.help_section.previous());
+                return true;
+            }
+            KeyCode::Right => {
+                self.state.show_help_section(self.state.help_section.next());
+                return true;
+            }
+            KeyCode::Char(digit @ '1'..='9') => {
+                let index = digit as usize - '1' as usize;
+                if let Some(section) = HelpSection::all().get(index) {
+                    self.state.show_help_section(*section);
+                }
+                return true;
+            }
+            _ => return false,
+        };
+        self.scroll_help(lines, page);
+        true
+    }
+
+    /// Scrolls the help by `lines` with `page` lines in view
+    fn scroll_help(&mut self, lines: isize, page: usize) {
+        let last = ui::help::last_scroll(&self.state, &self.theme, page);
+        self.state.scroll_help(lines, last);
+    }
+
+    /// Lines of help text the screen has room for
+    fn help_page(&self) -> usize {
+        let size = self.terminal.size().unwrap_or_default();
+        let screen = Rect::new(0, 0, size.width, size.height);
+        ui::help::visible_lines(ui::content_area(screen, &self.state))
+    }
+
+    /// Toggle help view
+    fn toggle_help(&mut self) {
+        use crate::state::View;
+
+        if self.state.view == View::Help {
+            self.state.set_view(View::Library);
+        } else {
+            self.state.set_view(View::Help);
+        }
+    }
+
+    /// Toggle theme
+    fn toggle_theme(&mut self) {
+        self.state.next_theme();
+        self.theme = Theme::new(self.state.theme);
+        self.state
+            .set_status(format!("Theme: {:?}", self.state.theme));
+    }
+
+    /// Toggle playback
+    async fn toggle_playback(&mut self) -> TuiResult<()> {
         let mut engine = self
             .media_engine
             .lock()
             .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))?;
 
-        let current = engine.position();
-        let new_position = current.saturating_sub(Duration::from_secs(10));
+        if engine.is_playing() {
+            engine
+                .pause()
+                .map_err(|e| TuiError::PlaybackError(format!("Pause error: {}", e)))?;
+            self.state.set_status("Paused");
+        } else {
+            engine
+                .play()
+                .map_err(|e| TuiError::PlaybackError(format!("Play error: {}", e)))?;
+            self.state.set_status("Playing");
+        }
+
+        Ok(())
+    }
+
+    /// Handle selection in current view
+    async fn handle_select(&mut self) -> TuiResult<()> {
+        use crate::state::View;
+
+        match self.state.view {
+            View::Library => {
+                if let Some(book) = self.selected_book() {
+                    self.play_picked(book).await?;
+                }
+            }
+            View::Search => {
+                if let Some(book) = self
+                    .state
+                    .search_results
+                    .get(self.state.selected_item)
+                    .cloned()
+                {
+                    self.play_picked(book).await?;
+                }
+            }
+            View::Playlists => {
+                self.play_playlist(false).await?;
+            }
+            View::Player => {
+                self.toggle_playback().await?;
+            }
+            View::Bookmarks => {
+                self.jump_to_bookmark().await?;
+            }
+            _ => {
+                self.state
+                    .set_status("Selection not implemented for this view");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Plays a book picked from a list, leaving any playlist behind
+    ///
+    /// A book whose drive is not connected is refused straight away.
+    async fn play_picked(&mut self, book: Book) -> TuiResult<()> {
+        if self.volumes.is_unavailable(&book.file_path) {
+            self.state.set_error(unavailable_message(&book));
+            return Ok(());
+        }
+        self.leave_playlist().await;
+        let position = self.saved_position(&book).await;
+        self.load_book(&book, position, true).await?;
+        if position > Duration::ZERO {
+            self.state.set_status(format!(
+                "Playing: {} from {}",
+                book.title,
+                format_duration(position)
+            ));
+        }
+        Ok(())
+    }
+
+    /// Load a book at `position`, playing it if `autoplay` is set
+    ///
+    /// # Errors
+    ///
+    /// Returns `TuiError::PlaybackError` if loading, seeking or playing fails
+    async fn load_book(
+        &mut self,
+        book: &Book,
+        position: Duration,
+        autoplay: bool,
+    ) -> TuiResult<()> {
+        if self.volumes.is_unavailable(&book.file_path) {
+            return Err(TuiError::PlaybackError(unavailable_message(book)));
+        }
+        // The book being left off is picked up where it was next time
+        self.save_position().await;
+        let equalizer = match playback::get_equalizer(&self.db_pool, book.id).await {
+            Ok(equalizer) => equalizer,
+            Err(e) => {
+                log::warn!("Could not load equalizer for '{}': {}", book.title, e);
+                None
+            }
+        };
+        let speed_ramp = match playback::get_speed_ramp(&self.db_pool, book.id).await {
+            Ok(ramp) => ramp,
+            Err(e) => {
+                log::warn!("Could not load speed ramp for '{}': {}", book.title, e);
+                None
+            }
+        };
+        self.save_speed_ramp().await;
+        {
+            let mut engine = self
+                .media_engine
+                .lock()
+                .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))?;
+
+            // A book stored as a folder plays its files one after another,
+            // with positions counted across all of them
+            let files = book_files(book)
+                .map_err(|e| TuiError::PlaybackError(format!("Load error: {}", e)))?;
+            let (first, rest) = files.split_first().ok_or_else(|| {
+                TuiError::PlaybackError(format!("No audio files for '{}'", book.title))
+            })?;
+
+            // The engine takes the path as a string
+            engine
+                .load_book(&first.to_string_lossy(), equalizer.as_ref())
+                .map_err(|e| TuiError::PlaybackError(format!("Load error: {}", e)))?;
+            for file in rest {
+                engine
+                    .enqueue(&file.to_string_lossy())
+                    .map_err(|e| TuiError::PlaybackError(format!("Load error: {}", e)))?;
+            }
+            if !rest.is_empty() {
+                engine.set_aggregate_position(true);
+            }
+
+            self.state.playback.current_file = Some(book.title.clone());
+            self.state.playback.equalizer = engine.equalizer_preset().name.clone();
+
+            // The previous book's ramp ends; this book's picks up where it was
+            engine
+                .clear_speed_ramp()
+                .map_err(|e| TuiError::PlaybackError(format!("Speed error: {}", e)))?;
+            if let Some(ramp) = speed_ramp {
+                if let Err(e) = engine.resume_speed_ramp(ramp) {
+                    log::warn!("Ignoring speed ramp of '{}': {}", book.title, e);
+                }
+            }
+
+            // Get duration from the engine after loading
+            if let Some(duration) = engine.duration {
+                self.state.playback.duration = duration;
+            }
+
+            let position = position.min(self.state.playback.duration);
+            if position > Duration::ZERO {
+                engine
+                    .seek(position)
+                    .map_err(|e| TuiError::PlaybackError(format!("Seek error: {}", e)))?;
+            }
+            self.state.playback.position = position;
+
+            if autoplay {
+                engine
+                    .play()
+                    .map_err(|e| TuiError::PlaybackError(format!("Play error: {}", e)))?;
+                self.state.set_status(format!("Playing: {}", book.title));
+            } else {
+                self.state.set_status(format!("Paused: {}", book.title));
+            }
+
+            self.state.set_view(crate::state::View::Player);
+        }
+
+        self.current_book = Some(book.clone());
+        self.paused_since = None;
+        self.apply_replay_gain(book).await;
+        self.load_bookmarks().await;
+        if let Some(sync) = &mut self.sync {
+            // Hear about the new book's other positions without waiting
+            sync.recorded = None;
+            sync.exchanged = None;
+        }
+        self.update_sync_banner();
+        self.state.up_next = None;
+        self.state.chapter_editor = None;
+        self.cancel_chapter_suggestion();
+        match self.library_manager.get_chapters(book.id).await {
+            Ok(chapters) => self.set_chapters(chapters)?,
+            Err(e) => {
+                log::warn!("Could not load chapters for '{}': {}", book.title, e);
+                self.set_chapters(Vec::new())?;
+            }
+        }
+        if let Some(mpris) = &mut self.mpris {
+            mpris.set_track(Some(book)).await;
+        }
+
+        Ok(())
+    }
+
+    /// Evens out the loaded book's loudness when `normalize_volume` is on
+    ///
+    /// A book that has not been measured plays as recorded while its
+    /// loudness is measured in the background, and takes the gain once that
+    /// is done. The volume shown stays the listener's own setting.
+    async fn apply_replay_gain(&mut self, book: &Book) {
+        if let Some(measurement) = self.loudness_measurement.take() {
+            measurement.cancel.cancel();
+        }
+        let gain = if self.player.normalize_volume {
+            match loudness::get_replay_gain(&self.db_pool, book.id).await {
+                Ok(gain) => gain,
+                Err(e) => {
+                    log::warn!("Could not load replay gain for '{}': {}", book.title, e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        self.set_replay_gain(gain);
+
+        // A read-only library has nowhere to keep the measurement
+        if gain.is_some()
+            || !self.player.normalize_volume
+            || self.state.read_only
+            || !self.loudness_measured.insert(book.id)
+        {
+            return;
+        }
+        let analyzer = self.library_manager.loudness_analyzer();
+        let cancel = analyzer.cancel_handle();
+        let book_id = book.id;
+        let task =
+            tokio::spawn(async move { analyzer.analyze(VerifyScope::Books(vec![book_id])).await });
+        self.loudness_measurement = Some(LoudnessMeasurement {
+            book_id,
+            cancel,
+            task,
+        });
+    }
+
+    /// Applies the gain of a book whose loudness has just been measured
+    async fn poll_loudness_measurement(&mut self) {
+        if !self
+            .loudness_measurement
+            .as_ref()
+            .is_some_and(|measurement| measurement.task.is_finished())
+        {
+            return;
+        }
+        let Some(measurement) = self.loudness_measurement.take() else {
+            return;
+        };
+        match measurement.task.await {
+            Ok(Ok(report)) => {
+                if let Some(failure) = report.failures.first() {
+                    log::warn!(
+                        "Could not measure loudness of '{}': {}",
+                        failure.title,
+                        failure.error
+                    );
+                }
+            }
+            Ok(Err(e)) => log::warn!("Loudness measurement failed: {}", e),
+            Err(e) => log::warn!("Loudness measurement stopped: {}", e),
+        }
+        if self.current_book.as_ref().map(|book| book.id) != Some(measurement.book_id) {
+            return;
+        }
+        match loudness::get_replay_gain(&self.db_pool, measurement.book_id).await {
+            Ok(Some(gain)) => self.set_replay_gain(Some(gain)),
+            Ok(None) => {}
+            Err(e) => log::warn!("Could not load replay gain: {}", e),
+        }
+    }
+
+    fn set_replay_gain(&mut self, gain_db: Option<f32>) {
+        match self.media_engine.lock() {
+            Ok(mut engine) => {
+                if let Err(e) = engine.set_replay_gain(gain_db) {
+                    log::warn!("Ignoring replay gain: {}", e);
+                }
+            }
+            Err(e) => log::warn!("Could not set replay gain: lock error: {}", e),
+        }
+    }
+
+    /// Show `chapters` for the loaded book and hand them to the media engine
+    fn set_chapters(&mut self, chapters: Vec<Chapter>) -> TuiResult<()> {
+        let markers = chapters
+            .iter()
+            .map(|c| {
+                (
+                    c.title.clone(),
+                    Duration::from_millis(c.start_time.as_millis()),
+                    Duration::from_millis(c.end_time.as_millis()),
+                )
+            })
+            .collect();
+        self.media_engine
+            .lock()
+            .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))?
+            .load_chapters(markers);
+
+        self.state.chapters = chapters;
+        self.state.update_chapter();
+        Ok(())
+    }
+
+    /// Lists the loaded book's bookmarks and caps its auto-bookmarks
+    ///
+    /// Lowering the limit drops the oldest auto-bookmarks from the database
+    /// too.
+    async fn load_bookmarks(&mut self) {
+        let Some(book_id) = self.current_book.as_ref().map(|book| book.id) else {
+            self.bookmark_store.unload();
+            self.state.bookmarks.clear();
+            return;
+        };
+        let duration = Some(self.state.playback.duration);
+        let loaded = self.bookmark_store.load(book_id, duration).await;
+        self.show_bookmarks(loaded, "Failed to load bookmarks");
+    }
+
+    /// Reloads the bookmarks shown for the loaded book, picking up changes
+    /// made elsewhere
+    async fn refresh_bookmarks(&mut self) {
+        if self.current_book.as_ref().map(|book| book.id) != self.bookmark_store.book_id() {
+            return self.load_bookmarks().await;
+        }
+        let refreshed = self.bookmark_store.refresh().await;
+        self.show_bookmarks(refreshed, "Failed to load bookmarks");
+    }
+
+    /// Shows the stored bookmarks, or the error of a change to them
+    ///
+    /// Returns whether the change went through.
+    fn show_bookmarks(&mut self, result: LibraryResult<()>, failure: &str) -> bool {
+        self.state.bookmarks = self.bookmark_store.bookmarks().to_vec();
+        match result {
+            Ok(()) => true,
+            Err(e) => {
+                self.state.set_error(format!("{}: {}", failure, e));
+                false
+            }
+        }
+    }
+
+    /// Reloads the playlists with their book counts and lengths, and the
+    /// books of the open one
+    async fn refresh_playlists(&mut self) {
+        let list = match playlists::list_playlists(&self.db_pool).await {
+            Ok(list) => list,
+            Err(e) => {
+                self.state
+                    .set_error(format!("Failed to load playlists: {}", e));
+                return;
+            }
+        };
+
+        let mut rows = Vec::with_capacity(list.len());
+        let mut contents = Vec::with_capacity(list.len());
+        for playlist in &list {
+            let books = match playlist_books(&self.db_pool, playlist).await {
+                Ok(books) => books,
+                Err(e) => {
+                    self.state
+                        .set_error(format!("Failed to load '{}': {}", playlist.name, e));
+                    Vec::new()
+                }
+            };
+            rows.push(PlaylistRow {
+                id: playlist.id,
+                name: playlist.name.clone(),
+                smart: playlist.playlist_type == PlaylistType::Smart,
+                books: books.len(),
+                duration: books
+                    .iter()
+                    .map(|book| Duration::from_millis(book.duration.as_millis()))
+                    .sum(),
+            });
+            contents.push(books);
+        }
+        self.playlists = list;
+        self.state.playlists = rows;
+
+        // Deleted playlists can leave the selection past the end
+        if self.state.view == crate::state::View::Playlists {
+            self.state.select(self.state.selected_item);
+        }
+        let index = self
+            .state
+            .selected_playlist()
+            .and_then(|row| self.playlists.iter().position(|p| p.id == row.id));
+        match (self.state.open_playlist.as_mut(), index) {
+            (Some(open), Some(index)) => {
+                open.books = std::mem::take(&mut contents[index]);
+                open.selected = open.selected.min(open.books.len().saturating_sub(1));
+            }
+            _ => self.state.open_playlist = None,
+        }
+    }
+
+    /// Shows the selected playlist's books, or hides them
+    async fn toggle_open_playlist(&mut self) {
+        if self.state.open_playlist.take().is_some() {
+            return;
+        }
+        self.state.open_playlist = Some(OpenPlaylist {
+            books: Vec::new(),
+            selected: 0,
+        });
+        self.refresh_playlists().await;
+    }
+
+    /// Asks for the name of a new playlist
+    fn prompt_new_playlist(&mut self) {
+        self.state.input = Some(TextPrompt::new(
+            "Playlist name",
+            "",
+            InputPurpose::NewPlaylist,
+        ));
+    }
+
+    /// Creates an empty playlist named `name` and selects it
+    async fn create_playlist(&mut self, name: &str) {
+        let name = name.trim();
+        if name.is_empty() {
+            self.state.set_status("Playlist not created: no name given");
+            return;
+        }
+        match playlists::find_playlist_by_name(&self.db_pool, name).await {
+            Ok(Some(_)) => {
+                self.state
+                    .set_error(format!("A playlist named '{}' already exists", name));
+                return;
+            }
+            Ok(None) => {}
+            Err(e) => {
+                self.state
+                    .set_error(format!("Failed to create playlist: {}", e));
+                return;
+            }
+        }
+
+        let playlist = Playlist::new_manual(name.to_string());
+        if let Err(e) = playlists::create_playlist(&self.db_pool, &playlist).await {
+            self.state
+                .set_error(format!("Failed to create playlist: {}", e));
+            return;
+        }
+        self.state.open_playlist = None;
+        self.refresh_playlists().await;
+        if let Some(index) = self.playlists.iter().position(|p| p.id == playlist.id) {
+            self.state.select(index);
+        }
+        self.state
+            .set_status(format!("Created playlist '{}'", playlist.name));
+    }
+
+    /// Asks before deleting the selected playlist
+    fn confirm_delete_playlist(&mut self) {
+        if let Some(row) = self.state.selected_playlist() {
+            let message = format!(
+                "Delete playlist '{}'? Its books stay in the library.",
+                row.name
+            );
+            let action = ConfirmAction::DeletePlaylist(row.id);
+            self.state.confirm = Some(ConfirmDialog::new(message, action).dangerous());
+        }
+    }
+
+    /// Deletes a playlist, leaving its books in the library
+    async fn delete_playlist(&mut self, id: PlaylistId) {
+        let name = self
+            .playlists
+            .iter()
+            .find(|playlist| playlist.id == id)
+            .map(|playlist| playlist.name.clone())
+            .unwrap_or_default();
+        match playlists::delete_playlist(&self.db_pool, id).await {
+            Ok(()) => {
+                self.state.open_playlist = None;
+                self.refresh_playlists().await;
+                self.state
+                    .set_status(format!("Deleted playlist '{}'", name));
+            }
+            Err(e) => self
+                .state
+                .set_error(format!("Failed to delete '{}': {}", name, e)),
+        }
+    }
+
+    /// Handle a key while a question awaits a yes or no
+    async fn handle_confirm_key(&mut self, code: KeyCode) {
+        let Some(dialog) = self.state.confirm.as_mut() else {
+            return;
+        };
+        let Some(answer) = dialog.handle_key(code) else {
+            return;
+        };
+        let Some(dialog) = self.state.confirm.take() else {
+            return;
+        };
+        if !answer {
+            self.state.set_status("Cancelled");
+            return;
+        }
+        match dialog.action {
+            ConfirmAction::DeleteBook(id) => self.delete_book(id).await,
+            ConfirmAction::DeletePlaylist(id) => self.delete_playlist(id).await,
+            ConfirmAction::DeleteBookmark(id) => self.delete_bookmark(id).await,
+            ConfirmAction::ResetSettings => self.reset_settings().await,
+        }
+    }
+
+    /// Adds the book selected in the library view to the selected playlist
+    async fn add_to_playlist(&mut self) {
+        let Some(row) = self.state.selected_playlist().cloned() else {
+            return;
+        };
+        let Some(book) = self.selected_book() else {
+            return;
+        };
+        match playlists::append_book_to_playlist(&self.db_pool, row.id, book.id).await {
+            Ok(true) => {
+                self.refresh_playlists().await;
+                self.state
+                    .set_status(format!("Added '{}' to '{}'", book.title, row.name));
+            }
+            Ok(false) => self
+                .state
+                .set_status(format!("'{}' is already in '{}'", book.title, row.name)),
+            Err(e) => self
+                .state
+                .set_error(format!("Failed to add '{}': {}", book.title, e)),
+        }
+    }
+
+    /// Removes the selected book from the open playlist
+    async fn remove_from_playlist(&mut self) {
+        let Some(row) = self.state.selected_playlist().cloned() else {
+            return;
+        };
+        let Some(book) = self
+            .state
+            .open_playlist
+            .as_mut()
+            .and_then(|open| open.remove_selected())
+        else {
+            return;
+        };
+        match playlists::remove_book_from_playlist(&self.db_pool, row.id, book.id).await {
+            Ok(()) => self
+                .state
+                .set_status(format!("Removed '{}' from '{}'", book.title, row.name)),
+            Err(e) => self
+                .state
+                .set_error(format!("Failed to remove '{}': {}", book.title, e)),
+        }
+        self.refresh_playlists().await;
+    }
+
+    /// Moves the selected book of the open playlist one place down, or up
+    async fn move_playlist_book(&mut self, down: bool) {
+        let Some(row) = self.state.selected_playlist().cloned() else {
+            return;
+        };
+        let Some(open) = self.state.open_playlist.as_mut() else {
+            return;
+        };
+        if !open.move_selected(down) {
+            return;
+        }
+        let order: Vec<BookId> = open.books.iter().map(|book| book.id).collect();
+        if let Err(e) = playlists::reorder_playlist_items(&self.db_pool, row.id, &order).await {
+            self.state
+                .set_error(format!("Failed to reorder '{}': {}", row.name, e));
+            self.refresh_playlists().await;
+        }
+    }
+
+    /// Puts every setting back to its default and switches to them
+    async fn reset_settings(&mut self) {
+        if let Err(e) = self.config_manager.reset() {
+            self.state.set_error(format!("Settings not reset: {}", e));
+            return;
+        }
+        let config = self.config_manager.load_effective();
+        self.apply_config(config).await;
+        self.state.config = Config::default();
+        self.state.setting_error = None;
+        self.state.set_status("Settings reset to defaults");
+    }
+
+    /// Reloads the feed subscriptions and their unplayed-episode counts
+    async fn refresh_subscriptions(&mut self) {
+        let loaded = match podcasts::list_podcasts(&self.db_pool).await {
+            Ok(list) => podcasts::count_unplayed_episodes(&self.db_pool)
+                .await
+                .map(|counts| (list, counts)),
+            Err(e) => Err(e),
+        };
+        match loaded {
+            Ok((list, counts)) => {
+                self.state.subscriptions = list
+                    .into_iter()
+                    .map(|podcast| Subscription {
+                        unplayed: counts.get(&podcast.id).copied().unwrap_or(0),
+                        title: podcast.title,
+                    })
+                    .collect()
+            }
+            Err(e) => self
+                .state
+                .set_error(format!("Failed to load subscriptions: {}", e)),
+        }
+    }
+
+    /// Places an auto-bookmark at the playback position
+    ///
+    /// Nothing is placed at the very start or on an existing auto-bookmark,
+    /// so quitting during a bookmarked pause adds no second one.
+    async fn place_auto_bookmark(&mut self, trigger: AutoBookmarkTrigger) {
+        let Some(book_id) = self.current_book.as_ref().map(|book| book.id) else {
+            return;
+        };
+        if self.state.read_only {
+            return;
+        }
+        let position = storystream_core::Duration::from(self.state.playback.position);
+        if position.is_zero()
+            || self
+                .state
+                .bookmarks
+                .iter()
+                .any(|b| b.is_auto() && b.position == position)
+        {
+            return;
+        }
+
+        let added = self
+            .bookmark_store
+            .add(Bookmark::auto(book_id, position, trigger))
+            .await;
+        self.show_bookmarks(added, "Failed to save bookmark");
+    }
+
+    /// Places a "Paused" auto-bookmark once a pause outlasts the threshold
+    ///
+    /// The clock restarts on every pause, so toggling play quickly adds none.
+    async fn track_pause(&mut self, was_playing: bool) {
+        let Some(threshold) = self.auto_bookmark_pause else {
+            return;
+        };
+        if self.state.playback.is_playing || self.current_book.is_none() {
+            self.paused_since = None;
+            return;
+        }
+        if was_playing && !self.book_finished() {
+            self.paused_since = Some(Instant::now());
+        }
+        if self
+            .paused_since
+            .is_some_and(|since| since.elapsed() >= threshold)
+        {
+            self.paused_since = None;
+            self.place_auto_bookmark(AutoBookmarkTrigger::Paused).await;
+        }
+    }
+
+    /// Bookmarks the playback position of the loaded book
+    async fn add_bookmark(&mut self) {
+        let Some(book_id) = self.current_book.as_ref().map(|book| book.id) else {
+            self.state.set_status("No book loaded");
+            return;
+        };
+        let position = storystream_core::Duration::from(self.state.playback.position);
+        if position.is_zero() {
+            self.state.set_status("Start playback before bookmarking");
+            return;
+        }
+
+        let added = self
+            .bookmark_store
+            .add(Bookmark::new(book_id, position))
+            .await;
+        if self.show_bookmarks(added, "Failed to save bookmark") {
+            self.state.set_status(format!("Bookmarked {}", position));
+        }
+    }
+
+    /// Deletes the selected bookmark
+    fn confirm_delete_bookmark(&mut self) {
+        let Some(bookmark) = self.state.bookmarks.get(self.state.selected_item) else {
+            return;
+        };
+        let position = Duration::from_millis(bookmark.position.as_millis());
+        let message = match &bookmark.title {
+            Some(title) => format!(
+                "Delete bookmark '{}' at {}?",
+                title,
+                format_duration(position)
+            ),
+            None => format!("Delete the bookmark at {}?", format_duration(position)),
+        };
+        let action = ConfirmAction::DeleteBookmark(bookmark.id);
+        self.state.confirm = Some(ConfirmDialog::new(message, action).dangerous());
+    }
+
+    /// Deletes a bookmark of the loaded book
+    async fn delete_bookmark(&mut self, id: BookmarkId) {
+        let removed = self.bookmark_store.remove(id).await;
+        if self.show_bookmarks(removed, "Failed to delete bookmark") {
+            self.state.set_status("Bookmark deleted");
+        }
+    }
+
+    /// Deletes every auto-bookmark of the loaded book
+    async fn clear_auto_bookmarks(&mut self) {
+        if self.current_book.is_none() {
+            return;
+        }
+        match self.bookmark_store.clear_auto().await {
+            Ok(count) => {
+                self.state.bookmarks = self.bookmark_store.bookmarks().to_vec();
+                self.state
+                    .set_status(format!("Cleared {} auto-bookmark(s)", count));
+            }
+            Err(e) => self
+                .state
+                .set_error(format!("Failed to clear auto-bookmarks: {}", e)),
+        }
+    }
+
+    /// Records the loaded book's position and swaps changes with other devices
+    ///
+    /// Runs every `SYNC_INTERVAL`; recording a position past another
+    /// device's also retires that device's banner.
+    fn exchange_positions(&mut self) {
+        let Some(sync) = &mut self.sync else {
+            return;
+        };
+        if sync
+            .exchanged
+            .is_some_and(|at| at.elapsed() < SYNC_INTERVAL)
+        {
+            return;
+        }
+        sync.exchanged = Some(Instant::now());
+
+        if let Some(book) = &self.current_book {
+            let position = self.state.playback.position.as_millis() as u64;
+            if sync.recorded != Some(position) {
+                if let Err(e) = sync.engine.record_position(&book.id.as_string(), position) {
+                    log::warn!("Could not record position for sync: {}", e);
+                }
+                sync.recorded = Some(position);
+            }
+        }
+        if let Some(transport) = &sync.transport {
+            if let Err(e) = transport.sync(&sync.engine) {
+                log::warn!("Position sync failed: {}", e);
+            }
+        }
+        if let Some(lan) = &mut sync.lan {
+            lan.sync();
+        }
+        self.update_sync_banner();
+    }
+
+    /// Shows what LAN sync finished in the background: devices found and pairings to confirm
+    fn poll_lan(&mut self) {
+        let Some(lan) = self.sync.as_mut().and_then(|sync| sync.lan.as_mut()) else {
+            return;
+        };
+        if let Some(message) = lan.poll() {
+            self.state.set_status(message);
+        }
+        self.state.lan = Some(lan.devices());
+        self.state.pairing = lan.prompt();
+    }
+
+    /// Says which library folders were not connected at startup
+    fn report_unavailable_volumes(&mut self) {
+        let roots: Vec<_> = self.volumes.unavailable_roots().collect();
+        match roots.as_slice() {
+            [] => {}
+            [root] => self.state.set_status(format!(
+                "Drive not connected: {}; its books are unavailable until it is",
+                root.display()
+            )),
+            many => self.state.set_status(format!(
+                "{} library drives are not connected; their books are unavailable",
+                many.len()
+            )),
+        }
+    }
+
+    /// Checks every few seconds whether library drives came or went
+    ///
+    /// Books on a drive that comes back are playable again straight away,
+    /// without a rescan.
+    fn probe_volumes(&mut self) {
+        if self.volumes_probed.elapsed() < VOLUME_PROBE_INTERVAL {
+            return;
+        }
+        self.volumes_probed = Instant::now();
+        for change in self.volumes.probe() {
+            match change {
+                VolumeChange::Disconnected(root) => self.state.set_status(format!(
+                    "Drive disconnected: {}; its books are unavailable",
+                    root.display()
+                )),
+                VolumeChange::Reconnected(root) => self
+                    .state
+                    .set_status(format!("Drive reconnected: {}", root.display())),
+            }
+        }
+    }
+
+    /// Carries out what remote controls asked for since the last tick
+    async fn poll_remote(&mut self) {
+        while let Some(request) = self.remote.as_ref().and_then(RemoteServer::next_request) {
+            let reply = self.answer_remote(request.command.clone()).await;
+            request.answer(reply);
+        }
+    }
+
+    /// Runs a remote command through the same methods as its key
+    async fn answer_remote(&mut self, command: RemoteCommand) -> Result<RemoteReply, String> {
+        let done = match command {
+            RemoteCommand::Status => Ok(()),
+            RemoteCommand::Play | RemoteCommand::Pause => {
+                let playing = self.remote_status()?.playing;
+                if playing == (command == RemoteCommand::Play) {
+                    Ok(())
+                } else {
+                    self.toggle_playback().await
+                }
+            }
+            RemoteCommand::SeekTo(position) => self.seek_to(position).await,
+            RemoteCommand::SeekBy(offset_ms) => self.seek_by(offset_ms).await,
+            RemoteCommand::Speed(speed) => self.set_speed(speed).await,
+            RemoteCommand::Volume(volume) => self.set_volume(volume).await,
+            RemoteCommand::Search(query) => {
+                let found = search_books(&self.db_pool, &query, SEARCH_LIMIT)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                let books = found
+                    .into_iter()
+                    .map(|result| RemoteBook {
+                        id: result.item.id.as_string(),
+                        title: result.item.title,
+                        author: result.item.author,
+                        duration_ms: result.item.duration.as_millis(),
+                    })
+                    .collect();
+                return Ok(RemoteReply::Books(books));
+            }
+        };
+        done.map_err(|e| e.to_string())?;
+        self.remote_status().map(RemoteReply::Status)
+    }
+
+    /// What the engine is playing, read now rather than at the last tick
+    fn remote_status(&self) -> Result<RemoteStatus, String> {
+        let engine = self
+            .media_engine
+            .lock()
+            .map_err(|e| format!("Lock error: {}", e))?;
+        let chapter = self
+            .state
+            .playback
+            .chapter
+            .and_then(|index| self.state.chapters.get(index))
+            .map(|chapter| chapter.title.clone());
+        Ok(RemoteStatus {
+            title: self.current_book.as_ref().map(|book| book.title.clone()),
+            author: self
+                .current_book
+                .as_ref()
+                .and_then(|book| book.author.clone()),
+            chapter,
+            playing: engine.is_playing(),
+            position_ms: engine.position().as_millis() as u64,
+            duration_ms: engine.duration.unwrap_or_default().as_millis() as u64,
+            speed: engine
+                .speed
+                .lock()
+                .map(|speed| speed.value())
+                .unwrap_or(1.0),
+            volume: engine.volume(),
+        })
+    }
+
+    /// Searches the network for other devices
+    fn find_devices(&mut self) {
+        let Some(lan) = self.sync.as_mut().and_then(|sync| sync.lan.as_mut()) else {
+            self.state.set_status("LAN sync is off");
+            return;
+        };
+        lan.search();
+        self.state.set_status("Looking for devices...");
+    }
+
+    /// Moves the device selection in the settings view
+    fn select_lan_device(&mut self, next: bool) {
+        let (Some(devices), Some(lan)) = (
+            self.state.lan.as_mut(),
+            self.sync.as_mut().and_then(|sync| sync.lan.as_mut()),
+        ) else {
+            return;
+        };
+        if next {
+            devices.select_next();
+        } else {
+            devices.select_previous();
+        }
+        lan.select(devices.selected);
+    }
+
+    /// Starts pairing with the device selected in the settings view
+    ///
+    /// Its code is shown for confirmation once both devices have it.
+    fn pair_device(&mut self) {
+        let Some(lan) = self.sync.as_mut().and_then(|sync| sync.lan.as_mut()) else {
+            self.state.set_status("LAN sync is off");
+            return;
+        };
+        match lan.pair_selected() {
+            Ok(()) => self.state.set_status("Pairing..."),
+            Err(reason) => self.state.set_status(reason),
+        }
+    }
+
+    /// Handle a key while a pairing code awaits confirmation
+    fn handle_pairing_key(&mut self, code: KeyCode) {
+        let accept = match code {
+            KeyCode::Char('y') | KeyCode::Enter => true,
+            KeyCode::Char('n') | KeyCode::Esc => false,
+            _ => return,
+        };
+        let Some(lan) = self.sync.as_mut().and_then(|sync| sync.lan.as_mut()) else {
+            self.state.pairing = None;
+            return;
+        };
+        match lan.answer(accept) {
+            Ok(device) if accept => self.state.set_status(format!("Paired with {}", device)),
+            Ok(device) => self
+                .state
+                .set_status(format!("Did not pair with {}", device)),
+            Err(e) => self.state.set_error(format!("Pairing failed: {}", e)),
+        }
+        self.state.pairing = lan.prompt();
+    }
+
+    /// Lists the output devices to pick from
+    fn open_output_picker(&mut self) {
+        let listed = match self.media_engine.lock() {
+            Ok(engine) => engine.list_output_devices().map(|devices| {
+                let chosen = engine.selected_output_device().map(|device| device.id);
+                (devices, chosen)
+            }),
+            Err(e) => Err(format!("Lock error: {}", e)),
+        };
+        match listed {
+            Ok((devices, chosen)) => {
+                let devices = devices
+                    .into_iter()
+                    .map(|device| (device.id, device.name))
+                    .collect();
+                self.state.output_picker = Some(OutputPicker::new(devices, chosen.as_deref()));
+            }
+            Err(e) => self.state.set_error(e),
+        }
+    }
+
+    /// Moves through the output devices, playing on the one picked
+    fn handle_output_picker_key(&mut self, code: KeyCode) {
+        let Some(picker) = self.state.output_picker.as_mut() else {
+            return;
+        };
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => picker.select_previous(),
+            KeyCode::Down | KeyCode::Char('j') => picker.select_next(),
+            KeyCode::Esc => self.state.output_picker = None,
+            KeyCode::Enter => {
+                let Some(choice) = picker.selected_choice().cloned() else {
+                    return;
+                };
+                self.state.output_picker = None;
+                let result = self
+                    .media_engine
+                    .lock()
+                    .map_err(|e| format!("Lock error: {}", e))
+                    .and_then(|mut engine| engine.set_output_device(choice.id.as_deref()));
+                match result {
+                    Ok(()) => self.state.set_status(format!("Output: {}", choice.name)),
+                    Err(e) => self
+                        .state
+                        .set_error(format!("Could not change output device: {}", e)),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Shows another device's position for the loaded book when it matters
+    ///
+    /// That is when the devices disagree, or the other one is further along.
+    fn update_sync_banner(&mut self) {
+        let remote = match (&self.sync, &self.current_book) {
+            (Some(sync), Some(book)) => sync
+                .engine
+                .remote_position(&book.id.as_string())
+                .ok()
+                .flatten(),
+            _ => None,
+        };
+
+        self.state.sync_banner = remote.and_then(|remote| {
+            let position = Duration::from_millis(remote.position_ms);
+            let ahead = position > self.state.playback.position + SYNC_BANNER_THRESHOLD;
+            (remote.conflict || ahead).then(|| SyncBanner {
+                device: remote.device_label().to_string(),
+                position,
+                conflict: remote.conflict,
+            })
+        });
+    }
+
+    /// Seeks to the other device's position and makes it the agreed one
+    async fn jump_to_remote_position(&mut self) -> TuiResult<()> {
+        let (Some(sync), Some(book)) = (&mut self.sync, &self.current_book) else {
+            return Ok(());
+        };
+        let adopted = sync
+            .engine
+            .adopt_remote_position(&book.id.as_string())
+            .map_err(|e| TuiError::PlaybackError(format!("Sync error: {}", e)))?;
+        self.state.sync_banner = None;
+        let Some(position_ms) = adopted else {
+            return Ok(());
+        };
+        // Publish the choice on the next tick so the other devices follow
+        sync.recorded = Some(position_ms);
+        sync.exchanged = None;
+
+        let position = Duration::from_millis(position_ms).min(self.state.playback.duration);
+        {
+            let mut engine = self
+                .media_engine
+                .lock()
+                .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))?;
+            engine
+                .seek(position)
+                .map_err(|e| TuiError::PlaybackError(format!("Seek error: {}", e)))?;
+        }
+        self.state.playback.position = position;
+        self.state.update_chapter();
+        self.state
+            .set_status(format!("Jumped to {}", format_duration(position)));
+
+        if let Some(mpris) = &self.mpris {
+            mpris.seeked(position).await;
+        }
+        Ok(())
+    }
+
+    /// Hides the banner until the other device moves again
+    fn dismiss_sync_banner(&mut self) {
+        if let (Some(sync), Some(book)) = (&self.sync, &self.current_book) {
+            if let Err(e) = sync.engine.dismiss_remote_position(&book.id.as_string()) {
+                log::warn!("Could not dismiss remote position: {}", e);
+            }
+        }
+        self.state.sync_banner = None;
+    }
+
+    /// Writes the loaded book's bookmarks as Markdown
+    ///
+    /// The file goes to `player.bookmark_export_dir`, or next to the book
+    /// when that is unset.
+    fn export_bookmarks(&mut self) {
+        let Some(book) = &self.current_book else {
+            self.state.set_status("No book loaded");
+            return;
+        };
+
+        let format = BookmarkFormat::Markdown;
+        let dir = match &self.player.bookmark_export_dir {
+            Some(dir) => dir.clone(),
+            None => book
+                .file_path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
+        };
+        let path = dir.join(export_file_name(&book.title, format));
+        let chapters = self
+            .media_engine
+            .lock()
+            .ok()
+            .map(|engine| engine.chapters());
+        let written = export_book(
+            format,
+            &book.title,
+            self.bookmark_store.engine(),
+            chapters.as_ref(),
+        )
+        .map_err(|e| e.to_string())
+        .and_then(|contents| {
+            std::fs::create_dir_all(&dir)
+                .and_then(|()| std::fs::write(&path, contents))
+                .map_err(|e| format!("Could not write {}: {}", path.display(), e))
+        });
+        match written {
+            Ok(()) => self.state.set_status(format!(
+                "Exported {} bookmarks to {}",
+                self.state.bookmarks.len(),
+                path.display()
+            )),
+            Err(e) => self.state.set_error(e),
+        }
+    }
+
+    /// Copies a link to the selected bookmark to the clipboard
+    ///
+    /// The terminal does the copying, so this needs one that lets programs
+    /// set the clipboard.
+    async fn share_bookmark(&mut self) {
+        let (Some(bookmark), Some(book)) = (
+            self.state.bookmarks.get(self.state.selected_item).cloned(),
+            self.current_book.clone(),
+        ) else {
+            return;
+        };
+        let file_hash = match books::get_file_hashes(&self.db_pool).await {
+            Ok(mut hashes) => hashes.remove(&book.id),
+            Err(e) => {
+                log::warn!("Could not read the hash of '{}': {}", book.title, e);
+                None
+            }
+        };
+
+        let text = bookmark.to_share_string(&book, file_hash.as_deref());
+        match execute!(io::stdout(), CopyToClipboard::to_clipboard_from(text)) {
+            Ok(()) => self.state.set_status(format!(
+                "Copied a link to {} in '{}'",
+                bookmark.position, book.title
+            )),
+            Err(e) => self
+                .state
+                .set_error(format!("Could not copy the link: {}", e)),
+        }
+    }
+
+    /// Seeks to the selected bookmark
+    async fn jump_to_bookmark(&mut self) -> TuiResult<()> {
+        let Some(bookmark) = self.state.bookmarks.get(self.state.selected_item) else {
+            return Ok(());
+        };
+        let label = bookmark.position.to_string();
+        let position = Duration::from_millis(bookmark.position.as_millis());
+        {
+            let mut engine = self
+                .media_engine
+                .lock()
+                .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))?;
+            engine
+                .seek(position)
+                .map_err(|e| TuiError::PlaybackError(format!("Seek error: {}", e)))?;
+        }
+        self.state.playback.position = position;
+        self.state.set_status(format!("Jumped to {}", label));
+
+        if let Some(mpris) = &self.mpris {
+            mpris.seeked(position).await;
+        }
+        Ok(())
+    }
+
+    /// Open the chapter editor on the loaded book's chapters
+    ///
+    /// A book without chapters starts as one chapter spanning the whole
+    /// book, ready to be split.
+    fn edit_chapters(&mut self) {
+        let Some(book) = &self.current_book else {
+            self.state.set_status("Load a book to edit its chapters");
+            return;
+        };
+
+        let chapters = if self.state.chapters.is_empty() {
+            vec![Chapter::new(
+                book.id,
+                "Chapter 1".to_string(),
+                0,
+                storystream_core::Duration::from_millis(0),
+                book.duration,
+            )]
+        } else {
+            self.state.chapters.clone()
+        };
+        let mut editor = ChapterEditor::new(chapters);
+        editor.selected = self.state.playback.chapter.unwrap_or(0);
+        self.state.chapter_editor = Some(editor);
+        self.state
+            .set_status(format!("Editing chapters of '{}'", book.title));
+    }
+
+    /// Handle a key while the chapter editor is open
+    ///
+    /// Returns `false` for keys the editor leaves to the player, so playback
+    /// and seeking keep working while editing.
+    async fn handle_chapter_edit_key(&mut self, code: KeyCode) -> TuiResult<bool> {
+        let position = self.state.playback.position;
+        let Some(editor) = self.state.chapter_editor.as_mut() else {
+            return Ok(false);
+        };
+
+        if editor.is_reviewing() {
+            match code {
+                KeyCode::Up | KeyCode::Char('k') => editor.select_previous_suggestion(),
+                KeyCode::Down | KeyCode::Char('j') => editor.select_next_suggestion(),
+                KeyCode::Char(' ') => editor.toggle_suggestion(),
+                KeyCode::Enter => match editor.apply_suggestions() {
+                    Some(_) => self.save_chapters().await?,
+                    None => self
+                        .state
+                        .set_status("No suggestion accepted, chapters unchanged"),
+                },
+                KeyCode::Esc => {
+                    editor.review(Vec::new());
+                    self.state.set_status("Chapter suggestions dismissed");
+                }
+                _ => return Ok(false),
+            }
+            return Ok(true);
+        }
+
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => editor.select_previous(),
+            KeyCode::Down | KeyCode::Char('j') => editor.select_next(),
+            KeyCode::Char('r') => {
+                if let Some(chapter) = editor.selected_chapter() {
+                    let prompt = TextPrompt::new(
+                        format!("Rename chapter {}", chapter.index + 1),
+                        chapter.title.clone(),
+                        InputPurpose::RenameChapter(editor.selected),
+                    );
+                    self.state.input = Some(prompt);
+                }
+            }
+            KeyCode::Char('<') | KeyCode::Char(',') => {
+                if !editor.nudge(-1) {
+                    self.state.set_status("Chapter start cannot move earlier");
+                }
+            }
+            KeyCode::Char('>') | KeyCode::Char('.') => {
+                if !editor.nudge(1) {
+                    self.state.set_status("Chapter start cannot move later");
+                }
+            }
+            KeyCode::Char('s') => {
+                if editor.split_at(position) {
+                    self.state.set_status(format!(
+                        "Split chapter at {}",
+                        crate::state::format_duration(position)
+                    ));
+                } else {
+                    self.state
+                        .set_status("Too close to a chapter boundary to split");
+                }
+            }
+            KeyCode::Char('m') => {
+                if !editor.merge_with_next() {
+                    self.state.set_status("No next chapter to merge with");
+                }
+            }
+            KeyCode::Char('w') => self.save_chapters().await?,
+            KeyCode::Char('x') => self.export_chapters(),
+            KeyCode::Char('a') => self.suggest_chapters(),
+            KeyCode::Esc if editor.suggesting => {
+                if let Some(suggestion) = &self.chapter_suggestion {
+                    suggestion.cancel.cancel();
+                }
+                self.state.set_status("Cancelling chapter suggestions...");
+            }
+            KeyCode::Esc | KeyCode::Char('e') => {
+                if editor.dirty {
+                    self.state.set_status("Discarded unsaved chapter changes");
+                } else {
+                    self.state.clear_status();
+                }
+                self.state.chapter_editor = None;
+                self.cancel_chapter_suggestion();
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Handles maintenance menu keys in the settings view
+    ///
+    /// Tasks are started by their [`Action`] keys; these keys cancel them
+    /// and act on what they found. Returns `false` for keys the menu does
+    /// not use.
+    async fn handle_maintenance_key(&mut self, code: KeyCode) -> TuiResult<bool> {
+        let has_issues = !self.file_issues.is_empty();
+        let has_duplicates = !self.duplicates.is_empty();
+        let has_plan = self.import_plan.is_some();
+        match code {
+            KeyCode::Esc if self.verification.is_some() => {
+                if let Some(verification) = &self.verification {
+                    verification.cancel.cancel();
+                }
+                self.state.set_status("Cancelling file verification...");
+            }
+            KeyCode::Esc if self.import.is_some() => {
+                if let Some(import) = &self.import {
+                    import.cancel.cancel();
+                }
+                self.state.set_status("Cancelling library import...");
+            }
+            KeyCode::Char(' ') if has_plan => self.toggle_planned_import(),
+            KeyCode::Enter if has_plan => self.commit_import_plan().await?,
+            KeyCode::Esc if has_plan => {
+                self.import_plan = None;
+                self.state.maintenance.issues.clear();
+                self.state.maintenance.summary = None;
+                self.state.set_status("Import plan discarded");
+            }
+            KeyCode::Up | KeyCode::Char('k') if has_issues || has_duplicates || has_plan => {
+                self.state.maintenance.select_previous()
+            }
+            KeyCode::Down | KeyCode::Char('j') if has_issues || has_duplicates || has_plan => {
+                self.state.maintenance.select_next()
+            }
+            KeyCode::Char('u') if has_issues => {
+                self.resolve_file_issue(SuggestedAction::UpdateHash).await?
+            }
+            KeyCode::Char('r') if has_issues => {
+                self.resolve_file_issue(SuggestedAction::RefreshMetadata)
+                    .await?
+            }
+            KeyCode::Char('c') if has_issues => {
+                self.resolve_file_issue(SuggestedAction::MarkCorrupt)
+                    .await?
+            }
+            KeyCode::Char('m') if has_duplicates => self.merge_duplicates().await?,
+            KeyCode::Char('s') if has_duplicates => {
+                let index = self.state.maintenance.selected;
+                if index < self.duplicates.len() {
+                    self.duplicates.remove(index);
+                    self.state.maintenance.resolve(index);
+                }
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Empties every cache namespace
+    fn clear_cache(&mut self) {
+        let Some(cache) = &self.cache else {
+            self.state.set_status("Caching is off");
+            return;
+        };
+        let mut freed = 0;
+        for namespace in cache.stats().namespaces {
+            match cache.clear(&namespace.name) {
+                Ok(bytes) => freed += bytes,
+                Err(e) => {
+                    self.state.set_error(format!(
+                        "Failed to clear the {} cache: {}",
+                        namespace.name, e
+                    ));
+                    return;
+                }
+            }
+        }
+        self.state.cache = Some(cache.stats());
+        self.state.set_status(format!(
+            "Cleared the cache, freeing {}",
+            ui::downloads::format_size(freed)
+        ));
+    }
+
+    /// Removes download history older than [`DOWNLOAD_HISTORY_MAX_AGE_DAYS`]
+    async fn prune_download_history(&mut self) {
+        let max_age = chrono::Duration::days(DOWNLOAD_HISTORY_MAX_AGE_DAYS);
+        match self.downloads.prune_history(max_age).await {
+            Ok(removed) => self.state.set_status(format!(
+                "Pruned {} download record(s) older than {} days",
+                removed, DOWNLOAD_HISTORY_MAX_AGE_DAYS
+            )),
+            Err(e) => self
+                .state
+                .set_error(format!("Failed to prune download history: {}", e)),
+        }
+    }
+
+    /// Reloads the downloads view every [`DOWNLOADS_REFRESH`] while it is shown
+    async fn poll_downloads(&mut self) {
+        if self.state.view != crate::state::View::Downloads {
+            return;
+        }
+        if self
+            .downloads_refreshed
+            .is_some_and(|at| at.elapsed() < DOWNLOADS_REFRESH)
+        {
+            return;
+        }
+        self.refresh_downloads().await;
+    }
+
+    /// Reloads download tasks and history
+    async fn refresh_downloads(&mut self) {
+        self.downloads_refreshed = Some(Instant::now());
+        self.state.downloads.tasks = self.downloads.tasks().await;
+        match self.downloads.history(DOWNLOAD_HISTORY_LIMIT).await {
+            Ok(history) => self.state.downloads.history = history,
+            Err(e) => self
+                .state
+                .set_error(format!("Failed to load download history: {}", e)),
+        }
+        let last = self.state.downloads.len().saturating_sub(1);
+        if self.state.selected_item > last {
+            self.state.selected_item = last;
+        }
+    }
+
+    /// Queues the selected failed download again
+    async fn retry_download(&mut self) {
+        let task_id = match self.state.downloads.record(self.state.selected_item) {
+            Some(record) if record.is_failed() => record.task_id.clone(),
+            _ => {
+                self.state.set_status("Select a failed download to retry");
+                return;
+            }
+        };
+
+        match self.downloads.retry_failed(&task_id).await {
+            Ok(()) => self.state.set_status("Download queued again"),
+            Err(e) => self
+                .state
+                .set_error(format!("Cannot retry download: {}", e)),
+        }
+        self.refresh_downloads().await;
+    }
+
+    /// Starts importing new files from the library folders in the background
+    fn start_import(&mut self) {
+        if self.import.is_some() {
+            self.state.set_status("A library import is already running");
+            return;
+        }
+
+        let (tx, events) = mpsc::channel(64);
+        let pipeline = self.library_manager.import_pipeline().with_events(tx);
+        let cancel = pipeline.cancel_handle();
+        let scanner = self.library_manager.scanner();
+        let task = tokio::spawn(async move { pipeline.run(scanner).await });
+
+        self.import = Some(LibraryImport {
+            cancel,
+            events,
+            task,
+        });
+        self.state.maintenance.import = Some(import_line(&PipelineProgress::default()));
+        self.state
+            .set_status("Importing new files from the library folders (Esc to cancel)");
+    }
+
+    /// Updates import progress, reloading the library once it is done
+    async fn poll_import(&mut self) -> TuiResult<()> {
+        let Some(import) = &mut self.import else {
+            return Ok(());
+        };
+        while let Ok(event) = import.events.try_recv() {
+            if let PipelineEvent::Progress(progress) = event {
+                self.state.maintenance.import = Some(import_line(&progress));
+            }
+        }
+        if !import.task.is_finished() {
+            return Ok(());
+        }
+
+        let Some(import) = self.import.take() else {
+            return Ok(());
+        };
+        self.state.maintenance.import = None;
+        match import.task.await {
+            Ok(Ok(report)) => {
+                let summary = import_summary(&report);
+                self.state.maintenance.summary = Some(summary.clone());
+                self.state.set_status(summary);
+                self.reload_library().await?;
+            }
+            Ok(Err(e)) => self
+                .state
+                .set_error(format!("Library import failed: {}", e)),
+            Err(e) => self
+                .state
+                .set_error(format!("Library import stopped: {}", e)),
+        }
+        Ok(())
+    }
+
+    /// Shows what the auto-import did with the files that changed
+    async fn poll_auto_import(&mut self) -> TuiResult<()> {
+        let Some(events) = &mut self.auto_imports else {
+            return Ok(());
+        };
+        let mut changed = false;
+        while let Ok(event) = events.try_recv() {
+            match event {
+                ImportEvent::Failed { .. } => self.state.set_error(event.to_string()),
+                _ => {
+                    changed = true;
+                    self.state.set_status(event.to_string());
+                }
+            }
+        }
+        if changed {
+            self.reload_library().await?;
+        }
+        Ok(())
+    }
+
+    /// Applies edits of the config file, keeping the config in use when an
+    /// edit is invalid
+    async fn poll_config(&mut self) {
+        while let Ok(change) = self.config_changes.try_recv() {
+            match change {
+                Ok(config) => {
+                    self.apply_config(config).await;
+                    if let Ok(file) = self.config_manager.load() {
+                        self.state.config = file;
+                    }
+                }
+                Err(e) => {
+                    let reason = e.to_string();
+                    let reason = reason.lines().next().unwrap_or_default();
+                    self.state
+                        .set_error(format!("Config not reloaded: {}", reason));
+                }
+            }
+        }
+    }
+
+    /// Switches to the theme, player defaults and library folders of an
+    /// edited `config`
+    async fn apply_config(&mut self, config: Config) {
+        if config.app.color_scheme != self.color_scheme {
+            self.color_scheme = config.app.color_scheme;
+            self.state.theme = color_scheme_to_theme(self.color_scheme);
+            self.theme = Theme::new(self.state.theme);
+        }
+        self.state.daily_goal_minutes = config.app.daily_goal_minutes;
+
+        let volume_changed = config.player.default_volume != self.player.default_volume;
+        self.auto_bookmark_pause = (config.player.auto_bookmark_pause_secs > 0)
+            .then(|| Duration::from_secs(config.player.auto_bookmark_pause_secs));
+        self.bookmark_store
+            .set_max_auto_bookmarks(config.player.max_auto_bookmarks);
+        self.player = config.player;
+        if volume_changed {
+            let volume = match &self.output_device {
+                Some((id, name)) => self.player.device_volume(id, name),
+                None => self.player.default_volume,
+            };
+            if let Ok(mut engine) = self.media_engine.lock() {
+                match engine.set_volume(volume as f32 / 100.0) {
+                    Ok(()) => self.state.playback.volume = engine.volume(),
+                    Err(e) => log::warn!("Could not apply the default volume: {}", e),
+                }
+            }
+        }
+
+        self.state
+            .set_status("Settings reloaded from the config file");
+        self.state.apply_keybindings(&config.keybindings);
+        if config.library.paths != self.library_manager.watch_directories() {
+            self.change_library_paths(config.library.paths, config.library.auto_import)
+                .await;
+        }
+    }
+
+    /// Scans and watches `paths` from now on
+    async fn change_library_paths(&mut self, paths: Vec<String>, auto_import: bool) {
+        self.volumes = VolumeMonitor::new(&paths);
+        let Some(manager) = Arc::get_mut(&mut self.library_manager) else {
+            log::warn!("Library folders change on the next start; the library is in use");
+            return;
+        };
+        let result = match manager.change_watch_directories(paths).await {
+            Ok(()) if auto_import && !self.state.read_only => manager.start_watching().await,
+            result => result,
+        };
+        self.auto_imports = manager.take_import_events();
+        if let Err(e) = result {
+            log::warn!("Auto-import is off: {}", e);
+        }
+        self.report_unavailable_volumes();
+    }
+
+    /// Starts working out what importing the library folders would do
+    fn start_import_plan(&mut self) {
+        if self.planning.is_some() || self.import.is_some() {
+            self.state.set_status("A library import is already running");
+            return;
+        }
+
+        self.planning = Some(tokio::spawn(self.library_manager.preview_import()));
+        self.state.maintenance.import =
+            Some("Planning import: reading and hashing the library folders...".to_string());
+        self.state
+            .set_status("Planning an import of the library folders");
+    }
+
+    /// Lists the finished import plan for review
+    async fn poll_import_plan(&mut self) {
+        if !self
+            .planning
+            .as_ref()
+            .is_some_and(|task| task.is_finished())
+        {
+            return;
+        }
+        let Some(task) = self.planning.take() else {
+            return;
+        };
+        self.state.maintenance.import = None;
+
+        let plan = match task.await {
+            Ok(Ok(plan)) => plan,
+            Ok(Err(e)) => {
+                self.state
+                    .set_error(format!("Failed to plan import: {}", e));
+                return;
+            }
+            Err(e) => {
+                self.state
+                    .set_error(format!("Import planning stopped: {}", e));
+                return;
+            }
+        };
+
+        self.state.maintenance.list = MaintenanceList::ImportPlan;
+        self.state.maintenance.issues = plan.items.iter().map(plan_item_line).collect();
+        self.state.maintenance.selected = 0;
+        self.state.maintenance.summary = Some(plan_summary(&plan));
+        self.file_issues.clear();
+        self.duplicates.clear();
+        self.state.set_status(plan_summary(&plan));
+        self.import_plan = Some(plan);
+    }
+
+    /// Includes or excludes the selected file of the import plan
+    fn toggle_planned_import(&mut self) {
+        let Some(plan) = &mut self.import_plan else {
+            return;
+        };
+        let index = self.state.maintenance.selected;
+        plan.toggle(index);
+        if let (Some(item), Some(line)) = (
+            plan.items.get(index),
+            self.state.maintenance.issues.get_mut(index),
+        ) {
+            *line = plan_item_line(item);
+        }
+        self.state.maintenance.summary = Some(plan_summary(plan));
+    }
+
+    /// Imports the included files of the plan under review
+    async fn commit_import_plan(&mut self) -> TuiResult<()> {
+        if self.refuse_read_only() {
+            return Ok(());
+        }
+        let Some(plan) = self.import_plan.take() else {
+            return Ok(());
+        };
+        self.state.maintenance.issues.clear();
+
+        match self.library_manager.commit_import(&plan).await {
+            Ok(_) => {
+                let summary = format!(
+                    "Imported: {} added, {} updated",
+                    plan.creates(),
+                    plan.updates()
+                );
+                self.state.maintenance.summary = Some(summary.clone());
+                self.state.set_status(summary);
+                self.reload_library().await?;
+            }
+            Err(e) => {
+                self.state.maintenance.summary = None;
+                self.state
+                    .set_error(format!("Nothing imported: {} (press I to plan again)", e));
+            }
+        }
+        Ok(())
+    }
+
+    /// Starts verifying every book file in the background
+    fn start_verification(&mut self, depth: VerifyDepth) {
+        if self.verification.is_some() {
+            self.state
+                .set_status("File verification is already running");
+            return;
+        }
+
+        let (tx, events) = mpsc::channel(64);
+        let verifier = self.library_manager.file_verifier().with_events(tx);
+        let cancel = verifier.cancel_handle();
+        let task = tokio::spawn(async move { verifier.verify(VerifyScope::All, depth).await });
+
+        self.verification = Some(Verification {
+            cancel,
+            events,
+            task,
+        });
+        self.state.maintenance.progress = Some((0, 0));
+        self.state.set_status(match depth {
+            VerifyDepth::Hash => "Verifying files (Esc to cancel)",
+            VerifyDepth::Full => "Verifying and decoding files (Esc to cancel)",
+        });
+    }
+
+    /// Updates verification progress, collecting the report once it is done
+    async fn poll_verification(&mut self) {
+        let Some(verification) = &mut self.verification else {
+            return;
+        };
+        while let Ok(event) = verification.events.try_recv() {
+            match event {
+                VerifyEvent::Started { total } => {
+                    self.state.maintenance.progress = Some((0, total))
+                }
+                VerifyEvent::Checked { done, total, .. } => {
+                    self.state.maintenance.progress = Some((done, total))
+                }
+                VerifyEvent::Finished { .. } => {}
+            }
+        }
+        if !verification.task.is_finished() {
+            return;
+        }
+
+        let Some(verification) = self.verification.take() else {
+            return;
+        };
+        self.state.maintenance.progress = None;
+        match verification.task.await {
+            Ok(Ok(report)) => {
+                let summary = verify_summary(&report);
+                self.state.maintenance.summary = Some(summary.clone());
+                self.state.maintenance.list = MaintenanceList::FileIssues;
+                self.state.maintenance.issues = report.issues.iter().map(issue_line).collect();
+                self.state.maintenance.selected = 0;
+                self.file_issues = report.issues;
+                self.duplicates.clear();
+                self.state.set_status(summary);
+            }
+            Ok(Err(e)) => self
+                .state
+                .set_error(format!("File verification failed: {}", e)),
+            Err(e) => self
+                .state
+                .set_error(format!("File verification stopped: {}", e)),
+        }
+    }
+
+    /// Applies `action` to the selected file issue
+    async fn resolve_file_issue(&mut self, action: SuggestedAction) -> TuiResult<()> {
+        if self.refuse_read_only() {
+            return Ok(());
+        }
+        let index = self.state.maintenance.selected;
+        let Some(issue) = self.file_issues.get(index) else {
+            return Ok(());
+        };
+        if !issue.actions.contains(&action) {
+            self.state.set_status(format!(
+                "Cannot {} for '{}'",
+                action.describe(),
+                issue.title
+            ));
+            return Ok(());
+        }
+
+        let verifier = self.library_manager.file_verifier();
+        if let Err(e) = verifier.apply(issue, action).await {
+            self.state
+                .set_error(format!("Failed to {}: {}", action.describe(), e));
+            return Ok(());
+        }
+
+        let title = issue.title.clone();
+        self.file_issues.remove(index);
+        self.state.maintenance.resolve(index);
+        if action == SuggestedAction::RefreshMetadata {
+            self.reload_library().await?;
+        }
+        self.state
+            .set_status(format!("'{}': done, {}", title, action.describe()));
+        Ok(())
+    }
+
+    /// Lists groups of books imported more than once for review
+    async fn find_duplicates(&mut self) {
+        let groups = match self.library_manager.find_duplicates().await {
+            Ok(groups) => groups,
+            Err(e) => {
+                self.state
+                    .set_error(format!("Failed to find duplicates: {}", e));
+                return;
+            }
+        };
+
+        let summary = if groups.is_empty() {
+            "No duplicate books found".to_string()
+        } else {
+            format!(
+                "Found {} group(s) of duplicates; the copy marked * is kept",
+                groups.len()
+            )
+        };
+        self.state.maintenance.summary = Some(summary.clone());
+        self.state.maintenance.list = MaintenanceList::Duplicates;
+        self.state.maintenance.issues = groups.iter().map(duplicate_line).collect();
+        self.state.maintenance.selected = 0;
+        self.duplicates = groups;
+        self.file_issues.clear();
+        self.state.set_status(summary);
+    }
+
+    /// Merges the selected duplicate group into its suggested copy
+    async fn merge_duplicates(&mut self) -> TuiResult<()> {
+        if self.refuse_read_only() {
+            return Ok(());
+        }
+        let index = self.state.maintenance.selected;
+        let Some(group) = self.duplicates.get(index) else {
+            return Ok(());
+        };
+
+        let keep = group.suggested_keep();
+        let remove = group.others(keep);
+        let merged = remove.len();
+        if let Err(e) = self.library_manager.merge_books(keep, remove).await {
+            self.state
+                .set_error(format!("Failed to merge duplicates: {}", e));
+            return Ok(());
+        }
+
+        let title = group.books[0].title.clone();
+        self.duplicates.remove(index);
+        self.state.maintenance.resolve(index);
+        self.reload_library().await?;
+        self.state
+            .set_status(format!("'{}': merged {} duplicate(s)", title, merged));
+        Ok(())
+    }
+
+    /// The selected library book, once its page is loaded
+    ///
+    /// Says in the status bar why there is none.
+    fn selected_book(&mut self) -> Option<Book> {
+        let index = self.state.library_selection();
+        match self.library.get(index) {
+            Some(book) => Some(book.clone()),
+            None if index < self.library.len() => {
+                self.state.set_status("Library is still loading");
+                None
+            }
+            None => {
+                self.state.set_status("No book selected");
+                None
+            }
+        }
+    }
+
+    /// Recounts the library and drops its loaded pages after books changed
+    async fn reload_library(&mut self) -> TuiResult<()> {
+        self.library
+            .invalidate()
+            .await
+            .map_err(|e| TuiError::PlaybackError(format!("Failed to reload books: {}", e)))
+    }
+
+    /// Show the details of the selected library book
+    fn show_book_detail(&mut self) {
+        if let Some(book) = self.selected_book() {
+            self.state.book_detail = Some(BookDetail::new(book));
+        }
+    }
+
+    /// Flip the favorite flag of the selected library book
+    async fn toggle_favorite(&mut self) {
+        let Some(book) = self.selected_book() else {
+            return;
+        };
+
+        let (id, favorite) = (book.id, !book.is_favorite);
+        let status = if favorite {
+            format!("Added {} to favorites", book.title)
+        } else {
+            format!("Removed {} from favorites", book.title)
+        };
+        if let Err(e) = self.library_manager.set_favorite(id, favorite).await {
+            self.state.set_error(format!("Favorite not saved: {}", e));
+            return;
+        }
+        if let Some(book) = self.library.find_mut(id) {
+            book.is_favorite = favorite;
+        }
+        self.state.set_status(status);
+    }
+
+    /// Asks before moving the selected library book to the trash
+    fn confirm_delete_book(&mut self) {
+        let Some(book) = self.selected_book() else {
+            return;
+        };
+        let message = format!("Move '{}' to the trash?", book.title);
+        let action = ConfirmAction::DeleteBook(book.id);
+        self.state.confirm = Some(ConfirmDialog::new(message, action).dangerous());
+    }
+
+    /// Moves a book to the trash, which takes it off the library list
+    async fn delete_book(&mut self, id: BookId) {
+        let title = self
+            .library
+            .find_mut(id)
+            .map(|book| book.title.clone())
+            .unwrap_or_default();
+        if let Err(e) = self.library_manager.soft_delete_book(id).await {
+            self.state
+                .set_error(format!("Failed to delete '{}': {}", title, e));
+            return;
+        }
+        if let Err(e) = self.reload_library().await {
+            self.state.set_error(e.to_string());
+            return;
+        }
+        self.state.library_items_count = self.library.len();
+        self.state.select(self.state.selected_item);
+        self.state
+            .set_status(format!("Moved '{}' to the trash", title));
+    }
+
+    /// Handle a key while the book detail popup is open
+    fn handle_detail_key(&mut self, code: KeyCode) {
+        let read_only = self.state.read_only;
+        let Some(detail) = self.state.book_detail.as_mut() else {
+            return;
+        };
+
+        match code {
+            KeyCode::Esc if detail.editing => detail.editing = false,
+            KeyCode::Esc | KeyCode::Char('i') => self.state.book_detail = None,
+            KeyCode::Char('e') | KeyCode::Char('r') if read_only => {
+                self.state.set_status(READ_ONLY_REASON)
+            }
+            KeyCode::Char('e') => detail.editing = true,
+            KeyCode::Char('r') if !detail.editing => {
+                let folder = detail
+                    .book
+                    .file_path
+                    .parent()
+                    .map(|dir| format!("{}{}", dir.display(), std::path::MAIN_SEPARATOR))
+                    .unwrap_or_default();
+                self.state.input = Some(TextPrompt::new(
+                    "Replace file with",
+                    folder,
+                    InputPurpose::ReplaceFile,
+                ));
+            }
+            KeyCode::Up | KeyCode::Char('k') if detail.editing => detail.select_previous(),
+            KeyCode::Down | KeyCode::Char('j') if detail.editing => detail.select_next(),
+            KeyCode::Char('w') if detail.editing => {
+                detail.write_tags = !detail.write_tags;
+            }
+            KeyCode::Enter if detail.editing => {
+                let field = detail.selected_field();
+                self.state.input = Some(TextPrompt::new(
+                    format!("Edit {}", field.label().to_lowercase()),
+                    field.value(&detail.book),
+                    InputPurpose::EditBook(field),
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    /// Save one edited field of the book in the detail popup
+    ///
+    /// The database keeps the edit even if writing the file's tags fails;
+    /// that failure only shows in the status bar.
+    async fn save_book_field(&mut self, field: BookField, value: String) {
+        let Some(detail) = &self.state.book_detail else {
+            return;
+        };
+
+        let edit = match field {
+            BookField::Title => MetadataEdit::new().with_title(value),
+            BookField::Author => MetadataEdit::new().with_author(value),
+            BookField::Narrator => MetadataEdit::new().with_narrator(value),
+            BookField::Series => MetadataEdit::new().with_series(value),
+        }
+        .with_write_tags(detail.write_tags);
+
+        let outcome = match self
+            .library_manager
+            .edit_metadata(detail.book.id, edit)
+            .await
+        {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                self.state.set_error(format!("Not saved: {}", e));
+                return;
+            }
+        };
+
+        let status = match &outcome.tags {
+            _ if outcome.changed.is_empty() => format!("{} unchanged", field.label()),
+            TagWrite::Skipped => format!("Saved {}", field.label().to_lowercase()),
+            TagWrite::Written => format!(
+                "Saved {} and updated file tags",
+                field.label().to_lowercase()
+            ),
+            TagWrite::Failed(e) => format!(
+                "Saved {}, but file tags were not updated: {}",
+                field.label().to_lowercase(),
+                e
+            ),
+        };
+        self.state.set_status(status);
+
+        let book = outcome.book;
+        if let Some(listed) = self.library.find_mut(book.id) {
+            *listed = book.clone();
+        }
+        if let Some(current) = self.current_book.as_mut().filter(|b| b.id == book.id) {
+            *current = book.clone();
+            self.state.playback.current_file = Some(book.title.clone());
+        }
+        if let Some(detail) = self.state.book_detail.as_mut() {
+            detail.book = book;
+        }
+    }
+
+    /// Replace the file of the book in the detail popup with another copy
+    ///
+    /// A loaded book is reloaded from the new file at its adjusted position,
+    /// playing again if it was.
+    async fn replace_book_file(&mut self, path: &str) {
+        let Some(detail) = &self.state.book_detail else {
+            return;
+        };
+        let id = detail.book.id;
+        let loaded = self.current_book.as_ref().is_some_and(|b| b.id == id);
+        let was_playing = loaded && self.state.playback.is_playing;
+        if loaded {
+            // The saved position is the one scaled to the new file
+            self.save_position().await;
+        }
+
+        let replacement = match self
+            .library_manager
+            .replace_source(id, PathBuf::from(path.trim()))
+            .await
+        {
+            Ok(replacement) => replacement,
+            Err(e) => {
+                self.state.set_error(format!("File not replaced: {}", e));
+                return;
+            }
+        };
+
+        let book = replacement.book.clone();
+        if let Some(listed) = self.library.find_mut(id) {
+            *listed = book.clone();
+        }
+        if let Some(detail) = self.state.book_detail.as_mut() {
+            detail.book = book.clone();
+            detail.replaced = replacement.summary();
+        }
+        let mut status = format!("Replaced the file of '{}'", book.title);
+        if loaded {
+            let position = match playback::get_playback_state(&self.db_pool, id).await {
+                Ok(state) => Duration::from_millis(state.position.as_millis()),
+                Err(_) => self.state.playback.position,
+            };
+            let view = self.state.view;
+            // Its position is saved already, and the engine's is for the old file
+            self.current_book = None;
+            if let Err(e) = self.load_book(&book, position, was_playing).await {
+                status = format!("Replaced the file, but could not reload it: {}", e);
+            }
+            self.state.set_view(view);
+        }
+        self.state.set_status(status);
+    }
+
+    /// Handle a key while a text prompt is open
+    async fn handle_input_key(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        let Some(input) = self.state.input.as_mut() else {
+            return;
+        };
+
+        match input.text.handle_key(code, modifiers) {
+            InputOutcome::Editing => {}
+            InputOutcome::Cancelled => self.state.input = None,
+            InputOutcome::Confirmed => {
+                if let Some(input) = self.state.input.take() {
+                    self.apply_input(input).await;
+                }
+            }
+        }
+    }
+
+    /// Apply a confirmed text prompt
+    async fn apply_input(&mut self, input: TextPrompt) {
+        let value = input.text.into_value();
+        match input.purpose {
+            InputPurpose::RenameChapter(index) => {
+                if let Some(editor) = self.state.chapter_editor.as_mut() {
+                    if !editor.rename(index, &value) {
+                        self.state.set_status("Chapter title unchanged");
+                    }
+                }
+            }
+            InputPurpose::EditBook(field) => self.save_book_field(field, value).await,
+            InputPurpose::Search => {
+                self.state.set_search_query(value);
+                self.run_search().await;
+            }
+            InputPurpose::SearchFilter(field) => {
+                match field.set(&mut self.state.search_filter, &value) {
+                    Ok(()) => self.run_search().await,
+                    Err(e) => self.state.set_error(format!("{}: {}", field.label(), e)),
+                }
+            }
+            InputPurpose::SpeedRamp => {
+                if let Err(e) = self.start_speed_ramp(&value).await {
+                    self.state
+                        .set_error(format!("Speed ramp not started: {}", e));
+                }
+            }
+            InputPurpose::ReplaceFile => self.replace_book_file(&value).await,
+            InputPurpose::NewPlaylist => self.create_playlist(&value).await,
+            InputPurpose::EditSetting(setting) => {
+                self.change_setting(setting, |config| setting.set(config, &value))
+                    .await
+            }
+            InputPurpose::UnlockLimits => {
+                if self.limits.unlock(&value) {
+                    self.state.set_status("Unlocked until StoryStream exits");
+                } else {
+                    self.state.set_status("Wrong password");
+                }
+            }
+        }
+    }
+
+    /// Save the edited chapters and reload them into the player
+    async fn save_chapters(&mut self) -> TuiResult<()> {
+        let (Some(book), Some(editor)) = (&self.current_book, &self.state.chapter_editor) else {
+            return Ok(());
+        };
+
+        let book_id = book.id;
+        match self
+            .library_manager
+            .update_chapters(book_id, editor.chapters.clone())
+            .await
+        {
+            Ok(saved) => {
+                let count = saved.len();
+                if let Some(editor) = self.state.chapter_editor.as_mut() {
+                    editor.chapters = saved.clone();
+                    editor.dirty = false;
+                }
+                self.set_chapters(saved)?;
+                self.state.set_status(format!("Saved {} chapters", count));
+            }
+            Err(e) => self.state.set_error(format!("Chapters not saved: {}", e)),
+        }
+        Ok(())
+    }
+
+    /// Looks for chapter breaks in the loaded book's audio in the background
+    ///
+    /// Decoding a whole book takes a while, so the suggestions come up for
+    /// review in the chapter editor when the task finishes.
+    fn suggest_chapters(&mut self) {
+        let (Some(book), Some(editor)) = (&self.current_book, self.state.chapter_editor.as_mut())
+        else {
+            return;
+        };
+        if self.chapter_suggestion.is_some() {
+            self.state.set_status("Already looking for chapter breaks");
+            return;
+        }
+
+        let suggester = self.library_manager.chapter_suggester();
+        let cancel = suggester.cancel_handle();
+        let book_id = book.id;
+        let task = tokio::spawn(async move { suggester.suggest(book_id).await });
+        editor.suggesting = true;
+        self.chapter_suggestion = Some(ChapterSuggestion {
+            book_id,
+            cancel,
+            task,
+        });
+        self.state.set_status(format!(
+            "Looking for chapter breaks in '{}' (Esc cancels)...",
+            book.title
+        ));
+    }
+
+    /// Brings finished chapter suggestions up for review in the chapter editor
+    async fn poll_chapter_suggestion(&mut self) {
+        if !self
+            .chapter_suggestion
+            .as_ref()
+            .is_some_and(|suggestion| suggestion.task.is_finished())
+        {
+            return;
+        }
+        let Some(suggestion) = self.chapter_suggestion.take() else {
+            return;
+        };
+        let loaded = self.current_book.as_ref().map(|book| book.id);
+        let Some(editor) = self
+            .state
+            .chapter_editor
+            .as_mut()
+            .filter(|_| loaded == Some(suggestion.book_id))
+        else {
+            // The editor was closed or another book loaded meanwhile
+            return;
+        };
+
+        editor.suggesting = false;
+        match suggestion.task.await {
+            Ok(Ok(found)) if found.is_empty() => self
+                .state
+                .set_status("No silences long enough for a chapter break"),
+            Ok(Ok(found)) => {
+                editor.review(found.iter().map(suggestion_row).collect());
+                self.state.set_status(format!(
+                    "{} chapter break(s) suggested: Space toggles, Enter applies and saves",
+                    found.len()
+                ));
+            }
+            Ok(Err(LibraryError::Cancelled)) => {
+                self.state.set_status("Chapter suggestions cancelled")
+            }
+            Ok(Err(e)) => self
+                .state
+                .set_error(format!("Could not suggest chapters: {}", e)),
+            Err(e) => self
+                .state
+                .set_error(format!("Chapter suggestions stopped: {}", e)),
+        }
+    }
+
+    /// Stops looking for chapter breaks, dropping whatever was found
+    fn cancel_chapter_suggestion(&mut self) {
+        if let Some(suggestion) = self.chapter_suggestion.take() {
+            suggestion.cancel.cancel();
+        }
+    }
+
+    /// Write the edited chapters to a CUE sheet next to the audio file
+    fn export_chapters(&mut self) {
+        let (Some(book), Some(editor)) = (&self.current_book, &self.state.chapter_editor) else {
+            return;
+        };
+
+        let path = book.file_path.with_extension("cue");
+        let status = match std::fs::write(&path, chapters_to_cue(book, &editor.chapters)) {
+            Ok(()) => format!("Exported chapters to {}", path.display()),
+            Err(e) => format!("Could not write {}: {}", path.display(), e),
+        };
+        self.state.set_status(status);
+    }
+
+    /// Load the most recently played unfinished book
+    ///
+    /// Problems end up in the status bar and leave the library view open,
+    /// so a missing file never stops the TUI from starting.
+    async fn resume_last_book(&mut self, player: &PlayerConfig) {
+        use crate::state::View;
+
+        let last = match books::get_in_progress_books(&self.db_pool, 1).await {
+            Ok(mut in_progress) => in_progress.pop(),
+            Err(e) => {
+                self.state
+                    .set_error(format!("Cannot look up the last book: {}", e));
+                return;
+            }
+        };
+        let Some(last) = last else {
+            return;
+        };
+
+        let book = last.book;
+        if self.volumes.is_unavailable(&book.file_path) {
+            self.state.set_view(View::Library);
+            self.state.set_error(format!(
+                "Cannot resume '{}': {}",
+                book.title,
+                unavailable_message(&book)
+            ));
+            return;
+        }
+        if !book.file_path.exists() {
+            self.state.set_view(View::Library);
+            self.state.set_status(format!(
+                "Cannot resume '{}': file not found at {}",
+                book.title,
+                book.file_path.display()
+            ));
+            return;
+        }
+
+        let position = Duration::from_millis(last.position.as_millis())
+            .saturating_sub(Duration::from_secs(player.resume_rewind_secs));
+        let autoplay = player.resume_autoplay && self.state.listening_lock.is_none();
+        if let Err(e) = self.load_book(&book, position, autoplay).await {
+            self.state.set_view(View::Library);
+            self.state
+                .set_error(format!("Cannot resume '{}': {}", book.title, e));
+            return;
+        }
+
+        // Keep following the playlist the book was started from
+        if let Ok(Some(session)) = self.library_manager.active_playlist().await {
+            self.playing_playlist = session.current() == Some(book.id);
+        }
+
+        self.state.set_status(format!(
+            "Resumed '{}' at {}{}",
+            book.title,
+            crate::state::format_duration(position),
+            if autoplay { "" } else { " (paused)" }
+        ));
+    }
+
+    /// Opens a shared bookmark link, such as one given on the command line
+    ///
+    /// A book in the library is loaded at the shared position; otherwise
+    /// the status bar says whether it can be fetched.
+    pub async fn open_link(&mut self, link: &str) -> TuiResult<()> {
+        let shared = match SharedBookmark::parse(link) {
+            Ok(shared) => shared,
+            Err(e) => {
+                self.state.set_error(format!("Cannot open the link: {}", e));
+                return Ok(());
+            }
+        };
+
+        match resolve_shared(&self.db_pool, &shared).await {
+            Ok(SharedTarget::Local(book)) => {
+                self.leave_playlist().await;
+                let position = Duration::from_millis(shared.position.as_millis());
+                self.load_book(&book, position, false).await?;
+                self.state
+                    .set_status(format!("Opened '{}' at {}", book.title, shared.position));
+            }
+            Ok(SharedTarget::Fetch(source)) => self.state.set_status(format!(
+                "'{}' is not in your library; it can be fetched from {}",
+                shared.title, source.source
+            )),
+            Ok(SharedTarget::Missing) => self
+                .state
+                .set_status(format!("'{}' is not in your library", shared.title)),
+            Err(e) => self
+                .state
+                .set_error(format!("Cannot look up '{}': {}", shared.title, e)),
+        }
+        Ok(())
+    }
+
+    /// Start the selected playlist, shuffled if `shuffle` is set
+    async fn play_playlist(&mut self, shuffle: bool) -> TuiResult<()> {
+        let Some(playlist) = self.playlists.get(self.state.selected_item).cloned() else {
+            self.state.set_status("No playlist selected");
+            return Ok(());
+        };
+
+        match self
+            .library_manager
+            .start_playlist(playlist.id, shuffle)
+            .await
+        {
+            Ok(progress) => self.apply_playlist_progress(progress).await,
+            Err(e) => {
+                self.state
+                    .set_error(format!("Cannot play '{}': {}", playlist.name, e));
+                Ok(())
+            }
+        }
+    }
+
+    /// Move to the next playlist book after the current one finished
+    async fn advance_playlist(&mut self) -> TuiResult<()> {
+        match self.library_manager.advance_playlist().await {
+            Ok(Some(progress)) => self.apply_playlist_progress(progress).await,
+            Ok(None) => {
+                self.playing_playlist = false;
+                Ok(())
+            }
+            Err(e) => {
+                self.playing_playlist = false;
+                self.state.set_error(format!("Playlist stopped: {}", e));
+                Ok(())
+            }
+        }
+    }
+
+    /// Load the playlist's current book and report skipped ones
+    async fn apply_playlist_progress(&mut self, progress: PlaylistProgress) -> TuiResult<()> {
+        let skipped: Vec<&str> = progress
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                PlaylistEvent::BookSkipped { reason, .. } => Some(reason.as_str()),
+                _ => None,
+            })
+            .collect();
+        let note = match skipped.as_slice() {
+            [] => String::new(),
+            [reason] => format!(" (skipped {})", reason),
+            many => format!(" (skipped {} missing books)", many.len()),
+        };
+
+        let Some(book) = progress.book else {
+            self.playing_playlist = false;
+            self.state.set_status(format!("Playlist finished{}", note));
+            return Ok(());
+        };
+
+        let position = self.saved_position(&book).await;
+        if let Err(e) = self.load_book(&book, position, true).await {
+            // Leave the session in place so the book can be retried
+            self.playing_playlist = false;
+            self.state
+                .set_error(format!("Cannot play '{}': {}", book.title, e));
+            return Ok(());
+        }
+        self.playing_playlist = true;
+        self.state.set_status(format!(
+            "Playlist: {} ({} of {}){}",
+            book.title,
+            progress.session.current_index + 1,
+            progress.session.queue.len(),
+            note
+        ));
+        Ok(())
+    }
+
+    /// Stop following the playlist when another book is picked by hand
+    async fn leave_playlist(&mut self) {
+        if self.playing_playlist {
+            self.playing_playlist = false;
+            let _ = self.library_manager.stop_playlist().await;
+        }
+    }
+
+    /// Offers a book to play next when the engine reports the end of the loaded one
+    ///
+    /// Playlists move on by themselves, so nothing is offered while one plays.
+    async fn suggest_up_next(&mut self) -> TuiResult<()> {
+        let ended = self
+            .media_engine
+            .lock()
+            .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))?
+            .take_playback_ended();
+        if !matches!(ended, Some(MediaEvent::PlaybackEnded)) || self.playing_playlist {
+            return Ok(());
+        }
+        let Some(book_id) = self.current_book.as_ref().map(|book| book.id) else {
+            return Ok(());
+        };
+
+        match self.library_manager.suggest_next(book_id).await {
+            Ok(suggestion) => {
+                self.state.up_next = suggestion.map(|suggestion| UpNext {
+                    reason: suggestion_reason(&suggestion),
+                    book: suggestion.book,
+                })
+            }
+            Err(e) => log::warn!("Could not suggest a book to play next: {}", e),
+        }
+        Ok(())
+    }
+
+    /// Plays the book offered after the last one finished
+    async fn play_up_next(&mut self) -> TuiResult<()> {
+        if let Some(up_next) = self.state.up_next.take() {
+            let position = self.saved_position(&up_next.book).await;
+            self.load_book(&up_next.book, position, true).await?;
+        }
+        Ok(())
+    }
 
-        engine
-            .seek(new_position)
-            .map_err(|e| TuiError::PlaybackError(format!("Seek error: {}", e)))?;
+    /// Whether the loaded book stopped at its end
+    fn book_finished(&self) -> bool {
+        let playback = &self.state.playback;
+        !playback.is_playing
+            && playback.duration > Duration::ZERO
+            && playback.remaining() <= Duration::from_secs(1)
+    }
 
+    /// Seek backward
+    async fn seek_backward(&mut self) -> TuiResult<()> {
+        self.seek_by(-10_000).await?;
         self.state.set_status("Seek -10s");
         Ok(())
     }
 
     /// Seek forward
     async fn seek_forward(&mut self) -> TuiResult<()> {
-        let mut engine = self
+        self.seek_by(10_000).await?;
+        self.state.set_status("Seek +10s");
+        Ok(())
+    }
+
+    /// Seeks `offset_ms` from the current position, backward when negative
+    async fn seek_by(&mut self, offset_ms: i64) -> TuiResult<()> {
+        let current = self
             .media_engine
             .lock()
-            .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))?;
+            .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))?
+            .position();
+        let offset = Duration::from_millis(offset_ms.unsigned_abs());
+        let position = if offset_ms < 0 {
+            current.saturating_sub(offset)
+        } else {
+            current + offset
+        };
+        self.seek_to(position).await
+    }
 
-        let current = engine.position();
-        let duration = engine.duration.unwrap_or(Duration::from_secs(0));
-        let new_position = (current + Duration::from_secs(10)).min(duration);
+    /// Seeks to `position`, stopping at the end of the book
+    async fn seek_to(&mut self, position: Duration) -> TuiResult<()> {
+        let position = {
+            let mut engine = self
+                .media_engine
+                .lock()
+                .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))?;
 
-        engine
-            .seek(new_position)
-            .map_err(|e| TuiError::PlaybackError(format!("Seek error: {}", e)))?;
+            let position = engine.duration.map_or(position, |end| position.min(end));
+            engine
+                .seek(position)
+                .map_err(|e| TuiError::PlaybackError(format!("Seek error: {}", e)))?;
+            position
+        };
 
-        self.state.set_status("Seek +10s");
+        if let Some(mpris) = &self.mpris {
+            mpris.seeked(position).await;
+        }
+        Ok(())
+    }
+
+    /// Jumps to the next chapter, or when going back restarts the current
+    /// one unless it has only just started
+    async fn change_chapter(&mut self, forward: bool) -> TuiResult<()> {
+        let moved = {
+            let mut engine = self
+                .media_engine
+                .lock()
+                .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))?;
+            let moved = if forward {
+                engine.next_chapter()
+            } else {
+                engine.previous_chapter()
+            };
+            moved.map(|()| engine.position())
+        };
+
+        let position = match moved {
+            Ok(position) => position,
+            Err(e) => {
+                self.state.set_status(e);
+                return Ok(());
+            }
+        };
+        self.state.playback.position = position;
+        self.state.update_chapter();
+        if let Some(chapter) = self
+            .state
+            .playback
+            .chapter
+            .and_then(|index| self.state.chapters.get(index))
+        {
+            self.state.set_status(format!("Chapter: {}", chapter.title));
+        }
+
+        if let Some(mpris) = &self.mpris {
+            mpris.seeked(position).await;
+        }
         Ok(())
     }
 
     /// Increase volume
     async fn volume_up(&mut self) -> TuiResult<()> {
-        let mut engine = self
-            .media_engine
+        let volume = self.volume()?;
+        self.set_volume(volume + 0.1).await
+    }
+
+    /// Decrease volume
+    async fn volume_down(&mut self) -> TuiResult<()> {
+        let volume = self.volume()?;
+        self.set_volume(volume - 0.1).await
+    }
+
+    fn volume(&self) -> TuiResult<f32> {
+        self.media_engine
             .lock()
-            .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))?;
+            .map(|engine| engine.volume())
+            .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))
+    }
 
-        let new_volume = (engine.volume() + 0.1).min(1.0);
-        engine
-            .set_volume(new_volume)
+    /// Sets the volume, from 0.0 to 1.0, and remembers it for the output device
+    async fn set_volume(&mut self, volume: f32) -> TuiResult<()> {
+        let volume = volume.clamp(0.0, 1.0);
+        self.media_engine
+            .lock()
+            .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))?
+            .set_volume(volume)
             .map_err(|e| TuiError::PlaybackError(format!("Volume error: {}", e)))?;
 
         self.state
-            .set_status(format!("Volume: {}%", (new_volume * 100.0) as u8));
+            .set_status(format!("Volume: {}%", (volume * 100.0) as u8));
+        self.remember_device_volume(volume);
         Ok(())
     }
 
-    /// Decrease volume
-    async fn volume_down(&mut self) -> TuiResult<()> {
+    /// Applies the remembered volume and equalizer when playback moves to
+    /// another output device
+    fn follow_output_device(&mut self) -> TuiResult<()> {
         let mut engine = self
             .media_engine
             .lock()
             .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))?;
 
-        let new_volume = (engine.volume() - 0.1).max(0.0);
+        let Some(device) = engine.output_device() else {
+            return Ok(());
+        };
+        if self
+            .output_device
+            .as_ref()
+            .is_some_and(|(id, _)| *id == device.id)
+        {
+            return Ok(());
+        }
+
+        let volume = self.player.device_volume(&device.id, &device.name);
         engine
-            .set_volume(new_volume)
+            .set_volume(volume as f32 / 100.0)
             .map_err(|e| TuiError::PlaybackError(format!("Volume error: {}", e)))?;
 
-        self.state
-            .set_status(format!("Volume: {}%", (new_volume * 100.0) as u8));
+        let profile = self.player.device_profile(&device.id, &device.name);
+        let preset = profile
+            .and_then(|profile| profile.equalizer_preset.as_deref())
+            .and_then(|name| {
+                engine
+                    .equalizer_presets()
+                    .iter()
+                    .find(|preset| preset.name == name)
+                    .cloned()
+            });
+        if let Some(preset) = preset {
+            match engine.apply_equalizer_preset(preset) {
+                Ok(()) => self.state.playback.equalizer = engine.equalizer_preset().name.clone(),
+                Err(e) => log::warn!("Could not apply the equalizer of '{}': {}", device.name, e),
+            }
+        }
+
+        self.state.playback.volume = engine.volume();
+        self.state.playback.volume_offset = profile.map_or(0, |profile| profile.volume_offset);
+        self.state.playback.output_device = Some(device.name.clone());
+        self.output_device = Some((device.id, device.name));
+        if let Some(output) = self.state.playback.format_output() {
+            self.state.set_status(format!("Output: {}", output));
+        }
         Ok(())
     }
 
+    /// Saves `volume` as the volume for the output device in use
+    fn remember_device_volume(&mut self, volume: f32) {
+        let Some((id, name)) = &self.output_device else {
+            return;
+        };
+        let percent = (volume * 100.0).round() as u8;
+        self.state.playback.volume_offset = self.player.remember_device_volume(id, name, percent);
+
+        let profiles = self.player.device_profiles.clone();
+        if let Err(e) = self
+            .config_manager
+            .update(|config| config.player.device_profiles = profiles)
+        {
+            log::warn!("Could not save the volume for '{}': {}", name, e);
+        }
+    }
+
     /// Decrease playback speed
     async fn speed_down(&mut self) -> TuiResult<()> {
-        let mut engine = self
+        let speed = self.manual_speed().await?;
+        self.set_speed(speed - 0.1).await
+    }
+
+    /// Increase playback speed
+    async fn speed_up(&mut self) -> TuiResult<()> {
+        let speed = self.manual_speed().await?;
+        self.set_speed(speed + 0.1).await
+    }
+
+    /// Ends a running speed ramp, which choosing a speed by hand takes over
+    /// from, and returns the speed playback is at
+    async fn manual_speed(&mut self) -> TuiResult<f32> {
+        if self.state.playback.ramp_target.is_some() {
+            self.end_speed_ramp().await?;
+        }
+        let engine = self
             .media_engine
             .lock()
             .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))?;
+        Ok(engine
+            .speed
+            .lock()
+            .map(|speed| speed.value())
+            .unwrap_or(1.0))
+    }
 
-        // Get current speed
-        let current_speed = if let Ok(speed_guard) = engine.speed.lock() {
-            speed_guard.value()
-        } else {
-            1.0
-        };
-
-        let new_speed_value = (current_speed - 0.1).max(0.5);
-        let new_speed = Speed::new(new_speed_value)
+    /// Sets the playback speed, kept between 0.5x and 2.0x
+    async fn set_speed(&mut self, value: f32) -> TuiResult<()> {
+        self.manual_speed().await?;
+        let value = value.clamp(0.5, 2.0);
+        let speed = Speed::new(value)
             .map_err(|e| TuiError::PlaybackError(format!("Invalid speed: {}", e)))?;
-
-        engine
-            .set_speed(new_speed)
+        self.media_engine
+            .lock()
+            .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))?
+            .set_speed(speed)
             .map_err(|e| TuiError::PlaybackError(format!("Speed error: {}", e)))?;
 
-        self.state
-            .set_status(format!("Speed: {:.1}x", new_speed_value));
+        self.state.set_status(format!("Speed: {:.1}x", value));
         Ok(())
     }
 
-    /// Increase playback speed
-    async fn speed_up(&mut self) -> TuiResult<()> {
+    /// Moves the sleep timer on to its next preset, turning it off after
+    /// the last one
+    fn cycle_sleep_timer(&mut self) -> TuiResult<()> {
         let mut engine = self
             .media_engine
             .lock()
             .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))?;
+        let current = engine.sleep_timer();
+        let status = match next_sleep_timer(current.as_ref(), !self.state.chapters.is_empty()) {
+            Some(timer) => {
+                engine
+                    .set_sleep_timer(timer)
+                    .map_err(|e| TuiError::PlaybackError(format!("Sleep timer error: {}", e)))?;
+                if timer.is_end_of_chapter() {
+                    "Sleep timer: end of chapter".to_string()
+                } else {
+                    format!("Sleep timer: {} minutes", timer.duration.as_seconds() / 60)
+                }
+            }
+            None => {
+                engine.cancel_sleep_timer();
+                "Sleep timer off".to_string()
+            }
+        };
+        drop(engine);
+        self.state.set_status(status);
+        Ok(())
+    }
 
-        // Get current speed
-        let current_speed = if let Ok(speed_guard) = engine.speed.lock() {
-            speed_guard.value()
-        } else {
-            1.0
+    /// Gives the running sleep timer more time
+    fn extend_sleep_timer(&mut self) -> TuiResult<()> {
+        let remaining = {
+            let mut engine = self
+                .media_engine
+                .lock()
+                .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))?;
+            engine
+                .extend_sleep_timer(SLEEP_EXTENSION)
+                .map_err(|e| TuiError::PlaybackError(format!("Sleep timer error: {}", e)))?;
+            engine.sleep_timer_remaining().unwrap_or_default()
         };
+        self.state
+            .set_status(format!("Sleep timer: {} left", format_duration(remaining)));
+        Ok(())
+    }
 
-        let new_speed_value = (current_speed + 0.1).min(2.0);
-        let new_speed = Speed::new(new_speed_value)
-            .map_err(|e| TuiError::PlaybackError(format!("Invalid speed: {}", e)))?;
+    /// Says when the sleep timer has paused playback and bookmarks the spot
+    async fn finish_sleep_timer(&mut self) {
+        let expired = self
+            .media_engine
+            .lock()
+            .is_ok_and(|mut engine| engine.take_sleep_timer_expired());
+        if expired {
+            self.state.set_status("Sleep timer paused playback");
+            self.place_auto_bookmark(AutoBookmarkTrigger::SleepTimer)
+                .await;
+        }
+    }
 
-        engine
-            .set_speed(new_speed)
-            .map_err(|e| TuiError::PlaybackError(format!("Speed error: {}", e)))?;
+    /// Ends the speed ramp, or asks for one when none is running
+    async fn toggle_speed_ramp(&mut self) -> TuiResult<()> {
+        if self.current_book.is_none() {
+            self.state.set_status("Load a book to ramp its speed");
+            return Ok(());
+        }
+        if self.state.playback.ramp_target.is_some() {
+            return self.end_speed_ramp().await;
+        }
+
+        let speed = self.state.playback.speed;
+        self.state.input = Some(TextPrompt::new(
+            "Speed ramp: start, target and change per hour",
+            format!("{:.2} {:.2} 0.1", speed, (speed + 0.5).min(Speed::MAX)),
+            InputPurpose::SpeedRamp,
+        ));
+        Ok(())
+    }
+
+    /// Starts a speed ramp for the loaded book from "start target per-hour"
+    async fn start_speed_ramp(&mut self, text: &str) -> Result<(), String> {
+        let Some(book_id) = self.current_book.as_ref().map(|b| b.id) else {
+            return Ok(());
+        };
+        let (start, target, per_hour) = parse_speed_ramp(text)?;
+        let ramp = {
+            let mut engine = self
+                .media_engine
+                .lock()
+                .map_err(|e| format!("Lock error: {}", e))?;
+            engine.set_speed_ramp(start, target, per_hour)?;
+            engine.speed_ramp().copied()
+        };
+
+        self.state.playback.speed = start.value();
+        self.state.playback.ramp_target = Some(target.value());
+        match playback::set_speed_ramp(&self.db_pool, book_id, ramp.as_ref()).await {
+            Ok(()) => self.state.set_status(format!(
+                "Ramping {} → {} by {:.2}x an hour",
+                start, target, per_hour
+            )),
+            Err(e) => self
+                .state
+                .set_error(format!("Ramping, but not saved for this book: {}", e)),
+        }
+        Ok(())
+    }
+
+    /// Ends the speed ramp, going back to the speed set before it
+    async fn end_speed_ramp(&mut self) -> TuiResult<()> {
+        let speed = {
+            let mut engine = self
+                .media_engine
+                .lock()
+                .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))?;
+            engine
+                .clear_speed_ramp()
+                .map_err(|e| TuiError::PlaybackError(format!("Speed error: {}", e)))?;
+            engine
+                .speed
+                .lock()
+                .map(|speed| speed.value())
+                .unwrap_or(1.0)
+        };
+        self.state.playback.speed = speed;
+        self.state.playback.ramp_target = None;
 
+        if let Some(book) = &self.current_book {
+            if let Err(e) = playback::set_speed_ramp(&self.db_pool, book.id, None).await {
+                log::warn!("Could not save speed ramp for '{}': {}", book.title, e);
+            }
+        }
         self.state
-            .set_status(format!("Speed: {:.1}x", new_speed_value));
+            .set_status(format!("Speed ramp off, back to {:.1}x", speed));
+        Ok(())
+    }
+
+    /// Says in the status bar when the audio device goes away and comes back
+    fn report_interruption(&mut self) {
+        let Some(event) = self
+            .media_engine
+            .lock()
+            .ok()
+            .and_then(|mut engine| engine.take_interruption())
+        else {
+            return;
+        };
+        match event {
+            MediaEvent::Interrupted => self.state.set_status("Paused — audio device interrupted"),
+            MediaEvent::InterruptionEnded { resumed: true } => {
+                self.state.set_status("Audio device is back, playing on")
+            }
+            MediaEvent::InterruptionEnded { resumed: false } => {
+                self.state.set_status("Audio device is back")
+            }
+            _ => {}
+        }
+    }
+
+    /// Says in the status bar when playback moved off a device that went away
+    fn report_device_error(&mut self) {
+        let error = self
+            .media_engine
+            .lock()
+            .ok()
+            .and_then(|mut engine| engine.take_device_error());
+        if let Some(error) = error {
+            self.state.set_error(error.to_string());
+        }
+    }
+
+    /// Lets the speed ramp count playing time, saving its progress at each step
+    async fn advance_speed_ramp(&mut self) -> TuiResult<()> {
+        let event = self
+            .media_engine
+            .lock()
+            .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))?
+            .advance_speed_ramp()
+            .map_err(|e| TuiError::PlaybackError(format!("Speed error: {}", e)))?;
+
+        if let Some(MediaEvent::SpeedChanged { speed, .. }) = event {
+            self.state.playback.speed = speed;
+            self.state.set_status(format!("Ramping {:.2}x", speed));
+            self.save_speed_ramp().await;
+        }
+        Ok(())
+    }
+
+    /// Saves how far the loaded book's speed ramp has got
+    async fn save_speed_ramp(&mut self) {
+        let Some(book) = &self.current_book else {
+            return;
+        };
+        let ramp = match self.media_engine.lock() {
+            Ok(engine) => engine.speed_ramp().copied(),
+            Err(_) => return,
+        };
+        if let Some(ramp) = ramp {
+            if let Err(e) = playback::set_speed_ramp(&self.db_pool, book.id, Some(&ramp)).await {
+                log::warn!("Could not save speed ramp for '{}': {}", book.title, e);
+            }
+        }
+    }
+
+    /// Switch to the next equalizer preset and remember it for the loaded book
+    async fn cycle_equalizer(&mut self) -> TuiResult<()> {
+        let Some(book_id) = self.current_book.as_ref().map(|b| b.id) else {
+            self.state.set_status("Load a book to change its equalizer");
+            return Ok(());
+        };
+
+        let preset = {
+            let mut engine = self
+                .media_engine
+                .lock()
+                .map_err(|e| TuiError::PlaybackError(format!("Lock error: {}", e)))?;
+            engine
+                .next_equalizer_preset()
+                .map_err(|e| TuiError::PlaybackError(format!("Equalizer error: {}", e)))?
+                .clone()
+        };
+        self.state.playback.equalizer = preset.name.clone();
+
+        match playback::set_equalizer(&self.db_pool, book_id, Some(&preset)).await {
+            Ok(()) => self.state.set_status(format!("Equalizer: {}", preset.name)),
+            Err(e) => self.state.set_status(format!(
+                "Equalizer: {} (not saved for this book: {})",
+                preset.name, e
+            )),
+        }
         Ok(())
     }
 
     /// Cleanup terminal state
     fn cleanup(&mut self) -> TuiResult<()> {
+        // Releases the bus name so media keys stop targeting us
+        self.mpris = None;
+        self.remote = None;
         disable_raw_mode()?;
         execute!(
             self.terminal.backend_mut(),
@@ -488,6 +4678,242 @@ impl IntegratedTuiApp {
     }
 }
 
+/// One-line outcome of a verification for the maintenance menu
+/// What playing a book whose drive is not connected says
+fn unavailable_message(book: &Book) -> String {
+    AppError::FileNotFound {
+        path: book.file_path.clone(),
+    }
+    .user_message()
+}
+
+/// Books of a playlist in playback order, leaving out those in the trash
+async fn playlist_books(pool: &DbPool, playlist: &Playlist) -> Result<Vec<Book>, AppError> {
+    let books = match playlist.smart_criteria {
+        Some(ref criteria) => playlists::get_smart_playlist_books(pool, criteria).await?,
+        None => playlists::get_playlist_books(pool, playlist.id).await?,
+    };
+    Ok(books
+        .into_iter()
+        .filter(|book| !book.is_deleted())
+        .collect())
+}
+
+fn verify_summary(report: &VerifyReport) -> String {
+    let problems = |matches: fn(&FileProblem) -> bool| report.count(matches);
+    let mut summary = format!(
+        "Checked {} file(s): {} changed, {} corrupt, {} missing",
+        report.checked,
+        problems(|p| *p == FileProblem::Changed),
+        problems(|p| matches!(p, FileProblem::Corrupt(_))),
+        problems(|p| *p == FileProblem::Missing),
+    );
+    if report.cancelled {
+        summary.push_str(" (cancelled)");
+    }
+    summary
+}
+
+/// Import counts, what waits at each stage and which stage is slowest
+fn import_line(progress: &PipelineProgress) -> String {
+    let queues = &progress.queues;
+    format!(
+        "Importing: {} found, {} added, {} known, {} failed | {} queued > {} reading > {} writing (slowest: {})",
+        progress.found,
+        progress.imported,
+        progress.skipped,
+        progress.failed,
+        queues.scanned,
+        queues.extracting,
+        queues.writing,
+        queues.bottleneck().describe(),
+    )
+}
+
+/// One-line outcome of a library import for the maintenance menu
+fn import_summary(report: &PipelineReport) -> String {
+    let mut summary = format!(
+        "Imported {} new book(s) from {} file(s): {} already in the library, {} unreadable",
+        report.imported,
+        report.found,
+        report.skipped,
+        report.failed.len(),
+    );
+    if report.relocated > 0 {
+        summary.push_str(&format!(", {} moved book(s) found", report.relocated));
+    }
+    if report.cancelled {
+        summary.push_str(" (cancelled)");
+    }
+    summary
+}
+
+/// Review row for a suggested chapter start; likely breaks start out accepted
+fn suggestion_row(suggestion: &SuggestedChapter) -> SuggestionRow {
+    SuggestionRow {
+        start: suggestion.start,
+        silence: suggestion.silence,
+        confidence: suggestion.confidence,
+        accepted: suggestion.confidence >= ACCEPT_CONFIDENCE,
+    }
+}
+
+/// Describes one file of an import plan: `+` added, `~` updated, `-` skipped
+fn plan_item_line(item: &PlannedImport) -> String {
+    let check = if item.include { "[x]" } else { "[ ]" };
+    match &item.action {
+        PlannedAction::Create => format!("{} + {}", check, item.title()),
+        PlannedAction::Update { changes, .. } if changes.is_empty() => {
+            format!("{} ~ {} (file changed)", check, item.title())
+        }
+        PlannedAction::Update { changes, .. } => {
+            format!(
+                "{} ~ {} ({} changed)",
+                check,
+                item.title(),
+                changes.join(", ")
+            )
+        }
+        PlannedAction::Skip(reason) => format!("    - {}: {}", item.title(), reason),
+    }
+}
+
+/// What committing an import plan would do
+fn plan_summary(plan: &ImportPlan) -> String {
+    format!(
+        "Import plan: {} to add, {} to update, {} skipped",
+        plan.creates(),
+        plan.updates(),
+        plan.skipped()
+    )
+}
+
+/// Describes a file issue and the ways to resolve it
+fn issue_line(issue: &FileIssue) -> String {
+    let problem = match &issue.problem {
+        FileProblem::Missing => "missing".to_string(),
+        FileProblem::Changed => "changed".to_string(),
+        FileProblem::Corrupt(reason) => format!("corrupt: {}", reason),
+    };
+    let actions: Vec<&str> = issue.actions.iter().map(|a| a.describe()).collect();
+    format!("{} - {} ({})", issue.title, problem, actions.join(", "))
+}
+
+/// Short reason shown with an up-next suggestion
+fn suggestion_reason(suggestion: &NextSuggestion) -> String {
+    let book = &suggestion.book;
+    match suggestion.reason {
+        SuggestionReason::NextInSeries => {
+            format!("Next in {}", book.series.as_deref().unwrap_or("the series"))
+        }
+        SuggestionReason::NextInPlaylist => "Next in your playlist".to_string(),
+        SuggestionReason::SameAuthor => format!(
+            "New from {}",
+            book.author.as_deref().unwrap_or("the same author")
+        ),
+    }
+}
+
+/// Describes a duplicate group, listing each copy with the kept one marked
+fn duplicate_line(group: &DuplicateGroup) -> String {
+    let keep = group.suggested_keep();
+    let copies: Vec<String> = group
+        .books
+        .iter()
+        .map(|book| {
+            let name = book
+                .file_path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| book.file_path.display().to_string());
+            let marker = if book.id == keep { "*" } else { "" };
+            format!("{}{}", marker, name)
+        })
+        .collect();
+    format!(
+        "{} - {} ({})",
+        group.books[0].title,
+        group.reason.describe(),
+        copies.join(", ")
+    )
+}
+
+/// The statistics view's totals as CSV, a `section,name,value` row each
+///
+/// Times are in hours; `daily_minutes` ends with `today`, and its last
+/// [`CHART_DAYS`](ui::statistics::CHART_DAYS) days get a row each.
+fn statistics_csv(
+    library: &LibraryStats,
+    daily_minutes: &[u32],
+    today: chrono::NaiveDate,
+) -> String {
+    use ui::statistics::{days_this_month, days_this_week, recent_minutes, CHART_DAYS};
+
+    let hours = |seconds: u64| format!("{:.2}", seconds as f64 / 3600.0);
+    let minutes_as_hours = |minutes: u32| hours(u64::from(minutes) * 60);
+    let mut rows = vec![
+        (
+            "total",
+            "hours_all_time".to_string(),
+            hours(library.listening_time.as_seconds()),
+        ),
+        (
+            "total",
+            "hours_this_week".to_string(),
+            minutes_as_hours(recent_minutes(daily_minutes, days_this_week(today))),
+        ),
+        (
+            "total",
+            "hours_this_month".to_string(),
+            minutes_as_hours(recent_minutes(daily_minutes, days_this_month(today))),
+        ),
+        (
+            "total",
+            "current_streak_days".to_string(),
+            library.current_streak_days.to_string(),
+        ),
+        (
+            "total",
+            "longest_streak_days".to_string(),
+            library.longest_streak_days.to_string(),
+        ),
+        (
+            "total",
+            "books".to_string(),
+            library.total_books.to_string(),
+        ),
+        (
+            "total",
+            "books_finished".to_string(),
+            library.finished_count.to_string(),
+        ),
+        (
+            "total",
+            "completion_percent".to_string(),
+            format!("{:.1}", library.finished_percentage()),
+        ),
+    ];
+    let recent = &daily_minutes[daily_minutes.len().saturating_sub(CHART_DAYS)..];
+    for (i, &minutes) in recent.iter().enumerate() {
+        let days_ago = (recent.len() - 1 - i) as u64;
+        if let Some(date) = today.checked_sub_days(chrono::Days::new(days_ago)) {
+            rows.push(("day", date.to_string(), minutes_as_hours(minutes)));
+        }
+    }
+    for (title, listened) in &library.top_listened_books {
+        rows.push(("book", title.clone(), hours(listened.as_seconds())));
+    }
+    for (author, listened) in &library.top_listened_authors {
+        rows.push(("author", author.clone(), hours(listened.as_seconds())));
+    }
+
+    let mut csv = String::from("section,name,value\n");
+    for (section, name, value) in rows {
+        csv.push_str(&format!("{},{},{}\n", section, csv_field(&name), value));
+    }
+    csv
+}
+
 impl Drop for IntegratedTuiApp {
     fn drop(&mut self) {
         // Cleanup is safe to fail in drop
@@ -499,10 +4925,355 @@ impl Drop for IntegratedTuiApp {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_statistics_csv() {
+        let mut library = LibraryStats::empty();
+        library.listening_time = storystream_core::Duration::from_seconds(5400);
+        library.current_streak_days = 2;
+        library.top_listened_books = vec![(
+            "Guns, Germs, and Steel".to_string(),
+            storystream_core::Duration::from_seconds(3600),
+        )];
+        library.top_listened_authors = vec![(
+            "Diamond".to_string(),
+            storystream_core::Duration::from_seconds(3600),
+        )];
+        // A Wednesday, so the week so far is three days
+        let today = chrono::NaiveDate::from_ymd_opt(2024, 3, 13).unwrap();
+        let csv = statistics_csv(&library, &[60, 0, 0, 30, 0, 15], today);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines[0], "section,name,value");
+        assert!(lines.contains(&"total,hours_all_time,1.50"));
+        assert!(lines.contains(&"total,hours_this_week,0.75"));
+        assert!(lines.contains(&"total,hours_this_month,1.75"));
+        assert!(lines.contains(&"total,current_streak_days,2"));
+        assert!(lines.contains(&"day,2024-03-08,1.00"));
+        assert!(lines.contains(&"day,2024-03-13,0.25"));
+        assert!(lines.contains(&"book,\"Guns, Germs, and Steel\",1.00"));
+        assert!(lines.contains(&"author,Diamond,1.00"));
+    }
+
     #[test]
     fn test_color_scheme_conversion() {
         assert_eq!(color_scheme_to_theme(ColorScheme::Light), ThemeType::Light);
         assert_eq!(color_scheme_to_theme(ColorScheme::Dark), ThemeType::Dark);
         assert_eq!(color_scheme_to_theme(ColorScheme::Auto), ThemeType::Dark);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_sleep_timer_presets_cycle() {
+        let mut timer = next_sleep_timer(None, true);
+        let mut minutes = Vec::new();
+        while let Some(current) = timer.filter(|t| !t.is_end_of_chapter()) {
+            minutes.push(current.duration.as_seconds() / 60);
+            timer = next_sleep_timer(Some(&current), true);
+        }
+        assert_eq!(minutes, [15, 30, 45, 60]);
+        assert!(timer.is_some_and(|t| t.is_end_of_chapter()));
+        assert!(next_sleep_timer(timer.as_ref(), true).is_none());
+
+        // Without chapters the last preset turns the timer off
+        let hour = SleepTimer::new(storystream_core::Duration::from_seconds(3600));
+        assert!(next_sleep_timer(Some(&hour), false).is_none());
+
+        // An extended timer moves on to the next longer preset
+        let extended = SleepTimer::new(storystream_core::Duration::from_seconds(50 * 60));
+        let next = next_sleep_timer(Some(&extended), false).unwrap();
+        assert_eq!(next.duration.as_seconds(), 3600);
+    }
+
+    #[test]
+    fn test_parse_speed_ramp() {
+        let (start, target, per_hour) = parse_speed_ramp("1.25x 1.75 0.1").unwrap();
+        assert_eq!(start.value(), 1.25);
+        assert_eq!(target.value(), 1.75);
+        assert_eq!(per_hour, 0.1);
+
+        assert!(parse_speed_ramp("1.25 1.75").is_err());
+        assert!(parse_speed_ramp("fast 1.75 0.1").is_err());
+        assert!(parse_speed_ramp("1.25 4.0 0.1").is_err());
+    }
+
+    #[test]
+    fn test_duplicate_line_marks_the_kept_copy() {
+        use storystream_library::DuplicateReason;
+
+        let copy = |path: &str, size: u64| {
+            Book::new(
+                "Dune".to_string(),
+                std::path::PathBuf::from(path),
+                size,
+                storystream_core::Duration::from_seconds(60),
+            )
+        };
+        let group = DuplicateGroup {
+            reason: DuplicateReason::IdenticalFile,
+            books: vec![copy("/a/dune.mp3", 10), copy("/b/dune.m4b", 20)],
+        };
+        assert_eq!(
+            duplicate_line(&group),
+            "Dune - identical files (dune.mp3, *dune.m4b)"
+        );
+    }
+
+    #[test]
+    fn test_plan_item_line_shows_inclusion() {
+        use storystream_library::SkipReason;
+
+        let book = Book::new(
+            "Dune".to_string(),
+            std::path::PathBuf::from("/books/dune.m4b"),
+            10,
+            storystream_core::Duration::from_seconds(60),
+        );
+        let mut plan = ImportPlan {
+            items: vec![
+                PlannedImport {
+                    path: book.file_path.clone(),
+                    action: PlannedAction::Update {
+                        book_id: book.id,
+                        changes: vec!["title".to_string()],
+                    },
+                    book: Some(book),
+                    file_hash: Some("abc".to_string()),
+                    include: true,
+                },
+                PlannedImport {
+                    path: std::path::PathBuf::from("/books/cover.jpg"),
+                    action: PlannedAction::Skip(SkipReason::Unsupported),
+                    book: None,
+                    file_hash: None,
+                    include: false,
+                },
+            ],
+        };
+        assert_eq!(plan_item_line(&plan.items[0]), "[x] ~ Dune (title changed)");
+        assert_eq!(
+            plan_item_line(&plan.items[1]),
+            "    - cover.jpg: not a supported audio format"
+        );
+
+        plan.toggle(0);
+        assert_eq!(plan_item_line(&plan.items[0]), "[ ] ~ Dune (title changed)");
+        assert_eq!(
+            plan_summary(&plan),
+            "Import plan: 0 to add, 0 to update, 1 skipped"
+        );
+    }
+
+    #[test]
+    fn test_import_line_names_the_slowest_stage() {
+        use storystream_library::QueueDepths;
+
+        let progress = PipelineProgress {
+            found: 120,
+            imported: 80,
+            skipped: 2,
+            failed: 1,
+            queues: QueueDepths {
+                scanned: 30,
+                extracting: 4,
+                writing: 3,
+            },
+        };
+        assert_eq!(
+            import_line(&progress),
+            "Importing: 120 found, 80 added, 2 known, 1 failed | 30 queued > 4 reading > 3 writing (slowest: reading metadata)"
+        );
+    }
+
+    #[test]
+    fn test_equalizer_presets_from_config() {
+        let mut player = PlayerConfig::default();
+        player
+            .equalizer_presets
+            .insert("bass boost".to_string(), vec![1.0; 10]);
+        player
+            .equalizer_presets
+            .insert("Car".to_string(), vec![2.0; 10]);
+        player
+            .equalizer_presets
+            .insert("Broken".to_string(), vec![2.0; 3]);
+
+        let presets = equalizer_presets(&player);
+        let builtin = EqualizerPreset::builtin();
+        assert_eq!(presets.len(), builtin.len() + 1);
+        let bass = EqualizerPreset::find("Bass Boost", &presets);
+        assert_eq!(bass.gains(), [1.0; 10]);
+        assert_eq!(EqualizerPreset::find("car", &presets).name, "Car");
+        assert_eq!(EqualizerPreset::find("Broken", &presets).name, "Flat");
+    }
+
+    /// The remote control API served by a real app, answered by its loop
+    #[cfg(feature = "remote")]
+    mod remote {
+        use super::*;
+        use media_engine::OutputTarget;
+        use serde_json::Value;
+        use std::io::{Read, Write};
+        use std::net::TcpStream;
+        use storystream_database::queries::books;
+        use storystream_media_formats::wav::wav_from_samples;
+        use tempfile::TempDir;
+
+        const TOKEN: &str = "s3cret";
+
+        /// An app serving remote control on a free port, playing into no
+        /// device, with a 20 second book in its library
+        async fn remote_app(dir: &Path) -> (IntegratedTuiApp, Book) {
+            let audio = dir.join("tone.wav");
+            let tone = (0..20 * 8_000).map(|i| (((i % 80) as i16) - 40) * 40);
+            std::fs::write(&audio, wav_from_samples(tone, 8_000)).unwrap();
+
+            let config_manager = ConfigManager::with_directory(dir.join("config")).unwrap();
+            let mut config = Config::default();
+            config.library.database_path = dir.join("library.db").display().to_string();
+            config.library.paths = vec![dir.display().to_string()];
+            config.library.auto_import = false;
+            config.player.resume_on_startup = false;
+            config.app.remote_control = true;
+            config.app.remote_bind = "127.0.0.1:0".to_string();
+            config.app.remote_token = Some(TOKEN.to_string());
+            config_manager.save(&config).unwrap();
+
+            let viewport = Viewport::Fixed(Rect::new(0, 0, 80, 24));
+            let app = IntegratedTuiApp::open(config_manager, viewport)
+                .await
+                .unwrap();
+            *app.media_engine.lock().unwrap() = MediaEngine::with_defaults()
+                .unwrap()
+                .with_output(OutputTarget::Null);
+
+            let size = std::fs::metadata(&audio).unwrap().len();
+            let mut book = Book::new(
+                "Tone".to_string(),
+                audio,
+                size,
+                storystream_core::Duration::from_seconds(20),
+            );
+            book.author = Some("Test Author".to_string());
+            books::create_book(&app.db_pool, &book).await.unwrap();
+            (app, book)
+        }
+
+        /// Sends a request and runs the app's loop until it is answered,
+        /// returning the status code and body
+        async fn request(
+            app: &mut IntegratedTuiApp,
+            method: &'static str,
+            path: &str,
+            token: Option<&'static str>,
+        ) -> (u16, String) {
+            let address = app.remote.as_ref().unwrap().address();
+            let path = path.to_string();
+            let client = std::thread::spawn(move || {
+                let mut stream = TcpStream::connect(address).unwrap();
+                let auth = token
+                    .map(|token| format!("Authorization: Bearer {}\r\n", token))
+                    .unwrap_or_default();
+                write!(
+                    stream,
+                    "{} {} HTTP/1.0\r\nHost: localhost\r\nContent-Length: 0\r\n{}\r\n",
+                    method, path, auth
+                )
+                .unwrap();
+                let mut response = String::new();
+                stream.read_to_string(&mut response).unwrap();
+                response
+            });
+            while !client.is_finished() {
+                app.poll_remote().await;
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+
+            let response = client.join().unwrap();
+            let status = response[9..12].parse().unwrap();
+            let body = response
+                .split_once("\r\n\r\n")
+                .map(|(_, body)| body.to_string())
+                .unwrap_or_default();
+            (status, body)
+        }
+
+        async fn json(app: &mut IntegratedTuiApp, method: &'static str, path: &str) -> Value {
+            let (status, body) = request(app, method, path, Some(TOKEN)).await;
+            assert_eq!(status, 200, "{} {}: {}", method, path, body);
+            serde_json::from_str(&body).unwrap()
+        }
+
+        #[tokio::test]
+        async fn test_remote_controls_playback() {
+            let dir = TempDir::new().unwrap();
+            let (mut app, book) = remote_app(dir.path()).await;
+            app.load_book(&book, Duration::ZERO, false).await.unwrap();
+
+            let status = json(&mut app, "GET", "/status").await;
+            assert_eq!(status["title"], "Tone");
+            assert_eq!(status["author"], "Test Author");
+            assert_eq!(status["playing"], false);
+            assert_eq!(status["duration_ms"], 20_000);
+
+            let status = json(&mut app, "POST", "/seek?to=12").await;
+            assert_eq!(status["position_ms"], 12_000);
+            let status = json(&mut app, "POST", "/seek?by=-4.5").await;
+            assert_eq!(status["position_ms"], 7_500);
+            // The app stops at the end of the book
+            let status = json(&mut app, "POST", "/seek?to=60").await;
+            assert_eq!(status["position_ms"], 20_000);
+
+            let status = json(&mut app, "POST", "/speed?value=1.5").await;
+            assert_eq!(status["speed"], 1.5);
+            let status = json(&mut app, "POST", "/volume?value=0.25").await;
+            assert_eq!(status["volume"], 0.25);
+
+            json(&mut app, "POST", "/seek?to=0").await;
+            let status = json(&mut app, "POST", "/play").await;
+            assert_eq!(status["playing"], true);
+            let status = json(&mut app, "POST", "/pause").await;
+            assert_eq!(status["playing"], false);
+
+            let books = json(&mut app, "GET", "/library/search?q=tone").await;
+            assert_eq!(books.as_array().unwrap().len(), 1);
+            assert_eq!(books[0]["id"], book.id.as_string());
+        }
+
+        #[tokio::test]
+        async fn test_remote_refuses_bad_requests() {
+            let dir = TempDir::new().unwrap();
+            let (mut app, _) = remote_app(dir.path()).await;
+
+            // The page itself needs no token, the API does
+            let (status, page) = request(&mut app, "GET", "/", None).await;
+            assert_eq!(status, 200);
+            assert!(page.contains("StoryStream"));
+            assert_eq!(request(&mut app, "GET", "/status", None).await.0, 401);
+            assert_eq!(
+                request(&mut app, "GET", "/status", Some("wrong")).await.0,
+                401
+            );
+            let path = format!("/status?token={}", TOKEN);
+            assert_eq!(request(&mut app, "GET", &path, None).await.0, 200);
+
+            assert_eq!(
+                request(&mut app, "POST", "/speed", Some(TOKEN)).await.0,
+                400
+            );
+            assert_eq!(
+                request(&mut app, "POST", "/rewind", Some(TOKEN)).await.0,
+                404
+            );
+            // Too far to seek to, and the server keeps serving after it
+            assert_eq!(
+                request(&mut app, "POST", "/seek?to=1e30", Some(TOKEN))
+                    .await
+                    .0,
+                400
+            );
+            // Nothing is loaded to seek in
+            let (status, body) = request(&mut app, "POST", "/seek?to=5", Some(TOKEN)).await;
+            assert_eq!(status, 409);
+            assert!(body.contains("error"));
+        }
+    }
+}
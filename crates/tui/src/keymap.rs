@@ -0,0 +1,308 @@
+// crates/tui/src/keymap.rs
+//! Keys bound to each action, from the defaults and the config file
+//!
+//! The `[keybindings]` section of the config file gives actions, by
+//! [`Action::name`], keys in place of their defaults:
+//!
+//! ```toml
+//! [keybindings]
+//! play_pause = "space"
+//! seek_forward = "l"
+//! volume_up = "+ ="
+//! ```
+//!
+//! Key handling, the command palette and the help view all read the
+//! [`KeyMap`], so a key changed there is the key shown everywhere.
+
+use crate::actions::{Action, KeyBinding, PALETTE_KEY};
+use crate::state::View;
+use crossterm::event::{KeyCode, KeyModifiers};
+use storystream_config::{KeybindingsConfig, ValidationError};
+
+/// Keys handled before actions are looked up, which actions cannot take
+const RESERVED: [KeyBinding; 8] = [
+    KeyBinding::plain(KeyCode::Up),
+    KeyBinding::plain(KeyCode::Down),
+    KeyBinding::plain(KeyCode::Char('k')),
+    KeyBinding::plain(KeyCode::Char('j')),
+    KeyBinding::plain(KeyCode::Enter),
+    KeyBinding::plain(KeyCode::Esc),
+    KeyBinding::ctrl(KeyCode::Char('c')),
+    PALETTE_KEY,
+];
+
+/// The keys of every action
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyMap {
+    /// Each action with its keys, in the order of [`Action::ALL`]
+    bindings: Vec<(Action, Vec<KeyBinding>)>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self {
+            bindings: Action::ALL
+                .into_iter()
+                .map(|action| (action, action.default_keys()))
+                .collect(),
+        }
+    }
+}
+
+impl KeyMap {
+    /// The default keys, with those the `[keybindings]` section changes
+    ///
+    /// An unknown action name or a key that does not parse is skipped with
+    /// a warning, and that action keeps its default keys.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ValidationError`] naming the setting when a key would run
+    /// two actions in the same view, or is one the TUI keeps for moving
+    /// around.
+    pub fn from_config(config: &KeybindingsConfig) -> Result<Self, ValidationError> {
+        let mut keymap = Self::default();
+        for (name, text) in &config.bindings {
+            let Some(action) = Action::from_name(name) else {
+                log::warn!("Ignoring [keybindings] {}: no action has that name", name);
+                continue;
+            };
+            let keys: Option<Vec<KeyBinding>> =
+                text.split_whitespace().map(KeyBinding::parse).collect();
+            let keys = match keys {
+                Some(keys) if !keys.is_empty() => keys,
+                _ => {
+                    log::warn!(
+                        "Ignoring [keybindings] {} = \"{}\": not a key, the default is kept",
+                        name,
+                        text
+                    );
+                    continue;
+                }
+            };
+            if let Some(key) = keys.iter().find(|key| RESERVED.contains(key)) {
+                return Err(ValidationError::with_value(
+                    field(action),
+                    "is a key kept for moving around",
+                    key,
+                ));
+            }
+            keymap.set(action, keys);
+        }
+
+        if let Some((first, second, key)) = keymap.conflict() {
+            // Named after the setting that was changed, which is the one to fix
+            let (changed, other) = if config.get(second.name()).is_some() {
+                (second, first)
+            } else {
+                (first, second)
+            };
+            return Err(ValidationError::with_value(
+                field(changed),
+                format!("is also the key of {}", other.name()),
+                key,
+            ));
+        }
+        Ok(keymap)
+    }
+
+    /// Returns the keys bound to `action`, the one shown first
+    pub fn keys(&self, action: Action) -> &[KeyBinding] {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .map_or(&[], |(_, keys)| keys)
+    }
+
+    /// Returns the keys of `action` as the help view shows them
+    pub fn describe(&self, action: Action) -> String {
+        let keys: Vec<String> = self.keys(action).iter().map(ToString::to_string).collect();
+        if keys.is_empty() {
+            "(no key)".to_string()
+        } else {
+            keys.join(" / ")
+        }
+    }
+
+    /// Returns the action bound to a key pressed in `view`
+    pub fn action_for(&self, code: KeyCode, modifiers: KeyModifiers, view: View) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(action, keys)| {
+                action.view().is_none_or(|v| v == view)
+                    && keys.iter().any(|key| key.matches(code, modifiers))
+            })
+            .map(|(action, _)| *action)
+    }
+
+    /// Whether a key press is one of the keys of `action`
+    pub fn runs(&self, action: Action, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        self.keys(action)
+            .iter()
+            .any(|key| key.matches(code, modifiers))
+    }
+
+    fn set(&mut self, action: Action, keys: Vec<KeyBinding>) {
+        if let Some((_, bound)) = self.bindings.iter_mut().find(|(bound, _)| *bound == action) {
+            *bound = keys;
+        }
+    }
+
+    /// Finds two actions sharing a key in a view where both keys work
+    fn conflict(&self) -> Option<(Action, Action, KeyBinding)> {
+        for (i, (first, first_keys)) in self.bindings.iter().enumerate() {
+            for (second, second_keys) in &self.bindings[i + 1..] {
+                let overlap = match (first.view(), second.view()) {
+                    (Some(a), Some(b)) => a == b,
+                    _ => true,
+                };
+                let shared = first_keys.iter().find(|key| second_keys.contains(key));
+                if let (true, Some(key)) = (overlap, shared) {
+                    return Some((*first, *second, *key));
+                }
+            }
+        }
+        None
+    }
+}
+
+fn field(action: Action) -> String {
+    format!("keybindings.{}", action.name())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(bindings: &[(&str, &str)]) -> KeybindingsConfig {
+        KeybindingsConfig {
+            bindings: bindings
+                .iter()
+                .map(|(name, keys)| (name.to_string(), keys.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_keys_resolve_by_view() {
+        let keymap = KeyMap::default();
+        let d = KeyCode::Char('d');
+        assert_eq!(
+            keymap.action_for(d, KeyModifiers::NONE, View::Bookmarks),
+            Some(Action::DeleteBookmark)
+        );
+        assert_eq!(
+            keymap.action_for(d, KeyModifiers::NONE, View::Settings),
+            Some(Action::FindDuplicates)
+        );
+        assert_eq!(
+            keymap.action_for(d, KeyModifiers::NONE, View::Library),
+            Some(Action::DeleteBook)
+        );
+        assert_eq!(keymap.action_for(d, KeyModifiers::NONE, View::Player), None);
+
+        let p = KeyCode::Char('p');
+        assert_eq!(
+            keymap.action_for(p, KeyModifiers::NONE, View::Player),
+            Some(Action::PreviousChapter)
+        );
+        assert_eq!(
+            keymap.action_for(p, KeyModifiers::NONE, View::Settings),
+            Some(Action::PruneDownloads)
+        );
+
+        // Uppercase letters arrive with Shift, which plain bindings ignore
+        assert_eq!(
+            keymap.action_for(KeyCode::Char('E'), KeyModifiers::SHIFT, View::Player),
+            Some(Action::CycleEqualizer)
+        );
+        assert_eq!(
+            keymap.action_for(KeyCode::Enter, KeyModifiers::SHIFT, View::Playlists),
+            Some(Action::ShufflePlaylist)
+        );
+        assert_eq!(
+            keymap.action_for(KeyCode::Enter, KeyModifiers::NONE, View::Playlists),
+            None
+        );
+        assert_eq!(
+            keymap.action_for(KeyCode::Char('e'), KeyModifiers::CONTROL, View::Bookmarks),
+            Some(Action::ExportBookmarks)
+        );
+    }
+
+    #[test]
+    fn test_config_replaces_default_keys() {
+        let keymap = KeyMap::from_config(&config(&[
+            ("seek_backward", "h"),
+            ("toggle_help", "?"),
+            ("seek_forward", "l"),
+            ("volume_up", "+ ctrl+up"),
+            ("quit", "Q"),
+        ]))
+        .unwrap();
+        let none = KeyModifiers::NONE;
+        assert_eq!(
+            keymap.action_for(KeyCode::Char('l'), none, View::Player),
+            Some(Action::SeekForward)
+        );
+        assert_eq!(keymap.action_for(KeyCode::Right, none, View::Player), None);
+        assert_eq!(
+            keymap.action_for(KeyCode::Up, KeyModifiers::CONTROL, View::Library),
+            Some(Action::VolumeUp)
+        );
+        assert_eq!(keymap.describe(Action::VolumeUp), "+ / Ctrl+↑");
+        assert!(keymap.runs(Action::Quit, KeyCode::Char('Q'), KeyModifiers::SHIFT));
+        assert!(!keymap.runs(Action::Quit, KeyCode::Char('q'), none));
+        assert_eq!(keymap.describe(Action::OpenView(View::Library)), "(no key)");
+
+        // Actions without a default key can be given one
+        let keymap = KeyMap::from_config(&config(&[("open_library", "1")])).unwrap();
+        assert_eq!(
+            keymap.action_for(KeyCode::Char('1'), none, View::Player),
+            Some(Action::OpenView(View::Library))
+        );
+    }
+
+    #[test]
+    fn test_unreadable_bindings_keep_defaults() {
+        let keymap = KeyMap::from_config(&config(&[
+            ("seek_forward", "right-arrow"),
+            ("play_pause", "   "),
+            ("fly", "f"),
+        ]))
+        .unwrap();
+        assert_eq!(keymap, KeyMap::default());
+    }
+
+    #[test]
+    fn test_duplicate_bindings_are_refused() {
+        // `p` is the player's previous chapter key, and play/pause works there
+        let error = KeyMap::from_config(&config(&[("play_pause", "p")])).unwrap_err();
+        assert_eq!(error.field, "keybindings.play_pause");
+        assert!(error.message.contains("previous_chapter"));
+        assert_eq!(error.value.as_deref(), Some("p"));
+
+        let error = KeyMap::from_config(&config(&[("next_chapter", "b")])).unwrap_err();
+        assert_eq!(error.field, "keybindings.next_chapter");
+
+        // Both are in the Settings view
+        let error = KeyMap::from_config(&config(&[("clear_cache", "i")])).unwrap_err();
+        assert!(error.message.contains("import_library"));
+
+        let error = KeyMap::from_config(&config(&[("quit", "esc")])).unwrap_err();
+        assert_eq!(error.field, "keybindings.quit");
+        assert!(KeyMap::from_config(&config(&[("search", "ctrl+p")])).is_err());
+
+        // The same key in different views is fine
+        assert!(KeyMap::from_config(&config(&[("retry_download", "d")])).is_ok());
+    }
+
+    #[test]
+    fn test_default_keys_do_not_collide() {
+        let keymap = KeyMap::default();
+        assert_eq!(keymap.conflict(), None);
+        for (_, keys) in &keymap.bindings {
+            assert!(keys.iter().all(|key| !RESERVED.contains(key)));
+        }
+    }
+}
@@ -0,0 +1,279 @@
+// crates/tui/src/lan.rs
+//! Position sync with paired devices on the local network
+//!
+//! With `app.lan_sync` on, the TUI serves sync requests, advertises itself
+//! over mDNS and exchanges positions with the paired devices a search
+//! finds. Searches, pairing and sync rounds run on threads of their own;
+//! the TUI calls [`LanSync::poll`] every tick to pick up what finished.
+//! Without the `discovery` feature [`LanSync::start`] returns `None` and
+//! the TUI carries on with the sync folder alone.
+
+#[cfg(feature = "discovery")]
+pub(crate) use imp::LanSync;
+
+#[cfg(not(feature = "discovery"))]
+pub(crate) use noop::LanSync;
+
+#[cfg(feature = "discovery")]
+mod imp {
+    use crate::state::{LanDevices, LanPeer, PairingPrompt};
+    use std::collections::VecDeque;
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+    use std::thread::JoinHandle;
+    use std::time::Duration;
+    use storystream_sync_engine::{
+        Advertisement, HttpTransport, PairedDevices, Peer, PendingPair, SyncEngine, SyncResult,
+        SyncServer,
+    };
+
+    /// How long a search listens for other devices
+    const SEARCH_TIME: Duration = Duration::from_secs(3);
+
+    /// This device's sync server, its advertisement and the devices around it
+    pub(crate) struct LanSync {
+        engine: Arc<SyncEngine>,
+        paired: Arc<Mutex<PairedDevices>>,
+        server: SyncServer,
+        _advertisement: Advertisement,
+        /// Devices the last search found
+        peers: Vec<Peer>,
+        /// Device picked in the settings view
+        selected: usize,
+        search: Option<JoinHandle<SyncResult<Vec<Peer>>>>,
+        /// Pairing this device started
+        pairing: Option<JoinHandle<SyncResult<PendingPair>>>,
+        /// Key exchanges waiting for the user to compare codes, first shown
+        pending: VecDeque<PendingPair>,
+        round: Option<JoinHandle<()>>,
+    }
+
+    impl LanSync {
+        /// Starts serving and advertising, then searches for devices
+        ///
+        /// Paired devices are kept in `config_dir`. Returns `None`, after
+        /// logging why, if the network cannot be used.
+        pub(crate) fn start(engine: Arc<SyncEngine>, config_dir: &Path) -> Option<Self> {
+            let started = PairedDevices::load(config_dir.join("paired_devices.json"))
+                .map(|paired| Arc::new(Mutex::new(paired)))
+                .and_then(|paired| {
+                    let server = SyncServer::start(Arc::clone(&engine), Arc::clone(&paired))?;
+                    let advertisement = engine.advertise(server.port())?;
+                    Ok((paired, server, advertisement))
+                });
+            let (paired, server, advertisement) = match started {
+                Ok(started) => started,
+                Err(e) => {
+                    log::warn!("LAN sync is off: {}", e);
+                    return None;
+                }
+            };
+
+            let mut lan = Self {
+                engine,
+                paired,
+                server,
+                _advertisement: advertisement,
+                peers: Vec::new(),
+                selected: 0,
+                search: None,
+                pairing: None,
+                pending: VecDeque::new(),
+                round: None,
+            };
+            lan.search();
+            Some(lan)
+        }
+
+        /// Starts looking for devices, unless a search is running
+        pub(crate) fn search(&mut self) {
+            if self.search.is_some() {
+                return;
+            }
+            let engine = Arc::clone(&self.engine);
+            self.search = Some(std::thread::spawn(move || {
+                engine.discover_peers(SEARCH_TIME)
+            }));
+        }
+
+        /// Starts pairing with the selected device
+        pub(crate) fn pair_selected(&mut self) -> Result<(), &'static str> {
+            let peer = self.peers.get(self.selected).ok_or("No device selected")?;
+            if !peer.is_compatible() {
+                return Err("That device runs another version of StoryStream");
+            }
+            if self.pairing.is_some() {
+                return Err("Already pairing");
+            }
+            let engine = Arc::clone(&self.engine);
+            let address = peer.address;
+            self.pairing = Some(std::thread::spawn(move || {
+                HttpTransport::pair(address, &engine)
+            }));
+            Ok(())
+        }
+
+        /// Syncs with every paired device the last search found
+        ///
+        /// Nothing starts while the previous round is still running.
+        pub(crate) fn sync(&mut self) {
+            if self
+                .round
+                .as_ref()
+                .is_some_and(|round| !round.is_finished())
+            {
+                return;
+            }
+            let transports: Vec<HttpTransport> = match self.paired.lock() {
+                Ok(paired) => self
+                    .peers
+                    .iter()
+                    .filter(|peer| peer.is_compatible())
+                    .filter_map(|peer| {
+                        let device = paired.get(&peer.device_id)?.clone();
+                        Some(HttpTransport::new(peer.address, device))
+                    })
+                    .collect(),
+                Err(_) => return,
+            };
+            if transports.is_empty() {
+                return;
+            }
+            let engine = Arc::clone(&self.engine);
+            self.round = Some(std::thread::spawn(move || {
+                for transport in transports {
+                    if let Err(e) = transport.sync(&engine) {
+                        log::warn!("LAN sync failed: {}", e);
+                    }
+                }
+            }));
+        }
+
+        /// Picks up finished searches and pairings
+        ///
+        /// Returns a message for the status bar when there is news.
+        pub(crate) fn poll(&mut self) -> Option<String> {
+            self.pending.extend(self.server.take_pairing_requests());
+
+            if let Some(pairing) = self.pairing.take_if(|task| task.is_finished()) {
+                match pairing.join() {
+                    Ok(Ok(pending)) => self.pending.push_back(pending),
+                    Ok(Err(e)) => return Some(format!("Pairing failed: {}", e)),
+                    Err(_) => return Some("Pairing failed".to_string()),
+                }
+            }
+
+            let search = self.search.take_if(|task| task.is_finished())?;
+            match search.join() {
+                Ok(Ok(peers)) => {
+                    self.peers = peers;
+                    self.selected = self.selected.min(self.peers.len().saturating_sub(1));
+                    Some(format!(
+                        "Found {} device(s) on the network",
+                        self.peers.len()
+                    ))
+                }
+                Ok(Err(e)) => Some(format!("Device search failed: {}", e)),
+                Err(_) => Some("Device search failed".to_string()),
+            }
+        }
+
+        /// Devices to list in the settings view
+        pub(crate) fn devices(&self) -> LanDevices {
+            let paired = self.paired.lock().ok();
+            LanDevices {
+                peers: self
+                    .peers
+                    .iter()
+                    .map(|peer| LanPeer {
+                        name: peer.label().to_string(),
+                        paired: paired
+                            .as_ref()
+                            .is_some_and(|paired| paired.get(&peer.device_id).is_some()),
+                        compatible: peer.is_compatible(),
+                    })
+                    .collect(),
+                selected: self.selected,
+                searching: self.search.is_some(),
+            }
+        }
+
+        /// Selects the device [`LanSync::pair_selected`] pairs with
+        pub(crate) fn select(&mut self, index: usize) {
+            self.selected = index.min(self.peers.len().saturating_sub(1));
+        }
+
+        /// Code of the pairing to confirm next
+        pub(crate) fn prompt(&self) -> Option<PairingPrompt> {
+            self.pending.front().map(|pending| PairingPrompt {
+                device: pending.device.label().to_string(),
+                code: pending.code.clone(),
+            })
+        }
+
+        /// Keeps or drops the pairing [`LanSync::prompt`] shows
+        ///
+        /// Returns the device's name.
+        pub(crate) fn answer(&mut self, accept: bool) -> Result<String, String> {
+            let pending = self
+                .pending
+                .pop_front()
+                .ok_or_else(|| "No pairing to confirm".to_string())?;
+            let name = pending.device.label().to_string();
+            if accept {
+                self.paired
+                    .lock()
+                    .map_err(|_| "Lock poisoned".to_string())?
+                    .add(pending.device)
+                    .map_err(|e| e.to_string())?;
+                // The device may have started the pairing, so learn its address
+                self.search();
+            }
+            Ok(name)
+        }
+    }
+}
+
+#[cfg(not(feature = "discovery"))]
+mod noop {
+    use crate::state::{LanDevices, PairingPrompt};
+    use std::path::Path;
+    use std::sync::Arc;
+    use storystream_sync_engine::SyncEngine;
+
+    /// Stand-in used when the `discovery` feature is off
+    pub(crate) struct LanSync;
+
+    impl LanSync {
+        pub(crate) fn start(_engine: Arc<SyncEngine>, _config_dir: &Path) -> Option<Self> {
+            log::warn!("LAN sync is off: this build has no network discovery");
+            None
+        }
+
+        pub(crate) fn search(&mut self) {}
+
+        pub(crate) fn pair_selected(&mut self) -> Result<(), &'static str> {
+            Err("No device selected")
+        }
+
+        pub(crate) fn sync(&mut self) {}
+
+        pub(crate) fn poll(&mut self) -> Option<String> {
+            None
+        }
+
+        pub(crate) fn devices(&self) -> LanDevices {
+            LanDevices::default()
+        }
+
+        pub(crate) fn select(&mut self, _index: usize) {}
+
+        pub(crate) fn prompt(&self) -> Option<PairingPrompt> {
+            None
+        }
+
+        pub(crate) fn answer(&mut self, _accept: bool) -> Result<String, String> {
+            Err("No pairing to confirm".to_string())
+        }
+    }
+}
@@ -1,10 +1,19 @@
 // crates/tui/src/lib.rs
 //! Terminal User Interface for StoryStream
 
+mod actions;
+mod announce;
 mod app;
 mod error;
 mod events;
+mod keymap;
+mod lan;
+mod library_window;
+mod mpris;
+mod palette;
 mod plugins;
+mod remote;
+mod settings;
 mod state;
 mod theme;
 pub mod ui;
@@ -12,11 +21,22 @@ pub mod ui;
 // Integration module for real functionality (requires tokio)
 pub mod integration;
 
+pub use actions::{Action, KeyBinding};
+pub use announce::Announcer;
 pub use app::App;
 pub use error::{TuiError, TuiResult};
 pub use integration::IntegratedTuiApp;
+pub use keymap::KeyMap;
+pub use palette::{CommandPalette, PaletteEntry};
 pub use plugins::{Plugin, PluginManager};
-pub use state::{AppState, PlaybackState, View};
+pub use remote::{
+    RemoteBook, RemoteCommand, RemoteReply, RemoteRequest, RemoteServer, RemoteStatus,
+};
+pub use settings::{Setting, SettingKind};
+pub use state::{
+    AppState, BookDetail, BookField, ChapterEditor, InputPurpose, Maintenance, PlaybackState,
+    SuggestionRow, TextPrompt, View,
+};
 pub use theme::{Theme, ThemeType};
 
 use crossterm::{
@@ -90,4 +110,4 @@ mod tests {
         let app = App::new();
         let _ = app;
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,447 @@
+// crates/tui/src/library_window.rs
+//! The library list, loaded a page at a time
+//!
+//! Reading every book at startup takes seconds and hundreds of MB on a
+//! library of ten thousand books. [`LibraryWindow`] only counts them and
+//! reads the first page; the pages around the selection are fetched in the
+//! background as it moves, and the most recently viewed ones are kept.
+//! Rows whose page is still on its way show as loading. Saved positions
+//! are few and small, so those of every book are read up front to show how
+//! far along each one is.
+
+use crate::state::{BookRow, LibraryRows};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::time::Duration;
+use storystream_core::types::book::Book;
+use storystream_core::{AppError, BookId};
+use storystream_database::queries::books::{self, BookSort};
+use storystream_database::queries::playback;
+use storystream_database::DbPool;
+use storystream_library::VolumeMonitor;
+use tokio::sync::mpsc;
+
+/// Books per fetched page
+pub(crate) const PAGE_SIZE: usize = 200;
+
+/// Pages kept once viewed; older ones are fetched again when needed
+const CACHED_PAGES: usize = 16;
+
+/// Rows handed to the library view, more than any terminal shows
+pub(crate) const LIBRARY_ROWS: usize = 120;
+
+/// Rows on each side of the selection that are fetched ahead of time
+const LOOKAHEAD: usize = PAGE_SIZE / 2;
+
+/// Where the library's books come from, in list order
+pub(crate) trait PageSource: Clone + Send + Sync + 'static {
+    /// Number of books
+    fn count(&self) -> impl Future<Output = Result<usize, AppError>> + Send;
+
+    /// Up to `limit` books starting at `offset`
+    fn page(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<Book>, AppError>> + Send;
+
+    /// Saved position of every book that has been opened
+    fn positions(
+        &self,
+    ) -> impl Future<Output = Result<HashMap<BookId, storystream_core::Duration>, AppError>> + Send;
+}
+
+impl PageSource for DbPool {
+    async fn count(&self) -> Result<usize, AppError> {
+        Ok(books::count_books(self).await? as usize)
+    }
+
+    async fn page(&self, offset: usize, limit: usize) -> Result<Vec<Book>, AppError> {
+        books::list_books_page(self, BookSort::Added, limit as i64, offset as i64).await
+    }
+
+    async fn positions(&self) -> Result<HashMap<BookId, storystream_core::Duration>, AppError> {
+        playback::get_positions(self).await
+    }
+}
+
+/// A page fetched in the background
+struct Fetched {
+    generation: u64,
+    page: usize,
+    books: Result<Vec<Book>, AppError>,
+}
+
+/// The library's books, with only the pages near the selection in memory
+pub(crate) struct LibraryWindow<S> {
+    source: S,
+    total: usize,
+    pages: HashMap<usize, Vec<Book>>,
+    /// Loaded pages, least recently viewed first
+    recent: VecDeque<usize>,
+    /// Pages being fetched
+    loading: HashSet<usize>,
+    /// Pages that could not be fetched, until the library changes
+    failed: HashSet<usize>,
+    /// Bumped when the library changes, so pages fetched before are dropped
+    generation: u64,
+    /// Book last left part way through, marked in the list
+    continue_listening: Option<BookId>,
+    /// Book loaded in the player, highlighted in the list
+    playing: Option<BookId>,
+    /// Saved position of each book that has been opened
+    positions: HashMap<BookId, storystream_core::Duration>,
+    sender: mpsc::UnboundedSender<Fetched>,
+    receiver: mpsc::UnboundedReceiver<Fetched>,
+}
+
+impl<S: PageSource> LibraryWindow<S> {
+    /// Counts the books and reads the first page
+    pub(crate) async fn open(source: S) -> Result<Self, AppError> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let mut window = Self {
+            source,
+            total: 0,
+            pages: HashMap::new(),
+            recent: VecDeque::new(),
+            loading: HashSet::new(),
+            failed: HashSet::new(),
+            generation: 0,
+            continue_listening: None,
+            playing: None,
+            positions: HashMap::new(),
+            sender,
+            receiver,
+        };
+        window.total = window.source.count().await?;
+        window.positions = window.source.positions().await?;
+        if window.total > 0 {
+            let first = window.source.page(0, PAGE_SIZE).await?;
+            window.store(0, first);
+        }
+        Ok(window)
+    }
+
+    /// Marks `book` as the one to continue listening to
+    pub(crate) fn set_continue_listening(&mut self, book: Option<BookId>) {
+        self.continue_listening = book;
+    }
+
+    /// Marks `book` as the one loaded in the player
+    pub(crate) fn set_playing(&mut self, book: Option<BookId>) {
+        self.playing = book;
+    }
+
+    /// Updates how far along `book` is as it plays
+    pub(crate) fn set_position(&mut self, book: BookId, position: storystream_core::Duration) {
+        self.positions.insert(book, position);
+    }
+
+    /// Number of books in the library
+    pub(crate) fn len(&self) -> usize {
+        self.total
+    }
+
+    /// Book at `index`, `None` if its page is not loaded
+    pub(crate) fn get(&self, index: usize) -> Option<&Book> {
+        self.pages
+            .get(&(index / PAGE_SIZE))
+            .and_then(|books| books.get(index % PAGE_SIZE))
+    }
+
+    /// Loaded copy of a book, to update after it changed
+    pub(crate) fn find_mut(&mut self, id: BookId) -> Option<&mut Book> {
+        self.pages
+            .values_mut()
+            .flat_map(|books| books.iter_mut())
+            .find(|book| book.id == id)
+    }
+
+    /// Fetches the pages around `index` that are not loaded yet
+    ///
+    /// Returns at once; [`LibraryWindow::poll`] picks the pages up.
+    pub(crate) fn show(&mut self, index: usize) {
+        if self.total == 0 {
+            return;
+        }
+        let first = index.saturating_sub(LOOKAHEAD) / PAGE_SIZE;
+        let last = (index + LOOKAHEAD).min(self.total - 1) / PAGE_SIZE;
+        for page in first..=last {
+            if self.pages.contains_key(&page) {
+                self.touch(page);
+                continue;
+            }
+            if self.failed.contains(&page) || !self.loading.insert(page) {
+                continue;
+            }
+            let source = self.source.clone();
+            let sender = self.sender.clone();
+            let generation = self.generation;
+            tokio::spawn(async move {
+                let books = source.page(page * PAGE_SIZE, PAGE_SIZE).await;
+                let _ = sender.send(Fetched {
+                    generation,
+                    page,
+                    books,
+                });
+            });
+        }
+    }
+
+    /// Stores the pages fetched since the last call; returns whether any arrived
+    pub(crate) fn poll(&mut self) -> bool {
+        let mut arrived = false;
+        while let Ok(fetched) = self.receiver.try_recv() {
+            if fetched.generation != self.generation {
+                continue;
+            }
+            self.loading.remove(&fetched.page);
+            match fetched.books {
+                Ok(books) => {
+                    self.store(fetched.page, books);
+                    arrived = true;
+                }
+                Err(e) => {
+                    log::warn!("Could not load library page {}: {}", fetched.page, e);
+                    self.failed.insert(fetched.page);
+                }
+            }
+        }
+        arrived
+    }
+
+    /// Recounts the books and forgets every page after the library changed
+    ///
+    /// Pages still being fetched are dropped when they arrive.
+    pub(crate) async fn invalidate(&mut self) -> Result<(), AppError> {
+        self.total = self.source.count().await?;
+        self.positions = self.source.positions().await?;
+        self.generation += 1;
+        self.pages.clear();
+        self.recent.clear();
+        self.loading.clear();
+        self.failed.clear();
+        Ok(())
+    }
+
+    /// Up to `count` rows starting at `first` for the library view
+    ///
+    /// Books on a drive `volumes` has seen go away are marked unavailable.
+    pub(crate) fn rows_from(
+        &self,
+        first: usize,
+        count: usize,
+        volumes: &VolumeMonitor,
+    ) -> LibraryRows {
+        let first = first.min(self.total);
+        let end = (first + count).min(self.total);
+        LibraryRows {
+            first,
+            rows: (first..end)
+                .map(|i| self.get(i).map(|book| self.row(book, volumes)))
+                .collect(),
+        }
+    }
+
+    fn row(&self, book: &Book, volumes: &VolumeMonitor) -> BookRow {
+        let duration = Duration::from_millis(book.duration.as_millis());
+        let total = book.duration.as_millis();
+        let progress = match self.positions.get(&book.id) {
+            Some(position) if total > 0 => (position.as_millis().min(total) * 100 / total) as u8,
+            _ => 0,
+        };
+        BookRow {
+            title: book.title.clone(),
+            author: book.author.clone(),
+            duration,
+            progress,
+            favorite: book.is_favorite,
+            playing: self.playing == Some(book.id),
+            unavailable: volumes.is_unavailable(&book.file_path),
+            continue_listening: self.continue_listening == Some(book.id),
+        }
+    }
+
+    fn store(&mut self, page: usize, books: Vec<Book>) {
+        self.pages.insert(page, books);
+        self.touch(page);
+        while self.recent.len() > CACHED_PAGES {
+            if let Some(oldest) = self.recent.pop_front() {
+                self.pages.remove(&oldest);
+            }
+        }
+    }
+
+    /// Marks a loaded page as the most recently viewed
+    fn touch(&mut self, page: usize) {
+        self.recent.retain(|&p| p != page);
+        self.recent.push_back(page);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// A library of `total` made-up books that counts the queries it answers
+    #[derive(Clone)]
+    struct MockPages {
+        total: usize,
+        queries: Arc<AtomicUsize>,
+    }
+
+    impl MockPages {
+        fn new(total: usize) -> Self {
+            Self {
+                total,
+                queries: Arc::new(AtomicUsize::new(0)),
+            }
+        }
+
+        fn queries(&self) -> usize {
+            self.queries.load(Ordering::SeqCst)
+        }
+    }
+
+    impl PageSource for MockPages {
+        async fn count(&self) -> Result<usize, AppError> {
+            self.queries.fetch_add(1, Ordering::SeqCst);
+            Ok(self.total)
+        }
+
+        async fn page(&self, offset: usize, limit: usize) -> Result<Vec<Book>, AppError> {
+            self.queries.fetch_add(1, Ordering::SeqCst);
+            Ok((offset..(offset + limit).min(self.total))
+                .map(|i| {
+                    Book::new(
+                        format!("Book {}", i),
+                        format!("/books/{}.m4b", i).into(),
+                        1024,
+                        storystream_core::Duration::from_seconds(3600),
+                    )
+                })
+                .collect())
+        }
+
+        async fn positions(&self) -> Result<HashMap<BookId, storystream_core::Duration>, AppError> {
+            Ok(HashMap::new())
+        }
+    }
+
+    async fn wait_for_pages(window: &mut LibraryWindow<MockPages>) {
+        for _ in 0..100 {
+            if window.poll() && window.loading.is_empty() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        panic!("pages never arrived");
+    }
+
+    #[tokio::test]
+    async fn test_startup_reads_one_page_of_a_large_library() {
+        let source = MockPages::new(50_000);
+        let window = LibraryWindow::open(source.clone()).await.unwrap();
+
+        // A count and the first page, however large the library
+        assert_eq!(source.queries(), 2);
+        assert_eq!(window.len(), 50_000);
+        assert_eq!(window.get(0).unwrap().title, "Book 0");
+        assert!(window.get(PAGE_SIZE).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_scrolling_fetches_the_pages_around_the_selection() {
+        let source = MockPages::new(50_000);
+        let mut window = LibraryWindow::open(source.clone()).await.unwrap();
+
+        window.show(30_000);
+        let rows = window.rows_from(29_995, 10, &VolumeMonitor::default());
+        assert_eq!(rows.first, 29_995);
+        assert!(rows.rows.iter().all(Option::is_none));
+
+        wait_for_pages(&mut window).await;
+        assert_eq!(window.get(30_000).unwrap().title, "Book 30000");
+        let rows = window.rows_from(29_995, 10, &VolumeMonitor::default());
+        assert_eq!(rows.rows[5].as_ref().unwrap().title, "Book 30000");
+        // The page holding the selection, and the one the lookahead reaches
+        assert_eq!(source.queries(), 4);
+
+        // Loaded pages are not fetched again
+        window.show(30_010);
+        assert!(!window.poll());
+        assert_eq!(source.queries(), 4);
+
+        // Rows stop at the end of the list
+        let rows = window.rows_from(49_995, 10, &VolumeMonitor::default());
+        assert_eq!((rows.first, rows.rows.len()), (49_995, 5));
+    }
+
+    #[tokio::test]
+    async fn test_rows_mark_the_book_to_continue() {
+        let mut window = LibraryWindow::open(MockPages::new(3)).await.unwrap();
+        let second = window.get(1).unwrap().id;
+        window.set_continue_listening(Some(second));
+
+        let rows = window.rows_from(0, 10, &VolumeMonitor::default());
+        let marked = rows
+            .rows
+            .iter()
+            .map(|row| row.as_ref().unwrap().continue_listening)
+            .collect::<Vec<_>>();
+        assert_eq!(marked, [false, true, false]);
+    }
+
+    #[tokio::test]
+    async fn test_rows_show_progress_and_the_playing_book() {
+        let mut window = LibraryWindow::open(MockPages::new(30)).await.unwrap();
+        let (first, second) = (window.get(0).unwrap().id, window.get(1).unwrap().id);
+        window.find_mut(second).unwrap().is_favorite = true;
+        window.set_position(first, storystream_core::Duration::from_seconds(900));
+        window.set_position(second, storystream_core::Duration::from_seconds(7200));
+        window.set_playing(Some(second));
+
+        let rows = window.rows_from(0, 3, &VolumeMonitor::default());
+        let rows: Vec<BookRow> = rows.rows.into_iter().map(Option::unwrap).collect();
+        assert_eq!(rows[0].progress, 25);
+        assert!(!rows[0].favorite && !rows[0].playing);
+        assert_eq!(rows[1].progress, 100);
+        assert!(rows[1].favorite && rows[1].playing);
+        assert_eq!(rows[2].progress, 0);
+        assert_eq!(rows[2].duration, Duration::from_secs(3600));
+    }
+
+    #[tokio::test]
+    async fn test_only_recent_pages_are_kept() {
+        let source = MockPages::new(50_000);
+        let mut window = LibraryWindow::open(source).await.unwrap();
+
+        for page in 1..=CACHED_PAGES * 2 {
+            window.show(page * PAGE_SIZE + PAGE_SIZE / 2);
+            wait_for_pages(&mut window).await;
+        }
+        assert_eq!(window.pages.len(), CACHED_PAGES);
+        assert!(window.get(0).is_none());
+        assert!(window.get(CACHED_PAGES * 2 * PAGE_SIZE).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_drops_pages_in_flight() {
+        let source = MockPages::new(1_000);
+        let mut window = LibraryWindow::open(source.clone()).await.unwrap();
+
+        window.show(900);
+        window.invalidate().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!window.poll());
+        assert!(window.get(0).is_none());
+        assert!(window.get(900).is_none());
+
+        window.show(900);
+        wait_for_pages(&mut window).await;
+        assert_eq!(window.get(900).unwrap().title, "Book 900");
+    }
+}
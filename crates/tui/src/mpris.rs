@@ -0,0 +1,555 @@
+// crates/tui/src/mpris.rs
+//! MPRIS media controls for desktop Linux
+//!
+//! Registers `org.mpris.MediaPlayer2.storystream` on the D-Bus session bus
+//! so media keys, desktop widgets and `playerctl` can drive the media
+//! engine. Chapters map to Next/Previous.
+//!
+//! The engine has no event channel, so the TUI calls [`MprisServer::refresh`]
+//! every tick and changes are published from there. Without the `mpris`
+//! feature, or without a session bus, [`MprisServer::start`] returns `None`
+//! and the TUI carries on without media controls.
+
+#[cfg(feature = "mpris")]
+pub(crate) use imp::MprisServer;
+
+#[cfg(not(feature = "mpris"))]
+pub(crate) use noop::MprisServer;
+
+#[cfg(feature = "mpris")]
+mod imp {
+    use media_engine::{MediaEngine, Speed};
+    use std::collections::HashMap;
+    use std::path::Path;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use storystream_core::types::book::Book;
+    use zbus::fdo;
+    use zbus::object_server::SignalContext;
+    use zbus::zvariant::{ObjectPath, OwnedValue, Value};
+    use zbus::Connection;
+
+    const BUS_NAME: &str = "org.mpris.MediaPlayer2.storystream";
+    const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+    const NO_TRACK: &str = "/org/mpris/MediaPlayer2/TrackList/NoTrack";
+
+    /// What MPRIS clients see of the loaded book
+    #[derive(Debug, Clone, PartialEq)]
+    pub(super) struct Track {
+        pub(super) id: String,
+        pub(super) title: String,
+        pub(super) author: Option<String>,
+        pub(super) art_url: Option<String>,
+        pub(super) length: Duration,
+    }
+
+    impl Track {
+        pub(super) fn from_book(book: &Book) -> Self {
+            Self {
+                id: book.id.as_string(),
+                title: book.title.clone(),
+                author: book.author.clone(),
+                art_url: book.cover_art_path.as_deref().map(file_url),
+                length: Duration::from_millis(book.duration.as_millis()),
+            }
+        }
+
+        /// Track ids are object paths, which only allow `[A-Za-z0-9_]`
+        pub(super) fn object_path(&self) -> String {
+            format!("/org/storystream/track/{}", self.id.replace('-', "_"))
+        }
+    }
+
+    /// Turns an absolute path into a `file://` URL
+    pub(super) fn file_url(path: &Path) -> String {
+        let mut url = String::from("file://");
+        for byte in path.to_string_lossy().bytes() {
+            match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                    url.push(byte as char)
+                }
+                _ => url.push_str(&format!("%{:02X}", byte)),
+            }
+        }
+        url
+    }
+
+    pub(super) fn metadata(track: Option<&Track>) -> HashMap<String, OwnedValue> {
+        let mut map = HashMap::new();
+        let mut insert = |key: &str, value: Value<'_>| {
+            if let Ok(value) = OwnedValue::try_from(value) {
+                map.insert(key.to_string(), value);
+            }
+        };
+
+        let Some(track) = track else {
+            if let Ok(path) = ObjectPath::try_from(NO_TRACK) {
+                insert("mpris:trackid", Value::from(path));
+            }
+            return map;
+        };
+
+        if let Ok(path) = ObjectPath::try_from(track.object_path()) {
+            insert("mpris:trackid", Value::from(path));
+        }
+        insert("mpris:length", Value::from(micros(track.length)));
+        insert("xesam:title", Value::from(track.title.as_str()));
+        if let Some(author) = &track.author {
+            insert("xesam:artist", Value::from(vec![author.as_str()]));
+        }
+        if let Some(art_url) = &track.art_url {
+            insert("mpris:artUrl", Value::from(art_url.as_str()));
+        }
+        map
+    }
+
+    fn micros(duration: Duration) -> i64 {
+        i64::try_from(duration.as_micros()).unwrap_or(i64::MAX)
+    }
+
+    fn from_micros(micros: i64) -> Duration {
+        Duration::from_micros(u64::try_from(micros).unwrap_or(0))
+    }
+
+    fn engine_error(e: impl std::fmt::Display) -> fdo::Error {
+        fdo::Error::Failed(e.to_string())
+    }
+
+    /// `org.mpris.MediaPlayer2`
+    struct Root {
+        quit: Arc<AtomicBool>,
+    }
+
+    #[zbus::interface(name = "org.mpris.MediaPlayer2")]
+    impl Root {
+        fn raise(&self) {}
+
+        fn quit(&self) {
+            self.quit.store(true, Ordering::SeqCst);
+        }
+
+        #[zbus(property)]
+        fn can_quit(&self) -> bool {
+            true
+        }
+
+        #[zbus(property)]
+        fn can_raise(&self) -> bool {
+            false
+        }
+
+        #[zbus(property)]
+        fn has_track_list(&self) -> bool {
+            false
+        }
+
+        #[zbus(property)]
+        fn identity(&self) -> String {
+            "StoryStream".to_string()
+        }
+
+        #[zbus(property)]
+        fn supported_uri_schemes(&self) -> Vec<String> {
+            Vec::new()
+        }
+
+        #[zbus(property)]
+        fn supported_mime_types(&self) -> Vec<String> {
+            Vec::new()
+        }
+    }
+
+    /// `org.mpris.MediaPlayer2.Player`
+    struct Player {
+        engine: Arc<Mutex<MediaEngine>>,
+        track: Option<Track>,
+    }
+
+    impl Player {
+        fn with_engine<T>(
+            &self,
+            f: impl FnOnce(&mut MediaEngine) -> Result<T, String>,
+        ) -> fdo::Result<T> {
+            let mut engine = self.engine.lock().map_err(engine_error)?;
+            f(&mut engine).map_err(engine_error)
+        }
+
+        fn status(&self) -> &'static str {
+            match self.engine.lock() {
+                Ok(engine) if engine.is_playing() => "Playing",
+                Ok(_) if self.track.is_some() => "Paused",
+                _ => "Stopped",
+            }
+        }
+    }
+
+    #[zbus::interface(name = "org.mpris.MediaPlayer2.Player")]
+    impl Player {
+        fn play(&self) -> fdo::Result<()> {
+            self.with_engine(|engine| engine.play())
+        }
+
+        fn pause(&self) -> fdo::Result<()> {
+            self.with_engine(|engine| engine.pause())
+        }
+
+        fn play_pause(&self) -> fdo::Result<()> {
+            self.with_engine(|engine| {
+                if engine.is_playing() {
+                    engine.pause()
+                } else {
+                    engine.play()
+                }
+            })
+        }
+
+        fn stop(&self) -> fdo::Result<()> {
+            self.with_engine(|engine| engine.stop())
+        }
+
+        fn next(&self) -> fdo::Result<()> {
+            self.with_engine(|engine| engine.next_chapter())
+        }
+
+        fn previous(&self) -> fdo::Result<()> {
+            self.with_engine(|engine| engine.previous_chapter())
+        }
+
+        /// Relative seek by `offset` microseconds
+        async fn seek(
+            &self,
+            offset: i64,
+            #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        ) -> fdo::Result<()> {
+            let position = self.with_engine(|engine| {
+                let current = micros(engine.position());
+                let length = engine.duration.map(micros).unwrap_or(i64::MAX);
+                let target = from_micros(current.saturating_add(offset).clamp(0, length));
+                engine.seek(target).map(|()| target)
+            })?;
+            Self::seeked(&ctxt, micros(position)).await?;
+            Ok(())
+        }
+
+        async fn set_position(
+            &self,
+            track_id: ObjectPath<'_>,
+            position: i64,
+            #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        ) -> fdo::Result<()> {
+            // Requests for a track that is no longer loaded are ignored
+            let current = self.track.as_ref().map(Track::object_path);
+            if current.as_deref() != Some(track_id.as_str()) || position < 0 {
+                return Ok(());
+            }
+            self.with_engine(|engine| engine.seek(from_micros(position)))?;
+            Self::seeked(&ctxt, position).await?;
+            Ok(())
+        }
+
+        fn open_uri(&self, _uri: String) -> fdo::Result<()> {
+            Err(fdo::Error::NotSupported(
+                "Open books from the library instead".to_string(),
+            ))
+        }
+
+        #[zbus(signal)]
+        async fn seeked(ctxt: &SignalContext<'_>, position: i64) -> zbus::Result<()>;
+
+        #[zbus(property)]
+        fn playback_status(&self) -> String {
+            self.status().to_string()
+        }
+
+        #[zbus(property)]
+        fn metadata(&self) -> HashMap<String, OwnedValue> {
+            metadata(self.track.as_ref())
+        }
+
+        #[zbus(property(emits_changed_signal = "false"))]
+        fn position(&self) -> i64 {
+            self.engine
+                .lock()
+                .map(|engine| micros(engine.position()))
+                .unwrap_or(0)
+        }
+
+        #[zbus(property)]
+        fn volume(&self) -> f64 {
+            self.engine
+                .lock()
+                .map(|engine| f64::from(engine.volume()))
+                .unwrap_or(0.0)
+        }
+
+        #[zbus(property)]
+        fn set_volume(&mut self, volume: f64) {
+            if let Err(e) =
+                self.with_engine(|engine| engine.set_volume(volume.clamp(0.0, 1.0) as f32))
+            {
+                log::debug!("MPRIS volume change failed: {}", e);
+            }
+        }
+
+        #[zbus(property)]
+        fn rate(&self) -> f64 {
+            self.engine
+                .lock()
+                .ok()
+                .and_then(|engine| engine.speed.lock().ok().map(|speed| speed.value()))
+                .map(f64::from)
+                .unwrap_or(1.0)
+        }
+
+        #[zbus(property)]
+        fn set_rate(&mut self, rate: f64) {
+            let result = Speed::new(rate as f32)
+                .map_err(engine_error)
+                .and_then(|speed| self.with_engine(|engine| engine.set_speed(speed)));
+            if let Err(e) = result {
+                log::debug!("MPRIS rate change failed: {}", e);
+            }
+        }
+
+        #[zbus(property)]
+        fn minimum_rate(&self) -> f64 {
+            0.5
+        }
+
+        #[zbus(property)]
+        fn maximum_rate(&self) -> f64 {
+            2.0
+        }
+
+        #[zbus(property)]
+        fn can_go_next(&self) -> bool {
+            self.engine
+                .lock()
+                .map(|engine| engine.chapters().has_chapters())
+                .unwrap_or(false)
+        }
+
+        #[zbus(property)]
+        fn can_go_previous(&self) -> bool {
+            self.can_go_next()
+        }
+
+        #[zbus(property)]
+        fn can_play(&self) -> bool {
+            self.track.is_some()
+        }
+
+        #[zbus(property)]
+        fn can_pause(&self) -> bool {
+            self.track.is_some()
+        }
+
+        #[zbus(property)]
+        fn can_seek(&self) -> bool {
+            self.track.is_some()
+        }
+
+        #[zbus(property(emits_changed_signal = "const"))]
+        fn can_control(&self) -> bool {
+            true
+        }
+    }
+
+    /// Live MPRIS registration, released when dropped
+    pub(crate) struct MprisServer {
+        connection: Connection,
+        quit: Arc<AtomicBool>,
+        status: &'static str,
+        volume: f64,
+    }
+
+    impl MprisServer {
+        /// Register on the session bus, or `None` when there is none
+        pub(crate) async fn start(engine: Arc<Mutex<MediaEngine>>) -> Option<Self> {
+            let quit = Arc::new(AtomicBool::new(false));
+            let player = Player {
+                engine,
+                track: None,
+            };
+            match Self::connect(Arc::clone(&quit), player).await {
+                Ok(connection) => Some(Self {
+                    connection,
+                    quit,
+                    status: "Stopped",
+                    volume: 1.0,
+                }),
+                Err(e) => {
+                    log::debug!("MPRIS unavailable: {}", e);
+                    None
+                }
+            }
+        }
+
+        async fn connect(quit: Arc<AtomicBool>, player: Player) -> zbus::Result<Connection> {
+            let connection = zbus::connection::Builder::session()?
+                .serve_at(OBJECT_PATH, Root { quit })?
+                .serve_at(OBJECT_PATH, player)?
+                .build()
+                .await?;
+
+            // A second instance gets its own name so both stay controllable
+            if connection.request_name(BUS_NAME).await.is_err() {
+                let instance = format!("{}.instance{}", BUS_NAME, std::process::id());
+                connection.request_name(instance.as_str()).await?;
+            }
+            Ok(connection)
+        }
+
+        /// Whether a client asked the player to quit
+        pub(crate) fn quit_requested(&self) -> bool {
+            self.quit.load(Ordering::SeqCst)
+        }
+
+        /// Publish the newly loaded book
+        pub(crate) async fn set_track(&mut self, book: Option<&Book>) {
+            let result: zbus::Result<()> = async {
+                let iface = self.player().await?;
+                let mut player = iface.get_mut().await;
+                player.track = book.map(Track::from_book);
+                let ctxt = iface.signal_context();
+                player.metadata_changed(ctxt).await?;
+                player.playback_status_changed(ctxt).await?;
+                player.can_go_next_changed(ctxt).await?;
+                player.can_go_previous_changed(ctxt).await?;
+                player.can_play_changed(ctxt).await?;
+                player.can_pause_changed(ctxt).await?;
+                player.can_seek_changed(ctxt).await
+            }
+            .await;
+            if let Err(e) = result {
+                log::debug!("MPRIS track update failed: {}", e);
+            }
+        }
+
+        /// Publish status and volume changes made since the last call
+        pub(crate) async fn refresh(&mut self) {
+            let result: zbus::Result<()> = async {
+                let iface = self.player().await?;
+                let player = iface.get().await;
+                let status = player.status();
+                if status != self.status {
+                    self.status = status;
+                    player
+                        .playback_status_changed(iface.signal_context())
+                        .await?;
+                }
+                let volume = player.volume();
+                if (volume - self.volume).abs() > f64::EPSILON {
+                    self.volume = volume;
+                    player.volume_changed(iface.signal_context()).await?;
+                }
+                Ok(())
+            }
+            .await;
+            if let Err(e) = result {
+                log::debug!("MPRIS refresh failed: {}", e);
+            }
+        }
+
+        /// Tell clients the position jumped
+        pub(crate) async fn seeked(&self, position: Duration) {
+            let result: zbus::Result<()> = async {
+                let iface = self.player().await?;
+                Player::seeked(iface.signal_context(), micros(position)).await
+            }
+            .await;
+            if let Err(e) = result {
+                log::debug!("MPRIS seek signal failed: {}", e);
+            }
+        }
+
+        async fn player(&self) -> zbus::Result<zbus::object_server::InterfaceRef<Player>> {
+            self.connection
+                .object_server()
+                .interface::<_, Player>(OBJECT_PATH)
+                .await
+        }
+    }
+}
+
+#[cfg(not(feature = "mpris"))]
+mod noop {
+    use media_engine::MediaEngine;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use storystream_core::types::book::Book;
+
+    /// Stand-in used when the `mpris` feature is off
+    pub(crate) struct MprisServer;
+
+    impl MprisServer {
+        pub(crate) async fn start(_engine: Arc<Mutex<MediaEngine>>) -> Option<Self> {
+            None
+        }
+
+        pub(crate) fn quit_requested(&self) -> bool {
+            false
+        }
+
+        pub(crate) async fn set_track(&mut self, _book: Option<&Book>) {}
+
+        pub(crate) async fn refresh(&mut self) {}
+
+        pub(crate) async fn seeked(&self, _position: Duration) {}
+    }
+}
+
+#[cfg(all(test, feature = "mpris"))]
+mod tests {
+    use super::imp::{file_url, metadata, Track};
+    use std::path::Path;
+    use std::time::Duration;
+    use storystream_core::types::book::Book;
+
+    #[test]
+    fn test_track_from_book() {
+        let mut book = Book::new(
+            "Dune".to_string(),
+            "/books/dune.m4b".into(),
+            1024,
+            storystream_core::Duration::from_seconds(3600),
+        );
+        book.author = Some("Frank Herbert".to_string());
+        book.cover_art_path = Some("/covers/dune cover.jpg".into());
+
+        let track = Track::from_book(&book);
+        assert_eq!(track.length, Duration::from_secs(3600));
+        assert_eq!(
+            track.art_url.as_deref(),
+            Some("file:///covers/dune%20cover.jpg")
+        );
+        assert!(!track.object_path().contains('-'));
+
+        let map = metadata(Some(&track));
+        for key in [
+            "mpris:trackid",
+            "mpris:length",
+            "xesam:title",
+            "xesam:artist",
+            "mpris:artUrl",
+        ] {
+            assert!(map.contains_key(key), "missing {}", key);
+        }
+    }
+
+    #[test]
+    fn test_metadata_without_track() {
+        let map = metadata(None);
+        assert_eq!(map.len(), 1);
+        assert!(map.contains_key("mpris:trackid"));
+    }
+
+    #[test]
+    fn test_file_url_escapes() {
+        assert_eq!(
+            file_url(Path::new("/a b/ü.mp3")),
+            "file:///a%20b/%C3%BC.mp3"
+        );
+    }
+}
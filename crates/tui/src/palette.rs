@@ -0,0 +1,211 @@
+// crates/tui/src/palette.rs
+//! Command palette: every action, filtered by a fuzzy query
+
+use crate::actions::Action;
+use crate::state::AppState;
+use std::cmp::Reverse;
+
+/// Action listed in the palette for the current query
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaletteEntry {
+    pub action: Action,
+    /// Why the action cannot run here; such entries are greyed out and last
+    pub unavailable: Option<&'static str>,
+}
+
+/// Command palette shown over any view; it takes all key input
+#[derive(Debug, Clone, Default)]
+pub struct CommandPalette {
+    /// Text typed so far
+    pub query: String,
+    /// Index into [`CommandPalette::entries`] of the selected entry
+    pub selected: usize,
+}
+
+impl CommandPalette {
+    /// Lists the actions matching the query, best match first
+    ///
+    /// Actions that can run in `state` come before those that cannot; ties
+    /// keep the order of [`Action::ALL`].
+    pub fn entries(&self, state: &AppState) -> Vec<PaletteEntry> {
+        let mut scored: Vec<_> = Action::ALL
+            .into_iter()
+            .enumerate()
+            .filter_map(|(order, action)| {
+                let score = fuzzy_score(&self.query, action.label())?;
+                let entry = PaletteEntry {
+                    action,
+                    unavailable: action.unavailable_reason(state),
+                };
+                Some((entry, score, order))
+            })
+            .collect();
+        scored.sort_by_key(|(entry, score, order)| {
+            (entry.unavailable.is_some(), Reverse(*score), *order)
+        });
+        scored.into_iter().map(|(entry, _, _)| entry).collect()
+    }
+
+    /// Adds a typed character and selects the new best match
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+        self.selected = 0;
+    }
+
+    /// Removes the last typed character and selects the new best match
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.selected = 0;
+    }
+
+    /// Selects the next of `count` entries
+    pub fn select_next(&mut self, count: usize) {
+        if self.selected + 1 < count {
+            self.selected += 1;
+        }
+    }
+
+    /// Selects the previous entry
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Returns the selected action
+    ///
+    /// # Errors
+    ///
+    /// Returns why nothing can run: no action matches, or the selected one
+    /// is unavailable in `state`
+    pub fn confirm(&self, state: &AppState) -> Result<Action, &'static str> {
+        let entries = self.entries(state);
+        let entry = entries.get(self.selected).ok_or("No matching command")?;
+        match entry.unavailable {
+            Some(reason) => Err(reason),
+            None => Ok(entry.action),
+        }
+    }
+}
+
+/// Scores how well `query` matches `text`, `None` if it does not match
+///
+/// Every character of the query, ignoring case and spaces, must appear in
+/// `text` in order. Each one found scores a point, more when it follows the
+/// previous one directly or starts a word. When the query fits `text` in
+/// several ways the best scoring one counts.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<u32> {
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    let mut wanted = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .peekable();
+    if wanted.peek().is_none() {
+        return Some(0);
+    }
+
+    let char_score = |j: usize| {
+        if j == 0 || !text[j - 1].is_alphanumeric() {
+            4
+        } else {
+            1
+        }
+    };
+    // Best score so far with the last matched character at each position
+    let mut best: Vec<Option<u32>> = vec![None; text.len()];
+    for (i, c) in wanted.enumerate() {
+        let mut next = vec![None; text.len()];
+        for j in (0..text.len()).filter(|&j| text[j] == c) {
+            let before = if i == 0 {
+                Some(0)
+            } else {
+                (0..j)
+                    .filter_map(|k| best[k].map(|score| score + if k + 1 == j { 4 } else { 0 }))
+                    .max()
+            };
+            next[j] = before.map(|score| score + char_score(j));
+        }
+        best = next;
+    }
+    best.into_iter().flatten().max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::View;
+
+    fn labels(palette: &CommandPalette, state: &AppState) -> Vec<&'static str> {
+        palette
+            .entries(state)
+            .iter()
+            .map(|entry| entry.action.label())
+            .collect()
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "Anything"), Some(0));
+        assert!(fuzzy_score("fav", "Toggle favorite").is_some());
+        assert!(fuzzy_score("FAV", "Toggle favorite").is_some());
+        assert_eq!(fuzzy_score("vaf", "Toggle favorite"), None);
+        assert_eq!(fuzzy_score("z", "Toggle favorite"), None);
+
+        // Word starts and runs beat scattered letters
+        let initials = fuzzy_score("cc", "Clear cache").unwrap();
+        let scattered = fuzzy_score("cc", "Rescan chapters").unwrap();
+        assert!(initials > scattered);
+        let run = fuzzy_score("cache", "Clear cache").unwrap();
+        let spread = fuzzy_score("cache", "Clear all chapter edits").unwrap_or(0);
+        assert!(run > spread);
+    }
+
+    #[test]
+    fn test_entries_filter_and_rank() {
+        let mut state = AppState::new();
+        state.set_view(View::Settings);
+        let mut palette = CommandPalette::default();
+
+        assert_eq!(palette.entries(&state).len(), Action::ALL.len());
+
+        for c in "clear cache".chars() {
+            palette.push(c);
+        }
+        assert_eq!(labels(&palette, &state)[0], "Clear cache");
+
+        palette.query = "bookmark".to_string();
+        let entries = palette.entries(&state);
+        let about_bookmarks =
+            |entry: &PaletteEntry| entry.action.label().to_lowercase().contains("bookmark");
+        assert!(entries.iter().all(about_bookmarks));
+        // None of the bookmark actions run in the settings view except opening it
+        assert_eq!(entries[0].action, Action::OpenView(View::Bookmarks));
+        assert!(entries[0].unavailable.is_none());
+        assert!(entries[1..].iter().all(|entry| entry.unavailable.is_some()));
+    }
+
+    #[test]
+    fn test_confirm_dispatches_selected_action() {
+        let mut state = AppState::new();
+        state.playback.current_file = Some("Book".to_string());
+        let mut palette = CommandPalette {
+            query: "speed".to_string(),
+            selected: 0,
+        };
+
+        assert_eq!(palette.confirm(&state), Ok(Action::SpeedDown));
+        palette.select_next(palette.entries(&state).len());
+        assert_eq!(palette.confirm(&state), Ok(Action::SpeedUp));
+
+        // Typing selects the best match again
+        palette.push(' ');
+        palette.push('u');
+        assert_eq!(palette.selected, 0);
+        assert_eq!(palette.confirm(&state), Ok(Action::SpeedUp));
+
+        palette.query = "export bookmarks".to_string();
+        assert_eq!(palette.confirm(&state), Err("Only in the bookmarks view"));
+
+        palette.query = "zzz".to_string();
+        assert_eq!(palette.confirm(&state), Err("No matching command"));
+    }
+}
@@ -0,0 +1,360 @@
+// crates/tui/src/remote.rs
+//! Remote control over HTTP
+//!
+//! With `app.remote_control` on, the TUI serves a small JSON API and a page
+//! that drives it, so a phone's browser can pause or seek the player:
+//!
+//! - `GET /status`: the loaded book, position, speed and volume
+//! - `POST /play` and `POST /pause`
+//! - `POST /seek?to=SECONDS` or `POST /seek?by=SECONDS`, which may be negative
+//! - `POST /speed?value=1.25` and `POST /volume?value=0.8`
+//! - `GET /library/search?q=TEXT`
+//!
+//! The server only parses requests. Each one reaches the TUI as a
+//! [`RemoteRequest`], which its event loop answers with the same code the
+//! keys run. The server listens on `app.remote_bind`, localhost unless
+//! changed, and with `app.remote_token` set every API request needs
+//! `Authorization: Bearer <token>` or `?token=<token>`.
+//!
+//! Without the `remote` feature [`RemoteServer::start`] always fails and
+//! the TUI carries on without remote control.
+
+use serde::Serialize;
+use std::sync::mpsc;
+use std::time::Duration;
+
+#[cfg(feature = "remote")]
+pub use imp::RemoteServer;
+
+#[cfg(not(feature = "remote"))]
+pub use noop::RemoteServer;
+
+/// What a remote control asked for
+#[derive(Debug, Clone, PartialEq)]
+pub enum RemoteCommand {
+    Status,
+    Play,
+    Pause,
+    SeekTo(Duration),
+    /// Milliseconds from the current position, backward when negative
+    SeekBy(i64),
+    Speed(f32),
+    Volume(f32),
+    Search(String),
+}
+
+/// Player state as remote controls see it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RemoteStatus {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub chapter: Option<String>,
+    pub playing: bool,
+    pub position_ms: u64,
+    pub duration_ms: u64,
+    pub speed: f32,
+    pub volume: f32,
+}
+
+/// A library search result
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RemoteBook {
+    pub id: String,
+    pub title: String,
+    pub author: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// Answer to a [`RemoteCommand`]; commands that change playback answer with
+/// the status after the change
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum RemoteReply {
+    Status(RemoteStatus),
+    Books(Vec<RemoteBook>),
+}
+
+/// A command waiting for the TUI to carry it out
+#[derive(Debug)]
+pub struct RemoteRequest {
+    pub command: RemoteCommand,
+    reply: mpsc::Sender<Result<RemoteReply, String>>,
+}
+
+impl RemoteRequest {
+    /// Sends the answer back to the remote control, or why the command failed
+    pub fn answer(self, reply: Result<RemoteReply, String>) {
+        // The remote control may have given up waiting
+        let _ = self.reply.send(reply);
+    }
+}
+
+#[cfg(feature = "remote")]
+mod imp {
+    use super::{RemoteCommand, RemoteReply, RemoteRequest};
+    use std::io::Cursor;
+    use std::net::SocketAddr;
+    use std::sync::{mpsc, Arc};
+    use std::thread::JoinHandle;
+    use std::time::Duration;
+    use tiny_http::{Header, Method, Request, Response, Server};
+
+    /// How long a request waits for the TUI to answer it
+    const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// The control page, which calls the API with the page's own `?token=`
+    const PAGE: &str = include_str!("remote.html");
+
+    /// Serves the remote control API on a thread of its own until dropped
+    pub struct RemoteServer {
+        server: Arc<Server>,
+        address: SocketAddr,
+        requests: mpsc::Receiver<RemoteRequest>,
+        thread: Option<JoinHandle<()>>,
+    }
+
+    impl RemoteServer {
+        /// Starts listening on `bind`, such as `127.0.0.1:8910`
+        ///
+        /// With a `token`, API requests without it are refused.
+        pub fn start(bind: &str, token: Option<String>) -> Result<Self, String> {
+            let server =
+                Server::http(bind).map_err(|e| format!("Cannot listen on {}: {}", bind, e))?;
+            let address = server
+                .server_addr()
+                .to_ip()
+                .ok_or_else(|| format!("Cannot listen on {}", bind))?;
+            if token.is_none() && !address.ip().is_loopback() {
+                log::warn!(
+                    "Remote control on {} accepts requests from anyone on the network",
+                    address
+                );
+            }
+
+            let server = Arc::new(server);
+            let (sender, requests) = mpsc::channel();
+            let thread = {
+                let server = Arc::clone(&server);
+                std::thread::spawn(move || {
+                    for request in server.incoming_requests() {
+                        serve(request, token.as_deref(), &sender);
+                    }
+                })
+            };
+            log::info!("Remote control listening on http://{}", address);
+
+            Ok(Self {
+                server,
+                address,
+                requests,
+                thread: Some(thread),
+            })
+        }
+
+        /// Address the server listens on, with the port picked for port 0
+        pub fn address(&self) -> SocketAddr {
+            self.address
+        }
+
+        /// Takes the next command waiting to be carried out
+        pub fn next_request(&self) -> Option<RemoteRequest> {
+            self.requests.try_recv().ok()
+        }
+    }
+
+    impl Drop for RemoteServer {
+        fn drop(&mut self) {
+            // Unanswered requests fail now rather than after the timeout
+            while self.requests.try_recv().is_ok() {}
+            self.server.unblock();
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+
+    fn serve(request: Request, token: Option<&str>, requests: &mpsc::Sender<RemoteRequest>) {
+        let url = request.url().to_string();
+        let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+
+        let response = if *request.method() == Method::Get && path == "/" {
+            respond(200, "text/html; charset=utf-8", PAGE.as_bytes().to_vec())
+        } else if !authorized(&request, query, token) {
+            error(401, "Missing or wrong token")
+        } else {
+            match route(request.method(), path, query) {
+                Ok(command) => dispatch(command, requests),
+                Err((status, message)) => error(status, &message),
+            }
+        };
+        if let Err(e) = request.respond(response) {
+            log::debug!("Could not answer a remote control request: {}", e);
+        }
+    }
+
+    /// Hands `command` to the TUI and waits for its answer
+    fn dispatch(
+        command: RemoteCommand,
+        requests: &mpsc::Sender<RemoteRequest>,
+    ) -> Response<Cursor<Vec<u8>>> {
+        let (reply, answer) = mpsc::channel();
+        if requests.send(RemoteRequest { command, reply }).is_err() {
+            return error(503, "The player has stopped");
+        }
+        match answer.recv_timeout(REPLY_TIMEOUT) {
+            Ok(Ok(reply)) => json(200, &reply),
+            Ok(Err(message)) => error(409, &message),
+            Err(_) => error(503, "The player did not answer"),
+        }
+    }
+
+    fn route(method: &Method, path: &str, query: &str) -> Result<RemoteCommand, (u16, String)> {
+        let number = |name: &str| {
+            let value = param(query, name)?;
+            Some(
+                value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|number| number.is_finite())
+                    .ok_or_else(|| (400, format!("{} must be a number", name))),
+            )
+        };
+        let required =
+            |name: &str| number(name).unwrap_or_else(|| Err((400, format!("{} is missing", name))));
+
+        match (method, path) {
+            (Method::Get, "/status") => Ok(RemoteCommand::Status),
+            (Method::Post, "/play") => Ok(RemoteCommand::Play),
+            (Method::Post, "/pause") => Ok(RemoteCommand::Pause),
+            (Method::Post, "/seek") => match (number("to"), number("by")) {
+                // Finite but too large for a Duration is still a bad request
+                (Some(to), _) => Duration::try_from_secs_f64(to?.max(0.0))
+                    .map(RemoteCommand::SeekTo)
+                    .map_err(|_| (400, "to is out of range".to_string())),
+                (None, Some(by)) => Ok(RemoteCommand::SeekBy((by? * 1000.0) as i64)),
+                (None, None) => Err((400, "to or by is missing".to_string())),
+            },
+            (Method::Post, "/speed") => Ok(RemoteCommand::Speed(required("value")? as f32)),
+            (Method::Post, "/volume") => Ok(RemoteCommand::Volume(required("value")? as f32)),
+            (Method::Get, "/library/search") => param(query, "q")
+                .map(RemoteCommand::Search)
+                .ok_or_else(|| (400, "q is missing".to_string())),
+            _ => Err((404, format!("No route for {} {}", method, path))),
+        }
+    }
+
+    fn authorized(request: &Request, query: &str, token: Option<&str>) -> bool {
+        let Some(token) = token else {
+            return true;
+        };
+        let bearer = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("Authorization"))
+            .and_then(|h| h.value.as_str().strip_prefix("Bearer ").map(str::to_string));
+        bearer.or_else(|| param(query, "token")).as_deref() == Some(token)
+    }
+
+    /// Value of the query parameter `name`, percent-decoded
+    fn param(query: &str, name: &str) -> Option<String> {
+        query.split('&').find_map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(key) == name).then(|| decode(value))
+        })
+    }
+
+    fn decode(text: &str) -> String {
+        let bytes = text.as_bytes();
+        let mut decoded = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            let escaped = bytes
+                .get(i + 1..i + 3)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+            match (bytes[i], escaped) {
+                (b'%', Some(byte)) => {
+                    decoded.push(byte);
+                    i += 3;
+                    continue;
+                }
+                (b'+', _) => decoded.push(b' '),
+                (byte, _) => decoded.push(byte),
+            }
+            i += 1;
+        }
+        String::from_utf8_lossy(&decoded).into_owned()
+    }
+
+    fn json(status: u16, reply: &RemoteReply) -> Response<Cursor<Vec<u8>>> {
+        let body = serde_json::to_vec(reply).unwrap_or_default();
+        respond(status, "application/json", body)
+    }
+
+    fn error(status: u16, message: &str) -> Response<Cursor<Vec<u8>>> {
+        let body = serde_json::json!({ "error": message }).to_string();
+        respond(status, "application/json", body.into_bytes())
+    }
+
+    fn respond(status: u16, content_type: &str, body: Vec<u8>) -> Response<Cursor<Vec<u8>>> {
+        let mut response = Response::from_data(body).with_status_code(status);
+        if let Ok(header) = Header::from_bytes(&b"Content-Type"[..], content_type.as_bytes()) {
+            response.add_header(header);
+        }
+        response
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_route() {
+            assert_eq!(
+                route(&Method::Post, "/seek", "by=-30"),
+                Ok(RemoteCommand::SeekBy(-30_000))
+            );
+            assert_eq!(
+                route(&Method::Post, "/seek", "to=90.5"),
+                Ok(RemoteCommand::SeekTo(Duration::from_millis(90_500)))
+            );
+            assert_eq!(
+                route(
+                    &Method::Get,
+                    "/library/search",
+                    "q=the%20hobbit+tolkien&token=x"
+                ),
+                Ok(RemoteCommand::Search("the hobbit tolkien".to_string()))
+            );
+            assert_eq!(route(&Method::Post, "/seek", "to=1e30").unwrap_err().0, 400);
+            assert_eq!(
+                route(&Method::Post, "/seek", "to=-5"),
+                Ok(RemoteCommand::SeekTo(Duration::ZERO))
+            );
+            assert_eq!(
+                route(&Method::Post, "/speed", "value=fast").unwrap_err().0,
+                400
+            );
+            assert_eq!(route(&Method::Post, "/volume", "").unwrap_err().0, 400);
+            assert_eq!(route(&Method::Get, "/play", "").unwrap_err().0, 404);
+        }
+    }
+}
+
+#[cfg(not(feature = "remote"))]
+mod noop {
+    use super::RemoteRequest;
+
+    /// Stand-in for builds without the `remote` feature
+    pub struct RemoteServer;
+
+    impl RemoteServer {
+        pub fn start(_bind: &str, _token: Option<String>) -> Result<Self, String> {
+            Err("this build has no remote control (enable the `remote` feature)".to_string())
+        }
+
+        pub fn next_request(&self) -> Option<RemoteRequest> {
+            None
+        }
+    }
+}
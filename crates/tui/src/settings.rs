@@ -0,0 +1,299 @@
+// crates/tui/src/settings.rs
+//! Settings the settings view lists and changes
+//!
+//! Each [`Setting`] is a key of the config file, such as
+//! `player.default_volume`. Values are changed through
+//! [`Config::with_setting`], which reads them as the field's type and runs
+//! the section's validation, so the view accepts exactly what the config
+//! file does. Numeric bounds and choices come from the config schema.
+
+use storystream_config::{schema, Config, ConfigError, ConfigResult};
+
+/// How the value of a [`Setting`] is changed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SettingKind {
+    /// On or off; Space, Enter and ←/→ flip it
+    Toggle,
+    /// ←/→ step it by `step` within the schema's range; Enter types it
+    Number { step: f64 },
+    /// One of the schema's options; ←/→ and Enter cycle through them
+    Choice,
+    /// Typed after Enter; blank text unsets it
+    Text,
+    /// Several folders typed after Enter, separated as in `PATH`
+    List,
+}
+
+/// Setting shown in the settings view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    DefaultVolume,
+    DefaultSpeed,
+    ResumeRewind,
+    NormalizeVolume,
+    ResumeOnStartup,
+    AutoBookmarkPause,
+    MaxAutoBookmarks,
+    BookmarkExportDir,
+    WatchedFolders,
+    AutoImport,
+    IgnoreHidden,
+    ColorScheme,
+    DailyGoal,
+    SyncFolder,
+    DeviceName,
+    LanSync,
+}
+
+impl Setting {
+    /// Settings in display order, grouped by [`Setting::group`]
+    pub const ALL: [Setting; 16] = [
+        Self::DefaultVolume,
+        Self::DefaultSpeed,
+        Self::ResumeRewind,
+        Self::NormalizeVolume,
+        Self::ResumeOnStartup,
+        Self::AutoBookmarkPause,
+        Self::MaxAutoBookmarks,
+        Self::BookmarkExportDir,
+        Self::WatchedFolders,
+        Self::AutoImport,
+        Self::IgnoreHidden,
+        Self::ColorScheme,
+        Self::DailyGoal,
+        Self::SyncFolder,
+        Self::DeviceName,
+        Self::LanSync,
+    ];
+
+    /// Key of the setting in the config file
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::DefaultVolume => "player.default_volume",
+            Self::DefaultSpeed => "player.default_speed",
+            Self::ResumeRewind => "player.resume_rewind_secs",
+            Self::NormalizeVolume => "player.normalize_volume",
+            Self::ResumeOnStartup => "player.resume_on_startup",
+            Self::AutoBookmarkPause => "player.auto_bookmark_pause_secs",
+            Self::MaxAutoBookmarks => "player.max_auto_bookmarks",
+            Self::BookmarkExportDir => "player.bookmark_export_dir",
+            Self::WatchedFolders => "library.paths",
+            Self::AutoImport => "library.auto_import",
+            Self::IgnoreHidden => "library.ignore_hidden",
+            Self::ColorScheme => "app.color_scheme",
+            Self::DailyGoal => "app.daily_goal_minutes",
+            Self::SyncFolder => "app.sync_folder",
+            Self::DeviceName => "app.device_name",
+            Self::LanSync => "app.lan_sync",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::DefaultVolume => "Default volume",
+            Self::DefaultSpeed => "Default speed",
+            Self::ResumeRewind => "Rewind on resume (s)",
+            Self::NormalizeVolume => "Even out loudness",
+            Self::ResumeOnStartup => "Resume last book on start",
+            Self::AutoBookmarkPause => "Auto-bookmark after pause (s)",
+            Self::MaxAutoBookmarks => "Auto-bookmarks kept",
+            Self::BookmarkExportDir => "Bookmark export folder",
+            Self::WatchedFolders => "Library folders",
+            Self::AutoImport => "Import new files",
+            Self::IgnoreHidden => "Skip hidden files",
+            Self::ColorScheme => "Color scheme",
+            Self::DailyGoal => "Daily goal (min)",
+            Self::SyncFolder => "Sync folder",
+            Self::DeviceName => "Device name",
+            Self::LanSync => "Local network sync",
+        }
+    }
+
+    /// Heading of the group the setting is listed under
+    pub fn group(self) -> &'static str {
+        match self {
+            Self::DefaultVolume
+            | Self::DefaultSpeed
+            | Self::ResumeRewind
+            | Self::NormalizeVolume
+            | Self::ResumeOnStartup
+            | Self::AutoBookmarkPause
+            | Self::MaxAutoBookmarks
+            | Self::BookmarkExportDir => "⚙️  Audio Settings",
+            Self::WatchedFolders | Self::AutoImport | Self::IgnoreHidden => "📁 Library Settings",
+            Self::ColorScheme | Self::DailyGoal => "🎨 Appearance",
+            Self::SyncFolder | Self::DeviceName | Self::LanSync => "🔄 Sync Settings",
+        }
+    }
+
+    pub fn kind(self) -> SettingKind {
+        match self {
+            Self::DefaultVolume | Self::DailyGoal => SettingKind::Number { step: 5.0 },
+            Self::DefaultSpeed => SettingKind::Number { step: 0.1 },
+            Self::ResumeRewind | Self::MaxAutoBookmarks => SettingKind::Number { step: 1.0 },
+            Self::AutoBookmarkPause => SettingKind::Number { step: 30.0 },
+            Self::NormalizeVolume
+            | Self::ResumeOnStartup
+            | Self::AutoImport
+            | Self::IgnoreHidden
+            | Self::LanSync => SettingKind::Toggle,
+            Self::ColorScheme => SettingKind::Choice,
+            Self::BookmarkExportDir | Self::SyncFolder | Self::DeviceName => SettingKind::Text,
+            Self::WatchedFolders => SettingKind::List,
+        }
+    }
+
+    /// Whether a change is used straight away rather than from the next start
+    pub fn applies_live(self) -> bool {
+        !matches!(
+            self,
+            Self::AutoImport
+                | Self::IgnoreHidden
+                | Self::SyncFolder
+                | Self::DeviceName
+                | Self::LanSync
+        )
+    }
+
+    /// The value in `config` as text to edit, empty when unset
+    pub fn text(self, config: &Config) -> String {
+        config.setting(self.key()).unwrap_or_default()
+    }
+
+    /// The value in `config` as the settings list shows it
+    pub fn display(self, config: &Config) -> String {
+        let text = self.text(config);
+        match self.kind() {
+            SettingKind::Toggle if text == "true" => "On".to_string(),
+            SettingKind::Toggle => "Off".to_string(),
+            SettingKind::Text if text.is_empty() => "(not set)".to_string(),
+            SettingKind::List if text.is_empty() => "(none)".to_string(),
+            SettingKind::List => std::env::split_paths(&text)
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            SettingKind::Number { .. } if self == Self::DefaultVolume => format!("{}%", text),
+            SettingKind::Number { .. } if self == Self::DefaultSpeed => format!("{}x", text),
+            _ => text,
+        }
+    }
+
+    /// `config` with the value flipped, stepped or cycled one way
+    ///
+    /// Numbers stop at the ends of their range. Returns `None` for settings
+    /// that are typed instead.
+    pub fn step(self, config: &Config, forward: bool) -> Option<ConfigResult<Config>> {
+        let key = self.key();
+        let text = self.text(config);
+        let value = match self.kind() {
+            SettingKind::Toggle => (text != "true").to_string(),
+            SettingKind::Number { step } => {
+                let current: f64 = text.parse().ok()?;
+                let (min, max) = schema::range(key).unwrap_or((f64::MIN, f64::MAX));
+                let stepped = if forward {
+                    current + step
+                } else {
+                    current - step
+                };
+                // Rounded to the step so repeated tenths do not drift
+                let stepped = ((stepped / step).round() * step).clamp(min, max);
+                if step.fract() == 0.0 {
+                    format!("{}", stepped as i64)
+                } else {
+                    format!("{}", (stepped * 100.0).round() / 100.0)
+                }
+            }
+            SettingKind::Choice => {
+                let options = schema::options(key)?;
+                let index = options
+                    .iter()
+                    .position(|option| *option == text)
+                    .unwrap_or(0);
+                let next = if forward {
+                    (index + 1) % options.len()
+                } else {
+                    (index + options.len() - 1) % options.len()
+                };
+                options[next].clone()
+            }
+            SettingKind::Text | SettingKind::List => return None,
+        };
+        Some(config.with_setting(key, &value))
+    }
+
+    /// `config` with the value set from typed `text`
+    ///
+    /// Blank text unsets a text setting, or puts a number back to its default.
+    pub fn set(self, config: &Config, text: &str) -> ConfigResult<Config> {
+        let text = text.trim();
+        match self.kind() {
+            SettingKind::Text | SettingKind::Number { .. } if text.is_empty() => {
+                config.without_setting(self.key())
+            }
+            _ => config.with_setting(self.key(), text),
+        }
+    }
+}
+
+/// Why a setting was refused, without the config error's prefix
+pub fn refusal(error: &ConfigError) -> String {
+    match error {
+        ConfigError::ValidationError(reason) => reason.clone(),
+        error => error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_step_within_their_range() {
+        let config = Config::default();
+        let louder = Setting::DefaultVolume.step(&config, true).unwrap().unwrap();
+        assert_eq!(louder.player.default_volume, 75);
+        assert_eq!(Setting::DefaultVolume.display(&louder), "75%");
+
+        let mut config = Config::default();
+        config.player.default_volume = 100;
+        config.player.default_speed = 1.95;
+        let stepped = Setting::DefaultVolume.step(&config, true).unwrap().unwrap();
+        assert_eq!(stepped.player.default_volume, 100);
+        let faster = Setting::DefaultSpeed.step(&config, true).unwrap().unwrap();
+        assert_eq!(faster.player.default_speed, 2.0);
+        let slower = Setting::DefaultSpeed.step(&faster, false).unwrap().unwrap();
+        assert_eq!(Setting::DefaultSpeed.display(&slower), "1.9x");
+
+        let toggled = Setting::LanSync.step(&config, true).unwrap().unwrap();
+        assert!(toggled.app.lan_sync);
+        assert_eq!(Setting::LanSync.display(&toggled), "On");
+
+        let scheme = Setting::ColorScheme.step(&config, false).unwrap().unwrap();
+        assert_eq!(Setting::ColorScheme.display(&scheme), "dark");
+
+        assert!(Setting::SyncFolder.step(&config, true).is_none());
+    }
+
+    #[test]
+    fn test_typed_settings_are_validated() {
+        let config = Config::default();
+        let named = Setting::DeviceName.set(&config, " Laptop ").unwrap();
+        assert_eq!(named.app.device_name.as_deref(), Some("Laptop"));
+        let unnamed = Setting::DeviceName.set(&named, "").unwrap();
+        assert_eq!(unnamed.app.device_name, None);
+        assert_eq!(Setting::DeviceName.display(&unnamed), "(not set)");
+
+        let error = Setting::DailyGoal.set(&config, "2000").unwrap_err();
+        assert!(refusal(&error).contains("app.daily_goal_minutes"));
+        let error = Setting::DefaultVolume.set(&config, "loud").unwrap_err();
+        assert!(refusal(&error).contains("not a whole number"));
+
+        if cfg!(unix) {
+            let folders = Setting::WatchedFolders
+                .set(&config, "/books:/more")
+                .unwrap();
+            assert_eq!(folders.library.paths, vec!["/books", "/more"]);
+            assert_eq!(Setting::WatchedFolders.display(&folders), "/books, /more");
+        }
+    }
+}
@@ -1,8 +1,18 @@
 // crates/tui/src/state.rs - CORRECTED VERSION
 //! Application state management
 
+use crate::keymap::KeyMap;
+use crate::palette::CommandPalette;
+use crate::settings::Setting;
+use crate::ui::{confirm::ConfirmDialog, help::HelpSection, text_input::TextInput};
 use std::collections::HashMap;
 use std::time::Duration;
+use storystream_config::{Config, KeybindingsConfig};
+use storystream_core::types::chapters;
+use storystream_core::{Book, Bookmark, CacheStats, Chapter, LibraryStats, PlaylistId};
+use storystream_database::queries::BookStats;
+use storystream_database::search::SearchFilter;
+use storystream_network::{DownloadInfo, DownloadRecord};
 
 /// Available views
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -14,6 +24,7 @@ pub enum View {
     Playlists,
     Statistics,
     Settings,
+    Downloads,
     Help,
     Plugin,
 }
@@ -39,8 +50,20 @@ pub struct PlaybackState {
     pub volume: f32,
     /// Playback speed (0.5 to 3.0)
     pub speed: f32,
+    /// Speed a running speed ramp is heading for
+    pub ramp_target: Option<f32>,
     /// Current chapter (index, not tuple)
     pub chapter: Option<usize>,
+    /// Name of the equalizer preset in use
+    pub equalizer: String,
+    /// Name of the output device playback runs on
+    pub output_device: Option<String>,
+    /// Percentage points the output device adds to the base volume
+    pub volume_offset: i16,
+    /// Time left before the sleep timer pauses playback, if one is set
+    pub sleep_remaining: Option<Duration>,
+    /// Whether the sleep timer waits for the end of the chapter
+    pub sleep_at_chapter_end: bool,
 }
 
 impl Default for PlaybackState {
@@ -52,7 +75,13 @@ impl Default for PlaybackState {
             is_playing: false,
             volume: 1.0,
             speed: 1.0,
+            ramp_target: None,
             chapter: None,
+            equalizer: "Flat".to_string(),
+            output_device: None,
+            volume_offset: 0,
+            sleep_remaining: None,
+            sleep_at_chapter_end: false,
         }
     }
 }
@@ -80,6 +109,861 @@ impl PlaybackState {
     pub fn format_duration(&self) -> String {
         format_duration(self.duration)
     }
+
+    /// Formats the speed, with the target of a running ramp
+    pub fn format_speed(&self) -> String {
+        match self.ramp_target {
+            Some(target) => format!("ramping {:.2}x → {:.2}x", self.speed, target),
+            None => format!("{:.1}x", self.speed),
+        }
+    }
+
+    /// Formats the output device with its volume offset, such as "Speaker (+20%)"
+    pub fn format_output(&self) -> Option<String> {
+        let device = self.output_device.as_ref()?;
+        Some(match self.volume_offset {
+            0 => device.clone(),
+            offset => format!("{} ({:+}%)", device, offset),
+        })
+    }
+
+    /// Formats the sleep timer, such as "Sleep in 14:32"
+    pub fn format_sleep_timer(&self) -> Option<String> {
+        let remaining = format_duration(self.sleep_remaining?);
+        Some(if self.sleep_at_chapter_end {
+            format!("Sleep at chapter end ({})", remaining)
+        } else {
+            format!("Sleep in {}", remaining)
+        })
+    }
+}
+
+/// What a confirmed text prompt is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputPurpose {
+    /// New title for the chapter at this index of the chapter editor
+    RenameChapter(usize),
+    /// New value for a field of the book in the detail popup
+    EditBook(BookField),
+    /// Text to search the library for
+    Search,
+    /// New value for a field of the search filter popup
+    SearchFilter(FilterField),
+    /// Start speed, target speed and change per hour of a speed ramp
+    SpeedRamp,
+    /// Password that lifts the listening limits
+    UnlockLimits,
+    /// New audio file for the book in the detail popup
+    ReplaceFile,
+    /// Name of a new playlist
+    NewPlaylist,
+    /// New value of a setting in the settings view
+    EditSetting(Setting),
+}
+
+/// Single line of text being typed into a modal prompt
+#[derive(Debug, Clone)]
+pub struct TextPrompt {
+    /// Label shown above the text
+    pub prompt: String,
+    /// Text typed so far
+    pub text: TextInput,
+    /// What the text is applied to once confirmed
+    pub purpose: InputPurpose,
+}
+
+impl TextPrompt {
+    /// Creates a prompt prefilled with `value`
+    pub fn new(prompt: impl Into<String>, value: impl Into<String>, purpose: InputPurpose) -> Self {
+        Self {
+            prompt: prompt.into(),
+            text: TextInput::new(value),
+            purpose,
+        }
+    }
+}
+
+/// Book metadata fields that can be edited from the detail popup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookField {
+    Title,
+    Author,
+    Narrator,
+    Series,
+}
+
+impl BookField {
+    /// Editable fields in display order
+    pub const ALL: [BookField; 4] = [Self::Title, Self::Author, Self::Narrator, Self::Series];
+
+    /// Returns the field's label
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Title => "Title",
+            Self::Author => "Author",
+            Self::Narrator => "Narrator",
+            Self::Series => "Series",
+        }
+    }
+
+    /// Returns the field's current value, empty when unset
+    pub fn value(self, book: &Book) -> String {
+        match self {
+            Self::Title => book.title.clone(),
+            Self::Author => book.author.clone().unwrap_or_default(),
+            Self::Narrator => book.narrator.clone().unwrap_or_default(),
+            Self::Series => book.series.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// Details of a library book shown over the library view
+#[derive(Debug, Clone)]
+pub struct BookDetail {
+    /// The book as last saved
+    pub book: Book,
+    /// Whether the fields can be selected and edited
+    pub editing: bool,
+    /// Index into [`BookField::ALL`] of the selected field
+    pub selected: usize,
+    /// Whether edits are also written into the audio file's tags
+    pub write_tags: bool,
+    /// What replacing the book's file kept and adjusted
+    pub replaced: Vec<String>,
+}
+
+impl BookDetail {
+    /// Shows `book` without editing
+    pub fn new(book: Book) -> Self {
+        Self {
+            book,
+            editing: false,
+            selected: 0,
+            write_tags: false,
+            replaced: Vec::new(),
+        }
+    }
+
+    /// Returns the selected field
+    pub fn selected_field(&self) -> BookField {
+        BookField::ALL[self.selected]
+    }
+
+    /// Selects the next field
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < BookField::ALL.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Selects the previous field
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+}
+
+/// Search constraints that can be set from the filter popup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterField {
+    Author,
+    Narrator,
+    Tag,
+    Favorite,
+    Finished,
+    MinLength,
+    MaxLength,
+    Format,
+}
+
+impl FilterField {
+    /// Filter fields in display order
+    pub const ALL: [FilterField; 8] = [
+        Self::Author,
+        Self::Narrator,
+        Self::Tag,
+        Self::Favorite,
+        Self::Finished,
+        Self::MinLength,
+        Self::MaxLength,
+        Self::Format,
+    ];
+
+    /// Returns the field's label
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Author => "Author",
+            Self::Narrator => "Narrator",
+            Self::Tag => "Tag",
+            Self::Favorite => "Favorite",
+            Self::Finished => "Finished",
+            Self::MinLength => "Min length",
+            Self::MaxLength => "Max length",
+            Self::Format => "Format",
+        }
+    }
+
+    /// Whether Enter toggles the field rather than prompting for text
+    pub fn is_toggle(self) -> bool {
+        matches!(self, Self::Favorite | Self::Finished)
+    }
+
+    /// Returns the field's current value in `filter`, empty when unset
+    pub fn value(self, filter: &SearchFilter) -> String {
+        match self {
+            Self::Author => filter.author.clone().unwrap_or_default(),
+            Self::Narrator => filter.narrator.clone().unwrap_or_default(),
+            Self::Tag => filter.tag.clone().unwrap_or_default(),
+            Self::Favorite if filter.favorite => "only".to_string(),
+            Self::Favorite => String::new(),
+            Self::Finished => match filter.finished {
+                Some(true) => "finished".to_string(),
+                Some(false) => "unfinished".to_string(),
+                None => String::new(),
+            },
+            Self::MinLength => filter
+                .min_duration
+                .map(|d| d.to_string())
+                .unwrap_or_default(),
+            Self::MaxLength => filter
+                .max_duration
+                .map(|d| d.to_string())
+                .unwrap_or_default(),
+            Self::Format => filter.format.clone().unwrap_or_default(),
+        }
+    }
+
+    /// Sets a text field from typed `text`; blank text clears it
+    ///
+    /// # Errors
+    ///
+    /// Returns a message if a length cannot be parsed
+    pub fn set(self, filter: &mut SearchFilter, text: &str) -> Result<(), String> {
+        let text = text.trim();
+        let value = (!text.is_empty()).then(|| text.to_string());
+        match self {
+            Self::Author => filter.author = value,
+            Self::Narrator => filter.narrator = value,
+            Self::Tag => filter.tag = value,
+            Self::Format => filter.format = value,
+            Self::MinLength => filter.min_duration = value.map(|v| v.parse()).transpose()?,
+            Self::MaxLength => filter.max_duration = value.map(|v| v.parse()).transpose()?,
+            Self::Favorite | Self::Finished => {}
+        }
+        Ok(())
+    }
+
+    /// Steps a toggle field to its next state
+    ///
+    /// Finished cycles through any, unfinished and finished.
+    pub fn toggle(self, filter: &mut SearchFilter) {
+        match self {
+            Self::Favorite => filter.favorite = !filter.favorite,
+            Self::Finished => {
+                filter.finished = match filter.finished {
+                    None => Some(false),
+                    Some(false) => Some(true),
+                    Some(true) => None,
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Clears the field in `filter`
+    pub fn clear(self, filter: &mut SearchFilter) {
+        match self {
+            Self::Favorite => filter.favorite = false,
+            Self::Finished => filter.finished = None,
+            _ => {
+                let _ = self.set(filter, "");
+            }
+        }
+    }
+}
+
+/// Filter popup shown over the search view
+#[derive(Debug, Clone, Default)]
+pub struct FilterPopup {
+    /// Index into [`FilterField::ALL`] of the selected field
+    pub selected: usize,
+}
+
+impl FilterPopup {
+    /// Returns the selected field
+    pub fn selected_field(&self) -> FilterField {
+        FilterField::ALL[self.selected]
+    }
+
+    /// Selects the next field
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < FilterField::ALL.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Selects the previous field
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+}
+
+/// A chapter start found in a long silence, up for review in the chapter editor
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuggestionRow {
+    pub start: Duration,
+    /// Length of the silence
+    pub silence: Duration,
+    /// How likely this is a real chapter break, from 0.0 to 1.0
+    pub confidence: f32,
+    /// Whether applying the suggestions starts a chapter here
+    pub accepted: bool,
+}
+
+/// Chapter list being edited in the player view
+#[derive(Debug, Clone)]
+pub struct ChapterEditor {
+    /// Edited chapters, kept normalized
+    pub chapters: Vec<Chapter>,
+    /// Index of the selected chapter
+    pub selected: usize,
+    /// Whether there are changes that have not been saved
+    pub dirty: bool,
+    /// Whether chapter breaks are being looked for in the audio
+    pub suggesting: bool,
+    /// Suggested chapter starts under review, empty when not reviewing
+    pub suggestions: Vec<SuggestionRow>,
+    /// Index of the selected suggestion
+    pub selected_suggestion: usize,
+}
+
+impl ChapterEditor {
+    /// Starts editing a copy of `chapters`
+    pub fn new(chapters: Vec<Chapter>) -> Self {
+        Self {
+            chapters,
+            selected: 0,
+            dirty: false,
+            suggesting: false,
+            suggestions: Vec::new(),
+            selected_suggestion: 0,
+        }
+    }
+
+    /// Returns the selected chapter
+    pub fn selected_chapter(&self) -> Option<&Chapter> {
+        self.chapters.get(self.selected)
+    }
+
+    /// Selects the next chapter
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.chapters.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Selects the previous chapter
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Renames the chapter at `index`, ignoring blank titles
+    pub fn rename(&mut self, index: usize, title: &str) -> bool {
+        let title = title.trim();
+        match self.chapters.get_mut(index) {
+            Some(chapter) if !title.is_empty() && chapter.title != title => {
+                chapter.title = title.to_string();
+                self.dirty = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Moves the selected chapter's start by `seconds`
+    pub fn nudge(&mut self, seconds: i64) -> bool {
+        let changed = chapters::nudge_chapter(&mut self.chapters, self.selected, seconds * 1000);
+        self.dirty |= changed;
+        changed
+    }
+
+    /// Splits the chapter playing at `position` and selects the new half
+    pub fn split_at(&mut self, position: Duration) -> bool {
+        let at = storystream_core::Duration::from_millis(position.as_millis() as u64);
+        match chapters::split_chapter(&mut self.chapters, at) {
+            Some(index) => {
+                self.selected = index;
+                self.dirty = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Merges the selected chapter with the next one
+    pub fn merge_with_next(&mut self) -> bool {
+        let changed = chapters::merge_with_next(&mut self.chapters, self.selected);
+        self.dirty |= changed;
+        changed
+    }
+
+    /// Whether suggested chapter starts are up for review
+    pub fn is_reviewing(&self) -> bool {
+        !self.suggestions.is_empty()
+    }
+
+    /// Shows suggested chapter starts for review
+    pub fn review(&mut self, suggestions: Vec<SuggestionRow>) {
+        self.suggesting = false;
+        self.suggestions = suggestions;
+        self.selected_suggestion = 0;
+    }
+
+    /// Selects the next suggestion
+    pub fn select_next_suggestion(&mut self) {
+        if self.selected_suggestion + 1 < self.suggestions.len() {
+            self.selected_suggestion += 1;
+        }
+    }
+
+    /// Selects the previous suggestion
+    pub fn select_previous_suggestion(&mut self) {
+        self.selected_suggestion = self.selected_suggestion.saturating_sub(1);
+    }
+
+    /// Accepts or rejects the selected suggestion
+    pub fn toggle_suggestion(&mut self) {
+        if let Some(row) = self.suggestions.get_mut(self.selected_suggestion) {
+            row.accepted = !row.accepted;
+        }
+    }
+
+    /// Replaces the chapters with ones starting at the accepted suggestions
+    ///
+    /// The new chapters are numbered from "Chapter 1", the first starting
+    /// with the book. Ends the review and returns the number of chapters,
+    /// or `None` when no suggestion was accepted.
+    pub fn apply_suggestions(&mut self) -> Option<usize> {
+        let suggestions = std::mem::take(&mut self.suggestions);
+        let first = self.chapters.first()?;
+        let book_id = first.book_id;
+        let end = self.chapters.last()?.end_time;
+
+        let mut starts = vec![storystream_core::Duration::from_millis(0)];
+        starts.extend(
+            suggestions
+                .iter()
+                .filter(|row| row.accepted)
+                .map(|row| storystream_core::Duration::from_millis(row.start.as_millis() as u64))
+                .filter(|&start| start < end),
+        );
+        if starts.len() == 1 {
+            return None;
+        }
+
+        self.chapters = starts
+            .iter()
+            .enumerate()
+            .map(|(i, &start)| {
+                let next = starts.get(i + 1).copied().unwrap_or(end);
+                Chapter::new(book_id, format!("Chapter {}", i + 1), i as u32, start, next)
+            })
+            .collect();
+        self.selected = 0;
+        self.dirty = true;
+        Some(self.chapters.len())
+    }
+}
+
+/// What the maintenance menu lists for review
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MaintenanceList {
+    /// Problems found by the last file verification
+    #[default]
+    FileIssues,
+    /// Groups of books imported more than once
+    Duplicates,
+    /// Files a staged import would add, update or skip
+    ImportPlan,
+}
+
+/// File verification, library imports, import plans and duplicate review
+/// run from the maintenance menu of the settings view
+#[derive(Debug, Clone, Default)]
+pub struct Maintenance {
+    /// Files checked and to check while a verification runs
+    pub progress: Option<(usize, usize)>,
+    /// Counts and queue depths while a library import runs
+    pub import: Option<String>,
+    /// Outcome of the last finished verification or duplicate search
+    pub summary: Option<String>,
+    /// What `issues` holds
+    pub list: MaintenanceList,
+    /// One line per file problem or duplicate group last found
+    pub issues: Vec<String>,
+    /// Index of the selected issue
+    pub selected: usize,
+}
+
+impl Maintenance {
+    /// Whether a verification or import is running
+    pub fn is_running(&self) -> bool {
+        self.progress.is_some() || self.import.is_some()
+    }
+
+    /// Selects the next issue
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.issues.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Selects the previous issue
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Removes a resolved issue, keeping the selection in range
+    pub fn resolve(&mut self, index: usize) {
+        if index < self.issues.len() {
+            self.issues.remove(index);
+        }
+        self.selected = self.selected.min(self.issues.len().saturating_sub(1));
+    }
+}
+
+/// Downloads listed in the downloads view, tasks first and then history
+#[derive(Debug, Clone, Default)]
+pub struct Downloads {
+    /// Running, queued and paused downloads
+    pub tasks: Vec<DownloadInfo>,
+    /// Finished downloads, most recent first
+    pub history: Vec<DownloadRecord>,
+}
+
+impl Downloads {
+    /// Number of rows in the view
+    pub fn len(&self) -> usize {
+        self.tasks.len() + self.history.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The history record shown at row `index`, if that row is one
+    pub fn record(&self, index: usize) -> Option<&DownloadRecord> {
+        index
+            .checked_sub(self.tasks.len())
+            .and_then(|i| self.history.get(i))
+    }
+}
+
+/// Another device's position in the loaded book, offered in the player view
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncBanner {
+    /// Name of the device that is elsewhere in the book
+    pub device: String,
+    /// Where that device is
+    pub position: Duration,
+    /// Whether both devices moved on since they last agreed
+    pub conflict: bool,
+}
+
+impl SyncBanner {
+    /// Banner text, with how far the other device is from `local`
+    pub fn message(&self, local: Duration) -> String {
+        let delta = self.position.as_secs() as i64 - local.as_secs() as i64;
+        let offset = if delta.abs() < 60 {
+            format!("{:+} s", delta)
+        } else {
+            format!("{:+} min", (delta as f64 / 60.0).round() as i64)
+        };
+        format!(
+            "{} is at {} ({}) — press g to jump",
+            self.device,
+            format_duration(self.position),
+            offset
+        )
+    }
+}
+
+/// Device found on the local network, listed in the settings view
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanPeer {
+    pub name: String,
+    /// Whether the user confirmed pairing with it
+    pub paired: bool,
+    /// Whether it speaks this version's sync protocol
+    pub compatible: bool,
+}
+
+/// Devices on the local network while LAN sync is on
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LanDevices {
+    /// Devices the last search found
+    pub peers: Vec<LanPeer>,
+    /// Index of the device to pair with
+    pub selected: usize,
+    /// Whether a search is running
+    pub searching: bool,
+}
+
+impl LanDevices {
+    /// Selects the next device
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.peers.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Selects the previous device
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+}
+
+/// Device playback can be moved to, listed in the output device picker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputChoice {
+    /// Identifier the audio backend reports, `None` for the system default
+    pub id: Option<String>,
+    pub name: String,
+    /// Whether playback is set to use it
+    pub chosen: bool,
+}
+
+/// Output device picker, opened from the settings view
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutputPicker {
+    /// The system default first, then each device
+    pub choices: Vec<OutputChoice>,
+    pub selected: usize,
+}
+
+impl OutputPicker {
+    /// Lists the system default and `devices` as `(id, name)`, marking
+    /// and selecting `chosen`, the device playback is set to use
+    pub fn new(devices: Vec<(String, String)>, chosen: Option<&str>) -> Self {
+        let mut choices = vec![OutputChoice {
+            id: None,
+            name: "System default".to_string(),
+            chosen: chosen.is_none(),
+        }];
+        choices.extend(devices.into_iter().map(|(id, name)| OutputChoice {
+            chosen: chosen == Some(id.as_str()),
+            id: Some(id),
+            name,
+        }));
+        // A chosen device that went away plays on the default
+        let selected = match choices.iter().position(|choice| choice.chosen) {
+            Some(index) => index,
+            None => {
+                choices[0].chosen = true;
+                0
+            }
+        };
+        Self { choices, selected }
+    }
+
+    /// Selects the next device
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.choices.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Selects the previous device
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn selected_choice(&self) -> Option<&OutputChoice> {
+        self.choices.get(self.selected)
+    }
+}
+
+/// Code to compare with another device's screen before pairing with it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairingPrompt {
+    /// Name of the other device
+    pub device: String,
+    pub code: String,
+}
+
+/// Screen shown instead of the player while the listening limits forbid it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListeningLock {
+    /// Which limit was reached
+    pub message: String,
+    /// Time until listening is allowed again
+    pub wait: Duration,
+    /// Whether a password can lift the limits
+    pub unlockable: bool,
+}
+
+impl ListeningLock {
+    /// Formats the wait as "2h 05m", or "12m" under an hour
+    pub fn format_wait(&self) -> String {
+        // Round up so the last minute does not show as "0m"
+        let minutes = self.wait.as_secs().div_ceil(60);
+        match minutes / 60 {
+            0 => format!("{}m", minutes),
+            hours => format!("{}h {:02}m", hours, minutes % 60),
+        }
+    }
+}
+
+/// A book as listed in the library view
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookRow {
+    pub title: String,
+    pub author: Option<String>,
+    pub duration: Duration,
+    /// Percent listened
+    pub progress: u8,
+    pub favorite: bool,
+    /// The book is loaded in the player
+    pub playing: bool,
+    /// The book's drive is not connected, so it cannot be played for now
+    pub unavailable: bool,
+    /// The book last left part way through
+    pub continue_listening: bool,
+}
+
+/// The stretch of the library list around the selection
+///
+/// Books whose page is still loading are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LibraryRows {
+    /// Library index of the first row
+    pub first: usize,
+    pub rows: Vec<Option<BookRow>>,
+}
+
+/// A book offered in the player view once the loaded one plays to its end
+#[derive(Debug, Clone)]
+pub struct UpNext {
+    pub book: Book,
+    /// Why it was picked, such as "Next in Stormlight Archive"
+    pub reason: String,
+}
+
+impl UpNext {
+    /// Prompt text
+    pub fn message(&self) -> String {
+        format!("Play next: {}? (Enter)", self.book.title)
+    }
+}
+
+/// A feed subscription as listed under the playlists
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subscription {
+    pub title: String,
+    /// Episodes not yet listened to the end
+    pub unplayed: usize,
+}
+
+impl Subscription {
+    /// Count line shown under the title, such as "3 unplayed"
+    pub fn label(&self) -> String {
+        match self.unplayed {
+            0 => "all played".to_string(),
+            n => format!("{} unplayed", n),
+        }
+    }
+}
+
+/// A playlist as listed in the playlists view
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaylistRow {
+    pub id: PlaylistId,
+    pub name: String,
+    /// Whether the playlist fills itself from its criteria, so books cannot
+    /// be added or removed by hand
+    pub smart: bool,
+    pub books: usize,
+    /// Length of all its books together
+    pub duration: Duration,
+}
+
+impl PlaylistRow {
+    /// Count line shown under the name, such as "3 books · 12:04:10"
+    pub fn label(&self) -> String {
+        let books = match self.books {
+            1 => "1 book".to_string(),
+            n => format!("{} books", n),
+        };
+        format!("{} · {}", books, format_duration(self.duration))
+    }
+}
+
+/// Books of the selected playlist, listed and moved through in the
+/// playlists view
+#[derive(Debug, Clone)]
+pub struct OpenPlaylist {
+    /// Books in playlist order
+    pub books: Vec<Book>,
+    pub selected: usize,
+}
+
+impl OpenPlaylist {
+    /// Selects the next book
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.books.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Selects the previous book
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn selected_book(&self) -> Option<&Book> {
+        self.books.get(self.selected)
+    }
+
+    /// Moves the selected book one place down, or up, keeping it selected
+    ///
+    /// Returns false when it is already at that end of the playlist.
+    pub fn move_selected(&mut self, down: bool) -> bool {
+        let target = match down {
+            true if self.selected + 1 < self.books.len() => self.selected + 1,
+            false if self.selected > 0 && self.selected < self.books.len() => self.selected - 1,
+            _ => return false,
+        };
+        self.books.swap(self.selected, target);
+        self.selected = target;
+        true
+    }
+
+    /// Removes the selected book, keeping the selection in range
+    pub fn remove_selected(&mut self) -> Option<Book> {
+        if self.selected >= self.books.len() {
+            return None;
+        }
+        let book = self.books.remove(self.selected);
+        self.selected = self.selected.min(self.books.len().saturating_sub(1));
+        Some(book)
+    }
+}
+
+/// Where a drag along the progress bar would seek to
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scrub {
+    pub position: Duration,
+    /// Label of the chapter start or bookmark the target snapped to
+    pub snapped_to: Option<String>,
+}
+
+impl Scrub {
+    /// Label for the progress bar, such as "12:30 → Chapter 7"
+    pub fn label(&self) -> String {
+        match &self.snapped_to {
+            Some(point) => format!("{} → {}", format_duration(self.position), point),
+            None => format_duration(self.position),
+        }
+    }
 }
 
 /// Application state
@@ -95,14 +979,94 @@ pub struct AppState {
     pub selected_item: usize,
     /// Library items count
     pub library_items_count: usize,
+    /// Library rows in view, `None` in the demo
+    pub library_rows: Option<LibraryRows>,
+    /// Library index of the top row in view, kept while other views show
+    pub library_scroll: usize,
+    /// Section the help view shows
+    pub help_section: HelpSection,
+    /// Line of the help section at the top of the view
+    pub help_scroll: usize,
     /// Status message
     pub status_message: Option<String>,
+    /// Whether the status message reports something that went wrong
+    pub status_is_error: bool,
     /// Search query
     pub search_query: String,
+    /// Structured constraints applied along with the search query
+    pub search_filter: SearchFilter,
+    /// Books found by the last search
+    pub search_results: Vec<Book>,
+    /// Search filter popup, open over the search view
+    pub filter_popup: Option<FilterPopup>,
     /// Mouse position
     pub mouse_position: Option<(u16, u16)>,
     /// Theme type
     pub theme: crate::theme::ThemeType,
+    /// Chapters of the loaded book
+    pub chapters: Vec<Chapter>,
+    /// Chapter editor, open while editing chapters in the player view
+    pub chapter_editor: Option<ChapterEditor>,
+    /// Bookmarks of the loaded book, in position order
+    pub bookmarks: Vec<Bookmark>,
+    /// Position another device reported for the loaded book
+    pub sync_banner: Option<SyncBanner>,
+    /// Devices on the local network, `None` while LAN sync is off
+    pub lan: Option<LanDevices>,
+    /// Pairing awaiting the user's confirmation; it takes all key input
+    pub pairing: Option<PairingPrompt>,
+    /// Output device picker; it takes all key input
+    pub output_picker: Option<OutputPicker>,
+    /// Lock screen shown while the listening limits forbid playback
+    pub listening_lock: Option<ListeningLock>,
+    /// Book suggested after the loaded one finished
+    pub up_next: Option<UpNext>,
+    /// Seek target while the progress bar is being dragged
+    pub scrub: Option<Scrub>,
+    /// Book details shown over the library view
+    pub book_detail: Option<BookDetail>,
+    /// Text prompt shown over the current view; it takes all key input
+    pub input: Option<TextPrompt>,
+    /// Command palette shown over the current view; it takes all key input
+    pub palette: Option<CommandPalette>,
+    /// Keys of the actions, the defaults unless the config changes them
+    pub keymap: KeyMap,
+    /// Question asked before something that cannot be undone; it takes all
+    /// key input
+    pub confirm: Option<ConfirmDialog>,
+    /// Config file as the settings view shows and edits it
+    pub config: Config,
+    /// Setting whose last change was refused, and why; shown under it
+    pub setting_error: Option<(Setting, String)>,
+    /// Minutes listened on each recent day, oldest first and ending today
+    pub daily_minutes: Vec<u32>,
+    /// Minutes a day needs to count toward the listening streak
+    pub daily_goal_minutes: u32,
+    /// Library totals and listening history, loaded with the statistics view
+    pub library_stats: Option<LibraryStats>,
+    /// Whether the statistics view's figures are being read
+    pub statistics_loading: bool,
+    /// Listening history of the loaded book
+    pub book_stats: Option<BookStats>,
+    /// File verification progress and results
+    pub maintenance: Maintenance,
+    /// Download tasks and history
+    pub downloads: Downloads,
+    /// Disk cache usage, `None` while caching is off
+    pub cache: Option<CacheStats>,
+    /// Playlists shown in the playlists view
+    pub playlists: Vec<PlaylistRow>,
+    /// Playlist whose books are shown; while open, the selection moves
+    /// through its books
+    pub open_playlist: Option<OpenPlaylist>,
+    /// Feed subscriptions shown in the playlists view
+    pub subscriptions: Vec<Subscription>,
+    /// The library was opened read-only, so nothing that changes it runs
+    pub read_only: bool,
+    /// Accessibility mode, which shows announcements on their own line
+    pub accessible: bool,
+    /// Latest announcement for screen readers
+    pub announcement: String,
     /// Per-view selection states (preserves cursor position when switching views)
     view_selections: HashMap<View, usize>,
 }
@@ -115,10 +1079,49 @@ impl Default for AppState {
             playback: PlaybackState::default(),
             selected_item: 0,
             library_items_count: 8, // Demo books
+            library_rows: None,
+            library_scroll: 0,
+            help_section: HelpSection::General,
+            help_scroll: 0,
             status_message: None,
+            status_is_error: false,
             search_query: String::new(),
+            search_filter: SearchFilter::default(),
+            search_results: Vec::new(),
+            filter_popup: None,
             mouse_position: None,
             theme: crate::theme::ThemeType::default(),
+            chapters: Vec::new(),
+            chapter_editor: None,
+            bookmarks: Vec::new(),
+            sync_banner: None,
+            lan: None,
+            pairing: None,
+            output_picker: None,
+            listening_lock: None,
+            up_next: None,
+            scrub: None,
+            book_detail: None,
+            input: None,
+            palette: None,
+            keymap: KeyMap::default(),
+            confirm: None,
+            config: Config::default(),
+            setting_error: None,
+            daily_minutes: Vec::new(),
+            daily_goal_minutes: 30,
+            library_stats: None,
+            statistics_loading: false,
+            book_stats: None,
+            maintenance: Maintenance::default(),
+            downloads: Downloads::default(),
+            cache: None,
+            playlists: Vec::new(),
+            open_playlist: None,
+            subscriptions: Vec::new(),
+            read_only: false,
+            accessible: false,
+            announcement: String::new(),
             view_selections: HashMap::new(),
         }
     }
@@ -152,6 +1155,79 @@ impl AppState {
         self.selected_item = *self.view_selections.get(&self.view).unwrap_or(&0);
     }
 
+    /// Selected library item, also while another view shows
+    pub fn library_selection(&self) -> usize {
+        if self.view == View::Library {
+            self.selected_item
+        } else {
+            self.view_selections
+                .get(&View::Library)
+                .copied()
+                .unwrap_or(0)
+        }
+    }
+
+    /// Setting selected in the settings view
+    pub fn selected_setting(&self) -> Setting {
+        Setting::ALL[self.selected_item.min(Setting::ALL.len() - 1)]
+    }
+
+    /// Playlist selected in the playlists view, also while another view shows
+    pub fn selected_playlist(&self) -> Option<&PlaylistRow> {
+        let index = if self.view == View::Playlists {
+            self.selected_item
+        } else {
+            self.view_selections
+                .get(&View::Playlists)
+                .copied()
+                .unwrap_or(0)
+        };
+        self.playlists.get(index)
+    }
+
+    /// The open playlist, while the playlists view shows
+    fn open_playlist_mut(&mut self) -> Option<&mut OpenPlaylist> {
+        match self.view {
+            View::Playlists => self.open_playlist.as_mut(),
+            _ => None,
+        }
+    }
+
+    /// Shows `section` in the help view, from its top
+    pub fn show_help_section(&mut self, section: HelpSection) {
+        self.help_section = section;
+        self.help_scroll = 0;
+    }
+
+    /// Scrolls the help by `lines`, up when negative, no further than `last`
+    pub fn scroll_help(&mut self, lines: isize, last: usize) {
+        self.help_scroll = self
+            .help_scroll
+            .min(last)
+            .saturating_add_signed(lines)
+            .min(last);
+    }
+
+    /// Scrolls the library just enough to show the selection in `visible`
+    /// rows
+    pub fn scroll_library(&mut self, visible: usize) {
+        let selected = self.library_selection();
+        let visible = visible.max(1);
+        if selected < self.library_scroll {
+            self.library_scroll = selected;
+        } else if selected >= self.library_scroll + visible {
+            self.library_scroll = selected + 1 - visible;
+        }
+        let last_page = self.library_items_count.saturating_sub(visible);
+        self.library_scroll = self.library_scroll.min(last_page);
+    }
+
+    /// Whether keys are typed as text into a prompt or the command
+    /// palette, rather than running their shortcuts
+    pub fn input_mode(&self) -> bool {
+        self.input.is_some() || self.palette.is_some()
+    }
+
     /// Requests quit
     pub fn quit(&mut self) {
         self.should_quit = true;
@@ -160,11 +1236,34 @@ impl AppState {
     /// Sets a status message
     pub fn set_status(&mut self, message: impl Into<String>) {
         self.status_message = Some(message.into());
+        self.status_is_error = false;
+    }
+
+    /// Sets a status message that reports something that went wrong
+    pub fn set_error(&mut self, message: impl Into<String>) {
+        self.status_message = Some(message.into());
+        self.status_is_error = true;
+    }
+
+    /// Uses the keys of the `[keybindings]` config section
+    ///
+    /// Keys that clash leave every action on its default keys, and the
+    /// status line says which setting to fix.
+    pub fn apply_keybindings(&mut self, config: &KeybindingsConfig) {
+        match KeyMap::from_config(config) {
+            Ok(keymap) => self.keymap = keymap,
+            Err(e) => {
+                log::warn!("Using the default keys: {}", e);
+                self.keymap = KeyMap::default();
+                self.set_error(format!("Default keys in use: {}", e));
+            }
+        }
     }
 
     /// Clears the status message
     pub fn clear_status(&mut self) {
         self.status_message = None;
+        self.status_is_error = false;
     }
 
     /// Sets the search query
@@ -178,7 +1277,13 @@ impl AppState {
     }
 
     /// Selects the next item in the current view
+    ///
+    /// In the playlists view with a playlist open, that is its next book.
     pub fn select_next(&mut self) {
+        if let Some(open) = self.open_playlist_mut() {
+            open.select_next();
+            return;
+        }
         let max_item = self.get_max_items_for_view().saturating_sub(1);
         if self.selected_item < max_item {
             self.selected_item += 1;
@@ -188,27 +1293,48 @@ impl AppState {
 
     /// Selects the previous item in the current view
     pub fn select_previous(&mut self) {
+        if let Some(open) = self.open_playlist_mut() {
+            open.select_previous();
+            return;
+        }
         if self.selected_item > 0 {
             self.selected_item -= 1;
             self.save_view_selection(); // Save immediately
         }
     }
 
+    /// Selects `index` in the current view, or its last item if there are
+    /// fewer
+    pub fn select(&mut self, index: usize) {
+        let max_item = self.get_max_items_for_view().saturating_sub(1);
+        self.selected_item = index.min(max_item);
+        self.save_view_selection();
+    }
+
     /// Resets selection to the first item
     pub fn reset_selection(&mut self) {
         self.selected_item = 0;
         self.save_view_selection(); // Save immediately
     }
 
+    /// Updates the current chapter from the playback position
+    pub fn update_chapter(&mut self) {
+        let position = self.playback.position.as_millis() as u64;
+        self.playback.chapter = self.chapters.iter().position(|c| {
+            c.start_time.as_millis() <= position && position < c.end_time.as_millis()
+        });
+    }
+
     /// Gets the maximum number of items for the current view
     fn get_max_items_for_view(&self) -> usize {
         match self.view {
             View::Library => self.library_items_count,
             View::Bookmarks => 10, // Example count
-            View::Search => 15,    // Example count
-            View::Playlists => 5,  // Example count
-            View::Settings => 10,  // Example count
+            View::Search => self.search_results.len(),
+            View::Playlists => self.playlists.len(),
+            View::Settings => Setting::ALL.len(),
             View::Statistics => 5, // Example count
+            View::Downloads => self.downloads.len(),
             _ => 0,
         }
     }
@@ -240,7 +1366,7 @@ impl AppState {
 }
 
 /// Helper function to format Duration as MM:SS or HH:MM:SS
-fn format_duration(duration: Duration) -> String {
+pub(crate) fn format_duration(duration: Duration) -> String {
     let total_secs = duration.as_secs();
     let hours = total_secs / 3600;
     let minutes = (total_secs % 3600) / 60;
@@ -349,6 +1475,114 @@ mod tests {
         assert_eq!(state.selected_item, 2);
     }
 
+    #[test]
+    fn test_prompts_take_typed_keys() {
+        let mut state = AppState::new();
+        assert!(!state.input_mode());
+
+        state.input = Some(TextPrompt::new(
+            "Playlist name",
+            "Road",
+            InputPurpose::NewPlaylist,
+        ));
+        assert!(state.input_mode());
+        assert_eq!(state.input.as_ref().unwrap().text.cursor(), 4);
+
+        state.input = None;
+        state.palette = Some(CommandPalette::default());
+        assert!(state.input_mode());
+    }
+
+    #[test]
+    fn test_open_playlist_takes_the_selection() {
+        let book = |title: &str| {
+            Book::new(
+                title.to_string(),
+                format!("/{}.mp3", title).into(),
+                1000,
+                storystream_core::Duration::from_seconds(60),
+            )
+        };
+        let row = |name: &str| PlaylistRow {
+            id: PlaylistId::new(),
+            name: name.to_string(),
+            smart: false,
+            books: 3,
+            duration: Duration::from_secs(180),
+        };
+
+        let mut state = AppState::new();
+        state.set_view(View::Playlists);
+        state.playlists = vec![row("First"), row("Second")];
+        state.select_next();
+        state.open_playlist = Some(OpenPlaylist {
+            books: vec![book("A"), book("B"), book("C")],
+            selected: 0,
+        });
+
+        // Moving goes through the books and leaves the playlist selected
+        state.select_next();
+        state.select_next();
+        state.select_next();
+        assert_eq!(state.selected_playlist().unwrap().name, "Second");
+        let open = state.open_playlist.as_mut().unwrap();
+        assert_eq!(open.selected, 2);
+        assert!(!open.move_selected(true));
+
+        assert!(open.move_selected(false));
+        let titles: Vec<&str> = open.books.iter().map(|b| b.title.as_str()).collect();
+        assert_eq!(titles, ["A", "C", "B"]);
+        assert_eq!(open.selected, 1);
+
+        assert_eq!(open.remove_selected().unwrap().title, "C");
+        assert_eq!(open.selected, 1);
+        assert_eq!(open.remove_selected().unwrap().title, "B");
+        assert_eq!(open.selected, 0);
+
+        // Other views keep their own selection
+        state.set_view(View::Downloads);
+        assert_eq!(state.selected_playlist().unwrap().name, "Second");
+        state.set_view(View::Playlists);
+        state.playlists.pop();
+        state.select(state.selected_item);
+        assert_eq!(state.selected_item, 0);
+    }
+
+    #[test]
+    fn test_library_scroll_survives_view_switches() {
+        let mut state = AppState::new();
+        state.library_items_count = 100;
+
+        for _ in 0..30 {
+            state.select_next();
+        }
+        state.scroll_library(20);
+        assert_eq!(state.library_scroll, 11);
+
+        // Moving up within the rows in view does not scroll
+        state.select_previous();
+        state.scroll_library(20);
+        assert_eq!(state.library_scroll, 11);
+
+        state.set_view(View::Bookmarks);
+        state.select_next();
+        assert_eq!(state.library_selection(), 29);
+        state.scroll_library(20);
+        assert_eq!(state.library_scroll, 11);
+
+        state.set_view(View::Library);
+        assert_eq!(state.selected_item, 29);
+
+        // A taller terminal shows the last rows rather than blank space
+        for _ in 0..70 {
+            state.select_next();
+        }
+        state.scroll_library(20);
+        assert_eq!(state.library_scroll, 80);
+        state.scroll_library(40);
+        assert_eq!(state.library_scroll, 60);
+    }
+
     #[test]
     fn test_app_state_quit() {
         let mut state = AppState::new();
@@ -413,4 +1647,324 @@ mod tests {
         let formatted = format_duration(duration);
         assert_eq!(formatted, "01:01:05");
     }
+
+    fn edited_chapters() -> ChapterEditor {
+        let book_id = storystream_core::BookId::new();
+        let chapters = [(0, 100), (100, 200)]
+            .iter()
+            .enumerate()
+            .map(|(i, &(start, end))| {
+                Chapter::new(
+                    book_id,
+                    format!("Chapter {}", i + 1),
+                    i as u32,
+                    storystream_core::Duration::from_seconds(start),
+                    storystream_core::Duration::from_seconds(end),
+                )
+            })
+            .collect();
+        ChapterEditor::new(chapters)
+    }
+
+    #[test]
+    fn test_chapter_editor_operations() {
+        let mut editor = edited_chapters();
+        assert!(!editor.rename(0, "  "));
+        assert!(!editor.dirty);
+
+        editor.select_next();
+        assert!(editor.nudge(-1));
+        assert_eq!(editor.chapters[0].end_time.as_millis(), 99_000);
+        assert!(editor.dirty);
+
+        assert!(editor.split_at(Duration::from_secs(150)));
+        assert_eq!(editor.selected, 2);
+        assert_eq!(editor.chapters.len(), 3);
+
+        editor.select_previous();
+        assert!(editor.merge_with_next());
+        assert_eq!(editor.chapters.len(), 2);
+        assert!(editor.rename(1, "Epilogue"));
+        assert_eq!(editor.selected_chapter().unwrap().title, "Epilogue");
+    }
+
+    #[test]
+    fn test_chapter_suggestions_replace_chapters() {
+        let mut editor = edited_chapters();
+        let row = |seconds, accepted| SuggestionRow {
+            start: Duration::from_secs(seconds),
+            silence: Duration::from_secs(3),
+            confidence: 0.8,
+            accepted,
+        };
+        editor.suggesting = true;
+        editor.review(vec![row(40, true), row(90, false), row(130, true)]);
+        assert!(editor.is_reviewing());
+        assert!(!editor.suggesting);
+
+        editor.select_next_suggestion();
+        editor.toggle_suggestion();
+        editor.select_next_suggestion();
+        editor.select_next_suggestion();
+        editor.toggle_suggestion();
+        assert_eq!(editor.apply_suggestions(), Some(3));
+        assert!(!editor.is_reviewing());
+        assert!(editor.dirty);
+
+        let starts: Vec<_> = editor
+            .chapters
+            .iter()
+            .map(|c| c.start_time.as_millis() / 1000)
+            .collect();
+        assert_eq!(starts, vec![0, 40, 90]);
+        assert_eq!(editor.chapters[2].title, "Chapter 3");
+        assert_eq!(editor.chapters[2].end_time.as_millis(), 200_000);
+
+        // Nothing accepted leaves the chapters alone
+        editor.review(vec![row(60, false)]);
+        assert_eq!(editor.apply_suggestions(), None);
+        assert_eq!(editor.chapters.len(), 3);
+    }
+
+    #[test]
+    fn test_maintenance_issue_selection() {
+        let mut maintenance = Maintenance {
+            issues: vec!["a".to_string(), "b".to_string()],
+            ..Default::default()
+        };
+        assert!(!maintenance.is_running());
+
+        maintenance.select_next();
+        maintenance.select_next();
+        assert_eq!(maintenance.selected, 1);
+
+        maintenance.resolve(1);
+        assert_eq!(maintenance.issues, vec!["a"]);
+        assert_eq!(maintenance.selected, 0);
+
+        maintenance.resolve(0);
+        assert!(maintenance.issues.is_empty());
+        assert_eq!(maintenance.selected, 0);
+    }
+
+    #[test]
+    fn test_downloads_rows() {
+        use storystream_network::{DownloadOutcome, DownloadStatus};
+
+        let mut state = AppState::new();
+        state.set_view(View::Downloads);
+        state.downloads.tasks.push(DownloadInfo {
+            id: "a".to_string(),
+            url: "https://example.com/a.mp3".to_string(),
+            destination: "/tmp/a.mp3".into(),
+            status: DownloadStatus::Queued,
+            attempts: 0,
+        });
+        state.downloads.history.push(DownloadRecord {
+            task_id: "b".to_string(),
+            url: "https://example.com/b.mp3".to_string(),
+            source_url: None,
+            destination: "/tmp/b.mp3".into(),
+            bytes: 0,
+            duration_ms: 10,
+            attempts: 2,
+            outcome: DownloadOutcome::Failed,
+            error_code: Some(404),
+            error: Some("HTTP 404: Not Found".to_string()),
+            finished_at: chrono::Utc::now(),
+        });
+
+        assert_eq!(state.downloads.len(), 2);
+        assert!(state.downloads.record(0).is_none());
+        state.select_next();
+        state.select_next();
+        assert_eq!(state.selected_item, 1);
+        assert_eq!(state.downloads.record(1).unwrap().task_id, "b");
+    }
+
+    #[test]
+    fn test_book_detail_field_selection() {
+        let mut book = Book::new(
+            "Emma".to_string(),
+            "/books/emma.m4b".into(),
+            1,
+            storystream_core::Duration::from_seconds(60),
+        );
+        book.narrator = Some("Juliet Stevenson".to_string());
+        let mut detail = BookDetail::new(book);
+
+        detail.select_previous();
+        assert_eq!(detail.selected_field(), BookField::Title);
+        for _ in 0..5 {
+            detail.select_next();
+        }
+        assert_eq!(detail.selected_field(), BookField::Series);
+        assert_eq!(BookField::Series.value(&detail.book), "");
+        assert_eq!(BookField::Narrator.value(&detail.book), "Juliet Stevenson");
+    }
+
+    #[test]
+    fn test_update_chapter_from_position() {
+        let mut state = AppState::new();
+        state.chapters = edited_chapters().chapters;
+
+        state.playback.position = Duration::from_secs(150);
+        state.update_chapter();
+        assert_eq!(state.playback.chapter, Some(1));
+
+        state.playback.position = Duration::from_secs(250);
+        state.update_chapter();
+        assert_eq!(state.playback.chapter, None);
+    }
+
+    #[test]
+    fn test_filter_fields() {
+        let mut filter = SearchFilter::default();
+
+        FilterField::Author.set(&mut filter, " Sanderson ").unwrap();
+        FilterField::MinLength.set(&mut filter, "20h").unwrap();
+        assert!(FilterField::MaxLength.set(&mut filter, "soon").is_err());
+        assert_eq!(FilterField::Author.value(&filter), "Sanderson");
+        assert_eq!(FilterField::MinLength.value(&filter), "20:00:00");
+        assert!(filter.max_duration.is_none());
+
+        FilterField::Finished.toggle(&mut filter);
+        assert_eq!(filter.finished, Some(false));
+        assert_eq!(FilterField::Finished.value(&filter), "unfinished");
+        FilterField::Finished.toggle(&mut filter);
+        FilterField::Finished.toggle(&mut filter);
+        assert_eq!(filter.finished, None);
+
+        FilterField::Author.set(&mut filter, "  ").unwrap();
+        FilterField::MinLength.clear(&mut filter);
+        assert!(filter.is_empty());
+
+        let mut popup = FilterPopup::default();
+        for _ in 0..10 {
+            popup.select_next();
+        }
+        assert_eq!(popup.selected_field(), FilterField::Format);
+    }
+
+    #[test]
+    fn test_sync_banner_message() {
+        let banner = SyncBanner {
+            device: "Phone".to_string(),
+            position: Duration::from_secs(5 * 3600 + 12 * 60 + 40),
+            conflict: false,
+        };
+
+        let local = banner.position - Duration::from_secs(18 * 60);
+        assert_eq!(
+            banner.message(local),
+            "Phone is at 05:12:40 (+18 min) — press g to jump"
+        );
+        assert_eq!(
+            banner.message(banner.position + Duration::from_secs(45)),
+            "Phone is at 05:12:40 (-45 s) — press g to jump"
+        );
+    }
+
+    #[test]
+    fn test_format_speed() {
+        let mut playback = PlaybackState {
+            speed: 1.32,
+            ..Default::default()
+        };
+        assert_eq!(playback.format_speed(), "1.3x");
+
+        playback.ramp_target = Some(1.75);
+        assert_eq!(playback.format_speed(), "ramping 1.32x → 1.75x");
+    }
+
+    #[test]
+    fn test_scrub_label() {
+        let mut scrub = Scrub {
+            position: Duration::from_secs(12 * 60 + 30),
+            snapped_to: None,
+        };
+        assert_eq!(scrub.label(), "12:30");
+
+        scrub.snapped_to = Some("Chapter 7".to_string());
+        assert_eq!(scrub.label(), "12:30 → Chapter 7");
+    }
+
+    #[test]
+    fn test_format_output() {
+        let mut playback = PlaybackState::default();
+        assert_eq!(playback.format_output(), None);
+
+        playback.output_device = Some("Speaker".to_string());
+        assert_eq!(playback.format_output().as_deref(), Some("Speaker"));
+
+        playback.volume_offset = 20;
+        assert_eq!(playback.format_output().as_deref(), Some("Speaker (+20%)"));
+
+        playback.volume_offset = -15;
+        assert_eq!(playback.format_output().as_deref(), Some("Speaker (-15%)"));
+    }
+
+    #[test]
+    fn test_format_sleep_timer() {
+        let mut playback = PlaybackState::default();
+        assert_eq!(playback.format_sleep_timer(), None);
+
+        playback.sleep_remaining = Some(Duration::from_secs(872));
+        assert_eq!(
+            playback.format_sleep_timer().as_deref(),
+            Some("Sleep in 14:32")
+        );
+
+        playback.sleep_at_chapter_end = true;
+        assert_eq!(
+            playback.format_sleep_timer().as_deref(),
+            Some("Sleep at chapter end (14:32)")
+        );
+    }
+
+    #[test]
+    fn test_subscription_label() {
+        let mut subscription = Subscription {
+            title: "Show".to_string(),
+            unplayed: 3,
+        };
+        assert_eq!(subscription.label(), "3 unplayed");
+
+        subscription.unplayed = 0;
+        assert_eq!(subscription.label(), "all played");
+    }
+    #[test]
+    fn test_listening_lock_wait() {
+        let mut lock = ListeningLock {
+            message: "Today's listening time is used up".to_string(),
+            wait: Duration::from_secs(2 * 3600 + 5 * 60),
+            unlockable: false,
+        };
+        assert_eq!(lock.format_wait(), "2h 05m");
+
+        lock.wait = Duration::from_secs(30);
+        assert_eq!(lock.format_wait(), "1m");
+    }
+
+    #[test]
+    fn test_output_picker() {
+        let devices = vec![
+            ("a1".to_string(), "Speakers".to_string()),
+            ("b2".to_string(), "Headphones".to_string()),
+        ];
+        let mut picker = OutputPicker::new(devices.clone(), Some("b2"));
+        assert_eq!(picker.choices.len(), 3);
+        assert_eq!(picker.selected, 2);
+        assert_eq!(
+            picker.selected_choice().and_then(|c| c.id.as_deref()),
+            Some("b2")
+        );
+        picker.select_next();
+        assert_eq!(picker.selected, 2);
+
+        // A device that went away leaves the system default chosen
+        let picker = OutputPicker::new(devices, Some("gone"));
+        assert_eq!(picker.selected, 0);
+        assert!(picker.choices[0].chosen);
+    }
 }
@@ -1,7 +1,7 @@
 // crates/tui/src/ui/bookmarks.rs
 //! Bookmarks view rendering
 
-use crate::state::AppState;
+use crate::state::{format_duration, AppState};
 use ratatui::{
     layout::Rect,
     style::Style,
@@ -9,40 +9,77 @@ use ratatui::{
     widgets::{Block, Borders, List, ListItem},
     Frame,
 };
+use std::time::Duration;
+use storystream_core::Bookmark;
 
 /// Renders the bookmarks view
-pub fn render(frame: &mut Frame, area: Rect, _state: &AppState, theme: &crate::theme::Theme) {
-    // Demo bookmarks
-    let bookmarks = vec![
-        "📌 00:15:32 - Call me Ishmael",
-        "📌 01:23:45 - The whale appears",
-        "📌 02:45:12 - Important quote",
-    ];
-
-    let items: Vec<ListItem> = bookmarks
-        .iter()
-        .map(|bookmark| ListItem::new(Line::from(Span::styled(*bookmark, theme.text_style()))))
-        .collect();
+pub fn render(frame: &mut Frame, area: Rect, state: &AppState, theme: &crate::theme::Theme) {
+    let items: Vec<ListItem> = if state.bookmarks.is_empty() {
+        vec![ListItem::new(Span::styled(
+            "No bookmarks for this book",
+            theme.text_secondary_style(),
+        ))]
+    } else {
+        state
+            .bookmarks
+            .iter()
+            .enumerate()
+            .map(|(i, bookmark)| {
+                // Auto-bookmarks are dimmed so the user's own stand out
+                let style = if i == state.selected_item {
+                    theme.highlight_style()
+                } else if bookmark.is_auto() {
+                    theme.text_secondary_style()
+                } else {
+                    theme.text_style()
+                };
+                ListItem::new(Line::from(Span::styled(bookmark_line(bookmark), style)))
+            })
+            .collect()
+    };
 
     let list = List::new(items)
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(theme.border_color()))
-                .title("🔖 Bookmarks (b: Add | d: Delete | Enter: Jump)"),
+                .title("🔖 Bookmarks (b: Add | d: Delete | X: Clear auto | Enter: Jump)"),
         )
         .style(theme.text_style());
 
     frame.render_widget(list, area);
 }
 
+fn bookmark_line(bookmark: &Bookmark) -> String {
+    let position = format_duration(Duration::from_millis(bookmark.position.as_millis()));
+    let title = bookmark.title.as_deref().unwrap_or("Untitled");
+    if bookmark.is_auto() {
+        format!("🕑 {} - {} (auto)", position, title)
+    } else {
+        format!("📌 {} - {}", position, title)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use storystream_core::{AutoBookmarkTrigger, BookId};
 
     #[test]
     fn test_bookmarks_render_compiles() {
         let state = AppState::new();
         let _ = state.view;
     }
+
+    #[test]
+    fn test_auto_bookmarks_are_marked() {
+        let book_id = BookId::new();
+        let position = storystream_core::Duration::from_seconds(90);
+
+        let manual = Bookmark::with_title(book_id, position, "Chase".to_string());
+        let auto = Bookmark::auto(book_id, position, AutoBookmarkTrigger::Paused);
+
+        assert_eq!(bookmark_line(&manual), "📌 01:30 - Chase");
+        assert_eq!(bookmark_line(&auto), "🕑 01:30 - Paused (auto)");
+    }
 }
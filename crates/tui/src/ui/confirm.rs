@@ -0,0 +1,174 @@
+// crates/tui/src/ui/confirm.rs
+//! Yes/no question asked before something that cannot be undone
+//!
+//! While a [`ConfirmDialog`] is open it takes every key, so the view under
+//! it does not act on them. No is selected at first; Enter or `y` is needed
+//! to go ahead.
+
+use crate::theme::Theme;
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+use storystream_core::{BookId, BookmarkId, PlaylistId};
+
+/// What runs once a [`ConfirmDialog`] is answered yes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfirmAction {
+    /// Move the book to the trash
+    DeleteBook(BookId),
+    DeletePlaylist(PlaylistId),
+    DeleteBookmark(BookmarkId),
+    /// Overwrite the config file with the default settings
+    ResetSettings,
+}
+
+/// Question shown over the current view
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfirmDialog {
+    pub message: String,
+    pub action: ConfirmAction,
+    /// Whether Yes is selected rather than No
+    pub yes_selected: bool,
+    /// Drawn with the theme's error color, for answers that lose data
+    pub dangerous: bool,
+}
+
+impl ConfirmDialog {
+    /// Asks `message` before running `action`, with No selected
+    pub fn new(message: impl Into<String>, action: ConfirmAction) -> Self {
+        Self {
+            message: message.into(),
+            action,
+            yes_selected: false,
+            dangerous: false,
+        }
+    }
+
+    /// Marks the question as one whose yes loses data
+    pub fn dangerous(mut self) -> Self {
+        self.dangerous = true;
+        self
+    }
+
+    /// Moves the selection for a key, returning the answer once given
+    ///
+    /// `y` and `n` answer straight away, Enter answers with the selection
+    /// and Esc answers no.
+    pub fn handle_key(&mut self, code: KeyCode) -> Option<bool> {
+        match code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => Some(true),
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => Some(false),
+            KeyCode::Enter => Some(self.yes_selected),
+            KeyCode::Left | KeyCode::Right | KeyCode::Tab | KeyCode::BackTab => {
+                self.yes_selected = !self.yes_selected;
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Renders the dialog centered over `area`
+pub fn render(frame: &mut Frame, area: Rect, dialog: &ConfirmDialog, theme: &Theme) {
+    let width = area.width.saturating_sub(4).min(54);
+    let height = 7.min(area.height);
+    let popup = Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+
+    let border = if dialog.dangerous {
+        theme.error
+    } else {
+        theme.accent
+    };
+    let button = |label: &str, selected: bool| {
+        if selected {
+            Span::styled(
+                format!("[ {} ]", label),
+                Style::default()
+                    .fg(border)
+                    .add_modifier(Modifier::BOLD | Modifier::REVERSED),
+            )
+        } else {
+            Span::styled(format!("  {}  ", label), theme.text_style())
+        }
+    };
+
+    let text = vec![
+        Line::from(Span::styled(dialog.message.clone(), theme.text_style())),
+        Line::from(""),
+        Line::from(vec![
+            button("Yes", dialog.yes_selected),
+            Span::raw("   "),
+            button("No", !dialog.yes_selected),
+        ])
+        .alignment(Alignment::Center),
+        Line::from(Span::styled(
+            "y/n: Answer | ←/→: Choose | Esc: Cancel",
+            theme.text_secondary_style(),
+        ))
+        .alignment(Alignment::Center),
+    ];
+    let paragraph = Paragraph::new(text).wrap(Wrap { trim: true }).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(border))
+            .title("Confirm"),
+    );
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(paragraph, popup);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answers_from_keys() {
+        let mut dialog = ConfirmDialog::new("Delete?", ConfirmAction::ResetSettings);
+        assert_eq!(dialog.handle_key(KeyCode::Enter), Some(false));
+        assert_eq!(dialog.handle_key(KeyCode::Char('q')), None);
+
+        assert_eq!(dialog.handle_key(KeyCode::Right), None);
+        assert!(dialog.yes_selected);
+        assert_eq!(dialog.handle_key(KeyCode::Enter), Some(true));
+
+        assert_eq!(dialog.handle_key(KeyCode::Esc), Some(false));
+        assert_eq!(dialog.handle_key(KeyCode::Char('y')), Some(true));
+        assert_eq!(dialog.handle_key(KeyCode::Char('n')), Some(false));
+    }
+
+    #[test]
+    fn test_dangerous_dialog_uses_the_error_color() {
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let theme = Theme::default();
+        let dialog = ConfirmDialog::new(
+            "Move 'Moby Dick' to the trash?",
+            ConfirmAction::DeleteBook(BookId::new()),
+        )
+        .dangerous();
+        let mut terminal = Terminal::new(TestBackend::new(70, 20)).unwrap();
+        terminal
+            .draw(|frame| render(frame, frame.area(), &dialog, &theme))
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let text: String = buffer.content().iter().map(|cell| cell.symbol()).collect();
+        assert!(text.contains("Moby Dick"));
+        assert!(text.contains("[ No ]"));
+        assert!(buffer
+            .content()
+            .iter()
+            .any(|cell| cell.symbol() == "┌" && cell.fg == theme.error));
+    }
+}
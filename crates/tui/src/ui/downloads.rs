@@ -0,0 +1,189 @@
+// crates/tui/src/ui/downloads.rs
+//! Downloads view rendering
+
+use crate::state::{format_duration, AppState};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, Paragraph},
+    Frame,
+};
+use std::path::Path;
+use std::time::Duration;
+use storystream_network::{DownloadInfo, DownloadRecord, DownloadStatus};
+
+/// Renders the downloads view
+pub fn render(frame: &mut Frame, area: Rect, state: &AppState, theme: &crate::theme::Theme) {
+    let tasks = state.downloads.tasks.len() as u16;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(tasks.clamp(1, 8) + 2), // Active and queued
+            Constraint::Min(0),                        // History
+            Constraint::Length(3),                     // Help
+        ])
+        .split(area);
+
+    render_tasks(frame, chunks[0], state, theme);
+    render_history(frame, chunks[1], state, theme);
+    render_downloads_help(frame, chunks[2], theme);
+}
+
+/// Renders running, queued and paused downloads
+fn render_tasks(frame: &mut Frame, area: Rect, state: &AppState, theme: &crate::theme::Theme) {
+    let items: Vec<ListItem> = if state.downloads.tasks.is_empty() {
+        vec![ListItem::new(Span::styled(
+            "No active downloads",
+            theme.text_secondary_style(),
+        ))]
+    } else {
+        state
+            .downloads
+            .tasks
+            .iter()
+            .enumerate()
+            .map(|(i, task)| {
+                let style = if i == state.selected_item {
+                    theme.highlight_style()
+                } else {
+                    theme.text_style()
+                };
+                ListItem::new(Line::from(Span::styled(task_line(task), style)))
+            })
+            .collect()
+    };
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.border_color()))
+                .title(format!("Active & Queued ({})", state.downloads.tasks.len())),
+        )
+        .style(theme.text_style());
+
+    frame.render_widget(list, area);
+}
+
+/// Renders finished downloads, with the error of each failure
+fn render_history(frame: &mut Frame, area: Rect, state: &AppState, theme: &crate::theme::Theme) {
+    let offset = state.downloads.tasks.len();
+    let items: Vec<ListItem> = state
+        .downloads
+        .history
+        .iter()
+        .enumerate()
+        .map(|(i, record)| {
+            let style = if offset + i == state.selected_item {
+                theme.highlight_style()
+            } else {
+                theme.text_style()
+            };
+
+            let mut lines = vec![Line::from(Span::styled(record_line(record), style))];
+            if let Some(error) = &record.error {
+                lines.push(Line::from(Span::styled(
+                    format!("  {}", error),
+                    theme.error_style(),
+                )));
+            }
+            if let Some(mirror) = record.source_url.as_ref().filter(|s| **s != record.url) {
+                lines.push(Line::from(Span::styled(
+                    format!("  via {}", mirror),
+                    theme.text_secondary_style(),
+                )));
+            }
+            ListItem::new(lines)
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.border_color()))
+                .title(format!("History ({})", state.downloads.history.len())),
+        )
+        .style(theme.text_style());
+
+    frame.render_widget(list, area);
+}
+
+/// Renders downloads help
+fn render_downloads_help(frame: &mut Frame, area: Rect, theme: &crate::theme::Theme) {
+    let help = Paragraph::new("↑/↓: Navigate | r: Retry failed download")
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.border_color())),
+        )
+        .style(theme.text_secondary_style());
+
+    frame.render_widget(help, area);
+}
+
+fn task_line(task: &DownloadInfo) -> String {
+    let status = match &task.status {
+        DownloadStatus::InProgress => "⬇ Downloading",
+        DownloadStatus::Queued => "⏳ Queued",
+        DownloadStatus::Paused => "⏸ Paused",
+        DownloadStatus::Completed => "✓ Done",
+        DownloadStatus::Failed(_) => "✗ Failed",
+        DownloadStatus::Cancelled => "Cancelled",
+    };
+    format!(
+        "{:<14} {} (attempt {})",
+        status,
+        file_name(&task.destination),
+        task.attempts
+    )
+}
+
+fn record_line(record: &DownloadRecord) -> String {
+    let outcome = match record.error_code {
+        _ if !record.is_failed() => "✓".to_string(),
+        Some(code) => format!("✗ {}", code),
+        None => "✗".to_string(),
+    };
+    format!(
+        "{:<5} {}  {} · {} · {} attempt(s) · {}",
+        outcome,
+        file_name(&record.destination),
+        format_size(record.bytes),
+        format_duration(Duration::from_millis(record.duration_ms)),
+        record.attempts,
+        record
+            .finished_at
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M")
+    )
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// Human-readable byte count
+pub(crate) fn format_size(bytes: u64) -> String {
+    const MB: u64 = 1024 * 1024;
+    if bytes >= MB {
+        format!("{:.1} MB", bytes as f64 / MB as f64)
+    } else {
+        format!("{} KB", bytes.div_ceil(1024))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0 KB");
+        assert_eq!(format_size(1500), "2 KB");
+        assert_eq!(format_size(5 * 1024 * 1024 + 512 * 1024), "5.5 MB");
+    }
+}
@@ -1,14 +1,23 @@
 // crates/tui/src/ui/help.rs
 //! Enhanced help view with detailed examples
+//!
+//! The guide is split into [`HelpSection`]s shown one at a time under a
+//! strip of tabs, each scrolled on its own with
+//! [`AppState::help_scroll`].
 
-use crate::state::AppState;
+use crate::{
+    actions::Action,
+    keymap::KeyMap,
+    state::{AppState, View},
+};
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Layout, Margin, Rect},
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem},
+    widgets::{Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Tabs},
     Frame,
 };
+use unicode_width::UnicodeWidthStr;
 
 /// Help sections
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -58,471 +67,888 @@ impl HelpSection {
             HelpSection::Examples => "Examples",
         }
     }
+
+    /// The section after this one, wrapping around to the first
+    pub fn next(self) -> Self {
+        let sections = Self::all();
+        let index = sections.iter().position(|s| *s == self).unwrap_or(0);
+        sections[(index + 1) % sections.len()]
+    }
+
+    /// The section before this one, wrapping around to the last
+    pub fn previous(self) -> Self {
+        let sections = Self::all();
+        let index = sections.iter().position(|s| *s == self).unwrap_or(0);
+        sections[(index + sections.len() - 1) % sections.len()]
+    }
+}
+
+/// Renders the current help section under a strip of section tabs
+///
+/// Keys of actions are the ones bound now, so keys changed in the config
+/// file's `[keybindings]` section are shown as changed.
+pub fn render(frame: &mut Frame, area: Rect, state: &AppState, theme: &crate::theme::Theme) {
+    let [tabs_area, body] = split(area);
+    render_tabs(frame, tabs_area, state.help_section, theme);
+
+    let lines = section_lines(state.help_section, &state.keymap, theme);
+    let visible = visible_lines(area);
+    let last = lines.len().saturating_sub(visible);
+    let offset = state.help_scroll.min(last);
+    let title = format!(
+        "❓ {} ({}/{}) · ↑/↓ PgUp/PgDn: Scroll · ←/→: Section",
+        state.help_section.title(),
+        offset + visible.min(lines.len()),
+        lines.len()
+    );
+    let help = Paragraph::new(lines)
+        .scroll((u16::try_from(offset).unwrap_or(u16::MAX), 0))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.border_color()))
+                .title(title),
+        );
+    frame.render_widget(help, body);
+
+    if last > 0 {
+        let mut scrollbar = ScrollbarState::new(last + 1)
+            .position(offset)
+            .viewport_content_length(visible);
+        frame.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight).style(theme.accent_style()),
+            body.inner(Margin {
+                horizontal: 0,
+                vertical: 1,
+            }),
+            &mut scrollbar,
+        );
+    }
+}
+
+/// Lines of help text in view when the help view fills `area`
+pub fn visible_lines(area: Rect) -> usize {
+    usize::from(split(area)[1].height.saturating_sub(2))
+}
+
+/// The largest scroll offset of the current section with `visible` lines
+/// in view
+pub fn last_scroll(state: &AppState, theme: &crate::theme::Theme, visible: usize) -> usize {
+    section_lines(state.help_section, &state.keymap, theme)
+        .len()
+        .saturating_sub(visible)
 }
 
-/// Renders the help view with sections
-pub fn render(frame: &mut Frame, area: Rect, _state: &AppState, theme: &crate::theme::Theme) {
-    // For now, show scrollable help with all sections
+/// Splits the help area into the section tabs and the text under them
+fn split(area: Rect) -> [Rect; 2] {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Min(0)])
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
         .split(area);
-
-    render_all_help(frame, chunks[0], theme);
+    [chunks[0], chunks[1]]
 }
 
-/// Renders comprehensive help content
-fn render_all_help(frame: &mut Frame, area: Rect, theme: &crate::theme::Theme) {
-    let help_content = vec![
-        // Header
-        Line::from(vec![Span::styled(
-            "═══════════════════════════════════════════════════════════════",
-            theme.accent_style(),
-        )]),
-        Line::from(vec![Span::styled(
-            "        ♪ STORYSTREAM COMPLETE USER GUIDE ♪",
-            theme.highlight_style(),
-        )]),
-        Line::from(vec![Span::styled(
-            "═══════════════════════════════════════════════════════════════",
-            theme.accent_style(),
-        )]),
-        Line::from(""),
-        // GENERAL
-        section_header("1. GENERAL NAVIGATION", theme),
-        Line::from(""),
-        help_item("q / Ctrl+C", "Quit application", theme),
-        help_item(
-            "Tab",
-            "Switch between views (Library → Player → Bookmarks → ...)",
-            theme,
-        ),
-        help_item("Shift+Tab", "Switch views in reverse", theme),
-        help_item("h", "Show/hide this help screen", theme),
-        help_item("t", "Cycle through color themes", theme),
-        help_item("Esc", "Cancel current operation or go back", theme),
-        Line::from(""),
-        example_box(
-            "Example: Press Tab repeatedly to cycle through all views",
-            theme,
-        ),
-        Line::from(""),
-        // LIBRARY
-        section_header("2. LIBRARY VIEW 📚", theme),
-        Line::from(""),
-        help_item("↑ / k", "Move selection up", theme),
-        help_item("↓ / j", "Move selection down", theme),
-        help_item("Enter", "Play selected audiobook", theme),
-        help_item("s", "Sync library with other devices", theme),
-        help_item("i", "Show detailed info about selected book", theme),
-        help_item("f", "Toggle favorite status", theme),
-        help_item("d", "Delete book (soft delete)", theme),
-        help_item("/", "Open search (or switch to Search view)", theme),
-        Line::from(""),
-        example_box("Example: Use ↑/↓ to browse, Enter to start playing", theme),
-        Line::from(""),
-        // PLAYER
-        section_header("3. PLAYER CONTROLS ▶️", theme),
-        Line::from(""),
-        subsection("Playback Control:", theme),
-        help_item("Space", "Play/Pause toggle", theme),
-        help_item("Enter", "Play (if paused)", theme),
-        help_item("p", "Pause", theme),
-        help_item(".", "Stop playback", theme),
-        Line::from(""),
-        subsection("Seeking:", theme),
-        help_item("←", "Seek backward 10 seconds", theme),
-        help_item("→", "Seek forward 10 seconds", theme),
-        help_item("Shift+←", "Seek backward 30 seconds", theme),
-        help_item("Shift+→", "Seek forward 30 seconds", theme),
-        help_item("Home", "Jump to beginning", theme),
-        help_item("End", "Jump to end", theme),
-        Line::from(""),
-        subsection("Speed Control:", theme),
-        help_item("[", "Decrease speed by 0.1x (min: 0.5x)", theme),
-        help_item("]", "Increase speed by 0.1x (max: 3.0x)", theme),
-        help_item("Shift+[", "Set speed to 0.5x", theme),
-        help_item("Shift+]", "Set speed to 3.0x", theme),
-        help_item("\\", "Reset speed to 1.0x", theme),
-        Line::from(""),
-        subsection("Volume Control:", theme),
-        help_item("+ / =", "Increase volume by 10%", theme),
-        help_item("-", "Decrease volume by 10%", theme),
-        help_item("0", "Mute/Unmute", theme),
-        Line::from(""),
-        subsection("Chapter Navigation:", theme),
-        help_item("n", "Next chapter", theme),
-        help_item("p / b", "Previous chapter", theme),
-        help_item("Ctrl+n", "Skip to last chapter", theme),
-        help_item("Ctrl+p", "Go to first chapter", theme),
-        help_item("1-9", "Jump to chapter 1-9", theme),
-        Line::from(""),
-        example_box(
-            "Example: Press Space to pause, then → → → to skip ahead 30s",
-            theme,
-        ),
-        Line::from(""),
-        // BOOKMARKS
-        section_header("4. BOOKMARKS 🔖", theme),
-        Line::from(""),
-        help_item("b", "Add bookmark at current position", theme),
-        help_item("Shift+B", "Add bookmark with custom note", theme),
-        help_item("Enter", "Jump to selected bookmark", theme),
-        help_item("d", "Delete selected bookmark", theme),
-        help_item("e", "Edit bookmark note/title", theme),
-        help_item("↑/↓", "Navigate bookmarks", theme),
-        help_item("Ctrl+e", "Export bookmarks to file", theme),
-        Line::from(""),
-        example_box(
-            "Example: While listening, press 'b' to bookmark important quotes",
-            theme,
-        ),
-        Line::from(""),
-        // SEARCH
-        section_header("5. SEARCH 🔍", theme),
-        Line::from(""),
-        help_item("/", "Open search from any view", theme),
-        help_item("Type text", "Search as you type", theme),
-        help_item("↑/↓", "Navigate search results", theme),
-        help_item("Enter", "Open selected result", theme),
-        help_item("Esc", "Clear search and return", theme),
-        help_item("Ctrl+f", "Focus search box", theme),
-        Line::from(""),
-        subsection("Search Examples:", theme),
-        Line::from(vec![
-            Span::styled("  • ", theme.text_secondary_style()),
-            Span::styled("'tolkien'", theme.highlight_style()),
-            Span::styled(" - Find all books by Tolkien", theme.text_style()),
-        ]),
-        Line::from(vec![
-            Span::styled("  • ", theme.text_secondary_style()),
-            Span::styled("'1984'", theme.highlight_style()),
-            Span::styled(" - Find book by title", theme.text_style()),
-        ]),
-        Line::from(vec![
-            Span::styled("  • ", theme.text_secondary_style()),
-            Span::styled("'sci-fi'", theme.highlight_style()),
-            Span::styled(" - Search in genres", theme.text_style()),
-        ]),
-        Line::from(""),
-        // PLAYLISTS
-        section_header("6. PLAYLISTS 📋", theme),
-        Line::from(""),
-        help_item("n", "Create new playlist", theme),
-        help_item("a", "Add current book to playlist", theme),
-        help_item("r", "Remove selected from playlist", theme),
-        help_item("↑/↓", "Navigate playlists/items", theme),
-        help_item("Enter", "Play playlist", theme),
-        help_item("Shift+Enter", "Shuffle and play", theme),
-        help_item("e", "Edit playlist", theme),
-        help_item("d", "Delete playlist", theme),
-        Line::from(""),
-        example_box(
-            "Example: Create a 'Bedtime Stories' playlist with calming books",
-            theme,
-        ),
-        Line::from(""),
-        // STATISTICS
-        section_header("7. STATISTICS 📊", theme),
-        Line::from(""),
-        help_item("r", "Refresh statistics", theme),
-        help_item("↑/↓", "Scroll through stats", theme),
-        help_item("e", "Export stats to CSV", theme),
-        Line::from(""),
-        subsection("Statistics Include:", theme),
-        Line::from(vec![
-            Span::styled("  • ", theme.text_secondary_style()),
-            Span::raw("Total listening time and books completed"),
-        ]),
-        Line::from(vec![
-            Span::styled("  • ", theme.text_secondary_style()),
-            Span::raw("Most listened books and authors"),
-        ]),
-        Line::from(vec![
-            Span::styled("  • ", theme.text_secondary_style()),
-            Span::raw("Listening trends and patterns"),
-        ]),
-        Line::from(vec![
-            Span::styled("  • ", theme.text_secondary_style()),
-            Span::raw("Completion rates and favorites"),
-        ]),
-        Line::from(""),
-        // SETTINGS
-        section_header("8. SETTINGS ⚙️", theme),
-        Line::from(""),
-        help_item("↑/↓", "Navigate settings", theme),
-        help_item("Enter", "Edit selected setting", theme),
-        help_item("Space", "Toggle boolean settings", theme),
-        help_item("←/→", "Adjust numeric values", theme),
-        help_item("t", "Cycle color themes", theme),
-        help_item("r", "Reset all settings to defaults", theme),
-        Line::from(""),
-        subsection("Configurable Settings:", theme),
-        Line::from(vec![
-            Span::styled("  • ", theme.text_secondary_style()),
-            Span::raw("Default playback speed and volume"),
-        ]),
-        Line::from(vec![
-            Span::styled("  • ", theme.text_secondary_style()),
-            Span::raw("Auto-save interval and resume behavior"),
-        ]),
-        Line::from(vec![
-            Span::styled("  • ", theme.text_secondary_style()),
-            Span::raw("Library paths and scan settings"),
-        ]),
-        Line::from(vec![
-            Span::styled("  • ", theme.text_secondary_style()),
-            Span::raw("Sync preferences and conflict resolution"),
-        ]),
-        Line::from(vec![
-            Span::styled("  • ", theme.text_secondary_style()),
-            Span::raw("Theme and appearance"),
-        ]),
-        Line::from(""),
-        // MOUSE CONTROLS
-        section_header("9. MOUSE CONTROLS 🖱️", theme),
-        Line::from(""),
-        help_item("Click", "Select items in lists", theme),
-        help_item("Double-click", "Activate/play selected item", theme),
-        help_item("Right-click", "Open context menu", theme),
-        help_item("Scroll wheel", "Scroll through lists", theme),
-        help_item("Click on tabs", "Switch views", theme),
-        help_item("Click progress bar", "Seek to position", theme),
-        help_item("Drag progress bar", "Scrub through audio", theme),
-        Line::from(""),
-        example_box(
-            "Example: Click on a book in the library to select it",
-            theme,
-        ),
-        Line::from(""),
-        // THEMES
-        section_header("10. COLOR THEMES 🎨", theme),
-        Line::from(""),
-        help_item("t", "Cycle to next theme", theme),
-        Line::from(""),
-        subsection("Available Themes:", theme),
-        Line::from(vec![
-            Span::styled("  • ", theme.text_secondary_style()),
-            Span::styled("Dark", theme.highlight_style()),
-            Span::raw(" - Classic dark theme (default)"),
-        ]),
-        Line::from(vec![
-            Span::styled("  • ", theme.text_secondary_style()),
-            Span::styled("Light", theme.highlight_style()),
-            Span::raw(" - Light theme for daytime use"),
-        ]),
-        Line::from(vec![
-            Span::styled("  • ", theme.text_secondary_style()),
-            Span::styled("High Contrast", theme.highlight_style()),
-            Span::raw(" - Maximum readability"),
-        ]),
-        Line::from(vec![
-            Span::styled("  • ", theme.text_secondary_style()),
-            Span::styled("Solarized Dark/Light", theme.highlight_style()),
-            Span::raw(" - Popular Solarized themes"),
-        ]),
-        Line::from(vec![
-            Span::styled("  • ", theme.text_secondary_style()),
-            Span::styled("Dracula", theme.highlight_style()),
-            Span::raw(" - Modern dark theme"),
-        ]),
-        Line::from(vec![
-            Span::styled("  • ", theme.text_secondary_style()),
-            Span::styled("Nord", theme.highlight_style()),
-            Span::raw(" - Arctic-inspired theme"),
-        ]),
-        Line::from(vec![
-            Span::styled("  • ", theme.text_secondary_style()),
-            Span::styled("Monokai", theme.highlight_style()),
-            Span::raw(" - Sublime Text classic"),
-        ]),
-        Line::from(""),
-        // TIPS AND TRICKS
-        section_header("11. TIPS & TRICKS 💡", theme),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "Speed Listening:",
-            theme.text_secondary_style().add_modifier(Modifier::BOLD),
-        )]),
-        Line::from("  Increase speed gradually to 1.5x-2.0x for efficient listening"),
-        Line::from("  Perfect for catching up on backlogs!"),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "Bookmarking Strategy:",
-            theme.text_secondary_style().add_modifier(Modifier::BOLD),
-        )]),
-        Line::from("  Press 'b' whenever you hear something interesting"),
-        Line::from("  Use Shift+B to add detailed notes"),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "Smart Playlists:",
-            theme.text_secondary_style().add_modifier(Modifier::BOLD),
-        )]),
-        Line::from("  Create mood-based playlists (Relaxing, Exciting, etc.)"),
-        Line::from("  Use 'Recently Played' to resume your listening journey"),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "Sync Across Devices:",
-            theme.text_secondary_style().add_modifier(Modifier::BOLD),
-        )]),
-        Line::from("  Enable auto-sync in Settings"),
-        Line::from("  Start on laptop, continue on phone seamlessly"),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "Night Listening:",
-            theme.text_secondary_style().add_modifier(Modifier::BOLD),
-        )]),
-        Line::from("  Switch to a dark theme (press 't')"),
-        Line::from("  Reduce volume gradually as you fall asleep"),
-        Line::from(""),
-        // EXAMPLES
-        section_header("12. COMPLETE WORKFLOW EXAMPLES 📖", theme),
-        Line::from(""),
-        subsection("Example 1: Starting a New Book", theme),
-        Line::from(vec![
-            Span::styled("  1. ", theme.highlight_style()),
-            Span::raw("Press Tab until you reach Library view"),
-        ]),
-        Line::from(vec![
-            Span::styled("  2. ", theme.highlight_style()),
-            Span::raw("Use ↑/↓ to browse your collection"),
-        ]),
-        Line::from(vec![
-            Span::styled("  3. ", theme.highlight_style()),
-            Span::raw("Press Enter to start playing"),
-        ]),
-        Line::from(vec![
-            Span::styled("  4. ", theme.highlight_style()),
-            Span::raw("Adjust speed with [ or ] if desired"),
-        ]),
-        Line::from(vec![
-            Span::styled("  5. ", theme.highlight_style()),
-            Span::raw("Press 'b' to bookmark important moments"),
-        ]),
-        Line::from(""),
-        subsection("Example 2: Resuming Your Audiobook", theme),
-        Line::from(vec![
-            Span::styled("  1. ", theme.highlight_style()),
-            Span::raw("Open StoryStream (position auto-saved)"),
-        ]),
-        Line::from(vec![
-            Span::styled("  2. ", theme.highlight_style()),
-            Span::raw("Go to Player view (Tab)"),
-        ]),
-        Line::from(vec![
-            Span::styled("  3. ", theme.highlight_style()),
-            Span::raw("Press Space to resume playback"),
-        ]),
-        Line::from(""),
-        subsection("Example 3: Creating a Playlist", theme),
-        Line::from(vec![
-            Span::styled("  1. ", theme.highlight_style()),
-            Span::raw("Switch to Playlists view"),
-        ]),
-        Line::from(vec![
-            Span::styled("  2. ", theme.highlight_style()),
-            Span::raw("Press 'n' to create new playlist"),
-        ]),
-        Line::from(vec![
-            Span::styled("  3. ", theme.highlight_style()),
-            Span::raw("Name it (e.g., 'Sci-Fi Favorites')"),
-        ]),
-        Line::from(vec![
-            Span::styled("  4. ", theme.highlight_style()),
-            Span::raw("Go to Library, select books, press 'a' to add"),
-        ]),
-        Line::from(vec![
-            Span::styled("  5. ", theme.highlight_style()),
-            Span::raw("Return to Playlists and press Enter to play"),
-        ]),
-        Line::from(""),
-        subsection("Example 4: Searching Your Library", theme),
-        Line::from(vec![
-            Span::styled("  1. ", theme.highlight_style()),
-            Span::raw("Press '/' from any view"),
-        ]),
-        Line::from(vec![
-            Span::styled("  2. ", theme.highlight_style()),
-            Span::raw("Type author name (e.g., 'tolkien')"),
-        ]),
-        Line::from(vec![
-            Span::styled("  3. ", theme.highlight_style()),
-            Span::raw("Use ↑/↓ to browse results"),
-        ]),
-        Line::from(vec![
-            Span::styled("  4. ", theme.highlight_style()),
-            Span::raw("Press Enter to play selected book"),
-        ]),
-        Line::from(""),
-        subsection("Example 5: Customizing Your Experience", theme),
-        Line::from(vec![
-            Span::styled("  1. ", theme.highlight_style()),
-            Span::raw("Go to Settings view"),
-        ]),
-        Line::from(vec![
-            Span::styled("  2. ", theme.highlight_style()),
-            Span::raw("Navigate to 'Default Speed'"),
-        ]),
-        Line::from(vec![
-            Span::styled("  3. ", theme.highlight_style()),
-            Span::raw("Use ←/→ to adjust (e.g., 1.25x)"),
-        ]),
-        Line::from(vec![
-            Span::styled("  4. ", theme.highlight_style()),
-            Span::raw("Press 't' to cycle themes until you find one you like"),
-        ]),
-        Line::from(vec![
-            Span::styled("  5. ", theme.highlight_style()),
-            Span::raw("Settings are automatically saved"),
-        ]),
-        Line::from(""),
-        // TROUBLESHOOTING
-        section_header("13. TROUBLESHOOTING 🔧", theme),
-        Line::from(""),
-        subsection("Problem: Controls don't respond", theme),
-        Line::from("  → Ensure the terminal window has focus"),
-        Line::from("  → Try pressing Esc to cancel any pending operation"),
-        Line::from(""),
-        subsection("Problem: Display looks wrong", theme),
-        Line::from("  → Resize terminal window (minimum 80x24)"),
-        Line::from("  → Try different theme (press 't')"),
-        Line::from("  → Restart the application"),
-        Line::from(""),
-        subsection("Problem: Audio doesn't play", theme),
-        Line::from("  → Check that the audio file exists"),
-        Line::from("  → Verify volume isn't muted (press '+' to increase)"),
-        Line::from("  → Check system audio settings"),
-        Line::from(""),
-        // FOOTER
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "═══════════════════════════════════════════════════════════════",
-            theme.accent_style(),
-        )]),
-        Line::from(vec![Span::styled(
-            "       Press 'h' or Esc to close help · Press Tab to navigate",
-            theme.text_secondary_style(),
-        )]),
-        Line::from(vec![Span::styled(
-            "═══════════════════════════════════════════════════════════════",
-            theme.accent_style(),
-        )]),
-    ];
-
-    let help = List::new(
-        help_content
-            .into_iter()
-            .map(|line| ListItem::new(line))
-            .collect::<Vec<_>>(),
-    )
-    .block(
-        Block::default()
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(theme.border_color()))
-            .title("❓ Complete User Guide (Scroll with ↑/↓ or Mouse Wheel)"),
-    );
+/// Renders the section tabs, dropping tabs on the left until the current
+/// one fits
+fn render_tabs(frame: &mut Frame, area: Rect, current: HelpSection, theme: &crate::theme::Theme) {
+    let sections = HelpSection::all();
+    let index = sections.iter().position(|s| *s == current).unwrap_or(0);
+    let titles: Vec<String> = sections
+        .iter()
+        .enumerate()
+        .map(|(i, section)| match i {
+            0..=8 => format!("{} {}", i + 1, section.title()),
+            _ => section.title().to_string(),
+        })
+        .collect();
+    // Each tab takes a space either side and a divider
+    let room = usize::from(area.width.saturating_sub(2));
+    let width = |titles: &[String]| titles.iter().map(|t| t.width() + 3).sum::<usize>();
+    let first = (0..=index)
+        .find(|&first| width(&titles[first..=index]) <= room)
+        .unwrap_or(index);
 
-    frame.render_widget(help, area);
+    let tabs = Tabs::new(titles[first..].to_vec())
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.border_color())),
+        )
+        .select(index - first)
+        .style(theme.text_secondary_style())
+        .highlight_style(theme.highlight_style().add_modifier(Modifier::REVERSED));
+    frame.render_widget(tabs, area);
+}
+
+/// The help text of one section
+fn section_lines(
+    section: HelpSection,
+    keys: &KeyMap,
+    theme: &crate::theme::Theme,
+) -> Vec<Line<'static>> {
+    match section {
+        HelpSection::General => vec![
+            section_header("GENERAL NAVIGATION", theme),
+            Line::from(""),
+            action_item(
+                Action::Quit,
+                "Quit application (Ctrl+C also quits)",
+                keys,
+                theme,
+            ),
+            action_item(
+                Action::NextView,
+                "Switch between views (Library → Player → Bookmarks → ...)",
+                keys,
+                theme,
+            ),
+            help_item("Shift+Tab", "Switch views in reverse", theme),
+            action_item(
+                Action::ToggleHelp,
+                "Show/hide this help screen",
+                keys,
+                theme,
+            ),
+            action_item(
+                Action::ToggleTheme,
+                "Cycle through color themes",
+                keys,
+                theme,
+            ),
+            help_item("Ctrl+P", "Find and run any action by name", theme),
+            help_item("Esc", "Cancel current operation or go back", theme),
+            Line::from(""),
+            subsection("In this help:", theme),
+            help_item("←/→ or 1-9", "Show another section", theme),
+            help_item("↑/↓ or j/k", "Scroll a line", theme),
+            help_item("PgUp/PgDn", "Scroll a page", theme),
+            help_item("Home/End", "Go to the top or bottom", theme),
+            Line::from(""),
+            example_box(
+                "Example: Press Tab repeatedly to cycle through all views",
+                theme,
+            ),
+            Line::from(""),
+            section_header("TROUBLESHOOTING 🔧", theme),
+            Line::from(""),
+            subsection("Problem: Controls don't respond", theme),
+            Line::from("  → Ensure the terminal window has focus"),
+            Line::from("  → Try pressing Esc to cancel any pending operation"),
+            Line::from(""),
+            subsection("Problem: Display looks wrong", theme),
+            Line::from("  → Resize terminal window (minimum 80x24)"),
+            Line::from("  → Try different theme (press 't')"),
+            Line::from("  → Restart the application"),
+            Line::from(""),
+            subsection("Problem: Audio doesn't play", theme),
+            Line::from("  → Check that the audio file exists"),
+            Line::from("  → Verify volume isn't muted (press '+' to increase)"),
+            Line::from("  → Check system audio settings"),
+        ],
+        HelpSection::Library => vec![
+            section_header("LIBRARY VIEW 📚", theme),
+            Line::from(""),
+            help_item("↑ / k", "Move selection up", theme),
+            help_item("↓ / j", "Move selection down", theme),
+            help_item("Enter", "Play selected audiobook", theme),
+            help_item("s", "Sync library with other devices", theme),
+            action_item(
+                Action::ShowBookDetail,
+                "Show detailed info about selected book",
+                keys,
+                theme,
+            ),
+            action_item(
+                Action::ToggleFavorite,
+                "Toggle favorite status",
+                keys,
+                theme,
+            ),
+            action_item(
+                Action::DeleteBook,
+                "Move book to the trash (asks first)",
+                keys,
+                theme,
+            ),
+            action_item(
+                Action::Search,
+                "Open search (or switch to Search view)",
+                keys,
+                theme,
+            ),
+            Line::from(""),
+            subsection("Editing Details (e in the info popup):", theme),
+            help_item("↑ / ↓", "Select title, author, narrator or series", theme),
+            help_item("Enter", "Edit selected field", theme),
+            help_item("w", "Also write changes to the file's tags", theme),
+            help_item("Esc", "Stop editing", theme),
+            subsection("Replacing the File (r in the info popup):", theme),
+            help_item(
+                "r",
+                "Pick a better copy; position and bookmarks carry over",
+                theme,
+            ),
+            Line::from(""),
+            example_box("Example: Use ↑/↓ to browse, Enter to start playing", theme),
+            Line::from(""),
+            section_header("DOWNLOADS ⬇️", theme),
+            Line::from(""),
+            help_item("↑/↓", "Navigate downloads", theme),
+            action_item(
+                Action::RetryDownload,
+                "Retry the selected failed download",
+                keys,
+                theme,
+            ),
+            Line::from(vec![
+                Span::styled("  • ", theme.text_secondary_style()),
+                Span::raw("Active and queued downloads, then finished ones"),
+            ]),
+            Line::from(vec![
+                Span::styled("  • ", theme.text_secondary_style()),
+                Span::raw("Failures show the HTTP status and error message"),
+            ]),
+        ],
+        HelpSection::Player => vec![
+            section_header("PLAYER CONTROLS ▶️", theme),
+            Line::from(""),
+            subsection("Playback Control:", theme),
+            action_item(Action::TogglePlayback, "Play/Pause toggle", keys, theme),
+            help_item("Enter", "Play (if paused)", theme),
+            help_item("p", "Pause", theme),
+            help_item(".", "Stop playback", theme),
+            Line::from(""),
+            subsection("Seeking:", theme),
+            action_item(
+                Action::SeekBackward,
+                "Seek backward 10 seconds",
+                keys,
+                theme,
+            ),
+            action_item(Action::SeekForward, "Seek forward 10 seconds", keys, theme),
+            help_item("Shift+←", "Seek backward 30 seconds", theme),
+            help_item("Shift+→", "Seek forward 30 seconds", theme),
+            help_item("Home", "Jump to beginning", theme),
+            help_item("End", "Jump to end", theme),
+            Line::from(""),
+            subsection("Speed Control:", theme),
+            action_item(
+                Action::SpeedDown,
+                "Decrease speed by 0.1x (min: 0.5x)",
+                keys,
+                theme,
+            ),
+            action_item(
+                Action::SpeedUp,
+                "Increase speed by 0.1x (max: 3.0x)",
+                keys,
+                theme,
+            ),
+            help_item("Shift+[", "Set speed to 0.5x", theme),
+            help_item("Shift+]", "Set speed to 3.0x", theme),
+            help_item("\\", "Reset speed to 1.0x", theme),
+            action_item(
+                Action::ToggleSpeedRamp,
+                "Ramp speed up gradually, or stop ramping",
+                keys,
+                theme,
+            ),
+            Line::from(""),
+            subsection("Volume Control:", theme),
+            action_item(Action::VolumeUp, "Increase volume by 10%", keys, theme),
+            action_item(Action::VolumeDown, "Decrease volume by 10%", keys, theme),
+            help_item("0", "Mute/Unmute", theme),
+            Line::from(""),
+            subsection("Equalizer:", theme),
+            action_item(
+                Action::CycleEqualizer,
+                "Next equalizer preset (remembered per book)",
+                keys,
+                theme,
+            ),
+            Line::from(""),
+            subsection("Sleep Timer:", theme),
+            action_item(
+                Action::CycleSleepTimer,
+                "15, 30, 45, 60 min, end of chapter, then off",
+                keys,
+                theme,
+            ),
+            action_item(
+                Action::ExtendSleepTimer,
+                "Add 15 minutes to a running timer",
+                keys,
+                theme,
+            ),
+            Line::from(""),
+            subsection("Sync (with app.sync_folder set):", theme),
+            help_item("g", "Jump to where another device is", theme),
+            help_item("Esc", "Dismiss the sync banner", theme),
+            Line::from(""),
+            subsection("Up Next (when a book ends):", theme),
+            help_item("Enter", "Play the suggested next book", theme),
+            help_item("Esc", "Dismiss the suggestion", theme),
+            Line::from(""),
+            subsection("Chapter Navigation:", theme),
+            action_item(Action::NextChapter, "Next chapter", keys, theme),
+            action_item(
+                Action::PreviousChapter,
+                "Restart chapter, or previous one in its first 3s",
+                keys,
+                theme,
+            ),
+            help_item("Ctrl+n", "Skip to last chapter", theme),
+            help_item("Ctrl+p", "Go to first chapter", theme),
+            help_item("1-9", "Jump to chapter 1-9", theme),
+            Line::from(""),
+            subsection("Chapter Editing (e in Player view):", theme),
+            help_item("r", "Rename selected chapter", theme),
+            help_item("< / >", "Move chapter start by 1s", theme),
+            help_item("s", "Split chapter at current position", theme),
+            help_item("m", "Merge with next chapter", theme),
+            help_item("w", "Save chapters", theme),
+            help_item("x", "Export chapters as a CUE sheet", theme),
+            help_item("a", "Suggest chapters from long silences", theme),
+            help_item(
+                "Space / Enter",
+                "Accept a suggestion / apply and save",
+                theme,
+            ),
+            help_item("Esc", "Stop editing", theme),
+            Line::from(""),
+            example_box(
+                "Example: Press Space to pause, then → → → to skip ahead 30s",
+                theme,
+            ),
+        ],
+        HelpSection::Bookmarks => vec![
+            section_header("BOOKMARKS 🔖", theme),
+            Line::from(""),
+            action_item(
+                Action::AddBookmark,
+                "Add bookmark at current position",
+                keys,
+                theme,
+            ),
+            help_item("Shift+B", "Add bookmark with custom note", theme),
+            help_item("Enter", "Jump to selected bookmark", theme),
+            action_item(
+                Action::DeleteBookmark,
+                "Delete selected bookmark",
+                keys,
+                theme,
+            ),
+            action_item(
+                Action::ClearAutoBookmarks,
+                "Clear the book's auto-bookmarks",
+                keys,
+                theme,
+            ),
+            help_item("e", "Edit bookmark note/title", theme),
+            help_item("↑/↓", "Navigate bookmarks", theme),
+            action_item(
+                Action::ExportBookmarks,
+                "Export bookmarks as Markdown",
+                keys,
+                theme,
+            ),
+            action_item(
+                Action::ShareBookmark,
+                "Copy a link to the selected bookmark",
+                keys,
+                theme,
+            ),
+            Line::from(""),
+            example_box(
+                "Example: While listening, press 'b' to bookmark important quotes",
+                theme,
+            ),
+            example_box(
+                "🕑 marks auto-bookmarks, placed after a long pause and when you quit",
+                theme,
+            ),
+        ],
+        HelpSection::Search => vec![
+            section_header("SEARCH 🔍", theme),
+            Line::from(""),
+            action_item(Action::Search, "Open search from any view", keys, theme),
+            help_item("Type text", "Search as you type", theme),
+            help_item("↑/↓", "Navigate search results", theme),
+            help_item("Enter", "Open selected result", theme),
+            help_item("Esc", "Clear search and return", theme),
+            help_item("Ctrl+f", "Focus search box", theme),
+            action_item(
+                Action::FilterSearch,
+                "Filter by author, narrator, tag, length, format...",
+                keys,
+                theme,
+            ),
+            Line::from(""),
+            subsection("Search Filters:", theme),
+            help_item("↑/↓", "Choose a filter", theme),
+            help_item(
+                "Enter",
+                "Edit the filter, or toggle favorite/finished",
+                theme,
+            ),
+            help_item("x", "Clear the selected filter", theme),
+            help_item("c", "Clear all filters", theme),
+            help_item("Esc / F", "Close the filters", theme),
+            Line::from(""),
+            subsection("Search Examples:", theme),
+            Line::from(vec![
+                Span::styled("  • ", theme.text_secondary_style()),
+                Span::styled("'tolkien'", theme.highlight_style()),
+                Span::styled(" - Find all books by Tolkien", theme.text_style()),
+            ]),
+            Line::from(vec![
+                Span::styled("  • ", theme.text_secondary_style()),
+                Span::styled("'1984'", theme.highlight_style()),
+                Span::styled(" - Find book by title", theme.text_style()),
+            ]),
+            Line::from(vec![
+                Span::styled("  • ", theme.text_secondary_style()),
+                Span::styled("'sci-fi'", theme.highlight_style()),
+                Span::styled(" - Search in genres", theme.text_style()),
+            ]),
+        ],
+        HelpSection::Playlists => vec![
+            section_header("PLAYLISTS 📋", theme),
+            Line::from(""),
+            action_item(Action::NewPlaylist, "Create new playlist", keys, theme),
+            action_item(
+                Action::AddToPlaylist,
+                "Add the book selected in the library",
+                keys,
+                theme,
+            ),
+            action_item(
+                Action::OpenPlaylist,
+                "Show / hide the playlist's books",
+                keys,
+                theme,
+            ),
+            action_item(
+                Action::RemoveFromPlaylist,
+                "Remove selected book from playlist",
+                keys,
+                theme,
+            ),
+            action_item(
+                Action::MovePlaylistBookDown,
+                "Move selected book down",
+                keys,
+                theme,
+            ),
+            action_item(
+                Action::MovePlaylistBookUp,
+                "Move selected book up",
+                keys,
+                theme,
+            ),
+            help_item("↑/↓", "Navigate playlists/items", theme),
+            help_item("Enter", "Play playlist", theme),
+            action_item(Action::ShufflePlaylist, "Shuffle and play", keys, theme),
+            action_item(Action::DeletePlaylist, "Delete playlist", keys, theme),
+            Line::from(vec![
+                Span::styled("  • ", theme.text_secondary_style()),
+                Span::raw("⚡ Smart playlists choose their own books and cannot be edited"),
+            ]),
+            Line::from(""),
+            Line::from(vec![
+                Span::styled("  • ", theme.text_secondary_style()),
+                Span::raw("Feed subscriptions are listed below, with their unplayed episodes"),
+            ]),
+            Line::from(""),
+            example_box(
+                "Example: Create a 'Bedtime Stories' playlist with calming books",
+                theme,
+            ),
+        ],
+        HelpSection::Statistics => vec![
+            section_header("STATISTICS 📊", theme),
+            Line::from(""),
+            action_item(
+                Action::RefreshStatistics,
+                "Read the statistics again",
+                keys,
+                theme,
+            ),
+            action_item(
+                Action::ExportStatistics,
+                "Export the totals as CSV to the bookmark export folder",
+                keys,
+                theme,
+            ),
+            Line::from(""),
+            subsection("Statistics Include:", theme),
+            Line::from(vec![
+                Span::styled("  • ", theme.text_secondary_style()),
+                Span::raw("Total listening time and books completed"),
+            ]),
+            Line::from(vec![
+                Span::styled("  • ", theme.text_secondary_style()),
+                Span::raw("Most listened books and authors"),
+            ]),
+            Line::from(vec![
+                Span::styled("  • ", theme.text_secondary_style()),
+                Span::raw("Hours listened on each of the last 14 days"),
+            ]),
+            Line::from(vec![
+                Span::styled("  • ", theme.text_secondary_style()),
+                Span::raw("Completion rates and favorites"),
+            ]),
+        ],
+        HelpSection::Settings => vec![
+            section_header("SETTINGS ⚙️", theme),
+            Line::from(""),
+            help_item("↑/↓", "Navigate settings", theme),
+            help_item("Enter", "Type a new value, or flip / cycle it", theme),
+            help_item("Space", "Toggle on/off settings", theme),
+            help_item("←/→", "Step numbers, or cycle choices", theme),
+            action_item(Action::ToggleTheme, "Cycle color themes", keys, theme),
+            action_item(
+                Action::ResetSettings,
+                "Reset all settings to defaults (asks first)",
+                keys,
+                theme,
+            ),
+            Line::from(""),
+            subsection("Maintenance:", theme),
+            action_item(
+                Action::ImportLibrary,
+                "Import new files from the library folders",
+                keys,
+                theme,
+            ),
+            action_item(
+                Action::PreviewImport,
+                "Preview an import, then pick what to import",
+                keys,
+                theme,
+            ),
+            action_item(
+                Action::VerifyFiles,
+                "Verify book files against their stored hashes",
+                keys,
+                theme,
+            ),
+            action_item(
+                Action::VerifyFilesFully,
+                "Verify and fully decode book files",
+                keys,
+                theme,
+            ),
+            help_item("Esc", "Cancel a running import or verification", theme),
+            help_item(
+                "u / r / c",
+                "Update hash / Refresh metadata / Mark corrupt",
+                theme,
+            ),
+            action_item(
+                Action::FindDuplicates,
+                "Find books imported more than once",
+                keys,
+                theme,
+            ),
+            help_item(
+                "m / s",
+                "Merge the selected duplicates into the * copy / Skip them",
+                theme,
+            ),
+            action_item(
+                Action::PruneDownloads,
+                "Prune download history older than 30 days",
+                keys,
+                theme,
+            ),
+            action_item(Action::ClearCache, "Clear the disk cache", keys, theme),
+            action_item(
+                Action::FindDevices,
+                "Find devices on the local network",
+                keys,
+                theme,
+            ),
+            help_item("J/K", "Select the next / previous device", theme),
+            action_item(
+                Action::PairDevice,
+                "Pair with the selected device",
+                keys,
+                theme,
+            ),
+            action_item(
+                Action::ChooseOutputDevice,
+                "Choose the audio output device",
+                keys,
+                theme,
+            ),
+            Line::from(""),
+            subsection("Configurable Settings:", theme),
+            Line::from(vec![
+                Span::styled("  • ", theme.text_secondary_style()),
+                Span::raw("Default playback speed and volume"),
+            ]),
+            Line::from(vec![
+                Span::styled("  • ", theme.text_secondary_style()),
+                Span::raw("Auto-save interval and resume behavior"),
+            ]),
+            Line::from(vec![
+                Span::styled("  • ", theme.text_secondary_style()),
+                Span::raw("Library paths and scan settings"),
+            ]),
+            Line::from(vec![
+                Span::styled("  • ", theme.text_secondary_style()),
+                Span::raw("Sync preferences and conflict resolution"),
+            ]),
+            Line::from(vec![
+                Span::styled("  • ", theme.text_secondary_style()),
+                Span::raw("Theme and appearance"),
+            ]),
+            Line::from(""),
+            section_header("COLOR THEMES 🎨", theme),
+            Line::from(""),
+            action_item(Action::ToggleTheme, "Cycle to next theme", keys, theme),
+            Line::from(""),
+            subsection("Available Themes:", theme),
+            Line::from(vec![
+                Span::styled("  • ", theme.text_secondary_style()),
+                Span::styled("Dark", theme.highlight_style()),
+                Span::raw(" - Classic dark theme (default)"),
+            ]),
+            Line::from(vec![
+                Span::styled("  • ", theme.text_secondary_style()),
+                Span::styled("Light", theme.highlight_style()),
+                Span::raw(" - Light theme for daytime use"),
+            ]),
+            Line::from(vec![
+                Span::styled("  • ", theme.text_secondary_style()),
+                Span::styled("High Contrast", theme.highlight_style()),
+                Span::raw(" - Maximum readability"),
+            ]),
+            Line::from(vec![
+                Span::styled("  • ", theme.text_secondary_style()),
+                Span::styled("Solarized Dark/Light", theme.highlight_style()),
+                Span::raw(" - Popular Solarized themes"),
+            ]),
+            Line::from(vec![
+                Span::styled("  • ", theme.text_secondary_style()),
+                Span::styled("Dracula", theme.highlight_style()),
+                Span::raw(" - Modern dark theme"),
+            ]),
+            Line::from(vec![
+                Span::styled("  • ", theme.text_secondary_style()),
+                Span::styled("Nord", theme.highlight_style()),
+                Span::raw(" - Arctic-inspired theme"),
+            ]),
+            Line::from(vec![
+                Span::styled("  • ", theme.text_secondary_style()),
+                Span::styled("Monokai", theme.highlight_style()),
+                Span::raw(" - Sublime Text classic"),
+            ]),
+        ],
+        HelpSection::KeyboardShortcuts => {
+            let mut lines = vec![
+                section_header("KEYBOARD SHORTCUTS ⌨️", theme),
+                Line::from(""),
+                subsection("Moving around:", theme),
+                help_item("↑/↓ or j/k", "Move the selection", theme),
+                help_item("Enter", "Open or play the selection", theme),
+                help_item("Esc", "Cancel current operation or go back", theme),
+                help_item("Ctrl+P", "Find and run any action by name", theme),
+            ];
+            let views = [
+                None,
+                Some(View::Library),
+                Some(View::Player),
+                Some(View::Bookmarks),
+                Some(View::Search),
+                Some(View::Playlists),
+                Some(View::Statistics),
+                Some(View::Downloads),
+                Some(View::Settings),
+            ];
+            for view in views {
+                let heading = match view {
+                    Some(view) => format!("In the {:?} view:", view),
+                    None => "Actions everywhere:".to_string(),
+                };
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::styled(
+                    heading,
+                    theme.highlight_style().add_modifier(Modifier::BOLD),
+                )));
+                for action in Action::ALL.into_iter().filter(|a| a.view() == view) {
+                    lines.push(action_item(action, action.label(), keys, theme));
+                }
+            }
+            lines
+        }
+        HelpSection::MouseControls => vec![
+            section_header("MOUSE CONTROLS 🖱️", theme),
+            Line::from(""),
+            help_item("Click", "Select items in lists", theme),
+            help_item("Double-click", "Activate/play selected item", theme),
+            help_item("Right-click", "Open context menu", theme),
+            help_item("Scroll wheel", "Scroll through lists", theme),
+            help_item("Click on tabs", "Switch views", theme),
+            help_item("Click now playing bar", "Open the player", theme),
+            help_item("Click progress bar", "Seek to position", theme),
+            help_item(
+                "Drag progress bar",
+                "Scrub, snapping to chapters and bookmarks",
+                theme,
+            ),
+            help_item("Shift+drag", "Scrub without snapping", theme),
+            help_item(
+                "Click ┴ under the bar",
+                "Jump to that chapter's start",
+                theme,
+            ),
+            Line::from(""),
+            example_box(
+                "Example: Click on a book in the library to select it",
+                theme,
+            ),
+        ],
+        HelpSection::Examples => vec![
+            section_header("COMPLETE WORKFLOW EXAMPLES 📖", theme),
+            Line::from(""),
+            subsection("Example 1: Starting a New Book", theme),
+            Line::from(vec![
+                Span::styled("  1. ", theme.highlight_style()),
+                Span::raw("Press Tab until you reach Library view"),
+            ]),
+            Line::from(vec![
+                Span::styled("  2. ", theme.highlight_style()),
+                Span::raw("Use ↑/↓ to browse your collection"),
+            ]),
+            Line::from(vec![
+                Span::styled("  3. ", theme.highlight_style()),
+                Span::raw("Press Enter to start playing"),
+            ]),
+            Line::from(vec![
+                Span::styled("  4. ", theme.highlight_style()),
+                Span::raw("Adjust speed with [ or ] if desired"),
+            ]),
+            Line::from(vec![
+                Span::styled("  5. ", theme.highlight_style()),
+                Span::raw("Press 'b' to bookmark important moments"),
+            ]),
+            Line::from(""),
+            subsection("Example 2: Resuming Your Audiobook", theme),
+            Line::from(vec![
+                Span::styled("  1. ", theme.highlight_style()),
+                Span::raw("Open StoryStream (position auto-saved)"),
+            ]),
+            Line::from(vec![
+                Span::styled("  2. ", theme.highlight_style()),
+                Span::raw("Go to Player view (Tab)"),
+            ]),
+            Line::from(vec![
+                Span::styled("  3. ", theme.highlight_style()),
+                Span::raw("Press Space to resume playback"),
+            ]),
+            Line::from(""),
+            subsection("Example 3: Creating a Playlist", theme),
+            Line::from(vec![
+                Span::styled("  1. ", theme.highlight_style()),
+                Span::raw("Switch to Playlists view"),
+            ]),
+            Line::from(vec![
+                Span::styled("  2. ", theme.highlight_style()),
+                Span::raw("Press 'n' to create new playlist"),
+            ]),
+            Line::from(vec![
+                Span::styled("  3. ", theme.highlight_style()),
+                Span::raw("Name it (e.g., 'Sci-Fi Favorites')"),
+            ]),
+            Line::from(vec![
+                Span::styled("  4. ", theme.highlight_style()),
+                Span::raw("Go to Library, select books, press 'a' to add"),
+            ]),
+            Line::from(vec![
+                Span::styled("  5. ", theme.highlight_style()),
+                Span::raw("Return to Playlists and press Enter to play"),
+            ]),
+            Line::from(""),
+            subsection("Example 4: Searching Your Library", theme),
+            Line::from(vec![
+                Span::styled("  1. ", theme.highlight_style()),
+                Span::raw("Press '/' from any view"),
+            ]),
+            Line::from(vec![
+                Span::styled("  2. ", theme.highlight_style()),
+                Span::raw("Type author name (e.g., 'tolkien')"),
+            ]),
+            Line::from(vec![
+                Span::styled("  3. ", theme.highlight_style()),
+                Span::raw("Use ↑/↓ to browse results"),
+            ]),
+            Line::from(vec![
+                Span::styled("  4. ", theme.highlight_style()),
+                Span::raw("Press Enter to play selected book"),
+            ]),
+            Line::from(""),
+            subsection("Example 5: Customizing Your Experience", theme),
+            Line::from(vec![
+                Span::styled("  1. ", theme.highlight_style()),
+                Span::raw("Go to Settings view"),
+            ]),
+            Line::from(vec![
+                Span::styled("  2. ", theme.highlight_style()),
+                Span::raw("Navigate to 'Default Speed'"),
+            ]),
+            Line::from(vec![
+                Span::styled("  3. ", theme.highlight_style()),
+                Span::raw("Use ←/→ to adjust (e.g., 1.25x)"),
+            ]),
+            Line::from(vec![
+                Span::styled("  4. ", theme.highlight_style()),
+                Span::raw("Press 't' to cycle themes until you find one you like"),
+            ]),
+            Line::from(vec![
+                Span::styled("  5. ", theme.highlight_style()),
+                Span::raw("Settings are automatically saved"),
+            ]),
+            Line::from(""),
+            section_header("TIPS & TRICKS 💡", theme),
+            Line::from(""),
+            Line::from(vec![Span::styled(
+                "Speed Listening:",
+                theme.text_secondary_style().add_modifier(Modifier::BOLD),
+            )]),
+            Line::from("  Increase speed gradually to 1.5x-2.0x for efficient listening"),
+            Line::from("  Perfect for catching up on backlogs!"),
+            Line::from(""),
+            Line::from(vec![Span::styled(
+                "Bookmarking Strategy:",
+                theme.text_secondary_style().add_modifier(Modifier::BOLD),
+            )]),
+            Line::from("  Press 'b' whenever you hear something interesting"),
+            Line::from("  Use Shift+B to add detailed notes"),
+            Line::from(""),
+            Line::from(vec![Span::styled(
+                "Smart Playlists:",
+                theme.text_secondary_style().add_modifier(Modifier::BOLD),
+            )]),
+            Line::from("  Create mood-based playlists (Relaxing, Exciting, etc.)"),
+            Line::from("  Use 'Recently Played' to resume your listening journey"),
+            Line::from(""),
+            Line::from(vec![Span::styled(
+                "Sync Across Devices:",
+                theme.text_secondary_style().add_modifier(Modifier::BOLD),
+            )]),
+            Line::from("  Enable auto-sync in Settings"),
+            Line::from("  Start on laptop, continue on phone seamlessly"),
+            Line::from(""),
+            Line::from(vec![Span::styled(
+                "Night Listening:",
+                theme.text_secondary_style().add_modifier(Modifier::BOLD),
+            )]),
+            Line::from("  Switch to a dark theme (press 't')"),
+            Line::from("  Reduce volume gradually as you fall asleep"),
+        ],
+    }
 }
 
 fn section_header<'a>(text: &'a str, theme: &crate::theme::Theme) -> Line<'a> {
@@ -550,6 +976,24 @@ fn help_item<'a>(key: &'a str, description: &'a str, theme: &crate::theme::Theme
     ])
 }
 
+/// A help line for `action`, with the keys it is bound to now
+fn action_item<'a>(
+    action: Action,
+    description: &'a str,
+    keys: &KeyMap,
+    theme: &crate::theme::Theme,
+) -> Line<'a> {
+    Line::from(vec![
+        Span::styled("  ", Style::default()),
+        Span::styled(
+            format!("{:20}", keys.describe(action)),
+            theme.highlight_style(),
+        ),
+        Span::styled(" → ", theme.text_secondary_style()),
+        Span::styled(description, theme.text_style()),
+    ])
+}
+
 fn example_box<'a>(text: &'a str, theme: &crate::theme::Theme) -> Line<'a> {
     Line::from(vec![
         Span::styled("  💡 ", theme.warning_style()),
@@ -563,6 +1007,22 @@ fn example_box<'a>(text: &'a str, theme: &crate::theme::Theme) -> Line<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    fn draw(state: &AppState, width: u16, height: u16) -> Vec<String> {
+        let theme = crate::theme::Theme::default();
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal
+            .draw(|frame| render(frame, frame.area(), state, &theme))
+            .unwrap();
+        terminal
+            .backend()
+            .buffer()
+            .content()
+            .chunks(usize::from(width))
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect())
+            .collect()
+    }
 
     #[test]
     fn test_help_render_compiles() {
@@ -570,6 +1030,30 @@ mod tests {
         let _ = state.view;
     }
 
+    #[test]
+    fn test_help_shows_the_keys_in_use() {
+        use storystream_config::KeybindingsConfig;
+
+        let mut state = AppState::new();
+        let mut config = KeybindingsConfig::default();
+        config
+            .bindings
+            .insert("seek_forward".to_string(), "l".to_string());
+        state.apply_keybindings(&config);
+        state.show_help_section(HelpSection::Player);
+
+        let rows = draw(&state, 100, 80);
+        let row = |description: &str| {
+            rows.iter()
+                .find(|row| row.contains(description))
+                .unwrap_or_else(|| panic!("no row for {}", description))
+                .clone()
+        };
+        assert!(row("Seek forward 10 seconds").contains("│  l  "));
+        assert!(row("Seek backward 10 seconds").contains("│  ←  "));
+        assert!(row("Increase volume by 10%").contains("+ / ="));
+    }
+
     #[test]
     fn test_help_sections() {
         let sections = HelpSection::all();
@@ -578,5 +1062,50 @@ mod tests {
         for section in sections {
             let _ = section.title();
         }
+        assert_eq!(HelpSection::General.next(), HelpSection::Library);
+        assert_eq!(HelpSection::General.previous(), HelpSection::Examples);
+        assert_eq!(HelpSection::Examples.next(), HelpSection::General);
+    }
+
+    #[test]
+    fn test_help_scrolls_within_the_section() {
+        let theme = crate::theme::Theme::default();
+        let mut state = AppState::new();
+        state.show_help_section(HelpSection::Player);
+        let visible = visible_lines(Rect::new(0, 0, 80, 24));
+        assert_eq!(visible, 19);
+        let last = last_scroll(&state, &theme, visible);
+        assert!(last > 0);
+
+        state.scroll_help(isize::MAX, last);
+        assert_eq!(state.help_scroll, last);
+        let rows = draw(&state, 80, 24);
+        assert!(rows[1].contains("3 Player"));
+        assert!(rows.iter().any(|row| row.contains("Player (")));
+        // The scrollbar thumb is at the bottom of the text
+        assert_eq!(rows[21].chars().nth(79), Some('█'));
+        assert_eq!(rows[22].chars().nth(79), Some('▼'));
+
+        state.scroll_help(-5, last);
+        assert_eq!(state.help_scroll, last - 5);
+        state.scroll_help(isize::MIN, last);
+        assert_eq!(state.help_scroll, 0);
+
+        state.scroll_help(3, last);
+        state.show_help_section(HelpSection::Search);
+        assert_eq!(state.help_scroll, 0);
+    }
+
+    #[test]
+    fn test_section_tabs_keep_the_current_one_in_view() {
+        let mut state = AppState::new();
+        state.show_help_section(HelpSection::Examples);
+        let rows = draw(&state, 80, 24);
+        assert!(rows[1].contains("Examples"));
+        assert!(!rows[1].contains("1 General"));
+
+        state.show_help_section(HelpSection::General);
+        let rows = draw(&state, 80, 24);
+        assert!(rows[1].contains("1 General"));
     }
 }
@@ -1,15 +1,29 @@
 // crates/tui/src/ui/library.rs
 //! Library view rendering
 
-use crate::state::AppState;
+use crate::state::{format_duration, AppState, BookDetail, BookField, LibraryRows};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::Style,
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
     Frame,
 };
 
+/// Shown after books whose drive is not connected
+const UNAVAILABLE_BADGE: &str = "  ⏏ drive not connected";
+
+/// Shown after the book last left part way through
+const CONTINUE_BADGE: &str = "  ▶ continue listening";
+
+/// Rows taken by the library view's borders and info box, around the list
+const LIBRARY_CHROME: u16 = 5;
+
+/// Number of books the library list shows in the content `area`
+pub fn visible_rows(area: Rect) -> usize {
+    usize::from(area.height.saturating_sub(LIBRARY_CHROME))
+}
+
 /// Renders the library view
 pub fn render(frame: &mut Frame, area: Rect, state: &AppState, theme: &crate::theme::Theme) {
     let chunks = Layout::default()
@@ -23,7 +37,76 @@ pub fn render(frame: &mut Frame, area: Rect, state: &AppState, theme: &crate::th
 
 /// Renders the book list
 fn render_book_list(frame: &mut Frame, area: Rect, state: &AppState, theme: &crate::theme::Theme) {
-    // Demo books for now
+    let (items, selected) = match &state.library_rows {
+        Some(rows) => (
+            book_items(rows, state.selected_item, theme),
+            state.selected_item.checked_sub(rows.first),
+        ),
+        None => (demo_items(state.selected_item, theme), None),
+    };
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.border_color()))
+                .title("📚 Library (↑/↓: Navigate | Enter: Play | /: Search | f: Favorite)"),
+        )
+        .style(theme.text_style());
+
+    // The list scrolls to keep the selected row in view
+    let mut list_state = ListState::default().with_selected(selected);
+    frame.render_stateful_widget(list, area, &mut list_state);
+}
+
+/// Rows of the library around the selection
+fn book_items(
+    rows: &LibraryRows,
+    selected: usize,
+    theme: &crate::theme::Theme,
+) -> Vec<ListItem<'static>> {
+    rows.rows
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let Some(row) = row else {
+                return ListItem::new(Line::from(Span::styled(
+                    "   loading…",
+                    theme.text_secondary_style(),
+                )));
+            };
+            let icon = if row.playing { "🔊" } else { "📖" };
+            let star = if row.favorite { "★ " } else { "" };
+            let line = match &row.author {
+                Some(author) => format!("{} {}{} by {}", icon, star, row.title, author),
+                None => format!("{} {}{}", icon, star, row.title),
+            };
+            let style = if rows.first + i == selected {
+                theme.highlight_style()
+            } else if row.unavailable {
+                theme.text_secondary_style()
+            } else if row.playing {
+                theme.accent_style()
+            } else {
+                theme.text_style()
+            };
+            let details = format!("  {}  {}%", format_duration(row.duration), row.progress);
+            let mut spans = vec![
+                Span::styled(line, style),
+                Span::styled(details, theme.text_secondary_style()),
+            ];
+            if row.unavailable {
+                spans.push(Span::styled(UNAVAILABLE_BADGE, theme.warning_style()));
+            } else if row.continue_listening {
+                spans.push(Span::styled(CONTINUE_BADGE, theme.accent_style()));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect()
+}
+
+/// Sample books shown when no library is open
+fn demo_items(selected: usize, theme: &crate::theme::Theme) -> Vec<ListItem<'static>> {
     let books = vec![
         "📖 Moby Dick by Herman Melville",
         "📖 Pride and Prejudice by Jane Austen",
@@ -35,45 +118,38 @@ fn render_book_list(frame: &mut Frame, area: Rect, state: &AppState, theme: &cra
         "📖 Harry Potter by J.K. Rowling",
     ];
 
-    let items: Vec<ListItem> = books
-        .iter()
+    books
+        .into_iter()
         .enumerate()
         .map(|(i, book)| {
-            let style = if i == state.selected_item {
+            let style = if i == selected {
                 theme.highlight_style()
             } else {
                 theme.text_style()
             };
 
-            ListItem::new(Line::from(Span::styled(*book, style)))
+            ListItem::new(Line::from(Span::styled(book, style)))
         })
-        .collect();
-
-    let list = List::new(items)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(theme.border_color()))
-                .title("📚 Library (↑/↓: Navigate | Enter: Play | /: Search | f: Favorite)"),
-        )
-        .style(theme.text_style());
-
-    frame.render_widget(list, area);
+        .collect()
 }
 
 /// Renders library information
 fn render_library_info(
     frame: &mut Frame,
     area: Rect,
-    _state: &AppState,
+    state: &AppState,
     theme: &crate::theme::Theme,
 ) {
+    let total = format!("{} books", state.library_items_count);
     let info = Paragraph::new(vec![Line::from(vec![
         Span::styled("Total: ", theme.text_secondary_style()),
-        Span::styled("8 books", theme.highlight_style()),
+        Span::styled(total, theme.highlight_style()),
         Span::raw("  |  "),
         Span::styled("Playing: ", theme.text_secondary_style()),
-        Span::styled("None", theme.text_style()),
+        Span::styled(
+            state.playback.current_file.as_deref().unwrap_or("None"),
+            theme.text_style(),
+        ),
         Span::raw("  |  "),
         Span::styled("Last sync: ", theme.text_secondary_style()),
         Span::styled("Never", theme.text_style()),
@@ -89,13 +165,178 @@ fn render_library_info(
     frame.render_widget(info, area);
 }
 
+/// Renders the selected book's details centered over `area`
+pub fn render_book_detail(
+    frame: &mut Frame,
+    area: Rect,
+    detail: &BookDetail,
+    theme: &crate::theme::Theme,
+) {
+    let width = area.width.saturating_sub(4).min(70);
+    let height = (12 + detail.replaced.len() as u16).min(area.height);
+    let popup = Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+
+    let mut lines: Vec<Line> = BookField::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let style = if detail.editing && i == detail.selected {
+                theme.highlight_style()
+            } else {
+                theme.text_style()
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!("{:>9}: ", field.label()),
+                    theme.text_secondary_style(),
+                ),
+                Span::styled(field.value(&detail.book), style),
+            ])
+        })
+        .collect();
+
+    let book = &detail.book;
+    let duration = std::time::Duration::from_millis(book.duration.as_millis());
+    lines.push(Line::from(vec![
+        Span::styled(format!("{:>9}: ", "Length"), theme.text_secondary_style()),
+        Span::styled(format_duration(duration), theme.text_style()),
+    ]));
+    lines.push(Line::from(vec![
+        Span::styled(format!("{:>9}: ", "File"), theme.text_secondary_style()),
+        Span::styled(book.file_path.display().to_string(), theme.text_style()),
+    ]));
+    for note in &detail.replaced {
+        lines.push(Line::from(Span::styled(
+            format!("{:>9}  {}", "", note),
+            theme.text_secondary_style(),
+        )));
+    }
+    lines.push(Line::from(""));
+
+    let keys = if detail.editing {
+        let tags = if detail.write_tags { "on" } else { "off" };
+        format!(
+            "↑/↓: Field | Enter: Edit | w: Write tags ({}) | Esc: Done",
+            tags
+        )
+    } else {
+        "e: Edit | r: Replace file | Esc: Close".to_string()
+    };
+    lines.push(Line::from(Span::styled(keys, theme.text_secondary_style())));
+
+    let title = if detail.editing {
+        "Edit Book"
+    } else {
+        "Book Details"
+    };
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.accent))
+            .title(title),
+    );
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(paragraph, popup);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::state::BookRow;
+    use std::time::Duration;
 
     #[test]
     fn test_library_render_compiles() {
         let state = AppState::new();
         let _ = state.selected_item;
     }
+
+    fn row(title: &str, author: Option<&str>) -> BookRow {
+        BookRow {
+            title: title.to_string(),
+            author: author.map(str::to_string),
+            duration: Duration::from_secs(3600),
+            progress: 0,
+            favorite: false,
+            playing: false,
+            unavailable: false,
+            continue_listening: false,
+        }
+    }
+
+    #[test]
+    fn test_book_items_mark_loading_rows() {
+        let theme = crate::theme::Theme::default();
+        let rows = LibraryRows {
+            first: 40,
+            rows: vec![
+                Some(BookRow {
+                    continue_listening: true,
+                    progress: 42,
+                    ..row("Dune", Some("Frank Herbert"))
+                }),
+                None,
+                Some(BookRow {
+                    unavailable: true,
+                    ..row("Emma", None)
+                }),
+            ],
+        };
+        let details = Span::styled("  01:00:00  0%", theme.text_secondary_style());
+        let items = book_items(&rows, 40, &theme);
+        assert_eq!(items.len(), 3);
+        assert_eq!(
+            items[0],
+            ListItem::new(Line::from(vec![
+                Span::styled("📖 Dune by Frank Herbert", theme.highlight_style()),
+                Span::styled("  01:00:00  42%", theme.text_secondary_style()),
+                Span::styled(CONTINUE_BADGE, theme.accent_style()),
+            ]))
+        );
+        assert_eq!(
+            items[1],
+            ListItem::new(Line::from(Span::styled(
+                "   loading…",
+                theme.text_secondary_style()
+            )))
+        );
+        assert_eq!(
+            items[2],
+            ListItem::new(Line::from(vec![
+                Span::styled("📖 Emma", theme.text_secondary_style()),
+                details,
+                Span::styled(UNAVAILABLE_BADGE, theme.warning_style()),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_book_items_mark_favorites_and_the_playing_book() {
+        let theme = crate::theme::Theme::default();
+        let rows = LibraryRows {
+            first: 0,
+            rows: vec![
+                Some(row("Dune", None)),
+                Some(BookRow {
+                    favorite: true,
+                    playing: true,
+                    ..row("Emma", Some("Jane Austen"))
+                }),
+            ],
+        };
+        let items = book_items(&rows, 0, &theme);
+        assert_eq!(
+            items[1],
+            ListItem::new(Line::from(vec![
+                Span::styled("🔊 ★ Emma by Jane Austen", theme.accent_style()),
+                Span::styled("  01:00:00  0%", theme.text_secondary_style()),
+            ]))
+        );
+    }
 }
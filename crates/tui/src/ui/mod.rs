@@ -2,40 +2,165 @@
 //! UI rendering modules
 
 pub mod bookmarks;
+pub mod confirm;
+pub mod downloads;
 pub mod help;
 pub mod library;
+pub mod now_playing;
+pub mod palette;
 pub mod player;
 pub mod playlists;
 pub mod search;
 pub mod settings;
 pub mod statistics;
+pub mod text_input;
 
 use crate::{
-    state::{AppState, View},
+    state::{AppState, InputPurpose, ListeningLock, View},
     theme::Theme,
 };
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
-    style::Style,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph, Tabs},
+    widgets::{Block, Borders, Clear, Paragraph, Tabs},
     Frame,
 };
 
-/// Renders the main UI
-pub fn render(frame: &mut Frame, state: &AppState, theme: &Theme) {
-    let chunks = Layout::default()
+/// Where the last frame drew what the mouse can click
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClickAreas {
+    /// Inside of the player's progress bar, unless a popup covers the view
+    pub progress_bar: Option<Rect>,
+    /// The now-playing bar, when it shows and no popup is open
+    pub now_playing: Option<Rect>,
+}
+
+/// Renders the main UI, returning what the mouse can click in it
+pub fn render(frame: &mut Frame, state: &AppState, theme: &Theme) -> ClickAreas {
+    let chunks = main_layout(frame.area(), state);
+
+    render_tabs(frame, chunks[0], state, theme);
+    let progress_bar = render_content(frame, chunks[1], state, theme);
+    let now_playing = now_playing::is_shown(state).then_some(chunks[2]);
+    if let Some(area) = now_playing {
+        now_playing::render(frame, area, state, theme);
+    }
+    render_status_bar(frame, chunks[3], state, theme);
+    if state.accessible {
+        render_announcement(frame, chunks[4], state, theme);
+    }
+
+    if let Some(detail) = &state.book_detail {
+        library::render_book_detail(frame, chunks[1], detail, theme);
+    }
+    if let Some(popup) = &state.filter_popup {
+        search::render_filter_popup(frame, chunks[1], popup, &state.search_filter, theme);
+    }
+    if let Some(palette) = &state.palette {
+        palette::render(frame, frame.area(), palette, state, theme);
+    }
+    if let Some(prompt) = &state.pairing {
+        settings::render_pairing(frame, frame.area(), prompt, theme);
+    }
+    if let Some(picker) = &state.output_picker {
+        settings::render_output_picker(frame, frame.area(), picker, theme);
+    }
+    if let Some(lock) = &state.listening_lock {
+        render_listening_lock(frame, chunks[1], lock, theme);
+    }
+    if state.input.is_some() {
+        render_input(frame, frame.area(), state, theme);
+    }
+    if let Some(dialog) = &state.confirm {
+        confirm::render(frame, frame.area(), dialog, theme);
+    }
+
+    let covered = state.book_detail.is_some()
+        || state.filter_popup.is_some()
+        || state.palette.is_some()
+        || state.pairing.is_some()
+        || state.output_picker.is_some()
+        || state.listening_lock.is_some()
+        || state.input.is_some()
+        || state.confirm.is_some();
+    ClickAreas {
+        progress_bar: progress_bar.filter(|_| !covered),
+        now_playing: now_playing.filter(|_| !covered),
+    }
+}
+
+/// Where [`render`] draws the current view on a screen of `screen`'s size
+pub fn content_area(screen: Rect, state: &AppState) -> Rect {
+    main_layout(screen, state)[1]
+}
+
+/// Splits the screen into tabs, content and status bar, with the
+/// now-playing bar while a book is loaded and the announcement line in
+/// accessibility mode
+fn main_layout(screen: Rect, state: &AppState) -> std::rc::Rc<[Rect]> {
+    let now_playing = now_playing::is_shown(state);
+    Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3), // Tabs
-            Constraint::Min(0),    // Content
-            Constraint::Length(3), // Status bar
+            Constraint::Length(3),                           // Tabs
+            Constraint::Min(0),                              // Content
+            Constraint::Length(u16::from(now_playing)),      // Now playing
+            Constraint::Length(3),                           // Status bar
+            Constraint::Length(u16::from(state.accessible)), // Announcement
         ])
-        .split(frame.area());
+        .split(screen)
+}
 
-    render_tabs(frame, chunks[0], state, theme);
-    render_content(frame, chunks[1], state, theme);
-    render_status_bar(frame, chunks[2], state, theme);
+/// Renders the latest announcement with the cursor on it, where screen
+/// readers look for changes
+fn render_announcement(frame: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let line = Paragraph::new(state.announcement.as_str()).style(theme.text_style());
+    frame.render_widget(line, area);
+    if state.input.is_none() {
+        frame.set_cursor_position((area.x, area.y));
+    }
+}
+
+/// Renders the text prompt centered over the current view
+fn render_input(frame: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    if let Some(input) = &state.input {
+        // Passwords are not shown as typed
+        let masked = input.purpose == InputPurpose::UnlockLimits;
+        text_input::render(frame, area, &input.prompt, &input.text, masked, theme);
+    }
+}
+
+/// Covers the current view with the listening limits' lock screen
+fn render_listening_lock(frame: &mut Frame, area: Rect, lock: &ListeningLock, theme: &Theme) {
+    let mut text = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("🔒 {}", lock.message),
+            theme.warning_style(),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("Available again in {}", lock.format_wait()),
+            theme.text_style(),
+        )),
+    ];
+    if lock.unlockable {
+        text.push(Line::from(""));
+        text.push(Line::from(Span::styled(
+            "U: Unlock with password",
+            theme.text_secondary_style(),
+        )));
+    }
+    let paragraph = Paragraph::new(text).alignment(Alignment::Center).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.accent))
+            .title("Listening time"),
+    );
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph, area);
 }
 
 /// Renders the tab bar
@@ -48,6 +173,7 @@ fn render_tabs(frame: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
         "Playlists",
         "Statistics",
         "Settings",
+        "Downloads",
         "Help",
     ];
     let index = match state.view {
@@ -58,7 +184,8 @@ fn render_tabs(frame: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
         View::Playlists => 4,
         View::Statistics => 5,
         View::Settings => 6,
-        View::Help => 7,
+        View::Downloads => 7,
+        View::Help => 8,
         View::Plugin => 0,
     };
 
@@ -76,22 +203,24 @@ fn render_tabs(frame: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
     frame.render_widget(tabs, area);
 }
 
-/// Renders the current view content
-fn render_content(frame: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+/// Renders the current view content, returning the player's progress bar
+fn render_content(frame: &mut Frame, area: Rect, state: &AppState, theme: &Theme) -> Option<Rect> {
     match state.view {
         View::Library => library::render(frame, area, state, theme),
-        View::Player => player::render(frame, area, state, theme),
+        View::Player => return Some(player::render(frame, area, state, theme)),
         View::Bookmarks => bookmarks::render(frame, area, state, theme),
         View::Search => search::render(frame, area, state, theme),
         View::Playlists => playlists::render(frame, area, state, theme),
         View::Statistics => statistics::render(frame, area, state, theme),
         View::Settings => settings::render(frame, area, state, theme),
+        View::Downloads => downloads::render(frame, area, state, theme),
         View::Help => help::render(frame, area, state, theme),
         View::Plugin => {
             // Plugin rendering would go here
             library::render(frame, area, state, theme)
         }
     }
+    None
 }
 
 /// Renders the status bar
@@ -100,23 +229,30 @@ fn render_status_bar(frame: &mut Frame, area: Rect, state: &AppState, theme: &Th
         msg.clone()
     } else {
         format!(
-            "q: Quit | h: Help | Tab: Switch | t: Theme ({}) | Mouse: Enabled",
+            "q: Quit | h: Help | Ctrl+P: Commands | Tab: Switch | t: Theme ({}) | Mouse: Enabled",
             theme.theme_type.name()
         )
     };
 
-    let status = Paragraph::new(Line::from(vec![
-        Span::styled(
-            " ● ",
-            Style::default().fg(if state.playback.is_playing {
-                theme.playing
-            } else {
-                theme.paused
-            }),
-        ),
-        Span::styled(status_text, theme.text_style()),
-    ]))
-    .block(
+    let mut spans = vec![Span::styled(
+        " ● ",
+        Style::default().fg(if state.playback.is_playing {
+            theme.playing
+        } else {
+            theme.paused
+        }),
+    )];
+    if state.read_only {
+        spans.push(Span::styled(
+            "READ-ONLY ",
+            Style::default()
+                .fg(theme.warning)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+    spans.push(Span::styled(status_text, theme.text_style()));
+
+    let status = Paragraph::new(Line::from(spans)).block(
         Block::default()
             .borders(Borders::ALL)
             .border_style(Style::default().fg(theme.border_color())),
@@ -0,0 +1,178 @@
+// crates/tui/src/ui/now_playing.rs
+//! One-line bar showing the loaded book over the status bar
+//!
+//! It shows in every view but the player, which has all of it and more, so
+//! what is playing and how far along it is can be seen while browsing.
+//! Clicking it opens the player.
+
+use crate::{
+    state::{format_duration, AppState, View},
+    theme::Theme,
+};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+const SEPARATOR: &str = " · ";
+
+/// Whether the bar takes a line of the screen
+pub fn is_shown(state: &AppState) -> bool {
+    state.playback.current_file.is_some() && state.view != View::Player
+}
+
+/// Renders the bar into the one-line `area`
+///
+/// The title, then the chapter, are shortened to fit; the icon, times and
+/// speed are always shown whole.
+pub fn render(frame: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let playback = &state.playback;
+    let Some(title) = playback.current_file.as_deref() else {
+        return;
+    };
+    let (icon, icon_color) = if playback.is_playing {
+        (" ▶ ", theme.playing)
+    } else {
+        (" ⏸ ", theme.paused)
+    };
+    let chapter = playback
+        .chapter
+        .and_then(|index| state.chapters.get(index))
+        .map(|chapter| chapter.title.as_str());
+    let times = format!(
+        "{} / {}",
+        format_duration(playback.position),
+        format_duration(playback.duration)
+    );
+    let speed = speed_text(playback.speed);
+
+    let fixed = icon.width() + SEPARATOR.width() * 2 + times.width() + speed.width() + 1;
+    let mut room = usize::from(area.width).saturating_sub(fixed);
+    let chapter = chapter.map(|chapter| {
+        room = room.saturating_sub(SEPARATOR.width());
+        // The chapter gives way to the title, down to half of the room
+        let width = chapter
+            .width()
+            .min(room / 2)
+            .max(room.saturating_sub(title.width()));
+        let chapter = fit(chapter, width.min(chapter.width()));
+        room = room.saturating_sub(chapter.width());
+        chapter
+    });
+    let title = fit(title, room);
+
+    let mut spans = vec![
+        Span::styled(
+            icon,
+            Style::default().fg(icon_color).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(title, theme.highlight_style()),
+    ];
+    if let Some(chapter) = chapter {
+        spans.push(Span::styled(SEPARATOR, theme.text_secondary_style()));
+        spans.push(Span::styled(chapter, theme.text_style()));
+    }
+    spans.push(Span::styled(SEPARATOR, theme.text_secondary_style()));
+    spans.push(Span::styled(times, theme.text_style()));
+    spans.push(Span::styled(SEPARATOR, theme.text_secondary_style()));
+    spans.push(Span::styled(speed, theme.accent_style()));
+
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+/// `text` cut to `width` columns, ending in "…" when it is cut
+pub fn fit(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    let mut fitted = String::new();
+    let mut used = 0;
+    for grapheme in text.graphemes(true) {
+        if used + grapheme.width() + 1 > width {
+            break;
+        }
+        used += grapheme.width();
+        fitted.push_str(grapheme);
+    }
+    if width > 0 {
+        fitted.push('…');
+    }
+    fitted
+}
+
+/// Speed with as many decimals as it needs, such as `1.0x` or `1.25x`
+fn speed_text(speed: f32) -> String {
+    let text = format!("{:.2}", speed);
+    format!("{}x", text.strip_suffix('0').unwrap_or(&text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+    use std::time::Duration;
+
+    #[test]
+    fn test_fit_cuts_with_an_ellipsis() {
+        assert_eq!(fit("Dune", 10), "Dune");
+        assert_eq!(fit("Dune", 4), "Dune");
+        assert_eq!(fit("The Way of Kings", 8), "The Way…");
+        assert_eq!(fit("日本語の本", 6), "日本…");
+        assert_eq!(fit("Dune", 0), "");
+        assert_eq!(speed_text(1.0), "1.0x");
+        assert_eq!(speed_text(1.25), "1.25x");
+    }
+
+    #[test]
+    fn test_bar_shows_the_loaded_book() {
+        let mut state = AppState::new();
+        assert!(!is_shown(&state));
+
+        state.playback.current_file = Some("The Way of Kings".to_string());
+        state.playback.position = Duration::from_secs(65);
+        state.playback.duration = Duration::from_secs(3 * 3600);
+        state.playback.speed = 1.5;
+        assert!(is_shown(&state));
+        state.set_view(View::Player);
+        assert!(!is_shown(&state));
+
+        let theme = Theme::default();
+        let draw = |state: &AppState, width| {
+            let mut terminal = Terminal::new(TestBackend::new(width, 1)).unwrap();
+            terminal
+                .draw(|frame| render(frame, frame.area(), state, &theme))
+                .unwrap();
+            let buffer = terminal.backend().buffer();
+            buffer
+                .content()
+                .iter()
+                .map(|cell| cell.symbol())
+                .collect::<String>()
+        };
+        let line = draw(&state, 80);
+        assert!(line.contains("⏸ The Way of Kings · 01:05 / 03:00:00 · 1.5x"));
+
+        // A narrow screen shortens the title but keeps the times
+        let line = draw(&state, 40);
+        assert!(line.contains("⏸ The Way o… · 01:05"));
+        assert!(line.contains("01:05 / 03:00:00 · 1.5x"));
+
+        // The chapter shares what room is left with the title
+        state.chapters = vec![storystream_core::Chapter::new(
+            storystream_core::BookId::new(),
+            "Prologue: To Kill".to_string(),
+            0,
+            storystream_core::Duration::from_seconds(0),
+            storystream_core::Duration::from_seconds(600),
+        )];
+        state.playback.chapter = Some(0);
+        state.playback.is_playing = true;
+        let line = draw(&state, 60);
+        assert!(line.contains("▶ The Way of Ki… · Prologue: To… · 01:05"));
+    }
+}
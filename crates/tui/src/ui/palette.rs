@@ -0,0 +1,94 @@
+// crates/tui/src/ui/palette.rs
+//! Command palette rendering
+
+use crate::{palette::CommandPalette, state::AppState, theme::Theme};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// Renders the palette near the top of `area`, over whatever view is open
+pub fn render(
+    frame: &mut Frame,
+    area: Rect,
+    palette: &CommandPalette,
+    state: &AppState,
+    theme: &Theme,
+) {
+    let entries = palette.entries(state);
+    let width = area.width.saturating_sub(4).min(64);
+    let height = (entries.len() as u16 + 6).clamp(7, 20).min(area.height);
+    let rect = Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 4,
+        width,
+        height,
+    };
+
+    // Borders, the query, two spacers and the key hint take six rows
+    let rows = usize::from(height.saturating_sub(6)).max(1);
+    let first = palette.selected.saturating_sub(rows - 1);
+    let inner = usize::from(width.saturating_sub(2));
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("> ", theme.accent_style()),
+            Span::styled(palette.query.clone(), theme.text_style()),
+            Span::styled("█", theme.accent_style()),
+        ]),
+        Line::from(""),
+    ];
+    if entries.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No matching command",
+            theme.text_secondary_style(),
+        )));
+    }
+    for (i, entry) in entries.iter().enumerate().skip(first).take(rows) {
+        let label = entry.action.label();
+        let (style, hint) = match entry.unavailable {
+            Some(reason) => (
+                theme.text_secondary_style().add_modifier(Modifier::DIM),
+                reason.to_string(),
+            ),
+            None => (
+                theme.text_style(),
+                state
+                    .keymap
+                    .keys(entry.action)
+                    .first()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+            ),
+        };
+        let (style, hint_style) = if i == palette.selected {
+            let style = style.patch(theme.highlight_style());
+            (style, style)
+        } else {
+            (style, style.patch(theme.text_secondary_style()))
+        };
+        let gap = inner.saturating_sub(label.chars().count() + hint.chars().count() + 1);
+        lines.push(Line::from(vec![
+            Span::styled(format!(" {}{}", label, " ".repeat(gap)), style),
+            Span::styled(hint, hint_style),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Type to filter | ↑/↓: Select | Enter: Run | Esc: Close",
+        theme.text_secondary_style(),
+    )));
+
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.accent))
+            .title("Commands"),
+    );
+
+    frame.render_widget(Clear, rect);
+    frame.render_widget(paragraph, rect);
+}
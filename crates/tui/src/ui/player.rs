@@ -1,18 +1,85 @@
 // crates/tui/src/ui/player.rs
 //! Player view rendering
 
-use crate::state::AppState;
+use crate::state::{format_duration, AppState, ChapterEditor, Scrub, SyncBanner, UpNext};
 use ratatui::{
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Gauge, Paragraph},
+    widgets::{Block, Borders, Gauge, List, ListItem, ListState, Paragraph},
     Frame,
 };
+use std::time::Duration;
+use storystream_core::Chapter;
 
-/// Renders the player view
-pub fn render(frame: &mut Frame, area: Rect, state: &AppState, theme: &crate::theme::Theme) {
-    let chunks = Layout::default()
+/// Renders the player view, returning the inside of its progress bar
+pub fn render(
+    frame: &mut Frame,
+    area: Rect,
+    state: &AppState,
+    theme: &crate::theme::Theme,
+) -> Rect {
+    let mut area = area;
+    if let Some(up_next) = &state.up_next {
+        let (banner, rest) = split_banner(area);
+        render_up_next(frame, banner, up_next, theme);
+        area = rest;
+    }
+    if let Some(banner) = &state.sync_banner {
+        let (top, rest) = split_banner(area);
+        render_sync_banner(frame, top, banner, state, theme);
+        area = rest;
+    }
+
+    let chunks = sections(area);
+
+    render_now_playing(frame, chunks[0], state, theme);
+    render_progress(frame, chunks[1], state, theme);
+    render_time_info(frame, chunks[2], state, theme);
+    render_controls(frame, chunks[3], state, theme);
+    render_chapter_info(frame, chunks[4], state, theme);
+    chunks[1].inner(Margin::new(1, 1))
+}
+
+/// Column of `bar` that `position` in a book `duration` long is drawn at
+pub fn bar_column(bar: Rect, position: Duration, duration: Duration) -> u16 {
+    if duration.is_zero() || bar.width == 0 {
+        return bar.x;
+    }
+    let ratio = (position.as_secs_f64() / duration.as_secs_f64()).clamp(0.0, 1.0);
+    bar.x + (ratio * f64::from(bar.width - 1)).round() as u16
+}
+
+/// Position in a book `duration` long under `column` of `bar`
+///
+/// Columns left or right of the bar give the start or the end.
+pub fn bar_position(bar: Rect, column: u16, duration: Duration) -> Duration {
+    if bar.width < 2 {
+        return Duration::ZERO;
+    }
+    let offset = column.clamp(bar.x, bar.right() - 1) - bar.x;
+    duration.mul_f64(f64::from(offset) / f64::from(bar.width - 1))
+}
+
+/// The chapter whose marker is drawn at `column` under `bar`
+///
+/// Markers sit on the bar's bottom border, one per chapter after the first.
+pub fn chapter_marker_at(bar: Rect, column: u16, state: &AppState) -> Option<&Chapter> {
+    let duration = state.playback.duration;
+    state
+        .chapters
+        .iter()
+        .filter(|chapter| !chapter.start_time.is_zero())
+        .find(|chapter| bar_column(bar, chapter_start(chapter), duration) == column)
+}
+
+fn chapter_start(chapter: &Chapter) -> Duration {
+    Duration::from_millis(chapter.start_time.as_millis())
+}
+
+/// Splits the area below the banners into the player's sections
+fn sections(area: Rect) -> std::rc::Rc<[Rect]> {
+    Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(5), // Title/Artist
@@ -21,13 +88,63 @@ pub fn render(frame: &mut Frame, area: Rect, state: &AppState, theme: &crate::th
             Constraint::Length(7), // Controls
             Constraint::Min(0),    // Chapter info
         ])
+        .split(area)
+}
+
+/// Splits a three-line banner off the top of `area`
+fn split_banner(area: Rect) -> (Rect, Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
         .split(area);
+    (chunks[0], chunks[1])
+}
 
-    render_now_playing(frame, chunks[0], state, theme);
-    render_progress(frame, chunks[1], state, theme);
-    render_time_info(frame, chunks[2], state, theme);
-    render_controls(frame, chunks[3], state, theme);
-    render_chapter_info(frame, chunks[4], state, theme);
+/// Renders the book suggested after the loaded one finished
+fn render_up_next(frame: &mut Frame, area: Rect, up_next: &UpNext, theme: &crate::theme::Theme) {
+    let paragraph = Paragraph::new(Span::styled(
+        up_next.message(),
+        theme.accent_style().add_modifier(Modifier::BOLD),
+    ))
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(theme.accent_style())
+            .title(format!("Up Next: {} (Esc: Dismiss)", up_next.reason)),
+    )
+    .alignment(Alignment::Center);
+
+    frame.render_widget(paragraph, area);
+}
+
+/// Renders another device's position, with the keys to act on it
+fn render_sync_banner(
+    frame: &mut Frame,
+    area: Rect,
+    banner: &SyncBanner,
+    state: &AppState,
+    theme: &crate::theme::Theme,
+) {
+    // A conflict means both devices listened, so it gets the louder style
+    let style = if banner.conflict {
+        theme.error_style()
+    } else {
+        theme.accent_style()
+    };
+
+    let paragraph = Paragraph::new(Span::styled(
+        banner.message(state.playback.position),
+        style.add_modifier(Modifier::BOLD),
+    ))
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(style)
+            .title("Sync (Esc: Dismiss)"),
+    )
+    .alignment(Alignment::Center);
+
+    frame.render_widget(paragraph, area);
 }
 
 /// Renders now playing information
@@ -64,19 +181,88 @@ fn render_now_playing(
 }
 
 /// Renders progress bar
+///
+/// While the bar is dragged it shows the seek target instead, with what
+/// the target snapped to, and the target's time above the pointer.
 fn render_progress(frame: &mut Frame, area: Rect, state: &AppState, theme: &crate::theme::Theme) {
-    let progress = (state.playback.progress() * 100.0) as u16;
-    let gauge = Gauge::default()
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(theme.border_color()))
-                .title("Progress"),
-        )
-        .gauge_style(theme.success_style())
-        .percent(progress);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border_color()));
+    let gauge = match &state.scrub {
+        Some(scrub) => {
+            let duration = state.playback.duration.as_secs_f64();
+            let ratio = if duration > 0.0 {
+                (scrub.position.as_secs_f64() / duration).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            Gauge::default()
+                .block(block.title("Progress (Shift: no snapping)"))
+                .gauge_style(theme.accent_style())
+                .ratio(ratio)
+                .label(scrub.label())
+        }
+        None => Gauge::default()
+            .block(block.title("Progress"))
+            .gauge_style(theme.success_style())
+            .percent((state.playback.progress() * 100.0) as u16),
+    };
 
     frame.render_widget(gauge, area);
+    render_chapter_markers(frame, area, state, theme);
+    if let Some(scrub) = &state.scrub {
+        render_scrub_time(frame, area, scrub, state.playback.duration, theme);
+    }
+}
+
+/// Marks where each chapter after the first starts on the bar's bottom border
+fn render_chapter_markers(
+    frame: &mut Frame,
+    area: Rect,
+    state: &AppState,
+    theme: &crate::theme::Theme,
+) {
+    let bar = area.inner(Margin::new(1, 1));
+    if state.playback.duration.is_zero() || bar.width == 0 {
+        return;
+    }
+    let row = area.bottom() - 1;
+    for (index, chapter) in state.chapters.iter().enumerate() {
+        if chapter.start_time.is_zero() {
+            continue;
+        }
+        let column = bar_column(bar, chapter_start(chapter), state.playback.duration);
+        let style = if state.playback.chapter == Some(index) {
+            theme.accent_style().add_modifier(Modifier::BOLD)
+        } else {
+            theme.accent_style()
+        };
+        if let Some(cell) = frame.buffer_mut().cell_mut((column, row)) {
+            cell.set_symbol("┴").set_style(style);
+        }
+    }
+}
+
+/// Shows the drag target's time on the bar's top border, over the pointer
+fn render_scrub_time(
+    frame: &mut Frame,
+    area: Rect,
+    scrub: &Scrub,
+    duration: Duration,
+    theme: &crate::theme::Theme,
+) {
+    let bar = area.inner(Margin::new(1, 1));
+    let label = format!(" {} ", format_duration(scrub.position));
+    let width = (label.len() as u16).min(area.width);
+    let center = bar_column(bar, scrub.position, duration);
+    let x = center
+        .saturating_sub(width / 2)
+        .clamp(area.x, area.right() - width);
+    let style = theme.accent_style().add_modifier(Modifier::REVERSED);
+    frame.render_widget(
+        Paragraph::new(Span::styled(label, style)),
+        Rect::new(x, area.y, width, 1),
+    );
 }
 
 /// Renders time information
@@ -109,34 +295,48 @@ fn render_controls(frame: &mut Frame, area: Rect, state: &AppState, theme: &crat
         "⏸ Paused"
     };
 
+    let output = match state.playback.format_output() {
+        Some(device) => Line::from(vec![
+            Span::styled("Output: ", theme.text_secondary_style()),
+            Span::styled(device, theme.text_style()),
+        ]),
+        None => Line::from(""),
+    };
+
+    let mut status_line = vec![Span::styled(
+        status,
+        Style::default()
+            .fg(if state.playback.is_playing {
+                theme.playing
+            } else {
+                theme.paused
+            })
+            .add_modifier(Modifier::BOLD),
+    )];
+    if let Some(sleep) = state.playback.format_sleep_timer() {
+        status_line.push(Span::raw("  |  "));
+        status_line.push(Span::styled(sleep, theme.accent_style()));
+    }
+
     let controls = vec![
-        Line::from(Span::styled(
-            status,
-            Style::default()
-                .fg(if state.playback.is_playing {
-                    theme.playing
-                } else {
-                    theme.paused
-                })
-                .add_modifier(Modifier::BOLD),
-        )),
+        Line::from(status_line),
         Line::from(""),
         Line::from(vec![
             Span::styled("Speed: ", theme.text_secondary_style()),
-            Span::styled(
-                format!("{:.1}x", state.playback.speed),
-                theme.highlight_style(),
-            ),
+            Span::styled(state.playback.format_speed(), theme.highlight_style()),
             Span::raw("  |  "),
             Span::styled("Volume: ", theme.text_secondary_style()),
             Span::styled(
                 format!("{}%", (state.playback.volume * 100.0) as u8),
                 theme.highlight_style(),
             ),
+            Span::raw("  |  "),
+            Span::styled("EQ: ", theme.text_secondary_style()),
+            Span::styled(state.playback.equalizer.as_str(), theme.highlight_style()),
         ]),
-        Line::from(""),
+        output,
         Line::from(Span::styled(
-            "Space: Play/Pause | ←/→: Seek | [/]: Speed | +/-: Volume",
+            "Space: Play/Pause | ←/→: Seek | [/]: Speed | R: Ramp | +/-: Volume | E: Equalizer | z: Sleep",
             theme.text_secondary_style(),
         )),
     ];
@@ -153,24 +353,34 @@ fn render_controls(frame: &mut Frame, area: Rect, state: &AppState, theme: &crat
     frame.render_widget(paragraph, area);
 }
 
-/// Renders chapter information
+/// Renders chapter information, or the chapter list while editing
 fn render_chapter_info(
     frame: &mut Frame,
     area: Rect,
     state: &AppState,
     theme: &crate::theme::Theme,
 ) {
-    let chapter_info = if let Some(ch) = state.playback.chapter {
-        format!("Chapter {} of ?", ch + 1)
-    } else {
-        "No chapters available".to_string()
+    if let Some(editor) = &state.chapter_editor {
+        render_chapter_editor(frame, area, editor, theme);
+        return;
+    }
+
+    let current = state.playback.chapter.and_then(|ch| state.chapters.get(ch));
+    let chapter_info = match current {
+        Some(chapter) => format!(
+            "Chapter {} of {}: {}",
+            chapter.index + 1,
+            state.chapters.len(),
+            chapter.title
+        ),
+        None => "No chapters available".to_string(),
     };
 
     let paragraph = Paragraph::new(vec![
         Line::from(Span::styled(chapter_info, theme.accent_style())),
         Line::from(""),
         Line::from(Span::styled(
-            "n: Next Chapter | p: Previous Chapter",
+            "n: Next Chapter | p: Previous Chapter | e: Edit Chapters",
             theme.text_secondary_style(),
         )),
     ])
@@ -185,6 +395,123 @@ fn render_chapter_info(
     frame.render_widget(paragraph, area);
 }
 
+/// Renders the chapter editor list with start times
+fn render_chapter_editor(
+    frame: &mut Frame,
+    area: Rect,
+    editor: &ChapterEditor,
+    theme: &crate::theme::Theme,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(area);
+
+    if editor.is_reviewing() {
+        render_chapter_suggestions(frame, chunks[0], editor, theme);
+        let keys = Paragraph::new(Span::styled(
+            "Space: Accept/Reject | Enter: Apply and save | Esc: Dismiss",
+            theme.text_secondary_style(),
+        ))
+        .alignment(Alignment::Center);
+        frame.render_widget(keys, chunks[1]);
+        return;
+    }
+
+    let items: Vec<ListItem> = editor
+        .chapters
+        .iter()
+        .enumerate()
+        .map(|(i, chapter)| {
+            let style = if i == editor.selected {
+                theme.highlight_style()
+            } else {
+                theme.text_style()
+            };
+            let start = std::time::Duration::from_millis(chapter.start_time.as_millis());
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:>9}  ", format_duration(start)),
+                    theme.text_secondary_style(),
+                ),
+                Span::styled(chapter.title.clone(), style),
+            ]))
+        })
+        .collect();
+
+    let title = if editor.suggesting {
+        "Edit Chapters (looking for breaks...)"
+    } else if editor.dirty {
+        "Edit Chapters (unsaved)"
+    } else {
+        "Edit Chapters"
+    };
+    let mut list_state = ListState::default().with_selected(Some(editor.selected));
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.accent))
+            .title(title),
+    );
+    frame.render_stateful_widget(list, chunks[0], &mut list_state);
+
+    let keys = Paragraph::new(Span::styled(
+        "r: Rename | </>: Nudge 1s | s: Split | m: Merge | a: Suggest | w: Save | x: Export CUE | Esc: Done",
+        theme.text_secondary_style(),
+    ))
+    .alignment(Alignment::Center);
+    frame.render_widget(keys, chunks[1]);
+}
+
+/// Renders suggested chapter starts with the silence each was found in
+fn render_chapter_suggestions(
+    frame: &mut Frame,
+    area: Rect,
+    editor: &ChapterEditor,
+    theme: &crate::theme::Theme,
+) {
+    let items: Vec<ListItem> = editor
+        .suggestions
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let style = if i == editor.selected_suggestion {
+                theme.highlight_style()
+            } else {
+                theme.text_style()
+            };
+            let mark = if row.accepted { "[x]" } else { "[ ]" };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{} ", mark), style),
+                Span::styled(format!("{:>9}  ", format_duration(row.start)), style),
+                Span::styled(
+                    format!(
+                        "{:.1}s silence, {:.0}% sure",
+                        row.silence.as_secs_f32(),
+                        row.confidence * 100.0
+                    ),
+                    theme.text_secondary_style(),
+                ),
+            ]))
+        })
+        .collect();
+
+    let accepted = editor.suggestions.iter().filter(|row| row.accepted).count();
+    let title = format!(
+        "Suggested Chapters ({} of {} accepted)",
+        accepted,
+        editor.suggestions.len()
+    );
+    let mut list_state = ListState::default().with_selected(Some(editor.selected_suggestion));
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.accent))
+            .title(title),
+    );
+    frame.render_stateful_widget(list, area, &mut list_state);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -194,4 +521,64 @@ mod tests {
         let state = AppState::new();
         let _ = state.playback.is_playing;
     }
+
+    #[test]
+    fn test_bar_columns_and_positions() {
+        let bar = Rect::new(10, 5, 11, 1);
+        let hour = Duration::from_secs(3600);
+        assert_eq!(bar_column(bar, Duration::ZERO, hour), 10);
+        assert_eq!(bar_column(bar, hour / 2, hour), 15);
+        assert_eq!(bar_column(bar, hour * 2, hour), 20);
+
+        assert_eq!(bar_position(bar, 15, hour), hour / 2);
+        // Clicks past either end are clamped to the book
+        assert_eq!(bar_position(bar, 2, hour), Duration::ZERO);
+        assert_eq!(bar_position(bar, 40, hour), hour);
+    }
+
+    #[test]
+    fn test_chapter_markers_are_drawn_and_found() {
+        use ratatui::{backend::TestBackend, Terminal};
+        use storystream_core::{BookId, Duration as BookDuration};
+
+        let theme = crate::theme::Theme::default();
+        let mut state = AppState::new();
+        state.playback.duration = Duration::from_secs(100);
+        let book_id = BookId::new();
+        state.chapters = [(0, "Opening"), (50, "Middle")]
+            .into_iter()
+            .enumerate()
+            .map(|(index, (start, title))| {
+                Chapter::new(
+                    book_id,
+                    title.to_string(),
+                    index as u32,
+                    BookDuration::from_seconds(start),
+                    BookDuration::from_seconds(start + 50),
+                )
+            })
+            .collect();
+        state.scrub = Some(Scrub {
+            position: Duration::from_secs(25),
+            snapped_to: None,
+        });
+
+        let mut terminal = Terminal::new(TestBackend::new(42, 30)).unwrap();
+        let mut bar = Rect::default();
+        terminal
+            .draw(|frame| bar = render(frame, frame.area(), &state, &theme))
+            .unwrap();
+        assert_eq!(bar, Rect::new(1, 6, 40, 1));
+
+        let buffer = terminal.backend().buffer();
+        let marker = bar_column(bar, Duration::from_secs(50), state.playback.duration);
+        assert_eq!(buffer[(marker, bar.bottom())].symbol(), "┴");
+        let top: String = (0..42).map(|x| buffer[(x, bar.y - 1)].symbol()).collect();
+        assert!(top.contains(" 00:25 "));
+
+        let chapter = chapter_marker_at(bar, marker, &state).map(|c| c.title.as_str());
+        assert_eq!(chapter, Some("Middle"));
+        // The first chapter starts with the book and has no marker
+        assert!(chapter_marker_at(bar, bar.x, &state).is_none());
+    }
 }
@@ -1,7 +1,7 @@
 // crates/tui/src/ui/playlists.rs
 //! Playlists view rendering
 
-use crate::state::AppState;
+use crate::state::{format_duration, AppState, PlaylistRow};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::Style,
@@ -9,6 +9,7 @@ use ratatui::{
     widgets::{Block, Borders, List, ListItem},
     Frame,
 };
+use std::time::Duration;
 
 /// Renders the playlists view
 pub fn render(frame: &mut Frame, area: Rect, state: &AppState, theme: &crate::theme::Theme) {
@@ -17,45 +18,62 @@ pub fn render(frame: &mut Frame, area: Rect, state: &AppState, theme: &crate::th
         .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
         .split(area);
 
-    render_playlist_list(frame, chunks[0], state, theme);
+    let sidebar = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(chunks[0]);
+
+    render_playlist_list(frame, sidebar[0], state, theme);
+    render_subscriptions(frame, sidebar[1], state, theme);
     render_playlist_items(frame, chunks[1], state, theme);
 }
 
-/// Renders the playlist list
+/// Icon of a playlist; smart ones fill themselves and look different
+fn playlist_icon(playlist: &PlaylistRow) -> &'static str {
+    if playlist.smart {
+        "⚡"
+    } else {
+        "🎵"
+    }
+}
+
+/// Renders the playlists with their book counts and lengths
 fn render_playlist_list(
     frame: &mut Frame,
     area: Rect,
     state: &AppState,
     theme: &crate::theme::Theme,
 ) {
-    let playlists = vec![
-        ("🎵 Recently Played", "8 books"),
-        ("⭐ Favorites", "12 books"),
-        ("📚 Currently Reading", "3 books"),
-        ("🏁 Finished", "45 books"),
-        ("🎧 Podcasts", "6 series"),
-        ("🆕 New Arrivals", "4 books"),
-    ];
-
-    let items: Vec<ListItem> = playlists
-        .iter()
-        .enumerate()
-        .map(|(i, (name, count))| {
-            let style = if i == state.selected_item {
-                theme.highlight_style()
-            } else {
-                theme.text_style()
-            };
+    let items: Vec<ListItem> = if state.playlists.is_empty() {
+        vec![ListItem::new(Line::from(Span::styled(
+            "No playlists yet (n: New playlist)",
+            theme.text_secondary_style(),
+        )))]
+    } else {
+        state
+            .playlists
+            .iter()
+            .enumerate()
+            .map(|(i, playlist)| {
+                let style = if i == state.selected_item {
+                    theme.highlight_style()
+                } else {
+                    theme.text_style()
+                };
 
-            ListItem::new(vec![
-                Line::from(Span::styled(*name, style)),
-                Line::from(Span::styled(
-                    format!("  {}", count),
-                    theme.text_secondary_style(),
-                )),
-            ])
-        })
-        .collect();
+                ListItem::new(vec![
+                    Line::from(Span::styled(
+                        format!("{} {}", playlist_icon(playlist), playlist.name),
+                        style,
+                    )),
+                    Line::from(Span::styled(
+                        format!("  {}", playlist.label()),
+                        theme.text_secondary_style(),
+                    )),
+                ])
+            })
+            .collect()
+    };
 
     let list = List::new(items)
         .block(
@@ -69,32 +87,131 @@ fn render_playlist_list(
     frame.render_widget(list, area);
 }
 
-/// Renders playlist items
+/// Renders the feed subscriptions with their unplayed-episode counts
+fn render_subscriptions(
+    frame: &mut Frame,
+    area: Rect,
+    state: &AppState,
+    theme: &crate::theme::Theme,
+) {
+    let items: Vec<ListItem> = if state.subscriptions.is_empty() {
+        vec![ListItem::new(Line::from(Span::styled(
+            "No feeds yet (storystream feed add URL)",
+            theme.text_secondary_style(),
+        )))]
+    } else {
+        state
+            .subscriptions
+            .iter()
+            .map(|subscription| {
+                let count_style = if subscription.unplayed > 0 {
+                    theme.highlight_style()
+                } else {
+                    theme.text_secondary_style()
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("🎙 {}", subscription.title), theme.text_style()),
+                    Span::styled(format!("  {}", subscription.label()), count_style),
+                ]))
+            })
+            .collect()
+    };
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.border_color()))
+                .title("🎙 Subscriptions"),
+        )
+        .style(theme.text_style());
+
+    frame.render_widget(list, area);
+}
+
+/// Renders the books of the open playlist, or the keys when none is open
 fn render_playlist_items(
     frame: &mut Frame,
     area: Rect,
-    _state: &AppState,
+    state: &AppState,
     theme: &crate::theme::Theme,
 ) {
-    let books = vec![
-        "📖 Moby Dick",
-        "📖 Pride and Prejudice",
-        "📖 1984",
-        "📖 To Kill a Mockingbird",
-        "📖 The Great Gatsby",
-    ];
-
-    let items: Vec<ListItem> = books
-        .iter()
-        .map(|book| ListItem::new(Line::from(Span::styled(*book, theme.text_style()))))
-        .collect();
+    let playlist = state.selected_playlist();
+    let (items, title): (Vec<ListItem>, String) = match (&state.open_playlist, playlist) {
+        (Some(open), Some(playlist)) => {
+            let items = if open.books.is_empty() {
+                let hint = if playlist.smart {
+                    "No books match this playlist"
+                } else {
+                    "No books yet (a: Add the book selected in the library)"
+                };
+                vec![ListItem::new(Line::from(Span::styled(
+                    hint,
+                    theme.text_secondary_style(),
+                )))]
+            } else {
+                open.books
+                    .iter()
+                    .enumerate()
+                    .map(|(i, book)| {
+                        let style = if i == open.selected {
+                            theme.highlight_style()
+                        } else {
+                            theme.text_style()
+                        };
+                        let author = book
+                            .author
+                            .as_deref()
+                            .map(|author| format!(" by {}", author))
+                            .unwrap_or_default();
+                        ListItem::new(Line::from(vec![
+                            Span::styled(format!("{}. {}{}", i + 1, book.title, author), style),
+                            Span::styled(
+                                format!(
+                                    "  {}",
+                                    format_duration(Duration::from_millis(
+                                        book.duration.as_millis()
+                                    ))
+                                ),
+                                theme.text_secondary_style(),
+                            ),
+                        ]))
+                    })
+                    .collect()
+            };
+            let keys = if playlist.smart {
+                "Esc: Close | Enter: Play"
+            } else {
+                "J/K: Move | x: Remove | Esc: Close | Enter: Play"
+            };
+            let title = format!("{} {} ({})", playlist_icon(playlist), playlist.name, keys);
+            (items, title)
+        }
+        _ => {
+            let keys = [
+                "o: Show books",
+                "Enter: Play",
+                "Shift+Enter: Shuffle and play",
+                "n: New playlist",
+                "a: Add the book selected in the library",
+                "d: Delete playlist",
+            ];
+            let items = keys
+                .iter()
+                .map(|key| {
+                    ListItem::new(Line::from(Span::styled(*key, theme.text_secondary_style())))
+                })
+                .collect();
+            (items, "📚 Books".to_string())
+        }
+    };
 
     let list = List::new(items)
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(theme.border_color()))
-                .title("📚 Recently Played (↑/↓: Navigate | Enter: Play | n: New playlist)"),
+                .title(title),
         )
         .style(theme.text_style());
 
@@ -110,4 +227,81 @@ mod tests {
         let state = AppState::new();
         let _ = state.view;
     }
+
+    #[test]
+    fn test_subscriptions_are_listed_with_counts() {
+        use crate::state::Subscription;
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let mut state = AppState::new();
+        state.subscriptions = vec![Subscription {
+            title: "Night Show".to_string(),
+            unplayed: 4,
+        }];
+        let theme = crate::theme::Theme::default();
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        terminal
+            .draw(|frame| render(frame, frame.area(), &state, &theme))
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let text: String = buffer.content().iter().map(|cell| cell.symbol()).collect();
+        assert!(text.contains("Subscriptions"));
+        assert!(text.contains("Night Show"));
+        assert!(text.contains("4 unplayed"));
+    }
+
+    #[test]
+    fn test_playlists_show_totals_and_open_books() {
+        use crate::state::{OpenPlaylist, View};
+        use ratatui::{backend::TestBackend, Terminal};
+        use storystream_core::{Book, PlaylistId};
+
+        let mut state = AppState::new();
+        state.set_view(View::Playlists);
+        state.playlists = vec![
+            PlaylistRow {
+                id: PlaylistId::new(),
+                name: "Road Trip".to_string(),
+                smart: false,
+                books: 2,
+                duration: Duration::from_secs(5400),
+            },
+            PlaylistRow {
+                id: PlaylistId::new(),
+                name: "Unfinished".to_string(),
+                smart: true,
+                books: 1,
+                duration: Duration::from_secs(60),
+            },
+        ];
+        let mut book = Book::new(
+            "Moby Dick".to_string(),
+            "/moby.mp3".into(),
+            1000,
+            storystream_core::Duration::from_seconds(3600),
+        );
+        book.author = Some("Melville".to_string());
+        state.open_playlist = Some(OpenPlaylist {
+            books: vec![book],
+            selected: 0,
+        });
+        let theme = crate::theme::Theme::default();
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        terminal
+            .draw(|frame| render(frame, frame.area(), &state, &theme))
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let text: String = buffer.content().iter().map(|cell| cell.symbol()).collect();
+        assert!(text.contains("Road Trip"));
+        assert!(text.contains("2 books · 01:30:00"));
+        assert!(text.contains("Unfinished"));
+        // Only the smart playlist has the smart icon
+        assert_eq!(text.matches('⚡').count(), 1);
+        assert!(text.contains("1. Moby Dick by Melville"));
+        assert!(text.contains("J/K: Move"));
+    }
 }
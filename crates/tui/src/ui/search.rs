@@ -1,14 +1,15 @@
 // crates/tui/src/ui/search.rs
 //! Search view rendering
 
-use crate::state::AppState;
+use crate::state::{format_duration, AppState, FilterField, FilterPopup};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::Style,
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph},
     Frame,
 };
+use storystream_database::search::SearchFilter;
 
 /// Renders the search view
 pub fn render(frame: &mut Frame, area: Rect, state: &AppState, theme: &crate::theme::Theme) {
@@ -33,12 +34,18 @@ fn render_search_input(
     state: &AppState,
     theme: &crate::theme::Theme,
 ) {
+    let filters = filter_summary(&state.search_filter);
+    let title = if filters.is_empty() {
+        "Search".to_string()
+    } else {
+        format!("Search ({})", filters)
+    };
     let input = Paragraph::new(format!("🔍 {}_", state.search_query))
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(theme.border_color()))
-                .title("Search"),
+                .title(title),
         )
         .style(theme.text_style());
 
@@ -52,52 +59,33 @@ fn render_search_results(
     state: &AppState,
     theme: &crate::theme::Theme,
 ) {
-    // Demo results based on query
-    let all_books = vec![
-        ("📖 Moby Dick", "Herman Melville"),
-        ("📖 Pride and Prejudice", "Jane Austen"),
-        ("📖 1984", "George Orwell"),
-        ("📖 To Kill a Mockingbird", "Harper Lee"),
-        ("📖 The Great Gatsby", "F. Scott Fitzgerald"),
-        ("📖 War and Peace", "Leo Tolstoy"),
-        ("📖 The Catcher in the Rye", "J.D. Salinger"),
-        ("📖 Harry Potter", "J.K. Rowling"),
-    ];
-
-    let filtered: Vec<_> = if state.search_query.is_empty() {
-        all_books.clone()
-    } else {
-        all_books
-            .iter()
-            .filter(|(title, author)| {
-                let query = state.search_query.to_lowercase();
-                title.to_lowercase().contains(&query) || author.to_lowercase().contains(&query)
-            })
-            .copied()
-            .collect()
-    };
-
-    let items: Vec<ListItem> = filtered
+    let items: Vec<ListItem> = state
+        .search_results
         .iter()
         .enumerate()
-        .map(|(i, (title, author))| {
+        .map(|(i, book)| {
             let style = if i == state.selected_item {
                 theme.highlight_style()
             } else {
                 theme.text_style()
             };
+            let length = std::time::Duration::from_millis(book.duration.as_millis());
 
             ListItem::new(vec![
-                Line::from(Span::styled(*title, style)),
+                Line::from(Span::styled(format!("📖 {}", book.title), style)),
                 Line::from(Span::styled(
-                    format!("  by {}", author),
+                    format!(
+                        "  by {} · {}",
+                        book.author.as_deref().unwrap_or("Unknown"),
+                        format_duration(length)
+                    ),
                     theme.text_secondary_style(),
                 )),
             ])
         })
         .collect();
 
-    let title = format!("Results ({} found)", filtered.len());
+    let title = format!("Results ({} found)", state.search_results.len());
     let list = List::new(items)
         .block(
             Block::default()
@@ -112,7 +100,7 @@ fn render_search_results(
 
 /// Renders search help
 fn render_search_help(frame: &mut Frame, area: Rect, theme: &crate::theme::Theme) {
-    let help = Paragraph::new("Type to search | ↑/↓: Navigate | Enter: Play | Esc: Clear search")
+    let help = Paragraph::new("/: Search text | F: Filters | ↑/↓: Navigate | Enter: Play")
         .block(
             Block::default()
                 .borders(Borders::ALL)
@@ -123,6 +111,76 @@ fn render_search_help(frame: &mut Frame, area: Rect, theme: &crate::theme::Theme
     frame.render_widget(help, area);
 }
 
+/// Renders the search filter popup centered over `area`
+pub fn render_filter_popup(
+    frame: &mut Frame,
+    area: Rect,
+    popup: &FilterPopup,
+    filter: &SearchFilter,
+    theme: &crate::theme::Theme,
+) {
+    let width = area.width.saturating_sub(4).min(60);
+    let height = (FilterField::ALL.len() as u16 + 4).min(area.height);
+    let rect = Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+
+    let mut lines: Vec<Line> = FilterField::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let style = if i == popup.selected {
+                theme.highlight_style()
+            } else {
+                theme.text_style()
+            };
+            let value = field.value(filter);
+            let value = if value.is_empty() {
+                "any".to_string()
+            } else {
+                value
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!("{:>10}: ", field.label()),
+                    theme.text_secondary_style(),
+                ),
+                Span::styled(value, style),
+            ])
+        })
+        .collect();
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Enter: Edit/Toggle | x: Clear | c: Clear all | Esc: Done",
+        theme.text_secondary_style(),
+    )));
+
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.accent))
+            .title("Search Filters"),
+    );
+
+    frame.render_widget(Clear, rect);
+    frame.render_widget(paragraph, rect);
+}
+
+/// Lists the filters that are set, e.g. `Author: sanderson, Finished: unfinished`
+fn filter_summary(filter: &SearchFilter) -> String {
+    FilterField::ALL
+        .iter()
+        .filter_map(|field| {
+            let value = field.value(filter);
+            (!value.is_empty()).then(|| format!("{}: {}", field.label(), value))
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -132,4 +190,17 @@ mod tests {
         let state = AppState::new();
         let _ = state.search_query;
     }
+
+    #[test]
+    fn test_filter_summary() {
+        let mut filter = SearchFilter::default();
+        assert_eq!(filter_summary(&filter), "");
+
+        filter.author = Some("Sanderson".to_string());
+        filter.finished = Some(false);
+        assert_eq!(
+            filter_summary(&filter),
+            "Author: Sanderson, Finished: unfinished"
+        );
+    }
 }
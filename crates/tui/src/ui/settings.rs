@@ -1,61 +1,429 @@
 // crates/tui/src/ui/settings.rs
 
-use crate::state::AppState;
+use super::downloads::format_size;
+use crate::settings::{Setting, SettingKind};
+use crate::state::{
+    AppState, LanDevices, Maintenance, MaintenanceList, OutputPicker, PairingPrompt,
+};
 use ratatui::{
-    layout::Rect,
-    style::Style,
-    text::Line,
-    widgets::{Block, Borders, List, ListItem},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Gauge, List, ListItem, ListState, Paragraph, Wrap},
     Frame,
 };
+use storystream_config::schema;
+use storystream_core::CacheStats;
+
+/// Issue lines shown before the maintenance block stops growing
+const MAX_VISIBLE_ISSUES: usize = 8;
 
 /// Renders the settings view
 pub fn render(frame: &mut Frame, area: Rect, state: &AppState, theme: &crate::theme::Theme) {
-    // Pre-format dynamic strings to avoid lifetime issues
-    let appearance_text = "🎨 Appearance (Press 't' to cycle)".to_string();
-    let theme_text = format!("  └─ Theme: {}", state.theme.name());
-
-    let settings = vec![
-        "⚙️  Audio Settings",
-        "  └─ Default Volume: 100%",
-        "  └─ Default Speed: 1.0x",
-        "",
-        "📁 Library Settings",
-        "  └─ Auto-scan: Enabled",
-        "  └─ Library Paths: ~/Audiobooks",
-        "",
-        "🔄 Sync Settings",
-        "  └─ Auto-sync: Disabled",
-        "  └─ Conflict Resolution: Use Newest",
-        "",
-        appearance_text.as_str(),
-        theme_text.as_str(),
-    ];
+    // Borders and the progress line, plus the issues and a key hint
+    let maintenance_height = match state.maintenance.issues.len().min(MAX_VISIBLE_ISSUES) {
+        0 => 3,
+        issues => 4 + issues as u16,
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(maintenance_height)])
+        .split(area);
 
-    let items: Vec<ListItem> = settings
-        .iter()
-        .map(|setting| ListItem::new(Line::from(*setting)))
-        .collect();
+    render_settings(frame, chunks[0], state, theme);
+    render_maintenance(frame, chunks[1], &state.maintenance, theme);
+}
+
+/// Renders the settings, under group headings, with what cannot be changed
+/// here listed alongside them
+fn render_settings(frame: &mut Frame, area: Rect, state: &AppState, theme: &crate::theme::Theme) {
+    let selected = state.selected_setting();
+    let mut items: Vec<ListItem> = Vec::new();
+    let mut selected_row = 0;
+    let mut previous: Option<Setting> = None;
+
+    for setting in Setting::ALL {
+        if previous.is_none_or(|previous| previous.group() != setting.group()) {
+            if let Some(previous) = previous {
+                items.extend(info_lines(previous, state).into_iter().map(ListItem::new));
+                items.push(ListItem::new(""));
+            }
+            items.push(ListItem::new(setting.group()));
+        }
+        previous = Some(setting);
+
+        if setting == selected {
+            selected_row = items.len();
+        }
+        items.push(ListItem::new(setting_line(
+            setting,
+            setting == selected,
+            state,
+        )));
+        if let Some((_, reason)) = state.setting_error.as_ref().filter(|(s, _)| *s == setting) {
+            items.push(ListItem::new(Line::from(Span::styled(
+                format!("     ✗ {}", reason),
+                Style::default().fg(theme.error),
+            ))));
+        }
+    }
+    if let Some(last) = previous {
+        items.extend(info_lines(last, state).into_iter().map(ListItem::new));
+    }
+    items.push(ListItem::new(""));
+    items.push(ListItem::new("💾 Cache (Press 'C' to clear)"));
+    items.extend(
+        cache_lines(state.cache.as_ref())
+            .into_iter()
+            .map(ListItem::new),
+    );
 
     let list = List::new(items)
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(theme.border_color()))
-                .title("⚙️  Settings (↑/↓: Navigate | Enter: Edit | t: Change Theme)"),
+                .title(
+                    "⚙️  Settings (↑/↓: Navigate | Space ←/→: Change | Enter: Edit | r: Reset all)",
+                ),
         )
-        .style(theme.text_style());
+        .style(theme.text_style())
+        .highlight_style(theme.highlight_style());
+    let mut list_state = ListState::default().with_selected(Some(selected_row));
 
-    frame.render_widget(list, area);
+    frame.render_stateful_widget(list, area, &mut list_state);
+}
+
+/// A setting and its value, with what it accepts once selected
+fn setting_line(setting: Setting, selected: bool, state: &AppState) -> String {
+    let mut line = format!(
+        "  └─ {}: {}",
+        setting.label(),
+        setting.display(&state.config)
+    );
+    if selected {
+        let accepted = match setting.kind() {
+            SettingKind::Number { .. } | SettingKind::Choice => {
+                schema::accepted_values(setting.key())
+            }
+            SettingKind::List => Some("folders separated as in PATH".to_string()),
+            SettingKind::Toggle | SettingKind::Text => None,
+        };
+        if let Some(accepted) = accepted {
+            line.push_str(&format!("  ({})", accepted));
+        }
+        if !setting.applies_live() {
+            line.push_str("  [used from the next start]");
+        }
+    }
+    line
+}
+
+/// Read-only lines listed after the group `last` ends
+fn info_lines(last: Setting, state: &AppState) -> Vec<String> {
+    match last {
+        Setting::BookmarkExportDir => vec![format!(
+            "  └─ Output Device: {} (o: Choose)",
+            state
+                .playback
+                .output_device
+                .as_deref()
+                .unwrap_or("System default")
+        )],
+        Setting::DailyGoal => vec![format!("  └─ Theme: {} (t: Cycle)", state.theme.name())],
+        Setting::LanSync => {
+            let mut lines = vec!["  └─ Devices (D: Find | J/K: Select | P: Pair)".to_string()];
+            lines.extend(lan_lines(state.lan.as_ref()));
+            lines
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Cache usage in total and per namespace
+fn cache_lines(stats: Option<&CacheStats>) -> Vec<String> {
+    let Some(stats) = stats else {
+        return vec!["  └─ Off".to_string()];
+    };
+    let mut lines = vec![format!(
+        "  └─ Used: {} of {}",
+        format_size(stats.total_bytes),
+        format_size(stats.max_bytes)
+    )];
+    lines.extend(stats.namespaces.iter().map(|namespace| {
+        format!(
+            "  └─ {}: {} file(s), {}",
+            namespace.name,
+            namespace.entries,
+            format_size(namespace.bytes)
+        )
+    }));
+    if stats.recovered > 0 {
+        lines.push(format!(
+            "  └─ Corrupt entries replaced: {}",
+            stats.recovered
+        ));
+    }
+    lines
+}
+
+/// Devices on the local network, the selected one marked
+fn lan_lines(lan: Option<&LanDevices>) -> Vec<String> {
+    let Some(lan) = lan else {
+        return vec!["  └─ Local network: Off (set app.lan_sync to turn it on)".to_string()];
+    };
+    let status = match (lan.searching, lan.peers.len()) {
+        (true, _) => "Searching...".to_string(),
+        (false, 0) => "No devices found".to_string(),
+        (false, count) => format!("{} device(s)", count),
+    };
+    let mut lines = vec![format!("  └─ Local network: {}", status)];
+    lines.extend(lan.peers.iter().enumerate().map(|(i, peer)| {
+        let marker = if i == lan.selected { "▶" } else { " " };
+        let note = match (peer.compatible, peer.paired) {
+            (false, _) => " (other version)",
+            (true, true) => " (paired)",
+            (true, false) => "",
+        };
+        format!("     {} {}{}", marker, peer.name, note)
+    }));
+    lines
+}
+
+/// Renders the pairing code to compare with the other device's screen
+pub fn render_pairing(
+    frame: &mut Frame,
+    area: Rect,
+    prompt: &PairingPrompt,
+    theme: &crate::theme::Theme,
+) {
+    let width = area.width.saturating_sub(4).min(50);
+    let height = 7.min(area.height);
+    let popup = Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+
+    let text = vec![
+        Line::from(Span::styled(
+            format!("Pair with {}?", prompt.device),
+            theme.text_style(),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("Code: {}", prompt.code),
+            theme.accent_style().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Pair only if the other screen shows the same code | y: Pair | n: Refuse",
+            theme.text_secondary_style(),
+        )),
+    ];
+    let paragraph = Paragraph::new(text).wrap(Wrap { trim: true }).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.accent))
+            .title("Pair device"),
+    );
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(paragraph, popup);
+}
+
+/// Renders the output devices to pick from, the one in use marked
+pub fn render_output_picker(
+    frame: &mut Frame,
+    area: Rect,
+    picker: &OutputPicker,
+    theme: &crate::theme::Theme,
+) {
+    let width = area.width.saturating_sub(4).min(50);
+    let height = (picker.choices.len() as u16 + 3).min(area.height);
+    let popup = Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+
+    let mut lines: Vec<Line> = picker
+        .choices
+        .iter()
+        .enumerate()
+        .map(|(i, choice)| {
+            let marker = if choice.chosen { "●" } else { " " };
+            let style = if i == picker.selected {
+                theme.highlight_style()
+            } else {
+                theme.text_style()
+            };
+            Line::from(Span::styled(format!(" {} {}", marker, choice.name), style))
+        })
+        .collect();
+    lines.push(Line::from(Span::styled(
+        "Enter: Play on it | Esc: Cancel",
+        theme.text_secondary_style(),
+    )));
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.accent))
+            .title("Output device"),
+    );
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(paragraph, popup);
+}
+
+/// Renders verification or import progress and the problems found
+fn render_maintenance(
+    frame: &mut Frame,
+    area: Rect,
+    maintenance: &Maintenance,
+    theme: &crate::theme::Theme,
+) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border_color()))
+        .title(
+            "🛠  Maintenance (i: Import library | I: Preview import | v: Verify files | V: Verify and decode | Esc: Cancel | d: Find duplicates | p: Prune download history)",
+        );
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)])
+        .split(inner);
+
+    if let Some((done, total)) = maintenance.progress {
+        let ratio = if total == 0 {
+            0.0
+        } else {
+            done as f64 / total as f64
+        };
+        let gauge = Gauge::default()
+            .gauge_style(theme.accent_style())
+            .ratio(ratio)
+            .label(format!("Verifying {}/{}", done, total));
+        frame.render_widget(gauge, chunks[0]);
+    } else if let Some(import) = &maintenance.import {
+        frame.render_widget(
+            Paragraph::new(Span::styled(import.as_str(), theme.accent_style())),
+            chunks[0],
+        );
+    } else {
+        let summary = maintenance
+            .summary
+            .as_deref()
+            .unwrap_or("Re-hash book files to find changed, missing or corrupt ones");
+        frame.render_widget(
+            Paragraph::new(Span::styled(summary, theme.text_secondary_style())),
+            chunks[0],
+        );
+    }
+
+    if maintenance.issues.is_empty() {
+        return;
+    }
+
+    // Keep the selected issue in view
+    let first = (maintenance.selected + 1).saturating_sub(MAX_VISIBLE_ISSUES);
+    let mut lines: Vec<Line> = maintenance
+        .issues
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(MAX_VISIBLE_ISSUES)
+        .map(|(i, issue)| {
+            let style = if i == maintenance.selected {
+                theme.highlight_style()
+            } else {
+                theme.text_style()
+            };
+            Line::from(Span::styled(format!("  {}", issue), style))
+        })
+        .collect();
+    let keys = match maintenance.list {
+        MaintenanceList::FileIssues => "u: Update hash | r: Refresh metadata | c: Mark corrupt",
+        MaintenanceList::Duplicates => "m: Merge into the copy kept | s: Skip group",
+        MaintenanceList::ImportPlan => "Space: Include/exclude | Enter: Import | Esc: Discard plan",
+    };
+    lines.push(Line::from(Span::styled(keys, theme.text_secondary_style())));
+    frame.render_widget(Paragraph::new(lines), chunks[1]);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::state::LanPeer;
 
     #[test]
     fn test_settings_render_compiles() {
         let state = AppState::new();
         let _ = state.view;
     }
+
+    #[test]
+    fn test_cache_lines() {
+        assert_eq!(cache_lines(None), vec!["  └─ Off"]);
+
+        let stats = CacheStats {
+            total_bytes: 3 * 1024 * 1024,
+            max_bytes: 500 * 1024 * 1024,
+            namespaces: vec![storystream_core::NamespaceStats {
+                name: "covers".to_string(),
+                entries: 12,
+                bytes: 3 * 1024 * 1024,
+            }],
+            recovered: 0,
+        };
+        assert_eq!(
+            cache_lines(Some(&stats)),
+            vec![
+                "  └─ Used: 3.0 MB of 500.0 MB",
+                "  └─ covers: 12 file(s), 3.0 MB"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lan_lines() {
+        assert_eq!(lan_lines(None).len(), 1);
+
+        let mut lan = LanDevices {
+            searching: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            lan_lines(Some(&lan)),
+            vec!["  └─ Local network: Searching..."]
+        );
+
+        lan.searching = false;
+        lan.peers = vec![
+            LanPeer {
+                name: "Phone".to_string(),
+                paired: true,
+                compatible: true,
+            },
+            LanPeer {
+                name: "Tablet".to_string(),
+                paired: false,
+                compatible: false,
+            },
+        ];
+        lan.selected = 1;
+        assert_eq!(
+            lan_lines(Some(&lan)),
+            vec![
+                "  └─ Local network: 2 device(s)",
+                "       Phone (paired)",
+                "     ▶ Tablet (other version)"
+            ]
+        );
+    }
 }
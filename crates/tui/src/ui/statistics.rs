@@ -1,59 +1,138 @@
 // crates/tui/src/ui/statistics.rs
 //! Statistics view rendering
+//!
+//! The figures are read in the background when the view opens or `r` is
+//! pressed; until the first read finishes the view says it is loading.
 
 use crate::state::AppState;
+use chrono::{Datelike, Days, Local, NaiveDate};
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
-    style::Style,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Gauge, List, ListItem, Paragraph},
+    widgets::{Bar, BarChart, BarGroup, Block, Borders, Gauge, List, ListItem, Paragraph},
     Frame,
 };
+use storystream_core::types::{current_streak, goal_completion, longest_streak};
+use storystream_core::{Duration, LibraryStats};
+
+/// Days of listening shown in the bar chart
+pub const CHART_DAYS: usize = 14;
 
 /// Renders the statistics view
-pub fn render(frame: &mut Frame, area: Rect, _state: &AppState, theme: &crate::theme::Theme) {
+pub fn render(frame: &mut Frame, area: Rect, state: &AppState, theme: &crate::theme::Theme) {
+    let library = match &state.library_stats {
+        Some(library) => library,
+        None if state.statistics_loading => {
+            render_loading(frame, area, theme);
+            return;
+        }
+        // Zeros when the figures could not be read
+        None => &LibraryStats::empty(),
+    };
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(7),  // Overview
-            Constraint::Length(10), // Listening stats
-            Constraint::Min(0),     // Top books
+            Constraint::Length(12), // Daily listening and listening time
+            Constraint::Min(0),     // Top books and authors
         ])
         .split(area);
+    let middle = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(chunks[1]);
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[2]);
 
-    render_overview(frame, chunks[0], theme);
-    render_listening_stats(frame, chunks[1], theme);
-    render_top_books(frame, chunks[2], theme);
+    let today = Local::now().date_naive();
+    render_overview(frame, chunks[0], library, state.statistics_loading, theme);
+    render_daily_listening(frame, middle[0], state, today, theme);
+    render_listening_stats(frame, middle[1], state, library, today, theme);
+    render_top_listened(
+        frame,
+        bottom[0],
+        "📚 Most Listened Books",
+        &library.top_listened_books,
+        theme,
+    );
+    render_top_listened(
+        frame,
+        bottom[1],
+        "🏆 Most Listened Authors",
+        &library.top_listened_authors,
+        theme,
+    );
+}
+
+/// Renders the placeholder shown until the figures are first read
+fn render_loading(frame: &mut Frame, area: Rect, theme: &crate::theme::Theme) {
+    let loading = Paragraph::new(vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            "Loading statistics…",
+            theme.text_secondary_style(),
+        )),
+    ])
+    .alignment(Alignment::Center)
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border_color()))
+            .title("📊 Statistics"),
+    );
+    frame.render_widget(loading, area);
 }
 
-/// Renders statistics overview
-fn render_overview(frame: &mut Frame, area: Rect, theme: &crate::theme::Theme) {
+/// Renders statistics overview, noting when newer figures are being read
+fn render_overview(
+    frame: &mut Frame,
+    area: Rect,
+    library: &LibraryStats,
+    reloading: bool,
+    theme: &crate::theme::Theme,
+) {
     let stats = vec![
         Line::from(vec![
             Span::styled("📚 Total Books: ", theme.text_secondary_style()),
-            Span::styled("156", theme.highlight_style()),
+            Span::styled(library.total_books.to_string(), theme.highlight_style()),
             Span::raw("  "),
             Span::styled("🎧 Hours Listened: ", theme.text_secondary_style()),
-            Span::styled("342.5", theme.highlight_style()),
+            Span::styled(hours(library.listening_time), theme.highlight_style()),
         ]),
         Line::from(""),
         Line::from(vec![
             Span::styled("⭐ Favorites: ", theme.text_secondary_style()),
-            Span::styled("23", theme.highlight_style()),
+            Span::styled(library.favorite_count.to_string(), theme.highlight_style()),
             Span::raw("  "),
             Span::styled("🏁 Finished: ", theme.text_secondary_style()),
-            Span::styled("89", theme.highlight_style()),
+            Span::styled(library.finished_count.to_string(), theme.highlight_style()),
             Span::raw("  "),
             Span::styled("📖 In Progress: ", theme.text_secondary_style()),
-            Span::styled("12", theme.highlight_style()),
+            Span::styled(
+                library.unfinished_count.to_string(),
+                theme.highlight_style(),
+            ),
         ]),
         Line::from(""),
         Line::from(vec![
-            Span::styled("📊 Average Rating: ", theme.text_secondary_style()),
-            Span::styled("4.2/5.0", theme.highlight_style()),
+            Span::styled("📅 Days in a Row: ", theme.text_secondary_style()),
+            Span::styled(
+                format!(
+                    "{} (best {})",
+                    library.current_streak_days, library.longest_streak_days
+                ),
+                theme.highlight_style(),
+            ),
             Span::raw("  "),
             Span::styled("🎯 Completion Rate: ", theme.text_secondary_style()),
-            Span::styled("57%", theme.highlight_style()),
+            Span::styled(
+                format!("{:.0}%", library.finished_percentage()),
+                theme.highlight_style(),
+            ),
         ]),
     ];
 
@@ -62,15 +141,117 @@ fn render_overview(frame: &mut Frame, area: Rect, theme: &crate::theme::Theme) {
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(theme.border_color()))
-                .title("📊 Overview"),
+                .title(if reloading {
+                    "📊 Overview (reloading…)"
+                } else {
+                    "📊 Overview"
+                }),
         )
         .style(theme.text_style());
 
     frame.render_widget(paragraph, area);
 }
 
-/// Renders listening statistics
-fn render_listening_stats(frame: &mut Frame, area: Rect, theme: &crate::theme::Theme) {
+/// Renders the listening streak, today's goal progress and a bar of hours
+/// for each recent day, the last on `last_day`
+fn render_daily_listening(
+    frame: &mut Frame,
+    area: Rect,
+    state: &AppState,
+    last_day: NaiveDate,
+    theme: &crate::theme::Theme,
+) {
+    let goal = state.daily_goal_minutes;
+    let today = state.daily_minutes.last().copied().unwrap_or(0);
+    let streak = current_streak(&state.daily_minutes, goal);
+    let longest = longest_streak(&state.daily_minutes, goal);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border_color()))
+        .title("🔥 Daily Listening");
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(0)])
+        .split(inner);
+
+    let summary = vec![
+        Line::from(vec![
+            Span::styled("Streak: ", theme.text_secondary_style()),
+            Span::styled(days(streak), theme.highlight_style()),
+            Span::raw("  "),
+            Span::styled("Longest: ", theme.text_secondary_style()),
+            Span::styled(days(longest), theme.highlight_style()),
+        ]),
+        Line::from(vec![
+            Span::styled("Today: ", theme.text_secondary_style()),
+            Span::styled(
+                format!(
+                    "{}/{} min ({:.0}%)",
+                    today,
+                    goal,
+                    goal_completion(today, goal) * 100.0
+                ),
+                if today >= goal {
+                    theme.success_style()
+                } else {
+                    theme.text_style()
+                },
+            ),
+        ]),
+    ];
+    frame.render_widget(Paragraph::new(summary).style(theme.text_style()), chunks[0]);
+
+    let recent = &state.daily_minutes[state.daily_minutes.len().saturating_sub(CHART_DAYS)..];
+    let bars: Vec<Bar> = recent
+        .iter()
+        .enumerate()
+        .map(|(i, &minutes)| {
+            let day = last_day
+                .checked_sub_days(Days::new((recent.len() - 1 - i) as u64))
+                .map_or(String::new(), |date| date.day().to_string());
+            Bar::default()
+                .value(u64::from(minutes))
+                .text_value(format!("{:.1}", f64::from(minutes) / 60.0))
+                .label(Line::from(day))
+        })
+        .collect();
+    // Scale to at least the goal so a day that meets it reaches the top
+    let max = recent.iter().copied().max().unwrap_or(0).max(goal);
+    let bar_width = (chunks[1].width + 1) / CHART_DAYS as u16;
+    let chart = BarChart::default()
+        .block(Block::default().title(format!("Hours, last {} days", CHART_DAYS)))
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(bar_width.saturating_sub(1).max(1))
+        .bar_gap(1)
+        .max(u64::from(max))
+        .bar_style(theme.accent_style())
+        .value_style(theme.highlight_style().add_modifier(Modifier::REVERSED))
+        .label_style(theme.text_secondary_style());
+    frame.render_widget(chart, chunks[1]);
+}
+
+fn days(count: u32) -> String {
+    if count == 1 {
+        "1 day".to_string()
+    } else {
+        format!("{} days", count)
+    }
+}
+
+/// Renders this week's and month's listening against the daily goal, the
+/// all-time total and the loaded book's history
+fn render_listening_stats(
+    frame: &mut Frame,
+    area: Rect,
+    state: &AppState,
+    library: &LibraryStats,
+    today: NaiveDate,
+    theme: &crate::theme::Theme,
+) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
@@ -82,33 +263,62 @@ fn render_listening_stats(frame: &mut Frame, area: Rect, theme: &crate::theme::T
         ])
         .split(area);
 
-    // This week
-    let gauge1 = Gauge::default()
-        .block(Block::default().title("This Week: 12.5 hours"))
-        .gauge_style(theme.success_style())
-        .percent(75);
-    frame.render_widget(gauge1, chunks[0]);
-
-    // This month
-    let gauge2 = Gauge::default()
-        .block(Block::default().title("This Month: 42.3 hours"))
-        .gauge_style(theme.accent_style())
-        .percent(60);
-    frame.render_widget(gauge2, chunks[1]);
-
-    // This year
-    let gauge3 = Gauge::default()
-        .block(Block::default().title("This Year: 342.5 hours"))
-        .gauge_style(theme.highlight_style())
-        .percent(85);
-    frame.render_widget(gauge3, chunks[2]);
-
-    // All time
-    let gauge4 = Gauge::default()
-        .block(Block::default().title("All Time: 1,247.8 hours"))
-        .gauge_style(Style::default().fg(theme.playing))
-        .percent(100);
-    frame.render_widget(gauge4, chunks[3]);
+    let goal = state.daily_goal_minutes;
+    for (chunk, days, label, style) in [
+        (
+            chunks[0],
+            days_this_week(today),
+            "This Week",
+            theme.success_style(),
+        ),
+        (
+            chunks[1],
+            days_this_month(today),
+            "This Month",
+            theme.accent_style(),
+        ),
+    ] {
+        let minutes = recent_minutes(&state.daily_minutes, days);
+        let gauge = Gauge::default()
+            .block(Block::default().title(format!(
+                "{}: {:.1} hours",
+                label,
+                f64::from(minutes) / 60.0
+            )))
+            .gauge_style(style)
+            .ratio(goal_completion(minutes, goal * days as u32));
+        frame.render_widget(gauge, chunk);
+    }
+
+    let all_time = Line::from(vec![
+        Span::styled("All Time: ", theme.text_secondary_style()),
+        Span::styled(
+            format!("{} hours", hours(library.listening_time)),
+            theme.highlight_style(),
+        ),
+    ]);
+    frame.render_widget(Paragraph::new(all_time), chunks[2]);
+
+    let this_book = match &state.book_stats {
+        Some(book) => Line::from(vec![
+            Span::styled("This Book: ", theme.text_secondary_style()),
+            Span::styled(
+                format!(
+                    "{} hours in {} sessions at {:.2}x",
+                    hours(book.listened),
+                    book.sessions,
+                    book.average_speed
+                ),
+                Style::default().fg(theme.playing),
+            ),
+            Span::raw(if book.finished { "  🏁" } else { "" }),
+        ]),
+        None => Line::from(Span::styled(
+            "This Book: nothing loaded",
+            theme.text_secondary_style(),
+        )),
+    };
+    frame.render_widget(Paragraph::new(this_book), chunks[3]);
 
     let block = Block::default()
         .borders(Borders::ALL)
@@ -117,31 +327,27 @@ fn render_listening_stats(frame: &mut Frame, area: Rect, theme: &crate::theme::T
     frame.render_widget(block, area);
 }
 
-/// Renders top books
-fn render_top_books(frame: &mut Frame, area: Rect, theme: &crate::theme::Theme) {
-    let books = vec![
-        ("1. 📖 Moby Dick", "Herman Melville", "23.5 hours"),
-        ("2. 📖 War and Peace", "Leo Tolstoy", "61.2 hours"),
-        ("3. 📖 Harry Potter Series", "J.K. Rowling", "117.3 hours"),
-        (
-            "4. 📖 The Lord of the Rings",
-            "J.R.R. Tolkien",
-            "54.8 hours",
-        ),
-        ("5. 📖 Foundation Series", "Isaac Asimov", "42.1 hours"),
-    ];
-
-    let items: Vec<ListItem> = books
+/// Renders names, such as books or authors, by time listened, most first
+fn render_top_listened(
+    frame: &mut Frame,
+    area: Rect,
+    title: &str,
+    entries: &[(String, Duration)],
+    theme: &crate::theme::Theme,
+) {
+    let items: Vec<ListItem> = entries
         .iter()
-        .map(|(rank, title, time)| {
+        .enumerate()
+        .map(|(rank, (name, listened))| {
             ListItem::new(vec![
-                Line::from(Span::styled(*rank, theme.highlight_style())),
-                Line::from(Span::styled(*title, theme.text_style())),
                 Line::from(Span::styled(
-                    format!("  {} listened", time),
+                    format!("{}. {}", rank + 1, name),
+                    theme.highlight_style(),
+                )),
+                Line::from(Span::styled(
+                    format!("  {} hours listened", hours(*listened)),
                     theme.text_secondary_style(),
                 )),
-                Line::from(""),
             ])
         })
         .collect();
@@ -151,13 +357,35 @@ fn render_top_books(frame: &mut Frame, area: Rect, theme: &crate::theme::Theme)
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(theme.border_color()))
-                .title("🏆 Most Listened"),
+                .title(title),
         )
         .style(theme.text_style());
 
     frame.render_widget(list, area);
 }
 
+/// Days of this week so far, from Monday to today
+pub fn days_this_week(today: NaiveDate) -> usize {
+    today.weekday().number_from_monday() as usize
+}
+
+/// Days of this month so far, today included
+pub fn days_this_month(today: NaiveDate) -> usize {
+    today.day() as usize
+}
+
+/// Minutes listened over the last `days` days, today included
+pub fn recent_minutes(daily_minutes: &[u32], days: usize) -> u32 {
+    daily_minutes[daily_minutes.len().saturating_sub(days)..]
+        .iter()
+        .sum()
+}
+
+/// Hours to one decimal place, such as "342.5"
+fn hours(duration: Duration) -> String {
+    format!("{:.1}", duration.as_seconds() as f64 / 3600.0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -167,4 +395,64 @@ mod tests {
         let state = AppState::new();
         let _ = state.view;
     }
+
+    #[test]
+    fn test_days_label() {
+        assert_eq!(days(1), "1 day");
+        assert_eq!(days(12), "12 days");
+    }
+
+    #[test]
+    fn test_listening_totals() {
+        assert_eq!(recent_minutes(&[10, 20, 30], 2), 50);
+        assert_eq!(recent_minutes(&[10, 20], 7), 30);
+        assert_eq!(hours(Duration::from_seconds(5400)), "1.5");
+    }
+
+    #[test]
+    fn test_calendar_periods() {
+        // A Wednesday
+        let today = NaiveDate::from_ymd_opt(2024, 3, 13).unwrap();
+        assert_eq!(days_this_week(today), 3);
+        assert_eq!(days_this_month(today), 13);
+    }
+
+    #[test]
+    fn test_view_shows_loading_then_figures() {
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let theme = crate::theme::Theme::default();
+        let draw = |state: &AppState| {
+            let mut terminal = Terminal::new(TestBackend::new(100, 40)).unwrap();
+            terminal
+                .draw(|frame| render(frame, frame.area(), state, &theme))
+                .unwrap();
+            let buffer = terminal.backend().buffer();
+            buffer
+                .content()
+                .iter()
+                .map(|cell| cell.symbol())
+                .collect::<String>()
+        };
+
+        let mut state = AppState::new();
+        state.statistics_loading = true;
+        assert!(draw(&state).contains("Loading statistics…"));
+
+        let mut library = LibraryStats::empty();
+        library.top_listened_books = vec![("Dune".to_string(), Duration::from_seconds(7200))];
+        state.library_stats = Some(library);
+        state.daily_minutes = vec![0; 13];
+        state.daily_minutes.push(90);
+        let screen = draw(&state);
+        assert!(screen.contains("(reloading…)"));
+        assert!(screen.contains("1. Dune"));
+        assert!(screen.contains("2.0 hours listened"));
+        assert!(screen.contains("Hours, last 14 days"));
+        // Today's bar is labelled with its hours
+        assert!(screen.contains("1.5"));
+
+        state.statistics_loading = false;
+        assert!(!draw(&state).contains("reloading"));
+    }
 }
@@ -0,0 +1,366 @@
+// crates/tui/src/ui/text_input.rs
+//! Single line of editable text, and the popup that shows it
+//!
+//! The cursor moves by grapheme, so an accented letter typed as two code
+//! points or an emoji with a skin tone is stepped over and deleted as one.
+//! Prompts keep a [`TextInput`] and pass it the keys while they are open;
+//! [`InputOutcome`] tells them when the text was confirmed or cancelled.
+
+use crate::theme::Theme;
+use crossterm::event::{KeyCode, KeyModifiers};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// What a key pressed in a [`TextInput`] did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputOutcome {
+    /// The text is still being edited; the key may have changed nothing
+    Editing,
+    /// Enter was pressed to apply the text
+    Confirmed,
+    /// Esc was pressed to drop the text
+    Cancelled,
+}
+
+/// Text being typed, with a cursor
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextInput {
+    value: String,
+    /// Byte offset of the cursor, always between graphemes
+    cursor: usize,
+}
+
+impl TextInput {
+    /// Starts with `value` and the cursor after it
+    pub fn new(value: impl Into<String>) -> Self {
+        let value = value.into();
+        Self {
+            cursor: value.len(),
+            value,
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn into_value(self) -> String {
+        self.value
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+
+    /// Graphemes before the cursor
+    pub fn cursor(&self) -> usize {
+        self.value[..self.cursor].graphemes(true).count()
+    }
+
+    /// Edits the text or moves the cursor for a key press
+    ///
+    /// Characters typed with Ctrl or Alt are left to the terminal rather
+    /// than typed.
+    pub fn handle_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> InputOutcome {
+        match code {
+            KeyCode::Enter => return InputOutcome::Confirmed,
+            KeyCode::Esc => return InputOutcome::Cancelled,
+            KeyCode::Char(c)
+                if !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                self.insert(c)
+            }
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Left => self.move_left(),
+            KeyCode::Right => self.move_right(),
+            KeyCode::Home => self.move_home(),
+            KeyCode::End => self.move_end(),
+            _ => {}
+        }
+        InputOutcome::Editing
+    }
+
+    /// Types `c` at the cursor
+    pub fn insert(&mut self, c: char) {
+        self.value.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+        // A combining mark joins the grapheme before it; typed before one,
+        // a letter takes it over and the cursor goes past both
+        self.cursor = self.boundary_at_or_after(self.cursor);
+    }
+
+    /// Deletes the grapheme before the cursor
+    pub fn backspace(&mut self) {
+        if let Some((start, _)) = self.value[..self.cursor].grapheme_indices(true).next_back() {
+            self.value.replace_range(start..self.cursor, "");
+            self.cursor = start;
+        }
+    }
+
+    /// Deletes the grapheme after the cursor
+    pub fn delete(&mut self) {
+        if let Some(grapheme) = self.value[self.cursor..].graphemes(true).next() {
+            let end = self.cursor + grapheme.len();
+            self.value.replace_range(self.cursor..end, "");
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        if let Some((start, _)) = self.value[..self.cursor].grapheme_indices(true).next_back() {
+            self.cursor = start;
+        }
+    }
+
+    pub fn move_right(&mut self) {
+        if let Some(grapheme) = self.value[self.cursor..].graphemes(true).next() {
+            self.cursor += grapheme.len();
+        }
+    }
+
+    pub fn move_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.value.len();
+    }
+
+    /// The part of the text that fits in `width` columns with the cursor
+    /// in view, its byte offset in the text, and the cursor's column in it
+    ///
+    /// Text scrolls left only once the cursor would fall off the right edge.
+    pub fn visible(&self, width: usize) -> (&str, usize, usize) {
+        if width == 0 {
+            return ("", self.cursor, 0);
+        }
+
+        // Drop graphemes from the start until the cursor has a column
+        let mut start = 0;
+        for (index, grapheme) in self.value[..self.cursor].grapheme_indices(true) {
+            if self.value[start..self.cursor].width() < width {
+                break;
+            }
+            start = index + grapheme.len();
+        }
+
+        let mut end = start;
+        let mut used = 0;
+        for grapheme in self.value[start..].graphemes(true) {
+            used += grapheme.width();
+            if used > width {
+                break;
+            }
+            end += grapheme.len();
+        }
+        let column = self.value[start..self.cursor].width();
+        (&self.value[start..end], start, column)
+    }
+
+    /// Same length and cursor, each grapheme shown as a dot
+    fn masked(&self) -> Self {
+        let value = "•".repeat(self.value.graphemes(true).count());
+        let cursor = "•".len() * self.cursor();
+        Self { value, cursor }
+    }
+
+    fn boundary_at_or_after(&self, offset: usize) -> usize {
+        self.value
+            .grapheme_indices(true)
+            .map(|(index, _)| index)
+            .find(|&index| index >= offset)
+            .unwrap_or(self.value.len())
+    }
+}
+
+/// Renders `input` in a popup titled `title`, centered over `area`
+///
+/// The grapheme under the cursor is shown reversed and the terminal's own
+/// cursor is put there too, where screen readers follow it. `masked` hides
+/// what was typed, for passwords.
+pub fn render(
+    frame: &mut Frame,
+    area: Rect,
+    title: &str,
+    input: &TextInput,
+    masked: bool,
+    theme: &Theme,
+) {
+    let width = area.width.saturating_sub(4).min(60);
+    let popup = Rect {
+        x: area.x + (area.width.saturating_sub(width)) / 2,
+        y: area.y + area.height.saturating_sub(5) / 2,
+        width,
+        height: 5.min(area.height),
+    };
+
+    let masked_input;
+    let input = if masked {
+        masked_input = input.masked();
+        &masked_input
+    } else {
+        input
+    };
+    let inner_width = usize::from(width.saturating_sub(2));
+    let (shown, offset, column) = input.visible(inner_width);
+    let split = input.cursor - offset;
+    let before = &shown[..split.min(shown.len())];
+    let rest = &shown[before.len()..];
+    let under = rest.graphemes(true).next().unwrap_or(" ");
+    let after = rest.get(under.len()..).unwrap_or("");
+
+    let cursor_style = theme.accent_style().add_modifier(Modifier::REVERSED);
+    let text = vec![
+        Line::from(vec![
+            Span::styled(before.to_string(), theme.text_style()),
+            Span::styled(under.to_string(), cursor_style),
+            Span::styled(after.to_string(), theme.text_style()),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Enter: Confirm | Esc: Cancel | ←/→ Home/End: Move",
+            theme.text_secondary_style(),
+        )),
+    ];
+    let paragraph = Paragraph::new(text).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.accent))
+            .title(title.to_string()),
+    );
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(paragraph, popup);
+    if popup.width > 2 && popup.height > 2 {
+        frame.set_cursor_position((popup.x + 1 + column as u16, popup.y + 1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(text: &str) -> TextInput {
+        let mut input = TextInput::default();
+        for c in text.chars() {
+            input.insert(c);
+        }
+        input
+    }
+
+    #[test]
+    fn test_editing_at_the_cursor() {
+        let mut input = typed("helo");
+        input.move_left();
+        input.insert('l');
+        assert_eq!(input.value(), "hello");
+        assert_eq!(input.cursor(), 4);
+
+        input.move_home();
+        input.delete();
+        input.insert('J');
+        assert_eq!(input.value(), "Jello");
+
+        input.move_end();
+        input.backspace();
+        assert_eq!(input.value(), "Jell");
+
+        // Nothing to delete past either end
+        input.delete();
+        input.move_home();
+        input.backspace();
+        input.move_left();
+        assert_eq!(input.value(), "Jell");
+        assert_eq!(input.cursor(), 0);
+    }
+
+    #[test]
+    fn test_graphemes_are_edited_whole() {
+        // "e" and a combining acute accent, then a thumbs up with a skin tone
+        let mut input = typed("Cafe\u{301} 👍🏽!");
+        assert_eq!(input.value().graphemes(true).count(), 7);
+
+        input.move_left();
+        input.move_left();
+        assert_eq!(input.cursor(), 5);
+        input.backspace();
+        assert_eq!(input.value(), "Cafe\u{301}👍🏽!");
+
+        input.backspace();
+        assert_eq!(input.value(), "Caf👍🏽!");
+        input.delete();
+        assert_eq!(input.value(), "Caf!");
+
+        // A mark typed at the start joins the letter typed before it
+        let mut input = TextInput::new("\u{301}x");
+        input.move_home();
+        input.insert('e');
+        assert_eq!(input.cursor(), 1);
+        input.move_right();
+        assert_eq!(input.cursor(), 2);
+    }
+
+    #[test]
+    fn test_keys_edit_and_finish() {
+        let mut input = TextInput::new("ab");
+        let none = KeyModifiers::NONE;
+        assert_eq!(
+            input.handle_key(KeyCode::Char('q'), none),
+            InputOutcome::Editing
+        );
+        assert_eq!(
+            input.handle_key(KeyCode::Char('c'), KeyModifiers::CONTROL),
+            InputOutcome::Editing
+        );
+        input.handle_key(KeyCode::Home, none);
+        input.handle_key(KeyCode::Char('Z'), KeyModifiers::SHIFT);
+        assert_eq!(input.value(), "Zabq");
+        assert_eq!(
+            input.handle_key(KeyCode::Enter, none),
+            InputOutcome::Confirmed
+        );
+        assert_eq!(
+            input.handle_key(KeyCode::Esc, none),
+            InputOutcome::Cancelled
+        );
+    }
+
+    #[test]
+    fn test_long_text_scrolls_to_the_cursor() {
+        let mut input = TextInput::new("abcdefghij");
+        assert_eq!(input.visible(5), ("ghij", 6, 4));
+
+        input.move_home();
+        assert_eq!(input.visible(5), ("abcde", 0, 0));
+        assert_eq!(TextInput::new("abc").visible(10), ("abc", 0, 3));
+
+        // Wide characters take two columns each
+        let input = TextInput::new("日本語");
+        assert_eq!(input.visible(4), ("語", 6, 2));
+    }
+
+    #[test]
+    fn test_masked_input_is_rendered_as_dots() {
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let input = TextInput::new("sécret");
+        let theme = Theme::default();
+        let mut terminal = Terminal::new(TestBackend::new(40, 10)).unwrap();
+        terminal
+            .draw(|frame| render(frame, frame.area(), "Password", &input, true, &theme))
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let text: String = buffer.content().iter().map(|cell| cell.symbol()).collect();
+        assert!(text.contains("Password"));
+        assert!(text.contains("••••••"));
+        assert!(!text.contains("cret"));
+    }
+}
@@ -0,0 +1,38 @@
+// crates/tui/tests/remote_tests.rs
+//! Remote control server lifecycle, with nothing answering its requests
+//!
+//! The API itself is tested against the TUI that answers it, in
+//! `integration.rs`.
+
+#![cfg(feature = "remote")]
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::mpsc;
+use std::time::Duration;
+use storystream_tui::RemoteServer;
+
+#[test]
+fn test_requests_fail_once_the_player_stops() {
+    let server = RemoteServer::start("127.0.0.1:0", None).unwrap();
+    let address = server.address();
+    let (sent, received) = mpsc::channel();
+    let client = std::thread::spawn(move || {
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream
+            .write_all(b"GET /status HTTP/1.0\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        sent.send(()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    });
+
+    // Stopping without answering must not wait out the reply timeout
+    received.recv().unwrap();
+    while server.next_request().is_none() {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    drop(server);
+    assert!(client.join().unwrap().starts_with("HTTP/1.0 503"));
+}
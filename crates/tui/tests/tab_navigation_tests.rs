@@ -2,8 +2,23 @@
 //! Integration tests for tab navigation and state preservation
 
 use std::time::Duration;
+use storystream_core::types::book::Book;
 use storystream_tui::{App, AppState, PlaybackState, View};
 
+/// Search hits to move through, as the Search view only selects real rows
+fn search_hits(count: usize) -> Vec<Book> {
+    (0..count)
+        .map(|i| {
+            Book::new(
+                format!("Hit {}", i),
+                format!("/hit_{}.mp3", i).into(),
+                1000,
+                storystream_core::Duration::from_seconds(60),
+            )
+        })
+        .collect()
+}
+
 #[test]
 fn test_basic_view_state_preservation() {
     let mut state = AppState::new();
@@ -66,6 +81,7 @@ fn test_multiple_view_state_preservation() {
 fn test_tab_cycling_preserves_all_states() {
     let mut app = App::new();
     app.state.library_items_count = 10;
+    app.state.search_results = search_hits(5);
 
     // Set different positions in multiple views
     // Library: position 4
@@ -91,8 +107,8 @@ fn test_tab_cycling_preserves_all_states() {
     assert_eq!(search_pos, 3);
 
     // Now tab through all views and come back to Library
-    // From Search: Search -> Playlists -> Statistics -> Settings -> Help -> Library (5 cycles)
-    for _ in 0..5 {
+    // From Search: Search -> Playlists -> Statistics -> Settings -> Downloads -> Help -> Library (6 cycles)
+    for _ in 0..6 {
         app.cycle_view();
     }
     assert_eq!(app.state.view, View::Library);
@@ -275,6 +291,7 @@ fn test_new_view_starts_at_zero() {
 fn test_comprehensive_workflow() {
     let mut app = App::new();
     app.state.library_items_count = 10;
+    app.state.search_results = search_hits(5);
 
     // User workflow: Browse library, check bookmarks, search, back to library
 